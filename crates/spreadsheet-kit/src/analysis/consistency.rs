@@ -0,0 +1,202 @@
+//! Pattern breaks in filled formula regions.
+//!
+//! A region is a run of adjacent non-empty cells down a column or across a
+//! row in which most cells share one formula in relative R1C1 form. Cells
+//! inside the region's span that do not follow it (a different formula, or a
+//! typed value pasted over the fill) are reported with the formula they were
+//! expected to hold.
+
+use crate::formula::pattern::{RelativeMode, r1c1_pattern, shift_formula_ast};
+use crate::model::{FormulaInconsistency, FormulaInconsistencyKind, FormulaRegionAxis};
+use crate::utils::cell_address;
+use formualizer_parse::ASTNode;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use umya_spreadsheet::Worksheet;
+
+/// Fewest cells sharing a pattern before a run counts as a filled region.
+const MIN_PATTERN_CELLS: usize = 3;
+
+enum Content {
+    Formula {
+        text: String,
+        pattern: String,
+        ast: Option<ASTNode>,
+    },
+    Value(String),
+}
+
+impl Content {
+    fn pattern(&self) -> Option<&str> {
+        match self {
+            Content::Formula { pattern, .. } => Some(pattern),
+            Content::Value(_) => None,
+        }
+    }
+}
+
+/// Pattern breaks on `sheet` in row-major order. A cell breaking both its
+/// column and its row region is reported once, against the column.
+pub fn find_formula_inconsistencies(sheet: &Worksheet) -> Vec<FormulaInconsistency> {
+    let mut cells: HashMap<(u32, u32), Content> = HashMap::new();
+    for cell in sheet.get_cell_collection() {
+        let coordinate = cell.get_coordinate();
+        let (col, row) = (*coordinate.get_col_num(), *coordinate.get_row_num());
+        let content = if cell.is_formula() {
+            let text = cell
+                .get_formula()
+                .trim()
+                .trim_start_matches('=')
+                .to_string();
+            if text.is_empty() {
+                continue;
+            }
+            match formualizer_parse::parse(&format!("={text}")) {
+                Ok(ast) => Content::Formula {
+                    pattern: r1c1_pattern(&ast, row, col),
+                    ast: Some(ast),
+                    text,
+                },
+                Err(_) => Content::Formula {
+                    pattern: text.to_ascii_uppercase(),
+                    ast: None,
+                    text,
+                },
+            }
+        } else {
+            let value = cell.get_value();
+            if value.trim().is_empty() {
+                continue;
+            }
+            Content::Value(value.to_string())
+        };
+        cells.insert((col, row), content);
+    }
+
+    let mut columns: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
+    let mut rows: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
+    for &(col, row) in cells.keys() {
+        columns.entry(col).or_default().push(row);
+        rows.entry(row).or_default().push(col);
+    }
+
+    let mut found: Vec<((u32, u32), FormulaInconsistency)> = Vec::new();
+    let mut flagged: HashSet<(u32, u32)> = HashSet::new();
+    for (axis, lines) in [
+        (FormulaRegionAxis::Column, &mut columns),
+        (FormulaRegionAxis::Row, &mut rows),
+    ] {
+        for (&line, positions) in lines.iter_mut() {
+            positions.sort_unstable();
+            for run in positions.chunk_by(|a, b| b - a == 1) {
+                scan_run(axis, line, run, &cells, &mut flagged, &mut found);
+            }
+        }
+    }
+
+    found.sort_by_key(|((col, row), _)| (*row, *col));
+    found.into_iter().map(|(_, finding)| finding).collect()
+}
+
+fn scan_run(
+    axis: FormulaRegionAxis,
+    line: u32,
+    run: &[u32],
+    cells: &HashMap<(u32, u32), Content>,
+    flagged: &mut HashSet<(u32, u32)>,
+    found: &mut Vec<((u32, u32), FormulaInconsistency)>,
+) {
+    let key = |position: u32| match axis {
+        FormulaRegionAxis::Column => (line, position),
+        FormulaRegionAxis::Row => (position, line),
+    };
+
+    // Most common pattern; ties go to the one that appears first.
+    let mut counts: HashMap<&str, (usize, u32)> = HashMap::new();
+    for &position in run {
+        if let Some(pattern) = cells[&key(position)].pattern() {
+            counts.entry(pattern).or_insert((0, position)).0 += 1;
+        }
+    }
+    let Some((expected, _)) = counts
+        .iter()
+        .max_by(|(_, (count_a, first_a)), (_, (count_b, first_b))| {
+            count_a.cmp(count_b).then(first_b.cmp(first_a))
+        })
+        .map(|(pattern, stats)| (pattern.to_string(), *stats))
+    else {
+        return;
+    };
+
+    let matching: Vec<u32> = run
+        .iter()
+        .copied()
+        .filter(|position| cells[&key(*position)].pattern() == Some(expected.as_str()))
+        .collect();
+    let (Some(&first), Some(&last)) = (matching.first(), matching.last()) else {
+        return;
+    };
+    let span = (last - first + 1) as usize;
+    if matching.len() < MIN_PATTERN_CELLS || matching.len() * 2 <= span {
+        return;
+    }
+
+    let example = key(first);
+    let example_ast = match &cells[&example] {
+        Content::Formula { ast, .. } => ast.as_ref(),
+        Content::Value(_) => None,
+    };
+    let (start_col, start_row) = key(first);
+    let (end_col, end_row) = key(last);
+    let region = format!(
+        "{}:{}",
+        cell_address(start_col, start_row),
+        cell_address(end_col, end_row)
+    );
+
+    for position in first..=last {
+        let (col, row) = key(position);
+        let content = &cells[&(col, row)];
+        if content.pattern() == Some(expected.as_str()) || !flagged.insert((col, row)) {
+            continue;
+        }
+        let expected_formula = example_ast.and_then(|ast| {
+            shift_formula_ast(
+                ast,
+                col as i32 - example.0 as i32,
+                row as i32 - example.1 as i32,
+                RelativeMode::Excel,
+            )
+            .ok()
+            .map(|formula| formula.trim_start_matches('=').to_string())
+        });
+        let (kind, actual_formula, actual_pattern, actual_value) = match content {
+            Content::Formula { text, pattern, .. } => (
+                FormulaInconsistencyKind::FormulaMismatch,
+                Some(text.clone()),
+                Some(pattern.clone()),
+                None,
+            ),
+            Content::Value(value) => (
+                FormulaInconsistencyKind::HardcodedValue,
+                None,
+                None,
+                Some(value.clone()),
+            ),
+        };
+        found.push((
+            (col, row),
+            FormulaInconsistency {
+                address: cell_address(col, row),
+                kind,
+                axis,
+                region: region.clone(),
+                pattern_cells: matching.len() as u32,
+                expected_pattern: expected.clone(),
+                expected_formula,
+                actual_formula,
+                actual_pattern,
+                actual_value,
+            },
+        ));
+    }
+}
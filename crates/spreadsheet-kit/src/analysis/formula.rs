@@ -1,6 +1,6 @@
 use crate::model::{
     FORMULA_PARSE_FAILED_PREFIX, FormulaGroup, FormulaParseDiagnosticsBuilder, FormulaParsePolicy,
-    format_formula_parse_failure,
+    TraceDirection, format_formula_parse_failure,
 };
 use crate::utils::column_number_to_name;
 use anyhow::{Context, Result, anyhow};
@@ -10,11 +10,13 @@ use formualizer_parse::{
     pretty::canonical_formula,
 };
 use parking_lot::{Mutex, RwLock};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
 use std::sync::Arc;
-use umya_spreadsheet::{CellFormulaValues, Worksheet};
+use umya_spreadsheet::{CellFormulaValues, Spreadsheet, Worksheet};
 
 const RANGE_EXPANSION_LIMIT: usize = 500;
+/// Names defined in terms of other names are followed this many levels.
+const NAME_RESOLUTION_DEPTH: usize = 4;
 
 #[derive(Clone)]
 pub struct FormulaAtlas {
@@ -101,6 +103,8 @@ fn parsed_from_ast(ast: &ASTNode) -> ParsedFormula {
 pub struct FormulaGraph {
     precedents: HashMap<String, Vec<String>>,
     dependents: HashMap<String, Vec<String>>,
+    /// Name or structured reference behind a `(cell, precedent)` edge.
+    precedent_names: HashMap<(String, String), String>,
     groups: HashMap<String, FormulaGroupAccumulator>,
     range_dependents: Vec<RangeDependentEntry>,
     sheet_name: String,
@@ -114,19 +118,113 @@ struct RangeDependentEntry {
     dependents: Vec<String>,
 }
 
+#[derive(Default)]
+struct EdgeAccumulator {
+    precedents: HashMap<String, HashSet<String>>,
+    dependents: HashMap<String, HashSet<String>>,
+    range_dependents: HashMap<String, (ReferenceType, HashSet<String>)>,
+    precedent_names: HashMap<(String, String), String>,
+}
+
+impl EdgeAccumulator {
+    /// Record `reference` as a precedent of `address` and return the
+    /// precedent key. With `local_sheet`, cell references to that sheet are
+    /// keyed by their unqualified address so the trace can continue from them.
+    fn add(
+        &mut self,
+        address: &str,
+        reference: &ReferenceType,
+        local_sheet: Option<&str>,
+    ) -> String {
+        match reference {
+            ReferenceType::Cell {
+                sheet, row, col, ..
+            } => {
+                let sheet = sheet.as_deref().filter(|sheet| {
+                    !local_sheet.is_some_and(|local| local.eq_ignore_ascii_case(sheet))
+                });
+                let dep_addr = format_cell_address(sheet, *row, *col);
+                self.precedents
+                    .entry(address.to_string())
+                    .or_default()
+                    .insert(dep_addr.clone());
+                self.dependents
+                    .entry(dep_addr.clone())
+                    .or_default()
+                    .insert(address.to_string());
+                dep_addr
+            }
+            ReferenceType::Range {
+                start_row,
+                start_col,
+                end_row,
+                end_col,
+                ..
+            } => {
+                let prec_str = reference.to_string();
+                self.precedents
+                    .entry(address.to_string())
+                    .or_default()
+                    .insert(prec_str.clone());
+
+                if is_large_or_infinite_range(*start_row, *start_col, *end_row, *end_col) {
+                    self.range_dependents
+                        .entry(prec_str.clone())
+                        .or_insert_with(|| (reference.clone(), HashSet::new()))
+                        .1
+                        .insert(address.to_string());
+                }
+                prec_str
+            }
+            ReferenceType::NamedRange(name) => {
+                self.precedents
+                    .entry(address.to_string())
+                    .or_default()
+                    .insert(name.clone());
+                name.clone()
+            }
+            ReferenceType::Table(_) | ReferenceType::External(_) => {
+                let table_str = reference.to_string();
+                self.precedents
+                    .entry(address.to_string())
+                    .or_default()
+                    .insert(table_str.clone());
+                table_str
+            }
+        }
+    }
+}
+
 impl FormulaGraph {
     pub fn build(
         sheet: &Worksheet,
         atlas: &FormulaAtlas,
         policy: FormulaParsePolicy,
+        diagnostics: Option<&mut FormulaParseDiagnosticsBuilder>,
+    ) -> Result<Self> {
+        Self::build_with_names(
+            sheet,
+            &ReferenceNames::default(),
+            atlas,
+            policy,
+            diagnostics,
+        )
+    }
+
+    /// Like [`FormulaGraph::build`], but defined names and structured table
+    /// references found in `names` become edges to the cells they cover,
+    /// annotated with the name used (see [`FormulaGraph::precedent_name`]).
+    /// Names that cannot be resolved stay as opaque precedents.
+    pub fn build_with_names(
+        sheet: &Worksheet,
+        names: &ReferenceNames,
+        atlas: &FormulaAtlas,
+        policy: FormulaParsePolicy,
         mut diagnostics: Option<&mut FormulaParseDiagnosticsBuilder>,
     ) -> Result<Self> {
         let sheet_name = sheet.get_name().to_string();
-        let mut precedents_build: HashMap<String, HashSet<String>> = HashMap::new();
-        let mut dependents_build: HashMap<String, HashSet<String>> = HashMap::new();
+        let mut edges = EdgeAccumulator::default();
         let mut groups: HashMap<String, FormulaGroupAccumulator> = HashMap::new();
-        let mut range_dependents_build: HashMap<String, (ReferenceType, HashSet<String>)> =
-            HashMap::new();
 
         let collect_policy = CollectPolicy {
             expand_small_ranges: true,
@@ -243,69 +341,50 @@ impl FormulaGraph {
             group.addresses.push(address.clone());
             group.is_volatile |= is_volatile;
 
+            let row = *coordinate.get_row_num();
             let refs = ast.collect_references(&collect_policy);
             for reference in refs {
-                match &reference {
-                    ReferenceType::Cell {
-                        sheet, row, col, ..
-                    } => {
-                        let dep_addr = format_cell_address(sheet.as_deref(), *row, *col);
-                        precedents_build
-                            .entry(address.clone())
-                            .or_default()
-                            .insert(dep_addr.clone());
-                        dependents_build
-                            .entry(dep_addr)
-                            .or_default()
-                            .insert(address.clone());
+                let resolved = match &reference {
+                    ReferenceType::NamedRange(name) => names
+                        .resolve_name(name, &collect_policy)
+                        .map(|targets| (name.clone(), targets)),
+                    ReferenceType::Table(_) => {
+                        let text = reference.to_string();
+                        names
+                            .resolve_structured(&text, row, &collect_policy)
+                            .map(|targets| (text, targets))
                     }
-                    ReferenceType::Range {
-                        start_row,
-                        start_col,
-                        end_row,
-                        end_col,
-                        ..
-                    } => {
-                        let prec_str = reference.to_string();
-                        precedents_build
-                            .entry(address.clone())
-                            .or_default()
-                            .insert(prec_str.clone());
-
-                        if is_large_or_infinite_range(*start_row, *start_col, *end_row, *end_col) {
-                            range_dependents_build
-                                .entry(prec_str)
-                                .or_insert_with(|| (reference.clone(), HashSet::new()))
-                                .1
-                                .insert(address.clone());
+                    _ => None,
+                };
+                match resolved {
+                    Some((label, targets)) => {
+                        for target in targets {
+                            let key = edges.add(&address, &target, Some(&sheet_name));
+                            edges
+                                .precedent_names
+                                .entry((address.clone(), key))
+                                .or_insert_with(|| label.clone());
                         }
                     }
-                    ReferenceType::NamedRange(name) => {
-                        precedents_build
-                            .entry(address.clone())
-                            .or_default()
-                            .insert(name.clone());
-                    }
-                    ReferenceType::Table(_) | ReferenceType::External(_) => {
-                        let table_str = reference.to_string();
-                        precedents_build
-                            .entry(address.clone())
-                            .or_default()
-                            .insert(table_str);
+                    None => {
+                        edges.add(&address, &reference, None);
                     }
                 }
             }
         }
 
-        let precedents = precedents_build
+        let precedents = edges
+            .precedents
             .into_iter()
             .map(|(k, v)| (k, v.into_iter().collect()))
             .collect();
-        let dependents = dependents_build
+        let dependents = edges
+            .dependents
             .into_iter()
             .map(|(k, v)| (k, v.into_iter().collect()))
             .collect();
-        let range_dependents = range_dependents_build
+        let range_dependents = edges
+            .range_dependents
             .into_iter()
             .map(|(key, (ref_type, addrs))| RangeDependentEntry {
                 range_key: key,
@@ -317,6 +396,7 @@ impl FormulaGraph {
         Ok(Self {
             precedents,
             dependents,
+            precedent_names: edges.precedent_names,
             groups,
             range_dependents,
             sheet_name,
@@ -342,6 +422,14 @@ impl FormulaGraph {
         self.precedents.get(address).cloned().unwrap_or_default()
     }
 
+    /// The defined name or structured reference through which the formula at
+    /// `address` refers to `precedent`, when it does not refer to it directly.
+    pub fn precedent_name(&self, address: &str, precedent: &str) -> Option<&str> {
+        self.precedent_names
+            .get(&(address.to_string(), precedent.to_string()))
+            .map(String::as_str)
+    }
+
     pub fn dependents(&self, address: &str) -> Vec<String> {
         self.dependents_limited(address, None).0
     }
@@ -383,6 +471,407 @@ impl FormulaGraph {
     }
 }
 
+/// Defined names and tables visible to formulas on one sheet, so references
+/// such as `=Revenue*TaxRate` or `=SUM(Sales[Amount])` can be followed to the
+/// cells they cover.
+#[derive(Debug, Clone, Default)]
+pub struct ReferenceNames {
+    /// Upper-cased name to its refers-to text, without the leading `=`.
+    names: HashMap<String, String>,
+    /// Upper-cased table name (and display name) to its extent.
+    tables: HashMap<String, TableExtent>,
+}
+
+#[derive(Debug, Clone)]
+struct TableExtent {
+    sheet: String,
+    start_row: u32,
+    start_col: u32,
+    end_row: u32,
+    end_col: u32,
+    /// Upper-cased header text of each column, left to right.
+    columns: Vec<String>,
+}
+
+impl ReferenceNames {
+    /// Names as seen from `sheet_name`: its sheet-scoped names shadow
+    /// workbook names. Every table in the workbook is visible.
+    pub fn for_sheet(book: &Spreadsheet, sheet_name: &str) -> Self {
+        let sheets = book.get_sheet_collection();
+        let sheet_index = sheets
+            .iter()
+            .position(|sheet| sheet.get_name().eq_ignore_ascii_case(sheet_name));
+
+        let mut global = Vec::new();
+        let mut local = Vec::new();
+        for defined in book.get_defined_names() {
+            if !defined.has_local_sheet_id() {
+                global.push(defined);
+            } else if Some(*defined.get_local_sheet_id() as usize) == sheet_index {
+                local.push(defined);
+            }
+        }
+        for (index, sheet) in sheets.iter().enumerate() {
+            for defined in sheet.get_defined_names() {
+                if Some(index) == sheet_index {
+                    local.push(defined);
+                } else if !defined.has_local_sheet_id() {
+                    global.push(defined);
+                }
+            }
+        }
+
+        let mut names = HashMap::new();
+        for defined in global.into_iter().chain(local) {
+            names.insert(
+                defined.get_name().to_ascii_uppercase(),
+                defined.get_address().trim_start_matches('=').to_string(),
+            );
+        }
+
+        let mut tables = HashMap::new();
+        for sheet in sheets {
+            for table in sheet.get_tables() {
+                let (start, end) = table.get_area();
+                let (start_col, start_row) = (*start.get_col_num(), *start.get_row_num());
+                let (end_col, end_row) = (*end.get_col_num(), *end.get_row_num());
+                let extent = TableExtent {
+                    sheet: sheet.get_name().to_string(),
+                    start_row,
+                    start_col,
+                    end_row,
+                    end_col,
+                    columns: (start_col..=end_col)
+                        .map(|col| {
+                            sheet
+                                .get_value((col, start_row))
+                                .trim()
+                                .to_ascii_uppercase()
+                        })
+                        .collect(),
+                };
+                for key in [table.get_name(), table.get_display_name()] {
+                    if !key.is_empty() {
+                        tables.insert(key.to_ascii_uppercase(), extent.clone());
+                    }
+                }
+            }
+        }
+
+        Self { names, tables }
+    }
+
+    /// Cell and range references that `name` refers to, or `None` if it is
+    /// unknown or refers to no cells (a constant, say).
+    fn resolve_name(&self, name: &str, policy: &CollectPolicy) -> Option<Vec<ReferenceType>> {
+        self.resolve_name_at_depth(name, policy, 0)
+    }
+
+    fn resolve_name_at_depth(
+        &self,
+        name: &str,
+        policy: &CollectPolicy,
+        depth: usize,
+    ) -> Option<Vec<ReferenceType>> {
+        if depth >= NAME_RESOLUTION_DEPTH {
+            return None;
+        }
+        let refers_to = self.names.get(&name.to_ascii_uppercase())?;
+        let ast = formualizer_parse::parse(&format!("={refers_to}")).ok()?;
+        let mut targets = Vec::new();
+        for reference in ast.collect_references(policy) {
+            match reference {
+                ReferenceType::Cell { .. } | ReferenceType::Range { .. } => targets.push(reference),
+                ReferenceType::NamedRange(inner) => {
+                    targets.extend(self.resolve_name_at_depth(&inner, policy, depth + 1)?)
+                }
+                ReferenceType::Table(_) | ReferenceType::External(_) => {}
+            }
+        }
+        (!targets.is_empty()).then_some(targets)
+    }
+
+    /// Cell and range references covered by a structured reference such as
+    /// `Sales[Amount]`, for a formula on `row` (which `[#This Row]` and `@`
+    /// select). Totals rows are not modelled, so `[#Totals]` is unresolved.
+    fn resolve_structured(
+        &self,
+        text: &str,
+        row: u32,
+        policy: &CollectPolicy,
+    ) -> Option<Vec<ReferenceType>> {
+        let range = self.structured_range(text, row)?;
+        let ast = formualizer_parse::parse(&format!("={range}")).ok()?;
+        let targets: Vec<ReferenceType> = ast
+            .collect_references(policy)
+            .into_iter()
+            .filter(|reference| {
+                matches!(
+                    reference,
+                    ReferenceType::Cell { .. } | ReferenceType::Range { .. }
+                )
+            })
+            .collect();
+        (!targets.is_empty()).then_some(targets)
+    }
+
+    /// A1 range text (`'Sheet'!$B$2:$B$9`) for a structured reference.
+    fn structured_range(&self, text: &str, row: u32) -> Option<String> {
+        let (name, body) = match text.find('[') {
+            Some(index) => (&text[..index], text[index + 1..].strip_suffix(']')?),
+            None => (text, ""),
+        };
+        let table = self.tables.get(&name.trim().to_ascii_uppercase())?;
+
+        let mut body = body.trim();
+        let mut this_row = false;
+        if let Some(rest) = body.strip_prefix('@') {
+            this_row = true;
+            body = rest.trim();
+        }
+        let column_range = body.contains("]:[");
+        let items: Vec<&str> = if body.starts_with('[') {
+            body.split(['[', ']'])
+                .map(str::trim)
+                .filter(|item| !item.is_empty() && *item != "," && *item != ":")
+                .collect()
+        } else if body.is_empty() {
+            Vec::new()
+        } else {
+            vec![body]
+        };
+
+        let header_row = table.start_row;
+        let data_start = header_row + 1;
+        let mut rows: Option<(u32, u32)> = None;
+        let mut include = |first: u32, last: u32| {
+            rows = Some(match rows {
+                Some((low, high)) => (low.min(first), high.max(last)),
+                None => (first, last),
+            });
+        };
+        let mut columns = Vec::new();
+        for item in items {
+            match item.strip_prefix('#').map(str::to_ascii_uppercase) {
+                Some(special) => match special.as_str() {
+                    "ALL" => include(header_row, table.end_row),
+                    "DATA" => include(data_start, table.end_row),
+                    "HEADERS" => include(header_row, header_row),
+                    "THIS ROW" => this_row = true,
+                    _ => return None,
+                },
+                None => columns.push(item.to_ascii_uppercase()),
+            }
+        }
+        if this_row {
+            if row < data_start || row > table.end_row {
+                return None;
+            }
+            include(row, row);
+        }
+        let (first_row, last_row) = rows.unwrap_or((data_start, table.end_row));
+        if first_row > last_row {
+            return None;
+        }
+
+        let column_of = |name: &String| {
+            table
+                .columns
+                .iter()
+                .position(|column| column == name)
+                .map(|index| table.start_col + index as u32)
+        };
+        let (first_col, last_col) = match columns.as_slice() {
+            [] => (table.start_col, table.end_col),
+            [column] => {
+                let col = column_of(column)?;
+                (col, col)
+            }
+            [from, to] if column_range => {
+                let (from, to) = (column_of(from)?, column_of(to)?);
+                (from.min(to), from.max(to))
+            }
+            _ => return None,
+        };
+
+        Some(format!(
+            "{}!${}${}:${}${}",
+            quote_sheet_name(&table.sheet),
+            column_number_to_name(first_col),
+            first_row,
+            column_number_to_name(last_col),
+            last_row
+        ))
+    }
+}
+
+pub(crate) fn quote_sheet_name(sheet: &str) -> String {
+    let plain = sheet
+        .chars()
+        .next()
+        .is_some_and(|ch| ch.is_ascii_alphabetic() || ch == '_')
+        && sheet
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '_');
+    if plain {
+        sheet.to_string()
+    } else {
+        format!("'{}'", sheet.replace('\'', "''"))
+    }
+}
+
+/// Formula cells reachable from one cell through precedent or dependent edges,
+/// followed across sheets.
+#[derive(Debug, Clone, Default)]
+pub struct FormulaSubgraph {
+    /// `(sheet, row, col)` of every formula cell in the subgraph, including the
+    /// origin when it holds a formula.
+    pub formula_cells: Vec<(String, u32, u32)>,
+    /// References the walk could not follow cell by cell (unknown names,
+    /// unresolvable table references, external links, and ranges too large
+    /// to expand).
+    pub unresolved: Vec<String>,
+}
+
+/// Walk the per-sheet formula graphs from `sheet_name!address` in `direction`.
+pub fn formula_subgraph(
+    book: &Spreadsheet,
+    atlas: &FormulaAtlas,
+    sheet_name: &str,
+    address: &str,
+    direction: &TraceDirection,
+) -> Result<FormulaSubgraph> {
+    let (row, col) =
+        parse_exact_cell(address).ok_or_else(|| anyhow!("invalid cell address '{}'", address))?;
+    if book.get_sheet_by_name(sheet_name).is_none() {
+        return Err(anyhow!("sheet {} not found", sheet_name));
+    }
+
+    let mut graphs: HashMap<String, FormulaGraph> = HashMap::new();
+    let load_graph = |graphs: &mut HashMap<String, FormulaGraph>, name: &str| -> Result<()> {
+        if !graphs.contains_key(name)
+            && let Some(sheet) = book.get_sheet_by_name(name)
+        {
+            let names = ReferenceNames::for_sheet(book, name);
+            let graph = FormulaGraph::build_with_names(
+                sheet,
+                &names,
+                atlas,
+                FormulaParsePolicy::Warn,
+                None,
+            )?;
+            graphs.insert(name.to_string(), graph);
+        }
+        Ok(())
+    };
+    if matches!(direction, TraceDirection::Dependents) {
+        for sheet in book.get_sheet_collection() {
+            load_graph(&mut graphs, sheet.get_name())?;
+        }
+    }
+
+    let is_formula = |sheet: &str, row: u32, col: u32| {
+        book.get_sheet_by_name(sheet)
+            .and_then(|ws| ws.get_cell((col, row)))
+            .is_some_and(|cell| cell.is_formula())
+    };
+
+    let mut visited: HashSet<(String, u32, u32)> = HashSet::new();
+    let mut unresolved: BTreeSet<String> = BTreeSet::new();
+    let mut queue = VecDeque::from([(sheet_name.to_string(), row, col)]);
+    visited.insert((sheet_name.to_string(), row, col));
+
+    while let Some((sheet, row, col)) = queue.pop_front() {
+        let local = format_cell_address(None, row, col);
+        let mut next: Vec<(String, u32, u32)> = Vec::new();
+        match direction {
+            TraceDirection::Precedents => {
+                if !is_formula(&sheet, row, col) {
+                    continue;
+                }
+                load_graph(&mut graphs, &sheet)?;
+                let Some(graph) = graphs.get(&sheet) else {
+                    continue;
+                };
+                for reference in graph.precedents(&local) {
+                    let (ref_sheet, cell_part) = split_sheet_prefix(&reference);
+                    match parse_exact_cell(cell_part) {
+                        Some((ref_row, ref_col)) => {
+                            next.push((ref_sheet.unwrap_or(&sheet).to_string(), ref_row, ref_col))
+                        }
+                        None if ref_sheet.is_some() => {
+                            unresolved.insert(reference);
+                        }
+                        None => {
+                            unresolved.insert(format!("{}!{}", sheet, reference));
+                        }
+                    }
+                }
+            }
+            TraceDirection::Dependents => {
+                let qualified = format_cell_address(Some(&sheet), row, col);
+                for (graph_sheet, graph) in &graphs {
+                    let mut found = graph.dependents(&qualified);
+                    if graph_sheet == &sheet {
+                        found.extend(graph.dependents(&local));
+                    }
+                    for dependent in found {
+                        if let Some((dep_row, dep_col)) = parse_exact_cell(&dependent) {
+                            next.push((graph_sheet.clone(), dep_row, dep_col));
+                        }
+                    }
+                }
+            }
+        }
+        for node in next {
+            if visited.insert(node.clone()) {
+                queue.push_back(node);
+            }
+        }
+    }
+
+    let mut formula_cells: Vec<(String, u32, u32)> = visited
+        .into_iter()
+        .filter(|(sheet, row, col)| is_formula(sheet, *row, *col))
+        .collect();
+    formula_cells.sort();
+
+    Ok(FormulaSubgraph {
+        formula_cells,
+        unresolved: unresolved.into_iter().collect(),
+    })
+}
+
+/// Parse a plain A1 cell (optionally `$`-anchored); rejects ranges and names.
+fn parse_exact_cell(address: &str) -> Option<(u32, u32)> {
+    let trimmed = address.trim();
+    let mut chars = trimmed.chars().peekable();
+    let mut col_str = String::new();
+    let mut row_str = String::new();
+    if chars.peek() == Some(&'$') {
+        chars.next();
+    }
+    while let Some(ch) = chars.peek().copied().filter(char::is_ascii_alphabetic) {
+        col_str.push(ch.to_ascii_uppercase());
+        chars.next();
+    }
+    if chars.peek() == Some(&'$') {
+        chars.next();
+    }
+    for ch in chars {
+        if !ch.is_ascii_digit() {
+            return None;
+        }
+        row_str.push(ch);
+    }
+    if col_str.is_empty() || col_str.len() > 3 || row_str.is_empty() {
+        return None;
+    }
+    let col = column_name_to_number(&col_str)?;
+    let row: u32 = row_str.parse().ok()?;
+    (row > 0).then_some((row, col))
+}
+
 fn format_cell_address(sheet: Option<&str>, row: u32, col: u32) -> String {
     let col_str = column_number_to_name(col);
     match sheet {
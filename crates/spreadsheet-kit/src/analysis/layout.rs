@@ -0,0 +1,407 @@
+//! Sheet layout kind detection: table, form, dashboard, or notes.
+//!
+//! Forms are recognized from label/input pairs: a short text label followed
+//! in the same row by a value, a formula, or an empty styled cell (an input
+//! box), or a `Label:` with an empty cell below it. Instructions are long
+//! prose cells and signature blocks are labels such as "Signature" or
+//! "Approved by". The sheet kind then weighs those against detected tables,
+//! charts, and how much of the sheet is prose.
+
+use crate::model::{CellValue, FormField, FormLayout, SheetKind};
+use crate::utils::column_number_to_name;
+use regex::Regex;
+use std::collections::{BTreeMap, HashSet};
+
+const MAX_LABEL_CHARS: usize = 60;
+const MAX_LABEL_WORDS: usize = 8;
+const MAX_INPUT_GAP_COLS: u32 = 3;
+const INSTRUCTION_MIN_WORDS: usize = 8;
+const INSTRUCTION_MIN_CHARS: usize = 60;
+const MAX_FORM_FIELDS: usize = 50;
+const MAX_FORM_NOTES: usize = 20;
+
+/// One occupied cell; `value` is `None` for empty cells that exist only
+/// because they are styled (bordered or filled input boxes).
+#[derive(Debug, Clone)]
+pub struct LayoutCell {
+    pub row: u32,
+    pub col: u32,
+    pub value: Option<CellValue>,
+    pub is_formula: bool,
+}
+
+/// Label/input pairs and prose found in a set of cells.
+#[derive(Debug, Clone, Default)]
+pub struct FormScan {
+    pub fields: Vec<FormField>,
+    pub instructions: Vec<String>,
+    pub signature_blocks: Vec<String>,
+    label_columns: usize,
+    colon_labels: usize,
+    blank_inputs: usize,
+    distinct_labels: usize,
+}
+
+impl FormScan {
+    /// Whether the pairs read as a form rather than a key/value parameter
+    /// block or table rows: at least three distinct labels covering
+    /// `min_coverage` of the occupied cells, with colon labels, empty input
+    /// boxes, or (when no table header was detected) labels in several
+    /// columns.
+    pub fn looks_like_form(
+        &self,
+        occupied_cells: usize,
+        min_coverage: f32,
+        has_table_header: bool,
+    ) -> bool {
+        let fields = self.fields.len();
+        if fields < 3 || occupied_cells == 0 {
+            return false;
+        }
+        let coverage = (fields * 2) as f32 / occupied_cells as f32;
+        let distinct = self.distinct_labels as f32 / fields as f32;
+        let form_cues = self.colon_labels * 2 >= fields
+            || self.blank_inputs * 3 >= fields
+            || (self.label_columns >= 2 && !has_table_header);
+        coverage >= min_coverage && distinct >= 0.9 && form_cues
+    }
+
+    pub fn into_layout(self) -> FormLayout {
+        let fields_truncated = self.fields.len() > MAX_FORM_FIELDS;
+        FormLayout {
+            field_count: self.fields.len() as u32,
+            fields: self.fields.into_iter().take(MAX_FORM_FIELDS).collect(),
+            fields_truncated,
+            instructions: self.instructions,
+            signature_blocks: self.signature_blocks,
+        }
+    }
+}
+
+/// Find label/input pairs, instruction text, and signature blocks.
+pub fn scan_form(cells: &[LayoutCell]) -> FormScan {
+    let signature_re = Regex::new(
+        r"(?i)\b(signature|signed|sign here|signatory|authori[sz]ed by|approved by|witness(ed)? by)\b",
+    )
+    .expect("valid signature regex");
+    let mut rows: BTreeMap<u32, Vec<&LayoutCell>> = BTreeMap::new();
+    for cell in cells {
+        rows.entry(cell.row).or_default().push(cell);
+    }
+    for row in rows.values_mut() {
+        row.sort_by_key(|cell| cell.col);
+    }
+    let blank_at = |row: u32, col: u32| {
+        rows.get(&row).is_some_and(|cells| {
+            cells
+                .iter()
+                .any(|cell| cell.col == col && cell.value.is_none() && !cell.is_formula)
+        })
+    };
+
+    let mut scan = FormScan::default();
+    let mut used: HashSet<(u32, u32)> = HashSet::new();
+    let mut label_columns: HashSet<u32> = HashSet::new();
+    let mut labels: HashSet<String> = HashSet::new();
+
+    for (&row, row_cells) in &rows {
+        for (idx, cell) in row_cells.iter().enumerate() {
+            if used.contains(&(row, cell.col)) || cell.is_formula {
+                continue;
+            }
+            let Some(CellValue::Text(text)) = &cell.value else {
+                continue;
+            };
+            let text = text.trim();
+            let address = address_of(row, cell.col);
+            if is_instruction_text(text) {
+                if scan.instructions.len() < MAX_FORM_NOTES {
+                    scan.instructions.push(address);
+                }
+                continue;
+            }
+            if !is_label_text(text) {
+                continue;
+            }
+            let has_colon = text.ends_with(':');
+            let signature = signature_re.is_match(text);
+
+            let beside = row_cells[idx + 1..]
+                .first()
+                .filter(|next| next.col - cell.col <= MAX_INPUT_GAP_COLS)
+                .filter(|next| match &next.value {
+                    None => true,
+                    Some(CellValue::Text(next_text)) => {
+                        has_colon && !next_text.trim().ends_with(':')
+                    }
+                    Some(_) => true,
+                })
+                .map(|next| (row, next.col, next.value.is_none() && !next.is_formula));
+            let below = (has_colon || signature)
+                .then(|| (row + 1, cell.col))
+                .filter(|&(r, c)| blank_at(r, c))
+                .map(|(r, c)| (r, c, true));
+
+            if signature && scan.signature_blocks.len() < MAX_FORM_NOTES {
+                scan.signature_blocks.push(address.clone());
+            }
+            let Some((input_row, input_col, blank)) = beside.or(below) else {
+                continue;
+            };
+            used.insert((row, cell.col));
+            used.insert((input_row, input_col));
+            label_columns.insert(cell.col);
+            if has_colon {
+                scan.colon_labels += 1;
+            }
+            if blank {
+                scan.blank_inputs += 1;
+            }
+            let label = text.trim_end_matches(':').trim().to_string();
+            labels.insert(label.to_lowercase());
+            scan.fields.push(FormField {
+                label,
+                label_address: address,
+                input_address: address_of(input_row, input_col),
+                filled: !blank,
+            });
+        }
+    }
+
+    scan.label_columns = label_columns.len();
+    scan.distinct_labels = labels.len();
+    scan
+}
+
+/// Counts feeding [`classify_sheet_kind`].
+#[derive(Debug, Clone, Default)]
+pub struct SheetKindInputs {
+    pub occupied_cells: usize,
+    pub text_cells: usize,
+    pub number_cells: usize,
+    pub formula_cells: usize,
+    pub used_columns: usize,
+    pub charts: usize,
+    /// Cells inside detected regions that have a header row and at least
+    /// three rows.
+    pub table_cells: usize,
+    pub detected_regions: usize,
+    /// Cells in the largest detected region.
+    pub largest_region_cells: usize,
+}
+
+/// Sheet layout kind and a one-line reason pointing at an extraction
+/// strategy.
+pub fn classify_sheet_kind(inputs: &SheetKindInputs, form: &FormScan) -> (SheetKind, String) {
+    let occupied = inputs.occupied_cells;
+    if occupied == 0 {
+        return (SheetKind::Empty, "sheet has no occupied cells".to_string());
+    }
+    let share = |count: usize| count as f32 / occupied as f32;
+    let table_share = share(inputs.table_cells);
+    let formula_share = share(inputs.formula_cells);
+
+    if inputs.charts > 0 && table_share < 0.8 {
+        return (
+            SheetKind::Dashboard,
+            format!(
+                "{} chart(s) over summary cells; read individual regions or inspect cells rather than paging rows",
+                inputs.charts
+            ),
+        );
+    }
+    if table_share >= 0.6 {
+        return (
+            SheetKind::Table,
+            format!(
+                "headed tables cover {:.0}% of occupied cells; use read-table with a region id",
+                table_share * 100.0
+            ),
+        );
+    }
+    if form.looks_like_form(occupied, 0.4, false)
+        || (!form.signature_blocks.is_empty() && form.fields.len() >= 2)
+    {
+        return (
+            SheetKind::Form,
+            format!(
+                "{} labeled input field(s){}; read the form fields (label -> input cell) instead of a table",
+                form.fields.len(),
+                if form.signature_blocks.is_empty() {
+                    String::new()
+                } else {
+                    " and a signature block".to_string()
+                }
+            ),
+        );
+    }
+    if inputs.detected_regions >= 4
+        && formula_share >= 0.3
+        && share(inputs.largest_region_cells) < 0.4
+    {
+        return (
+            SheetKind::Dashboard,
+            format!(
+                "{} small formula-driven blocks; read individual regions rather than paging rows",
+                inputs.detected_regions
+            ),
+        );
+    }
+    if share(inputs.text_cells) >= 0.9
+        && share(inputs.number_cells) <= 0.05
+        && (form.instructions.len() * 3 >= inputs.text_cells || inputs.used_columns <= 2)
+    {
+        return (
+            SheetKind::Notes,
+            "mostly free text; read it with sheet-page or range-values".to_string(),
+        );
+    }
+    if table_share >= 0.3 {
+        return (
+            SheetKind::Table,
+            format!(
+                "headed tables cover {:.0}% of occupied cells alongside other content; use read-table with a region id",
+                table_share * 100.0
+            ),
+        );
+    }
+    (
+        SheetKind::Mixed,
+        "no dominant layout; start from detected regions".to_string(),
+    )
+}
+
+fn is_label_text(text: &str) -> bool {
+    !text.is_empty()
+        && text.chars().count() <= MAX_LABEL_CHARS
+        && text.split_whitespace().count() <= MAX_LABEL_WORDS
+        && text.chars().any(char::is_alphabetic)
+}
+
+fn is_instruction_text(text: &str) -> bool {
+    text.split_whitespace().count() >= INSTRUCTION_MIN_WORDS
+        || text.chars().count() >= INSTRUCTION_MIN_CHARS
+}
+
+fn address_of(row: u32, col: u32) -> String {
+    format!("{}{}", column_number_to_name(col), row)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(row: u32, col: u32, value: &str) -> LayoutCell {
+        LayoutCell {
+            row,
+            col,
+            value: Some(CellValue::Text(value.to_string())),
+            is_formula: false,
+        }
+    }
+
+    fn number(row: u32, col: u32, value: f64) -> LayoutCell {
+        LayoutCell {
+            row,
+            col,
+            value: Some(CellValue::Number(value)),
+            is_formula: false,
+        }
+    }
+
+    fn blank(row: u32, col: u32) -> LayoutCell {
+        LayoutCell {
+            row,
+            col,
+            value: None,
+            is_formula: false,
+        }
+    }
+
+    #[test]
+    fn scans_labeled_inputs_instructions_and_signature_blocks() {
+        let cells = vec![
+            text(1, 1, "Expense claim"),
+            text(
+                2,
+                1,
+                "Fill in every field and attach receipts before sending the form to finance.",
+            ),
+            text(4, 1, "Employee name:"),
+            text(4, 2, "Dana Reyes"),
+            text(4, 4, "Department:"),
+            blank(4, 5),
+            text(5, 1, "Amount:"),
+            number(5, 2, 120.0),
+            text(7, 1, "Signature:"),
+            blank(8, 1),
+        ];
+        let scan = scan_form(&cells);
+        let pairs: Vec<(&str, &str, bool)> = scan
+            .fields
+            .iter()
+            .map(|f| (f.label.as_str(), f.input_address.as_str(), f.filled))
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![
+                ("Employee name", "B4", true),
+                ("Department", "E4", false),
+                ("Amount", "B5", true),
+                ("Signature", "A8", false),
+            ]
+        );
+        assert_eq!(scan.instructions, vec!["A2".to_string()]);
+        assert_eq!(scan.signature_blocks, vec!["A7".to_string()]);
+        assert!(scan.looks_like_form(cells.len(), 0.4, false));
+
+        let (kind, _) = classify_sheet_kind(
+            &SheetKindInputs {
+                occupied_cells: cells.len(),
+                text_cells: 7,
+                number_cells: 1,
+                used_columns: 5,
+                detected_regions: 2,
+                ..Default::default()
+            },
+            &scan,
+        );
+        assert_eq!(kind, SheetKind::Form);
+    }
+
+    #[test]
+    fn key_value_parameters_and_table_rows_are_not_forms() {
+        let parameters = vec![
+            text(1, 1, "Rate"),
+            number(1, 2, 0.05),
+            text(2, 1, "Term"),
+            number(2, 2, 12.0),
+            text(3, 1, "Principal"),
+            number(3, 2, 1000.0),
+        ];
+        let scan = scan_form(&parameters);
+        assert_eq!(scan.fields.len(), 3);
+        assert!(!scan.looks_like_form(parameters.len(), 0.4, false));
+
+        let mut table = vec![text(1, 1, "Name"), text(1, 2, "Amount")];
+        for (row, name) in ["Alice", "Bob", "Cara", "Dan"].iter().enumerate() {
+            table.push(text(row as u32 + 2, 1, name));
+            table.push(number(row as u32 + 2, 2, 10.0));
+        }
+        let scan = scan_form(&table);
+        let (kind, _) = classify_sheet_kind(
+            &SheetKindInputs {
+                occupied_cells: table.len(),
+                text_cells: 6,
+                number_cells: 4,
+                used_columns: 2,
+                table_cells: table.len(),
+                detected_regions: 1,
+                largest_region_cells: table.len(),
+                ..Default::default()
+            },
+            &scan,
+        );
+        assert_eq!(kind, SheetKind::Table);
+    }
+}
@@ -1,4 +1,6 @@
 pub mod classification;
+pub mod consistency;
 pub mod formula;
+pub mod layout;
 pub mod stats;
 pub mod style;
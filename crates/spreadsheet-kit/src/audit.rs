@@ -0,0 +1,47 @@
+//! Write-audit journal for batch write commands.
+//!
+//! With `--audit-log <path.jsonl>`, every batch write that replaces a workbook
+//! appends one JSON line describing what it did: when, which command, a hash
+//! of the ops payload, the per-op counts, the changed cells, and hashes of the
+//! workbook before and after. Records are only written once the target has
+//! been replaced, so dry runs and vetoed or failed writes leave no entry.
+
+use crate::hooks::ChangedCellManifest;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WriteAuditRecord {
+    /// RFC 3339 UTC time the target was replaced.
+    pub timestamp: String,
+    pub command: String,
+    pub source_path: String,
+    pub target_path: String,
+    /// SHA-256 of the `--ops` payload file as read.
+    pub ops_hash: String,
+    pub operation_counts: BTreeMap<String, u64>,
+    /// SHA-256 of the source workbook before the write.
+    pub before_hash: String,
+    /// SHA-256 of the workbook written to the target.
+    pub after_hash: String,
+    pub changed_cells: ChangedCellManifest,
+}
+
+/// Append `record` as one line to the JSONL journal at `path`, creating the
+/// file if needed.
+pub fn append_audit_record(path: &Path, record: &WriteAuditRecord) -> Result<()> {
+    let mut line = serde_json::to_vec(record)?;
+    line.push(b'\n');
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("unable to open audit log '{}'", path.display()))?;
+    // One write call per record keeps concurrent appenders from interleaving.
+    file.write_all(&line)
+        .with_context(|| format!("unable to append to audit log '{}'", path.display()))
+}
@@ -1,9 +1,14 @@
+use crate::diff::cache::PartCache;
+use crate::diff::compare::CompareOptions;
+use crate::diff::ignore::DiffIgnore;
+use crate::diff::{Change, calculate_changeset_with};
 use crate::runtime::stateless::StatelessRuntime;
+use crate::tools::workbook_props::{EXCEL_1904_OFFSET_DAYS, read_workbook_props};
 use anyhow::{Result, anyhow, bail};
 use serde::Serialize;
 use serde_json::{Map, Value, json};
 use std::collections::{BTreeMap, BTreeSet};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 const DIFF_LIMIT_MAX: u32 = 2_000;
 const GROUP_PREVIEW_LIMIT: usize = 25;
@@ -72,6 +77,104 @@ pub struct DiffCommandArgs {
     pub limit: u32,
     pub offset: u32,
     pub exclude_recalc_result: bool,
+    pub compare: CompareOptions,
+    pub ignore_file: Option<PathBuf>,
+}
+
+pub struct DiffManyCommandArgs {
+    pub original: PathBuf,
+    pub candidates: Vec<PathBuf>,
+    pub sheet: Option<String>,
+    pub sheets: Option<Vec<String>>,
+    pub range: Option<String>,
+    pub details: bool,
+    pub limit: u32,
+    pub offset: u32,
+    pub exclude_recalc_result: bool,
+    pub compare: CompareOptions,
+    pub ignore_file: Option<PathBuf>,
+}
+
+/// Filters and paging shared by `diff` and `diff-many`.
+struct DiffView {
+    sheet_filters: Vec<String>,
+    range_bounds: Option<A1Bounds>,
+    details: bool,
+    limit: u32,
+    offset: u32,
+    exclude_recalc_result: bool,
+    compare: CompareOptions,
+    /// Ignore rules and the file they came from
+    ignore: Option<(DiffIgnore, String)>,
+}
+
+impl DiffView {
+    fn new(
+        sheet: Option<String>,
+        sheets: Option<Vec<String>>,
+        range: Option<String>,
+        details: bool,
+        limit: u32,
+        offset: u32,
+        exclude_recalc_result: bool,
+        compare: CompareOptions,
+        ignore_file: Option<PathBuf>,
+    ) -> Result<Self> {
+        if sheet.is_some() && sheets.is_some() {
+            bail!("invalid argument: --sheet and --sheets are mutually exclusive");
+        }
+        if details && (limit == 0 || limit > DIFF_LIMIT_MAX) {
+            bail!(
+                "invalid argument: --limit must be between 1 and {}",
+                DIFF_LIMIT_MAX
+            );
+        }
+        compare
+            .validate()
+            .map_err(|error| anyhow!("invalid argument: {error}"))?;
+        let ignore = ignore_file
+            .map(|path| {
+                DiffIgnore::load(&path)
+                    .map(|ignore| (ignore, path.display().to_string()))
+                    .map_err(|error| anyhow!("invalid argument: {error:#}"))
+            })
+            .transpose()?;
+
+        let sheet_filters: Vec<String> = if let Some(s) = sheet {
+            vec![s]
+        } else {
+            sheets.unwrap_or_default()
+        };
+
+        let range_bounds = if let Some(range) = range.as_ref() {
+            Some(
+                parse_a1_range(range)
+                    .ok_or_else(|| anyhow!("invalid argument: --range must be A1 notation"))?,
+            )
+        } else {
+            None
+        };
+
+        Ok(Self {
+            sheet_filters,
+            range_bounds,
+            details,
+            limit,
+            offset,
+            exclude_recalc_result,
+            compare,
+            ignore,
+        })
+    }
+
+    /// Drop changes matched by the ignore file, returning how many were
+    /// dropped.
+    fn apply_ignore(&self, changes: &mut Vec<Change>) -> Result<usize> {
+        match &self.ignore {
+            Some((ignore, _)) => ignore.retain(changes),
+            None => Ok(0),
+        }
+    }
 }
 
 pub async fn diff(args: DiffCommandArgs) -> Result<Value> {
@@ -85,43 +188,125 @@ pub async fn diff(args: DiffCommandArgs) -> Result<Value> {
         limit,
         offset,
         exclude_recalc_result,
+        compare,
+        ignore_file,
     } = args;
-    if sheet.is_some() && sheets.is_some() {
-        bail!("invalid argument: --sheet and --sheets are mutually exclusive");
-    }
+    let view = DiffView::new(
+        sheet,
+        sheets,
+        range,
+        details,
+        limit,
+        offset,
+        exclude_recalc_result,
+        compare,
+        ignore_file,
+    )?;
 
     let runtime = StatelessRuntime;
     let original = runtime.normalize_existing_file(&original)?;
     let modified = runtime.normalize_existing_file(&modified)?;
 
-    if details && (limit == 0 || limit > DIFF_LIMIT_MAX) {
-        bail!(
-            "invalid argument: --limit must be between 1 and {}",
-            DIFF_LIMIT_MAX
-        );
-    }
-
-    let sheet_filters: Vec<String> = if let Some(s) = sheet {
-        vec![s]
-    } else {
-        sheets.unwrap_or_default()
+    let mut changes = calculate_changeset_with(&original, &modified, None, &view.compare, None)?;
+    let ignored = view.apply_ignore(&mut changes)?;
+    let changes = match serde_json::to_value(changes)? {
+        Value::Array(changes) => changes,
+        _ => Vec::new(),
     };
 
-    let range_bounds = if let Some(range) = range.as_ref() {
-        Some(
-            parse_a1_range(range)
-                .ok_or_else(|| anyhow!("invalid argument: --range must be A1 notation"))?,
-        )
-    } else {
-        None
-    };
+    Ok(diff_response(&original, &modified, changes, ignored, &view))
+}
 
-    let mut payload = runtime.diff_json(&original, &modified)?;
-    let changes = payload
-        .get_mut("changes")
-        .and_then(Value::as_array_mut)
-        .map(std::mem::take)
-        .unwrap_or_default();
+/// Diff one baseline against several candidates. Sheet parts are cached by
+/// content hash, so the baseline is parsed once however many candidates
+/// there are, and identical candidate sheets are parsed once between them.
+pub async fn diff_many(args: DiffManyCommandArgs) -> Result<Value> {
+    let DiffManyCommandArgs {
+        original,
+        candidates,
+        sheet,
+        sheets,
+        range,
+        details,
+        limit,
+        offset,
+        exclude_recalc_result,
+        compare,
+        ignore_file,
+    } = args;
+    let view = DiffView::new(
+        sheet,
+        sheets,
+        range,
+        details,
+        limit,
+        offset,
+        exclude_recalc_result,
+        compare,
+        ignore_file,
+    )?;
+    if candidates.is_empty() {
+        bail!("invalid argument: diff-many requires at least one CANDIDATE");
+    }
+
+    let runtime = StatelessRuntime;
+    let original = runtime.normalize_existing_file(&original)?;
+    let candidates = candidates
+        .iter()
+        .map(|candidate| runtime.normalize_existing_file(candidate))
+        .collect::<Result<Vec<_>>>()?;
+
+    let started = std::time::Instant::now();
+    let cache = PartCache::default();
+    let mut results = Vec::with_capacity(candidates.len());
+    for candidate in &candidates {
+        let mut changes =
+            calculate_changeset_with(&original, candidate, None, &view.compare, Some(&cache))?;
+        let ignored = view.apply_ignore(&mut changes)?;
+        let changes = match serde_json::to_value(changes)? {
+            Value::Array(changes) => changes,
+            _ => Vec::new(),
+        };
+        let mut result = diff_response(&original, candidate, changes, ignored, &view);
+        if let Some(object) = result.as_object_mut() {
+            object.remove("original");
+        }
+        results.push(result);
+    }
+
+    Ok(json!({
+        "original": original.display().to_string(),
+        "candidate_count": results.len(),
+        "candidates": results,
+        "cache": cache.stats(),
+        "elapsed_ms": started.elapsed().as_millis() as u64,
+    }))
+}
+
+fn diff_response(
+    original: &Path,
+    modified: &Path,
+    changes: Vec<Value>,
+    ignored: usize,
+    view: &DiffView,
+) -> Value {
+    let DiffView {
+        sheet_filters,
+        range_bounds,
+        details,
+        limit,
+        offset,
+        exclude_recalc_result,
+        compare,
+        ignore,
+    } = view;
+    let (range_bounds, details, limit, offset, exclude_recalc_result) = (
+        *range_bounds,
+        *details,
+        *limit,
+        *offset,
+        *exclude_recalc_result,
+    );
 
     let mut counts_by_kind: BTreeMap<String, u32> = BTreeMap::new();
     let mut counts_by_type: BTreeMap<String, u32> = BTreeMap::new();
@@ -131,7 +316,7 @@ pub async fn diff(args: DiffCommandArgs) -> Result<Value> {
     let mut filtered = Vec::new();
     let mut recalc_result_change_count = 0u32;
     for change in changes {
-        if !change_matches_filters(&change, &sheet_filters, range_bounds) {
+        if !change_matches_filters(&change, sheet_filters, range_bounds) {
             continue;
         }
 
@@ -191,7 +376,18 @@ pub async fn diff(args: DiffCommandArgs) -> Result<Value> {
         (0, Vec::new(), false, None)
     };
 
-    let summary = json!({
+    let mut filters = json!({ "exclude_recalc_result": exclude_recalc_result });
+    if !compare.is_default()
+        && let (Some(filters), Ok(Value::Object(options))) =
+            (filters.as_object_mut(), serde_json::to_value(compare))
+    {
+        filters.extend(options);
+    }
+    if let (Some(filters), Some((_, path))) = (filters.as_object_mut(), ignore) {
+        filters.insert("ignore_file".to_string(), Value::String(path.clone()));
+    }
+
+    let mut summary = json!({
         "total_changes": total_changes,
         "returned_changes": returned_changes,
         "truncated": truncated,
@@ -207,10 +403,13 @@ pub async fn diff(args: DiffCommandArgs) -> Result<Value> {
         "group_preview": group_preview,
         "group_preview_truncated": group_preview_truncated,
         "sheet_summaries": sheet_summaries,
-        "filters": {
-            "exclude_recalc_result": exclude_recalc_result,
-        }
+        "filters": filters,
     });
+    if ignore.is_some()
+        && let Some(summary) = summary.as_object_mut()
+    {
+        summary.insert("ignored_change_count".to_string(), Value::from(ignored));
+    }
 
     let mut response = Map::new();
     response.insert(
@@ -223,6 +422,9 @@ pub async fn diff(args: DiffCommandArgs) -> Result<Value> {
     );
     response.insert("change_count".to_string(), Value::from(total_changes));
     response.insert("summary".to_string(), summary);
+    if let Some(warning) = date_system_warning(original, modified) {
+        response.insert("warnings".to_string(), json!([warning]));
+    }
 
     if details {
         response.insert("changes".to_string(), Value::Array(paged_changes));
@@ -237,7 +439,24 @@ pub async fn diff(args: DiffCommandArgs) -> Result<Value> {
         );
     }
 
-    Ok(Value::Object(response))
+    Value::Object(response)
+}
+
+/// Cells are compared as stored, so a date kept in one date system never
+/// matches the same date kept in the other.
+fn date_system_warning(original: &Path, modified: &Path) -> Option<String> {
+    let date_system = |path: &Path| {
+        read_workbook_props(path)
+            .map(|props| if props.date1904 { "1904" } else { "1900" })
+            .unwrap_or("1900")
+    };
+    let (original_system, modified_system) = (date_system(original), date_system(modified));
+    (original_system != modified_system).then(|| {
+        format!(
+            "original uses the {original_system} date system and modified uses {modified_system}; \
+             the same date is stored {EXCEL_1904_OFFSET_DAYS} serial days apart, so date cells report value changes"
+        )
+    })
 }
 
 fn build_groups(changes: &[Value]) -> Vec<DiffGroup> {
@@ -362,7 +581,12 @@ fn change_group_type(change: &Value) -> &str {
 }
 
 fn change_kind(change: &Value) -> &'static str {
-    if change.get("address").is_some() {
+    let change_type = change.get("type").and_then(Value::as_str).unwrap_or("");
+    if change_type.starts_with("validation_") {
+        "validation"
+    } else if change_type.starts_with("conditional_format_") {
+        "conditional_format"
+    } else if change.get("address").is_some() {
         "cell"
     } else if change.get("display_name").is_some() {
         "table"
@@ -383,6 +607,7 @@ fn change_type_key(change: &Value) -> &str {
             Some("table_added") => "table_added",
             Some("table_deleted") => "table_deleted",
             Some("table_modified") => "table_modified",
+            Some("table_renamed") => "table_renamed",
             _ => "table_unknown",
         },
         "name" => match change.get("type").and_then(Value::as_str) {
@@ -391,6 +616,10 @@ fn change_type_key(change: &Value) -> &str {
             Some("name_modified") => "name_modified",
             _ => "name_unknown",
         },
+        "validation" | "conditional_format" => change
+            .get("type")
+            .and_then(Value::as_str)
+            .unwrap_or("unknown"),
         _ => "unknown",
     }
 }
@@ -411,10 +640,9 @@ fn change_address(change: &Value) -> Option<&str> {
 }
 
 fn change_item_name(change: &Value) -> Option<&str> {
-    change
-        .get("display_name")
-        .and_then(Value::as_str)
-        .or_else(|| change.get("name").and_then(Value::as_str))
+    ["display_name", "name", "range", "new_range"]
+        .iter()
+        .find_map(|key| change.get(*key).and_then(Value::as_str))
 }
 
 fn group_sort_key(change: &Value) -> (String, String, u32, u32, u32, u32, String) {
@@ -454,8 +682,19 @@ fn diff_group_sort_key(group: &DiffGroup) -> (u8, String, u32, u32, u32, u32, St
 fn review_priority_rank(group_type: &str) -> u8 {
     match group_type {
         "formula_edit" | "value_edit" | "style_edit" | "added" | "deleted" => 0,
-        "table_modified" | "name_modified" | "table_added" | "table_deleted" | "name_added"
-        | "name_deleted" => 1,
+        "table_modified"
+        | "name_modified"
+        | "table_added"
+        | "table_deleted"
+        | "table_renamed"
+        | "name_added"
+        | "name_deleted"
+        | "validation_added"
+        | "validation_deleted"
+        | "validation_modified"
+        | "conditional_format_added"
+        | "conditional_format_deleted"
+        | "conditional_format_modified" => 1,
         "recalc_result" => 2,
         _ => 3,
     }
@@ -609,14 +848,17 @@ fn address_in_bounds(address: &str, bounds: A1Bounds) -> bool {
 }
 
 fn range_intersects(range: &str, bounds: A1Bounds) -> bool {
-    let Some(candidate) = parse_a1_range(range) else {
-        return false;
-    };
+    // Validations and conditional formats may cover several areas ("A1:A5 C1:C5").
+    range.split_whitespace().any(|area| {
+        let Some(candidate) = parse_a1_range(area) else {
+            return false;
+        };
 
-    !(candidate.end_col < bounds.start_col
-        || candidate.start_col > bounds.end_col
-        || candidate.end_row < bounds.start_row
-        || candidate.start_row > bounds.end_row)
+        !(candidate.end_col < bounds.start_col
+            || candidate.start_col > bounds.end_col
+            || candidate.end_row < bounds.start_row
+            || candidate.start_row > bounds.end_row)
+    })
 }
 
 fn addresses_are_adjacent(left: &str, right: &str) -> bool {
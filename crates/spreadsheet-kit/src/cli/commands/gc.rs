@@ -0,0 +1,17 @@
+//! CLI command for `asp gc`: remove scratch left behind by crashed servers
+//! and interrupted CLI runs.
+
+use crate::scratch::{self, collect_garbage};
+use anyhow::Result;
+use serde_json::Value;
+use std::path::PathBuf;
+use std::time::Duration;
+
+pub async fn gc(scratch_dir: Option<PathBuf>, older_than: u64, dry_run: bool) -> Result<Value> {
+    let root = scratch_dir.unwrap_or_else(scratch::default_root);
+    let report = tokio::task::spawn_blocking(move || {
+        collect_garbage(&root, Duration::from_secs(older_than), None, dry_run)
+    })
+    .await??;
+    Ok(serde_json::to_value(report)?)
+}
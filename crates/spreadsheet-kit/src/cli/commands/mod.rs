@@ -1,6 +1,10 @@
 pub mod diff;
+pub mod gc;
 pub mod read;
 pub mod recalc;
+pub mod revisions;
+#[cfg(feature = "serve")]
+pub mod serve;
 pub mod session;
 pub mod verify;
 pub mod write;
@@ -1,24 +1,41 @@
 use anyhow::{Context, Result, anyhow, bail};
 use serde_json::Value;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::cli::{
-    FindValueMode, FormulaSort, LabelDirectionArg, LayoutModeArg, LayoutRenderArg,
-    RangeValuesFormatArg, SheetPageFormatArg, TableReadFormat, TableSampleModeArg,
-    TraceDirectionArg,
+    CsvDialectArgs, CsvLineEndingArg, CsvQuoteArg, DateHandlingArg, FindCompareOpArg,
+    FindValueMode, FindValueTypeArg, FormulaSort, HeaderMatchArg, LabelDirectionArg, LayoutModeArg,
+    LayoutRenderArg, NameScopeArg, RangeValuesFormatArg, RoundingModeArg, SchemaFormatArg,
+    SheetPageFormatArg, SheetPageOrientationArg, TableOrientationArg, TableReadFormat,
+    TableSampleModeArg, TraceDirectionArg,
 };
+use crate::column_map::{ColumnMapTable, TableSample, propose_column_map};
+use crate::metadata::{METADATA_SHEET_NAME, annotation_bounds, read_annotations, read_metadata};
 use crate::model::{
-    FindMode, FormulaParsePolicy, LabelDirection, LayoutMode, LayoutRender, SheetPageFormat,
-    TableOutputFormat, TraceCursor, TraceDirection,
+    ExternalLinkStatus, FindMode, FormulaLintRule, FormulaParsePolicy, LabelDirection, LayoutMode,
+    LayoutRender, NamedItemKind, NamedRangeDescriptor, NamedRangeScope, ReferenceStyle,
+    SheetPageFormat, SheetPageOrientation, TableOutputFormat, TraceCursor, TraceDirection,
 };
 use crate::runtime::stateless::StatelessRuntime;
 use crate::tools;
+use crate::tools::csv_format::{CsvLineEnding, CsvOptions, CsvQuoting};
+use crate::tools::named_query::{
+    NamedQuery, WORKSPACE_QUERY_FILES, load_named_queries, workspace_queries_file,
+};
+use crate::tools::param_enums::{
+    AggregateOp, DateHandling, HeaderMatchMode, RoundingMode, SchemaFormat, TableOrientation,
+};
 use crate::tools::{
-    DescribeWorkbookParams, FindFormulaParams, FindValueParams, FormulaSortBy, FormulaTraceParams,
-    InspectCellsParams, LayoutPageParams, ListSheetsParams, ManifestStubParams, NamedRangesParams,
-    RangeValuesParams, ReadTableParams, SampleMode, ScanVolatilesParams, SheetFormulaMapParams,
-    SheetOverviewParams, SheetPageParams, SheetStatisticsParams, TableFilter, TableProfileParams,
+    ComparisonOp, ComparisonValueType, DescribeWorkbookParams, EstimateReadParams,
+    FindFormulaParams, FindValueParams, FormulaSortBy, FormulaTraceParams, InspectCellsParams,
+    LayoutPageParams, LintFormulasParams, ListSheetsParams, ManifestStubParams, MatchMode,
+    NamedRangesParams, QueryTableParams, RangeValuesParams, ReadTableParams, SampleMode,
+    ScanErrorsParams, ScanVolatilesParams, SheetFormulaMapParams, SheetOverviewParams,
+    SheetPageParams, SheetStatisticsParams, TableAggregation, TableFilter, TableProfileParams,
+    ValueComparison,
 };
+use crate::workbook::cell_to_value_with_date_system;
+use crate::write::{PreservedPackage, save_workbook_preserving};
 
 // ---------------------------------------------------------------------------
 // Session resolution helper
@@ -46,7 +63,7 @@ pub fn resolve_file_or_session(
 
             let mut tmp = tempfile::Builder::new()
                 .suffix(".xlsx")
-                .tempfile()
+                .tempfile_in(crate::scratch::temp_dir()?)
                 .context("failed to create temp file for session read")?;
             std::io::Write::write_all(&mut tmp, &bytes)
                 .context("failed to write materialized session to temp file")?;
@@ -69,6 +86,8 @@ const SHEET_PAGE_DEFAULT_INCLUDE_FORMULAS: bool = true;
 const SHEET_PAGE_DEFAULT_INCLUDE_STYLES: bool = false;
 const SHEET_PAGE_DEFAULT_INCLUDE_HEADER: bool = true;
 
+const VISUAL_DIFF_REGION_LIMIT: usize = 50;
+
 pub async fn list_sheets(file: PathBuf) -> Result<Value> {
     let runtime = StatelessRuntime;
     let (state, workbook_id) = runtime.open_state_for_file(&file).await?;
@@ -109,6 +128,7 @@ pub async fn range_values(
     ranges: Vec<String>,
     format: Option<RangeValuesFormatArg>,
     include_formulas: Option<bool>,
+    include_formatting: Option<bool>,
 ) -> Result<Value> {
     if ranges.is_empty() {
         bail!("at least one range must be provided");
@@ -127,8 +147,12 @@ pub async fn range_values(
             ranges,
             include_headers: None,
             include_formulas,
+            include_formatting,
             format: Some(resolved_format),
             page_size: None,
+            csv_options: None,
+            compression: None,
+            compress_min_bytes: None,
         },
     )
     .await?;
@@ -142,17 +166,63 @@ pub async fn range_export(
     format: String,
     output: Option<String>,
     include_formulas: Option<bool>,
+    include_formatting: Option<bool>,
+    csv: CsvDialectArgs,
 ) -> Result<Value> {
     let is_csv = format == "csv";
     let is_grid = format == "grid";
-    if !is_csv && !is_grid && format != "json" {
+    let is_arrow = format == "arrow";
+    if !is_csv && !is_grid && !is_arrow && format != "json" {
         bail!("unsupported format: {}", format);
     }
+    let csv_options = csv_options_from_args(csv)?;
+    let arrow_output = match (is_arrow, &output) {
+        (true, Some(path)) if path != "-" => Some(PathBuf::from(path)),
+        (true, _) => return Err(invalid_argument("--format arrow needs --output <PATH>")),
+        (false, _) => None,
+    };
 
     let runtime = StatelessRuntime;
     let (state, workbook_id) = runtime.open_state_for_file(&file).await?;
     let sheet = resolve_sheet_name(&state, &workbook_id, &sheet).await?;
 
+    if let Some(path) = arrow_output {
+        let mut headers = Vec::new();
+        let mut rows = Vec::new();
+        let mut offset = 0;
+        loop {
+            let page = tools::read_table(
+                state.clone(),
+                ReadTableParams {
+                    workbook_or_fork_id: workbook_id.clone(),
+                    sheet_name: Some(sheet.clone()),
+                    range: Some(range.clone()),
+                    limit: Some(ARROW_EXPORT_PAGE_ROWS),
+                    offset: Some(offset),
+                    format: Some(TableOutputFormat::Json),
+                    ..Default::default()
+                },
+            )
+            .await?;
+            if headers.is_empty() {
+                headers = page.headers;
+            }
+            rows.extend(page.rows);
+            match page.next_offset {
+                Some(next) => offset = next,
+                None => break,
+            }
+        }
+        let columns = write_arrow_table(&path, &headers, &rows)?;
+        return Ok(serde_json::json!({
+            "status": "ok",
+            "path": path.display().to_string(),
+            "format": "arrow",
+            "row_count": rows.len(),
+            "columns": columns,
+        }));
+    }
+
     if is_grid {
         let payload = tools::grid_export(
             state,
@@ -192,8 +262,12 @@ pub async fn range_export(
             ranges: vec![range],
             include_headers: None,
             include_formulas,
+            include_formatting,
             format: Some(table_format),
             page_size: None,
+            csv_options,
+            compression: None,
+            compress_min_bytes: None,
         },
     )
     .await?;
@@ -268,12 +342,41 @@ pub async fn sheet_page(
     page_size: Option<u32>,
     columns: Option<Vec<String>>,
     columns_by_header: Option<Vec<String>>,
+    header_match: Option<HeaderMatchArg>,
+    header_match_threshold: Option<f64>,
     include_formulas: Option<bool>,
     include_styles: Option<bool>,
     include_header: Option<bool>,
     format: SheetPageFormatArg,
+    orientation: Option<SheetPageOrientationArg>,
+    start_column: Option<String>,
+    date_handling: Option<DateHandlingArg>,
 ) -> Result<Value> {
     validate_sheet_page_arguments(page_size, columns.as_ref())?;
+    if let Some(threshold) = header_match_threshold {
+        if !matches!(header_match, Some(HeaderMatchArg::Fuzzy)) {
+            return Err(invalid_argument(
+                "--header-match-threshold requires --header-match fuzzy",
+            ));
+        }
+        if !(threshold > 0.0 && threshold <= 1.0) {
+            return Err(invalid_argument(
+                "--header-match-threshold must be greater than 0 and at most 1",
+            ));
+        }
+    }
+    if let Some(start_column) = start_column.as_deref() {
+        if !matches!(orientation, Some(SheetPageOrientationArg::Columns)) {
+            return Err(invalid_argument(
+                "--start-column requires --orientation columns",
+            ));
+        }
+        if !is_valid_column_token(start_column.trim()) {
+            return Err(invalid_argument(format!(
+                "invalid --start-column: '{start_column}'"
+            )));
+        }
+    }
 
     let runtime = StatelessRuntime;
     let (state, workbook_id) = runtime.open_state_for_file(&file).await?;
@@ -287,16 +390,375 @@ pub async fn sheet_page(
             page_size: page_size.unwrap_or(SHEET_PAGE_DEFAULT_PAGE_SIZE),
             columns,
             columns_by_header,
+            header_match: header_match.map(map_header_match),
+            header_match_threshold,
             include_formulas: include_formulas.unwrap_or(SHEET_PAGE_DEFAULT_INCLUDE_FORMULAS),
             include_styles: include_styles.unwrap_or(SHEET_PAGE_DEFAULT_INCLUDE_STYLES),
             include_header: include_header.unwrap_or(SHEET_PAGE_DEFAULT_INCLUDE_HEADER),
             format: Some(map_sheet_page_format(format)),
+            orientation: orientation.map(map_sheet_page_orientation),
+            start_column,
+            date_handling: date_handling.map(map_date_handling),
+            compression: None,
+            compress_min_bytes: None,
         },
     )
     .await?;
     Ok(serde_json::to_value(response)?)
 }
 
+/// Page through every sheet in workbook order with one `(sheet, row)` cursor.
+/// `page_size` rows are shared across sheets: a page finishes the current
+/// sheet and moves on to the next until the budget is spent.
+#[allow(clippy::too_many_arguments)]
+pub async fn workbook_page(
+    file: PathBuf,
+    start_sheet: Option<String>,
+    start_row: Option<u32>,
+    page_size: Option<u32>,
+    include_formulas: Option<bool>,
+    include_styles: Option<bool>,
+    include_header: Option<bool>,
+    format: SheetPageFormatArg,
+) -> Result<Value> {
+    validate_sheet_page_arguments(page_size, None)?;
+    if matches!(start_row, Some(0)) {
+        return Err(invalid_argument("--start-row must be at least 1"));
+    }
+    if start_row.is_some() && start_sheet.is_none() {
+        return Err(invalid_argument("--start-row requires --start-sheet"));
+    }
+
+    let runtime = StatelessRuntime;
+    let (state, workbook_id) = runtime.open_state_for_file(&file).await?;
+    let sheet_names: Vec<String> = tools::list_sheets(
+        state.clone(),
+        ListSheetsParams {
+            workbook_or_fork_id: workbook_id.clone(),
+            limit: Some(u32::MAX),
+            offset: None,
+            include_bounds: Some(false),
+        },
+    )
+    .await?
+    .sheets
+    .into_iter()
+    .map(|entry| entry.name)
+    .collect();
+
+    let mut index = match start_sheet {
+        Some(requested) => {
+            let name = resolve_sheet_name(&state, &workbook_id, &requested).await?;
+            sheet_names
+                .iter()
+                .position(|candidate| candidate == &name)
+                .unwrap_or(0)
+        }
+        None => 0,
+    };
+    let mut row = start_row.unwrap_or(SHEET_PAGE_DEFAULT_START_ROW);
+    let mut remaining = page_size.unwrap_or(SHEET_PAGE_DEFAULT_PAGE_SIZE);
+    let mut pages = Vec::new();
+    let mut next_cursor = None;
+
+    while index < sheet_names.len() {
+        if remaining == 0 {
+            next_cursor = Some((sheet_names[index].clone(), row));
+            break;
+        }
+        let response = tools::sheet_page(
+            state.clone(),
+            SheetPageParams {
+                workbook_or_fork_id: workbook_id.clone(),
+                sheet_name: sheet_names[index].clone(),
+                start_row: row,
+                page_size: remaining,
+                columns: None,
+                columns_by_header: None,
+                header_match: None,
+                header_match_threshold: None,
+                include_formulas: include_formulas.unwrap_or(SHEET_PAGE_DEFAULT_INCLUDE_FORMULAS),
+                include_styles: include_styles.unwrap_or(SHEET_PAGE_DEFAULT_INCLUDE_STYLES),
+                include_header: include_header.unwrap_or(SHEET_PAGE_DEFAULT_INCLUDE_HEADER),
+                format: Some(map_sheet_page_format(format)),
+                orientation: None,
+                start_column: None,
+                date_handling: None,
+                compression: None,
+                compress_min_bytes: None,
+            },
+        )
+        .await?;
+
+        let returned = match (&response.compact, &response.values_only) {
+            (Some(compact), _) => compact.rows.len(),
+            (None, Some(values)) => values.rows.len(),
+            (None, None) => response.rows.len(),
+        } as u32;
+        remaining = remaining.saturating_sub(returned);
+        let continue_at = response.next_start_row;
+        pages.push(serde_json::to_value(response)?);
+
+        if let Some(next_row) = continue_at {
+            next_cursor = Some((sheet_names[index].clone(), next_row));
+            break;
+        }
+        index += 1;
+        row = SHEET_PAGE_DEFAULT_START_ROW;
+    }
+
+    let mut payload = serde_json::json!({
+        "workbook_id": workbook_id,
+        "format": map_sheet_page_format(format),
+        "sheet_count": sheet_names.len(),
+        "pages": pages,
+    });
+    if let Some((sheet, row)) = next_cursor {
+        payload["next_start_sheet"] = Value::String(sheet);
+        payload["next_start_row"] = Value::from(row);
+    }
+    Ok(payload)
+}
+
+/// Render a sheet range to a PNG at `output` for visual verification.
+pub async fn render(
+    file: PathBuf,
+    sheet: String,
+    range: Option<String>,
+    output: PathBuf,
+    dpi: Option<u32>,
+    scale: Option<f32>,
+    force: bool,
+) -> Result<Value> {
+    let is_png = output
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("png"));
+    if !is_png {
+        return Err(invalid_argument(format!(
+            "--output '{}' must be a .png path",
+            output.display()
+        )));
+    }
+    let dpi = tools::fork::resolve_render_dpi(dpi, scale)
+        .map_err(|error| invalid_argument(format!("--dpi/--scale {error}")))?;
+
+    let runtime = StatelessRuntime;
+    let source = runtime.normalize_existing_file(&file)?;
+    let target = runtime.normalize_destination_path(&output)?;
+    if target.exists() && !force {
+        bail!(
+            "output exists: output path '{}' already exists",
+            target.display()
+        );
+    }
+
+    let (state, workbook_id) = runtime.open_state_for_file(&source).await?;
+    let sheet = resolve_sheet_name(&state, &workbook_id, &sheet).await?;
+
+    // The executor writes an intermediate PDF beside its output, so render in a
+    // scratch directory and copy only the finished PNG to the target.
+    let staging = tempfile::Builder::new()
+        .prefix(".render-")
+        .tempdir_in(crate::scratch::temp_dir()?)
+        .context("failed to create render staging directory")?;
+    let staged = staging.path().join("render.png");
+    let rendered =
+        tools::fork::render_sheet_range(&source, &staged, &sheet, range.as_deref(), dpi).await?;
+    std::fs::copy(&rendered.output_path, &target)
+        .with_context(|| format!("failed to write render to '{}'", target.display()))?;
+
+    let mut payload = serde_json::json!({
+        "file": source.display().to_string(),
+        "sheet_name": sheet,
+        "range": range
+            .as_deref()
+            .unwrap_or(tools::fork::DEFAULT_SCREENSHOT_RANGE),
+        "output_path": target.display().to_string(),
+        "size_bytes": rendered.size_bytes,
+        "duration_ms": rendered.duration_ms,
+    });
+    if let Some(dpi) = dpi {
+        payload["dpi"] = Value::from(dpi);
+    }
+    Ok(payload)
+}
+
+/// Render the same range from two workbooks and write an overlay PNG of
+/// what changed visually.
+#[allow(clippy::too_many_arguments)]
+pub async fn visual_diff(
+    original: PathBuf,
+    modified: PathBuf,
+    sheet: String,
+    range: Option<String>,
+    output: PathBuf,
+    dpi: Option<u32>,
+    scale: Option<f32>,
+    tolerance: u8,
+    force: bool,
+) -> Result<Value> {
+    let is_png = output
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("png"));
+    if !is_png {
+        return Err(invalid_argument(format!(
+            "--output '{}' must be a .png path",
+            output.display()
+        )));
+    }
+    let dpi = tools::fork::resolve_render_dpi(dpi, scale)
+        .map_err(|error| invalid_argument(format!("--dpi/--scale {error}")))?;
+
+    let runtime = StatelessRuntime;
+    let original = runtime.normalize_existing_file(&original)?;
+    let modified = runtime.normalize_existing_file(&modified)?;
+    let target = runtime.normalize_destination_path(&output)?;
+    if target.exists() && !force {
+        bail!(
+            "output exists: output path '{}' already exists",
+            target.display()
+        );
+    }
+
+    let (state, workbook_id) = runtime.open_state_for_file(&original).await?;
+    let original_sheet = resolve_sheet_name(&state, &workbook_id, &sheet).await?;
+    let (state, workbook_id) = runtime.open_state_for_file(&modified).await?;
+    let modified_sheet = resolve_sheet_name(&state, &workbook_id, &sheet).await?;
+
+    let staging = tempfile::Builder::new()
+        .prefix(".visual-diff-")
+        .tempdir_in(crate::scratch::temp_dir()?)
+        .context("failed to create render staging directory")?;
+    let staged = staging.path().join("overlay.png");
+    let rendered = tools::fork::render_visual_diff(
+        &original,
+        &original_sheet,
+        &modified,
+        &modified_sheet,
+        &staged,
+        range.as_deref(),
+        dpi,
+        tolerance,
+    )
+    .await?;
+    std::fs::copy(&rendered.output_path, &target)
+        .with_context(|| format!("failed to write overlay to '{}'", target.display()))?;
+
+    let region_count = rendered.regions.len();
+    let mut regions = rendered.regions;
+    regions.truncate(VISUAL_DIFF_REGION_LIMIT);
+    let mut payload = serde_json::json!({
+        "original": original.display().to_string(),
+        "modified": modified.display().to_string(),
+        "sheet_name": modified_sheet,
+        "range": range
+            .as_deref()
+            .unwrap_or(tools::fork::DEFAULT_SCREENSHOT_RANGE),
+        "output_path": target.display().to_string(),
+        "identical": rendered.changed_pixels == 0,
+        "width": rendered.width,
+        "height": rendered.height,
+        "changed_pixels": rendered.changed_pixels,
+        "changed_ratio": (rendered.changed_ratio * 1e6).round() / 1e6,
+        "tolerance": tolerance,
+        "region_count": region_count,
+        "regions": regions,
+        "size_bytes": rendered.size_bytes,
+        "duration_ms": rendered.duration_ms,
+    });
+    if region_count > VISUAL_DIFF_REGION_LIMIT {
+        payload["regions_truncated"] = Value::Bool(true);
+    }
+    if let Some(dpi) = dpi {
+        payload["dpi"] = Value::from(dpi);
+    }
+    Ok(payload)
+}
+
+pub async fn export_pdf(
+    file: PathBuf,
+    sheet: Option<String>,
+    print_area: Option<String>,
+    output: PathBuf,
+    force: bool,
+) -> Result<Value> {
+    let is_pdf = output
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"));
+    if !is_pdf {
+        return Err(invalid_argument(format!(
+            "--output '{}' must be a .pdf path",
+            output.display()
+        )));
+    }
+    if print_area.is_some() && sheet.is_none() {
+        return Err(invalid_argument("--print-area requires --sheet"));
+    }
+
+    let runtime = StatelessRuntime;
+    let source = runtime.normalize_existing_file(&file)?;
+    let target = runtime.normalize_destination_path(&output)?;
+    if target.exists() && !force {
+        bail!(
+            "output exists: output path '{}' already exists",
+            target.display()
+        );
+    }
+
+    let (state, workbook_id) = runtime.open_state_for_file(&source).await?;
+    let sheet = match sheet {
+        Some(name) => Some(resolve_sheet_name(&state, &workbook_id, &name).await?),
+        None => None,
+    };
+
+    // Export into a scratch directory so a failed or partial soffice run never
+    // leaves a truncated PDF at the target.
+    let staging = tempfile::Builder::new()
+        .prefix(".export-pdf-")
+        .tempdir_in(crate::scratch::temp_dir()?)
+        .context("failed to create pdf export staging directory")?;
+    let staged = staging.path().join("export.pdf");
+    let exported =
+        tools::fork::export_workbook_pdf(&source, &staged, sheet.as_deref(), print_area.as_deref())
+            .await?;
+    std::fs::copy(&exported.output_path, &target)
+        .with_context(|| format!("failed to write pdf to '{}'", target.display()))?;
+
+    let mut payload = serde_json::json!({
+        "file": source.display().to_string(),
+        "output_path": target.display().to_string(),
+        "size_bytes": exported.size_bytes,
+        "duration_ms": exported.duration_ms,
+    });
+    if let Some(sheet) = sheet {
+        payload["sheet_name"] = Value::from(sheet);
+    }
+    if let Some(print_area) = print_area {
+        payload["print_area"] = Value::from(print_area);
+    }
+    Ok(payload)
+}
+
+pub async fn dedupe_scan(roots: Vec<PathBuf>, min_similarity: f64) -> Result<Value> {
+    if !(0.0..=1.0).contains(&min_similarity) {
+        return Err(invalid_argument(format!(
+            "--min-similarity must be between 0 and 1, got {min_similarity}"
+        )));
+    }
+    let extensions: Vec<String> = crate::config::DEFAULT_EXTENSIONS
+        .iter()
+        .map(|ext| ext.to_string())
+        .collect();
+    let report = tokio::task::spawn_blocking(move || {
+        tools::dedupe::scan_duplicates(&roots, &extensions, min_similarity, None)
+    })
+    .await??;
+    Ok(serde_json::to_value(report)?)
+}
+
 pub async fn describe(file: PathBuf) -> Result<Value> {
     let runtime = StatelessRuntime;
     let (state, workbook_id) = runtime.open_state_for_file(&file).await?;
@@ -310,21 +772,311 @@ pub async fn describe(file: PathBuf) -> Result<Value> {
     Ok(serde_json::to_value(response)?)
 }
 
+/// Tool metadata stored in the workbook's hidden `_metadata` sheet.
+pub async fn metadata(
+    file: PathBuf,
+    namespace: Option<String>,
+    key: Option<String>,
+) -> Result<Value> {
+    let runtime = StatelessRuntime;
+    let (state, workbook_id) = runtime.open_state_for_file(&file).await?;
+    let workbook = state.open_workbook(&workbook_id).await?;
+    let entries: Vec<_> = workbook
+        .with_spreadsheet(read_metadata)?
+        .into_iter()
+        .filter(|entry| {
+            namespace
+                .as_deref()
+                .is_none_or(|namespace| entry.namespace == namespace.trim())
+                && key.as_deref().is_none_or(|key| entry.key == key.trim())
+        })
+        .collect();
+    Ok(serde_json::json!({
+        "workbook_id": workbook_id,
+        "sheet": METADATA_SHEET_NAME,
+        "count": entries.len(),
+        "entries": entries,
+    }))
+}
+
+/// Cell annotations stored by `annotate`, optionally limited to one sheet and
+/// to annotations overlapping `range`.
+pub async fn annotations(
+    file: PathBuf,
+    sheet: Option<String>,
+    range: Option<String>,
+) -> Result<Value> {
+    if range.is_some() && sheet.is_none() {
+        bail!("invalid argument: --range requires --sheet");
+    }
+    let bounds = range
+        .as_deref()
+        .map(annotation_bounds)
+        .transpose()
+        .map_err(|error| anyhow!("invalid argument: --range: {error:#}"))?;
+
+    let runtime = StatelessRuntime;
+    let (state, workbook_id) = runtime.open_state_for_file(&file).await?;
+    let workbook = state.open_workbook(&workbook_id).await?;
+    let annotations: Vec<_> = workbook
+        .with_spreadsheet(read_annotations)?
+        .into_iter()
+        .filter(|annotation| {
+            sheet
+                .as_deref()
+                .is_none_or(|sheet| annotation.sheet == sheet.trim())
+                && bounds.is_none_or(|bounds| annotation.overlaps(bounds))
+        })
+        .collect();
+    Ok(serde_json::json!({
+        "workbook_id": workbook_id,
+        "count": annotations.len(),
+        "annotations": annotations,
+    }))
+}
+
+pub async fn extract_strings(
+    file: PathBuf,
+    format: String,
+    output: Option<String>,
+) -> Result<Value> {
+    if format != "json" && format != "csv" {
+        bail!("unsupported format: {}", format);
+    }
+    let file = StatelessRuntime.normalize_existing_file(&file)?;
+    let strings = tools::translation::extract_strings(&file)
+        .with_context(|| format!("unable to extract strings from '{}'", file.display()))?;
+
+    let rendered = if format == "csv" {
+        tools::translation::strings_to_csv(&strings)
+    } else {
+        let payload = serde_json::json!({
+            "file": file.display().to_string(),
+            "count": strings.len(),
+            "strings": strings,
+        });
+        match &output {
+            Some(_) => serde_json::to_string_pretty(&payload)? + "\n",
+            None => return Ok(payload),
+        }
+    };
+    match output.as_deref() {
+        Some(out_path) if out_path != "-" => {
+            std::fs::write(out_path, rendered)?;
+            Ok(serde_json::json!({
+                "status": "ok",
+                "path": out_path,
+                "count": strings.len(),
+            }))
+        }
+        _ => {
+            print!("{}", rendered);
+            std::process::exit(0);
+        }
+    }
+}
+
+pub async fn list_external_links(file: PathBuf) -> Result<Value> {
+    let file = StatelessRuntime.normalize_existing_file(&file)?;
+    let links = tools::external_links::read_external_links(&file)
+        .with_context(|| format!("unable to read external links of '{}'", file.display()))?;
+    let stale_count = links
+        .iter()
+        .filter(|link| link.status == ExternalLinkStatus::Stale)
+        .count();
+    Ok(serde_json::json!({
+        "file": file.display().to_string(),
+        "count": links.len(),
+        "stale_count": stale_count,
+        "links": links,
+    }))
+}
+
 #[allow(clippy::too_many_arguments)]
 pub async fn read_table(
     file: PathBuf,
     sheet: Option<String>,
     range: Option<String>,
     table_name: Option<String>,
+    scope: Option<NameScopeArg>,
     region_id: Option<u32>,
+    header_rows: Option<u32>,
+    no_header: bool,
+    orientation: Option<TableOrientationArg>,
     limit: Option<u32>,
     offset: Option<u32>,
     sample_mode: Option<TableSampleModeArg>,
     filters_json: Option<String>,
     filters_file: Option<PathBuf>,
     format: Option<TableReadFormat>,
+    output: Option<PathBuf>,
+    named_query: Option<NamedQuery>,
+    decimals: Option<u32>,
+    rounding: Option<RoundingModeArg>,
+    csv: CsvDialectArgs,
+    date_handling: Option<DateHandlingArg>,
 ) -> Result<Value> {
     validate_read_table_arguments(limit, offset, sample_mode)?;
+    validate_positive_limit(header_rows, "--header-rows")?;
+    let filters = parse_table_filters(filters_json, filters_file)?;
+    let csv_options = csv_options_from_args(csv)?;
+    let arrow_output = match (format, output) {
+        (Some(TableReadFormat::Arrow), Some(path)) => Some(path),
+        (Some(TableReadFormat::Arrow), None) => {
+            return Err(invalid_argument(
+                "--table-format arrow needs --output <PATH>",
+            ));
+        }
+        (_, Some(_)) => {
+            return Err(invalid_argument(
+                "--output is only used with --table-format arrow",
+            ));
+        }
+        (_, None) => None,
+    };
+
+    let runtime = StatelessRuntime;
+    let (state, workbook_id) = runtime.open_state_for_file(&file).await?;
+    let mut params = ReadTableParams {
+        workbook_or_fork_id: workbook_id.clone(),
+        sheet_name: sheet,
+        table_name,
+        region_id,
+        range,
+        header_row: None,
+        header_rows: if no_header { Some(0) } else { header_rows },
+        orientation: orientation.map(map_table_orientation),
+        columns: None,
+        filters,
+        sample_mode: sample_mode.map(map_table_sample_mode),
+        limit,
+        offset,
+        format: format.map(map_table_read_format),
+        include_headers: None,
+        include_types: None,
+        sort: None,
+        query: None,
+        decimals,
+        rounding: rounding.map(map_rounding_mode),
+        name_scope: scope.map(map_name_scope),
+        csv_options,
+        date_handling: date_handling.map(map_date_handling),
+        compression: None,
+        compress_min_bytes: None,
+    };
+    if let Some(named_query) = named_query {
+        named_query.apply_to(&mut params);
+        if no_header {
+            // --no-header overrides a stored header_row as well.
+            params.header_row = None;
+        }
+    }
+    if let Some(name) = params.sheet_name.take() {
+        params.sheet_name = Some(resolve_sheet_name(&state, &workbook_id, &name).await?);
+    }
+    let response = tools::read_table(state, params).await?;
+    if let Some(path) = arrow_output {
+        let columns = write_arrow_table(&path, &response.headers, &response.rows)?;
+        return Ok(serde_json::json!({
+            "status": "ok",
+            "path": path.display().to_string(),
+            "format": "arrow",
+            "workbook_id": response.workbook_id,
+            "sheet_name": response.sheet_name,
+            "table_name": response.table_name,
+            "warnings": response.warnings,
+            "row_count": response.rows.len(),
+            "columns": columns,
+            "total_rows": response.total_rows,
+            "next_offset": response.next_offset,
+        }));
+    }
+    Ok(serde_json::to_value(response)?)
+}
+
+/// Rows per `read_table` page when `range-export --format arrow` collects a
+/// whole range.
+const ARROW_EXPORT_PAGE_ROWS: u32 = 10_000;
+
+#[cfg(feature = "arrow")]
+fn write_arrow_table(
+    path: &Path,
+    headers: &[String],
+    rows: &[crate::model::TableRow],
+) -> Result<Vec<tools::arrow_ipc::ArrowColumn>> {
+    let (bytes, columns) = tools::arrow_ipc::table_to_ipc_file(headers, rows)?;
+    std::fs::write(path, bytes).with_context(|| format!("unable to write '{}'", path.display()))?;
+    Ok(columns)
+}
+
+#[cfg(not(feature = "arrow"))]
+fn write_arrow_table(
+    _path: &Path,
+    _headers: &[String],
+    _rows: &[crate::model::TableRow],
+) -> Result<Vec<Value>> {
+    Err(invalid_argument(
+        "arrow output needs a build with the `arrow` feature",
+    ))
+}
+
+/// Look up `--query` in `--queries-file`, or the workspace query file under
+/// `workspace` (default: current directory).
+pub fn load_named_query(
+    query: Option<String>,
+    queries_file: Option<PathBuf>,
+    workspace: Option<&Path>,
+) -> Result<Option<NamedQuery>> {
+    let Some(name) = query else {
+        return Ok(None);
+    };
+    let path = match queries_file {
+        Some(path) => path,
+        None => {
+            let root = workspace
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
+            workspace_queries_file(&root).ok_or_else(|| {
+                invalid_argument(format!(
+                    "--query '{name}' needs --queries-file or {} under '{}'",
+                    WORKSPACE_QUERY_FILES[0],
+                    root.display()
+                ))
+            })?
+        }
+    };
+    let mut queries = load_named_queries(&path)
+        .map_err(|err| invalid_argument(format!("--queries-file: {err:#}")))?;
+    match queries.remove(&name) {
+        Some(named_query) => Ok(Some(named_query)),
+        None => Err(invalid_argument(format!(
+            "unknown named query '{name}' in '{}' (defined: {})",
+            path.display(),
+            queries.keys().cloned().collect::<Vec<_>>().join(", ")
+        ))),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn estimate(
+    file: PathBuf,
+    sheet: Option<String>,
+    range: Option<String>,
+    table_name: Option<String>,
+    region_id: Option<u32>,
+    limit: Option<u32>,
+    filters_json: Option<String>,
+    filters_file: Option<PathBuf>,
+    format: Option<TableReadFormat>,
+) -> Result<Value> {
+    if matches!(limit, Some(0)) {
+        return Err(invalid_argument("--limit must be at least 1"));
+    }
+    if matches!(format, Some(TableReadFormat::Arrow)) {
+        return Err(invalid_argument(
+            "estimate sizes JSON/CSV payloads; --table-format arrow writes a file",
+        ));
+    }
     let filters = parse_table_filters(filters_json, filters_file)?;
 
     let runtime = StatelessRuntime;
@@ -333,37 +1085,38 @@ pub async fn read_table(
         Some(name) => Some(resolve_sheet_name(&state, &workbook_id, &name).await?),
         None => None,
     };
-    let response = tools::read_table(
+    let response = tools::estimate_read(
         state,
-        ReadTableParams {
+        EstimateReadParams {
             workbook_or_fork_id: workbook_id,
             sheet_name,
             table_name,
             region_id,
             range,
-            header_row: None,
-            header_rows: None,
             columns: None,
             filters,
-            sample_mode: sample_mode.map(map_table_sample_mode),
             limit,
-            offset,
             format: format.map(map_table_read_format),
-            include_headers: None,
-            include_types: None,
         },
     )
     .await?;
     Ok(serde_json::to_value(response)?)
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn find_value(
     file: PathBuf,
     query: String,
     sheet: Option<String>,
     mode: Option<FindValueMode>,
     label_direction: Option<LabelDirectionArg>,
+    regex: bool,
+    comparison: Option<ValueComparison>,
+    all_sheets: bool,
+    limit: Option<u32>,
+    offset: Option<u32>,
 ) -> Result<Value> {
+    validate_positive_limit(limit, "--limit")?;
     let runtime = StatelessRuntime;
     let (state, workbook_id) = runtime.open_state_for_file(&file).await?;
     let sheet_name = match sheet {
@@ -386,7 +1139,12 @@ pub async fn find_value(
             label,
             mode: mapped_mode,
             direction: label_direction.map(map_label_direction),
+            match_mode: regex.then_some(MatchMode::Regex),
             sheet_name,
+            group_by_sheet: all_sheets,
+            comparison,
+            limit: limit.unwrap_or(50),
+            offset,
             ..FindValueParams::default()
         },
     )
@@ -398,6 +1156,8 @@ pub async fn named_ranges(
     file: PathBuf,
     sheet: Option<String>,
     name_prefix: Option<String>,
+    scope: Option<NameScopeArg>,
+    evaluate: bool,
 ) -> Result<Value> {
     let runtime = StatelessRuntime;
     let (state, workbook_id) = runtime.open_state_for_file(&file).await?;
@@ -406,16 +1166,98 @@ pub async fn named_ranges(
         None => None,
     };
 
-    let response = tools::named_ranges(
+    let mut response = tools::named_ranges(
         state,
         NamedRangesParams {
             workbook_or_fork_id: workbook_id,
             sheet_name,
             name_prefix,
+            scope: scope.map(map_name_scope),
         },
     )
     .await?;
-    Ok(serde_json::to_value(response)?)
+    if !evaluate {
+        return Ok(serde_json::to_value(response)?);
+    }
+
+    let backend = evaluate_named_items(&file, &mut response.items).await?;
+    let mut payload = serde_json::to_value(response)?;
+    if let Some(backend) = backend {
+        payload["evaluated_with"] = Value::String(backend);
+    }
+    Ok(payload)
+}
+
+/// Scratch sheet holding one `=<name>` formula per evaluated name.
+const EVALUATE_NAMES_SHEET: &str = "_evaluate_names";
+
+/// Fill in the current value of formula and constant names. Each name is
+/// referenced from a scratch sheet on a temp copy, which is recalculated and
+/// read back, so the source workbook is never touched. Returns the recalc
+/// backend used, or `None` when no item needed evaluating.
+async fn evaluate_named_items(
+    source: &Path,
+    items: &mut [NamedRangeDescriptor],
+) -> Result<Option<String>> {
+    let targets: Vec<usize> = items
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| matches!(item.kind, NamedItemKind::Formula | NamedItemKind::Constant))
+        .map(|(idx, _)| idx)
+        .collect();
+    if targets.is_empty() {
+        return Ok(None);
+    }
+
+    let mut book = umya_spreadsheet::reader::xlsx::read(source).map_err(|error| {
+        anyhow!(
+            "failed to read workbook '{}' for evaluation: {}",
+            source.display(),
+            error
+        )
+    })?;
+    let scratch = book
+        .new_sheet(EVALUATE_NAMES_SHEET)
+        .map_err(|error| anyhow!("unable to add scratch sheet for evaluation: {error}"))?;
+    for (row, idx) in targets.iter().enumerate() {
+        let item = &items[*idx];
+        let reference = match (&item.scope_kind, &item.scope_sheet_name) {
+            (Some(NamedRangeScope::Sheet), Some(sheet)) => {
+                format!("'{}'!{}", sheet.replace('\'', "''"), item.name)
+            }
+            _ => item.name.clone(),
+        };
+        scratch
+            .get_cell_mut((1, row as u32 + 1))
+            .set_formula(reference);
+    }
+
+    let temp_file = tempfile::Builder::new()
+        .prefix(".evaluate-names-")
+        .suffix(".xlsx")
+        .tempfile_in(crate::scratch::temp_dir()?)
+        .map_err(|error| anyhow!("unable to create temp workbook copy: {error}"))?;
+    let temp_path = temp_file.path().to_path_buf();
+    save_workbook_preserving(&book, &temp_path, &PreservedPackage::capture(source))
+        .map_err(|error| anyhow!("unable to write temp workbook copy: {error:#}"))?;
+
+    let outcome = StatelessRuntime.recalculate_file(&temp_path).await?;
+    let book = umya_spreadsheet::reader::xlsx::read(&temp_path).map_err(|error| {
+        anyhow!(
+            "failed to read recalculated copy of '{}': {}",
+            source.display(),
+            error
+        )
+    })?;
+    let scratch = book
+        .get_sheet_by_name(EVALUATE_NAMES_SHEET)
+        .ok_or_else(|| anyhow!("scratch sheet missing after recalculation"))?;
+    for (row, idx) in targets.iter().enumerate() {
+        items[*idx].value = scratch
+            .get_cell((1, row as u32 + 1))
+            .and_then(|cell| cell_to_value_with_date_system(cell, date1904));
+    }
+    Ok(Some(outcome.backend))
 }
 
 pub async fn find_formula(
@@ -424,6 +1266,7 @@ pub async fn find_formula(
     sheet: Option<String>,
     limit: Option<u32>,
     offset: Option<u32>,
+    reference_style: ReferenceStyle,
 ) -> Result<Value> {
     validate_positive_limit(limit, "--limit")?;
 
@@ -446,6 +1289,7 @@ pub async fn find_formula(
             offset: offset.unwrap_or(0),
             context_rows: None,
             context_cols: None,
+            reference_style,
         },
     )
     .await?;
@@ -485,7 +1329,76 @@ pub async fn scan_volatiles(
     Ok(serde_json::to_value(response)?)
 }
 
-pub async fn sheet_statistics(file: PathBuf, sheet: String) -> Result<Value> {
+pub async fn scan_errors(
+    file: PathBuf,
+    sheet: Option<String>,
+    summary_only: bool,
+    limit: Option<u32>,
+    offset: Option<u32>,
+) -> Result<Value> {
+    validate_positive_limit(limit, "--limit")?;
+
+    let runtime = StatelessRuntime;
+    let (state, workbook_id) = runtime.open_state_for_file(&file).await?;
+    let sheet_name = match sheet {
+        Some(name) => Some(resolve_sheet_name(&state, &workbook_id, &name).await?),
+        None => None,
+    };
+
+    let response = tools::scan_errors(
+        state,
+        ScanErrorsParams {
+            workbook_or_fork_id: workbook_id,
+            sheet_name,
+            summary_only: Some(summary_only),
+            limit,
+            offset,
+        },
+    )
+    .await?;
+    Ok(serde_json::to_value(response)?)
+}
+
+pub async fn lint_formulas(
+    file: PathBuf,
+    sheet: Option<String>,
+    rules: Vec<FormulaLintRule>,
+    max_if_depth: Option<u32>,
+    summary_only: bool,
+    limit: Option<u32>,
+    offset: Option<u32>,
+) -> Result<Value> {
+    validate_positive_limit(limit, "--limit")?;
+
+    let runtime = StatelessRuntime;
+    let (state, workbook_id) = runtime.open_state_for_file(&file).await?;
+    let sheet_name = match sheet {
+        Some(name) => Some(resolve_sheet_name(&state, &workbook_id, &name).await?),
+        None => None,
+    };
+
+    let response = tools::lint_formulas(
+        state,
+        LintFormulasParams {
+            workbook_or_fork_id: workbook_id,
+            sheet_name,
+            rules,
+            max_if_depth,
+            summary_only: Some(summary_only),
+            limit,
+            offset,
+        },
+    )
+    .await?;
+    Ok(serde_json::to_value(response)?)
+}
+
+pub async fn sheet_statistics(
+    file: PathBuf,
+    sheet: String,
+    decimals: Option<u32>,
+    rounding: Option<RoundingModeArg>,
+) -> Result<Value> {
     let runtime = StatelessRuntime;
     let (state, workbook_id) = runtime.open_state_for_file(&file).await?;
     let sheet_name = resolve_sheet_name(&state, &workbook_id, &sheet).await?;
@@ -497,6 +1410,8 @@ pub async fn sheet_statistics(file: PathBuf, sheet: String) -> Result<Value> {
             sheet_name,
             sample_rows: None,
             summary_only: None,
+            decimals,
+            rounding: rounding.map(map_rounding_mode),
         },
     )
     .await?;
@@ -509,6 +1424,8 @@ pub async fn formula_map(
     limit: Option<u32>,
     sort_by: Option<FormulaSort>,
     formula_parse_policy: Option<FormulaParsePolicy>,
+    inconsistencies: bool,
+    reference_style: ReferenceStyle,
 ) -> Result<Value> {
     let runtime = StatelessRuntime;
     let (state, workbook_id) = runtime.open_state_for_file(&file).await?;
@@ -526,6 +1443,8 @@ pub async fn formula_map(
             include_addresses: None,
             addresses_limit: None,
             formula_parse_policy,
+            inconsistencies,
+            reference_style,
         },
     )
     .await?;
@@ -543,6 +1462,7 @@ pub async fn formula_trace(
     cursor_depth: Option<u32>,
     cursor_offset: Option<usize>,
     formula_parse_policy: Option<FormulaParsePolicy>,
+    reference_style: ReferenceStyle,
 ) -> Result<Value> {
     validate_formula_trace_arguments(depth, page_size)?;
     let cursor = build_trace_cursor(cursor_depth, cursor_offset)?;
@@ -562,13 +1482,21 @@ pub async fn formula_trace(
             page_size,
             cursor,
             formula_parse_policy,
+            reference_style,
         },
     )
     .await?;
     Ok(serde_json::to_value(response)?)
 }
 
-pub async fn table_profile(file: PathBuf, sheet: Option<String>) -> Result<Value> {
+pub async fn table_profile(
+    file: PathBuf,
+    sheet: Option<String>,
+    decimals: Option<u32>,
+    rounding: Option<RoundingModeArg>,
+    top_n: Option<u32>,
+    emit_schema: Option<SchemaFormatArg>,
+) -> Result<Value> {
     let runtime = StatelessRuntime;
     let (state, workbook_id) = runtime.open_state_for_file(&file).await?;
     let sheet_name = match sheet {
@@ -585,17 +1513,193 @@ pub async fn table_profile(file: PathBuf, sheet: Option<String>) -> Result<Value
             sample_mode: None,
             sample_size: None,
             summary_only: None,
+            decimals,
+            rounding: rounding.map(map_rounding_mode),
+            top_n,
+            emit_schema: emit_schema.map(map_schema_format),
+        },
+    )
+    .await?;
+    // With --emit-schema the schema is the whole output, ready to save.
+    match response.schema {
+        Some(schema) => Ok(schema),
+        None => Ok(serde_json::to_value(response)?),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn query_table(
+    file: PathBuf,
+    sheet: Option<String>,
+    range: Option<String>,
+    table_name: Option<String>,
+    region_id: Option<u32>,
+    header_rows: Option<u32>,
+    group_by: Option<Vec<String>>,
+    aggregations: Vec<String>,
+    filters_json: Option<String>,
+    filters_file: Option<PathBuf>,
+    limit: Option<u32>,
+    offset: Option<u32>,
+    decimals: Option<u32>,
+    rounding: Option<RoundingModeArg>,
+) -> Result<Value> {
+    validate_positive_limit(limit, "--limit")?;
+    validate_positive_limit(header_rows, "--header-rows")?;
+    let group_by: Vec<String> = group_by
+        .unwrap_or_default()
+        .into_iter()
+        .map(|column| column.trim().to_string())
+        .filter(|column| !column.is_empty())
+        .collect();
+    let aggregations = aggregations
+        .iter()
+        .map(|spec| parse_aggregation(spec))
+        .collect::<Result<Vec<_>>>()?;
+    if group_by.is_empty() && aggregations.is_empty() {
+        return Err(invalid_argument(
+            "query-table needs --group-by, at least one --agg, or both",
+        ));
+    }
+    let filters = parse_table_filters(filters_json, filters_file)?;
+
+    let runtime = StatelessRuntime;
+    let (state, workbook_id) = runtime.open_state_for_file(&file).await?;
+    let sheet_name = match sheet {
+        Some(name) => Some(resolve_sheet_name(&state, &workbook_id, &name).await?),
+        None => None,
+    };
+    let response = tools::query_table(
+        state,
+        QueryTableParams {
+            workbook_or_fork_id: workbook_id,
+            sheet_name,
+            table_name,
+            region_id,
+            range,
+            header_row: None,
+            header_rows,
+            group_by: Some(group_by),
+            aggregations,
+            filters,
+            limit,
+            offset,
+            decimals,
+            rounding: rounding.map(map_rounding_mode),
+            name_scope: None,
         },
     )
     .await?;
     Ok(serde_json::to_value(response)?)
 }
 
+/// Parse an `--agg` spec: `OP[:COLUMN][=ALIAS]`, e.g. `sum:Amount=total`.
+fn parse_aggregation(spec: &str) -> Result<TableAggregation> {
+    let (body, alias) = match spec.split_once('=') {
+        Some((body, alias)) => (body, Some(alias.trim().to_string())),
+        None => (spec, None),
+    };
+    let (op, column) = match body.split_once(':') {
+        Some((op, column)) => (op, Some(column.trim().to_string())),
+        None => (body, None),
+    };
+    let op: AggregateOp = serde_json::from_value(Value::String(op.trim().to_string()))
+        .map_err(|err| invalid_argument(format!("--agg '{spec}': {err}")))?;
+    if column.as_deref().is_some_and(str::is_empty) || alias.as_deref().is_some_and(str::is_empty) {
+        return Err(invalid_argument(format!(
+            "--agg '{spec}' must look like OP[:COLUMN][=ALIAS]"
+        )));
+    }
+    Ok(TableAggregation { op, column, alias })
+}
+
+/// One side of `map-columns`: a workbook and the table selector within it.
+pub struct MapColumnsTableArgs {
+    pub file: PathBuf,
+    pub sheet: Option<String>,
+    pub range: Option<String>,
+    pub table_name: Option<String>,
+    pub region_id: Option<u32>,
+}
+
+/// Rows read from each table when comparing value distributions.
+const MAP_COLUMNS_ROW_SAMPLE: u32 = 2_000;
+
+pub async fn map_columns(
+    source: MapColumnsTableArgs,
+    target: MapColumnsTableArgs,
+    min_score: f64,
+    output: Option<PathBuf>,
+) -> Result<Value> {
+    if !(0.0..=1.0).contains(&min_score) {
+        return Err(invalid_argument("--min-score must be between 0 and 1"));
+    }
+    let (source_sample, source_table) = read_column_map_table(source).await?;
+    let (target_sample, target_table) = read_column_map_table(target).await?;
+    let map = propose_column_map(
+        (&source_sample, source_table),
+        (&target_sample, target_table),
+        min_score,
+    );
+
+    let mut response = serde_json::to_value(&map)?;
+    if let Some(path) = output {
+        std::fs::write(&path, serde_json::to_string_pretty(&map)?)
+            .with_context(|| format!("unable to write mapping file '{}'", path.display()))?;
+        response["mapping_file"] = Value::String(path.display().to_string());
+    }
+    Ok(response)
+}
+
+async fn read_column_map_table(args: MapColumnsTableArgs) -> Result<(TableSample, ColumnMapTable)> {
+    let runtime = StatelessRuntime;
+    let (state, workbook_id) = runtime.open_state_for_file(&args.file).await?;
+    let sheet_name = match args.sheet {
+        Some(name) => Some(resolve_sheet_name(&state, &workbook_id, &name).await?),
+        None => None,
+    };
+    let response = tools::read_table(
+        state,
+        ReadTableParams {
+            workbook_or_fork_id: workbook_id,
+            sheet_name,
+            table_name: args.table_name,
+            region_id: args.region_id,
+            range: args.range,
+            limit: Some(MAP_COLUMNS_ROW_SAMPLE),
+            format: Some(TableOutputFormat::Values),
+            ..Default::default()
+        },
+    )
+    .await?;
+    if response.headers.is_empty() {
+        return Err(invalid_argument(format!(
+            "no table headers found in '{}' sheet '{}'",
+            args.file.display(),
+            response.sheet_name
+        )));
+    }
+
+    let table = ColumnMapTable {
+        file: args.file.display().to_string(),
+        sheet_name: response.sheet_name,
+        table_name: response.table_name,
+        headers: response.headers.clone(),
+    };
+    let sample = TableSample {
+        headers: response.headers,
+        rows: response.values.unwrap_or_default(),
+    };
+    Ok((sample, table))
+}
+
 fn map_table_read_format(format: TableReadFormat) -> TableOutputFormat {
     match format {
         TableReadFormat::Json => TableOutputFormat::Json,
         TableReadFormat::Values => TableOutputFormat::Values,
         TableReadFormat::Csv => TableOutputFormat::Csv,
+        // Arrow files are encoded from the typed json rows.
+        TableReadFormat::Arrow => TableOutputFormat::Json,
     }
 }
 
@@ -617,6 +1721,20 @@ fn map_sheet_page_format(format: SheetPageFormatArg) -> SheetPageFormat {
     }
 }
 
+fn map_sheet_page_orientation(orientation: SheetPageOrientationArg) -> SheetPageOrientation {
+    match orientation {
+        SheetPageOrientationArg::Rows => SheetPageOrientation::Rows,
+        SheetPageOrientationArg::Columns => SheetPageOrientation::Columns,
+    }
+}
+
+fn map_table_orientation(orientation: TableOrientationArg) -> TableOrientation {
+    match orientation {
+        TableOrientationArg::Rows => TableOrientation::Rows,
+        TableOrientationArg::Transposed => TableOrientation::Transposed,
+    }
+}
+
 fn map_table_sample_mode(mode: TableSampleModeArg) -> SampleMode {
     match mode {
         TableSampleModeArg::First => SampleMode::First,
@@ -625,6 +1743,112 @@ fn map_table_sample_mode(mode: TableSampleModeArg) -> SampleMode {
     }
 }
 
+fn map_schema_format(format: SchemaFormatArg) -> SchemaFormat {
+    match format {
+        SchemaFormatArg::Jsonschema => SchemaFormat::Jsonschema,
+        SchemaFormatArg::Arrow => SchemaFormat::Arrow,
+    }
+}
+
+fn map_rounding_mode(mode: RoundingModeArg) -> RoundingMode {
+    match mode {
+        RoundingModeArg::HalfUp => RoundingMode::HalfUp,
+        RoundingModeArg::HalfEven => RoundingMode::HalfEven,
+    }
+}
+
+fn map_header_match(mode: HeaderMatchArg) -> HeaderMatchMode {
+    match mode {
+        HeaderMatchArg::Exact => HeaderMatchMode::Exact,
+        HeaderMatchArg::Contains => HeaderMatchMode::Contains,
+        HeaderMatchArg::Fuzzy => HeaderMatchMode::Fuzzy,
+    }
+}
+
+fn map_date_handling(mode: DateHandlingArg) -> DateHandling {
+    match mode {
+        DateHandlingArg::Raw => DateHandling::Raw,
+        DateHandlingArg::Iso => DateHandling::Iso,
+        DateHandlingArg::Both => DateHandling::Both,
+    }
+}
+
+/// CSV dialect from the `--csv-*` flags; `None` when none were given so the
+/// tool defaults apply.
+fn csv_options_from_args(args: CsvDialectArgs) -> Result<Option<CsvOptions>> {
+    if args.delimiter.is_none()
+        && args.quote.is_none()
+        && !args.bom
+        && args.line_ending.is_none()
+        && args.decimal_separator.is_none()
+    {
+        return Ok(None);
+    }
+
+    let mut options = CsvOptions::default();
+    if let Some(delimiter) = args.delimiter {
+        let mut chars = delimiter.chars();
+        options.delimiter = match (chars.next(), chars.next()) {
+            _ if delimiter.eq_ignore_ascii_case("tab") => '\t',
+            (Some(ch), None) => ch,
+            _ => {
+                return Err(invalid_argument(format!(
+                    "--csv-delimiter must be one character or 'tab', got '{delimiter}'"
+                )));
+            }
+        };
+    }
+    if let Some(quote) = args.quote {
+        options.quoting = match quote {
+            CsvQuoteArg::Minimal => CsvQuoting::Minimal,
+            CsvQuoteArg::All => CsvQuoting::All,
+            CsvQuoteArg::NonNumeric => CsvQuoting::NonNumeric,
+            CsvQuoteArg::Never => CsvQuoting::Never,
+        };
+    }
+    options.bom = args.bom;
+    if let Some(line_ending) = args.line_ending {
+        options.line_ending = match line_ending {
+            CsvLineEndingArg::Lf => CsvLineEnding::Lf,
+            CsvLineEndingArg::Crlf => CsvLineEnding::Crlf,
+        };
+    }
+    if let Some(separator) = args.decimal_separator {
+        options.decimal_separator = separator;
+    }
+    options
+        .validate()
+        .map_err(|err| invalid_argument(format!("{err:#}")))?;
+    Ok(Some(options))
+}
+
+fn map_name_scope(scope: NameScopeArg) -> NamedRangeScope {
+    match scope {
+        NameScopeArg::Workbook => NamedRangeScope::Workbook,
+        NameScopeArg::Sheet => NamedRangeScope::Sheet,
+    }
+}
+
+pub fn find_value_comparison(
+    value_type: Option<FindValueTypeArg>,
+    op: Option<FindCompareOpArg>,
+    upper: Option<String>,
+) -> Option<ValueComparison> {
+    let (value_type, op) = value_type.zip(op)?;
+    Some(ValueComparison {
+        value_type: match value_type {
+            FindValueTypeArg::Number => ComparisonValueType::Number,
+            FindValueTypeArg::Date => ComparisonValueType::Date,
+        },
+        op: match op {
+            FindCompareOpArg::Gt => ComparisonOp::Gt,
+            FindCompareOpArg::Lt => ComparisonOp::Lt,
+            FindCompareOpArg::Between => ComparisonOp::Between,
+        },
+        upper,
+    })
+}
+
 fn map_find_value_mode(mode: FindValueMode) -> FindMode {
     match mode {
         FindValueMode::Value => FindMode::Value,
@@ -726,7 +1950,7 @@ fn validate_read_table_arguments(
     Ok(())
 }
 
-fn parse_table_filters(
+pub(crate) fn parse_table_filters(
     filters_json: Option<String>,
     filters_file: Option<PathBuf>,
 ) -> Result<Option<Vec<TableFilter>>> {
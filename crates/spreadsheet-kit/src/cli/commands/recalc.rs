@@ -1,11 +1,23 @@
+use crate::analysis::formula::{FormulaAtlas, formula_subgraph};
+use crate::cli::TraceDirectionArg;
+use crate::config::{ExecutorStrategy, RecalcBackendKind};
+use crate::core::types::RecalculateOutcome;
+use crate::diff::Change;
+use crate::diff::merge::CellDiff;
+use crate::hooks::changed_cell_manifest;
+use crate::model::TraceDirection;
 use crate::runtime::stateless::StatelessRuntime;
+use crate::webhook::{WebhookEvent, WebhookRecalcSummary, notify_webhook, webhook_configured};
 use anyhow::{Result, anyhow, bail};
 use serde::Serialize;
 use serde_json::Value;
 use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
-use tempfile::Builder;
+use tempfile::{Builder, NamedTempFile};
+
+/// Changed cells listed individually in a recalc diff; `change_count` stays exact.
+const RECALC_DIFF_LIMIT: usize = 2_000;
 
 #[derive(Debug, Serialize)]
 struct RecalculateResponse {
@@ -22,6 +34,258 @@ struct RecalculateResponse {
     changed: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     changed_cells_summary: Option<ChangedCellsSummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    partial: Option<PartialRecalcSummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    recalc_diff: Option<RecalcDiffReport>,
+}
+
+#[derive(Debug, Serialize)]
+struct RecalcDiffReport {
+    change_count: u64,
+    by_sheet: BTreeMap<String, u64>,
+    changes: Vec<RecalcValueChange>,
+    truncated: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct RecalcValueChange {
+    sheet: String,
+    address: String,
+    old_value: Option<String>,
+    new_value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    formula: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct PartialRecalcSummary {
+    cell: String,
+    scope: &'static str,
+    /// Formula cells recalculated and written back
+    formula_cells: u64,
+    /// References the dependency walk could not follow cell by cell
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    unresolved_references: Vec<String>,
+}
+
+/// Diff cached values between the pre-recalc snapshot and the recalculated
+/// workbook, keeping only cells whose value changed.
+fn build_recalc_diff(before: &Path, after: &Path, ignore: &[String]) -> Result<RecalcDiffReport> {
+    let changes = crate::core::diff::calculate_changeset(before, after, None)?;
+    let mut report = RecalcDiffReport {
+        change_count: 0,
+        by_sheet: BTreeMap::new(),
+        changes: Vec::new(),
+        truncated: false,
+    };
+
+    for change in changes {
+        let Change::Cell(cell) = change else {
+            continue;
+        };
+        if ignore.iter().any(|sheet| sheet == &cell.sheet) {
+            continue;
+        }
+        let CellDiff::Modified {
+            address,
+            old_value,
+            new_value,
+            new_formula,
+            ..
+        } = cell.diff
+        else {
+            continue;
+        };
+        if old_value == new_value {
+            continue;
+        }
+
+        report.change_count += 1;
+        *report.by_sheet.entry(cell.sheet.clone()).or_insert(0) += 1;
+        if report.changes.len() >= RECALC_DIFF_LIMIT {
+            report.truncated = true;
+            continue;
+        }
+        report.changes.push(RecalcValueChange {
+            sheet: cell.sheet,
+            address,
+            old_value,
+            new_value,
+            formula: new_formula,
+        });
+    }
+
+    Ok(report)
+}
+
+/// Copy `source` aside so an in-place recalc can be diffed afterwards.
+fn pre_recalc_snapshot(runtime: &StatelessRuntime, source: &Path) -> Result<NamedTempFile> {
+    let snapshot = Builder::new()
+        .prefix(".recalculate-snapshot-")
+        .suffix(".xlsx")
+        .tempfile_in(crate::scratch::temp_dir()?)
+        .map_err(|error| anyhow!("unable to create pre-recalc snapshot: {}", error))?;
+    runtime
+        .copy_file(source, snapshot.path())
+        .map_err(|error| {
+            anyhow!(
+                "unable to snapshot workbook '{}' before recalculation: {}",
+                source.display(),
+                error
+            )
+        })?;
+    Ok(snapshot)
+}
+
+/// The `--cell`/`--scope` pair selecting a dependency subtree to recalculate.
+struct PartialTarget {
+    sheet: String,
+    address: String,
+    scope: TraceDirection,
+}
+
+impl PartialTarget {
+    fn parse(
+        cell: Option<String>,
+        scope: Option<TraceDirectionArg>,
+        engine: Option<RecalcBackendKind>,
+    ) -> Result<Option<Self>> {
+        let (cell, scope) = match (cell, scope) {
+            (None, None) => return Ok(None),
+            (Some(cell), Some(scope)) => (cell, scope),
+            (Some(_), None) => bail!("invalid argument: --cell requires --scope"),
+            (None, Some(_)) => bail!("invalid argument: --scope requires --cell"),
+        };
+        if matches!(engine, Some(RecalcBackendKind::Libreoffice)) {
+            bail!(
+                "invalid argument: --cell/--scope partial recalculation is only supported by the builtin engine"
+            );
+        }
+        let Some((sheet, address)) = cell.trim().rsplit_once('!') else {
+            bail!(
+                "invalid argument: --cell '{}' must be SHEET!CELL (for example Sheet1!C2)",
+                cell
+            );
+        };
+        let sheet = sheet.trim_start_matches('\'').trim_end_matches('\'');
+        if sheet.is_empty() || address.is_empty() {
+            bail!(
+                "invalid argument: --cell '{}' must be SHEET!CELL (for example Sheet1!C2)",
+                cell
+            );
+        }
+        Ok(Some(Self {
+            sheet: sheet.to_string(),
+            address: address.to_ascii_uppercase(),
+            scope: match scope {
+                TraceDirectionArg::Precedents => TraceDirection::Precedents,
+                TraceDirectionArg::Dependents => TraceDirection::Dependents,
+            },
+        }))
+    }
+
+    fn scope_name(&self) -> &'static str {
+        match self.scope {
+            TraceDirection::Precedents => "precedents",
+            TraceDirection::Dependents => "dependents",
+        }
+    }
+}
+
+/// Engine, timeout, and soffice strategy for a full recalculation.
+struct RecalcRunOptions {
+    engine: Option<RecalcBackendKind>,
+    timeout_ms: Option<u64>,
+    strategy: ExecutorStrategy,
+}
+
+impl RecalcRunOptions {
+    fn parse(
+        engine: Option<RecalcBackendKind>,
+        timeout_ms: Option<u64>,
+        strategy: Option<ExecutorStrategy>,
+        partial: bool,
+    ) -> Result<Self> {
+        if timeout_ms == Some(0) {
+            bail!("invalid argument: --timeout-ms must be at least 1");
+        }
+        if timeout_ms.is_some() && partial {
+            bail!(
+                "invalid argument: --timeout-ms applies to full recalculation and cannot be combined with --cell/--scope"
+            );
+        }
+        if strategy.is_some() && !matches!(engine, Some(RecalcBackendKind::Libreoffice)) {
+            bail!("invalid argument: --strategy requires --engine libreoffice");
+        }
+        if strategy == Some(ExecutorStrategy::Pooled) {
+            bail!(
+                "invalid argument: --strategy pooled is not implemented yet; use fire-and-forget"
+            );
+        }
+        Ok(Self {
+            engine,
+            timeout_ms,
+            strategy: strategy.unwrap_or_default(),
+        })
+    }
+}
+
+/// Recalculate `path` in place, either fully or only the subtree selected by
+/// `partial`.
+async fn run_recalc(
+    runtime: &StatelessRuntime,
+    path: &Path,
+    options: &RecalcRunOptions,
+    partial: Option<&PartialTarget>,
+) -> Result<(RecalculateOutcome, Option<PartialRecalcSummary>)> {
+    let Some(target) = partial else {
+        let outcome = runtime
+            .recalculate_file_with_engine(
+                path,
+                options.engine,
+                options.timeout_ms,
+                options.strategy,
+            )
+            .await?;
+        return Ok((outcome, None));
+    };
+
+    let book = umya_spreadsheet::reader::xlsx::read(path).map_err(|e| {
+        anyhow!(
+            "failed to read workbook '{}' for dependency analysis: {}",
+            path.display(),
+            e
+        )
+    })?;
+    let subgraph = formula_subgraph(
+        &book,
+        &FormulaAtlas::default(),
+        &target.sheet,
+        &target.address,
+        &target.scope,
+    )
+    .map_err(|error| {
+        if error.to_string().starts_with("invalid cell address") {
+            anyhow!("invalid argument: --cell {}", error)
+        } else {
+            error
+        }
+    })?;
+
+    let formula_cells = subgraph.formula_cells.len() as u64;
+    let outcome = runtime
+        .recalculate_cells(path, subgraph.formula_cells)
+        .await?;
+    Ok((
+        outcome,
+        Some(PartialRecalcSummary {
+            cell: format!("{}!{}", target.sheet, target.address),
+            scope: target.scope_name(),
+            formula_cells,
+            unresolved_references: subgraph.unresolved,
+        }),
+    ))
 }
 
 #[derive(Debug, Serialize)]
@@ -113,21 +377,31 @@ fn build_changed_cells_summary(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn recalculate(
     file: PathBuf,
     output: Option<PathBuf>,
     force: bool,
     ignore_sheets: Option<Vec<String>>,
     changed_cells: bool,
+    engine: Option<RecalcBackendKind>,
+    cell: Option<String>,
+    scope: Option<TraceDirectionArg>,
+    diff: bool,
+    timeout_ms: Option<u64>,
+    strategy: Option<ExecutorStrategy>,
 ) -> Result<Value> {
     if force && output.is_none() {
         bail!("invalid argument: --force requires --output <PATH>");
     }
+    let partial = PartialTarget::parse(cell, scope, engine)?;
+    let options = RecalcRunOptions::parse(engine, timeout_ms, strategy, partial.is_some())?;
 
     let runtime = StatelessRuntime;
     let source = runtime.normalize_existing_file(&file)?;
 
     let ignore_list = ignore_sheets.clone().unwrap_or_default();
+    let notify = webhook_configured();
 
     match output {
         None => {
@@ -138,7 +412,24 @@ pub async fn recalculate(
                 None
             };
 
-            let outcome = runtime.recalculate_file(&source).await?;
+            // The webhook's changed-cell manifest needs the pre-recalc state too.
+            let diff_snapshot = if diff || notify {
+                Some(pre_recalc_snapshot(&runtime, &source)?)
+            } else {
+                None
+            };
+
+            let (outcome, partial) =
+                run_recalc(&runtime, &source, &options, partial.as_ref()).await?;
+
+            let recalc_diff = diff_snapshot
+                .as_ref()
+                .filter(|_| diff)
+                .map(|snapshot| build_recalc_diff(snapshot.path(), &source, &ignore_list))
+                .transpose()?;
+            if let Some(snapshot) = diff_snapshot.as_ref().filter(|_| notify) {
+                notify_recalc_webhook(snapshot.path(), &source, &source, &outcome).await?;
+            }
 
             let summary = if changed_cells {
                 let after_snapshot = snapshot_cell_values(&source, &ignore_list)?;
@@ -165,6 +456,8 @@ pub async fn recalculate(
                 target_path: None,
                 changed: None,
                 changed_cells_summary: summary,
+                partial,
+                recalc_diff,
             })?)
         }
         Some(output_path) => {
@@ -212,7 +505,15 @@ pub async fn recalculate(
                 None
             };
 
-            let outcome = runtime.recalculate_file(&temp_path).await?;
+            let (outcome, partial) =
+                run_recalc(&runtime, &temp_path, &options, partial.as_ref()).await?;
+
+            // The untouched source is the pre-recalc snapshot.
+            let recalc_diff = if diff {
+                Some(build_recalc_diff(&source, &temp_path, &ignore_list)?)
+            } else {
+                None
+            };
 
             // Snapshot after recalc (from the recalculated temp file).
             let summary = if changed_cells {
@@ -247,6 +548,9 @@ pub async fn recalculate(
                     error.error
                 )
             })?;
+            if notify {
+                notify_recalc_webhook(&source, &source, &target, &outcome).await?;
+            }
 
             Ok(serde_json::to_value(RecalculateResponse {
                 file: target.display().to_string(),
@@ -258,11 +562,39 @@ pub async fn recalculate(
                 target_path: Some(target.display().to_string()),
                 changed: Some(true),
                 changed_cells_summary: summary,
+                partial,
+                recalc_diff,
             })?)
         }
     }
 }
 
+/// Notify the webhook of a completed recalculation; `before` holds the
+/// pre-recalc workbook and `target` the recalculated one.
+async fn notify_recalc_webhook(
+    before: &Path,
+    source: &Path,
+    target: &Path,
+    outcome: &RecalculateOutcome,
+) -> Result<()> {
+    let failed = |error: anyhow::Error| {
+        anyhow!("write failed: workbook recalculated but webhook failed: {error:#}")
+    };
+    let changed_cells = changed_cell_manifest(before, target).map_err(failed)?;
+    let event = WebhookEvent::recalc_completed(
+        source.display().to_string(),
+        target.display().to_string(),
+        changed_cells,
+        WebhookRecalcSummary {
+            backend: outcome.backend.clone(),
+            duration_ms: outcome.duration_ms,
+            cells_evaluated: outcome.cells_evaluated,
+            eval_errors: outcome.eval_errors.clone(),
+        },
+    );
+    notify_webhook(&event).await.map_err(failed)
+}
+
 fn ensure_output_path_is_distinct(source: &Path, output: &Path) -> Result<()> {
     let source_identity = canonical_identity_path(source)?;
     let output_identity = canonical_identity_path(output)?;
@@ -0,0 +1,139 @@
+//! CLI commands for `asp revisions`: inspect the `<stem>.rev-<n>` history
+//! written by `--output auto`. Restoring lives with the other writes in
+//! [`super::write::restore_revision`].
+
+use super::diff::{DiffCommandArgs, diff};
+use crate::diff::compare::CompareOptions;
+use crate::revisions::{RevisionHistory, RevisionRecord};
+use crate::runtime::stateless::StatelessRuntime;
+use crate::utils::hash_file_sha256_hex;
+use anyhow::{Result, anyhow};
+use serde::Serialize;
+use serde_json::Value;
+use std::path::PathBuf;
+
+#[derive(Debug, Serialize)]
+struct RevisionEntry {
+    #[serde(flatten)]
+    record: RevisionRecord,
+    /// `ok`, `missing` (file deleted), or `modified` (file no longer matches
+    /// the recorded hash).
+    status: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+struct ListRevisionsResponse {
+    file: String,
+    manifest: String,
+    count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next_revision: Option<String>,
+    revisions: Vec<RevisionEntry>,
+}
+
+pub async fn list_revisions(file: PathBuf) -> Result<Value> {
+    let file = StatelessRuntime.normalize_existing_file(&file)?;
+    let (history, _) = RevisionHistory::of(&file)?;
+    let manifest = history.manifest()?;
+
+    let revisions = manifest
+        .revisions
+        .into_iter()
+        .map(|record| {
+            let path = history.revision_path(record.revision);
+            let status = if !path.is_file() {
+                "missing"
+            } else if hash_file_sha256_hex(&path)? == record.sha256 {
+                "ok"
+            } else {
+                "modified"
+            };
+            Ok(RevisionEntry { record, status })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let next_revision = history
+        .next_revision()
+        .ok()
+        .map(|revision| history.revision_path(revision).display().to_string());
+
+    Ok(serde_json::to_value(ListRevisionsResponse {
+        file: history.base_path().display().to_string(),
+        manifest: history.manifest_path().display().to_string(),
+        count: revisions.len(),
+        next_revision,
+        revisions,
+    })?)
+}
+
+pub struct DiffRevisionsArgs {
+    pub file: PathBuf,
+    pub from: Option<u32>,
+    pub to: Option<u32>,
+    pub details: bool,
+    pub limit: u32,
+    pub offset: u32,
+    pub exclude_recalc_result: bool,
+    pub compare: CompareOptions,
+    pub ignore_file: Option<PathBuf>,
+}
+
+/// Diff two revisions. `to` defaults to the newest recorded revision and
+/// `from` to the parent `to` was written from.
+pub async fn diff_revisions(args: DiffRevisionsArgs) -> Result<Value> {
+    let file = StatelessRuntime.normalize_existing_file(&args.file)?;
+    let (history, _) = RevisionHistory::of(&file)?;
+    let manifest = history.manifest()?;
+
+    let to = match args.to {
+        Some(to) => to,
+        None => manifest
+            .revisions
+            .last()
+            .map(|record| record.revision)
+            .ok_or_else(|| {
+                anyhow!(
+                    "invalid argument: no revisions recorded in '{}'; write one with --output auto or pass --to",
+                    history.manifest_path().display()
+                )
+            })?,
+    };
+    let from = args.from.unwrap_or_else(|| {
+        manifest
+            .revisions
+            .iter()
+            .find(|record| record.revision == to)
+            .map(|record| record.parent)
+            .unwrap_or(0)
+    });
+    if from == to {
+        return Err(anyhow!(
+            "invalid argument: --from and --to both name revision {to}"
+        ));
+    }
+
+    let original = history
+        .load(from, true)
+        .map_err(|error| anyhow!("invalid argument: {error:#}"))?;
+    let modified = history
+        .load(to, true)
+        .map_err(|error| anyhow!("invalid argument: {error:#}"))?;
+    let mut payload = diff(DiffCommandArgs {
+        original,
+        modified,
+        sheet: None,
+        sheets: None,
+        range: None,
+        details: args.details,
+        limit: args.limit,
+        offset: args.offset,
+        exclude_recalc_result: args.exclude_recalc_result,
+        compare: args.compare,
+        ignore_file: args.ignore_file,
+    })
+    .await?;
+    if let Value::Object(map) = &mut payload {
+        map.insert("from_revision".to_string(), Value::from(from));
+        map.insert("to_revision".to_string(), Value::from(to));
+    }
+    Ok(payload)
+}
@@ -0,0 +1,683 @@
+//! CLI command for `asp serve`: a long-running process that keeps parsed
+//! workbooks in memory between requests.
+//!
+//! On stdio it is an MCP server (newline-delimited JSON-RPC 2.0) whose tools
+//! are `open_workbook`, `close_workbook`, `workbook_revision`,
+//! `list_open_workbooks`, and the read tools of the MCP server under the same
+//! names, taking the `workbook_id` or `path` of an open workbook in place of
+//! `workbook_or_fork_id`.
+//!
+//! With `--http`, requests are JSON objects `{"id", "method", "params"}`
+//! posted to `POST /rpc`. The HTTP API has no authentication and `open` reads
+//! any path the process can, so it only binds loopback addresses. Methods:
+//!
+//! - `open` `{path}`: parse a workbook and keep it resident; reopening an
+//!   unchanged file is free, reopening a changed file reloads it
+//! - `revision` `{workbook_id | path}`: loaded vs on-disk revision
+//! - `call` `{workbook_id | path, tool, params}`: run a read tool against the
+//!   resident workbook
+//! - `close` `{workbook_id | path}` and `list`
+
+use crate::cli::errors::{ErrorEnvelope, envelope_for};
+use crate::model::WorkbookId;
+use crate::runtime::stateless::StatelessRuntime;
+use crate::state::AppState;
+use crate::tools;
+use crate::utils::hash_file_sha256_hex;
+use crate::workbook::WorkbookContext;
+use anyhow::{Context, Result, anyhow, bail};
+use axum::Router;
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::routing::post;
+use parking_lot::RwLock;
+use schemars::schema_for;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::io::{BufRead, Write};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[derive(Debug, Deserialize)]
+struct ServeRequest {
+    #[serde(default)]
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Debug, Serialize)]
+struct ServeResponse {
+    #[serde(skip_serializing_if = "Value::is_null")]
+    id: Value,
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<ErrorEnvelope>,
+}
+
+/// MCP protocol versions this server speaks, newest first.
+const MCP_PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
+const MCP_INSTRUCTIONS: &str = "Call open_workbook with a path to parse a workbook once and keep it in memory, then pass its workbook_id (or path) to the read tools. workbook_revision reports whether the file changed on disk; opening it again reloads it.";
+
+/// Read tools callable against a resident workbook, by their MCP names.
+const READ_TOOLS: &[&str] = &[
+    "describe_workbook",
+    "list_sheets",
+    "workbook_summary",
+    "sheet_overview",
+    "sheet_page",
+    "sheet_formula_map",
+    "formula_trace",
+    "named_ranges",
+    "sheet_statistics",
+    "find_formula",
+    "scan_volatiles",
+    "scan_errors",
+    "lint_formulas",
+    "workbook_style_summary",
+    "sheet_styles",
+    "range_values",
+    "inspect_cells",
+    "find_value",
+    "read_table",
+    "estimate_read",
+    "query_table",
+    "table_profile",
+    "grid_export",
+    "layout_page",
+];
+
+#[derive(Debug, Deserialize)]
+struct JsonRpcRequest {
+    /// Absent for notifications, which get no response.
+    #[serde(default)]
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonRpcResponse {
+    jsonrpc: &'static str,
+    id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<JsonRpcError>,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonRpcError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ToolCallParams {
+    name: String,
+    #[serde(default)]
+    arguments: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenParams {
+    path: PathBuf,
+}
+
+/// Identifies a resident workbook by the id `open` returned or by its path.
+#[derive(Debug, Deserialize)]
+struct WorkbookRef {
+    #[serde(default)]
+    workbook_id: Option<String>,
+    #[serde(default)]
+    path: Option<PathBuf>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CallParams {
+    #[serde(flatten)]
+    workbook: WorkbookRef,
+    tool: String,
+    #[serde(default)]
+    params: Value,
+}
+
+struct ResidentWorkbook {
+    path: PathBuf,
+    workbook_id: WorkbookId,
+    state: Arc<AppState>,
+    context: Arc<WorkbookContext>,
+    calls: AtomicU64,
+}
+
+impl ResidentWorkbook {
+    fn summary(&self) -> Value {
+        json!({
+            "workbook_id": self.workbook_id,
+            "path": self.path.display().to_string(),
+            "revision_id": self.context.revision_id,
+            "sheets": self.context.sheet_names(),
+            "calls": self.calls.load(Ordering::Relaxed),
+        })
+    }
+}
+
+#[derive(Default)]
+struct Server {
+    workbooks: RwLock<BTreeMap<String, Arc<ResidentWorkbook>>>,
+    requests: AtomicU64,
+}
+
+pub async fn serve(http: Option<SocketAddr>) -> Result<Value> {
+    if let Some(addr) = http
+        && !addr.ip().is_loopback()
+    {
+        bail!(
+            "invalid argument: --http {addr} is not a loopback address; serve has no authentication, so bind 127.0.0.1 or [::1]"
+        );
+    }
+    let server = Arc::new(Server::default());
+    match http {
+        Some(addr) => serve_http(server.clone(), addr).await?,
+        None => serve_stdio(&server).await?,
+    }
+    Ok(json!({
+        "requests": server.requests.load(Ordering::Relaxed),
+        "open_workbooks": server.workbooks.read().len(),
+    }))
+}
+
+async fn serve_stdio(server: &Server) -> Result<()> {
+    let stdin = std::io::stdin();
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+        let Some(response) = server.handle_mcp(line.as_bytes()).await else {
+            continue;
+        };
+        let mut stdout = std::io::stdout().lock();
+        serde_json::to_writer(&mut stdout, &response)?;
+        stdout.write_all(b"\n")?;
+        stdout.flush()?;
+    }
+}
+
+async fn serve_http(server: Arc<Server>, addr: SocketAddr) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("failed to bind {addr}"))?;
+    eprintln!(
+        "asp serve listening on http://{}/rpc",
+        listener.local_addr()?
+    );
+    let app = Router::new()
+        .route("/rpc", post(handle_http))
+        .with_state(server);
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
+    Ok(())
+}
+
+async fn handle_http(State(server): State<Arc<Server>>, body: Bytes) -> impl IntoResponse {
+    let response = server.handle(&body).await;
+    (
+        [(header::CONTENT_TYPE, "application/json")],
+        serde_json::to_string(&response).unwrap_or_default(),
+    )
+}
+
+impl Server {
+    async fn handle(&self, body: &[u8]) -> ServeResponse {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        let request = match serde_json::from_slice::<ServeRequest>(body) {
+            Ok(request) => request,
+            Err(error) => {
+                return ServeResponse::failure(
+                    Value::Null,
+                    anyhow!("invalid argument: request is not valid JSON: {error}"),
+                );
+            }
+        };
+        match self.dispatch(&request.method, request.params).await {
+            Ok(result) => ServeResponse {
+                id: request.id,
+                ok: true,
+                result: Some(result),
+                error: None,
+            },
+            Err(error) => ServeResponse::failure(request.id, error),
+        }
+    }
+
+    async fn dispatch(&self, method: &str, params: Value) -> Result<Value> {
+        match method {
+            "open" => self.open(parse_params(params)?).await,
+            "close" => self.close(&parse_params(params)?),
+            "revision" => self.revision(&parse_params(params)?),
+            "list" => Ok(self.list()),
+            "call" => self.call(parse_params(params)?).await,
+            other => bail!(
+                "invalid argument: unknown method '{other}' (expected open, close, revision, list, or call)"
+            ),
+        }
+    }
+
+    /// Answer one MCP message; notifications get `None`.
+    async fn handle_mcp(&self, body: &[u8]) -> Option<JsonRpcResponse> {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        let request = match serde_json::from_slice::<JsonRpcRequest>(body) {
+            Ok(request) => request,
+            Err(error) => {
+                return Some(JsonRpcResponse::error(
+                    Value::Null,
+                    -32700,
+                    format!("parse error: {error}"),
+                ));
+            }
+        };
+        let id = request.id?;
+        let result = match request.method.as_str() {
+            "initialize" => {
+                let requested = request.params["protocolVersion"].as_str();
+                let version = MCP_PROTOCOL_VERSIONS
+                    .iter()
+                    .find(|version| Some(**version) == requested)
+                    .unwrap_or(&MCP_PROTOCOL_VERSIONS[0]);
+                json!({
+                    "protocolVersion": version,
+                    "capabilities": { "tools": {} },
+                    "serverInfo": {
+                        "name": "asp-serve",
+                        "version": env!("CARGO_PKG_VERSION"),
+                    },
+                    "instructions": MCP_INSTRUCTIONS,
+                })
+            }
+            "ping" => json!({}),
+            "tools/list" => json!({ "tools": tool_definitions() }),
+            "tools/call" => {
+                let call = match parse_params::<ToolCallParams>(request.params) {
+                    Ok(call) => call,
+                    Err(error) => {
+                        return Some(JsonRpcResponse::error(id, -32602, format!("{error:#}")));
+                    }
+                };
+                match self.call_tool(&call.name, call.arguments).await {
+                    Some(Ok(result)) => json!({
+                        "content": [{ "type": "text", "text": result.to_string() }],
+                        "structuredContent": result,
+                        "isError": false,
+                    }),
+                    Some(Err(error)) => {
+                        let envelope = serde_json::to_string(&envelope_for(&error))
+                            .unwrap_or_else(|_| format!("{error:#}"));
+                        json!({
+                            "content": [{ "type": "text", "text": envelope }],
+                            "isError": true,
+                        })
+                    }
+                    None => {
+                        return Some(JsonRpcResponse::error(
+                            id,
+                            -32602,
+                            format!("unknown tool '{}'", call.name),
+                        ));
+                    }
+                }
+            }
+            other => {
+                return Some(JsonRpcResponse::error(
+                    id,
+                    -32601,
+                    format!("method not found: {other}"),
+                ));
+            }
+        };
+        Some(JsonRpcResponse {
+            jsonrpc: "2.0",
+            id,
+            result: Some(result),
+            error: None,
+        })
+    }
+
+    /// Run an MCP tool, or `None` when no tool has that name.
+    async fn call_tool(&self, name: &str, arguments: Value) -> Option<Result<Value>> {
+        let result = match name {
+            "open_workbook" => match parse_params(arguments) {
+                Ok(params) => self.open(params).await,
+                Err(error) => Err(error),
+            },
+            "close_workbook" => parse_params(arguments).and_then(|params| self.close(&params)),
+            "workbook_revision" => {
+                parse_params(arguments).and_then(|params| self.revision(&params))
+            }
+            "list_open_workbooks" => Ok(self.list()),
+            tool if READ_TOOLS.contains(&tool) => {
+                let mut params = match arguments {
+                    Value::Null => Map::new(),
+                    Value::Object(map) => map,
+                    _ => {
+                        return Some(Err(anyhow!(
+                            "invalid argument: tool arguments must be a JSON object"
+                        )));
+                    }
+                };
+                let workbook = WorkbookRef {
+                    workbook_id: params
+                        .remove("workbook_id")
+                        .and_then(|id| id.as_str().map(str::to_string)),
+                    path: params
+                        .remove("path")
+                        .and_then(|path| path.as_str().map(PathBuf::from)),
+                };
+                self.call(CallParams {
+                    workbook,
+                    tool: tool.to_string(),
+                    params: Value::Object(params),
+                })
+                .await
+            }
+            _ => return None,
+        };
+        Some(result)
+    }
+
+    fn list(&self) -> Value {
+        json!({
+            "workbooks": self
+                .workbooks
+                .read()
+                .values()
+                .map(|workbook| workbook.summary())
+                .collect::<Vec<_>>(),
+        })
+    }
+
+    async fn open(&self, params: OpenParams) -> Result<Value> {
+        let path = StatelessRuntime.normalize_existing_file(&params.path)?;
+        let existing = self.find_by_path(&path);
+        if let Some(existing) = &existing
+            && current_revision(&existing.path)? == existing.context.revision_id
+        {
+            let mut summary = existing.summary();
+            summary["reloaded"] = Value::Bool(false);
+            return Ok(summary);
+        }
+
+        let (state, workbook_id) = StatelessRuntime.open_state_for_file(&path).await?;
+        let context = state.open_workbook(&workbook_id).await?;
+        let resident = Arc::new(ResidentWorkbook {
+            path,
+            workbook_id: workbook_id.clone(),
+            state,
+            context,
+            calls: AtomicU64::new(0),
+        });
+        self.workbooks
+            .write()
+            .insert(workbook_id.as_str().to_string(), resident.clone());
+        let mut summary = resident.summary();
+        summary["reloaded"] = Value::Bool(existing.is_some());
+        Ok(summary)
+    }
+
+    fn close(&self, params: &WorkbookRef) -> Result<Value> {
+        let resident = self.resident(params)?;
+        self.workbooks.write().remove(resident.workbook_id.as_str());
+        Ok(json!({
+            "workbook_id": resident.workbook_id,
+            "closed": true,
+            "calls": resident.calls.load(Ordering::Relaxed),
+        }))
+    }
+
+    fn revision(&self, params: &WorkbookRef) -> Result<Value> {
+        let resident = self.resident(params)?;
+        let current = current_revision(&resident.path)?;
+        Ok(json!({
+            "workbook_id": resident.workbook_id,
+            "path": resident.path.display().to_string(),
+            "revision_id": resident.context.revision_id,
+            "current_revision_id": current,
+            "stale": current != resident.context.revision_id,
+        }))
+    }
+
+    async fn call(&self, params: CallParams) -> Result<Value> {
+        let resident = self.resident(&params.workbook)?;
+        let mut tool_params = match params.params {
+            Value::Null => Map::new(),
+            Value::Object(map) => map,
+            _ => bail!("invalid argument: call params must be a JSON object"),
+        };
+        tool_params.remove("workbook_or_fork_id");
+        tool_params.insert(
+            "workbook_id".to_string(),
+            Value::String(resident.workbook_id.as_str().to_string()),
+        );
+        resident.calls.fetch_add(1, Ordering::Relaxed);
+
+        let result = call_read_tool(
+            resident.state.clone(),
+            &params.tool,
+            Value::Object(tool_params),
+        )
+        .await?;
+        Ok(json!({
+            "workbook_id": resident.workbook_id,
+            "revision_id": resident.context.revision_id,
+            "tool": params.tool,
+            "result": result,
+        }))
+    }
+
+    fn resident(&self, reference: &WorkbookRef) -> Result<Arc<ResidentWorkbook>> {
+        let found = match (&reference.workbook_id, &reference.path) {
+            (Some(id), _) => self.workbooks.read().get(id).cloned(),
+            (None, Some(path)) => {
+                let path = StatelessRuntime.normalize_existing_file(path)?;
+                self.find_by_path(&path)
+            }
+            (None, None) => bail!("invalid argument: workbook_id or path is required"),
+        };
+        found.ok_or_else(|| anyhow!("invalid argument: workbook is not open; call `open` first"))
+    }
+
+    fn find_by_path(&self, path: &Path) -> Option<Arc<ResidentWorkbook>> {
+        self.workbooks
+            .read()
+            .values()
+            .find(|workbook| workbook.path == path)
+            .cloned()
+    }
+}
+
+impl JsonRpcResponse {
+    fn error(id: Value, code: i64, message: String) -> Self {
+        Self {
+            jsonrpc: "2.0",
+            id,
+            result: None,
+            error: Some(JsonRpcError { code, message }),
+        }
+    }
+}
+
+impl ServeResponse {
+    fn failure(id: Value, error: anyhow::Error) -> Self {
+        Self {
+            id,
+            ok: false,
+            result: None,
+            error: Some(envelope_for(&error)),
+        }
+    }
+}
+
+fn parse_params<T: DeserializeOwned>(params: Value) -> Result<T> {
+    let params = if params.is_null() { json!({}) } else { params };
+    serde_json::from_value(params).map_err(|error| anyhow!("invalid argument: {error}"))
+}
+
+fn current_revision(path: &Path) -> Result<String> {
+    hash_file_sha256_hex(path).with_context(|| format!("unable to hash workbook {:?}", path))
+}
+
+async fn run_tool<P, R, F, Fut>(state: Arc<AppState>, params: Value, tool: F) -> Result<Value>
+where
+    P: DeserializeOwned,
+    R: Serialize,
+    F: FnOnce(Arc<AppState>, P) -> Fut,
+    Fut: Future<Output = Result<R>>,
+{
+    let response = tool(state, parse_params(params)?).await?;
+    Ok(serde_json::to_value(response)?)
+}
+
+async fn call_read_tool(state: Arc<AppState>, tool: &str, params: Value) -> Result<Value> {
+    match tool {
+        "describe_workbook" => run_tool(state, params, tools::describe_workbook).await,
+        "list_sheets" => run_tool(state, params, tools::list_sheets).await,
+        "workbook_summary" => run_tool(state, params, tools::workbook_summary).await,
+        "sheet_overview" => run_tool(state, params, tools::sheet_overview).await,
+        "sheet_page" => run_tool(state, params, tools::sheet_page).await,
+        "sheet_formula_map" => run_tool(state, params, tools::sheet_formula_map).await,
+        "formula_trace" => run_tool(state, params, tools::formula_trace).await,
+        "named_ranges" => run_tool(state, params, tools::named_ranges).await,
+        "sheet_statistics" => run_tool(state, params, tools::sheet_statistics).await,
+        "find_formula" => run_tool(state, params, tools::find_formula).await,
+        "scan_volatiles" => run_tool(state, params, tools::scan_volatiles).await,
+        "scan_errors" => run_tool(state, params, tools::scan_errors).await,
+        "lint_formulas" => run_tool(state, params, tools::lint_formulas).await,
+        "workbook_style_summary" => run_tool(state, params, tools::workbook_style_summary).await,
+        "sheet_styles" => run_tool(state, params, tools::sheet_styles).await,
+        "range_values" => run_tool(state, params, tools::range_values).await,
+        "inspect_cells" => run_tool(state, params, tools::inspect_cells).await,
+        "find_value" => run_tool(state, params, tools::find_value).await,
+        "read_table" => run_tool(state, params, tools::read_table).await,
+        "estimate_read" => run_tool(state, params, tools::estimate_read).await,
+        "query_table" => run_tool(state, params, tools::query_table).await,
+        "table_profile" => run_tool(state, params, tools::table_profile).await,
+        "grid_export" => run_tool(state, params, tools::grid_export).await,
+        "layout_page" => run_tool(state, params, tools::layout_page).await,
+        other => bail!("invalid argument: unknown read tool '{other}'"),
+    }
+}
+
+/// MCP tool definitions: the resident-workbook tools, then the read tools.
+fn tool_definitions() -> Vec<Value> {
+    let workbook_ref = json!({
+        "type": "object",
+        "properties": {
+            "workbook_id": {
+                "type": "string",
+                "description": "workbook_id returned by open_workbook",
+            },
+            "path": { "type": "string", "description": "Path of an open workbook" },
+        },
+    });
+    let mut tools = vec![
+        json!({
+            "name": "open_workbook",
+            "description": "Parse a workbook and keep it in memory. Reopening an unchanged file is free; reopening a changed file reloads it.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Workbook path" },
+                },
+                "required": ["path"],
+            },
+        }),
+        json!({
+            "name": "close_workbook",
+            "description": "Drop a resident workbook.",
+            "inputSchema": workbook_ref,
+        }),
+        json!({
+            "name": "workbook_revision",
+            "description": "Compare the loaded revision of a resident workbook with the file on disk.",
+            "inputSchema": workbook_ref,
+        }),
+        json!({
+            "name": "list_open_workbooks",
+            "description": "List resident workbooks.",
+            "inputSchema": { "type": "object", "properties": {} },
+        }),
+    ];
+    for tool in READ_TOOLS {
+        let mut schema = read_tool_schema(tool);
+        if let Some(properties) = schema["properties"].as_object_mut() {
+            properties.remove("workbook_or_fork_id");
+            properties.insert(
+                "workbook_id".to_string(),
+                workbook_ref["properties"]["workbook_id"].clone(),
+            );
+            properties.insert(
+                "path".to_string(),
+                workbook_ref["properties"]["path"].clone(),
+            );
+        }
+        if let Some(required) = schema["required"].as_array_mut() {
+            required.retain(|name| name != "workbook_or_fork_id");
+        }
+        tools.push(json!({
+            "name": tool,
+            "description": format!("Run {tool} against a resident workbook, given its workbook_id or path."),
+            "inputSchema": schema,
+        }));
+    }
+    tools
+}
+
+fn read_tool_schema(tool: &str) -> Value {
+    let schema = match tool {
+        "describe_workbook" => schema_for!(tools::DescribeWorkbookParams),
+        "list_sheets" => schema_for!(tools::ListSheetsParams),
+        "workbook_summary" => schema_for!(tools::WorkbookSummaryParams),
+        "sheet_overview" => schema_for!(tools::SheetOverviewParams),
+        "sheet_page" => schema_for!(tools::SheetPageParams),
+        "sheet_formula_map" => schema_for!(tools::SheetFormulaMapParams),
+        "formula_trace" => schema_for!(tools::FormulaTraceParams),
+        "named_ranges" => schema_for!(tools::NamedRangesParams),
+        "sheet_statistics" => schema_for!(tools::SheetStatisticsParams),
+        "find_formula" => schema_for!(tools::FindFormulaParams),
+        "scan_volatiles" => schema_for!(tools::ScanVolatilesParams),
+        "scan_errors" => schema_for!(tools::ScanErrorsParams),
+        "lint_formulas" => schema_for!(tools::LintFormulasParams),
+        "workbook_style_summary" => schema_for!(tools::WorkbookStyleSummaryParams),
+        "sheet_styles" => schema_for!(tools::SheetStylesParams),
+        "range_values" => schema_for!(tools::RangeValuesParams),
+        "inspect_cells" => schema_for!(tools::InspectCellsParams),
+        "find_value" => schema_for!(tools::FindValueParams),
+        "read_table" => schema_for!(tools::ReadTableParams),
+        "estimate_read" => schema_for!(tools::EstimateReadParams),
+        "query_table" => schema_for!(tools::QueryTableParams),
+        "table_profile" => schema_for!(tools::TableProfileParams),
+        "grid_export" => schema_for!(tools::GridExportParams),
+        "layout_page" => schema_for!(tools::LayoutPageParams),
+        other => unreachable!("{other} is not in READ_TOOLS"),
+    };
+    serde_json::to_value(schema).unwrap_or_else(|_| json!({ "type": "object" }))
+}
@@ -844,7 +844,9 @@ fn compute_staging_impact(handle: &SessionHandle, kind: &OpKind, payload: &Value
                 };
 
             let wb_bytes = handle.materialize()?;
-            let mut tmp = tempfile::Builder::new().suffix(".xlsx").tempfile()?;
+            let mut tmp = tempfile::Builder::new()
+                .suffix(".xlsx")
+                .tempfile_in(crate::scratch::temp_dir()?)?;
             std::io::Write::write_all(&mut tmp, &wb_bytes)?;
 
             let (report, _) =
@@ -1,9 +1,45 @@
+use crate::cli::LintFormatArg;
+use crate::expectations::{
+    ExpectationReport, TESTS_SHEET_NAME, evaluate_expectations, load_expectations_file,
+    read_expectations_sheet,
+};
+use crate::lint::{
+    LintFailOn, LintProfile, LintReport, LintRuleRegistry, lint_report_to_sarif,
+    lint_workbook_with_rules, load_lint_profile,
+};
+use crate::lint_plugins::register_rule_plugins;
 use crate::runtime::stateless::StatelessRuntime;
+use crate::tools::workbook_props::read_workbook_props;
 use crate::tools::{self, NamedRangesParams};
 use crate::verification::{VerifyOptions, compare_workbooks};
-use anyhow::Result;
+use crate::workbook::with_date_system;
+use anyhow::{Result, anyhow, bail};
+use serde::Serialize;
 use serde_json::Value;
 use std::path::PathBuf;
+use tempfile::Builder;
+
+#[derive(Debug, Serialize)]
+struct RunTestsResponse {
+    file: String,
+    all_passed: bool,
+    recalculated: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    backend: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    eval_errors: Option<Vec<String>>,
+    #[serde(flatten)]
+    report: ExpectationReport,
+}
+
+#[derive(Debug, Serialize)]
+struct LintResponse {
+    file: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    profile: Option<String>,
+    #[serde(flatten)]
+    report: LintReport,
+}
 
 pub async fn verify(
     baseline: PathBuf,
@@ -41,6 +77,7 @@ pub async fn verify(
                     workbook_or_fork_id: baseline_id.clone(),
                     sheet_name: sheet_name.clone(),
                     name_prefix: None,
+                    scope: None,
                 },
             )
             .await?,
@@ -56,6 +93,7 @@ pub async fn verify(
                     workbook_or_fork_id: current_id.clone(),
                     sheet_name,
                     name_prefix: None,
+                    scope: None,
                 },
             )
             .await?,
@@ -76,3 +114,118 @@ pub async fn verify(
 
     Ok(serde_json::to_value(response)?)
 }
+
+pub async fn run_tests(file: PathBuf, tests: Option<PathBuf>, no_recalc: bool) -> Result<Value> {
+    let runtime = StatelessRuntime;
+    let source = runtime.normalize_existing_file(&file)?;
+    let sidecar = match tests {
+        Some(path) => Some((
+            load_expectations_file(&path).map_err(|err| anyhow!("invalid argument: {err:#}"))?,
+            path.display().to_string(),
+        )),
+        None => None,
+    };
+
+    // Recalculate a scratch copy so the source workbook is never touched.
+    let temp_file = Builder::new()
+        .prefix(".run-tests-")
+        .suffix(".xlsx")
+        .tempfile_in(crate::scratch::temp_dir()?)
+        .map_err(|error| anyhow!("unable to create temp workbook copy: {error}"))?;
+    let temp_path = temp_file.path().to_path_buf();
+    runtime.copy_file(&source, &temp_path).map_err(|error| {
+        anyhow!(
+            "unable to copy workbook '{}' for testing: {}",
+            source.display(),
+            error
+        )
+    })?;
+
+    let (recalculated, backend, eval_errors) = if no_recalc {
+        (false, None, None)
+    } else {
+        let outcome = runtime.recalculate_file(&temp_path).await?;
+        (true, Some(outcome.backend), outcome.eval_errors)
+    };
+
+    let book = umya_spreadsheet::reader::xlsx::read(&temp_path).map_err(|error| {
+        anyhow!(
+            "failed to read workbook '{}' for testing: {}",
+            source.display(),
+            error
+        )
+    })?;
+
+    // Recalculation rewrites the scratch copy, so take the date system from the source.
+    let date1904 = read_workbook_props(&source)
+        .map(|props| props.date1904)
+        .unwrap_or(false);
+    let (tests, test_source) = match sidecar {
+        Some(sidecar) => sidecar,
+        None => match with_date_system(date1904, || read_expectations_sheet(&book))
+            .map_err(|err| anyhow!("invalid argument: {err:#}"))?
+        {
+            Some(tests) => (tests, TESTS_SHEET_NAME.to_string()),
+            None => bail!(
+                "invalid argument: workbook has no '{}' sheet; add one or pass --tests <PATH>",
+                TESTS_SHEET_NAME
+            ),
+        },
+    };
+
+    let report = with_date_system(date1904, || {
+        evaluate_expectations(&book, &tests, test_source)
+    });
+    Ok(serde_json::to_value(RunTestsResponse {
+        file: source.display().to_string(),
+        all_passed: report.all_passed(),
+        recalculated,
+        backend,
+        eval_errors,
+        report,
+    })?)
+}
+
+pub async fn lint(
+    file: PathBuf,
+    profile: Option<PathBuf>,
+    rules_dir: Option<PathBuf>,
+    fail_on: Option<LintFailOn>,
+    sheet: Option<String>,
+    format: LintFormatArg,
+) -> Result<Value> {
+    let runtime = StatelessRuntime;
+    let source = runtime.normalize_existing_file(&file)?;
+    let lint_profile = match &profile {
+        Some(path) => {
+            load_lint_profile(path).map_err(|err| anyhow!("invalid argument: {err:#}"))?
+        }
+        None => LintProfile::default(),
+    };
+    let fail_on = fail_on.or(lint_profile.fail_on).unwrap_or_default();
+
+    let book = umya_spreadsheet::reader::xlsx::read(&source).map_err(|error| {
+        anyhow!(
+            "failed to read workbook '{}' for linting: {}",
+            source.display(),
+            error
+        )
+    })?;
+    let mut registry = LintRuleRegistry::default();
+    if let Some(dir) = &rules_dir {
+        register_rule_plugins(&mut registry, dir)
+            .map_err(|err| anyhow!("invalid argument: {err:#}"))?;
+    }
+    let report =
+        lint_workbook_with_rules(&book, &lint_profile, fail_on, sheet.as_deref(), &registry)
+            .map_err(|err| anyhow!("invalid argument: {err:#}"))?;
+    if matches!(format, LintFormatArg::Sarif) {
+        return Ok(lint_report_to_sarif(&report, &registry, &source));
+    }
+
+    Ok(serde_json::to_value(LintResponse {
+        file: source.display().to_string(),
+        profile: profile.map(|path| path.display().to_string()),
+        report,
+    })?)
+}
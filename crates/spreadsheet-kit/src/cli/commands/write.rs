@@ -1,32 +1,79 @@
-use crate::cli::{AppendRegionFooterPolicyArg, CloneMergePolicyArg, ClonePatchTargetsArg};
+use crate::audit::{WriteAuditRecord, append_audit_record};
+use crate::cli::{
+    AppendRegionFooterPolicyArg, CloneMergePolicyArg, ClonePatchTargetsArg, RefRepairConfidenceArg,
+};
+use crate::column_map::ColumnMap;
 use crate::config::{OutputProfile, RecalcBackendKind, ServerConfig, TransportKind};
 use crate::core::types::CellEdit;
+use crate::expectations::{
+    Expectation, ExpectationReport, evaluate_expectations, load_expectations_file,
+};
 use crate::formula::pattern::{RelativeMode, parse_base_formula, shift_formula_ast};
+use crate::hooks::{
+    WriteHookEvent, WriteHookPhase, changed_cell_manifest, run_write_hooks, write_hooks_configured,
+};
+use crate::materialize::{
+    MaterializeDefinition, MaterializeRecord, MaterializedRange, read_materialize_sheet,
+    record_materialization, write_materialized_table,
+};
+use crate::metadata::{self, MetadataEntry};
 use crate::model::{
     CommandClass, FORMULA_PARSE_FAILED_PREFIX, FormulaParseDiagnostics,
     FormulaParseDiagnosticsBuilder, FormulaParsePolicy, GridPayload, NamedItemKind, Warning,
     validate_formula,
 };
+use crate::revisions::{
+    AUTO_OUTPUT, RevisionHistory, RevisionRecord, next_revision_path, record_revision,
+};
 use crate::runtime::stateless::StatelessRuntime;
+use crate::snapshots::{
+    SnapshotCopy, SnapshotRecord, SnapshotStore, list_snapshots as list_snapshot_records,
+    load_snapshot, take_snapshot,
+};
 use crate::state::AppState;
+use crate::tools::converge::{ConvergencePlan, DesiredStateSpec, PlannedChange, plan_convergence};
+use crate::tools::copy_sheet::resolve_copy_sheet_sources;
+use crate::tools::doc_properties::{PropertiesApplyResult, PropertyOp, apply_property_ops};
 use crate::tools::filters::WorkbookFilter;
 use crate::tools::fork::{
-    ApplyFormulaPatternOpInput, ColumnSizeOp, ColumnSizeOpInput, CreateForkParams,
-    GridImportParams, MatrixCell, SaveForkParams, StructureBatchParamsInput, StructureOp,
-    StructureOpInput, StyleBatchParamsInput, StyleOp, StyleOpInput, TransformOp, TransformTarget,
-    apply_column_size_ops_to_file, apply_formula_pattern_ops_to_file, apply_structure_ops_to_file,
-    apply_style_ops_to_file, apply_transform_ops_to_file, create_fork, grid_import,
-    normalize_column_size_payload, normalize_structure_batch, normalize_style_batch,
+    ApplyFormulaPatternOpInput, ColumnSizeApplyResult, ColumnSizeOp, ColumnSizeOpInput,
+    CreateForkParams, FormulaPatternBatchApplyResult, GridImportParams, MatrixCell,
+    RowSizeApplyResult, RowSizeOp, RowSizeOpInput, SaveForkParams, StructureApplyResult,
+    StructureBatchParamsInput, StructureOp, StructureOpInput, StyleApplyResult,
+    StyleBatchParamsInput, StyleOp, StyleOpInput, TransformApplyResult, TransformOp,
+    TransformTarget, apply_column_size_ops_to_file, apply_formula_pattern_ops_to_file,
+    apply_row_size_ops_to_file, apply_structure_ops_to_file, apply_style_ops_to_file,
+    apply_transform_ops_to_file, create_fork, grid_import, normalize_column_size_payload,
+    normalize_row_size_payload, normalize_structure_batch, normalize_style_batch,
     resolve_style_ops_for_workbook, resolve_transform_ops_for_workbook, save_fork,
 };
-use crate::tools::rules_batch::{RulesOp, apply_rules_ops_to_file};
-use crate::tools::sheet_layout::{SheetLayoutOp, apply_sheet_layout_ops_to_file};
-use crate::workbook::WorkbookContext;
+use crate::tools::import_rows::{load_import_row_files, parse_csv_records};
+use crate::tools::instantiate::{InstantiateSummary, instantiate_workbook, parse_template_values};
+use crate::tools::named_query::NamedQuery;
+use crate::tools::ref_repair::{RefRepairConfidence, RefRepairResult, repair_refs_in_file};
+use crate::tools::rules_batch::{RulesApplyResult, RulesOp, apply_rules_ops_to_file};
+use crate::tools::sheet_layout::{
+    SheetLayoutApplyResult, SheetLayoutOp, apply_sheet_layout_ops_to_file,
+};
+use crate::tools::translation::{
+    ApplyStringsSummary, apply_strings as apply_translations, parse_translation_table,
+};
+use crate::tools::value_locale::ValueLocale;
+use crate::tools::workbook_props::read_workbook_props;
+use crate::tools::write_cells::{
+    CellBlocksPayload, WriteCellsSummary, validate_cell_blocks, write_cells_to_path,
+};
+use crate::tools::{ReadTableParams, read_table};
+use crate::utils::hash_file_sha256_hex;
+use crate::webhook::{WebhookEvent, notify_webhook, webhook_configured};
+use crate::workbook::{WorkbookContext, with_date_system};
+use crate::write::save_workbook;
 use anyhow::{Context, Result, anyhow, bail};
 use regex::Regex;
 use schemars::{JsonSchema, schema_for};
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use serde_json::Value;
+use std::cell::OnceCell;
 use std::collections::{BTreeMap, BTreeSet};
 use std::fs::{self, OpenOptions};
 use std::io::ErrorKind;
@@ -42,6 +89,51 @@ struct CopyResponse {
     bytes_copied: u64,
 }
 
+#[derive(Debug, Serialize)]
+struct InstantiateResponse {
+    mode: String,
+    template_path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    target_path: Option<String>,
+    summary: InstantiateSummary,
+}
+
+#[derive(Debug, Serialize)]
+struct ApplyStringsResponse {
+    mode: String,
+    source_path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    target_path: Option<String>,
+    summary: ApplyStringsSummary,
+}
+
+#[derive(Debug, Serialize)]
+struct RestoreResponse {
+    file: String,
+    restored: SnapshotRecord,
+    /// Snapshot of the workbook as it was just before the restore, so the
+    /// restore itself can be undone.
+    snapshot: Option<SnapshotRecord>,
+}
+
+#[derive(Debug, Serialize)]
+struct RestoreRevisionResponse {
+    file: String,
+    restored_revision: u32,
+    /// Revision holding the workbook as it was just before the restore, so
+    /// the restore itself can be undone.
+    backup: RevisionRecord,
+}
+
+#[derive(Debug, Serialize)]
+struct ListSnapshotsResponse {
+    snapshot_dir: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<String>,
+    count: usize,
+    snapshots: Vec<SnapshotRecord>,
+}
+
 #[derive(Debug, Serialize)]
 struct CreateWorkbookResponse {
     path: String,
@@ -120,7 +212,23 @@ enum ColumnSizeOpWithSheetInput {
     },
 }
 
-impl ColumnSizeOpWithSheetInput {
+/// Per-op entry of a sheet-scoped batch payload (`column-size`, `row-size`).
+trait SheetScopedOpInput: DeserializeOwned {
+    type Op: DeserializeOwned;
+
+    fn sheet_name(&self) -> &str;
+    fn into_op_input(self) -> Self::Op;
+}
+
+#[derive(Debug, Deserialize)]
+struct SheetScopedOpsPayload<T> {
+    sheet_name: String,
+    ops: Vec<T>,
+}
+
+impl SheetScopedOpInput for ColumnSizeOpWithSheetInput {
+    type Op = ColumnSizeOpInput;
+
     fn sheet_name(&self) -> &str {
         match self {
             Self::Canonical { sheet_name, .. } | Self::Shorthand { sheet_name, .. } => sheet_name,
@@ -137,6 +245,46 @@ impl ColumnSizeOpWithSheetInput {
     }
 }
 
+#[derive(Debug, Deserialize, JsonSchema)]
+struct RowSizeOpsPayload {
+    sheet_name: String,
+    ops: Vec<RowSizeOpInput>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum RowSizeOpWithSheetInput {
+    Canonical {
+        sheet_name: String,
+        target: crate::tools::fork::RowTarget,
+        size: crate::tools::fork::RowSizeSpec,
+    },
+    Shorthand {
+        sheet_name: String,
+        range: String,
+        size: crate::tools::fork::RowSizeSpec,
+    },
+}
+
+impl SheetScopedOpInput for RowSizeOpWithSheetInput {
+    type Op = RowSizeOpInput;
+
+    fn sheet_name(&self) -> &str {
+        match self {
+            Self::Canonical { sheet_name, .. } | Self::Shorthand { sheet_name, .. } => sheet_name,
+        }
+    }
+
+    fn into_op_input(self) -> RowSizeOpInput {
+        match self {
+            Self::Canonical { target, size, .. } => {
+                RowSizeOpInput::Canonical(RowSizeOp { target, size })
+            }
+            Self::Shorthand { range, size, .. } => RowSizeOpInput::Shorthand { range, size },
+        }
+    }
+}
+
 const TRANSFORM_PAYLOAD_SHAPE: &str = r#"{"ops":[{"kind":"<transform_kind>",...}]}"#;
 const TRANSFORM_PAYLOAD_MINIMAL_EXAMPLE: &str = r#"{"ops":[{"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"range","range":"A1:A1"},"value":"1"}]}"#;
 const STYLE_PAYLOAD_SHAPE: &str =
@@ -155,11 +303,20 @@ const COLUMN_SIZE_PAYLOAD_MINIMAL_EXAMPLE: &str =
     r#"{"sheet_name":"Sheet1","ops":[{"range":"A:A","size":{"kind":"width","width_chars":12.0}}]}"#;
 const COLUMN_SIZE_PAYLOAD_ALTERNATE_EXAMPLE: &str =
     r#"{"ops":[{"sheet_name":"Sheet1","range":"A:A","size":{"kind":"width","width_chars":12.0}}]}"#;
+const ROW_SIZE_PAYLOAD_SHAPE: &str = r#"{"sheet_name":"...","ops":[{"range":"2:10","size":{"kind":"height","height_points":30.0}}]}"#;
+const ROW_SIZE_PAYLOAD_ALTERNATE_SHAPE: &str = r#"{"ops":[{"sheet_name":"...","range":"2:10","size":{"kind":"height","height_points":30.0}}]}"#;
+const ROW_SIZE_PAYLOAD_MINIMAL_EXAMPLE: &str =
+    r#"{"sheet_name":"Sheet1","ops":[{"range":"2:4","size":{"kind":"auto"}}]}"#;
+const ROW_SIZE_PAYLOAD_ALTERNATE_EXAMPLE: &str =
+    r#"{"ops":[{"sheet_name":"Sheet1","range":"2:4","size":{"kind":"auto"}}]}"#;
 const SHEET_LAYOUT_PAYLOAD_SHAPE: &str = r#"{"ops":[{"kind":"<layout_kind>",...}]}"#;
 const SHEET_LAYOUT_PAYLOAD_MINIMAL_EXAMPLE: &str =
     r#"{"ops":[{"kind":"freeze_panes","sheet_name":"Sheet1","freeze_rows":1,"freeze_cols":1}]}"#;
 const RULES_PAYLOAD_SHAPE: &str = r#"{"ops":[{"kind":"<rules_kind>",...}]}"#;
 const RULES_PAYLOAD_MINIMAL_EXAMPLE: &str = r#"{"ops":[{"kind":"set_data_validation","sheet_name":"Sheet1","target_range":"B2:B4","validation":{"kind":"list","formula1":"\"A,B,C\""}}]}"#;
+const PROPERTIES_PAYLOAD_SHAPE: &str = r#"{"ops":[{"kind":"<property_kind>",...}]}"#;
+const PROPERTIES_PAYLOAD_MINIMAL_EXAMPLE: &str =
+    r#"{"ops":[{"kind":"set_custom","name":"RunId","value":"run-2024-09-30-01"}]}"#;
 const EDIT_FORMULA_HINT: &str =
     "Tip: formulas in edit shorthand use double equals, e.g. A1==SUM(B1:B5).";
 const SHELL_QUOTING_HINT: &str = "Hint: if this edit was passed as a shell argument, check quoting: double quotes let the shell expand $-style absolute references (\"$A$1\" reaches asp as \"1\"), and unquoted parentheses break the shell. Single-quote each edit, or use --edits-file (one edit per line, '-' for stdin) to bypass shell quoting.";
@@ -204,6 +361,20 @@ enum ColumnSizeOpsSchemaPayload {
     PerOp(ColumnSizeOpsPerOpPayload),
 }
 
+#[allow(dead_code)]
+#[derive(Debug, JsonSchema)]
+struct RowSizeOpsPerOpPayload {
+    ops: Vec<RowSizeOpWithSheetInput>,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, JsonSchema)]
+#[serde(untagged)]
+enum RowSizeOpsSchemaPayload {
+    Canonical(RowSizeOpsPayload),
+    PerOp(RowSizeOpsPerOpPayload),
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum BatchSchemaCommand {
     Transform,
@@ -211,8 +382,10 @@ pub enum BatchSchemaCommand {
     ApplyFormulaPattern,
     Structure,
     ColumnSize,
+    RowSize,
     SheetLayout,
     Rules,
+    Properties,
 }
 
 pub fn batch_payload_schema(command: BatchSchemaCommand) -> Result<Value> {
@@ -230,10 +403,14 @@ pub fn batch_payload_schema(command: BatchSchemaCommand) -> Result<Value> {
         BatchSchemaCommand::ColumnSize => {
             serde_json::to_value(schema_for!(ColumnSizeOpsSchemaPayload))?
         }
+        BatchSchemaCommand::RowSize => serde_json::to_value(schema_for!(RowSizeOpsSchemaPayload))?,
         BatchSchemaCommand::SheetLayout => {
             serde_json::to_value(schema_for!(OpsPayload<SheetLayoutOp>))?
         }
         BatchSchemaCommand::Rules => serde_json::to_value(schema_for!(OpsPayload<RulesOp>))?,
+        BatchSchemaCommand::Properties => {
+            serde_json::to_value(schema_for!(OpsPayload<PropertyOp>))?
+        }
     };
 
     Ok(serde_json::json!({
@@ -281,6 +458,13 @@ pub fn batch_payload_example(command: BatchSchemaCommand) -> Result<Value> {
                 "size": {"kind": "width", "width_chars": 12.0}
             }]
         }),
+        BatchSchemaCommand::RowSize => serde_json::json!({
+            "sheet_name": "Sheet1",
+            "ops": [{
+                "target": {"kind": "rows", "range": "2:4"},
+                "size": {"kind": "auto", "max_height_points": 60.0}
+            }]
+        }),
         BatchSchemaCommand::SheetLayout => serde_json::json!({
             "ops": [{
                 "kind": "freeze_panes",
@@ -297,6 +481,12 @@ pub fn batch_payload_example(command: BatchSchemaCommand) -> Result<Value> {
                 "validation": {"kind": "list", "formula1": "\"A,B,C\""}
             }]
         }),
+        BatchSchemaCommand::Properties => serde_json::json!({
+            "ops": [
+                {"kind": "set_core", "title": "Q3 Close", "creator": "close-pipeline"},
+                {"kind": "set_custom", "name": "RunId", "value": "run-2024-09-30-01"}
+            ]
+        }),
     };
 
     Ok(serde_json::json!({
@@ -336,6 +526,8 @@ struct BatchDryRunResponse {
     formula_parse_diagnostics: Option<FormulaParseDiagnostics>,
     #[serde(skip_serializing_if = "Option::is_none")]
     write_path_provenance: Option<WritePathProvenance>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    verification: Option<ExpectationReport>,
 }
 
 #[derive(Debug, Serialize)]
@@ -350,6 +542,8 @@ struct BatchApplyResponse {
     formula_parse_diagnostics: Option<FormulaParseDiagnostics>,
     #[serde(skip_serializing_if = "Option::is_none")]
     write_path_provenance: Option<WritePathProvenance>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    verification: Option<ExpectationReport>,
 }
 
 #[derive(Debug)]
@@ -377,6 +571,258 @@ pub async fn copy(source: PathBuf, dest: PathBuf) -> Result<Value> {
     })?)
 }
 
+pub async fn instantiate(
+    template: PathBuf,
+    values: String,
+    output: Option<PathBuf>,
+    dry_run: bool,
+    force: bool,
+    strict: bool,
+) -> Result<Value> {
+    let runtime = StatelessRuntime;
+    let source = runtime.normalize_existing_file(&template)?;
+    if !dry_run && output.is_none() {
+        return Err(invalid_argument(
+            "choose --output <PATH> for the filled workbook, or --dry-run",
+        ));
+    }
+    let mode = validate_batch_mode(&source, dry_run, false, output, force)?;
+
+    let values_path = values
+        .strip_prefix('@')
+        .filter(|path| !path.is_empty())
+        .ok_or_else(|| invalid_argument("--values must be provided as @<path>"))?;
+    let raw = fs::read_to_string(values_path).map_err(|error| {
+        invalid_argument(format!(
+            "unable to read --values '{}': {}",
+            values_path, error
+        ))
+    })?;
+    let json: Value = serde_json::from_str(&raw)
+        .map_err(|error| invalid_argument(format!("--values is not valid JSON: {error}")))?;
+    let values =
+        parse_template_values(&json).map_err(|error| invalid_argument(error.to_string()))?;
+
+    let mut operation_counts = BTreeMap::new();
+    operation_counts.insert("instantiate".to_string(), values.len() as u64);
+    let apply = |path: &Path| {
+        let summary = instantiate_workbook(path, &values).map_err(classify_apply_error)?;
+        if strict && !summary.unresolved.is_empty() {
+            return Err(unresolved_placeholders(&summary));
+        }
+        Ok(summary)
+    };
+    let template_path = source.display().to_string();
+    let (summary, mode_name, target_path) = match mode {
+        BatchMutationMode::Output { target, force } => {
+            let target = runtime.normalize_destination_path(&target)?;
+            ensure_output_path_is_distinct(&source, &target)?;
+            let summary = apply_to_output_with_temp(
+                &source,
+                &target,
+                force,
+                WriteCommit::new("instantiate").with_operation_counts(&operation_counts),
+                apply,
+            )
+            .await?;
+            (summary, "output", Some(target.display().to_string()))
+        }
+        _ => {
+            let (summary, _temp_path) =
+                apply_to_temp_copy(&source, source.parent(), ".instantiate-", apply)?;
+            (summary, "dry_run", None)
+        }
+    };
+
+    Ok(serde_json::to_value(InstantiateResponse {
+        mode: mode_name.to_string(),
+        template_path,
+        target_path,
+        summary,
+    })?)
+}
+
+pub async fn apply_strings(
+    file: PathBuf,
+    strings: String,
+    dry_run: bool,
+    in_place: bool,
+    output: Option<PathBuf>,
+    force: bool,
+) -> Result<Value> {
+    let runtime = StatelessRuntime;
+    let source = runtime.normalize_existing_file(&file)?;
+    let mode = validate_batch_mode(&source, dry_run, in_place, output, force)?;
+
+    let strings_path = strings
+        .strip_prefix('@')
+        .filter(|path| !path.is_empty())
+        .ok_or_else(|| invalid_argument("--strings must be provided as @<path>"))?;
+    let raw = fs::read_to_string(strings_path).map_err(|error| {
+        invalid_argument(format!(
+            "unable to read --strings '{}': {}",
+            strings_path, error
+        ))
+    })?;
+    let translations =
+        parse_translation_table(&raw).map_err(|error| invalid_argument(format!("{error:#}")))?;
+
+    let mut operation_counts = BTreeMap::new();
+    operation_counts.insert("apply_strings".to_string(), translations.len() as u64);
+    let apply = |path: &Path| apply_translations(path, &translations).map_err(classify_apply_error);
+    let source_path = source.display().to_string();
+    let (summary, mode_name, target_path) = match mode {
+        BatchMutationMode::DryRun => {
+            let (summary, _temp_path) =
+                apply_to_temp_copy(&source, source.parent(), ".apply-strings-", apply)?;
+            (summary, "dry_run", None)
+        }
+        BatchMutationMode::InPlace => {
+            let summary = apply_in_place_with_temp(
+                &source,
+                WriteCommit::new("apply-strings").with_operation_counts(&operation_counts),
+                apply,
+            )
+            .await?;
+            (summary, "in_place", Some(source_path.clone()))
+        }
+        BatchMutationMode::Output { target, force } => {
+            let target = runtime.normalize_destination_path(&target)?;
+            ensure_output_path_is_distinct(&source, &target)?;
+            let summary = apply_to_output_with_temp(
+                &source,
+                &target,
+                force,
+                WriteCommit::new("apply-strings").with_operation_counts(&operation_counts),
+                apply,
+            )
+            .await?;
+            (summary, "output", Some(target.display().to_string()))
+        }
+    };
+
+    Ok(serde_json::to_value(ApplyStringsResponse {
+        mode: mode_name.to_string(),
+        source_path,
+        target_path,
+        summary,
+    })?)
+}
+
+pub async fn restore(
+    file: PathBuf,
+    snapshot_id: String,
+    snapshot_dir: PathBuf,
+    force: bool,
+) -> Result<Value> {
+    let runtime = StatelessRuntime;
+    let source = runtime.normalize_existing_file(&file)?;
+    let store = open_snapshot_store(&snapshot_dir)?;
+    let (record, snapshot_copy) = load_snapshot(&store, &snapshot_id)
+        .map_err(|error| invalid_argument(format!("{error:#}")))?;
+    let source_path = source.display().to_string();
+    if record.source_path != source_path && !force {
+        return Err(invalid_argument(format!(
+            "snapshot '{}' was taken from '{}', not '{}'; pass --force to restore it anyway",
+            record.id, record.source_path, source_path
+        )));
+    }
+
+    let snapshot_location = store.describe(&record);
+    let pre_restore = SnapshotDir::new(store);
+    apply_in_place_with_temp(
+        &source,
+        WriteCommit::new("restore").with_snapshot_dir(Some(&pre_restore)),
+        |path| {
+            fs::copy(snapshot_copy.path(), path).map_err(|error| {
+                write_failed(format!(
+                    "unable to stage snapshot '{}': {}",
+                    snapshot_location, error
+                ))
+            })?;
+            Ok(())
+        },
+    )
+    .await?;
+
+    Ok(serde_json::to_value(RestoreResponse {
+        file: source_path,
+        restored: record,
+        snapshot: pre_restore.taken.into_inner(),
+    })?)
+}
+
+pub async fn list_snapshots(snapshot_dir: PathBuf, file: Option<PathBuf>) -> Result<Value> {
+    let runtime = StatelessRuntime;
+    // Snapshots record the canonical source path; a deleted workbook can
+    // still be matched by its absolute path.
+    let file = file
+        .map(|file| {
+            if file.exists() {
+                runtime.normalize_existing_file(&file)
+            } else {
+                runtime.normalize_destination_path(&file)
+            }
+        })
+        .transpose()?
+        .map(|file| file.display().to_string());
+    let store = open_snapshot_store(&snapshot_dir)?;
+    let mut snapshots =
+        list_snapshot_records(&store).map_err(|error| invalid_argument(format!("{error:#}")))?;
+    if let Some(file) = &file {
+        snapshots.retain(|record| &record.source_path == file);
+    }
+
+    Ok(serde_json::to_value(ListSnapshotsResponse {
+        snapshot_dir: store.to_string(),
+        file,
+        count: snapshots.len(),
+        snapshots,
+    })?)
+}
+
+pub async fn restore_revision(file: PathBuf, revision: u32, force: bool) -> Result<Value> {
+    let runtime = StatelessRuntime;
+    let file = runtime.normalize_existing_file(&file)?;
+    let (history, _) =
+        RevisionHistory::of(&file).map_err(|error| invalid_argument(format!("{error:#}")))?;
+    if revision == 0 {
+        return Err(invalid_argument(
+            "revision 0 is the base workbook itself; pass a revision from `revisions list`",
+        ));
+    }
+    let revision_path = history
+        .load(revision, force)
+        .map_err(|error| invalid_argument(format!("{error:#}")))?;
+    let base = runtime.normalize_existing_file(&history.base_path())?;
+
+    let backup = history
+        .copy_to_next_revision(&base, 0, "revisions-restore")
+        .map_err(|error| {
+            write_failed(format!(
+                "unable to keep '{}' before restoring: {error:#}",
+                base.display()
+            ))
+        })?;
+    apply_in_place_with_temp(&base, WriteCommit::new("revisions-restore"), |path| {
+        fs::copy(&revision_path, path).map_err(|error| {
+            write_failed(format!(
+                "unable to stage revision '{}': {}",
+                revision_path.display(),
+                error
+            ))
+        })?;
+        Ok(())
+    })
+    .await?;
+
+    Ok(serde_json::to_value(RestoreRevisionResponse {
+        file: base.display().to_string(),
+        restored_revision: revision,
+        backup,
+    })?)
+}
+
 pub async fn create_workbook(
     path: PathBuf,
     sheets: Option<Vec<String>>,
@@ -455,7 +901,11 @@ pub async fn edit(
     output: Option<PathBuf>,
     force: bool,
     formula_parse_policy: Option<FormulaParsePolicy>,
+    locale: Option<String>,
 ) -> Result<Value> {
+    let locale = locale
+        .map(|tag| ValueLocale::parse(&tag).map_err(|error| invalid_argument(error.to_string())))
+        .transpose()?;
     let mut edits = edits;
     if let Some(path) = edits_file {
         let mut file_edits = load_edits_file(&path)?;
@@ -468,7 +918,7 @@ pub async fn edit(
 
     let runtime = StatelessRuntime;
     let source = runtime.normalize_existing_file(&file)?;
-    let mode = validate_edit_mode(dry_run, in_place, output, force)?;
+    let mode = validate_edit_mode(&source, dry_run, in_place, output, force)?;
 
     let mut normalized_edits = Vec::with_capacity(edits.len());
     let mut warnings = Vec::new();
@@ -544,7 +994,7 @@ pub async fn edit(
     match mode {
         EditMutationMode::DryRun => {
             let _ = apply_to_temp_copy(&source, source.parent(), ".edit-", |path| {
-                runtime.apply_edits(path, &sheet_name, &edits_to_write)
+                runtime.apply_edits(path, &sheet_name, &edits_to_write, locale.as_ref())
             })?;
 
             Ok(serde_json::to_value(EditDryRunResponse {
@@ -561,9 +1011,10 @@ pub async fn edit(
             })?)
         }
         EditMutationMode::InPlace => {
-            apply_in_place_with_temp(&source, ".edit-", |path| {
-                runtime.apply_edits(path, &sheet_name, &edits_to_write)
-            })?;
+            apply_in_place_with_temp(&source, WriteCommit::new("edit"), |path| {
+                runtime.apply_edits(path, &sheet_name, &edits_to_write, locale.as_ref())
+            })
+            .await?;
 
             Ok(serde_json::to_value(EditResponse {
                 file: source.display().to_string(),
@@ -583,9 +1034,10 @@ pub async fn edit(
             let target = runtime.normalize_destination_path(&target)?;
             ensure_output_path_is_distinct(&source, &target)?;
 
-            apply_to_output_with_temp(&source, &target, force, ".edit-", |path| {
-                runtime.apply_edits(path, &sheet_name, &edits_to_write)
-            })?;
+            apply_to_output_with_temp(&source, &target, force, WriteCommit::new("edit"), |path| {
+                runtime.apply_edits(path, &sheet_name, &edits_to_write, locale.as_ref())
+            })
+            .await?;
 
             Ok(serde_json::to_value(EditResponse {
                 file: target.display().to_string(),
@@ -604,6 +1056,7 @@ pub async fn edit(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn transform_batch(
     file: PathBuf,
     ops: String,
@@ -612,135 +1065,90 @@ pub async fn transform_batch(
     output: Option<PathBuf>,
     force: bool,
     formula_parse_policy: Option<FormulaParsePolicy>,
+    verify_with: Option<PathBuf>,
+    audit_log: Option<PathBuf>,
+    snapshot_dir: Option<PathBuf>,
+    continue_on_error: bool,
+    differential_save: bool,
 ) -> Result<Value> {
     let runtime = StatelessRuntime;
     let source = runtime.normalize_existing_file(&file)?;
-    let mode = validate_batch_mode(dry_run, in_place, output, force)?;
+    let mode = validate_batch_mode(&source, dry_run, in_place, output, force)?;
+    let verify_with = load_verify_with(verify_with)?;
+    let audit_log = load_audit_log(audit_log, &ops)?;
+    let snapshot_dir = load_snapshot_dir(snapshot_dir, &mode)?;
 
-    let payload: OpsPayload<TransformOp> = parse_ops_payload(
+    let mut payload: OpsPayload<TransformOp> = parse_ops_payload(
         &ops,
         TRANSFORM_PAYLOAD_SHAPE,
         TRANSFORM_PAYLOAD_MINIMAL_EXAMPLE,
     )?;
-
-    let (state, workbook_id) = runtime.open_state_for_file(&source).await?;
-    let workbook = state.open_workbook(&workbook_id).await?;
-    let resolved_ops = resolve_transform_ops_for_workbook(&workbook, &payload.ops)
-        .map_err(|error| invalid_ops_payload(error.to_string()))?;
-    let _ = state.close_workbook(&workbook_id);
+    load_import_row_files(&mut payload.ops, &std::env::current_dir()?, false)
+        .map_err(|error| invalid_ops_payload(format!("{error:#}")))?;
 
     let policy = formula_parse_policy.unwrap_or(FormulaParsePolicy::default_for_command_class(
         CommandClass::BatchWrite,
     ));
 
-    let (ops_to_apply, formula_parse_diagnostics) = if policy == FormulaParsePolicy::Off {
-        (resolved_ops, None)
+    let (state, workbook_id) = runtime.open_state_for_file(&source).await?;
+    let workbook = state.open_workbook(&workbook_id).await?;
+    let (ops_to_apply, formula_parse_diagnostics, op_outcomes, isolated) = if continue_on_error {
+        let mut outcomes = BatchOpOutcomes::default();
+        let resolved_ops = resolve_each_op(
+            &payload.ops,
+            |op| op_kind(op, summarize_transform_operation_counts),
+            &mut outcomes,
+            |ops| resolve_transform_ops_for_workbook(&workbook, ops),
+        );
+        let (screened_ops, formula_parse_diagnostics) =
+            screen_transform_formulas_each(resolved_ops, policy, &mut outcomes);
+        let (ops_to_apply, isolated) = isolate_failing_ops(
+            &source,
+            screened_ops,
+            |op| op_kind(op, summarize_transform_operation_counts),
+            &mut outcomes,
+            |path, ops| apply_transform_ops_to_file(path, ops, differential_save),
+        )?;
+        (
+            ops_to_apply,
+            formula_parse_diagnostics,
+            Some(outcomes),
+            isolated,
+        )
     } else {
-        let mut builder = FormulaParseDiagnosticsBuilder::new(policy);
-        let mut valid_ops = Vec::new();
-        for op in resolved_ops {
-            match &op {
-                TransformOp::FillRange {
-                    sheet_name,
-                    value,
-                    is_formula,
-                    ..
-                } if *is_formula => match validate_formula(value) {
-                    Ok(()) => valid_ops.push(op),
-                    Err(err_msg) => {
-                        if policy == FormulaParsePolicy::Fail {
-                            bail!(
-                                "{}FillRange formula failed: {}",
-                                FORMULA_PARSE_FAILED_PREFIX,
-                                err_msg
-                            );
-                        }
-                        builder.record_error(sheet_name, "FillRange", value, &err_msg);
-                    }
-                },
-                TransformOp::WriteMatrix {
-                    sheet_name,
-                    anchor,
-                    rows,
-                    overwrite_formulas,
-                } => {
-                    let mut has_errors = false;
-                    let mut valid_rows = Vec::new();
-                    let (anchor_col, anchor_row) = parse_cell_ref_for_cli(anchor)?;
-
-                    for (r_idx, row) in rows.iter().enumerate() {
-                        let mut valid_row = Vec::new();
-                        let r = anchor_row + r_idx as u32;
-                        for (c_idx, cell_opt) in row.iter().enumerate() {
-                            let c = anchor_col + c_idx as u32;
-                            if let Some(MatrixCell::Formula(f)) = cell_opt {
-                                match validate_formula(f) {
-                                    Ok(()) => valid_row.push(cell_opt.clone()),
-                                    Err(err_msg) => {
-                                        if policy == FormulaParsePolicy::Fail {
-                                            bail!(
-                                                "{}WriteMatrix formula failed at {}: {}",
-                                                FORMULA_PARSE_FAILED_PREFIX,
-                                                crate::utils::cell_address(c, r),
-                                                err_msg
-                                            );
-                                        }
-                                        builder.record_error(
-                                            sheet_name,
-                                            &crate::utils::cell_address(c, r),
-                                            f,
-                                            &err_msg,
-                                        );
-                                        has_errors = true;
-                                        valid_row.push(None);
-                                    }
-                                }
-                            } else {
-                                valid_row.push(cell_opt.clone());
-                            }
-                        }
-                        valid_rows.push(valid_row);
-                    }
-
-                    if has_errors && policy == FormulaParsePolicy::Warn {
-                        valid_ops.push(TransformOp::WriteMatrix {
-                            sheet_name: sheet_name.clone(),
-                            anchor: anchor.clone(),
-                            rows: valid_rows,
-                            overwrite_formulas: *overwrite_formulas,
-                        });
-                    } else {
-                        valid_ops.push(op);
-                    }
-                }
-                _ => valid_ops.push(op),
-            }
-        }
-        let diagnostics = if builder.has_errors() {
-            Some(builder.build())
-        } else {
-            None
-        };
-        (valid_ops, diagnostics)
+        let resolved_ops = resolve_transform_ops_for_workbook(&workbook, &payload.ops)
+            .map_err(|error| invalid_ops_payload(error.to_string()))?;
+        let (ops_to_apply, formula_parse_diagnostics) =
+            screen_transform_formulas(resolved_ops, policy)?;
+        (ops_to_apply, formula_parse_diagnostics, None, None)
     };
+    let _ = state.close_workbook(&workbook_id);
 
-    let op_count = ops_to_apply.len();
+    let op_count = if continue_on_error {
+        payload.ops.len()
+    } else {
+        ops_to_apply.len()
+    };
     let operation_counts = summarize_transform_operation_counts(&ops_to_apply);
     let write_path_provenance =
         formula_write_provenance("transform_batch", transform_formula_targets(&ops_to_apply));
 
-    match mode {
+    let response = match mode {
         BatchMutationMode::DryRun => {
-            let (apply_result, _temp_path) =
+            let (apply_result, temp_path) =
                 apply_to_temp_copy(&source, source.parent(), ".transform-batch-", |path| {
-                    apply_transform_ops_to_file(path, &ops_to_apply).map_err(classify_apply_error)
+                    apply_or_reuse(isolated, path, |path| {
+                        apply_transform_ops_to_file(path, &ops_to_apply, differential_save)
+                            .map_err(classify_apply_error)
+                    })
                 })?;
+            let verification = verify_staged_workbook(&temp_path, verify_with.as_ref()).await?;
 
             let result_counts = apply_result.summary.counts;
             let warnings = warning_strings_to_cli_warnings(apply_result.summary.warnings);
             let would_change = transform_summary_indicates_change(&result_counts);
 
-            dry_run_response(
+            let response = dry_run_response(
                 op_count,
                 operation_counts,
                 result_counts,
@@ -748,18 +1156,35 @@ pub async fn transform_batch(
                 would_change,
                 formula_parse_diagnostics,
                 write_path_provenance.clone(),
-            )
+                verification,
+            )?;
+            Ok(attach_row_permutations(
+                response,
+                apply_result.summary.row_permutations,
+            ))
         }
         BatchMutationMode::InPlace => {
-            let apply_result = apply_in_place_with_temp(&source, ".transform-batch-", |path| {
-                apply_transform_ops_to_file(path, &ops_to_apply).map_err(classify_apply_error)
-            })?;
+            let (apply_result, verification) = apply_in_place_with_verify(
+                &source,
+                verify_with.as_ref(),
+                WriteCommit::new("transform-batch")
+                    .with_operation_counts(&operation_counts)
+                    .with_audit_log(audit_log.as_ref())
+                    .with_snapshot_dir(snapshot_dir.as_ref()),
+                |path| {
+                    apply_or_reuse(isolated, path, |path| {
+                        apply_transform_ops_to_file(path, &ops_to_apply, differential_save)
+                            .map_err(classify_apply_error)
+                    })
+                },
+            )
+            .await?;
 
             let result_counts = apply_result.summary.counts;
             let warnings = warning_strings_to_cli_warnings(apply_result.summary.warnings);
             let changed = transform_summary_indicates_change(&result_counts);
 
-            apply_response(
+            let response = apply_response(
                 op_count,
                 apply_result.ops_applied,
                 warnings,
@@ -768,16 +1193,30 @@ pub async fn transform_batch(
                 source.display().to_string(),
                 formula_parse_diagnostics,
                 write_path_provenance.clone(),
-            )
+                verification,
+            )?;
+            Ok(attach_snapshot(response, snapshot_dir.as_ref()))
         }
         BatchMutationMode::Output { target, force } => {
             let target = runtime.normalize_destination_path(&target)?;
             ensure_output_path_is_distinct(&source, &target)?;
 
-            let apply_result =
-                apply_to_output_with_temp(&source, &target, force, ".transform-batch-", |path| {
-                    apply_transform_ops_to_file(path, &ops_to_apply).map_err(classify_apply_error)
-                })?;
+            let (apply_result, verification) = apply_to_output_with_verify(
+                &source,
+                &target,
+                force,
+                verify_with.as_ref(),
+                WriteCommit::new("transform-batch")
+                    .with_operation_counts(&operation_counts)
+                    .with_audit_log(audit_log.as_ref()),
+                |path| {
+                    apply_or_reuse(isolated, path, |path| {
+                        apply_transform_ops_to_file(path, &ops_to_apply, differential_save)
+                            .map_err(classify_apply_error)
+                    })
+                },
+            )
+            .await?;
 
             let result_counts = apply_result.summary.counts;
             let warnings = warning_strings_to_cli_warnings(apply_result.summary.warnings);
@@ -792,8 +1231,151 @@ pub async fn transform_batch(
                 source.display().to_string(),
                 formula_parse_diagnostics,
                 write_path_provenance.clone(),
+                verification,
             )
         }
+    }?;
+    Ok(attach_op_outcomes(response, op_outcomes))
+}
+
+/// Apply `policy` to the formulas in transform ops: under `warn`, ops (or
+/// matrix cells) whose formulas do not parse are dropped and reported; under
+/// `fail`, the first one aborts the command.
+fn screen_transform_formulas(
+    resolved_ops: Vec<TransformOp>,
+    policy: FormulaParsePolicy,
+) -> Result<(Vec<TransformOp>, Option<FormulaParseDiagnostics>)> {
+    if policy == FormulaParsePolicy::Off {
+        return Ok((resolved_ops, None));
+    }
+
+    let mut builder = FormulaParseDiagnosticsBuilder::new(policy);
+    let mut valid_ops = Vec::new();
+    for op in resolved_ops {
+        if let Some(op) = screen_transform_op(op, policy, &mut builder)? {
+            valid_ops.push(op);
+        }
+    }
+    Ok((valid_ops, finish_formula_diagnostics(builder)))
+}
+
+/// [`screen_transform_formulas`] for `--continue-on-error`: an op whose
+/// formula fails under `fail` is recorded as failed rather than aborting the
+/// command, and an op dropped under `warn` is recorded as skipped.
+fn screen_transform_formulas_each(
+    resolved_ops: Vec<(usize, TransformOp)>,
+    policy: FormulaParsePolicy,
+    outcomes: &mut BatchOpOutcomes,
+) -> (Vec<(usize, TransformOp)>, Option<FormulaParseDiagnostics>) {
+    if policy == FormulaParsePolicy::Off {
+        return (resolved_ops, None);
+    }
+
+    let mut builder = FormulaParseDiagnosticsBuilder::new(policy);
+    let mut valid_ops = Vec::new();
+    for (index, op) in resolved_ops {
+        let kind = op_kind(&op, summarize_transform_operation_counts);
+        match screen_transform_op(op, policy, &mut builder) {
+            Ok(Some(op)) => valid_ops.push((index, op)),
+            Ok(None) => outcomes.skip(
+                index,
+                kind,
+                "formula does not parse (dropped under --formula-parse-policy warn)",
+            ),
+            Err(error) => outcomes.fail(index, kind, &error),
+        }
+    }
+    (valid_ops, finish_formula_diagnostics(builder))
+}
+
+fn finish_formula_diagnostics(
+    builder: FormulaParseDiagnosticsBuilder,
+) -> Option<FormulaParseDiagnostics> {
+    builder.has_errors().then(|| builder.build())
+}
+
+/// Screen one transform op; `None` means `warn` dropped it.
+fn screen_transform_op(
+    op: TransformOp,
+    policy: FormulaParsePolicy,
+    builder: &mut FormulaParseDiagnosticsBuilder,
+) -> Result<Option<TransformOp>> {
+    match &op {
+        TransformOp::FillRange {
+            sheet_name,
+            value,
+            is_formula,
+            ..
+        } if *is_formula => match validate_formula(value) {
+            Ok(()) => Ok(Some(op)),
+            Err(err_msg) => {
+                if policy == FormulaParsePolicy::Fail {
+                    bail!(
+                        "{}FillRange formula failed: {}",
+                        FORMULA_PARSE_FAILED_PREFIX,
+                        err_msg
+                    );
+                }
+                builder.record_error(sheet_name, "FillRange", value, &err_msg);
+                Ok(None)
+            }
+        },
+        TransformOp::WriteMatrix {
+            sheet_name,
+            anchor,
+            rows,
+            overwrite_formulas,
+        } => {
+            let mut has_errors = false;
+            let mut valid_rows = Vec::new();
+            let (anchor_col, anchor_row) = parse_cell_ref_for_cli(anchor)?;
+
+            for (r_idx, row) in rows.iter().enumerate() {
+                let mut valid_row = Vec::new();
+                let r = anchor_row + r_idx as u32;
+                for (c_idx, cell_opt) in row.iter().enumerate() {
+                    let c = anchor_col + c_idx as u32;
+                    if let Some(MatrixCell::Formula(f)) = cell_opt {
+                        match validate_formula(f) {
+                            Ok(()) => valid_row.push(cell_opt.clone()),
+                            Err(err_msg) => {
+                                if policy == FormulaParsePolicy::Fail {
+                                    bail!(
+                                        "{}WriteMatrix formula failed at {}: {}",
+                                        FORMULA_PARSE_FAILED_PREFIX,
+                                        crate::utils::cell_address(c, r),
+                                        err_msg
+                                    );
+                                }
+                                builder.record_error(
+                                    sheet_name,
+                                    &crate::utils::cell_address(c, r),
+                                    f,
+                                    &err_msg,
+                                );
+                                has_errors = true;
+                                valid_row.push(None);
+                            }
+                        }
+                    } else {
+                        valid_row.push(cell_opt.clone());
+                    }
+                }
+                valid_rows.push(valid_row);
+            }
+
+            if has_errors && policy == FormulaParsePolicy::Warn {
+                Ok(Some(TransformOp::WriteMatrix {
+                    sheet_name: sheet_name.clone(),
+                    anchor: anchor.clone(),
+                    rows: valid_rows,
+                    overwrite_formulas: *overwrite_formulas,
+                }))
+            } else {
+                Ok(Some(op))
+            }
+        }
+        _ => Ok(Some(op)),
     }
 }
 
@@ -816,7 +1398,7 @@ pub async fn replace_in_formulas(
 
     let runtime = StatelessRuntime;
     let source = runtime.normalize_existing_file(&file)?;
-    let mode = validate_batch_mode(dry_run, in_place, output, force)?;
+    let mode = validate_batch_mode(&source, dry_run, in_place, output, force)?;
 
     let op = ReplaceInFormulasOp {
         sheet_name: sheet.clone(),
@@ -861,9 +1443,15 @@ pub async fn replace_in_formulas(
             })?)
         }
         BatchMutationMode::InPlace => {
-            let result = apply_in_place_with_temp(&source, ".replace-in-formulas-", |path| {
-                apply_replace_in_formulas_to_file(path, &op, policy).map_err(classify_apply_error)
-            })?;
+            let result = apply_in_place_with_temp(
+                &source,
+                WriteCommit::new("replace-in-formulas"),
+                |path| {
+                    apply_replace_in_formulas_to_file(path, &op, policy)
+                        .map_err(classify_apply_error)
+                },
+            )
+            .await?;
 
             let warnings = warning_strings_to_cli_warnings(result.warnings.clone());
             let changed = result.formulas_changed > 0;
@@ -896,12 +1484,13 @@ pub async fn replace_in_formulas(
                 &source,
                 &target,
                 force,
-                ".replace-in-formulas-",
+                WriteCommit::new("replace-in-formulas"),
                 |path| {
                     apply_replace_in_formulas_to_file(path, &op, policy)
                         .map_err(classify_apply_error)
                 },
-            )?;
+            )
+            .await?;
 
             let warnings = warning_strings_to_cli_warnings(result.warnings.clone());
             let changed = result.formulas_changed > 0;
@@ -962,6 +1551,160 @@ struct ReplaceInFormulasApplyResponse {
     formula_parse_diagnostics: Option<FormulaParseDiagnostics>,
 }
 
+#[derive(Debug, Serialize)]
+struct RepairRefsResponse {
+    file: String,
+    /// Workbook the restored references were taken from.
+    baseline: String,
+    /// Snapshot used as the baseline, when it came from `--snapshot-dir`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    baseline_snapshot: Option<String>,
+    dry_run: bool,
+    min_confidence: RefRepairConfidence,
+    #[serde(flatten)]
+    result: RefRepairResult,
+    changed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    target_path: Option<String>,
+    /// Snapshot taken of the workbook just before an in-place repair.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    snapshot: Option<SnapshotRecord>,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn repair_refs(
+    file: PathBuf,
+    sheet: Option<String>,
+    baseline: Option<PathBuf>,
+    snapshot_dir: Option<PathBuf>,
+    snapshot_id: Option<String>,
+    min_confidence: RefRepairConfidenceArg,
+    dry_run: bool,
+    in_place: bool,
+    output: Option<PathBuf>,
+    force: bool,
+) -> Result<Value> {
+    let runtime = StatelessRuntime;
+    let source = runtime.normalize_existing_file(&file)?;
+    let mode = validate_batch_mode(&source, dry_run, in_place, output, force)?;
+    let min_confidence = match min_confidence {
+        RefRepairConfidenceArg::High => RefRepairConfidence::High,
+        RefRepairConfidenceArg::Medium => RefRepairConfidence::Medium,
+        RefRepairConfidenceArg::Low => RefRepairConfidence::Low,
+    };
+
+    let snapshot_store = snapshot_dir
+        .as_deref()
+        .map(open_snapshot_store)
+        .transpose()?;
+    let (baseline_copy, baseline_label, baseline_snapshot) = match (baseline, &snapshot_store) {
+        (Some(baseline), _) => {
+            if snapshot_id.is_some() {
+                return Err(invalid_argument(
+                    "--snapshot cannot be combined with --baseline",
+                ));
+            }
+            let baseline = runtime.normalize_existing_file(&baseline)?;
+            let label = baseline.display().to_string();
+            (SnapshotCopy::Local(baseline), label, None)
+        }
+        (None, Some(store)) => {
+            let (record, copy) = match &snapshot_id {
+                Some(id) => load_snapshot(store, id),
+                None => latest_snapshot_of(store, &source),
+            }
+            .map_err(|error| invalid_argument(format!("{error:#}")))?;
+            (copy, store.describe(&record), Some(record.id))
+        }
+        (None, None) => {
+            return Err(invalid_argument(
+                "repair-refs needs a baseline: pass --baseline <PATH> or --snapshot-dir <DIR>",
+            ));
+        }
+    };
+    let baseline_path = baseline_copy.path();
+    if baseline_path == source {
+        return Err(invalid_argument(
+            "--baseline must be an earlier copy of the workbook, not the workbook itself",
+        ));
+    }
+
+    let sheet_filter = sheet.as_deref();
+    let mut response = RepairRefsResponse {
+        file: source.display().to_string(),
+        baseline: baseline_label,
+        baseline_snapshot,
+        dry_run: matches!(mode, BatchMutationMode::DryRun),
+        min_confidence,
+        result: RefRepairResult {
+            broken_formulas: 0,
+            proposed: 0,
+            applied: 0,
+            proposals: Vec::new(),
+        },
+        changed: false,
+        target_path: None,
+        snapshot: None,
+    };
+    let repair = |path: &Path, apply: bool| {
+        repair_refs_in_file(path, baseline_path, sheet_filter, min_confidence, apply)
+            .map_err(classify_apply_error)
+    };
+
+    response.result = match mode {
+        BatchMutationMode::DryRun => repair(&source, false)?,
+        BatchMutationMode::InPlace => {
+            // In-place repairs are undoable like any other in-place write
+            // when snapshots are kept.
+            let pre_repair = snapshot_store.map(SnapshotDir::new);
+            let result = apply_in_place_with_temp(
+                &source,
+                WriteCommit::new("repair-refs").with_snapshot_dir(pre_repair.as_ref()),
+                |path| repair(path, true),
+            )
+            .await?;
+            response.target_path = Some(source.display().to_string());
+            response.snapshot = pre_repair.and_then(|dir| dir.taken.into_inner());
+            result
+        }
+        BatchMutationMode::Output { target, force } => {
+            let target = runtime.normalize_destination_path(&target)?;
+            ensure_output_path_is_distinct(&source, &target)?;
+            let result = apply_to_output_with_temp(
+                &source,
+                &target,
+                force,
+                WriteCommit::new("repair-refs"),
+                |path| repair(path, true),
+            )
+            .await?;
+            response.target_path = Some(target.display().to_string());
+            result
+        }
+    };
+    response.changed = response.result.applied > 0;
+    Ok(serde_json::to_value(response)?)
+}
+
+/// Newest snapshot in `store` taken from `source`.
+fn latest_snapshot_of(
+    store: &SnapshotStore,
+    source: &Path,
+) -> Result<(SnapshotRecord, SnapshotCopy)> {
+    let source_path = source.display().to_string();
+    let record = list_snapshot_records(store)?
+        .into_iter()
+        .find(|record| record.source_path == source_path)
+        .ok_or_else(|| {
+            anyhow!(
+                "no snapshot of '{}' in '{}'; pass --snapshot <ID> or --baseline <PATH>",
+                source_path,
+                store
+            )
+        })?;
+    load_snapshot(store, &record.id)
+}
+
 #[allow(clippy::too_many_arguments)]
 pub async fn range_import(
     file: PathBuf,
@@ -975,10 +1718,19 @@ pub async fn range_import(
     in_place: bool,
     output: Option<PathBuf>,
     force: bool,
+    verify_with: Option<PathBuf>,
+    audit_log: Option<PathBuf>,
+    snapshot_dir: Option<PathBuf>,
 ) -> Result<Value> {
     let runtime = StatelessRuntime;
     let source = runtime.normalize_existing_file(&file)?;
-    let mode = validate_batch_mode(dry_run, in_place, output, force)?;
+    let mode = validate_batch_mode(&source, dry_run, in_place, output, force)?;
+    let verify_with = load_verify_with(verify_with)?;
+    let snapshot_dir = load_snapshot_dir(snapshot_dir, &mode)?;
+    let audit_log = match from_grid.as_deref().or(from_csv.as_deref()) {
+        Some(import_path) => load_audit_log(audit_log, import_path)?,
+        None => None,
+    };
 
     let grid: GridPayload = match (from_grid, from_csv) {
         (Some(grid_path), None) => {
@@ -1020,11 +1772,12 @@ pub async fn range_import(
 
     match mode {
         BatchMutationMode::DryRun => {
-            let (apply_result, _temp_path) =
+            let (apply_result, temp_path) =
                 apply_to_temp_copy(&source, source.parent(), ".range-import-", |path| {
                     apply_grid_import_to_path(path, &sheet, &anchor, &grid, clear_target)
                         .map_err(classify_apply_error)
                 })?;
+            let verification = verify_staged_workbook(&temp_path, verify_with.as_ref()).await?;
 
             let result_counts = apply_result.summary.counts;
             let warnings = warning_strings_to_cli_warnings(apply_result.summary.warnings);
@@ -1038,19 +1791,29 @@ pub async fn range_import(
                 would_change,
                 apply_result.formula_parse_diagnostics,
                 write_path_provenance,
+                verification,
             )
         }
         BatchMutationMode::InPlace => {
-            let apply_result = apply_in_place_with_temp(&source, ".range-import-", |path| {
-                apply_grid_import_to_path(path, &sheet, &anchor, &grid, clear_target)
-                    .map_err(classify_apply_error)
-            })?;
+            let (apply_result, verification) = apply_in_place_with_verify(
+                &source,
+                verify_with.as_ref(),
+                WriteCommit::new("range-import")
+                    .with_operation_counts(&operation_counts)
+                    .with_audit_log(audit_log.as_ref())
+                    .with_snapshot_dir(snapshot_dir.as_ref()),
+                |path| {
+                    apply_grid_import_to_path(path, &sheet, &anchor, &grid, clear_target)
+                        .map_err(classify_apply_error)
+                },
+            )
+            .await?;
 
             let result_counts = apply_result.summary.counts;
             let warnings = warning_strings_to_cli_warnings(apply_result.summary.warnings);
             let changed = grid_import_summary_indicates_change(&result_counts);
 
-            apply_response(
+            let response = apply_response(
                 op_count,
                 1,
                 warnings,
@@ -1059,17 +1822,28 @@ pub async fn range_import(
                 source.display().to_string(),
                 apply_result.formula_parse_diagnostics,
                 write_path_provenance,
-            )
+                verification,
+            )?;
+            Ok(attach_snapshot(response, snapshot_dir.as_ref()))
         }
         BatchMutationMode::Output { target, force } => {
             let target = runtime.normalize_destination_path(&target)?;
             ensure_output_path_is_distinct(&source, &target)?;
 
-            let apply_result =
-                apply_to_output_with_temp(&source, &target, force, ".range-import-", |path| {
+            let (apply_result, verification) = apply_to_output_with_verify(
+                &source,
+                &target,
+                force,
+                verify_with.as_ref(),
+                WriteCommit::new("range-import")
+                    .with_operation_counts(&operation_counts)
+                    .with_audit_log(audit_log.as_ref()),
+                |path| {
                     apply_grid_import_to_path(path, &sheet, &anchor, &grid, clear_target)
                         .map_err(classify_apply_error)
-                })?;
+                },
+            )
+            .await?;
 
             let result_counts = apply_result.summary.counts;
             let warnings = warning_strings_to_cli_warnings(apply_result.summary.warnings);
@@ -1084,122 +1858,206 @@ pub async fn range_import(
                 source.display().to_string(),
                 apply_result.formula_parse_diagnostics,
                 write_path_provenance,
+                verification,
             )
         }
     }
 }
 
-pub async fn style_batch(
+#[derive(Debug, Serialize)]
+struct WriteCellsResponse {
+    mode: String,
+    file: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    target_path: Option<String>,
+    summary: WriteCellsSummary,
+    metrics: WriteCellsMetrics,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    would_change: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    changed: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    verification: Option<ExpectationReport>,
+}
+
+#[derive(Debug, Serialize)]
+struct WriteCellsMetrics {
+    payload_bytes: u64,
+    parse_ms: u64,
+    write_ms: u64,
+    /// Parse, write and commit, end to end.
+    elapsed_ms: u64,
+    cells_per_second: u64,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn write_cells(
     file: PathBuf,
-    ops: String,
+    payload: String,
     dry_run: bool,
     in_place: bool,
     output: Option<PathBuf>,
     force: bool,
+    verify_with: Option<PathBuf>,
+    audit_log: Option<PathBuf>,
+    snapshot_dir: Option<PathBuf>,
 ) -> Result<Value> {
+    let started = std::time::Instant::now();
     let runtime = StatelessRuntime;
     let source = runtime.normalize_existing_file(&file)?;
-    let mode = validate_batch_mode(dry_run, in_place, output, force)?;
-
-    let payload: OpsPayload<StyleOpInput> =
-        parse_ops_payload(&ops, STYLE_PAYLOAD_SHAPE, STYLE_PAYLOAD_MINIMAL_EXAMPLE)?;
-    let (normalized, base_warnings) = normalize_style_batch(StyleBatchParamsInput {
-        fork_id: String::new(),
-        ops: payload.ops,
-        mode: None,
-        label: None,
-    })
-    .map_err(|error| invalid_ops_payload(error.to_string()))?;
-
-    let (state, workbook_id) = runtime.open_state_for_file(&source).await?;
-    let workbook = state.open_workbook(&workbook_id).await?;
-    let resolved_ops = resolve_style_ops_for_workbook(&workbook, &normalized.ops)
-        .map_err(|error| invalid_ops_payload(error.to_string()))?;
-    let _ = state.close_workbook(&workbook_id);
+    let mode = validate_batch_mode(&source, dry_run, in_place, output, force)?;
+    let verify_with = load_verify_with(verify_with)?;
+    let audit_log = load_audit_log(audit_log, &payload)?;
+    let snapshot_dir = load_snapshot_dir(snapshot_dir, &mode)?;
 
-    let op_count = resolved_ops.len();
-    let operation_counts = summarize_style_operation_counts(&resolved_ops);
+    let payload_path = payload
+        .strip_prefix('@')
+        .filter(|path| !path.is_empty())
+        .ok_or_else(|| invalid_argument("--payload must be provided as @<path>"))?;
+    let payload_file = fs::File::open(payload_path).map_err(|error| {
+        invalid_argument(format!(
+            "unable to read --payload '{}': {}",
+            payload_path, error
+        ))
+    })?;
+    let payload_bytes = payload_file.metadata().map(|meta| meta.len()).unwrap_or(0);
+    // Parsed straight from the file: at bulk-load sizes an intermediate
+    // `Value` tree costs more than the write itself.
+    let blocks: CellBlocksPayload =
+        serde_json::from_reader(std::io::BufReader::new(payload_file)).map_err(|error| {
+            invalid_ops_payload(format!(
+                "cell payload does not match required schema: {error}; expected {{\"blocks\":[{{\"sheet_name\":\"Sheet1\",\"range\":\"A1\",\"columns\":[{{\"type\":\"number\",\"values\":[1,2]}}]}}]}}"
+            ))
+        })?;
+    validate_cell_blocks(&blocks).map_err(|error| invalid_ops_payload(format!("{error:#}")))?;
+    let parse_ms = started.elapsed().as_millis() as u64;
 
-    match mode {
+    let mut operation_counts = BTreeMap::new();
+    operation_counts.insert("write_cells".to_string(), blocks.blocks.len() as u64);
+    let apply = |path: &Path| write_cells_to_path(path, &blocks).map_err(classify_apply_error);
+    let source_path = source.display().to_string();
+    let (summary, verification, mode_name, target_path) = match mode {
         BatchMutationMode::DryRun => {
-            let (apply_result, _temp_path) =
-                apply_to_temp_copy(&source, source.parent(), ".style-batch-", |path| {
-                    apply_style_ops_to_file(path, &resolved_ops).map_err(classify_apply_error)
-                })?;
-
-            let result_counts = apply_result.summary.counts;
-            let warnings = merge_cli_warnings(
-                base_warnings.clone(),
-                warning_strings_to_cli_warnings(apply_result.summary.warnings),
-            );
-            let would_change = style_summary_indicates_change(&result_counts);
-
-            dry_run_response(
-                op_count,
-                operation_counts,
-                result_counts,
-                warnings,
-                would_change,
-                None,
-                None,
-            )
+            let (summary, temp_path) =
+                apply_to_temp_copy(&source, source.parent(), ".write-cells-", apply)?;
+            let verification = verify_staged_workbook(&temp_path, verify_with.as_ref()).await?;
+            (summary, verification, "dry_run", None)
         }
         BatchMutationMode::InPlace => {
-            let apply_result = apply_in_place_with_temp(&source, ".style-batch-", |path| {
-                apply_style_ops_to_file(path, &resolved_ops).map_err(classify_apply_error)
-            })?;
-
-            let result_counts = apply_result.summary.counts;
-            let warnings = merge_cli_warnings(
-                base_warnings.clone(),
-                warning_strings_to_cli_warnings(apply_result.summary.warnings),
-            );
-            let changed = style_summary_indicates_change(&result_counts);
-
-            apply_response(
-                op_count,
-                apply_result.ops_applied,
-                warnings,
-                changed,
-                source.display().to_string(),
-                source.display().to_string(),
-                None,
-                None,
+            let (summary, verification) = apply_in_place_with_verify(
+                &source,
+                verify_with.as_ref(),
+                WriteCommit::new("write-cells")
+                    .with_operation_counts(&operation_counts)
+                    .with_audit_log(audit_log.as_ref())
+                    .with_snapshot_dir(snapshot_dir.as_ref()),
+                apply,
             )
+            .await?;
+            (summary, verification, "in_place", Some(source_path.clone()))
         }
         BatchMutationMode::Output { target, force } => {
             let target = runtime.normalize_destination_path(&target)?;
             ensure_output_path_is_distinct(&source, &target)?;
-
-            let apply_result =
-                apply_to_output_with_temp(&source, &target, force, ".style-batch-", |path| {
-                    apply_style_ops_to_file(path, &resolved_ops).map_err(classify_apply_error)
-                })?;
-
-            let result_counts = apply_result.summary.counts;
-            let warnings = merge_cli_warnings(
-                base_warnings,
-                warning_strings_to_cli_warnings(apply_result.summary.warnings),
-            );
-            let changed = style_summary_indicates_change(&result_counts);
-
-            apply_response(
-                op_count,
-                apply_result.ops_applied,
-                warnings,
-                changed,
-                target.display().to_string(),
-                source.display().to_string(),
-                None,
-                None,
+            let (summary, verification) = apply_to_output_with_verify(
+                &source,
+                &target,
+                force,
+                verify_with.as_ref(),
+                WriteCommit::new("write-cells")
+                    .with_operation_counts(&operation_counts)
+                    .with_audit_log(audit_log.as_ref()),
+                apply,
+            )
+            .await?;
+            (
+                summary,
+                verification,
+                "output",
+                Some(target.display().to_string()),
             )
         }
-    }
-}
+    };
 
-pub async fn apply_formula_pattern(
+    let elapsed = started.elapsed();
+    let metrics = WriteCellsMetrics {
+        payload_bytes,
+        parse_ms,
+        write_ms: summary.write_ms,
+        elapsed_ms: elapsed.as_millis() as u64,
+        cells_per_second: (summary.cells_written as f64 / elapsed.as_secs_f64().max(0.001)) as u64,
+    };
+    let wrote = summary.cells_written > 0;
+    let dry_run = target_path.is_none();
+    let response = serde_json::to_value(WriteCellsResponse {
+        mode: mode_name.to_string(),
+        file: target_path.clone().unwrap_or_else(|| source_path.clone()),
+        source_path: (!dry_run).then_some(source_path),
+        target_path,
+        summary,
+        metrics,
+        would_change: dry_run.then_some(wrote),
+        changed: (!dry_run).then_some(wrote),
+        verification,
+    })?;
+    Ok(attach_snapshot(response, snapshot_dir.as_ref()))
+}
+
+#[derive(Debug, Serialize)]
+struct MaterializeResponse {
+    mode: String,
+    file: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    target_path: Option<String>,
+    refresh: bool,
+    refreshed_at: String,
+    results: Vec<MaterializeResult>,
+    /// Refreshed sheets whose row count differs from the previous run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    row_count_changes: Option<usize>,
+    warnings: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    would_change: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    changed: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+struct MaterializeResult {
+    target_sheet: String,
+    #[serde(flatten)]
+    written: MaterializedRange,
+    matched_rows: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    previous_rows: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    row_delta: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    previous_refreshed_at: Option<String>,
+    definition: MaterializeDefinition,
+}
+
+/// Query output gathered before any workbook is touched.
+struct MaterializeJob {
+    record: MaterializeRecord,
+    headers: Vec<String>,
+    rows: Vec<crate::model::TableRow>,
+    matched_rows: u32,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn materialize(
     file: PathBuf,
-    ops: String,
+    target_sheet: Option<String>,
+    anchor: Option<String>,
+    query: ReadTableParams,
+    named_query: Option<NamedQuery>,
+    refresh: bool,
+    new_workbook: bool,
     dry_run: bool,
     in_place: bool,
     output: Option<PathBuf>,
@@ -1207,36 +2065,358 @@ pub async fn apply_formula_pattern(
 ) -> Result<Value> {
     let runtime = StatelessRuntime;
     let source = runtime.normalize_existing_file(&file)?;
-    let mode = validate_batch_mode(dry_run, in_place, output, force)?;
+    let mode = validate_batch_mode(&source, dry_run, in_place, output, force)?;
+    if new_workbook && matches!(mode, BatchMutationMode::InPlace) {
+        return Err(invalid_argument(
+            "--new-workbook writes a separate file; use --output <PATH> instead of --in-place",
+        ));
+    }
 
-    let payload: OpsPayload<ApplyFormulaPatternOpInput> = parse_ops_payload(
-        &ops,
-        APPLY_FORMULA_PATTERN_PAYLOAD_SHAPE,
-        APPLY_FORMULA_PATTERN_PAYLOAD_MINIMAL_EXAMPLE,
-    )?;
+    let records = if refresh {
+        read_refresh_records(&source, target_sheet.as_slice())?
+    } else {
+        let target_sheet = target_sheet.ok_or_else(|| {
+            invalid_argument("--target-sheet is required unless --refresh is given")
+        })?;
+        let mut query = query;
+        if let Some(named_query) = named_query {
+            named_query.apply_to(&mut query);
+        }
+        if !new_workbook
+            && query
+                .sheet_name
+                .as_deref()
+                .is_some_and(|name| name.eq_ignore_ascii_case(&target_sheet))
+        {
+            return Err(invalid_argument(
+                "--target-sheet must differ from the queried sheet; its contents are replaced",
+            ));
+        }
+        vec![MaterializeRecord::from(MaterializeDefinition {
+            target_sheet,
+            anchor: anchor.unwrap_or_else(|| "A1".to_string()),
+            source: new_workbook.then(|| source.display().to_string()),
+            query: NamedQuery::from_params(&query),
+        })]
+    };
 
-    let op_count = payload.ops.len();
-    let operation_counts = summarize_formula_pattern_operation_counts(&payload.ops);
-    let write_path_provenance = formula_write_provenance(
-        "apply_formula_pattern",
-        apply_formula_pattern_targets(&payload.ops),
-    );
+    run_materialize(source, mode, records, new_workbook, refresh).await
+}
 
-    match mode {
+pub async fn refresh(
+    file: PathBuf,
+    sheets: Vec<String>,
+    dry_run: bool,
+    in_place: bool,
+    output: Option<PathBuf>,
+    force: bool,
+) -> Result<Value> {
+    let source = StatelessRuntime.normalize_existing_file(&file)?;
+    let mode = validate_batch_mode(&source, dry_run, in_place, output, force)?;
+    let records = read_refresh_records(&source, &sheets)?;
+    run_materialize(source, mode, records, false, true).await
+}
+
+/// Recorded materializations of `source`, limited to `sheets` when any are
+/// named.
+fn read_refresh_records(source: &Path, sheets: &[String]) -> Result<Vec<MaterializeRecord>> {
+    let book = umya_spreadsheet::reader::xlsx::read(source)
+        .with_context(|| format!("failed to read workbook '{}'", source.display()))?;
+    let records =
+        read_materialize_sheet(&book).map_err(|error| invalid_argument(format!("{error:#}")))?;
+    if let Some(missing) = sheets.iter().find(|name| {
+        !records
+            .iter()
+            .any(|record| record.definition.target_sheet.eq_ignore_ascii_case(name))
+    }) {
+        return Err(invalid_argument(format!(
+            "no materialization is recorded for sheet '{missing}' in '{}'",
+            source.display()
+        )));
+    }
+    let records: Vec<_> = records
+        .into_iter()
+        .filter(|record| {
+            sheets.is_empty()
+                || sheets
+                    .iter()
+                    .any(|name| record.definition.target_sheet.eq_ignore_ascii_case(name))
+        })
+        .collect();
+    if records.is_empty() {
+        return Err(invalid_argument(format!(
+            "no materializations are recorded in '{}'",
+            source.display()
+        )));
+    }
+    Ok(records)
+}
+
+async fn run_materialize(
+    source: PathBuf,
+    mode: BatchMutationMode,
+    records: Vec<MaterializeRecord>,
+    new_workbook: bool,
+    refresh: bool,
+) -> Result<Value> {
+    let runtime = StatelessRuntime;
+    let mut jobs = Vec::with_capacity(records.len());
+    let mut warnings = Vec::new();
+    for record in records {
+        let query_source = match record.definition.source.as_deref() {
+            Some(path) => {
+                let path = PathBuf::from(path);
+                if path.is_relative() {
+                    source
+                        .parent()
+                        .map(|parent| parent.join(&path))
+                        .unwrap_or(path)
+                } else {
+                    path
+                }
+            }
+            None => source.clone(),
+        };
+        let job = run_materialize_query(&query_source, record).await?;
+        if (job.rows.len() as u32) < job.matched_rows {
+            warnings.push(format!(
+                "sheet '{}' holds {} of {} matching rows; raise the query limit to include the rest",
+                job.record.definition.target_sheet,
+                job.rows.len(),
+                job.matched_rows
+            ));
+        }
+        jobs.push(job);
+    }
+
+    let refreshed_at = chrono::Utc::now().to_rfc3339();
+    let apply = |path: &Path| -> Result<Vec<MaterializeResult>> {
+        apply_materialize_jobs_to_path(path, &jobs, new_workbook, &refreshed_at)
+    };
+    let source_path = source.display().to_string();
+    let mut response = match mode {
         BatchMutationMode::DryRun => {
-            let (apply_result, _temp_path) = apply_to_temp_copy(
+            let (results, _temp_path) =
+                apply_to_temp_copy(&source, source.parent(), ".materialize-", apply)?;
+            MaterializeResponse {
+                mode: "dry_run".to_string(),
+                file: source_path,
+                source_path: None,
+                target_path: None,
+                refresh,
+                refreshed_at,
+                results,
+                row_count_changes: None,
+                warnings,
+                would_change: Some(true),
+                changed: None,
+            }
+        }
+        BatchMutationMode::InPlace => {
+            let results =
+                apply_in_place_with_temp(&source, WriteCommit::new("materialize"), apply).await?;
+            MaterializeResponse {
+                mode: "in_place".to_string(),
+                file: source_path.clone(),
+                source_path: Some(source_path.clone()),
+                target_path: Some(source_path),
+                refresh,
+                refreshed_at,
+                results,
+                row_count_changes: None,
+                warnings,
+                would_change: None,
+                changed: Some(true),
+            }
+        }
+        BatchMutationMode::Output { target, force } => {
+            let target = runtime.normalize_destination_path(&target)?;
+            ensure_output_path_is_distinct(&source, &target)?;
+            let results = apply_to_output_with_temp(
                 &source,
-                source.parent(),
-                ".apply-formula-pattern-",
-                |path| {
-                    apply_formula_pattern_ops_to_file(path, &payload.ops)
-                        .map_err(classify_apply_error)
-                },
-            )?;
+                &target,
+                force,
+                WriteCommit::new("materialize"),
+                apply,
+            )
+            .await?;
+            let target_path = target.display().to_string();
+            MaterializeResponse {
+                mode: "output".to_string(),
+                file: target_path.clone(),
+                source_path: Some(source_path),
+                target_path: Some(target_path),
+                refresh,
+                refreshed_at,
+                results,
+                row_count_changes: None,
+                warnings,
+                would_change: None,
+                changed: Some(true),
+            }
+        }
+    };
+    if refresh {
+        response.row_count_changes = Some(
+            response
+                .results
+                .iter()
+                .filter(|result| result.row_delta.is_some_and(|delta| delta != 0))
+                .count(),
+        );
+    }
+    Ok(serde_json::to_value(response)?)
+}
+
+/// Run a stored query against `path`, returning every matching row unless the
+/// query sets its own limit.
+async fn run_materialize_query(path: &Path, record: MaterializeRecord) -> Result<MaterializeJob> {
+    let definition = &record.definition;
+    let (state, workbook_id) = StatelessRuntime.open_state_for_file(path).await?;
+    let mut params = ReadTableParams {
+        workbook_or_fork_id: workbook_id,
+        ..Default::default()
+    };
+    definition.query.apply_to(&mut params);
+    params.limit = Some(params.limit.unwrap_or(u32::MAX));
+    params.offset = None;
+    params.format = Some(crate::model::TableOutputFormat::Json);
+    let response = read_table(state, params).await.map_err(|error| {
+        invalid_argument(format!(
+            "query for sheet '{}' failed: {error:#}",
+            definition.target_sheet
+        ))
+    })?;
+    Ok(MaterializeJob {
+        record,
+        headers: response.headers,
+        rows: response.rows,
+        matched_rows: response.total_rows,
+    })
+}
+
+fn apply_materialize_jobs_to_path(
+    path: &Path,
+    jobs: &[MaterializeJob],
+    new_workbook: bool,
+    refreshed_at: &str,
+) -> Result<Vec<MaterializeResult>> {
+    let mut book = if new_workbook {
+        let mut book = umya_spreadsheet::new_file();
+        if let Some(first) = jobs.first() {
+            book.get_sheet_by_name_mut("Sheet1")
+                .ok_or_else(|| anyhow!("failed to initialize workbook default sheet"))?
+                .set_name(first.record.definition.target_sheet.as_str());
+        }
+        book
+    } else {
+        umya_spreadsheet::reader::xlsx::read(path)
+            .with_context(|| format!("failed to read workbook '{}'", path.display()))?
+    };
+
+    let mut results = Vec::with_capacity(jobs.len());
+    for job in jobs {
+        let definition = &job.record.definition;
+        let written = write_materialized_table(
+            &mut book,
+            &definition.target_sheet,
+            &definition.anchor,
+            &job.headers,
+            &job.rows,
+        )
+        .map_err(|error| invalid_argument(format!("{error:#}")))?;
+        record_materialization(&mut book, definition, written.rows, refreshed_at)?;
+        let previous_rows = job.record.rows;
+        results.push(MaterializeResult {
+            target_sheet: definition.target_sheet.clone(),
+            matched_rows: job.matched_rows,
+            previous_rows,
+            row_delta: previous_rows.map(|previous| written.rows as i64 - previous as i64),
+            previous_refreshed_at: job.record.refreshed_at.clone(),
+            written,
+            definition: definition.clone(),
+        });
+    }
+
+    save_workbook(&book, path)?;
+    Ok(results)
+}
+
+pub async fn style_batch(
+    file: PathBuf,
+    ops: String,
+    dry_run: bool,
+    in_place: bool,
+    output: Option<PathBuf>,
+    force: bool,
+    verify_with: Option<PathBuf>,
+    audit_log: Option<PathBuf>,
+    snapshot_dir: Option<PathBuf>,
+    continue_on_error: bool,
+) -> Result<Value> {
+    let runtime = StatelessRuntime;
+    let source = runtime.normalize_existing_file(&file)?;
+    let mode = validate_batch_mode(&source, dry_run, in_place, output, force)?;
+    let verify_with = load_verify_with(verify_with)?;
+    let audit_log = load_audit_log(audit_log, &ops)?;
+    let snapshot_dir = load_snapshot_dir(snapshot_dir, &mode)?;
+
+    let payload: OpsPayload<StyleOpInput> =
+        parse_ops_payload(&ops, STYLE_PAYLOAD_SHAPE, STYLE_PAYLOAD_MINIMAL_EXAMPLE)?;
+    let (normalized, base_warnings) = normalize_style_batch(StyleBatchParamsInput {
+        fork_id: String::new(),
+        ops: payload.ops,
+        mode: None,
+        label: None,
+    })
+    .map_err(|error| invalid_ops_payload(error.to_string()))?;
+
+    let (state, workbook_id) = runtime.open_state_for_file(&source).await?;
+    let workbook = state.open_workbook(&workbook_id).await?;
+    let style_op_kind = |_: &StyleOp| "style".to_string();
+    let (resolved_ops, op_outcomes, isolated) = if continue_on_error {
+        let mut outcomes = BatchOpOutcomes::default();
+        let resolved_ops = resolve_each_op(&normalized.ops, style_op_kind, &mut outcomes, |ops| {
+            resolve_style_ops_for_workbook(&workbook, ops)
+        });
+        let (resolved_ops, isolated) = isolate_failing_ops(
+            &source,
+            resolved_ops,
+            style_op_kind,
+            &mut outcomes,
+            apply_style_ops_to_file,
+        )?;
+        (resolved_ops, Some(outcomes), isolated)
+    } else {
+        let resolved_ops = resolve_style_ops_for_workbook(&workbook, &normalized.ops)
+            .map_err(|error| invalid_ops_payload(error.to_string()))?;
+        (resolved_ops, None, None)
+    };
+    let _ = state.close_workbook(&workbook_id);
+
+    let op_count = if continue_on_error {
+        normalized.ops.len()
+    } else {
+        resolved_ops.len()
+    };
+    let operation_counts = summarize_style_operation_counts(&resolved_ops);
+
+    let response = match mode {
+        BatchMutationMode::DryRun => {
+            let (apply_result, temp_path) =
+                apply_to_temp_copy(&source, source.parent(), ".style-batch-", |path| {
+                    apply_or_reuse(isolated, path, |path| {
+                        apply_style_ops_to_file(path, &resolved_ops).map_err(classify_apply_error)
+                    })
+                })?;
+            let verification = verify_staged_workbook(&temp_path, verify_with.as_ref()).await?;
 
             let result_counts = apply_result.summary.counts;
-            let warnings = warning_strings_to_cli_warnings(apply_result.summary.warnings);
-            let would_change = formula_pattern_summary_indicates_change(&result_counts);
+            let warnings = merge_cli_warnings(
+                base_warnings.clone(),
+                warning_strings_to_cli_warnings(apply_result.summary.warnings),
+            );
+            let would_change = style_summary_indicates_change(&result_counts);
 
             dry_run_response(
                 op_count,
@@ -1245,21 +2425,34 @@ pub async fn apply_formula_pattern(
                 warnings,
                 would_change,
                 None,
-                write_path_provenance.clone(),
+                None,
+                verification,
             )
         }
         BatchMutationMode::InPlace => {
-            let apply_result =
-                apply_in_place_with_temp(&source, ".apply-formula-pattern-", |path| {
-                    apply_formula_pattern_ops_to_file(path, &payload.ops)
-                        .map_err(classify_apply_error)
-                })?;
+            let (apply_result, verification) = apply_in_place_with_verify(
+                &source,
+                verify_with.as_ref(),
+                WriteCommit::new("style-batch")
+                    .with_operation_counts(&operation_counts)
+                    .with_audit_log(audit_log.as_ref())
+                    .with_snapshot_dir(snapshot_dir.as_ref()),
+                |path| {
+                    apply_or_reuse(isolated, path, |path| {
+                        apply_style_ops_to_file(path, &resolved_ops).map_err(classify_apply_error)
+                    })
+                },
+            )
+            .await?;
 
             let result_counts = apply_result.summary.counts;
-            let warnings = warning_strings_to_cli_warnings(apply_result.summary.warnings);
-            let changed = formula_pattern_summary_indicates_change(&result_counts);
+            let warnings = merge_cli_warnings(
+                base_warnings.clone(),
+                warning_strings_to_cli_warnings(apply_result.summary.warnings),
+            );
+            let changed = style_summary_indicates_change(&result_counts);
 
-            apply_response(
+            let response = apply_response(
                 op_count,
                 apply_result.ops_applied,
                 warnings,
@@ -1267,27 +2460,37 @@ pub async fn apply_formula_pattern(
                 source.display().to_string(),
                 source.display().to_string(),
                 None,
-                write_path_provenance.clone(),
-            )
+                None,
+                verification,
+            )?;
+            Ok(attach_snapshot(response, snapshot_dir.as_ref()))
         }
         BatchMutationMode::Output { target, force } => {
             let target = runtime.normalize_destination_path(&target)?;
             ensure_output_path_is_distinct(&source, &target)?;
 
-            let apply_result = apply_to_output_with_temp(
+            let (apply_result, verification) = apply_to_output_with_verify(
                 &source,
                 &target,
                 force,
-                ".apply-formula-pattern-",
+                verify_with.as_ref(),
+                WriteCommit::new("style-batch")
+                    .with_operation_counts(&operation_counts)
+                    .with_audit_log(audit_log.as_ref()),
                 |path| {
-                    apply_formula_pattern_ops_to_file(path, &payload.ops)
-                        .map_err(classify_apply_error)
+                    apply_or_reuse(isolated, path, |path| {
+                        apply_style_ops_to_file(path, &resolved_ops).map_err(classify_apply_error)
+                    })
                 },
-            )?;
+            )
+            .await?;
 
             let result_counts = apply_result.summary.counts;
-            let warnings = warning_strings_to_cli_warnings(apply_result.summary.warnings);
-            let changed = formula_pattern_summary_indicates_change(&result_counts);
+            let warnings = merge_cli_warnings(
+                base_warnings,
+                warning_strings_to_cli_warnings(apply_result.summary.warnings),
+            );
+            let changed = style_summary_indicates_change(&result_counts);
 
             apply_response(
                 op_count,
@@ -1297,39 +2500,185 @@ pub async fn apply_formula_pattern(
                 target.display().to_string(),
                 source.display().to_string(),
                 None,
-                write_path_provenance.clone(),
+                None,
+                verification,
             )
         }
-    }
+    }?;
+    Ok(attach_op_outcomes(response, op_outcomes))
 }
 
-pub async fn check_ref_impact(
+pub async fn apply_formula_pattern(
     file: PathBuf,
-    ops_ref: String,
-    show_formula_delta: bool,
+    ops: String,
+    dry_run: bool,
+    in_place: bool,
+    output: Option<PathBuf>,
+    force: bool,
+    verify_with: Option<PathBuf>,
+    audit_log: Option<PathBuf>,
+    snapshot_dir: Option<PathBuf>,
+    continue_on_error: bool,
 ) -> Result<Value> {
     let runtime = StatelessRuntime;
     let source = runtime.normalize_existing_file(&file)?;
+    let mode = validate_batch_mode(&source, dry_run, in_place, output, force)?;
+    let verify_with = load_verify_with(verify_with)?;
+    let audit_log = load_audit_log(audit_log, &ops)?;
+    let snapshot_dir = load_snapshot_dir(snapshot_dir, &mode)?;
 
-    // Load and parse the ops payload (same format as structure-batch).
-    let payload: OpsPayload<StructureOpInput> = parse_ops_payload(
-        &ops_ref,
-        STRUCTURE_PAYLOAD_SHAPE,
-        STRUCTURE_PAYLOAD_MINIMAL_EXAMPLE,
+    let payload: OpsPayload<ApplyFormulaPatternOpInput> = parse_ops_payload(
+        &ops,
+        APPLY_FORMULA_PATTERN_PAYLOAD_SHAPE,
+        APPLY_FORMULA_PATTERN_PAYLOAD_MINIMAL_EXAMPLE,
     )?;
-    let (normalized, _warnings) = normalize_structure_batch(StructureBatchParamsInput {
-        fork_id: String::new(),
-        ops: payload.ops,
-        mode: None,
-        label: None,
-        formula_parse_policy: None,
-        impact_report: None,
-        show_formula_delta: None,
-    })
-    .map_err(|error| invalid_ops_payload(error.to_string()))?;
 
-    // Call compute_structure_impact (read-only analysis, never mutates the file).
-    let (impact_report, formula_delta) = crate::tools::structure_impact::compute_structure_impact(
+    let op_count = payload.ops.len();
+    let (ops_to_apply, op_outcomes, isolated) = continue_on_error_ops(
+        continue_on_error,
+        &source,
+        payload.ops,
+        |_| "formula_pattern".to_string(),
+        apply_formula_pattern_ops_to_file,
+    )?;
+    let operation_counts = summarize_formula_pattern_operation_counts(&ops_to_apply);
+    let write_path_provenance = formula_write_provenance(
+        "apply_formula_pattern",
+        apply_formula_pattern_targets(&ops_to_apply),
+    );
+
+    let response = match mode {
+        BatchMutationMode::DryRun => {
+            let (apply_result, temp_path) = apply_to_temp_copy(
+                &source,
+                source.parent(),
+                ".apply-formula-pattern-",
+                |path| {
+                    apply_or_reuse(isolated, path, |path| {
+                        apply_formula_pattern_ops_to_file(path, &ops_to_apply)
+                            .map_err(classify_apply_error)
+                    })
+                },
+            )?;
+            let verification = verify_staged_workbook(&temp_path, verify_with.as_ref()).await?;
+
+            let result_counts = apply_result.summary.counts;
+            let warnings = warning_strings_to_cli_warnings(apply_result.summary.warnings);
+            let would_change = formula_pattern_summary_indicates_change(&result_counts);
+
+            dry_run_response(
+                op_count,
+                operation_counts,
+                result_counts,
+                warnings,
+                would_change,
+                None,
+                write_path_provenance.clone(),
+                verification,
+            )
+        }
+        BatchMutationMode::InPlace => {
+            let (apply_result, verification) = apply_in_place_with_verify(
+                &source,
+                verify_with.as_ref(),
+                WriteCommit::new("apply-formula-pattern")
+                    .with_operation_counts(&operation_counts)
+                    .with_audit_log(audit_log.as_ref())
+                    .with_snapshot_dir(snapshot_dir.as_ref()),
+                |path| {
+                    apply_or_reuse(isolated, path, |path| {
+                        apply_formula_pattern_ops_to_file(path, &ops_to_apply)
+                            .map_err(classify_apply_error)
+                    })
+                },
+            )
+            .await?;
+
+            let result_counts = apply_result.summary.counts;
+            let warnings = warning_strings_to_cli_warnings(apply_result.summary.warnings);
+            let changed = formula_pattern_summary_indicates_change(&result_counts);
+
+            let response = apply_response(
+                op_count,
+                apply_result.ops_applied,
+                warnings,
+                changed,
+                source.display().to_string(),
+                source.display().to_string(),
+                None,
+                write_path_provenance.clone(),
+                verification,
+            )?;
+            Ok(attach_snapshot(response, snapshot_dir.as_ref()))
+        }
+        BatchMutationMode::Output { target, force } => {
+            let target = runtime.normalize_destination_path(&target)?;
+            ensure_output_path_is_distinct(&source, &target)?;
+
+            let (apply_result, verification) = apply_to_output_with_verify(
+                &source,
+                &target,
+                force,
+                verify_with.as_ref(),
+                WriteCommit::new("apply-formula-pattern")
+                    .with_operation_counts(&operation_counts)
+                    .with_audit_log(audit_log.as_ref()),
+                |path| {
+                    apply_or_reuse(isolated, path, |path| {
+                        apply_formula_pattern_ops_to_file(path, &ops_to_apply)
+                            .map_err(classify_apply_error)
+                    })
+                },
+            )
+            .await?;
+
+            let result_counts = apply_result.summary.counts;
+            let warnings = warning_strings_to_cli_warnings(apply_result.summary.warnings);
+            let changed = formula_pattern_summary_indicates_change(&result_counts);
+
+            apply_response(
+                op_count,
+                apply_result.ops_applied,
+                warnings,
+                changed,
+                target.display().to_string(),
+                source.display().to_string(),
+                None,
+                write_path_provenance.clone(),
+                verification,
+            )
+        }
+    }?;
+    Ok(attach_op_outcomes(response, op_outcomes))
+}
+
+pub async fn check_ref_impact(
+    file: PathBuf,
+    ops_ref: String,
+    show_formula_delta: bool,
+) -> Result<Value> {
+    let runtime = StatelessRuntime;
+    let source = runtime.normalize_existing_file(&file)?;
+
+    // Load and parse the ops payload (same format as structure-batch).
+    let payload: OpsPayload<StructureOpInput> = parse_ops_payload(
+        &ops_ref,
+        STRUCTURE_PAYLOAD_SHAPE,
+        STRUCTURE_PAYLOAD_MINIMAL_EXAMPLE,
+    )?;
+    let (normalized, _warnings) = normalize_structure_batch(StructureBatchParamsInput {
+        fork_id: String::new(),
+        ops: payload.ops,
+        mode: None,
+        label: None,
+        formula_parse_policy: None,
+        impact_report: None,
+        show_formula_delta: None,
+    })
+    .map_err(|error| invalid_ops_payload(error.to_string()))?;
+
+    // Call compute_structure_impact (read-only analysis, never mutates the file).
+    let (impact_report, formula_delta) = crate::tools::structure_impact::compute_structure_impact(
         &source,
         &normalized.ops,
         show_formula_delta,
@@ -1356,6 +2705,10 @@ pub async fn structure_batch(
     formula_parse_policy: Option<FormulaParsePolicy>,
     impact_report: bool,
     show_formula_delta: bool,
+    verify_with: Option<PathBuf>,
+    audit_log: Option<PathBuf>,
+    snapshot_dir: Option<PathBuf>,
+    continue_on_error: bool,
 ) -> Result<Value> {
     // --impact-report and --show-formula-delta require --dry-run.
     if (impact_report || show_formula_delta) && !dry_run {
@@ -1367,14 +2720,17 @@ pub async fn structure_batch(
 
     let runtime = StatelessRuntime;
     let source = runtime.normalize_existing_file(&file)?;
-    let mode = validate_batch_mode(dry_run, in_place, output, force)?;
+    let mode = validate_batch_mode(&source, dry_run, in_place, output, force)?;
+    let verify_with = load_verify_with(verify_with)?;
+    let audit_log = load_audit_log(audit_log, &ops)?;
+    let snapshot_dir = load_snapshot_dir(snapshot_dir, &mode)?;
 
     let payload: OpsPayload<StructureOpInput> = parse_ops_payload(
         &ops,
         STRUCTURE_PAYLOAD_SHAPE,
         STRUCTURE_PAYLOAD_MINIMAL_EXAMPLE,
     )?;
-    let (normalized, base_warnings) = normalize_structure_batch(StructureBatchParamsInput {
+    let (mut normalized, base_warnings) = normalize_structure_batch(StructureBatchParamsInput {
         fork_id: String::new(),
         ops: payload.ops,
         mode: None,
@@ -1384,6 +2740,8 @@ pub async fn structure_batch(
         show_formula_delta: None,
     })
     .map_err(|error| invalid_ops_payload(error.to_string()))?;
+    resolve_copy_sheet_sources(&mut normalized.ops, &std::env::current_dir()?, false)
+        .map_err(|error| invalid_ops_payload(format!("{error:#}")))?;
 
     let policy =
         normalized
@@ -1393,15 +2751,25 @@ pub async fn structure_batch(
             ));
 
     let op_count = normalized.ops.len();
-    let operation_counts = summarize_structure_operation_counts(&normalized.ops);
+    let (ops_to_apply, op_outcomes, isolated) = continue_on_error_ops(
+        continue_on_error,
+        &source,
+        normalized.ops,
+        |op| op_kind(op, summarize_structure_operation_counts),
+        |path, ops| apply_structure_ops_to_file(path, ops, policy),
+    )?;
+    let operation_counts = summarize_structure_operation_counts(&ops_to_apply);
 
-    match mode {
+    let response = match mode {
         BatchMutationMode::DryRun => {
-            let (apply_result, _temp_path) =
+            let (apply_result, temp_path) =
                 apply_to_temp_copy(&source, source.parent(), ".structure-batch-", |path| {
-                    apply_structure_ops_to_file(path, &normalized.ops, policy)
-                        .map_err(classify_apply_error)
+                    apply_or_reuse(isolated, path, |path| {
+                        apply_structure_ops_to_file(path, &ops_to_apply, policy)
+                            .map_err(classify_apply_error)
+                    })
                 })?;
+            let verification = verify_staged_workbook(&temp_path, verify_with.as_ref()).await?;
 
             let formula_parse_diagnostics = apply_result.formula_parse_diagnostics;
             let result_counts = apply_result.summary.counts;
@@ -1419,13 +2787,14 @@ pub async fn structure_batch(
                 would_change,
                 formula_parse_diagnostics,
                 None,
+                verification,
             )?;
 
             // Attach optional impact report and formula delta preview.
             if impact_report || show_formula_delta {
                 let (report, delta) = crate::tools::structure_impact::compute_structure_impact(
                     &source,
-                    &normalized.ops,
+                    &ops_to_apply,
                     show_formula_delta,
                 )?;
                 if impact_report {
@@ -1439,10 +2808,21 @@ pub async fn structure_batch(
             Ok(response)
         }
         BatchMutationMode::InPlace => {
-            let apply_result = apply_in_place_with_temp(&source, ".structure-batch-", |path| {
-                apply_structure_ops_to_file(path, &normalized.ops, policy)
-                    .map_err(classify_apply_error)
-            })?;
+            let (apply_result, verification) = apply_in_place_with_verify(
+                &source,
+                verify_with.as_ref(),
+                WriteCommit::new("structure-batch")
+                    .with_operation_counts(&operation_counts)
+                    .with_audit_log(audit_log.as_ref())
+                    .with_snapshot_dir(snapshot_dir.as_ref()),
+                |path| {
+                    apply_or_reuse(isolated, path, |path| {
+                        apply_structure_ops_to_file(path, &ops_to_apply, policy)
+                            .map_err(classify_apply_error)
+                    })
+                },
+            )
+            .await?;
 
             let formula_parse_diagnostics = apply_result.formula_parse_diagnostics;
             let result_counts = apply_result.summary.counts;
@@ -1452,7 +2832,7 @@ pub async fn structure_batch(
             );
             let changed = structure_summary_indicates_change(&result_counts);
 
-            apply_response(
+            let response = apply_response(
                 op_count,
                 apply_result.ops_applied,
                 warnings,
@@ -1461,17 +2841,30 @@ pub async fn structure_batch(
                 source.display().to_string(),
                 formula_parse_diagnostics,
                 None,
-            )
+                verification,
+            )?;
+            Ok(attach_snapshot(response, snapshot_dir.as_ref()))
         }
         BatchMutationMode::Output { target, force } => {
             let target = runtime.normalize_destination_path(&target)?;
             ensure_output_path_is_distinct(&source, &target)?;
 
-            let apply_result =
-                apply_to_output_with_temp(&source, &target, force, ".structure-batch-", |path| {
-                    apply_structure_ops_to_file(path, &normalized.ops, policy)
-                        .map_err(classify_apply_error)
-                })?;
+            let (apply_result, verification) = apply_to_output_with_verify(
+                &source,
+                &target,
+                force,
+                verify_with.as_ref(),
+                WriteCommit::new("structure-batch")
+                    .with_operation_counts(&operation_counts)
+                    .with_audit_log(audit_log.as_ref()),
+                |path| {
+                    apply_or_reuse(isolated, path, |path| {
+                        apply_structure_ops_to_file(path, &ops_to_apply, policy)
+                            .map_err(classify_apply_error)
+                    })
+                },
+            )
+            .await?;
 
             let formula_parse_diagnostics = apply_result.formula_parse_diagnostics;
             let result_counts = apply_result.summary.counts;
@@ -1490,9 +2883,11 @@ pub async fn structure_batch(
                 source.display().to_string(),
                 formula_parse_diagnostics,
                 None,
+                verification,
             )
         }
-    }
+    }?;
+    Ok(attach_op_outcomes(response, op_outcomes))
 }
 
 pub async fn column_size_batch(
@@ -1502,10 +2897,17 @@ pub async fn column_size_batch(
     in_place: bool,
     output: Option<PathBuf>,
     force: bool,
+    verify_with: Option<PathBuf>,
+    audit_log: Option<PathBuf>,
+    snapshot_dir: Option<PathBuf>,
+    continue_on_error: bool,
 ) -> Result<Value> {
     let runtime = StatelessRuntime;
     let source = runtime.normalize_existing_file(&file)?;
-    let mode = validate_batch_mode(dry_run, in_place, output, force)?;
+    let mode = validate_batch_mode(&source, dry_run, in_place, output, force)?;
+    let verify_with = load_verify_with(verify_with)?;
+    let audit_log = load_audit_log(audit_log, &ops)?;
+    let snapshot_dir = load_snapshot_dir(snapshot_dir, &mode)?;
 
     let payload: ColumnSizeOpsPayload = parse_column_size_ops_payload(&ops)?;
     let (normalized_ops, base_warnings) =
@@ -1513,16 +2915,26 @@ pub async fn column_size_batch(
             .map_err(|error| invalid_ops_payload(error.to_string()))?;
 
     let op_count = normalized_ops.len();
-    let operation_counts = summarize_column_size_operation_counts(&normalized_ops);
+    let (ops_to_apply, op_outcomes, isolated) = continue_on_error_ops(
+        continue_on_error,
+        &source,
+        normalized_ops,
+        |op| op_kind(op, summarize_column_size_operation_counts),
+        |path, ops| apply_column_size_ops_to_file(path, &payload.sheet_name, ops),
+    )?;
+    let operation_counts = summarize_column_size_operation_counts(&ops_to_apply);
 
-    match mode {
+    let response = match mode {
         BatchMutationMode::DryRun => {
             let sheet_name = payload.sheet_name.clone();
-            let (apply_result, _temp_path) =
+            let (apply_result, temp_path) =
                 apply_to_temp_copy(&source, source.parent(), ".column-size-batch-", |path| {
-                    apply_column_size_ops_to_file(path, &sheet_name, &normalized_ops)
-                        .map_err(classify_apply_error)
+                    apply_or_reuse(isolated, path, |path| {
+                        apply_column_size_ops_to_file(path, &sheet_name, &ops_to_apply)
+                            .map_err(classify_apply_error)
+                    })
                 })?;
+            let verification = verify_staged_workbook(&temp_path, verify_with.as_ref()).await?;
 
             let result_counts = apply_result.summary.counts;
             let warnings = merge_cli_warnings(
@@ -1539,14 +2951,26 @@ pub async fn column_size_batch(
                 would_change,
                 None,
                 None,
+                verification,
             )
         }
         BatchMutationMode::InPlace => {
             let sheet_name = payload.sheet_name.clone();
-            let apply_result = apply_in_place_with_temp(&source, ".column-size-batch-", |path| {
-                apply_column_size_ops_to_file(path, &sheet_name, &normalized_ops)
-                    .map_err(classify_apply_error)
-            })?;
+            let (apply_result, verification) = apply_in_place_with_verify(
+                &source,
+                verify_with.as_ref(),
+                WriteCommit::new("column-size-batch")
+                    .with_operation_counts(&operation_counts)
+                    .with_audit_log(audit_log.as_ref())
+                    .with_snapshot_dir(snapshot_dir.as_ref()),
+                |path| {
+                    apply_or_reuse(isolated, path, |path| {
+                        apply_column_size_ops_to_file(path, &sheet_name, &ops_to_apply)
+                            .map_err(classify_apply_error)
+                    })
+                },
+            )
+            .await?;
 
             let result_counts = apply_result.summary.counts;
             let warnings = merge_cli_warnings(
@@ -1555,7 +2979,7 @@ pub async fn column_size_batch(
             );
             let changed = column_size_summary_indicates_change(&result_counts);
 
-            apply_response(
+            let response = apply_response(
                 op_count,
                 apply_result.ops_applied,
                 warnings,
@@ -1564,23 +2988,31 @@ pub async fn column_size_batch(
                 source.display().to_string(),
                 None,
                 None,
-            )
+                verification,
+            )?;
+            Ok(attach_snapshot(response, snapshot_dir.as_ref()))
         }
         BatchMutationMode::Output { target, force } => {
             let target = runtime.normalize_destination_path(&target)?;
             ensure_output_path_is_distinct(&source, &target)?;
 
             let sheet_name = payload.sheet_name;
-            let apply_result = apply_to_output_with_temp(
+            let (apply_result, verification) = apply_to_output_with_verify(
                 &source,
                 &target,
                 force,
-                ".column-size-batch-",
+                verify_with.as_ref(),
+                WriteCommit::new("column-size-batch")
+                    .with_operation_counts(&operation_counts)
+                    .with_audit_log(audit_log.as_ref()),
                 |path| {
-                    apply_column_size_ops_to_file(path, &sheet_name, &normalized_ops)
-                        .map_err(classify_apply_error)
+                    apply_or_reuse(isolated, path, |path| {
+                        apply_column_size_ops_to_file(path, &sheet_name, &ops_to_apply)
+                            .map_err(classify_apply_error)
+                    })
                 },
-            )?;
+            )
+            .await?;
 
             let result_counts = apply_result.summary.counts;
             let warnings = merge_cli_warnings(
@@ -1598,42 +3030,65 @@ pub async fn column_size_batch(
                 source.display().to_string(),
                 None,
                 None,
+                verification,
             )
         }
-    }
+    }?;
+    Ok(attach_op_outcomes(response, op_outcomes))
 }
 
-pub async fn sheet_layout_batch(
+pub async fn row_size_batch(
     file: PathBuf,
     ops: String,
     dry_run: bool,
     in_place: bool,
     output: Option<PathBuf>,
     force: bool,
+    verify_with: Option<PathBuf>,
+    audit_log: Option<PathBuf>,
+    snapshot_dir: Option<PathBuf>,
+    continue_on_error: bool,
 ) -> Result<Value> {
     let runtime = StatelessRuntime;
     let source = runtime.normalize_existing_file(&file)?;
-    let mode = validate_batch_mode(dry_run, in_place, output, force)?;
+    let mode = validate_batch_mode(&source, dry_run, in_place, output, force)?;
+    let verify_with = load_verify_with(verify_with)?;
+    let audit_log = load_audit_log(audit_log, &ops)?;
+    let snapshot_dir = load_snapshot_dir(snapshot_dir, &mode)?;
 
-    let payload: OpsPayload<SheetLayoutOp> = parse_ops_payload(
-        &ops,
-        SHEET_LAYOUT_PAYLOAD_SHAPE,
-        SHEET_LAYOUT_PAYLOAD_MINIMAL_EXAMPLE,
-    )?;
+    let payload: RowSizeOpsPayload = parse_row_size_ops_payload(&ops)?;
+    let (normalized_ops, base_warnings) =
+        normalize_row_size_payload(payload.sheet_name.clone(), payload.ops)
+            .map_err(|error| invalid_ops_payload(error.to_string()))?;
 
-    let op_count = payload.ops.len();
-    let operation_counts = summarize_sheet_layout_operation_counts(&payload.ops);
+    let op_count = normalized_ops.len();
+    let (ops_to_apply, op_outcomes, isolated) = continue_on_error_ops(
+        continue_on_error,
+        &source,
+        normalized_ops,
+        |op| op_kind(op, summarize_row_size_operation_counts),
+        |path, ops| apply_row_size_ops_to_file(path, &payload.sheet_name, ops),
+    )?;
+    let operation_counts = summarize_row_size_operation_counts(&ops_to_apply);
 
-    match mode {
+    let response = match mode {
         BatchMutationMode::DryRun => {
-            let (apply_result, _temp_path) =
-                apply_to_temp_copy(&source, source.parent(), ".sheet-layout-batch-", |path| {
-                    apply_sheet_layout_ops_to_file(path, &payload.ops).map_err(classify_apply_error)
+            let sheet_name = payload.sheet_name.clone();
+            let (apply_result, temp_path) =
+                apply_to_temp_copy(&source, source.parent(), ".row-size-batch-", |path| {
+                    apply_or_reuse(isolated, path, |path| {
+                        apply_row_size_ops_to_file(path, &sheet_name, &ops_to_apply)
+                            .map_err(classify_apply_error)
+                    })
                 })?;
+            let verification = verify_staged_workbook(&temp_path, verify_with.as_ref()).await?;
 
             let result_counts = apply_result.summary.counts;
-            let warnings = warning_strings_to_cli_warnings(apply_result.summary.warnings);
-            let would_change = sheet_layout_summary_indicates_change(&result_counts);
+            let warnings = merge_cli_warnings(
+                base_warnings.clone(),
+                warning_strings_to_cli_warnings(apply_result.summary.warnings),
+            );
+            let would_change = row_size_summary_indicates_change(&result_counts);
 
             dry_run_response(
                 op_count,
@@ -1643,18 +3098,35 @@ pub async fn sheet_layout_batch(
                 would_change,
                 None,
                 None,
+                verification,
             )
         }
         BatchMutationMode::InPlace => {
-            let apply_result = apply_in_place_with_temp(&source, ".sheet-layout-batch-", |path| {
-                apply_sheet_layout_ops_to_file(path, &payload.ops).map_err(classify_apply_error)
-            })?;
+            let sheet_name = payload.sheet_name.clone();
+            let (apply_result, verification) = apply_in_place_with_verify(
+                &source,
+                verify_with.as_ref(),
+                WriteCommit::new("row-size-batch")
+                    .with_operation_counts(&operation_counts)
+                    .with_audit_log(audit_log.as_ref())
+                    .with_snapshot_dir(snapshot_dir.as_ref()),
+                |path| {
+                    apply_or_reuse(isolated, path, |path| {
+                        apply_row_size_ops_to_file(path, &sheet_name, &ops_to_apply)
+                            .map_err(classify_apply_error)
+                    })
+                },
+            )
+            .await?;
 
             let result_counts = apply_result.summary.counts;
-            let warnings = warning_strings_to_cli_warnings(apply_result.summary.warnings);
-            let changed = sheet_layout_summary_indicates_change(&result_counts);
+            let warnings = merge_cli_warnings(
+                base_warnings.clone(),
+                warning_strings_to_cli_warnings(apply_result.summary.warnings),
+            );
+            let changed = row_size_summary_indicates_change(&result_counts);
 
-            apply_response(
+            let response = apply_response(
                 op_count,
                 apply_result.ops_applied,
                 warnings,
@@ -1663,25 +3135,38 @@ pub async fn sheet_layout_batch(
                 source.display().to_string(),
                 None,
                 None,
-            )
+                verification,
+            )?;
+            Ok(attach_snapshot(response, snapshot_dir.as_ref()))
         }
         BatchMutationMode::Output { target, force } => {
             let target = runtime.normalize_destination_path(&target)?;
             ensure_output_path_is_distinct(&source, &target)?;
 
-            let apply_result = apply_to_output_with_temp(
+            let sheet_name = payload.sheet_name;
+            let (apply_result, verification) = apply_to_output_with_verify(
                 &source,
                 &target,
                 force,
-                ".sheet-layout-batch-",
+                verify_with.as_ref(),
+                WriteCommit::new("row-size-batch")
+                    .with_operation_counts(&operation_counts)
+                    .with_audit_log(audit_log.as_ref()),
                 |path| {
-                    apply_sheet_layout_ops_to_file(path, &payload.ops).map_err(classify_apply_error)
+                    apply_or_reuse(isolated, path, |path| {
+                        apply_row_size_ops_to_file(path, &sheet_name, &ops_to_apply)
+                            .map_err(classify_apply_error)
+                    })
                 },
-            )?;
+            )
+            .await?;
 
             let result_counts = apply_result.summary.counts;
-            let warnings = warning_strings_to_cli_warnings(apply_result.summary.warnings);
-            let changed = sheet_layout_summary_indicates_change(&result_counts);
+            let warnings = merge_cli_warnings(
+                base_warnings,
+                warning_strings_to_cli_warnings(apply_result.summary.warnings),
+            );
+            let changed = row_size_summary_indicates_change(&result_counts);
 
             apply_response(
                 op_count,
@@ -1692,46 +3177,62 @@ pub async fn sheet_layout_batch(
                 source.display().to_string(),
                 None,
                 None,
+                verification,
             )
         }
-    }
+    }?;
+    Ok(attach_op_outcomes(response, op_outcomes))
 }
 
-pub async fn rules_batch(
+pub async fn sheet_layout_batch(
     file: PathBuf,
     ops: String,
     dry_run: bool,
     in_place: bool,
     output: Option<PathBuf>,
     force: bool,
-    formula_parse_policy: Option<FormulaParsePolicy>,
+    verify_with: Option<PathBuf>,
+    audit_log: Option<PathBuf>,
+    snapshot_dir: Option<PathBuf>,
+    continue_on_error: bool,
 ) -> Result<Value> {
     let runtime = StatelessRuntime;
     let source = runtime.normalize_existing_file(&file)?;
-    let mode = validate_batch_mode(dry_run, in_place, output, force)?;
-
-    let payload: OpsPayload<RulesOp> =
-        parse_ops_payload(&ops, RULES_PAYLOAD_SHAPE, RULES_PAYLOAD_MINIMAL_EXAMPLE)?;
+    let mode = validate_batch_mode(&source, dry_run, in_place, output, force)?;
+    let verify_with = load_verify_with(verify_with)?;
+    let audit_log = load_audit_log(audit_log, &ops)?;
+    let snapshot_dir = load_snapshot_dir(snapshot_dir, &mode)?;
 
-    let policy = formula_parse_policy.unwrap_or(FormulaParsePolicy::default_for_command_class(
-        CommandClass::BatchWrite,
-    ));
+    let payload: OpsPayload<SheetLayoutOp> = parse_ops_payload(
+        &ops,
+        SHEET_LAYOUT_PAYLOAD_SHAPE,
+        SHEET_LAYOUT_PAYLOAD_MINIMAL_EXAMPLE,
+    )?;
 
     let op_count = payload.ops.len();
-    let operation_counts = summarize_rules_operation_counts(&payload.ops);
+    let (ops_to_apply, op_outcomes, isolated) = continue_on_error_ops(
+        continue_on_error,
+        &source,
+        payload.ops,
+        |op| op_kind(op, summarize_sheet_layout_operation_counts),
+        apply_sheet_layout_ops_to_file,
+    )?;
+    let operation_counts = summarize_sheet_layout_operation_counts(&ops_to_apply);
 
-    match mode {
+    let response = match mode {
         BatchMutationMode::DryRun => {
-            let (apply_result, _temp_path) =
-                apply_to_temp_copy(&source, source.parent(), ".rules-batch-", |path| {
-                    apply_rules_ops_to_file(path, &payload.ops, policy)
-                        .map_err(classify_apply_error)
+            let (apply_result, temp_path) =
+                apply_to_temp_copy(&source, source.parent(), ".sheet-layout-batch-", |path| {
+                    apply_or_reuse(isolated, path, |path| {
+                        apply_sheet_layout_ops_to_file(path, &ops_to_apply)
+                            .map_err(classify_apply_error)
+                    })
                 })?;
+            let verification = verify_staged_workbook(&temp_path, verify_with.as_ref()).await?;
 
-            let formula_parse_diagnostics = apply_result.formula_parse_diagnostics;
             let result_counts = apply_result.summary.counts;
             let warnings = warning_strings_to_cli_warnings(apply_result.summary.warnings);
-            let would_change = rules_summary_indicates_change(&result_counts);
+            let would_change = sheet_layout_summary_indicates_change(&result_counts);
 
             dry_run_response(
                 op_count,
@@ -1739,46 +3240,70 @@ pub async fn rules_batch(
                 result_counts,
                 warnings,
                 would_change,
-                formula_parse_diagnostics,
                 None,
+                None,
+                verification,
             )
         }
         BatchMutationMode::InPlace => {
-            let apply_result = apply_in_place_with_temp(&source, ".rules-batch-", |path| {
-                apply_rules_ops_to_file(path, &payload.ops, policy).map_err(classify_apply_error)
-            })?;
+            let (apply_result, verification) = apply_in_place_with_verify(
+                &source,
+                verify_with.as_ref(),
+                WriteCommit::new("sheet-layout-batch")
+                    .with_operation_counts(&operation_counts)
+                    .with_audit_log(audit_log.as_ref())
+                    .with_snapshot_dir(snapshot_dir.as_ref()),
+                |path| {
+                    apply_or_reuse(isolated, path, |path| {
+                        apply_sheet_layout_ops_to_file(path, &ops_to_apply)
+                            .map_err(classify_apply_error)
+                    })
+                },
+            )
+            .await?;
 
-            let formula_parse_diagnostics = apply_result.formula_parse_diagnostics;
             let result_counts = apply_result.summary.counts;
             let warnings = warning_strings_to_cli_warnings(apply_result.summary.warnings);
-            let changed = rules_summary_indicates_change(&result_counts);
+            let changed = sheet_layout_summary_indicates_change(&result_counts);
 
-            apply_response(
+            let response = apply_response(
                 op_count,
                 apply_result.ops_applied,
                 warnings,
                 changed,
                 source.display().to_string(),
                 source.display().to_string(),
-                formula_parse_diagnostics,
                 None,
-            )
+                None,
+                verification,
+            )?;
+            Ok(attach_snapshot(response, snapshot_dir.as_ref()))
         }
         BatchMutationMode::Output { target, force } => {
             let target = runtime.normalize_destination_path(&target)?;
             ensure_output_path_is_distinct(&source, &target)?;
 
-            let apply_result =
-                apply_to_output_with_temp(&source, &target, force, ".rules-batch-", |path| {
-                    apply_rules_ops_to_file(path, &payload.ops, policy)
-                        .map_err(classify_apply_error)
-                })?;
-
-            let formula_parse_diagnostics = apply_result.formula_parse_diagnostics;
-            let result_counts = apply_result.summary.counts;
-            let warnings = warning_strings_to_cli_warnings(apply_result.summary.warnings);
-            let changed = rules_summary_indicates_change(&result_counts);
-
+            let (apply_result, verification) = apply_to_output_with_verify(
+                &source,
+                &target,
+                force,
+                verify_with.as_ref(),
+                WriteCommit::new("sheet-layout-batch")
+                    .with_operation_counts(&operation_counts)
+                    .with_audit_log(audit_log.as_ref()),
+                |path| {
+                    apply_or_reuse(isolated, path, |path| {
+                        apply_sheet_layout_ops_to_file(path, &ops_to_apply)
+                            .map_err(classify_apply_error)
+                    })
+                },
+            )
+            .await?;
+
+            let result_counts = apply_result.summary.counts;
+            let warnings = warning_strings_to_cli_warnings(apply_result.summary.warnings);
+            let changed = sheet_layout_summary_indicates_change(&result_counts);
+
             apply_response(
                 op_count,
                 apply_result.ops_applied,
@@ -1786,1074 +3311,2654 @@ pub async fn rules_batch(
                 changed,
                 target.display().to_string(),
                 source.display().to_string(),
-                formula_parse_diagnostics,
                 None,
+                None,
+                verification,
             )
         }
-    }
+    }?;
+    Ok(attach_op_outcomes(response, op_outcomes))
 }
 
-fn validate_edit_mode(
+#[allow(clippy::too_many_arguments)]
+pub async fn rules_batch(
+    file: PathBuf,
+    ops: String,
     dry_run: bool,
     in_place: bool,
     output: Option<PathBuf>,
     force: bool,
-) -> Result<EditMutationMode> {
-    if force && output.is_none() {
-        return Err(invalid_argument("--force requires --output <PATH>"));
-    }
+    formula_parse_policy: Option<FormulaParsePolicy>,
+    verify_with: Option<PathBuf>,
+    audit_log: Option<PathBuf>,
+    snapshot_dir: Option<PathBuf>,
+    continue_on_error: bool,
+) -> Result<Value> {
+    let runtime = StatelessRuntime;
+    let source = runtime.normalize_existing_file(&file)?;
+    let mode = validate_batch_mode(&source, dry_run, in_place, output, force)?;
+    let verify_with = load_verify_with(verify_with)?;
+    let audit_log = load_audit_log(audit_log, &ops)?;
+    let snapshot_dir = load_snapshot_dir(snapshot_dir, &mode)?;
 
-    if dry_run {
-        if in_place {
-            return Err(invalid_argument(
-                "--dry-run cannot be combined with --in-place",
-            ));
+    let payload: OpsPayload<RulesOp> =
+        parse_ops_payload(&ops, RULES_PAYLOAD_SHAPE, RULES_PAYLOAD_MINIMAL_EXAMPLE)?;
+
+    let policy = formula_parse_policy.unwrap_or(FormulaParsePolicy::default_for_command_class(
+        CommandClass::BatchWrite,
+    ));
+
+    let op_count = payload.ops.len();
+    let (ops_to_apply, op_outcomes, isolated) = continue_on_error_ops(
+        continue_on_error,
+        &source,
+        payload.ops,
+        |op| op_kind(op, summarize_rules_operation_counts),
+        |path, ops| apply_rules_ops_to_file(path, ops, policy),
+    )?;
+    let operation_counts = summarize_rules_operation_counts(&ops_to_apply);
+
+    let response = match mode {
+        BatchMutationMode::DryRun => {
+            let (apply_result, temp_path) =
+                apply_to_temp_copy(&source, source.parent(), ".rules-batch-", |path| {
+                    apply_or_reuse(isolated, path, |path| {
+                        apply_rules_ops_to_file(path, &ops_to_apply, policy)
+                            .map_err(classify_apply_error)
+                    })
+                })?;
+            let verification = verify_staged_workbook(&temp_path, verify_with.as_ref()).await?;
+
+            let formula_parse_diagnostics = apply_result.formula_parse_diagnostics;
+            let result_counts = apply_result.summary.counts;
+            let warnings = warning_strings_to_cli_warnings(apply_result.summary.warnings);
+            let would_change = rules_summary_indicates_change(&result_counts);
+
+            dry_run_response(
+                op_count,
+                operation_counts,
+                result_counts,
+                warnings,
+                would_change,
+                formula_parse_diagnostics,
+                None,
+                verification,
+            )
         }
-        if output.is_some() {
-            return Err(invalid_argument(
-                "--dry-run cannot be combined with --output <PATH>",
-            ));
+        BatchMutationMode::InPlace => {
+            let (apply_result, verification) = apply_in_place_with_verify(
+                &source,
+                verify_with.as_ref(),
+                WriteCommit::new("rules-batch")
+                    .with_operation_counts(&operation_counts)
+                    .with_audit_log(audit_log.as_ref())
+                    .with_snapshot_dir(snapshot_dir.as_ref()),
+                |path| {
+                    apply_or_reuse(isolated, path, |path| {
+                        apply_rules_ops_to_file(path, &ops_to_apply, policy)
+                            .map_err(classify_apply_error)
+                    })
+                },
+            )
+            .await?;
+
+            let formula_parse_diagnostics = apply_result.formula_parse_diagnostics;
+            let result_counts = apply_result.summary.counts;
+            let warnings = warning_strings_to_cli_warnings(apply_result.summary.warnings);
+            let changed = rules_summary_indicates_change(&result_counts);
+
+            let response = apply_response(
+                op_count,
+                apply_result.ops_applied,
+                warnings,
+                changed,
+                source.display().to_string(),
+                source.display().to_string(),
+                formula_parse_diagnostics,
+                None,
+                verification,
+            )?;
+            Ok(attach_snapshot(response, snapshot_dir.as_ref()))
         }
-        return Ok(EditMutationMode::DryRun);
-    }
+        BatchMutationMode::Output { target, force } => {
+            let target = runtime.normalize_destination_path(&target)?;
+            ensure_output_path_is_distinct(&source, &target)?;
 
-    if in_place && output.is_some() {
-        return Err(invalid_argument(
-            "--in-place cannot be combined with --output <PATH>",
-        ));
-    }
+            let (apply_result, verification) = apply_to_output_with_verify(
+                &source,
+                &target,
+                force,
+                verify_with.as_ref(),
+                WriteCommit::new("rules-batch")
+                    .with_operation_counts(&operation_counts)
+                    .with_audit_log(audit_log.as_ref()),
+                |path| {
+                    apply_or_reuse(isolated, path, |path| {
+                        apply_rules_ops_to_file(path, &ops_to_apply, policy)
+                            .map_err(classify_apply_error)
+                    })
+                },
+            )
+            .await?;
 
-    if let Some(target) = output {
-        return Ok(EditMutationMode::Output { target, force });
-    }
+            let formula_parse_diagnostics = apply_result.formula_parse_diagnostics;
+            let result_counts = apply_result.summary.counts;
+            let warnings = warning_strings_to_cli_warnings(apply_result.summary.warnings);
+            let changed = rules_summary_indicates_change(&result_counts);
 
-    Ok(EditMutationMode::InPlace)
+            apply_response(
+                op_count,
+                apply_result.ops_applied,
+                warnings,
+                changed,
+                target.display().to_string(),
+                source.display().to_string(),
+                formula_parse_diagnostics,
+                None,
+                verification,
+            )
+        }
+    }?;
+    Ok(attach_op_outcomes(response, op_outcomes))
 }
 
-fn validate_batch_mode(
+#[allow(clippy::too_many_arguments)]
+pub async fn properties_batch(
+    file: PathBuf,
+    ops: String,
     dry_run: bool,
     in_place: bool,
     output: Option<PathBuf>,
     force: bool,
-) -> Result<BatchMutationMode> {
-    if force && output.is_none() {
-        return Err(invalid_argument("--force requires --output <PATH>"));
+    verify_with: Option<PathBuf>,
+    audit_log: Option<PathBuf>,
+    snapshot_dir: Option<PathBuf>,
+    continue_on_error: bool,
+) -> Result<Value> {
+    let runtime = StatelessRuntime;
+    let source = runtime.normalize_existing_file(&file)?;
+    let mode = validate_batch_mode(&source, dry_run, in_place, output, force)?;
+    let verify_with = load_verify_with(verify_with)?;
+    let audit_log = load_audit_log(audit_log, &ops)?;
+    let snapshot_dir = load_snapshot_dir(snapshot_dir, &mode)?;
+
+    let payload: OpsPayload<PropertyOp> = parse_ops_payload(
+        &ops,
+        PROPERTIES_PAYLOAD_SHAPE,
+        PROPERTIES_PAYLOAD_MINIMAL_EXAMPLE,
+    )?;
+
+    let op_count = payload.ops.len();
+    let (ops_to_apply, op_outcomes, isolated) = continue_on_error_ops(
+        continue_on_error,
+        &source,
+        payload.ops,
+        |op| op.kind().to_string(),
+        apply_property_ops,
+    )?;
+    let mut operation_counts = BTreeMap::new();
+    for op in &ops_to_apply {
+        *operation_counts.entry(op.kind().to_string()).or_insert(0) += 1;
     }
+    let apply = |path: &Path| {
+        apply_or_reuse(isolated, path, |path| {
+            apply_property_ops(path, &ops_to_apply).map_err(classify_apply_error)
+        })
+    };
 
-    if dry_run {
-        if in_place {
-            return Err(invalid_argument(
-                "--dry-run cannot be combined with --in-place",
-            ));
+    let response = match mode {
+        BatchMutationMode::DryRun => {
+            let (apply_result, temp_path) =
+                apply_to_temp_copy(&source, source.parent(), ".properties-batch-", apply)?;
+            let verification = verify_staged_workbook(&temp_path, verify_with.as_ref()).await?;
+            let would_change = properties_summary_indicates_change(&apply_result.counts);
+            dry_run_response(
+                op_count,
+                operation_counts,
+                apply_result.counts,
+                warning_strings_to_cli_warnings(apply_result.warnings),
+                would_change,
+                None,
+                None,
+                verification,
+            )
         }
-        if output.is_some() {
-            return Err(invalid_argument(
-                "--dry-run cannot be combined with --output <PATH>",
-            ));
+        BatchMutationMode::InPlace => {
+            let (apply_result, verification) = apply_in_place_with_verify(
+                &source,
+                verify_with.as_ref(),
+                WriteCommit::new("properties-batch")
+                    .with_operation_counts(&operation_counts)
+                    .with_audit_log(audit_log.as_ref())
+                    .with_snapshot_dir(snapshot_dir.as_ref()),
+                apply,
+            )
+            .await?;
+            let changed = properties_summary_indicates_change(&apply_result.counts);
+            let response = apply_response(
+                op_count,
+                apply_result.ops_applied,
+                warning_strings_to_cli_warnings(apply_result.warnings),
+                changed,
+                source.display().to_string(),
+                source.display().to_string(),
+                None,
+                None,
+                verification,
+            )?;
+            Ok(attach_snapshot(response, snapshot_dir.as_ref()))
         }
-        return Ok(BatchMutationMode::DryRun);
-    }
-
-    if in_place && output.is_some() {
-        return Err(invalid_argument(
-            "--in-place cannot be combined with --output <PATH>",
-        ));
-    }
+        BatchMutationMode::Output { target, force } => {
+            let target = runtime.normalize_destination_path(&target)?;
+            ensure_output_path_is_distinct(&source, &target)?;
+            let (apply_result, verification) = apply_to_output_with_verify(
+                &source,
+                &target,
+                force,
+                verify_with.as_ref(),
+                WriteCommit::new("properties-batch")
+                    .with_operation_counts(&operation_counts)
+                    .with_audit_log(audit_log.as_ref()),
+                apply,
+            )
+            .await?;
+            let changed = properties_summary_indicates_change(&apply_result.counts);
+            apply_response(
+                op_count,
+                apply_result.ops_applied,
+                warning_strings_to_cli_warnings(apply_result.warnings),
+                changed,
+                target.display().to_string(),
+                source.display().to_string(),
+                None,
+                None,
+                verification,
+            )
+        }
+    }?;
+    Ok(attach_op_outcomes(response, op_outcomes))
+}
 
-    if in_place {
-        return Ok(BatchMutationMode::InPlace);
-    }
+fn properties_summary_indicates_change(counts: &BTreeMap<String, u64>) -> bool {
+    any_count_non_zero(
+        counts,
+        &[
+            "core_fields_set",
+            "app_fields_set",
+            "custom_set",
+            "custom_removed",
+        ],
+    )
+}
 
-    if let Some(target) = output {
-        return Ok(BatchMutationMode::Output { target, force });
-    }
+/// `--plan` payload for `apply-plan`: batch payloads applied in order to one
+/// staged copy of the workbook.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ApplyPlanPayload {
+    steps: Vec<ApplyPlanStepInput>,
+}
 
-    Err(invalid_argument(
-        "choose exactly one mutation mode: --dry-run, --in-place, or --output <PATH>",
-    ))
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum ApplyPlanStepInput {
+    Transform { ops: Vec<TransformOp> },
+    Style { ops: Vec<StyleOpInput> },
+    Structure { ops: Vec<StructureOpInput> },
+    Rules { ops: Vec<RulesOp> },
 }
 
-fn parse_ops_payload_object(raw: &str, guidance: &str) -> Result<serde_json::Map<String, Value>> {
-    let path = raw
-        .strip_prefix('@')
-        .ok_or_else(|| invalid_ops_payload("--ops must be provided as @<path>"))?;
-    if path.is_empty() {
-        return Err(invalid_ops_payload(
-            "--ops file reference cannot be empty; expected @<path>",
-        ));
-    }
+const APPLY_PLAN_PAYLOAD_SHAPE: &str =
+    r#"{"steps":[{"kind":"transform|style|structure|rules","ops":[...]}]}"#;
+const APPLY_PLAN_PAYLOAD_MINIMAL_EXAMPLE: &str = r#"{"steps":[{"kind":"structure","ops":[{"kind":"insert_rows","sheet_name":"Sheet1","at_row":2,"count":1}]},{"kind":"transform","ops":[{"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"cells","cells":["B2"]},"value":"0"}]}]}"#;
 
-    let raw_payload = fs::read_to_string(path).map_err(|error| {
-        invalid_ops_payload(format!("unable to read ops payload '{}': {}", path, error))
-    })?;
+enum PlanStepOps {
+    Transform(Vec<TransformOp>),
+    Style(Vec<StyleOp>),
+    Structure(Vec<StructureOp>),
+    Rules(Vec<RulesOp>),
+}
 
-    let json_value: serde_json::Value = serde_json::from_str(&raw_payload).map_err(|error| {
-        invalid_ops_payload(format!(
-            "ops payload is not valid JSON: {error}; {guidance}"
-        ))
-    })?;
+/// A plan step with its ops validated and resolved against the source
+/// workbook, ready to apply.
+struct ResolvedPlanStep {
+    ops: PlanStepOps,
+    warnings: Vec<Warning>,
+    formula_parse_diagnostics: Option<FormulaParseDiagnostics>,
+}
 
-    let object = json_value.as_object().ok_or_else(|| {
-        invalid_ops_payload(format!("ops payload must be a JSON object; {guidance}"))
-    })?;
+impl ResolvedPlanStep {
+    fn kind(&self) -> &'static str {
+        match self.ops {
+            PlanStepOps::Transform(_) => "transform",
+            PlanStepOps::Style(_) => "style",
+            PlanStepOps::Structure(_) => "structure",
+            PlanStepOps::Rules(_) => "rules",
+        }
+    }
 
-    Ok(object.clone())
+    fn op_count(&self) -> usize {
+        match &self.ops {
+            PlanStepOps::Transform(ops) => ops.len(),
+            PlanStepOps::Style(ops) => ops.len(),
+            PlanStepOps::Structure(ops) => ops.len(),
+            PlanStepOps::Rules(ops) => ops.len(),
+        }
+    }
+
+    fn operation_counts(&self) -> BTreeMap<String, u64> {
+        match &self.ops {
+            PlanStepOps::Transform(ops) => summarize_transform_operation_counts(ops),
+            PlanStepOps::Style(ops) => summarize_style_operation_counts(ops),
+            PlanStepOps::Structure(ops) => summarize_structure_operation_counts(ops),
+            PlanStepOps::Rules(ops) => summarize_rules_operation_counts(ops),
+        }
+    }
 }
 
-fn parse_column_size_ops_payload(raw: &str) -> Result<ColumnSizeOpsPayload> {
-    let guidance = format!(
-        "expected top-level shape: {} OR {}; minimal valid example: {} OR {}",
-        COLUMN_SIZE_PAYLOAD_SHAPE,
-        COLUMN_SIZE_PAYLOAD_ALTERNATE_SHAPE,
-        COLUMN_SIZE_PAYLOAD_MINIMAL_EXAMPLE,
-        COLUMN_SIZE_PAYLOAD_ALTERNATE_EXAMPLE,
-    );
-
-    let object = parse_ops_payload_object(raw, &guidance)?;
+#[derive(Debug, Serialize)]
+struct ApplyPlanStepResult {
+    index: usize,
+    kind: &'static str,
+    op_count: usize,
+    applied_count: usize,
+    changed: bool,
+    result_counts: BTreeMap<String, u64>,
+    warnings: Vec<Warning>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    formula_parse_diagnostics: Option<FormulaParseDiagnostics>,
+}
 
-    if object.contains_key("sheet_name") {
-        let top_level_sheet = object
-            .get("sheet_name")
-            .and_then(Value::as_str)
-            .map(str::to_string);
+#[derive(Debug, Serialize)]
+struct ApplyPlanResponse {
+    dry_run: bool,
+    step_count: usize,
+    op_count: usize,
+    changed: bool,
+    /// Per-op counts across all steps, keyed `<step kind>.<op kind>`.
+    operation_counts: BTreeMap<String, u64>,
+    steps: Vec<ApplyPlanStepResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    target_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    verification: Option<ExpectationReport>,
+}
 
-        if let (Some(top_level_sheet), Some(ops_array)) =
-            (top_level_sheet, object.get("ops").and_then(Value::as_array))
-        {
-            for (index, raw_entry) in ops_array.iter().enumerate() {
-                if let Some(per_op_sheet) = raw_entry
-                    .as_object()
-                    .and_then(|entry| entry.get("sheet_name"))
-                    .and_then(Value::as_str)
-                    && per_op_sheet != top_level_sheet
-                {
-                    return Err(invalid_ops_payload(format!(
-                        "ops payload has mixed sheet_name values between top-level and ops[{index}] ('{}' vs '{}'); {guidance}",
-                        top_level_sheet, per_op_sheet
-                    )));
-                }
-            }
-        }
+#[allow(clippy::too_many_arguments)]
+pub async fn apply_plan(
+    file: PathBuf,
+    plan: String,
+    dry_run: bool,
+    in_place: bool,
+    output: Option<PathBuf>,
+    force: bool,
+    formula_parse_policy: Option<FormulaParsePolicy>,
+    verify_with: Option<PathBuf>,
+    audit_log: Option<PathBuf>,
+    snapshot_dir: Option<PathBuf>,
+) -> Result<Value> {
+    let runtime = StatelessRuntime;
+    let source = runtime.normalize_existing_file(&file)?;
+    let mode = validate_batch_mode(&source, dry_run, in_place, output, force)?;
+    let verify_with = load_verify_with(verify_with)?;
+    let audit_log = load_audit_log(audit_log, &plan)?;
+    let snapshot_dir = load_snapshot_dir(snapshot_dir, &mode)?;
 
-        return serde_json::from_value(Value::Object(object)).map_err(|error| {
-            invalid_ops_payload(format!(
-                "ops payload does not match required schema: {error}; {guidance}"
-            ))
-        });
+    let payload = parse_plan_payload(&plan)?;
+    if payload.steps.is_empty() {
+        return Err(invalid_ops_payload("plan must contain at least one step"));
     }
+    let policy = formula_parse_policy.unwrap_or(FormulaParsePolicy::default_for_command_class(
+        CommandClass::BatchWrite,
+    ));
 
-    let ops_value = object.get("ops").ok_or_else(|| {
-        invalid_ops_payload(format!("ops payload must include 'ops'; {guidance}"))
-    })?;
-    let ops_array = ops_value.as_array().ok_or_else(|| {
-        invalid_ops_payload(format!(
-            "ops payload field 'ops' must be an array; {guidance}"
-        ))
-    })?;
+    let response = run_plan_steps(
+        "apply-plan",
+        &source,
+        mode,
+        payload,
+        policy,
+        verify_with.as_ref(),
+        audit_log.as_ref(),
+        snapshot_dir.as_ref(),
+    )
+    .await?;
+    Ok(attach_snapshot(
+        serde_json::to_value(response)?,
+        snapshot_dir.as_ref(),
+    ))
+}
 
-    let mut normalized_ops = Vec::with_capacity(ops_array.len());
-    let mut inferred_sheet_name: Option<String> = None;
+/// Resolve every step of `payload` against `source` and apply them in `mode`,
+/// for `apply-plan` and `apply-state`.
+#[allow(clippy::too_many_arguments)]
+async fn run_plan_steps(
+    command: &'static str,
+    source: &Path,
+    mode: BatchMutationMode,
+    payload: ApplyPlanPayload,
+    policy: FormulaParsePolicy,
+    verify_with: Option<&VerifyWithGate>,
+    audit_log: Option<&AuditLog>,
+    snapshot_dir: Option<&SnapshotDir>,
+) -> Result<ApplyPlanResponse> {
+    let runtime = StatelessRuntime;
+    let dry_run = matches!(mode, BatchMutationMode::DryRun);
 
-    for (index, raw_entry) in ops_array.iter().enumerate() {
-        let op_with_sheet: ColumnSizeOpWithSheetInput = serde_json::from_value(raw_entry.clone())
-            .map_err(|error| {
-            invalid_ops_payload(format!(
-                "ops payload does not match required schema at ops[{index}]: {error}; {guidance}"
-            ))
-        })?;
+    // Every step is validated and resolved before anything is staged, so a
+    // bad payload late in the plan fails without touching the file.
+    let (state, workbook_id) = runtime.open_state_for_file(source).await?;
+    let workbook = state.open_workbook(&workbook_id).await?;
+    let steps = payload
+        .steps
+        .into_iter()
+        .enumerate()
+        .map(|(index, step)| {
+            resolve_plan_step(&workbook, step, policy)
+                .map_err(|error| plan_step_error(index, error))
+        })
+        .collect::<Result<Vec<_>>>();
+    let _ = state.close_workbook(&workbook_id);
+    let steps = steps?;
 
-        let sheet_name = op_with_sheet.sheet_name().to_string();
-        match &inferred_sheet_name {
-            Some(existing) if existing != &sheet_name => {
-                return Err(invalid_ops_payload(format!(
-                    "ops payload has mixed sheet_name values in per-op shape; found '{}' and '{}'; {guidance}",
-                    existing, sheet_name
-                )));
-            }
-            None => inferred_sheet_name = Some(sheet_name),
-            _ => {}
+    let step_count = steps.len();
+    let op_count = steps.iter().map(ResolvedPlanStep::op_count).sum();
+    let mut operation_counts = BTreeMap::new();
+    for step in &steps {
+        for (key, count) in step.operation_counts() {
+            *operation_counts
+                .entry(format!("{}.{}", step.kind(), key))
+                .or_insert(0) += count;
         }
-
-        normalized_ops.push(op_with_sheet.into_op_input());
     }
+    let apply_steps = |path: &Path| apply_plan_steps(path, steps, policy);
 
-    let sheet_name = inferred_sheet_name.ok_or_else(|| {
+    let (step_results, verification, source_path, target_path) = match mode {
+        BatchMutationMode::DryRun => {
+            let (step_results, temp_path) =
+                apply_to_temp_copy(source, source.parent(), ".apply-plan-", apply_steps)?;
+            let verification = verify_staged_workbook(&temp_path, verify_with).await?;
+            (step_results, verification, None, None)
+        }
+        BatchMutationMode::InPlace => {
+            let (step_results, verification) = apply_in_place_with_verify(
+                source,
+                verify_with,
+                WriteCommit::new(command)
+                    .with_operation_counts(&operation_counts)
+                    .with_audit_log(audit_log)
+                    .with_snapshot_dir(snapshot_dir),
+                apply_steps,
+            )
+            .await?;
+            let path = source.display().to_string();
+            (step_results, verification, Some(path.clone()), Some(path))
+        }
+        BatchMutationMode::Output { target, force } => {
+            let target = runtime.normalize_destination_path(&target)?;
+            ensure_output_path_is_distinct(source, &target)?;
+            let (step_results, verification) = apply_to_output_with_verify(
+                source,
+                &target,
+                force,
+                verify_with,
+                WriteCommit::new(command)
+                    .with_operation_counts(&operation_counts)
+                    .with_audit_log(audit_log),
+                apply_steps,
+            )
+            .await?;
+            (
+                step_results,
+                verification,
+                Some(source.display().to_string()),
+                Some(target.display().to_string()),
+            )
+        }
+    };
+
+    Ok(ApplyPlanResponse {
+        dry_run,
+        step_count,
+        op_count,
+        changed: step_results.iter().any(|step| step.changed),
+        operation_counts,
+        steps: step_results,
+        source_path,
+        target_path,
+        verification,
+    })
+}
+
+fn parse_plan_payload(raw: &str) -> Result<ApplyPlanPayload> {
+    let guidance = format!(
+        "expected top-level shape: {APPLY_PLAN_PAYLOAD_SHAPE}; minimal valid example: {APPLY_PLAN_PAYLOAD_MINIMAL_EXAMPLE}"
+    );
+    let path = raw
+        .strip_prefix('@')
+        .filter(|path| !path.is_empty())
+        .ok_or_else(|| invalid_ops_payload("--plan must be provided as @<path>"))?;
+    let raw_payload = fs::read_to_string(path).map_err(|error| {
+        invalid_ops_payload(format!("unable to read plan '{}': {}", path, error))
+    })?;
+    serde_json::from_str(&raw_payload).map_err(|error| {
         invalid_ops_payload(format!(
-            "ops payload must provide top-level sheet_name or per-op sheet_name values; {guidance}"
+            "plan does not match required schema: {error}; {guidance}"
         ))
-    })?;
-
-    Ok(ColumnSizeOpsPayload {
-        sheet_name,
-        ops: normalized_ops,
     })
 }
 
-#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
-#[serde(rename_all = "snake_case")]
-enum AppendRegionTargetKind {
-    DetectedRegion,
-    Table,
+const DESIRED_STATE_SPEC_SHAPE: &str = r#"{"targets":[{"sheet_name":"...","anchor":"A1","rows":[[...]],"styles":[{"range":"...","patch":{...}}]}]}"#;
+const DESIRED_STATE_SPEC_MINIMAL_EXAMPLE: &str = r#"{"targets":[{"sheet_name":"Sheet1","anchor":"A1","rows":[["Region","Amount"],["North",10],["Total","=SUM(B2:B2)"]],"styles":[{"range":"A1:B1","patch":{"font":{"bold":true}}}]}]}"#;
+
+/// Changes listed in a `plan` or `apply-state` response; the emitted plan
+/// is never cut short.
+const MAX_PLAN_CHANGES: usize = 200;
+
+/// Differences between a desired-state spec and the workbook.
+#[derive(Debug, Serialize)]
+struct DriftReport {
+    cells_checked: u32,
+    cells_to_write: u32,
+    cells_to_clear: u32,
+    cells_to_restyle: u32,
+    op_count: usize,
+    changes: Vec<PlannedChange>,
+    changes_truncated: bool,
+}
+
+impl DriftReport {
+    fn from_plan(plan: &mut ConvergencePlan) -> Self {
+        let mut changes = std::mem::take(&mut plan.changes);
+        let changes_truncated = changes.len() > MAX_PLAN_CHANGES;
+        changes.truncate(MAX_PLAN_CHANGES);
+        Self {
+            cells_checked: plan.cells_checked,
+            cells_to_write: plan.cells_to_write,
+            cells_to_clear: plan.cells_to_clear,
+            cells_to_restyle: plan.cells_to_restyle,
+            op_count: plan.op_count(),
+            changes,
+            changes_truncated,
+        }
+    }
 }
 
-#[derive(Debug, Clone, Serialize)]
-struct AppendFooterCandidate {
-    row: u32,
-    matched: bool,
+#[derive(Debug, Serialize)]
+struct PlanResponse {
+    file: String,
+    converged: bool,
+    #[serde(flatten)]
+    drift: DriftReport,
+    plan: Value,
     #[serde(skip_serializing_if = "Option::is_none")]
-    reason: Option<String>,
+    plan_path: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
-struct AppendRegionResponse {
-    mode: String,
+struct ApplyStateResponse {
     file: String,
+    /// The workbook already matched the spec before this run
+    converged: bool,
+    /// Set with `--check`: whether the workbook is free of drift
     #[serde(skip_serializing_if = "Option::is_none")]
-    source_path: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    target_path: Option<String>,
-    sheet_name: String,
-    target_kind: AppendRegionTargetKind,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    region_id: Option<u32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    table_name: Option<String>,
-    region_bounds: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    header_row: Option<u32>,
-    footer_policy: String,
-    insert_at_row: u32,
-    insert_reason: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    footer_row: Option<u32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    footer_detection: Option<String>,
-    #[serde(skip_serializing_if = "Vec::is_empty", default)]
-    footer_candidates: Vec<AppendFooterCandidate>,
-    #[serde(skip_serializing_if = "Vec::is_empty", default)]
-    footer_formula_targets: Vec<String>,
-    rows_appended: u32,
-    columns_written: u32,
-    target_anchor: String,
-    target_range: String,
-    expand_adjacent_sums: bool,
-    confidence: String,
-    confidence_reason: String,
-    warnings: Vec<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    would_change: Option<bool>,
+    passed: Option<bool>,
+    drift: DriftReport,
+    applied: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
-    changed: Option<bool>,
+    apply: Option<ApplyPlanResponse>,
 }
 
-#[derive(Debug, Clone)]
-struct AppendRegionPlan {
-    sheet_name: String,
-    target_kind: AppendRegionTargetKind,
-    region_id: Option<u32>,
-    table_name: Option<String>,
-    region_bounds: String,
-    header_row: Option<u32>,
-    footer_policy: String,
-    insert_at_row: u32,
-    insert_reason: String,
-    footer_row: Option<u32>,
-    footer_detection: Option<String>,
-    footer_candidates: Vec<AppendFooterCandidate>,
-    footer_formula_targets: Vec<String>,
-    rows_appended: u32,
-    columns_written: u32,
-    target_anchor: String,
-    target_range: String,
-    confidence: String,
-    confidence_reason: String,
-    warnings: Vec<String>,
-    rows: Vec<Vec<Option<MatrixCell>>>,
-}
+/// Compare a desired-state spec with the workbook and emit the `apply-plan`
+/// payload that converges it, without writing to the workbook.
+pub async fn plan(file: PathBuf, spec: String, output: Option<PathBuf>) -> Result<Value> {
+    let runtime = StatelessRuntime;
+    let source = runtime.normalize_existing_file(&file)?;
+    let spec = parse_desired_state_spec(&spec)?;
 
-struct AppendFooterScan {
-    footer_row: Option<u32>,
-    footer_detection: Option<String>,
-    footer_candidates: Vec<AppendFooterCandidate>,
-    footer_formula_targets: Vec<String>,
-}
+    let book = umya_spreadsheet::reader::xlsx::read(&source).map_err(|error| {
+        anyhow!(
+            "failed to read workbook '{}' for planning: {}",
+            source.display(),
+            error
+        )
+    })?;
+    let mut plan = plan_convergence(&book, &spec)
+        .map_err(|error| invalid_ops_payload(format!("{error:#}")))?;
+    let payload = plan.to_apply_plan_payload()?;
+
+    let plan_path = match output {
+        Some(path) => {
+            let mut text = serde_json::to_string_pretty(&payload)?;
+            text.push('\n');
+            fs::write(&path, text)
+                .map_err(|error| anyhow!("failed to write plan '{}': {}", path.display(), error))?;
+            Some(path.display().to_string())
+        }
+        None => None,
+    };
 
-struct AppendRegionTarget {
-    sheet_name: String,
-    target_kind: AppendRegionTargetKind,
-    region_id: Option<u32>,
-    table_name: Option<String>,
-    bounds: AppendBounds,
-    region_bounds: String,
-    header_row: Option<u32>,
-    headers_truncated: bool,
+    Ok(serde_json::to_value(PlanResponse {
+        file: source.display().to_string(),
+        converged: plan.is_converged(),
+        drift: DriftReport::from_plan(&mut plan),
+        plan: payload,
+        plan_path,
+    })?)
 }
 
+/// Converge the workbook to a desired-state spec in one step: plan the ops
+/// and apply them like `apply-plan`, reporting the drift found either way.
+/// With `check`, only report drift.
 #[allow(clippy::too_many_arguments)]
-pub async fn append_region(
+pub async fn apply_state(
     file: PathBuf,
-    sheet_name: String,
-    region_id: Option<u32>,
-    table_name: Option<String>,
-    rows_ref: Option<String>,
-    from_csv: Option<String>,
-    header: bool,
-    footer_policy: AppendRegionFooterPolicyArg,
+    spec: String,
+    check: bool,
     dry_run: bool,
     in_place: bool,
     output: Option<PathBuf>,
     force: bool,
+    verify_with: Option<PathBuf>,
+    audit_log: Option<PathBuf>,
+    snapshot_dir: Option<PathBuf>,
 ) -> Result<Value> {
-    let selected_modes = dry_run as u8 + in_place as u8 + output.is_some() as u8;
-    if selected_modes != 1 {
-        return Err(invalid_argument(
-            "choose exactly one of --dry-run, --in-place, or --output <PATH>",
-        ));
-    }
-    if force && output.is_none() {
-        return Err(invalid_argument("--force requires --output <PATH>"));
-    }
-
     let runtime = StatelessRuntime;
     let source = runtime.normalize_existing_file(&file)?;
-    let rows = match (rows_ref, from_csv) {
-        (Some(rows_ref), None) => parse_append_region_rows_payload(&rows_ref)?,
-        (None, Some(csv_path)) => parse_append_region_rows_from_csv(&csv_path, header)?,
-        (Some(_), Some(_)) => {
+    let mode = if check {
+        None
+    } else {
+        Some(validate_batch_mode(
+            &source, dry_run, in_place, output, force,
+        )?)
+    };
+    let verify_with = load_verify_with(verify_with)?;
+    let audit_log = load_audit_log(audit_log, &spec)?;
+    let snapshot_dir = match &mode {
+        Some(mode) => load_snapshot_dir(snapshot_dir, mode)?,
+        None => None,
+    };
+    let spec = parse_desired_state_spec(&spec)?;
+
+    let book = umya_spreadsheet::reader::xlsx::read(&source).map_err(|error| {
+        anyhow!(
+            "failed to read workbook '{}' for planning: {}",
+            source.display(),
+            error
+        )
+    })?;
+    let mut plan = plan_convergence(&book, &spec)
+        .map_err(|error| invalid_ops_payload(format!("{error:#}")))?;
+    drop(book);
+    let converged = plan.is_converged();
+
+    let apply = match mode {
+        Some(mode) if !converged => {
+            let payload: ApplyPlanPayload = serde_json::from_value(plan.to_apply_plan_payload()?)?;
+            let policy = FormulaParsePolicy::default_for_command_class(CommandClass::BatchWrite);
+            Some(
+                run_plan_steps(
+                    "apply-state",
+                    &source,
+                    mode,
+                    payload,
+                    policy,
+                    verify_with.as_ref(),
+                    audit_log.as_ref(),
+                    snapshot_dir.as_ref(),
+                )
+                .await?,
+            )
+        }
+        _ => None,
+    };
+
+    let response = serde_json::to_value(ApplyStateResponse {
+        file: source.display().to_string(),
+        converged,
+        passed: check.then_some(converged),
+        drift: DriftReport::from_plan(&mut plan),
+        applied: apply
+            .as_ref()
+            .is_some_and(|apply| apply.changed && !apply.dry_run),
+        apply,
+    })?;
+    Ok(attach_snapshot(response, snapshot_dir.as_ref()))
+}
+
+fn parse_desired_state_spec(raw: &str) -> Result<DesiredStateSpec> {
+    let guidance = format!(
+        "expected top-level shape: {DESIRED_STATE_SPEC_SHAPE}; minimal valid example: {DESIRED_STATE_SPEC_MINIMAL_EXAMPLE}"
+    );
+    let path = raw
+        .strip_prefix('@')
+        .filter(|path| !path.is_empty())
+        .ok_or_else(|| invalid_ops_payload("--spec must be provided as @<path>"))?;
+    let raw_spec = fs::read_to_string(path).map_err(|error| {
+        invalid_ops_payload(format!("unable to read spec '{}': {}", path, error))
+    })?;
+    serde_json::from_str(&raw_spec).map_err(|error| {
+        invalid_ops_payload(format!(
+            "spec does not match required schema: {error}; {guidance}"
+        ))
+    })
+}
+
+fn resolve_plan_step(
+    workbook: &WorkbookContext,
+    step: ApplyPlanStepInput,
+    policy: FormulaParsePolicy,
+) -> Result<ResolvedPlanStep> {
+    let resolved = match step {
+        ApplyPlanStepInput::Transform { mut ops } => {
+            load_import_row_files(&mut ops, &std::env::current_dir()?, false)
+                .map_err(|error| invalid_ops_payload(format!("{error:#}")))?;
+            let resolved = resolve_transform_ops_for_workbook(workbook, &ops)
+                .map_err(|error| invalid_ops_payload(error.to_string()))?;
+            let (ops, formula_parse_diagnostics) = screen_transform_formulas(resolved, policy)?;
+            ResolvedPlanStep {
+                ops: PlanStepOps::Transform(ops),
+                warnings: Vec::new(),
+                formula_parse_diagnostics,
+            }
+        }
+        ApplyPlanStepInput::Style { ops } => {
+            let (normalized, warnings) = normalize_style_batch(StyleBatchParamsInput {
+                fork_id: String::new(),
+                ops,
+                mode: None,
+                label: None,
+            })
+            .map_err(|error| invalid_ops_payload(error.to_string()))?;
+            let ops = resolve_style_ops_for_workbook(workbook, &normalized.ops)
+                .map_err(|error| invalid_ops_payload(error.to_string()))?;
+            ResolvedPlanStep {
+                ops: PlanStepOps::Style(ops),
+                warnings,
+                formula_parse_diagnostics: None,
+            }
+        }
+        ApplyPlanStepInput::Structure { ops } => {
+            let (mut normalized, warnings) = normalize_structure_batch(StructureBatchParamsInput {
+                fork_id: String::new(),
+                ops,
+                mode: None,
+                label: None,
+                formula_parse_policy: Some(policy),
+                impact_report: None,
+                show_formula_delta: None,
+            })
+            .map_err(|error| invalid_ops_payload(error.to_string()))?;
+            resolve_copy_sheet_sources(&mut normalized.ops, &std::env::current_dir()?, false)
+                .map_err(|error| invalid_ops_payload(format!("{error:#}")))?;
+            ResolvedPlanStep {
+                ops: PlanStepOps::Structure(normalized.ops),
+                warnings,
+                formula_parse_diagnostics: None,
+            }
+        }
+        ApplyPlanStepInput::Rules { ops } => ResolvedPlanStep {
+            ops: PlanStepOps::Rules(ops),
+            warnings: Vec::new(),
+            formula_parse_diagnostics: None,
+        },
+    };
+    Ok(resolved)
+}
+
+/// Apply resolved plan steps in order to the staged workbook at `path`. The
+/// first failing step aborts the plan; the caller drops the staged file.
+fn apply_plan_steps(
+    path: &Path,
+    steps: Vec<ResolvedPlanStep>,
+    policy: FormulaParsePolicy,
+) -> Result<Vec<ApplyPlanStepResult>> {
+    let mut results = Vec::with_capacity(steps.len());
+    for (index, step) in steps.into_iter().enumerate() {
+        let kind = step.kind();
+        let op_count = step.op_count();
+        let (applied_count, summary, diagnostics, changed) = match &step.ops {
+            PlanStepOps::Transform(ops) => {
+                apply_transform_ops_to_file(path, ops, false).map(|result| {
+                    let changed = transform_summary_indicates_change(&result.summary.counts);
+                    (result.ops_applied, result.summary, None, changed)
+                })
+            }
+            PlanStepOps::Style(ops) => apply_style_ops_to_file(path, ops).map(|result| {
+                let changed = style_summary_indicates_change(&result.summary.counts);
+                (result.ops_applied, result.summary, None, changed)
+            }),
+            PlanStepOps::Structure(ops) => {
+                apply_structure_ops_to_file(path, ops, policy).map(|result| {
+                    let changed = structure_summary_indicates_change(&result.summary.counts);
+                    (
+                        result.ops_applied,
+                        result.summary,
+                        result.formula_parse_diagnostics,
+                        changed,
+                    )
+                })
+            }
+            PlanStepOps::Rules(ops) => apply_rules_ops_to_file(path, ops, policy).map(|result| {
+                let changed = rules_summary_indicates_change(&result.summary.counts);
+                (
+                    result.ops_applied,
+                    result.summary,
+                    result.formula_parse_diagnostics,
+                    changed,
+                )
+            }),
+        }
+        .map_err(|error| plan_step_error(index, classify_apply_error(error)))?;
+
+        results.push(ApplyPlanStepResult {
+            index,
+            kind,
+            op_count,
+            applied_count,
+            changed,
+            result_counts: summary.counts,
+            warnings: merge_cli_warnings(
+                step.warnings,
+                warning_strings_to_cli_warnings(summary.warnings),
+            ),
+            formula_parse_diagnostics: step.formula_parse_diagnostics.or(diagnostics),
+        });
+    }
+    Ok(results)
+}
+
+/// Name the failing step while keeping the error-code prefix (`invalid ops
+/// payload: `, `write failed: `, ...) at the front of the message.
+fn plan_step_error(index: usize, error: anyhow::Error) -> anyhow::Error {
+    let message = format!("{error:#}");
+    match message.split_once(": ") {
+        Some((prefix, detail)) => anyhow!("{prefix}: plan step {index} failed: {detail}"),
+        None => anyhow!("plan step {index} failed: {message}"),
+    }
+}
+
+fn validate_edit_mode(
+    source: &Path,
+    dry_run: bool,
+    in_place: bool,
+    output: Option<PathBuf>,
+    force: bool,
+) -> Result<EditMutationMode> {
+    if force && output.is_none() {
+        return Err(invalid_argument("--force requires --output <PATH>"));
+    }
+
+    if dry_run {
+        if in_place {
             return Err(invalid_argument(
-                "--rows and --from-csv are mutually exclusive",
+                "--dry-run cannot be combined with --in-place",
             ));
         }
-        (None, None) => {
+        if output.is_some() {
             return Err(invalid_argument(
-                "append-region requires exactly one of --rows or --from-csv",
+                "--dry-run cannot be combined with --output <PATH>",
             ));
         }
-    };
-    let plan = build_append_region_plan(
-        &source,
-        &sheet_name,
-        region_id,
-        table_name.as_deref(),
-        footer_policy,
-        rows,
-    )?;
-
-    if dry_run {
-        return Ok(serde_json::to_value(build_append_region_response(
-            &plan,
-            "dry_run",
-            source.display().to_string(),
-            None,
-            Some(true),
-            None,
-            None,
-        ))?);
+        return Ok(EditMutationMode::DryRun);
     }
 
-    if in_place {
-        let source_path = source.display().to_string();
-        let ((), temp_path) =
-            apply_to_temp_copy(&source, source.parent(), ".append-region-", |work_path| {
-                apply_append_region_plan_to_file(work_path, &plan)
-            })?;
-        atomic_replace_target(temp_path, &source, true)?;
-        return Ok(serde_json::to_value(build_append_region_response(
-            &plan,
-            "in_place",
-            source_path.clone(),
-            Some(source_path.clone()),
-            None,
-            Some(source_path),
-            Some(true),
-        ))?);
+    if in_place && output.is_some() {
+        return Err(invalid_argument(
+            "--in-place cannot be combined with --output <PATH>",
+        ));
     }
 
-    let target = runtime.normalize_destination_path(
-        output
-            .as_ref()
-            .expect("output required unless dry-run or in-place"),
-    )?;
-    ensure_output_path_is_distinct(&source, &target)?;
-    if path_entry_exists(&target)? && !force {
-        return Err(output_exists(format!(
-            "output path '{}' already exists",
-            target.display()
-        )));
+    if let Some(target) = output {
+        let target = resolve_output_target(source, target)?;
+        return Ok(EditMutationMode::Output { target, force });
     }
 
-    let source_path = source.display().to_string();
-    let target_path = target.display().to_string();
-    let ((), temp_path) =
-        apply_to_temp_copy(&source, target.parent(), ".append-region-", |work_path| {
-            apply_append_region_plan_to_file(work_path, &plan)
-        })?;
-    atomic_replace_target(temp_path, &target, force)?;
-
-    Ok(serde_json::to_value(build_append_region_response(
-        &plan,
-        "output",
-        target_path.clone(),
-        Some(source_path),
-        None,
-        Some(target_path),
-        Some(true),
-    ))?)
+    Ok(EditMutationMode::InPlace)
 }
 
-fn build_append_region_response(
-    plan: &AppendRegionPlan,
-    mode: &str,
-    file: String,
-    source_path: Option<String>,
-    would_change: Option<bool>,
-    target_path: Option<String>,
-    changed: Option<bool>,
-) -> AppendRegionResponse {
-    AppendRegionResponse {
-        mode: mode.to_string(),
-        file,
-        source_path,
-        target_path,
-        sheet_name: plan.sheet_name.clone(),
-        target_kind: plan.target_kind,
-        region_id: plan.region_id,
-        table_name: plan.table_name.clone(),
-        region_bounds: plan.region_bounds.clone(),
-        header_row: plan.header_row,
-        footer_policy: plan.footer_policy.clone(),
-        insert_at_row: plan.insert_at_row,
-        insert_reason: plan.insert_reason.clone(),
-        footer_row: plan.footer_row,
-        footer_detection: plan.footer_detection.clone(),
-        footer_candidates: plan.footer_candidates.clone(),
-        footer_formula_targets: plan.footer_formula_targets.clone(),
-        rows_appended: plan.rows_appended,
-        columns_written: plan.columns_written,
-        target_anchor: plan.target_anchor.clone(),
-        target_range: plan.target_range.clone(),
-        expand_adjacent_sums: true,
-        confidence: plan.confidence.clone(),
-        confidence_reason: plan.confidence_reason.clone(),
-        warnings: plan.warnings.clone(),
-        would_change,
-        changed,
+/// `--output auto` names the next revision of `source` (see
+/// [`crate::revisions`]); any other value is used as given.
+fn resolve_output_target(source: &Path, target: PathBuf) -> Result<PathBuf> {
+    if target.as_os_str() != AUTO_OUTPUT {
+        return Ok(target);
     }
+    next_revision_path(source)
+        .map_err(|error| invalid_argument(format!("unable to resolve --output auto: {error:#}")))
 }
 
-fn build_append_region_plan(
+fn validate_batch_mode(
     source: &Path,
-    sheet_name: &str,
-    region_id: Option<u32>,
-    table_name: Option<&str>,
-    footer_policy: AppendRegionFooterPolicyArg,
-    rows: Vec<Vec<Option<MatrixCell>>>,
-) -> Result<AppendRegionPlan> {
-    if rows.is_empty() {
-        return Err(invalid_argument(
-            "append-region requires at least one row in the rows payload",
-        ));
+    dry_run: bool,
+    in_place: bool,
+    output: Option<PathBuf>,
+    force: bool,
+) -> Result<BatchMutationMode> {
+    if force && output.is_none() {
+        return Err(invalid_argument("--force requires --output <PATH>"));
     }
 
-    let config = Arc::new(local_workbook_config(source));
-    let workbook = WorkbookContext::load(&config, source)?;
-    let target =
-        resolve_append_region_target(&workbook, source, sheet_name, region_id, table_name)?;
-    let bounds = target.bounds;
+    if dry_run {
+        if in_place {
+            return Err(invalid_argument(
+                "--dry-run cannot be combined with --in-place",
+            ));
+        }
+        if output.is_some() {
+            return Err(invalid_argument(
+                "--dry-run cannot be combined with --output <PATH>",
+            ));
+        }
+        return Ok(BatchMutationMode::DryRun);
+    }
 
-    let columns_written = rows.iter().map(Vec::len).max().unwrap_or(0) as u32;
-    if columns_written == 0 {
+    if in_place && output.is_some() {
         return Err(invalid_argument(
-            "append-region rows payload must contain at least one non-empty column",
+            "--in-place cannot be combined with --output <PATH>",
         ));
     }
-    let region_width = bounds.end_col - bounds.start_col + 1;
-    if columns_written > region_width {
-        let target_label = target
-            .table_name
-            .clone()
-            .map(|name| format!("table '{}'", name))
-            .or_else(|| target.region_id.map(|id| format!("region {}", id)))
-            .unwrap_or_else(|| "append target".to_string());
-        return Err(invalid_argument(format!(
-            "rows payload is wider than {} on sheet '{}': payload columns={}, region columns={}",
-            target_label, target.sheet_name, columns_written, region_width
-        )));
+
+    if in_place {
+        return Ok(BatchMutationMode::InPlace);
     }
 
-    let footer_scan = detect_append_footer(
-        source,
-        &target.sheet_name,
-        bounds.start_col,
-        bounds.end_col,
-        bounds.end_row,
-    )?;
-    let footer_policy_label = append_footer_policy_label(footer_policy).to_string();
-    let (insert_at_row, insert_reason) = match footer_policy {
-        AppendRegionFooterPolicyArg::Auto => {
-            if let Some(row) = footer_scan.footer_row {
-                (
-                    row,
-                    format!("auto policy selected detected footer row {}", row),
-                )
-            } else {
-                (
-                    bounds.end_row + 1,
-                    format!(
-                        "auto policy found no footer row; appending after detected region end row {}",
-                        bounds.end_row
-                    ),
-                )
-            }
-        }
-        AppendRegionFooterPolicyArg::BeforeFooter => {
-            let row = footer_scan.footer_row.ok_or_else(|| {
-                invalid_argument(
-                    "footer policy 'before-footer' requires a detected footer/subtotal row; use --footer-policy auto or append-at-end to continue without one",
-                )
-            })?;
-            (
-                row,
-                format!("before_footer policy selected detected footer row {}", row),
-            )
-        }
-        AppendRegionFooterPolicyArg::AppendAtEnd => {
-            if let Some(row) = footer_scan.footer_row {
-                (
-                    bounds.end_row + 1,
-                    format!(
-                        "append_at_end policy bypassed detected footer row {} and appended after region end row {}",
-                        row, bounds.end_row
-                    ),
-                )
-            } else {
-                (
-                    bounds.end_row + 1,
-                    format!(
-                        "append_at_end policy appended after detected region end row {}",
-                        bounds.end_row
-                    ),
-                )
-            }
-        }
-    };
-    let target_anchor = format!(
-        "{}{}",
-        column_number_to_name(bounds.start_col),
-        insert_at_row
+    if let Some(target) = output {
+        let target = resolve_output_target(source, target)?;
+        return Ok(BatchMutationMode::Output { target, force });
+    }
+
+    Err(invalid_argument(
+        "choose exactly one mutation mode: --dry-run, --in-place, or --output <PATH>",
+    ))
+}
+
+fn parse_ops_payload_object(raw: &str, guidance: &str) -> Result<serde_json::Map<String, Value>> {
+    let path = raw
+        .strip_prefix('@')
+        .ok_or_else(|| invalid_ops_payload("--ops must be provided as @<path>"))?;
+    if path.is_empty() {
+        return Err(invalid_ops_payload(
+            "--ops file reference cannot be empty; expected @<path>",
+        ));
+    }
+
+    let raw_payload = fs::read_to_string(path).map_err(|error| {
+        invalid_ops_payload(format!("unable to read ops payload '{}': {}", path, error))
+    })?;
+
+    let json_value: serde_json::Value = serde_json::from_str(&raw_payload).map_err(|error| {
+        invalid_ops_payload(format!(
+            "ops payload is not valid JSON: {error}; {guidance}"
+        ))
+    })?;
+
+    let object = json_value.as_object().ok_or_else(|| {
+        invalid_ops_payload(format!("ops payload must be a JSON object; {guidance}"))
+    })?;
+
+    Ok(object.clone())
+}
+
+fn parse_column_size_ops_payload(raw: &str) -> Result<ColumnSizeOpsPayload> {
+    let guidance = format!(
+        "expected top-level shape: {} OR {}; minimal valid example: {} OR {}",
+        COLUMN_SIZE_PAYLOAD_SHAPE,
+        COLUMN_SIZE_PAYLOAD_ALTERNATE_SHAPE,
+        COLUMN_SIZE_PAYLOAD_MINIMAL_EXAMPLE,
+        COLUMN_SIZE_PAYLOAD_ALTERNATE_EXAMPLE,
     );
-    let target_range = format_a1_range(
-        bounds.start_col,
-        bounds.start_col + columns_written - 1,
-        insert_at_row,
-        insert_at_row + rows.len() as u32 - 1,
+    let (sheet_name, ops) =
+        parse_sheet_scoped_ops_payload::<ColumnSizeOpWithSheetInput>(raw, &guidance)?;
+    Ok(ColumnSizeOpsPayload { sheet_name, ops })
+}
+
+fn parse_row_size_ops_payload(raw: &str) -> Result<RowSizeOpsPayload> {
+    let guidance = format!(
+        "expected top-level shape: {} OR {}; minimal valid example: {} OR {}",
+        ROW_SIZE_PAYLOAD_SHAPE,
+        ROW_SIZE_PAYLOAD_ALTERNATE_SHAPE,
+        ROW_SIZE_PAYLOAD_MINIMAL_EXAMPLE,
+        ROW_SIZE_PAYLOAD_ALTERNATE_EXAMPLE,
     );
+    let (sheet_name, ops) =
+        parse_sheet_scoped_ops_payload::<RowSizeOpWithSheetInput>(raw, &guidance)?;
+    Ok(RowSizeOpsPayload { sheet_name, ops })
+}
 
-    let mut warnings = Vec::new();
-    if target.headers_truncated {
-        warnings.push(
-            "detected region headers were truncated; verify the append target carefully"
-                .to_string(),
-        );
-    }
-    match footer_policy {
-        AppendRegionFooterPolicyArg::Auto if footer_scan.footer_row.is_none() => {
-            warnings.push("no footer row detected; appending at detected region end".to_string());
+/// Accept either a top-level `sheet_name` or per-op `sheet_name` values that
+/// all agree; mixed sheets are rejected.
+fn parse_sheet_scoped_ops_payload<E: SheetScopedOpInput>(
+    raw: &str,
+    guidance: &str,
+) -> Result<(String, Vec<E::Op>)> {
+    let object = parse_ops_payload_object(raw, guidance)?;
+
+    if object.contains_key("sheet_name") {
+        let top_level_sheet = object
+            .get("sheet_name")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+
+        if let (Some(top_level_sheet), Some(ops_array)) =
+            (top_level_sheet, object.get("ops").and_then(Value::as_array))
+        {
+            for (index, raw_entry) in ops_array.iter().enumerate() {
+                if let Some(per_op_sheet) = raw_entry
+                    .as_object()
+                    .and_then(|entry| entry.get("sheet_name"))
+                    .and_then(Value::as_str)
+                    && per_op_sheet != top_level_sheet
+                {
+                    return Err(invalid_ops_payload(format!(
+                        "ops payload has mixed sheet_name values between top-level and ops[{index}] ('{}' vs '{}'); {guidance}",
+                        top_level_sheet, per_op_sheet
+                    )));
+                }
+            }
         }
-        AppendRegionFooterPolicyArg::AppendAtEnd if footer_scan.footer_row.is_some() => {
-            warnings.push(format!(
-                "footer policy '{}' ignored detected footer row {}",
-                footer_policy_label,
-                footer_scan.footer_row.unwrap_or_default()
-            ));
+
+        let payload: SheetScopedOpsPayload<E::Op> = serde_json::from_value(Value::Object(object))
+            .map_err(|error| {
+            invalid_ops_payload(format!(
+                "ops payload does not match required schema: {error}; {guidance}"
+            ))
+        })?;
+        return Ok((payload.sheet_name, payload.ops));
+    }
+
+    let ops_value = object.get("ops").ok_or_else(|| {
+        invalid_ops_payload(format!("ops payload must include 'ops'; {guidance}"))
+    })?;
+    let ops_array = ops_value.as_array().ok_or_else(|| {
+        invalid_ops_payload(format!(
+            "ops payload field 'ops' must be an array; {guidance}"
+        ))
+    })?;
+
+    let mut normalized_ops = Vec::with_capacity(ops_array.len());
+    let mut inferred_sheet_name: Option<String> = None;
+
+    for (index, raw_entry) in ops_array.iter().enumerate() {
+        let op_with_sheet: E = serde_json::from_value(raw_entry.clone()).map_err(|error| {
+            invalid_ops_payload(format!(
+                "ops payload does not match required schema at ops[{index}]: {error}; {guidance}"
+            ))
+        })?;
+
+        let sheet_name = op_with_sheet.sheet_name().to_string();
+        match &inferred_sheet_name {
+            Some(existing) if existing != &sheet_name => {
+                return Err(invalid_ops_payload(format!(
+                    "ops payload has mixed sheet_name values in per-op shape; found '{}' and '{}'; {guidance}",
+                    existing, sheet_name
+                )));
+            }
+            None => inferred_sheet_name = Some(sheet_name),
+            _ => {}
         }
-        _ => {}
+
+        normalized_ops.push(op_with_sheet.into_op_input());
     }
 
-    let (confidence, confidence_reason) = append_plan_confidence(&target, &footer_scan);
+    let sheet_name = inferred_sheet_name.ok_or_else(|| {
+        invalid_ops_payload(format!(
+            "ops payload must provide top-level sheet_name or per-op sheet_name values; {guidance}"
+        ))
+    })?;
 
-    Ok(AppendRegionPlan {
-        sheet_name: target.sheet_name,
-        target_kind: target.target_kind,
-        region_id: target.region_id,
-        table_name: target.table_name,
-        region_bounds: target.region_bounds,
-        header_row: target.header_row,
-        footer_policy: footer_policy_label,
-        insert_at_row,
-        insert_reason,
-        footer_row: footer_scan.footer_row,
-        footer_detection: footer_scan.footer_detection,
-        footer_candidates: footer_scan.footer_candidates,
-        footer_formula_targets: footer_scan.footer_formula_targets,
-        rows_appended: rows.len() as u32,
-        columns_written,
-        target_anchor,
-        target_range,
-        confidence: confidence.to_string(),
-        confidence_reason,
-        warnings,
-        rows,
-    })
+    Ok((sheet_name, normalized_ops))
 }
 
-fn resolve_append_region_target(
-    workbook: &WorkbookContext,
-    source: &Path,
-    sheet_name: &str,
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum AppendRegionTargetKind {
+    DetectedRegion,
+    Table,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct AppendFooterCandidate {
+    row: u32,
+    matched: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct AppendRegionResponse {
+    mode: String,
+    file: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    target_path: Option<String>,
+    sheet_name: String,
+    target_kind: AppendRegionTargetKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
     region_id: Option<u32>,
-    table_name: Option<&str>,
-) -> Result<AppendRegionTarget> {
-    match (region_id, table_name) {
-        (Some(_), Some(_)) => Err(invalid_argument(
-            "--region-id and --table-name are mutually exclusive",
-        )),
-        (None, None) => Err(invalid_argument(
-            "append-region requires exactly one of --region-id or --table-name",
-        )),
-        (Some(region_id), None) => {
-            let region = workbook.detected_region(sheet_name, region_id).map_err(|_| {
-                invalid_argument(format!(
-                    "region {} was not found on sheet '{}'; run `asp sheet-overview {} {}` to inspect detected region ids",
-                    region_id,
-                    sheet_name,
-                    source.display(),
-                    sheet_name
-                ))
-            })?;
-            let bounds = parse_append_region_bounds(&region.bounds).ok_or_else(|| {
-                invalid_argument(format!(
-                    "detected region {} on sheet '{}' has unsupported bounds '{}'",
-                    region_id, sheet_name, region.bounds
-                ))
-            })?;
-            Ok(AppendRegionTarget {
-                sheet_name: sheet_name.to_string(),
-                target_kind: AppendRegionTargetKind::DetectedRegion,
-                region_id: Some(region_id),
-                table_name: None,
-                bounds,
-                region_bounds: region.bounds,
-                header_row: region.header_row,
-                headers_truncated: region.headers_truncated,
-            })
+    #[serde(skip_serializing_if = "Option::is_none")]
+    table_name: Option<String>,
+    region_bounds: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    header_row: Option<u32>,
+    footer_policy: String,
+    insert_at_row: u32,
+    insert_reason: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    footer_row: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    footer_detection: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    footer_candidates: Vec<AppendFooterCandidate>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    footer_formula_targets: Vec<String>,
+    rows_appended: u32,
+    columns_written: u32,
+    target_anchor: String,
+    target_range: String,
+    expand_adjacent_sums: bool,
+    confidence: String,
+    confidence_reason: String,
+    warnings: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    would_change: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    changed: Option<bool>,
+}
+
+#[derive(Debug, Clone)]
+struct AppendRegionPlan {
+    sheet_name: String,
+    target_kind: AppendRegionTargetKind,
+    region_id: Option<u32>,
+    table_name: Option<String>,
+    region_bounds: String,
+    header_row: Option<u32>,
+    footer_policy: String,
+    insert_at_row: u32,
+    insert_reason: String,
+    footer_row: Option<u32>,
+    footer_detection: Option<String>,
+    footer_candidates: Vec<AppendFooterCandidate>,
+    footer_formula_targets: Vec<String>,
+    rows_appended: u32,
+    columns_written: u32,
+    target_anchor: String,
+    target_range: String,
+    confidence: String,
+    confidence_reason: String,
+    warnings: Vec<String>,
+    rows: Vec<Vec<Option<MatrixCell>>>,
+}
+
+struct AppendFooterScan {
+    footer_row: Option<u32>,
+    footer_detection: Option<String>,
+    footer_candidates: Vec<AppendFooterCandidate>,
+    footer_formula_targets: Vec<String>,
+}
+
+struct AppendRegionTarget {
+    sheet_name: String,
+    target_kind: AppendRegionTargetKind,
+    region_id: Option<u32>,
+    table_name: Option<String>,
+    bounds: AppendBounds,
+    region_bounds: String,
+    header_row: Option<u32>,
+    headers_truncated: bool,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn append_region(
+    file: PathBuf,
+    sheet_name: String,
+    region_id: Option<u32>,
+    table_name: Option<String>,
+    rows_ref: Option<String>,
+    from_csv: Option<String>,
+    header: bool,
+    column_map: Option<String>,
+    footer_policy: AppendRegionFooterPolicyArg,
+    dry_run: bool,
+    in_place: bool,
+    output: Option<PathBuf>,
+    force: bool,
+) -> Result<Value> {
+    let selected_modes = dry_run as u8 + in_place as u8 + output.is_some() as u8;
+    if selected_modes != 1 {
+        return Err(invalid_argument(
+            "choose exactly one of --dry-run, --in-place, or --output <PATH>",
+        ));
+    }
+    if force && output.is_none() {
+        return Err(invalid_argument("--force requires --output <PATH>"));
+    }
+
+    let runtime = StatelessRuntime;
+    let source = runtime.normalize_existing_file(&file)?;
+    if column_map.is_some() && from_csv.is_none() {
+        return Err(invalid_argument("--column-map requires --from-csv"));
+    }
+    let rows = match (rows_ref, from_csv) {
+        (Some(rows_ref), None) => parse_append_region_rows_payload(&rows_ref)?,
+        (None, Some(csv_path)) => {
+            let column_map = column_map
+                .as_deref()
+                .map(parse_append_region_column_map)
+                .transpose()?;
+            parse_append_region_rows_from_csv(&csv_path, header, column_map.as_ref())?
         }
-        (None, Some(table_name)) => resolve_append_table_target(workbook, sheet_name, table_name),
+        (Some(_), Some(_)) => {
+            return Err(invalid_argument(
+                "--rows and --from-csv are mutually exclusive",
+            ));
+        }
+        (None, None) => {
+            return Err(invalid_argument(
+                "append-region requires exactly one of --rows or --from-csv",
+            ));
+        }
+    };
+    let plan = build_append_region_plan(
+        &source,
+        &sheet_name,
+        region_id,
+        table_name.as_deref(),
+        footer_policy,
+        rows,
+    )?;
+
+    if dry_run {
+        return Ok(serde_json::to_value(build_append_region_response(
+            &plan,
+            "dry_run",
+            source.display().to_string(),
+            None,
+            Some(true),
+            None,
+            None,
+        ))?);
+    }
+
+    if in_place {
+        let source_path = source.display().to_string();
+        let ((), temp_path) =
+            apply_to_temp_copy(&source, source.parent(), ".append-region-", |work_path| {
+                apply_append_region_plan_to_file(work_path, &plan)
+            })?;
+        commit_staged_workbook(
+            &WriteCommit::new("append-region"),
+            &source,
+            temp_path,
+            &source,
+            true,
+        )
+        .await?;
+        return Ok(serde_json::to_value(build_append_region_response(
+            &plan,
+            "in_place",
+            source_path.clone(),
+            Some(source_path.clone()),
+            None,
+            Some(source_path),
+            Some(true),
+        ))?);
+    }
+
+    let target = runtime.normalize_destination_path(
+        output
+            .as_ref()
+            .expect("output required unless dry-run or in-place"),
+    )?;
+    ensure_output_path_is_distinct(&source, &target)?;
+    if path_entry_exists(&target)? && !force {
+        return Err(output_exists(format!(
+            "output path '{}' already exists",
+            target.display()
+        )));
+    }
+
+    let source_path = source.display().to_string();
+    let target_path = target.display().to_string();
+    let ((), temp_path) =
+        apply_to_temp_copy(&source, target.parent(), ".append-region-", |work_path| {
+            apply_append_region_plan_to_file(work_path, &plan)
+        })?;
+    commit_staged_workbook(
+        &WriteCommit::new("append-region"),
+        &source,
+        temp_path,
+        &target,
+        force,
+    )
+    .await?;
+
+    Ok(serde_json::to_value(build_append_region_response(
+        &plan,
+        "output",
+        target_path.clone(),
+        Some(source_path),
+        None,
+        Some(target_path),
+        Some(true),
+    ))?)
+}
+
+fn build_append_region_response(
+    plan: &AppendRegionPlan,
+    mode: &str,
+    file: String,
+    source_path: Option<String>,
+    would_change: Option<bool>,
+    target_path: Option<String>,
+    changed: Option<bool>,
+) -> AppendRegionResponse {
+    AppendRegionResponse {
+        mode: mode.to_string(),
+        file,
+        source_path,
+        target_path,
+        sheet_name: plan.sheet_name.clone(),
+        target_kind: plan.target_kind,
+        region_id: plan.region_id,
+        table_name: plan.table_name.clone(),
+        region_bounds: plan.region_bounds.clone(),
+        header_row: plan.header_row,
+        footer_policy: plan.footer_policy.clone(),
+        insert_at_row: plan.insert_at_row,
+        insert_reason: plan.insert_reason.clone(),
+        footer_row: plan.footer_row,
+        footer_detection: plan.footer_detection.clone(),
+        footer_candidates: plan.footer_candidates.clone(),
+        footer_formula_targets: plan.footer_formula_targets.clone(),
+        rows_appended: plan.rows_appended,
+        columns_written: plan.columns_written,
+        target_anchor: plan.target_anchor.clone(),
+        target_range: plan.target_range.clone(),
+        expand_adjacent_sums: true,
+        confidence: plan.confidence.clone(),
+        confidence_reason: plan.confidence_reason.clone(),
+        warnings: plan.warnings.clone(),
+        would_change,
+        changed,
+    }
+}
+
+fn build_append_region_plan(
+    source: &Path,
+    sheet_name: &str,
+    region_id: Option<u32>,
+    table_name: Option<&str>,
+    footer_policy: AppendRegionFooterPolicyArg,
+    rows: Vec<Vec<Option<MatrixCell>>>,
+) -> Result<AppendRegionPlan> {
+    if rows.is_empty() {
+        return Err(invalid_argument(
+            "append-region requires at least one row in the rows payload",
+        ));
+    }
+
+    let config = Arc::new(local_workbook_config(source));
+    let workbook = WorkbookContext::load(&config, source)?;
+    let target =
+        resolve_append_region_target(&workbook, source, sheet_name, region_id, table_name)?;
+    let bounds = target.bounds;
+
+    let columns_written = rows.iter().map(Vec::len).max().unwrap_or(0) as u32;
+    if columns_written == 0 {
+        return Err(invalid_argument(
+            "append-region rows payload must contain at least one non-empty column",
+        ));
+    }
+    let region_width = bounds.end_col - bounds.start_col + 1;
+    if columns_written > region_width {
+        let target_label = target
+            .table_name
+            .clone()
+            .map(|name| format!("table '{}'", name))
+            .or_else(|| target.region_id.map(|id| format!("region {}", id)))
+            .unwrap_or_else(|| "append target".to_string());
+        return Err(invalid_argument(format!(
+            "rows payload is wider than {} on sheet '{}': payload columns={}, region columns={}",
+            target_label, target.sheet_name, columns_written, region_width
+        )));
+    }
+
+    let footer_scan = detect_append_footer(
+        source,
+        &target.sheet_name,
+        bounds.start_col,
+        bounds.end_col,
+        bounds.end_row,
+    )?;
+    let footer_policy_label = append_footer_policy_label(footer_policy).to_string();
+    let (insert_at_row, insert_reason) = match footer_policy {
+        AppendRegionFooterPolicyArg::Auto => {
+            if let Some(row) = footer_scan.footer_row {
+                (
+                    row,
+                    format!("auto policy selected detected footer row {}", row),
+                )
+            } else {
+                (
+                    bounds.end_row + 1,
+                    format!(
+                        "auto policy found no footer row; appending after detected region end row {}",
+                        bounds.end_row
+                    ),
+                )
+            }
+        }
+        AppendRegionFooterPolicyArg::BeforeFooter => {
+            let row = footer_scan.footer_row.ok_or_else(|| {
+                invalid_argument(
+                    "footer policy 'before-footer' requires a detected footer/subtotal row; use --footer-policy auto or append-at-end to continue without one",
+                )
+            })?;
+            (
+                row,
+                format!("before_footer policy selected detected footer row {}", row),
+            )
+        }
+        AppendRegionFooterPolicyArg::AppendAtEnd => {
+            if let Some(row) = footer_scan.footer_row {
+                (
+                    bounds.end_row + 1,
+                    format!(
+                        "append_at_end policy bypassed detected footer row {} and appended after region end row {}",
+                        row, bounds.end_row
+                    ),
+                )
+            } else {
+                (
+                    bounds.end_row + 1,
+                    format!(
+                        "append_at_end policy appended after detected region end row {}",
+                        bounds.end_row
+                    ),
+                )
+            }
+        }
+    };
+    let target_anchor = format!(
+        "{}{}",
+        column_number_to_name(bounds.start_col),
+        insert_at_row
+    );
+    let target_range = format_a1_range(
+        bounds.start_col,
+        bounds.start_col + columns_written - 1,
+        insert_at_row,
+        insert_at_row + rows.len() as u32 - 1,
+    );
+
+    let mut warnings = Vec::new();
+    if target.headers_truncated {
+        warnings.push(
+            "detected region headers were truncated; verify the append target carefully"
+                .to_string(),
+        );
+    }
+    match footer_policy {
+        AppendRegionFooterPolicyArg::Auto if footer_scan.footer_row.is_none() => {
+            warnings.push("no footer row detected; appending at detected region end".to_string());
+        }
+        AppendRegionFooterPolicyArg::AppendAtEnd if footer_scan.footer_row.is_some() => {
+            warnings.push(format!(
+                "footer policy '{}' ignored detected footer row {}",
+                footer_policy_label,
+                footer_scan.footer_row.unwrap_or_default()
+            ));
+        }
+        _ => {}
+    }
+
+    let (confidence, confidence_reason) = append_plan_confidence(&target, &footer_scan);
+
+    Ok(AppendRegionPlan {
+        sheet_name: target.sheet_name,
+        target_kind: target.target_kind,
+        region_id: target.region_id,
+        table_name: target.table_name,
+        region_bounds: target.region_bounds,
+        header_row: target.header_row,
+        footer_policy: footer_policy_label,
+        insert_at_row,
+        insert_reason,
+        footer_row: footer_scan.footer_row,
+        footer_detection: footer_scan.footer_detection,
+        footer_candidates: footer_scan.footer_candidates,
+        footer_formula_targets: footer_scan.footer_formula_targets,
+        rows_appended: rows.len() as u32,
+        columns_written,
+        target_anchor,
+        target_range,
+        confidence: confidence.to_string(),
+        confidence_reason,
+        warnings,
+        rows,
+    })
+}
+
+fn resolve_append_region_target(
+    workbook: &WorkbookContext,
+    source: &Path,
+    sheet_name: &str,
+    region_id: Option<u32>,
+    table_name: Option<&str>,
+) -> Result<AppendRegionTarget> {
+    match (region_id, table_name) {
+        (Some(_), Some(_)) => Err(invalid_argument(
+            "--region-id and --table-name are mutually exclusive",
+        )),
+        (None, None) => Err(invalid_argument(
+            "append-region requires exactly one of --region-id or --table-name",
+        )),
+        (Some(region_id), None) => {
+            let region = workbook.detected_region(sheet_name, region_id).map_err(|_| {
+                invalid_argument(format!(
+                    "region {} was not found on sheet '{}'; run `asp sheet-overview {} {}` to inspect detected region ids",
+                    region_id,
+                    sheet_name,
+                    source.display(),
+                    sheet_name
+                ))
+            })?;
+            let bounds = parse_append_region_bounds(&region.bounds).ok_or_else(|| {
+                invalid_argument(format!(
+                    "detected region {} on sheet '{}' has unsupported bounds '{}'",
+                    region_id, sheet_name, region.bounds
+                ))
+            })?;
+            Ok(AppendRegionTarget {
+                sheet_name: sheet_name.to_string(),
+                target_kind: AppendRegionTargetKind::DetectedRegion,
+                region_id: Some(region_id),
+                table_name: None,
+                bounds,
+                region_bounds: region.bounds,
+                header_row: region.header_row,
+                headers_truncated: region.headers_truncated,
+            })
+        }
+        (None, Some(table_name)) => resolve_append_table_target(workbook, sheet_name, table_name),
+    }
+}
+
+fn resolve_append_table_target(
+    workbook: &WorkbookContext,
+    sheet_name: &str,
+    table_name: &str,
+) -> Result<AppendRegionTarget> {
+    let lower_name = table_name.to_ascii_lowercase();
+    let items = workbook.named_items()?;
+    let same_sheet = |item: &crate::model::NamedRangeDescriptor| {
+        item.sheet_name
+            .as_deref()
+            .map(|item_sheet| item_sheet.eq_ignore_ascii_case(sheet_name))
+            .unwrap_or(false)
+    };
+
+    let exact_matches: Vec<_> = items
+        .iter()
+        .filter(|item| item.kind == NamedItemKind::Table)
+        .filter(|item| same_sheet(item))
+        .filter(|item| item.name.eq_ignore_ascii_case(table_name))
+        .cloned()
+        .collect();
+    let candidates = if !exact_matches.is_empty() {
+        exact_matches
+    } else {
+        items
+            .into_iter()
+            .filter(|item| item.kind == NamedItemKind::Table)
+            .filter(|item| same_sheet(item))
+            .filter(|item| item.name.to_ascii_lowercase().contains(&lower_name))
+            .collect()
+    };
+
+    let item = match candidates.len() {
+        1 => candidates.into_iter().next().expect("one candidate"),
+        0 => {
+            return Err(invalid_argument(format!(
+                "table '{}' was not found on sheet '{}'; run `asp named-ranges {}` to inspect available table names",
+                table_name,
+                sheet_name,
+                workbook.path.display()
+            )));
+        }
+        _ => {
+            let matches = candidates
+                .into_iter()
+                .map(|item| item.name)
+                .collect::<Vec<_>>()
+                .join(", ");
+            return Err(invalid_argument(format!(
+                "table '{}' matched multiple tables on sheet '{}': {}",
+                table_name, sheet_name, matches
+            )));
+        }
+    };
+
+    let bounds = parse_append_named_item_bounds(&item.refers_to).ok_or_else(|| {
+        invalid_argument(format!(
+            "table '{}' on sheet '{}' has unsupported bounds '{}'",
+            item.name, sheet_name, item.refers_to
+        ))
+    })?;
+
+    Ok(AppendRegionTarget {
+        sheet_name: sheet_name.to_string(),
+        target_kind: AppendRegionTargetKind::Table,
+        region_id: None,
+        table_name: Some(item.name.clone()),
+        region_bounds: format_a1_range(
+            bounds.start_col,
+            bounds.end_col,
+            bounds.start_row,
+            bounds.end_row,
+        ),
+        header_row: Some(bounds.start_row),
+        headers_truncated: false,
+        bounds,
+    })
+}
+
+fn parse_append_named_item_bounds(raw: &str) -> Option<AppendBounds> {
+    let refers_to = raw.trim().trim_start_matches('=');
+    let range_part = refers_to
+        .split_once('!')
+        .map(|(_, rest)| rest)
+        .unwrap_or(refers_to);
+    parse_append_region_bounds(range_part)
+}
+
+fn append_plan_confidence(
+    target: &AppendRegionTarget,
+    footer_scan: &AppendFooterScan,
+) -> (&'static str, String) {
+    if let Some(reason) = footer_scan.footer_detection.as_deref() {
+        if reason.starts_with("footer keyword") {
+            return (
+                "high",
+                format!("explicit footer keyword detected: {}", reason),
+            );
+        }
+        return (
+            "medium",
+            format!("formula-derived footer signal detected: {}", reason),
+        );
+    }
+
+    if matches!(target.target_kind, AppendRegionTargetKind::Table) {
+        return (
+            "medium",
+            format!(
+                "resolved table target '{}' but found no explicit footer row",
+                target.table_name.as_deref().unwrap_or_default()
+            ),
+        );
+    }
+
+    if target.header_row.is_some() {
+        return (
+            "medium",
+            "detected region includes a header row but no explicit footer row was found"
+                .to_string(),
+        );
+    }
+
+    (
+        "low",
+        "no explicit header or footer cues were found; verify the append plan before apply"
+            .to_string(),
+    )
+}
+
+fn append_footer_policy_label(policy: AppendRegionFooterPolicyArg) -> &'static str {
+    match policy {
+        AppendRegionFooterPolicyArg::Auto => "auto",
+        AppendRegionFooterPolicyArg::BeforeFooter => "before_footer",
+        AppendRegionFooterPolicyArg::AppendAtEnd => "append_at_end",
+    }
+}
+
+fn apply_append_region_plan_to_file(path: &Path, plan: &AppendRegionPlan) -> Result<()> {
+    let structure_ops = vec![StructureOp::InsertRows {
+        sheet_name: plan.sheet_name.clone(),
+        at_row: plan.insert_at_row,
+        count: plan.rows_appended,
+        expand_adjacent_sums: true,
+    }];
+    apply_structure_ops_to_file(path, &structure_ops, FormulaParsePolicy::Warn)?;
+
+    let transform_ops = vec![TransformOp::WriteMatrix {
+        sheet_name: plan.sheet_name.clone(),
+        anchor: plan.target_anchor.clone(),
+        rows: plan.rows.clone(),
+        overwrite_formulas: false,
+    }];
+    apply_transform_ops_to_file(path, &transform_ops, false)?;
+
+    if matches!(plan.target_kind, AppendRegionTargetKind::Table)
+        && let Some(table_name) = plan.table_name.as_deref()
+    {
+        expand_table_target_on_file(path, &plan.sheet_name, table_name, plan.rows_appended)?;
+    }
+
+    Ok(())
+}
+
+fn expand_table_target_on_file(
+    path: &Path,
+    sheet_name: &str,
+    table_name: &str,
+    appended_rows: u32,
+) -> Result<()> {
+    let mut book = umya_spreadsheet::reader::xlsx::read(path)
+        .with_context(|| format!("failed to read workbook '{}'", path.display()))?;
+    let sheet = book
+        .get_sheet_by_name_mut(sheet_name)
+        .ok_or_else(|| invalid_argument(format!("sheet '{}' was not found", sheet_name)))?;
+    let table = sheet
+        .get_tables_mut()
+        .iter_mut()
+        .find(|table| {
+            table.get_name().eq_ignore_ascii_case(table_name)
+                || table.get_display_name().eq_ignore_ascii_case(table_name)
+        })
+        .ok_or_else(|| {
+            invalid_argument(format!(
+                "table '{}' was not found on sheet '{}' after append",
+                table_name, sheet_name
+            ))
+        })?;
+
+    let start_col = *table.get_area().0.get_col_num();
+    let start_row = *table.get_area().0.get_row_num();
+    let end_col = *table.get_area().1.get_col_num();
+    let end_row = *table.get_area().1.get_row_num();
+    table.set_area(((start_col, start_row), (end_col, end_row + appended_rows)));
+
+    save_workbook(&book, path)?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum CloneHelperKind {
+    CloneTemplateRow,
+    CloneRowBand,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum CloneAnchorKind {
+    Before,
+    After,
+    InsertAt,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct CloneTemplateSummary {
+    non_empty_cell_count: u32,
+    formula_cell_count: u32,
+    style_cell_count: u32,
+    validation_cell_count: u32,
+    merged_ranges_fully_contained: Vec<String>,
+    merged_ranges_crossing_boundary: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct CloneTemplateRowResponse {
+    mode: String,
+    file: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    target_path: Option<String>,
+    sheet_name: String,
+    helper_kind: CloneHelperKind,
+    source_row: u32,
+    source_row_range: String,
+    anchor_kind: CloneAnchorKind,
+    anchor_row: u32,
+    insert_at_row: u32,
+    count: u32,
+    rows_inserted: u32,
+    inserted_row_range: String,
+    expand_adjacent_sums: bool,
+    patch_target_mode: String,
+    merge_policy: String,
+    template_summary: CloneTemplateSummary,
+    formula_targets: Vec<String>,
+    likely_patch_targets: Vec<String>,
+    adjacent_sum_targets: Vec<String>,
+    warnings: Vec<String>,
+    confidence: String,
+    confidence_reason: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    would_change: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    changed: Option<bool>,
+}
+
+#[derive(Debug, Clone)]
+struct CloneTemplateRowPlan {
+    sheet_name: String,
+    helper_kind: CloneHelperKind,
+    source_row: u32,
+    source_row_range: String,
+    anchor_kind: CloneAnchorKind,
+    anchor_row: u32,
+    insert_at_row: u32,
+    count: u32,
+    rows_inserted: u32,
+    inserted_row_range: String,
+    expand_adjacent_sums: bool,
+    patch_target_mode: String,
+    merge_policy: String,
+    template_summary: CloneTemplateSummary,
+    formula_targets: Vec<String>,
+    likely_patch_targets: Vec<String>,
+    adjacent_sum_targets: Vec<String>,
+    warnings: Vec<String>,
+    confidence: String,
+    confidence_reason: String,
+    contained_merges: Vec<CloneMergeSpan>,
+    contained_validations: Vec<CloneValidationSpec>,
+}
+
+#[derive(Debug, Clone)]
+struct CloneTemplateCellPreview {
+    col: u32,
+    value: String,
+    is_formula: bool,
+}
+
+#[derive(Debug, Clone)]
+struct CloneMergeSpan {
+    start_col: u32,
+    end_col: u32,
+    range: String,
+}
+
+#[derive(Debug, Clone)]
+struct CloneValidationSpec {
+    data_validation: umya_spreadsheet::structs::DataValidation,
+    start_col: u32,
+    end_col: u32,
+    start_row_offset: u32,
+    end_row_offset: u32,
+}
+
+#[derive(Debug, Clone)]
+struct CloneTemplateCellData {
+    col: u32,
+    value: String,
+    formula: Option<String>,
+    style: umya_spreadsheet::Style,
+}
+
+#[derive(Debug, Clone)]
+struct CloneBandTemplateRow {
+    source_row: u32,
+    row_offset: u32,
+    preview_cells: Vec<CloneTemplateCellPreview>,
+    cell_data: Vec<CloneTemplateCellData>,
+    row_dimension: Option<umya_spreadsheet::structs::Row>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct CloneInsertedBlock {
+    block_index: u32,
+    row_range: String,
+}
+
+#[derive(Debug, Serialize)]
+struct CloneRowBandResponse {
+    mode: String,
+    file: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    target_path: Option<String>,
+    sheet_name: String,
+    helper_kind: CloneHelperKind,
+    source_row_range: String,
+    source_row_count: u32,
+    anchor_kind: CloneAnchorKind,
+    anchor_row: u32,
+    insert_at_row: u32,
+    repeat: u32,
+    rows_inserted: u32,
+    inserted_row_range: String,
+    inserted_blocks: Vec<CloneInsertedBlock>,
+    expand_adjacent_sums: bool,
+    patch_target_mode: String,
+    merge_policy: String,
+    template_summary: CloneTemplateSummary,
+    formula_targets: Vec<String>,
+    likely_patch_targets: Vec<String>,
+    adjacent_sum_targets: Vec<String>,
+    warnings: Vec<String>,
+    confidence: String,
+    confidence_reason: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    would_change: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    changed: Option<bool>,
+}
+
+#[derive(Debug, Clone)]
+struct CloneRowBandPlan {
+    sheet_name: String,
+    helper_kind: CloneHelperKind,
+    source_row_range: String,
+    source_row_count: u32,
+    anchor_kind: CloneAnchorKind,
+    anchor_row: u32,
+    insert_at_row: u32,
+    repeat: u32,
+    rows_inserted: u32,
+    inserted_row_range: String,
+    inserted_blocks: Vec<CloneInsertedBlock>,
+    expand_adjacent_sums: bool,
+    patch_target_mode: String,
+    merge_policy: String,
+    template_summary: CloneTemplateSummary,
+    formula_targets: Vec<String>,
+    likely_patch_targets: Vec<String>,
+    adjacent_sum_targets: Vec<String>,
+    warnings: Vec<String>,
+    confidence: String,
+    confidence_reason: String,
+    template_rows: Vec<CloneBandTemplateRow>,
+    contained_merges: Vec<CloneBandMergeSpan>,
+    contained_validations: Vec<CloneValidationSpec>,
+}
+
+#[derive(Debug, Clone)]
+struct CloneBandMergeSpan {
+    start_col: u32,
+    end_col: u32,
+    start_row_offset: u32,
+    end_row_offset: u32,
+    range: String,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn clone_template_row(
+    file: PathBuf,
+    sheet_name: String,
+    source_row: u32,
+    before: Option<u32>,
+    after: Option<u32>,
+    insert_at: Option<u32>,
+    count: u32,
+    expand_adjacent_sums: bool,
+    patch_targets: ClonePatchTargetsArg,
+    merge_policy: CloneMergePolicyArg,
+    dry_run: bool,
+    in_place: bool,
+    output: Option<PathBuf>,
+    force: bool,
+) -> Result<Value> {
+    let selected_modes = dry_run as u8 + in_place as u8 + output.is_some() as u8;
+    if selected_modes != 1 {
+        return Err(invalid_argument(
+            "choose exactly one of --dry-run, --in-place, or --output <PATH>",
+        ));
+    }
+    if force && output.is_none() {
+        return Err(invalid_argument("--force requires --output <PATH>"));
+    }
+
+    let runtime = StatelessRuntime;
+    let source = runtime.normalize_existing_file(&file)?;
+    let plan = build_clone_template_row_plan(
+        &source,
+        &sheet_name,
+        source_row,
+        before,
+        after,
+        insert_at,
+        count,
+        expand_adjacent_sums,
+        patch_targets,
+        merge_policy,
+    )?;
+
+    if dry_run {
+        return Ok(serde_json::to_value(build_clone_template_row_response(
+            &plan,
+            "dry_run",
+            source.display().to_string(),
+            None,
+            Some(true),
+            None,
+            None,
+        ))?);
+    }
+
+    if in_place {
+        let source_path = source.display().to_string();
+        let ((), temp_path) = apply_to_temp_copy(
+            &source,
+            source.parent(),
+            ".clone-template-row-",
+            |work_path| apply_clone_template_row_plan_to_file(work_path, &plan),
+        )?;
+        commit_staged_workbook(
+            &WriteCommit::new("clone-template-row"),
+            &source,
+            temp_path,
+            &source,
+            true,
+        )
+        .await?;
+        return Ok(serde_json::to_value(build_clone_template_row_response(
+            &plan,
+            "in_place",
+            source_path.clone(),
+            Some(source_path.clone()),
+            None,
+            Some(source_path),
+            Some(true),
+        ))?);
+    }
+
+    let target = runtime.normalize_destination_path(
+        output
+            .as_ref()
+            .expect("output required unless dry-run or in-place"),
+    )?;
+    ensure_output_path_is_distinct(&source, &target)?;
+    if path_entry_exists(&target)? && !force {
+        return Err(output_exists(format!(
+            "output path '{}' already exists",
+            target.display()
+        )));
+    }
+
+    let source_path = source.display().to_string();
+    let target_path = target.display().to_string();
+    let ((), temp_path) = apply_to_temp_copy(
+        &source,
+        target.parent(),
+        ".clone-template-row-",
+        |work_path| apply_clone_template_row_plan_to_file(work_path, &plan),
+    )?;
+    commit_staged_workbook(
+        &WriteCommit::new("clone-template-row"),
+        &source,
+        temp_path,
+        &target,
+        force,
+    )
+    .await?;
+
+    Ok(serde_json::to_value(build_clone_template_row_response(
+        &plan,
+        "output",
+        target_path.clone(),
+        Some(source_path),
+        None,
+        Some(target_path),
+        Some(true),
+    ))?)
+}
+
+fn build_clone_template_row_response(
+    plan: &CloneTemplateRowPlan,
+    mode: &str,
+    file: String,
+    source_path: Option<String>,
+    would_change: Option<bool>,
+    target_path: Option<String>,
+    changed: Option<bool>,
+) -> CloneTemplateRowResponse {
+    CloneTemplateRowResponse {
+        mode: mode.to_string(),
+        file,
+        source_path,
+        target_path,
+        sheet_name: plan.sheet_name.clone(),
+        helper_kind: plan.helper_kind,
+        source_row: plan.source_row,
+        source_row_range: plan.source_row_range.clone(),
+        anchor_kind: plan.anchor_kind,
+        anchor_row: plan.anchor_row,
+        insert_at_row: plan.insert_at_row,
+        count: plan.count,
+        rows_inserted: plan.rows_inserted,
+        inserted_row_range: plan.inserted_row_range.clone(),
+        expand_adjacent_sums: plan.expand_adjacent_sums,
+        patch_target_mode: plan.patch_target_mode.clone(),
+        merge_policy: plan.merge_policy.clone(),
+        template_summary: plan.template_summary.clone(),
+        formula_targets: plan.formula_targets.clone(),
+        likely_patch_targets: plan.likely_patch_targets.clone(),
+        adjacent_sum_targets: plan.adjacent_sum_targets.clone(),
+        warnings: plan.warnings.clone(),
+        confidence: plan.confidence.clone(),
+        confidence_reason: plan.confidence_reason.clone(),
+        would_change,
+        changed,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn build_clone_template_row_plan(
+    source: &Path,
+    sheet_name: &str,
+    source_row: u32,
+    before: Option<u32>,
+    after: Option<u32>,
+    insert_at: Option<u32>,
+    count: u32,
+    expand_adjacent_sums: bool,
+    patch_targets: ClonePatchTargetsArg,
+    merge_policy: CloneMergePolicyArg,
+) -> Result<CloneTemplateRowPlan> {
+    if source_row == 0 {
+        return Err(invalid_argument("--source-row must be at least 1"));
+    }
+    if count == 0 {
+        return Err(invalid_argument("--count must be at least 1"));
     }
-}
 
-fn resolve_append_table_target(
-    workbook: &WorkbookContext,
-    sheet_name: &str,
-    table_name: &str,
-) -> Result<AppendRegionTarget> {
-    let lower_name = table_name.to_ascii_lowercase();
-    let items = workbook.named_items()?;
-    let same_sheet = |item: &crate::model::NamedRangeDescriptor| {
-        item.sheet_name
-            .as_deref()
-            .map(|item_sheet| item_sheet.eq_ignore_ascii_case(sheet_name))
-            .unwrap_or(false)
-    };
+    let (anchor_kind, anchor_row, insert_at_row) = resolve_clone_anchor(before, after, insert_at)?;
+    let book = umya_spreadsheet::reader::xlsx::read(source)
+        .with_context(|| format!("failed to read workbook '{}'", source.display()))?;
+    let sheet = book
+        .get_sheet_by_name(sheet_name)
+        .ok_or_else(|| invalid_argument(format!("sheet '{}' was not found", sheet_name)))?;
 
-    let exact_matches: Vec<_> = items
-        .iter()
-        .filter(|item| item.kind == NamedItemKind::Table)
-        .filter(|item| same_sheet(item))
-        .filter(|item| item.name.eq_ignore_ascii_case(table_name))
-        .cloned()
-        .collect();
-    let candidates = if !exact_matches.is_empty() {
-        exact_matches
+    let template_cells = inspect_template_row_cells(sheet, source_row);
+    let (contained_merges, crossing_merges) = inspect_clone_row_merges(sheet, source_row)?;
+    let (contained_validations, crossing_validations, validation_cell_count) =
+        inspect_clone_row_validations(sheet, source_row)?;
+
+    if matches!(merge_policy, CloneMergePolicyArg::Strict) && !crossing_merges.is_empty() {
+        return Err(unsafe_clone_template(format!(
+            "source row {} intersects merged ranges that cross the clone boundary: {}",
+            source_row,
+            crossing_merges.join(", ")
+        )));
+    }
+
+    let source_row_range = format!("{}:{}", source_row, source_row);
+    let inserted_row_range = format!(
+        "{}:{}",
+        insert_at_row,
+        insert_at_row + count.saturating_sub(1)
+    );
+
+    let formula_targets = build_clone_formula_targets(&template_cells, insert_at_row, count);
+    let likely_patch_targets = build_clone_patch_targets(
+        &template_cells,
+        &contained_validations,
+        insert_at_row,
+        count,
+        patch_targets,
+    );
+    let adjacent_sum_targets = if expand_adjacent_sums {
+        preview_adjacent_sum_targets(sheet, insert_at_row, count)
     } else {
-        items
-            .into_iter()
-            .filter(|item| item.kind == NamedItemKind::Table)
-            .filter(|item| same_sheet(item))
-            .filter(|item| item.name.to_ascii_lowercase().contains(&lower_name))
-            .collect()
+        Vec::new()
     };
 
-    let item = match candidates.len() {
-        1 => candidates.into_iter().next().expect("one candidate"),
-        0 => {
-            return Err(invalid_argument(format!(
-                "table '{}' was not found on sheet '{}'; run `asp named-ranges {}` to inspect available table names",
-                table_name,
-                sheet_name,
-                workbook.path.display()
-            )));
-        }
-        _ => {
-            let matches = candidates
-                .into_iter()
-                .map(|item| item.name)
-                .collect::<Vec<_>>()
-                .join(", ");
-            return Err(invalid_argument(format!(
-                "table '{}' matched multiple tables on sheet '{}': {}",
-                table_name, sheet_name, matches
-            )));
-        }
-    };
+    let non_empty_cell_count = template_cells
+        .iter()
+        .filter(|cell| cell.is_formula || !cell.value.trim().is_empty())
+        .count() as u32;
+    let formula_cell_count = template_cells.iter().filter(|cell| cell.is_formula).count() as u32;
+    let style_cell_count = template_cells.len() as u32;
 
-    let bounds = parse_append_named_item_bounds(&item.refers_to).ok_or_else(|| {
-        invalid_argument(format!(
-            "table '{}' on sheet '{}' has unsupported bounds '{}'",
-            item.name, sheet_name, item.refers_to
-        ))
-    })?;
+    let mut warnings = Vec::new();
+    if template_cells.is_empty() {
+        warnings.push(format!(
+            "source row {} has no materialized cells; cloning will insert blank rows",
+            source_row
+        ));
+    }
+    if !crossing_merges.is_empty() {
+        warnings.push(format!(
+            "merge-policy '{}' will not reproduce boundary-crossing merged ranges: {}",
+            clone_merge_policy_label(merge_policy),
+            crossing_merges.join(", ")
+        ));
+    }
+    if !crossing_validations.is_empty() {
+        warnings.push(format!(
+            "row-scoped validation cloning skipped boundary-crossing validation ranges: {}",
+            crossing_validations.join(", ")
+        ));
+    }
+    if expand_adjacent_sums && adjacent_sum_targets.is_empty() {
+        warnings.push(
+            "no adjacent SUM footer formulas qualified for expansion below the inserted rows"
+                .to_string(),
+        );
+    }
 
-    Ok(AppendRegionTarget {
+    let (confidence, confidence_reason) = if template_cells.is_empty() {
+        (
+            "low",
+            "template row has no materialized cells; verify that inserting blank rows is intended"
+                .to_string(),
+        )
+    } else if !crossing_merges.is_empty() || !crossing_validations.is_empty() {
+        (
+            "medium",
+            "clone can proceed, but boundary-crossing merges or validations will not be fully reproduced"
+                .to_string(),
+        )
+    } else {
+        (
+            "high",
+            "template row cloned cleanly with no merge or validation boundary conflicts"
+                .to_string(),
+        )
+    };
+
+    Ok(CloneTemplateRowPlan {
         sheet_name: sheet_name.to_string(),
-        target_kind: AppendRegionTargetKind::Table,
-        region_id: None,
-        table_name: Some(item.name.clone()),
-        region_bounds: format_a1_range(
-            bounds.start_col,
-            bounds.end_col,
-            bounds.start_row,
-            bounds.end_row,
-        ),
-        header_row: Some(bounds.start_row),
-        headers_truncated: false,
-        bounds,
+        helper_kind: CloneHelperKind::CloneTemplateRow,
+        source_row,
+        source_row_range,
+        anchor_kind,
+        anchor_row,
+        insert_at_row,
+        count,
+        rows_inserted: count,
+        inserted_row_range,
+        expand_adjacent_sums,
+        patch_target_mode: clone_patch_targets_label(patch_targets).to_string(),
+        merge_policy: clone_merge_policy_label(merge_policy).to_string(),
+        template_summary: CloneTemplateSummary {
+            non_empty_cell_count,
+            formula_cell_count,
+            style_cell_count,
+            validation_cell_count,
+            merged_ranges_fully_contained: contained_merges
+                .iter()
+                .map(|span| span.range.clone())
+                .collect(),
+            merged_ranges_crossing_boundary: crossing_merges,
+        },
+        formula_targets,
+        likely_patch_targets,
+        adjacent_sum_targets,
+        warnings,
+        confidence: confidence.to_string(),
+        confidence_reason,
+        contained_merges,
+        contained_validations,
     })
 }
 
-fn parse_append_named_item_bounds(raw: &str) -> Option<AppendBounds> {
-    let refers_to = raw.trim().trim_start_matches('=');
-    let range_part = refers_to
-        .split_once('!')
-        .map(|(_, rest)| rest)
-        .unwrap_or(refers_to);
-    parse_append_region_bounds(range_part)
-}
+fn resolve_clone_anchor(
+    before: Option<u32>,
+    after: Option<u32>,
+    insert_at: Option<u32>,
+) -> Result<(CloneAnchorKind, u32, u32)> {
+    let selections = before.is_some() as u8 + after.is_some() as u8 + insert_at.is_some() as u8;
+    if selections != 1 {
+        return Err(invalid_argument(
+            "choose exactly one of --before <ROW>, --after <ROW>, or --insert-at <ROW>",
+        ));
+    }
 
-fn append_plan_confidence(
-    target: &AppendRegionTarget,
-    footer_scan: &AppendFooterScan,
-) -> (&'static str, String) {
-    if let Some(reason) = footer_scan.footer_detection.as_deref() {
-        if reason.starts_with("footer keyword") {
-            return (
-                "high",
-                format!("explicit footer keyword detected: {}", reason),
-            );
+    if let Some(row) = before {
+        if row == 0 {
+            return Err(invalid_argument("--before must be at least 1"));
         }
-        return (
-            "medium",
-            format!("formula-derived footer signal detected: {}", reason),
-        );
+        return Ok((CloneAnchorKind::Before, row, row));
     }
-
-    if matches!(target.target_kind, AppendRegionTargetKind::Table) {
-        return (
-            "medium",
-            format!(
-                "resolved table target '{}' but found no explicit footer row",
-                target.table_name.as_deref().unwrap_or_default()
-            ),
-        );
+    if let Some(row) = after {
+        if row == 0 {
+            return Err(invalid_argument("--after must be at least 1"));
+        }
+        return Ok((CloneAnchorKind::After, row, row + 1));
     }
-
-    if target.header_row.is_some() {
-        return (
-            "medium",
-            "detected region includes a header row but no explicit footer row was found"
-                .to_string(),
-        );
+    let row = insert_at.expect("one anchor row required");
+    if row == 0 {
+        return Err(invalid_argument("--insert-at must be at least 1"));
     }
-
-    (
-        "low",
-        "no explicit header or footer cues were found; verify the append plan before apply"
-            .to_string(),
-    )
+    Ok((CloneAnchorKind::InsertAt, row, row))
 }
 
-fn append_footer_policy_label(policy: AppendRegionFooterPolicyArg) -> &'static str {
-    match policy {
-        AppendRegionFooterPolicyArg::Auto => "auto",
-        AppendRegionFooterPolicyArg::BeforeFooter => "before_footer",
-        AppendRegionFooterPolicyArg::AppendAtEnd => "append_at_end",
+fn inspect_template_row_cells(
+    sheet: &umya_spreadsheet::Worksheet,
+    source_row: u32,
+) -> Vec<CloneTemplateCellPreview> {
+    let max_col = sheet.get_highest_column();
+    let mut cells = Vec::new();
+    for col in 1..=max_col {
+        let Some(cell) = sheet.get_cell((col, source_row)) else {
+            continue;
+        };
+        cells.push(CloneTemplateCellPreview {
+            col,
+            value: cell.get_value().to_string(),
+            is_formula: cell.is_formula(),
+        });
     }
+    cells
 }
 
-fn apply_append_region_plan_to_file(path: &Path, plan: &AppendRegionPlan) -> Result<()> {
-    let structure_ops = vec![StructureOp::InsertRows {
-        sheet_name: plan.sheet_name.clone(),
-        at_row: plan.insert_at_row,
-        count: plan.rows_appended,
-        expand_adjacent_sums: true,
-    }];
-    apply_structure_ops_to_file(path, &structure_ops, FormulaParsePolicy::Warn)?;
-
-    let transform_ops = vec![TransformOp::WriteMatrix {
-        sheet_name: plan.sheet_name.clone(),
-        anchor: plan.target_anchor.clone(),
-        rows: plan.rows.clone(),
-        overwrite_formulas: false,
-    }];
-    apply_transform_ops_to_file(path, &transform_ops)?;
-
-    if matches!(plan.target_kind, AppendRegionTargetKind::Table)
-        && let Some(table_name) = plan.table_name.as_deref()
-    {
-        expand_table_target_on_file(path, &plan.sheet_name, table_name, plan.rows_appended)?;
+fn inspect_clone_row_merges(
+    sheet: &umya_spreadsheet::Worksheet,
+    source_row: u32,
+) -> Result<(Vec<CloneMergeSpan>, Vec<String>)> {
+    let mut contained = Vec::new();
+    let mut crossing = Vec::new();
+    for range in sheet.get_merge_cells() {
+        let raw = range.get_range();
+        let Some(bounds) = parse_append_region_bounds(&raw) else {
+            continue;
+        };
+        if !(bounds.start_row..=bounds.end_row).contains(&source_row) {
+            continue;
+        }
+        if bounds.start_row == source_row && bounds.end_row == source_row {
+            contained.push(CloneMergeSpan {
+                start_col: bounds.start_col,
+                end_col: bounds.end_col,
+                range: raw,
+            });
+        } else {
+            crossing.push(raw);
+        }
     }
-
-    Ok(())
+    Ok((contained, crossing))
 }
 
-fn expand_table_target_on_file(
-    path: &Path,
-    sheet_name: &str,
-    table_name: &str,
-    appended_rows: u32,
-) -> Result<()> {
-    let mut book = umya_spreadsheet::reader::xlsx::read(path)
-        .with_context(|| format!("failed to read workbook '{}'", path.display()))?;
-    let sheet = book
-        .get_sheet_by_name_mut(sheet_name)
-        .ok_or_else(|| invalid_argument(format!("sheet '{}' was not found", sheet_name)))?;
-    let table = sheet
-        .get_tables_mut()
-        .iter_mut()
-        .find(|table| {
-            table.get_name().eq_ignore_ascii_case(table_name)
-                || table.get_display_name().eq_ignore_ascii_case(table_name)
-        })
-        .ok_or_else(|| {
-            invalid_argument(format!(
-                "table '{}' was not found on sheet '{}' after append",
-                table_name, sheet_name
-            ))
-        })?;
+fn inspect_clone_row_validations(
+    sheet: &umya_spreadsheet::Worksheet,
+    source_row: u32,
+) -> Result<(Vec<CloneValidationSpec>, Vec<String>, u32)> {
+    let mut contained = Vec::new();
+    let mut crossing = Vec::new();
+    let mut validation_cols = BTreeSet::new();
 
-    let start_col = *table.get_area().0.get_col_num();
-    let start_row = *table.get_area().0.get_row_num();
-    let end_col = *table.get_area().1.get_col_num();
-    let end_row = *table.get_area().1.get_row_num();
-    table.set_area(((start_col, start_row), (end_col, end_row + appended_rows)));
+    let Some(validations) = sheet.get_data_validations() else {
+        return Ok((contained, crossing, 0));
+    };
 
-    umya_spreadsheet::writer::xlsx::write(&book, path)
-        .with_context(|| format!("failed to write workbook '{}'", path.display()))?;
-    Ok(())
-}
+    for data_validation in validations.get_data_validation_list() {
+        for range in data_validation
+            .get_sequence_of_references()
+            .get_range_collection()
+        {
+            let raw = range.get_range();
+            let Some(bounds) = parse_append_region_bounds(&raw) else {
+                continue;
+            };
+            if !(bounds.start_row..=bounds.end_row).contains(&source_row) {
+                continue;
+            }
+            for col in bounds.start_col..=bounds.end_col {
+                validation_cols.insert(col);
+            }
+            if bounds.start_row == source_row && bounds.end_row == source_row {
+                let mut clone = data_validation.clone();
+                clone
+                    .get_sequence_of_references_mut()
+                    .set_sqref(format_a1_range(
+                        bounds.start_col,
+                        bounds.end_col,
+                        source_row,
+                        source_row,
+                    ));
+                contained.push(CloneValidationSpec {
+                    data_validation: clone,
+                    start_col: bounds.start_col,
+                    end_col: bounds.end_col,
+                    start_row_offset: 0,
+                    end_row_offset: 0,
+                });
+            } else {
+                crossing.push(raw);
+            }
+        }
+    }
 
-#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
-#[serde(rename_all = "snake_case")]
-enum CloneHelperKind {
-    CloneTemplateRow,
-    CloneRowBand,
+    Ok((contained, crossing, validation_cols.len() as u32))
 }
 
-#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
-#[serde(rename_all = "snake_case")]
-enum CloneAnchorKind {
-    Before,
-    After,
-    InsertAt,
+fn build_clone_formula_targets(
+    template_cells: &[CloneTemplateCellPreview],
+    insert_at_row: u32,
+    count: u32,
+) -> Vec<String> {
+    let formula_cols: Vec<u32> = template_cells
+        .iter()
+        .filter(|cell| cell.is_formula)
+        .map(|cell| cell.col)
+        .collect();
+    let mut targets = Vec::new();
+    for row in insert_at_row..(insert_at_row + count) {
+        for col in &formula_cols {
+            targets.push(format!("{}{}", column_number_to_name(*col), row));
+        }
+    }
+    targets
 }
 
-#[derive(Debug, Serialize, Clone)]
-struct CloneTemplateSummary {
-    non_empty_cell_count: u32,
-    formula_cell_count: u32,
-    style_cell_count: u32,
-    validation_cell_count: u32,
-    merged_ranges_fully_contained: Vec<String>,
-    merged_ranges_crossing_boundary: Vec<String>,
-}
+fn get_likely_input_cols_for_row(
+    preview_cells: &[CloneTemplateCellPreview],
+    validations: &[CloneValidationSpec],
+    row_offset: u32,
+    patch_targets: ClonePatchTargetsArg,
+) -> Vec<u32> {
+    let mut target_cols = std::collections::BTreeSet::new();
 
-#[derive(Debug, Serialize)]
-struct CloneTemplateRowResponse {
-    mode: String,
-    file: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    source_path: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    target_path: Option<String>,
-    sheet_name: String,
-    helper_kind: CloneHelperKind,
-    source_row: u32,
-    source_row_range: String,
-    anchor_kind: CloneAnchorKind,
-    anchor_row: u32,
-    insert_at_row: u32,
-    count: u32,
-    rows_inserted: u32,
-    inserted_row_range: String,
-    expand_adjacent_sums: bool,
-    patch_target_mode: String,
-    merge_policy: String,
-    template_summary: CloneTemplateSummary,
-    formula_targets: Vec<String>,
-    likely_patch_targets: Vec<String>,
-    adjacent_sum_targets: Vec<String>,
-    warnings: Vec<String>,
-    confidence: String,
-    confidence_reason: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    would_change: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    changed: Option<bool>,
+    match patch_targets {
+        ClonePatchTargetsArg::None => {}
+        ClonePatchTargetsArg::AllNonFormula => {
+            for cell in preview_cells {
+                if !cell.is_formula {
+                    target_cols.insert(cell.col);
+                }
+            }
+        }
+        ClonePatchTargetsArg::LikelyInputs => {
+            for cell in preview_cells {
+                if cell.is_formula {
+                    continue;
+                }
+                if looks_like_footer_label(&cell.value) {
+                    continue;
+                }
+                let is_numeric = cell.value.trim().parse::<f64>().is_ok();
+                let has_validation = validations.iter().any(|v| {
+                    v.start_col <= cell.col
+                        && v.end_col >= cell.col
+                        && v.start_row_offset <= row_offset
+                        && v.end_row_offset >= row_offset
+                });
+                if is_numeric || has_validation {
+                    target_cols.insert(cell.col);
+                }
+            }
+
+            // Add completely empty cells that have validation
+            for v in validations {
+                if v.start_row_offset <= row_offset && v.end_row_offset >= row_offset {
+                    for col in v.start_col..=v.end_col {
+                        target_cols.insert(col);
+                    }
+                }
+            }
+        }
+    }
+
+    target_cols.into_iter().collect()
 }
 
-#[derive(Debug, Clone)]
-struct CloneTemplateRowPlan {
-    sheet_name: String,
-    helper_kind: CloneHelperKind,
-    source_row: u32,
-    source_row_range: String,
-    anchor_kind: CloneAnchorKind,
-    anchor_row: u32,
+fn build_clone_patch_targets(
+    template_cells: &[CloneTemplateCellPreview],
+    validations: &[CloneValidationSpec],
     insert_at_row: u32,
     count: u32,
-    rows_inserted: u32,
-    inserted_row_range: String,
-    expand_adjacent_sums: bool,
-    patch_target_mode: String,
-    merge_policy: String,
-    template_summary: CloneTemplateSummary,
-    formula_targets: Vec<String>,
-    likely_patch_targets: Vec<String>,
-    adjacent_sum_targets: Vec<String>,
-    warnings: Vec<String>,
-    confidence: String,
-    confidence_reason: String,
-    contained_merges: Vec<CloneMergeSpan>,
-    contained_validations: Vec<CloneValidationSpec>,
-}
+    patch_targets: ClonePatchTargetsArg,
+) -> Vec<String> {
+    let target_cols = get_likely_input_cols_for_row(template_cells, validations, 0, patch_targets);
 
-#[derive(Debug, Clone)]
-struct CloneTemplateCellPreview {
-    col: u32,
-    value: String,
-    is_formula: bool,
+    let mut targets = Vec::new();
+    for row in insert_at_row..(insert_at_row + count) {
+        for col in &target_cols {
+            targets.push(format!("{}{}", column_number_to_name(*col), row));
+        }
+    }
+    targets
 }
 
-#[derive(Debug, Clone)]
-struct CloneMergeSpan {
-    start_col: u32,
-    end_col: u32,
-    range: String,
+fn looks_like_footer_label(value: &str) -> bool {
+    let text = value.trim().to_ascii_lowercase();
+    text.starts_with("total")
+        || text.contains("grand total")
+        || text.contains("subtotal")
+        || text.contains("footer")
 }
 
-#[derive(Debug, Clone)]
-struct CloneValidationSpec {
-    data_validation: umya_spreadsheet::structs::DataValidation,
-    start_col: u32,
-    end_col: u32,
-    start_row_offset: u32,
-    end_row_offset: u32,
+fn preview_adjacent_sum_targets(
+    sheet: &umya_spreadsheet::Worksheet,
+    insert_at_row: u32,
+    count: u32,
+) -> Vec<String> {
+    let mut targets = Vec::new();
+    let pre_shift_subtotal_row = insert_at_row;
+    let post_shift_subtotal_row = insert_at_row + count;
+    let sum_re = simple_sum_range_regex();
+    let max_col = sheet.get_highest_column();
+    for col in 1..=max_col {
+        let Some(cell) = sheet.get_cell((col, pre_shift_subtotal_row)) else {
+            continue;
+        };
+        if !cell.is_formula() {
+            continue;
+        }
+        let formula_text = cell.get_formula().to_string();
+        let formula_bare = formula_text.strip_prefix('=').unwrap_or(&formula_text);
+        let Some(caps) = sum_re.captures(formula_bare) else {
+            continue;
+        };
+        let col1 = caps.get(1).map(|m| m.as_str()).unwrap_or_default();
+        let col2 = caps.get(3).map(|m| m.as_str()).unwrap_or_default();
+        let row2: u32 = caps
+            .get(4)
+            .and_then(|m| m.as_str().parse::<u32>().ok())
+            .unwrap_or(0);
+        if col1 == col2 && row2 + 1 == insert_at_row {
+            targets.push(format!(
+                "{}{}",
+                column_number_to_name(col),
+                post_shift_subtotal_row
+            ));
+        }
+    }
+    targets
 }
 
-#[derive(Debug, Clone)]
-struct CloneTemplateCellData {
-    col: u32,
-    value: String,
-    formula: Option<String>,
-    style: umya_spreadsheet::Style,
+fn simple_sum_range_regex() -> Regex {
+    Regex::new(r"(?i)^SUM\(([A-Z]{1,3})(\d+):([A-Z]{1,3})(\d+)\)$").expect("valid simple sum regex")
 }
 
-#[derive(Debug, Clone)]
-struct CloneBandTemplateRow {
-    source_row: u32,
-    row_offset: u32,
-    preview_cells: Vec<CloneTemplateCellPreview>,
-    cell_data: Vec<CloneTemplateCellData>,
-    row_dimension: Option<umya_spreadsheet::structs::Row>,
+fn apply_clone_template_row_plan_to_file(path: &Path, plan: &CloneTemplateRowPlan) -> Result<()> {
+    let structure_ops = vec![StructureOp::CloneRow {
+        sheet_name: plan.sheet_name.clone(),
+        source_row: plan.source_row,
+        insert_at: plan.insert_at_row,
+        count: plan.count,
+        expand_adjacent_sums: plan.expand_adjacent_sums,
+    }];
+    apply_structure_ops_to_file(path, &structure_ops, FormulaParsePolicy::Warn)?;
+    apply_clone_template_row_postprocess(path, plan)?;
+    Ok(())
 }
 
-#[derive(Debug, Clone, Serialize)]
-struct CloneInsertedBlock {
-    block_index: u32,
-    row_range: String,
-}
+fn apply_clone_template_row_postprocess(path: &Path, plan: &CloneTemplateRowPlan) -> Result<()> {
+    if plan.contained_merges.is_empty() && plan.contained_validations.is_empty() {
+        return Ok(());
+    }
 
-#[derive(Debug, Serialize)]
-struct CloneRowBandResponse {
-    mode: String,
-    file: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    source_path: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    target_path: Option<String>,
-    sheet_name: String,
-    helper_kind: CloneHelperKind,
-    source_row_range: String,
-    source_row_count: u32,
-    anchor_kind: CloneAnchorKind,
-    anchor_row: u32,
-    insert_at_row: u32,
-    repeat: u32,
-    rows_inserted: u32,
-    inserted_row_range: String,
-    inserted_blocks: Vec<CloneInsertedBlock>,
-    expand_adjacent_sums: bool,
-    patch_target_mode: String,
-    merge_policy: String,
-    template_summary: CloneTemplateSummary,
-    formula_targets: Vec<String>,
-    likely_patch_targets: Vec<String>,
-    adjacent_sum_targets: Vec<String>,
-    warnings: Vec<String>,
-    confidence: String,
-    confidence_reason: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    would_change: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    changed: Option<bool>,
-}
+    let mut book = umya_spreadsheet::reader::xlsx::read(path)
+        .with_context(|| format!("failed to read workbook '{}'", path.display()))?;
+    let sheet = book
+        .get_sheet_by_name_mut(&plan.sheet_name)
+        .ok_or_else(|| invalid_argument(format!("sheet '{}' was not found", plan.sheet_name)))?;
 
-#[derive(Debug, Clone)]
-struct CloneRowBandPlan {
-    sheet_name: String,
-    helper_kind: CloneHelperKind,
-    source_row_range: String,
-    source_row_count: u32,
-    anchor_kind: CloneAnchorKind,
-    anchor_row: u32,
-    insert_at_row: u32,
-    repeat: u32,
-    rows_inserted: u32,
-    inserted_row_range: String,
-    inserted_blocks: Vec<CloneInsertedBlock>,
-    expand_adjacent_sums: bool,
-    patch_target_mode: String,
-    merge_policy: String,
-    template_summary: CloneTemplateSummary,
-    formula_targets: Vec<String>,
-    likely_patch_targets: Vec<String>,
-    adjacent_sum_targets: Vec<String>,
-    warnings: Vec<String>,
-    confidence: String,
-    confidence_reason: String,
-    template_rows: Vec<CloneBandTemplateRow>,
-    contained_merges: Vec<CloneBandMergeSpan>,
-    contained_validations: Vec<CloneValidationSpec>,
+    for copy_idx in 0..plan.count {
+        let dest_row = plan.insert_at_row + copy_idx;
+        for merge in &plan.contained_merges {
+            sheet.add_merge_cells(format_a1_range(
+                merge.start_col,
+                merge.end_col,
+                dest_row,
+                dest_row,
+            ));
+        }
+    }
+
+    if !plan.contained_validations.is_empty() {
+        if sheet.get_data_validations().is_none() {
+            sheet.set_data_validations(umya_spreadsheet::structs::DataValidations::default());
+        }
+        let validations = sheet
+            .get_data_validations_mut()
+            .expect("data validations exist after initialization");
+        for copy_idx in 0..plan.count {
+            let dest_row = plan.insert_at_row + copy_idx;
+            for spec in &plan.contained_validations {
+                let mut clone = spec.data_validation.clone();
+                clone
+                    .get_sequence_of_references_mut()
+                    .set_sqref(format_a1_range(
+                        spec.start_col,
+                        spec.end_col,
+                        dest_row,
+                        dest_row,
+                    ));
+                validations.add_data_validation_list(clone);
+            }
+        }
+    }
+
+    save_workbook(&book, path)?;
+    Ok(())
 }
 
-#[derive(Debug, Clone)]
-struct CloneBandMergeSpan {
-    start_col: u32,
-    end_col: u32,
-    start_row_offset: u32,
-    end_row_offset: u32,
-    range: String,
+fn clone_patch_targets_label(mode: ClonePatchTargetsArg) -> &'static str {
+    match mode {
+        ClonePatchTargetsArg::LikelyInputs => "likely_inputs",
+        ClonePatchTargetsArg::AllNonFormula => "all_non_formula",
+        ClonePatchTargetsArg::None => "none",
+    }
+}
+
+fn clone_merge_policy_label(policy: CloneMergePolicyArg) -> &'static str {
+    match policy {
+        CloneMergePolicyArg::Safe => "safe",
+        CloneMergePolicyArg::Strict => "strict",
+    }
 }
 
 #[allow(clippy::too_many_arguments)]
-pub async fn clone_template_row(
+pub async fn clone_row_band(
     file: PathBuf,
     sheet_name: String,
-    source_row: u32,
+    source_rows: String,
     before: Option<u32>,
     after: Option<u32>,
     insert_at: Option<u32>,
-    count: u32,
+    repeat: u32,
     expand_adjacent_sums: bool,
     patch_targets: ClonePatchTargetsArg,
     merge_policy: CloneMergePolicyArg,
@@ -2874,21 +5979,21 @@ pub async fn clone_template_row(
 
     let runtime = StatelessRuntime;
     let source = runtime.normalize_existing_file(&file)?;
-    let plan = build_clone_template_row_plan(
+    let plan = build_clone_row_band_plan(
         &source,
         &sheet_name,
-        source_row,
+        &source_rows,
         before,
         after,
         insert_at,
-        count,
+        repeat,
         expand_adjacent_sums,
         patch_targets,
         merge_policy,
     )?;
 
     if dry_run {
-        return Ok(serde_json::to_value(build_clone_template_row_response(
+        return Ok(serde_json::to_value(build_clone_row_band_response(
             &plan,
             "dry_run",
             source.display().to_string(),
@@ -2901,14 +6006,19 @@ pub async fn clone_template_row(
 
     if in_place {
         let source_path = source.display().to_string();
-        let ((), temp_path) = apply_to_temp_copy(
+        let ((), temp_path) =
+            apply_to_temp_copy(&source, source.parent(), ".clone-row-band-", |work_path| {
+                apply_clone_row_band_plan_to_file(work_path, &plan)
+            })?;
+        commit_staged_workbook(
+            &WriteCommit::new("clone-row-band"),
             &source,
-            source.parent(),
-            ".clone-template-row-",
-            |work_path| apply_clone_template_row_plan_to_file(work_path, &plan),
-        )?;
-        atomic_replace_target(temp_path, &source, true)?;
-        return Ok(serde_json::to_value(build_clone_template_row_response(
+            temp_path,
+            &source,
+            true,
+        )
+        .await?;
+        return Ok(serde_json::to_value(build_clone_row_band_response(
             &plan,
             "in_place",
             source_path.clone(),
@@ -2934,15 +6044,20 @@ pub async fn clone_template_row(
 
     let source_path = source.display().to_string();
     let target_path = target.display().to_string();
-    let ((), temp_path) = apply_to_temp_copy(
+    let ((), temp_path) =
+        apply_to_temp_copy(&source, target.parent(), ".clone-row-band-", |work_path| {
+            apply_clone_row_band_plan_to_file(work_path, &plan)
+        })?;
+    commit_staged_workbook(
+        &WriteCommit::new("clone-row-band"),
         &source,
-        target.parent(),
-        ".clone-template-row-",
-        |work_path| apply_clone_template_row_plan_to_file(work_path, &plan),
-    )?;
-    atomic_replace_target(temp_path, &target, force)?;
+        temp_path,
+        &target,
+        force,
+    )
+    .await?;
 
-    Ok(serde_json::to_value(build_clone_template_row_response(
+    Ok(serde_json::to_value(build_clone_row_band_response(
         &plan,
         "output",
         target_path.clone(),
@@ -2953,30 +6068,31 @@ pub async fn clone_template_row(
     ))?)
 }
 
-fn build_clone_template_row_response(
-    plan: &CloneTemplateRowPlan,
+fn build_clone_row_band_response(
+    plan: &CloneRowBandPlan,
     mode: &str,
     file: String,
     source_path: Option<String>,
     would_change: Option<bool>,
     target_path: Option<String>,
     changed: Option<bool>,
-) -> CloneTemplateRowResponse {
-    CloneTemplateRowResponse {
+) -> CloneRowBandResponse {
+    CloneRowBandResponse {
         mode: mode.to_string(),
         file,
         source_path,
         target_path,
         sheet_name: plan.sheet_name.clone(),
         helper_kind: plan.helper_kind,
-        source_row: plan.source_row,
         source_row_range: plan.source_row_range.clone(),
+        source_row_count: plan.source_row_count,
         anchor_kind: plan.anchor_kind,
         anchor_row: plan.anchor_row,
         insert_at_row: plan.insert_at_row,
-        count: plan.count,
+        repeat: plan.repeat,
         rows_inserted: plan.rows_inserted,
         inserted_row_range: plan.inserted_row_range.clone(),
+        inserted_blocks: plan.inserted_blocks.clone(),
         expand_adjacent_sums: plan.expand_adjacent_sums,
         patch_target_mode: plan.patch_target_mode.clone(),
         merge_policy: plan.merge_policy.clone(),
@@ -2993,78 +6109,90 @@ fn build_clone_template_row_response(
 }
 
 #[allow(clippy::too_many_arguments)]
-fn build_clone_template_row_plan(
+fn build_clone_row_band_plan(
     source: &Path,
     sheet_name: &str,
-    source_row: u32,
+    source_rows: &str,
     before: Option<u32>,
     after: Option<u32>,
     insert_at: Option<u32>,
-    count: u32,
+    repeat: u32,
     expand_adjacent_sums: bool,
     patch_targets: ClonePatchTargetsArg,
     merge_policy: CloneMergePolicyArg,
-) -> Result<CloneTemplateRowPlan> {
-    if source_row == 0 {
-        return Err(invalid_argument("--source-row must be at least 1"));
-    }
-    if count == 0 {
-        return Err(invalid_argument("--count must be at least 1"));
+) -> Result<CloneRowBandPlan> {
+    if repeat == 0 {
+        return Err(invalid_argument("--repeat must be at least 1"));
     }
-
+    let (source_start_row, source_end_row) = parse_clone_row_band(source_rows)?;
+    let source_row_count = source_end_row - source_start_row + 1;
     let (anchor_kind, anchor_row, insert_at_row) = resolve_clone_anchor(before, after, insert_at)?;
+
     let book = umya_spreadsheet::reader::xlsx::read(source)
         .with_context(|| format!("failed to read workbook '{}'", source.display()))?;
     let sheet = book
         .get_sheet_by_name(sheet_name)
         .ok_or_else(|| invalid_argument(format!("sheet '{}' was not found", sheet_name)))?;
 
-    let template_cells = inspect_template_row_cells(sheet, source_row);
-    let (contained_merges, crossing_merges) = inspect_clone_row_merges(sheet, source_row)?;
+    let template_rows = inspect_clone_band_rows(sheet, source_start_row, source_end_row);
+    let (contained_merges, crossing_merges) =
+        inspect_clone_band_merges(sheet, source_start_row, source_end_row)?;
     let (contained_validations, crossing_validations, validation_cell_count) =
-        inspect_clone_row_validations(sheet, source_row)?;
+        inspect_clone_band_validations(sheet, source_start_row, source_end_row)?;
 
     if matches!(merge_policy, CloneMergePolicyArg::Strict) && !crossing_merges.is_empty() {
         return Err(unsafe_clone_template(format!(
-            "source row {} intersects merged ranges that cross the clone boundary: {}",
-            source_row,
+            "source rows {}:{} intersect merged ranges that cross the clone boundary: {}",
+            source_start_row,
+            source_end_row,
             crossing_merges.join(", ")
         )));
     }
 
-    let source_row_range = format!("{}:{}", source_row, source_row);
+    let rows_inserted = source_row_count * repeat;
+    let source_row_range = format!("{}:{}", source_start_row, source_end_row);
     let inserted_row_range = format!(
         "{}:{}",
         insert_at_row,
-        insert_at_row + count.saturating_sub(1)
+        insert_at_row + rows_inserted.saturating_sub(1)
     );
-
-    let formula_targets = build_clone_formula_targets(&template_cells, insert_at_row, count);
-    let likely_patch_targets = build_clone_patch_targets(
-        &template_cells,
+    let inserted_blocks = build_clone_inserted_blocks(insert_at_row, source_row_count, repeat);
+    let formula_targets =
+        build_clone_band_formula_targets(&template_rows, insert_at_row, source_row_count, repeat);
+    let likely_patch_targets = build_clone_band_patch_targets(
+        &template_rows,
         &contained_validations,
         insert_at_row,
-        count,
+        source_row_count,
+        repeat,
         patch_targets,
     );
     let adjacent_sum_targets = if expand_adjacent_sums {
-        preview_adjacent_sum_targets(sheet, insert_at_row, count)
+        preview_adjacent_sum_targets(sheet, insert_at_row, rows_inserted)
     } else {
         Vec::new()
     };
 
-    let non_empty_cell_count = template_cells
+    let non_empty_cell_count = template_rows
         .iter()
+        .flat_map(|row| row.preview_cells.iter())
         .filter(|cell| cell.is_formula || !cell.value.trim().is_empty())
         .count() as u32;
-    let formula_cell_count = template_cells.iter().filter(|cell| cell.is_formula).count() as u32;
-    let style_cell_count = template_cells.len() as u32;
+    let formula_cell_count = template_rows
+        .iter()
+        .flat_map(|row| row.preview_cells.iter())
+        .filter(|cell| cell.is_formula)
+        .count() as u32;
+    let style_cell_count = template_rows
+        .iter()
+        .map(|row| row.cell_data.len() as u32)
+        .sum();
 
     let mut warnings = Vec::new();
-    if template_cells.is_empty() {
+    if template_rows.iter().all(|row| row.cell_data.is_empty()) {
         warnings.push(format!(
-            "source row {} has no materialized cells; cloning will insert blank rows",
-            source_row
+            "source rows {}:{} have no materialized cells; cloning will insert blank rows",
+            source_start_row, source_end_row
         ));
     }
     if !crossing_merges.is_empty() {
@@ -3076,7 +6204,7 @@ fn build_clone_template_row_plan(
     }
     if !crossing_validations.is_empty() {
         warnings.push(format!(
-            "row-scoped validation cloning skipped boundary-crossing validation ranges: {}",
+            "band validation cloning skipped boundary-crossing validation ranges: {}",
             crossing_validations.join(", ")
         ));
     }
@@ -3087,10 +6215,13 @@ fn build_clone_template_row_plan(
         );
     }
 
-    let (confidence, confidence_reason) = if template_cells.is_empty() {
+    let (confidence, confidence_reason) = if template_rows
+        .iter()
+        .all(|row| row.cell_data.is_empty())
+    {
         (
             "low",
-            "template row has no materialized cells; verify that inserting blank rows is intended"
+            "source row band has no materialized cells; verify that inserting blank rows is intended"
                 .to_string(),
         )
     } else if !crossing_merges.is_empty() || !crossing_validations.is_empty() {
@@ -3102,22 +6233,22 @@ fn build_clone_template_row_plan(
     } else {
         (
             "high",
-            "template row cloned cleanly with no merge or validation boundary conflicts"
-                .to_string(),
+            "row band cloned cleanly with no merge or validation boundary conflicts".to_string(),
         )
     };
 
-    Ok(CloneTemplateRowPlan {
+    Ok(CloneRowBandPlan {
         sheet_name: sheet_name.to_string(),
-        helper_kind: CloneHelperKind::CloneTemplateRow,
-        source_row,
+        helper_kind: CloneHelperKind::CloneRowBand,
         source_row_range,
+        source_row_count,
         anchor_kind,
         anchor_row,
         insert_at_row,
-        count,
-        rows_inserted: count,
+        repeat,
+        rows_inserted,
         inserted_row_range,
+        inserted_blocks,
         expand_adjacent_sums,
         patch_target_mode: clone_patch_targets_label(patch_targets).to_string(),
         merge_policy: clone_merge_policy_label(merge_policy).to_string(),
@@ -3138,65 +6269,86 @@ fn build_clone_template_row_plan(
         warnings,
         confidence: confidence.to_string(),
         confidence_reason,
+        template_rows,
         contained_merges,
         contained_validations,
     })
 }
 
-fn resolve_clone_anchor(
-    before: Option<u32>,
-    after: Option<u32>,
-    insert_at: Option<u32>,
-) -> Result<(CloneAnchorKind, u32, u32)> {
-    let selections = before.is_some() as u8 + after.is_some() as u8 + insert_at.is_some() as u8;
-    if selections != 1 {
+fn parse_clone_row_band(raw: &str) -> Result<(u32, u32)> {
+    let (start, end) = raw
+        .split_once(':')
+        .ok_or_else(|| invalid_argument("--source-rows must use START:END notation"))?;
+    let start_row = start
+        .trim()
+        .parse::<u32>()
+        .map_err(|_| invalid_argument("--source-rows start row must be a positive integer"))?;
+    let end_row = end
+        .trim()
+        .parse::<u32>()
+        .map_err(|_| invalid_argument("--source-rows end row must be a positive integer"))?;
+    if start_row == 0 || end_row == 0 {
         return Err(invalid_argument(
-            "choose exactly one of --before <ROW>, --after <ROW>, or --insert-at <ROW>",
+            "--source-rows values must both be at least 1",
         ));
     }
-
-    if let Some(row) = before {
-        if row == 0 {
-            return Err(invalid_argument("--before must be at least 1"));
-        }
-        return Ok((CloneAnchorKind::Before, row, row));
-    }
-    if let Some(row) = after {
-        if row == 0 {
-            return Err(invalid_argument("--after must be at least 1"));
-        }
-        return Ok((CloneAnchorKind::After, row, row + 1));
-    }
-    let row = insert_at.expect("one anchor row required");
-    if row == 0 {
-        return Err(invalid_argument("--insert-at must be at least 1"));
+    if start_row > end_row {
+        return Err(invalid_argument(
+            "--source-rows must be an ascending contiguous range like 12:14",
+        ));
     }
-    Ok((CloneAnchorKind::InsertAt, row, row))
+    Ok((start_row, end_row))
 }
 
-fn inspect_template_row_cells(
+fn inspect_clone_band_rows(
     sheet: &umya_spreadsheet::Worksheet,
-    source_row: u32,
-) -> Vec<CloneTemplateCellPreview> {
+    source_start_row: u32,
+    source_end_row: u32,
+) -> Vec<CloneBandTemplateRow> {
     let max_col = sheet.get_highest_column();
-    let mut cells = Vec::new();
-    for col in 1..=max_col {
-        let Some(cell) = sheet.get_cell((col, source_row)) else {
-            continue;
-        };
-        cells.push(CloneTemplateCellPreview {
-            col,
-            value: cell.get_value().to_string(),
-            is_formula: cell.is_formula(),
+    let mut rows = Vec::new();
+    for source_row in source_start_row..=source_end_row {
+        let row_offset = source_row - source_start_row;
+        let mut preview_cells = Vec::new();
+        let mut cell_data = Vec::new();
+        for col in 1..=max_col {
+            let Some(cell) = sheet.get_cell((col, source_row)) else {
+                continue;
+            };
+            let value = cell.get_value().to_string();
+            let is_formula = cell.is_formula();
+            preview_cells.push(CloneTemplateCellPreview {
+                col,
+                value: value.clone(),
+                is_formula,
+            });
+            cell_data.push(CloneTemplateCellData {
+                col,
+                value,
+                formula: if is_formula {
+                    Some(cell.get_formula().to_string())
+                } else {
+                    None
+                },
+                style: cell.get_style().clone(),
+            });
+        }
+        rows.push(CloneBandTemplateRow {
+            source_row,
+            row_offset,
+            preview_cells,
+            cell_data,
+            row_dimension: sheet.get_row_dimension(&source_row).cloned(),
         });
     }
-    cells
+    rows
 }
 
-fn inspect_clone_row_merges(
+fn inspect_clone_band_merges(
     sheet: &umya_spreadsheet::Worksheet,
-    source_row: u32,
-) -> Result<(Vec<CloneMergeSpan>, Vec<String>)> {
+    source_start_row: u32,
+    source_end_row: u32,
+) -> Result<(Vec<CloneBandMergeSpan>, Vec<String>)> {
     let mut contained = Vec::new();
     let mut crossing = Vec::new();
     for range in sheet.get_merge_cells() {
@@ -3204,13 +6356,15 @@ fn inspect_clone_row_merges(
         let Some(bounds) = parse_append_region_bounds(&raw) else {
             continue;
         };
-        if !(bounds.start_row..=bounds.end_row).contains(&source_row) {
+        if bounds.end_row < source_start_row || bounds.start_row > source_end_row {
             continue;
         }
-        if bounds.start_row == source_row && bounds.end_row == source_row {
-            contained.push(CloneMergeSpan {
+        if bounds.start_row >= source_start_row && bounds.end_row <= source_end_row {
+            contained.push(CloneBandMergeSpan {
                 start_col: bounds.start_col,
                 end_col: bounds.end_col,
+                start_row_offset: bounds.start_row - source_start_row,
+                end_row_offset: bounds.end_row - source_start_row,
                 range: raw,
             });
         } else {
@@ -3220,13 +6374,14 @@ fn inspect_clone_row_merges(
     Ok((contained, crossing))
 }
 
-fn inspect_clone_row_validations(
+fn inspect_clone_band_validations(
     sheet: &umya_spreadsheet::Worksheet,
-    source_row: u32,
+    source_start_row: u32,
+    source_end_row: u32,
 ) -> Result<(Vec<CloneValidationSpec>, Vec<String>, u32)> {
     let mut contained = Vec::new();
     let mut crossing = Vec::new();
-    let mut validation_cols = BTreeSet::new();
+    let mut validation_cells = BTreeSet::new();
 
     let Some(validations) = sheet.get_data_validations() else {
         return Ok((contained, crossing, 0));
@@ -3241,28 +6396,32 @@ fn inspect_clone_row_validations(
             let Some(bounds) = parse_append_region_bounds(&raw) else {
                 continue;
             };
-            if !(bounds.start_row..=bounds.end_row).contains(&source_row) {
+            if bounds.end_row < source_start_row || bounds.start_row > source_end_row {
                 continue;
             }
-            for col in bounds.start_col..=bounds.end_col {
-                validation_cols.insert(col);
+            let intersect_start = bounds.start_row.max(source_start_row);
+            let intersect_end = bounds.end_row.min(source_end_row);
+            for row in intersect_start..=intersect_end {
+                for col in bounds.start_col..=bounds.end_col {
+                    validation_cells.insert((row, col));
+                }
             }
-            if bounds.start_row == source_row && bounds.end_row == source_row {
+            if bounds.start_row >= source_start_row && bounds.end_row <= source_end_row {
                 let mut clone = data_validation.clone();
                 clone
                     .get_sequence_of_references_mut()
                     .set_sqref(format_a1_range(
                         bounds.start_col,
                         bounds.end_col,
-                        source_row,
-                        source_row,
+                        bounds.start_row,
+                        bounds.end_row,
                     ));
                 contained.push(CloneValidationSpec {
                     data_validation: clone,
                     start_col: bounds.start_col,
                     end_col: bounds.end_col,
-                    start_row_offset: 0,
-                    end_row_offset: 0,
+                    start_row_offset: bounds.start_row - source_start_row,
+                    end_row_offset: bounds.end_row - source_start_row,
                 });
             } else {
                 crossing.push(raw);
@@ -3270,180 +6429,147 @@ fn inspect_clone_row_validations(
         }
     }
 
-    Ok((contained, crossing, validation_cols.len() as u32))
+    Ok((contained, crossing, validation_cells.len() as u32))
 }
 
-fn build_clone_formula_targets(
-    template_cells: &[CloneTemplateCellPreview],
+fn build_clone_inserted_blocks(
     insert_at_row: u32,
-    count: u32,
-) -> Vec<String> {
-    let formula_cols: Vec<u32> = template_cells
-        .iter()
-        .filter(|cell| cell.is_formula)
-        .map(|cell| cell.col)
-        .collect();
-    let mut targets = Vec::new();
-    for row in insert_at_row..(insert_at_row + count) {
-        for col in &formula_cols {
-            targets.push(format!("{}{}", column_number_to_name(*col), row));
-        }
-    }
-    targets
-}
-
-fn get_likely_input_cols_for_row(
-    preview_cells: &[CloneTemplateCellPreview],
-    validations: &[CloneValidationSpec],
-    row_offset: u32,
-    patch_targets: ClonePatchTargetsArg,
-) -> Vec<u32> {
-    let mut target_cols = std::collections::BTreeSet::new();
-
-    match patch_targets {
-        ClonePatchTargetsArg::None => {}
-        ClonePatchTargetsArg::AllNonFormula => {
-            for cell in preview_cells {
-                if !cell.is_formula {
-                    target_cols.insert(cell.col);
-                }
-            }
-        }
-        ClonePatchTargetsArg::LikelyInputs => {
-            for cell in preview_cells {
-                if cell.is_formula {
-                    continue;
-                }
-                if looks_like_footer_label(&cell.value) {
-                    continue;
-                }
-                let is_numeric = cell.value.trim().parse::<f64>().is_ok();
-                let has_validation = validations.iter().any(|v| {
-                    v.start_col <= cell.col
-                        && v.end_col >= cell.col
-                        && v.start_row_offset <= row_offset
-                        && v.end_row_offset >= row_offset
-                });
-                if is_numeric || has_validation {
-                    target_cols.insert(cell.col);
-                }
-            }
-
-            // Add completely empty cells that have validation
-            for v in validations {
-                if v.start_row_offset <= row_offset && v.end_row_offset >= row_offset {
-                    for col in v.start_col..=v.end_col {
-                        target_cols.insert(col);
-                    }
-                }
+    source_row_count: u32,
+    repeat: u32,
+) -> Vec<CloneInsertedBlock> {
+    (0..repeat)
+        .map(|block_index| {
+            let start_row = insert_at_row + block_index * source_row_count;
+            CloneInsertedBlock {
+                block_index,
+                row_range: format!(
+                    "{}:{}",
+                    start_row,
+                    start_row + source_row_count.saturating_sub(1)
+                ),
             }
-        }
-    }
-
-    target_cols.into_iter().collect()
+        })
+        .collect()
 }
 
-fn build_clone_patch_targets(
-    template_cells: &[CloneTemplateCellPreview],
-    validations: &[CloneValidationSpec],
+fn build_clone_band_formula_targets(
+    template_rows: &[CloneBandTemplateRow],
     insert_at_row: u32,
-    count: u32,
-    patch_targets: ClonePatchTargetsArg,
+    source_row_count: u32,
+    repeat: u32,
 ) -> Vec<String> {
-    let target_cols = get_likely_input_cols_for_row(template_cells, validations, 0, patch_targets);
-
     let mut targets = Vec::new();
-    for row in insert_at_row..(insert_at_row + count) {
-        for col in &target_cols {
-            targets.push(format!("{}{}", column_number_to_name(*col), row));
+    for block_index in 0..repeat {
+        let block_start = insert_at_row + block_index * source_row_count;
+        for row in template_rows {
+            let dest_row = block_start + row.row_offset;
+            for cell in row.preview_cells.iter().filter(|cell| cell.is_formula) {
+                targets.push(format!("{}{}", column_number_to_name(cell.col), dest_row));
+            }
         }
     }
     targets
 }
 
-fn looks_like_footer_label(value: &str) -> bool {
-    let text = value.trim().to_ascii_lowercase();
-    text.starts_with("total")
-        || text.contains("grand total")
-        || text.contains("subtotal")
-        || text.contains("footer")
-}
-
-fn preview_adjacent_sum_targets(
-    sheet: &umya_spreadsheet::Worksheet,
+fn build_clone_band_patch_targets(
+    template_rows: &[CloneBandTemplateRow],
+    validations: &[CloneValidationSpec],
     insert_at_row: u32,
-    count: u32,
+    source_row_count: u32,
+    repeat: u32,
+    patch_targets: ClonePatchTargetsArg,
 ) -> Vec<String> {
     let mut targets = Vec::new();
-    let pre_shift_subtotal_row = insert_at_row;
-    let post_shift_subtotal_row = insert_at_row + count;
-    let sum_re = simple_sum_range_regex();
-    let max_col = sheet.get_highest_column();
-    for col in 1..=max_col {
-        let Some(cell) = sheet.get_cell((col, pre_shift_subtotal_row)) else {
-            continue;
-        };
-        if !cell.is_formula() {
-            continue;
-        }
-        let formula_text = cell.get_formula().to_string();
-        let formula_bare = formula_text.strip_prefix('=').unwrap_or(&formula_text);
-        let Some(caps) = sum_re.captures(formula_bare) else {
-            continue;
-        };
-        let col1 = caps.get(1).map(|m| m.as_str()).unwrap_or_default();
-        let col2 = caps.get(3).map(|m| m.as_str()).unwrap_or_default();
-        let row2: u32 = caps
-            .get(4)
-            .and_then(|m| m.as_str().parse::<u32>().ok())
-            .unwrap_or(0);
-        if col1 == col2 && row2 + 1 == insert_at_row {
-            targets.push(format!(
-                "{}{}",
-                column_number_to_name(col),
-                post_shift_subtotal_row
-            ));
+
+    // precompute target cols per row_offset
+    let mut cols_by_offset = std::collections::HashMap::new();
+    for row in template_rows {
+        let cols = get_likely_input_cols_for_row(
+            &row.preview_cells,
+            validations,
+            row.row_offset,
+            patch_targets,
+        );
+        cols_by_offset.insert(row.row_offset, cols);
+    }
+
+    for block_index in 0..repeat {
+        let block_start = insert_at_row + block_index * source_row_count;
+        for row in template_rows {
+            let dest_row = block_start + row.row_offset;
+            if let Some(cols) = cols_by_offset.get(&row.row_offset) {
+                for col in cols {
+                    targets.push(format!("{}{}", column_number_to_name(*col), dest_row));
+                }
+            }
         }
     }
     targets
 }
 
-fn simple_sum_range_regex() -> Regex {
-    Regex::new(r"(?i)^SUM\(([A-Z]{1,3})(\d+):([A-Z]{1,3})(\d+)\)$").expect("valid simple sum regex")
-}
-
-fn apply_clone_template_row_plan_to_file(path: &Path, plan: &CloneTemplateRowPlan) -> Result<()> {
-    let structure_ops = vec![StructureOp::CloneRow {
+fn apply_clone_row_band_plan_to_file(path: &Path, plan: &CloneRowBandPlan) -> Result<()> {
+    let structure_ops = vec![StructureOp::InsertRows {
         sheet_name: plan.sheet_name.clone(),
-        source_row: plan.source_row,
-        insert_at: plan.insert_at_row,
-        count: plan.count,
+        at_row: plan.insert_at_row,
+        count: plan.rows_inserted,
         expand_adjacent_sums: plan.expand_adjacent_sums,
     }];
     apply_structure_ops_to_file(path, &structure_ops, FormulaParsePolicy::Warn)?;
-    apply_clone_template_row_postprocess(path, plan)?;
+    apply_clone_row_band_postprocess(path, plan)?;
     Ok(())
 }
 
-fn apply_clone_template_row_postprocess(path: &Path, plan: &CloneTemplateRowPlan) -> Result<()> {
-    if plan.contained_merges.is_empty() && plan.contained_validations.is_empty() {
-        return Ok(());
-    }
-
+fn apply_clone_row_band_postprocess(path: &Path, plan: &CloneRowBandPlan) -> Result<()> {
     let mut book = umya_spreadsheet::reader::xlsx::read(path)
         .with_context(|| format!("failed to read workbook '{}'", path.display()))?;
     let sheet = book
         .get_sheet_by_name_mut(&plan.sheet_name)
         .ok_or_else(|| invalid_argument(format!("sheet '{}' was not found", plan.sheet_name)))?;
 
-    for copy_idx in 0..plan.count {
-        let dest_row = plan.insert_at_row + copy_idx;
+    for block_index in 0..plan.repeat {
+        let block_start = plan.insert_at_row + block_index * plan.source_row_count;
+        for row in &plan.template_rows {
+            let dest_row = block_start + row.row_offset;
+            if let Some(src_dim) = &row.row_dimension {
+                let dest_dim = sheet.get_row_dimension_mut(&dest_row);
+                dest_dim
+                    .set_height(*src_dim.get_height())
+                    .set_descent(*src_dim.get_descent())
+                    .set_thick_bot(*src_dim.get_thick_bot())
+                    .set_custom_height(*src_dim.get_custom_height())
+                    .set_hidden(*src_dim.get_hidden())
+                    .set_style(src_dim.get_style().clone());
+            }
+            for cell in &row.cell_data {
+                let dest_cell = sheet.get_cell_mut((cell.col, dest_row));
+                dest_cell.set_style(cell.style.clone());
+                dest_cell.get_cell_value_mut().remove_formula();
+                if let Some(formula) = &cell.formula {
+                    let shifted = parse_base_formula(formula)
+                        .and_then(|ast| {
+                            shift_formula_ast(
+                                &ast,
+                                0,
+                                dest_row as i32 - row.source_row as i32,
+                                RelativeMode::Excel,
+                            )
+                        })
+                        .ok()
+                        .map(|value| value.strip_prefix('=').unwrap_or(&value).to_string())
+                        .unwrap_or_else(|| formula.clone());
+                    dest_cell.set_formula(shifted);
+                    dest_cell.set_formula_result_default("");
+                } else {
+                    dest_cell.set_value(cell.value.clone());
+                }
+            }
+        }
         for merge in &plan.contained_merges {
             sheet.add_merge_cells(format_a1_range(
                 merge.start_col,
                 merge.end_col,
-                dest_row,
-                dest_row,
+                block_start + merge.start_row_offset,
+                block_start + merge.end_row_offset,
             ));
         }
     }
@@ -3455,8 +6581,8 @@ fn apply_clone_template_row_postprocess(path: &Path, plan: &CloneTemplateRowPlan
         let validations = sheet
             .get_data_validations_mut()
             .expect("data validations exist after initialization");
-        for copy_idx in 0..plan.count {
-            let dest_row = plan.insert_at_row + copy_idx;
+        for block_index in 0..plan.repeat {
+            let block_start = plan.insert_at_row + block_index * plan.source_row_count;
             for spec in &plan.contained_validations {
                 let mut clone = spec.data_validation.clone();
                 clone
@@ -3464,1339 +6590,1426 @@ fn apply_clone_template_row_postprocess(path: &Path, plan: &CloneTemplateRowPlan
                     .set_sqref(format_a1_range(
                         spec.start_col,
                         spec.end_col,
-                        dest_row,
-                        dest_row,
+                        block_start + spec.start_row_offset,
+                        block_start + spec.end_row_offset,
                     ));
                 validations.add_data_validation_list(clone);
             }
         }
     }
 
-    umya_spreadsheet::writer::xlsx::write(&book, path)
-        .with_context(|| format!("failed to write workbook '{}'", path.display()))?;
+    save_workbook(&book, path)?;
     Ok(())
 }
 
-fn clone_patch_targets_label(mode: ClonePatchTargetsArg) -> &'static str {
-    match mode {
-        ClonePatchTargetsArg::LikelyInputs => "likely_inputs",
-        ClonePatchTargetsArg::AllNonFormula => "all_non_formula",
-        ClonePatchTargetsArg::None => "none",
-    }
-}
+fn parse_append_region_rows_from_csv(
+    csv_path: &str,
+    skip_header: bool,
+    column_map: Option<&ColumnMap>,
+) -> Result<Vec<Vec<Option<MatrixCell>>>> {
+    let csv_raw = fs::read_to_string(csv_path).map_err(|e| {
+        invalid_argument(format!("unable to read --from-csv '{}': {}", csv_path, e))
+    })?;
+    let mut records = parse_csv_records(&csv_raw)
+        .map_err(|e| invalid_argument(format!("invalid CSV in '{}': {}", csv_path, e)))?;
 
-fn clone_merge_policy_label(policy: CloneMergePolicyArg) -> &'static str {
-    match policy {
-        CloneMergePolicyArg::Safe => "safe",
-        CloneMergePolicyArg::Strict => "strict",
+    let header_row = if skip_header && !records.is_empty() {
+        Some(records.remove(0))
+    } else {
+        None
+    };
+    if let (Some(column_map), Some(header_row)) = (column_map, header_row) {
+        records = remap_csv_records(records, &header_row, column_map)?;
     }
+
+    Ok(records
+        .into_iter()
+        .map(|row| {
+            row.into_iter()
+                .map(|field| {
+                    let value = csv_field_to_json(&field);
+                    if value.is_null() {
+                        None
+                    } else {
+                        Some(MatrixCell::Value(value))
+                    }
+                })
+                .collect()
+        })
+        .collect())
 }
 
-#[allow(clippy::too_many_arguments)]
-pub async fn clone_row_band(
-    file: PathBuf,
-    sheet_name: String,
-    source_rows: String,
-    before: Option<u32>,
-    after: Option<u32>,
-    insert_at: Option<u32>,
-    repeat: u32,
-    expand_adjacent_sums: bool,
-    patch_targets: ClonePatchTargetsArg,
-    merge_policy: CloneMergePolicyArg,
-    dry_run: bool,
-    in_place: bool,
-    output: Option<PathBuf>,
-    force: bool,
-) -> Result<Value> {
-    let selected_modes = dry_run as u8 + in_place as u8 + output.is_some() as u8;
-    if selected_modes != 1 {
-        return Err(invalid_argument(
-            "choose exactly one of --dry-run, --in-place, or --output <PATH>",
-        ));
+fn parse_append_region_column_map(raw_ref: &str) -> Result<ColumnMap> {
+    let raw = if let Some(path) = raw_ref.strip_prefix('@') {
+        fs::read_to_string(path)
+            .map_err(|e| invalid_argument(format!("unable to read --column-map '{path}': {e}")))?
+    } else {
+        raw_ref.to_string()
+    };
+    serde_json::from_str(&raw)
+        .map_err(|e| invalid_argument(format!("--column-map is not a map-columns mapping: {e}")))
+}
+
+/// Reorder CSV `records` into the target column order of `column_map`, taking
+/// each target column from the CSV column whose header is mapped to it.
+fn remap_csv_records(
+    records: Vec<Vec<String>>,
+    header_row: &[String],
+    column_map: &ColumnMap,
+) -> Result<Vec<Vec<String>>> {
+    let normalize = |header: &str| header.trim().to_lowercase();
+    let mut positions = Vec::with_capacity(column_map.target.headers.len());
+    for target_header in &column_map.target.headers {
+        let position = match column_map.source_for(target_header) {
+            Some(source_header) => Some(
+                header_row
+                    .iter()
+                    .position(|header| normalize(header) == normalize(source_header))
+                    .ok_or_else(|| {
+                        invalid_argument(format!(
+                            "--column-map maps '{source_header}' to '{target_header}', but the CSV has no '{source_header}' column"
+                        ))
+                    })?,
+            ),
+            None => None,
+        };
+        positions.push(position);
     }
-    if force && output.is_none() {
-        return Err(invalid_argument("--force requires --output <PATH>"));
+    // Trailing unmapped target columns would only add blanks.
+    while positions.last().is_some_and(Option::is_none) {
+        positions.pop();
     }
 
-    let runtime = StatelessRuntime;
-    let source = runtime.normalize_existing_file(&file)?;
-    let plan = build_clone_row_band_plan(
-        &source,
-        &sheet_name,
-        &source_rows,
-        before,
-        after,
-        insert_at,
-        repeat,
-        expand_adjacent_sums,
-        patch_targets,
-        merge_policy,
-    )?;
-
-    if dry_run {
-        return Ok(serde_json::to_value(build_clone_row_band_response(
-            &plan,
-            "dry_run",
-            source.display().to_string(),
-            None,
-            Some(true),
-            None,
-            None,
-        ))?);
-    }
+    Ok(records
+        .into_iter()
+        .map(|row| {
+            positions
+                .iter()
+                .map(|position| {
+                    position
+                        .and_then(|idx| row.get(idx).cloned())
+                        .unwrap_or_default()
+                })
+                .collect()
+        })
+        .collect())
+}
 
-    if in_place {
-        let source_path = source.display().to_string();
-        let ((), temp_path) =
-            apply_to_temp_copy(&source, source.parent(), ".clone-row-band-", |work_path| {
-                apply_clone_row_band_plan_to_file(work_path, &plan)
-            })?;
-        atomic_replace_target(temp_path, &source, true)?;
-        return Ok(serde_json::to_value(build_clone_row_band_response(
-            &plan,
-            "in_place",
-            source_path.clone(),
-            Some(source_path.clone()),
-            None,
-            Some(source_path),
-            Some(true),
-        ))?);
-    }
+fn parse_append_region_rows_payload(raw_ref: &str) -> Result<Vec<Vec<Option<MatrixCell>>>> {
+    let raw = if let Some(path) = raw_ref.strip_prefix('@') {
+        fs::read_to_string(path)
+            .with_context(|| format!("failed to read rows payload file '{}'", path))?
+    } else {
+        raw_ref.to_string()
+    };
 
-    let target = runtime.normalize_destination_path(
-        output
-            .as_ref()
-            .expect("output required unless dry-run or in-place"),
-    )?;
-    ensure_output_path_is_distinct(&source, &target)?;
-    if path_entry_exists(&target)? && !force {
-        return Err(output_exists(format!(
-            "output path '{}' already exists",
-            target.display()
-        )));
-    }
+    let value: Value = serde_json::from_str(&raw).map_err(|error| {
+        invalid_argument(format!(
+            "rows payload must be valid JSON (top-level array or object with rows array): {}",
+            error
+        ))
+    })?;
 
-    let source_path = source.display().to_string();
-    let target_path = target.display().to_string();
-    let ((), temp_path) =
-        apply_to_temp_copy(&source, target.parent(), ".clone-row-band-", |work_path| {
-            apply_clone_row_band_plan_to_file(work_path, &plan)
-        })?;
-    atomic_replace_target(temp_path, &target, force)?;
+    let rows_value = if let Some(rows) = value.get("rows") {
+        rows
+    } else {
+        &value
+    };
+    let rows = rows_value.as_array().ok_or_else(|| {
+        invalid_argument("rows payload must be a top-level array or object with a 'rows' array")
+    })?;
 
-    Ok(serde_json::to_value(build_clone_row_band_response(
-        &plan,
-        "output",
-        target_path.clone(),
-        Some(source_path),
-        None,
-        Some(target_path),
-        Some(true),
-    ))?)
+    rows.iter()
+        .map(|row| {
+            let cells = row.as_array().ok_or_else(|| {
+                invalid_argument("each appended row must be a JSON array of cell values")
+            })?;
+            cells.iter().map(parse_append_matrix_cell).collect()
+        })
+        .collect()
 }
 
-fn build_clone_row_band_response(
-    plan: &CloneRowBandPlan,
-    mode: &str,
-    file: String,
-    source_path: Option<String>,
-    would_change: Option<bool>,
-    target_path: Option<String>,
-    changed: Option<bool>,
-) -> CloneRowBandResponse {
-    CloneRowBandResponse {
-        mode: mode.to_string(),
-        file,
-        source_path,
-        target_path,
-        sheet_name: plan.sheet_name.clone(),
-        helper_kind: plan.helper_kind,
-        source_row_range: plan.source_row_range.clone(),
-        source_row_count: plan.source_row_count,
-        anchor_kind: plan.anchor_kind,
-        anchor_row: plan.anchor_row,
-        insert_at_row: plan.insert_at_row,
-        repeat: plan.repeat,
-        rows_inserted: plan.rows_inserted,
-        inserted_row_range: plan.inserted_row_range.clone(),
-        inserted_blocks: plan.inserted_blocks.clone(),
-        expand_adjacent_sums: plan.expand_adjacent_sums,
-        patch_target_mode: plan.patch_target_mode.clone(),
-        merge_policy: plan.merge_policy.clone(),
-        template_summary: plan.template_summary.clone(),
-        formula_targets: plan.formula_targets.clone(),
-        likely_patch_targets: plan.likely_patch_targets.clone(),
-        adjacent_sum_targets: plan.adjacent_sum_targets.clone(),
-        warnings: plan.warnings.clone(),
-        confidence: plan.confidence.clone(),
-        confidence_reason: plan.confidence_reason.clone(),
-        would_change,
-        changed,
+fn parse_append_matrix_cell(value: &Value) -> Result<Option<MatrixCell>> {
+    match value {
+        Value::Null => Ok(None),
+        Value::Object(map) if map.len() == 1 && map.contains_key("f") => {
+            let formula = map
+                .get("f")
+                .and_then(Value::as_str)
+                .ok_or_else(|| invalid_argument("formula cells must use {'f': 'FORMULA'}"))?;
+            Ok(Some(MatrixCell::Formula(formula.to_string())))
+        }
+        Value::Object(map) if map.len() == 1 && map.contains_key("v") => Ok(Some(
+            MatrixCell::Value(map.get("v").cloned().unwrap_or(Value::Null)),
+        )),
+        Value::Object(_) => Err(invalid_argument(
+            "object cells must use {'v': ...} for values or {'f': 'FORMULA'} for formulas",
+        )),
+        other => Ok(Some(MatrixCell::Value(other.clone()))),
     }
 }
 
-#[allow(clippy::too_many_arguments)]
-fn build_clone_row_band_plan(
+fn detect_append_footer(
     source: &Path,
     sheet_name: &str,
-    source_rows: &str,
-    before: Option<u32>,
-    after: Option<u32>,
-    insert_at: Option<u32>,
-    repeat: u32,
-    expand_adjacent_sums: bool,
-    patch_targets: ClonePatchTargetsArg,
-    merge_policy: CloneMergePolicyArg,
-) -> Result<CloneRowBandPlan> {
-    if repeat == 0 {
-        return Err(invalid_argument("--repeat must be at least 1"));
-    }
-    let (source_start_row, source_end_row) = parse_clone_row_band(source_rows)?;
-    let source_row_count = source_end_row - source_start_row + 1;
-    let (anchor_kind, anchor_row, insert_at_row) = resolve_clone_anchor(before, after, insert_at)?;
-
+    start_col: u32,
+    end_col: u32,
+    region_end_row: u32,
+) -> Result<AppendFooterScan> {
     let book = umya_spreadsheet::reader::xlsx::read(source)
         .with_context(|| format!("failed to read workbook '{}'", source.display()))?;
     let sheet = book
         .get_sheet_by_name(sheet_name)
         .ok_or_else(|| invalid_argument(format!("sheet '{}' was not found", sheet_name)))?;
 
-    let template_rows = inspect_clone_band_rows(sheet, source_start_row, source_end_row);
-    let (contained_merges, crossing_merges) =
-        inspect_clone_band_merges(sheet, source_start_row, source_end_row)?;
-    let (contained_validations, crossing_validations, validation_cell_count) =
-        inspect_clone_band_validations(sheet, source_start_row, source_end_row)?;
-
-    if matches!(merge_policy, CloneMergePolicyArg::Strict) && !crossing_merges.is_empty() {
-        return Err(unsafe_clone_template(format!(
-            "source rows {}:{} intersect merged ranges that cross the clone boundary: {}",
-            source_start_row,
-            source_end_row,
-            crossing_merges.join(", ")
-        )));
-    }
-
-    let rows_inserted = source_row_count * repeat;
-    let source_row_range = format!("{}:{}", source_start_row, source_end_row);
-    let inserted_row_range = format!(
-        "{}:{}",
-        insert_at_row,
-        insert_at_row + rows_inserted.saturating_sub(1)
-    );
-    let inserted_blocks = build_clone_inserted_blocks(insert_at_row, source_row_count, repeat);
-    let formula_targets =
-        build_clone_band_formula_targets(&template_rows, insert_at_row, source_row_count, repeat);
-    let likely_patch_targets = build_clone_band_patch_targets(
-        &template_rows,
-        &contained_validations,
-        insert_at_row,
-        source_row_count,
-        repeat,
-        patch_targets,
-    );
-    let adjacent_sum_targets = if expand_adjacent_sums {
-        preview_adjacent_sum_targets(sheet, insert_at_row, rows_inserted)
-    } else {
-        Vec::new()
-    };
-
-    let non_empty_cell_count = template_rows
-        .iter()
-        .flat_map(|row| row.preview_cells.iter())
-        .filter(|cell| cell.is_formula || !cell.value.trim().is_empty())
-        .count() as u32;
-    let formula_cell_count = template_rows
-        .iter()
-        .flat_map(|row| row.preview_cells.iter())
-        .filter(|cell| cell.is_formula)
-        .count() as u32;
-    let style_cell_count = template_rows
-        .iter()
-        .map(|row| row.cell_data.len() as u32)
-        .sum();
+    let mut footer_row = None;
+    let mut footer_detection = None;
+    let mut footer_formula_targets = Vec::new();
+    let mut footer_candidates = Vec::new();
 
-    let mut warnings = Vec::new();
-    if template_rows.iter().all(|row| row.cell_data.is_empty()) {
-        warnings.push(format!(
-            "source rows {}:{} have no materialized cells; cloning will insert blank rows",
-            source_start_row, source_end_row
-        ));
-    }
-    if !crossing_merges.is_empty() {
-        warnings.push(format!(
-            "merge-policy '{}' will not reproduce boundary-crossing merged ranges: {}",
-            clone_merge_policy_label(merge_policy),
-            crossing_merges.join(", ")
-        ));
-    }
-    if !crossing_validations.is_empty() {
-        warnings.push(format!(
-            "band validation cloning skipped boundary-crossing validation ranges: {}",
-            crossing_validations.join(", ")
-        ));
-    }
-    if expand_adjacent_sums && adjacent_sum_targets.is_empty() {
-        warnings.push(
-            "no adjacent SUM footer formulas qualified for expansion below the inserted rows"
-                .to_string(),
-        );
+    for row in [region_end_row, region_end_row + 1] {
+        let reason = footer_reason_for_row(sheet, start_col, end_col, row);
+        let matched = reason.is_some();
+        if footer_row.is_none() && matched {
+            footer_row = Some(row);
+            footer_detection = reason.clone();
+            footer_formula_targets = footer_formula_targets_for_row(sheet, start_col, end_col, row);
+        }
+        footer_candidates.push(AppendFooterCandidate {
+            row,
+            matched,
+            reason,
+        });
     }
 
-    let (confidence, confidence_reason) = if template_rows
-        .iter()
-        .all(|row| row.cell_data.is_empty())
-    {
-        (
-            "low",
-            "source row band has no materialized cells; verify that inserting blank rows is intended"
-                .to_string(),
-        )
-    } else if !crossing_merges.is_empty() || !crossing_validations.is_empty() {
-        (
-            "medium",
-            "clone can proceed, but boundary-crossing merges or validations will not be fully reproduced"
-                .to_string(),
-        )
-    } else {
-        (
-            "high",
-            "row band cloned cleanly with no merge or validation boundary conflicts".to_string(),
-        )
-    };
-
-    Ok(CloneRowBandPlan {
-        sheet_name: sheet_name.to_string(),
-        helper_kind: CloneHelperKind::CloneRowBand,
-        source_row_range,
-        source_row_count,
-        anchor_kind,
-        anchor_row,
-        insert_at_row,
-        repeat,
-        rows_inserted,
-        inserted_row_range,
-        inserted_blocks,
-        expand_adjacent_sums,
-        patch_target_mode: clone_patch_targets_label(patch_targets).to_string(),
-        merge_policy: clone_merge_policy_label(merge_policy).to_string(),
-        template_summary: CloneTemplateSummary {
-            non_empty_cell_count,
-            formula_cell_count,
-            style_cell_count,
-            validation_cell_count,
-            merged_ranges_fully_contained: contained_merges
-                .iter()
-                .map(|span| span.range.clone())
-                .collect(),
-            merged_ranges_crossing_boundary: crossing_merges,
-        },
-        formula_targets,
-        likely_patch_targets,
-        adjacent_sum_targets,
-        warnings,
-        confidence: confidence.to_string(),
-        confidence_reason,
-        template_rows,
-        contained_merges,
-        contained_validations,
+    Ok(AppendFooterScan {
+        footer_row,
+        footer_detection,
+        footer_candidates,
+        footer_formula_targets,
     })
 }
 
-fn parse_clone_row_band(raw: &str) -> Result<(u32, u32)> {
-    let (start, end) = raw
-        .split_once(':')
-        .ok_or_else(|| invalid_argument("--source-rows must use START:END notation"))?;
-    let start_row = start
-        .trim()
-        .parse::<u32>()
-        .map_err(|_| invalid_argument("--source-rows start row must be a positive integer"))?;
-    let end_row = end
-        .trim()
-        .parse::<u32>()
-        .map_err(|_| invalid_argument("--source-rows end row must be a positive integer"))?;
-    if start_row == 0 || end_row == 0 {
-        return Err(invalid_argument(
-            "--source-rows values must both be at least 1",
-        ));
-    }
-    if start_row > end_row {
-        return Err(invalid_argument(
-            "--source-rows must be an ascending contiguous range like 12:14",
-        ));
-    }
-    Ok((start_row, end_row))
-}
-
-fn inspect_clone_band_rows(
+fn footer_formula_targets_for_row(
     sheet: &umya_spreadsheet::Worksheet,
-    source_start_row: u32,
-    source_end_row: u32,
-) -> Vec<CloneBandTemplateRow> {
-    let max_col = sheet.get_highest_column();
-    let mut rows = Vec::new();
-    for source_row in source_start_row..=source_end_row {
-        let row_offset = source_row - source_start_row;
-        let mut preview_cells = Vec::new();
-        let mut cell_data = Vec::new();
-        for col in 1..=max_col {
-            let Some(cell) = sheet.get_cell((col, source_row)) else {
-                continue;
-            };
-            let value = cell.get_value().to_string();
-            let is_formula = cell.is_formula();
-            preview_cells.push(CloneTemplateCellPreview {
-                col,
-                value: value.clone(),
-                is_formula,
-            });
-            cell_data.push(CloneTemplateCellData {
-                col,
-                value,
-                formula: if is_formula {
-                    Some(cell.get_formula().to_string())
-                } else {
-                    None
-                },
-                style: cell.get_style().clone(),
-            });
+    start_col: u32,
+    end_col: u32,
+    row: u32,
+) -> Vec<String> {
+    let mut addresses = Vec::new();
+    for col in start_col..=end_col {
+        let Some(cell) = sheet.get_cell((col, row)) else {
+            continue;
+        };
+        if !cell.get_formula().trim().is_empty() {
+            addresses.push(format!("{}{}", column_number_to_name(col), row));
         }
-        rows.push(CloneBandTemplateRow {
-            source_row,
-            row_offset,
-            preview_cells,
-            cell_data,
-            row_dimension: sheet.get_row_dimension(&source_row).cloned(),
-        });
     }
-    rows
+    addresses
 }
 
-fn inspect_clone_band_merges(
+fn footer_reason_for_row(
     sheet: &umya_spreadsheet::Worksheet,
-    source_start_row: u32,
-    source_end_row: u32,
-) -> Result<(Vec<CloneBandMergeSpan>, Vec<String>)> {
-    let mut contained = Vec::new();
-    let mut crossing = Vec::new();
-    for range in sheet.get_merge_cells() {
-        let raw = range.get_range();
-        let Some(bounds) = parse_append_region_bounds(&raw) else {
+    start_col: u32,
+    end_col: u32,
+    row: u32,
+) -> Option<String> {
+    let mut saw_formula = false;
+    let mut saw_non_formula_non_empty = false;
+    let mut saw_footer_label = None;
+    for col in start_col..=end_col {
+        let Some(cell) = sheet.get_cell((col, row)) else {
             continue;
         };
-        if bounds.end_row < source_start_row || bounds.start_row > source_end_row {
+        let value = cell.get_value().trim().to_string();
+        let formula = cell.get_formula().trim().to_string();
+        let has_formula = !formula.is_empty();
+        if has_formula {
+            saw_formula = true;
+        } else if !value.is_empty() {
+            if looks_like_footer_label(&value) {
+                saw_footer_label = Some(value.clone());
+            } else {
+                saw_non_formula_non_empty = true;
+            }
+        }
+    }
+
+    if let Some(label) = saw_footer_label
+        && saw_formula
+    {
+        return Some(format!(
+            "footer keyword '{}' and formula on row {}",
+            label, row
+        ));
+    }
+
+    (saw_formula && !saw_non_formula_non_empty)
+        .then(|| format!("formula-bearing summary row {}", row))
+}
+
+fn local_workbook_config(source: &Path) -> ServerConfig {
+    let workspace_root = source
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .to_path_buf();
+    ServerConfig {
+        workspace_root: workspace_root.clone(),
+        screenshot_dir: workspace_root.join("screenshots"),
+        path_mappings: Vec::new(),
+        cache_capacity: 8,
+        supported_extensions: vec![
+            "xlsx".to_string(),
+            "xlsm".to_string(),
+            "xls".to_string(),
+            "xlsb".to_string(),
+        ],
+        single_workbook: None,
+        enabled_tools: None,
+        transport: TransportKind::Http,
+        http_bind_address: "127.0.0.1:8079".parse().expect("http bind address"),
+        recalc_enabled: false,
+        recalc_backend: RecalcBackendKind::Auto,
+        vba_enabled: false,
+        max_concurrent_recalcs: 2,
+        tool_timeout_ms: Some(30_000),
+        max_response_bytes: Some(1_000_000),
+        output_profile: OutputProfile::TokenDense,
+        max_payload_bytes: Some(65_536),
+        max_cells: Some(10_000),
+        max_items: Some(500),
+        allow_overwrite: false,
+        named_queries: Default::default(),
+        workbook_aliases: Default::default(),
+        virtual_workspace: false,
+        pin_revisions: false,
+        parse_cache_dir: None,
+        scratch_dir: None,
+        scratch_max_bytes: None,
+        checkpoint_store: None,
+        schedule: None,
+        schedule_mode: None,
+        profile: None,
+    }
+}
+
+fn parse_append_region_bounds(raw: &str) -> Option<AppendBounds> {
+    let (left, right) = raw.split_once(':').map_or((raw, raw), |(a, b)| (a, b));
+    let (start_col, start_row) = parse_append_coord(left)?;
+    let (end_col, end_row) = parse_append_coord(right)?;
+    Some(AppendBounds {
+        start_col: start_col.min(end_col),
+        end_col: start_col.max(end_col),
+        start_row: start_row.min(end_row),
+        end_row: start_row.max(end_row),
+    })
+}
+
+#[derive(Debug, Clone, Copy)]
+struct AppendBounds {
+    start_col: u32,
+    end_col: u32,
+    start_row: u32,
+    end_row: u32,
+}
+
+fn parse_append_coord(raw: &str) -> Option<(u32, u32)> {
+    let coord = raw.trim().trim_start_matches('$');
+    if coord.is_empty() {
+        return None;
+    }
+
+    let mut letters = String::new();
+    let mut digits = String::new();
+    for ch in coord.chars() {
+        if ch == '$' {
             continue;
         }
-        if bounds.start_row >= source_start_row && bounds.end_row <= source_end_row {
-            contained.push(CloneBandMergeSpan {
-                start_col: bounds.start_col,
-                end_col: bounds.end_col,
-                start_row_offset: bounds.start_row - source_start_row,
-                end_row_offset: bounds.end_row - source_start_row,
-                range: raw,
-            });
+        if ch.is_ascii_alphabetic() {
+            if !digits.is_empty() {
+                return None;
+            }
+            letters.push(ch.to_ascii_uppercase());
+        } else if ch.is_ascii_digit() {
+            digits.push(ch);
         } else {
-            crossing.push(raw);
+            return None;
         }
     }
-    Ok((contained, crossing))
+
+    if letters.is_empty() || digits.is_empty() {
+        return None;
+    }
+
+    let mut col = 0u32;
+    for ch in letters.bytes() {
+        col = col
+            .saturating_mul(26)
+            .saturating_add((ch - b'A' + 1) as u32);
+    }
+    let row = digits.parse().ok()?;
+    (col > 0 && row > 0).then_some((col, row))
 }
 
-fn inspect_clone_band_validations(
-    sheet: &umya_spreadsheet::Worksheet,
-    source_start_row: u32,
-    source_end_row: u32,
-) -> Result<(Vec<CloneValidationSpec>, Vec<String>, u32)> {
-    let mut contained = Vec::new();
-    let mut crossing = Vec::new();
-    let mut validation_cells = BTreeSet::new();
+fn column_number_to_name(mut col: u32) -> String {
+    let mut chars = Vec::new();
+    while col > 0 {
+        let rem = ((col - 1) % 26) as u8;
+        chars.push((b'A' + rem) as char);
+        col = (col - 1) / 26;
+    }
+    chars.iter().rev().collect()
+}
 
-    let Some(validations) = sheet.get_data_validations() else {
-        return Ok((contained, crossing, 0));
-    };
+fn format_a1_range(start_col: u32, end_col: u32, start_row: u32, end_row: u32) -> String {
+    let start = format!("{}{}", column_number_to_name(start_col), start_row);
+    let end = format!("{}{}", column_number_to_name(end_col), end_row);
+    if start == end {
+        start
+    } else {
+        format!("{}:{}", start, end)
+    }
+}
 
-    for data_validation in validations.get_data_validation_list() {
-        for range in data_validation
-            .get_sequence_of_references()
-            .get_range_collection()
-        {
-            let raw = range.get_range();
-            let Some(bounds) = parse_append_region_bounds(&raw) else {
-                continue;
-            };
-            if bounds.end_row < source_start_row || bounds.start_row > source_end_row {
-                continue;
-            }
-            let intersect_start = bounds.start_row.max(source_start_row);
-            let intersect_end = bounds.end_row.min(source_end_row);
-            for row in intersect_start..=intersect_end {
-                for col in bounds.start_col..=bounds.end_col {
-                    validation_cells.insert((row, col));
-                }
-            }
-            if bounds.start_row >= source_start_row && bounds.end_row <= source_end_row {
-                let mut clone = data_validation.clone();
-                clone
-                    .get_sequence_of_references_mut()
-                    .set_sqref(format_a1_range(
-                        bounds.start_col,
-                        bounds.end_col,
-                        bounds.start_row,
-                        bounds.end_row,
-                    ));
-                contained.push(CloneValidationSpec {
-                    data_validation: clone,
-                    start_col: bounds.start_col,
-                    end_col: bounds.end_col,
-                    start_row_offset: bounds.start_row - source_start_row,
-                    end_row_offset: bounds.end_row - source_start_row,
-                });
-            } else {
-                crossing.push(raw);
-            }
-        }
+fn parse_ops_payload<T: DeserializeOwned>(
+    raw: &str,
+    expected_shape: &str,
+    minimal_example: &str,
+) -> Result<T> {
+    let guidance = format!(
+        "expected top-level shape: {expected_shape}; minimal valid example: {minimal_example}"
+    );
+    let object = parse_ops_payload_object(raw, &guidance)?;
+
+    serde_json::from_value(Value::Object(object)).map_err(|error| {
+        invalid_ops_payload(format!(
+            "ops payload does not match required schema: {error}; {guidance}"
+        ))
+    })
+}
+
+fn summarize_transform_operation_counts(ops: &[TransformOp]) -> BTreeMap<String, u64> {
+    let mut counts = BTreeMap::new();
+    for op in ops {
+        let key = match op {
+            TransformOp::ClearRange { .. } => "clear_range",
+            TransformOp::ClearContents { .. } => "clear_contents",
+            TransformOp::ClearFormats { .. } => "clear_formats",
+            TransformOp::ClearAll { .. } => "clear_all",
+            TransformOp::FillRange { .. } => "fill_range",
+            TransformOp::ReplaceInRange { .. } => "replace_in_range",
+            TransformOp::WriteMatrix { .. } => "write_matrix",
+            TransformOp::ImportRows { .. } => "import_rows",
+            TransformOp::AppendTableRows { .. } => "append_table_rows",
+            TransformOp::SortRange { .. } => "sort_range",
+            TransformOp::SortTable { .. } => "sort_table",
+            TransformOp::CopyRange { .. } => "copy_range",
+            TransformOp::MoveRange { .. } => "move_range",
+            TransformOp::FillSeries { .. } => "fill_series",
+            TransformOp::Custom { .. } => "custom",
+        };
+        *counts.entry(key.to_string()).or_insert(0) += 1;
     }
+    counts
+}
 
-    Ok((contained, crossing, validation_cells.len() as u32))
+fn summarize_style_operation_counts(ops: &[StyleOp]) -> BTreeMap<String, u64> {
+    let mut counts = BTreeMap::new();
+    counts.insert("style_ops".to_string(), ops.len() as u64);
+    counts
 }
 
-fn build_clone_inserted_blocks(
-    insert_at_row: u32,
-    source_row_count: u32,
-    repeat: u32,
-) -> Vec<CloneInsertedBlock> {
-    (0..repeat)
-        .map(|block_index| {
-            let start_row = insert_at_row + block_index * source_row_count;
-            CloneInsertedBlock {
-                block_index,
-                row_range: format!(
-                    "{}:{}",
-                    start_row,
-                    start_row + source_row_count.saturating_sub(1)
-                ),
-            }
-        })
-        .collect()
+fn summarize_formula_pattern_operation_counts(
+    ops: &[ApplyFormulaPatternOpInput],
+) -> BTreeMap<String, u64> {
+    let mut counts = BTreeMap::new();
+    counts.insert("apply_formula_pattern_ops".to_string(), ops.len() as u64);
+    counts
 }
 
-fn build_clone_band_formula_targets(
-    template_rows: &[CloneBandTemplateRow],
-    insert_at_row: u32,
-    source_row_count: u32,
-    repeat: u32,
-) -> Vec<String> {
-    let mut targets = Vec::new();
-    for block_index in 0..repeat {
-        let block_start = insert_at_row + block_index * source_row_count;
-        for row in template_rows {
-            let dest_row = block_start + row.row_offset;
-            for cell in row.preview_cells.iter().filter(|cell| cell.is_formula) {
-                targets.push(format!("{}{}", column_number_to_name(cell.col), dest_row));
-            }
-        }
+fn summarize_structure_operation_counts(ops: &[StructureOp]) -> BTreeMap<String, u64> {
+    let mut counts = BTreeMap::new();
+    for op in ops {
+        let key = match op {
+            StructureOp::InsertRows { .. } => "insert_rows",
+            StructureOp::DeleteRows { .. } => "delete_rows",
+            StructureOp::InsertCols { .. } => "insert_cols",
+            StructureOp::DeleteCols { .. } => "delete_cols",
+            StructureOp::RenameSheet { .. } => "rename_sheet",
+            StructureOp::RenameSheets { .. } => "rename_sheets",
+            StructureOp::RenameNames { .. } => "rename_names",
+            StructureOp::CreateSheet { .. } => "create_sheet",
+            StructureOp::DeleteSheet { .. } => "delete_sheet",
+            StructureOp::CopySheet { .. } => "copy_sheet",
+            StructureOp::SetTabColor { .. } => "set_tab_color",
+            StructureOp::HideSheet { .. } => "hide_sheet",
+            StructureOp::UnhideSheet { .. } => "unhide_sheet",
+            StructureOp::ReorderSheets { .. } => "reorder_sheets",
+            StructureOp::CopyRange { .. } => "copy_range",
+            StructureOp::MoveRange { .. } => "move_range",
+            StructureOp::MergeCells { .. } => "merge_cells",
+            StructureOp::UnmergeCells { .. } => "unmerge_cells",
+            StructureOp::CloneRow { .. } => "clone_row",
+            StructureOp::BreakExternalLink { .. } => "break_external_link",
+            StructureOp::RepointExternalLink { .. } => "repoint_external_link",
+        };
+        *counts.entry(key.to_string()).or_insert(0) += 1;
     }
-    targets
+    counts
 }
 
-fn build_clone_band_patch_targets(
-    template_rows: &[CloneBandTemplateRow],
-    validations: &[CloneValidationSpec],
-    insert_at_row: u32,
-    source_row_count: u32,
-    repeat: u32,
-    patch_targets: ClonePatchTargetsArg,
-) -> Vec<String> {
-    let mut targets = Vec::new();
+fn summarize_column_size_operation_counts(ops: &[ColumnSizeOp]) -> BTreeMap<String, u64> {
+    let mut counts = BTreeMap::new();
+    for op in ops {
+        let key = match op.size {
+            crate::tools::fork::ColumnSizeSpec::Auto { .. } => "auto",
+            crate::tools::fork::ColumnSizeSpec::Width { .. } => "width",
+            crate::tools::fork::ColumnSizeSpec::Autofit { .. } => "autofit",
+        };
+        *counts.entry(key.to_string()).or_insert(0) += 1;
+    }
+    counts
+}
 
-    // precompute target cols per row_offset
-    let mut cols_by_offset = std::collections::HashMap::new();
-    for row in template_rows {
-        let cols = get_likely_input_cols_for_row(
-            &row.preview_cells,
-            validations,
-            row.row_offset,
-            patch_targets,
-        );
-        cols_by_offset.insert(row.row_offset, cols);
+fn summarize_row_size_operation_counts(ops: &[RowSizeOp]) -> BTreeMap<String, u64> {
+    let mut counts = BTreeMap::new();
+    for op in ops {
+        let key = match op.size {
+            crate::tools::fork::RowSizeSpec::Auto { .. } => "auto",
+            crate::tools::fork::RowSizeSpec::Height { .. } => "height",
+        };
+        *counts.entry(key.to_string()).or_insert(0) += 1;
     }
+    counts
+}
 
-    for block_index in 0..repeat {
-        let block_start = insert_at_row + block_index * source_row_count;
-        for row in template_rows {
-            let dest_row = block_start + row.row_offset;
-            if let Some(cols) = cols_by_offset.get(&row.row_offset) {
-                for col in cols {
-                    targets.push(format!("{}{}", column_number_to_name(*col), dest_row));
-                }
-            }
-        }
+fn summarize_sheet_layout_operation_counts(ops: &[SheetLayoutOp]) -> BTreeMap<String, u64> {
+    let mut counts = BTreeMap::new();
+    for op in ops {
+        let key = match op {
+            SheetLayoutOp::FreezePanes { .. } => "freeze_panes",
+            SheetLayoutOp::SetZoom { .. } => "set_zoom",
+            SheetLayoutOp::SetGridlines { .. } => "set_gridlines",
+            SheetLayoutOp::SetPageMargins { .. } => "set_page_margins",
+            SheetLayoutOp::SetPageSetup { .. } => "set_page_setup",
+            SheetLayoutOp::SetOrientation { .. } => "set_orientation",
+            SheetLayoutOp::SetHeaderFooter { .. } => "set_header_footer",
+            SheetLayoutOp::SetPrintArea { .. } => "set_print_area",
+            SheetLayoutOp::SetPageBreaks { .. } => "set_page_breaks",
+            SheetLayoutOp::SetRowsHidden { .. } => "set_rows_hidden",
+            SheetLayoutOp::SetColumnsHidden { .. } => "set_columns_hidden",
+            SheetLayoutOp::GroupRows { .. } => "group_rows",
+            SheetLayoutOp::UngroupRows { .. } => "ungroup_rows",
+            SheetLayoutOp::GroupColumns { .. } => "group_columns",
+            SheetLayoutOp::UngroupColumns { .. } => "ungroup_columns",
+        };
+        *counts.entry(key.to_string()).or_insert(0) += 1;
     }
-    targets
+    counts
 }
 
-fn apply_clone_row_band_plan_to_file(path: &Path, plan: &CloneRowBandPlan) -> Result<()> {
-    let structure_ops = vec![StructureOp::InsertRows {
-        sheet_name: plan.sheet_name.clone(),
-        at_row: plan.insert_at_row,
-        count: plan.rows_inserted,
-        expand_adjacent_sums: plan.expand_adjacent_sums,
-    }];
-    apply_structure_ops_to_file(path, &structure_ops, FormulaParsePolicy::Warn)?;
-    apply_clone_row_band_postprocess(path, plan)?;
-    Ok(())
+fn summarize_rules_operation_counts(ops: &[RulesOp]) -> BTreeMap<String, u64> {
+    let mut counts = BTreeMap::new();
+    for op in ops {
+        let key = match op {
+            RulesOp::SetDataValidation { .. } => "set_data_validation",
+            RulesOp::AddConditionalFormat { .. } => "add_conditional_format",
+            RulesOp::SetConditionalFormat { .. } => "set_conditional_format",
+            RulesOp::ClearConditionalFormats { .. } => "clear_conditional_formats",
+        };
+        *counts.entry(key.to_string()).or_insert(0) += 1;
+    }
+    counts
 }
 
-fn apply_clone_row_band_postprocess(path: &Path, plan: &CloneRowBandPlan) -> Result<()> {
-    let mut book = umya_spreadsheet::reader::xlsx::read(path)
-        .with_context(|| format!("failed to read workbook '{}'", path.display()))?;
-    let sheet = book
-        .get_sheet_by_name_mut(&plan.sheet_name)
-        .ok_or_else(|| invalid_argument(format!("sheet '{}' was not found", plan.sheet_name)))?;
+fn transform_summary_indicates_change(counts: &BTreeMap<String, u64>) -> bool {
+    const CHANGE_KEYS: &[&str] = &[
+        "cells_value_cleared",
+        "cells_formula_cleared",
+        "cells_format_cleared",
+        "cells_value_set",
+        "cells_formula_set",
+        "cells_value_replaced",
+        "cells_formula_replaced",
+        "rows_moved",
+        "cells_copied",
+        "cells_moved",
+        "custom_cells_changed",
+    ];
+    any_count_non_zero(counts, CHANGE_KEYS)
+}
 
-    for block_index in 0..plan.repeat {
-        let block_start = plan.insert_at_row + block_index * plan.source_row_count;
-        for row in &plan.template_rows {
-            let dest_row = block_start + row.row_offset;
-            if let Some(src_dim) = &row.row_dimension {
-                let dest_dim = sheet.get_row_dimension_mut(&dest_row);
-                dest_dim
-                    .set_height(*src_dim.get_height())
-                    .set_descent(*src_dim.get_descent())
-                    .set_thick_bot(*src_dim.get_thick_bot())
-                    .set_custom_height(*src_dim.get_custom_height())
-                    .set_hidden(*src_dim.get_hidden())
-                    .set_style(src_dim.get_style().clone());
-            }
-            for cell in &row.cell_data {
-                let dest_cell = sheet.get_cell_mut((cell.col, dest_row));
-                dest_cell.set_style(cell.style.clone());
-                dest_cell.get_cell_value_mut().remove_formula();
-                if let Some(formula) = &cell.formula {
-                    let shifted = parse_base_formula(formula)
-                        .and_then(|ast| {
-                            shift_formula_ast(
-                                &ast,
-                                0,
-                                dest_row as i32 - row.source_row as i32,
-                                RelativeMode::Excel,
-                            )
-                        })
-                        .ok()
-                        .map(|value| value.strip_prefix('=').unwrap_or(&value).to_string())
-                        .unwrap_or_else(|| formula.clone());
-                    dest_cell.set_formula(shifted);
-                    dest_cell.set_formula_result_default("");
-                } else {
-                    dest_cell.set_value(cell.value.clone());
-                }
-            }
-        }
-        for merge in &plan.contained_merges {
-            sheet.add_merge_cells(format_a1_range(
-                merge.start_col,
-                merge.end_col,
-                block_start + merge.start_row_offset,
-                block_start + merge.end_row_offset,
-            ));
-        }
-    }
+fn style_summary_indicates_change(counts: &BTreeMap<String, u64>) -> bool {
+    any_count_non_zero(counts, &["cells_style_changed"])
+}
 
-    if !plan.contained_validations.is_empty() {
-        if sheet.get_data_validations().is_none() {
-            sheet.set_data_validations(umya_spreadsheet::structs::DataValidations::default());
-        }
-        let validations = sheet
-            .get_data_validations_mut()
-            .expect("data validations exist after initialization");
-        for block_index in 0..plan.repeat {
-            let block_start = plan.insert_at_row + block_index * plan.source_row_count;
-            for spec in &plan.contained_validations {
-                let mut clone = spec.data_validation.clone();
-                clone
-                    .get_sequence_of_references_mut()
-                    .set_sqref(format_a1_range(
-                        spec.start_col,
-                        spec.end_col,
-                        block_start + spec.start_row_offset,
-                        block_start + spec.end_row_offset,
-                    ));
-                validations.add_data_validation_list(clone);
-            }
-        }
-    }
+fn formula_pattern_summary_indicates_change(counts: &BTreeMap<String, u64>) -> bool {
+    any_count_non_zero(counts, &["cells_filled"])
+}
+
+fn structure_summary_indicates_change(counts: &BTreeMap<String, u64>) -> bool {
+    any_count_non_zero(
+        counts,
+        &[
+            "rows_inserted",
+            "rows_deleted",
+            "cols_inserted",
+            "cols_deleted",
+            "sheets_renamed",
+            "names_renamed",
+            "sheets_created",
+            "sheets_deleted",
+            "sheets_copied",
+            "cells_copied",
+            "cells_moved",
+            "ranges_copied",
+            "ranges_moved",
+        ],
+    )
+}
 
-    umya_spreadsheet::writer::xlsx::write(&book, path)
-        .with_context(|| format!("failed to write workbook '{}'", path.display()))?;
-    Ok(())
+fn column_size_summary_indicates_change(counts: &BTreeMap<String, u64>) -> bool {
+    any_count_non_zero(counts, &["columns_sized"])
 }
 
-fn parse_append_region_rows_from_csv(
-    csv_path: &str,
-    skip_header: bool,
-) -> Result<Vec<Vec<Option<MatrixCell>>>> {
-    let csv_raw = fs::read_to_string(csv_path).map_err(|e| {
-        invalid_argument(format!("unable to read --from-csv '{}': {}", csv_path, e))
-    })?;
-    let mut records = parse_csv_records(&csv_raw)
-        .map_err(|e| invalid_argument(format!("invalid CSV in '{}': {}", csv_path, e)))?;
+fn row_size_summary_indicates_change(counts: &BTreeMap<String, u64>) -> bool {
+    any_count_non_zero(counts, &["rows_sized"])
+}
 
-    if skip_header && !records.is_empty() {
-        records.remove(0);
-    }
+fn sheet_layout_summary_indicates_change(counts: &BTreeMap<String, u64>) -> bool {
+    any_count_non_zero(
+        counts,
+        &[
+            "ops",
+            "freeze_panes_ops",
+            "set_zoom_ops",
+            "set_gridlines_ops",
+            "set_page_margins_ops",
+            "set_page_setup_ops",
+            "set_orientation_ops",
+            "set_header_footer_ops",
+            "set_print_area_ops",
+            "set_page_breaks_ops",
+            "set_rows_hidden_ops",
+            "set_columns_hidden_ops",
+            "group_rows_ops",
+            "ungroup_rows_ops",
+            "group_columns_ops",
+            "ungroup_columns_ops",
+        ],
+    )
+}
 
-    Ok(records
-        .into_iter()
-        .map(|row| {
-            row.into_iter()
-                .map(|field| {
-                    let value = csv_field_to_json(&field);
-                    if value.is_null() {
-                        None
-                    } else {
-                        Some(MatrixCell::Value(value))
-                    }
-                })
-                .collect()
-        })
-        .collect())
+fn rules_summary_indicates_change(counts: &BTreeMap<String, u64>) -> bool {
+    any_count_non_zero(
+        counts,
+        &[
+            "validations_set",
+            "validations_replaced",
+            "conditional_formats_added",
+            "conditional_formats_set",
+            "conditional_formats_replaced",
+            "conditional_formats_cleared",
+        ],
+    )
 }
 
-fn parse_append_region_rows_payload(raw_ref: &str) -> Result<Vec<Vec<Option<MatrixCell>>>> {
-    let raw = if let Some(path) = raw_ref.strip_prefix('@') {
-        fs::read_to_string(path)
-            .with_context(|| format!("failed to read rows payload file '{}'", path))?
-    } else {
-        raw_ref.to_string()
-    };
+fn grid_import_summary_indicates_change(counts: &BTreeMap<String, u64>) -> bool {
+    counts
+        .iter()
+        .any(|(key, value)| key != "ops" && *value > 0 && !key.starts_with("warnings_"))
+}
 
-    let value: Value = serde_json::from_str(&raw).map_err(|error| {
-        invalid_argument(format!(
-            "rows payload must be valid JSON (top-level array or object with rows array): {}",
-            error
-        ))
-    })?;
+fn any_count_non_zero(counts: &BTreeMap<String, u64>, keys: &[&str]) -> bool {
+    keys.iter()
+        .any(|key| counts.get(*key).copied().unwrap_or(0) > 0)
+}
 
-    let rows_value = if let Some(rows) = value.get("rows") {
-        rows
-    } else {
-        &value
-    };
-    let rows = rows_value.as_array().ok_or_else(|| {
-        invalid_argument("rows payload must be a top-level array or object with a 'rows' array")
-    })?;
+fn warning_strings_to_cli_warnings(messages: Vec<String>) -> Vec<Warning> {
+    messages.into_iter().map(parse_warning_message).collect()
+}
 
-    rows.iter()
-        .map(|row| {
-            let cells = row.as_array().ok_or_else(|| {
-                invalid_argument("each appended row must be a JSON array of cell values")
-            })?;
-            cells.iter().map(parse_append_matrix_cell).collect()
-        })
-        .collect()
+fn merge_cli_warnings(mut left: Vec<Warning>, mut right: Vec<Warning>) -> Vec<Warning> {
+    left.append(&mut right);
+    left
 }
 
-fn parse_append_matrix_cell(value: &Value) -> Result<Option<MatrixCell>> {
-    match value {
-        Value::Null => Ok(None),
-        Value::Object(map) if map.len() == 1 && map.contains_key("f") => {
-            let formula = map
-                .get("f")
-                .and_then(Value::as_str)
-                .ok_or_else(|| invalid_argument("formula cells must use {'f': 'FORMULA'}"))?;
-            Ok(Some(MatrixCell::Formula(formula.to_string())))
+fn parse_warning_message(message: String) -> Warning {
+    if let Some((code, detail)) = message.split_once(':') {
+        let code = code.trim();
+        let detail = detail.trim();
+        if is_warning_code(code) && !detail.is_empty() {
+            return Warning {
+                code: code.to_string(),
+                message: detail.to_string(),
+            };
         }
-        Value::Object(map) if map.len() == 1 && map.contains_key("v") => Ok(Some(
-            MatrixCell::Value(map.get("v").cloned().unwrap_or(Value::Null)),
-        )),
-        Value::Object(_) => Err(invalid_argument(
-            "object cells must use {'v': ...} for values or {'f': 'FORMULA'} for formulas",
-        )),
-        other => Ok(Some(MatrixCell::Value(other.clone()))),
     }
-}
 
-fn detect_append_footer(
-    source: &Path,
-    sheet_name: &str,
-    start_col: u32,
-    end_col: u32,
-    region_end_row: u32,
-) -> Result<AppendFooterScan> {
-    let book = umya_spreadsheet::reader::xlsx::read(source)
-        .with_context(|| format!("failed to read workbook '{}'", source.display()))?;
-    let sheet = book
-        .get_sheet_by_name(sheet_name)
-        .ok_or_else(|| invalid_argument(format!("sheet '{}' was not found", sheet_name)))?;
+    Warning {
+        code: "WARN_INFO".to_string(),
+        message,
+    }
+}
 
-    let mut footer_row = None;
-    let mut footer_detection = None;
-    let mut footer_formula_targets = Vec::new();
-    let mut footer_candidates = Vec::new();
+fn is_warning_code(value: &str) -> bool {
+    value.starts_with("WARN_")
+        && value
+            .chars()
+            .all(|ch| ch.is_ascii_uppercase() || ch == '_' || ch.is_ascii_digit())
+}
 
-    for row in [region_end_row, region_end_row + 1] {
-        let reason = footer_reason_for_row(sheet, start_col, end_col, row);
-        let matched = reason.is_some();
-        if footer_row.is_none() && matched {
-            footer_row = Some(row);
-            footer_detection = reason.clone();
-            footer_formula_targets = footer_formula_targets_for_row(sheet, start_col, end_col, row);
-        }
-        footer_candidates.push(AppendFooterCandidate {
-            row,
-            matched,
-            reason,
-        });
+fn formula_write_provenance(
+    written_via: &str,
+    formula_targets: Vec<String>,
+) -> Option<WritePathProvenance> {
+    if formula_targets.is_empty() {
+        None
+    } else {
+        Some(WritePathProvenance {
+            written_via: written_via.to_string(),
+            formula_targets,
+        })
     }
-
-    Ok(AppendFooterScan {
-        footer_row,
-        footer_detection,
-        footer_candidates,
-        footer_formula_targets,
-    })
 }
 
-fn footer_formula_targets_for_row(
-    sheet: &umya_spreadsheet::Worksheet,
-    start_col: u32,
-    end_col: u32,
-    row: u32,
-) -> Vec<String> {
-    let mut addresses = Vec::new();
-    for col in start_col..=end_col {
-        let Some(cell) = sheet.get_cell((col, row)) else {
-            continue;
-        };
-        if !cell.get_formula().trim().is_empty() {
-            addresses.push(format!("{}{}", column_number_to_name(col), row));
-        }
+fn parse_cell_ref_for_cli(cell: &str) -> Result<(u32, u32)> {
+    let (col, row, _, _) = umya_spreadsheet::helper::coordinate::index_from_coordinate(cell);
+    match (col, row) {
+        (Some(c), Some(r)) if c > 0 && r > 0 => Ok((c, r)),
+        _ => Err(invalid_ops_payload(format!(
+            "invalid cell reference '{}' (expected A1-style reference)",
+            cell
+        ))),
     }
-    addresses
 }
 
-fn footer_reason_for_row(
-    sheet: &umya_spreadsheet::Worksheet,
-    start_col: u32,
-    end_col: u32,
-    row: u32,
-) -> Option<String> {
-    let mut saw_formula = false;
-    let mut saw_non_formula_non_empty = false;
-    let mut saw_footer_label = None;
-    for col in start_col..=end_col {
-        let Some(cell) = sheet.get_cell((col, row)) else {
-            continue;
-        };
-        let value = cell.get_value().trim().to_string();
-        let formula = cell.get_formula().trim().to_string();
-        let has_formula = !formula.is_empty();
-        if has_formula {
-            saw_formula = true;
-        } else if !value.is_empty() {
-            if looks_like_footer_label(&value) {
-                saw_footer_label = Some(value.clone());
+fn transform_formula_targets(ops: &[TransformOp]) -> Vec<String> {
+    ops.iter()
+        .filter_map(|op| match op {
+            TransformOp::FillRange {
+                sheet_name,
+                target,
+                is_formula,
+                ..
+            } if *is_formula => Some(format!("{}!{}", sheet_name, transform_target_label(target))),
+            TransformOp::ReplaceInRange {
+                sheet_name,
+                target,
+                include_formulas,
+                ..
+            } if *include_formulas => {
+                Some(format!("{}!{}", sheet_name, transform_target_label(target)))
+            }
+            TransformOp::WriteMatrix {
+                sheet_name,
+                anchor,
+                rows,
+                ..
+            } if rows.iter().any(|r| {
+                r.iter()
+                    .any(|c| matches!(c, Some(crate::tools::fork::MatrixCell::Formula(_))))
+            }) =>
+            {
+                Some(format!("{}!{}", sheet_name, anchor))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+fn transform_target_label(target: &TransformTarget) -> String {
+    match target {
+        TransformTarget::Range { range } => range.clone(),
+        TransformTarget::Region { region_id } => format!("region:{}", region_id),
+        TransformTarget::Cells { cells } => {
+            if cells.is_empty() {
+                "cells".to_string()
             } else {
-                saw_non_formula_non_empty = true;
+                format!("cells:{}", cells.join(","))
             }
         }
     }
+}
 
-    if let Some(label) = saw_footer_label
-        && saw_formula
-    {
-        return Some(format!(
-            "footer keyword '{}' and formula on row {}",
-            label, row
-        ));
-    }
-
-    (saw_formula && !saw_non_formula_non_empty)
-        .then(|| format!("formula-bearing summary row {}", row))
+fn apply_formula_pattern_targets(ops: &[ApplyFormulaPatternOpInput]) -> Vec<String> {
+    ops.iter()
+        .map(|op| format!("{}!{}", op.sheet_name, op.target_range))
+        .collect()
 }
 
-fn local_workbook_config(source: &Path) -> ServerConfig {
-    let workspace_root = source
-        .parent()
-        .unwrap_or_else(|| Path::new("."))
-        .to_path_buf();
-    ServerConfig {
-        workspace_root: workspace_root.clone(),
-        screenshot_dir: workspace_root.join("screenshots"),
-        path_mappings: Vec::new(),
-        cache_capacity: 8,
-        supported_extensions: vec![
-            "xlsx".to_string(),
-            "xlsm".to_string(),
-            "xls".to_string(),
-            "xlsb".to_string(),
-        ],
-        single_workbook: None,
-        enabled_tools: None,
-        transport: TransportKind::Http,
-        http_bind_address: "127.0.0.1:8079".parse().expect("http bind address"),
-        recalc_enabled: false,
-        recalc_backend: RecalcBackendKind::Auto,
-        vba_enabled: false,
-        max_concurrent_recalcs: 2,
-        tool_timeout_ms: Some(30_000),
-        max_response_bytes: Some(1_000_000),
-        output_profile: OutputProfile::TokenDense,
-        max_payload_bytes: Some(65_536),
-        max_cells: Some(10_000),
-        max_items: Some(500),
-        allow_overwrite: false,
-    }
+#[allow(clippy::too_many_arguments)]
+fn dry_run_response(
+    op_count: usize,
+    operation_counts: BTreeMap<String, u64>,
+    result_counts: BTreeMap<String, u64>,
+    warnings: Vec<Warning>,
+    would_change: bool,
+    formula_parse_diagnostics: Option<FormulaParseDiagnostics>,
+    write_path_provenance: Option<WritePathProvenance>,
+    verification: Option<ExpectationReport>,
+) -> Result<Value> {
+    Ok(serde_json::to_value(BatchDryRunResponse {
+        op_count,
+        validated_count: op_count,
+        would_change,
+        warnings,
+        summary: DryRunSummary {
+            operation_counts,
+            result_counts,
+        },
+        formula_parse_diagnostics,
+        write_path_provenance,
+        verification,
+    })?)
 }
 
-fn parse_append_region_bounds(raw: &str) -> Option<AppendBounds> {
-    let (left, right) = raw.split_once(':').map_or((raw, raw), |(a, b)| (a, b));
-    let (start_col, start_row) = parse_append_coord(left)?;
-    let (end_col, end_row) = parse_append_coord(right)?;
-    Some(AppendBounds {
-        start_col: start_col.min(end_col),
-        end_col: start_col.max(end_col),
-        start_row: start_row.min(end_row),
-        end_row: start_row.max(end_row),
-    })
+#[allow(clippy::too_many_arguments)]
+fn apply_response(
+    op_count: usize,
+    applied_count: usize,
+    warnings: Vec<Warning>,
+    changed: bool,
+    target_path: String,
+    source_path: String,
+    formula_parse_diagnostics: Option<FormulaParseDiagnostics>,
+    write_path_provenance: Option<WritePathProvenance>,
+    verification: Option<ExpectationReport>,
+) -> Result<Value> {
+    Ok(serde_json::to_value(BatchApplyResponse {
+        op_count,
+        applied_count,
+        warnings,
+        changed,
+        target_path,
+        source_path,
+        formula_parse_diagnostics,
+        write_path_provenance,
+        verification,
+    })?)
 }
 
-#[derive(Debug, Clone, Copy)]
-struct AppendBounds {
-    start_col: u32,
-    end_col: u32,
-    start_row: u32,
-    end_row: u32,
+/// Names a CLI write for temp-file staging, for pre/post-apply hooks, for the
+/// `--audit-log` journal, and for `--snapshot-dir` snapshots.
+struct WriteCommit<'a> {
+    command: &'a str,
+    operation_counts: Option<&'a BTreeMap<String, u64>>,
+    audit_log: Option<&'a AuditLog>,
+    snapshot_dir: Option<&'a SnapshotDir>,
 }
 
-fn parse_append_coord(raw: &str) -> Option<(u32, u32)> {
-    let coord = raw.trim().trim_start_matches('$');
-    if coord.is_empty() {
-        return None;
+impl<'a> WriteCommit<'a> {
+    fn new(command: &'a str) -> Self {
+        Self {
+            command,
+            operation_counts: None,
+            audit_log: None,
+            snapshot_dir: None,
+        }
     }
 
-    let mut letters = String::new();
-    let mut digits = String::new();
-    for ch in coord.chars() {
-        if ch == '$' {
-            continue;
-        }
-        if ch.is_ascii_alphabetic() {
-            if !digits.is_empty() {
-                return None;
-            }
-            letters.push(ch.to_ascii_uppercase());
-        } else if ch.is_ascii_digit() {
-            digits.push(ch);
-        } else {
-            return None;
-        }
+    fn with_operation_counts(mut self, counts: &'a BTreeMap<String, u64>) -> Self {
+        self.operation_counts = Some(counts);
+        self
     }
 
-    if letters.is_empty() || digits.is_empty() {
-        return None;
+    fn with_audit_log(mut self, audit_log: Option<&'a AuditLog>) -> Self {
+        self.audit_log = audit_log;
+        self
     }
 
-    let mut col = 0u32;
-    for ch in letters.bytes() {
-        col = col
-            .saturating_mul(26)
-            .saturating_add((ch - b'A' + 1) as u32);
+    fn with_snapshot_dir(mut self, snapshot_dir: Option<&'a SnapshotDir>) -> Self {
+        self.snapshot_dir = snapshot_dir;
+        self
     }
-    let row = digits.parse().ok()?;
-    (col > 0 && row > 0).then_some((col, row))
-}
 
-fn column_number_to_name(mut col: u32) -> String {
-    let mut chars = Vec::new();
-    while col > 0 {
-        let rem = ((col - 1) % 26) as u8;
-        chars.push((b'A' + rem) as char);
-        col = (col - 1) / 26;
+    fn temp_prefix(&self) -> String {
+        format!(".{}-", self.command)
     }
-    chars.iter().rev().collect()
 }
 
-fn format_a1_range(start_col: u32, end_col: u32, start_row: u32, end_row: u32) -> String {
-    let start = format!("{}{}", column_number_to_name(start_col), start_row);
-    let end = format!("{}{}", column_number_to_name(end_col), end_row);
-    if start == end {
-        start
-    } else {
-        format!("{}:{}", start, end)
+/// Replace `target` with the staged workbook, running pre-apply hooks (which
+/// may veto the write) before the replace and post-apply hooks after it. With
+/// a snapshot directory, the current target is copied there just before it is
+/// replaced; with an audit log, a journal record is appended once it is; with
+/// a webhook, it is notified last.
+async fn commit_staged_workbook(
+    commit: &WriteCommit<'_>,
+    source: &Path,
+    temp_path: TempPath,
+    target: &Path,
+    allow_overwrite: bool,
+) -> Result<()> {
+    let hooks_configured = write_hooks_configured();
+    let notify_webhook_configured = webhook_configured();
+    if !hooks_configured && commit.audit_log.is_none() && !notify_webhook_configured {
+        snapshot_target(commit, target)?;
+        atomic_replace_target(temp_path, target, allow_overwrite)?;
+        return record_revision_target(commit, source, target);
+    }
+
+    let staged: &Path = temp_path.as_ref();
+    let changed_cells = changed_cell_manifest(source, staged)
+        .map_err(|error| write_failed(format!("{error:#}")))?;
+    let mut event = WriteHookEvent {
+        phase: WriteHookPhase::PreApply,
+        command: commit.command.to_string(),
+        source_path: source.display().to_string(),
+        target_path: target.display().to_string(),
+        staged_path: Some(staged.display().to_string()),
+        operation_counts: commit.operation_counts.cloned().unwrap_or_default(),
+        changed_cells,
+    };
+    if hooks_configured {
+        run_write_hooks(&event).map_err(|error| anyhow!("hook rejected: {error:#}"))?;
+    }
+
+    let hashes = match commit.audit_log {
+        Some(_) => Some((hash_file_sha256_hex(source)?, hash_file_sha256_hex(staged)?)),
+        None => None,
+    };
+    snapshot_target(commit, target)?;
+    atomic_replace_target(temp_path, target, allow_overwrite)?;
+    record_revision_target(commit, source, target)?;
+
+    if let (Some(audit_log), Some((before_hash, after_hash))) = (commit.audit_log, hashes) {
+        let record = WriteAuditRecord {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            command: event.command.clone(),
+            source_path: event.source_path.clone(),
+            target_path: event.target_path.clone(),
+            ops_hash: audit_log.ops_hash.clone(),
+            operation_counts: event.operation_counts.clone(),
+            before_hash,
+            after_hash,
+            changed_cells: event.changed_cells.clone(),
+        };
+        append_audit_record(&audit_log.path, &record).map_err(|error| {
+            write_failed(format!("workbook written but audit log failed: {error:#}"))
+        })?;
+    }
+
+    if hooks_configured {
+        event.phase = WriteHookPhase::PostApply;
+        event.staged_path = None;
+        run_write_hooks(&event).map_err(|error| anyhow!("hook failed: {error:#}"))?;
+    }
+
+    if notify_webhook_configured {
+        notify_webhook(&WebhookEvent::write_completed(&event))
+            .await
+            .map_err(|error| {
+                write_failed(format!("workbook written but webhook failed: {error:#}"))
+            })?;
     }
+    Ok(())
 }
 
-fn parse_ops_payload<T: DeserializeOwned>(
-    raw: &str,
-    expected_shape: &str,
-    minimal_example: &str,
-) -> Result<T> {
-    let guidance = format!(
-        "expected top-level shape: {expected_shape}; minimal valid example: {minimal_example}"
-    );
-    let object = parse_ops_payload_object(raw, &guidance)?;
+/// Append `target` to the revision manifest of `source` when the write
+/// produced a `<stem>.rev-<n>` revision, e.g. through `--output auto`.
+fn record_revision_target(commit: &WriteCommit<'_>, source: &Path, target: &Path) -> Result<()> {
+    if source == target {
+        return Ok(());
+    }
+    record_revision(source, target, commit.command).map_err(|error| {
+        write_failed(format!(
+            "workbook written but revision manifest failed: {error:#}"
+        ))
+    })?;
+    Ok(())
+}
 
-    serde_json::from_value(Value::Object(object)).map_err(|error| {
-        invalid_ops_payload(format!(
-            "ops payload does not match required schema: {error}; {guidance}"
+fn snapshot_target(commit: &WriteCommit<'_>, target: &Path) -> Result<()> {
+    let Some(snapshot_dir) = commit.snapshot_dir else {
+        return Ok(());
+    };
+    let record = take_snapshot(&snapshot_dir.store, target, commit.command).map_err(|error| {
+        write_failed(format!(
+            "unable to snapshot '{}' before writing: {error:#}",
+            target.display()
         ))
-    })
+    })?;
+    let _ = snapshot_dir.taken.set(record);
+    Ok(())
 }
 
-fn summarize_transform_operation_counts(ops: &[TransformOp]) -> BTreeMap<String, u64> {
-    let mut counts = BTreeMap::new();
-    for op in ops {
-        let key = match op {
-            TransformOp::ClearRange { .. } => "clear_range",
-            TransformOp::FillRange { .. } => "fill_range",
-            TransformOp::ReplaceInRange { .. } => "replace_in_range",
-            TransformOp::WriteMatrix { .. } => "write_matrix",
-        };
-        *counts.entry(key.to_string()).or_insert(0) += 1;
+async fn apply_in_place_with_temp<T, F>(
+    source: &Path,
+    commit: WriteCommit<'_>,
+    apply_fn: F,
+) -> Result<T>
+where
+    F: FnOnce(&Path) -> Result<T>,
+{
+    let (apply_result, temp_path) =
+        apply_to_temp_copy(source, source.parent(), &commit.temp_prefix(), apply_fn)?;
+    commit_staged_workbook(&commit, source, temp_path, source, true).await?;
+    Ok(apply_result)
+}
+
+async fn apply_to_output_with_temp<T, F>(
+    source: &Path,
+    target: &Path,
+    force: bool,
+    commit: WriteCommit<'_>,
+    apply_fn: F,
+) -> Result<T>
+where
+    F: FnOnce(&Path) -> Result<T>,
+{
+    let target_exists = path_entry_exists(target)?;
+    if target_exists && !force {
+        return Err(output_exists(format!(
+            "output path '{}' already exists",
+            target.display()
+        )));
     }
-    counts
+
+    let (apply_result, temp_path) =
+        apply_to_temp_copy(source, target.parent(), &commit.temp_prefix(), apply_fn)?;
+    commit_staged_workbook(&commit, source, temp_path, target, force).await?;
+    Ok(apply_result)
+}
+
+/// `--audit-log` journal for a batch write, with the hash of its ops payload.
+struct AuditLog {
+    path: PathBuf,
+    ops_hash: String,
 }
 
-fn summarize_style_operation_counts(ops: &[StyleOp]) -> BTreeMap<String, u64> {
-    let mut counts = BTreeMap::new();
-    counts.insert("style_ops".to_string(), ops.len() as u64);
-    counts
+fn load_audit_log(path: Option<PathBuf>, ops: &str) -> Result<Option<AuditLog>> {
+    let Some(path) = path else {
+        return Ok(None);
+    };
+    let ops_path = ops.strip_prefix('@').unwrap_or(ops);
+    let ops_hash = hash_file_sha256_hex(Path::new(ops_path)).map_err(|error| {
+        invalid_ops_payload(format!(
+            "unable to read ops payload '{}': {}",
+            ops_path, error
+        ))
+    })?;
+    Ok(Some(AuditLog { path, ops_hash }))
 }
 
-fn summarize_formula_pattern_operation_counts(
-    ops: &[ApplyFormulaPatternOpInput],
-) -> BTreeMap<String, u64> {
-    let mut counts = BTreeMap::new();
-    counts.insert("apply_formula_pattern_ops".to_string(), ops.len() as u64);
-    counts
+/// `--snapshot-dir` for an in-place write, holding the snapshot once taken.
+struct SnapshotDir {
+    store: SnapshotStore,
+    taken: OnceCell<SnapshotRecord>,
 }
 
-fn summarize_structure_operation_counts(ops: &[StructureOp]) -> BTreeMap<String, u64> {
-    let mut counts = BTreeMap::new();
-    for op in ops {
-        let key = match op {
-            StructureOp::InsertRows { .. } => "insert_rows",
-            StructureOp::DeleteRows { .. } => "delete_rows",
-            StructureOp::InsertCols { .. } => "insert_cols",
-            StructureOp::DeleteCols { .. } => "delete_cols",
-            StructureOp::RenameSheet { .. } => "rename_sheet",
-            StructureOp::CreateSheet { .. } => "create_sheet",
-            StructureOp::DeleteSheet { .. } => "delete_sheet",
-            StructureOp::CopyRange { .. } => "copy_range",
-            StructureOp::MoveRange { .. } => "move_range",
-            StructureOp::MergeCells { .. } => "merge_cells",
-            StructureOp::UnmergeCells { .. } => "unmerge_cells",
-            StructureOp::CloneRow { .. } => "clone_row",
-        };
-        *counts.entry(key.to_string()).or_insert(0) += 1;
+impl SnapshotDir {
+    fn new(store: SnapshotStore) -> Self {
+        Self {
+            store,
+            taken: OnceCell::new(),
+        }
     }
-    counts
 }
 
-fn summarize_column_size_operation_counts(ops: &[ColumnSizeOp]) -> BTreeMap<String, u64> {
-    let mut counts = BTreeMap::new();
-    for op in ops {
-        let key = match op.size {
-            crate::tools::fork::ColumnSizeSpec::Auto { .. } => "auto",
-            crate::tools::fork::ColumnSizeSpec::Width { .. } => "width",
-        };
-        *counts.entry(key.to_string()).or_insert(0) += 1;
+fn open_snapshot_store(location: &Path) -> Result<SnapshotStore> {
+    SnapshotStore::open(location).map_err(|error| invalid_argument(format!("{error:#}")))
+}
+
+fn load_snapshot_dir(
+    path: Option<PathBuf>,
+    mode: &BatchMutationMode,
+) -> Result<Option<SnapshotDir>> {
+    let Some(path) = path else {
+        return Ok(None);
+    };
+    if !matches!(mode, BatchMutationMode::InPlace) {
+        return Err(invalid_argument("--snapshot-dir requires --in-place"));
     }
-    counts
+    Ok(Some(SnapshotDir::new(open_snapshot_store(&path)?)))
 }
 
-fn summarize_sheet_layout_operation_counts(ops: &[SheetLayoutOp]) -> BTreeMap<String, u64> {
-    let mut counts = BTreeMap::new();
-    for op in ops {
-        let key = match op {
-            SheetLayoutOp::FreezePanes { .. } => "freeze_panes",
-            SheetLayoutOp::SetZoom { .. } => "set_zoom",
-            SheetLayoutOp::SetGridlines { .. } => "set_gridlines",
-            SheetLayoutOp::SetPageMargins { .. } => "set_page_margins",
-            SheetLayoutOp::SetPageSetup { .. } => "set_page_setup",
-            SheetLayoutOp::SetPrintArea { .. } => "set_print_area",
-            SheetLayoutOp::SetPageBreaks { .. } => "set_page_breaks",
-        };
-        *counts.entry(key.to_string()).or_insert(0) += 1;
+/// Report the snapshot an in-place write took under `snapshot` in its response.
+fn attach_snapshot(mut response: Value, snapshot_dir: Option<&SnapshotDir>) -> Value {
+    if let (Some(object), Some(record)) = (
+        response.as_object_mut(),
+        snapshot_dir.and_then(|dir| dir.taken.get()),
+    ) {
+        object.insert(
+            "snapshot".to_string(),
+            serde_json::to_value(record).unwrap_or(Value::Null),
+        );
     }
-    counts
+    response
 }
 
-fn summarize_rules_operation_counts(ops: &[RulesOp]) -> BTreeMap<String, u64> {
-    let mut counts = BTreeMap::new();
-    for op in ops {
-        let key = match op {
-            RulesOp::SetDataValidation { .. } => "set_data_validation",
-            RulesOp::AddConditionalFormat { .. } => "add_conditional_format",
-            RulesOp::SetConditionalFormat { .. } => "set_conditional_format",
-            RulesOp::ClearConditionalFormats { .. } => "clear_conditional_formats",
-        };
-        *counts.entry(key.to_string()).or_insert(0) += 1;
+/// Report the row order sort ops would produce under `row_permutations`.
+fn attach_row_permutations(
+    mut response: Value,
+    permutations: Vec<crate::fork::RowPermutation>,
+) -> Value {
+    if let Some(object) = response.as_object_mut()
+        && !permutations.is_empty()
+    {
+        object.insert(
+            "row_permutations".to_string(),
+            serde_json::to_value(permutations).unwrap_or(Value::Null),
+        );
     }
-    counts
+    response
 }
 
-fn transform_summary_indicates_change(counts: &BTreeMap<String, u64>) -> bool {
-    const CHANGE_KEYS: &[&str] = &[
-        "cells_value_cleared",
-        "cells_formula_cleared",
-        "cells_value_set",
-        "cells_formula_set",
-        "cells_value_replaced",
-        "cells_formula_replaced",
-    ];
-    any_count_non_zero(counts, CHANGE_KEYS)
+/// One op of a `--continue-on-error` batch; `index` is its position in the
+/// payload's `ops`.
+#[derive(Debug, Serialize)]
+struct BatchOpOutcome {
+    index: usize,
+    kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
 }
 
-fn style_summary_indicates_change(counts: &BTreeMap<String, u64>) -> bool {
-    any_count_non_zero(counts, &["cells_style_changed"])
+/// What a `--continue-on-error` batch did with each op: `applied` ops reached
+/// the workbook, `skipped` ops were dropped before reaching it (e.g. by
+/// `--formula-parse-policy warn`), and `failed` ops errored and were left out.
+#[derive(Debug, Default, Serialize)]
+struct BatchOpOutcomes {
+    applied: Vec<BatchOpOutcome>,
+    skipped: Vec<BatchOpOutcome>,
+    failed: Vec<BatchOpOutcome>,
 }
 
-fn formula_pattern_summary_indicates_change(counts: &BTreeMap<String, u64>) -> bool {
-    any_count_non_zero(counts, &["cells_filled"])
+impl BatchOpOutcomes {
+    fn apply(&mut self, index: usize, kind: String) {
+        self.applied.push(BatchOpOutcome {
+            index,
+            kind,
+            code: None,
+            reason: None,
+        });
+    }
+
+    fn skip(&mut self, index: usize, kind: String, reason: impl Into<String>) {
+        self.skipped.push(BatchOpOutcome {
+            index,
+            kind,
+            code: None,
+            reason: Some(reason.into()),
+        });
+    }
+
+    fn fail(&mut self, index: usize, kind: String, error: &anyhow::Error) {
+        let envelope = crate::cli::errors::envelope_for(error);
+        self.failed.push(BatchOpOutcome {
+            index,
+            kind,
+            code: Some(envelope.code),
+            reason: Some(envelope.message),
+        });
+    }
 }
 
-fn structure_summary_indicates_change(counts: &BTreeMap<String, u64>) -> bool {
-    any_count_non_zero(
-        counts,
-        &[
-            "rows_inserted",
-            "rows_deleted",
-            "cols_inserted",
-            "cols_deleted",
-            "sheets_renamed",
-            "sheets_created",
-            "sheets_deleted",
-            "cells_copied",
-            "cells_moved",
-            "ranges_copied",
-            "ranges_moved",
-        ],
-    )
+/// The kind `summarize` counts `op` under, for naming ops in outcomes.
+fn op_kind<Op>(op: &Op, summarize: fn(&[Op]) -> BTreeMap<String, u64>) -> String {
+    summarize(std::slice::from_ref(op))
+        .into_keys()
+        .next()
+        .unwrap_or_default()
+}
+
+/// Run `resolve` on each op by itself, so an op that does not resolve fails
+/// alone instead of rejecting the whole batch.
+fn resolve_each_op<I, O>(
+    ops: &[I],
+    kind: impl Fn(&I) -> String,
+    outcomes: &mut BatchOpOutcomes,
+    resolve: impl Fn(&[I]) -> Result<Vec<O>>,
+) -> Vec<(usize, O)> {
+    let mut resolved = Vec::new();
+    for (index, op) in ops.iter().enumerate() {
+        match resolve(std::slice::from_ref(op)) {
+            Ok(ops) => resolved.extend(ops.into_iter().map(|op| (index, op))),
+            Err(error) => outcomes.fail(index, kind(op), &invalid_ops_payload(error.to_string())),
+        }
+    }
+    resolved
 }
 
-fn column_size_summary_indicates_change(counts: &BTreeMap<String, u64>) -> bool {
-    any_count_non_zero(counts, &["columns_sized"])
+/// Apply `ops` one at a time, in order, to one scratch copy of `source`, each
+/// on top of the ops before it that succeeded. Ops that fail are recorded in
+/// `outcomes` and dropped. Returns the kept ops and, when any op was kept, the
+/// scratch copy with their combined result for [`apply_or_reuse`].
+fn isolate_failing_ops<Op, T: MergeApplyResult>(
+    source: &Path,
+    ops: Vec<(usize, Op)>,
+    kind: impl Fn(&Op) -> String,
+    outcomes: &mut BatchOpOutcomes,
+    apply_fn: impl Fn(&Path, &[Op]) -> Result<T>,
+) -> Result<(Vec<Op>, Option<IsolatedResult<T>>)> {
+    let prefix = ".continue-on-error-";
+    let ((), mut working) = apply_to_temp_copy(source, source.parent(), prefix, |_| Ok(()))?;
+    let mut kept = Vec::new();
+    let mut combined: Option<T> = None;
+    for (index, op) in ops {
+        match apply_fn(&working, std::slice::from_ref(&op)) {
+            Ok(result) => {
+                outcomes.apply(index, kind(&op));
+                kept.push(op);
+                if let Some(combined) = combined.as_mut() {
+                    combined.merge(result);
+                } else {
+                    combined = Some(result);
+                }
+            }
+            Err(error) => {
+                outcomes.fail(index, kind(&op), &classify_apply_error(error));
+                // Some ops rewrite package parts before they can fail, so the
+                // scratch copy is rebuilt from the ops kept so far.
+                let ((), rebuilt) = apply_to_temp_copy(source, source.parent(), prefix, |path| {
+                    if !kept.is_empty() {
+                        apply_fn(path, &kept)?;
+                    }
+                    Ok(())
+                })?;
+                working = rebuilt;
+            }
+        }
+    }
+    let isolated = combined.map(|result| IsolatedResult {
+        staged: working,
+        result,
+    });
+    Ok((kept, isolated))
 }
 
-fn sheet_layout_summary_indicates_change(counts: &BTreeMap<String, u64>) -> bool {
-    any_count_non_zero(
-        counts,
-        &[
-            "ops",
-            "freeze_panes_ops",
-            "set_zoom_ops",
-            "set_gridlines_ops",
-            "set_page_margins_ops",
-            "set_page_setup_ops",
-            "set_print_area_ops",
-            "set_page_breaks_ops",
-        ],
-    )
+/// The scratch workbook left by [`isolate_failing_ops`] and the combined
+/// result of the ops applied to it.
+struct IsolatedResult<T> {
+    staged: TempPath,
+    result: T,
 }
 
-fn rules_summary_indicates_change(counts: &BTreeMap<String, u64>) -> bool {
-    any_count_non_zero(
-        counts,
-        &[
-            "validations_set",
-            "validations_replaced",
-            "conditional_formats_added",
-            "conditional_formats_set",
-            "conditional_formats_replaced",
-            "conditional_formats_cleared",
-        ],
-    )
+/// Copy the `--continue-on-error` scratch result onto `path` instead of
+/// applying its ops a second time; without one, run `apply_fn`.
+fn apply_or_reuse<T>(
+    isolated: Option<IsolatedResult<T>>,
+    path: &Path,
+    apply_fn: impl FnOnce(&Path) -> Result<T>,
+) -> Result<T> {
+    let Some(isolated) = isolated else {
+        return apply_fn(path);
+    };
+    fs::copy(&isolated.staged, path).map_err(|error| {
+        write_failed(format!(
+            "unable to stage continue-on-error result to '{}': {}",
+            path.display(),
+            error
+        ))
+    })?;
+    Ok(isolated.result)
 }
 
-fn grid_import_summary_indicates_change(counts: &BTreeMap<String, u64>) -> bool {
-    counts
-        .iter()
-        .any(|(key, value)| key != "ops" && *value > 0 && !key.starts_with("warnings_"))
+/// Per-op apply results folded into one, as if the ops had been applied
+/// together.
+trait MergeApplyResult {
+    fn merge(&mut self, other: Self);
 }
 
-fn any_count_non_zero(counts: &BTreeMap<String, u64>, keys: &[&str]) -> bool {
-    keys.iter()
-        .any(|key| counts.get(*key).copied().unwrap_or(0) > 0)
+impl MergeApplyResult for TransformApplyResult {
+    fn merge(&mut self, other: Self) {
+        self.ops_applied += other.ops_applied;
+        self.summary.merge(other.summary);
+    }
 }
 
-fn warning_strings_to_cli_warnings(messages: Vec<String>) -> Vec<Warning> {
-    messages.into_iter().map(parse_warning_message).collect()
+impl MergeApplyResult for StyleApplyResult {
+    fn merge(&mut self, other: Self) {
+        self.ops_applied += other.ops_applied;
+        self.summary.merge(other.summary);
+    }
 }
 
-fn merge_cli_warnings(mut left: Vec<Warning>, mut right: Vec<Warning>) -> Vec<Warning> {
-    left.append(&mut right);
-    left
+impl MergeApplyResult for FormulaPatternBatchApplyResult {
+    fn merge(&mut self, other: Self) {
+        self.ops_applied += other.ops_applied;
+        self.summary.merge(other.summary);
+    }
 }
 
-fn parse_warning_message(message: String) -> Warning {
-    if let Some((code, detail)) = message.split_once(':') {
-        let code = code.trim();
-        let detail = detail.trim();
-        if is_warning_code(code) && !detail.is_empty() {
-            return Warning {
-                code: code.to_string(),
-                message: detail.to_string(),
-            };
-        }
+impl MergeApplyResult for StructureApplyResult {
+    fn merge(&mut self, other: Self) {
+        self.ops_applied += other.ops_applied;
+        self.summary.merge(other.summary);
+        self.formula_parse_diagnostics = FormulaParseDiagnostics::merge(
+            self.formula_parse_diagnostics.take(),
+            other.formula_parse_diagnostics,
+        );
     }
+}
 
-    Warning {
-        code: "WARN_INFO".to_string(),
-        message,
+impl MergeApplyResult for ColumnSizeApplyResult {
+    fn merge(&mut self, other: Self) {
+        self.ops_applied += other.ops_applied;
+        self.summary.merge(other.summary);
     }
 }
 
-fn is_warning_code(value: &str) -> bool {
-    value.starts_with("WARN_")
-        && value
-            .chars()
-            .all(|ch| ch.is_ascii_uppercase() || ch == '_' || ch.is_ascii_digit())
+impl MergeApplyResult for RowSizeApplyResult {
+    fn merge(&mut self, other: Self) {
+        self.ops_applied += other.ops_applied;
+        self.summary.merge(other.summary);
+    }
 }
 
-fn formula_write_provenance(
-    written_via: &str,
-    formula_targets: Vec<String>,
-) -> Option<WritePathProvenance> {
-    if formula_targets.is_empty() {
-        None
-    } else {
-        Some(WritePathProvenance {
-            written_via: written_via.to_string(),
-            formula_targets,
-        })
+impl MergeApplyResult for SheetLayoutApplyResult {
+    fn merge(&mut self, other: Self) {
+        self.ops_applied += other.ops_applied;
+        self.summary.merge(other.summary);
     }
 }
 
-fn parse_cell_ref_for_cli(cell: &str) -> Result<(u32, u32)> {
-    let (col, row, _, _) = umya_spreadsheet::helper::coordinate::index_from_coordinate(cell);
-    match (col, row) {
-        (Some(c), Some(r)) if c > 0 && r > 0 => Ok((c, r)),
-        _ => Err(invalid_ops_payload(format!(
-            "invalid cell reference '{}' (expected A1-style reference)",
-            cell
-        ))),
+impl MergeApplyResult for RulesApplyResult {
+    fn merge(&mut self, other: Self) {
+        self.ops_applied += other.ops_applied;
+        self.summary.merge(other.summary);
+        self.formula_parse_diagnostics = FormulaParseDiagnostics::merge(
+            self.formula_parse_diagnostics.take(),
+            other.formula_parse_diagnostics,
+        );
     }
 }
 
-fn transform_formula_targets(ops: &[TransformOp]) -> Vec<String> {
-    ops.iter()
-        .filter_map(|op| match op {
-            TransformOp::FillRange {
-                sheet_name,
-                target,
-                is_formula,
-                ..
-            } if *is_formula => Some(format!("{}!{}", sheet_name, transform_target_label(target))),
-            TransformOp::ReplaceInRange {
-                sheet_name,
-                target,
-                include_formulas,
-                ..
-            } if *include_formulas => {
-                Some(format!("{}!{}", sheet_name, transform_target_label(target)))
-            }
-            TransformOp::WriteMatrix {
-                sheet_name,
-                anchor,
-                rows,
-                ..
-            } if rows.iter().any(|r| {
-                r.iter()
-                    .any(|c| matches!(c, Some(crate::tools::fork::MatrixCell::Formula(_))))
-            }) =>
-            {
-                Some(format!("{}!{}", sheet_name, anchor))
+impl MergeApplyResult for PropertiesApplyResult {
+    fn merge(&mut self, other: Self) {
+        self.ops_applied += other.ops_applied;
+        for (key, count) in other.counts {
+            *self.counts.entry(key).or_insert(0) += count;
+        }
+        for warning in other.warnings {
+            if !self.warnings.contains(&warning) {
+                self.warnings.push(warning);
             }
-            _ => None,
-        })
-        .collect()
+        }
+    }
 }
 
-fn transform_target_label(target: &TransformTarget) -> String {
-    match target {
-        TransformTarget::Range { range } => range.clone(),
-        TransformTarget::Region { region_id } => format!("region:{}", region_id),
-        TransformTarget::Cells { cells } => {
-            if cells.is_empty() {
-                "cells".to_string()
-            } else {
-                format!("cells:{}", cells.join(","))
-            }
+/// Without `--continue-on-error`, pass `ops` through untouched; with it, drop
+/// the ops that fail (see [`isolate_failing_ops`]).
+fn continue_on_error_ops<Op, T: MergeApplyResult>(
+    continue_on_error: bool,
+    source: &Path,
+    ops: Vec<Op>,
+    kind: impl Fn(&Op) -> String,
+    apply_fn: impl Fn(&Path, &[Op]) -> Result<T>,
+) -> Result<(Vec<Op>, Option<BatchOpOutcomes>, Option<IsolatedResult<T>>)> {
+    if !continue_on_error {
+        return Ok((ops, None, None));
+    }
+    let mut outcomes = BatchOpOutcomes::default();
+    let (kept, isolated) = isolate_failing_ops(
+        source,
+        ops.into_iter().enumerate().collect(),
+        kind,
+        &mut outcomes,
+        apply_fn,
+    )?;
+    Ok((kept, Some(outcomes), isolated))
+}
+
+/// Report a `--continue-on-error` batch's per-op outcomes under `applied`,
+/// `skipped` and `failed`.
+fn attach_op_outcomes(mut response: Value, outcomes: Option<BatchOpOutcomes>) -> Value {
+    if let (Some(object), Some(outcomes)) = (response.as_object_mut(), outcomes) {
+        for (key, entries) in [
+            ("applied", outcomes.applied),
+            ("skipped", outcomes.skipped),
+            ("failed", outcomes.failed),
+        ] {
+            object.insert(
+                key.to_string(),
+                serde_json::to_value(entries).unwrap_or(Value::Null),
+            );
         }
     }
+    response
 }
 
-fn apply_formula_pattern_targets(ops: &[ApplyFormulaPatternOpInput]) -> Vec<String> {
-    ops.iter()
-        .map(|op| format!("{}!{}", op.sheet_name, op.target_range))
-        .collect()
+/// Expectations loaded from `--verify-with`, checked against the staged workbook
+/// before it replaces the target.
+struct VerifyWithGate {
+    tests: Vec<Expectation>,
+    source: String,
 }
 
-fn dry_run_response(
-    op_count: usize,
-    operation_counts: BTreeMap<String, u64>,
-    result_counts: BTreeMap<String, u64>,
-    warnings: Vec<Warning>,
-    would_change: bool,
-    formula_parse_diagnostics: Option<FormulaParseDiagnostics>,
-    write_path_provenance: Option<WritePathProvenance>,
-) -> Result<Value> {
-    Ok(serde_json::to_value(BatchDryRunResponse {
-        op_count,
-        validated_count: op_count,
-        would_change,
-        warnings,
-        summary: DryRunSummary {
-            operation_counts,
-            result_counts,
-        },
-        formula_parse_diagnostics,
-        write_path_provenance,
-    })?)
+fn load_verify_with(path: Option<PathBuf>) -> Result<Option<VerifyWithGate>> {
+    let Some(path) = path else {
+        return Ok(None);
+    };
+    let tests = load_expectations_file(&path)
+        .map_err(|error| invalid_argument(format!("--verify-with: {error:#}")))?;
+    Ok(Some(VerifyWithGate {
+        tests,
+        source: path.display().to_string(),
+    }))
 }
 
-#[allow(clippy::too_many_arguments)]
-fn apply_response(
-    op_count: usize,
-    applied_count: usize,
-    warnings: Vec<Warning>,
-    changed: bool,
-    target_path: String,
-    source_path: String,
-    formula_parse_diagnostics: Option<FormulaParseDiagnostics>,
-    write_path_provenance: Option<WritePathProvenance>,
-) -> Result<Value> {
-    Ok(serde_json::to_value(BatchApplyResponse {
-        op_count,
-        applied_count,
-        warnings,
-        changed,
-        target_path,
-        source_path,
-        formula_parse_diagnostics,
-        write_path_provenance,
-    })?)
+/// Recalculate a throwaway copy of the staged workbook and evaluate
+/// `--verify-with` expectations against it; the staged file itself is
+/// committed as written. Any failing expectation aborts the command before
+/// the staged file is persisted, so the target is left untouched.
+async fn verify_staged_workbook(
+    staged: &Path,
+    gate: Option<&VerifyWithGate>,
+) -> Result<Option<ExpectationReport>> {
+    let Some(gate) = gate else {
+        return Ok(None);
+    };
+
+    let scratch = crate::scratch::temp_dir()?;
+    let (_, recalculated) =
+        apply_to_temp_copy(staged, Some(&scratch), ".verify-with-", |_| Ok(()))?;
+    let recalculated: &Path = recalculated.as_ref();
+    StatelessRuntime.recalculate_file(recalculated).await?;
+    let book = umya_spreadsheet::reader::xlsx::read(recalculated).map_err(|error| {
+        write_failed(format!(
+            "unable to read staged workbook '{}' for verification: {}",
+            staged.display(),
+            error
+        ))
+    })?;
+
+    let date1904 = read_workbook_props(staged)
+        .map(|props| props.date1904)
+        .unwrap_or(false);
+    let report = with_date_system(date1904, || {
+        evaluate_expectations(&book, &gate.tests, gate.source.clone())
+    });
+    if !report.all_passed() {
+        return Err(verification_failed(&report));
+    }
+    Ok(Some(report))
 }
 
-fn apply_in_place_with_temp<T, F>(source: &Path, temp_prefix: &str, apply_fn: F) -> Result<T>
+async fn apply_in_place_with_verify<T, F>(
+    source: &Path,
+    verify_with: Option<&VerifyWithGate>,
+    commit: WriteCommit<'_>,
+    apply_fn: F,
+) -> Result<(T, Option<ExpectationReport>)>
 where
     F: FnOnce(&Path) -> Result<T>,
 {
     let (apply_result, temp_path) =
-        apply_to_temp_copy(source, source.parent(), temp_prefix, apply_fn)?;
-    atomic_replace_target(temp_path, source, true)?;
-    Ok(apply_result)
+        apply_to_temp_copy(source, source.parent(), &commit.temp_prefix(), apply_fn)?;
+    let verification = verify_staged_workbook(&temp_path, verify_with).await?;
+    commit_staged_workbook(&commit, source, temp_path, source, true).await?;
+    Ok((apply_result, verification))
 }
 
-fn apply_to_output_with_temp<T, F>(
+async fn apply_to_output_with_verify<T, F>(
     source: &Path,
     target: &Path,
     force: bool,
-    temp_prefix: &str,
+    verify_with: Option<&VerifyWithGate>,
+    commit: WriteCommit<'_>,
     apply_fn: F,
-) -> Result<T>
+) -> Result<(T, Option<ExpectationReport>)>
 where
     F: FnOnce(&Path) -> Result<T>,
 {
@@ -4809,9 +8022,10 @@ where
     }
 
     let (apply_result, temp_path) =
-        apply_to_temp_copy(source, target.parent(), temp_prefix, apply_fn)?;
-    atomic_replace_target(temp_path, target, force)?;
-    Ok(apply_result)
+        apply_to_temp_copy(source, target.parent(), &commit.temp_prefix(), apply_fn)?;
+    let verification = verify_staged_workbook(&temp_path, verify_with).await?;
+    commit_staged_workbook(&commit, source, temp_path, target, force).await?;
+    Ok((apply_result, verification))
 }
 
 fn apply_to_temp_copy<T, F>(
@@ -5067,60 +8281,6 @@ fn csv_field_to_json(field: &str) -> serde_json::Value {
     serde_json::Value::String(field.to_string())
 }
 
-fn parse_csv_records(raw: &str) -> Result<Vec<Vec<String>>> {
-    let mut records: Vec<Vec<String>> = Vec::new();
-    let mut row: Vec<String> = Vec::new();
-    let mut field = String::new();
-    let mut chars = raw.chars().peekable();
-    let mut in_quotes = false;
-
-    while let Some(ch) = chars.next() {
-        if in_quotes {
-            if ch == '"' {
-                if matches!(chars.peek(), Some('"')) {
-                    let _ = chars.next();
-                    field.push('"');
-                } else {
-                    in_quotes = false;
-                }
-            } else {
-                field.push(ch);
-            }
-            continue;
-        }
-
-        match ch {
-            '"' => in_quotes = true,
-            ',' => {
-                row.push(std::mem::take(&mut field));
-            }
-            '\n' => {
-                row.push(std::mem::take(&mut field));
-                records.push(std::mem::take(&mut row));
-            }
-            '\r' => {
-                if matches!(chars.peek(), Some('\n')) {
-                    let _ = chars.next();
-                }
-                row.push(std::mem::take(&mut field));
-                records.push(std::mem::take(&mut row));
-            }
-            _ => field.push(ch),
-        }
-    }
-
-    if in_quotes {
-        return Err(anyhow!("unterminated quoted field"));
-    }
-
-    if !field.is_empty() || !row.is_empty() {
-        row.push(field);
-        records.push(row);
-    }
-
-    Ok(records)
-}
-
 fn apply_grid_import_to_path(
     path: &Path,
     sheet_name: &str,
@@ -5156,6 +8316,17 @@ fn apply_grid_import_to_path(
         max_cells: Some(10_000),
         max_items: Some(500),
         allow_overwrite: true,
+        named_queries: Default::default(),
+        workbook_aliases: Default::default(),
+        virtual_workspace: false,
+        pin_revisions: false,
+        parse_cache_dir: None,
+        scratch_dir: None,
+        scratch_max_bytes: None,
+        checkpoint_store: None,
+        schedule: None,
+        schedule_mode: None,
+        profile: None,
     });
 
     let sheet_name = sheet_name.to_string();
@@ -5260,10 +8431,52 @@ fn output_exists(message: impl AsRef<str>) -> anyhow::Error {
     anyhow!("output exists: {}", message.as_ref())
 }
 
+fn unresolved_placeholders(summary: &InstantiateSummary) -> anyhow::Error {
+    let details = summary
+        .unresolved
+        .iter()
+        .map(|item| {
+            format!(
+                "{{{{{}}}}} ({})",
+                item.placeholder,
+                item.locations.first().map(String::as_str).unwrap_or("?")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    anyhow!(
+        "unresolved placeholders: {} placeholder(s) have no value; nothing was written: {}",
+        summary.unresolved.len(),
+        details
+    )
+}
+
 fn write_failed(message: impl AsRef<str>) -> anyhow::Error {
     anyhow!("write failed: {}", message.as_ref())
 }
 
+fn verification_failed(report: &ExpectationReport) -> anyhow::Error {
+    let details = report
+        .failures()
+        .take(5)
+        .map(|result| {
+            format!(
+                "{} {}",
+                result.name.as_deref().unwrap_or(&result.cell),
+                result.message.as_deref().unwrap_or("failed")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("; ");
+    anyhow!(
+        "verification failed: {} of {} expectation(s) from '{}' failed; changes were not written: {}",
+        report.failed,
+        report.total,
+        report.source,
+        details
+    )
+}
+
 // ── Named Range CRUD CLI ─────────────────────────────────────────────────────
 
 #[derive(Debug, Serialize)]
@@ -5337,7 +8550,7 @@ pub async fn define_name(
 
     let runtime = StatelessRuntime;
     let source = runtime.normalize_existing_file(&file)?;
-    let mode = validate_edit_mode(dry_run, in_place, output, force)?;
+    let mode = validate_edit_mode(&source, dry_run, in_place, output, force)?;
 
     let scope_str = match scope_kind {
         crate::model::NamedRangeScope::Workbook => "workbook",
@@ -5368,7 +8581,7 @@ pub async fn define_name(
             })?)
         }
         EditMutationMode::InPlace => {
-            apply_in_place_with_temp(&source, ".defname-", |path| {
+            apply_in_place_with_temp(&source, WriteCommit::new("define-name"), |path| {
                 define_name_in_file(
                     path,
                     &name,
@@ -5376,7 +8589,8 @@ pub async fn define_name(
                     scope_kind,
                     scope_sheet_name.as_deref(),
                 )
-            })?;
+            })
+            .await?;
             Ok(serde_json::to_value(DefineNameCliResponse {
                 file: source.display().to_string(),
                 name,
@@ -5389,15 +8603,22 @@ pub async fn define_name(
             })?)
         }
         EditMutationMode::Output { target, force: f } => {
-            apply_to_output_with_temp(&source, &target, f, ".defname-", |path| {
-                define_name_in_file(
-                    path,
-                    &name,
-                    &refers_to,
-                    scope_kind,
-                    scope_sheet_name.as_deref(),
-                )
-            })?;
+            apply_to_output_with_temp(
+                &source,
+                &target,
+                f,
+                WriteCommit::new("define-name"),
+                |path| {
+                    define_name_in_file(
+                        path,
+                        &name,
+                        &refers_to,
+                        scope_kind,
+                        scope_sheet_name.as_deref(),
+                    )
+                },
+            )
+            .await?;
             Ok(serde_json::to_value(DefineNameCliResponse {
                 file: source.display().to_string(),
                 name,
@@ -5438,7 +8659,7 @@ pub async fn update_name(
 
     let runtime = StatelessRuntime;
     let source = runtime.normalize_existing_file(&file)?;
-    let mode = validate_edit_mode(dry_run, in_place, output, force)?;
+    let mode = validate_edit_mode(&source, dry_run, in_place, output, force)?;
 
     match mode {
         EditMutationMode::DryRun => {
@@ -5474,7 +8695,7 @@ pub async fn update_name(
         }
         EditMutationMode::InPlace => {
             let (previous_refers_to, eff_scope, eff_sheet) =
-                apply_in_place_with_temp(&source, ".updname-", |path| {
+                apply_in_place_with_temp(&source, WriteCommit::new("update-name"), |path| {
                     update_name_in_file(
                         path,
                         &name,
@@ -5482,7 +8703,8 @@ pub async fn update_name(
                         scope_kind,
                         scope_sheet_name.as_deref(),
                     )
-                })?;
+                })
+                .await?;
             let scope_str = match eff_scope {
                 crate::model::NamedRangeScope::Workbook => "workbook",
                 crate::model::NamedRangeScope::Sheet => "sheet",
@@ -5503,8 +8725,12 @@ pub async fn update_name(
             })?)
         }
         EditMutationMode::Output { target, force: f } => {
-            let (previous_refers_to, eff_scope, eff_sheet) =
-                apply_to_output_with_temp(&source, &target, f, ".updname-", |path| {
+            let (previous_refers_to, eff_scope, eff_sheet) = apply_to_output_with_temp(
+                &source,
+                &target,
+                f,
+                WriteCommit::new("update-name"),
+                |path| {
                     update_name_in_file(
                         path,
                         &name,
@@ -5512,7 +8738,9 @@ pub async fn update_name(
                         scope_kind,
                         scope_sheet_name.as_deref(),
                     )
-                })?;
+                },
+            )
+            .await?;
             let scope_str = match eff_scope {
                 crate::model::NamedRangeScope::Workbook => "workbook",
                 crate::model::NamedRangeScope::Sheet => "sheet",
@@ -5555,7 +8783,7 @@ pub async fn delete_name(
 
     let runtime = StatelessRuntime;
     let source = runtime.normalize_existing_file(&file)?;
-    let mode = validate_edit_mode(dry_run, in_place, output, force)?;
+    let mode = validate_edit_mode(&source, dry_run, in_place, output, force)?;
 
     match mode {
         EditMutationMode::DryRun => {
@@ -5572,12 +8800,8 @@ pub async fn delete_name(
             })?)
         }
         EditMutationMode::InPlace => {
-            delete_name_in_file_via_helper(
-                &source,
-                &name,
-                scope_kind,
-                scope_sheet_name.as_deref(),
-            )?;
+            delete_name_in_file_via_helper(&source, &name, scope_kind, scope_sheet_name.as_deref())
+                .await?;
             Ok(serde_json::to_value(DeleteNameCliResponse {
                 file: source.display().to_string(),
                 name,
@@ -5588,9 +8812,14 @@ pub async fn delete_name(
             })?)
         }
         EditMutationMode::Output { target, force: f } => {
-            apply_to_output_with_temp(&source, &target, f, ".delname-", |path| {
-                delete_name_in_file(path, &name, scope_kind, scope_sheet_name.as_deref())
-            })?;
+            apply_to_output_with_temp(
+                &source,
+                &target,
+                f,
+                WriteCommit::new("delete-name"),
+                |path| delete_name_in_file(path, &name, scope_kind, scope_sheet_name.as_deref()),
+            )
+            .await?;
             Ok(serde_json::to_value(DeleteNameCliResponse {
                 file: source.display().to_string(),
                 name,
@@ -5603,16 +8832,279 @@ pub async fn delete_name(
     }
 }
 
-fn delete_name_in_file_via_helper(
+async fn delete_name_in_file_via_helper(
     source: &Path,
     name: &str,
     scope_kind: Option<crate::model::NamedRangeScope>,
     scope_sheet_name: Option<&str>,
 ) -> Result<bool> {
     use crate::tools::delete_name_in_file;
-    apply_in_place_with_temp(source, ".delname-", |path| {
+    apply_in_place_with_temp(source, WriteCommit::new("delete-name"), |path| {
         delete_name_in_file(path, name, scope_kind, scope_sheet_name)
     })
+    .await
+}
+
+#[derive(Debug, Serialize)]
+struct SetMetadataResponse {
+    file: String,
+    namespace: String,
+    key: String,
+    value: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    previous_value: Option<Value>,
+    replaced: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    target_path: Option<String>,
+    dry_run: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct ClearMetadataResponse {
+    file: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    namespace: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    key: Option<String>,
+    removed_count: usize,
+    removed: Vec<MetadataEntry>,
+    changed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    target_path: Option<String>,
+    dry_run: bool,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn set_metadata(
+    file: PathBuf,
+    namespace: String,
+    key: String,
+    value: String,
+    dry_run: bool,
+    in_place: bool,
+    output: Option<PathBuf>,
+    force: bool,
+) -> Result<Value> {
+    if namespace.trim().is_empty() || key.trim().is_empty() {
+        return Err(invalid_argument("namespace and key must not be empty"));
+    }
+    // Bare text that is not JSON is stored as a string.
+    let value = serde_json::from_str(&value).unwrap_or(Value::String(value));
+
+    let source = StatelessRuntime.normalize_existing_file(&file)?;
+    let mode = validate_edit_mode(&source, dry_run, in_place, output, force)?;
+    let updated_at = chrono::Utc::now().to_rfc3339();
+    let stored = value.clone();
+    let (previous_value, target) = apply_metadata_mutation(&source, mode, "set-metadata", |book| {
+        metadata::set_metadata(book, &namespace, &key, stored, &updated_at)
+    })
+    .await?;
+
+    Ok(serde_json::to_value(SetMetadataResponse {
+        file: source.display().to_string(),
+        namespace: namespace.trim().to_string(),
+        key: key.trim().to_string(),
+        value,
+        replaced: previous_value.is_some(),
+        previous_value,
+        source_path: target.as_ref().map(|_| source.display().to_string()),
+        dry_run: target.is_none(),
+        target_path: target.map(|target| target.display().to_string()),
+    })?)
+}
+
+pub async fn clear_metadata(
+    file: PathBuf,
+    namespace: Option<String>,
+    key: Option<String>,
+    dry_run: bool,
+    in_place: bool,
+    output: Option<PathBuf>,
+    force: bool,
+) -> Result<Value> {
+    if key.is_some() && namespace.is_none() {
+        return Err(invalid_argument("--key requires --namespace"));
+    }
+
+    let source = StatelessRuntime.normalize_existing_file(&file)?;
+    let mode = validate_edit_mode(&source, dry_run, in_place, output, force)?;
+    let book = umya_spreadsheet::reader::xlsx::read(&source)
+        .with_context(|| format!("failed to read workbook '{}'", source.display()))?;
+    let matches = metadata::read_metadata(&book).iter().any(|entry| {
+        namespace
+            .as_deref()
+            .is_none_or(|namespace| entry.namespace == namespace.trim())
+            && key.as_deref().is_none_or(|key| entry.key == key.trim())
+    });
+    drop(book);
+
+    let mut response = ClearMetadataResponse {
+        file: source.display().to_string(),
+        namespace: namespace.clone(),
+        key: key.clone(),
+        removed_count: 0,
+        removed: Vec::new(),
+        changed: false,
+        source_path: None,
+        target_path: None,
+        dry_run: matches!(mode, EditMutationMode::DryRun),
+    };
+    // Nothing to remove: leave the file untouched.
+    if !matches {
+        return Ok(serde_json::to_value(response)?);
+    }
+
+    let (removed, target) = apply_metadata_mutation(&source, mode, "clear-metadata", |book| {
+        metadata::clear_metadata(book, namespace.as_deref(), key.as_deref())
+    })
+    .await?;
+    response.removed_count = removed.len();
+    response.removed = removed;
+    response.changed = target.is_some();
+    response.source_path = target.as_ref().map(|_| source.display().to_string());
+    response.target_path = target.map(|target| target.display().to_string());
+    Ok(serde_json::to_value(response)?)
+}
+
+#[derive(Debug, Serialize)]
+struct AnnotateResponse {
+    file: String,
+    sheet: String,
+    range: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    previous_value: Option<Value>,
+    /// An annotation already existed on the range and was replaced or removed.
+    replaced: bool,
+    removed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    target_path: Option<String>,
+    dry_run: bool,
+}
+
+/// Attach (or with `remove`, detach) a JSON annotation to a cell or range,
+/// stored in the `annotations` namespace of the `_metadata` sheet.
+#[allow(clippy::too_many_arguments)]
+pub async fn annotate(
+    file: PathBuf,
+    sheet: String,
+    range: String,
+    value: Option<String>,
+    remove: bool,
+    dry_run: bool,
+    in_place: bool,
+    output: Option<PathBuf>,
+    force: bool,
+) -> Result<Value> {
+    let key = metadata::annotation_key(&sheet, &range)
+        .map_err(|error| invalid_argument(format!("{error:#}")))?;
+    let value = match (value, remove) {
+        (Some(_), true) => {
+            return Err(invalid_argument("--remove cannot be combined with VALUE"));
+        }
+        (None, false) => return Err(invalid_argument("pass VALUE or --remove")),
+        // Bare text that is not JSON is stored as a string.
+        (Some(value), false) => Some(serde_json::from_str(&value).unwrap_or(Value::String(value))),
+        (None, true) => None,
+    };
+
+    let source = StatelessRuntime.normalize_existing_file(&file)?;
+    let mode = validate_edit_mode(&source, dry_run, in_place, output, force)?;
+    let sheet = sheet.trim().to_string();
+    let normalized_range = key
+        .rsplit_once('!')
+        .map(|(_, range)| range.to_string())
+        .unwrap_or_default();
+
+    let command = if remove {
+        "remove-annotation"
+    } else {
+        "annotate"
+    };
+    let updated_at = chrono::Utc::now().to_rfc3339();
+    let stored = value.clone();
+    let (previous_value, target) = apply_metadata_mutation(&source, mode, command, |book| {
+        if book.get_sheet_by_name(&sheet).is_none() {
+            bail!("sheet '{sheet}' not found");
+        }
+        match stored {
+            Some(stored) => metadata::set_metadata(
+                book,
+                metadata::ANNOTATIONS_NAMESPACE,
+                &key,
+                stored,
+                &updated_at,
+            ),
+            None => {
+                let removed = metadata::clear_metadata(
+                    book,
+                    Some(metadata::ANNOTATIONS_NAMESPACE),
+                    Some(&key),
+                )?;
+                if removed.is_empty() {
+                    bail!("no annotation on {key}");
+                }
+                Ok(removed.into_iter().next().map(|entry| entry.value))
+            }
+        }
+    })
+    .await?;
+
+    Ok(serde_json::to_value(AnnotateResponse {
+        file: source.display().to_string(),
+        sheet,
+        range: normalized_range,
+        value,
+        replaced: previous_value.is_some(),
+        previous_value,
+        removed: remove,
+        source_path: target.as_ref().map(|_| source.display().to_string()),
+        dry_run: target.is_none(),
+        target_path: target.map(|target| target.display().to_string()),
+    })?)
+}
+
+/// Apply `mutate` to the workbook as `mode` directs. Returns its result and
+/// the written path, `None` for a dry run.
+async fn apply_metadata_mutation<T>(
+    source: &Path,
+    mode: EditMutationMode,
+    command: &str,
+    mutate: impl FnOnce(&mut umya_spreadsheet::Spreadsheet) -> Result<T>,
+) -> Result<(T, Option<PathBuf>)> {
+    let apply = |path: &Path| -> Result<T> {
+        let mut book = umya_spreadsheet::reader::xlsx::read(path)
+            .with_context(|| format!("failed to read workbook '{}'", path.display()))?;
+        let result = mutate(&mut book).map_err(|error| invalid_argument(format!("{error:#}")))?;
+        save_workbook(&book, path)?;
+        Ok(result)
+    };
+    match mode {
+        EditMutationMode::DryRun => {
+            let (result, _temp_path) =
+                apply_to_temp_copy(source, source.parent(), &format!(".{command}-"), apply)?;
+            Ok((result, None))
+        }
+        EditMutationMode::InPlace => {
+            let result = apply_in_place_with_temp(source, WriteCommit::new(command), apply).await?;
+            Ok((result, Some(source.to_path_buf())))
+        }
+        EditMutationMode::Output { target, force } => {
+            let target = StatelessRuntime.normalize_destination_path(&target)?;
+            ensure_output_path_is_distinct(source, &target)?;
+            let result =
+                apply_to_output_with_temp(source, &target, force, WriteCommit::new(command), apply)
+                    .await?;
+            Ok((result, Some(target)))
+        }
+    }
 }
 
 pub fn parse_shorthand_for_tests(entries: Vec<String>) -> Result<(Vec<CellEdit>, Vec<Warning>)> {
@@ -1,4 +1,5 @@
 use crate::cli::OutputFormat;
+use crate::errors::RecalcTimeoutError;
 use crate::model::{FORMULA_PARSE_FAILED, FORMULA_PARSE_FAILED_PREFIX};
 use anyhow::{Result, bail};
 use serde::Serialize;
@@ -20,17 +21,36 @@ pub struct ErrorEnvelope {
     pub did_you_mean: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub try_this: Option<String>,
+    /// Structured context for codes that carry more than a message.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
 }
 
 pub fn envelope_for(error: &anyhow::Error) -> ErrorEnvelope {
     let message = error.to_string();
 
+    if let Some(timeout) = error
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<RecalcTimeoutError>())
+    {
+        return ErrorEnvelope {
+            code: "RECALC_TIMEOUT".to_string(),
+            message: timeout.to_string(),
+            did_you_mean: None,
+            try_this: Some(
+                "raise --timeout-ms, recalculate a subtree with --cell/--scope, or switch engines with --engine".to_string(),
+            ),
+            details: serde_json::to_value(timeout).ok(),
+        };
+    }
+
     if let Some((requested, suggested)) = parse_sheet_suggestion(&message) {
         return ErrorEnvelope {
             code: "SHEET_NOT_FOUND".to_string(),
             message: format!("sheet '{}' was not found", requested),
             did_you_mean: Some(suggested),
             try_this: Some("run `asp read sheets <file>` to inspect valid names".to_string()),
+            details: None,
         };
     }
 
@@ -40,6 +60,7 @@ pub fn envelope_for(error: &anyhow::Error) -> ErrorEnvelope {
             message: format!("sheet '{}' was not found", requested),
             did_you_mean: None,
             try_this: Some("run `asp read sheets <file>` to inspect valid names".to_string()),
+            details: None,
         };
     }
 
@@ -54,6 +75,7 @@ pub fn envelope_for(error: &anyhow::Error) -> ErrorEnvelope {
             message: detail.to_string(),
             did_you_mean: None,
             try_this: Some(try_this),
+            details: None,
         };
     }
 
@@ -63,6 +85,7 @@ pub fn envelope_for(error: &anyhow::Error) -> ErrorEnvelope {
             message: detail.to_string(),
             did_you_mean: None,
             try_this: Some("pass --ops @<path-to-json> with payload {\"ops\":[...]}".to_string()),
+            details: None,
         };
     }
 
@@ -74,6 +97,7 @@ pub fn envelope_for(error: &anyhow::Error) -> ErrorEnvelope {
             try_this: Some(
                 "run `asp example session op transform.write_matrix` or `asp schema session op transform.write_matrix` to inspect the canonical payload contract".to_string(),
             ),
+            details: None,
         };
     }
 
@@ -83,6 +107,7 @@ pub fn envelope_for(error: &anyhow::Error) -> ErrorEnvelope {
             message: detail.to_string(),
             did_you_mean: None,
             try_this: Some("choose a new --output path or re-run with --force".to_string()),
+            details: None,
         };
     }
 
@@ -94,6 +119,7 @@ pub fn envelope_for(error: &anyhow::Error) -> ErrorEnvelope {
             try_this: Some(
                 "re-run with --merge-policy safe or choose a different template row".to_string(),
             ),
+            details: None,
         };
     }
 
@@ -103,6 +129,59 @@ pub fn envelope_for(error: &anyhow::Error) -> ErrorEnvelope {
             message: detail.to_string(),
             did_you_mean: None,
             try_this: Some("check destination permissions and available disk space".to_string()),
+            details: None,
+        };
+    }
+
+    if let Some(detail) = message.strip_prefix("verification failed: ") {
+        return ErrorEnvelope {
+            code: "VERIFICATION_FAILED".to_string(),
+            message: detail.to_string(),
+            did_you_mean: None,
+            try_this: Some(
+                "fix the ops payload or the --verify-with expectations and re-run; the target workbook was not modified"
+                    .to_string(),
+            ),
+            details: None,
+        };
+    }
+
+    if let Some(detail) = message.strip_prefix("unresolved placeholders: ") {
+        return ErrorEnvelope {
+            code: "UNRESOLVED_PLACEHOLDERS".to_string(),
+            message: detail.to_string(),
+            did_you_mean: None,
+            try_this: Some(
+                "add the missing keys to --values, or drop --strict to write with them left in place"
+                    .to_string(),
+            ),
+            details: None,
+        };
+    }
+
+    if let Some(detail) = message.strip_prefix("hook rejected: ") {
+        return ErrorEnvelope {
+            code: "HOOK_REJECTED".to_string(),
+            message: detail.to_string(),
+            did_you_mean: None,
+            try_this: Some(
+                "inspect the pre-apply hook output; the target workbook was not modified"
+                    .to_string(),
+            ),
+            details: None,
+        };
+    }
+
+    if let Some(detail) = message.strip_prefix("hook failed: ") {
+        return ErrorEnvelope {
+            code: "HOOK_FAILED".to_string(),
+            message: detail.to_string(),
+            did_you_mean: None,
+            try_this: Some(
+                "the workbook was written; inspect the post-apply hook output and re-run the hook if needed"
+                    .to_string(),
+            ),
+            details: None,
         };
     }
 
@@ -112,6 +191,7 @@ pub fn envelope_for(error: &anyhow::Error) -> ErrorEnvelope {
             message,
             did_you_mean: None,
             try_this: Some("check the workbook path and permissions".to_string()),
+            details: None,
         };
     }
 
@@ -121,6 +201,7 @@ pub fn envelope_for(error: &anyhow::Error) -> ErrorEnvelope {
             message,
             did_you_mean: None,
             try_this: Some("pass one or more A1 ranges, for example: `A1:C10`".to_string()),
+            details: None,
         };
     }
 
@@ -130,6 +211,7 @@ pub fn envelope_for(error: &anyhow::Error) -> ErrorEnvelope {
             message,
             did_you_mean: None,
             try_this: Some("add one or more edits like `A1=42` or `B2==SUM(A1:A1)`".to_string()),
+            details: None,
         };
     }
 
@@ -141,6 +223,7 @@ pub fn envelope_for(error: &anyhow::Error) -> ErrorEnvelope {
             try_this: Some(
                 "use `<cell>=<value>` for values or `<cell>==<formula>` for formulas".to_string(),
             ),
+            details: None,
         };
     }
 
@@ -150,6 +233,7 @@ pub fn envelope_for(error: &anyhow::Error) -> ErrorEnvelope {
             message,
             did_you_mean: Some("json".to_string()),
             try_this: Some("re-run with `--output-format json`".to_string()),
+            details: None,
         };
     }
 
@@ -162,6 +246,7 @@ pub fn envelope_for(error: &anyhow::Error) -> ErrorEnvelope {
                 "re-run with --formula-parse-policy warn to collect diagnostics instead of aborting"
                     .to_string(),
             ),
+            details: None,
         };
     }
 
@@ -170,6 +255,7 @@ pub fn envelope_for(error: &anyhow::Error) -> ErrorEnvelope {
         message,
         did_you_mean: None,
         try_this: None,
+        details: None,
     }
 }
 
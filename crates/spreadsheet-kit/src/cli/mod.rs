@@ -2,11 +2,14 @@ pub mod commands;
 pub mod errors;
 pub mod output;
 
-use crate::model::FormulaParsePolicy;
+use crate::lint::LintFailOn;
+use crate::model::{FormulaLintRule, FormulaParsePolicy, ReferenceStyle};
 use anyhow::Result;
 use clap::{Args, Parser, Subcommand, ValueEnum};
 use serde_json::Value;
 use std::ffi::OsString;
+#[cfg(feature = "serve")]
+use std::net::SocketAddr;
 use std::path::PathBuf;
 
 #[derive(Debug, Clone, Copy, ValueEnum)]
@@ -20,6 +23,8 @@ pub enum TableReadFormat {
     Json,
     Values,
     Csv,
+    /// Arrow IPC (Feather v2) file written to --output
+    Arrow,
 }
 
 #[derive(Debug, Clone, Copy, ValueEnum)]
@@ -41,6 +46,141 @@ pub enum SheetPageFormatArg {
     ValuesOnly,
 }
 
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum SheetPageOrientationArg {
+    Rows,
+    Columns,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum RoundingModeArg {
+    HalfUp,
+    HalfEven,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum DateHandlingArg {
+    Raw,
+    Iso,
+    Both,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum HeaderMatchArg {
+    Exact,
+    Contains,
+    Fuzzy,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum CsvQuoteArg {
+    Minimal,
+    All,
+    NonNumeric,
+    Never,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum CsvLineEndingArg {
+    Lf,
+    Crlf,
+}
+
+/// CSV dialect flags shared by commands that emit csv text.
+#[derive(Debug, Args, Clone)]
+pub struct CsvDialectArgs {
+    #[arg(
+        long = "csv-delimiter",
+        value_name = "CHAR",
+        help = "Field delimiter for csv output: one character, or 'tab' (default ',')"
+    )]
+    pub delimiter: Option<String>,
+    #[arg(
+        long = "csv-quote",
+        value_enum,
+        value_name = "POLICY",
+        help = "Quoting for csv output: minimal (default), all, non-numeric, or never"
+    )]
+    pub quote: Option<CsvQuoteArg>,
+    #[arg(
+        long = "csv-bom",
+        help = "Start csv output with a UTF-8 byte order mark (helps Excel detect the encoding)"
+    )]
+    pub bom: bool,
+    #[arg(
+        long = "csv-line-ending",
+        value_enum,
+        value_name = "ENDING",
+        help = "Row terminator for csv output: lf (default) or crlf"
+    )]
+    pub line_ending: Option<CsvLineEndingArg>,
+    #[arg(
+        long = "csv-decimal-separator",
+        value_name = "CHAR",
+        help = "Decimal separator for numbers in csv output (default '.')"
+    )]
+    pub decimal_separator: Option<char>,
+}
+
+/// Value comparison flags shared by the diff commands.
+#[derive(Debug, Args, Clone)]
+pub struct DiffCompareArgs {
+    #[arg(
+        long = "abs-tolerance",
+        value_name = "DELTA",
+        help = "Treat numbers differing by at most DELTA as unchanged"
+    )]
+    pub abs_tolerance: Option<f64>,
+    #[arg(
+        long = "rel-tolerance",
+        value_name = "FRACTION",
+        help = "Treat numbers differing by at most FRACTION of the larger magnitude as unchanged (0.001 = 0.1%)"
+    )]
+    pub rel_tolerance: Option<f64>,
+    #[arg(
+        long = "ignore-whitespace",
+        help = "Trim text and collapse whitespace runs before comparing values"
+    )]
+    pub ignore_whitespace: bool,
+    #[arg(long = "ignore-case", help = "Compare text values case-insensitively")]
+    pub ignore_case: bool,
+    #[arg(
+        long = "ignore-volatile",
+        help = "Ignore result changes of unchanged formulas that call NOW, TODAY, RAND, OFFSET, INDIRECT, or other volatile functions"
+    )]
+    pub ignore_volatile: bool,
+}
+
+impl From<DiffCompareArgs> for crate::diff::compare::CompareOptions {
+    fn from(args: DiffCompareArgs) -> Self {
+        Self {
+            abs_tolerance: args.abs_tolerance,
+            rel_tolerance: args.rel_tolerance,
+            ignore_whitespace: args.ignore_whitespace,
+            ignore_case: args.ignore_case,
+            ignore_volatile: args.ignore_volatile,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum NameScopeArg {
+    Workbook,
+    Sheet,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum SchemaFormatArg {
+    Jsonschema,
+    Arrow,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum TableOrientationArg {
+    Rows,
+    Transposed,
+}
+
 #[derive(Debug, Clone, Copy, ValueEnum)]
 pub enum TableSampleModeArg {
     First,
@@ -60,6 +200,19 @@ pub enum FindValueMode {
     Label,
 }
 
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum FindValueTypeArg {
+    Number,
+    Date,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum FindCompareOpArg {
+    Gt,
+    Lt,
+    Between,
+}
+
 #[derive(Debug, Clone, Copy, ValueEnum)]
 pub enum LabelDirectionArg {
     Right,
@@ -92,6 +245,46 @@ pub enum LayoutRenderArg {
     Both,
 }
 
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum LintFormatArg {
+    Json,
+    Sarif,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum RecalcEngineArg {
+    Auto,
+    Builtin,
+    Libreoffice,
+}
+
+impl From<RecalcEngineArg> for crate::config::RecalcBackendKind {
+    fn from(value: RecalcEngineArg) -> Self {
+        match value {
+            RecalcEngineArg::Auto => Self::Auto,
+            RecalcEngineArg::Builtin => Self::Formualizer,
+            RecalcEngineArg::Libreoffice => Self::Libreoffice,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum RecalcStrategyArg {
+    FireAndForget,
+    /// Rejected until a pooled soffice executor exists.
+    #[value(hide = true)]
+    Pooled,
+}
+
+impl From<RecalcStrategyArg> for crate::config::ExecutorStrategy {
+    fn from(value: RecalcStrategyArg) -> Self {
+        match value {
+            RecalcStrategyArg::FireAndForget => Self::FireAndForget,
+            RecalcStrategyArg::Pooled => Self::Pooled,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, ValueEnum)]
 pub enum AppendRegionFooterPolicyArg {
     Auto,
@@ -99,6 +292,13 @@ pub enum AppendRegionFooterPolicyArg {
     AppendAtEnd,
 }
 
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum RefRepairConfidenceArg {
+    High,
+    Medium,
+    Low,
+}
+
 #[derive(Debug, Clone, Copy, ValueEnum)]
 pub enum ClonePatchTargetsArg {
     LikelyInputs,
@@ -302,6 +502,72 @@ pub enum SessionCommands {
     },
 }
 
+#[derive(Debug, Subcommand)]
+pub enum RevisionsCommands {
+    #[command(about = "List revisions recorded for a workbook, oldest first")]
+    List {
+        #[arg(value_name = "FILE", help = "Base workbook or any of its revisions")]
+        file: PathBuf,
+    },
+    #[command(about = "Diff two revisions of a workbook (0 is the base workbook)")]
+    Diff {
+        #[arg(value_name = "FILE", help = "Base workbook or any of its revisions")]
+        file: PathBuf,
+        #[arg(
+            long,
+            value_name = "REV",
+            help = "Baseline revision (default: the parent of --to)"
+        )]
+        from: Option<u32>,
+        #[arg(
+            long,
+            value_name = "REV",
+            help = "Revision to compare (default: the newest recorded revision)"
+        )]
+        to: Option<u32>,
+        #[arg(
+            long,
+            help = "Include paged change items; default output is summary-only"
+        )]
+        details: bool,
+        #[arg(
+            long = "exclude-recalc-result",
+            help = "Exclude recalc_result cell changes from summary and details"
+        )]
+        exclude_recalc_result: bool,
+        #[command(flatten)]
+        compare: DiffCompareArgs,
+        #[arg(
+            long = "ignore-file",
+            value_name = "PATH",
+            help = "JSON ignore rules: {\"sheets\":[...],\"ranges\":[\"Sheet!A1\"],\"styles\":true}"
+        )]
+        ignore_file: Option<PathBuf>,
+        #[arg(
+            long,
+            default_value_t = 200,
+            help = "Page size for --details (1..2000)"
+        )]
+        limit: u32,
+        #[arg(long, default_value_t = 0, help = "Offset for --details pagination")]
+        offset: u32,
+    },
+    #[command(
+        about = "Replace the base workbook with a revision, keeping the current one as a new revision"
+    )]
+    Restore {
+        #[arg(value_name = "FILE", help = "Base workbook or any of its revisions")]
+        file: PathBuf,
+        #[arg(long, value_name = "REV", help = "Revision to restore")]
+        revision: u32,
+        #[arg(
+            long,
+            help = "Restore even if the revision was modified after it was written"
+        )]
+        force: bool,
+    },
+}
+
 #[derive(Debug, Subcommand)]
 pub enum DiscoverabilityCommands {
     #[command(about = "Schema/example target for transform-batch payloads")]
@@ -314,10 +580,14 @@ pub enum DiscoverabilityCommands {
     StructureBatch,
     #[command(about = "Schema/example target for column-size-batch payloads")]
     ColumnSizeBatch,
+    #[command(about = "Schema/example target for row-size-batch payloads")]
+    RowSizeBatch,
     #[command(about = "Schema/example target for sheet-layout-batch payloads")]
     SheetLayoutBatch,
     #[command(about = "Schema/example target for rules-batch payloads")]
     RulesBatch,
+    #[command(about = "Schema/example target for properties-batch payloads")]
+    PropertiesBatch,
     #[command(about = "Schema/example target for event-sourced session op payloads")]
     SessionOp {
         #[arg(
@@ -353,14 +623,28 @@ enum SurfaceReadCommands {
     Cells(SurfaceLeafArgs),
     #[command(about = "Read one sheet page with deterministic continuation")]
     Page(SurfaceLeafArgs),
+    #[command(about = "Page through every sheet with one continuation cursor")]
+    WorkbookPage(SurfaceLeafArgs),
     #[command(about = "Read a table-like region as json, values, or csv")]
     Table(SurfaceLeafArgs),
+    #[command(about = "Estimate rows, cells, and payload size of a table read")]
+    Estimate(SurfaceLeafArgs),
     #[command(about = "List workbook named ranges and table/formula named items")]
     Names(SurfaceLeafArgs),
+    #[command(about = "List tool metadata stored in the workbook")]
+    Metadata(SurfaceLeafArgs),
+    #[command(about = "List machine-readable annotations attached to cells and ranges")]
+    Annotations(SurfaceLeafArgs),
+    #[command(about = "List links to other workbooks and whether their cached values are stale")]
+    ExternalLinks(SurfaceLeafArgs),
+    #[command(about = "Extract user-visible text into a translation table")]
+    Strings(SurfaceLeafArgs),
     #[command(about = "Describe workbook-level metadata and sheet counts")]
     Workbook(SurfaceLeafArgs),
     #[command(about = "Render a range with layout metadata")]
     Layout(SurfaceLeafArgs),
+    #[command(about = "Render a sheet range to PNG for visual verification")]
+    Render(SurfaceLeafArgs),
 }
 
 #[derive(Debug, Subcommand)]
@@ -375,18 +659,34 @@ enum SurfaceAnalyzeCommands {
     FormulaTrace(SurfaceLeafArgs),
     #[command(about = "Scan workbook formulas for volatile functions")]
     ScanVolatiles(SurfaceLeafArgs),
+    #[command(about = "Report cells whose cached values are errors, grouped by error type")]
+    ScanErrors(SurfaceLeafArgs),
+    #[command(
+        about = "Lint formulas for hard-coded constants, full-column aggregations, deep IFs, and inconsistencies"
+    )]
+    LintFormulas(SurfaceLeafArgs),
     #[command(about = "Compute per-sheet statistics for density and column types")]
     SheetStatistics(SurfaceLeafArgs),
     #[command(about = "Profile table headers, types, and column distributions")]
     TableProfile(SurfaceLeafArgs),
+    #[command(about = "Group a table and compute sum/avg/count/min/max per group")]
+    QueryTable(SurfaceLeafArgs),
+    #[command(about = "Propose a column correspondence between two differently-labeled tables")]
+    MapColumns(SurfaceLeafArgs),
     #[command(about = "Analyze structural operation impact without mutation")]
     RefImpact(SurfaceLeafArgs),
+    #[command(
+        about = "Diff a desired-state spec against a workbook and emit the minimal ops to converge it"
+    )]
+    Plan(SurfaceLeafArgs),
 }
 
 #[derive(Debug, Subcommand)]
 enum SurfaceWriteFormulaCommands {
     #[command(about = "Find and replace text in formula bodies (not values)")]
     Replace(SurfaceLeafArgs),
+    #[command(about = "Propose and apply repairs for #REF! references from a baseline workbook")]
+    Repair(SurfaceLeafArgs),
 }
 
 #[derive(Debug, Subcommand)]
@@ -399,6 +699,14 @@ enum SurfaceWriteNameCommands {
     Delete(SurfaceLeafArgs),
 }
 
+#[derive(Debug, Subcommand)]
+enum SurfaceWriteMetadataCommands {
+    #[command(about = "Store a tool metadata entry in the workbook")]
+    Set(SurfaceLeafArgs),
+    #[command(about = "Remove tool metadata entries from the workbook")]
+    Clear(SurfaceLeafArgs),
+}
+
 #[derive(Debug, Subcommand)]
 enum SurfaceWriteBatchCommands {
     #[command(about = "Apply stateless transform operations from an @ops payload")]
@@ -411,12 +719,16 @@ enum SurfaceWriteBatchCommands {
     Structure(SurfaceLeafArgs),
     #[command(about = "Apply stateless column sizing operations from an @ops payload")]
     ColumnSize(SurfaceLeafArgs),
+    #[command(about = "Apply stateless row sizing operations from an @ops payload")]
+    RowSize(SurfaceLeafArgs),
     #[command(about = "Apply stateless sheet layout operations from an @ops payload")]
     SheetLayout(SurfaceLeafArgs),
     #[command(
         about = "Apply stateless data validation and conditional format operations from an @ops payload"
     )]
     Rules(SurfaceLeafArgs),
+    #[command(about = "Set core, app and custom document properties from an @ops payload")]
+    Properties(SurfaceLeafArgs),
 }
 
 #[derive(Debug, Subcommand)]
@@ -425,8 +737,14 @@ enum SurfaceWriteCommands {
     Cells(SurfaceLeafArgs),
     #[command(about = "Import range data from grid JSON or CSV")]
     Import(SurfaceLeafArgs),
+    #[command(about = "Stream a columnar payload of typed values into sheets for bulk loads")]
+    Bulk(SurfaceLeafArgs),
     #[command(about = "Append rows into a detected region with footer-aware insertion")]
     Append(SurfaceLeafArgs),
+    #[command(about = "Materialize a table query into a sheet with stored refresh metadata")]
+    Materialize(SurfaceLeafArgs),
+    #[command(about = "Re-run recorded materializations and rewrite their sheets")]
+    Refresh(SurfaceLeafArgs),
     #[command(about = "Clone one template row into inserted rows with preview-first planning")]
     CloneTemplateRow(SurfaceLeafArgs),
     #[command(about = "Clone a contiguous template row band with preview-first planning")]
@@ -435,8 +753,20 @@ enum SurfaceWriteCommands {
     Formulas(SurfaceWriteFormulaCommands),
     #[command(subcommand, about = "Named range mutation helpers")]
     Name(SurfaceWriteNameCommands),
+    #[command(subcommand, about = "Tool metadata stored in the workbook")]
+    Metadata(SurfaceWriteMetadataCommands),
+    #[command(about = "Attach a machine-readable annotation to a cell or range")]
+    Annotate(SurfaceLeafArgs),
+    #[command(about = "Write translated text from a translation table back into a workbook")]
+    Strings(SurfaceLeafArgs),
     #[command(subcommand, about = "Stateless batch mutation surfaces")]
     Batch(SurfaceWriteBatchCommands),
+    #[command(
+        about = "Apply a plan of transform, style, structure, and rules payloads as one atomic write"
+    )]
+    Plan(SurfaceLeafArgs),
+    #[command(about = "Converge a workbook to a desired-state spec in one step, reporting drift")]
+    State(SurfaceLeafArgs),
 }
 
 #[derive(Debug, Subcommand)]
@@ -445,8 +775,18 @@ enum SurfaceWorkbookCommands {
     Create(SurfaceLeafArgs),
     #[command(about = "Copy a workbook to a new path for safe edits")]
     Copy(SurfaceLeafArgs),
+    #[command(about = "Fill {{placeholder}} tokens in a template workbook into a new file")]
+    Instantiate(SurfaceLeafArgs),
+    #[command(about = "Restore a workbook from a snapshot taken by --snapshot-dir")]
+    Restore(SurfaceLeafArgs),
+    #[command(about = "List snapshots taken by --snapshot-dir, newest first")]
+    Snapshots(SurfaceLeafArgs),
     #[command(about = "Recalculate workbook formulas")]
     Recalculate(SurfaceLeafArgs),
+    #[command(about = "Export a workbook or one sheet to PDF, honoring print areas")]
+    ExportPdf(SurfaceLeafArgs),
+    #[command(about = "Find exact, same-content, and near-duplicate workbooks under folders")]
+    DedupeScan(SurfaceLeafArgs),
 }
 
 #[derive(Debug, Subcommand)]
@@ -455,6 +795,20 @@ enum SurfaceVerifyCommands {
     Proof(SurfaceLeafArgs),
     #[command(about = "Diff two workbook versions with summary-first, paged details")]
     Diff(SurfaceLeafArgs),
+    #[command(
+        about = "Diff one baseline workbook against several candidates, parsing the baseline once"
+    )]
+    DiffMany(SurfaceLeafArgs),
+    #[command(about = "Render a range from two workbooks and overlay what changed visually")]
+    VisualDiff(SurfaceLeafArgs),
+    #[command(
+        about = "Recalculate a workbook copy and check `_tests` sheet or sidecar expectations"
+    )]
+    RunTests(SurfaceLeafArgs),
+    #[command(
+        about = "Lint a workbook against a profile and fail on findings at or above a severity"
+    )]
+    Lint(SurfaceLeafArgs),
 }
 
 #[derive(Debug, Subcommand)]
@@ -469,10 +823,14 @@ enum SurfaceDiscoverabilityBatchCommands {
     Structure,
     #[command(about = "Schema/example target for column size batch payloads")]
     ColumnSize,
+    #[command(about = "Schema/example target for row size batch payloads")]
+    RowSize,
     #[command(about = "Schema/example target for sheet layout batch payloads")]
     SheetLayout,
     #[command(about = "Schema/example target for rules batch payloads")]
     Rules,
+    #[command(about = "Schema/example target for properties batch payloads")]
+    Properties,
 }
 
 #[derive(Debug, Subcommand)]
@@ -525,6 +883,41 @@ enum SurfaceCommands {
     },
     #[command(about = "Event-sourced session management", subcommand, hide = false)]
     Session(Box<SessionCommands>),
+    #[cfg(feature = "serve")]
+    #[command(
+        about = "Keep workbooks parsed in memory and answer requests over stdio MCP or local HTTP"
+    )]
+    Serve {
+        #[arg(
+            long,
+            value_name = "ADDR",
+            help = "Serve POST /rpc on this loopback address (e.g. 127.0.0.1:8790) instead of stdio"
+        )]
+        http: Option<SocketAddr>,
+    },
+    #[command(
+        subcommand,
+        about = "Workbook revisions written by --output auto (list, diff, restore)"
+    )]
+    Revisions(RevisionsCommands),
+    #[command(about = "Remove orphaned fork copies and temp files from the scratch directory")]
+    Gc {
+        #[arg(
+            long,
+            value_name = "DIR",
+            help = "Scratch root to clean (default: $SPREADSHEET_MCP_SCRATCH_DIR or <system temp>/spreadsheet-mcp)"
+        )]
+        scratch_dir: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "SECONDS",
+            default_value_t = 900,
+            help = "Treat scratch untouched for this long as orphaned"
+        )]
+        older_than: u64,
+        #[arg(long, help = "List what would be removed without deleting anything")]
+        dry_run: bool,
+    },
     #[command(about = "SheetPort manifest lifecycle and execution commands")]
     Sheetport {
         #[command(subcommand)]
@@ -537,7 +930,7 @@ enum SurfaceCommands {
     name = "asp",
     version,
     about = "Stateless spreadsheet CLI for reads, writes, and verification workflows",
-    long_about = "Stateless spreadsheet CLI for AI and automation workflows.\n\nPrimary command: asp\nCompatibility alias: agent-spreadsheet\n\nVerify install:\n  asp --version\n  asp --help\n\nPrimary groups:\n  • read      -> workbook extraction and inspection\n  • analyze   -> search, profiling, and diagnostics\n  • write     -> direct edits, workflow helpers, and batch mutations\n  • workbook  -> file-level create/copy/recalculate flows\n  • verify    -> proof and diff review surfaces\n  • session   -> event-sourced stateful editing\n  • sheetport -> manifest lifecycle and execution\n\nDiscoverability:\n  • asp schema write batch transform\n  • asp example write batch transform\n  • asp schema session op transform.write_matrix\n\nTip: global --output-format csv is currently unsupported and returns an error. Use --output-format json, or command-level CSV options such as asp read table --table-format csv."
+    long_about = "Stateless spreadsheet CLI for AI and automation workflows.\n\nPrimary command: asp\nCompatibility alias: agent-spreadsheet\n\nVerify install:\n  asp --version\n  asp --help\n\nPrimary groups:\n  • read      -> workbook extraction and inspection\n  • analyze   -> search, profiling, and diagnostics\n  • write     -> direct edits, workflow helpers, and batch mutations\n  • workbook  -> file-level create/copy/recalculate flows\n  • verify    -> proof and diff review surfaces\n  • session   -> event-sourced stateful editing\n  • serve     -> long-running process with in-memory workbooks\n  • sheetport -> manifest lifecycle and execution\n\nDiscoverability:\n  • asp schema write batch transform\n  • asp example write batch transform\n  • asp schema session op transform.write_matrix\n\nTip: global --output-format csv is currently unsupported and returns an error. Use --output-format json, or command-level CSV options such as asp read table --table-format csv."
 )]
 struct SurfaceCli {
     #[arg(
@@ -568,6 +961,20 @@ struct SurfaceCli {
     #[arg(long, global = true, help = "Suppress non-fatal warnings")]
     quiet: bool,
 
+    #[arg(
+        long,
+        global = true,
+        help = "Attach per-phase workbook open timings (zip open, SST parse, sheet parse, region detection) to the response"
+    )]
+    timings: bool,
+
+    #[arg(
+        long = "canonical-json",
+        global = true,
+        help = "Emit canonical JSON: object keys sorted and floats in shortest ECMAScript form, so equal payloads are byte-identical"
+    )]
+    canonical_json: bool,
+
     #[command(subcommand)]
     command: SurfaceCommands,
 }
@@ -577,7 +984,7 @@ struct SurfaceCli {
     name = "asp",
     version,
     about = "Stateless spreadsheet CLI for reads, edits, and diffs",
-    long_about = "Stateless spreadsheet CLI for AI and automation workflows.\n\nPrimary command: asp\nCompatibility alias: agent-spreadsheet\n\nVerify install:\n  asp --version\n  asp --help\n\nCommon workflows:\n  • Inspect a workbook: list-sheets → sheet-overview → table-profile\n  • Deterministic pagination loops: sheet-page (--format + next_start_row) and read-table (--limit/--offset + next_offset)\n  • Find labels or values: find-value --mode label|value\n  • Discover payload contracts: schema <target> / example <target>\n  • Stateless batch writes: transform/style/formula/structure/column/layout/rules via --ops @ops.json + one mode (--dry-run|--in-place|--output)\n  • Copy → edit → recalculate → diff for safe what-if changes\n  • SheetPort manifest loop: sheetport manifest candidates → draft/edit YAML → sheetport manifest validate → sheetport bind-check → sheetport run\n\nTip: global --output-format csv is currently unsupported and returns an error. Use --output-format json, or command-level CSV options such as read-table --table-format csv."
+    long_about = "Stateless spreadsheet CLI for AI and automation workflows.\n\nPrimary command: asp\nCompatibility alias: agent-spreadsheet\n\nVerify install:\n  asp --version\n  asp --help\n\nCommon workflows:\n  • Inspect a workbook: list-sheets → sheet-overview → table-profile\n  • Deterministic pagination loops: sheet-page (--format + next_start_row), workbook-page (next_start_sheet + next_start_row), and read-table (--limit/--offset + next_offset)\n  • Find labels or values: find-value --mode label|value\n  • Discover payload contracts: schema <target> / example <target>\n  • Stateless batch writes: transform/style/formula/structure/column/layout/rules via --ops @ops.json + one mode (--dry-run|--in-place|--output)\n  • Copy → edit → recalculate → diff for safe what-if changes\n  • SheetPort manifest loop: sheetport manifest candidates → draft/edit YAML → sheetport manifest validate → sheetport bind-check → sheetport run\n\nTip: global --output-format csv is currently unsupported and returns an error. Use --output-format json, or command-level CSV options such as read-table --table-format csv."
 )]
 pub struct Cli {
     #[arg(
@@ -608,6 +1015,20 @@ pub struct Cli {
     #[arg(long, global = true, help = "Suppress non-fatal warnings")]
     pub quiet: bool,
 
+    #[arg(
+        long,
+        global = true,
+        help = "Attach per-phase workbook open timings (zip open, SST parse, sheet parse, region detection) to the response"
+    )]
+    pub timings: bool,
+
+    #[arg(
+        long = "canonical-json",
+        global = true,
+        help = "Emit canonical JSON: object keys sorted and floats in shortest ECMAScript form, so equal payloads are byte-identical"
+    )]
+    pub canonical_json: bool,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -655,7 +1076,7 @@ pub enum Commands {
     },
     #[command(
         about = "Read raw values for one or more A1 ranges",
-        after_long_help = "Examples:\n  agent-spreadsheet range-values data.xlsx Sheet1 A1:C20\n  agent-spreadsheet range-values data.xlsx \"Q1 Actuals\" A1:B5 D10:E20\n  agent-spreadsheet range-values data.xlsx Sheet1 A1:C20 --include-formulas\n\nDense default:\n  range-values defaults to dense JSON encoding optimized for agent consumption:\n  dictionary + row_runs + optional sparse formulas.\n\nFormula semantics:\n  By default, range-values returns resolved values only.\n  Use --include-formulas to include formulas in the response (sparse list in dense mode, matrix in json mode).\n\nShape behavior:\n  range-values keeps a stable top-level shape in both canonical and compact modes (no single-range flattening).\n\nRelated:\n  Use inspect-cells when you need formula + value + style metadata in one response."
+        after_long_help = "Examples:\n  agent-spreadsheet range-values data.xlsx Sheet1 A1:C20\n  agent-spreadsheet range-values data.xlsx \"Q1 Actuals\" A1:B5 D10:E20\n  agent-spreadsheet range-values data.xlsx Sheet1 A1:C20 --include-formulas\n  agent-spreadsheet range-values data.xlsx \"Income Statement\" A1:F40 --include-formatting\n\nDense default:\n  range-values defaults to dense JSON encoding optimized for agent consumption:\n  dictionary + row_runs + optional sparse formulas.\n\nFormula semantics:\n  By default, range-values returns resolved values only.\n  Use --include-formulas to include formulas in the response (sparse list in dense mode, matrix in json mode).\n\nFormatting:\n  --include-formatting adds a sparse formatting list per range: row/col (zero-based within returned rows), number_format (omitted for General), and display, the text Excel shows (0.1234 under 0.0% displays as 12.3%).\n\nShape behavior:\n  range-values keeps a stable top-level shape in both canonical and compact modes (no single-range flattening).\n\nRelated:\n  Use inspect-cells when you need formula + value + style metadata in one response."
     )]
     RangeValues {
         #[arg(value_name = "FILE", help = "Path to the workbook")]
@@ -682,6 +1103,14 @@ pub enum Commands {
             help = "Include formulas (sparse list in dense mode, matrix in json mode)"
         )]
         include_formulas: Option<bool>,
+        #[arg(
+            long = "include-formatting",
+            value_name = "BOOL",
+            num_args = 0..=1,
+            default_missing_value = "true",
+            help = "Include number format codes and display text (e.g. 12.3%) for non-empty cells"
+        )]
+        include_formatting: Option<bool>,
         #[arg(
             long,
             value_name = "ID",
@@ -697,7 +1126,7 @@ pub enum Commands {
     },
     #[command(
         about = "Export a range to a specific format (e.g., csv, grid)",
-        after_long_help = "Examples:\n  agent-spreadsheet range-export data.xlsx Sheet1 A1:C20 --format csv --output data.csv\n  agent-spreadsheet range-export data.xlsx Sheet1 A1:C20 --format csv --output -"
+        after_long_help = "Examples:\n  agent-spreadsheet range-export data.xlsx Sheet1 A1:C20 --format csv --output data.csv\n  agent-spreadsheet range-export data.xlsx Sheet1 A1:C20 --format csv --output -\n  agent-spreadsheet range-export data.xlsx Sheet1 A1:C20 --format csv --csv-delimiter ';' --csv-decimal-separator , --csv-bom --output data-eu.csv\n  agent-spreadsheet range-export data.xlsx Sheet1 A1:F500 --format arrow --output data.arrow\n  agent-spreadsheet range-export data.xlsx Sheet1 A1:C20 --format csv --include-formatting --output displayed.csv\n\nCSV dialect:\n  --csv-* flags apply to --format csv only; the defaults give comma-delimited, minimally quoted, LF-terminated rows.\n\nArrow output:\n  --format arrow writes an Arrow IPC (Feather v2) file to --output, using the first row of the range as column names and typing columns as table-profile does."
     )]
     RangeExport {
         #[arg(value_name = "FILE", help = "Path to the workbook")]
@@ -706,7 +1135,11 @@ pub enum Commands {
         sheet: String,
         #[arg(value_name = "RANGE", help = "A1 range (for example A1:C10)")]
         range: String,
-        #[arg(long, help = "Output format (e.g. csv, grid)", default_value = "json")]
+        #[arg(
+            long,
+            help = "Output format: json, csv, grid, or arrow",
+            default_value = "json"
+        )]
         format: String,
         #[arg(long, help = "Output path or '-' for stdout")]
         output: Option<String>,
@@ -718,6 +1151,16 @@ pub enum Commands {
             help = "Include parsed formulas in formula cells alongside evaluated values (JSON only)"
         )]
         include_formulas: Option<bool>,
+        #[arg(
+            long = "include-formatting",
+            value_name = "BOOL",
+            num_args = 0..=1,
+            default_missing_value = "true",
+            help = "JSON: add number formats and display text per cell; csv: write display text instead of raw values"
+        )]
+        include_formatting: Option<bool>,
+        #[command(flatten)]
+        csv: CsvDialectArgs,
         #[arg(
             long,
             value_name = "ID",
@@ -758,6 +1201,60 @@ pub enum Commands {
         output: Option<PathBuf>,
         #[arg(long, help = "Allow overwriting --output when it already exists")]
         force: bool,
+        #[arg(
+            long = "verify-with",
+            value_name = "TESTS_JSON",
+            help = "Recalculate a copy of the staged result and check these expectations before writing; fail without writing on any mismatch"
+        )]
+        verify_with: Option<PathBuf>,
+        #[arg(
+            long = "audit-log",
+            value_name = "PATH",
+            help = "Append a JSONL record of the applied write (command, import file hash, changed cells, before/after hashes) to this file"
+        )]
+        audit_log: Option<PathBuf>,
+        #[arg(
+            long = "snapshot-dir",
+            value_name = "DIR",
+            help = "With --in-place, copy the workbook into this directory (or s3:// / gs:// prefix, with the object-store feature) before replacing it; undo with `restore`"
+        )]
+        snapshot_dir: Option<PathBuf>,
+    },
+    #[command(
+        about = "Stream a columnar payload of typed values into sheets for bulk loads",
+        after_long_help = "Examples:\n  agent-spreadsheet write-cells data.xlsx --payload @cells.json --in-place\n  agent-spreadsheet write-cells data.xlsx --payload @cells.json --output loaded.xlsx\n\nPayload:\n  {\"blocks\":[{\"sheet_name\":\"Data\",\"range\":\"A2\",\"columns\":[{\"type\":\"string\",\"values\":[\"north\",\"south\"]},{\"type\":\"number\",\"values\":[1.5,null]}]}]}\n  Each block writes its columns side by side from the top-left cell of range; a multi-cell range must match the block's shape. Column types are number, string and bool; null leaves the cell unchanged. Later blocks win where blocks overlap.\n\nwrite-cells merges values into the sheet XML in one streaming pass instead of loading the workbook, so it suits loads of 100k+ cells. Strings are stored inline, replaced cells keep their style, and cells anchoring a shared or array formula are refused. Formula results are not recalculated; run recalculate afterwards if formulas depend on the written cells.\n\nThe response reports summary counts and metrics: payload_bytes, parse_ms, write_ms, elapsed_ms and cells_per_second."
+    )]
+    WriteCells {
+        #[arg(value_name = "FILE", help = "Path to the workbook")]
+        file: PathBuf,
+        #[arg(long, value_name = "@PATH", help = "Columnar cell payload as @<path>")]
+        payload: String,
+        #[arg(long, help = "Validate and time the write without mutating files")]
+        dry_run: bool,
+        #[arg(long, help = "Apply by atomically replacing the source file")]
+        in_place: bool,
+        #[arg(long, help = "Apply to this output path")]
+        output: Option<PathBuf>,
+        #[arg(long, help = "Allow overwriting --output when it already exists")]
+        force: bool,
+        #[arg(
+            long = "verify-with",
+            value_name = "TESTS_JSON",
+            help = "Recalculate a copy of the staged result and check these expectations before writing; fail without writing on any mismatch"
+        )]
+        verify_with: Option<PathBuf>,
+        #[arg(
+            long = "audit-log",
+            value_name = "PATH",
+            help = "Append a JSONL record of the applied write (command, ops hash, changed cells, before/after hashes) to this file"
+        )]
+        audit_log: Option<PathBuf>,
+        #[arg(
+            long = "snapshot-dir",
+            value_name = "DIR",
+            help = "With --in-place, copy the workbook into this directory (or s3:// / gs:// prefix, with the object-store feature) before replacing it; undo with `restore`"
+        )]
+        snapshot_dir: Option<PathBuf>,
     },
     #[command(
         about = "Inspect detail snapshots for targeted A1 cells/ranges (detail view, default max 25 cells)",
@@ -806,7 +1303,7 @@ For broader discovery, use sheet-page, range-values, or layout-page."
     },
     #[command(
         about = "Read one sheet page with deterministic continuation",
-        after_long_help = "Examples:\n  agent-spreadsheet sheet-page data.xlsx Sheet1 --format compact --page-size 200\n  agent-spreadsheet sheet-page data.xlsx Sheet1 --format compact --page-size 200 --start-row 201\n  agent-spreadsheet sheet-page data.xlsx Sheet1 --format full --columns A,C:E --include-styles\n\nMachine contract:\n  - Inspect the top-level format field first.\n  - format=full: consume top-level rows/header_row/next_start_row.\n  - format=compact: consume compact.headers/compact.header_row/compact.rows plus next_start_row.\n  - format=values_only: consume values_only.rows plus next_start_row.\n  - Global --shape compact preserves the active sheet-page branch (no flattening).\n\nPagination loop:\n  1) Run without --start-row.\n  2) If next_start_row is present, pass it to --start-row for the next request.\n  3) Stop when next_start_row is omitted.\n\nMachine continuation example:\n  Request page 1, read next_start_row, then request page 2 with --start-row <next_start_row>."
+        after_long_help = "Examples:\n  agent-spreadsheet sheet-page data.xlsx Sheet1 --format compact --page-size 200\n  agent-spreadsheet sheet-page data.xlsx Sheet1 --format compact --page-size 200 --start-row 201\n  agent-spreadsheet sheet-page data.xlsx Sheet1 --format full --columns A,C:E --include-styles\n  agent-spreadsheet sheet-page metrics.xlsx Sheet1 --format compact --orientation columns --columns-by-header Revenue,Margin\n\nMachine contract:\n  - Inspect the top-level format field first.\n  - format=full: consume top-level rows/header_row/next_start_row.\n  - format=compact: consume compact.headers/compact.header_row/compact.rows plus next_start_row.\n  - format=values_only: consume values_only.rows plus next_start_row.\n  - Global --shape compact preserves the active sheet-page branch (no flattening).\n  - --orientation columns: consume column_page.columns (each with column, header, and values, or cells for format=full) plus next_start_column.\n\nPagination loop:\n  1) Run without --start-row.\n  2) If next_start_row is present, pass it to --start-row for the next request.\n  3) Stop when next_start_row is omitted.\n  Column pages continue the same way with next_start_column and --start-column.\n\nMachine continuation example:\n  Request page 1, read next_start_row, then request page 2 with --start-row <next_start_row>."
     )]
     SheetPage {
         #[arg(value_name = "FILE", help = "Path to the workbook")]
@@ -835,6 +1332,21 @@ For broader discovery, use sheet-page, range-values, or layout-page."
             help = "Column selectors by header text (case-insensitive)"
         )]
         columns_by_header: Option<Vec<String>>,
+        #[arg(
+            long = "header-match",
+            value_enum,
+            value_name = "MODE",
+            requires = "columns_by_header",
+            help = "How --columns-by-header matches: exact (default), contains (every header containing the text), or fuzzy (the most similar header)"
+        )]
+        header_match: Option<HeaderMatchArg>,
+        #[arg(
+            long = "header-match-threshold",
+            value_name = "SCORE",
+            requires = "header_match",
+            help = "Minimum similarity (0-1] for --header-match fuzzy (default 0.6)"
+        )]
+        header_match_threshold: Option<f64>,
         #[arg(
             long = "include-formulas",
             value_name = "BOOL",
@@ -867,6 +1379,26 @@ For broader discovery, use sheet-page, range-values, or layout-page."
             help = "Page output format: full, compact, or values_only"
         )]
         format: SheetPageFormatArg,
+        #[arg(
+            long,
+            value_enum,
+            value_name = "ORIENTATION",
+            help = "Page orientation: rows (default) or columns (one record per column; --page-size counts columns)"
+        )]
+        orientation: Option<SheetPageOrientationArg>,
+        #[arg(
+            long = "start-column",
+            value_name = "COLUMN",
+            help = "First column letter for --orientation columns pages"
+        )]
+        start_column: Option<String>,
+        #[arg(
+            long = "date-handling",
+            value_enum,
+            value_name = "MODE",
+            help = "Date-formatted cells: iso (default, ISO-8601 text), raw (serial number), or both (ISO text plus serials in date_serials)"
+        )]
+        date_handling: Option<DateHandlingArg>,
         #[arg(
             long,
             value_name = "ID",
@@ -881,30 +1413,115 @@ For broader discovery, use sheet-page, range-values, or layout-page."
         session_workspace: Option<PathBuf>,
     },
     #[command(
-        about = "Read a table-like region as json, values, or csv",
-        after_long_help = "Examples:\n  agent-spreadsheet read-table data.xlsx --sheet Sheet1 --table-format values\n  agent-spreadsheet read-table data.xlsx --sheet Sheet1 --table-format csv --limit 50 --offset 0\n  agent-spreadsheet read-table data.xlsx --table-name SalesTable --sample-mode distributed --limit 20\n\nPagination loop:\n  Repeat with --offset set to next_offset until next_offset is omitted."
+        about = "Page through every sheet in workbook order with one continuation cursor",
+        after_long_help = "Examples:\n  agent-spreadsheet workbook-page data.xlsx --format compact --page-size 200\n  agent-spreadsheet workbook-page data.xlsx --format compact --page-size 200 --start-sheet Summary --start-row 41\n\nMachine contract:\n  - pages lists one sheet-page payload per sheet visited, in workbook order; each carries sheet_name and the active format branch.\n  - --page-size is shared across sheets: once a sheet is exhausted the page continues on the next sheet.\n  - next_start_sheet + next_start_row form the continuation cursor; both are omitted after the last row of the last sheet.\n\nPagination loop:\n  1) Run without --start-sheet/--start-row.\n  2) If next_start_sheet is present, pass it to --start-sheet and next_start_row to --start-row.\n  3) Stop when next_start_sheet is omitted."
     )]
-    ReadTable {
+    WorkbookPage {
         #[arg(value_name = "FILE", help = "Path to the workbook")]
         file: PathBuf,
-        #[arg(long, value_name = "SHEET", help = "Restrict read to a specific sheet")]
-        sheet: Option<String>,
-        #[arg(long, value_name = "RANGE", help = "Optional A1 range override")]
-        range: Option<String>,
-        #[arg(long, value_name = "NAME", help = "Read from a named Excel table")]
-        table_name: Option<String>,
-        #[arg(long, value_name = "ID", help = "Read from a detected region id")]
-        region_id: Option<u32>,
         #[arg(
-            long,
-            value_name = "LIMIT",
-            help = "Maximum rows to return (must be at least 1)"
+            long = "start-sheet",
+            value_name = "SHEET",
+            help = "Sheet to resume from (default: first sheet)"
         )]
-        limit: Option<u32>,
-        #[arg(long, value_name = "OFFSET", help = "Row offset for pagination")]
-        offset: Option<u32>,
+        start_sheet: Option<String>,
         #[arg(
-            long = "sample-mode",
+            long,
+            value_name = "ROW",
+            help = "1-based row to resume from within --start-sheet"
+        )]
+        start_row: Option<u32>,
+        #[arg(
+            long = "page-size",
+            value_name = "N",
+            help = "Rows per page across all sheets (must be at least 1)"
+        )]
+        page_size: Option<u32>,
+        #[arg(
+            long = "include-formulas",
+            value_name = "BOOL",
+            num_args = 0..=1,
+            default_missing_value = "true",
+            help = "Include formulas (default true)"
+        )]
+        include_formulas: Option<bool>,
+        #[arg(
+            long = "include-styles",
+            value_name = "BOOL",
+            num_args = 0..=1,
+            default_missing_value = "true",
+            help = "Include style metadata (default false)"
+        )]
+        include_styles: Option<bool>,
+        #[arg(
+            long = "include-header",
+            value_name = "BOOL",
+            num_args = 0..=1,
+            default_missing_value = "true",
+            help = "Include each sheet's header row (default true)"
+        )]
+        include_header: Option<bool>,
+        #[arg(
+            long,
+            value_enum,
+            value_name = "FORMAT",
+            required = true,
+            help = "Page output format: full, compact, or values_only"
+        )]
+        format: SheetPageFormatArg,
+    },
+    #[command(
+        about = "Read a table-like region as json, values, or csv",
+        after_long_help = "Examples:\n  agent-spreadsheet read-table data.xlsx --sheet Sheet1 --table-format values\n  agent-spreadsheet read-table data.xlsx --sheet Sheet1 --table-format csv --limit 50 --offset 0\n  agent-spreadsheet read-table data.xlsx --table-name SalesTable --sample-mode distributed --limit 20\n  agent-spreadsheet read-table data.xlsx --query monthly_expenses --limit 50\n  agent-spreadsheet read-table data.xlsx --sheet Budget --header-rows 2 --table-format json\n  agent-spreadsheet read-table data.xlsx --sheet Assumptions --range A1:F12 --orientation transposed\n  agent-spreadsheet read-table data.xlsx --sheet Ledger --decimals 2 --rounding half-even\n  agent-spreadsheet read-table data.xlsx --sheet Ledger --table-format csv --csv-delimiter ';' --csv-decimal-separator , --csv-bom --csv-line-ending crlf\n  agent-spreadsheet read-table data.xlsx --table-name SalesTable --table-format arrow --limit 5000 --output sales.arrow\n\nPagination loop:\n  Repeat with --offset set to next_offset until next_offset is omitted.\n\nArrow output:\n  --table-format arrow writes the page as an Arrow IPC (Feather v2) file to --output and prints a summary with the column types.\n  Columns holding any number are float64 (other cells in them become null), all others utf8, as inferred by table-profile.\n\nNamed queries:\n  --query loads `queries.<name>` from --queries-file, else .asp/queries.yaml (or .yml/.json) in the workspace.\n  A stored query may set sheet_name, table_name, region_id, range, header_row, header_rows, orientation, columns, filters, sort, sample_mode, limit, format, decimals, and rounding.\n  Flags passed on the command line override the stored values; --table-name/--region-id/--range replace the stored target."
+    )]
+    ReadTable {
+        #[arg(value_name = "FILE", help = "Path to the workbook")]
+        file: PathBuf,
+        #[arg(long, value_name = "SHEET", help = "Restrict read to a specific sheet")]
+        sheet: Option<String>,
+        #[arg(long, value_name = "RANGE", help = "Optional A1 range override")]
+        range: Option<String>,
+        #[arg(long, value_name = "NAME", help = "Read from a named Excel table")]
+        table_name: Option<String>,
+        #[arg(
+            long,
+            value_enum,
+            value_name = "SCOPE",
+            requires = "table_name",
+            help = "Which definition of --table-name to read when it exists at both workbook and sheet scope (default: the --sheet one, else workbook)"
+        )]
+        scope: Option<NameScopeArg>,
+        #[arg(long, value_name = "ID", help = "Read from a detected region id")]
+        region_id: Option<u32>,
+        #[arg(
+            long = "header-rows",
+            value_name = "N",
+            help = "Read the first N rows as headers, flattened into names like \"Q1 / Actual\" (default: 1)"
+        )]
+        header_rows: Option<u32>,
+        #[arg(
+            long = "no-header",
+            conflicts_with = "header_rows",
+            help = "Read every row as data and name columns by letter"
+        )]
+        no_header: bool,
+        #[arg(
+            long,
+            value_enum,
+            value_name = "ORIENTATION",
+            help = "rows (default), or transposed: headers down the first column, one record per column"
+        )]
+        orientation: Option<TableOrientationArg>,
+        #[arg(
+            long,
+            value_name = "LIMIT",
+            help = "Maximum rows to return (must be at least 1)"
+        )]
+        limit: Option<u32>,
+        #[arg(long, value_name = "OFFSET", help = "Row offset for pagination")]
+        offset: Option<u32>,
+        #[arg(
+            long = "sample-mode",
             value_enum,
             value_name = "MODE",
             help = "Sampling mode: first, last, or distributed"
@@ -929,6 +1546,48 @@ For broader discovery, use sheet-page, range-values, or layout-page."
             help = "Output format for this command"
         )]
         table_format: Option<TableReadFormat>,
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "File to write --table-format arrow output to (required for arrow)"
+        )]
+        output: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "NAME",
+            help = "Apply a named query from the workspace query definitions"
+        )]
+        query: Option<String>,
+        #[arg(
+            long = "queries-file",
+            value_name = "PATH",
+            requires = "query",
+            help = "YAML/JSON file of named queries (default: <workspace>/.asp/queries.yaml)"
+        )]
+        queries_file: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "N",
+            help = "Round numeric values to N decimal places"
+        )]
+        decimals: Option<u32>,
+        #[arg(
+            long,
+            value_enum,
+            value_name = "MODE",
+            requires = "decimals",
+            help = "Tie-breaking for --decimals: half-up (default, as Excel ROUND) or half-even (banker's)"
+        )]
+        rounding: Option<RoundingModeArg>,
+        #[command(flatten)]
+        csv: CsvDialectArgs,
+        #[arg(
+            long = "date-handling",
+            value_enum,
+            value_name = "MODE",
+            help = "Date-formatted cells: iso (default, ISO-8601 text), raw (serial number), or both (ISO text plus serials in date_serials)"
+        )]
+        date_handling: Option<DateHandlingArg>,
         #[arg(
             long,
             value_name = "ID",
@@ -942,9 +1601,50 @@ For broader discovery, use sheet-page, range-values, or layout-page."
         )]
         session_workspace: Option<PathBuf>,
     },
+    #[command(
+        about = "Estimate rows, cells, and payload size of a read-table call before running it",
+        after_long_help = "Examples:\n  agent-spreadsheet estimate data.xlsx --sheet Sheet1 --range A1:F5000\n  agent-spreadsheet estimate data.xlsx --table-name SalesTable --filters-json '[{\"column\":\"Region\",\"op\":\"eq\",\"value\":\"West\"}]'\n  agent-spreadsheet estimate data.xlsx --sheet Sheet1 --limit 500 --table-format csv\n\nMachine contract:\n  - row_count excludes the header row and reflects --filters-json/--filters-file.\n  - estimated_payload_bytes is extrapolated from a distributed sample of up to 200 rows.\n  - would_truncate is true when the requested --limit exceeds max_rows_per_page; truncated_by names the cap(s) responsible."
+    )]
+    Estimate {
+        #[arg(value_name = "FILE", help = "Path to the workbook")]
+        file: PathBuf,
+        #[arg(long, value_name = "SHEET", help = "Restrict read to a specific sheet")]
+        sheet: Option<String>,
+        #[arg(long, value_name = "RANGE", help = "Optional A1 range override")]
+        range: Option<String>,
+        #[arg(long, value_name = "NAME", help = "Estimate a named Excel table")]
+        table_name: Option<String>,
+        #[arg(long, value_name = "ID", help = "Estimate a detected region id")]
+        region_id: Option<u32>,
+        #[arg(
+            long,
+            value_name = "LIMIT",
+            help = "Rows the read would request (default 100, must be at least 1)"
+        )]
+        limit: Option<u32>,
+        #[arg(
+            long = "filters-json",
+            value_name = "JSON",
+            help = "Inline JSON array of filters (mutually exclusive with --filters-file)"
+        )]
+        filters_json: Option<String>,
+        #[arg(
+            long = "filters-file",
+            value_name = "PATH",
+            help = "Path to JSON array of filters (mutually exclusive with --filters-json)"
+        )]
+        filters_file: Option<PathBuf>,
+        #[arg(
+            long = "table-format",
+            value_enum,
+            value_name = "FORMAT",
+            help = "Output format the read would use"
+        )]
+        table_format: Option<TableReadFormat>,
+    },
     #[command(
         about = "Find cells matching a text query by value or label",
-        after_long_help = "Examples:\n  agent-spreadsheet find-value data.xlsx Revenue --mode value\n  agent-spreadsheet find-value data.xlsx \"Net Income\" --sheet \"Q1 Actuals\" --mode label --label-direction below\n\nLabel mode behavior:\n  - QUERY is matched against label cells.\n  - Result value is taken from an adjacent cell, not from the label itself.\n  - --label-direction any (default) checks right first, then below."
+        after_long_help = "Examples:\n  agent-spreadsheet find-value data.xlsx Revenue --mode value\n  agent-spreadsheet find-value data.xlsx \"Net Income\" --sheet \"Q1 Actuals\" --mode label --label-direction below\n  agent-spreadsheet find-value ledger.xlsx \"^ACC-\\d{6}$\" --regex --all-sheets\n  agent-spreadsheet find-value ledger.xlsx \"^ACC-\\d{6}$\" --regex --limit 100 --offset 100\n  agent-spreadsheet find-value model.xlsx 1,000,000 --value-type number --op gt --sheet Assumptions\n  agent-spreadsheet find-value model.xlsx 2026-01-01 --value-type date --op between --upper 2026-03-31\n\nLabel mode behavior:\n  - QUERY is matched against label cells.\n  - Result value is taken from an adjacent cell, not from the label itself.\n  - --label-direction any (default) checks right first, then below.\n\nRegex matching:\n  - --regex treats QUERY as a regular expression matched anywhere in the cell's text; anchor with ^ and $ to match whole cells.\n  - Matching is case-insensitive; start the pattern with (?-i) for case-sensitive matching.\n\nPagination and grouping:\n  - --limit (default 50) and --offset page through matches; next_offset is set while more remain.\n  - --all-sheets returns one entry per sheet with matches (match_count and first_addresses) instead of individual cells; match_count at the top level totals every sheet, and --limit/--offset page the sheets.\n\nNumber and date comparisons:\n  - --value-type number|date with --op gt|lt|between compares cell values against QUERY instead of matching text; between also needs --upper and includes both bounds.\n  - Dates are ISO (2026-01-31 or 2026-01-31T09:00:00) and match date-formatted cells only; number comparisons skip date-formatted cells."
     )]
     FindValue {
         #[arg(value_name = "FILE", help = "Path to the workbook")]
@@ -967,6 +1667,46 @@ For broader discovery, use sheet-page, range-values, or layout-page."
             help = "For --mode label, read the value from right, below, or any (default: any)"
         )]
         label_direction: Option<LabelDirectionArg>,
+        #[arg(long, help = "Treat QUERY as a regular expression")]
+        regex: bool,
+        #[arg(
+            long = "value-type",
+            value_enum,
+            value_name = "TYPE",
+            requires = "op",
+            conflicts_with = "regex",
+            help = "Compare number or date cells against QUERY instead of matching text"
+        )]
+        value_type: Option<FindValueTypeArg>,
+        #[arg(
+            long,
+            value_enum,
+            value_name = "OP",
+            requires = "value_type",
+            help = "Comparison for --value-type: gt, lt, or between (inclusive, needs --upper)"
+        )]
+        op: Option<FindCompareOpArg>,
+        #[arg(
+            long,
+            value_name = "VALUE",
+            requires = "op",
+            help = "Upper bound for --op between"
+        )]
+        upper: Option<String>,
+        #[arg(
+            long = "all-sheets",
+            conflicts_with = "sheet",
+            help = "Summarize matches per sheet instead of listing cells"
+        )]
+        all_sheets: bool,
+        #[arg(
+            long,
+            value_name = "N",
+            help = "Maximum matches (or sheets with --all-sheets) to return (default: 50)"
+        )]
+        limit: Option<u32>,
+        #[arg(long, value_name = "N", help = "Match offset for continuation")]
+        offset: Option<u32>,
         #[arg(
             long,
             value_name = "ID",
@@ -982,7 +1722,7 @@ For broader discovery, use sheet-page, range-values, or layout-page."
     },
     #[command(
         about = "List workbook named ranges and table/formula named items",
-        after_long_help = "Examples:\n  agent-spreadsheet named-ranges data.xlsx\n  agent-spreadsheet named-ranges data.xlsx --sheet \"Q1 Actuals\" --name-prefix Sales"
+        after_long_help = "Examples:\n  agent-spreadsheet named-ranges data.xlsx\n  agent-spreadsheet named-ranges data.xlsx --sheet \"Q1 Actuals\" --name-prefix Sales\n  agent-spreadsheet named-ranges model.xlsx --name-prefix Assumption --evaluate\n\nKinds:\n  named_range -> cell references (including unions and whole rows/columns)\n  constant    -> literals and array constants, e.g. =0.075\n  formula     -> anything else, e.g. =Sheet1!$B$2*(1+Growth)\n  table       -> Excel tables\n\n--evaluate adds the current `value` of constant and formula names, computed with the recalc backend on a temp copy (the file is not modified).\n\nA name defined at both workbook and sheet scope is listed once per scope, each with scope_kind/scope_sheet_name and scope_conflict: true. Pass --scope to read-table, update-name, or delete-name to pick one; update-name and delete-name refuse to guess."
     )]
     NamedRanges {
         #[arg(value_name = "FILE", help = "Path to the workbook")]
@@ -995,6 +1735,18 @@ For broader discovery, use sheet-page, range-values, or layout-page."
             help = "Optional case-insensitive prefix filter for item names"
         )]
         name_prefix: Option<String>,
+        #[arg(
+            long,
+            value_enum,
+            value_name = "SCOPE",
+            help = "Only names of this scope: workbook or sheet"
+        )]
+        scope: Option<NameScopeArg>,
+        #[arg(
+            long,
+            help = "Compute the current value of constant and formula names with the recalc backend"
+        )]
+        evaluate: bool,
         #[arg(
             long,
             value_name = "ID",
@@ -1008,6 +1760,73 @@ For broader discovery, use sheet-page, range-values, or layout-page."
         )]
         session_workspace: Option<PathBuf>,
     },
+    #[command(
+        about = "List tool metadata stored in the workbook",
+        after_long_help = "Examples:\n  asp metadata model.xlsx\n  asp metadata model.xlsx --namespace queries\n  asp metadata model.xlsx --namespace names --key revenue\n\nEntries live in the hidden _metadata sheet, one row per namespace/key with a JSON value.\nWrite them with `asp set-metadata`; remove them with `asp clear-metadata`."
+    )]
+    Metadata {
+        #[arg(value_name = "FILE", help = "Path to the workbook")]
+        file: PathBuf,
+        #[arg(
+            long,
+            value_name = "NAMESPACE",
+            help = "Only entries in this namespace"
+        )]
+        namespace: Option<String>,
+        #[arg(long, value_name = "KEY", help = "Only entries with this key")]
+        key: Option<String>,
+    },
+    #[command(
+        about = "List machine-readable annotations attached to cells and ranges",
+        after_long_help = "Examples:\n  asp annotations model.xlsx\n  asp annotations model.xlsx --sheet Invoices\n  asp annotations model.xlsx --sheet Invoices --range B2:B40\n\nAnnotations are JSON values written by `asp annotate`. They live in the hidden _metadata sheet (namespace annotations), not in visible comments.\n--range returns every annotation whose range overlaps it."
+    )]
+    Annotations {
+        #[arg(value_name = "FILE", help = "Path to the workbook")]
+        file: PathBuf,
+        #[arg(long, value_name = "SHEET", help = "Only annotations on this sheet")]
+        sheet: Option<String>,
+        #[arg(
+            long,
+            value_name = "A1_RANGE",
+            requires = "sheet",
+            help = "Only annotations overlapping this range (requires --sheet)"
+        )]
+        range: Option<String>,
+    },
+    #[command(
+        about = "List links to other workbooks and whether their cached values are stale",
+        after_long_help = "Examples:\n  asp list-external-links model.xlsx\n  asp read external-links model.xlsx\n\nEach link reports:\n  - index: the [n] formulas use to refer to it, e.g. [1]Rates!B2\n  - target: the linked workbook as recorded; resolved_path: the local file it resolves to (relative to the workbook, falling back to the same file name next to it)\n  - used_by / defined_names: formula cells (first 50) and names that reference it\n  - status: current, stale (cached values differ from the linked workbook; see stale_cells), missing, unresolvable (URL or no target), or unchecked\n\nBreak or re-point links with structure-batch ops:\n  {\"kind\":\"break_external_link\",\"link\":1}\n  {\"kind\":\"repoint_external_link\",\"link\":1,\"target\":\"rates-2025.xlsx\"}"
+    )]
+    ListExternalLinks {
+        #[arg(value_name = "FILE", help = "Path to the workbook")]
+        file: PathBuf,
+    },
+    #[command(
+        about = "Extract user-visible text into a translation table",
+        after_long_help = r#"Examples:
+  asp extract-strings report.xlsx > strings.json
+  asp extract-strings report.xlsx --format csv --output strings.csv
+  asp read strings report.xlsx --format csv --output -
+
+Strings and their ids:
+  Sheet1!B2         text cell (formulas, numbers and booleans are skipped)
+  sheet:Sheet1      sheet name
+  Sheet1#header     page header or footer: header, footer, even_header, even_footer,
+                    first_header, first_footer (Excel codes such as &C and &P included)
+  Sheet1#chart1     title of the first chart on the sheet
+
+JSON output is {"strings":[{"id","kind","sheet","source"}]}; CSV output has the columns
+id,kind,sheet,source,target with target left empty. Fill in target and pass the table
+to apply-strings."#
+    )]
+    ExtractStrings {
+        #[arg(value_name = "FILE", help = "Path to the workbook")]
+        file: PathBuf,
+        #[arg(long, help = "Output format: json or csv", default_value = "json")]
+        format: String,
+        #[arg(long, help = "Output path or '-' for stdout")]
+        output: Option<String>,
+    },
     #[command(
         about = "Define a new named range in a workbook",
         after_long_help = "Examples:\n  agent-spreadsheet define-name data.xlsx MyRange 'Sheet1!$A$1:$B$10'\n  agent-spreadsheet define-name data.xlsx SheetLocal 'Sheet1!$A$1' --scope sheet --scope-sheet-name Sheet1 --in-place"
@@ -1097,9 +1916,140 @@ For broader discovery, use sheet-page, range-values, or layout-page."
         #[arg(long, help = "Allow overwriting --output when it already exists")]
         force: bool,
     },
+    #[command(
+        about = "Store a tool metadata entry in the workbook",
+        after_long_help = "Examples:\n  asp set-metadata model.xlsx names revenue '{\"range\":\"Model!B4:B40\",\"unit\":\"USD\"}' --in-place\n  asp set-metadata model.xlsx audit last_review 'signed off by finance' --output reviewed.xlsx\n\nBehavior:\n  - VALUE is parsed as JSON; text that is not valid JSON is stored as a string\n  - replaces any entry with the same NAMESPACE and KEY and reports previous_value\n  - entries live in a hidden _metadata sheet, which Excel keeps on save"
+    )]
+    SetMetadata {
+        #[arg(value_name = "FILE", help = "Path to the workbook")]
+        file: PathBuf,
+        #[arg(
+            value_name = "NAMESPACE",
+            help = "Entry group, e.g. names, queries, snapshots, audit"
+        )]
+        namespace: String,
+        #[arg(value_name = "KEY", help = "Entry key within the namespace")]
+        key: String,
+        #[arg(
+            value_name = "VALUE",
+            help = "JSON value (bare text is stored as a string)"
+        )]
+        value: String,
+        #[arg(long, help = "Validate without mutating files")]
+        dry_run: bool,
+        #[arg(long, help = "Apply by atomically replacing the source file")]
+        in_place: bool,
+        #[arg(long, value_name = "PATH", help = "Apply to this output path")]
+        output: Option<PathBuf>,
+        #[arg(long, help = "Allow overwriting --output when it already exists")]
+        force: bool,
+    },
+    #[command(
+        about = "Remove tool metadata entries from the workbook",
+        after_long_help = "Examples:\n  asp clear-metadata model.xlsx --namespace snapshots --in-place\n  asp clear-metadata model.xlsx --namespace names --key revenue --in-place\n  asp clear-metadata model.xlsx --dry-run\n\nBehavior:\n  - without --namespace every entry is removed; --key narrows a namespace to one entry\n  - the hidden _metadata sheet is deleted once it holds no entries\n  - when nothing matches, the file is left untouched and removed_count is 0"
+    )]
+    ClearMetadata {
+        #[arg(value_name = "FILE", help = "Path to the workbook")]
+        file: PathBuf,
+        #[arg(
+            long,
+            value_name = "NAMESPACE",
+            help = "Only remove entries in this namespace"
+        )]
+        namespace: Option<String>,
+        #[arg(
+            long,
+            value_name = "KEY",
+            requires = "namespace",
+            help = "Only remove this key (requires --namespace)"
+        )]
+        key: Option<String>,
+        #[arg(long, help = "Validate without mutating files")]
+        dry_run: bool,
+        #[arg(long, help = "Apply by atomically replacing the source file")]
+        in_place: bool,
+        #[arg(long, value_name = "PATH", help = "Apply to this output path")]
+        output: Option<PathBuf>,
+        #[arg(long, help = "Allow overwriting --output when it already exists")]
+        force: bool,
+    },
+    #[command(
+        about = "Attach a machine-readable annotation to a cell or range",
+        after_long_help = "Examples:\n  asp annotate model.xlsx Invoices B12 '{\"verified_against\":\"invoice #123\"}' --in-place\n  asp annotate model.xlsx Invoices B2:B40 'pending review' --output reviewed.xlsx\n  asp annotate model.xlsx Invoices B12 --remove --in-place\n\nBehavior:\n  - VALUE is parsed as JSON; text that is not valid JSON is stored as a string\n  - one annotation per sheet and range; annotating the same range again replaces it and reports previous_value\n  - stored in the hidden _metadata sheet (namespace annotations), so nothing visible changes\n  - annotations do not move with the cells when rows or columns are inserted or deleted\n  - read them back with `asp annotations`"
+    )]
+    Annotate {
+        #[arg(value_name = "FILE", help = "Path to the workbook")]
+        file: PathBuf,
+        #[arg(value_name = "SHEET", help = "Sheet holding the annotated cells")]
+        sheet: String,
+        #[arg(
+            value_name = "A1_RANGE",
+            help = "Cell or range to annotate, e.g. B12 or B2:B40"
+        )]
+        range: String,
+        #[arg(
+            value_name = "VALUE",
+            required_unless_present = "remove",
+            conflicts_with = "remove",
+            help = "JSON value (bare text is stored as a string)"
+        )]
+        value: Option<String>,
+        #[arg(
+            long,
+            help = "Remove the annotation on the range instead of setting one"
+        )]
+        remove: bool,
+        #[arg(long, help = "Validate without mutating files")]
+        dry_run: bool,
+        #[arg(long, help = "Apply by atomically replacing the source file")]
+        in_place: bool,
+        #[arg(long, value_name = "PATH", help = "Apply to this output path")]
+        output: Option<PathBuf>,
+        #[arg(long, help = "Allow overwriting --output when it already exists")]
+        force: bool,
+    },
+    #[command(
+        about = "Write translated text from a translation table back into a workbook",
+        after_long_help = r#"Examples:
+  asp apply-strings report.xlsx --strings @strings-de.csv --output report-de.xlsx
+  asp apply-strings report.xlsx --strings @strings-de.json --dry-run
+  asp write strings report.xlsx --strings @strings-de.json --in-place
+
+Strings (`--strings @file`):
+  the extract-strings table with a target per entry, as JSON ({"strings":[...]} or a bare
+  array of {"id","source","target"}) or CSV with a header row naming id and target columns
+  (source optional, other columns ignored).
+
+Behavior:
+  - entries with an empty target are skipped as untranslated
+  - when source is given and no longer matches the workbook, the entry is skipped with
+    WARN_STRING_STALE; extract the strings again
+  - cells keep their style and number format; formulas are never touched
+  - sheet renames update formulas, defined names and chart series that refer to the sheet
+  - chart title text goes into the existing runs, so title formatting is kept
+  - an invalid or duplicate translated sheet name fails without writing"#
+    )]
+    ApplyStrings {
+        #[arg(value_name = "FILE", help = "Path to the workbook")]
+        file: PathBuf,
+        #[arg(
+            long,
+            value_name = "@PATH",
+            help = "Translation table (JSON or CSV) as @<path>"
+        )]
+        strings: String,
+        #[arg(long, help = "Validate without mutating files")]
+        dry_run: bool,
+        #[arg(long, help = "Apply by atomically replacing the source file")]
+        in_place: bool,
+        #[arg(long, value_name = "PATH", help = "Apply to this output path")]
+        output: Option<PathBuf>,
+        #[arg(long, help = "Allow overwriting --output when it already exists")]
+        force: bool,
+    },
     #[command(
         about = "Find formulas containing a text query with pagination",
-        after_long_help = "Examples:\n  agent-spreadsheet find-formula data.xlsx SUM(\n  agent-spreadsheet find-formula data.xlsx VLOOKUP --sheet \"Q1 Actuals\" --limit 25 --offset 50\n\nRelated:\n  Use inspect-cells for per-cell formula/value/cached/style snapshots in a target range."
+        after_long_help = "Examples:\n  agent-spreadsheet find-formula data.xlsx SUM(\n  agent-spreadsheet find-formula data.xlsx VLOOKUP --sheet \"Q1 Actuals\" --limit 25 --offset 50\n  agent-spreadsheet find-formula data.xlsx \"RC[-1]\" --reference-style r1c1\n\nWith --reference-style r1c1, formulas are shown in R1C1 notation relative to their own cell and the query matches that form.\n\nRelated:\n  Use inspect-cells for per-cell formula/value/cached/style snapshots in a target range."
     )]
     FindFormula {
         #[arg(value_name = "FILE", help = "Path to the workbook")]
@@ -1116,6 +2066,14 @@ For broader discovery, use sheet-page, range-values, or layout-page."
         limit: Option<u32>,
         #[arg(long, value_name = "N", help = "Match offset for continuation")]
         offset: Option<u32>,
+        #[arg(
+            long = "reference-style",
+            value_enum,
+            value_name = "STYLE",
+            default_value = "a1",
+            help = "Display formulas in a1 (default) or r1c1 notation"
+        )]
+        reference_style: ReferenceStyle,
     },
     #[command(
         about = "Scan workbook formulas for volatile functions",
@@ -1142,19 +2100,92 @@ For broader discovery, use sheet-page, range-values, or layout-page."
         )]
         formula_parse_policy: Option<FormulaParsePolicy>,
     },
+    #[command(
+        about = "Report cells whose cached values are errors, grouped by error type",
+        after_long_help = "Examples:\n  agent-spreadsheet scan-errors model.xlsx\n  agent-spreadsheet scan-errors model.xlsx --sheet Calc --limit 20 --offset 20\n  agent-spreadsheet scan-errors model.xlsx --summary-only\n\nReads cached values as last saved or recalculated; run recalculate first to scan fresh results.\nItems are ordered by error type, then sheet, row, and column; groups count every error type across the scanned sheets regardless of paging."
+    )]
+    ScanErrors {
+        #[arg(value_name = "FILE", help = "Path to the workbook")]
+        file: PathBuf,
+        #[arg(long, value_name = "SHEET", help = "Optional sheet name filter")]
+        sheet: Option<String>,
+        #[arg(
+            long = "summary-only",
+            help = "Return per-error-type counts without cell entries"
+        )]
+        summary_only: bool,
+        #[arg(
+            long,
+            value_name = "N",
+            help = "Maximum entries to return (must be at least 1)"
+        )]
+        limit: Option<u32>,
+        #[arg(long, value_name = "N", help = "Entry offset for continuation")]
+        offset: Option<u32>,
+    },
+    #[command(
+        about = "Lint formulas for hard-coded constants, full-column aggregations, deep IFs, and inconsistencies",
+        after_long_help = "Examples:\n  agent-spreadsheet lint-formulas model.xlsx\n  agent-spreadsheet lint-formulas model.xlsx --sheet Calc --rule nested_if --max-if-depth 2\n  agent-spreadsheet lint-formulas model.xlsx --summary-only\n  agent-spreadsheet lint-formulas model.xlsx --limit 50 --offset 50\n\nRules: hardcoded_constant (numeric literals other than 0 and 1 in arithmetic), full_column_reference (A:A inside SUM, COUNTIF, AVERAGE, ...), nested_if (IF nesting deeper than --max-if-depth, default 3), inconsistent_formula (a cell whose formula differs from matching neighbours above and below or left and right).\nFindings are ordered by rule, then sheet, row, and column; rule counts cover the scanned sheets regardless of paging."
+    )]
+    LintFormulas {
+        #[arg(value_name = "FILE", help = "Path to the workbook")]
+        file: PathBuf,
+        #[arg(long, value_name = "SHEET", help = "Optional sheet name filter")]
+        sheet: Option<String>,
+        #[arg(
+            long = "rule",
+            value_enum,
+            value_name = "RULE",
+            help = "Only run this rule (repeatable; default: all rules)"
+        )]
+        rules: Vec<FormulaLintRule>,
+        #[arg(
+            long = "max-if-depth",
+            value_name = "N",
+            help = "Deepest IF nesting allowed before nested_if fires (default: 3)"
+        )]
+        max_if_depth: Option<u32>,
+        #[arg(
+            long = "summary-only",
+            help = "Return per-rule counts without findings"
+        )]
+        summary_only: bool,
+        #[arg(
+            long,
+            value_name = "N",
+            help = "Maximum findings to return (must be at least 1)"
+        )]
+        limit: Option<u32>,
+        #[arg(long, value_name = "N", help = "Finding offset for continuation")]
+        offset: Option<u32>,
+    },
     #[command(
         about = "Compute per-sheet statistics for density and column types",
-        after_long_help = "Examples:\n  agent-spreadsheet sheet-statistics data.xlsx Sheet1\n  agent-spreadsheet sheet-statistics data.xlsx \"Q1 Actuals\""
+        after_long_help = "Examples:\n  agent-spreadsheet sheet-statistics data.xlsx Sheet1\n  agent-spreadsheet sheet-statistics data.xlsx \"Q1 Actuals\"\n  agent-spreadsheet sheet-statistics data.xlsx Ledger --decimals 2\n\nWith --decimals, min/max/mean are computed from raw values and then rounded."
     )]
     SheetStatistics {
         #[arg(value_name = "FILE", help = "Path to the workbook")]
         file: PathBuf,
         #[arg(value_name = "SHEET", help = "Sheet to summarize")]
         sheet: String,
+        #[arg(
+            long,
+            value_name = "N",
+            help = "Round numeric values to N decimal places"
+        )]
+        decimals: Option<u32>,
+        #[arg(
+            long,
+            value_enum,
+            value_name = "MODE",
+            requires = "decimals",
+            help = "Tie-breaking for --decimals: half-up (default, as Excel ROUND) or half-even (banker's)"
+        )]
+        rounding: Option<RoundingModeArg>,
     },
     #[command(
         about = "Summarize formulas on a sheet by complexity or frequency",
-        after_long_help = "Examples:\n  agent-spreadsheet formula-map data.xlsx Sheet1\n  agent-spreadsheet formula-map data.xlsx \"Q1 Actuals\" --sort-by count --limit 25"
+        after_long_help = "Examples:\n  agent-spreadsheet formula-map data.xlsx Sheet1\n  agent-spreadsheet formula-map data.xlsx \"Q1 Actuals\" --sort-by count --limit 25\n  agent-spreadsheet formula-map data.xlsx Model --inconsistencies\n  agent-spreadsheet formula-map data.xlsx Model --reference-style r1c1\n\nWith --reference-style r1c1, a formula filled down a column or across a row forms one group shown in R1C1 notation.\nWith --inconsistencies, formulas are grouped by relative R1C1 pattern per filled column or row region, and cells breaking their region's pattern (a different formula, or a typed value pasted over the fill) are listed with the expected pattern and the formula the cell would hold."
     )]
     FormulaMap {
         #[arg(value_name = "FILE", help = "Path to the workbook")]
@@ -1177,10 +2208,23 @@ For broader discovery, use sheet-page, range-values, or layout-page."
             help = "Formula parse policy: fail, warn (default), or off"
         )]
         formula_parse_policy: Option<FormulaParsePolicy>,
+        #[arg(
+            long,
+            help = "Report cells breaking the formula pattern of their column or row region"
+        )]
+        inconsistencies: bool,
+        #[arg(
+            long = "reference-style",
+            value_enum,
+            value_name = "STYLE",
+            default_value = "a1",
+            help = "Display formulas in a1 (default) or r1c1 notation"
+        )]
+        reference_style: ReferenceStyle,
     },
     #[command(
         about = "Trace formula precedents or dependents from one origin cell",
-        after_long_help = "Examples:\n  agent-spreadsheet formula-trace data.xlsx Sheet1 C2 precedents --depth 2\n  agent-spreadsheet formula-trace data.xlsx Sheet1 C2 dependents --page-size 25\n  agent-spreadsheet formula-trace data.xlsx Sheet1 C2 precedents --cursor-depth 1 --cursor-offset 25\n\nContinuation:\n  Reuse next_cursor.depth/next_cursor.offset as --cursor-depth/--cursor-offset to continue paged traces.\n\nRelated:\n  Use inspect-cells for a local per-cell triage view that includes formula/value/cached/style metadata."
+        after_long_help = "Examples:\n  agent-spreadsheet formula-trace data.xlsx Sheet1 C2 precedents --depth 2\n  agent-spreadsheet formula-trace data.xlsx Sheet1 C2 dependents --page-size 25\n  agent-spreadsheet formula-trace data.xlsx Sheet1 C2 precedents --cursor-depth 1 --cursor-offset 25\n  agent-spreadsheet formula-trace data.xlsx Sheet1 C2 precedents --reference-style r1c1\n\nContinuation:\n  Reuse next_cursor.depth/next_cursor.offset as --cursor-depth/--cursor-offset to continue paged traces.\n\nRelated:\n  Use inspect-cells for a local per-cell triage view that includes formula/value/cached/style metadata."
     )]
     FormulaTrace {
         #[arg(value_name = "FILE", help = "Path to the workbook")]
@@ -1205,43 +2249,181 @@ For broader discovery, use sheet-page, range-values, or layout-page."
             value_name = "N",
             help = "Page size for trace edges (must be between 5 and 200)"
         )]
-        page_size: Option<usize>,
+        page_size: Option<usize>,
+        #[arg(
+            long = "cursor-depth",
+            value_name = "DEPTH",
+            help = "Continuation cursor depth (must be paired with --cursor-offset)"
+        )]
+        cursor_depth: Option<u32>,
+        #[arg(
+            long = "cursor-offset",
+            value_name = "OFFSET",
+            help = "Continuation cursor offset (must be paired with --cursor-depth)"
+        )]
+        cursor_offset: Option<usize>,
+        #[arg(
+            long = "formula-parse-policy",
+            value_enum,
+            value_name = "POLICY",
+            help = "Formula parse policy: fail, warn (default), or off"
+        )]
+        formula_parse_policy: Option<FormulaParsePolicy>,
+        #[arg(
+            long = "reference-style",
+            value_enum,
+            value_name = "STYLE",
+            default_value = "a1",
+            help = "Display formulas in a1 (default) or r1c1 notation"
+        )]
+        reference_style: ReferenceStyle,
+        #[arg(
+            long,
+            value_name = "ID",
+            help = "Read from a session's materialized state instead of the file"
+        )]
+        session: Option<String>,
+        #[arg(
+            long = "session-workspace",
+            value_name = "PATH",
+            help = "Workspace root for session resolution"
+        )]
+        session_workspace: Option<PathBuf>,
+    },
+    #[command(about = "Describe workbook-level metadata and sheet counts")]
+    Describe {
+        #[arg(value_name = "FILE", help = "Path to the workbook")]
+        file: PathBuf,
+        #[arg(
+            long,
+            value_name = "ID",
+            help = "Read from a session's materialized state instead of the file"
+        )]
+        session: Option<String>,
+        #[arg(
+            long = "session-workspace",
+            value_name = "PATH",
+            help = "Workspace root for session resolution"
+        )]
+        session_workspace: Option<PathBuf>,
+    },
+    #[command(
+        about = "Profile table headers, types, and column distributions",
+        after_long_help = "Examples:\n  agent-spreadsheet table-profile data.xlsx\n  agent-spreadsheet table-profile data.xlsx --sheet \"Q1 Actuals\"\n  agent-spreadsheet table-profile data.xlsx --sheet Ledger --decimals 2 --rounding half-even\n  agent-spreadsheet table-profile data.xlsx --sheet Ledger --top-n 10\n\nMachine contract:\n  - column_types[].stats covers every data row: count, nulls, null_pct, distinct, top_values (value + count), and min/max/mean/stddev over numeric cells.\n  - stats.outliers, when present, lists numbers outside Tukey's fences (1.5 IQR beyond the quartiles) with their sheet rows; it needs at least 4 numbers.\n  - The other column_types fields and samples cover the sample rows only."
+    )]
+    TableProfile {
+        #[arg(value_name = "FILE", help = "Path to the workbook")]
+        file: PathBuf,
+        #[arg(long, value_name = "SHEET", help = "Optional sheet to profile")]
+        sheet: Option<String>,
+        #[arg(
+            long,
+            value_name = "N",
+            help = "Round numeric values to N decimal places"
+        )]
+        decimals: Option<u32>,
+        #[arg(
+            long,
+            value_enum,
+            value_name = "MODE",
+            requires = "decimals",
+            help = "Tie-breaking for --decimals: half-up (default, as Excel ROUND) or half-even (banker's)"
+        )]
+        rounding: Option<RoundingModeArg>,
+        #[arg(
+            long = "top-n",
+            value_name = "N",
+            help = "Most frequent values to list per column in stats.top_values (default: 5)"
+        )]
+        top_n: Option<u32>,
+        #[arg(
+            long = "emit-schema",
+            value_enum,
+            value_name = "FORMAT",
+            help = "Print a schema for the table instead of the profile: jsonschema (draft 2020-12) or arrow (Arrow JSON schema)"
+        )]
+        emit_schema: Option<SchemaFormatArg>,
+        #[arg(
+            long,
+            value_name = "ID",
+            help = "Read from a session's materialized state instead of the file"
+        )]
+        session: Option<String>,
+        #[arg(
+            long = "session-workspace",
+            value_name = "PATH",
+            help = "Workspace root for session resolution"
+        )]
+        session_workspace: Option<PathBuf>,
+    },
+    #[command(
+        about = "Group a table and compute sum/avg/count/min/max per group",
+        after_long_help = "Examples:\n  agent-spreadsheet query-table sales.xlsx --sheet Sales --group-by Region --agg sum:Amount --agg count\n  agent-spreadsheet query-table sales.xlsx --table-name SalesTable --group-by Region,Product --agg avg:Amount=avg_amount --agg max:Date\n  agent-spreadsheet query-table sales.xlsx --sheet Sales --agg sum:Amount --filters-json '[{\"column\":\"Status\",\"op\":\"eq\",\"value\":\"Closed\"}]'\n\nAggregations:\n  --agg OP[:COLUMN][=ALIAS], repeatable. OP is sum, avg, count, min, or max; COLUMN is a header name.\n  sum and avg use numeric cells only; min and max compare numbers, else dates. count:COLUMN counts non-blank cells, bare count counts rows.\n  Output columns are named ALIAS, else OP_COLUMN (e.g. sum_Amount), else count.\n\nMachine contract:\n  - columns lists the --group-by columns, then one column per --agg; rows holds one array per group.\n  - Groups are ordered by their group-by values; without --group-by the whole table is one group.\n  - Filters apply before grouping; matched_rows counts the rows that passed.\n  - Page groups with --limit/--offset and next_offset, as in read-table."
+    )]
+    QueryTable {
+        #[arg(value_name = "FILE", help = "Path to the workbook")]
+        file: PathBuf,
+        #[arg(
+            long,
+            value_name = "SHEET",
+            help = "Restrict the query to a specific sheet"
+        )]
+        sheet: Option<String>,
+        #[arg(long, value_name = "RANGE", help = "Optional A1 range override")]
+        range: Option<String>,
+        #[arg(long, value_name = "NAME", help = "Query a named Excel table")]
+        table_name: Option<String>,
+        #[arg(long, value_name = "ID", help = "Query a detected region id")]
+        region_id: Option<u32>,
+        #[arg(
+            long = "header-rows",
+            value_name = "N",
+            help = "Read the first N rows as headers, flattened into names like \"Q1 / Actual\" (default: 1)"
+        )]
+        header_rows: Option<u32>,
+        #[arg(
+            long = "group-by",
+            value_name = "HEADERS",
+            value_delimiter = ',',
+            help = "Header names to group by"
+        )]
+        group_by: Option<Vec<String>>,
         #[arg(
-            long = "cursor-depth",
-            value_name = "DEPTH",
-            help = "Continuation cursor depth (must be paired with --cursor-offset)"
+            long = "agg",
+            value_name = "OP[:COLUMN][=ALIAS]",
+            help = "Aggregation to compute per group (repeatable), e.g. sum:Amount or count"
         )]
-        cursor_depth: Option<u32>,
+        aggregations: Vec<String>,
         #[arg(
-            long = "cursor-offset",
-            value_name = "OFFSET",
-            help = "Continuation cursor offset (must be paired with --cursor-depth)"
+            long = "filters-json",
+            value_name = "JSON",
+            help = "Inline JSON array of filters (mutually exclusive with --filters-file)"
         )]
-        cursor_offset: Option<usize>,
+        filters_json: Option<String>,
         #[arg(
-            long = "formula-parse-policy",
-            value_enum,
-            value_name = "POLICY",
-            help = "Formula parse policy: fail, warn (default), or off"
+            long = "filters-file",
+            value_name = "PATH",
+            help = "Path to JSON array of filters (mutually exclusive with --filters-json)"
         )]
-        formula_parse_policy: Option<FormulaParsePolicy>,
+        filters_file: Option<PathBuf>,
         #[arg(
             long,
-            value_name = "ID",
-            help = "Read from a session's materialized state instead of the file"
+            value_name = "LIMIT",
+            help = "Maximum groups to return (default 100, must be at least 1)"
         )]
-        session: Option<String>,
+        limit: Option<u32>,
+        #[arg(long, value_name = "OFFSET", help = "Groups to skip before returning")]
+        offset: Option<u32>,
+        #[arg(long, value_name = "N", help = "Round aggregates to N decimal places")]
+        decimals: Option<u32>,
         #[arg(
-            long = "session-workspace",
-            value_name = "PATH",
-            help = "Workspace root for session resolution"
+            long,
+            value_enum,
+            value_name = "MODE",
+            requires = "decimals",
+            help = "Tie-breaking for --decimals: half-up (default, as Excel ROUND) or half-even (banker's)"
         )]
-        session_workspace: Option<PathBuf>,
-    },
-    #[command(about = "Describe workbook-level metadata and sheet counts")]
-    Describe {
-        #[arg(value_name = "FILE", help = "Path to the workbook")]
-        file: PathBuf,
+        rounding: Option<RoundingModeArg>,
         #[arg(
             long,
             value_name = "ID",
@@ -1256,26 +2438,59 @@ For broader discovery, use sheet-page, range-values, or layout-page."
         session_workspace: Option<PathBuf>,
     },
     #[command(
-        about = "Profile table headers, types, and column distributions",
-        after_long_help = "Examples:\n  agent-spreadsheet table-profile data.xlsx\n  agent-spreadsheet table-profile data.xlsx --sheet \"Q1 Actuals\""
+        about = "Propose a column correspondence between two differently-labeled tables",
+        after_long_help = "Examples:\n  agent-spreadsheet map-columns vendor.xlsx ledger.xlsx --source-sheet Export --target-sheet Ledger\n  agent-spreadsheet map-columns vendor.xlsx ledger.xlsx --target-table LedgerTable --output mapping.json\n  agent-spreadsheet append-region ledger.xlsx --sheet Ledger --table-name LedgerTable --from-csv vendor.csv --header --column-map @mapping.json --dry-run\n\nScoring:\n  Every source/target column pair gets a header_score (word overlap, abbreviations such as Qty/Quantity, edit distance)\n  and a value_score (shared value types, numeric range overlap, shared text values or value shapes such as INV-0042).\n  score = 0.6 * header_score + 0.4 * value_score. Columns are paired one to one, best score first, and pairs below\n  --min-score (default 0.4) are left in unmapped_source / unmapped_target.\n\nTable selection per side follows read-table: --*-sheet, --*-range, --*-table, --*-region-id (default: first sheet, detected header).\nValues are compared over the first 2000 rows of each table.\n\nOutput:\n  The mapping (source, target, columns, unmapped_source, unmapped_target) is printed; --output also writes it as a\n  mapping file for append-region --column-map. Review and edit the columns list before relying on it."
     )]
-    TableProfile {
-        #[arg(value_name = "FILE", help = "Path to the workbook")]
-        file: PathBuf,
-        #[arg(long, value_name = "SHEET", help = "Optional sheet to profile")]
-        sheet: Option<String>,
+    MapColumns {
+        #[arg(value_name = "SOURCE", help = "Workbook with the incoming table")]
+        source: PathBuf,
+        #[arg(value_name = "TARGET", help = "Workbook with the table to map onto")]
+        target: PathBuf,
+        #[arg(long = "source-sheet", value_name = "SHEET", help = "Source sheet")]
+        source_sheet: Option<String>,
+        #[arg(long = "source-range", value_name = "RANGE", help = "Source A1 range")]
+        source_range: Option<String>,
+        #[arg(
+            long = "source-table",
+            value_name = "NAME",
+            help = "Source Excel table name"
+        )]
+        source_table: Option<String>,
         #[arg(
-            long,
+            long = "source-region-id",
             value_name = "ID",
-            help = "Read from a session's materialized state instead of the file"
+            help = "Source detected region id"
         )]
-        session: Option<String>,
+        source_region_id: Option<u32>,
+        #[arg(long = "target-sheet", value_name = "SHEET", help = "Target sheet")]
+        target_sheet: Option<String>,
+        #[arg(long = "target-range", value_name = "RANGE", help = "Target A1 range")]
+        target_range: Option<String>,
         #[arg(
-            long = "session-workspace",
+            long = "target-table",
+            value_name = "NAME",
+            help = "Target Excel table name"
+        )]
+        target_table: Option<String>,
+        #[arg(
+            long = "target-region-id",
+            value_name = "ID",
+            help = "Target detected region id"
+        )]
+        target_region_id: Option<u32>,
+        #[arg(
+            long = "min-score",
+            value_name = "SCORE",
+            default_value_t = 0.4,
+            help = "Lowest combined score (0-1) to pair two columns"
+        )]
+        min_score: f64,
+        #[arg(
+            long,
             value_name = "PATH",
-            help = "Workspace root for session resolution"
+            help = "Also write the mapping file to this path"
         )]
-        session_workspace: Option<PathBuf>,
+        output: Option<PathBuf>,
     },
     #[command(
         about = "Render a range with layout: column widths, borders, bold/italic, alignment",
@@ -1340,6 +2555,73 @@ For broader discovery, use sheet-page, range-values, or layout-page."
         )]
         session_workspace: Option<PathBuf>,
     },
+    #[command(
+        about = "Render a sheet range to PNG for visual verification",
+        after_long_help = "Examples:\n  agent-spreadsheet render data.xlsx Sheet1 --range A1:F30 --output shot.png\n  agent-spreadsheet render data.xlsx Sheet1 --range A1:F30 --output shot.png --dpi 150\n  agent-spreadsheet render data.xlsx Summary --output summary.png --scale 2 --force\n\nRequires LibreOffice (soffice) and a build with recalc-libreoffice; --dpi/--scale also need pdftoppm.\nThe PNG is cropped to the rendered content. Max range: 100 rows x 30 columns (default A1:M40); oversized ranges fail with suggested tiles.\n--dpi sets the rasterization resolution (36-600, default 96) and --scale multiplies it (0.25-4.0); dpi x scale may not exceed 600."
+    )]
+    Render {
+        #[arg(value_name = "FILE", help = "Path to the workbook")]
+        file: PathBuf,
+        #[arg(value_name = "SHEET", help = "Sheet to render")]
+        sheet: String,
+        #[arg(
+            long,
+            value_name = "RANGE",
+            help = "A1 range to render (default: A1:M40)"
+        )]
+        range: Option<String>,
+        #[arg(long, value_name = "PATH", help = "PNG output path")]
+        output: PathBuf,
+        #[arg(long, value_name = "DPI", help = "Rasterization resolution (36-600)")]
+        dpi: Option<u32>,
+        #[arg(
+            long,
+            value_name = "FACTOR",
+            help = "Resolution multiplier applied to --dpi (0.25-4.0)"
+        )]
+        scale: Option<f32>,
+        #[arg(long, help = "Allow overwriting --output when it already exists")]
+        force: bool,
+    },
+    #[command(
+        about = "Export a workbook or one sheet to PDF, honoring print areas",
+        after_long_help = "Examples:\n  agent-spreadsheet export-pdf report.xlsx --output report.pdf\n  agent-spreadsheet export-pdf report.xlsx --sheet Summary --output summary.pdf\n  agent-spreadsheet export-pdf report.xlsx --sheet Summary --print-area A1:H40 --output summary.pdf --force\n\nRequires LibreOffice (soffice) and a build with recalc-libreoffice.\nWithout --sheet every sheet is exported using the print areas saved in the workbook.\n--sheet exports only that sheet: its saved print area when one is defined, otherwise the whole sheet.\n--print-area overrides the sheet's print area for this export only; the workbook is not modified.\nCached formula values are exported as-is; run recalculate first if inputs changed."
+    )]
+    ExportPdf {
+        #[arg(value_name = "FILE", help = "Path to the workbook")]
+        file: PathBuf,
+        #[arg(long, value_name = "SHEET", help = "Export only this sheet")]
+        sheet: Option<String>,
+        #[arg(
+            long = "print-area",
+            value_name = "RANGE",
+            help = "A1 range to print on --sheet, overriding its saved print area"
+        )]
+        print_area: Option<String>,
+        #[arg(long, value_name = "PATH", help = "PDF output path")]
+        output: PathBuf,
+        #[arg(long, help = "Allow overwriting --output when it already exists")]
+        force: bool,
+    },
+    #[command(
+        about = "Find exact, same-content, and near-duplicate workbooks under folders",
+        after_long_help = "Examples:\n  agent-spreadsheet dedupe-scan\n  agent-spreadsheet dedupe-scan shared/finance archive --min-similarity 0.8\n\nexact_duplicates are byte-identical files.\ncontent_duplicates hold the same cells but different bytes, e.g. the same workbook saved again.\nnear_duplicates pair workbooks whose rows mostly match, e.g. report_final.xlsx and report_final_v2.xlsx; similarity is the share of distinct rows the two have in common.\nEvery group and pair lists the newest file first. Excel lock files (~$name.xlsx) are ignored."
+    )]
+    DedupeScan {
+        #[arg(
+            value_name = "FOLDER",
+            default_value = ".",
+            help = "Folders to scan recursively"
+        )]
+        roots: Vec<PathBuf>,
+        #[arg(
+            long = "min-similarity",
+            value_name = "RATIO",
+            default_value_t = crate::tools::dedupe::DEFAULT_MIN_SIMILARITY,
+            help = "Lowest row similarity (0-1) reported as a near duplicate"
+        )]
+        min_similarity: f64,
+    },
     #[command(
         about = "Create a new workbook at a destination path",
         after_long_help = "Examples:
@@ -1367,6 +2649,88 @@ For broader discovery, use sheet-page, range-values, or layout-page."
         #[arg(value_name = "DEST", help = "Destination workbook path")]
         dest: PathBuf,
     },
+    #[command(
+        about = "Fill {{placeholder}} tokens in a template workbook into a new file",
+        after_long_help = r#"Examples:
+  asp instantiate report-template.xlsx --values @vars.json --output acme-q3.xlsx
+  asp instantiate report-template.xlsx --values @vars.json --dry-run
+  asp instantiate report-template.xlsx --values @vars.json --output acme-q3.xlsx --strict
+
+Values (`--values @vars.json`):
+  {"client":"Acme Ltd","quarter":"Q3 2025","revenue":1250000,"approved":true,"owner":{"name":"Dana"}}
+  Nested objects are flattened with dots: {{owner.name}}. null fills an empty string.
+
+Placeholders:
+  {{name}} (spaces inside the braces allowed) is replaced in text cells, sheet headers and
+  footers, and defined names. A cell holding only one placeholder takes the value's type, so
+  numbers and booleans stay numbers and booleans; elsewhere the value is spliced in as text.
+  Formulas are left unchanged and reported; reference a cell holding the placeholder instead.
+
+The response lists unresolved placeholders with where they were found, and supplied values
+no placeholder used. --strict fails without writing when any placeholder is unresolved.
+The template itself is never modified."#
+    )]
+    Instantiate {
+        #[arg(value_name = "TEMPLATE", help = "Template workbook path")]
+        template: PathBuf,
+        #[arg(
+            long,
+            value_name = "@PATH",
+            help = "JSON object of placeholder values as @<path>"
+        )]
+        values: String,
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "Write the filled workbook to this path"
+        )]
+        output: Option<PathBuf>,
+        #[arg(long, help = "Report what would be filled without writing a file")]
+        dry_run: bool,
+        #[arg(long, help = "Allow overwriting --output when it already exists")]
+        force: bool,
+        #[arg(long, help = "Fail without writing when any placeholder has no value")]
+        strict: bool,
+    },
+    #[command(
+        about = "Restore a workbook from a snapshot taken by --snapshot-dir",
+        after_long_help = "Examples:\n  asp transform-batch model.xlsx --ops @ops.json --in-place --snapshot-dir .snapshots\n  asp list-snapshots --snapshot-dir .snapshots --file model.xlsx\n  asp restore model.xlsx --snapshot 20260301T101500123Z-3f2a9c1e --snapshot-dir .snapshots\n\nBehavior:\n  - replaces FILE atomically with the snapshot copy after checking its recorded hash\n  - refuses a snapshot taken from a different workbook unless --force is given\n  - snapshots the current FILE into the same directory first, so the restore can be undone\n  - --snapshot-dir may be an s3://bucket/prefix or gs://bucket/prefix in builds with the object-store feature"
+    )]
+    Restore {
+        #[arg(value_name = "FILE", help = "Workbook to restore")]
+        file: PathBuf,
+        #[arg(long, value_name = "ID", help = "Snapshot id from list-snapshots")]
+        snapshot: String,
+        #[arg(
+            long = "snapshot-dir",
+            value_name = "DIR",
+            help = "Directory the snapshot was written to"
+        )]
+        snapshot_dir: PathBuf,
+        #[arg(
+            long,
+            help = "Restore even if the snapshot was taken from a different workbook"
+        )]
+        force: bool,
+    },
+    #[command(
+        about = "List snapshots taken by --snapshot-dir, newest first",
+        after_long_help = "Examples:\n  asp list-snapshots --snapshot-dir .snapshots\n  asp list-snapshots --snapshot-dir .snapshots --file model.xlsx\n\nEach entry reports the id to pass to `restore`, the command whose write it preceded, the source workbook, and the SHA-256 of the copy."
+    )]
+    ListSnapshots {
+        #[arg(
+            long = "snapshot-dir",
+            value_name = "DIR",
+            help = "Directory passed to --snapshot-dir"
+        )]
+        snapshot_dir: PathBuf,
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "Only list snapshots taken from this workbook"
+        )]
+        file: Option<PathBuf>,
+    },
     #[command(
         about = "Apply one or more shorthand cell edits to a sheet",
         after_long_help = r#"Examples:
@@ -1384,6 +2748,11 @@ Formula shorthand:
   Use double equals for formulas, e.g. C2==SUM(A1:A10).
   Single equals writes a literal value/text, e.g. C2=SUM(A1:A10).
 
+Locale-formatted values:
+  --locale de-DE reads B2=1.234,56 as 1234.56 and C2=31.12.2024 as a date
+  (stored as a serial with a dd.mm.yyyy format). Digits that do not follow
+  the locale's separators, such as 1.5 under de-DE, are stored as text.
+
 Shell quoting (positional edits):
   Single-quote every edit that contains parentheses, spaces, or $:
   unquoted ( breaks the shell, and double quotes let the shell expand
@@ -1429,10 +2798,16 @@ Diagnostics note:
             help = "Formula parse policy: fail (default for edit), warn, or off"
         )]
         formula_parse_policy: Option<FormulaParsePolicy>,
+        #[arg(
+            long,
+            value_name = "TAG",
+            help = "Read literal numbers and dates as typed in this locale, e.g. de-DE for 1.234,56 and 31.12.2024"
+        )]
+        locale: Option<String>,
     },
     #[command(
         about = "Append rows into a detected region with footer-aware insertion",
-        after_long_help = "Examples:\n  asp append-region workbook.xlsx --sheet Sheet1 --region-id 0 --rows @rows.json --dry-run\n  asp append-region workbook.xlsx --sheet Sheet1 --table-name SalesTable --from-csv rows.csv --header --footer-policy before-footer --output updated.xlsx --force\n\nTarget selection:\n  Use exactly one of --region-id or --table-name.\n  --region-id comes from `asp sheet-overview`.\n  --table-name resolves an existing sheet table by name.\n\nInput payloads:\n  Use exactly one of --rows or --from-csv.\n  --rows accepts a top-level JSON array of rows, or an object with a rows array.\n  Cells may be raw JSON scalars/null, {'v': ...} value cells, or {'f': 'FORMULA'} formula cells.\n  --from-csv imports CSV rows and treats empty fields as blanks; use --header to skip the first CSV row.\n\nFooter policies:\n  - auto (default): insert before a detected footer row when found, else append at the region end\n  - before-footer: require a detected footer/subtotal row and fail when none is found\n  - append-at-end: always append after the detected region end, even when a footer row is present\n\nBehavior:\n  - resolves a detected region or table target\n  - reports footer candidates, policy choice, and formula footer targets in dry-run output\n  - writes the appended matrix into inserted rows\n  - expands adjacent SUM footers below the insertion band when rows are inserted before them"
+        after_long_help = "Examples:\n  asp append-region workbook.xlsx --sheet Sheet1 --region-id 0 --rows @rows.json --dry-run\n  asp append-region workbook.xlsx --sheet Sheet1 --table-name SalesTable --from-csv rows.csv --header --footer-policy before-footer --output updated.xlsx --force\n\nTarget selection:\n  Use exactly one of --region-id or --table-name.\n  --region-id comes from `asp sheet-overview`.\n  --table-name resolves an existing sheet table by name.\n\nInput payloads:\n  Use exactly one of --rows or --from-csv.\n  --rows accepts a top-level JSON array of rows, or an object with a rows array.\n  Cells may be raw JSON scalars/null, {'v': ...} value cells, or {'f': 'FORMULA'} formula cells.\n  --from-csv imports CSV rows and treats empty fields as blanks; use --header to skip the first CSV row.\n  --column-map @mapping.json (from map-columns, requires --header) reorders CSV columns by header into the mapping's target column order; unmapped target columns stay blank.\n\nFooter policies:\n  - auto (default): insert before a detected footer row when found, else append at the region end\n  - before-footer: require a detected footer/subtotal row and fail when none is found\n  - append-at-end: always append after the detected region end, even when a footer row is present\n\nBehavior:\n  - resolves a detected region or table target\n  - reports footer candidates, policy choice, and formula footer targets in dry-run output\n  - writes the appended matrix into inserted rows\n  - expands adjacent SUM footers below the insertion band when rows are inserted before them"
     )]
     AppendRegion {
         #[arg(value_name = "FILE", help = "Workbook path to update")]
@@ -1469,6 +2844,13 @@ Diagnostics note:
         from_csv: Option<String>,
         #[arg(long, help = "Skip first CSV row when importing --from-csv")]
         header: bool,
+        #[arg(
+            long = "column-map",
+            value_name = "JSON_OR_@FILE",
+            requires = "header",
+            help = "Mapping from map-columns: place each CSV column (by header) under its mapped target column"
+        )]
+        column_map: Option<String>,
         #[arg(
             long = "footer-policy",
             value_enum,
@@ -1486,6 +2868,125 @@ Diagnostics note:
         #[arg(long, help = "Allow overwriting --output when it already exists")]
         force: bool,
     },
+    #[command(
+        about = "Materialize a table query into a sheet with stored refresh metadata",
+        after_long_help = "Examples:\n  asp materialize sales.xlsx --target-sheet \"West Summary\" --sheet Orders --range A1:F500 --filters-json '[{\"column\":\"Region\",\"op\":\"eq\",\"value\":\"West\"}]' --in-place\n  asp materialize sales.xlsx --target-sheet Expenses --query monthly_expenses --output summary.xlsx --new-workbook\n  asp materialize sales.xlsx --refresh --in-place\n  asp materialize sales.xlsx --refresh --target-sheet \"West Summary\" --dry-run\n\nQuery:\n  Same selectors as read-table: --sheet, --range, --table-name, --region-id, --filters-json/--filters-file, --limit.\n  --query applies a named query (see `asp read-table --help`); flags override its stored fields.\n  Every matching row is written unless the query sets a limit.\n\nBehavior:\n  - the target sheet is created if missing and its previous contents are replaced\n  - the header row is written at --anchor (default A1), followed by values only (no formulas)\n  - the resolved query is recorded in a hidden _materialized sheet, one row per target sheet\n  - --new-workbook writes only the result to --output and records the source workbook path\n  - --refresh re-runs recorded definitions (all, or only --target-sheet) and rewrites their sheets; `asp refresh` does the same and reports row-count changes"
+    )]
+    Materialize {
+        #[arg(value_name = "FILE", help = "Workbook to query and update")]
+        file: PathBuf,
+        #[arg(
+            long = "target-sheet",
+            value_name = "SHEET",
+            help = "Sheet to write the result into (with --refresh: only refresh this sheet)"
+        )]
+        target_sheet: Option<String>,
+        #[arg(
+            long,
+            value_name = "CELL",
+            help = "Top-left cell of the result header row (default A1)"
+        )]
+        anchor: Option<String>,
+        #[arg(long, value_name = "SHEET", help = "Sheet to query")]
+        sheet: Option<String>,
+        #[arg(long, value_name = "RANGE", help = "A1 range to query")]
+        range: Option<String>,
+        #[arg(long, value_name = "NAME", help = "Query a named Excel table")]
+        table_name: Option<String>,
+        #[arg(long, value_name = "ID", help = "Query a detected region id")]
+        region_id: Option<u32>,
+        #[arg(
+            long = "filters-json",
+            value_name = "JSON",
+            help = "Inline JSON array of filters (mutually exclusive with --filters-file)"
+        )]
+        filters_json: Option<String>,
+        #[arg(
+            long = "filters-file",
+            value_name = "PATH",
+            help = "Path to JSON array of filters (mutually exclusive with --filters-json)"
+        )]
+        filters_file: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "LIMIT",
+            help = "Maximum rows to write (default: all matching rows)"
+        )]
+        limit: Option<u32>,
+        #[arg(
+            long,
+            value_name = "NAME",
+            help = "Apply a named query from the workspace query definitions"
+        )]
+        query: Option<String>,
+        #[arg(
+            long = "queries-file",
+            value_name = "PATH",
+            requires = "query",
+            help = "YAML/JSON file of named queries (default: <workspace>/.asp/queries.yaml)"
+        )]
+        queries_file: Option<PathBuf>,
+        #[arg(
+            long,
+            conflicts_with_all = [
+                "anchor",
+                "sheet",
+                "range",
+                "table_name",
+                "region_id",
+                "filters_json",
+                "filters_file",
+                "limit",
+                "query",
+                "new_workbook",
+            ],
+            help = "Re-run the definitions recorded in the workbook's _materialized sheet"
+        )]
+        refresh: bool,
+        #[arg(
+            long = "new-workbook",
+            help = "Write the result into a new workbook at --output instead of a copy of FILE"
+        )]
+        new_workbook: bool,
+        #[arg(long, help = "Preview the written ranges without mutating files")]
+        dry_run: bool,
+        #[arg(long, help = "Apply by atomically replacing the source file")]
+        in_place: bool,
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "Write the result to this output path"
+        )]
+        output: Option<PathBuf>,
+        #[arg(long, help = "Allow overwriting --output when it already exists")]
+        force: bool,
+    },
+    #[command(
+        about = "Re-run recorded materializations and rewrite their sheets",
+        after_long_help = "Examples:\n  asp refresh dashboard.xlsx --in-place\n  asp refresh dashboard.xlsx --sheet \"West Summary\" --sheet Expenses --dry-run\n\nBehavior:\n  - re-runs the queries `asp materialize` recorded in the hidden _materialized sheet\n  - rewrites each target sheet and updates its recorded row count and refresh time\n  - reports previous_rows and row_delta per sheet, plus row_count_changes overall\n  - definitions recorded with a source workbook re-read it (relative paths resolve next to FILE)"
+    )]
+    Refresh {
+        #[arg(value_name = "FILE", help = "Workbook holding materialized sheets")]
+        file: PathBuf,
+        #[arg(
+            long = "sheet",
+            value_name = "SHEET",
+            help = "Only refresh this materialized sheet (repeatable)"
+        )]
+        sheets: Vec<String>,
+        #[arg(long, help = "Preview the rewritten ranges without mutating files")]
+        dry_run: bool,
+        #[arg(long, help = "Apply by atomically replacing the source file")]
+        in_place: bool,
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "Write the refreshed workbook to this output path"
+        )]
+        output: Option<PathBuf>,
+        #[arg(long, help = "Allow overwriting --output when it already exists")]
+        force: bool,
+    },
     #[command(
         about = "Clone one template row into inserted rows with preview-first planning",
         after_long_help = "Examples:\n  asp clone-template-row workbook.xlsx --sheet Sheet1 --source-row 12 --after 12 --count 2 --dry-run\n  asp clone-template-row workbook.xlsx --sheet Sheet1 --source-row 8 --before 20 --patch-targets all-non-formula --output updated.xlsx --force\n\nAnchor selection:\n  Use exactly one of --before, --after, or --insert-at.\n\nBehavior:\n  - clones a single template row using the existing row-clone structure path\n  - reports formula targets, patch targets, merge-boundary warnings, and confidence metadata in dry-run output\n  - merge-policy safe warns on boundary-crossing merges; strict fails instead"
@@ -1629,6 +3130,18 @@ Payload examples (`--ops @transform_ops.json`):
     {"ops":[{"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"range","range":"B2:B4"},"value":"0"}]}
   Advanced:
     {"ops":[{"kind":"replace_in_range","sheet_name":"Sheet1","target":{"kind":"region","region_id":1},"find":"N/A","replace":"","match_mode":"contains","case_sensitive":false,"include_formulas":true}]}
+  Bulk import (rows inline or "@rows.csv"/"@rows.json", relative to the working directory):
+    {"ops":[{"kind":"import_rows","sheet_name":"Sheet1","anchor":"A1","rows":"@new_rows.csv","has_header":true,"match_headers":true,"append":true}]}
+  Table append (grows the table, copies calculated-column formulas down, keeps the totals row last):
+    {"ops":[{"kind":"append_table_rows","sheet_name":"Sheet1","table_name":"Orders","rows":[{"Customer":"Acme","Qty":3}]}]}
+  Clear (clear_contents keeps formats, clear_formats keeps values, clear_all removes both):
+    {"ops":[{"kind":"clear_contents","sheet_name":"Sheet1","target":{"kind":"range","range":"B2:F200"}}]}
+  Sort (whole rows move, formulas follow their row; --dry-run reports row_permutations):
+    {"ops":[{"kind":"sort_range","sheet_name":"Sheet1","range":"A1:D40","has_header":true,"keys":[{"column":"Region"},{"column":"D","order":"desc","value_type":"number"}]}]}
+  Copy / move (paste all|formulas|values|formats; a move repoints references to the moved cells like Excel's cut):
+    {"ops":[{"kind":"move_range","sheet_name":"Report","src_range":"A20:F32","dest_anchor":"H2","paste":"all"}]}
+  Fill series (series_type linear|growth|date; date_unit day|weekday|month|year; stop ends each line early):
+    {"ops":[{"kind":"fill_series","sheet_name":"Forecast","range":"B1:M1","start":"2025-01-31","series_type":"date","date_unit":"month"}]}
 
 Required envelope:
   Top-level object with an `ops` array.
@@ -1670,6 +3183,29 @@ Diagnostics note:
         output: Option<PathBuf>,
         #[arg(long, help = "Allow overwriting --output when it already exists")]
         force: bool,
+        #[arg(
+            long = "verify-with",
+            value_name = "TESTS_JSON",
+            help = "Recalculate a copy of the staged result and check these expectations before writing; fail without writing on any mismatch"
+        )]
+        verify_with: Option<PathBuf>,
+        #[arg(
+            long = "audit-log",
+            value_name = "PATH",
+            help = "Append a JSONL record of the applied write (command, ops hash, changed cells, before/after hashes) to this file"
+        )]
+        audit_log: Option<PathBuf>,
+        #[arg(
+            long = "snapshot-dir",
+            value_name = "DIR",
+            help = "With --in-place, copy the workbook into this directory (or s3:// / gs:// prefix, with the object-store feature) before replacing it; undo with `restore`"
+        )]
+        snapshot_dir: Option<PathBuf>,
+        #[arg(
+            long = "continue-on-error",
+            help = "Skip ops that fail and apply the rest, reporting applied, skipped, and failed ops individually (default: all or nothing)"
+        )]
+        continue_on_error: bool,
         #[arg(
             long = "print-schema",
             hide = true,
@@ -1683,6 +3219,11 @@ Diagnostics note:
             help = "Formula parse policy: fail, warn (default for transform-batch), or off"
         )]
         formula_parse_policy: Option<FormulaParsePolicy>,
+        #[arg(
+            long = "differential-save",
+            help = "Keep every package part except the edited sheets' cell data byte-identical, for clean package diffs (not faster than a full save; batches that need more fall back with a warning)"
+        )]
+        differential_save: bool,
     },
     #[command(
         about = "Apply stateless style operations from an @ops payload",
@@ -1695,10 +3236,13 @@ Payload examples (`--ops @style_ops.json`):
     {"ops":[{"sheet_name":"Sheet1","target":{"kind":"range","range":"B2:B2"},"patch":{"font":{"bold":true}}}]}
   Advanced:
     {"ops":[{"sheet_name":"Sheet1","target":{"kind":"cells","cells":["B2","B3"]},"patch":{"number_format":"$#,##0.00","alignment":{"horizontal":"right"}},"op_mode":"merge"}]}
+  Convert number formats between locales (currency, date order, [$-LCID] tags):
+    {"ops":[{"target":{"kind":"workbook"},"patch":{"number_format_locale":{"from":"en-US","to":"de-DE"}}}]}
 
 Required envelope:
   Top-level object with an `ops` array.
-  Style ops require `sheet_name`, `target`, and `patch` (no top-level op `kind`)."#
+  Style ops require `sheet_name` (except `workbook` targets), `target`, and `patch` (no top-level op `kind`).
+  Targets: range, cells, region, sheet (every stored cell of the sheet), workbook."#
     )]
     StyleBatch {
         #[arg(
@@ -1726,6 +3270,29 @@ Required envelope:
         output: Option<PathBuf>,
         #[arg(long, help = "Allow overwriting --output when it already exists")]
         force: bool,
+        #[arg(
+            long = "verify-with",
+            value_name = "TESTS_JSON",
+            help = "Recalculate a copy of the staged result and check these expectations before writing; fail without writing on any mismatch"
+        )]
+        verify_with: Option<PathBuf>,
+        #[arg(
+            long = "audit-log",
+            value_name = "PATH",
+            help = "Append a JSONL record of the applied write (command, ops hash, changed cells, before/after hashes) to this file"
+        )]
+        audit_log: Option<PathBuf>,
+        #[arg(
+            long = "snapshot-dir",
+            value_name = "DIR",
+            help = "With --in-place, copy the workbook into this directory (or s3:// / gs:// prefix, with the object-store feature) before replacing it; undo with `restore`"
+        )]
+        snapshot_dir: Option<PathBuf>,
+        #[arg(
+            long = "continue-on-error",
+            help = "Skip ops that fail and apply the rest, reporting applied, skipped, and failed ops individually (default: all or nothing)"
+        )]
+        continue_on_error: bool,
         #[arg(
             long = "print-schema",
             hide = true,
@@ -1785,6 +3352,29 @@ Diagnostics note:
         output: Option<PathBuf>,
         #[arg(long, help = "Allow overwriting --output when it already exists")]
         force: bool,
+        #[arg(
+            long = "verify-with",
+            value_name = "TESTS_JSON",
+            help = "Recalculate a copy of the staged result and check these expectations before writing; fail without writing on any mismatch"
+        )]
+        verify_with: Option<PathBuf>,
+        #[arg(
+            long = "audit-log",
+            value_name = "PATH",
+            help = "Append a JSONL record of the applied write (command, ops hash, changed cells, before/after hashes) to this file"
+        )]
+        audit_log: Option<PathBuf>,
+        #[arg(
+            long = "snapshot-dir",
+            value_name = "DIR",
+            help = "With --in-place, copy the workbook into this directory (or s3:// / gs:// prefix, with the object-store feature) before replacing it; undo with `restore`"
+        )]
+        snapshot_dir: Option<PathBuf>,
+        #[arg(
+            long = "continue-on-error",
+            help = "Skip ops that fail and apply the rest, reporting applied, skipped, and failed ops individually (default: all or nothing)"
+        )]
+        continue_on_error: bool,
         #[arg(
             long = "print-schema",
             hide = true,
@@ -1803,6 +3393,13 @@ Payload examples (`--ops @structure_ops.json`):
     {"ops":[{"kind":"rename_sheet","old_name":"Summary","new_name":"Dashboard"}]}
   Advanced:
     {"ops":[{"kind":"copy_range","sheet_name":"Sheet1","dest_sheet_name":"Summary","src_range":"A1:C4","dest_anchor":"A1","include_styles":true,"include_formulas":true}]}
+  Copy a sheet from another workbook (styles, merges, conditional formats, tables):
+    {"ops":[{"kind":"copy_sheet","source_file":"templates/cover.xlsx","source_sheet":"Cover","new_name":"Q3 Cover","position":0}]}
+  source_file is relative to the current directory and may be the workbook being edited.
+  Sheet tabs (list-sheets reports visibility and tab_color):
+    {"ops":[{"kind":"set_tab_color","sheet_name":"Inputs","color":"#FFC000"},{"kind":"hide_sheet","sheet_name":"Lookups","very_hidden":true},{"kind":"reorder_sheets","order":["Summary","Inputs"]}]}
+  hide_sheet keeps at least one sheet visible; unhide_sheet reverses it. reorder_sheets moves the
+  listed sheets to the front in that order, and omitting color in set_tab_color removes it.
 
 Required envelope:
   Top-level object with an `ops` array.
@@ -1838,9 +3435,32 @@ Cache note:
             value_name = "PATH",
             help = "Apply structure ops to this output path"
         )]
-        output: Option<PathBuf>,
-        #[arg(long, help = "Allow overwriting --output when it already exists")]
-        force: bool,
+        output: Option<PathBuf>,
+        #[arg(long, help = "Allow overwriting --output when it already exists")]
+        force: bool,
+        #[arg(
+            long = "verify-with",
+            value_name = "TESTS_JSON",
+            help = "Recalculate a copy of the staged result and check these expectations before writing; fail without writing on any mismatch"
+        )]
+        verify_with: Option<PathBuf>,
+        #[arg(
+            long = "audit-log",
+            value_name = "PATH",
+            help = "Append a JSONL record of the applied write (command, ops hash, changed cells, before/after hashes) to this file"
+        )]
+        audit_log: Option<PathBuf>,
+        #[arg(
+            long = "snapshot-dir",
+            value_name = "DIR",
+            help = "With --in-place, copy the workbook into this directory (or s3:// / gs:// prefix, with the object-store feature) before replacing it; undo with `restore`"
+        )]
+        snapshot_dir: Option<PathBuf>,
+        #[arg(
+            long = "continue-on-error",
+            help = "Skip ops that fail and apply the rest, reporting applied, skipped, and failed ops individually (default: all or nothing)"
+        )]
+        continue_on_error: bool,
         #[arg(
             long = "print-schema",
             hide = true,
@@ -1906,6 +3526,15 @@ Payload examples (`--ops @column_size_ops.json`):
     {"sheet_name":"Sheet1","ops":[{"range":"A:A","size":{"kind":"width","width_chars":12.0}}]}
   Advanced:
     {"sheet_name":"Sheet1","ops":[{"target":{"kind":"columns","range":"A:C"},"size":{"kind":"auto","min_width_chars":8.0,"max_width_chars":24.0}}]}
+  Content autofit:
+    {"sheet_name":"Sheet1","ops":[{"range":"A:F","size":{"kind":"autofit","max_width_chars":60.0}}]}
+
+Size kinds:
+  width    fixed width in characters
+  auto     spreadsheet engine auto-width
+  autofit  measure displayed text (number formats applied, font size and bold
+           scaled, wrap-text cells by longest word) plus padding_chars (default 1);
+           empty columns keep their width
 
 Required envelope:
   Preferred: top-level object with `sheet_name` and `ops`.
@@ -1941,6 +3570,108 @@ Required envelope:
         output: Option<PathBuf>,
         #[arg(long, help = "Allow overwriting --output when it already exists")]
         force: bool,
+        #[arg(
+            long = "verify-with",
+            value_name = "TESTS_JSON",
+            help = "Recalculate a copy of the staged result and check these expectations before writing; fail without writing on any mismatch"
+        )]
+        verify_with: Option<PathBuf>,
+        #[arg(
+            long = "audit-log",
+            value_name = "PATH",
+            help = "Append a JSONL record of the applied write (command, ops hash, changed cells, before/after hashes) to this file"
+        )]
+        audit_log: Option<PathBuf>,
+        #[arg(
+            long = "snapshot-dir",
+            value_name = "DIR",
+            help = "With --in-place, copy the workbook into this directory (or s3:// / gs:// prefix, with the object-store feature) before replacing it; undo with `restore`"
+        )]
+        snapshot_dir: Option<PathBuf>,
+        #[arg(
+            long = "continue-on-error",
+            help = "Skip ops that fail and apply the rest, reporting applied, skipped, and failed ops individually (default: all or nothing)"
+        )]
+        continue_on_error: bool,
+        #[arg(
+            long = "print-schema",
+            hide = true,
+            help = "Print the full JSON schema for the --ops payload and exit"
+        )]
+        print_schema: bool,
+    },
+    #[command(
+        about = "Apply stateless row sizing operations from an @ops payload",
+        after_long_help = r#"Examples:
+  agent-spreadsheet row-size-batch workbook.xlsx --ops @row_size_ops.json --in-place
+  agent-spreadsheet row-size-batch workbook.xlsx --ops @row_size_ops.json --output rows.xlsx
+
+Payload examples (`--ops @row_size_ops.json`):
+  Minimal:
+    {"sheet_name":"Sheet1","ops":[{"range":"1","size":{"kind":"height","height_points":30.0}}]}
+  Advanced:
+    {"sheet_name":"Sheet1","ops":[{"target":{"kind":"rows","range":"2:10"},"size":{"kind":"auto","min_height_points":15.0,"max_height_points":60.0}}]}
+
+Auto sizing estimates height from cell text: explicit line breaks always add
+lines, and wrap-text cells also wrap at the column width, scaled by font size.
+
+Required envelope:
+  Preferred: top-level object with `sheet_name` and `ops`.
+  Also accepted: top-level `ops` where each op includes `sheet_name`.
+  Each op requires `size.kind`; canonical form also includes `target.kind:"rows"`."#
+    )]
+    RowSizeBatch {
+        #[arg(
+            value_name = "FILE",
+            help = "Workbook path to update",
+            required_unless_present = "print_schema"
+        )]
+        file: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "OPS_REF",
+            help = "Ops payload file reference (@path)",
+            required_unless_present = "print_schema"
+        )]
+        ops: Option<String>,
+        #[arg(long, help = "Validate ops and report summary without mutating files")]
+        dry_run: bool,
+        #[arg(
+            long,
+            help = "Apply row sizing ops by atomically replacing the source file"
+        )]
+        in_place: bool,
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "Apply row sizing ops to this output path"
+        )]
+        output: Option<PathBuf>,
+        #[arg(long, help = "Allow overwriting --output when it already exists")]
+        force: bool,
+        #[arg(
+            long = "verify-with",
+            value_name = "TESTS_JSON",
+            help = "Recalculate a copy of the staged result and check these expectations before writing; fail without writing on any mismatch"
+        )]
+        verify_with: Option<PathBuf>,
+        #[arg(
+            long = "audit-log",
+            value_name = "PATH",
+            help = "Append a JSONL record of the applied write (command, ops hash, changed cells, before/after hashes) to this file"
+        )]
+        audit_log: Option<PathBuf>,
+        #[arg(
+            long = "snapshot-dir",
+            value_name = "DIR",
+            help = "With --in-place, copy the workbook into this directory (or s3:// / gs:// prefix, with the object-store feature) before replacing it; undo with `restore`"
+        )]
+        snapshot_dir: Option<PathBuf>,
+        #[arg(
+            long = "continue-on-error",
+            help = "Skip ops that fail and apply the rest, reporting applied, skipped, and failed ops individually (default: all or nothing)"
+        )]
+        continue_on_error: bool,
         #[arg(
             long = "print-schema",
             hide = true,
@@ -1959,6 +3690,10 @@ Payload examples (`--ops @layout_ops.json`):
     {"ops":[{"kind":"freeze_panes","sheet_name":"Sheet1","freeze_rows":1,"freeze_cols":1}]}
   Advanced:
     {"ops":[{"kind":"set_page_setup","sheet_name":"Sheet1","orientation":"landscape","fit_to_width":1,"fit_to_height":1}]}
+  Print setup before PDF export:
+    {"ops":[{"kind":"set_print_area","sheet_name":"Report","range":"A1:G40"},{"kind":"set_orientation","sheet_name":"Report","orientation":"landscape"},{"kind":"set_header_footer","sheet_name":"Report","header":"&CQuarterly Report","footer":"&RPage &P of &N"}]}
+  Hide/group detail rows:
+    {"ops":[{"kind":"group_rows","sheet_name":"Sheet1","rows":"5:9","collapsed":true},{"kind":"set_columns_hidden","sheet_name":"Sheet1","columns":"D:E","hidden":true}]}
 
 Required envelope:
   Top-level object with an `ops` array.
@@ -1993,6 +3728,29 @@ Required envelope:
         output: Option<PathBuf>,
         #[arg(long, help = "Allow overwriting --output when it already exists")]
         force: bool,
+        #[arg(
+            long = "verify-with",
+            value_name = "TESTS_JSON",
+            help = "Recalculate a copy of the staged result and check these expectations before writing; fail without writing on any mismatch"
+        )]
+        verify_with: Option<PathBuf>,
+        #[arg(
+            long = "audit-log",
+            value_name = "PATH",
+            help = "Append a JSONL record of the applied write (command, ops hash, changed cells, before/after hashes) to this file"
+        )]
+        audit_log: Option<PathBuf>,
+        #[arg(
+            long = "snapshot-dir",
+            value_name = "DIR",
+            help = "With --in-place, copy the workbook into this directory (or s3:// / gs:// prefix, with the object-store feature) before replacing it; undo with `restore`"
+        )]
+        snapshot_dir: Option<PathBuf>,
+        #[arg(
+            long = "continue-on-error",
+            help = "Skip ops that fail and apply the rest, reporting applied, skipped, and failed ops individually (default: all or nothing)"
+        )]
+        continue_on_error: bool,
         #[arg(
             long = "print-schema",
             hide = true,
@@ -2011,11 +3769,17 @@ Payload examples (`--ops @rules_ops.json`):
     {"ops":[{"kind":"set_data_validation","sheet_name":"Sheet1","target_range":"B2:B4","validation":{"kind":"list","formula1":"\"A,B,C\""}}]}
   Advanced:
     {"ops":[{"kind":"set_conditional_format","sheet_name":"Sheet1","target_range":"C2:C10","rule":{"kind":"expression","formula":"C2>100"},"style":{"fill_color":"#FFF2CC","bold":true}}]}
+  Dropdown from a named range or another sheet's range (instead of formula1):
+    {"ops":[{"kind":"set_data_validation","sheet_name":"Sheet1","target_range":"B2:B50","validation":{"kind":"list","list_source":{"kind":"range","sheet_name":"Lookup Lists","range":"A1:A10"}}}]}
 
 Required envelope:
   Top-level object with an `ops` array.
   Each op requires a `kind` discriminator and kind-specific required fields.
 
+List sources:
+  list_source.kind=named_range emits the name verbatim; list_source.kind=range emits an absolute,
+  quoted reference such as 'Lookup Lists'!$A$1:$A$10. Missing names/sheets reject the batch.
+
 Note:
   Data-validation and conditional-format formulas are rule-level (not cell-level) and do not affect
   cell formula caches. No recalculate is needed after rules-batch operations."##
@@ -2046,6 +3810,29 @@ Note:
         output: Option<PathBuf>,
         #[arg(long, help = "Allow overwriting --output when it already exists")]
         force: bool,
+        #[arg(
+            long = "verify-with",
+            value_name = "TESTS_JSON",
+            help = "Recalculate a copy of the staged result and check these expectations before writing; fail without writing on any mismatch"
+        )]
+        verify_with: Option<PathBuf>,
+        #[arg(
+            long = "audit-log",
+            value_name = "PATH",
+            help = "Append a JSONL record of the applied write (command, ops hash, changed cells, before/after hashes) to this file"
+        )]
+        audit_log: Option<PathBuf>,
+        #[arg(
+            long = "snapshot-dir",
+            value_name = "DIR",
+            help = "With --in-place, copy the workbook into this directory (or s3:// / gs:// prefix, with the object-store feature) before replacing it; undo with `restore`"
+        )]
+        snapshot_dir: Option<PathBuf>,
+        #[arg(
+            long = "continue-on-error",
+            help = "Skip ops that fail and apply the rest, reporting applied, skipped, and failed ops individually (default: all or nothing)"
+        )]
+        continue_on_error: bool,
         #[arg(
             long = "print-schema",
             hide = true,
@@ -2060,6 +3847,268 @@ Note:
         )]
         formula_parse_policy: Option<FormulaParsePolicy>,
     },
+    #[command(
+        about = "Set title, author, company and custom document properties from an @ops payload",
+        after_long_help = r##"Examples:
+  agent-spreadsheet properties-batch report.xlsx --ops @props.json --dry-run
+  agent-spreadsheet properties-batch report.xlsx --ops @props.json --in-place
+
+Payload examples (`--ops @props.json`):
+  Minimal:
+    {"ops":[{"kind":"set_core","title":"Q3 Close"}]}
+  Stamp a generated workbook with its run:
+    {"ops":[{"kind":"set_core","creator":"close-pipeline","keywords":"finance; close"},{"kind":"set_app","company":"Acme"},{"kind":"set_custom","name":"RunId","value":"run-2024-09-30-01"},{"kind":"set_custom","name":"Approved","value":false}]}
+  Remove a custom property or clear a core one:
+    {"ops":[{"kind":"remove_custom","name":"Draft"},{"kind":"set_core","description":""}]}
+
+Ops:
+  set_core       title, subject, creator, keywords, description, last_modified_by, category, content_status
+  set_app        company, manager
+  set_custom     name + value (string, number or boolean); replaces a property of the same name
+  remove_custom  name
+
+Fields left out are unchanged; an empty string removes the property. Only the
+docProps parts are rewritten, so sheets, styles and formula caches are untouched.
+`describe` reports the result under `properties`."##
+    )]
+    PropertiesBatch {
+        #[arg(
+            value_name = "FILE",
+            help = "Workbook path to update",
+            required_unless_present = "print_schema"
+        )]
+        file: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "OPS_REF",
+            help = "Ops payload file reference (@path)",
+            required_unless_present = "print_schema"
+        )]
+        ops: Option<String>,
+        #[arg(long, help = "Validate ops and report summary without mutating files")]
+        dry_run: bool,
+        #[arg(
+            long,
+            help = "Apply property ops by atomically replacing the source file"
+        )]
+        in_place: bool,
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "Apply property ops to this output path"
+        )]
+        output: Option<PathBuf>,
+        #[arg(long, help = "Allow overwriting --output when it already exists")]
+        force: bool,
+        #[arg(
+            long = "verify-with",
+            value_name = "TESTS_JSON",
+            help = "Recalculate a copy of the staged result and check these expectations before writing; fail without writing on any mismatch"
+        )]
+        verify_with: Option<PathBuf>,
+        #[arg(
+            long = "audit-log",
+            value_name = "PATH",
+            help = "Append a JSONL record of the applied write (command, ops hash, changed cells, before/after hashes) to this file"
+        )]
+        audit_log: Option<PathBuf>,
+        #[arg(
+            long = "snapshot-dir",
+            value_name = "DIR",
+            help = "With --in-place, copy the workbook into this directory (or s3:// / gs:// prefix, with the object-store feature) before replacing it; undo with `restore`"
+        )]
+        snapshot_dir: Option<PathBuf>,
+        #[arg(
+            long = "continue-on-error",
+            help = "Skip ops that fail and apply the rest, reporting applied, skipped, and failed ops individually (default: all or nothing)"
+        )]
+        continue_on_error: bool,
+        #[arg(
+            long = "print-schema",
+            hide = true,
+            help = "Print the full JSON schema for the --ops payload and exit"
+        )]
+        print_schema: bool,
+    },
+    #[command(
+        about = "Diff a desired-state spec against a workbook and emit the minimal ops to converge it",
+        after_long_help = r##"Examples:
+  asp plan model.xlsx --spec @desired.json
+  asp plan model.xlsx --spec @desired.json --output plan.json
+  asp apply-plan model.xlsx --plan @plan.json --in-place
+
+Spec shape:
+  {"targets":[
+    {"sheet_name":"Sheet1","anchor":"A1",
+     "rows":[["Region","Amount"],["North",10],["Total","=SUM(B2:B2)"]],
+     "styles":[{"range":"A1:B1","patch":{"font":{"bold":true}}}]}
+  ]}
+  rows run from anchor (default A1): null means empty, text starting with = is a formula,
+  and {"v":...} or {"f":...} spell out a value or formula. Cells past the end of a short row are left alone.
+  styles take the same patches as style-batch; a cell counts as styled when the patch would not change it.
+
+Machine contract:
+  - plan is an apply-plan payload: a transform step (write_matrix for changed cells, clear_range for
+    cells that should be empty) and a style step (only the cells a patch would change); empty steps are left out
+  - each step's ops also work as a transform-batch or style-batch payload
+  - converged is true when nothing needs to change; changes lists each cell to write, clear, or restyle
+    (first 200, with changes_truncated)
+  - the workbook is never modified; --output writes the plan payload to a file"##
+    )]
+    Plan {
+        #[arg(value_name = "FILE", help = "Workbook path to compare against")]
+        file: PathBuf,
+        #[arg(
+            long,
+            value_name = "SPEC_REF",
+            help = "Desired-state spec file reference (@path)"
+        )]
+        spec: String,
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "Also write the plan payload to this file, ready for apply-plan --plan @PATH"
+        )]
+        output: Option<PathBuf>,
+    },
+    #[command(
+        about = "Apply a plan of transform, style, structure, and rules payloads as one atomic write",
+        after_long_help = r##"Examples:
+  asp apply-plan model.xlsx --plan @plan.json --dry-run
+  asp apply-plan model.xlsx --plan @plan.json --in-place --snapshot-dir .snapshots
+  asp apply-plan model.xlsx --plan @plan.json --output updated.xlsx --verify-with tests.json
+
+Plan shape:
+  {"steps":[
+    {"kind":"structure","ops":[{"kind":"insert_rows","sheet_name":"Sheet1","at_row":2,"count":1}]},
+    {"kind":"transform","ops":[{"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"cells","cells":["B2"]},"value":"0"}]},
+    {"kind":"style","ops":[{"sheet_name":"Sheet1","target":{"kind":"range","range":"B2:B2"},"patch":{"font":{"bold":true}}}]}
+  ]}
+  Each step's ops use the same payload as transform-batch, style-batch, structure-batch, or rules-batch.
+
+Behavior:
+  - every step is validated and resolved against the source workbook before anything is staged
+  - steps apply in order to one staged copy; later steps see earlier steps' changes
+  - the staged copy replaces the target only if every step succeeds (and --verify-with passes);
+    on any failure nothing is written and the error names the failing step
+  - region_id targets refer to regions detected in the source workbook"##
+    )]
+    ApplyPlan {
+        #[arg(value_name = "FILE", help = "Workbook path to update")]
+        file: PathBuf,
+        #[arg(
+            long,
+            value_name = "PLAN_REF",
+            help = "Plan payload file reference (@path)"
+        )]
+        plan: String,
+        #[arg(
+            long,
+            help = "Validate and apply the plan to a scratch copy without mutating files"
+        )]
+        dry_run: bool,
+        #[arg(long, help = "Apply the plan by atomically replacing the source file")]
+        in_place: bool,
+        #[arg(long, value_name = "PATH", help = "Apply the plan to this output path")]
+        output: Option<PathBuf>,
+        #[arg(long, help = "Allow overwriting --output when it already exists")]
+        force: bool,
+        #[arg(
+            long = "formula-parse-policy",
+            value_enum,
+            value_name = "POLICY",
+            help = "Formula parse policy for transform, structure, and rules steps: fail, warn, or off"
+        )]
+        formula_parse_policy: Option<FormulaParsePolicy>,
+        #[arg(
+            long = "verify-with",
+            value_name = "TESTS_JSON",
+            help = "Recalculate a copy of the staged result and check these expectations before writing; fail without writing on any mismatch"
+        )]
+        verify_with: Option<PathBuf>,
+        #[arg(
+            long = "audit-log",
+            value_name = "PATH",
+            help = "Append a JSONL record of the applied write (command, plan hash, changed cells, before/after hashes) to this file"
+        )]
+        audit_log: Option<PathBuf>,
+        #[arg(
+            long = "snapshot-dir",
+            value_name = "DIR",
+            help = "With --in-place, copy the workbook into this directory (or s3:// / gs:// prefix, with the object-store feature) before replacing it; undo with `restore`"
+        )]
+        snapshot_dir: Option<PathBuf>,
+    },
+    #[command(
+        about = "Converge a workbook to a desired-state spec in one step, reporting drift",
+        after_long_help = r##"Examples:
+  asp apply-state model.xlsx --spec @desired.json --in-place --snapshot-dir .snapshots
+  asp apply-state model.xlsx --spec @desired.json --dry-run
+  asp apply-state model.xlsx --spec @desired.json --check
+
+Takes the same --spec as plan. Computes the converging ops and applies them as apply-plan would,
+so the mode flags, --verify-with, --audit-log, and --snapshot-dir behave the same.
+
+Machine contract:
+  - converged is true when the workbook already matched the spec; nothing is written then
+  - drift reports what differed before this run: cells_to_write, cells_to_clear, cells_to_restyle,
+    and per-cell changes (first 200, with changes_truncated)
+  - apply is the apply-plan response for the ops that were applied; applied is true once they are written
+  - --check only reports drift and sets passed; it exits 2 when the workbook has drifted"##
+    )]
+    ApplyState {
+        #[arg(value_name = "FILE", help = "Workbook path to converge")]
+        file: PathBuf,
+        #[arg(
+            long,
+            value_name = "SPEC_REF",
+            help = "Desired-state spec file reference (@path)"
+        )]
+        spec: String,
+        #[arg(
+            long,
+            conflicts_with_all = [
+                "dry_run",
+                "in_place",
+                "output",
+                "force",
+                "verify_with",
+                "audit_log",
+                "snapshot_dir",
+            ],
+            help = "Only report drift without writing; exit 2 if the workbook differs from the spec"
+        )]
+        check: bool,
+        #[arg(
+            long,
+            help = "Apply the converging ops to a scratch copy without mutating files"
+        )]
+        dry_run: bool,
+        #[arg(long, help = "Apply by atomically replacing the source file")]
+        in_place: bool,
+        #[arg(long, value_name = "PATH", help = "Apply to this output path")]
+        output: Option<PathBuf>,
+        #[arg(long, help = "Allow overwriting --output when it already exists")]
+        force: bool,
+        #[arg(
+            long = "verify-with",
+            value_name = "TESTS_JSON",
+            help = "Recalculate a copy of the staged result and check these expectations before writing; fail without writing on any mismatch"
+        )]
+        verify_with: Option<PathBuf>,
+        #[arg(
+            long = "audit-log",
+            value_name = "PATH",
+            help = "Append a JSONL record of the applied write (command, spec hash, changed cells, before/after hashes) to this file"
+        )]
+        audit_log: Option<PathBuf>,
+        #[arg(
+            long = "snapshot-dir",
+            value_name = "DIR",
+            help = "With --in-place, copy the workbook into this directory (or s3:// / gs:// prefix, with the object-store feature) before replacing it; undo with `restore`"
+        )]
+        snapshot_dir: Option<PathBuf>,
+    },
     #[command(
         about = "SheetPort manifest lifecycle and execution commands",
         after_long_help = "Examples:\n  agent-spreadsheet sheetport manifest candidates model.xlsx\n  agent-spreadsheet sheetport manifest validate manifest.yaml\n  agent-spreadsheet sheetport bind-check model.xlsx manifest.yaml\n  agent-spreadsheet sheetport run model.xlsx manifest.yaml --inputs @inputs.json"
@@ -2107,40 +4156,110 @@ Formula parse policy:
         replace: String,
         #[arg(
             long,
-            value_name = "RANGE",
-            help = "Optional A1 range to scope replacement (default: used range)"
+            value_name = "RANGE",
+            help = "Optional A1 range to scope replacement (default: used range)"
+        )]
+        range: Option<String>,
+        #[arg(long, help = "Interpret --find as a regular expression")]
+        regex: bool,
+        #[arg(long, help = "Case-sensitive matching (default: true)")]
+        case_sensitive: Option<bool>,
+        #[arg(long, help = "Validate ops and report summary without mutating files")]
+        dry_run: bool,
+        #[arg(
+            long,
+            help = "Apply replacement by atomically replacing the source file"
+        )]
+        in_place: bool,
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "Apply replacement to this output path"
+        )]
+        output: Option<PathBuf>,
+        #[arg(long, help = "Allow overwriting --output when it already exists")]
+        force: bool,
+        #[arg(
+            long = "formula-parse-policy",
+            value_enum,
+            value_name = "POLICY",
+            help = "Formula parse policy: warn (default), fail, or off"
+        )]
+        formula_parse_policy: Option<FormulaParsePolicy>,
+    },
+    #[command(
+        about = "Propose and apply repairs for #REF! references from a baseline workbook",
+        after_long_help = r#"Examples:
+  agent-spreadsheet repair-refs model.xlsx --baseline model-before.xlsx --dry-run
+  agent-spreadsheet repair-refs model.xlsx --snapshot-dir .snapshots --dry-run
+  agent-spreadsheet repair-refs model.xlsx --snapshot-dir .snapshots --snapshot 20260301T101500123Z-3f2a9c1e --in-place
+  agent-spreadsheet repair-refs model.xlsx --baseline model-before.xlsx --sheet Calc --min-confidence low --output repaired.xlsx
+
+Mode selection:
+  Choose exactly one of --dry-run, --in-place, or --output <PATH>.
+  --dry-run lists proposals without writing.
+
+Baseline:
+  --baseline <PATH> names an earlier copy of the workbook.
+  --snapshot-dir <DIR> uses the newest snapshot of FILE taken by --snapshot-dir, or --snapshot <ID>.
+  With --in-place, --snapshot-dir also snapshots FILE before the repair, so it can be undone with restore.
+
+Matching:
+  Each formula containing #REF! is matched to a formula on the same sheet of the baseline, and each #REF!
+  token is replaced with the reference at the same position. Confidence:
+    high   => same address, identical apart from #REF!
+    medium => the only other baseline formula identical apart from #REF!
+    low    => the only baseline formula with the same shape; its other references differ
+  Only proposals at or above --min-confidence (default medium) are applied. A restored reference whose
+  sheet no longer exists is reported with a reason instead of a repair. Review low-confidence proposals:
+  a reference to deleted rows points at whatever now occupies them."#
+    )]
+    RepairRefs {
+        #[arg(value_name = "FILE", help = "Workbook path to repair")]
+        file: PathBuf,
+        #[arg(
+            long,
+            value_name = "SHEET",
+            help = "Only repair formulas on this sheet"
+        )]
+        sheet: Option<String>,
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "Earlier copy of the workbook to restore references from"
         )]
-        range: Option<String>,
-        #[arg(long, help = "Interpret --find as a regular expression")]
-        regex: bool,
-        #[arg(long, help = "Case-sensitive matching (default: true)")]
-        case_sensitive: Option<bool>,
-        #[arg(long, help = "Validate ops and report summary without mutating files")]
-        dry_run: bool,
+        baseline: Option<PathBuf>,
         #[arg(
-            long,
-            help = "Apply replacement by atomically replacing the source file"
+            long = "snapshot-dir",
+            value_name = "DIR",
+            help = "Take the baseline from this snapshot directory (and snapshot FILE before --in-place)"
         )]
-        in_place: bool,
+        snapshot_dir: Option<PathBuf>,
         #[arg(
             long,
-            value_name = "PATH",
-            help = "Apply replacement to this output path"
+            value_name = "ID",
+            help = "Snapshot id to use as the baseline (default: newest snapshot of FILE)"
         )]
-        output: Option<PathBuf>,
-        #[arg(long, help = "Allow overwriting --output when it already exists")]
-        force: bool,
+        snapshot: Option<String>,
         #[arg(
-            long = "formula-parse-policy",
+            long = "min-confidence",
             value_enum,
-            value_name = "POLICY",
-            help = "Formula parse policy: warn (default), fail, or off"
+            default_value = "medium",
+            help = "Lowest proposal confidence to apply"
         )]
-        formula_parse_policy: Option<FormulaParsePolicy>,
+        min_confidence: RefRepairConfidenceArg,
+        #[arg(long, help = "List proposed repairs without mutating files")]
+        dry_run: bool,
+        #[arg(long, help = "Apply repairs by atomically replacing the source file")]
+        in_place: bool,
+        #[arg(long, value_name = "PATH", help = "Apply repairs to this output path")]
+        output: Option<PathBuf>,
+        #[arg(long, help = "Allow overwriting --output when it already exists")]
+        force: bool,
     },
     #[command(
         about = "Recalculate workbook formulas",
-        after_long_help = "Examples:\n  asp recalculate data.xlsx\n  asp recalculate data.xlsx --output /tmp/recalced.xlsx\n  asp recalculate data.xlsx --output /tmp/recalced.xlsx --force\n\nDefault (no flags): recalculate the file in-place.\n--output <PATH>: copy source to output, recalculate the copy, leave source unchanged.\n--force: allow overwriting an existing --output file."
+        after_long_help = "Examples:\n  asp recalculate data.xlsx\n  asp recalculate data.xlsx --output /tmp/recalced.xlsx\n  asp recalculate data.xlsx --output /tmp/recalced.xlsx --force\n  asp recalculate data.xlsx --engine libreoffice\n  asp recalculate data.xlsx --cell Sheet1!B2 --scope dependents\n  asp recalculate data.xlsx --diff\n  asp recalculate data.xlsx --engine libreoffice --timeout-ms 120000\n\nDefault (no flags): recalculate the file in-place.\n--output <PATH>: copy source to output, recalculate the copy, leave source unchanged.\n--force: allow overwriting an existing --output file.\n--engine: auto (default) uses the built-in engine and falls back to LibreOffice only when the built-in engine is not compiled in; builtin and libreoffice fail with the reason when that engine is unavailable. Without --engine, SPREADSHEET_MCP_RECALC_BACKEND sets the preference. The response backend field reports the engine used.\n--cell/--scope: recalculate only the formula cells upstream (precedents) or downstream (dependents) of one cell, leaving other cached values untouched; requires the built-in engine. The response partial field lists how many formula cells were recalculated and any named ranges, tables, or large ranges the dependency walk could not follow.\n--diff: diff the workbook against a pre-recalc snapshot and list every cell whose cached value changed (old_value -> new_value) under recalc_diff, honoring --ignore-sheets.\n--timeout-ms: stop a full recalculation after this many milliseconds (default 30000). A timeout fails with error code RECALC_TIMEOUT; its details report the engine, the phase that was interrupted, and the phases that finished. Timed-out soffice processes are killed.\n--strategy: how --engine libreoffice drives soffice: fire-and-forget (default) launches one process per recalc. A pooled strategy that reuses one soffice is not implemented yet."
     )]
     Recalculate {
         #[arg(value_name = "FILE", help = "Workbook path to recalculate")]
@@ -2165,6 +4284,44 @@ Formula parse policy:
             help = "Include a summary of cells whose values changed after recalculation"
         )]
         changed_cells: bool,
+        #[arg(
+            long,
+            value_enum,
+            value_name = "ENGINE",
+            help = "Recalc engine: auto, builtin, or libreoffice (default: SPREADSHEET_MCP_RECALC_BACKEND or auto)"
+        )]
+        engine: Option<RecalcEngineArg>,
+        #[arg(
+            long,
+            value_name = "SHEET!CELL",
+            help = "Recalculate only the dependency subtree of this cell (requires --scope)"
+        )]
+        cell: Option<String>,
+        #[arg(
+            long,
+            value_enum,
+            value_name = "SCOPE",
+            help = "Subtree to recalculate from --cell: precedents or dependents"
+        )]
+        scope: Option<TraceDirectionArg>,
+        #[arg(
+            long,
+            help = "Report every cell whose cached value changed (old -> new) after recalculation"
+        )]
+        diff: bool,
+        #[arg(
+            long = "timeout-ms",
+            value_name = "MS",
+            help = "Fail with RECALC_TIMEOUT if a full recalculation takes longer than this (default: 30000)"
+        )]
+        timeout_ms: Option<u64>,
+        #[arg(
+            long,
+            value_enum,
+            value_name = "STRATEGY",
+            help = "soffice strategy for --engine libreoffice (only fire-and-forget today)"
+        )]
+        strategy: Option<RecalcStrategyArg>,
     },
     #[command(
         about = "Compare two workbook states and verify target deltas plus error provenance",
@@ -2201,9 +4358,68 @@ Formula parse policy:
         #[arg(long, help = "Return only target proof output (requires --targets)")]
         targets_only: bool,
     },
+    #[command(
+        about = "Recalculate a workbook copy and check `_tests` sheet or sidecar expectations",
+        after_long_help = "Examples:\n  asp verify run-tests model.xlsx\n  asp verify run-tests model.xlsx --tests model.tests.json\n  asp verify run-tests model.xlsx --no-recalc\n\nExpectation sources:\n  - --tests <PATH>: sidecar JSON {\"tests\":[{\"name\":\"Revenue\",\"cell\":\"Summary!B2\",\"expect\":1200,\"tolerance\":0.01}]}\n  - otherwise a sheet named _tests with header columns name | cell | expect | tolerance\n\nBehavior:\n  - recalculates a temporary copy; the source workbook is never modified\n  - reports pass/fail per expectation plus totals\n  - numbers compare within tolerance (default 1e-9); strings, booleans, and error literals compare exactly\n  - an expect of null (or an empty expect cell) requires the target cell to be empty\n\nExit codes:\n  0 = every expectation passed\n  2 = some expectation failed; the report is still printed to stdout\n  1 = the command itself failed (error envelope on stderr)"
+    )]
+    RunTests {
+        #[arg(value_name = "FILE", help = "Workbook path to test")]
+        file: PathBuf,
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "Sidecar expectations JSON (default: the workbook's _tests sheet)"
+        )]
+        tests: Option<PathBuf>,
+        #[arg(
+            long = "no-recalc",
+            help = "Check cached values without recalculating first"
+        )]
+        no_recalc: bool,
+    },
+    #[command(
+        about = "Lint a workbook against a profile and fail on findings at or above a severity",
+        after_long_help = "Examples:\n  asp verify lint model.xlsx\n  asp verify lint model.xlsx --profile lint.yaml\n  asp verify lint model.xlsx --fail-on warning --sheet Summary\n  asp verify lint model.xlsx --rules-dir lint-rules\n  asp verify lint model.xlsx --format sarif > lint.sarif\n\nRules (default severity):\n  formula_parse_error (error), error_value (error), broken_reference (error),\n  volatile_function (warning), external_link (warning),\n  validation_broken (error), validation_violation (warning)\n\nProfile (YAML or JSON):\n  fail_on: warning\n  rules:\n    volatile_function: info\n    error_value: off\n  exclude_sheets: [Scratch]\n  max_findings: 200\n\nCustom rules (--rules-dir lint-rules):\n  each *.yaml/*.yml/*.json manifest adds a command-backed rule (WASM plugins are\n  not supported) run once per checked sheet:\n    id: negative_amount\n    default_severity: error\n    command: python3 negative_amount.py\n  the command reads {rule, sheet_name, cells} on stdin and prints\n  {\"findings\":[{\"location\":\"B7\",\"message\":\"...\"}]}; profiles and fail_on apply\n\nSARIF output (--format sarif):\n  SARIF 2.1.0 log for code-review tooling; each sheet is an artifact under the\n  WORKBOOK base URI and each cell or validation range is a region (row = line,\n  column = character column). Severity maps error/warning/info to\n  error/warning/note; pass/fail state is in runs[0].properties.\n\nExit codes:\n  0 = no findings at or above the fail-on severity\n  2 = lint failed; the report is still printed to stdout\n  1 = the command itself failed (error envelope on stderr)"
+    )]
+    Lint {
+        #[arg(value_name = "FILE", help = "Workbook path to lint")]
+        file: PathBuf,
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "Lint profile (YAML or JSON) with rule severities, exclusions, and fail_on"
+        )]
+        profile: Option<PathBuf>,
+        #[arg(
+            long = "rules-dir",
+            value_name = "DIR",
+            help = "Directory of manifests adding command-backed lint rules"
+        )]
+        rules_dir: Option<PathBuf>,
+        #[arg(
+            long = "fail-on",
+            value_enum,
+            value_name = "SEVERITY",
+            help = "Lowest severity that fails the run: error (default), warning, info, or never; overrides the profile"
+        )]
+        fail_on: Option<LintFailOn>,
+        #[arg(
+            long,
+            value_name = "SHEET",
+            help = "Limit cell and validation checks to one sheet"
+        )]
+        sheet: Option<String>,
+        #[arg(
+            long,
+            value_enum,
+            default_value_t = LintFormatArg::Json,
+            help = "Report format: json (default) or sarif for code-review annotations"
+        )]
+        format: LintFormatArg,
+    },
     #[command(
         about = "Diff two workbook versions with summary-first, paged details",
-        after_long_help = "Examples:\n  asp diff baseline.xlsx candidate.xlsx\n  asp diff baseline.xlsx candidate.xlsx --details --limit 200 --offset 0\n  asp diff baseline.xlsx candidate.xlsx --sheet \"GL Data\" --range A1:P200\n  asp diff baseline.xlsx candidate.xlsx --exclude-recalc-result\n\nBehavior:\n  - summary output now includes grouped change buckets and subtype counts\n  - recalc_result changes are counted separately from direct edits\n  - --exclude-recalc-result suppresses cached-value churn so direct edits are easier to review"
+        after_long_help = "Examples:\n  asp diff baseline.xlsx candidate.xlsx\n  asp diff baseline.xlsx candidate.xlsx --details --limit 200 --offset 0\n  asp diff baseline.xlsx candidate.xlsx --sheet \"GL Data\" --range A1:P200\n  asp diff baseline.xlsx candidate.xlsx --exclude-recalc-result\n  asp diff baseline.xlsx candidate.xlsx --rel-tolerance 1e-6 --ignore-volatile\n\nBehavior:\n  - summary output now includes grouped change buckets and subtype counts\n  - recalc_result changes are counted separately from direct edits\n  - --exclude-recalc-result suppresses cached-value churn so direct edits are easier to review\n\nWorkbook objects are compared as well as cells:\n  - names: name_added, name_deleted, name_modified (retargeted); sheet-scoped names are matched by sheet name\n  - tables: table_added, table_deleted, table_modified (resized), table_renamed\n  - data validations: validation_added, validation_deleted, validation_modified\n  - conditional formats: conditional_format_added, conditional_format_deleted, conditional_format_modified\n  - all of these are reported under the structural review priority\n\nValue comparison:\n  - numbers match within 1e-9 and text must match exactly by default\n  - --abs-tolerance / --rel-tolerance widen numeric matching; a pair matching either tolerance is unchanged\n  - --ignore-whitespace and --ignore-case normalize text values; formulas are always compared exactly\n  - --ignore-volatile drops result changes of unchanged formulas that call NOW, TODAY, RAND, OFFSET, INDIRECT, etc.; style edits on those cells are still reported\n  - active options are echoed under summary.filters\n\nIgnore file (--ignore-file diff-ignore.json):\n  {\"sheets\":[\"Scratch*\"],\"ranges\":[\"Summary!B1\",\"'Refresh Log'!A1:D500\"],\"styles\":true}\n  - sheets: drop every change on matching sheets (case-insensitive; * and ? wildcards)\n  - ranges: drop cell changes inside these cells or ranges; unqualified ranges apply to every sheet\n  - styles: drop changes that only touch formatting\n  - summary.ignored_change_count reports how many changes the rules dropped"
     )]
     Diff {
         #[arg(value_name = "ORIGINAL", help = "Baseline workbook path")]
@@ -2235,6 +4451,70 @@ Formula parse policy:
             help = "Exclude recalc_result cell changes from summary and details"
         )]
         exclude_recalc_result: bool,
+        #[command(flatten)]
+        compare: DiffCompareArgs,
+        #[arg(
+            long = "ignore-file",
+            value_name = "PATH",
+            help = "JSON ignore rules: {\"sheets\":[...],\"ranges\":[\"Sheet!A1\"],\"styles\":true}"
+        )]
+        ignore_file: Option<PathBuf>,
+        #[arg(
+            long,
+            default_value_t = 200,
+            help = "Page size for --details (1..2000)"
+        )]
+        limit: u32,
+        #[arg(long, default_value_t = 0, help = "Offset for --details pagination")]
+        offset: u32,
+    },
+    #[command(
+        about = "Diff one baseline workbook against several candidates, parsing the baseline once",
+        after_long_help = "Examples:\n  asp diff-many baseline.xlsx scenario_low.xlsx scenario_mid.xlsx scenario_high.xlsx\n  asp diff-many baseline.xlsx scenarios/*.xlsx --sheet Outputs --exclude-recalc-result\n  asp diff-many baseline.xlsx a.xlsx b.xlsx --details --limit 100\n\nBehavior:\n  - each entry of candidates has the same change_count, summary, and (with --details) changes as asp diff\n  - sheet parts are cached by content hash, so the baseline is parsed once and sheets identical across candidates are parsed once\n  - cache reports parts_parsed, parts_reused, and cells_cached for the run\n  - --abs-tolerance, --rel-tolerance, --ignore-whitespace, --ignore-case, and --ignore-volatile compare values as in asp diff\n  - --ignore-file applies the same ignore rules as asp diff to every candidate"
+    )]
+    DiffMany {
+        #[arg(value_name = "ORIGINAL", help = "Baseline workbook path")]
+        original: PathBuf,
+        #[arg(
+            value_name = "CANDIDATE",
+            required = true,
+            num_args = 1..,
+            help = "Candidate workbook paths to compare against the baseline"
+        )]
+        candidates: Vec<PathBuf>,
+        #[arg(long, help = "Limit diff to one sheet name")]
+        sheet: Option<String>,
+        #[arg(
+            long,
+            value_name = "SHEETS",
+            value_delimiter = ',',
+            help = "Limit diff to multiple sheet names (comma-separated)"
+        )]
+        sheets: Option<Vec<String>>,
+        #[arg(
+            long,
+            value_name = "A1_RANGE",
+            help = "Optional A1 range filter (e.g. A1:C100)"
+        )]
+        range: Option<String>,
+        #[arg(
+            long,
+            help = "Include paged change items per candidate; default output is summary-only"
+        )]
+        details: bool,
+        #[arg(
+            long = "exclude-recalc-result",
+            help = "Exclude recalc_result cell changes from summary and details"
+        )]
+        exclude_recalc_result: bool,
+        #[command(flatten)]
+        compare: DiffCompareArgs,
+        #[arg(
+            long = "ignore-file",
+            value_name = "PATH",
+            help = "JSON ignore rules: {\"sheets\":[...],\"ranges\":[\"Sheet!A1\"],\"styles\":true}"
+        )]
+        ignore_file: Option<PathBuf>,
         #[arg(
             long,
             default_value_t = 200,
@@ -2244,6 +4524,43 @@ Formula parse policy:
         #[arg(long, default_value_t = 0, help = "Offset for --details pagination")]
         offset: u32,
     },
+    #[command(
+        about = "Render a range from two workbooks and overlay what changed visually",
+        after_long_help = "Examples:\n  asp visual-diff report_v1.xlsx report_v2.xlsx Summary --output summary-diff.png\n  asp visual-diff before.xlsx after.xlsx Summary --range A1:H40 --output diff.png --dpi 150\n  asp verify visual-diff before.xlsx after.xlsx Dashboard --output diff.png --tolerance 32 --force\n\nRequires LibreOffice (soffice) and a build with recalc-libreoffice; --dpi/--scale also need pdftoppm.\nBoth workbooks render SHEET at the same range (default A1:M40, max 100 rows x 30 columns); the renders are cropped to the union of their content so they stay aligned.\nThe overlay is the modified render faded, with changed pixels tinted red and each changed region outlined.\n\nOutput:\n  - identical: true when no pixel changed beyond --tolerance\n  - changed_pixels / changed_ratio: how much of the image changed\n  - regions: boxes of changed pixels (x, y, width, height in overlay pixels), top to bottom; the first 50 are listed\n  - --tolerance (0-255, default 16) is the per-channel difference ignored as anti-aliasing noise\n\nCached formula values are rendered as-is; recalculate both workbooks first if inputs changed. Use asp diff for the cell-level changes behind a visual change."
+    )]
+    VisualDiff {
+        #[arg(value_name = "ORIGINAL", help = "Original workbook path")]
+        original: PathBuf,
+        #[arg(value_name = "MODIFIED", help = "Modified workbook path")]
+        modified: PathBuf,
+        #[arg(value_name = "SHEET", help = "Sheet to render from both workbooks")]
+        sheet: String,
+        #[arg(
+            long,
+            value_name = "RANGE",
+            help = "A1 range to render (default: A1:M40)"
+        )]
+        range: Option<String>,
+        #[arg(long, value_name = "PATH", help = "Overlay PNG output path")]
+        output: PathBuf,
+        #[arg(long, value_name = "DPI", help = "Rasterization resolution (36-600)")]
+        dpi: Option<u32>,
+        #[arg(
+            long,
+            value_name = "FACTOR",
+            help = "Resolution multiplier applied to --dpi (0.25-4.0)"
+        )]
+        scale: Option<f32>,
+        #[arg(
+            long,
+            value_name = "DELTA",
+            default_value_t = 16,
+            help = "Per-channel pixel difference ignored as rendering noise (0-255)"
+        )]
+        tolerance: u8,
+        #[arg(long, help = "Allow overwriting --output when it already exists")]
+        force: bool,
+    },
     #[command(
         about = "Print canonical JSON schema for a command or payload target",
         after_long_help = "Examples:\n  asp schema transform-batch\n  asp schema structure-batch\n  asp schema session-op transform.write_matrix"
@@ -2266,6 +4583,46 @@ Formula parse policy:
         after_long_help = "Session commands provide event-sourced workbook editing with undo/redo, branching, staged apply, and payload discovery.\n\nWorkflow:\n  1. asp session start --base model.xlsx\n  2. asp example session-op transform.write_matrix\n  3. asp session op --session <id> --ops @edits.json\n  4. asp session apply --session <id> <staged_id>\n  5. asp session materialize --session <id> --output result.xlsx\n\nDiscoverability:\n  • asp schema session-op transform.write_matrix\n  • asp example session-op transform.write_matrix"
     )]
     Session(Box<SessionCommands>),
+    #[cfg(feature = "serve")]
+    #[command(
+        about = "Keep workbooks parsed in memory and answer requests over stdio MCP or local HTTP",
+        after_long_help = "On stdio, serve is an MCP server (newline-delimited JSON-RPC 2.0). Tools:\n  open_workbook        {\"path\"}          parse and keep a workbook resident\n  close_workbook       {\"workbook_id\"}   drop a resident workbook\n  workbook_revision    {\"workbook_id\"}   loaded vs on-disk revision\n  list_open_workbooks  {}                resident workbooks\n  <read tool>          MCP read tools (range_values, read_table, ...) with workbook_id or path in place of workbook_or_fork_id\n\nWith --http, requests are JSON objects {\"id\", \"method\", \"params\"} posted to POST /rpc. Methods: open {\"path\"}, call {\"workbook_id\", \"tool\", \"params\"}, revision, close, list.\n\nReopening a path whose file changed on disk reloads it. The HTTP API has no authentication, so --http only accepts loopback addresses.\n\nExamples:\n  asp serve\n  asp serve --http 127.0.0.1:8790"
+    )]
+    Serve {
+        #[arg(
+            long,
+            value_name = "ADDR",
+            help = "Serve POST /rpc on this loopback address (e.g. 127.0.0.1:8790) instead of stdio"
+        )]
+        http: Option<SocketAddr>,
+    },
+    #[command(
+        about = "Workbook revisions written by --output auto (list, diff, restore)",
+        subcommand,
+        after_long_help = "Write commands that take --output <PATH> accept --output auto, which writes <stem>.rev-<n>.<ext> next to the source and records it in <stem>.revisions.json. Writing from a revision with --output auto produces the next revision of the same history, with that revision as its parent. Revision 0 is the base workbook.\n\nExamples:\n  asp transform-batch model.xlsx --ops @ops.json --output auto\n  asp revisions list model.xlsx\n  asp revisions diff model.xlsx --from 1 --to 2\n  asp revisions restore model.xlsx --revision 1\n\nBehavior:\n  - list reports each revision's parent, command, and whether the file is ok, missing, or modified\n  - diff defaults to the newest revision against its parent\n  - restore keeps the current base workbook as a new revision before replacing it, so it can be undone"
+    )]
+    Revisions(RevisionsCommands),
+    #[command(
+        about = "Remove orphaned fork copies and temp files from the scratch directory",
+        after_long_help = "Servers keep fork working copies, checkpoints, and staged snapshots in a per-process directory under the scratch root and refresh a heartbeat while running; CLI commands put short-lived temp files under <root>/tmp. gc removes process directories whose heartbeat is older than --older-than and temp entries older than --older-than. Other files under the root are never touched.\n\nExamples:\n  asp gc --dry-run\n  asp gc --scratch-dir /var/tmp/spreadsheet-mcp --older-than 3600"
+    )]
+    Gc {
+        #[arg(
+            long,
+            value_name = "DIR",
+            help = "Scratch root to clean (default: $SPREADSHEET_MCP_SCRATCH_DIR or <system temp>/spreadsheet-mcp)"
+        )]
+        scratch_dir: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "SECONDS",
+            default_value_t = 900,
+            help = "Treat scratch untouched for this long as orphaned"
+        )]
+        older_than: u64,
+        #[arg(long, help = "List what would be removed without deleting anything")]
+        dry_run: bool,
+    },
     #[command(
         about = "[Deprecated] Execute a SheetPort manifest with JSON inputs",
         after_long_help = "Use `agent-spreadsheet sheetport run ...` for new workflows.\n\nExamples:\n  agent-spreadsheet run-manifest data.xlsx manifest.yaml --inputs '{\"loan\": 10000}'\n  agent-spreadsheet sheetport run data.xlsx manifest.yaml --inputs @inputs.json"
@@ -2311,12 +4668,21 @@ pub async fn run_command(command: Commands) -> Result<Value> {
             ranges,
             format,
             include_formulas,
+            include_formatting,
             session,
             session_workspace,
         } => {
             let (resolved, _guard) =
                 commands::read::resolve_file_or_session(file, session, session_workspace)?;
-            commands::read::range_values(resolved, sheet, ranges, format, include_formulas).await
+            commands::read::range_values(
+                resolved,
+                sheet,
+                ranges,
+                format,
+                include_formulas,
+                include_formatting,
+            )
+            .await
         }
         Commands::RangeExport {
             file,
@@ -2325,13 +4691,24 @@ pub async fn run_command(command: Commands) -> Result<Value> {
             format,
             output,
             include_formulas,
+            include_formatting,
+            csv,
             session,
             session_workspace,
         } => {
             let (resolved, _guard) =
                 commands::read::resolve_file_or_session(file, session, session_workspace)?;
-            commands::read::range_export(resolved, sheet, range, format, output, include_formulas)
-                .await
+            commands::read::range_export(
+                resolved,
+                sheet,
+                range,
+                format,
+                output,
+                include_formulas,
+                include_formatting,
+                csv,
+            )
+            .await
         }
         Commands::RangeImport {
             file,
@@ -2345,6 +4722,9 @@ pub async fn run_command(command: Commands) -> Result<Value> {
             in_place,
             output,
             force,
+            verify_with,
+            audit_log,
+            snapshot_dir,
         } => {
             commands::write::range_import(
                 file,
@@ -2358,6 +4738,33 @@ pub async fn run_command(command: Commands) -> Result<Value> {
                 in_place,
                 output,
                 force,
+                verify_with,
+                audit_log,
+                snapshot_dir,
+            )
+            .await
+        }
+        Commands::WriteCells {
+            file,
+            payload,
+            dry_run,
+            in_place,
+            output,
+            force,
+            verify_with,
+            audit_log,
+            snapshot_dir,
+        } => {
+            commands::write::write_cells(
+                file,
+                payload,
+                dry_run,
+                in_place,
+                output,
+                force,
+                verify_with,
+                audit_log,
+                snapshot_dir,
             )
             .await
         }
@@ -2381,10 +4788,15 @@ pub async fn run_command(command: Commands) -> Result<Value> {
             page_size,
             columns,
             columns_by_header,
+            header_match,
+            header_match_threshold,
             include_formulas,
             include_styles,
             include_header,
             format,
+            orientation,
+            start_column,
+            date_handling,
             session,
             session_workspace,
         } => {
@@ -2397,6 +4809,33 @@ pub async fn run_command(command: Commands) -> Result<Value> {
                 page_size,
                 columns,
                 columns_by_header,
+                header_match,
+                header_match_threshold,
+                include_formulas,
+                include_styles,
+                include_header,
+                format,
+                orientation,
+                start_column,
+                date_handling,
+            )
+            .await
+        }
+        Commands::WorkbookPage {
+            file,
+            start_sheet,
+            start_row,
+            page_size,
+            include_formulas,
+            include_styles,
+            include_header,
+            format,
+        } => {
+            commands::read::workbook_page(
+                file,
+                start_sheet,
+                start_row,
+                page_size,
                 include_formulas,
                 include_styles,
                 include_header,
@@ -2404,32 +4843,82 @@ pub async fn run_command(command: Commands) -> Result<Value> {
             )
             .await
         }
-        Commands::ReadTable {
+        Commands::ReadTable {
+            file,
+            sheet,
+            range,
+            table_name,
+            scope,
+            region_id,
+            header_rows,
+            no_header,
+            orientation,
+            limit,
+            offset,
+            sample_mode,
+            filters_json,
+            filters_file,
+            table_format,
+            output,
+            query,
+            queries_file,
+            decimals,
+            rounding,
+            csv,
+            date_handling,
+            session,
+            session_workspace,
+        } => {
+            let named_query = commands::read::load_named_query(
+                query,
+                queries_file,
+                session_workspace.as_deref(),
+            )?;
+            let (resolved, _guard) =
+                commands::read::resolve_file_or_session(file, session, session_workspace)?;
+            commands::read::read_table(
+                resolved,
+                sheet,
+                range,
+                table_name,
+                scope,
+                region_id,
+                header_rows,
+                no_header,
+                orientation,
+                limit,
+                offset,
+                sample_mode,
+                filters_json,
+                filters_file,
+                table_format,
+                output,
+                named_query,
+                decimals,
+                rounding,
+                csv,
+                date_handling,
+            )
+            .await
+        }
+        Commands::Estimate {
             file,
             sheet,
             range,
             table_name,
             region_id,
             limit,
-            offset,
-            sample_mode,
             filters_json,
             filters_file,
             table_format,
-            session,
-            session_workspace,
         } => {
-            let (resolved, _guard) =
-                commands::read::resolve_file_or_session(file, session, session_workspace)?;
-            commands::read::read_table(
-                resolved,
+            commands::read::estimate(
+                file,
                 sheet,
                 range,
                 table_name,
                 region_id,
                 limit,
-                offset,
-                sample_mode,
                 filters_json,
                 filters_file,
                 table_format,
@@ -2442,24 +4931,60 @@ pub async fn run_command(command: Commands) -> Result<Value> {
             sheet,
             mode,
             label_direction,
+            regex,
+            value_type,
+            op,
+            upper,
+            all_sheets,
+            limit,
+            offset,
             session,
             session_workspace,
         } => {
             let (resolved, _guard) =
                 commands::read::resolve_file_or_session(file, session, session_workspace)?;
-            commands::read::find_value(resolved, query, sheet, mode, label_direction).await
+            let comparison = commands::read::find_value_comparison(value_type, op, upper);
+            commands::read::find_value(
+                resolved,
+                query,
+                sheet,
+                mode,
+                label_direction,
+                regex,
+                comparison,
+                all_sheets,
+                limit,
+                offset,
+            )
+            .await
         }
         Commands::NamedRanges {
             file,
             sheet,
             name_prefix,
+            scope,
+            evaluate,
             session,
             session_workspace,
         } => {
             let (resolved, _guard) =
                 commands::read::resolve_file_or_session(file, session, session_workspace)?;
-            commands::read::named_ranges(resolved, sheet, name_prefix).await
+            commands::read::named_ranges(resolved, sheet, name_prefix, scope, evaluate).await
+        }
+        Commands::Metadata {
+            file,
+            namespace,
+            key,
+        } => commands::read::metadata(file, namespace, key).await,
+        Commands::Annotations { file, sheet, range } => {
+            commands::read::annotations(file, sheet, range).await
         }
+        Commands::ListExternalLinks { file } => commands::read::list_external_links(file).await,
+        Commands::ExtractStrings {
+            file,
+            format,
+            output,
+        } => commands::read::extract_strings(file, format, output).await,
         Commands::DefineName {
             file,
             name,
@@ -2530,13 +5055,65 @@ pub async fn run_command(command: Commands) -> Result<Value> {
             )
             .await
         }
+        Commands::SetMetadata {
+            file,
+            namespace,
+            key,
+            value,
+            dry_run,
+            in_place,
+            output,
+            force,
+        } => {
+            commands::write::set_metadata(
+                file, namespace, key, value, dry_run, in_place, output, force,
+            )
+            .await
+        }
+        Commands::ClearMetadata {
+            file,
+            namespace,
+            key,
+            dry_run,
+            in_place,
+            output,
+            force,
+        } => {
+            commands::write::clear_metadata(file, namespace, key, dry_run, in_place, output, force)
+                .await
+        }
+        Commands::Annotate {
+            file,
+            sheet,
+            range,
+            value,
+            remove,
+            dry_run,
+            in_place,
+            output,
+            force,
+        } => {
+            commands::write::annotate(
+                file, sheet, range, value, remove, dry_run, in_place, output, force,
+            )
+            .await
+        }
+        Commands::ApplyStrings {
+            file,
+            strings,
+            dry_run,
+            in_place,
+            output,
+            force,
+        } => commands::write::apply_strings(file, strings, dry_run, in_place, output, force).await,
         Commands::FindFormula {
             file,
             query,
             sheet,
             limit,
             offset,
-        } => commands::read::find_formula(file, query, sheet, limit, offset).await,
+            reference_style,
+        } => commands::read::find_formula(file, query, sheet, limit, offset, reference_style).await,
         Commands::ScanVolatiles {
             file,
             sheet,
@@ -2544,16 +5121,59 @@ pub async fn run_command(command: Commands) -> Result<Value> {
             offset,
             formula_parse_policy,
         } => commands::read::scan_volatiles(file, sheet, limit, offset, formula_parse_policy).await,
-        Commands::SheetStatistics { file, sheet } => {
-            commands::read::sheet_statistics(file, sheet).await
+        Commands::ScanErrors {
+            file,
+            sheet,
+            summary_only,
+            limit,
+            offset,
+        } => commands::read::scan_errors(file, sheet, summary_only, limit, offset).await,
+        Commands::LintFormulas {
+            file,
+            sheet,
+            rules,
+            max_if_depth,
+            summary_only,
+            limit,
+            offset,
+        } => {
+            commands::read::lint_formulas(
+                file,
+                sheet,
+                rules,
+                max_if_depth,
+                summary_only,
+                limit,
+                offset,
+            )
+            .await
         }
+        Commands::SheetStatistics {
+            file,
+            sheet,
+            decimals,
+            rounding,
+        } => commands::read::sheet_statistics(file, sheet, decimals, rounding).await,
         Commands::FormulaMap {
             file,
             sheet,
             limit,
             sort_by,
             formula_parse_policy,
-        } => commands::read::formula_map(file, sheet, limit, sort_by, formula_parse_policy).await,
+            inconsistencies,
+            reference_style,
+        } => {
+            commands::read::formula_map(
+                file,
+                sheet,
+                limit,
+                sort_by,
+                formula_parse_policy,
+                inconsistencies,
+                reference_style,
+            )
+            .await
+        }
         Commands::FormulaTrace {
             file,
             sheet,
@@ -2564,6 +5184,7 @@ pub async fn run_command(command: Commands) -> Result<Value> {
             cursor_depth,
             cursor_offset,
             formula_parse_policy,
+            reference_style,
             session,
             session_workspace,
         } => {
@@ -2579,6 +5200,7 @@ pub async fn run_command(command: Commands) -> Result<Value> {
                 cursor_depth,
                 cursor_offset,
                 formula_parse_policy,
+                reference_style,
             )
             .await
         }
@@ -2594,12 +5216,89 @@ pub async fn run_command(command: Commands) -> Result<Value> {
         Commands::TableProfile {
             file,
             sheet,
+            decimals,
+            rounding,
+            top_n,
+            emit_schema,
+            session,
+            session_workspace,
+        } => {
+            let (resolved, _guard) =
+                commands::read::resolve_file_or_session(file, session, session_workspace)?;
+            commands::read::table_profile(resolved, sheet, decimals, rounding, top_n, emit_schema)
+                .await
+        }
+        Commands::QueryTable {
+            file,
+            sheet,
+            range,
+            table_name,
+            region_id,
+            header_rows,
+            group_by,
+            aggregations,
+            filters_json,
+            filters_file,
+            limit,
+            offset,
+            decimals,
+            rounding,
             session,
             session_workspace,
         } => {
             let (resolved, _guard) =
                 commands::read::resolve_file_or_session(file, session, session_workspace)?;
-            commands::read::table_profile(resolved, sheet).await
+            commands::read::query_table(
+                resolved,
+                sheet,
+                range,
+                table_name,
+                region_id,
+                header_rows,
+                group_by,
+                aggregations,
+                filters_json,
+                filters_file,
+                limit,
+                offset,
+                decimals,
+                rounding,
+            )
+            .await
+        }
+        Commands::MapColumns {
+            source,
+            target,
+            source_sheet,
+            source_range,
+            source_table,
+            source_region_id,
+            target_sheet,
+            target_range,
+            target_table,
+            target_region_id,
+            min_score,
+            output,
+        } => {
+            commands::read::map_columns(
+                commands::read::MapColumnsTableArgs {
+                    file: source,
+                    sheet: source_sheet,
+                    range: source_range,
+                    table_name: source_table,
+                    region_id: source_region_id,
+                },
+                commands::read::MapColumnsTableArgs {
+                    file: target,
+                    sheet: target_sheet,
+                    range: target_range,
+                    table_name: target_table,
+                    region_id: target_region_id,
+                },
+                min_score,
+                output,
+            )
+            .await
         }
         Commands::LayoutPage {
             file,
@@ -2628,12 +5327,102 @@ pub async fn run_command(command: Commands) -> Result<Value> {
             )
             .await
         }
+        Commands::Render {
+            file,
+            sheet,
+            range,
+            output,
+            dpi,
+            scale,
+            force,
+        } => commands::read::render(file, sheet, range, output, dpi, scale, force).await,
+        Commands::ExportPdf {
+            file,
+            sheet,
+            print_area,
+            output,
+            force,
+        } => commands::read::export_pdf(file, sheet, print_area, output, force).await,
+        Commands::DedupeScan {
+            roots,
+            min_similarity,
+        } => commands::read::dedupe_scan(roots, min_similarity).await,
         Commands::CreateWorkbook {
             path,
             sheets,
             overwrite,
         } => commands::write::create_workbook(path, sheets, overwrite).await,
         Commands::Copy { source, dest } => commands::write::copy(source, dest).await,
+        Commands::Instantiate {
+            template,
+            values,
+            output,
+            dry_run,
+            force,
+            strict,
+        } => commands::write::instantiate(template, values, output, dry_run, force, strict).await,
+        Commands::Plan { file, spec, output } => commands::write::plan(file, spec, output).await,
+        Commands::ApplyState {
+            file,
+            spec,
+            check,
+            dry_run,
+            in_place,
+            output,
+            force,
+            verify_with,
+            audit_log,
+            snapshot_dir,
+        } => {
+            commands::write::apply_state(
+                file,
+                spec,
+                check,
+                dry_run,
+                in_place,
+                output,
+                force,
+                verify_with,
+                audit_log,
+                snapshot_dir,
+            )
+            .await
+        }
+        Commands::ApplyPlan {
+            file,
+            plan,
+            dry_run,
+            in_place,
+            output,
+            force,
+            formula_parse_policy,
+            verify_with,
+            audit_log,
+            snapshot_dir,
+        } => {
+            commands::write::apply_plan(
+                file,
+                plan,
+                dry_run,
+                in_place,
+                output,
+                force,
+                formula_parse_policy,
+                verify_with,
+                audit_log,
+                snapshot_dir,
+            )
+            .await
+        }
+        Commands::Restore {
+            file,
+            snapshot,
+            snapshot_dir,
+            force,
+        } => commands::write::restore(file, snapshot, snapshot_dir, force).await,
+        Commands::ListSnapshots { snapshot_dir, file } => {
+            commands::write::list_snapshots(snapshot_dir, file).await
+        }
         Commands::Edit {
             file,
             sheet,
@@ -2644,6 +5433,7 @@ pub async fn run_command(command: Commands) -> Result<Value> {
             edits,
             edits_file,
             formula_parse_policy,
+            locale,
         } => {
             commands::write::edit(
                 file,
@@ -2655,6 +5445,7 @@ pub async fn run_command(command: Commands) -> Result<Value> {
                 output,
                 force,
                 formula_parse_policy,
+                locale,
             )
             .await
         }
@@ -2666,6 +5457,7 @@ pub async fn run_command(command: Commands) -> Result<Value> {
             rows,
             from_csv,
             header,
+            column_map,
             footer_policy,
             dry_run,
             in_place,
@@ -2680,6 +5472,7 @@ pub async fn run_command(command: Commands) -> Result<Value> {
                 rows,
                 from_csv,
                 header,
+                column_map,
                 footer_policy,
                 dry_run,
                 in_place,
@@ -2688,6 +5481,59 @@ pub async fn run_command(command: Commands) -> Result<Value> {
             )
             .await
         }
+        Commands::Materialize {
+            file,
+            target_sheet,
+            anchor,
+            sheet,
+            range,
+            table_name,
+            region_id,
+            filters_json,
+            filters_file,
+            limit,
+            query,
+            queries_file,
+            refresh,
+            new_workbook,
+            dry_run,
+            in_place,
+            output,
+            force,
+        } => {
+            let named_query = commands::read::load_named_query(query, queries_file, None)?;
+            let filters = commands::read::parse_table_filters(filters_json, filters_file)?;
+            commands::write::materialize(
+                file,
+                target_sheet,
+                anchor,
+                crate::tools::ReadTableParams {
+                    sheet_name: sheet,
+                    range,
+                    table_name,
+                    region_id,
+                    filters,
+                    limit,
+                    ..Default::default()
+                },
+                named_query,
+                refresh,
+                new_workbook,
+                dry_run,
+                in_place,
+                output,
+                force,
+            )
+            .await
+        }
+        Commands::Refresh {
+            file,
+            sheets,
+            dry_run,
+            in_place,
+            output,
+            force,
+        } => commands::write::refresh(file, sheets, dry_run, in_place, output, force).await,
         Commands::CloneTemplateRow {
             file,
             sheet_name,
@@ -2763,8 +5609,13 @@ pub async fn run_command(command: Commands) -> Result<Value> {
             in_place,
             output,
             force,
+            verify_with,
+            audit_log,
+            snapshot_dir,
+            continue_on_error,
             print_schema,
             formula_parse_policy,
+            differential_save,
         } => {
             if print_schema {
                 commands::write::batch_payload_schema(
@@ -2785,6 +5636,11 @@ pub async fn run_command(command: Commands) -> Result<Value> {
                     output,
                     force,
                     formula_parse_policy,
+                    verify_with,
+                    audit_log,
+                    snapshot_dir,
+                    continue_on_error,
+                    differential_save,
                 )
                 .await
             }
@@ -2796,6 +5652,10 @@ pub async fn run_command(command: Commands) -> Result<Value> {
             in_place,
             output,
             force,
+            verify_with,
+            audit_log,
+            snapshot_dir,
+            continue_on_error,
             print_schema,
         } => {
             if print_schema {
@@ -2807,7 +5667,19 @@ pub async fn run_command(command: Commands) -> Result<Value> {
                 let ops = ops.ok_or_else(|| {
                     anyhow::anyhow!("invalid argument: style-batch requires --ops @<path>")
                 })?;
-                commands::write::style_batch(file, ops, dry_run, in_place, output, force).await
+                commands::write::style_batch(
+                    file,
+                    ops,
+                    dry_run,
+                    in_place,
+                    output,
+                    force,
+                    verify_with,
+                    audit_log,
+                    snapshot_dir,
+                    continue_on_error,
+                )
+                .await
             }
         }
         Commands::ApplyFormulaPattern {
@@ -2817,6 +5689,10 @@ pub async fn run_command(command: Commands) -> Result<Value> {
             in_place,
             output,
             force,
+            verify_with,
+            audit_log,
+            snapshot_dir,
+            continue_on_error,
             print_schema,
         } => {
             if print_schema {
@@ -2832,8 +5708,19 @@ pub async fn run_command(command: Commands) -> Result<Value> {
                         "invalid argument: apply-formula-pattern requires --ops @<path>"
                     )
                 })?;
-                commands::write::apply_formula_pattern(file, ops, dry_run, in_place, output, force)
-                    .await
+                commands::write::apply_formula_pattern(
+                    file,
+                    ops,
+                    dry_run,
+                    in_place,
+                    output,
+                    force,
+                    verify_with,
+                    audit_log,
+                    snapshot_dir,
+                    continue_on_error,
+                )
+                .await
             }
         }
         Commands::StructureBatch {
@@ -2843,6 +5730,10 @@ pub async fn run_command(command: Commands) -> Result<Value> {
             in_place,
             output,
             force,
+            verify_with,
+            audit_log,
+            snapshot_dir,
+            continue_on_error,
             print_schema,
             formula_parse_policy,
             impact_report,
@@ -2850,56 +5741,112 @@ pub async fn run_command(command: Commands) -> Result<Value> {
         } => {
             if print_schema {
                 commands::write::batch_payload_schema(
-                    commands::write::BatchSchemaCommand::Structure,
+                    commands::write::BatchSchemaCommand::Structure,
+                )
+            } else {
+                let file = file.ok_or_else(|| {
+                    anyhow::anyhow!("invalid argument: structure-batch requires <FILE>")
+                })?;
+                let ops = ops.ok_or_else(|| {
+                    anyhow::anyhow!("invalid argument: structure-batch requires --ops @<path>")
+                })?;
+                commands::write::structure_batch(
+                    file,
+                    ops,
+                    dry_run,
+                    in_place,
+                    output,
+                    force,
+                    formula_parse_policy,
+                    impact_report,
+                    show_formula_delta,
+                    verify_with,
+                    audit_log,
+                    snapshot_dir,
+                    continue_on_error,
+                )
+                .await
+            }
+        }
+        Commands::CheckRefImpact {
+            file,
+            ops,
+            show_formula_delta,
+        } => commands::write::check_ref_impact(file, ops, show_formula_delta).await,
+        Commands::ColumnSizeBatch {
+            file,
+            ops,
+            dry_run,
+            in_place,
+            output,
+            force,
+            verify_with,
+            audit_log,
+            snapshot_dir,
+            continue_on_error,
+            print_schema,
+        } => {
+            if print_schema {
+                commands::write::batch_payload_schema(
+                    commands::write::BatchSchemaCommand::ColumnSize,
                 )
             } else {
                 let file = file.ok_or_else(|| {
-                    anyhow::anyhow!("invalid argument: structure-batch requires <FILE>")
+                    anyhow::anyhow!("invalid argument: column-size-batch requires <FILE>")
                 })?;
                 let ops = ops.ok_or_else(|| {
-                    anyhow::anyhow!("invalid argument: structure-batch requires --ops @<path>")
+                    anyhow::anyhow!("invalid argument: column-size-batch requires --ops @<path>")
                 })?;
-                commands::write::structure_batch(
+                commands::write::column_size_batch(
                     file,
                     ops,
                     dry_run,
                     in_place,
                     output,
                     force,
-                    formula_parse_policy,
-                    impact_report,
-                    show_formula_delta,
+                    verify_with,
+                    audit_log,
+                    snapshot_dir,
+                    continue_on_error,
                 )
                 .await
             }
         }
-        Commands::CheckRefImpact {
-            file,
-            ops,
-            show_formula_delta,
-        } => commands::write::check_ref_impact(file, ops, show_formula_delta).await,
-        Commands::ColumnSizeBatch {
+        Commands::RowSizeBatch {
             file,
             ops,
             dry_run,
             in_place,
             output,
             force,
+            verify_with,
+            audit_log,
+            snapshot_dir,
+            continue_on_error,
             print_schema,
         } => {
             if print_schema {
-                commands::write::batch_payload_schema(
-                    commands::write::BatchSchemaCommand::ColumnSize,
-                )
+                commands::write::batch_payload_schema(commands::write::BatchSchemaCommand::RowSize)
             } else {
                 let file = file.ok_or_else(|| {
-                    anyhow::anyhow!("invalid argument: column-size-batch requires <FILE>")
+                    anyhow::anyhow!("invalid argument: row-size-batch requires <FILE>")
                 })?;
                 let ops = ops.ok_or_else(|| {
-                    anyhow::anyhow!("invalid argument: column-size-batch requires --ops @<path>")
+                    anyhow::anyhow!("invalid argument: row-size-batch requires --ops @<path>")
                 })?;
-                commands::write::column_size_batch(file, ops, dry_run, in_place, output, force)
-                    .await
+                commands::write::row_size_batch(
+                    file,
+                    ops,
+                    dry_run,
+                    in_place,
+                    output,
+                    force,
+                    verify_with,
+                    audit_log,
+                    snapshot_dir,
+                    continue_on_error,
+                )
+                .await
             }
         }
         Commands::SheetLayoutBatch {
@@ -2909,6 +5856,10 @@ pub async fn run_command(command: Commands) -> Result<Value> {
             in_place,
             output,
             force,
+            verify_with,
+            audit_log,
+            snapshot_dir,
+            continue_on_error,
             print_schema,
         } => {
             if print_schema {
@@ -2922,8 +5873,19 @@ pub async fn run_command(command: Commands) -> Result<Value> {
                 let ops = ops.ok_or_else(|| {
                     anyhow::anyhow!("invalid argument: sheet-layout-batch requires --ops @<path>")
                 })?;
-                commands::write::sheet_layout_batch(file, ops, dry_run, in_place, output, force)
-                    .await
+                commands::write::sheet_layout_batch(
+                    file,
+                    ops,
+                    dry_run,
+                    in_place,
+                    output,
+                    force,
+                    verify_with,
+                    audit_log,
+                    snapshot_dir,
+                    continue_on_error,
+                )
+                .await
             }
         }
         Commands::RulesBatch {
@@ -2933,6 +5895,10 @@ pub async fn run_command(command: Commands) -> Result<Value> {
             in_place,
             output,
             force,
+            verify_with,
+            audit_log,
+            snapshot_dir,
+            continue_on_error,
             print_schema,
             formula_parse_policy,
         } => {
@@ -2953,6 +5919,49 @@ pub async fn run_command(command: Commands) -> Result<Value> {
                     output,
                     force,
                     formula_parse_policy,
+                    verify_with,
+                    audit_log,
+                    snapshot_dir,
+                    continue_on_error,
+                )
+                .await
+            }
+        }
+        Commands::PropertiesBatch {
+            file,
+            ops,
+            dry_run,
+            in_place,
+            output,
+            force,
+            verify_with,
+            audit_log,
+            snapshot_dir,
+            continue_on_error,
+            print_schema,
+        } => {
+            if print_schema {
+                commands::write::batch_payload_schema(
+                    commands::write::BatchSchemaCommand::Properties,
+                )
+            } else {
+                let file = file.ok_or_else(|| {
+                    anyhow::anyhow!("invalid argument: properties-batch requires <FILE>")
+                })?;
+                let ops = ops.ok_or_else(|| {
+                    anyhow::anyhow!("invalid argument: properties-batch requires --ops @<path>")
+                })?;
+                commands::write::properties_batch(
+                    file,
+                    ops,
+                    dry_run,
+                    in_place,
+                    output,
+                    force,
+                    verify_with,
+                    audit_log,
+                    snapshot_dir,
+                    continue_on_error,
                 )
                 .await
             }
@@ -3014,13 +6023,60 @@ pub async fn run_command(command: Commands) -> Result<Value> {
             )
             .await
         }
+        Commands::RepairRefs {
+            file,
+            sheet,
+            baseline,
+            snapshot_dir,
+            snapshot,
+            min_confidence,
+            dry_run,
+            in_place,
+            output,
+            force,
+        } => {
+            commands::write::repair_refs(
+                file,
+                sheet,
+                baseline,
+                snapshot_dir,
+                snapshot,
+                min_confidence,
+                dry_run,
+                in_place,
+                output,
+                force,
+            )
+            .await
+        }
         Commands::Recalculate {
             file,
             output,
             force,
             ignore_sheets,
             changed_cells,
-        } => commands::recalc::recalculate(file, output, force, ignore_sheets, changed_cells).await,
+            engine,
+            cell,
+            scope,
+            diff,
+            timeout_ms,
+            strategy,
+        } => {
+            commands::recalc::recalculate(
+                file,
+                output,
+                force,
+                ignore_sheets,
+                changed_cells,
+                engine.map(Into::into),
+                cell,
+                scope,
+                diff,
+                timeout_ms,
+                strategy.map(Into::into),
+            )
+            .await
+        }
         Commands::Verify {
             baseline,
             current,
@@ -3041,6 +6097,19 @@ pub async fn run_command(command: Commands) -> Result<Value> {
             )
             .await
         }
+        Commands::RunTests {
+            file,
+            tests,
+            no_recalc,
+        } => commands::verify::run_tests(file, tests, no_recalc).await,
+        Commands::Lint {
+            file,
+            profile,
+            rules_dir,
+            fail_on,
+            sheet,
+            format,
+        } => commands::verify::lint(file, profile, rules_dir, fail_on, sheet, format).await,
         Commands::Diff {
             original,
             modified,
@@ -3051,6 +6120,8 @@ pub async fn run_command(command: Commands) -> Result<Value> {
             limit,
             offset,
             exclude_recalc_result,
+            compare,
+            ignore_file,
         } => {
             commands::diff::diff(commands::diff::DiffCommandArgs {
                 original,
@@ -3062,11 +6133,96 @@ pub async fn run_command(command: Commands) -> Result<Value> {
                 limit,
                 offset,
                 exclude_recalc_result,
+                compare: compare.into(),
+                ignore_file,
+            })
+            .await
+        }
+        Commands::DiffMany {
+            original,
+            candidates,
+            sheet,
+            sheets,
+            range,
+            details,
+            limit,
+            offset,
+            exclude_recalc_result,
+            compare,
+            ignore_file,
+        } => {
+            commands::diff::diff_many(commands::diff::DiffManyCommandArgs {
+                original,
+                candidates,
+                sheet,
+                sheets,
+                range,
+                details,
+                limit,
+                offset,
+                exclude_recalc_result,
+                compare: compare.into(),
+                ignore_file,
             })
             .await
         }
+        Commands::VisualDiff {
+            original,
+            modified,
+            sheet,
+            range,
+            output,
+            dpi,
+            scale,
+            tolerance,
+            force,
+        } => {
+            commands::read::visual_diff(
+                original, modified, sheet, range, output, dpi, scale, tolerance, force,
+            )
+            .await
+        }
         Commands::Schema { command } => run_schema_command(command),
         Commands::Example { command } => run_example_command(command),
+        #[cfg(feature = "serve")]
+        Commands::Serve { http } => commands::serve::serve(http).await,
+        Commands::Gc {
+            scratch_dir,
+            older_than,
+            dry_run,
+        } => commands::gc::gc(scratch_dir, older_than, dry_run).await,
+        Commands::Revisions(command) => match command {
+            RevisionsCommands::List { file } => commands::revisions::list_revisions(file).await,
+            RevisionsCommands::Diff {
+                file,
+                from,
+                to,
+                details,
+                exclude_recalc_result,
+                compare,
+                ignore_file,
+                limit,
+                offset,
+            } => {
+                commands::revisions::diff_revisions(commands::revisions::DiffRevisionsArgs {
+                    file,
+                    from,
+                    to,
+                    details,
+                    limit,
+                    offset,
+                    exclude_recalc_result,
+                    compare: compare.into(),
+                    ignore_file,
+                })
+                .await
+            }
+            RevisionsCommands::Restore {
+                file,
+                revision,
+                force,
+            } => commands::write::restore_revision(file, revision, force).await,
+        },
         Commands::Session(command) => match *command {
             SessionCommands::Start {
                 base,
@@ -3151,12 +6307,18 @@ fn run_schema_command(command: DiscoverabilityCommands) -> Result<Value> {
         DiscoverabilityCommands::ColumnSizeBatch => {
             commands::write::batch_payload_schema(commands::write::BatchSchemaCommand::ColumnSize)
         }
+        DiscoverabilityCommands::RowSizeBatch => {
+            commands::write::batch_payload_schema(commands::write::BatchSchemaCommand::RowSize)
+        }
         DiscoverabilityCommands::SheetLayoutBatch => {
             commands::write::batch_payload_schema(commands::write::BatchSchemaCommand::SheetLayout)
         }
         DiscoverabilityCommands::RulesBatch => {
             commands::write::batch_payload_schema(commands::write::BatchSchemaCommand::Rules)
         }
+        DiscoverabilityCommands::PropertiesBatch => {
+            commands::write::batch_payload_schema(commands::write::BatchSchemaCommand::Properties)
+        }
         DiscoverabilityCommands::SessionOp { kind } => {
             commands::session::session_payload_schema(kind)
         }
@@ -3180,12 +6342,18 @@ fn run_example_command(command: DiscoverabilityCommands) -> Result<Value> {
         DiscoverabilityCommands::ColumnSizeBatch => {
             commands::write::batch_payload_example(commands::write::BatchSchemaCommand::ColumnSize)
         }
+        DiscoverabilityCommands::RowSizeBatch => {
+            commands::write::batch_payload_example(commands::write::BatchSchemaCommand::RowSize)
+        }
         DiscoverabilityCommands::SheetLayoutBatch => {
             commands::write::batch_payload_example(commands::write::BatchSchemaCommand::SheetLayout)
         }
         DiscoverabilityCommands::RulesBatch => {
             commands::write::batch_payload_example(commands::write::BatchSchemaCommand::Rules)
         }
+        DiscoverabilityCommands::PropertiesBatch => {
+            commands::write::batch_payload_example(commands::write::BatchSchemaCommand::Properties)
+        }
         DiscoverabilityCommands::SessionOp { kind } => {
             commands::session::session_payload_example(kind)
         }
@@ -3208,7 +6376,7 @@ fn first_subcommand_index(argv: &[OsString]) -> Option<usize> {
                 expect_global_value = true;
                 continue;
             }
-            "--compact" | "--quiet" => continue,
+            "--compact" | "--quiet" | "--timings" | "--canonical-json" => continue,
             _ => {}
         }
 
@@ -3247,12 +6415,18 @@ fn normalize_legacy_global_format_argv(argv: Vec<OsString>) -> Vec<OsString> {
             .map(|value| value.to_string_lossy().into_owned())
     });
     let preserve_sheet_page_format = first_subcommand_name == "sheet-page"
+        || first_subcommand_name == "workbook-page"
         || first_subcommand_name == "range-export"
         || first_subcommand_name == "range-values"
+        || first_subcommand_name == "extract-strings"
         || (first_subcommand_name == "read"
             && matches!(
                 second_subcommand_name.as_deref(),
-                Some("page") | Some("export") | Some("values")
+                Some("page")
+                    | Some("workbook-page")
+                    | Some("export")
+                    | Some("values")
+                    | Some("strings")
             ));
 
     let mut normalized = Vec::with_capacity(argv.len());
@@ -3307,39 +6481,70 @@ fn flat_to_canonical_command(flat: &str) -> Option<&'static str> {
         "range-export" => Some("read export"),
         "inspect-cells" => Some("read cells"),
         "sheet-page" => Some("read page"),
+        "workbook-page" => Some("read workbook-page"),
         "read-table" => Some("read table"),
+        "estimate" => Some("read estimate"),
         "named-ranges" => Some("read names"),
+        "metadata" => Some("read metadata"),
+        "annotations" => Some("read annotations"),
+        "list-external-links" => Some("read external-links"),
+        "extract-strings" => Some("read strings"),
         "describe" => Some("read workbook"),
         "layout-page" => Some("read layout"),
+        "render" => Some("read render"),
         "find-value" => Some("analyze find-value"),
         "find-formula" => Some("analyze find-formula"),
         "formula-map" => Some("analyze formula-map"),
         "formula-trace" => Some("analyze formula-trace"),
         "scan-volatiles" => Some("analyze scan-volatiles"),
+        "scan-errors" => Some("analyze scan-errors"),
+        "lint-formulas" => Some("analyze lint-formulas"),
         "sheet-statistics" => Some("analyze sheet-statistics"),
         "table-profile" => Some("analyze table-profile"),
+        "query-table" => Some("analyze query-table"),
+        "map-columns" => Some("analyze map-columns"),
         "check-ref-impact" => Some("analyze ref-impact"),
+        "plan" => Some("analyze plan"),
         "edit" => Some("write cells"),
         "range-import" => Some("write import"),
+        "write-cells" => Some("write bulk"),
         "append-region" => Some("write append"),
+        "materialize" => Some("write materialize"),
+        "refresh" => Some("write refresh"),
         "clone-template-row" => Some("write clone-template-row"),
         "clone-row-band" => Some("write clone-row-band"),
         "replace-in-formulas" => Some("write formulas replace"),
+        "repair-refs" => Some("write formulas repair"),
         "transform-batch" => Some("write batch transform"),
         "style-batch" => Some("write batch style"),
         "apply-formula-pattern" => Some("write batch formula-pattern"),
         "structure-batch" => Some("write batch structure"),
         "column-size-batch" => Some("write batch column-size"),
+        "row-size-batch" => Some("write batch row-size"),
         "sheet-layout-batch" => Some("write batch sheet-layout"),
         "rules-batch" => Some("write batch rules"),
+        "properties-batch" => Some("write batch properties"),
+        "apply-plan" => Some("write plan"),
+        "apply-state" => Some("write state"),
         "define-name" => Some("write name define"),
         "update-name" => Some("write name update"),
         "delete-name" => Some("write name delete"),
+        "set-metadata" => Some("write metadata set"),
+        "clear-metadata" => Some("write metadata clear"),
+        "annotate" => Some("write annotate"),
+        "apply-strings" => Some("write strings"),
         "create-workbook" => Some("workbook create"),
         "copy" => Some("workbook copy"),
+        "instantiate" => Some("workbook instantiate"),
+        "restore" => Some("workbook restore"),
+        "list-snapshots" => Some("workbook snapshots"),
         "recalculate" => Some("workbook recalculate"),
+        "export-pdf" => Some("workbook export-pdf"),
+        "dedupe-scan" => Some("workbook dedupe-scan"),
         "verify" => Some("verify proof"),
         "diff" => Some("verify diff"),
+        "diff-many" => Some("verify diff-many"),
+        "visual-diff" => Some("verify visual-diff"),
         "run-manifest" => Some("sheetport run"),
         _ => None,
     }
@@ -3353,39 +6558,70 @@ fn flat_to_nested_tokens(flat: &str) -> Option<&'static [&'static str]> {
         "range-export" => Some(&["read", "export"]),
         "inspect-cells" => Some(&["read", "cells"]),
         "sheet-page" => Some(&["read", "page"]),
+        "workbook-page" => Some(&["read", "workbook-page"]),
         "read-table" => Some(&["read", "table"]),
+        "estimate" => Some(&["read", "estimate"]),
         "named-ranges" => Some(&["read", "names"]),
+        "metadata" => Some(&["read", "metadata"]),
+        "annotations" => Some(&["read", "annotations"]),
+        "list-external-links" => Some(&["read", "external-links"]),
+        "extract-strings" => Some(&["read", "strings"]),
         "describe" => Some(&["read", "workbook"]),
         "layout-page" => Some(&["read", "layout"]),
+        "render" => Some(&["read", "render"]),
         "find-value" => Some(&["analyze", "find-value"]),
         "find-formula" => Some(&["analyze", "find-formula"]),
         "formula-map" => Some(&["analyze", "formula-map"]),
         "formula-trace" => Some(&["analyze", "formula-trace"]),
         "scan-volatiles" => Some(&["analyze", "scan-volatiles"]),
+        "scan-errors" => Some(&["analyze", "scan-errors"]),
+        "lint-formulas" => Some(&["analyze", "lint-formulas"]),
         "sheet-statistics" => Some(&["analyze", "sheet-statistics"]),
         "table-profile" => Some(&["analyze", "table-profile"]),
+        "query-table" => Some(&["analyze", "query-table"]),
+        "map-columns" => Some(&["analyze", "map-columns"]),
         "check-ref-impact" => Some(&["analyze", "ref-impact"]),
+        "plan" => Some(&["analyze", "plan"]),
         "edit" => Some(&["write", "cells"]),
         "range-import" => Some(&["write", "import"]),
+        "write-cells" => Some(&["write", "bulk"]),
         "append-region" => Some(&["write", "append"]),
+        "materialize" => Some(&["write", "materialize"]),
+        "refresh" => Some(&["write", "refresh"]),
         "clone-template-row" => Some(&["write", "clone-template-row"]),
         "clone-row-band" => Some(&["write", "clone-row-band"]),
         "replace-in-formulas" => Some(&["write", "formulas", "replace"]),
+        "repair-refs" => Some(&["write", "formulas", "repair"]),
         "transform-batch" => Some(&["write", "batch", "transform"]),
         "style-batch" => Some(&["write", "batch", "style"]),
         "apply-formula-pattern" => Some(&["write", "batch", "formula-pattern"]),
         "structure-batch" => Some(&["write", "batch", "structure"]),
         "column-size-batch" => Some(&["write", "batch", "column-size"]),
+        "row-size-batch" => Some(&["write", "batch", "row-size"]),
         "sheet-layout-batch" => Some(&["write", "batch", "sheet-layout"]),
         "rules-batch" => Some(&["write", "batch", "rules"]),
+        "properties-batch" => Some(&["write", "batch", "properties"]),
+        "apply-plan" => Some(&["write", "plan"]),
+        "apply-state" => Some(&["write", "state"]),
         "define-name" => Some(&["write", "name", "define"]),
         "update-name" => Some(&["write", "name", "update"]),
         "delete-name" => Some(&["write", "name", "delete"]),
+        "set-metadata" => Some(&["write", "metadata", "set"]),
+        "clear-metadata" => Some(&["write", "metadata", "clear"]),
+        "annotate" => Some(&["write", "annotate"]),
+        "apply-strings" => Some(&["write", "strings"]),
         "create-workbook" => Some(&["workbook", "create"]),
         "copy" => Some(&["workbook", "copy"]),
+        "instantiate" => Some(&["workbook", "instantiate"]),
+        "restore" => Some(&["workbook", "restore"]),
+        "list-snapshots" => Some(&["workbook", "snapshots"]),
         "recalculate" => Some(&["workbook", "recalculate"]),
+        "export-pdf" => Some(&["workbook", "export-pdf"]),
+        "dedupe-scan" => Some(&["workbook", "dedupe-scan"]),
         "verify" => Some(&["verify", "proof"]),
         "diff" => Some(&["verify", "diff"]),
+        "diff-many" => Some(&["verify", "diff-many"]),
+        "visual-diff" => Some(&["verify", "visual-diff"]),
         "run-manifest" => Some(&["sheetport", "run"]),
         _ => None,
     }
@@ -3398,8 +6634,10 @@ fn legacy_discoverability_tokens(target: &str) -> Option<&'static [&'static str]
         "apply-formula-pattern" => Some(&["write", "batch", "formula-pattern"]),
         "structure-batch" => Some(&["write", "batch", "structure"]),
         "column-size-batch" => Some(&["write", "batch", "column-size"]),
+        "row-size-batch" => Some(&["write", "batch", "row-size"]),
         "sheet-layout-batch" => Some(&["write", "batch", "sheet-layout"]),
         "rules-batch" => Some(&["write", "batch", "rules"]),
+        "properties-batch" => Some(&["write", "batch", "properties"]),
         _ => None,
     }
 }
@@ -3412,34 +6650,65 @@ fn canonical_leaf_path_to_flat(tokens: &[String]) -> Option<&'static str> {
         [a, b] if a == "read" && b == "export" => Some("range-export"),
         [a, b] if a == "read" && b == "cells" => Some("inspect-cells"),
         [a, b] if a == "read" && b == "page" => Some("sheet-page"),
+        [a, b] if a == "read" && b == "workbook-page" => Some("workbook-page"),
         [a, b] if a == "read" && b == "table" => Some("read-table"),
+        [a, b] if a == "read" && b == "estimate" => Some("estimate"),
         [a, b] if a == "read" && b == "names" => Some("named-ranges"),
+        [a, b] if a == "read" && b == "metadata" => Some("metadata"),
+        [a, b] if a == "read" && b == "annotations" => Some("annotations"),
+        [a, b] if a == "read" && b == "external-links" => Some("list-external-links"),
+        [a, b] if a == "read" && b == "strings" => Some("extract-strings"),
         [a, b] if a == "read" && b == "workbook" => Some("describe"),
         [a, b] if a == "read" && b == "layout" => Some("layout-page"),
+        [a, b] if a == "read" && b == "render" => Some("render"),
         [a, b] if a == "analyze" && b == "find-value" => Some("find-value"),
         [a, b] if a == "analyze" && b == "find-formula" => Some("find-formula"),
         [a, b] if a == "analyze" && b == "formula-map" => Some("formula-map"),
         [a, b] if a == "analyze" && b == "formula-trace" => Some("formula-trace"),
         [a, b] if a == "analyze" && b == "scan-volatiles" => Some("scan-volatiles"),
+        [a, b] if a == "analyze" && b == "scan-errors" => Some("scan-errors"),
+        [a, b] if a == "analyze" && b == "lint-formulas" => Some("lint-formulas"),
         [a, b] if a == "analyze" && b == "sheet-statistics" => Some("sheet-statistics"),
         [a, b] if a == "analyze" && b == "table-profile" => Some("table-profile"),
+        [a, b] if a == "analyze" && b == "query-table" => Some("query-table"),
+        [a, b] if a == "analyze" && b == "map-columns" => Some("map-columns"),
         [a, b] if a == "analyze" && b == "ref-impact" => Some("check-ref-impact"),
+        [a, b] if a == "analyze" && b == "plan" => Some("plan"),
         [a, b] if a == "write" && b == "cells" => Some("edit"),
+        [a, b] if a == "write" && b == "annotate" => Some("annotate"),
+        [a, b] if a == "write" && b == "strings" => Some("apply-strings"),
         [a, b] if a == "write" && b == "import" => Some("range-import"),
+        [a, b] if a == "write" && b == "bulk" => Some("write-cells"),
         [a, b] if a == "write" && b == "append" => Some("append-region"),
+        [a, b] if a == "write" && b == "materialize" => Some("materialize"),
+        [a, b] if a == "write" && b == "refresh" => Some("refresh"),
         [a, b] if a == "write" && b == "clone-template-row" => Some("clone-template-row"),
         [a, b] if a == "write" && b == "clone-row-band" => Some("clone-row-band"),
+        [a, b] if a == "write" && b == "plan" => Some("apply-plan"),
+        [a, b] if a == "write" && b == "state" => Some("apply-state"),
         [a, b] if a == "workbook" && b == "create" => Some("create-workbook"),
         [a, b] if a == "workbook" && b == "copy" => Some("copy"),
+        [a, b] if a == "workbook" && b == "instantiate" => Some("instantiate"),
+        [a, b] if a == "workbook" && b == "restore" => Some("restore"),
+        [a, b] if a == "workbook" && b == "snapshots" => Some("list-snapshots"),
         [a, b] if a == "workbook" && b == "recalculate" => Some("recalculate"),
+        [a, b] if a == "workbook" && b == "export-pdf" => Some("export-pdf"),
+        [a, b] if a == "workbook" && b == "dedupe-scan" => Some("dedupe-scan"),
         [a, b] if a == "verify" && b == "proof" => Some("verify"),
         [a, b] if a == "verify" && b == "diff" => Some("diff"),
+        [a, b] if a == "verify" && b == "diff-many" => Some("diff-many"),
+        [a, b] if a == "verify" && b == "visual-diff" => Some("visual-diff"),
+        [a, b] if a == "verify" && b == "run-tests" => Some("run-tests"),
+        [a, b] if a == "verify" && b == "lint" => Some("lint"),
         [a, b, c] if a == "write" && b == "formulas" && c == "replace" => {
             Some("replace-in-formulas")
         }
+        [a, b, c] if a == "write" && b == "formulas" && c == "repair" => Some("repair-refs"),
         [a, b, c] if a == "write" && b == "name" && c == "define" => Some("define-name"),
         [a, b, c] if a == "write" && b == "name" && c == "update" => Some("update-name"),
         [a, b, c] if a == "write" && b == "name" && c == "delete" => Some("delete-name"),
+        [a, b, c] if a == "write" && b == "metadata" && c == "set" => Some("set-metadata"),
+        [a, b, c] if a == "write" && b == "metadata" && c == "clear" => Some("clear-metadata"),
         [a, b, c] if a == "write" && b == "batch" && c == "transform" => Some("transform-batch"),
         [a, b, c] if a == "write" && b == "batch" && c == "style" => Some("style-batch"),
         [a, b, c] if a == "write" && b == "batch" && c == "formula-pattern" => {
@@ -3449,10 +6718,12 @@ fn canonical_leaf_path_to_flat(tokens: &[String]) -> Option<&'static str> {
         [a, b, c] if a == "write" && b == "batch" && c == "column-size" => {
             Some("column-size-batch")
         }
+        [a, b, c] if a == "write" && b == "batch" && c == "row-size" => Some("row-size-batch"),
         [a, b, c] if a == "write" && b == "batch" && c == "sheet-layout" => {
             Some("sheet-layout-batch")
         }
         [a, b, c] if a == "write" && b == "batch" && c == "rules" => Some("rules-batch"),
+        [a, b, c] if a == "write" && b == "batch" && c == "properties" => Some("properties-batch"),
         _ => None,
     }
 }
@@ -3479,11 +6750,19 @@ fn rewrite_flat_surface_text(text: &str) -> String {
             "asp schema column-size-batch",
             "asp schema write batch column-size",
         ),
+        (
+            "asp schema row-size-batch",
+            "asp schema write batch row-size",
+        ),
         (
             "asp schema sheet-layout-batch",
             "asp schema write batch sheet-layout",
         ),
         ("asp schema rules-batch", "asp schema write batch rules"),
+        (
+            "asp schema properties-batch",
+            "asp schema write batch properties",
+        ),
         (
             "asp example transform-batch",
             "asp example write batch transform",
@@ -3501,11 +6780,19 @@ fn rewrite_flat_surface_text(text: &str) -> String {
             "asp example column-size-batch",
             "asp example write batch column-size",
         ),
+        (
+            "asp example row-size-batch",
+            "asp example write batch row-size",
+        ),
         (
             "asp example sheet-layout-batch",
             "asp example write batch sheet-layout",
         ),
         ("asp example rules-batch", "asp example write batch rules"),
+        (
+            "asp example properties-batch",
+            "asp example write batch properties",
+        ),
     ];
     for (from, to) in replacements {
         rewritten = rewritten.replace(from, to);
@@ -3519,38 +6806,69 @@ fn rewrite_flat_surface_text(text: &str) -> String {
         "range-export",
         "inspect-cells",
         "sheet-page",
+        "workbook-page",
         "read-table",
+        "estimate",
         "named-ranges",
+        "metadata",
+        "annotations",
+        "list-external-links",
+        "extract-strings",
         "describe",
         "layout-page",
+        "render",
         "find-value",
         "find-formula",
         "formula-map",
         "formula-trace",
         "scan-volatiles",
+        "scan-errors",
+        "lint-formulas",
         "sheet-statistics",
         "table-profile",
+        "query-table",
+        "map-columns",
         "check-ref-impact",
+        "plan",
         "edit",
         "range-import",
+        "write-cells",
         "append-region",
+        "materialize",
+        "refresh",
         "clone-template-row",
         "clone-row-band",
         "replace-in-formulas",
+        "repair-refs",
         "transform-batch",
         "style-batch",
         "apply-formula-pattern",
         "structure-batch",
         "column-size-batch",
+        "row-size-batch",
         "sheet-layout-batch",
         "rules-batch",
+        "properties-batch",
+        "apply-plan",
+        "apply-state",
         "define-name",
         "update-name",
         "delete-name",
+        "set-metadata",
+        "clear-metadata",
+        "annotate",
+        "apply-strings",
         "create-workbook",
         "copy",
+        "instantiate",
+        "restore",
+        "list-snapshots",
         "recalculate",
+        "export-pdf",
+        "dedupe-scan",
         "verify",
+        "diff-many",
+        "visual-diff",
         "diff",
         "run-manifest",
     ];
@@ -3596,8 +6914,16 @@ fn normalize_legacy_command_argv(argv: Vec<OsString>) -> (Vec<OsString>, Vec<Str
         let next_token = argv
             .get(index + 1)
             .map(|value| value.to_string_lossy().into_owned());
-        let conflicts_with_canonical_group =
-            token == "verify" && matches!(next_token.as_deref(), Some("proof") | Some("diff"));
+        let conflicts_with_canonical_group = token == "verify"
+            && matches!(
+                next_token.as_deref(),
+                Some("proof")
+                    | Some("diff")
+                    | Some("diff-many")
+                    | Some("visual-diff")
+                    | Some("run-tests")
+                    | Some("lint")
+            );
 
         if !conflicts_with_canonical_group {
             let mut normalized = Vec::with_capacity(argv.len() + path.len());
@@ -3715,10 +7041,16 @@ fn resolve_surface_discoverability(
                 SurfaceDiscoverabilityBatchCommands::ColumnSize => {
                     DiscoverabilityCommands::ColumnSizeBatch
                 }
+                SurfaceDiscoverabilityBatchCommands::RowSize => {
+                    DiscoverabilityCommands::RowSizeBatch
+                }
                 SurfaceDiscoverabilityBatchCommands::SheetLayout => {
                     DiscoverabilityCommands::SheetLayoutBatch
                 }
                 SurfaceDiscoverabilityBatchCommands::Rules => DiscoverabilityCommands::RulesBatch,
+                SurfaceDiscoverabilityBatchCommands::Properties => {
+                    DiscoverabilityCommands::PropertiesBatch
+                }
             },
         },
         SurfaceDiscoverabilityCommands::Session(command) => match command {
@@ -3758,14 +7090,38 @@ fn resolve_surface_command(
                 parse_flat_command_from_surface("sheet-page", args.args)
                     .map(ResolvedSurfaceCommand::Command)
             }
+            SurfaceReadCommands::WorkbookPage(args) => {
+                parse_flat_command_from_surface("workbook-page", args.args)
+                    .map(ResolvedSurfaceCommand::Command)
+            }
             SurfaceReadCommands::Table(args) => {
                 parse_flat_command_from_surface("read-table", args.args)
                     .map(ResolvedSurfaceCommand::Command)
             }
+            SurfaceReadCommands::Estimate(args) => {
+                parse_flat_command_from_surface("estimate", args.args)
+                    .map(ResolvedSurfaceCommand::Command)
+            }
             SurfaceReadCommands::Names(args) => {
                 parse_flat_command_from_surface("named-ranges", args.args)
                     .map(ResolvedSurfaceCommand::Command)
             }
+            SurfaceReadCommands::Metadata(args) => {
+                parse_flat_command_from_surface("metadata", args.args)
+                    .map(ResolvedSurfaceCommand::Command)
+            }
+            SurfaceReadCommands::Annotations(args) => {
+                parse_flat_command_from_surface("annotations", args.args)
+                    .map(ResolvedSurfaceCommand::Command)
+            }
+            SurfaceReadCommands::ExternalLinks(args) => {
+                parse_flat_command_from_surface("list-external-links", args.args)
+                    .map(ResolvedSurfaceCommand::Command)
+            }
+            SurfaceReadCommands::Strings(args) => {
+                parse_flat_command_from_surface("extract-strings", args.args)
+                    .map(ResolvedSurfaceCommand::Command)
+            }
             SurfaceReadCommands::Workbook(args) => {
                 parse_flat_command_from_surface("describe", args.args)
                     .map(ResolvedSurfaceCommand::Command)
@@ -3774,6 +7130,10 @@ fn resolve_surface_command(
                 parse_flat_command_from_surface("layout-page", args.args)
                     .map(ResolvedSurfaceCommand::Command)
             }
+            SurfaceReadCommands::Render(args) => {
+                parse_flat_command_from_surface("render", args.args)
+                    .map(ResolvedSurfaceCommand::Command)
+            }
         },
         SurfaceCommands::Analyze(command) => match command {
             SurfaceAnalyzeCommands::FindValue(args) => {
@@ -3796,6 +7156,14 @@ fn resolve_surface_command(
                 parse_flat_command_from_surface("scan-volatiles", args.args)
                     .map(ResolvedSurfaceCommand::Command)
             }
+            SurfaceAnalyzeCommands::ScanErrors(args) => {
+                parse_flat_command_from_surface("scan-errors", args.args)
+                    .map(ResolvedSurfaceCommand::Command)
+            }
+            SurfaceAnalyzeCommands::LintFormulas(args) => {
+                parse_flat_command_from_surface("lint-formulas", args.args)
+                    .map(ResolvedSurfaceCommand::Command)
+            }
             SurfaceAnalyzeCommands::SheetStatistics(args) => {
                 parse_flat_command_from_surface("sheet-statistics", args.args)
                     .map(ResolvedSurfaceCommand::Command)
@@ -3804,10 +7172,22 @@ fn resolve_surface_command(
                 parse_flat_command_from_surface("table-profile", args.args)
                     .map(ResolvedSurfaceCommand::Command)
             }
+            SurfaceAnalyzeCommands::QueryTable(args) => {
+                parse_flat_command_from_surface("query-table", args.args)
+                    .map(ResolvedSurfaceCommand::Command)
+            }
+            SurfaceAnalyzeCommands::MapColumns(args) => {
+                parse_flat_command_from_surface("map-columns", args.args)
+                    .map(ResolvedSurfaceCommand::Command)
+            }
             SurfaceAnalyzeCommands::RefImpact(args) => {
                 parse_flat_command_from_surface("check-ref-impact", args.args)
                     .map(ResolvedSurfaceCommand::Command)
             }
+            SurfaceAnalyzeCommands::Plan(args) => {
+                parse_flat_command_from_surface("plan", args.args)
+                    .map(ResolvedSurfaceCommand::Command)
+            }
         },
         SurfaceCommands::Write(command) => match command {
             SurfaceWriteCommands::Cells(args) => parse_flat_command_from_surface("edit", args.args)
@@ -3816,10 +7196,22 @@ fn resolve_surface_command(
                 parse_flat_command_from_surface("range-import", args.args)
                     .map(ResolvedSurfaceCommand::Command)
             }
+            SurfaceWriteCommands::Bulk(args) => {
+                parse_flat_command_from_surface("write-cells", args.args)
+                    .map(ResolvedSurfaceCommand::Command)
+            }
             SurfaceWriteCommands::Append(args) => {
                 parse_flat_command_from_surface("append-region", args.args)
                     .map(ResolvedSurfaceCommand::Command)
             }
+            SurfaceWriteCommands::Materialize(args) => {
+                parse_flat_command_from_surface("materialize", args.args)
+                    .map(ResolvedSurfaceCommand::Command)
+            }
+            SurfaceWriteCommands::Refresh(args) => {
+                parse_flat_command_from_surface("refresh", args.args)
+                    .map(ResolvedSurfaceCommand::Command)
+            }
             SurfaceWriteCommands::CloneTemplateRow(args) => {
                 parse_flat_command_from_surface("clone-template-row", args.args)
                     .map(ResolvedSurfaceCommand::Command)
@@ -3828,11 +7220,31 @@ fn resolve_surface_command(
                 parse_flat_command_from_surface("clone-row-band", args.args)
                     .map(ResolvedSurfaceCommand::Command)
             }
+            SurfaceWriteCommands::Plan(args) => {
+                parse_flat_command_from_surface("apply-plan", args.args)
+                    .map(ResolvedSurfaceCommand::Command)
+            }
+            SurfaceWriteCommands::State(args) => {
+                parse_flat_command_from_surface("apply-state", args.args)
+                    .map(ResolvedSurfaceCommand::Command)
+            }
+            SurfaceWriteCommands::Annotate(args) => {
+                parse_flat_command_from_surface("annotate", args.args)
+                    .map(ResolvedSurfaceCommand::Command)
+            }
+            SurfaceWriteCommands::Strings(args) => {
+                parse_flat_command_from_surface("apply-strings", args.args)
+                    .map(ResolvedSurfaceCommand::Command)
+            }
             SurfaceWriteCommands::Formulas(command) => match command {
                 SurfaceWriteFormulaCommands::Replace(args) => {
                     parse_flat_command_from_surface("replace-in-formulas", args.args)
                         .map(ResolvedSurfaceCommand::Command)
                 }
+                SurfaceWriteFormulaCommands::Repair(args) => {
+                    parse_flat_command_from_surface("repair-refs", args.args)
+                        .map(ResolvedSurfaceCommand::Command)
+                }
             },
             SurfaceWriteCommands::Name(command) => match command {
                 SurfaceWriteNameCommands::Define(args) => {
@@ -3848,6 +7260,16 @@ fn resolve_surface_command(
                         .map(ResolvedSurfaceCommand::Command)
                 }
             },
+            SurfaceWriteCommands::Metadata(command) => match command {
+                SurfaceWriteMetadataCommands::Set(args) => {
+                    parse_flat_command_from_surface("set-metadata", args.args)
+                        .map(ResolvedSurfaceCommand::Command)
+                }
+                SurfaceWriteMetadataCommands::Clear(args) => {
+                    parse_flat_command_from_surface("clear-metadata", args.args)
+                        .map(ResolvedSurfaceCommand::Command)
+                }
+            },
             SurfaceWriteCommands::Batch(command) => match command {
                 SurfaceWriteBatchCommands::Transform(args) => {
                     parse_flat_command_from_surface("transform-batch", args.args)
@@ -3869,6 +7291,10 @@ fn resolve_surface_command(
                     parse_flat_command_from_surface("column-size-batch", args.args)
                         .map(ResolvedSurfaceCommand::Command)
                 }
+                SurfaceWriteBatchCommands::RowSize(args) => {
+                    parse_flat_command_from_surface("row-size-batch", args.args)
+                        .map(ResolvedSurfaceCommand::Command)
+                }
                 SurfaceWriteBatchCommands::SheetLayout(args) => {
                     parse_flat_command_from_surface("sheet-layout-batch", args.args)
                         .map(ResolvedSurfaceCommand::Command)
@@ -3877,6 +7303,10 @@ fn resolve_surface_command(
                     parse_flat_command_from_surface("rules-batch", args.args)
                         .map(ResolvedSurfaceCommand::Command)
                 }
+                SurfaceWriteBatchCommands::Properties(args) => {
+                    parse_flat_command_from_surface("properties-batch", args.args)
+                        .map(ResolvedSurfaceCommand::Command)
+                }
             },
         },
         SurfaceCommands::Workbook(command) => match command {
@@ -3888,10 +7318,30 @@ fn resolve_surface_command(
                 parse_flat_command_from_surface("copy", args.args)
                     .map(ResolvedSurfaceCommand::Command)
             }
+            SurfaceWorkbookCommands::Instantiate(args) => {
+                parse_flat_command_from_surface("instantiate", args.args)
+                    .map(ResolvedSurfaceCommand::Command)
+            }
+            SurfaceWorkbookCommands::Restore(args) => {
+                parse_flat_command_from_surface("restore", args.args)
+                    .map(ResolvedSurfaceCommand::Command)
+            }
+            SurfaceWorkbookCommands::Snapshots(args) => {
+                parse_flat_command_from_surface("list-snapshots", args.args)
+                    .map(ResolvedSurfaceCommand::Command)
+            }
             SurfaceWorkbookCommands::Recalculate(args) => {
                 parse_flat_command_from_surface("recalculate", args.args)
                     .map(ResolvedSurfaceCommand::Command)
             }
+            SurfaceWorkbookCommands::ExportPdf(args) => {
+                parse_flat_command_from_surface("export-pdf", args.args)
+                    .map(ResolvedSurfaceCommand::Command)
+            }
+            SurfaceWorkbookCommands::DedupeScan(args) => {
+                parse_flat_command_from_surface("dedupe-scan", args.args)
+                    .map(ResolvedSurfaceCommand::Command)
+            }
         },
         SurfaceCommands::Verify(command) => match command {
             SurfaceVerifyCommands::Proof(args) => {
@@ -3900,6 +7350,20 @@ fn resolve_surface_command(
             }
             SurfaceVerifyCommands::Diff(args) => parse_flat_command_from_surface("diff", args.args)
                 .map(ResolvedSurfaceCommand::Command),
+            SurfaceVerifyCommands::DiffMany(args) => {
+                parse_flat_command_from_surface("diff-many", args.args)
+                    .map(ResolvedSurfaceCommand::Command)
+            }
+            SurfaceVerifyCommands::VisualDiff(args) => {
+                parse_flat_command_from_surface("visual-diff", args.args)
+                    .map(ResolvedSurfaceCommand::Command)
+            }
+            SurfaceVerifyCommands::RunTests(args) => {
+                parse_flat_command_from_surface("run-tests", args.args)
+                    .map(ResolvedSurfaceCommand::Command)
+            }
+            SurfaceVerifyCommands::Lint(args) => parse_flat_command_from_surface("lint", args.args)
+                .map(ResolvedSurfaceCommand::Command),
         },
         SurfaceCommands::Schema { command } => Ok(ResolvedSurfaceCommand::Schema(
             resolve_surface_discoverability(command),
@@ -3910,6 +7374,22 @@ fn resolve_surface_command(
         SurfaceCommands::Session(command) => {
             Ok(ResolvedSurfaceCommand::Command(Commands::Session(command)))
         }
+        #[cfg(feature = "serve")]
+        SurfaceCommands::Serve { http } => {
+            Ok(ResolvedSurfaceCommand::Command(Commands::Serve { http }))
+        }
+        SurfaceCommands::Revisions(command) => Ok(ResolvedSurfaceCommand::Command(
+            Commands::Revisions(command),
+        )),
+        SurfaceCommands::Gc {
+            scratch_dir,
+            older_than,
+            dry_run,
+        } => Ok(ResolvedSurfaceCommand::Command(Commands::Gc {
+            scratch_dir,
+            older_than,
+            dry_run,
+        })),
         SurfaceCommands::Sheetport { command } => {
             Ok(ResolvedSurfaceCommand::Command(Commands::Sheetport {
                 command,
@@ -3928,6 +7408,10 @@ pub async fn run() -> Result<()> {
         Err(error) => error.exit(),
     };
 
+    if surface.timings {
+        crate::timings::enable_timings();
+    }
+
     let result = match resolve_surface_command(surface.command) {
         Ok(ResolvedSurfaceCommand::Command(command)) => {
             run_with_options(
@@ -3936,6 +7420,7 @@ pub async fn run() -> Result<()> {
                 surface.shape,
                 surface.compact,
                 surface.quiet,
+                surface.canonical_json,
             )
             .await
         }
@@ -3948,6 +7433,7 @@ pub async fn run() -> Result<()> {
                     output::CompactProjectionTarget::None,
                     surface.compact,
                     surface.quiet,
+                    surface.canonical_json,
                 ) {
                     emit_error_and_exit(error);
                 }
@@ -3964,6 +7450,7 @@ pub async fn run() -> Result<()> {
                     output::CompactProjectionTarget::None,
                     surface.compact,
                     surface.quiet,
+                    surface.canonical_json,
                 ) {
                     emit_error_and_exit(error);
                 }
@@ -3989,12 +7476,19 @@ pub async fn run_with_options(
     shape: OutputShape,
     compact: bool,
     quiet: bool,
+    canonical_json: bool,
 ) -> Result<()> {
     if let Err(error) = errors::ensure_output_supported(format) {
         emit_error_and_exit(error);
     }
 
     let projection_target = compact_projection_target_for_command(&command);
+    let gated_by_passed = matches!(
+        &command,
+        Commands::Lint { .. }
+            | Commands::RunTests { .. }
+            | Commands::ApplyState { check: true, .. }
+    );
     let emit_layout_ascii_direct = matches!(
         &command,
         Commands::LayoutPage {
@@ -4003,8 +7497,12 @@ pub async fn run_with_options(
         }
     );
 
+    let started = std::time::Instant::now();
     match run_command(command).await {
-        Ok(payload) => {
+        Ok(mut payload) => {
+            if crate::timings::timings_enabled() {
+                attach_timings(&mut payload, started.elapsed());
+            }
             if emit_layout_ascii_direct {
                 if let Some(ascii) = payload.get("ascii_render").and_then(|v| v.as_str()) {
                     print!("{ascii}");
@@ -4018,17 +7516,39 @@ pub async fn run_with_options(
                 ));
             }
 
-            if let Err(error) =
-                output::emit_value(&payload, format, shape, projection_target, compact, quiet)
-            {
+            if let Err(error) = output::emit_value(
+                &payload,
+                format,
+                shape,
+                projection_target,
+                compact,
+                quiet,
+                canonical_json,
+            ) {
                 emit_error_and_exit(error);
             }
+            if gated_by_passed && gate_passed(&payload) == Some(false) {
+                std::process::exit(GATE_FAILED_EXIT_CODE);
+            }
             Ok(())
         }
         Err(error) => emit_error_and_exit(error),
     }
 }
 
+/// Add the `--timings` report under a top-level `timings` key of object payloads.
+fn attach_timings(payload: &mut Value, elapsed: std::time::Duration) {
+    if let Value::Object(map) = payload {
+        map.insert(
+            "timings".to_string(),
+            serde_json::json!({
+                "command_ms": elapsed.as_millis() as u64,
+                "phases": crate::timings::take_timings(),
+            }),
+        );
+    }
+}
+
 fn compact_projection_target_for_command(command: &Commands) -> output::CompactProjectionTarget {
     match command {
         Commands::RangeValues { .. } => output::CompactProjectionTarget::RangeValues,
@@ -4039,6 +7559,17 @@ fn compact_projection_target_for_command(command: &Commands) -> output::CompactP
     }
 }
 
+/// Exit code for gating commands (such as `lint`) whose report was emitted but failed.
+const GATE_FAILED_EXIT_CODE: i32 = 2;
+
+/// Gate state from a top-level boolean `passed` or `all_passed` (`run-tests`, whose
+/// `passed` is a count), or from run properties for SARIF logs.
+fn gate_passed(payload: &Value) -> Option<bool> {
+    ["/passed", "/all_passed", "/runs/0/properties/passed"]
+        .into_iter()
+        .find_map(|pointer| payload.pointer(pointer).and_then(Value::as_bool))
+}
+
 fn emit_error_and_exit(error: anyhow::Error) -> ! {
     let envelope = errors::envelope_for(&error);
     let stderr = std::io::stderr();
@@ -4253,6 +7784,8 @@ mod tests {
                 limit,
                 offset,
                 exclude_recalc_result,
+                compare,
+                ignore_file,
             } => {
                 assert_eq!(original, PathBuf::from("baseline.xlsx"));
                 assert_eq!(modified, PathBuf::from("candidate.xlsx"));
@@ -4263,6 +7796,9 @@ mod tests {
                 assert_eq!(limit, 150);
                 assert_eq!(offset, 300);
                 assert!(!exclude_recalc_result);
+                assert!(compare.abs_tolerance.is_none());
+                assert!(!compare.ignore_volatile);
+                assert!(ignore_file.is_none());
             }
             other => panic!("unexpected command: {other:?}"),
         }
@@ -4317,6 +7853,35 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parses_diff_compare_flags() {
+        let cli = Cli::try_parse_from([
+            "agent-spreadsheet",
+            "diff",
+            "baseline.xlsx",
+            "candidate.xlsx",
+            "--abs-tolerance",
+            "0.005",
+            "--rel-tolerance",
+            "1e-6",
+            "--ignore-whitespace",
+            "--ignore-case",
+            "--ignore-volatile",
+        ])
+        .expect("parse diff command with compare flags");
+
+        match cli.command {
+            Commands::Diff { compare, .. } => {
+                assert_eq!(compare.abs_tolerance, Some(0.005));
+                assert_eq!(compare.rel_tolerance, Some(1e-6));
+                assert!(compare.ignore_whitespace);
+                assert!(compare.ignore_case);
+                assert!(compare.ignore_volatile);
+            }
+            other => panic!("unexpected command: {other:?}"),
+        }
+    }
+
     #[test]
     fn parses_range_import_arguments() {
         let cli = Cli::try_parse_from([
@@ -4345,6 +7910,9 @@ mod tests {
                 in_place,
                 output,
                 force,
+                verify_with,
+                audit_log,
+                snapshot_dir,
             } => {
                 assert_eq!(file, PathBuf::from("workbook.xlsx"));
                 assert_eq!(sheet, "Sheet1");
@@ -4357,6 +7925,9 @@ mod tests {
                 assert!(in_place);
                 assert!(output.is_none());
                 assert!(!force);
+                assert!(verify_with.is_none());
+                assert!(audit_log.is_none());
+                assert!(snapshot_dir.is_none());
             }
             other => panic!("unexpected command: {other:?}"),
         }
@@ -4715,6 +8286,10 @@ mod tests {
             "--output",
             "out.xlsx",
             "--force",
+            "--verify-with",
+            "tests.json",
+            "--audit-log",
+            "audit.jsonl",
         ])
         .expect("parse transform-batch");
 
@@ -4726,6 +8301,10 @@ mod tests {
                 in_place,
                 output,
                 force,
+                verify_with,
+                audit_log,
+                snapshot_dir,
+                continue_on_error,
                 print_schema,
                 formula_parse_policy,
             } => {
@@ -4735,6 +8314,10 @@ mod tests {
                 assert!(!in_place);
                 assert_eq!(output, Some(PathBuf::from("out.xlsx")));
                 assert!(force);
+                assert_eq!(verify_with, Some(PathBuf::from("tests.json")));
+                assert_eq!(audit_log, Some(PathBuf::from("audit.jsonl")));
+                assert!(snapshot_dir.is_none());
+                assert!(!continue_on_error);
                 assert!(!print_schema);
                 assert_eq!(formula_parse_policy, None);
             }
@@ -4762,6 +8345,10 @@ mod tests {
                 in_place,
                 output,
                 force,
+                verify_with,
+                audit_log,
+                snapshot_dir,
+                continue_on_error,
                 print_schema,
             } => {
                 assert_eq!(file, Some(PathBuf::from("workbook.xlsx")));
@@ -4770,6 +8357,10 @@ mod tests {
                 assert!(!in_place);
                 assert!(output.is_none());
                 assert!(!force);
+                assert!(verify_with.is_none());
+                assert!(audit_log.is_none());
+                assert!(snapshot_dir.is_none());
+                assert!(!continue_on_error);
                 assert!(!print_schema);
             }
             other => panic!("unexpected command: {other:?}"),
@@ -4796,6 +8387,10 @@ mod tests {
                 in_place,
                 output,
                 force,
+                verify_with,
+                audit_log,
+                snapshot_dir,
+                continue_on_error,
                 print_schema,
             } => {
                 assert_eq!(file, Some(PathBuf::from("workbook.xlsx")));
@@ -4804,6 +8399,10 @@ mod tests {
                 assert!(in_place);
                 assert!(output.is_none());
                 assert!(!force);
+                assert!(verify_with.is_none());
+                assert!(audit_log.is_none());
+                assert!(snapshot_dir.is_none());
+                assert!(!continue_on_error);
                 assert!(!print_schema);
             }
             other => panic!("unexpected command: {other:?}"),
@@ -4820,6 +8419,7 @@ mod tests {
             "@structure.json",
             "--output",
             "out.xlsx",
+            "--continue-on-error",
         ])
         .expect("parse structure-batch");
         match structure.command {
@@ -4827,12 +8427,14 @@ mod tests {
                 file,
                 ops,
                 output,
+                continue_on_error,
                 print_schema,
                 ..
             } => {
                 assert_eq!(file, Some(PathBuf::from("workbook.xlsx")));
                 assert_eq!(ops, Some("@structure.json".to_string()));
                 assert_eq!(output, Some(PathBuf::from("out.xlsx")));
+                assert!(continue_on_error);
                 assert!(!print_schema);
             }
             other => panic!("unexpected command: {other:?}"),
@@ -4907,6 +8509,10 @@ mod tests {
                 in_place,
                 output,
                 force,
+                verify_with,
+                audit_log,
+                snapshot_dir,
+                continue_on_error,
                 print_schema,
                 formula_parse_policy,
             } => {
@@ -4916,6 +8522,10 @@ mod tests {
                 assert!(!in_place);
                 assert_eq!(output, Some(PathBuf::from("rules.xlsx")));
                 assert!(force);
+                assert!(verify_with.is_none());
+                assert!(audit_log.is_none());
+                assert!(snapshot_dir.is_none());
+                assert!(!continue_on_error);
                 assert!(!print_schema);
                 assert!(formula_parse_policy.is_none());
             }
@@ -5084,7 +8694,9 @@ mod tests {
                 sheet,
                 limit,
                 offset,
+                reference_style,
             } => {
+                assert_eq!(reference_style, ReferenceStyle::A1);
                 assert_eq!(file, PathBuf::from("workbook.xlsx"));
                 assert_eq!(query, "SUM(");
                 assert_eq!(sheet.as_deref(), Some("Sheet1"));
@@ -5103,7 +8715,7 @@ mod tests {
         .expect("parse sheet-statistics");
 
         match stats.command {
-            Commands::SheetStatistics { file, sheet } => {
+            Commands::SheetStatistics { file, sheet, .. } => {
                 assert_eq!(file, PathBuf::from("workbook.xlsx"));
                 assert_eq!(sheet, "Summary");
             }
@@ -19,6 +19,7 @@ pub fn emit_value(
     projection_target: CompactProjectionTarget,
     compact: bool,
     quiet: bool,
+    canonical_json: bool,
 ) -> Result<()> {
     if matches!(format, OutputFormat::Csv) {
         bail!("csv output is not implemented yet for agent-spreadsheet")
@@ -31,12 +32,110 @@ pub fn emit_value(
     let stdout = std::io::stdout();
     let mut handle = stdout.lock();
     let _ = (compact, quiet);
-    serde_json::to_writer(&mut handle, &value)?;
     use std::io::Write;
+    if canonical_json {
+        handle.write_all(to_canonical_json(&value).as_bytes())?;
+    } else {
+        serde_json::to_writer(&mut handle, &value)?;
+    }
     handle.write_all(b"\n")?;
     Ok(())
 }
 
+/// Serialize `value` as canonical JSON in the style of RFC 8785: no
+/// whitespace, object keys sorted by UTF-16 code units, and floats in
+/// ECMAScript's shortest form (`10.0` is `10`, `1e21` is `1e+21`). Equal
+/// payloads are byte-identical across runs and platforms, so they can be
+/// hashed or snapshotted.
+pub fn to_canonical_json(value: &Value) -> String {
+    let mut out = String::new();
+    write_canonical_json(value, &mut out);
+    out
+}
+
+fn write_canonical_json(value: &Value, out: &mut String) {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(flag) => out.push_str(if *flag { "true" } else { "false" }),
+        Value::Number(number) => out.push_str(&canonical_number(number)),
+        Value::String(text) => push_json_string(text, out),
+        Value::Array(items) => {
+            out.push('[');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                write_canonical_json(item, out);
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+            out.push('{');
+            for (index, (key, item)) in entries.into_iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                push_json_string(key, out);
+                out.push(':');
+                write_canonical_json(item, out);
+            }
+            out.push('}');
+        }
+    }
+}
+
+fn push_json_string(text: &str, out: &mut String) {
+    out.push_str(&serde_json::to_string(text).expect("strings always serialize"));
+}
+
+/// Integers print exactly; floats use the ECMAScript `Number#toString`
+/// rules that RFC 8785 adopts.
+fn canonical_number(number: &serde_json::Number) -> String {
+    if let Some(int) = number.as_i64() {
+        return int.to_string();
+    }
+    if let Some(int) = number.as_u64() {
+        return int.to_string();
+    }
+    let value = number.as_f64().unwrap_or(0.0);
+    if value == 0.0 {
+        return "0".to_string();
+    }
+    // `{:e}` gives the shortest round-tripping digits, e.g. `1.2345e3`.
+    let scientific = format!("{:e}", value.abs());
+    let (mantissa, exponent) = scientific
+        .split_once('e')
+        .expect("LowerExp always writes an exponent");
+    let exponent: i32 = exponent.parse().expect("LowerExp exponent is an integer");
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let k = digits.len() as i32;
+    let n = exponent + 1;
+    let body = if k <= n && n <= 21 {
+        format!("{digits}{}", "0".repeat((n - k) as usize))
+    } else if 0 < n && n <= 21 {
+        let (whole, fraction) = digits.split_at(n as usize);
+        format!("{whole}.{fraction}")
+    } else if -6 < n && n <= 0 {
+        format!("0.{}{digits}", "0".repeat((-n) as usize))
+    } else {
+        let (first, rest) = digits.split_at(1);
+        let fraction = if rest.is_empty() {
+            String::new()
+        } else {
+            format!(".{rest}")
+        };
+        let sign = if n > 0 { '+' } else { '-' };
+        format!("{first}{fraction}e{sign}{}", (n - 1).abs())
+    };
+    if value < 0.0 {
+        format!("-{body}")
+    } else {
+        body
+    }
+}
+
 fn apply_shape(value: &mut Value, shape: OutputShape, projection_target: CompactProjectionTarget) {
     if !matches!(shape, OutputShape::Compact) {
         return;
@@ -68,7 +167,8 @@ fn project_read_table_compact(value: &mut Value) {
 
 fn project_sheet_page_compact(value: &mut Value) {
     // Ticket 3109 contract: preserve active payload branch (`rows` | `compact` |
-    // `values_only`) and continuation fields without flattening or collapsing.
+    // `values_only` | `column_page`) and continuation fields without flattening
+    // or collapsing.
     // Global pruning already removed empty wrappers before projection.
     let _ = value;
 }
@@ -108,6 +208,18 @@ mod tests {
     use super::*;
     use serde_json::json;
 
+    #[test]
+    fn canonical_json_sorts_keys_and_normalizes_numbers() {
+        let payload = json!({
+            "zeta": [1.0, -0.0, 0.1, 1e21, 1.5e-7, 123456789012.5, -42],
+            "alpha": {"b": true, "a": null, "é": "x", "Z": "\u{1f600}\n"},
+        });
+        assert_eq!(
+            to_canonical_json(&payload),
+            r#"{"alpha":{"Z":"😀\n","a":null,"b":true,"é":"x"},"zeta":[1,0,0.1,1e+21,1.5e-7,123456789012.5,-42]}"#
+        );
+    }
+
     #[test]
     fn compact_shape_3109_formula_trace_omits_highlights_only_for_trace_target() {
         let mut compact_payload = json!({
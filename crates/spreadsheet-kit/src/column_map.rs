@@ -0,0 +1,425 @@
+//! Column correspondence between two differently-labeled tables.
+//!
+//! `map-columns` scores every (source column, target column) pair by how alike
+//! their headers read and how alike their values look, then pairs columns one
+//! to one, best score first. The result is a mapping file that `append-region
+//! --column-map` uses to line CSV columns up with the target table.
+
+use crate::model::CellValuePrimitive;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Weight of the header score in a pair's combined score; values make up the
+/// rest.
+const HEADER_WEIGHT: f64 = 0.6;
+/// Distinct values compared per column.
+const VALUE_SAMPLE_LIMIT: usize = 500;
+
+/// Headers and row values of one side of a mapping.
+#[derive(Debug, Clone)]
+pub struct TableSample {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<Option<CellValuePrimitive>>>,
+}
+
+/// Where a table was read from, as recorded in a mapping file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnMapTable {
+    pub file: String,
+    pub sheet_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub table_name: Option<String>,
+    /// Header of every column, in column order.
+    pub headers: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnMatch {
+    pub source: String,
+    pub target: String,
+    /// Combined score in `0.0..=1.0`.
+    pub score: f64,
+    pub header_score: f64,
+    pub value_score: f64,
+}
+
+/// Mapping file written by `map-columns`. Only `target.headers` and
+/// `columns` are needed to apply it; scores are there for review.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnMap {
+    pub source: ColumnMapTable,
+    pub target: ColumnMapTable,
+    pub columns: Vec<ColumnMatch>,
+    #[serde(default)]
+    pub unmapped_source: Vec<String>,
+    #[serde(default)]
+    pub unmapped_target: Vec<String>,
+}
+
+impl ColumnMap {
+    /// Source header mapped to `target_header`, if any.
+    pub fn source_for(&self, target_header: &str) -> Option<&str> {
+        self.columns
+            .iter()
+            .find(|column| column.target == target_header)
+            .map(|column| column.source.as_str())
+    }
+}
+
+/// Pair source columns with target columns, keeping pairs that score at
+/// least `min_score`.
+pub fn propose_column_map(
+    source: (&TableSample, ColumnMapTable),
+    target: (&TableSample, ColumnMapTable),
+    min_score: f64,
+) -> ColumnMap {
+    let (source_sample, source_table) = source;
+    let (target_sample, target_table) = target;
+    let source_profiles: Vec<ColumnProfile> = (0..source_sample.headers.len())
+        .map(|idx| ColumnProfile::new(source_sample, idx))
+        .collect();
+    let target_profiles: Vec<ColumnProfile> = (0..target_sample.headers.len())
+        .map(|idx| ColumnProfile::new(target_sample, idx))
+        .collect();
+
+    let mut candidates = Vec::new();
+    for (s, source_profile) in source_profiles.iter().enumerate() {
+        for (t, target_profile) in target_profiles.iter().enumerate() {
+            let header_score =
+                header_similarity(&source_sample.headers[s], &target_sample.headers[t]);
+            let value_score = source_profile.similarity(target_profile);
+            let score = HEADER_WEIGHT * header_score + (1.0 - HEADER_WEIGHT) * value_score;
+            candidates.push((
+                s,
+                t,
+                round3(score),
+                round3(header_score),
+                round3(value_score),
+            ));
+        }
+    }
+    // Best score first; ties go to the leftmost columns so output is stable.
+    candidates.sort_by(|a, b| b.2.total_cmp(&a.2).then(a.0.cmp(&b.0)).then(a.1.cmp(&b.1)));
+
+    let mut used_source = BTreeSet::new();
+    let mut used_target = BTreeSet::new();
+    let mut columns = Vec::new();
+    for (s, t, score, header_score, value_score) in candidates {
+        if score < min_score || used_source.contains(&s) || used_target.contains(&t) {
+            continue;
+        }
+        used_source.insert(s);
+        used_target.insert(t);
+        columns.push((
+            t,
+            ColumnMatch {
+                source: source_sample.headers[s].clone(),
+                target: target_sample.headers[t].clone(),
+                score,
+                header_score,
+                value_score,
+            },
+        ));
+    }
+    columns.sort_by_key(|(t, _)| *t);
+
+    ColumnMap {
+        unmapped_source: (0..source_sample.headers.len())
+            .filter(|idx| !used_source.contains(idx))
+            .map(|idx| source_sample.headers[idx].clone())
+            .collect(),
+        unmapped_target: (0..target_sample.headers.len())
+            .filter(|idx| !used_target.contains(idx))
+            .map(|idx| target_sample.headers[idx].clone())
+            .collect(),
+        columns: columns.into_iter().map(|(_, column)| column).collect(),
+        source: source_table,
+        target: target_table,
+    }
+}
+
+pub(crate) fn round3(value: f64) -> f64 {
+    (value * 1000.0).round() / 1000.0
+}
+
+/// Similarity of two headers: the better of word overlap and edit distance
+/// over the letters and digits, so "Cust. Name" ~ "customer_name" and
+/// "Qty" ~ "Quantity" both score.
+pub(crate) fn header_similarity(a: &str, b: &str) -> f64 {
+    let words_a = header_words(a);
+    let words_b = header_words(b);
+    if words_a.is_empty() || words_b.is_empty() {
+        return 0.0;
+    }
+    let compact_a: String = words_a.concat();
+    let compact_b: String = words_b.concat();
+    if compact_a == compact_b {
+        return 1.0;
+    }
+
+    // Words match when equal or when one abbreviates the other ("cust").
+    let matched = words_a
+        .iter()
+        .filter(|word| words_b.iter().any(|other| words_match(word, other)))
+        .count()
+        + words_b
+            .iter()
+            .filter(|word| words_a.iter().any(|other| words_match(word, other)))
+            .count();
+    let word_score = matched as f64 / (words_a.len() + words_b.len()) as f64;
+
+    let distance = levenshtein(&compact_a, &compact_b);
+    let longest = compact_a.chars().count().max(compact_b.chars().count());
+    let edit_score = 1.0 - distance as f64 / longest as f64;
+
+    let abbreviation_score =
+        if is_abbreviation(&compact_a, &compact_b) || is_abbreviation(&compact_b, &compact_a) {
+            0.8
+        } else {
+            0.0
+        };
+
+    word_score.max(edit_score).max(abbreviation_score)
+}
+
+fn header_words(header: &str) -> Vec<String> {
+    header
+        .split(|ch: char| !ch.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect()
+}
+
+fn words_match(a: &str, b: &str) -> bool {
+    a == b || (a.len() >= 3 && b.starts_with(a)) || (b.len() >= 3 && a.starts_with(b))
+}
+
+/// `short` keeps the first letter of `long` and the rest of its letters in
+/// order ("qty" for "quantity", "amt" for "amount").
+fn is_abbreviation(short: &str, long: &str) -> bool {
+    if short.len() < 2 || short.len() >= long.len() {
+        return false;
+    }
+    let mut long_chars = long.chars();
+    let mut short_chars = short.chars();
+    if short_chars.next() != long_chars.next() {
+        return false;
+    }
+    short_chars.all(|ch| long_chars.any(|other| other == ch))
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b_chars: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b_chars.len()).collect();
+    for (i, ch_a) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b_chars.len() + 1];
+        for (j, ch_b) in b_chars.iter().enumerate() {
+            let cost = usize::from(ch_a != *ch_b);
+            current[j + 1] = (previous[j] + cost)
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b_chars.len()]
+}
+
+/// What one column's values look like.
+struct ColumnProfile {
+    numbers: usize,
+    texts: usize,
+    bools: usize,
+    min: f64,
+    max: f64,
+    /// Distinct lowercased text values (capped).
+    values: BTreeSet<String>,
+    /// Distinct value shapes: letters as `a`, digits as `9` ("INV-0042" -> "aaa-9999").
+    shapes: BTreeSet<String>,
+}
+
+impl ColumnProfile {
+    fn new(sample: &TableSample, column: usize) -> Self {
+        let mut profile = ColumnProfile {
+            numbers: 0,
+            texts: 0,
+            bools: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            values: BTreeSet::new(),
+            shapes: BTreeSet::new(),
+        };
+        for row in &sample.rows {
+            match row.get(column).cloned().flatten() {
+                Some(CellValuePrimitive::Number(value)) => {
+                    profile.numbers += 1;
+                    profile.min = profile.min.min(value);
+                    profile.max = profile.max.max(value);
+                }
+                Some(CellValuePrimitive::Text(text)) => {
+                    let text = text.trim().to_lowercase();
+                    if text.is_empty() {
+                        continue;
+                    }
+                    profile.texts += 1;
+                    if profile.values.len() < VALUE_SAMPLE_LIMIT {
+                        profile.shapes.insert(value_shape(&text));
+                        profile.values.insert(text);
+                    }
+                }
+                Some(CellValuePrimitive::Bool(_)) => profile.bools += 1,
+                None => {}
+            }
+        }
+        profile
+    }
+
+    fn filled(&self) -> usize {
+        self.numbers + self.texts + self.bools
+    }
+
+    fn similarity(&self, other: &ColumnProfile) -> f64 {
+        let (total_a, total_b) = (self.filled(), other.filled());
+        if total_a == 0 || total_b == 0 {
+            return 0.0;
+        }
+        let share = |count: usize, total: usize| count as f64 / total as f64;
+        let type_distance = (share(self.numbers, total_a) - share(other.numbers, total_b)).abs()
+            + (share(self.texts, total_a) - share(other.texts, total_b)).abs()
+            + (share(self.bools, total_a) - share(other.bools, total_b)).abs();
+        let type_score = 1.0 - type_distance / 2.0;
+
+        let numeric_weight = share(self.numbers, total_a).min(share(other.numbers, total_b));
+        let text_weight = share(self.texts, total_a).min(share(other.texts, total_b));
+        let bool_weight = share(self.bools, total_a).min(share(other.bools, total_b));
+        let weight = numeric_weight + text_weight + bool_weight;
+        if weight == 0.0 {
+            return 0.0;
+        }
+        let content = (numeric_weight * self.range_overlap(other)
+            + text_weight * self.text_overlap(other)
+            + bool_weight)
+            / weight;
+        type_score * content
+    }
+
+    /// Overlap of the two value ranges relative to their union.
+    fn range_overlap(&self, other: &ColumnProfile) -> f64 {
+        if self.numbers == 0 || other.numbers == 0 {
+            return 0.0;
+        }
+        let union = self.max.max(other.max) - self.min.min(other.min);
+        if union == 0.0 {
+            return 1.0;
+        }
+        let overlap = self.max.min(other.max) - self.min.max(other.min);
+        (overlap.max(0.0) / union).max(if overlap >= 0.0 { 0.1 } else { 0.0 })
+    }
+
+    /// Shared values, or failing that shared value shapes.
+    fn text_overlap(&self, other: &ColumnProfile) -> f64 {
+        let value_score = jaccard(&self.values, &other.values);
+        let shape_score = 0.8 * jaccard(&self.shapes, &other.shapes);
+        value_score.max(shape_score)
+    }
+}
+
+fn value_shape(text: &str) -> String {
+    text.chars()
+        .map(|ch| {
+            if ch.is_alphabetic() {
+                'a'
+            } else if ch.is_ascii_digit() {
+                '9'
+            } else {
+                ch
+            }
+        })
+        .collect()
+}
+
+fn jaccard(a: &BTreeSet<String>, b: &BTreeSet<String>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(headers: &[&str], rows: Vec<Vec<CellValuePrimitive>>) -> TableSample {
+        TableSample {
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+            rows: rows
+                .into_iter()
+                .map(|row| row.into_iter().map(Some).collect())
+                .collect(),
+        }
+    }
+
+    fn table(headers: &[&str]) -> ColumnMapTable {
+        ColumnMapTable {
+            file: "book.xlsx".to_string(),
+            sheet_name: "Sheet1".to_string(),
+            table_name: None,
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn header_similarity_handles_case_punctuation_and_abbreviations() {
+        assert_eq!(header_similarity("Customer Name", "customer_name"), 1.0);
+        assert!(header_similarity("Cust. Name", "Customer Name") >= 0.99);
+        assert!(header_similarity("Qty", "Quantity") >= 0.8);
+        assert!(header_similarity("Region", "Amount") < 0.3);
+    }
+
+    #[test]
+    fn maps_renamed_columns_by_header_and_values() {
+        use CellValuePrimitive::{Number, Text};
+        let source = sample(
+            &["Amt", "Client", "Ref"],
+            vec![
+                vec![Number(120.0), Text("Acme".into()), Text("INV-0001".into())],
+                vec![Number(80.0), Text("Globex".into()), Text("INV-0002".into())],
+            ],
+        );
+        let target = sample(
+            &["Invoice", "Customer", "Amount", "Notes"],
+            vec![
+                vec![Text("INV-0107".into()), Text("Acme".into()), Number(95.0)],
+                vec![
+                    Text("INV-0108".into()),
+                    Text("Globex".into()),
+                    Number(150.0),
+                ],
+            ],
+        );
+        let map = propose_column_map(
+            (&source, table(&headers_of(&source))),
+            (&target, table(&headers_of(&target))),
+            0.3,
+        );
+        let pairs: Vec<(&str, &str)> = map
+            .columns
+            .iter()
+            .map(|c| (c.source.as_str(), c.target.as_str()))
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![
+                ("Ref", "Invoice"),
+                ("Client", "Customer"),
+                ("Amt", "Amount")
+            ]
+        );
+        assert_eq!(map.unmapped_target, vec!["Notes".to_string()]);
+        assert_eq!(map.source_for("Customer"), Some("Client"));
+    }
+
+    fn headers_of(sample: &TableSample) -> Vec<&str> {
+        sample.headers.iter().map(String::as_str).collect()
+    }
+}
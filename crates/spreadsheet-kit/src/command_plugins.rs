@@ -0,0 +1,98 @@
+//! Shared plumbing for plugins backed by external commands.
+//!
+//! A plugin directory holds one `*.json`, `*.yaml` or `*.yml` manifest per
+//! plugin. Each plugin command runs through the platform shell from its
+//! manifest's directory, reads one JSON request on stdin and prints one JSON
+//! response on stdout. A non-zero exit fails the call with the command's
+//! stderr.
+
+use crate::hooks::{run_with_stdin, shell_command};
+use crate::utils::cell_address;
+use anyhow::{Context, Result, anyhow, bail};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::fs;
+use std::path::{Path, PathBuf};
+use umya_spreadsheet::Worksheet;
+
+/// A non-empty cell as sent to plugin commands.
+#[derive(Debug, Serialize)]
+pub struct PluginCell {
+    pub address: String,
+    pub value: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub formula: Option<String>,
+}
+
+/// Manifest files in `dir`, sorted by path.
+pub(crate) fn manifest_paths(dir: &Path) -> Result<Vec<PathBuf>> {
+    let entries =
+        fs::read_dir(dir).with_context(|| format!("unable to read plugin dir {:?}", dir))?;
+    let mut manifests: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| matches!(ext, "json" | "yaml" | "yml"))
+        })
+        .collect();
+    manifests.sort();
+    Ok(manifests)
+}
+
+/// Parse a manifest; JSON manifests parse as YAML too.
+pub(crate) fn read_manifest<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("unable to read {:?}", path))?;
+    serde_yaml::from_str(&contents).with_context(|| format!("invalid plugin manifest {:?}", path))
+}
+
+/// Run `command` from `dir` with `request` on stdin and parse its stdout.
+pub(crate) fn run_json_command<T: DeserializeOwned>(
+    command: &str,
+    dir: &Path,
+    envs: &[(&str, &str)],
+    request: &impl Serialize,
+) -> Result<T> {
+    let mut process = shell_command(command);
+    process.current_dir(dir).envs(envs.iter().copied());
+    let output = run_with_stdin(process, serde_json::to_vec(request)?)
+        .map_err(|error| anyhow!("command '{command}' could not start: {error}"))?;
+    if !output.status.success() {
+        let status = output
+            .status
+            .code()
+            .map(|code| format!("exit code {code}"))
+            .unwrap_or_else(|| "terminated by signal".to_string());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let detail = stderr.trim();
+        if detail.is_empty() {
+            bail!("command '{command}' failed with {status}");
+        }
+        bail!("command '{command}' failed with {status}: {detail}");
+    }
+    serde_json::from_slice(&output.stdout)
+        .with_context(|| format!("command '{command}' printed an invalid response"))
+}
+
+/// Non-empty cells of `sheet` in row-major order.
+pub(crate) fn sheet_cells(sheet: &Worksheet) -> Vec<PluginCell> {
+    let mut cells: Vec<_> = sheet
+        .get_cell_collection()
+        .into_iter()
+        .map(|cell| {
+            let coordinate = cell.get_coordinate();
+            let row = *coordinate.get_row_num();
+            let col = *coordinate.get_col_num();
+            let cell = PluginCell {
+                address: cell_address(col, row),
+                value: cell.get_value().to_string(),
+                formula: cell.is_formula().then(|| cell.get_formula().to_string()),
+            };
+            ((row, col), cell)
+        })
+        .filter(|(_, cell)| !cell.value.is_empty() || cell.formula.is_some())
+        .collect();
+    cells.sort_by_key(|(position, _)| *position);
+    cells.into_iter().map(|(_, cell)| cell).collect()
+}
@@ -1,8 +1,14 @@
+use crate::repository::WorkbookAliases;
+use crate::repository::alias::{
+    load_workbook_aliases, validate_alias_name, workspace_aliases_file,
+};
+use crate::schedule::{ScheduleConfig, ScheduleMode};
+use crate::tools::named_query::{NamedQueries, load_named_queries, workspace_queries_file};
 use anyhow::{Context, Result};
 use clap::{Parser, ValueEnum};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::fs;
 use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
@@ -10,7 +16,7 @@ use std::time::Duration;
 
 const DEFAULT_CACHE_CAPACITY: usize = 5;
 const DEFAULT_MAX_RECALCS: usize = 2;
-const DEFAULT_EXTENSIONS: &[&str] = &["xlsx", "xlsm", "xls", "xlsb"];
+pub(crate) const DEFAULT_EXTENSIONS: &[&str] = &["xlsx", "xlsm", "xls", "xlsb"];
 const DEFAULT_HTTP_BIND: &str = "127.0.0.1:8079";
 const DEFAULT_TOOL_TIMEOUT_MS: u64 = 30_000;
 const DEFAULT_MAX_RESPONSE_BYTES: u64 = 1_000_000;
@@ -49,12 +55,25 @@ pub enum OutputProfile {
 )]
 #[serde(rename_all = "lowercase")]
 pub enum RecalcBackendKind {
+    #[value(alias = "builtin")]
+    #[serde(alias = "builtin")]
     Formualizer,
     Libreoffice,
     #[default]
     Auto,
 }
 
+/// How the LibreOffice engine drives soffice for a recalc.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExecutorStrategy {
+    /// Launch one headless soffice process per recalc.
+    #[default]
+    FireAndForget,
+    /// Reuse a long-lived soffice over a UNO socket. Not implemented yet;
+    /// runs fire-and-forget with a warning.
+    Pooled,
+}
+
 #[derive(Debug, Clone)]
 pub struct ServerConfig {
     pub workspace_root: PathBuf,
@@ -80,6 +99,32 @@ pub struct ServerConfig {
     pub max_cells: Option<u64>,
     pub max_items: Option<u64>,
     pub allow_overwrite: bool,
+    /// Stored `read_table` queries addressable by name (`query` param).
+    pub named_queries: NamedQueries,
+    /// Stable names for workbooks, accepted wherever a workbook id is.
+    pub workbook_aliases: WorkbookAliases,
+    /// Serve workbooks uploaded over the protocol instead of scanning
+    /// `workspace_root`.
+    pub virtual_workspace: bool,
+    /// Pin each workbook to the revision first read in an MCP session, so
+    /// paged reads stay consistent while the file is saved elsewhere.
+    pub pin_revisions: bool,
+    /// Directory for the on-disk parse cache; `None` disables it.
+    pub parse_cache_dir: Option<PathBuf>,
+    /// Scratch root for fork copies, checkpoints, and staged snapshots;
+    /// `None` uses `SPREADSHEET_MCP_SCRATCH_DIR` or the system temp directory.
+    pub scratch_dir: Option<PathBuf>,
+    /// Quota on this server's scratch usage; `None` is unlimited.
+    pub scratch_max_bytes: Option<u64>,
+    /// `s3://` or `gs://` URL that fork checkpoints are kept in instead of
+    /// scratch; needs the `object-store` feature.
+    pub checkpoint_store: Option<String>,
+    /// Scheduled jobs from the config file's `schedule` block.
+    pub schedule: Option<ScheduleConfig>,
+    /// Run the schedule instead of serving a transport.
+    pub schedule_mode: Option<ScheduleMode>,
+    /// Config file profile layered over its top-level settings.
+    pub profile: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -133,13 +178,23 @@ impl ServerConfig {
             max_cells: cli_max_cells,
             max_items: cli_max_items,
             allow_overwrite: cli_allow_overwrite,
+            virtual_workspace: cli_virtual_workspace,
+            pin_revisions: cli_pin_revisions,
+            parse_cache_dir: cli_parse_cache_dir,
+            scratch_dir: cli_scratch_dir,
+            scratch_max_bytes: cli_scratch_max_bytes,
+            checkpoint_store: cli_checkpoint_store,
+            schedule: cli_schedule,
+            schedule_once: cli_schedule_once,
+            profile: cli_profile,
         } = args;
 
-        let file_config = if let Some(path) = config.as_ref() {
+        let mut file_config = if let Some(path) = config.as_ref() {
             load_config_file(path)?
         } else {
             PartialConfig::default()
         };
+        let profile = file_config.select_profile(cli_profile)?;
 
         let PartialConfig {
             workspace_root: file_workspace_root,
@@ -162,6 +217,17 @@ impl ServerConfig {
             max_cells: file_max_cells,
             max_items: file_max_items,
             allow_overwrite: file_allow_overwrite,
+            named_queries: file_named_queries,
+            workbook_aliases: file_workbook_aliases,
+            virtual_workspace: file_virtual_workspace,
+            pin_revisions: file_pin_revisions,
+            parse_cache_dir: file_parse_cache_dir,
+            scratch_dir: file_scratch_dir,
+            scratch_max_bytes: file_scratch_max_bytes,
+            checkpoint_store: file_checkpoint_store,
+            schedule: file_schedule,
+            default_profile: _,
+            profiles: _,
         } = file_config;
 
         let mut path_mappings = Vec::new();
@@ -344,6 +410,65 @@ impl ServerConfig {
 
         let allow_overwrite = cli_allow_overwrite || file_allow_overwrite.unwrap_or(false);
 
+        let virtual_workspace = cli_virtual_workspace || file_virtual_workspace.unwrap_or(false);
+
+        let pin_revisions = cli_pin_revisions || file_pin_revisions.unwrap_or(false);
+
+        let parse_cache_dir = cli_parse_cache_dir.or(file_parse_cache_dir);
+
+        let scratch_dir = cli_scratch_dir.or(file_scratch_dir);
+        let scratch_max_bytes = cli_scratch_max_bytes
+            .or(file_scratch_max_bytes)
+            .filter(|bytes| *bytes > 0);
+
+        let checkpoint_store = cli_checkpoint_store
+            .or(file_checkpoint_store)
+            .map(|url| url.trim().to_string())
+            .filter(|url| !url.is_empty());
+        if let Some(url) = checkpoint_store.as_deref() {
+            validate_checkpoint_store(url)?;
+        }
+
+        let schedule_mode = if cli_schedule_once {
+            Some(ScheduleMode::Once)
+        } else if cli_schedule {
+            Some(ScheduleMode::Loop)
+        } else {
+            None
+        };
+        if let Some(schedule) = file_schedule.as_ref() {
+            schedule.validate(allow_overwrite)?;
+        }
+        if schedule_mode.is_some() {
+            anyhow::ensure!(
+                file_schedule
+                    .as_ref()
+                    .is_some_and(|schedule| !schedule.jobs.is_empty()),
+                "--schedule needs a `schedule` block with at least one job in --config"
+            );
+        }
+
+        let named_queries = match file_named_queries {
+            Some(queries) => queries,
+            None => match workspace_queries_file(&workspace_root) {
+                Some(path) => load_named_queries(&path)?,
+                None => NamedQueries::new(),
+            },
+        };
+
+        let workbook_aliases = match file_workbook_aliases {
+            Some(aliases) => {
+                for alias in aliases.keys() {
+                    validate_alias_name(alias)?;
+                }
+                aliases
+            }
+            None => match workspace_aliases_file(&workspace_root) {
+                Some(path) => load_workbook_aliases(&path)?,
+                None => WorkbookAliases::new(),
+            },
+        };
+
         Ok(Self {
             workspace_root,
             screenshot_dir,
@@ -365,6 +490,17 @@ impl ServerConfig {
             max_cells,
             max_items,
             allow_overwrite,
+            named_queries,
+            workbook_aliases,
+            virtual_workspace,
+            pin_revisions,
+            parse_cache_dir,
+            scratch_dir,
+            scratch_max_bytes,
+            checkpoint_store,
+            schedule: file_schedule,
+            schedule_mode,
+            profile,
         })
     }
 
@@ -587,8 +723,7 @@ pub struct CliArgs {
         env = "SPREADSHEET_MCP_RECALC_BACKEND",
         value_enum,
         value_name = "KIND",
-        default_value = "auto",
-        help = "Recalc backend preference: auto, formualizer, or libreoffice"
+        help = "Recalc backend preference: auto (default), formualizer (alias builtin), or libreoffice"
     )]
     pub recalc_backend: Option<RecalcBackendKind>,
 
@@ -666,6 +801,74 @@ pub struct CliArgs {
         help = "Allow save_fork to overwrite original workbook files"
     )]
     pub allow_overwrite: bool,
+
+    #[arg(
+        long,
+        env = "SPREADSHEET_MCP_VIRTUAL_WORKSPACE",
+        help = "Serve an in-memory workspace filled via upload_workbook instead of scanning the workspace root"
+    )]
+    pub virtual_workspace: bool,
+
+    #[arg(
+        long,
+        env = "SPREADSHEET_MCP_PIN_REVISIONS",
+        help = "Pin each workbook to the revision first read in a session so paged reads stay consistent if the file is saved mid-session; close_workbook releases the pin"
+    )]
+    pub pin_revisions: bool,
+
+    #[arg(
+        long,
+        env = "SPREADSHEET_MCP_PARSE_CACHE_DIR",
+        value_name = "DIR",
+        help = "Cache extracted workbook structure in DIR, keyed by file content, to skip re-parsing unchanged workbooks"
+    )]
+    pub parse_cache_dir: Option<PathBuf>,
+
+    #[arg(
+        long,
+        env = "SPREADSHEET_MCP_SCRATCH_DIR",
+        value_name = "DIR",
+        help = "Scratch root for fork copies, checkpoints, and temp files; orphans older than 15 minutes are removed on startup (default: <system temp>/spreadsheet-mcp)"
+    )]
+    pub scratch_dir: Option<PathBuf>,
+
+    #[arg(
+        long,
+        env = "SPREADSHEET_MCP_SCRATCH_MAX_BYTES",
+        value_name = "BYTES",
+        help = "Max scratch bytes this server may use for forks, checkpoints, and staged changes (default: unlimited; 0 disables)",
+        value_parser = clap::value_parser!(u64)
+    )]
+    pub scratch_max_bytes: Option<u64>,
+
+    #[arg(
+        long,
+        env = "SPREADSHEET_MCP_CHECKPOINT_STORE",
+        value_name = "URL",
+        help = "Keep fork checkpoints in object storage (s3://bucket/prefix or gs://bucket/prefix) instead of scratch; needs the object-store feature"
+    )]
+    pub checkpoint_store: Option<String>,
+
+    #[arg(
+        long,
+        env = "SPREADSHEET_MCP_SCHEDULE",
+        help = "Run the jobs in the config file's `schedule` block at their cron times instead of serving a transport"
+    )]
+    pub schedule: bool,
+
+    #[arg(
+        long = "schedule-once",
+        help = "Run every scheduled job once and exit; fails if any job failed or raised an alert"
+    )]
+    pub schedule_once: bool,
+
+    #[arg(
+        long,
+        env = "SPREADSHEET_MCP_PROFILE",
+        value_name = "NAME",
+        help = "Apply a named profile from the config file's `profiles` block over its top-level settings (default: its `default_profile`)"
+    )]
+    pub profile: Option<String>,
 }
 
 #[derive(Debug, Default, Deserialize)]
@@ -690,6 +893,118 @@ struct PartialConfig {
     max_cells: Option<u64>,
     max_items: Option<u64>,
     allow_overwrite: Option<bool>,
+    named_queries: Option<NamedQueries>,
+    workbook_aliases: Option<WorkbookAliases>,
+    virtual_workspace: Option<bool>,
+    pin_revisions: Option<bool>,
+    parse_cache_dir: Option<PathBuf>,
+    scratch_dir: Option<PathBuf>,
+    scratch_max_bytes: Option<u64>,
+    checkpoint_store: Option<String>,
+    schedule: Option<ScheduleConfig>,
+    default_profile: Option<String>,
+    profiles: Option<BTreeMap<String, ConfigProfile>>,
+}
+
+/// Named bundle of policy, caps, output and recalc settings. The selected
+/// profile overrides the file's top-level values; command-line flags and
+/// environment variables still override both.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigProfile {
+    extensions: Option<Vec<String>>,
+    enabled_tools: Option<Vec<String>>,
+    allow_overwrite: Option<bool>,
+    virtual_workspace: Option<bool>,
+    pin_revisions: Option<bool>,
+    vba_enabled: Option<bool>,
+    recalc_enabled: Option<bool>,
+    recalc_backend: Option<RecalcBackendKind>,
+    max_concurrent_recalcs: Option<usize>,
+    cache_capacity: Option<usize>,
+    tool_timeout_ms: Option<u64>,
+    max_response_bytes: Option<u64>,
+    max_payload_bytes: Option<u64>,
+    max_cells: Option<u64>,
+    max_items: Option<u64>,
+    scratch_max_bytes: Option<u64>,
+    output_profile: Option<OutputProfile>,
+}
+
+impl PartialConfig {
+    /// Layer the profile named on the command line, else `default_profile`,
+    /// over the top-level settings, and return its name.
+    fn select_profile(&mut self, requested: Option<String>) -> Result<Option<String>> {
+        let requested = requested
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty());
+        let Some(name) = requested.or_else(|| self.default_profile.clone()) else {
+            return Ok(None);
+        };
+        let mut profiles = self.profiles.take().unwrap_or_default();
+        let Some(profile) = profiles.remove(&name) else {
+            let available = profiles.keys().cloned().collect::<Vec<_>>();
+            if available.is_empty() {
+                anyhow::bail!("profile '{name}' not found: the config file defines no profiles");
+            }
+            anyhow::bail!(
+                "profile '{name}' not found (available: {})",
+                available.join(", ")
+            );
+        };
+
+        let ConfigProfile {
+            extensions,
+            enabled_tools,
+            allow_overwrite,
+            virtual_workspace,
+            pin_revisions,
+            vba_enabled,
+            recalc_enabled,
+            recalc_backend,
+            max_concurrent_recalcs,
+            cache_capacity,
+            tool_timeout_ms,
+            max_response_bytes,
+            max_payload_bytes,
+            max_cells,
+            max_items,
+            scratch_max_bytes,
+            output_profile,
+        } = profile;
+        self.extensions = extensions.or(self.extensions.take());
+        self.enabled_tools = enabled_tools.or(self.enabled_tools.take());
+        self.allow_overwrite = allow_overwrite.or(self.allow_overwrite);
+        self.virtual_workspace = virtual_workspace.or(self.virtual_workspace);
+        self.pin_revisions = pin_revisions.or(self.pin_revisions);
+        self.vba_enabled = vba_enabled.or(self.vba_enabled);
+        self.recalc_enabled = recalc_enabled.or(self.recalc_enabled);
+        self.recalc_backend = recalc_backend.or(self.recalc_backend);
+        self.max_concurrent_recalcs = max_concurrent_recalcs.or(self.max_concurrent_recalcs);
+        self.cache_capacity = cache_capacity.or(self.cache_capacity);
+        self.tool_timeout_ms = tool_timeout_ms.or(self.tool_timeout_ms);
+        self.max_response_bytes = max_response_bytes.or(self.max_response_bytes);
+        self.max_payload_bytes = max_payload_bytes.or(self.max_payload_bytes);
+        self.max_cells = max_cells.or(self.max_cells);
+        self.max_items = max_items.or(self.max_items);
+        self.scratch_max_bytes = scratch_max_bytes.or(self.scratch_max_bytes);
+        self.output_profile = output_profile.or(self.output_profile);
+        Ok(Some(name))
+    }
+}
+
+/// Check the checkpoint store URL and its credentials at startup rather
+/// than on the first checkpoint.
+#[cfg(all(not(target_arch = "wasm32"), feature = "object-store"))]
+fn validate_checkpoint_store(url: &str) -> Result<()> {
+    crate::object_store::ObjectStore::from_url(url)
+        .map(|_| ())
+        .with_context(|| format!("invalid checkpoint_store '{url}'"))
+}
+
+#[cfg(not(all(not(target_arch = "wasm32"), feature = "object-store")))]
+fn validate_checkpoint_store(url: &str) -> Result<()> {
+    anyhow::bail!("checkpoint_store '{url}' needs a build with the object-store feature")
 }
 
 fn load_config_file(path: &Path) -> Result<PartialConfig> {
@@ -25,9 +25,62 @@ pub async fn execute_with_backend(
     })
 }
 
+/// Recalculate only the given formula cells with the built-in engine. The
+/// LibreOffice engine has no targeted mode, so partial recalculation requires
+/// `recalc-formualizer`.
+#[cfg(feature = "recalc")]
+pub async fn execute_partial(
+    path: &Path,
+    targets: Vec<(String, u32, u32)>,
+) -> Result<RecalculateOutcome> {
+    #[cfg(feature = "recalc-formualizer")]
+    {
+        let result = crate::recalc::FormualizerBackend
+            .recalculate_cells(path, targets)
+            .await?;
+        Ok(RecalculateOutcome {
+            backend: result.backend_name.to_string(),
+            duration_ms: result.duration_ms,
+            cells_evaluated: result.cells_evaluated,
+            eval_errors: result.eval_errors,
+        })
+    }
+
+    #[cfg(not(feature = "recalc-formualizer"))]
+    {
+        let _ = (path, targets);
+        Err(anyhow!(
+            "partial recalculation requires the built-in engine (rebuild with --features recalc-formualizer)"
+        ))
+    }
+}
+
 #[cfg(feature = "recalc")]
 pub fn select_backend_from_env() -> Result<Arc<dyn crate::recalc::RecalcBackend>> {
     use crate::config::RecalcBackendKind;
+
+    let requested = match std::env::var("SPREADSHEET_MCP_RECALC_BACKEND") {
+        Ok(value) => parse_recalc_backend_kind(&value).ok_or_else(|| {
+            anyhow!(
+                "invalid SPREADSHEET_MCP_RECALC_BACKEND '{}'; expected auto, builtin, formualizer, or libreoffice",
+                value
+            )
+        })?,
+        Err(_) => RecalcBackendKind::Auto,
+    };
+    select_backend(requested, crate::config::ExecutorStrategy::default())
+}
+
+/// Resolve a recalc engine. `Auto` prefers the built-in formualizer engine and
+/// falls back to LibreOffice when only that is available; explicit requests
+/// fail with the reason the engine is missing instead of silently switching.
+/// `strategy` only affects the LibreOffice engine.
+#[cfg(feature = "recalc")]
+pub fn select_backend(
+    requested: crate::config::RecalcBackendKind,
+    strategy: crate::config::ExecutorStrategy,
+) -> Result<Arc<dyn crate::recalc::RecalcBackend>> {
+    use crate::config::RecalcBackendKind;
     use crate::recalc::RecalcBackend;
 
     #[cfg(feature = "recalc-formualizer")]
@@ -39,7 +92,10 @@ pub fn select_backend_from_env() -> Result<Arc<dyn crate::recalc::RecalcBackend>
     #[cfg(feature = "recalc-libreoffice")]
     let libreoffice: Option<Arc<dyn RecalcBackend>> = {
         let backend: Arc<dyn RecalcBackend> = Arc::new(crate::recalc::LibreOfficeBackend::new(
-            crate::recalc::RecalcConfig::default(),
+            crate::recalc::RecalcConfig {
+                strategy,
+                ..Default::default()
+            },
         ));
         if backend.is_available() {
             Some(backend)
@@ -48,34 +104,59 @@ pub fn select_backend_from_env() -> Result<Arc<dyn crate::recalc::RecalcBackend>
         }
     };
     #[cfg(not(feature = "recalc-libreoffice"))]
-    let libreoffice: Option<Arc<dyn RecalcBackend>> = None;
-
-    let requested = std::env::var("SPREADSHEET_MCP_RECALC_BACKEND")
-        .ok()
-        .and_then(|value| parse_recalc_backend_kind(&value))
-        .unwrap_or(RecalcBackendKind::Auto);
+    let libreoffice: Option<Arc<dyn RecalcBackend>> = {
+        let _ = strategy;
+        None
+    };
 
-    let selected = match requested {
-        RecalcBackendKind::Formualizer => formualizer,
-        RecalcBackendKind::Libreoffice => libreoffice,
-        RecalcBackendKind::Auto => formualizer.or(libreoffice),
+    let builtin_missing = if cfg!(feature = "recalc-formualizer") {
+        None
+    } else {
+        Some("the built-in engine is not compiled in (rebuild with --features recalc-formualizer)")
+    };
+    let libreoffice_missing = if cfg!(feature = "recalc-libreoffice") {
+        "LibreOffice (soffice) was not found on this machine"
+    } else {
+        "LibreOffice support is not compiled in (rebuild with --features recalc-libreoffice)"
     };
 
-    selected.ok_or_else(|| {
-        anyhow!(
-            "no recalc backend available for this build/runtime; requested {:?}",
-            requested
-        )
-    })
+    match requested {
+        RecalcBackendKind::Formualizer => formualizer.ok_or_else(|| {
+            anyhow!(
+                "recalc engine 'builtin' unavailable: {}",
+                builtin_missing.unwrap_or_default()
+            )
+        }),
+        RecalcBackendKind::Libreoffice => libreoffice.ok_or_else(|| {
+            if formualizer.is_some() {
+                anyhow!(
+                    "recalc engine 'libreoffice' unavailable: {}; use --engine builtin (or auto) to recalculate with the built-in engine",
+                    libreoffice_missing
+                )
+            } else {
+                anyhow!(
+                    "recalc engine 'libreoffice' unavailable: {}",
+                    libreoffice_missing
+                )
+            }
+        }),
+        RecalcBackendKind::Auto => formualizer.or(libreoffice).ok_or_else(|| {
+            anyhow!(
+                "no recalc engine available: {} and {}",
+                libreoffice_missing,
+                builtin_missing.unwrap_or_default()
+            )
+        }),
+    }
 }
 
 #[cfg(feature = "recalc")]
 fn parse_recalc_backend_kind(value: &str) -> Option<crate::config::RecalcBackendKind> {
     use crate::config::RecalcBackendKind;
 
-    match value.to_ascii_lowercase().as_str() {
+    match value.trim().to_ascii_lowercase().as_str() {
         "auto" => Some(RecalcBackendKind::Auto),
-        "formualizer" => Some(RecalcBackendKind::Formualizer),
+        "builtin" | "formualizer" => Some(RecalcBackendKind::Formualizer),
         "libreoffice" => Some(RecalcBackendKind::Libreoffice),
         _ => None,
     }
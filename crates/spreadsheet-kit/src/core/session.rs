@@ -8,7 +8,8 @@ use crate::model::{
     WorkbookId,
 };
 use crate::styles::descriptor_from_style;
-use crate::workbook::{WorkbookContext, cell_to_value};
+use crate::tools::workbook_props::{read_workbook_props_from_reader, rewrite_date1904};
+use crate::workbook::{DateSystemScope, WorkbookContext, cell_to_value};
 use anyhow::{Context, Result, anyhow};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -24,6 +25,9 @@ use umya_spreadsheet::{Spreadsheet, Worksheet};
 /// be reused by CLI, SDK, or WASM bindings.
 pub struct WorkbookSession {
     spreadsheet: Spreadsheet,
+    /// umya does not keep the workbook's date system, so it is tracked here and
+    /// applied to cell reads and serialization.
+    date1904: bool,
 }
 
 impl WorkbookSession {
@@ -33,7 +37,13 @@ impl WorkbookSession {
         let cursor = std::io::Cursor::new(workbook_bytes);
         let spreadsheet = umya_spreadsheet::reader::xlsx::read_reader(cursor, true)
             .context("failed to parse workbook bytes")?;
-        Ok(Self { spreadsheet })
+        let date1904 = read_workbook_props_from_reader(std::io::Cursor::new(workbook_bytes))
+            .map(|props| props.date1904)
+            .unwrap_or(false);
+        Ok(Self {
+            spreadsheet,
+            date1904,
+        })
     }
 
     /// Open a workbook session from a filesystem path.
@@ -392,6 +402,7 @@ impl WorkbookSession {
 
     /// Search for values in one sheet or across all sheets.
     pub fn find_value(&self, params: SessionFindValueParams) -> Result<FindValueResponse> {
+        let _date_system = DateSystemScope::enter(self.date1904);
         if params.query.trim().is_empty() {
             return Err(anyhow!("query is required"));
         }
@@ -464,12 +475,14 @@ impl WorkbookSession {
             workbook_id: WorkbookId("session".to_string()),
             match_count: matches.len() as u32,
             matches,
+            sheets: Vec::new(),
             next_offset: truncated.then_some(offset + limit),
         })
     }
 
     /// Read a rectangular table snapshot from a sheet.
     pub fn read_table(&self, params: SessionReadTableParams) -> Result<ReadTableResponse> {
+        let _date_system = DateSystemScope::enter(self.date1904);
         let sheet_name = if let Some(name) = params.sheet_name.clone() {
             name
         } else {
@@ -606,9 +619,12 @@ impl WorkbookSession {
             } else {
                 None
             },
+            date_serials: None,
             csv,
             total_rows: data_rows_count as u32,
             next_offset,
+            header_detection: None,
+            compressed: None,
         })
     }
 
@@ -618,6 +634,7 @@ impl WorkbookSession {
         sheet_name: &str,
         ranges: impl Into<SessionRangeSelection>,
     ) -> Result<Vec<RangeValuesEntry>> {
+        let _date_system = DateSystemScope::enter(self.date1904);
         let sheet = self.sheet_by_name_required(sheet_name)?;
         let ranges = ranges.into().into_vec();
         if ranges.is_empty() {
@@ -642,6 +659,7 @@ impl WorkbookSession {
                 range,
                 rows: Some(rows),
                 formulas: None,
+                formatting: None,
                 values: None,
                 dense: None,
                 csv: None,
@@ -655,6 +673,7 @@ impl WorkbookSession {
 
     /// Read a page-oriented snapshot from a sheet.
     pub fn sheet_page(&self, params: SessionSheetPageParams) -> Result<SheetPageResponse> {
+        let _date_system = DateSystemScope::enter(self.date1904);
         if params.page_size == 0 {
             return Err(anyhow!("page_size must be greater than zero"));
         }
@@ -699,6 +718,7 @@ impl WorkbookSession {
 
     /// Export a range as grid payload (value/formula/style patch surface).
     pub fn grid_export(&self, sheet_name: &str, range: &str) -> Result<GridPayload> {
+        let _date_system = DateSystemScope::enter(self.date1904);
         let sheet = self.sheet_by_name_required(sheet_name)?;
         let bounds = parse_range_bounds(range)?;
 
@@ -859,6 +879,12 @@ impl WorkbookSession {
         let mut bytes = Vec::new();
         umya_spreadsheet::writer::xlsx::write_writer(&self.spreadsheet, &mut bytes)
             .context("failed to serialize workbook to bytes")?;
+        if self.date1904
+            && let Some(rewritten) =
+                rewrite_date1904(&bytes, true).context("failed to restore 1904 date system")?
+        {
+            bytes = rewritten;
+        }
         Ok(bytes)
     }
 
@@ -888,6 +914,9 @@ impl WorkbookSession {
         let spreadsheet = umya_spreadsheet::reader::xlsx::read_reader(cursor, true)
             .context("failed to parse workbook after reload")?;
         self.spreadsheet = spreadsheet;
+        self.date1904 = read_workbook_props_from_reader(std::io::Cursor::new(&bytes))
+            .map(|props| props.date1904)
+            .unwrap_or(false);
         Ok(())
     }
 
@@ -928,6 +957,17 @@ impl WorkbookSession {
             max_cells: Some(10_000),
             max_items: Some(500),
             allow_overwrite: true,
+            named_queries: Default::default(),
+            workbook_aliases: Default::default(),
+            virtual_workspace: false,
+            pin_revisions: false,
+            parse_cache_dir: None,
+            scratch_dir: None,
+            scratch_max_bytes: None,
+            checkpoint_store: None,
+            schedule: None,
+            schedule_mode: None,
+            profile: None,
         });
 
         WorkbookContext::load_from_bytes(
@@ -1403,6 +1443,7 @@ fn style_descriptor_to_patch(desc: crate::model::StyleDescriptor) -> Option<Styl
             })
         }),
         number_format: None,
+        number_format_locale: None,
     })
 }
 
@@ -1479,7 +1520,16 @@ fn build_row_snapshot(
         }
     }
 
-    RowSnapshot { row_index, cells }
+    let hidden = sheet
+        .get_row_dimension(&row_index)
+        .is_some_and(|row| *row.get_hidden());
+
+    RowSnapshot {
+        row_index,
+        hidden,
+        outline_level: 0,
+        cells,
+    }
 }
 
 fn build_cell_snapshot(
@@ -1751,6 +1801,16 @@ fn build_compact_payload(
         headers,
         header_row,
         rows: data_rows,
+        hidden_rows: rows
+            .iter()
+            .filter(|row| row.hidden)
+            .map(|row| row.row_index)
+            .collect(),
+        row_outline_levels: rows
+            .iter()
+            .filter(|row| row.outline_level > 0)
+            .map(|row| (row.row_index, row.outline_level))
+            .collect(),
     }
 }
 
@@ -1811,9 +1871,16 @@ fn build_sheet_page_response(
         header_row,
         compact: compact_payload,
         values_only: values_only_payload,
+        orientation: None,
+        column_page: None,
+        next_start_column: None,
+        date_serials: None,
+        resolved_headers: Vec::new(),
+        unmatched_headers: Vec::new(),
         format,
         truncated: false,
         budget: None,
+        compressed: None,
     }
 }
 
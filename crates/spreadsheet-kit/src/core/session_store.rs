@@ -717,7 +717,7 @@ fn replay_event_on_session(
         "transform.clear_range" | "transform.fill_range" | "transform.replace_in_range" => {
             let ops: Vec<TransformOp> = deserialize_ops_array(payload)?;
             replay_via_temp_file(session, |path| {
-                apply_transform_ops_to_file(path, &ops)?;
+                apply_transform_ops_to_file(path, &ops, false)?;
                 Ok(())
             })?;
         }
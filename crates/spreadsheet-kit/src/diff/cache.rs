@@ -0,0 +1,86 @@
+//! Parsed cell streams kept across diffs, keyed by the hash of the sheet
+//! part and of the shared string table its string cells resolve against.
+//!
+//! Diffing one baseline against many candidates then parses each baseline
+//! sheet once, and a candidate sheet that is byte-identical to one already
+//! seen (for example the same edit in several scenarios) is not parsed again.
+
+use super::cells::RawCell;
+use parking_lot::Mutex;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Cells held across all cached parts before new parts stop being kept.
+pub const DEFAULT_CACHE_CELL_LIMIT: usize = 5_000_000;
+
+/// `(sheet part hash, shared strings hash)`
+pub type PartKey = (u64, u64);
+
+#[derive(Debug, Default, Clone, Copy, Serialize, JsonSchema)]
+pub struct PartCacheStats {
+    /// Sheet parts parsed into cell streams
+    pub parts_parsed: u64,
+    /// Sheet parts served from the cache instead of being parsed
+    pub parts_reused: u64,
+    /// Cells currently held
+    pub cells_cached: u64,
+}
+
+#[derive(Default)]
+struct PartCacheInner {
+    parts: HashMap<PartKey, Arc<Vec<RawCell>>>,
+    stats: PartCacheStats,
+}
+
+pub struct PartCache {
+    cell_limit: usize,
+    inner: Mutex<PartCacheInner>,
+}
+
+impl Default for PartCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CACHE_CELL_LIMIT)
+    }
+}
+
+impl PartCache {
+    /// A cache that stops keeping new parts once it holds `cell_limit`
+    /// cells; parts past the limit are still parsed, just not kept.
+    pub fn new(cell_limit: usize) -> Self {
+        Self {
+            cell_limit,
+            inner: Mutex::new(PartCacheInner::default()),
+        }
+    }
+
+    pub fn stats(&self) -> PartCacheStats {
+        self.inner.lock().stats
+    }
+
+    /// Cells of the part with `key`, parsing them with `parse` on a miss.
+    pub(crate) fn get_or_parse(
+        &self,
+        key: PartKey,
+        parse: impl FnOnce() -> anyhow::Result<Vec<RawCell>>,
+    ) -> anyhow::Result<Arc<Vec<RawCell>>> {
+        {
+            let mut inner = self.inner.lock();
+            if let Some(cells) = inner.parts.get(&key).cloned() {
+                inner.stats.parts_reused += 1;
+                return Ok(cells);
+            }
+        }
+
+        let cells = Arc::new(parse()?);
+        let mut inner = self.inner.lock();
+        inner.stats.parts_parsed += 1;
+        let held = inner.stats.cells_cached as usize;
+        if held + cells.len() <= self.cell_limit {
+            inner.stats.cells_cached += cells.len() as u64;
+            inner.parts.insert(key, cells.clone());
+        }
+        Ok(cells)
+    }
+}
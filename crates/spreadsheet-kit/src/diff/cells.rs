@@ -5,7 +5,7 @@ use quick_xml::events::{BytesStart, Event};
 use quick_xml::reader::Reader;
 use std::io::BufRead;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct RawCell {
     pub address: CellAddress,
     pub value: Option<String>,
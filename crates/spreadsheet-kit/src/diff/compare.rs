@@ -0,0 +1,156 @@
+//! How two stored cell values are judged equal. By default numbers match
+//! within 1e-9 and text must match exactly; [`CompareOptions`] widens that
+//! so recalculation noise does not read as a change.
+
+use crate::analysis::formula::FormulaAtlas;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Numbers closer than this always compare equal.
+const BASE_EPSILON: f64 = 1e-9;
+
+/// Functions whose results change on every recalculation. Narrower than the
+/// list formula analysis uses, which also flags functions (INDEX, ROW, ...)
+/// that are only volatile in some engines.
+const RECALC_VOLATILE_FUNCTIONS: &[&str] = &[
+    "NOW",
+    "TODAY",
+    "RAND",
+    "RANDBETWEEN",
+    "RANDARRAY",
+    "OFFSET",
+    "INDIRECT",
+    "INFO",
+    "CELL",
+];
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct CompareOptions {
+    /// Numbers within this absolute difference compare equal
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub abs_tolerance: Option<f64>,
+    /// Numbers within this fraction of the larger magnitude compare equal
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rel_tolerance: Option<f64>,
+    /// Trim text and collapse runs of whitespace before comparing
+    #[serde(default)]
+    pub ignore_whitespace: bool,
+    /// Compare text case-insensitively
+    #[serde(default)]
+    pub ignore_case: bool,
+    /// Ignore result changes of unchanged formulas that call a volatile
+    /// function (NOW, TODAY, RAND, OFFSET, INDIRECT, ...)
+    #[serde(default)]
+    pub ignore_volatile: bool,
+}
+
+impl CompareOptions {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for (name, tolerance) in [
+            ("abs_tolerance", self.abs_tolerance),
+            ("rel_tolerance", self.rel_tolerance),
+        ] {
+            if let Some(tolerance) = tolerance
+                && !(tolerance.is_finite() && tolerance >= 0.0)
+            {
+                anyhow::bail!("{name} must be a non-negative number");
+            }
+        }
+        Ok(())
+    }
+
+    pub fn is_default(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+/// [`CompareOptions`] ready to apply, with the formula parser that
+/// `ignore_volatile` needs.
+pub struct CellComparer {
+    options: CompareOptions,
+    atlas: Option<FormulaAtlas>,
+}
+
+impl Default for CellComparer {
+    fn default() -> Self {
+        Self::new(CompareOptions::default())
+    }
+}
+
+impl CellComparer {
+    pub fn new(options: CompareOptions) -> Self {
+        let atlas = options.ignore_volatile.then(|| {
+            FormulaAtlas::new(
+                RECALC_VOLATILE_FUNCTIONS
+                    .iter()
+                    .map(|name| name.to_string())
+                    .collect(),
+            )
+        });
+        Self { options, atlas }
+    }
+
+    pub fn values_equal(&self, a: &Option<String>, b: &Option<String>) -> bool {
+        match (a, b) {
+            (None, None) => true,
+            (Some(a), Some(b)) => {
+                if let (Ok(fa), Ok(fb)) = (a.parse::<f64>(), b.parse::<f64>()) {
+                    self.numbers_equal(fa, fb)
+                } else if self.options.ignore_whitespace || self.options.ignore_case {
+                    self.normalize_text(a) == self.normalize_text(b)
+                } else {
+                    a == b
+                }
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether `formula` calls a volatile function. Always false unless
+    /// `ignore_volatile` is set; formulas that fail to parse are not volatile.
+    pub fn is_volatile(&self, formula: &str) -> bool {
+        let Some(atlas) = &self.atlas else {
+            return false;
+        };
+        let formula = if formula.starts_with('=') {
+            formula.to_string()
+        } else {
+            format!("={formula}")
+        };
+        atlas
+            .parse(&formula)
+            .map(|parsed| parsed.is_volatile)
+            .unwrap_or(false)
+    }
+
+    fn numbers_equal(&self, a: f64, b: f64) -> bool {
+        let diff = (a - b).abs();
+        if diff < BASE_EPSILON {
+            return true;
+        }
+        if let Some(abs) = self.options.abs_tolerance
+            && diff <= abs
+        {
+            return true;
+        }
+        if let Some(rel) = self.options.rel_tolerance
+            && diff <= rel * a.abs().max(b.abs())
+        {
+            return true;
+        }
+        false
+    }
+
+    fn normalize_text(&self, text: &str) -> String {
+        let text = if self.options.ignore_whitespace {
+            text.split_whitespace().collect::<Vec<_>>().join(" ")
+        } else {
+            text.to_string()
+        };
+        if self.options.ignore_case {
+            text.to_lowercase()
+        } else {
+            text
+        }
+    }
+}
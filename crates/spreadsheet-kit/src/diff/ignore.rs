@@ -0,0 +1,161 @@
+//! Ignore rules for diffs, usually kept in a JSON file next to a workbook,
+//! so cells that change on every refresh (timestamps, refresh logs, scratch
+//! tabs) stay out of change reports.
+
+use super::merge::{CellDiff, ModificationType};
+use super::names::NameDiff;
+use super::{CellChange, Change};
+use crate::metadata::annotation_bounds;
+use anyhow::{Context, Result, anyhow};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DiffIgnore {
+    /// Sheets whose cell, table, rule and sheet-scoped name changes are dropped;
+    /// case-insensitive, with `*` matching any run of characters and `?`
+    /// any one character
+    #[serde(default)]
+    pub sheets: Vec<String>,
+    /// Cells or ranges whose cell changes are dropped: `Summary!B1`,
+    /// `'Refresh Log'!A1:D500`, or unqualified to apply on every sheet
+    #[serde(default)]
+    pub ranges: Vec<String>,
+    /// Drop changes that only touch a cell's formatting
+    #[serde(default)]
+    pub styles: bool,
+}
+
+/// An ignored range: its sheet (`None` for every sheet) and bounds.
+struct IgnoredRange {
+    sheet: Option<String>,
+    min: (u32, u32),
+    max: (u32, u32),
+}
+
+impl DiffIgnore {
+    /// Read ignore rules from a JSON file such as
+    /// `{"sheets":["Scratch*"],"ranges":["Summary!B1"],"styles":true}`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let raw = fs::read_to_string(path)
+            .with_context(|| format!("unable to read ignore file '{}'", path.display()))?;
+        let ignore: Self = serde_json::from_str(&raw).map_err(|err| {
+            anyhow!(
+                "ignore file '{}' must be {{\"sheets\":[...],\"ranges\":[...],\"styles\":bool}}: {err}",
+                path.display()
+            )
+        })?;
+        ignore.compile_ranges()?;
+        Ok(ignore)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sheets.is_empty() && self.ranges.is_empty() && !self.styles
+    }
+
+    /// Drop ignored changes, returning how many were dropped.
+    pub fn retain(&self, changes: &mut Vec<Change>) -> Result<usize> {
+        let ranges = self.compile_ranges()?;
+        let before = changes.len();
+        changes.retain(|change| !self.ignores(change, &ranges));
+        Ok(before - changes.len())
+    }
+
+    fn ignores(&self, change: &Change, ranges: &[IgnoredRange]) -> bool {
+        match change {
+            Change::Cell(CellChange { sheet, diff }) => {
+                if self.sheet_ignored(sheet) {
+                    return true;
+                }
+                let address = match diff {
+                    CellDiff::Added { address, .. } | CellDiff::Deleted { address, .. } => address,
+                    CellDiff::Modified {
+                        address, subtype, ..
+                    } => {
+                        if self.styles && matches!(subtype, ModificationType::StyleEdit) {
+                            return true;
+                        }
+                        address
+                    }
+                };
+                let Ok(((col, row), _)) = annotation_bounds(address) else {
+                    return false;
+                };
+                ranges.iter().any(|range| {
+                    range
+                        .sheet
+                        .as_deref()
+                        .is_none_or(|name| name.eq_ignore_ascii_case(sheet))
+                        && (range.min.0..=range.max.0).contains(&col)
+                        && (range.min.1..=range.max.1).contains(&row)
+                })
+            }
+            Change::Table(table) => self.sheet_ignored(table.sheet()),
+            Change::Rule(rule) => self.sheet_ignored(rule.sheet()),
+            Change::Name(
+                NameDiff::NameAdded { scope_sheet, .. }
+                | NameDiff::NameDeleted { scope_sheet, .. }
+                | NameDiff::NameModified { scope_sheet, .. },
+            ) => scope_sheet
+                .as_deref()
+                .is_some_and(|sheet| self.sheet_ignored(sheet)),
+        }
+    }
+
+    fn sheet_ignored(&self, sheet: &str) -> bool {
+        self.sheets
+            .iter()
+            .any(|pattern| wildcard_match(&pattern.to_lowercase(), &sheet.to_lowercase()))
+    }
+
+    fn compile_ranges(&self) -> Result<Vec<IgnoredRange>> {
+        self.ranges
+            .iter()
+            .map(|raw| {
+                let (sheet, range) = match raw.rsplit_once('!') {
+                    Some((sheet, range)) => {
+                        let sheet = sheet.trim();
+                        let sheet = sheet
+                            .strip_prefix('\'')
+                            .and_then(|name| name.strip_suffix('\''))
+                            .map(|name| name.replace("''", "'"))
+                            .unwrap_or_else(|| sheet.to_string());
+                        (Some(sheet), range)
+                    }
+                    None => (None, raw.as_str()),
+                };
+                let (min, max) = annotation_bounds(&range.replace('$', ""))
+                    .map_err(|_| anyhow!("ignore range '{}' is not an A1 cell or range", raw))?;
+                Ok(IgnoredRange { sheet, min, max })
+            })
+            .collect()
+    }
+}
+
+/// Match `text` against `pattern`, where `*` matches any run of characters
+/// and `?` any one character.
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&ch| ch == '*')
+}
@@ -1,7 +1,9 @@
 use super::cells::RawCell;
+use super::compare::CellComparer;
 use anyhow::Result;
 use schemars::JsonSchema;
 use serde::Serialize;
+use std::borrow::Borrow;
 use std::cmp::Ordering;
 
 #[derive(Debug, Serialize, Clone, JsonSchema)]
@@ -37,9 +39,20 @@ pub enum ModificationType {
     StyleEdit,
 }
 
-pub fn diff_streams(
-    base: impl Iterator<Item = Result<RawCell>>,
-    fork: impl Iterator<Item = Result<RawCell>>,
+/// Diff two address-ordered cell streams. Cells may be owned (parsed as the
+/// stream is read) or borrowed from a [`PartCache`](super::cache::PartCache).
+pub fn diff_streams<C: Borrow<RawCell>>(
+    base: impl Iterator<Item = Result<C>>,
+    fork: impl Iterator<Item = Result<C>>,
+) -> Result<Vec<CellDiff>> {
+    diff_streams_with(base, fork, &CellComparer::default())
+}
+
+/// [`diff_streams`], judging values equal with `comparer`.
+pub fn diff_streams_with<C: Borrow<RawCell>>(
+    base: impl Iterator<Item = Result<C>>,
+    fork: impl Iterator<Item = Result<C>>,
+    comparer: &CellComparer,
 ) -> Result<Vec<CellDiff>> {
     let mut diffs = Vec::new();
     let mut base_iter = base.peekable();
@@ -55,8 +68,12 @@ pub fn diff_streams(
         }
 
         // Get references to Ok items
-        let b_opt = base_iter.peek().map(|r| r.as_ref().unwrap());
-        let f_opt = fork_iter.peek().map(|r| r.as_ref().unwrap());
+        let b_opt = base_iter
+            .peek()
+            .map(|r| <C as Borrow<RawCell>>::borrow(r.as_ref().unwrap()));
+        let f_opt = fork_iter
+            .peek()
+            .map(|r| <C as Borrow<RawCell>>::borrow(r.as_ref().unwrap()));
 
         match (b_opt, f_opt) {
             (None, None) => break,
@@ -96,7 +113,7 @@ pub fn diff_streams(
                     }
                     Ordering::Equal => {
                         // Same address -> Compare
-                        if let Some(diff) = compare_cells(b, f) {
+                        if let Some(diff) = compare_cells(b, f, comparer) {
                             diffs.push(diff);
                         }
                         base_iter.next();
@@ -110,9 +127,15 @@ pub fn diff_streams(
     Ok(diffs)
 }
 
-fn compare_cells(base: &RawCell, fork: &RawCell) -> Option<CellDiff> {
+fn compare_cells(base: &RawCell, fork: &RawCell, comparer: &CellComparer) -> Option<CellDiff> {
     let formula_changed = base.formula != fork.formula;
-    let value_changed = !values_equal(&base.value, &fork.value);
+    // A volatile formula left as it was only changed by being recalculated.
+    let volatile_result = !formula_changed
+        && fork
+            .formula
+            .as_deref()
+            .is_some_and(|formula| comparer.is_volatile(formula));
+    let value_changed = !volatile_result && !comparer.values_equal(&base.value, &fork.value);
     let style_changed = base.style_id != fork.style_id;
 
     if !formula_changed && !value_changed && !style_changed {
@@ -141,18 +164,3 @@ fn compare_cells(base: &RawCell, fork: &RawCell) -> Option<CellDiff> {
         new_style_id: if style_changed { fork.style_id } else { None },
     })
 }
-
-fn values_equal(a: &Option<String>, b: &Option<String>) -> bool {
-    match (a, b) {
-        (None, None) => true,
-        (Some(a), Some(b)) => {
-            // Try numeric comparison with epsilon
-            if let (Ok(fa), Ok(fb)) = (a.parse::<f64>(), b.parse::<f64>()) {
-                (fa - fb).abs() < 1e-9
-            } else {
-                a == b
-            }
-        }
-        _ => false,
-    }
-}
@@ -1,17 +1,24 @@
 pub mod address;
+pub mod cache;
 pub mod cells;
+pub mod compare;
 pub mod hash;
+pub mod ignore;
 pub mod merge;
 pub mod names;
+pub mod rules;
 pub mod sst;
 pub mod tables;
 
 use anyhow::Result;
-use cells::CellIterator;
-use merge::{CellDiff, diff_streams};
+use cache::PartCache;
+use cells::{CellIterator, RawCell};
+use compare::{CellComparer, CompareOptions};
+use merge::{CellDiff, diff_streams_with};
 use names::{DefinedName, NameDiff, NameKey, diff_names, parse_defined_names};
 use quick_xml::events::Event;
 use quick_xml::reader::Reader;
+use rules::{RuleDiff, SheetRules, diff_sheet_rules, parse_dxfs, parse_sheet_rules};
 use schemars::JsonSchema;
 use serde::Serialize;
 use sst::Sst;
@@ -28,6 +35,7 @@ pub enum Change {
     Cell(CellChange),
     Table(TableDiff),
     Name(NameDiff),
+    Rule(RuleDiff),
 }
 
 #[derive(Debug, Serialize, JsonSchema)]
@@ -44,13 +52,61 @@ pub fn calculate_changeset(
     base_path: &Path,
     fork_path: &Path,
     sheet_filter: Option<&str>,
+) -> Result<Vec<Change>> {
+    changeset(
+        base_path,
+        fork_path,
+        sheet_filter,
+        &CellComparer::default(),
+        None,
+    )
+}
+
+/// [`calculate_changeset`], reading changed sheets through `cache` so a
+/// baseline diffed against several candidates is parsed only once.
+pub fn calculate_changeset_with_cache(
+    base_path: &Path,
+    fork_path: &Path,
+    sheet_filter: Option<&str>,
+    cache: &PartCache,
+) -> Result<Vec<Change>> {
+    changeset(
+        base_path,
+        fork_path,
+        sheet_filter,
+        &CellComparer::default(),
+        Some(cache),
+    )
+}
+
+/// [`calculate_changeset`] with value comparison widened by `options`
+/// (numeric tolerance, text normalization, volatile results), optionally
+/// reading sheets through `cache`.
+pub fn calculate_changeset_with(
+    base_path: &Path,
+    fork_path: &Path,
+    sheet_filter: Option<&str>,
+    options: &CompareOptions,
+    cache: Option<&PartCache>,
+) -> Result<Vec<Change>> {
+    options.validate()?;
+    let comparer = CellComparer::new(options.clone());
+    changeset(base_path, fork_path, sheet_filter, &comparer, cache)
+}
+
+fn changeset(
+    base_path: &Path,
+    fork_path: &Path,
+    sheet_filter: Option<&str>,
+    comparer: &CellComparer,
+    cache: Option<&PartCache>,
 ) -> Result<Vec<Change>> {
     let mut base_zip = ZipArchive::new(File::open(base_path)?)?;
     let mut fork_zip = ZipArchive::new(File::open(fork_path)?)?;
 
-    // Load SSTs
-    let base_sst = load_sst(&mut base_zip).ok();
-    let fork_sst = load_sst(&mut fork_zip).ok();
+    // SSTs are parsed on first use, so identical sheets never need them.
+    let mut base_sst = LazySst::default();
+    let mut fork_sst = LazySst::default();
     let base_sst_hash = base_zip
         .by_name("xl/sharedStrings.xml")
         .ok()
@@ -72,7 +128,12 @@ pub fn calculate_changeset(
     // Names are global (or scoped), not filtered by sheet_filter usually,
     // unless scope matches? For now return all name changes.
     // Ideally we filter scoped names by sheet_filter.
-    let name_diffs = diff_names(&base_meta.names, &fork_meta.names, &base_meta.sheet_id_map);
+    let name_diffs = diff_names(
+        &base_meta.names,
+        &fork_meta.names,
+        &base_meta.sheet_id_map,
+        &fork_meta.sheet_id_map,
+    );
     for d in name_diffs {
         if let Some(filter) = sheet_filter {
             match &d {
@@ -99,19 +160,30 @@ pub fn calculate_changeset(
     let fork_tables = load_tables(&mut fork_zip, &fork_meta.sheet_map)?;
     let table_diffs = diff_tables(&base_tables, &fork_tables);
     for d in table_diffs {
-        if let Some(filter) = sheet_filter {
-            match &d {
-                TableDiff::TableAdded { sheet, .. } if sheet != filter => continue,
-                TableDiff::TableDeleted { sheet, .. } if sheet != filter => continue,
-                TableDiff::TableModified { sheet, .. } if sheet != filter => continue,
-                _ => {}
-            }
+        if let Some(filter) = sheet_filter
+            && d.sheet() != filter
+        {
+            continue;
         }
         all_changes.push(Change::Table(d));
     }
 
-    // 3. Diff Cells (per sheet)
-    // We iterate the UNION of sheets
+    // 3. Diff validations and conditional formats, then cells (per sheet)
+    // We iterate the UNION of sheets. Conditional formats refer to styles.xml,
+    // so a styles change can alter them even when the sheet part is unchanged.
+    let base_styles_hash = base_zip
+        .by_name("xl/styles.xml")
+        .ok()
+        .and_then(|f| hash::compute_hash(f).ok())
+        .unwrap_or(0);
+    let fork_styles_hash = fork_zip
+        .by_name("xl/styles.xml")
+        .ok()
+        .and_then(|f| hash::compute_hash(f).ok())
+        .unwrap_or(0);
+    let mut base_dxfs = LazyDxfs::default();
+    let mut fork_dxfs = LazyDxfs::default();
+
     let mut all_sheets: Vec<_> = base_meta
         .sheet_map
         .keys()
@@ -151,36 +223,70 @@ pub fn calculate_changeset(
             0
         };
 
+        if base_hash != fork_hash || base_styles_hash != fork_styles_hash {
+            let base_rules = load_sheet_rules(&mut base_zip, &mut base_dxfs, base_path_str)?;
+            let fork_rules = load_sheet_rules(&mut fork_zip, &mut fork_dxfs, fork_path_str)?;
+            all_changes.extend(
+                diff_sheet_rules(name, &base_rules, &fork_rules)
+                    .into_iter()
+                    .map(Change::Rule),
+            );
+        }
+
         if base_hash != 0 && base_hash == fork_hash && base_sst_hash == fork_sst_hash {
             continue;
         }
 
-        // Diff Streams
-        let base_iter = if let Some(p) = base_path_str {
-            if let Ok(f) = base_zip.by_name(p) {
-                Some(CellIterator::new(BufReader::new(f), base_sst.as_ref()))
+        let diffs = if let Some(cache) = cache {
+            let base_cells = cached_cells(
+                cache,
+                &mut base_zip,
+                &mut base_sst,
+                base_path_str,
+                (base_hash, base_sst_hash),
+            )?;
+            let fork_cells = cached_cells(
+                cache,
+                &mut fork_zip,
+                &mut fork_sst,
+                fork_path_str,
+                (fork_hash, fork_sst_hash),
+            )?;
+            diff_streams_with(
+                base_cells.iter().flat_map(|cells| cells.iter()).map(Ok),
+                fork_cells.iter().flat_map(|cells| cells.iter()).map(Ok),
+                comparer,
+            )?
+        } else {
+            // Diff Streams
+            let base_sst = base_sst.get(&mut base_zip);
+            let fork_sst = fork_sst.get(&mut fork_zip);
+            let base_iter = if let Some(p) = base_path_str {
+                if let Ok(f) = base_zip.by_name(p) {
+                    Some(CellIterator::new(BufReader::new(f), base_sst))
+                } else {
+                    None
+                }
             } else {
                 None
-            }
-        } else {
-            None
-        };
+            };
 
-        let fork_iter = if let Some(p) = fork_path_str {
-            if let Ok(f) = fork_zip.by_name(p) {
-                Some(CellIterator::new(BufReader::new(f), fork_sst.as_ref()))
+            let fork_iter = if let Some(p) = fork_path_str {
+                if let Ok(f) = fork_zip.by_name(p) {
+                    Some(CellIterator::new(BufReader::new(f), fork_sst))
+                } else {
+                    None
+                }
             } else {
                 None
-            }
-        } else {
-            None
-        };
+            };
 
-        let diffs = match (base_iter, fork_iter) {
-            (Some(b), Some(f)) => diff_streams(b, f)?,
-            (Some(b), None) => diff_streams(b, std::iter::empty())?,
-            (None, Some(f)) => diff_streams(std::iter::empty(), f)?,
-            (None, None) => Vec::new(),
+            match (base_iter, fork_iter) {
+                (Some(b), Some(f)) => diff_streams_with(b, f, comparer)?,
+                (Some(b), None) => diff_streams_with(b, std::iter::empty(), comparer)?,
+                (None, Some(f)) => diff_streams_with(std::iter::empty(), f, comparer)?,
+                (None, None) => Vec::new(),
+            }
         };
 
         for d in diffs {
@@ -199,6 +305,80 @@ fn load_sst(zip: &mut ZipArchive<File>) -> Result<Sst> {
     Sst::from_reader(BufReader::new(f))
 }
 
+/// Shared strings of one package, parsed the first time a sheet needs them.
+#[derive(Default)]
+struct LazySst {
+    loaded: Option<Option<Sst>>,
+}
+
+impl LazySst {
+    fn get(&mut self, zip: &mut ZipArchive<File>) -> Option<&Sst> {
+        self.loaded
+            .get_or_insert_with(|| load_sst(zip).ok())
+            .as_ref()
+    }
+}
+
+/// Differential formats of one package, parsed the first time a changed
+/// sheet's rules are read.
+#[derive(Default)]
+struct LazyDxfs {
+    loaded: Option<Vec<String>>,
+}
+
+impl LazyDxfs {
+    fn get(&mut self, zip: &mut ZipArchive<File>) -> &[String] {
+        self.loaded.get_or_insert_with(|| {
+            zip.by_name("xl/styles.xml")
+                .ok()
+                .and_then(|f| parse_dxfs(&mut Reader::from_reader(BufReader::new(f))).ok())
+                .unwrap_or_default()
+        })
+    }
+}
+
+/// Validations and conditional formats of the sheet part at `part_path`;
+/// none when the package has no such part.
+fn load_sheet_rules(
+    zip: &mut ZipArchive<File>,
+    dxfs: &mut LazyDxfs,
+    part_path: Option<&String>,
+) -> Result<SheetRules> {
+    let Some(part_path) = part_path else {
+        return Ok(SheetRules::default());
+    };
+    if zip.by_name(part_path).is_err() {
+        return Ok(SheetRules::default());
+    }
+    let dxfs = dxfs.get(zip);
+    let part = zip.by_name(part_path)?;
+    parse_sheet_rules(&mut Reader::from_reader(BufReader::new(part)), dxfs)
+}
+
+/// Cells of the sheet part at `part_path`, from `cache` when a part with the
+/// same hashes was parsed before. `None` when the package has no such part.
+fn cached_cells(
+    cache: &PartCache,
+    zip: &mut ZipArchive<File>,
+    sst: &mut LazySst,
+    part_path: Option<&String>,
+    key: cache::PartKey,
+) -> Result<Option<std::sync::Arc<Vec<RawCell>>>> {
+    let Some(part_path) = part_path else {
+        return Ok(None);
+    };
+    if zip.by_name(part_path).is_err() {
+        return Ok(None);
+    }
+    cache
+        .get_or_parse(key, || {
+            let sst = sst.get(zip);
+            let part = zip.by_name(part_path)?;
+            CellIterator::new(BufReader::new(part), sst).collect()
+        })
+        .map(Some)
+}
+
 struct WorkbookMeta {
     sheet_map: HashMap<String, String>, // name -> path
     sheet_id_map: HashMap<u32, String>, // index (0-based from sheetId or array?) -> name
@@ -119,50 +119,42 @@ pub fn parse_defined_names<R: BufRead>(
     Ok(names)
 }
 
+/// Compare defined names. Sheet-scoped names are matched by the name of
+/// their sheet rather than its position, so reordering sheets is not
+/// reported as names moving between them, and names match without regard
+/// to case, as Excel resolves them.
 pub fn diff_names(
     base_names: &HashMap<NameKey, DefinedName>,
     fork_names: &HashMap<NameKey, DefinedName>,
-    sheet_id_map: &HashMap<u32, String>, // index -> sheet name
+    base_sheet_ids: &HashMap<u32, String>, // index -> sheet name
+    fork_sheet_ids: &HashMap<u32, String>,
 ) -> Vec<NameDiff> {
+    let base_names = resolve_scopes(base_names, base_sheet_ids);
+    let fork_names = resolve_scopes(fork_names, fork_sheet_ids);
+
     let mut diffs = Vec::new();
     let all_keys: HashSet<_> = base_names.keys().chain(fork_names.keys()).collect();
 
     for key in all_keys {
-        let base = base_names.get(key);
-        let fork = fork_names.get(key);
-
-        if let Some(b) = base
-            && b.hidden
-        {
-            continue;
-        }
-        if let Some(f) = fork
-            && f.hidden
-        {
-            continue;
-        }
-
-        let sheet_name = key.scope.and_then(|id| sheet_id_map.get(&id).cloned());
-
-        match (base, fork) {
-            (None, Some(f)) => {
+        match (base_names.get(key), fork_names.get(key)) {
+            (None, Some((f, scope_sheet))) => {
                 diffs.push(NameDiff::NameAdded {
-                    name: key.name.clone(),
+                    name: f.key.name.clone(),
                     formula: f.formula.clone(),
-                    scope_sheet: sheet_name,
+                    scope_sheet: scope_sheet.clone(),
                 });
             }
-            (Some(_), None) => {
+            (Some((b, scope_sheet)), None) => {
                 diffs.push(NameDiff::NameDeleted {
-                    name: key.name.clone(),
-                    scope_sheet: sheet_name,
+                    name: b.key.name.clone(),
+                    scope_sheet: scope_sheet.clone(),
                 });
             }
-            (Some(b), Some(f)) => {
+            (Some((b, _)), Some((f, scope_sheet))) => {
                 if b.formula != f.formula {
                     diffs.push(NameDiff::NameModified {
-                        name: key.name.clone(),
-                        scope_sheet: sheet_name,
+                        name: f.key.name.clone(),
+                        scope_sheet: scope_sheet.clone(),
                         old_formula: b.formula.clone(),
                         new_formula: f.formula.clone(),
                     });
@@ -173,19 +165,43 @@ pub fn diff_names(
     }
 
     // Sort for stability
-    diffs.sort_by(|a, b| {
-        let name_a = match a {
-            NameDiff::NameAdded { name, .. } => name,
-            NameDiff::NameDeleted { name, .. } => name,
-            NameDiff::NameModified { name, .. } => name,
-        };
-        let name_b = match b {
-            NameDiff::NameAdded { name, .. } => name,
-            NameDiff::NameDeleted { name, .. } => name,
-            NameDiff::NameModified { name, .. } => name,
-        };
-        name_a.cmp(name_b)
-    });
+    diffs.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
 
     diffs
 }
+
+type ScopedNames<'a> = HashMap<(String, Option<String>), (&'a DefinedName, Option<String>)>;
+
+/// Visible names keyed by lowercase name and lowercase scope sheet name,
+/// each with the display name of its scope sheet.
+fn resolve_scopes<'a>(
+    names: &'a HashMap<NameKey, DefinedName>,
+    sheet_ids: &HashMap<u32, String>,
+) -> ScopedNames<'a> {
+    names
+        .values()
+        .filter(|defined| !defined.hidden)
+        .map(|defined| {
+            let scope_sheet = defined.key.scope.and_then(|id| sheet_ids.get(&id).cloned());
+            let key = (
+                defined.key.name.to_lowercase(),
+                scope_sheet.as_ref().map(|sheet| sheet.to_lowercase()),
+            );
+            (key, (defined, scope_sheet))
+        })
+        .collect()
+}
+
+impl NameDiff {
+    fn sort_key(&self) -> (&str, Option<&str>) {
+        match self {
+            NameDiff::NameAdded {
+                name, scope_sheet, ..
+            }
+            | NameDiff::NameDeleted { name, scope_sheet }
+            | NameDiff::NameModified {
+                name, scope_sheet, ..
+            } => (name, scope_sheet.as_deref()),
+        }
+    }
+}
@@ -0,0 +1,484 @@
+//! Data validations and conditional formats of a sheet, compared as typed
+//! rules rather than as the XML that stores them.
+//!
+//! A rule is identified by the range it applies to and by its definition. A
+//! rule whose definition changed in place, or that kept its definition and
+//! moved to another range, is reported as modified; anything left unpaired
+//! is added or deleted. Conditional formats carry the differential format
+//! they apply, so a rule repointed at another format index with the same
+//! look is not a change. Rules kept in `extLst` extensions are not compared.
+
+use anyhow::Result;
+use quick_xml::Writer;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::reader::Reader;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::BufRead;
+
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct ValidationRule {
+    /// `list`, `whole`, `decimal`, `date`, `time`, `textLength`, `custom`
+    /// or `none`
+    pub validation_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operator: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub formula1: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub formula2: Option<String>,
+    pub allow_blank: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_style: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct ConditionalRule {
+    /// `cellIs`, `expression`, `colorScale`, `dataBar`, `iconSet`, ...
+    pub rule_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operator: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub formulas: Vec<String>,
+    /// Other rule attributes (`text`, `rank`, `timePeriod`, ...); priority
+    /// is left out, so renumbering alone is not a change
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub options: BTreeMap<String, String>,
+    /// Scale, bar and icon parameters, one `element attr=value ...` each
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub parameters: Vec<String>,
+    /// The differential format the rule applies, as stored in styles.xml
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    pub stop_if_true: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleDiff {
+    ValidationAdded {
+        sheet: String,
+        range: String,
+        rule: ValidationRule,
+    },
+    ValidationDeleted {
+        sheet: String,
+        range: String,
+        rule: ValidationRule,
+    },
+    ValidationModified {
+        sheet: String,
+        old_range: String,
+        new_range: String,
+        old_rule: ValidationRule,
+        new_rule: ValidationRule,
+    },
+    ConditionalFormatAdded {
+        sheet: String,
+        range: String,
+        rule: ConditionalRule,
+    },
+    ConditionalFormatDeleted {
+        sheet: String,
+        range: String,
+        rule: ConditionalRule,
+    },
+    ConditionalFormatModified {
+        sheet: String,
+        old_range: String,
+        new_range: String,
+        old_rule: ConditionalRule,
+        new_rule: ConditionalRule,
+    },
+}
+
+impl RuleDiff {
+    pub fn sheet(&self) -> &str {
+        match self {
+            RuleDiff::ValidationAdded { sheet, .. }
+            | RuleDiff::ValidationDeleted { sheet, .. }
+            | RuleDiff::ValidationModified { sheet, .. }
+            | RuleDiff::ConditionalFormatAdded { sheet, .. }
+            | RuleDiff::ConditionalFormatDeleted { sheet, .. }
+            | RuleDiff::ConditionalFormatModified { sheet, .. } => sheet,
+        }
+    }
+}
+
+/// The rules of one sheet, each with the range (`sqref`) it applies to.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SheetRules {
+    pub validations: Vec<(String, ValidationRule)>,
+    pub conditional_formats: Vec<(String, ConditionalRule)>,
+}
+
+/// Read the rules of a worksheet part, resolving `dxfId`s against `dxfs`
+/// (see [`parse_dxfs`]). Cell data is skipped without being parsed.
+pub fn parse_sheet_rules<R: BufRead>(
+    reader: &mut Reader<R>,
+    dxfs: &[String],
+) -> Result<SheetRules> {
+    let mut rules = SheetRules::default();
+    let mut buf = Vec::new();
+    let mut cf_range: Option<String> = None;
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(ref e)) if e.name().as_ref() == b"sheetData" => {
+                let end = e.to_end().into_owned();
+                reader.read_to_end_into(end.name(), &mut Vec::new())?;
+            }
+            Ok(Event::Start(ref e)) if e.name().as_ref() == b"extLst" => {
+                let end = e.to_end().into_owned();
+                reader.read_to_end_into(end.name(), &mut Vec::new())?;
+            }
+            Ok(Event::Start(ref e)) if e.name().as_ref() == b"dataValidation" => {
+                let e = e.to_owned();
+                let range = normalize_sqref(&attribute(&e, b"sqref")?.unwrap_or_default());
+                let rule = read_validation(reader, &e)?;
+                rules.validations.push((range, rule));
+            }
+            Ok(Event::Empty(ref e)) if e.name().as_ref() == b"dataValidation" => {
+                let range = normalize_sqref(&attribute(e, b"sqref")?.unwrap_or_default());
+                let rule = validation_from_attributes(e)?;
+                rules.validations.push((range, rule));
+            }
+            Ok(Event::Start(ref e)) if e.name().as_ref() == b"conditionalFormatting" => {
+                cf_range = Some(normalize_sqref(
+                    &attribute(e, b"sqref")?.unwrap_or_default(),
+                ));
+            }
+            Ok(Event::End(ref e)) if e.name().as_ref() == b"conditionalFormatting" => {
+                cf_range = None;
+            }
+            Ok(Event::Start(ref e)) if e.name().as_ref() == b"cfRule" => {
+                let e = e.to_owned();
+                let rule = read_conditional(reader, &e, true, dxfs)?;
+                if let Some(range) = &cf_range {
+                    rules.conditional_formats.push((range.clone(), rule));
+                }
+            }
+            Ok(Event::Empty(ref e)) if e.name().as_ref() == b"cfRule" => {
+                let rule = read_conditional(reader, e, false, dxfs)?;
+                if let Some(range) = &cf_range {
+                    rules.conditional_formats.push((range.clone(), rule));
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(e.into()),
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(rules)
+}
+
+/// The differential formats (`<dxfs>`) of a styles part, each as the XML
+/// of its children, in index order.
+pub fn parse_dxfs<R: BufRead>(reader: &mut Reader<R>) -> Result<Vec<String>> {
+    let mut dxfs = Vec::new();
+    let mut buf = Vec::new();
+    let mut current: Option<Writer<Vec<u8>>> = None;
+
+    loop {
+        buf.clear();
+        let event = reader.read_event_into(&mut buf)?;
+        match &event {
+            Event::Start(e) if e.name().as_ref() == b"dxf" => {
+                current = Some(Writer::new(Vec::new()));
+            }
+            Event::Empty(e) if e.name().as_ref() == b"dxf" => dxfs.push(String::new()),
+            Event::End(e) if e.name().as_ref() == b"dxf" => {
+                if let Some(writer) = current.take() {
+                    dxfs.push(String::from_utf8_lossy(&writer.into_inner()).to_string());
+                }
+            }
+            Event::End(e) if e.name().as_ref() == b"dxfs" => break,
+            Event::Eof => break,
+            _ => {
+                if let Some(writer) = current.as_mut() {
+                    writer.write_event(event.borrow())?;
+                }
+            }
+        }
+    }
+
+    Ok(dxfs)
+}
+
+pub fn diff_sheet_rules(sheet: &str, base: &SheetRules, fork: &SheetRules) -> Vec<RuleDiff> {
+    let mut diffs = Vec::new();
+    for paired in pair_rules(&base.validations, &fork.validations) {
+        diffs.push(match paired {
+            Paired::Added(range, rule) => RuleDiff::ValidationAdded {
+                sheet: sheet.to_string(),
+                range,
+                rule,
+            },
+            Paired::Deleted(range, rule) => RuleDiff::ValidationDeleted {
+                sheet: sheet.to_string(),
+                range,
+                rule,
+            },
+            Paired::Modified {
+                old_range,
+                new_range,
+                old_rule,
+                new_rule,
+            } => RuleDiff::ValidationModified {
+                sheet: sheet.to_string(),
+                old_range,
+                new_range,
+                old_rule,
+                new_rule,
+            },
+        });
+    }
+    for paired in pair_rules(&base.conditional_formats, &fork.conditional_formats) {
+        diffs.push(match paired {
+            Paired::Added(range, rule) => RuleDiff::ConditionalFormatAdded {
+                sheet: sheet.to_string(),
+                range,
+                rule,
+            },
+            Paired::Deleted(range, rule) => RuleDiff::ConditionalFormatDeleted {
+                sheet: sheet.to_string(),
+                range,
+                rule,
+            },
+            Paired::Modified {
+                old_range,
+                new_range,
+                old_rule,
+                new_rule,
+            } => RuleDiff::ConditionalFormatModified {
+                sheet: sheet.to_string(),
+                old_range,
+                new_range,
+                old_rule,
+                new_rule,
+            },
+        });
+    }
+    diffs
+}
+
+enum Paired<R> {
+    Added(String, R),
+    Deleted(String, R),
+    Modified {
+        old_range: String,
+        new_range: String,
+        old_rule: R,
+        new_rule: R,
+    },
+}
+
+/// Pair base and fork rules: identical rules first (unchanged), then rules
+/// on the same range (edited), then identical definitions on different
+/// ranges (retargeted). The rest were added or deleted.
+fn pair_rules<R: PartialEq + Clone>(base: &[(String, R)], fork: &[(String, R)]) -> Vec<Paired<R>> {
+    let mut base_left: Vec<Option<&(String, R)>> = base.iter().map(Some).collect();
+    let mut fork_left: Vec<Option<&(String, R)>> = fork.iter().map(Some).collect();
+    let mut out = Vec::new();
+
+    let passes: [&dyn Fn(&(String, R), &(String, R)) -> bool; 3] = [
+        &|b, f| b.0 == f.0 && b.1 == f.1,
+        &|b, f| b.0 == f.0,
+        &|b, f| b.1 == f.1,
+    ];
+    for (pass, matches) in passes.iter().enumerate() {
+        for fork_slot in fork_left.iter_mut() {
+            let Some(fork_entry) = *fork_slot else {
+                continue;
+            };
+            let Some(base_slot) = base_left
+                .iter_mut()
+                .find(|slot| slot.is_some_and(|base_entry| matches(base_entry, fork_entry)))
+            else {
+                continue;
+            };
+            let base_entry = base_slot.take().expect("matched slot is filled");
+            *fork_slot = None;
+            if pass > 0 {
+                out.push(Paired::Modified {
+                    old_range: base_entry.0.clone(),
+                    new_range: fork_entry.0.clone(),
+                    old_rule: base_entry.1.clone(),
+                    new_rule: fork_entry.1.clone(),
+                });
+            }
+        }
+    }
+
+    out.extend(
+        base_left
+            .into_iter()
+            .flatten()
+            .map(|(range, rule)| Paired::Deleted(range.clone(), rule.clone())),
+    );
+    out.extend(
+        fork_left
+            .into_iter()
+            .flatten()
+            .map(|(range, rule)| Paired::Added(range.clone(), rule.clone())),
+    );
+    out
+}
+
+fn read_validation<R: BufRead>(
+    reader: &mut Reader<R>,
+    start: &BytesStart,
+) -> Result<ValidationRule> {
+    let mut rule = validation_from_attributes(start)?;
+    let mut buf = Vec::new();
+    let mut field: Option<u8> = None;
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(e) if e.name().as_ref() == b"formula1" => field = Some(1),
+            Event::Start(e) if e.name().as_ref() == b"formula2" => field = Some(2),
+            Event::Text(text) => {
+                let text = text.unescape()?.to_string();
+                match field {
+                    Some(1) => rule
+                        .formula1
+                        .get_or_insert_with(String::new)
+                        .push_str(&text),
+                    Some(2) => rule
+                        .formula2
+                        .get_or_insert_with(String::new)
+                        .push_str(&text),
+                    _ => {}
+                }
+            }
+            Event::End(e) if e.name().as_ref() == b"dataValidation" => break,
+            Event::End(_) => field = None,
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+    Ok(rule)
+}
+
+fn validation_from_attributes(e: &BytesStart) -> Result<ValidationRule> {
+    Ok(ValidationRule {
+        validation_type: attribute(e, b"type")?.unwrap_or_else(|| "none".to_string()),
+        operator: attribute(e, b"operator")?,
+        formula1: None,
+        formula2: None,
+        allow_blank: flag(attribute(e, b"allowBlank")?),
+        error_style: attribute(e, b"errorStyle")?,
+        prompt: attribute(e, b"prompt")?,
+        error: attribute(e, b"error")?,
+    })
+}
+
+fn read_conditional<R: BufRead>(
+    reader: &mut Reader<R>,
+    start: &BytesStart,
+    has_children: bool,
+    dxfs: &[String],
+) -> Result<ConditionalRule> {
+    let mut rule = ConditionalRule {
+        rule_type: String::new(),
+        operator: None,
+        formulas: Vec::new(),
+        options: BTreeMap::new(),
+        parameters: Vec::new(),
+        format: None,
+        stop_if_true: false,
+    };
+    for attr in start.attributes() {
+        let attr = attr?;
+        let value = attr.unescape_value()?.to_string();
+        match attr.key.as_ref() {
+            b"type" => rule.rule_type = value,
+            b"operator" => rule.operator = Some(value),
+            b"stopIfTrue" => rule.stop_if_true = flag(Some(value)),
+            b"dxfId" => {
+                rule.format = value.parse::<usize>().ok().map(|id| {
+                    dxfs.get(id)
+                        .cloned()
+                        .unwrap_or_else(|| format!("dxfId={id}"))
+                });
+            }
+            b"priority" => {}
+            key => {
+                rule.options
+                    .insert(String::from_utf8_lossy(key).to_string(), value);
+            }
+        }
+    }
+    if !has_children {
+        return Ok(rule);
+    }
+
+    let mut buf = Vec::new();
+    let mut in_formula = false;
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(e) if e.name().as_ref() == b"formula" => {
+                in_formula = true;
+                rule.formulas.push(String::new());
+            }
+            Event::Text(text) if in_formula => {
+                if let Some(formula) = rule.formulas.last_mut() {
+                    formula.push_str(&text.unescape()?);
+                }
+            }
+            Event::End(e) if e.name().as_ref() == b"formula" => in_formula = false,
+            Event::Start(e) if e.name().as_ref() == b"extLst" => {
+                let end = e.to_end().into_owned();
+                reader.read_to_end_into(end.name(), &mut Vec::new())?;
+            }
+            Event::Start(e) | Event::Empty(e) => {
+                let mut parameter = String::from_utf8_lossy(e.name().as_ref()).to_string();
+                for attr in e.attributes() {
+                    let attr = attr?;
+                    parameter.push_str(&format!(
+                        " {}={}",
+                        String::from_utf8_lossy(attr.key.as_ref()),
+                        attr.unescape_value()?
+                    ));
+                }
+                rule.parameters.push(parameter);
+            }
+            Event::End(e) if e.name().as_ref() == b"cfRule" => break,
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+    Ok(rule)
+}
+
+fn attribute(e: &BytesStart, key: &[u8]) -> Result<Option<String>> {
+    for attr in e.attributes() {
+        let attr = attr?;
+        if attr.key.as_ref() == key {
+            return Ok(Some(attr.unescape_value()?.to_string()));
+        }
+    }
+    Ok(None)
+}
+
+fn flag(value: Option<String>) -> bool {
+    matches!(value.as_deref(), Some("1" | "true"))
+}
+
+/// `sqref` areas separated by single spaces, upper-cased, in sorted order.
+fn normalize_sqref(sqref: &str) -> String {
+    let mut areas: Vec<String> = sqref
+        .split_whitespace()
+        .map(|area| area.replace('$', "").to_ascii_uppercase())
+        .collect();
+    areas.sort();
+    areas.join(" ")
+}
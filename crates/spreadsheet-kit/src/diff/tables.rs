@@ -3,11 +3,13 @@ use quick_xml::events::Event;
 use quick_xml::reader::Reader;
 use schemars::JsonSchema;
 use serde::Serialize;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::io::BufRead;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct TableInfo {
+    /// Workbook-wide table id; kept when Excel renames a table
+    pub id: Option<u32>,
     pub display_name: String,
     pub range: String, // "A1:D5"
     pub sheet: String, // "Sheet1"
@@ -31,6 +33,33 @@ pub enum TableDiff {
         old_range: String,
         new_range: String,
     },
+    TableRenamed {
+        display_name: String,
+        old_display_name: String,
+        sheet: String,
+        old_range: String,
+        new_range: String,
+    },
+}
+
+impl TableDiff {
+    pub fn display_name(&self) -> &str {
+        match self {
+            TableDiff::TableAdded { display_name, .. }
+            | TableDiff::TableDeleted { display_name, .. }
+            | TableDiff::TableModified { display_name, .. }
+            | TableDiff::TableRenamed { display_name, .. } => display_name,
+        }
+    }
+
+    pub fn sheet(&self) -> &str {
+        match self {
+            TableDiff::TableAdded { sheet, .. }
+            | TableDiff::TableDeleted { sheet, .. }
+            | TableDiff::TableModified { sheet, .. }
+            | TableDiff::TableRenamed { sheet, .. } => sheet,
+        }
+    }
 }
 
 pub fn parse_table_xml<R: BufRead>(
@@ -38,6 +67,7 @@ pub fn parse_table_xml<R: BufRead>(
     sheet_name: String,
 ) -> Result<TableInfo> {
     let mut buf = Vec::new();
+    let mut id = None;
     let mut display_name = String::new();
     let mut range = String::new();
 
@@ -47,6 +77,7 @@ pub fn parse_table_xml<R: BufRead>(
                 for attr in e.attributes() {
                     let attr = attr?;
                     match attr.key.as_ref() {
+                        b"id" => id = String::from_utf8_lossy(&attr.value).parse().ok(),
                         b"displayName" => {
                             display_name = String::from_utf8_lossy(&attr.value).to_string()
                         }
@@ -74,6 +105,7 @@ pub fn parse_table_xml<R: BufRead>(
     }
 
     Ok(TableInfo {
+        id,
         display_name,
         range,
         sheet: sheet_name,
@@ -87,7 +119,56 @@ pub fn diff_tables(
     let mut diffs = Vec::new();
     let all_keys: HashSet<_> = base_tables.keys().chain(fork_tables.keys()).collect();
 
+    // A table only in the base and one only in the fork are the same table
+    // renamed when they share an id, or failing that a sheet and range.
+    let mut renamed: HashMap<&String, &String> = HashMap::new();
+    let mut claimed: BTreeSet<&String> = BTreeSet::new();
+    let mut removed: Vec<&String> = base_tables
+        .keys()
+        .filter(|key| !fork_tables.contains_key(*key))
+        .collect();
+    removed.sort();
+    let mut introduced: Vec<&String> = fork_tables
+        .keys()
+        .filter(|key| !base_tables.contains_key(*key))
+        .collect();
+    introduced.sort();
+    for by_id in [true, false] {
+        for old in &removed {
+            if renamed.contains_key(*old) {
+                continue;
+            }
+            let base = &base_tables[*old];
+            let found = introduced.iter().copied().find(|new| {
+                let fork = &fork_tables[*new];
+                !claimed.contains(*new)
+                    && if by_id {
+                        base.id.is_some() && base.id == fork.id
+                    } else {
+                        base.sheet == fork.sheet && base.range == fork.range
+                    }
+            });
+            if let Some(new) = found {
+                renamed.insert(*old, new);
+                claimed.insert(new);
+            }
+        }
+    }
+    for (old, new) in &renamed {
+        let (b, f) = (&base_tables[*old], &fork_tables[*new]);
+        diffs.push(TableDiff::TableRenamed {
+            display_name: f.display_name.clone(),
+            old_display_name: b.display_name.clone(),
+            sheet: f.sheet.clone(),
+            old_range: b.range.clone(),
+            new_range: f.range.clone(),
+        });
+    }
+
     for key in all_keys {
+        if renamed.contains_key(key) || claimed.contains(key) {
+            continue;
+        }
         let base = base_tables.get(key);
         let fork = fork_tables.get(key);
 
@@ -126,19 +207,7 @@ pub fn diff_tables(
     }
 
     // Sort
-    diffs.sort_by(|a, b| {
-        let name_a = match a {
-            TableDiff::TableAdded { display_name, .. } => display_name,
-            TableDiff::TableDeleted { display_name, .. } => display_name,
-            TableDiff::TableModified { display_name, .. } => display_name,
-        };
-        let name_b = match b {
-            TableDiff::TableAdded { display_name, .. } => display_name,
-            TableDiff::TableDeleted { display_name, .. } => display_name,
-            TableDiff::TableModified { display_name, .. } => display_name,
-        };
-        name_a.cmp(name_b)
-    });
+    diffs.sort_by(|a, b| a.display_name().cmp(b.display_name()));
 
     diffs
 }
@@ -1,3 +1,4 @@
+use serde::Serialize;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -34,3 +35,22 @@ impl InvalidParamsError {
         self.path.as_deref()
     }
 }
+
+/// A recalculation stopped by its timeout, with how far it got. The CLI maps
+/// this to the `RECALC_TIMEOUT` error code.
+#[derive(Debug, Clone, Error, Serialize)]
+#[error(
+    "recalc timeout: {backend} engine did not finish within {timeout_ms}ms (stopped during {phase} after {elapsed_ms}ms)"
+)]
+pub struct RecalcTimeoutError {
+    pub backend: String,
+    pub timeout_ms: u64,
+    pub elapsed_ms: u64,
+    /// Phase that was interrupted, e.g. `evaluate` or `soffice`.
+    pub phase: String,
+    /// Phases that finished before the timeout fired.
+    pub completed_phases: Vec<String>,
+    /// Formula cells loaded into the engine before the timeout, when known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub formula_cells: Option<u64>,
+}
@@ -0,0 +1,262 @@
+//! Expectation runner: spreadsheets as testable artifacts.
+//!
+//! Expectations are declared either in a workbook sheet named `_tests` or in a
+//! sidecar JSON file, and evaluated against (typically freshly recalculated)
+//! cell values.
+
+use crate::model::CellValue;
+use crate::workbook::cell_to_value;
+use anyhow::{Context, Result, anyhow, bail};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+/// Sheet name that carries embedded expectations.
+pub const TESTS_SHEET_NAME: &str = "_tests";
+
+const DEFAULT_NUMERIC_TOLERANCE: f64 = 1e-9;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Expectation {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Target cell in `Sheet!A1` notation.
+    pub cell: String,
+    /// Expected value: number, string (including error literals like `#N/A`),
+    /// boolean, or null for an empty cell.
+    pub expect: Value,
+    /// Absolute tolerance for numeric comparisons.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tolerance: Option<f64>,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct ExpectationFile {
+    pub tests: Vec<Expectation>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ExpectationResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub cell: String,
+    pub expected: Value,
+    pub actual: Option<CellValue>,
+    pub passed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ExpectationReport {
+    pub source: String,
+    pub total: u32,
+    pub passed: u32,
+    pub failed: u32,
+    pub results: Vec<ExpectationResult>,
+}
+
+impl ExpectationReport {
+    pub fn all_passed(&self) -> bool {
+        self.failed == 0
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &ExpectationResult> {
+        self.results.iter().filter(|result| !result.passed)
+    }
+}
+
+/// Load expectations from a sidecar JSON file. Accepts `{"tests":[...]}` or a bare array.
+pub fn load_expectations_file(path: &Path) -> Result<Vec<Expectation>> {
+    let raw = fs::read_to_string(path)
+        .with_context(|| format!("unable to read tests file '{}'", path.display()))?;
+    let value: Value = serde_json::from_str(&raw)
+        .map_err(|err| anyhow!("tests file '{}' is not valid JSON: {err}", path.display()))?;
+    let tests = if value.is_array() {
+        serde_json::from_value::<Vec<Expectation>>(value)
+    } else {
+        serde_json::from_value::<ExpectationFile>(value).map(|file| file.tests)
+    }
+    .map_err(|err| {
+        anyhow!(
+            "tests file '{}' must be {{\"tests\":[{{\"cell\":\"Sheet!A1\",\"expect\":...}}]}}: {err}",
+            path.display()
+        )
+    })?;
+    for test in &tests {
+        parse_expectation_cell(&test.cell)?;
+    }
+    Ok(tests)
+}
+
+/// Read expectations from the embedded `_tests` sheet, if present.
+///
+/// Row 1 is a header row; recognised columns are `name`, `cell`, `expect`
+/// (or `expected`), and `tolerance`. Rows without a cell reference are skipped.
+pub fn read_expectations_sheet(
+    book: &umya_spreadsheet::Spreadsheet,
+) -> Result<Option<Vec<Expectation>>> {
+    let Some(sheet) = book.get_sheet_by_name(TESTS_SHEET_NAME) else {
+        return Ok(None);
+    };
+
+    let (max_col, max_row) = sheet.get_highest_column_and_row();
+    let mut name_col = None;
+    let mut cell_col = None;
+    let mut expect_col = None;
+    let mut tolerance_col = None;
+    for col in 1..=max_col {
+        let header = sheet.get_value((col, 1)).trim().to_ascii_lowercase();
+        match header.as_str() {
+            "name" => name_col = Some(col),
+            "cell" => cell_col = Some(col),
+            "expect" | "expected" => expect_col = Some(col),
+            "tolerance" => tolerance_col = Some(col),
+            _ => {}
+        }
+    }
+    let (Some(cell_col), Some(expect_col)) = (cell_col, expect_col) else {
+        bail!("sheet '{TESTS_SHEET_NAME}' must have 'cell' and 'expect' header columns in row 1");
+    };
+
+    let mut tests = Vec::new();
+    for row in 2..=max_row {
+        let cell = sheet.get_value((cell_col, row)).trim().to_string();
+        if cell.is_empty() {
+            continue;
+        }
+        parse_expectation_cell(&cell)
+            .with_context(|| format!("sheet '{TESTS_SHEET_NAME}' row {row}"))?;
+        let expect = match sheet.get_cell((expect_col, row)).and_then(cell_to_value) {
+            None => Value::Null,
+            Some(CellValue::Number(n)) => serde_json::json!(n),
+            Some(CellValue::Bool(b)) => Value::Bool(b),
+            Some(CellValue::Text(s)) | Some(CellValue::Error(s)) | Some(CellValue::Date(s)) => {
+                Value::String(s)
+            }
+        };
+        let tolerance = tolerance_col
+            .map(|col| sheet.get_value((col, row)))
+            .and_then(|raw| raw.trim().parse::<f64>().ok());
+        let name = name_col
+            .map(|col| sheet.get_value((col, row)).trim().to_string())
+            .filter(|value| !value.is_empty());
+        tests.push(Expectation {
+            name,
+            cell,
+            expect,
+            tolerance,
+        });
+    }
+    Ok(Some(tests))
+}
+
+/// Evaluate expectations against the current cell values of `book`.
+pub fn evaluate_expectations(
+    book: &umya_spreadsheet::Spreadsheet,
+    tests: &[Expectation],
+    source: impl Into<String>,
+) -> ExpectationReport {
+    let mut results = Vec::with_capacity(tests.len());
+    for test in tests {
+        let (actual, outcome) = match parse_expectation_cell(&test.cell) {
+            Ok((sheet_name, address)) => match book.get_sheet_by_name(&sheet_name) {
+                Some(sheet) => {
+                    let actual = sheet.get_cell(address.as_str()).and_then(cell_to_value);
+                    let outcome = compare_expected(&test.expect, actual.as_ref(), test.tolerance);
+                    (actual, outcome)
+                }
+                None => (None, Err(format!("sheet '{sheet_name}' not found"))),
+            },
+            Err(err) => (None, Err(err.to_string())),
+        };
+        results.push(ExpectationResult {
+            name: test.name.clone(),
+            cell: test.cell.clone(),
+            expected: test.expect.clone(),
+            actual,
+            passed: outcome.is_ok(),
+            message: outcome.err(),
+        });
+    }
+
+    let passed = results.iter().filter(|result| result.passed).count() as u32;
+    let total = results.len() as u32;
+    ExpectationReport {
+        source: source.into(),
+        total,
+        passed,
+        failed: total - passed,
+        results,
+    }
+}
+
+fn parse_expectation_cell(raw: &str) -> Result<(String, String)> {
+    let (sheet_part, cell_part) = raw
+        .rsplit_once('!')
+        .ok_or_else(|| anyhow!("expectation cell '{raw}' must use Sheet!A1 notation"))?;
+    let sheet_part = sheet_part.trim();
+    let sheet_name = sheet_part
+        .strip_prefix('\'')
+        .and_then(|inner| inner.strip_suffix('\''))
+        .map(|inner| inner.replace("''", "'"))
+        .unwrap_or_else(|| sheet_part.to_string());
+    let address = cell_part.trim().replace('$', "").to_ascii_uppercase();
+    let (col, row, _, _) = umya_spreadsheet::helper::coordinate::index_from_coordinate(&address);
+    if sheet_name.is_empty() || col.is_none() || row.is_none() {
+        bail!("expectation cell '{raw}' must use Sheet!A1 notation");
+    }
+    Ok((sheet_name, address))
+}
+
+fn compare_expected(
+    expected: &Value,
+    actual: Option<&CellValue>,
+    tolerance: Option<f64>,
+) -> std::result::Result<(), String> {
+    let mismatch = || {
+        Err(format!(
+            "expected {}, got {}",
+            expected,
+            actual
+                .map(describe_actual)
+                .unwrap_or_else(|| "empty".to_string())
+        ))
+    };
+
+    match (expected, actual) {
+        (Value::Null, None) => Ok(()),
+        (Value::Null, Some(CellValue::Text(text))) if text.is_empty() => Ok(()),
+        (Value::Number(expected_number), Some(CellValue::Number(actual_number))) => {
+            let expected_number = expected_number.as_f64().unwrap_or(f64::NAN);
+            let tolerance = tolerance.unwrap_or(DEFAULT_NUMERIC_TOLERANCE).abs();
+            if (expected_number - actual_number).abs() <= tolerance {
+                Ok(())
+            } else {
+                mismatch()
+            }
+        }
+        (Value::Bool(expected_bool), Some(CellValue::Bool(actual_bool)))
+            if expected_bool == actual_bool =>
+        {
+            Ok(())
+        }
+        (
+            Value::String(expected_text),
+            Some(CellValue::Text(actual_text))
+            | Some(CellValue::Error(actual_text))
+            | Some(CellValue::Date(actual_text)),
+        ) if expected_text == actual_text => Ok(()),
+        _ => mismatch(),
+    }
+}
+
+fn describe_actual(value: &CellValue) -> String {
+    match value {
+        CellValue::Number(n) => n.to_string(),
+        CellValue::Bool(b) => b.to_string(),
+        CellValue::Text(s) | CellValue::Error(s) | CellValue::Date(s) => format!("\"{s}\""),
+    }
+}
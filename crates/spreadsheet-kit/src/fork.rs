@@ -1,3 +1,6 @@
+#[cfg(feature = "object-store")]
+use crate::object_store::ObjectStore;
+use crate::scratch::{DEFAULT_ORPHAN_AGE, ScratchSpace, collect_garbage};
 use crate::security::canonicalize_and_enforce_within_workspace;
 use crate::utils::make_short_random_id;
 use anyhow::{Result, anyhow};
@@ -12,11 +15,8 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
+use tempfile::TempPath;
 
-const FORK_DIR: &str = "/tmp/mcp-forks";
-const CHECKPOINT_DIR: &str = "/tmp/mcp-checkpoints";
-#[allow(dead_code)]
-const STAGED_SNAPSHOT_DIR: &str = "/tmp/mcp-staged";
 const DEFAULT_TTL_SECS: u64 = 0;
 const DEFAULT_MAX_FORKS: usize = 10;
 const CLEANUP_TASK_CHECK_SECS: u64 = 60;
@@ -50,6 +50,44 @@ pub struct ChangeSummary {
     #[serde(skip_serializing_if = "BTreeMap::is_empty")]
     pub flags: BTreeMap<String, bool>,
     pub warnings: Vec<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub row_permutations: Vec<RowPermutation>,
+}
+
+/// New row order of a block reordered by a `sort_range` or `sort_table` op.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RowPermutation {
+    pub sheet_name: String,
+    /// Rows that were reordered, without header or totals rows
+    pub range: String,
+    /// Original row of the row now at each row of `range`, top to bottom
+    pub source_rows: Vec<u32>,
+}
+
+impl ChangeSummary {
+    /// Fold in the summary of a later batch applied on top of this one.
+    pub fn merge(&mut self, other: ChangeSummary) {
+        for (target, items) in [
+            (&mut self.op_kinds, other.op_kinds),
+            (&mut self.affected_sheets, other.affected_sheets),
+            (&mut self.affected_bounds, other.affected_bounds),
+            (&mut self.warnings, other.warnings),
+        ] {
+            for item in items {
+                if !target.contains(&item) {
+                    target.push(item);
+                }
+            }
+        }
+        for (key, count) in other.counts {
+            *self.counts.entry(key).or_insert(0) += count;
+        }
+        for (key, flag) in other.flags {
+            *self.flags.entry(key).or_insert(false) |= flag;
+        }
+        self.row_permutations.extend(other.row_permutations);
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -67,10 +105,21 @@ pub struct Checkpoint {
     pub checkpoint_id: String,
     pub created_at: DateTime<Utc>,
     pub label: Option<String>,
-    pub snapshot_path: PathBuf,
+    pub blob: CheckpointBlob,
+    pub size_bytes: u64,
     pub recalc_needed: bool,
 }
 
+/// Where a checkpoint's copy of the fork is kept.
+#[derive(Debug, Clone)]
+pub enum CheckpointBlob {
+    /// A file in the fork's scratch checkpoint directory.
+    Local(PathBuf),
+    /// An object in the configured checkpoint store, with the SHA-256 of
+    /// the bytes uploaded so a restore can detect a corrupted copy.
+    Remote { key: String, sha256: String },
+}
+
 #[derive(Debug)]
 pub struct ForkContext {
     pub fork_id: String,
@@ -82,12 +131,21 @@ pub struct ForkContext {
     pub staged_changes: Vec<StagedChange>,
     pub checkpoints: Vec<Checkpoint>,
     pub recalc_needed: bool,
+    /// Virtual workspace key when the fork was created from uploaded bytes;
+    /// `base_path` is then a snapshot owned by the fork.
+    pub virtual_key: Option<String>,
+    checkpoint_dir: PathBuf,
     base_hash: String,
     base_modified: std::time::SystemTime,
 }
 
 impl ForkContext {
-    fn new(fork_id: String, base_path: PathBuf, work_path: PathBuf) -> Result<Self> {
+    fn new(
+        fork_id: String,
+        base_path: PathBuf,
+        work_path: PathBuf,
+        checkpoint_dir: PathBuf,
+    ) -> Result<Self> {
         let metadata = fs::metadata(&base_path)?;
         let base_modified = metadata.modified()?;
         let base_hash = hash_file(&base_path)?;
@@ -102,6 +160,8 @@ impl ForkContext {
             staged_changes: Vec::new(),
             checkpoints: Vec::new(),
             recalc_needed: false,
+            virtual_key: None,
+            checkpoint_dir,
             base_hash,
             base_modified,
         })
@@ -134,19 +194,22 @@ impl ForkContext {
         Ok(())
     }
 
-    fn checkpoint_dir(&self) -> PathBuf {
-        PathBuf::from(CHECKPOINT_DIR).join(&self.fork_id)
+    fn remove_base_snapshot(&self) {
+        if self.virtual_key.is_some() {
+            let _ = fs::remove_file(&self.base_path);
+        }
     }
 
-    fn cleanup_files(&self) {
+    fn cleanup_files(&self, store: &CheckpointStore) {
         let _ = fs::remove_file(&self.work_path);
+        self.remove_base_snapshot();
         for staged in &self.staged_changes {
             remove_staged_snapshot(staged);
         }
-        let checkpoint_dir = self.checkpoint_dir();
-        if checkpoint_dir.starts_with(CHECKPOINT_DIR) {
-            let _ = fs::remove_dir_all(&checkpoint_dir);
+        for checkpoint in &self.checkpoints {
+            store.remove(&checkpoint.blob);
         }
+        let _ = fs::remove_dir_all(&self.checkpoint_dir);
     }
 }
 
@@ -161,7 +224,13 @@ fn hash_file(path: &Path) -> Result<String> {
 pub struct ForkConfig {
     pub ttl: Duration,
     pub max_forks: usize,
-    pub fork_dir: PathBuf,
+    /// Scratch root; fork files live in this process's owner directory under it.
+    pub scratch_root: PathBuf,
+    /// Quota on this process's scratch directory; `None` is unlimited.
+    pub scratch_max_bytes: Option<u64>,
+    /// `s3://` or `gs://` URL to keep checkpoints in instead of scratch;
+    /// needs the `object-store` feature.
+    pub checkpoint_store: Option<String>,
 }
 
 impl Default for ForkConfig {
@@ -169,7 +238,118 @@ impl Default for ForkConfig {
         Self {
             ttl: Duration::from_secs(DEFAULT_TTL_SECS),
             max_forks: DEFAULT_MAX_FORKS,
-            fork_dir: PathBuf::from(FORK_DIR),
+            scratch_root: crate::scratch::default_root(),
+            scratch_max_bytes: None,
+            checkpoint_store: None,
+        }
+    }
+}
+
+/// Backing storage for checkpoint copies.
+enum CheckpointStore {
+    /// Files under the registry's scratch directory.
+    Scratch,
+    /// Objects keyed `<owner>/<fork_id>/<checkpoint_id>.xlsx` under the
+    /// store's prefix; the owner is the scratch directory name, so servers
+    /// sharing a bucket never collide.
+    #[cfg(feature = "object-store")]
+    Object { store: ObjectStore, owner: String },
+}
+
+impl CheckpointStore {
+    fn from_config(url: Option<&str>, scratch: &ScratchSpace) -> Result<Self> {
+        let Some(url) = url else {
+            return Ok(Self::Scratch);
+        };
+        #[cfg(feature = "object-store")]
+        {
+            let owner = scratch
+                .dir()
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            Ok(Self::Object {
+                store: ObjectStore::from_url(url)?,
+                owner,
+            })
+        }
+        #[cfg(not(feature = "object-store"))]
+        {
+            let _ = scratch;
+            Err(anyhow!(
+                "checkpoint store '{url}' needs a build with the object-store feature"
+            ))
+        }
+    }
+
+    /// Copy `work_path` into a new checkpoint; returns the blob and its size.
+    fn write(
+        &self,
+        scratch: &ScratchSpace,
+        fork_id: &str,
+        checkpoint_id: &str,
+        work_path: &Path,
+    ) -> Result<(CheckpointBlob, u64)> {
+        match self {
+            Self::Scratch => {
+                let size = fs::metadata(work_path)?.len();
+                scratch.reserve(size)?;
+                let dir = scratch.checkpoints_dir().join(fork_id);
+                fs::create_dir_all(&dir)?;
+                let snapshot_path = dir.join(format!("{}.xlsx", checkpoint_id));
+                fs::copy(work_path, &snapshot_path)?;
+                Ok((CheckpointBlob::Local(snapshot_path), size))
+            }
+            #[cfg(feature = "object-store")]
+            Self::Object { store, owner } => {
+                let bytes = fs::read(work_path)?;
+                let size = bytes.len() as u64;
+                let sha256 = crate::utils::hash_bytes_sha256_hex(&bytes);
+                let key = format!("{owner}/{fork_id}/{checkpoint_id}.xlsx");
+                store.put(&key, bytes)?;
+                Ok((CheckpointBlob::Remote { key, sha256 }, size))
+            }
+        }
+    }
+
+    /// Overwrite `work_path` with the checkpoint's copy.
+    fn restore(&self, blob: &CheckpointBlob, work_path: &Path) -> Result<()> {
+        match (self, blob) {
+            (_, CheckpointBlob::Local(path)) => {
+                fs::copy(path, work_path)?;
+                Ok(())
+            }
+            #[cfg(feature = "object-store")]
+            (Self::Object { store, .. }, CheckpointBlob::Remote { key, sha256 }) => {
+                let bytes = store.get(key)?;
+                if crate::utils::hash_bytes_sha256_hex(&bytes) != *sha256 {
+                    return Err(anyhow!(
+                        "checkpoint {} does not match its recorded hash",
+                        store.display_key(key)
+                    ));
+                }
+                fs::write(work_path, bytes)?;
+                Ok(())
+            }
+            (_, CheckpointBlob::Remote { key, .. }) => Err(anyhow!(
+                "checkpoint '{key}' is in an object store this server is not configured for"
+            )),
+        }
+    }
+
+    /// Best-effort removal of a checkpoint's copy.
+    fn remove(&self, blob: &CheckpointBlob) {
+        match (self, blob) {
+            (_, CheckpointBlob::Local(path)) => {
+                let _ = fs::remove_file(path);
+            }
+            #[cfg(feature = "object-store")]
+            (Self::Object { store, .. }, CheckpointBlob::Remote { key, .. }) => {
+                if let Err(error) = store.delete(key) {
+                    tracing::warn!(%error, key = %store.display_key(key), "failed to delete checkpoint");
+                }
+            }
+            (_, CheckpointBlob::Remote { .. }) => {}
         }
     }
 }
@@ -177,25 +357,50 @@ impl Default for ForkConfig {
 pub struct ForkRegistry {
     forks: Mutex<HashMap<String, ForkContext>>,
     config: ForkConfig,
+    scratch: ScratchSpace,
+    checkpoint_store: CheckpointStore,
 }
 
 impl ForkRegistry {
+    /// Create the registry's scratch directory and remove scratch orphaned by
+    /// earlier processes that did not shut down cleanly.
     pub fn new(config: ForkConfig) -> Result<Self> {
-        fs::create_dir_all(&config.fork_dir)?;
+        let scratch = ScratchSpace::create(&config.scratch_root, config.scratch_max_bytes)?;
+        match collect_garbage(
+            scratch.root(),
+            DEFAULT_ORPHAN_AGE,
+            Some(scratch.dir()),
+            false,
+        ) {
+            Ok(report) if !report.removed.is_empty() => tracing::info!(
+                entries = report.removed.len(),
+                bytes = report.removed_bytes,
+                "removed orphaned scratch files"
+            ),
+            Ok(_) => {}
+            Err(error) => tracing::warn!(%error, "failed to sweep orphaned scratch files"),
+        }
+        let checkpoint_store =
+            CheckpointStore::from_config(config.checkpoint_store.as_deref(), &scratch)?;
         Ok(Self {
             forks: Mutex::new(HashMap::new()),
             config,
+            scratch,
+            checkpoint_store,
         })
     }
 
+    pub fn scratch(&self) -> &ScratchSpace {
+        &self.scratch
+    }
+
+    /// Keep the scratch heartbeat fresh and, with a TTL, evict idle forks.
     pub fn start_cleanup_task(self: Arc<Self>) {
-        if self.config.ttl.is_zero() {
-            return;
-        }
         tokio::spawn(async move {
             let mut interval = tokio::time::interval(Duration::from_secs(CLEANUP_TASK_CHECK_SECS));
             loop {
                 interval.tick().await;
+                self.scratch.heartbeat();
                 self.evict_expired();
             }
         });
@@ -203,16 +408,7 @@ impl ForkRegistry {
 
     pub fn create_fork(&self, base_path: &Path, workspace_root: &Path) -> Result<String> {
         self.evict_expired();
-
-        {
-            let forks = self.forks.lock();
-            if forks.len() >= self.config.max_forks {
-                return Err(anyhow!(
-                    "max forks ({}) reached, discard existing forks first",
-                    self.config.max_forks
-                ));
-            }
-        }
+        self.ensure_fork_capacity()?;
 
         let ext = base_path
             .extension()
@@ -247,32 +443,89 @@ impl ForkRegistry {
             ));
         }
 
-        let fork_id = {
-            let mut attempts: u32 = 0;
-            loop {
-                let candidate = make_short_random_id("fork", 12);
-                let work_path = self.config.fork_dir.join(format!("{}.xlsx", candidate));
-                let exists_in_registry = self.forks.lock().contains_key(&candidate);
-                if !exists_in_registry && !work_path.exists() {
-                    break candidate;
-                }
-                attempts += 1;
-                if attempts > 20 {
-                    return Err(anyhow!("failed to allocate unique fork id"));
-                }
-            }
-        };
-        let work_path = self.config.fork_dir.join(format!("{}.xlsx", fork_id));
+        self.scratch.reserve(metadata.len())?;
+        let fork_id = self.allocate_fork_id()?;
+        let work_path = self.scratch.forks_dir().join(format!("{}.xlsx", fork_id));
 
         fs::copy(&base_path_canon, &work_path)?;
 
-        let context = ForkContext::new(fork_id.clone(), base_path_canon, work_path)?;
+        let context = ForkContext::new(
+            fork_id.clone(),
+            base_path_canon,
+            work_path,
+            self.scratch.checkpoints_dir().join(&fork_id),
+        )?;
+
+        self.forks.lock().insert(fork_id.clone(), context);
+
+        Ok(fork_id)
+    }
+
+    /// Fork a workbook held in memory by the virtual workspace. The bytes are
+    /// snapshotted next to the fork and serve as its base.
+    pub fn create_fork_from_bytes(&self, bytes: &[u8], virtual_key: &str) -> Result<String> {
+        self.evict_expired();
+        self.ensure_fork_capacity()?;
+
+        if bytes.len() as u64 > MAX_FILE_SIZE {
+            return Err(anyhow!(
+                "base workbook too large: {} bytes (max {} MB)",
+                bytes.len(),
+                MAX_FILE_SIZE / 1024 / 1024
+            ));
+        }
+
+        self.scratch.reserve(2 * bytes.len() as u64)?;
+        let fork_id = self.allocate_fork_id()?;
+        let base_path = self
+            .scratch
+            .forks_dir()
+            .join(format!("{}.base.xlsx", fork_id));
+        let work_path = self.scratch.forks_dir().join(format!("{}.xlsx", fork_id));
+
+        fs::write(&base_path, bytes)?;
+        fs::copy(&base_path, &work_path)?;
+
+        let mut context = ForkContext::new(
+            fork_id.clone(),
+            base_path,
+            work_path,
+            self.scratch.checkpoints_dir().join(&fork_id),
+        )?;
+        context.virtual_key = Some(virtual_key.to_string());
 
         self.forks.lock().insert(fork_id.clone(), context);
 
         Ok(fork_id)
     }
 
+    fn ensure_fork_capacity(&self) -> Result<()> {
+        let forks = self.forks.lock();
+        if forks.len() >= self.config.max_forks {
+            return Err(anyhow!(
+                "max forks ({}) reached, discard existing forks first",
+                self.config.max_forks
+            ));
+        }
+        Ok(())
+    }
+
+    fn allocate_fork_id(&self) -> Result<String> {
+        let mut attempts: u32 = 0;
+        loop {
+            let candidate = make_short_random_id("fork", 12);
+            let work_path = self.scratch.forks_dir().join(format!("{}.xlsx", candidate));
+            let exists_in_registry = self.forks.lock().contains_key(&candidate);
+            if !exists_in_registry && !work_path.exists() {
+                return Ok(candidate);
+            }
+            attempts += 1;
+            if attempts > 20 {
+                return Err(anyhow!("failed to allocate unique fork id"));
+            }
+        }
+    }
+
     pub fn get_fork(&self, fork_id: &str) -> Result<Arc<ForkContext>> {
         self.evict_expired();
 
@@ -308,7 +561,7 @@ impl ForkRegistry {
     pub fn discard_fork(&self, fork_id: &str) -> Result<()> {
         let mut forks = self.forks.lock();
         if let Some(ctx) = forks.remove(fork_id) {
-            ctx.cleanup_files();
+            ctx.cleanup_files(&self.checkpoint_store);
         }
         Ok(())
     }
@@ -347,7 +600,7 @@ impl ForkRegistry {
         fs::copy(&ctx.work_path, target_path)?;
 
         if drop_fork && let Some(ctx) = forks.remove(fork_id) {
-            let _ = fs::remove_file(&ctx.work_path);
+            ctx.cleanup_files(&self.checkpoint_store);
         }
 
         Ok(())
@@ -385,10 +638,9 @@ impl ForkRegistry {
         };
 
         let checkpoint_id = make_short_random_id("cp", 12);
-        let dir = PathBuf::from(CHECKPOINT_DIR).join(fork_id);
-        fs::create_dir_all(&dir)?;
-        let snapshot_path = dir.join(format!("{}.xlsx", checkpoint_id));
-        fs::copy(&work_path, &snapshot_path)?;
+        let (blob, size_bytes) =
+            self.checkpoint_store
+                .write(&self.scratch, fork_id, &checkpoint_id, &work_path)?;
 
         let recalc_needed = self
             .get_fork(fork_id)
@@ -399,15 +651,21 @@ impl ForkRegistry {
             checkpoint_id: checkpoint_id.clone(),
             created_at: Utc::now(),
             label,
-            snapshot_path,
+            blob,
+            size_bytes,
             recalc_needed,
         };
 
-        self.with_fork_mut(fork_id, |ctx| {
+        let pushed = self.with_fork_mut(fork_id, |ctx| {
             ctx.checkpoints.push(checkpoint.clone());
-            enforce_checkpoint_limits(ctx)?;
+            enforce_checkpoint_limits(ctx, &self.checkpoint_store);
             Ok(())
-        })?;
+        });
+        if let Err(error) = pushed {
+            // The fork went away while the copy was written.
+            self.checkpoint_store.remove(&checkpoint.blob);
+            return Err(error);
+        }
 
         Ok(checkpoint)
     }
@@ -425,7 +683,7 @@ impl ForkRegistry {
                 .position(|c| c.checkpoint_id == checkpoint_id)
                 .ok_or_else(|| anyhow!("checkpoint not found: {}", checkpoint_id))?;
             let removed = ctx.checkpoints.remove(index);
-            let _ = fs::remove_file(&removed.snapshot_path);
+            self.checkpoint_store.remove(&removed.blob);
             Ok(())
         })
     }
@@ -447,7 +705,8 @@ impl ForkRegistry {
             (ctx.work_path.clone(), checkpoint)
         };
 
-        fs::copy(&checkpoint.snapshot_path, &work_path)?;
+        self.checkpoint_store
+            .restore(&checkpoint.blob, &work_path)?;
 
         self.with_fork_mut(fork_id, |ctx| {
             let cutoff = checkpoint.created_at;
@@ -468,6 +727,26 @@ impl ForkRegistry {
         Ok(checkpoint)
     }
 
+    /// Copy the fork's working file to a snapshot for a staged change. The
+    /// snapshot is deleted when the returned path is dropped, so a preview
+    /// that fails before it is staged leaves nothing behind; `keep` it when
+    /// handing it to [`StagedChange::fork_path_snapshot`].
+    pub fn stage_snapshot(
+        &self,
+        fork_id: &str,
+        change_id: &str,
+        work_path: &Path,
+    ) -> Result<TempPath> {
+        self.scratch.reserve(fs::metadata(work_path)?.len())?;
+        let snapshot = TempPath::from_path(
+            self.scratch
+                .staged_dir()
+                .join(format!("{fork_id}_{change_id}.xlsx")),
+        );
+        fs::copy(work_path, &snapshot)?;
+        Ok(snapshot)
+    }
+
     pub fn add_staged_change(&self, fork_id: &str, staged: StagedChange) -> Result<()> {
         self.with_fork_mut(fork_id, |ctx| {
             ctx.staged_changes.push(staged);
@@ -511,7 +790,7 @@ impl ForkRegistry {
 
         for id in expired {
             if let Some(ctx) = forks.remove(&id) {
-                ctx.cleanup_files();
+                ctx.cleanup_files(&self.checkpoint_store);
                 tracing::debug!(fork_id = %id, "evicted expired fork");
             }
         }
@@ -531,27 +810,20 @@ fn enforce_staged_limits(ctx: &mut ForkContext) {
     }
 }
 
-fn enforce_checkpoint_limits(ctx: &mut ForkContext) -> Result<()> {
+fn enforce_checkpoint_limits(ctx: &mut ForkContext, store: &CheckpointStore) {
     while ctx.checkpoints.len() > DEFAULT_MAX_CHECKPOINTS_PER_FORK {
         let removed = ctx.checkpoints.remove(0);
-        let _ = fs::remove_file(&removed.snapshot_path);
+        store.remove(&removed.blob);
     }
 
     loop {
-        let mut total_bytes = 0u64;
-        for cp in &ctx.checkpoints {
-            if let Ok(meta) = fs::metadata(&cp.snapshot_path) {
-                total_bytes += meta.len();
-            }
-        }
+        let total_bytes: u64 = ctx.checkpoints.iter().map(|cp| cp.size_bytes).sum();
         if total_bytes <= DEFAULT_MAX_CHECKPOINT_TOTAL_BYTES || ctx.checkpoints.len() <= 1 {
             break;
         }
         let removed = ctx.checkpoints.remove(0);
-        let _ = fs::remove_file(&removed.snapshot_path);
+        store.remove(&removed.blob);
     }
-
-    Ok(())
 }
 
 impl Clone for ForkContext {
@@ -566,6 +838,8 @@ impl Clone for ForkContext {
             staged_changes: self.staged_changes.clone(),
             checkpoints: self.checkpoints.clone(),
             recalc_needed: self.recalc_needed,
+            virtual_key: self.virtual_key.clone(),
+            checkpoint_dir: self.checkpoint_dir.clone(),
             base_hash: self.base_hash.clone(),
             base_modified: self.base_modified,
         }
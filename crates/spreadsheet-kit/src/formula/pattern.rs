@@ -1,7 +1,7 @@
 use anyhow::{Result, anyhow, bail};
 use formualizer_parse::parser::ReferenceType;
 use formualizer_parse::pretty::canonical_formula;
-use formualizer_parse::{ASTNode, ASTNodeType};
+use formualizer_parse::{ASTNode, ASTNodeType, LiteralValue};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RelativeMode {
@@ -43,6 +43,293 @@ pub fn shift_formula_ast(
     Ok(canonical_formula(&shifted))
 }
 
+/// A block of cells moved by a cut and paste: `min`/`max` bound it on
+/// `src_sheet` and it now starts `delta_col`/`delta_row` away on `dest_sheet`.
+#[derive(Debug, Clone, Copy)]
+pub struct MovedBlock<'a> {
+    pub src_sheet: &'a str,
+    pub dest_sheet: &'a str,
+    pub min: (u32, u32),
+    pub max: (u32, u32),
+    pub delta_col: i32,
+    pub delta_row: i32,
+}
+
+impl MovedBlock<'_> {
+    fn contains(&self, sheet: &str, col: u32, row: u32) -> bool {
+        sheet.eq_ignore_ascii_case(self.src_sheet)
+            && (self.min.0..=self.max.0).contains(&col)
+            && (self.min.1..=self.max.1).contains(&row)
+    }
+}
+
+/// Point references into `block` at its new location, the way Excel updates
+/// formulas on cut and paste: absolute and relative references alike follow
+/// the moved cells, and ranges only partly inside the block stay as they
+/// were. `from_sheet` is the sheet the formula was written on and `on_sheet`
+/// the one it lives on now; they differ for a formula that moved to another
+/// sheet with the block, whose unqualified references then gain a sheet
+/// name. Returns the rewritten formula, or `None` when nothing changed.
+pub fn relocate_formula_ast(
+    ast: &ASTNode,
+    from_sheet: &str,
+    on_sheet: &str,
+    block: &MovedBlock,
+) -> Option<String> {
+    let mut relocated = ast.clone();
+    let mut changed = false;
+    relocate_refs_in_place(&mut relocated, from_sheet, on_sheet, block, &mut changed);
+    changed.then(|| canonical_formula(&relocated))
+}
+
+fn relocate_refs_in_place(
+    node: &mut ASTNode,
+    from_sheet: &str,
+    on_sheet: &str,
+    block: &MovedBlock,
+    changed: &mut bool,
+) {
+    match &mut node.node_type {
+        ASTNodeType::Reference {
+            original,
+            reference,
+        } => {
+            if relocate_reference(reference, from_sheet, on_sheet, block) {
+                *original = reference.to_string();
+                *changed = true;
+            }
+        }
+        ASTNodeType::UnaryOp { expr, .. } => {
+            relocate_refs_in_place(expr, from_sheet, on_sheet, block, changed);
+        }
+        ASTNodeType::BinaryOp { left, right, .. } => {
+            relocate_refs_in_place(left, from_sheet, on_sheet, block, changed);
+            relocate_refs_in_place(right, from_sheet, on_sheet, block, changed);
+        }
+        ASTNodeType::Function { args, .. } => {
+            for arg in args.iter_mut() {
+                relocate_refs_in_place(arg, from_sheet, on_sheet, block, changed);
+            }
+        }
+        ASTNodeType::Array(rows) => {
+            for row in rows.iter_mut() {
+                for cell in row.iter_mut() {
+                    relocate_refs_in_place(cell, from_sheet, on_sheet, block, changed);
+                }
+            }
+        }
+        ASTNodeType::Literal(_) => {}
+    }
+}
+
+/// Relocate one reference in place; true when it changed.
+fn relocate_reference(
+    reference: &mut ReferenceType,
+    from_sheet: &str,
+    on_sheet: &str,
+    block: &MovedBlock,
+) -> bool {
+    let (sheet, inside) = match reference {
+        ReferenceType::Cell {
+            sheet, row, col, ..
+        } => {
+            let target = sheet.clone().unwrap_or_else(|| from_sheet.to_string());
+            let inside = block.contains(&target, *col, *row);
+            if inside {
+                *col = (*col as i64 + block.delta_col as i64) as u32;
+                *row = (*row as i64 + block.delta_row as i64) as u32;
+            }
+            (sheet, inside)
+        }
+        ReferenceType::Range {
+            sheet,
+            start_row,
+            start_col,
+            end_row,
+            end_col,
+            ..
+        } => {
+            let target = sheet.clone().unwrap_or_else(|| from_sheet.to_string());
+            let inside = match (*start_col, *start_row, *end_col, *end_row) {
+                (Some(c1), Some(r1), Some(c2), Some(r2)) => {
+                    block.contains(&target, c1, r1) && block.contains(&target, c2, r2)
+                }
+                _ => false,
+            };
+            if inside {
+                for col in [start_col, end_col] {
+                    *col = col.map(|value| (value as i64 + block.delta_col as i64) as u32);
+                }
+                for row in [start_row, end_row] {
+                    *row = row.map(|value| (value as i64 + block.delta_row as i64) as u32);
+                }
+            }
+            (sheet, inside)
+        }
+        ReferenceType::Table(_) | ReferenceType::NamedRange(_) | ReferenceType::External(_) => {
+            return false;
+        }
+    };
+
+    let target = if inside {
+        block.dest_sheet.to_string()
+    } else {
+        sheet.clone().unwrap_or_else(|| from_sheet.to_string())
+    };
+    let qualified = if target.eq_ignore_ascii_case(on_sheet) && sheet.is_none() {
+        None
+    } else {
+        Some(target)
+    };
+    let sheet_changed = qualified != *sheet;
+    *sheet = qualified;
+    inside || sheet_changed
+}
+
+/// `formula` (A1 text, with or without `=`) in R1C1 form as seen from the cell
+/// at (`row`, `col`), or `None` if it does not parse.
+pub fn formula_to_r1c1(formula: &str, row: u32, col: u32) -> Option<String> {
+    parse_base_formula(formula)
+        .ok()
+        .map(|ast| r1c1_pattern(&ast, row, col))
+}
+
+/// Render a formula in R1C1 form relative to the cell at (`row`, `col`), so
+/// copies of one formula filled down a column or across a row render the same.
+/// Parentheses are emitted only where operator precedence needs them.
+pub fn r1c1_pattern(ast: &ASTNode, row: u32, col: u32) -> String {
+    match &ast.node_type {
+        ASTNodeType::Reference {
+            original,
+            reference,
+        } => match reference {
+            ReferenceType::Cell {
+                sheet,
+                row: ref_row,
+                col: ref_col,
+                row_abs,
+                col_abs,
+                ..
+            } => format!(
+                "{}{}{}",
+                r1c1_sheet_prefix(sheet.as_deref()),
+                r1c1_axis('R', Some(*ref_row), row, *row_abs),
+                r1c1_axis('C', Some(*ref_col), col, *col_abs)
+            ),
+            ReferenceType::Range {
+                sheet,
+                start_row,
+                start_col,
+                end_row,
+                end_col,
+                start_row_abs,
+                start_col_abs,
+                end_row_abs,
+                end_col_abs,
+                ..
+            } => format!(
+                "{}{}{}:{}{}",
+                r1c1_sheet_prefix(sheet.as_deref()),
+                r1c1_axis('R', *start_row, row, *start_row_abs),
+                r1c1_axis('C', *start_col, col, *start_col_abs),
+                r1c1_axis('R', *end_row, row, *end_row_abs),
+                r1c1_axis('C', *end_col, col, *end_col_abs)
+            ),
+            _ => original.clone(),
+        },
+        ASTNodeType::Literal(value) => match value {
+            LiteralValue::Int(value) => value.to_string(),
+            LiteralValue::Number(value) => value.to_string(),
+            LiteralValue::Boolean(value) => value.to_string().to_ascii_uppercase(),
+            LiteralValue::Text(value) => format!("\"{}\"", value.replace('"', "\"\"")),
+            other => format!("{other:?}"),
+        },
+        ASTNodeType::UnaryOp { op, expr } => {
+            let mut inner = r1c1_pattern(expr, row, col);
+            if matches!(expr.node_type, ASTNodeType::BinaryOp { .. }) {
+                inner = format!("({inner})");
+            }
+            if op == "%" {
+                format!("{inner}%")
+            } else {
+                format!("{op}{inner}")
+            }
+        }
+        ASTNodeType::BinaryOp { op, left, right } => {
+            let precedence = binary_precedence(op);
+            let operand = |node: &ASTNode, is_right: bool| {
+                let text = r1c1_pattern(node, row, col);
+                match &node.node_type {
+                    ASTNodeType::BinaryOp { op: inner, .. }
+                        if binary_precedence(inner) < precedence
+                            || (is_right && binary_precedence(inner) == precedence) =>
+                    {
+                        format!("({text})")
+                    }
+                    _ => text,
+                }
+            };
+            format!("{}{op}{}", operand(left, false), operand(right, true))
+        }
+        ASTNodeType::Function { name, args } => format!(
+            "{}({})",
+            name.trim_start_matches("_xlfn.").to_ascii_uppercase(),
+            args.iter()
+                .map(|arg| r1c1_pattern(arg, row, col))
+                .collect::<Vec<_>>()
+                .join(",")
+        ),
+        ASTNodeType::Array(rows) => format!(
+            "{{{}}}",
+            rows.iter()
+                .map(|items| items
+                    .iter()
+                    .map(|item| r1c1_pattern(item, row, col))
+                    .collect::<Vec<_>>()
+                    .join(","))
+                .collect::<Vec<_>>()
+                .join(";")
+        ),
+    }
+}
+
+/// Excel operator precedence; all binary operators are left-associative.
+fn binary_precedence(op: &str) -> u8 {
+    match op {
+        ":" | " " | "," => 6,
+        "^" => 5,
+        "*" | "/" => 4,
+        "+" | "-" => 3,
+        "&" => 2,
+        _ => 1,
+    }
+}
+
+fn r1c1_sheet_prefix(sheet: Option<&str>) -> String {
+    match sheet {
+        Some(name)
+            if name
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || ch == '_') =>
+        {
+            format!("{name}!")
+        }
+        Some(name) => format!("'{}'!", name.replace('\'', "''")),
+        None => String::new(),
+    }
+}
+
+/// `R5` absolute, `R[-1]` relative, bare `R` for the same row, empty for an
+/// open side of a whole-row or whole-column range.
+fn r1c1_axis(label: char, value: Option<u32>, origin: u32, absolute: bool) -> String {
+    match value {
+        None => String::new(),
+        Some(value) if absolute => format!("{label}{value}"),
+        Some(value) if value == origin => label.to_string(),
+        Some(value) => format!("{label}[{}]", i64::from(value) - i64::from(origin)),
+    }
+}
+
 /// Walk the AST and mutate all reference nodes in-place.
 fn shift_refs_in_place(
     node: &mut ASTNode,
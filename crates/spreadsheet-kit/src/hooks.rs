@@ -0,0 +1,296 @@
+//! Pre-apply and post-apply hooks around stateless workbook writes.
+//!
+//! Every CLI write that replaces a workbook file (in-place or `--output`)
+//! passes through these hooks after the edit is staged and before the target
+//! is replaced, and again once the target is written. Hooks receive a
+//! [`WriteHookEvent`] with the command, its op counts, and a manifest of the
+//! cells whose value or formula changed.
+//!
+//! Two kinds of hooks are supported:
+//! - shell commands from `SPREADSHEET_MCP_PRE_APPLY_HOOK` and
+//!   `SPREADSHEET_MCP_POST_APPLY_HOOK`, run through the platform shell with the
+//!   event JSON on stdin;
+//! - library callbacks implementing [`WriteHook`], registered process-wide with
+//!   [`register_write_hook`].
+//!
+//! A failing pre-apply hook vetoes the write and leaves the target untouched.
+//! A failing post-apply hook is reported as an error after the write.
+
+use anyhow::{Result, anyhow};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+use std::sync::{Arc, OnceLock, RwLock};
+
+pub const PRE_APPLY_HOOK_ENV: &str = "SPREADSHEET_MCP_PRE_APPLY_HOOK";
+pub const POST_APPLY_HOOK_ENV: &str = "SPREADSHEET_MCP_POST_APPLY_HOOK";
+
+/// Changed cells listed individually in a manifest; totals stay exact.
+const MANIFEST_CELL_LIMIT: usize = 1_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WriteHookPhase {
+    PreApply,
+    PostApply,
+}
+
+impl WriteHookPhase {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::PreApply => "pre_apply",
+            Self::PostApply => "post_apply",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WriteHookEvent {
+    pub phase: WriteHookPhase,
+    pub command: String,
+    pub source_path: String,
+    pub target_path: String,
+    /// Path of the staged workbook; only meaningful during `pre_apply`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub staged_path: Option<String>,
+    pub operation_counts: BTreeMap<String, u64>,
+    pub changed_cells: ChangedCellManifest,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ChangedCellManifest {
+    pub total_changed: u64,
+    pub by_sheet: BTreeMap<String, u64>,
+    pub cells: Vec<ChangedCell>,
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangedCell {
+    pub sheet: String,
+    pub address: String,
+    pub before: String,
+    pub after: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before_formula: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after_formula: Option<String>,
+}
+
+/// A library callback around workbook writes. Returning an error from
+/// `pre_apply` vetoes the write.
+pub trait WriteHook: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn pre_apply(&self, _event: &WriteHookEvent) -> Result<()> {
+        Ok(())
+    }
+
+    fn post_apply(&self, _event: &WriteHookEvent) -> Result<()> {
+        Ok(())
+    }
+}
+
+fn registry() -> &'static RwLock<Vec<Arc<dyn WriteHook>>> {
+    static REGISTRY: OnceLock<RwLock<Vec<Arc<dyn WriteHook>>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| RwLock::new(Vec::new()))
+}
+
+/// Register a library hook for this process. Hooks run in registration
+/// order, after any shell hook for the same phase.
+pub fn register_write_hook(hook: impl WriteHook + 'static) {
+    if let Ok(mut hooks) = registry().write() {
+        hooks.push(Arc::new(hook));
+    }
+}
+
+fn registered_hooks() -> Vec<Arc<dyn WriteHook>> {
+    registry()
+        .read()
+        .map(|hooks| hooks.clone())
+        .unwrap_or_default()
+}
+
+fn shell_hook(phase: WriteHookPhase) -> Option<String> {
+    let var = match phase {
+        WriteHookPhase::PreApply => PRE_APPLY_HOOK_ENV,
+        WriteHookPhase::PostApply => POST_APPLY_HOOK_ENV,
+    };
+    std::env::var(var)
+        .ok()
+        .filter(|command| !command.trim().is_empty())
+}
+
+/// True when any shell or library hook is configured, so callers can skip
+/// building the changed-cell manifest otherwise.
+pub fn write_hooks_configured() -> bool {
+    shell_hook(WriteHookPhase::PreApply).is_some()
+        || shell_hook(WriteHookPhase::PostApply).is_some()
+        || !registered_hooks().is_empty()
+}
+
+/// Run every hook for `event.phase`, stopping at the first failure.
+pub fn run_write_hooks(event: &WriteHookEvent) -> Result<()> {
+    if let Some(command) = shell_hook(event.phase) {
+        run_shell_hook(&command, event)?;
+    }
+    for hook in registered_hooks() {
+        let result = match event.phase {
+            WriteHookPhase::PreApply => hook.pre_apply(event),
+            WriteHookPhase::PostApply => hook.post_apply(event),
+        };
+        result.map_err(|error| {
+            anyhow!(
+                "{} hook '{}' failed: {error:#}",
+                event.phase.as_str(),
+                hook.name()
+            )
+        })?;
+    }
+    Ok(())
+}
+
+fn run_shell_hook(command: &str, event: &WriteHookEvent) -> Result<()> {
+    let payload = serde_json::to_vec(event)?;
+    let mut process = shell_command(command);
+    process
+        .env("SPREADSHEET_MCP_HOOK_PHASE", event.phase.as_str())
+        .env("SPREADSHEET_MCP_HOOK_COMMAND", &event.command)
+        .env("SPREADSHEET_MCP_HOOK_TARGET", &event.target_path);
+    let output = run_with_stdin(process, payload).map_err(|error| {
+        anyhow!(
+            "{} hook '{}' could not start: {error}",
+            event.phase.as_str(),
+            command
+        )
+    })?;
+    if output.status.success() {
+        return Ok(());
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let detail = stderr.trim();
+    let status = output
+        .status
+        .code()
+        .map(|code| format!("exit code {code}"))
+        .unwrap_or_else(|| "terminated by signal".to_string());
+    if detail.is_empty() {
+        Err(anyhow!(
+            "{} hook '{}' failed with {status}",
+            event.phase.as_str(),
+            command
+        ))
+    } else {
+        Err(anyhow!(
+            "{} hook '{}' failed with {status}: {detail}",
+            event.phase.as_str(),
+            command
+        ))
+    }
+}
+
+/// `command` run through the platform shell.
+pub(crate) fn shell_command(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut process = Command::new("cmd");
+        process.arg("/C").arg(command);
+        process
+    } else {
+        let mut process = Command::new("sh");
+        process.arg("-c").arg(command);
+        process
+    }
+}
+
+/// Run `command` with `payload` on stdin, collecting stdout and stderr.
+/// Stdin is written from its own thread so a child that writes a lot before
+/// reading cannot fill a pipe and stall both sides.
+pub(crate) fn run_with_stdin(mut command: Command, payload: Vec<u8>) -> std::io::Result<Output> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let writer = child.stdin.take().map(|mut stdin| {
+        // A child that ignores stdin may exit before reading it; that is not a failure.
+        std::thread::spawn(move || {
+            let _ = stdin.write_all(&payload);
+        })
+    });
+    let output = child.wait_with_output();
+    if let Some(writer) = writer {
+        let _ = writer.join();
+    }
+    output
+}
+
+/// Diff cell values and formulas between two workbook files.
+pub fn changed_cell_manifest(before: &Path, after: &Path) -> Result<ChangedCellManifest> {
+    let before = snapshot_cells(before)?;
+    let after = snapshot_cells(after)?;
+
+    let mut manifest = ChangedCellManifest::default();
+    let mut keys: Vec<&(String, u32, u32)> = before.keys().chain(after.keys()).collect();
+    keys.sort();
+    keys.dedup();
+
+    for key in keys {
+        let empty = (String::new(), None);
+        let (before_value, before_formula) = before.get(key).unwrap_or(&empty);
+        let (after_value, after_formula) = after.get(key).unwrap_or(&empty);
+        if before_value == after_value && before_formula == after_formula {
+            continue;
+        }
+
+        manifest.total_changed += 1;
+        *manifest.by_sheet.entry(key.0.clone()).or_insert(0) += 1;
+        if manifest.cells.len() >= MANIFEST_CELL_LIMIT {
+            manifest.truncated = true;
+            continue;
+        }
+        manifest.cells.push(ChangedCell {
+            sheet: key.0.clone(),
+            address: crate::utils::cell_address(key.2, key.1),
+            before: before_value.clone(),
+            after: after_value.clone(),
+            before_formula: before_formula.clone(),
+            after_formula: after_formula.clone(),
+        });
+    }
+
+    Ok(manifest)
+}
+
+/// Cells keyed by (sheet, row, column) so manifests list cells in row order.
+type CellSnapshot = BTreeMap<(String, u32, u32), (String, Option<String>)>;
+
+fn snapshot_cells(path: &Path) -> Result<CellSnapshot> {
+    let book = umya_spreadsheet::reader::xlsx::read(path).map_err(|error| {
+        anyhow!(
+            "unable to read workbook '{}' for hook manifest: {}",
+            path.display(),
+            error
+        )
+    })?;
+
+    let mut cells = BTreeMap::new();
+    for sheet in book.get_sheet_collection() {
+        let sheet_name = sheet.get_name().to_string();
+        for cell in sheet.get_cell_collection() {
+            let coordinate = cell.get_coordinate();
+            let formula = cell.is_formula().then(|| cell.get_formula().to_string());
+            cells.insert(
+                (
+                    sheet_name.clone(),
+                    *coordinate.get_row_num(),
+                    *coordinate.get_col_num(),
+                ),
+                (cell.get_value().to_string(), formula),
+            );
+        }
+    }
+    Ok(cells)
+}
@@ -1,30 +1,57 @@
 pub mod analysis;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod audit;
 pub mod caps;
 #[cfg(all(not(target_arch = "wasm32"), feature = "recalc"))]
 pub mod cli;
+pub mod column_map;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod command_plugins;
 pub mod config;
 pub mod core;
 #[cfg(feature = "recalc")]
 pub mod diff;
 pub mod errors;
+pub mod expectations;
 #[cfg(feature = "recalc")]
 pub mod fork;
 pub mod formula;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod hooks;
+pub mod lint;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod lint_plugins;
+pub mod materialize;
+pub mod metadata;
 pub mod model;
+pub mod number_format;
+#[cfg(all(not(target_arch = "wasm32"), feature = "object-store"))]
+pub mod object_store;
+pub mod parse_cache;
 pub mod read;
 #[cfg(feature = "recalc")]
 pub mod recalc;
 pub mod repository;
 pub mod response_prune;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod revisions;
 pub mod rules;
 pub mod runtime;
+pub mod schedule;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod scratch;
 pub mod security;
 pub mod session;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod snapshots;
 pub mod state;
 pub mod styles;
+pub mod timings;
 pub mod tools;
 pub mod types;
 pub mod utils;
 pub mod verification;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod webhook;
 pub mod workbook;
 pub mod write;
@@ -0,0 +1,914 @@
+//! Workbook linting: formula, error, volatility, external-link, and validation
+//! checks combined behind a configurable profile with severity gating.
+//!
+//! Custom checks implement [`LintRule`] and are registered on a
+//! [`LintRuleRegistry`]; they share profile severities, exclusions, finding
+//! caps, and fail-on gating with the built-in rules.
+
+use crate::analysis::formula::{FormulaAtlas, quote_sheet_name};
+use crate::model::format_formula_parse_failure;
+use crate::verification::is_error_text;
+use anyhow::{Context, Result, anyhow, bail};
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+use umya_spreadsheet::{DataValidationValues, DefinedName, Spreadsheet, Worksheet};
+
+const DEFAULT_MAX_FINDINGS: usize = 500;
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum LintSeverity {
+    Info,
+    Warning,
+    Error,
+}
+
+/// Per-rule profile setting: a severity override or `off`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LintRuleSetting {
+    Off,
+    Info,
+    Warning,
+    Error,
+}
+
+impl LintRuleSetting {
+    fn severity(self) -> Option<LintSeverity> {
+        match self {
+            LintRuleSetting::Off => None,
+            LintRuleSetting::Info => Some(LintSeverity::Info),
+            LintRuleSetting::Warning => Some(LintSeverity::Warning),
+            LintRuleSetting::Error => Some(LintSeverity::Error),
+        }
+    }
+}
+
+/// Lowest severity that fails the lint run.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, clap::ValueEnum, Default,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum LintFailOn {
+    #[default]
+    Error,
+    Warning,
+    Info,
+    /// Report findings without ever failing.
+    Never,
+}
+
+impl LintFailOn {
+    fn threshold(self) -> Option<LintSeverity> {
+        match self {
+            LintFailOn::Error => Some(LintSeverity::Error),
+            LintFailOn::Warning => Some(LintSeverity::Warning),
+            LintFailOn::Info => Some(LintSeverity::Info),
+            LintFailOn::Never => None,
+        }
+    }
+}
+
+/// Built-in rules with their default severities.
+pub const LINT_RULES: &[(&str, LintSeverity, &str)] = &[
+    (
+        "formula_parse_error",
+        LintSeverity::Error,
+        "formula text cannot be parsed",
+    ),
+    (
+        "error_value",
+        LintSeverity::Error,
+        "cell holds an error value such as #DIV/0! or #N/A",
+    ),
+    (
+        "broken_reference",
+        LintSeverity::Error,
+        "formula or defined name contains #REF!",
+    ),
+    (
+        "volatile_function",
+        LintSeverity::Warning,
+        "formula calls a volatile function (NOW, RAND, OFFSET, INDIRECT, ...)",
+    ),
+    (
+        "external_link",
+        LintSeverity::Warning,
+        "formula or defined name references another workbook",
+    ),
+    (
+        "validation_broken",
+        LintSeverity::Error,
+        "data validation has a missing or broken source",
+    ),
+    (
+        "validation_violation",
+        LintSeverity::Warning,
+        "cell value is not in its inline dropdown list",
+    ),
+];
+
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct LintProfile {
+    #[serde(default)]
+    pub fail_on: Option<LintFailOn>,
+    /// Rule id → `off` | `info` | `warning` | `error`.
+    #[serde(default)]
+    pub rules: BTreeMap<String, LintRuleSetting>,
+    /// Sheets to skip entirely.
+    #[serde(default)]
+    pub exclude_sheets: Vec<String>,
+    /// Maximum findings listed in the report (counts stay complete).
+    #[serde(default)]
+    pub max_findings: Option<usize>,
+}
+
+/// Load a lint profile from YAML or JSON. Rule ids are checked against the
+/// active [`LintRuleRegistry`] when the profile is used.
+pub fn load_lint_profile(path: &Path) -> Result<LintProfile> {
+    let raw = fs::read_to_string(path)
+        .with_context(|| format!("unable to read lint profile '{}'", path.display()))?;
+    serde_yaml::from_str(&raw)
+        .map_err(|err| anyhow!("lint profile '{}' is invalid: {err}", path.display()))
+}
+
+/// A custom lint or data-quality check.
+///
+/// Findings are reported through [`LintFindings`] under the rule's own id, so
+/// profiles can override their severity or turn them off like built-in rules.
+pub trait LintRule: Send + Sync {
+    /// Stable snake_case id used in profiles and reports.
+    fn id(&self) -> &str;
+    fn default_severity(&self) -> LintSeverity;
+    fn description(&self) -> &str;
+
+    /// Called once for every sheet being checked.
+    fn check_sheet(
+        &self,
+        _book: &Spreadsheet,
+        _sheet: &Worksheet,
+        _findings: &mut LintFindings<'_, '_>,
+    ) {
+    }
+
+    /// Called once per run for workbook-scoped checks.
+    fn check_workbook(&self, _book: &Spreadsheet, _findings: &mut LintFindings<'_, '_>) {}
+}
+
+/// Catalog entry for a built-in or registered rule.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct LintRuleInfo {
+    pub id: String,
+    pub default_severity: LintSeverity,
+    pub description: String,
+    pub builtin: bool,
+}
+
+/// Built-in rules plus any registered custom rules.
+#[derive(Default)]
+pub struct LintRuleRegistry {
+    custom: Vec<Box<dyn LintRule>>,
+}
+
+impl LintRuleRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a custom rule. Ids must be snake_case and must not collide with
+    /// built-in or previously registered rules.
+    pub fn register(&mut self, rule: impl LintRule + 'static) -> Result<()> {
+        let id = rule.id();
+        if id.is_empty()
+            || !id
+                .chars()
+                .all(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit() || ch == '_')
+        {
+            bail!("lint rule id '{id}' must be non-empty snake_case");
+        }
+        if self.default_severity(id).is_some() {
+            bail!("lint rule id '{id}' is already registered");
+        }
+        self.custom.push(Box::new(rule));
+        Ok(())
+    }
+
+    pub fn with_rule(mut self, rule: impl LintRule + 'static) -> Result<Self> {
+        self.register(rule)?;
+        Ok(self)
+    }
+
+    pub fn rules(&self) -> Vec<LintRuleInfo> {
+        let builtin = LINT_RULES
+            .iter()
+            .map(|(id, severity, description)| LintRuleInfo {
+                id: id.to_string(),
+                default_severity: *severity,
+                description: description.to_string(),
+                builtin: true,
+            });
+        let custom = self.custom.iter().map(|rule| LintRuleInfo {
+            id: rule.id().to_string(),
+            default_severity: rule.default_severity(),
+            description: rule.description().to_string(),
+            builtin: false,
+        });
+        builtin.chain(custom).collect()
+    }
+
+    fn default_severity(&self, id: &str) -> Option<LintSeverity> {
+        LINT_RULES
+            .iter()
+            .find(|(rule, _, _)| *rule == id)
+            .map(|(_, severity, _)| *severity)
+            .or_else(|| {
+                self.custom
+                    .iter()
+                    .find(|rule| rule.id() == id)
+                    .map(|rule| rule.default_severity())
+            })
+    }
+
+    fn validate_profile(&self, profile: &LintProfile) -> Result<()> {
+        for rule in profile.rules.keys() {
+            if self.default_severity(rule).is_none() {
+                let known = self
+                    .rules()
+                    .into_iter()
+                    .map(|info| info.id)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                bail!("lint profile names unknown rule '{rule}'; known rules: {known}");
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct LintFinding {
+    pub rule: String,
+    pub severity: LintSeverity,
+    /// `Sheet!A1` for cell findings, the sheet name for validation findings, or
+    /// the defined name for workbook-scoped findings.
+    pub location: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub formula: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
+pub struct LintSummary {
+    pub error: u32,
+    pub warning: u32,
+    pub info: u32,
+    pub by_rule: BTreeMap<String, u32>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct LintReport {
+    pub passed: bool,
+    pub fail_on: LintFailOn,
+    pub sheets_checked: Vec<String>,
+    pub summary: LintSummary,
+    pub findings: Vec<LintFinding>,
+    pub findings_truncated: bool,
+}
+
+struct FindingSink<'a> {
+    profile: &'a LintProfile,
+    registry: &'a LintRuleRegistry,
+    max_findings: usize,
+    summary: LintSummary,
+    findings: Vec<LintFinding>,
+    truncated: bool,
+}
+
+impl FindingSink<'_> {
+    fn severity_for(&self, rule: &str) -> Option<LintSeverity> {
+        match self.profile.rules.get(rule) {
+            Some(setting) => setting.severity(),
+            None => self.registry.default_severity(rule),
+        }
+    }
+
+    fn push(&mut self, rule: &str, location: String, message: String, formula: Option<String>) {
+        let Some(severity) = self.severity_for(rule) else {
+            return;
+        };
+        match severity {
+            LintSeverity::Error => self.summary.error += 1,
+            LintSeverity::Warning => self.summary.warning += 1,
+            LintSeverity::Info => self.summary.info += 1,
+        }
+        *self.summary.by_rule.entry(rule.to_string()).or_insert(0) += 1;
+        if self.findings.len() >= self.max_findings {
+            self.truncated = true;
+            return;
+        }
+        self.findings.push(LintFinding {
+            rule: rule.to_string(),
+            severity,
+            location,
+            message,
+            formula,
+        });
+    }
+}
+
+/// Findings handle passed to a [`LintRule`]; everything reported here is
+/// attributed to that rule.
+pub struct LintFindings<'s, 'a> {
+    sink: &'s mut FindingSink<'a>,
+    rule: String,
+}
+
+impl LintFindings<'_, '_> {
+    /// Report a finding at `Sheet!A1`, `Sheet!A1:B5`, or a workbook-level name.
+    pub fn report(&mut self, location: impl Into<String>, message: impl Into<String>) {
+        self.sink
+            .push(&self.rule, location.into(), message.into(), None);
+    }
+
+    pub fn report_with_formula(
+        &mut self,
+        location: impl Into<String>,
+        message: impl Into<String>,
+        formula: impl Into<String>,
+    ) {
+        self.sink.push(
+            &self.rule,
+            location.into(),
+            message.into(),
+            Some(formula.into()),
+        );
+    }
+}
+
+/// Run the built-in lint rules over `book`. `sheet_filter` limits cell and
+/// validation checks to one sheet; defined names are always checked.
+pub fn lint_workbook(
+    book: &Spreadsheet,
+    profile: &LintProfile,
+    fail_on: LintFailOn,
+    sheet_filter: Option<&str>,
+) -> Result<LintReport> {
+    lint_workbook_with_rules(
+        book,
+        profile,
+        fail_on,
+        sheet_filter,
+        &LintRuleRegistry::default(),
+    )
+}
+
+/// Like [`lint_workbook`], also running the custom rules in `registry`.
+pub fn lint_workbook_with_rules(
+    book: &Spreadsheet,
+    profile: &LintProfile,
+    fail_on: LintFailOn,
+    sheet_filter: Option<&str>,
+    registry: &LintRuleRegistry,
+) -> Result<LintReport> {
+    if let Some(sheet_name) = sheet_filter
+        && book.get_sheet_by_name(sheet_name).is_none()
+    {
+        bail!("sheet '{}' not found", sheet_name);
+    }
+    registry.validate_profile(profile)?;
+
+    let atlas = FormulaAtlas::default();
+    let external_re = external_reference_regex();
+    let mut sink = FindingSink {
+        profile,
+        registry,
+        max_findings: profile.max_findings.unwrap_or(DEFAULT_MAX_FINDINGS),
+        summary: LintSummary::default(),
+        findings: Vec::new(),
+        truncated: false,
+    };
+
+    let excluded: HashSet<&str> = profile.exclude_sheets.iter().map(String::as_str).collect();
+    let mut sheets_checked = Vec::new();
+    for sheet in book.get_sheet_collection() {
+        let sheet_name = sheet.get_name();
+        if excluded.contains(sheet_name) || sheet_filter.is_some_and(|name| name != sheet_name) {
+            continue;
+        }
+        sheets_checked.push(sheet_name.to_string());
+        lint_cells(sheet, &atlas, &external_re, &mut sink);
+        lint_validations(book, sheet, &mut sink);
+        for rule in enabled_custom_rules(registry, &sink) {
+            let mut findings = LintFindings {
+                sink: &mut sink,
+                rule: rule.id().to_string(),
+            };
+            rule.check_sheet(book, sheet, &mut findings);
+        }
+    }
+    lint_defined_names(book, &external_re, &mut sink);
+    for rule in enabled_custom_rules(registry, &sink) {
+        let mut findings = LintFindings {
+            sink: &mut sink,
+            rule: rule.id().to_string(),
+        };
+        rule.check_workbook(book, &mut findings);
+    }
+
+    let passed = match fail_on.threshold() {
+        Some(threshold) => !summary_severities(&sink.summary).any(|severity| severity >= threshold),
+        None => true,
+    };
+
+    Ok(LintReport {
+        passed,
+        fail_on,
+        sheets_checked,
+        summary: sink.summary,
+        findings: sink.findings,
+        findings_truncated: sink.truncated,
+    })
+}
+
+/// Custom rules not switched off by the profile.
+fn enabled_custom_rules<'r>(
+    registry: &'r LintRuleRegistry,
+    sink: &FindingSink,
+) -> Vec<&'r dyn LintRule> {
+    registry
+        .custom
+        .iter()
+        .filter(|rule| sink.severity_for(rule.id()).is_some())
+        .map(|rule| rule.as_ref())
+        .collect()
+}
+
+fn summary_severities(summary: &LintSummary) -> impl Iterator<Item = LintSeverity> {
+    [
+        (summary.error, LintSeverity::Error),
+        (summary.warning, LintSeverity::Warning),
+        (summary.info, LintSeverity::Info),
+    ]
+    .into_iter()
+    .filter(|(count, _)| *count > 0)
+    .map(|(_, severity)| severity)
+}
+
+fn lint_cells(
+    sheet: &Worksheet,
+    atlas: &FormulaAtlas,
+    external_re: &Regex,
+    sink: &mut FindingSink,
+) {
+    let sheet_name = sheet.get_name();
+    let mut cells = sheet.get_cell_collection();
+    cells.sort_by_key(|cell| {
+        (
+            *cell.get_coordinate().get_row_num(),
+            *cell.get_coordinate().get_col_num(),
+        )
+    });
+
+    for cell in cells {
+        let location = format!("{}!{}", sheet_name, cell.get_coordinate().get_coordinate());
+        let formula = cell.is_formula().then(|| cell.get_formula().to_string());
+
+        if let Some(formula) = &formula {
+            let formula_with_prefix = if formula.starts_with('=') {
+                formula.clone()
+            } else {
+                format!("={formula}")
+            };
+            match atlas.parse(&formula_with_prefix) {
+                Ok(parsed) if parsed.is_volatile => sink.push(
+                    "volatile_function",
+                    location.clone(),
+                    "formula calls a volatile function and recalculates on every change"
+                        .to_string(),
+                    Some(formula.clone()),
+                ),
+                Ok(_) => {}
+                // Mirror the formula graph: only report formulas both parsers reject.
+                Err(_) => {
+                    if let Err(err) = formualizer_parse::parse(&formula_with_prefix) {
+                        sink.push(
+                            "formula_parse_error",
+                            location.clone(),
+                            format_formula_parse_failure(&formula_with_prefix, &err),
+                            Some(formula.clone()),
+                        );
+                    }
+                }
+            }
+            let code = strip_string_literals(formula);
+            if code.to_ascii_uppercase().contains("#REF!") {
+                sink.push(
+                    "broken_reference",
+                    location.clone(),
+                    "formula contains a #REF! reference".to_string(),
+                    Some(formula.clone()),
+                );
+            }
+            if external_re.is_match(&code) {
+                sink.push(
+                    "external_link",
+                    location.clone(),
+                    "formula references another workbook".to_string(),
+                    Some(formula.clone()),
+                );
+            }
+        }
+
+        let raw = cell.get_value();
+        if is_error_text(&raw) {
+            sink.push(
+                "error_value",
+                location,
+                format!("cell evaluates to {}", raw.trim()),
+                formula,
+            );
+        }
+    }
+}
+
+fn lint_validations(book: &Spreadsheet, sheet: &Worksheet, sink: &mut FindingSink) {
+    let Some(validations) = sheet.get_data_validations() else {
+        return;
+    };
+    let sheet_name = sheet.get_name();
+
+    for validation in validations.get_data_validation_list() {
+        let sqref = validation.get_sequence_of_references().get_sqref();
+        let location = format!("{sheet_name}!{sqref}");
+        let source = validation.get_formula1().trim().trim_start_matches('=');
+        let kind = validation.get_type();
+        let needs_source = !matches!(kind, DataValidationValues::None);
+
+        if needs_source && source.is_empty() {
+            sink.push(
+                "validation_broken",
+                location,
+                "data validation has no source formula".to_string(),
+                None,
+            );
+            continue;
+        }
+        if source.to_ascii_uppercase().contains("#REF!") {
+            sink.push(
+                "validation_broken",
+                location,
+                "data validation source contains #REF!".to_string(),
+                Some(source.to_string()),
+            );
+            continue;
+        }
+        if !matches!(kind, DataValidationValues::List) {
+            continue;
+        }
+
+        if let Some(items) = inline_list_items(source) {
+            let allowed: HashSet<String> =
+                items.iter().map(|item| item.to_ascii_lowercase()).collect();
+            let bounds = sqref_bounds(&sqref);
+            let mut cells = sheet
+                .get_cell_collection()
+                .into_iter()
+                .filter(|cell| {
+                    let col = *cell.get_coordinate().get_col_num();
+                    let row = *cell.get_coordinate().get_row_num();
+                    bounds.iter().any(|(c1, r1, c2, r2)| {
+                        (*c1..=*c2).contains(&col) && (*r1..=*r2).contains(&row)
+                    })
+                })
+                .collect::<Vec<_>>();
+            cells.sort_by_key(|cell| {
+                (
+                    *cell.get_coordinate().get_row_num(),
+                    *cell.get_coordinate().get_col_num(),
+                )
+            });
+            for cell in cells {
+                let value = cell.get_value();
+                let value = value.trim();
+                if value.is_empty() || allowed.contains(&value.to_ascii_lowercase()) {
+                    continue;
+                }
+                sink.push(
+                    "validation_violation",
+                    format!("{}!{}", sheet_name, cell.get_coordinate().get_coordinate()),
+                    format!(
+                        "value '{value}' is not in the dropdown list ({})",
+                        items.join(",")
+                    ),
+                    None,
+                );
+            }
+        } else if let Some(problem) = dropdown_name_problem(book, sheet, source) {
+            sink.push(
+                "validation_broken",
+                location,
+                problem,
+                Some(source.to_string()),
+            );
+        }
+    }
+}
+
+fn lint_defined_names(book: &Spreadsheet, external_re: &Regex, sink: &mut FindingSink) {
+    let sheet_scoped = book
+        .get_sheet_collection()
+        .iter()
+        .flat_map(|sheet| sheet.get_defined_names().iter());
+    for defined in book.get_defined_names().iter().chain(sheet_scoped) {
+        let name = defined.get_name().to_string();
+        if name.starts_with("_xlnm.") {
+            continue;
+        }
+        let address = defined.get_address();
+        let code = strip_string_literals(&address);
+        if code.to_ascii_uppercase().contains("#REF!") {
+            sink.push(
+                "broken_reference",
+                name.clone(),
+                "defined name refers to #REF!".to_string(),
+                Some(address.clone()),
+            );
+        }
+        if external_re.is_match(&code) {
+            sink.push(
+                "external_link",
+                name,
+                "defined name references another workbook".to_string(),
+                Some(address),
+            );
+        }
+    }
+}
+
+/// Matches `[1]Sheet!A1`, `'[Book.xlsx]Sheet'!A1`, and `'C:\dir\[Book.xlsx]Sheet'!A1`
+/// while leaving structured table references like `Table1[Col]` alone.
+fn external_reference_regex() -> Regex {
+    Regex::new(r"\[[^\[\]]+\][^\[\]!(),]*!").expect("valid external reference regex")
+}
+
+fn strip_string_literals(formula: &str) -> String {
+    let mut out = String::with_capacity(formula.len());
+    let mut in_string = false;
+    for ch in formula.chars() {
+        if ch == '"' {
+            in_string = !in_string;
+            continue;
+        }
+        if !in_string {
+            out.push(ch);
+        }
+    }
+    out
+}
+
+/// `"A,B,C"` → `["A","B","C"]`; anything else (ranges, names) → `None`.
+fn inline_list_items(source: &str) -> Option<Vec<String>> {
+    let inner = source.strip_prefix('"')?.strip_suffix('"')?;
+    Some(
+        inner
+            .replace("\"\"", "\"")
+            .split(',')
+            .map(|item| item.trim().to_string())
+            .collect(),
+    )
+}
+
+fn is_bare_identifier(source: &str) -> bool {
+    static IDENTIFIER: OnceLock<Regex> = OnceLock::new();
+    static CELL_REF: OnceLock<Regex> = OnceLock::new();
+    let identifier = IDENTIFIER.get_or_init(|| {
+        Regex::new(r"^[A-Za-z_\\][A-Za-z0-9_.]*$").expect("valid identifier regex")
+    });
+    let cell_ref = CELL_REF
+        .get_or_init(|| Regex::new(r"^[A-Za-z]{1,3}[0-9]+$").expect("valid cell reference regex"));
+    identifier.is_match(source) && !cell_ref.is_match(source)
+}
+
+/// Why a dropdown whose source is a defined name would not resolve, if it
+/// would not. `Name` resolves against the workbook and the validation's own
+/// sheet; `Sheet2!Name` and `'My Sheet'!Name` against that sheet's names.
+fn dropdown_name_problem(book: &Spreadsheet, sheet: &Worksheet, source: &str) -> Option<String> {
+    if let Some((scope, name)) = source.rsplit_once('!') {
+        if !is_bare_identifier(name) {
+            return None;
+        }
+        let scope = scope
+            .strip_prefix('\'')
+            .and_then(|quoted| quoted.strip_suffix('\''))
+            .map(|quoted| quoted.replace("''", "'"))
+            .unwrap_or_else(|| scope.to_string());
+        let Some(scope_sheet) = book.get_sheet_by_name(&scope) else {
+            return Some(format!("dropdown source sheet '{scope}' does not exist"));
+        };
+        if has_defined_name(scope_sheet.get_defined_names(), name) {
+            return None;
+        }
+        return Some(format!(
+            "dropdown source name '{name}' is not defined on sheet '{scope}'"
+        ));
+    }
+    if !is_bare_identifier(source)
+        || has_defined_name(book.get_defined_names(), source)
+        || has_defined_name(sheet.get_defined_names(), source)
+    {
+        return None;
+    }
+    let other_scopes: Vec<&str> = book
+        .get_sheet_collection()
+        .iter()
+        .filter(|other| has_defined_name(other.get_defined_names(), source))
+        .map(|other| other.get_name())
+        .collect();
+    if other_scopes.is_empty() {
+        return Some(format!("dropdown source name '{source}' is not defined"));
+    }
+    Some(format!(
+        "dropdown source name '{source}' is scoped to sheet '{}' and does not resolve here; \
+         qualify it as {}!{source}",
+        other_scopes.join("', '"),
+        quote_sheet_name(other_scopes[0])
+    ))
+}
+
+fn has_defined_name(names: &[DefinedName], name: &str) -> bool {
+    names
+        .iter()
+        .any(|defined| defined.get_name().eq_ignore_ascii_case(name))
+}
+
+fn sqref_bounds(sqref: &str) -> Vec<(u32, u32, u32, u32)> {
+    sqref
+        .split_whitespace()
+        .filter_map(|range| {
+            let (a, b) = range.split_once(':').unwrap_or((range, range));
+            let (c1, r1, _, _) = umya_spreadsheet::helper::coordinate::index_from_coordinate(a);
+            let (c2, r2, _, _) = umya_spreadsheet::helper::coordinate::index_from_coordinate(b);
+            Some((c1?.min(c2?), r1?.min(r2?), c1?.max(c2?), r1?.max(r2?)))
+        })
+        .collect()
+}
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_WORKBOOK_BASE_ID: &str = "WORKBOOK";
+
+impl LintSeverity {
+    fn sarif_level(self) -> &'static str {
+        match self {
+            LintSeverity::Error => "error",
+            LintSeverity::Warning => "warning",
+            LintSeverity::Info => "note",
+        }
+    }
+}
+
+/// Render a lint report as a SARIF 2.1.0 log.
+///
+/// Each sheet is an artifact under the `WORKBOOK` base URI (the workbook path),
+/// and cells or validation ranges become regions with rows as lines and
+/// columns as character offsets. Workbook-scoped findings (defined names) only
+/// carry a logical location. Gate state is kept under `runs[0].properties`.
+pub fn lint_report_to_sarif(
+    report: &LintReport,
+    registry: &LintRuleRegistry,
+    workbook_path: &Path,
+) -> Value {
+    let catalog = registry.rules();
+    let rules: Vec<Value> = catalog
+        .iter()
+        .map(|rule| {
+            json!({
+                "id": rule.id,
+                "shortDescription": {"text": rule.description},
+                "defaultConfiguration": {"level": rule.default_severity.sarif_level()},
+            })
+        })
+        .collect();
+
+    let results: Vec<Value> = report
+        .findings
+        .iter()
+        .map(|finding| {
+            let mut result = json!({
+                "ruleId": finding.rule,
+                "level": finding.severity.sarif_level(),
+                "message": {"text": finding.message},
+                "locations": sarif_locations(&finding.location),
+            });
+            if let Some(index) = catalog.iter().position(|rule| rule.id == finding.rule) {
+                result["ruleIndex"] = json!(index);
+            }
+            if let Some(formula) = &finding.formula {
+                result["properties"] = json!({"formula": formula});
+            }
+            result
+        })
+        .collect();
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "asp lint",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                }
+            },
+            "originalUriBaseIds": {
+                SARIF_WORKBOOK_BASE_ID: {"uri": workbook_base_uri(workbook_path)},
+            },
+            "results": results,
+            "properties": {
+                "passed": report.passed,
+                "fail_on": report.fail_on,
+                "sheets_checked": report.sheets_checked,
+                "summary": report.summary,
+                "findings_truncated": report.findings_truncated,
+            },
+        }]
+    })
+}
+
+fn sarif_locations(location: &str) -> Vec<Value> {
+    let Some((sheet_name, reference)) = location.rsplit_once('!') else {
+        return vec![json!({
+            "logicalLocations": [{"name": location, "kind": "variable"}],
+        })];
+    };
+
+    let artifact = json!({
+        "uri": percent_encode_uri(sheet_name, false),
+        "uriBaseId": SARIF_WORKBOOK_BASE_ID,
+    });
+    reference
+        .split_whitespace()
+        .map(|range| {
+            let mut physical = json!({"artifactLocation": artifact.clone()});
+            if let Some(region) = sarif_region(range) {
+                physical["region"] = region;
+            }
+            json!({
+                "physicalLocation": physical,
+                "logicalLocations": [{
+                    "name": range,
+                    "fullyQualifiedName": format!("{sheet_name}!{range}"),
+                    "kind": "element",
+                }],
+            })
+        })
+        .collect()
+}
+
+/// Rows map to lines and columns to (end-exclusive) character columns.
+fn sarif_region(range: &str) -> Option<Value> {
+    let (start, end) = range.split_once(':').unwrap_or((range, range));
+    let (c1, r1, _, _) = umya_spreadsheet::helper::coordinate::index_from_coordinate(start);
+    let (c2, r2, _, _) = umya_spreadsheet::helper::coordinate::index_from_coordinate(end);
+    let (c1, r1, c2, r2) = (c1?, r1?, c2?, r2?);
+    Some(json!({
+        "startLine": r1.min(r2),
+        "startColumn": c1.min(c2),
+        "endLine": r1.max(r2),
+        "endColumn": c1.max(c2) + 1,
+        "snippet": {"text": range},
+    }))
+}
+
+fn workbook_base_uri(path: &Path) -> String {
+    let raw = path.display().to_string().replace('\\', "/");
+    let raw = if raw.starts_with('/') {
+        raw
+    } else {
+        format!("/{raw}")
+    };
+    format!("file://{}/", percent_encode_uri(&raw, true))
+}
+
+fn percent_encode_uri(raw: &str, keep_slash: bool) -> String {
+    let mut out = String::with_capacity(raw.len());
+    for byte in raw.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b':' => {
+                out.push(byte as char)
+            }
+            b'/' if keep_slash => out.push('/'),
+            _ => out.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    out
+}
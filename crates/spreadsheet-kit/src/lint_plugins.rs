@@ -0,0 +1,141 @@
+//! Lint rules backed by external commands, for `asp verify lint --rules-dir`.
+//!
+//! Each manifest in the directory adds one rule:
+//!
+//! ```yaml
+//! id: negative_amount
+//! description: Amount column holds a negative number
+//! default_severity: error
+//! command: python3 negative_amount.py
+//! ```
+//!
+//! The command runs once per checked sheet with a [`RuleRequest`] on stdin
+//! and prints a [`RuleResponse`]. Its findings share profile severities,
+//! exclusions and `fail_on` gating with the built-in rules. A command that
+//! fails is reported as a finding of its rule rather than aborting the run.
+
+use crate::command_plugins::{
+    PluginCell, manifest_paths, read_manifest, run_json_command, sheet_cells,
+};
+use crate::lint::{LintFindings, LintRule, LintRuleRegistry, LintSeverity};
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use umya_spreadsheet::{Spreadsheet, Worksheet};
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Manifest {
+    id: String,
+    #[serde(default)]
+    description: String,
+    default_severity: LintSeverity,
+    command: String,
+}
+
+/// A lint rule that delegates each sheet to an external command.
+#[derive(Debug)]
+pub struct CommandLintRule {
+    id: String,
+    description: String,
+    default_severity: LintSeverity,
+    command: String,
+    dir: PathBuf,
+}
+
+/// Sent to the command on stdin.
+#[derive(Debug, Serialize)]
+pub struct RuleRequest<'a> {
+    pub rule: &'a str,
+    pub sheet_name: &'a str,
+    pub cells: Vec<PluginCell>,
+}
+
+/// Printed by the command on stdout.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RuleResponse {
+    #[serde(default)]
+    pub findings: Vec<RuleFinding>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RuleFinding {
+    /// Cell or range on the checked sheet (`B7`, `B2:B9`), or `Sheet!A1`.
+    pub location: String,
+    pub message: String,
+    #[serde(default)]
+    pub formula: Option<String>,
+}
+
+/// Register a [`CommandLintRule`] for every manifest in `dir`.
+pub fn register_rule_plugins(registry: &mut LintRuleRegistry, dir: &Path) -> Result<()> {
+    for path in manifest_paths(dir)? {
+        let manifest: Manifest = read_manifest(&path)?;
+        if manifest.command.trim().is_empty() {
+            bail!("lint rule manifest {:?} has an empty command", path);
+        }
+        registry
+            .register(CommandLintRule {
+                id: manifest.id,
+                description: manifest.description,
+                default_severity: manifest.default_severity,
+                command: manifest.command,
+                dir: path.parent().map(Path::to_path_buf).unwrap_or_default(),
+            })
+            .with_context(|| format!("lint rule manifest {:?}", path))?;
+    }
+    Ok(())
+}
+
+impl LintRule for CommandLintRule {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn default_severity(&self) -> LintSeverity {
+        self.default_severity
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn check_sheet(
+        &self,
+        _book: &Spreadsheet,
+        sheet: &Worksheet,
+        findings: &mut LintFindings<'_, '_>,
+    ) {
+        let sheet_name = sheet.get_name();
+        let response: Result<RuleResponse> = run_json_command(
+            &self.command,
+            &self.dir,
+            &[("SPREADSHEET_MCP_LINT_RULE", &self.id)],
+            &RuleRequest {
+                rule: &self.id,
+                sheet_name,
+                cells: sheet_cells(sheet),
+            },
+        );
+        let response = match response {
+            Ok(response) => response,
+            Err(error) => {
+                findings.report(sheet_name, format!("rule command failed: {error:#}"));
+                return;
+            }
+        };
+        for finding in response.findings {
+            let location = if finding.location.contains('!') {
+                finding.location
+            } else {
+                format!("{sheet_name}!{}", finding.location)
+            };
+            match finding.formula {
+                Some(formula) => findings.report_with_formula(location, finding.message, formula),
+                None => findings.report(location, finding.message),
+            }
+        }
+    }
+}
@@ -0,0 +1,238 @@
+//! Materialized query results: reproducible summary tabs.
+//!
+//! A materialization snapshots the rows of a `read_table` query into a target
+//! sheet and records how they were produced in a hidden `_materialized` sheet,
+//! one row per target, so the snapshot can be refreshed later from the same
+//! definition.
+
+use crate::model::{CellValue, TableRow};
+use crate::tools::named_query::NamedQuery;
+use crate::utils::column_number_to_name;
+use anyhow::{Context, Result, anyhow, bail};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Hidden sheet that stores materialization definitions.
+pub const MATERIALIZED_SHEET_NAME: &str = "_materialized";
+
+const DEFINITION_HEADERS: [&str; 6] = [
+    "target_sheet",
+    "anchor",
+    "source",
+    "query",
+    "rows",
+    "refreshed_at",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MaterializeDefinition {
+    /// Sheet whose contents are replaced by the query result.
+    pub target_sheet: String,
+    /// Top-left cell of the header row.
+    pub anchor: String,
+    /// Workbook the query reads from; omitted when it is the workbook holding
+    /// the definition.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    pub query: NamedQuery,
+}
+
+/// A stored definition plus what its last run wrote.
+#[derive(Debug, Clone)]
+pub struct MaterializeRecord {
+    pub definition: MaterializeDefinition,
+    /// Rows written by the last run.
+    pub rows: Option<u32>,
+    pub refreshed_at: Option<String>,
+}
+
+impl From<MaterializeDefinition> for MaterializeRecord {
+    fn from(definition: MaterializeDefinition) -> Self {
+        Self {
+            definition,
+            rows: None,
+            refreshed_at: None,
+        }
+    }
+}
+
+/// Cells written for one materialization.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct MaterializedRange {
+    pub range: String,
+    pub rows: u32,
+    pub columns: u32,
+}
+
+/// Read definitions from the `_materialized` sheet; empty when it is absent.
+pub fn read_materialize_sheet(
+    book: &umya_spreadsheet::Spreadsheet,
+) -> Result<Vec<MaterializeRecord>> {
+    let Some(sheet) = book.get_sheet_by_name(MATERIALIZED_SHEET_NAME) else {
+        return Ok(Vec::new());
+    };
+
+    let max_row = sheet.get_highest_row();
+    let mut records = Vec::new();
+    for row in 2..=max_row {
+        let target_sheet = sheet.get_value((1, row)).trim().to_string();
+        if target_sheet.is_empty() {
+            continue;
+        }
+        let anchor = sheet.get_value((2, row)).trim().to_string();
+        let source =
+            Some(sheet.get_value((3, row)).trim().to_string()).filter(|source| !source.is_empty());
+        let query = serde_json::from_str(&sheet.get_value((4, row))).with_context(|| {
+            format!("sheet '{MATERIALIZED_SHEET_NAME}' row {row} has an invalid query")
+        })?;
+        let rows = sheet.get_value((5, row)).trim().parse::<f64>().ok();
+        let refreshed_at = Some(sheet.get_value((6, row)).trim().to_string())
+            .filter(|refreshed_at| !refreshed_at.is_empty());
+        records.push(MaterializeRecord {
+            definition: MaterializeDefinition {
+                target_sheet,
+                anchor: if anchor.is_empty() {
+                    "A1".to_string()
+                } else {
+                    anchor
+                },
+                source,
+                query,
+            },
+            rows: rows.map(|rows| rows as u32),
+            refreshed_at,
+        });
+    }
+    Ok(records)
+}
+
+/// Insert or replace the definition row for `definition.target_sheet`,
+/// creating the hidden `_materialized` sheet on first use.
+pub fn record_materialization(
+    book: &mut umya_spreadsheet::Spreadsheet,
+    definition: &MaterializeDefinition,
+    rows: u32,
+    refreshed_at: &str,
+) -> Result<()> {
+    let query = serde_json::to_string(&definition.query)?;
+    if book.get_sheet_by_name(MATERIALIZED_SHEET_NAME).is_none() {
+        let sheet = book
+            .new_sheet(MATERIALIZED_SHEET_NAME)
+            .map_err(|err| anyhow!("failed to create sheet '{MATERIALIZED_SHEET_NAME}': {err}"))?;
+        sheet.set_sheet_state("hidden".to_string());
+        for (idx, header) in DEFINITION_HEADERS.iter().enumerate() {
+            sheet.get_cell_mut((idx as u32 + 1, 1)).set_value(*header);
+        }
+    }
+    let sheet = book
+        .get_sheet_by_name_mut(MATERIALIZED_SHEET_NAME)
+        .ok_or_else(|| anyhow!("sheet '{MATERIALIZED_SHEET_NAME}' was not found"))?;
+
+    let max_row = sheet.get_highest_row().max(1);
+    let row = (2..=max_row)
+        .find(|row| {
+            sheet
+                .get_value((1, *row))
+                .trim()
+                .eq_ignore_ascii_case(&definition.target_sheet)
+        })
+        .unwrap_or(max_row + 1);
+
+    sheet
+        .get_cell_mut((1, row))
+        .set_value(definition.target_sheet.as_str());
+    sheet
+        .get_cell_mut((2, row))
+        .set_value(definition.anchor.as_str());
+    sheet
+        .get_cell_mut((3, row))
+        .set_value(definition.source.as_deref().unwrap_or(""));
+    sheet.get_cell_mut((4, row)).set_value(query);
+    sheet.get_cell_mut((5, row)).set_value_number(rows);
+    sheet.get_cell_mut((6, row)).set_value(refreshed_at);
+    Ok(())
+}
+
+/// Replace the contents of `target_sheet` (created if missing) with a header
+/// row and the given rows, starting at `anchor`. Values are written as static
+/// snapshots; formulas are not carried over.
+pub fn write_materialized_table(
+    book: &mut umya_spreadsheet::Spreadsheet,
+    target_sheet: &str,
+    anchor: &str,
+    headers: &[String],
+    rows: &[TableRow],
+) -> Result<MaterializedRange> {
+    if target_sheet.eq_ignore_ascii_case(MATERIALIZED_SHEET_NAME) {
+        bail!("'{MATERIALIZED_SHEET_NAME}' is reserved for materialization definitions");
+    }
+    let address = anchor.trim().replace('$', "").to_ascii_uppercase();
+    let (col, row, _, _) = umya_spreadsheet::helper::coordinate::index_from_coordinate(&address);
+    let (Some(start_col), Some(start_row)) = (col, row) else {
+        bail!("anchor '{anchor}' must be an A1 cell reference");
+    };
+
+    if book.get_sheet_by_name(target_sheet).is_none() {
+        book.new_sheet(target_sheet)
+            .map_err(|err| anyhow!("failed to create sheet '{target_sheet}': {err}"))?;
+    }
+    let sheet = book
+        .get_sheet_by_name_mut(target_sheet)
+        .ok_or_else(|| anyhow!("sheet '{target_sheet}' was not found"))?;
+
+    let existing: Vec<(u32, u32)> = sheet
+        .get_cell_collection()
+        .iter()
+        .map(|cell| {
+            (
+                *cell.get_coordinate().get_col_num(),
+                *cell.get_coordinate().get_row_num(),
+            )
+        })
+        .collect();
+    for coordinate in existing {
+        sheet.remove_cell(coordinate);
+    }
+
+    for (offset, header) in headers.iter().enumerate() {
+        sheet
+            .get_cell_mut((start_col + offset as u32, start_row))
+            .set_value(header.as_str());
+    }
+    for (row_offset, row) in rows.iter().enumerate() {
+        let row_idx = start_row + 1 + row_offset as u32;
+        for (col_offset, header) in headers.iter().enumerate() {
+            let Some(value) = row.get(header).and_then(Option::as_ref) else {
+                continue;
+            };
+            let cell = sheet.get_cell_mut((start_col + col_offset as u32, row_idx));
+            match value {
+                CellValue::Number(n) => {
+                    cell.set_value_number(*n);
+                }
+                CellValue::Bool(b) => {
+                    cell.set_value_bool(*b);
+                }
+                CellValue::Text(s) | CellValue::Error(s) | CellValue::Date(s) => {
+                    cell.set_value(s.as_str());
+                }
+            }
+        }
+    }
+
+    let columns = headers.len().max(1) as u32;
+    let rows = rows.len() as u32;
+    let end_col = start_col + columns - 1;
+    let end_row = start_row + rows;
+    Ok(MaterializedRange {
+        range: format!(
+            "{}{}:{}{}",
+            column_number_to_name(start_col),
+            start_row,
+            column_number_to_name(end_col),
+            end_row
+        ),
+        rows,
+        columns,
+    })
+}
@@ -0,0 +1,255 @@
+//! Tool metadata persisted inside the workbook.
+//!
+//! Entries live in a hidden `_metadata` sheet, one row per namespace and key,
+//! so they travel with the file and survive round-trips through Excel. Values
+//! are JSON. Namespaces group entries by purpose, e.g. `names` for semantic
+//! names, `queries` for stored queries, `annotations` for notes attached to
+//! cells, `snapshots` or `audit` for pointers to records kept elsewhere.
+
+use crate::utils::cell_address;
+use anyhow::{Result, anyhow, bail};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use umya_spreadsheet::Spreadsheet;
+
+/// Hidden sheet that stores tool metadata.
+pub const METADATA_SHEET_NAME: &str = "_metadata";
+
+const METADATA_HEADERS: [&str; 4] = ["namespace", "key", "value", "updated_at"];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct MetadataEntry {
+    pub namespace: String,
+    pub key: String,
+    /// Stored JSON value; a cell that no longer parses as JSON (e.g. after a
+    /// hand edit) reads back as a string.
+    pub value: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+}
+
+/// Entries from the `_metadata` sheet in row order; empty when it is absent.
+pub fn read_metadata(book: &Spreadsheet) -> Vec<MetadataEntry> {
+    let Some(sheet) = book.get_sheet_by_name(METADATA_SHEET_NAME) else {
+        return Vec::new();
+    };
+
+    let mut entries = Vec::new();
+    for row in 2..=sheet.get_highest_row() {
+        let namespace = sheet.get_value((1, row)).trim().to_string();
+        let key = sheet.get_value((2, row)).trim().to_string();
+        if namespace.is_empty() || key.is_empty() {
+            continue;
+        }
+        let raw = sheet.get_value((3, row));
+        let value = serde_json::from_str(&raw).unwrap_or(Value::String(raw));
+        let updated_at = Some(sheet.get_value((4, row)).trim().to_string())
+            .filter(|updated_at| !updated_at.is_empty());
+        entries.push(MetadataEntry {
+            namespace,
+            key,
+            value,
+            updated_at,
+        });
+    }
+    entries
+}
+
+/// The entry stored under `namespace` and `key`, if any.
+pub fn get_metadata(book: &Spreadsheet, namespace: &str, key: &str) -> Option<MetadataEntry> {
+    read_metadata(book)
+        .into_iter()
+        .find(|entry| entry.namespace == namespace && entry.key == key)
+}
+
+/// Insert or replace the entry for `namespace` and `key`, creating the hidden
+/// `_metadata` sheet on first use. Returns the value it replaced.
+pub fn set_metadata(
+    book: &mut Spreadsheet,
+    namespace: &str,
+    key: &str,
+    value: Value,
+    updated_at: &str,
+) -> Result<Option<Value>> {
+    let namespace = namespace.trim();
+    let key = key.trim();
+    if namespace.is_empty() || key.is_empty() {
+        bail!("metadata namespace and key must not be empty");
+    }
+
+    let mut entries = read_metadata(book);
+    let entry = MetadataEntry {
+        namespace: namespace.to_string(),
+        key: key.to_string(),
+        value,
+        updated_at: Some(updated_at.to_string()),
+    };
+    let previous = match entries
+        .iter_mut()
+        .find(|existing| existing.namespace == namespace && existing.key == key)
+    {
+        Some(existing) => Some(std::mem::replace(existing, entry).value),
+        None => {
+            entries.push(entry);
+            None
+        }
+    };
+    write_metadata_sheet(book, &entries)?;
+    Ok(previous)
+}
+
+/// Remove entries in `namespace` (all namespaces when `None`), limited to
+/// `key` when given. The sheet is dropped once no entries remain. Returns the
+/// removed entries.
+pub fn clear_metadata(
+    book: &mut Spreadsheet,
+    namespace: Option<&str>,
+    key: Option<&str>,
+) -> Result<Vec<MetadataEntry>> {
+    let (removed, kept): (Vec<_>, Vec<_>) = read_metadata(book).into_iter().partition(|entry| {
+        namespace.is_none_or(|namespace| entry.namespace == namespace.trim())
+            && key.is_none_or(|key| entry.key == key.trim())
+    });
+    if removed.is_empty() {
+        return Ok(removed);
+    }
+
+    if kept.is_empty() {
+        book.remove_sheet_by_name(METADATA_SHEET_NAME)
+            .map_err(|err| anyhow!("failed to remove sheet '{METADATA_SHEET_NAME}': {err}"))?;
+    } else {
+        write_metadata_sheet(book, &kept)?;
+    }
+    Ok(removed)
+}
+
+/// Rewrite the `_metadata` sheet to hold exactly `entries`.
+fn write_metadata_sheet(book: &mut Spreadsheet, entries: &[MetadataEntry]) -> Result<()> {
+    if book.get_sheet_by_name(METADATA_SHEET_NAME).is_none() {
+        let sheet = book
+            .new_sheet(METADATA_SHEET_NAME)
+            .map_err(|err| anyhow!("failed to create sheet '{METADATA_SHEET_NAME}': {err}"))?;
+        sheet.set_sheet_state("hidden".to_string());
+    }
+    let sheet = book
+        .get_sheet_by_name_mut(METADATA_SHEET_NAME)
+        .ok_or_else(|| anyhow!("sheet '{METADATA_SHEET_NAME}' was not found"))?;
+
+    let existing: Vec<(u32, u32)> = sheet
+        .get_cell_collection()
+        .iter()
+        .map(|cell| {
+            (
+                *cell.get_coordinate().get_col_num(),
+                *cell.get_coordinate().get_row_num(),
+            )
+        })
+        .collect();
+    for coordinate in existing {
+        sheet.remove_cell(coordinate);
+    }
+
+    for (idx, header) in METADATA_HEADERS.iter().enumerate() {
+        sheet.get_cell_mut((idx as u32 + 1, 1)).set_value(*header);
+    }
+    for (offset, entry) in entries.iter().enumerate() {
+        let row = offset as u32 + 2;
+        sheet
+            .get_cell_mut((1, row))
+            .set_value(entry.namespace.as_str());
+        sheet.get_cell_mut((2, row)).set_value(entry.key.as_str());
+        // Stored as text so the raw JSON is kept verbatim.
+        sheet
+            .get_cell_mut((3, row))
+            .set_value_string(serde_json::to_string(&entry.value)?);
+        sheet
+            .get_cell_mut((4, row))
+            .set_value(entry.updated_at.as_deref().unwrap_or(""));
+    }
+    Ok(())
+}
+
+/// Namespace holding cell annotations: machine-readable notes attached to a
+/// cell or range without touching visible comments. Keys are `Sheet!A1` or
+/// `Sheet!A1:B2`.
+pub const ANNOTATIONS_NAMESPACE: &str = "annotations";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct CellAnnotation {
+    pub sheet: String,
+    /// Normalized A1 range, e.g. `B2` or `B2:D9`.
+    pub range: String,
+    pub value: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+}
+
+impl CellAnnotation {
+    /// Whether the annotated range shares a cell with `bounds`
+    /// (`((min_col, min_row), (max_col, max_row))`).
+    pub fn overlaps(&self, bounds: ((u32, u32), (u32, u32))) -> bool {
+        let Ok(((start_col, start_row), (end_col, end_row))) = annotation_bounds(&self.range)
+        else {
+            return false;
+        };
+        let ((min_col, min_row), (max_col, max_row)) = bounds;
+        start_col <= max_col && end_col >= min_col && start_row <= max_row && end_row >= min_row
+    }
+}
+
+/// Metadata key for an annotation on `range` of `sheet`; the range is
+/// normalized so `b2`, `B2:B2`, and `$B$2` share one key.
+pub fn annotation_key(sheet: &str, range: &str) -> Result<String> {
+    let sheet = sheet.trim();
+    if sheet.is_empty() {
+        bail!("annotation sheet must not be empty");
+    }
+    let ((start_col, start_row), (end_col, end_row)) = annotation_bounds(range)?;
+    let start = cell_address(start_col, start_row);
+    if (start_col, start_row) == (end_col, end_row) {
+        return Ok(format!("{sheet}!{start}"));
+    }
+    Ok(format!(
+        "{sheet}!{start}:{}",
+        cell_address(end_col, end_row)
+    ))
+}
+
+/// Annotations stored in the `_metadata` sheet, in row order. Entries whose
+/// key is not a `Sheet!range` reference are skipped.
+pub fn read_annotations(book: &Spreadsheet) -> Vec<CellAnnotation> {
+    read_metadata(book)
+        .into_iter()
+        .filter(|entry| entry.namespace == ANNOTATIONS_NAMESPACE)
+        .filter_map(|entry| {
+            let (sheet, range) = entry.key.rsplit_once('!')?;
+            annotation_bounds(range).ok()?;
+            Some(CellAnnotation {
+                sheet: sheet.to_string(),
+                range: range.to_string(),
+                value: entry.value,
+                updated_at: entry.updated_at,
+            })
+        })
+        .collect()
+}
+
+/// Bounds of an A1 cell or range, `((min_col, min_row), (max_col, max_row))`.
+pub fn annotation_bounds(range: &str) -> Result<((u32, u32), (u32, u32))> {
+    let trimmed = range.trim();
+    let (start, end) = trimmed.split_once(':').unwrap_or((trimmed, trimmed));
+    let (start_col, start_row, _, _) =
+        umya_spreadsheet::helper::coordinate::index_from_coordinate(start.trim());
+    let (end_col, end_row, _, _) =
+        umya_spreadsheet::helper::coordinate::index_from_coordinate(end.trim());
+    let (Some(start_col), Some(start_row), Some(end_col), Some(end_row)) =
+        (start_col, start_row, end_col, end_row)
+    else {
+        bail!("invalid A1 range '{range}'");
+    };
+    Ok((
+        (start_col.min(end_col), start_row.min(end_row)),
+        (start_col.max(end_col), start_row.max(end_row)),
+    ))
+}
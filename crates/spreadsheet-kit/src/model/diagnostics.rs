@@ -78,6 +78,25 @@ struct GroupAccumulator {
     sample_addresses: Vec<String>,
 }
 
+impl FormulaParseDiagnostics {
+    /// Diagnostics of two batches reported as one; groups past the cap are
+    /// dropped and flagged as truncated.
+    pub fn merge(first: Option<Self>, second: Option<Self>) -> Option<Self> {
+        let (mut merged, other) = match (first, second) {
+            (Some(first), Some(second)) => (first, second),
+            (first, second) => return first.or(second),
+        };
+        merged.total_errors += other.total_errors;
+        merged.groups_truncated |= other.groups_truncated;
+        merged.groups.extend(other.groups);
+        if merged.groups.len() > MAX_GROUPS {
+            merged.groups.truncate(MAX_GROUPS);
+            merged.groups_truncated = true;
+        }
+        Some(merged)
+    }
+}
+
 impl FormulaParseDiagnosticsBuilder {
     pub fn new(policy: FormulaParsePolicy) -> Self {
         Self {
@@ -49,6 +49,242 @@ pub struct WorkbookListResponse {
     pub next_offset: Option<u32>,
 }
 
+/// Exact and near-duplicate workbooks found under one or more roots.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DedupeScanResponse {
+    pub roots: Vec<String>,
+    pub files_scanned: u32,
+    /// Similarity a pair of workbooks needs to be reported as near duplicates.
+    pub min_similarity: f64,
+    /// Byte-identical files.
+    pub exact_duplicates: Vec<DuplicateGroup>,
+    /// Files whose bytes differ but whose sheets hold the same values and
+    /// formulas, e.g. the same workbook saved again under another name.
+    pub content_duplicates: Vec<DuplicateGroup>,
+    /// Pairs of workbooks sharing most of their rows, most similar first.
+    pub near_duplicates: Vec<NearDuplicatePair>,
+    /// Files whose cells could not be read; they still take part in exact
+    /// matching.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<DedupeSkippedFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DuplicateGroup {
+    /// SHA-256 of the file bytes (exact) or of the cell content (content).
+    pub fingerprint: String,
+    /// Most recently modified file of the group.
+    pub newest: String,
+    /// Newest first.
+    pub files: Vec<DuplicateFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DuplicateFile {
+    pub path: String,
+    pub bytes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NearDuplicatePair {
+    /// The two workbooks, newest first.
+    pub files: [DuplicateFile; 2],
+    /// Shared rows over all distinct rows of both workbooks (Jaccard index).
+    pub similarity: f64,
+    pub shared_rows: u32,
+    /// Distinct non-empty rows in each workbook, in `files` order.
+    pub rows: [u32; 2],
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DedupeSkippedFile {
+    pub path: String,
+    pub reason: String,
+}
+
+/// Workspace workbooks with enough structure to pick one before opening it.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WorkspaceCatalogResponse {
+    pub workbooks: Vec<CatalogWorkbook>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_offset: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CatalogWorkbook {
+    pub workbook_id: WorkbookId,
+    pub short_id: String,
+    pub slug: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub folder: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    pub bytes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+    pub sheet_count: u32,
+    pub sheets: Vec<CatalogSheet>,
+    /// Why the workbook could not be opened; its sheets are then empty.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CatalogSheet {
+    pub name: String,
+    pub row_count: u32,
+    pub column_count: u32,
+    /// Excel tables and detected table-like regions, in sheet order.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tables: Vec<CatalogTable>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CatalogTable {
+    /// Excel table name; absent for detected regions.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub table_name: Option<String>,
+    /// Detected region id, usable with `read_table` and `sheet_overview`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region_id: Option<u32>,
+    pub range: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub headers: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub row_count: Option<u32>,
+}
+
+/// Workspace workbooks whose sheet names or cells match a search.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WorkspaceSearchResponse {
+    pub workbooks_searched: u32,
+    /// Matching workbooks, in workspace listing order.
+    pub hits: Vec<WorkspaceSearchHit>,
+    /// True when `max_workbooks` stopped the search before every workbook
+    /// was checked.
+    pub truncated: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<WorkspaceSearchSkipped>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WorkspaceSearchHit {
+    pub workbook_id: WorkbookId,
+    pub slug: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Sheets whose names match `sheet_name`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub matching_sheets: Vec<String>,
+    /// First cell matches for `query`; call `find_value` on the workbook for
+    /// the rest.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub matches: Vec<FindValueMatch>,
+    /// More cells matched than `matches_per_workbook` returned.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub more_matches: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WorkspaceSearchSkipped {
+    pub workbook_id: WorkbookId,
+    pub slug: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RegisterAliasResponse {
+    pub alias: String,
+    pub target: String,
+    pub workbook_id: WorkbookId,
+    pub short_id: String,
+    /// Target the alias pointed at before this call, if it existed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replaced_target: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ListAliasesResponse {
+    pub aliases: Vec<WorkbookAliasInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WorkbookAliasInfo {
+    pub alias: String,
+    pub target: String,
+    /// Workbook the alias currently resolves to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workbook_id: Option<WorkbookId>,
+    /// Why the alias does not resolve, e.g. its file was moved
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkspaceEventKind {
+    Added,
+    Changed,
+    Removed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WorkspaceEvent {
+    pub seq: u64,
+    pub kind: WorkspaceEventKind,
+    pub workbook_id: WorkbookId,
+    pub short_id: String,
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revision_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_revision_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WatchWorkspaceResponse {
+    pub events: Vec<WorkspaceEvent>,
+    /// Pass as `since_seq` on the next call.
+    pub next_seq: u64,
+    /// Older events were dropped before they could be returned; call
+    /// `list_workbooks` to resynchronize.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub missed_events: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct UploadWorkbookResponse {
+    pub key: String,
+    /// Decoded bytes received so far for this key.
+    pub received_bytes: u64,
+    /// The workbook is stored and readable; the fields below are set.
+    pub complete: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workbook_id: Option<WorkbookId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub short_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revision_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DownloadWorkbookResponse {
+    pub workbook_id: WorkbookId,
+    /// Compare across chunks; a change means the workbook was replaced
+    /// mid-download.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revision_id: Option<String>,
+    pub total_bytes: u64,
+    pub offset: u64,
+    /// Base64-encoded bytes starting at `offset`.
+    pub data: String,
+    /// Pass as `offset` on the next call; absent on the last chunk.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_offset: Option<u64>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct WorkbookDescription {
     pub workbook_id: WorkbookId,
@@ -66,6 +302,172 @@ pub struct WorkbookDescription {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub revision_id: Option<String>,
     pub caps: BackendCaps,
+    /// Date serials count from 1904-01-01 (the Mac Excel date system) rather
+    /// than 1900; dates read from the workbook are already converted.
+    #[serde(default)]
+    pub date1904: bool,
+    /// Excel stores values rounded to their displayed format ("Set precision
+    /// as displayed"). Aggregates and recalculated results are not rounded
+    /// that way; pass `decimals` to aggregate and query tools to match them.
+    #[serde(default)]
+    pub precision_as_displayed: bool,
+    /// Per-sheet counts of embedded OLE objects and linked documents, which
+    /// cell reads do not see; `sheet_overview` lists them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub embedded_objects: Vec<SheetEmbeddedObjects>,
+    /// Title, author and other document properties, including custom ones.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub properties: Option<DocumentProperties>,
+}
+
+/// Document properties from `docProps/` (core, app and custom), as shown in
+/// Excel under File > Info. Absent fields are not set in the workbook.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct DocumentProperties {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    /// Author
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub creator: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keywords: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_modified_by: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_status: Option<String>,
+    /// W3C date-time, e.g. `2024-05-01T09:30:00Z`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modified: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub company: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manager: Option<String>,
+    /// The application that last saved the workbook
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub application: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub custom: Vec<CustomDocumentProperty>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct CustomDocumentProperty {
+    pub name: String,
+    /// A string, number or boolean; dates are ISO 8601 strings
+    pub value: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SheetEmbeddedObjects {
+    pub sheet_name: String,
+    pub embedded: u32,
+    pub linked: u32,
+    /// Uncompressed size of the embedded parts.
+    pub embedded_bytes: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EmbeddedObjectKind {
+    /// OLE compound object stored in the package (`oleObject*.bin`).
+    Ole,
+    /// Office document or other file stored in the package as-is.
+    Package,
+    /// Object whose data lives in an external file.
+    Linked,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct EmbeddedObject {
+    pub kind: EmbeddedObjectKind,
+    /// OLE program id such as `Word.Document.12` or `AcroExch.Document.DC`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prog_id: Option<String>,
+    /// Lowercase file extension of the embedded part or linked file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_type: Option<String>,
+    /// Package part holding an embedded object.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub part: Option<String>,
+    /// Path or URL of a linked object's file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// Uncompressed size of an embedded part.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_bytes: Option<u64>,
+    /// Top-left cell the object is drawn over.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anchor_cell: Option<String>,
+}
+
+/// A link to another workbook, from `xl/externalLinks/*`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExternalLink {
+    /// 1-based position formulas use to refer to the link, as in `[1]Rates!B2`.
+    pub index: u32,
+    /// Package part holding the link and its cached values.
+    pub part: String,
+    /// Linked workbook path or URL as recorded in the file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// Local file the target resolves to, relative to the linking workbook.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_path: Option<String>,
+    pub status: ExternalLinkStatus,
+    /// Sheets of the linked workbook the link caches values from.
+    pub sheet_names: Vec<String>,
+    pub cached_cell_count: u32,
+    /// Cached values that no longer match the linked workbook.
+    pub stale_cell_count: u32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stale_cells: Vec<ExternalStaleCell>,
+    /// Formula cells that reference the link.
+    pub used_by_count: u32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub used_by: Vec<ExternalLinkUse>,
+    /// Defined names whose formula references the link.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub defined_names: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExternalLinkStatus {
+    /// Every cached value matches the linked workbook.
+    Current,
+    /// Some cached values differ from the linked workbook.
+    Stale,
+    /// The linked workbook was not found on disk.
+    Missing,
+    /// The target is a URL or absent, so it cannot be checked.
+    Unresolvable,
+    /// The linked workbook exists but holds no cached values to compare, or
+    /// could not be read.
+    Unchecked,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExternalStaleCell {
+    pub sheet: String,
+    pub cell: String,
+    pub cached: String,
+    /// Value in the linked workbook now; absent when its sheet is gone.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExternalLinkUse {
+    pub sheet: String,
+    pub cell: String,
+    pub formula: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -115,6 +517,10 @@ pub struct EntryPoint {
 pub struct SheetSummary {
     pub name: String,
     pub visible: bool,
+    pub visibility: SheetVisibility,
+    /// Tab color as `AARRGGBB`, when set with an explicit RGB value
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tab_color: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub row_count: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -130,6 +536,34 @@ pub struct SheetSummary {
     pub style_tags: Vec<String>,
 }
 
+/// A sheet's tab state. `very_hidden` sheets cannot be unhidden from
+/// Excel's UI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SheetVisibility {
+    Visible,
+    Hidden,
+    VeryHidden,
+}
+
+impl SheetVisibility {
+    pub fn from_sheet_state(state: &str) -> Self {
+        match state {
+            "hidden" => Self::Hidden,
+            "veryHidden" => Self::VeryHidden,
+            _ => Self::Visible,
+        }
+    }
+
+    pub fn as_sheet_state(self) -> &'static str {
+        match self {
+            Self::Visible => "visible",
+            Self::Hidden => "hidden",
+            Self::VeryHidden => "veryHidden",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum SheetClassification {
@@ -161,6 +595,115 @@ pub struct SheetOverviewResponse {
     pub formula_ratio: f32,
     pub notable_features: Vec<String>,
     pub notes: Vec<String>,
+    #[serde(default)]
+    pub layout: SheetOverviewLayout,
+    /// Layout kind of the whole sheet, for picking an extraction strategy.
+    #[serde(default)]
+    pub sheet_kind: SheetKind,
+    #[serde(default)]
+    pub sheet_kind_reason: String,
+    /// Labeled input fields, instructions, and signature blocks; present
+    /// for form sheets.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub form: Option<FormLayout>,
+    /// Embedded OLE objects and linked documents on this sheet.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub embedded_objects: Vec<EmbeddedObject>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SheetKind {
+    /// Headed rows and columns; read with `read_table`.
+    Table,
+    /// Scattered labeled input cells, instructions, and signature blocks.
+    Form,
+    /// Charts or small formula-driven summary blocks.
+    Dashboard,
+    /// Mostly free text.
+    Notes,
+    #[default]
+    Mixed,
+    Empty,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct FormLayout {
+    pub field_count: u32,
+    pub fields: Vec<FormField>,
+    pub fields_truncated: bool,
+    /// Cells holding instruction prose.
+    pub instructions: Vec<String>,
+    /// Signature, "approved by", and similar label cells.
+    pub signature_blocks: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FormField {
+    /// Label text without a trailing colon.
+    pub label: String,
+    pub label_address: String,
+    /// Cell the label describes: to its right, or below a `Label:` cell.
+    pub input_address: String,
+    /// False when the input cell is an empty (styled) box.
+    pub filled: bool,
+}
+
+/// View and visibility state of a sheet: panes, zoom, gridlines, hidden rows/columns.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct SheetOverviewLayout {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pane: Option<SheetPaneLayout>,
+    pub zoom_percent: u32,
+    pub show_gridlines: bool,
+    /// The sheet is laid out right to left: column A at the right edge, as
+    /// in Arabic or Hebrew workbooks.
+    pub right_to_left: bool,
+    pub hidden_row_count: u32,
+    /// Hidden row spans such as `"5"` or `"5:9"`.
+    pub hidden_rows: Vec<String>,
+    pub hidden_column_count: u32,
+    /// Hidden column spans such as `"C"` or `"C:E"`.
+    pub hidden_columns: Vec<String>,
+    pub hidden_spans_truncated: bool,
+    pub print: SheetPrintLayout,
+}
+
+/// Print setup: what a PDF export or print of the sheet will use.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct SheetPrintLayout {
+    /// Print area such as `A1:G30`; absent when the whole used range prints.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub print_area: Option<String>,
+    /// `portrait` or `landscape`.
+    pub orientation: String,
+    pub margins: SheetPageMargins,
+    /// Header text with Excel codes such as `&P` (page) and `&N` (pages).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub header: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub footer: Option<String>,
+}
+
+/// Page margins in inches.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct SheetPageMargins {
+    pub left: f64,
+    pub right: f64,
+    pub top: f64,
+    pub bottom: f64,
+    pub header: f64,
+    pub footer: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SheetPaneLayout {
+    /// `frozen`, `frozen_split`, or `split`.
+    pub state: String,
+    pub frozen_rows: u32,
+    pub frozen_cols: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_left_cell: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -188,6 +731,8 @@ pub enum RegionKind {
     Styles,
     #[serde(rename = "likely_comments")]
     Comments,
+    #[serde(rename = "likely_form")]
+    Form,
     #[serde(rename = "unknown")]
     Other,
 }
@@ -220,6 +765,26 @@ pub struct SheetPageResponse {
     pub compact: Option<SheetPageCompact>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub values_only: Option<SheetPageValues>,
+    /// Present only for `orientation=columns` pages.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub orientation: Option<SheetPageOrientation>,
+    /// Column-oriented records, replacing the row branches when
+    /// `orientation=columns`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column_page: Option<SheetPageColumns>,
+    /// Continuation for column-oriented pages: pass as `start_column`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_start_column: Option<String>,
+    /// Serial numbers behind date cells on the page, by A1 address; present
+    /// with `date_handling=both`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date_serials: Option<BTreeMap<String, f64>>,
+    /// The header each `columns_by_header` selector resolved to.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub resolved_headers: Vec<HeaderResolution>,
+    /// `columns_by_header` selectors that matched no header.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unmatched_headers: Vec<String>,
     pub format: SheetPageFormat,
     /// True when the response was truncated by cell/payload budget limits.
     #[serde(default, skip_serializing_if = "is_false")]
@@ -227,6 +792,21 @@ pub struct SheetPageResponse {
     /// Machine-consumable budget/continuation metadata.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub budget: Option<ReadBudget>,
+    /// Page data moved into a compressed blob; present when `compression`
+    /// was requested and the page reached `compress_min_bytes`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compressed: Option<CompressedPayload>,
+}
+
+/// A header column selected by a `columns_by_header` selector.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HeaderResolution {
+    pub selector: String,
+    pub column: String,
+    pub header: String,
+    /// Similarity in 0..=1; present for fuzzy matches.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
 }
 
 /// Machine-consumable output-budget metadata attached to read-surface responses.
@@ -257,9 +837,17 @@ fn is_false(v: &bool) -> bool {
     !v
 }
 
+fn is_zero_u8(v: &u8) -> bool {
+    *v == 0
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct RowSnapshot {
     pub row_index: u32,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub hidden: bool,
+    #[serde(default, skip_serializing_if = "is_zero_u8")]
+    pub outline_level: u8,
     pub cells: Vec<CellSnapshot>,
 }
 
@@ -326,6 +914,12 @@ pub struct SheetPageCompact {
     pub headers: Vec<String>,
     pub header_row: Vec<Option<CellValue>>,
     pub rows: Vec<Vec<Option<CellValue>>>,
+    /// Row indices on this page that are hidden.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hidden_rows: Vec<u32>,
+    /// Outline (grouping) level keyed by row index, for grouped rows on this page.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub row_outline_levels: BTreeMap<u32, u8>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -333,6 +927,39 @@ pub struct SheetPageValues {
     pub rows: Vec<Vec<Option<CellValue>>>,
 }
 
+/// Whether a sheet page is read row-by-row (default) or column-by-column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SheetPageOrientation {
+    #[default]
+    Rows,
+    Columns,
+}
+
+/// Column-oriented page: each record is one column with its values for every
+/// row from `first_row` through `last_row`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SheetPageColumns {
+    pub first_row: u32,
+    pub last_row: u32,
+    pub columns: Vec<ColumnSnapshot>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ColumnSnapshot {
+    /// Column letter, e.g. "C".
+    pub column: String,
+    /// Row 1 text when the header is included.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub header: Option<String>,
+    /// Per-cell values (compact and values_only formats).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub values: Vec<Option<CellValue>>,
+    /// Per-cell snapshots (full format).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cells: Vec<CellSnapshot>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SheetStatisticsResponse {
     pub workbook_id: WorkbookId,
@@ -364,12 +991,31 @@ pub struct SheetFormulaMapResponse {
     pub workbook_id: WorkbookId,
     pub sheet_name: String,
     pub groups: Vec<FormulaGroup>,
+    /// Cells breaking the formula pattern of their column or row region; only
+    /// present in inconsistencies mode.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inconsistencies: Option<Vec<FormulaInconsistency>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub formula_parse_diagnostics: Option<FormulaParseDiagnostics>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub next_offset: Option<u32>,
 }
 
+/// How formula references are displayed. In `r1c1` form references are
+/// relative to the formula's own cell, so a formula filled down a column reads
+/// the same in every row.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema, clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum ReferenceStyle {
+    #[default]
+    #[value(name = "a1")]
+    A1,
+    #[value(name = "r1c1")]
+    R1C1,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct FormulaGroup {
     pub fingerprint: String,
@@ -383,6 +1029,44 @@ pub struct FormulaGroup {
     pub is_volatile: bool,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FormulaRegionAxis {
+    Column,
+    Row,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FormulaInconsistencyKind {
+    /// A formula with a different R1C1 pattern than the rest of the region.
+    FormulaMismatch,
+    /// A typed value where the region's formula was expected.
+    HardcodedValue,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FormulaInconsistency {
+    pub address: String,
+    pub kind: FormulaInconsistencyKind,
+    pub axis: FormulaRegionAxis,
+    /// Span of the column or row region whose pattern this cell breaks.
+    pub region: String,
+    /// Cells in the region that follow the pattern.
+    pub pattern_cells: u32,
+    /// The region's formula in relative R1C1 form, e.g. `RC[-2]*RC[-1]`.
+    pub expected_pattern: String,
+    /// The region's formula as it would read in this cell.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected_formula: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actual_formula: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actual_pattern: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actual_value: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct FormulaTraceResponse {
     pub workbook_id: WorkbookId,
@@ -402,6 +1086,10 @@ pub struct FormulaTraceEdge {
     pub to: String,
     pub formula: Option<String>,
     pub note: Option<String>,
+    /// Defined name or structured table reference (`Sales[Amount]`) the
+    /// formula used to reach this precedent, when it did not cite it directly.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub via_name: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -502,6 +1190,14 @@ pub struct NamedRangeDescriptor {
     pub kind: NamedItemKind,
     pub sheet_name: Option<String>,
     pub comment: Option<String>,
+    /// Current value of a formula or constant name, when evaluation was
+    /// requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<CellValue>,
+    /// The same name is also defined at the other scope. Reads and writes
+    /// that look the name up take a `scope` to choose between them.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub scope_conflict: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
@@ -509,6 +1205,8 @@ pub struct NamedRangeDescriptor {
 pub enum NamedItemKind {
     NamedRange,
     Table,
+    /// A literal or array constant, e.g. `=0.075` or `={1,2,3}`.
+    Constant,
     Formula,
     Unknown,
 }
@@ -583,6 +1281,96 @@ pub struct VolatileScanResponse {
     pub next_offset: Option<u32>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ErrorScanEntry {
+    pub sheet_name: String,
+    pub address: String,
+    /// Cached error value, e.g. `#REF!` or `#DIV/0!`.
+    pub error: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub formula: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ErrorScanGroup {
+    pub error: String,
+    pub count: u32,
+    /// Sheets holding at least one cell with this error.
+    pub sheets: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ErrorScanResponse {
+    pub workbook_id: WorkbookId,
+    pub total_errors: u32,
+    /// One entry per error type across the scanned sheets, most frequent first.
+    pub groups: Vec<ErrorScanGroup>,
+    /// Error cells ordered by error type, sheet, then row and column.
+    pub items: Vec<ErrorScanEntry>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_offset: Option<u32>,
+}
+
+/// Formula style checks run by `lint_formulas`.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    clap::ValueEnum,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum FormulaLintRule {
+    /// Numeric literal used as an arithmetic operand, e.g. `=B2*1.08`.
+    #[value(name = "hardcoded_constant")]
+    HardcodedConstant,
+    /// Whole-column reference such as `A:A` inside an aggregation.
+    #[value(name = "full_column_reference")]
+    FullColumnReference,
+    /// IFs nested deeper than the configured maximum.
+    #[value(name = "nested_if")]
+    NestedIf,
+    /// Formula that breaks the pattern its row or column neighbours share.
+    #[value(name = "inconsistent_formula")]
+    InconsistentFormula,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FormulaLintFinding {
+    pub rule: FormulaLintRule,
+    pub sheet_name: String,
+    pub address: String,
+    pub message: String,
+    pub formula: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FormulaLintRuleCount {
+    pub rule: FormulaLintRule,
+    pub count: u32,
+    /// Sheets with at least one finding for this rule.
+    pub sheets: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FormulaLintResponse {
+    pub workbook_id: WorkbookId,
+    pub total_findings: u32,
+    /// Finding counts per rule across the scanned sheets, in rule order.
+    pub rules: Vec<FormulaLintRuleCount>,
+    /// Findings ordered by rule, sheet, then row and column.
+    pub items: Vec<FormulaLintFinding>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_offset: Option<u32>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
 pub struct StyleDescriptor {
     pub font: Option<FontDescriptor>,
@@ -672,6 +1460,18 @@ pub struct StylePatch {
     pub alignment: Option<Option<AlignmentPatch>>,
     #[serde(default)]
     pub number_format: Option<Option<String>>,
+    /// Convert the current number format between locale conventions, after
+    /// any `number_format` above is applied
+    #[serde(default)]
+    pub number_format_locale: Option<NumberFormatLocalePatch>,
+}
+
+/// Locales for [`crate::number_format::convert_format_code`], as tags such
+/// as `en-US` or `de-DE`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NumberFormatLocalePatch {
+    pub from: String,
+    pub to: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
@@ -906,11 +1706,25 @@ pub struct LabelHit {
     pub label: String,
 }
 
+/// Matches on one sheet, returned by `find_value` with `group_by_sheet`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FindValueSheetMatches {
+    pub sheet_name: String,
+    pub match_count: u32,
+    /// The first few matching cells, in scan order.
+    pub first_addresses: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct FindValueResponse {
     pub workbook_id: WorkbookId,
     pub matches: Vec<FindValueMatch>,
+    /// Matches returned, or with `group_by_sheet` the matches across every
+    /// sheet searched.
     pub match_count: u32,
+    /// Per-sheet match counts, with `group_by_sheet`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sheets: Vec<FindValueSheetMatches>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub next_offset: Option<u32>,
 }
@@ -932,11 +1746,137 @@ pub struct ReadTableResponse {
     pub values: Option<Vec<Vec<Option<CellValuePrimitive>>>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub types: Option<Vec<Vec<Option<CellValueKind>>>>,
+    /// Serial numbers behind date cells, aligned to rows × headers like
+    /// `types`; present with `date_handling=both`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date_serials: Option<Vec<Vec<Option<f64>>>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub csv: Option<String>,
     pub total_rows: u32,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub next_offset: Option<u32>,
+    /// Which rows were read as headers and why.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header_detection: Option<HeaderDetection>,
+    /// Data fields moved into a compressed blob; present when `compression`
+    /// was requested and the result reached `compress_min_bytes`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compressed: Option<CompressedPayload>,
+}
+
+/// Where `read_table` took its header row from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HeaderSource {
+    /// `header_row` was given.
+    Explicit,
+    /// The header row of an Excel table.
+    Table,
+    /// The header row found by region detection.
+    Detected,
+    /// The first row of the range, with nothing better to go on.
+    RangeTop,
+    /// `header_rows: 0`; columns are named by letter.
+    #[serde(rename = "none")]
+    NoHeader,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HeaderDetection {
+    pub source: HeaderSource,
+    /// First header row; absent without headers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header_row: Option<u32>,
+    /// Header rows flattened into each column name, joined with ` / `.
+    pub header_rows: u32,
+    pub data_start_row: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ResponseCompression {
+    Gzip,
+    Zstd,
+}
+
+/// A compressed part of a tool result.
+///
+/// `data` is the base64 of the compressed UTF-8 JSON object holding the
+/// response fields listed in `fields`, which are left out of the response
+/// itself. Decoding it and merging the object back gives the uncompressed
+/// result.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CompressedPayload {
+    pub encoding: ResponseCompression,
+    pub fields: Vec<String>,
+    /// Size of the JSON object before compression.
+    pub original_bytes: u64,
+    /// Size of the compressed object before base64 encoding.
+    pub compressed_bytes: u64,
+    pub data: String,
+}
+
+/// Grouped aggregates over a table, one row per group.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct QueryTableResponse {
+    pub workbook_id: WorkbookId,
+    pub sheet_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub table_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<Warning>,
+    /// Group-by columns, then one column per aggregation.
+    pub columns: Vec<String>,
+    /// Groups ordered by their group-by values.
+    pub rows: Vec<Vec<Option<CellValuePrimitive>>>,
+    /// Data rows that passed the filters.
+    pub matched_rows: u32,
+    pub total_groups: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_offset: Option<u32>,
+}
+
+/// Preflight sizing for a prospective `read_table` call.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ReadEstimateResponse {
+    pub workbook_id: WorkbookId,
+    pub sheet_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub table_name: Option<String>,
+    /// Resolved A1 range, header row included.
+    pub range: String,
+    pub format: TableOutputFormat,
+    /// Data rows matching the filters.
+    pub row_count: u32,
+    pub column_count: u32,
+    /// `row_count * column_count`.
+    pub cell_count: u64,
+    /// Estimated serialized size of every matching row in one response.
+    pub estimated_payload_bytes: u64,
+    /// Average serialized bytes per row, measured on a sample.
+    pub bytes_per_row: u64,
+    /// Rows measured to derive `bytes_per_row`.
+    pub sampled_rows: u32,
+    pub caps: ReadEstimateCaps,
+    /// Page size the estimate was checked against.
+    pub requested_rows: u32,
+    /// True when a page of `requested_rows` would be cut short by a cap.
+    pub would_truncate: bool,
+    /// Caps that would truncate the requested page (`max_cells`, `max_payload_bytes`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub truncated_by: Vec<String>,
+    /// Largest page size that fits within every cap.
+    pub max_rows_per_page: u32,
+    /// Pages needed to read every matching row at `min(requested_rows, max_rows_per_page)`.
+    pub pages_needed: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ReadEstimateCaps {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_cells: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_payload_bytes: Option<usize>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -949,6 +1889,56 @@ pub struct ColumnTypeSummary {
     pub min: Option<f64>,
     pub max: Option<f64>,
     pub mean: Option<f64>,
+    /// Statistics over every data row; the fields above cover the sample only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats: Option<ColumnStats>,
+}
+
+/// Whole-column statistics from `table_profile`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ColumnStats {
+    /// Non-blank cells.
+    pub count: u32,
+    pub nulls: u32,
+    /// Blank cells as a percentage of all data rows.
+    pub null_pct: f64,
+    /// Distinct non-blank values, numbers included.
+    pub distinct: u32,
+    /// Most frequent values, most frequent first.
+    pub top_values: Vec<ValueFrequency>,
+    /// Cells holding numbers; min/max/mean/stddev cover only these.
+    pub numeric_count: u32,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub mean: Option<f64>,
+    /// Sample standard deviation (as Excel `STDEV.S`).
+    pub stddev: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outliers: Option<ColumnOutliers>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ValueFrequency {
+    pub value: String,
+    pub count: u32,
+}
+
+/// Numbers outside Tukey's fences: more than 1.5 interquartile ranges below
+/// the first quartile or above the third.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ColumnOutliers {
+    pub lower_fence: f64,
+    pub upper_fence: f64,
+    pub count: u32,
+    /// The first few outlying cells, in sheet order.
+    pub cells: Vec<OutlierCell>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct OutlierCell {
+    /// Sheet row of the cell.
+    pub row: u32,
+    pub value: f64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -963,6 +1953,9 @@ pub struct TableProfileResponse {
     pub samples: Vec<TableRow>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub notes: Vec<String>,
+    /// Schema inferred over every row, when `emit_schema` is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<serde_json::Value>,
 }
 
 /// Canonical `range-values` response contract.
@@ -980,6 +1973,10 @@ pub struct RangeValuesResponse {
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub warnings: Vec<Warning>,
     pub values: Vec<RangeValuesEntry>,
+    /// `values` moved into a compressed blob; present when `compression` was
+    /// requested and the values reached `compress_min_bytes`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compressed: Option<CompressedPayload>,
 }
 
 /// Per-range payload for `range-values`.
@@ -997,6 +1994,10 @@ pub struct RangeValuesEntry {
     /// for literal/non-formula cells.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub formulas: Option<Vec<Vec<Option<String>>>>,
+    /// Number format and display text of each non-empty cell when
+    /// `include_formatting=true`, sparse in every output format.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub formatting: Option<Vec<RangeValuesCellFormat>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub values: Option<Vec<Vec<Option<CellValuePrimitive>>>>,
     /// Dense JSON encoding optimized for agent consumption.
@@ -1044,6 +2045,19 @@ pub struct RangeValuesDenseRun {
     pub len: u32,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RangeValuesCellFormat {
+    /// Zero-based row index within returned rows.
+    pub row: u32,
+    /// Zero-based column index within returned rows.
+    pub col: u32,
+    /// Number-format code; omitted for `General`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub number_format: Option<String>,
+    /// Text Excel displays for the cell, e.g. `12.3%` for `0.1234`.
+    pub display: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct RangeValuesDenseFormula {
     /// Zero-based row index within returned rows.
@@ -1203,9 +2217,14 @@ pub struct LayoutPageResponse {
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub merged_cells: Vec<String>,
     pub rows: Vec<LayoutRowInfo>,
-    /// ASCII art render (present when render=ascii or render=both)
+    /// ASCII art render (present when render=ascii or render=both). Mirrored
+    /// for right-to-left sheets, so column A is drawn at the right.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ascii_render: Option<String>,
+    /// The sheet is laid out right to left; `columns` and `rows` stay in A1
+    /// order.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub right_to_left: bool,
     /// True when the requested range was capped to the row/column limits
     #[serde(skip_serializing_if = "std::ops::Not::not")]
     pub truncated: bool,
@@ -0,0 +1,456 @@
+//! Number-format codes and the text Excel displays for a cell.
+//!
+//! Rendering goes through umya-spreadsheet's format engine, which only knows
+//! the 1900 date system; date serials in 1904-system workbooks are moved onto
+//! the 1900 epoch before they are rendered.
+
+use crate::workbook::{is_date_formatted, uses_1904_date_system};
+use umya_spreadsheet::Cell;
+use umya_spreadsheet::helper::number_format::to_formatted_string;
+
+/// Days from the 1900 date-system epoch to the 1904 one.
+const EPOCH_1904_OFFSET_DAYS: f64 = 1462.0;
+
+/// Number-format code applied to `cell`, or `None` for `General`.
+pub fn cell_format_code(cell: &Cell) -> Option<String> {
+    cell.get_style()
+        .get_number_format()
+        .map(|format| format.get_format_code().to_string())
+        .filter(|code| !code.is_empty() && code != "General")
+}
+
+/// Text Excel shows for `cell`, e.g. `12.3%` for `0.1234` under `0.0%`.
+/// Non-numeric values and `General` numbers are returned as stored. Date
+/// serials follow the date system of the enclosing
+/// [`crate::workbook::with_date_system`] scope.
+pub fn cell_display_text(cell: &Cell) -> Option<String> {
+    let raw = cell.get_value();
+    if raw.is_empty() {
+        return None;
+    }
+    let (Ok(number), Some(code)) = (raw.parse::<f64>(), cell_format_code(cell)) else {
+        return Some(raw.to_string());
+    };
+    let number = if uses_1904_date_system() && is_date_formatted(cell) {
+        number + EPOCH_1904_OFFSET_DAYS
+    } else {
+        number
+    };
+    Some(render_number(number, &code))
+}
+
+/// Render `value` under an Excel number-format code.
+pub fn render_number(value: f64, format_code: &str) -> String {
+    to_formatted_string(value.to_string(), format_code)
+}
+
+/// Built-in codes Excel already displays in the reader's locale (short date
+/// and date-time, ids 14 and 22); rewriting them would pin one locale.
+const LOCALE_ADAPTIVE_CODES: [&str; 2] = ["mm-dd-yy", "m/d/yy h:mm"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DateOrder {
+    DayMonthYear,
+    MonthDayYear,
+    YearMonthDay,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CurrencyPlacement {
+    /// `$1,234.56`
+    Prefix,
+    /// `R$ 1,234.56`
+    PrefixSpaced,
+    /// `1,234.56 €`
+    SuffixSpaced,
+}
+
+/// Locale conventions a number-format code spells out explicitly: currency
+/// symbol and placement, `[$-LCID]` locale tags, and numeric date order.
+///
+/// Decimal and group separators are not among them: `#,##0.00` is stored the
+/// same in every workbook and Excel shows it with the reader's separators,
+/// so a German reader already sees `1.234,56`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatLocale {
+    lcid: &'static str,
+    currency: &'static str,
+    placement: CurrencyPlacement,
+    date_order: DateOrder,
+    date_separator: char,
+}
+
+impl FormatLocale {
+    /// Conventions for a tag such as `en-US`, `de-DE` or `pt_BR`; a bare
+    /// language uses its most common region.
+    pub fn parse(tag: &str) -> anyhow::Result<Self> {
+        use CurrencyPlacement::*;
+        use DateOrder::*;
+        let mut parts = tag.trim().split(['-', '_']);
+        let language = parts.next().unwrap_or_default().to_ascii_lowercase();
+        let region = parts.next().map(|region| region.to_ascii_uppercase());
+        let locale = |lcid, currency, placement, date_order, date_separator| Self {
+            lcid,
+            currency,
+            placement,
+            date_order,
+            date_separator,
+        };
+        Ok(match (language.as_str(), region.as_deref()) {
+            ("en", None | Some("US")) => locale("409", "$", Prefix, MonthDayYear, '/'),
+            ("en", Some("GB")) => locale("809", "£", Prefix, DayMonthYear, '/'),
+            ("en", Some("AU")) => locale("C09", "$", Prefix, DayMonthYear, '/'),
+            ("en", Some("IE")) => locale("1809", "€", Prefix, DayMonthYear, '/'),
+            ("de", None | Some("DE")) => locale("407", "€", SuffixSpaced, DayMonthYear, '.'),
+            ("de", Some("AT")) => locale("C07", "€", PrefixSpaced, DayMonthYear, '.'),
+            ("de", Some("CH")) => locale("807", "CHF", PrefixSpaced, DayMonthYear, '.'),
+            ("fr", None | Some("FR")) => locale("40C", "€", SuffixSpaced, DayMonthYear, '/'),
+            ("fr", Some("CH")) => locale("100C", "CHF", PrefixSpaced, DayMonthYear, '.'),
+            ("es", None | Some("ES")) => locale("C0A", "€", SuffixSpaced, DayMonthYear, '/'),
+            ("it", None | Some("IT")) => locale("410", "€", SuffixSpaced, DayMonthYear, '/'),
+            ("nl", None | Some("NL")) => locale("413", "€", PrefixSpaced, DayMonthYear, '-'),
+            ("pt", None | Some("BR")) => locale("416", "R$", PrefixSpaced, DayMonthYear, '/'),
+            ("pt", Some("PT")) => locale("816", "€", SuffixSpaced, DayMonthYear, '/'),
+            ("sv", None | Some("SE")) => locale("41D", "kr", SuffixSpaced, YearMonthDay, '-'),
+            ("da", None | Some("DK")) => locale("406", "kr.", SuffixSpaced, DayMonthYear, '-'),
+            ("nb" | "no", None | Some("NO")) => {
+                locale("414", "kr", PrefixSpaced, DayMonthYear, '.')
+            }
+            ("pl", None | Some("PL")) => locale("415", "zł", SuffixSpaced, DayMonthYear, '.'),
+            ("ja", None | Some("JP")) => locale("411", "¥", Prefix, YearMonthDay, '/'),
+            ("zh", None | Some("CN")) => locale("804", "¥", Prefix, YearMonthDay, '/'),
+            _ => anyhow::bail!(
+                "unsupported locale '{tag}' for number formats (expected one of en-US, en-GB, en-AU, en-IE, de-DE, de-AT, de-CH, fr-FR, fr-CH, es-ES, it-IT, nl-NL, pt-BR, pt-PT, sv-SE, da-DK, nb-NO, pl-PL, ja-JP, zh-CN)"
+            ),
+        })
+    }
+}
+
+/// One lexical piece of a format code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum FormatToken {
+    /// `"text"`, without the quotes
+    Quoted(String),
+    /// `\x`
+    Escaped(char),
+    /// `[...]`, without the brackets
+    Bracket(String),
+    Plain(char),
+}
+
+impl FormatToken {
+    fn push_to(&self, code: &mut String) {
+        match self {
+            Self::Quoted(text) => {
+                code.push('"');
+                code.push_str(text);
+                code.push('"');
+            }
+            Self::Escaped(ch) => {
+                code.push('\\');
+                code.push(*ch);
+            }
+            Self::Bracket(text) => {
+                code.push('[');
+                code.push_str(text);
+                code.push(']');
+            }
+            Self::Plain(ch) => code.push(*ch),
+        }
+    }
+
+    /// Literal text the token displays, if it is a literal.
+    fn literal(&self) -> Option<String> {
+        match self {
+            Self::Quoted(text) => Some(text.clone()),
+            Self::Escaped(ch) => Some(ch.to_string()),
+            Self::Plain(ch) if !ch.is_ascii_alphanumeric() && !"#?.,%*_;@/".contains(*ch) => {
+                Some(ch.to_string())
+            }
+            _ => None,
+        }
+    }
+
+    fn is_digit_placeholder(&self) -> bool {
+        matches!(self, Self::Plain('0' | '#' | '?'))
+    }
+
+    fn is_space(&self) -> bool {
+        self.literal().is_some_and(|text| text.trim().is_empty())
+    }
+}
+
+fn tokenize_format(code: &str) -> Vec<FormatToken> {
+    let mut tokens = Vec::new();
+    let mut chars = code.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' => tokens.push(FormatToken::Quoted(
+                chars.by_ref().take_while(|ch| *ch != '"').collect(),
+            )),
+            '[' => tokens.push(FormatToken::Bracket(
+                chars.by_ref().take_while(|ch| *ch != ']').collect(),
+            )),
+            '\\' => match chars.next() {
+                Some(escaped) => tokens.push(FormatToken::Escaped(escaped)),
+                None => tokens.push(FormatToken::Plain('\\')),
+            },
+            other => tokens.push(FormatToken::Plain(other)),
+        }
+    }
+    tokens
+}
+
+/// `code` rewritten from the conventions of locale `from` to those of `to`:
+/// `from`'s currency symbol becomes `to`'s and moves to `to`'s side of the
+/// number, `[$-LCID]` tags for `from` are retagged, and numeric dates in
+/// `from`'s day/month/year order are reordered with `to`'s separator.
+/// Anything else, including other currencies and ISO `yyyy-mm-dd` dates, is
+/// kept as is.
+pub fn convert_format_code(code: &str, from: &FormatLocale, to: &FormatLocale) -> String {
+    if LOCALE_ADAPTIVE_CODES.contains(&code) || code.eq_ignore_ascii_case("general") {
+        return code.to_string();
+    }
+    let tokens = tokenize_format(code);
+    let mut converted = String::with_capacity(code.len() + 8);
+    for (index, section) in tokens
+        .split(|token| *token == FormatToken::Plain(';'))
+        .enumerate()
+    {
+        if index > 0 {
+            converted.push(';');
+        }
+        let mut section = section.to_vec();
+        convert_currency(&mut section, from, to);
+        convert_date_order(&mut section, from, to);
+        for token in &section {
+            token.push_to(&mut converted);
+        }
+    }
+    converted
+}
+
+fn convert_currency(section: &mut Vec<FormatToken>, from: &FormatLocale, to: &FormatLocale) {
+    let mut currency_index = None;
+    for (index, token) in section.iter_mut().enumerate() {
+        match token {
+            FormatToken::Bracket(tag) if tag.starts_with('$') => {
+                let (symbol, lcid) = tag[1..].split_once('-').unwrap_or((&tag[1..], ""));
+                if symbol.is_empty() {
+                    if lcid.eq_ignore_ascii_case(from.lcid) {
+                        *tag = format!("$-{}", to.lcid);
+                    }
+                } else if symbol == from.currency {
+                    *tag = format!("${}-{}", to.currency, to.lcid);
+                    currency_index.get_or_insert(index);
+                }
+            }
+            _ if token.literal().as_deref() == Some(from.currency) => {
+                *token = if to.currency == "$" {
+                    FormatToken::Plain('$')
+                } else {
+                    FormatToken::Quoted(to.currency.to_string())
+                };
+                currency_index.get_or_insert(index);
+            }
+            _ => {}
+        }
+    }
+    // Multi-character symbols written unquoted, such as `kr`, are not
+    // recognised; neither are accounting layouts that pad with `*`.
+    let Some(currency_index) = currency_index else {
+        return;
+    };
+    if section.contains(&FormatToken::Plain('*')) {
+        return;
+    }
+    let Some(first_digit) = section.iter().position(FormatToken::is_digit_placeholder) else {
+        return;
+    };
+    let is_prefix = currency_index < first_digit;
+    let wants_prefix = to.placement != CurrencyPlacement::SuffixSpaced;
+    if is_prefix == wants_prefix {
+        return;
+    }
+
+    let symbol = section.remove(currency_index);
+    if is_prefix {
+        while currency_index < section.len() && section[currency_index].is_space() {
+            section.remove(currency_index);
+        }
+    } else {
+        while currency_index > 0 && section[currency_index - 1].is_space() {
+            section.remove(currency_index - 1);
+        }
+    }
+    match to.placement {
+        CurrencyPlacement::SuffixSpaced => {
+            let last_digit = section
+                .iter()
+                .rposition(FormatToken::is_digit_placeholder)
+                .unwrap_or(section.len() - 1);
+            section.splice(
+                last_digit + 1..last_digit + 1,
+                [FormatToken::Escaped(' '), symbol],
+            );
+        }
+        CurrencyPlacement::PrefixSpaced | CurrencyPlacement::Prefix => {
+            let first_digit = section
+                .iter()
+                .position(FormatToken::is_digit_placeholder)
+                .unwrap_or(0);
+            let mut inserted = vec![symbol];
+            if to.placement == CurrencyPlacement::PrefixSpaced {
+                inserted.push(FormatToken::Escaped(' '));
+            }
+            section.splice(first_digit..first_digit, inserted);
+        }
+    }
+}
+
+fn convert_date_order(section: &mut Vec<FormatToken>, from: &FormatLocale, to: &FormatLocale) {
+    // Runs of one date letter, as (start, end, letter).
+    let mut runs: Vec<(usize, usize, char)> = Vec::new();
+    let mut index = 0;
+    while index < section.len() {
+        if let FormatToken::Plain(ch) = section[index] {
+            let letter = ch.to_ascii_lowercase();
+            if matches!(letter, 'd' | 'm' | 'y') {
+                let start = index;
+                while index < section.len()
+                    && matches!(section[index], FormatToken::Plain(next) if next.to_ascii_lowercase() == letter)
+                {
+                    index += 1;
+                }
+                runs.push((start, index, letter));
+                continue;
+            }
+        }
+        index += 1;
+    }
+
+    for window in runs.windows(3) {
+        let [
+            (first_start, first_end, a),
+            (second_start, second_end, b),
+            (third_start, third_end, c),
+        ] = [window[0], window[1], window[2]];
+        let separator = |start: usize, end: usize| -> Option<char> {
+            if end != start + 1 {
+                return None;
+            }
+            let text = section[start].literal().or_else(|| match section[start] {
+                FormatToken::Plain(ch @ ('.' | '/')) => Some(ch.to_string()),
+                _ => None,
+            })?;
+            let mut chars = text.chars();
+            let ch = chars.next()?;
+            (chars.next().is_none() && matches!(ch, '/' | '.' | '-')).then_some(ch)
+        };
+        let (Some(first_separator), Some(second_separator)) = (
+            separator(first_end, second_start),
+            separator(second_end, third_start),
+        ) else {
+            continue;
+        };
+        let numeric = |start: usize, end: usize, letter: char| match letter {
+            'y' => matches!(end - start, 2 | 4),
+            _ => end - start <= 2,
+        };
+        if first_separator != second_separator
+            || !numeric(first_start, first_end, a)
+            || !numeric(second_start, second_end, b)
+            || !numeric(third_start, third_end, c)
+        {
+            continue;
+        }
+        let order = match [a, b, c] {
+            ['d', 'm', 'y'] => DateOrder::DayMonthYear,
+            ['m', 'd', 'y'] => DateOrder::MonthDayYear,
+            ['y', 'm', 'd'] => DateOrder::YearMonthDay,
+            _ => continue,
+        };
+        // Year-first dates read the same everywhere (ISO), so they stay.
+        if order != from.date_order || order == DateOrder::YearMonthDay {
+            continue;
+        }
+
+        let run_tokens = |start: usize, end: usize| section[start..end].to_vec();
+        let mut by_letter: Vec<(char, Vec<FormatToken>)> = vec![
+            (a, run_tokens(first_start, first_end)),
+            (b, run_tokens(second_start, second_end)),
+            (c, run_tokens(third_start, third_end)),
+        ];
+        let target_letters = match to.date_order {
+            DateOrder::DayMonthYear => ['d', 'm', 'y'],
+            DateOrder::MonthDayYear => ['m', 'd', 'y'],
+            DateOrder::YearMonthDay => ['y', 'm', 'd'],
+        };
+        let separator_token = FormatToken::Plain(to.date_separator);
+        let mut replacement = Vec::new();
+        for (position, letter) in target_letters.iter().enumerate() {
+            if position > 0 {
+                replacement.push(separator_token.clone());
+            }
+            let slot = by_letter
+                .iter()
+                .position(|(run_letter, _)| run_letter == letter)
+                .expect("one run per date letter");
+            replacement.extend(by_letter.remove(slot).1);
+        }
+        section.splice(first_start..third_end, replacement);
+        // One date per section; a second would be rare and the indices moved.
+        return;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert(code: &str, from: &str, to: &str) -> String {
+        convert_format_code(
+            code,
+            &FormatLocale::parse(from).expect("from"),
+            &FormatLocale::parse(to).expect("to"),
+        )
+    }
+
+    #[test]
+    fn currency_symbols_swap_and_move_to_the_target_side() {
+        assert_eq!(convert("$#,##0.00", "en-US", "de-DE"), r#"#,##0.00\ "€""#);
+        assert_eq!(
+            convert(r#"#,##0.00\ "€";[Red]\-#,##0.00\ "€""#, "de-DE", "en-GB"),
+            r##""£"#,##0.00;[Red]\-"£"#,##0.00"##
+        );
+        assert_eq!(
+            convert("[$€-407] #,##0.00", "de-DE", "pt-BR"),
+            "[$R$-416] #,##0.00"
+        );
+        assert_eq!(convert("#,##0.00", "en-US", "de-DE"), "#,##0.00");
+        // Another currency and accounting padding keep their layout.
+        assert_eq!(convert(r##""£"#,##0"##, "en-US", "de-DE"), r##""£"#,##0"##);
+        assert_eq!(
+            convert(r#"_-* #,##0.00\ "€"_-"#, "de-DE", "en-US"),
+            r#"_-* #,##0.00\ $_-"#
+        );
+    }
+
+    #[test]
+    fn numeric_dates_reorder_and_locale_tags_follow() {
+        assert_eq!(convert("m/d/yyyy", "en-US", "de-DE"), "d.m.yyyy");
+        assert_eq!(convert("mm/dd/yy h:mm", "en-US", "en-GB"), "dd/mm/yy h:mm");
+        assert_eq!(
+            convert("[$-409]mmmm d, yyyy", "en-US", "fr-FR"),
+            "[$-40C]mmmm d, yyyy"
+        );
+        assert_eq!(convert("yyyy-mm-dd", "en-US", "de-DE"), "yyyy-mm-dd");
+        assert_eq!(convert("mm-dd-yy", "en-US", "de-DE"), "mm-dd-yy");
+        assert_eq!(convert("dd.mm.yyyy", "de-DE", "sv-SE"), "yyyy-mm-dd");
+    }
+
+    #[test]
+    fn unknown_locales_are_rejected() {
+        assert!(FormatLocale::parse("xx-YY").is_err());
+    }
+}
@@ -0,0 +1,454 @@
+//! Object storage for fork checkpoints and `--snapshot-dir` snapshots
+//! (`object-store` feature).
+//!
+//! A store is addressed by URL:
+//!
+//! - `s3://bucket/prefix` keeps objects in S3, signed with SigV4 from
+//!   `AWS_ACCESS_KEY_ID`/`AWS_SECRET_ACCESS_KEY` (and `AWS_SESSION_TOKEN`) in
+//!   `AWS_REGION` (default `us-east-1`). `AWS_ENDPOINT_URL_S3` or
+//!   `AWS_ENDPOINT_URL` points it at an S3-compatible service instead.
+//! - `gs://bucket/prefix` keeps objects in Google Cloud Storage through its
+//!   S3-compatible XML API, signed with an HMAC key from
+//!   `GCS_HMAC_ACCESS_KEY_ID`/`GCS_HMAC_SECRET`.
+//!
+//! Requests are plain HTTPS, so no cloud SDK is linked in.
+
+use crate::repository::remote::{
+    AwsCredentials, EMPTY_PAYLOAD_SHA256, env_value, s3_object_url, sign_s3_request, uri_encode,
+};
+use crate::utils::hash_bytes_sha256_hex;
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::Utc;
+use std::time::Duration;
+
+pub const GCS_HMAC_ACCESS_KEY_ENV: &str = "GCS_HMAC_ACCESS_KEY_ID";
+pub const GCS_HMAC_SECRET_ENV: &str = "GCS_HMAC_SECRET";
+
+const GCS_ENDPOINT: &str = "https://storage.googleapis.com";
+const OBJECT_REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+
+pub struct ObjectStore {
+    url: String,
+    bucket: String,
+    prefix: String,
+    endpoint: Option<String>,
+    region: String,
+    credentials: AwsCredentials,
+}
+
+impl std::fmt::Debug for ObjectStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ObjectStore")
+            .field("url", &self.url)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ObjectStore {
+    /// Parse an `s3://` or `gs://` URL and load its credentials from the
+    /// environment.
+    pub fn from_url(url: &str) -> Result<Self> {
+        let url = url.trim().trim_end_matches('/');
+        let (scheme, location) = url
+            .split_once("://")
+            .ok_or_else(|| anyhow!("object store URL '{url}' must start with s3:// or gs://"))?;
+        let (bucket, prefix) = location.split_once('/').unwrap_or((location, ""));
+        if bucket.is_empty() {
+            bail!("object store URL '{url}' has no bucket");
+        }
+
+        let (endpoint, region, credentials) = match scheme.to_ascii_lowercase().as_str() {
+            "s3" => (
+                env_value(&["AWS_ENDPOINT_URL_S3", "AWS_ENDPOINT_URL"]),
+                env_value(&["AWS_REGION", "AWS_DEFAULT_REGION"])
+                    .unwrap_or_else(|| "us-east-1".to_string()),
+                AwsCredentials::from_env(
+                    "AWS_ACCESS_KEY_ID",
+                    "AWS_SECRET_ACCESS_KEY",
+                    Some("AWS_SESSION_TOKEN"),
+                )
+                .ok_or_else(|| {
+                    anyhow!("{url} needs AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY")
+                })?,
+            ),
+            "gs" => (
+                Some(GCS_ENDPOINT.to_string()),
+                "auto".to_string(),
+                AwsCredentials::from_env(GCS_HMAC_ACCESS_KEY_ENV, GCS_HMAC_SECRET_ENV, None)
+                    .ok_or_else(|| {
+                        anyhow!("{url} needs {GCS_HMAC_ACCESS_KEY_ENV} and {GCS_HMAC_SECRET_ENV}")
+                    })?,
+            ),
+            other => bail!("unsupported object store scheme '{other}://' (use s3:// or gs://)"),
+        };
+
+        Ok(Self {
+            url: url.to_string(),
+            bucket: bucket.to_string(),
+            prefix: prefix.trim_matches('/').to_string(),
+            endpoint,
+            region,
+            credentials,
+        })
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// `key` as a full `s3://`/`gs://` URL, for messages.
+    pub fn display_key(&self, key: &str) -> String {
+        format!("{}/{key}", self.url)
+    }
+
+    pub fn put(&self, key: &str, body: Vec<u8>) -> Result<()> {
+        self.send(reqwest::Method::PUT, key, Some(body)).map(|_| ())
+    }
+
+    pub fn get(&self, key: &str) -> Result<Vec<u8>> {
+        self.send(reqwest::Method::GET, key, None)
+    }
+
+    /// Delete `key`; deleting a missing object succeeds.
+    pub fn delete(&self, key: &str) -> Result<()> {
+        self.send(reqwest::Method::DELETE, key, None).map(|_| ())
+    }
+
+    /// Keys under `prefix`, relative to the store's own prefix, following
+    /// continuation tokens until the listing is complete.
+    pub fn list(&self, prefix: &str) -> Result<Vec<String>> {
+        let full_prefix = self.full_key(prefix);
+        let mut keys = Vec::new();
+        let mut continuation: Option<String> = None;
+        loop {
+            let mut target =
+                s3_object_url(self.endpoint.as_deref(), &self.region, &self.bucket, "")?;
+            // SigV4 signs the query as sent, so parameters go in name order.
+            let mut query = Vec::new();
+            if let Some(token) = &continuation {
+                query.push(format!("continuation-token={}", uri_encode(token)));
+            }
+            query.push("list-type=2".to_string());
+            query.push(format!("prefix={}", uri_encode(&full_prefix)));
+            target.set_query(Some(&query.join("&")));
+
+            let body =
+                self.send_to(reqwest::Method::GET, target, None, self.display_key(prefix))?;
+            let body = String::from_utf8_lossy(&body);
+            for key in element_texts(&body, "Key")? {
+                let relative = if self.prefix.is_empty() {
+                    Some(key.as_str())
+                } else {
+                    key.strip_prefix(self.prefix.as_str())
+                        .and_then(|rest| rest.strip_prefix('/'))
+                };
+                if let Some(relative) = relative {
+                    keys.push(relative.to_string());
+                }
+            }
+            continuation = element_texts(&body, "NextContinuationToken")?
+                .into_iter()
+                .next();
+            if continuation.is_none() {
+                return Ok(keys);
+            }
+        }
+    }
+
+    fn full_key(&self, key: &str) -> String {
+        if self.prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}/{key}", self.prefix)
+        }
+    }
+
+    fn send(&self, method: reqwest::Method, key: &str, body: Option<Vec<u8>>) -> Result<Vec<u8>> {
+        let target = s3_object_url(
+            self.endpoint.as_deref(),
+            &self.region,
+            &self.bucket,
+            &self.full_key(key),
+        )?;
+        self.send_to(method, target, body, self.display_key(key))
+    }
+
+    fn send_to(
+        &self,
+        method: reqwest::Method,
+        target: reqwest::Url,
+        body: Option<Vec<u8>>,
+        display: String,
+    ) -> Result<Vec<u8>> {
+        let payload_sha256 = match &body {
+            Some(body) => hash_bytes_sha256_hex(body),
+            None => EMPTY_PAYLOAD_SHA256.to_string(),
+        };
+        let headers = sign_s3_request(
+            method.as_str(),
+            &target,
+            &payload_sha256,
+            &self.region,
+            &self.credentials,
+            Utc::now(),
+        );
+
+        // The caller may already be inside a runtime, so the request gets its
+        // own thread and runtime.
+        std::thread::spawn(move || -> Result<Vec<u8>> {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()?;
+            runtime.block_on(async move {
+                let client = reqwest::Client::builder()
+                    .timeout(OBJECT_REQUEST_TIMEOUT)
+                    .build()?;
+                let mut request = client.request(method.clone(), target);
+                for (name, value) in headers {
+                    request = request.header(name, value);
+                }
+                if let Some(body) = body {
+                    request = request.body(body);
+                }
+                let response = request
+                    .send()
+                    .await
+                    .with_context(|| format!("{method} {display} failed"))?;
+                let status = response.status();
+                if method == reqwest::Method::DELETE && status == reqwest::StatusCode::NOT_FOUND {
+                    return Ok(Vec::new());
+                }
+                if !status.is_success() {
+                    bail!("{method} {display} returned HTTP {status}");
+                }
+                let bytes = response
+                    .bytes()
+                    .await
+                    .with_context(|| format!("failed to read body of {display}"))?;
+                Ok(bytes.to_vec())
+            })
+        })
+        .join()
+        .map_err(|_| anyhow!("object store thread panicked"))?
+    }
+}
+
+/// Unescaped text of every `<tag>…</tag>` element in a listing response.
+fn element_texts(xml: &str, tag: &str) -> Result<Vec<String>> {
+    let open = format!("<{tag}>");
+    let close = format!("</{tag}>");
+    let mut texts = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        let after = &rest[start + open.len()..];
+        let Some(end) = after.find(&close) else {
+            break;
+        };
+        let text = quick_xml::escape::unescape(&after[..end])
+            .with_context(|| format!("invalid <{tag}> in object listing"))?;
+        texts.push(text.into_owned());
+        rest = &after[end + close.len()..];
+    }
+    Ok(texts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::io::{Read, Write};
+    use std::sync::mpsc;
+
+    /// A minimal S3 stand-in: stores PUT bodies by path and reports each
+    /// request's method, path and lowercased headers.
+    fn serve_bucket(requests: usize) -> (String, mpsc::Receiver<(String, String, String)>) {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind listener");
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut objects: HashMap<String, Vec<u8>> = HashMap::new();
+            for stream in listener.incoming().take(requests) {
+                let mut stream = stream.expect("accept");
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                let header_end = loop {
+                    if let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                        break end + 4;
+                    }
+                    let read = stream.read(&mut buf).expect("read request");
+                    assert!(read > 0, "connection closed before headers");
+                    request.extend_from_slice(&buf[..read]);
+                };
+                let head = String::from_utf8_lossy(&request[..header_end]).to_string();
+                let mut request_line = head.lines().next().unwrap_or_default().split(' ');
+                let method = request_line.next().unwrap_or_default().to_string();
+                let path = request_line.next().unwrap_or_default().to_string();
+                let headers = head.to_ascii_lowercase();
+                let length: usize = headers
+                    .lines()
+                    .find_map(|line| line.strip_prefix("content-length:"))
+                    .map(|value| value.trim().parse().expect("content length"))
+                    .unwrap_or(0);
+                while request.len() < header_end + length {
+                    let read = stream.read(&mut buf).expect("read body");
+                    assert!(read > 0, "connection closed before body");
+                    request.extend_from_slice(&buf[..read]);
+                }
+                let body = request[header_end..header_end + length].to_vec();
+                let (status, payload) = match method.as_str() {
+                    "GET" if path.contains("?list-type=2") => {
+                        let (bucket, query) = path.split_once('?').unwrap_or_default();
+                        let prefix = query
+                            .split('&')
+                            .find_map(|param| param.strip_prefix("prefix="))
+                            .unwrap_or_default()
+                            .replace("%2F", "/");
+                        let listed = format!("{bucket}{prefix}");
+                        let contents: String = objects
+                            .keys()
+                            .filter(|key| key.starts_with(&listed))
+                            .map(|key| {
+                                format!("<Contents><Key>{}</Key></Contents>", &key[bucket.len()..])
+                            })
+                            .collect();
+                        (
+                            "200 OK",
+                            format!("<ListBucketResult>{contents}<IsTruncated>false</IsTruncated></ListBucketResult>")
+                                .into_bytes(),
+                        )
+                    }
+                    "PUT" => {
+                        objects.insert(path.clone(), body);
+                        ("200 OK", Vec::new())
+                    }
+                    "GET" => match objects.get(&path) {
+                        Some(bytes) => ("200 OK", bytes.clone()),
+                        None => ("404 Not Found", Vec::new()),
+                    },
+                    "DELETE" => {
+                        objects.remove(&path);
+                        ("204 No Content", Vec::new())
+                    }
+                    _ => ("405 Method Not Allowed", Vec::new()),
+                };
+                stream
+                    .write_all(
+                        format!(
+                            "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                            payload.len()
+                        )
+                        .as_bytes(),
+                    )
+                    .and_then(|()| stream.write_all(&payload))
+                    .expect("write response");
+                let _ = sender.send((method, path, headers));
+            }
+        });
+        (endpoint, receiver)
+    }
+
+    #[test]
+    fn put_get_and_delete_signed_objects() {
+        let (endpoint, requests) = serve_bucket(4);
+        let store = ObjectStore {
+            url: "s3://models/checkpoints".to_string(),
+            bucket: "models".to_string(),
+            prefix: "checkpoints".to_string(),
+            endpoint: Some(endpoint),
+            region: "us-east-1".to_string(),
+            credentials: AwsCredentials {
+                access_key_id: "AKIDEXAMPLE".to_string(),
+                secret_access_key: "secret".to_string(),
+                session_token: None,
+            },
+        };
+
+        store
+            .put("owner-1/fork-1/cp 1.xlsx", b"workbook".to_vec())
+            .expect("put");
+        let (method, path, headers) = requests.recv().expect("put request");
+        assert_eq!(method, "PUT");
+        assert_eq!(path, "/models/checkpoints/owner-1/fork-1/cp%201.xlsx");
+        assert!(
+            headers.contains("authorization: aws4-hmac-sha256 credential=akidexample/"),
+            "{headers}"
+        );
+        assert!(
+            headers.contains(&format!(
+                "x-amz-content-sha256: {}",
+                hash_bytes_sha256_hex(b"workbook")
+            )),
+            "{headers}"
+        );
+
+        assert_eq!(
+            store.get("owner-1/fork-1/cp 1.xlsx").expect("get"),
+            b"workbook"
+        );
+        store.delete("owner-1/fork-1/cp 1.xlsx").expect("delete");
+        let err = store
+            .get("owner-1/fork-1/cp 1.xlsx")
+            .expect_err("deleted object");
+        assert!(err.to_string().contains("404"), "{err}");
+        assert!(
+            err.to_string()
+                .contains("s3://models/checkpoints/owner-1/fork-1/cp 1.xlsx"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn snapshots_round_trip_through_a_bucket() {
+        use crate::snapshots::{SnapshotStore, list_snapshots, load_snapshot, take_snapshot};
+
+        // Two PUTs for the snapshot, a listing plus a record GET, then the
+        // record and workbook GETs of the load.
+        let (endpoint, requests) = serve_bucket(6);
+        let store = SnapshotStore::Object(ObjectStore {
+            url: "s3://models/snapshots".to_string(),
+            bucket: "models".to_string(),
+            prefix: "snapshots".to_string(),
+            endpoint: Some(endpoint),
+            region: "us-east-1".to_string(),
+            credentials: AwsCredentials {
+                access_key_id: "AKIDEXAMPLE".to_string(),
+                secret_access_key: "secret".to_string(),
+                session_token: None,
+            },
+        });
+        let dir = tempfile::tempdir().expect("tempdir");
+        let source = dir.path().join("model.xlsx");
+        std::fs::write(&source, b"workbook bytes").expect("write source");
+
+        let record = take_snapshot(&store, &source, "transform-batch").expect("take");
+        let (_, workbook_path, _) = requests.recv().expect("workbook put");
+        let (_, record_path, _) = requests.recv().expect("record put");
+        assert_eq!(
+            workbook_path,
+            format!("/models/snapshots/{}", record.file_name)
+        );
+        assert_eq!(record_path, format!("/models/snapshots/{}.json", record.id));
+
+        let listed = list_snapshots(&store).expect("list");
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, record.id);
+        let (_, list_path, _) = requests.recv().expect("list request");
+        assert_eq!(list_path, "/models/?list-type=2&prefix=snapshots%2F");
+
+        let (loaded, copy) = load_snapshot(&store, &record.id).expect("load");
+        assert_eq!(loaded.sha256, record.sha256);
+        assert_eq!(std::fs::read(copy.path()).expect("copy"), b"workbook bytes");
+        assert_eq!(
+            store.describe(&loaded),
+            format!("s3://models/snapshots/{}", record.file_name)
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_schemes_and_missing_buckets() {
+        let err = ObjectStore::from_url("ftp://bucket/prefix").expect_err("scheme");
+        assert!(err.to_string().contains("s3:// or gs://"), "{err}");
+        let err = ObjectStore::from_url("s3:///prefix").expect_err("bucket");
+        assert!(err.to_string().contains("no bucket"), "{err}");
+    }
+}
@@ -0,0 +1,81 @@
+//! Optional on-disk cache of workbook structure, keyed by file content.
+//!
+//! Entries are named after the SHA-256 of the workbook bytes, the same hash
+//! revision ids are derived from, so an unchanged file maps to the same entry
+//! wherever it lives and an edited one never sees stale data. An entry holds what the structural reads derive from a
+//! full parse (sheet list, sheet summaries, overviews with detected
+//! regions); a workbook with a cache entry is only parsed when a call needs
+//! its cells.
+
+use crate::model::{SheetOverviewResponse, SheetSummary};
+use crate::utils::hash_file_sha256_hex;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Enables the cache for CLI commands when set to a directory.
+pub const PARSE_CACHE_DIR_ENV: &str = "SPREADSHEET_MCP_PARSE_CACHE_DIR";
+
+/// Bumped whenever [`WorkbookStructure`] or the analysis feeding it changes,
+/// so older entries are ignored rather than misread.
+const PARSE_CACHE_FORMAT: u32 = 3;
+
+/// Structure extracted from one workbook revision.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorkbookStructure {
+    pub sheet_names: Vec<String>,
+    pub defined_names: usize,
+    pub tables: usize,
+    /// Sheet summaries including bounds, once listed.
+    #[serde(default)]
+    pub summaries: Option<Vec<SheetSummary>>,
+    /// Sheet overviews by sheet name, as produced before any per-call
+    /// truncation.
+    #[serde(default)]
+    pub overviews: BTreeMap<String, SheetOverviewResponse>,
+}
+
+/// Directory of [`WorkbookStructure`] entries.
+#[derive(Debug, Clone)]
+pub struct ParseCache {
+    dir: PathBuf,
+}
+
+impl ParseCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Content key for the workbook file at `path`.
+    pub fn key_for_file(path: &Path) -> Result<String> {
+        hash_file_sha256_hex(path).with_context(|| format!("failed to hash {:?}", path))
+    }
+
+    /// Stored structure for `key`; unreadable or outdated entries are misses.
+    pub fn load(&self, key: &str) -> Option<WorkbookStructure> {
+        let contents = fs::read(self.entry_path(key)).ok()?;
+        serde_json::from_slice(&contents).ok()
+    }
+
+    /// Write (or replace) the entry for `key`.
+    pub fn store(&self, key: &str, structure: &WorkbookStructure) -> Result<()> {
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("failed to create parse cache dir {:?}", self.dir))?;
+        let path = self.entry_path(key);
+        let staging = path.with_extension(format!("{}.tmp", std::process::id()));
+        fs::write(&staging, serde_json::to_vec(structure)?)
+            .with_context(|| format!("failed to write {:?}", staging))?;
+        fs::rename(&staging, &path).with_context(|| format!("failed to write {:?}", path))?;
+        Ok(())
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.v{PARSE_CACHE_FORMAT}.json"))
+    }
+}
@@ -1,15 +1,38 @@
+use crate::repository::alias::resolve_alias_path;
+use crate::repository::is_remote_url;
 use anyhow::{Result, bail};
 use std::fs;
 use std::path::{Path, PathBuf};
 
 pub fn normalize_existing_file(path: &Path) -> Result<PathBuf> {
-    let absolute = if path.is_absolute() {
+    if path.to_str().is_some_and(is_remote_url) {
+        bail!(
+            "'{}' is a remote URL; only read commands accept remote workbooks",
+            path.display()
+        );
+    }
+    let cwd = std::env::current_dir()?;
+    let mut absolute = if path.is_absolute() {
         path.to_path_buf()
     } else {
-        std::env::current_dir()?.join(path)
+        cwd.join(path)
     };
     if !absolute.exists() {
-        bail!("file '{}' does not exist", absolute.display());
+        // A name that is not a file may be an alias from the workspace alias
+        // file in the current directory.
+        let aliased = match path.to_str() {
+            Some(name) => resolve_alias_path(&cwd, name)?,
+            None => None,
+        };
+        match aliased {
+            Some(target) if target.is_file() => absolute = target,
+            Some(target) => bail!(
+                "alias '{}' points to '{}', which does not exist",
+                path.display(),
+                target.display()
+            ),
+            None => bail!("file '{}' does not exist", absolute.display()),
+        }
     }
     if !absolute.is_file() {
         bail!("path '{}' is not a file", absolute.display());
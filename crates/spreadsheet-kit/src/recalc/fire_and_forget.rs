@@ -1,5 +1,6 @@
 use super::RecalcConfig;
 use super::executor::{RecalcExecutor, RecalcResult};
+use crate::errors::RecalcTimeoutError;
 use anyhow::{Result, anyhow};
 use async_trait::async_trait;
 use std::path::{Path, PathBuf};
@@ -72,10 +73,20 @@ impl RecalcExecutor for FireAndForgetExecutor {
             .env("XDG_CONFIG_HOME", "/tmp/.config")
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
+            .kill_on_drop(true)
             .output()
         })
         .await
-        .map_err(|_| anyhow!("soffice timed out after {:?}", self.timeout))
+        .map_err(|_| {
+            anyhow::Error::new(RecalcTimeoutError {
+                backend: "libreoffice".to_string(),
+                timeout_ms: self.timeout.as_millis() as u64,
+                elapsed_ms: start.elapsed().as_millis() as u64,
+                phase: "soffice".to_string(),
+                completed_phases: Vec::new(),
+                formula_cells: None,
+            })
+        })
         .and_then(|res| res.map_err(|e| anyhow!("failed to spawn soffice: {}", e)));
 
         let output = output_result?;
@@ -1,11 +1,13 @@
 use super::RecalcResult;
+use crate::errors::RecalcTimeoutError;
 use crate::recalc::RecalcBackend;
 use crate::utils::column_number_to_name;
+use crate::write::PreservedPackage;
 use anyhow::{Result, anyhow};
 use async_trait::async_trait;
 use formualizer::common::PackedSheetCell;
 use formualizer::eval::engine::ingest::EngineLoadStream;
-use formualizer::eval::engine::{Engine, EvalConfig, FormulaParsePolicy};
+use formualizer::eval::engine::{DateSystem, Engine, EvalConfig, FormulaParsePolicy};
 use formualizer::workbook::workbook::WBResolver;
 use formualizer::workbook::{
     FormulaCacheUpdate, LiteralValue, SpreadsheetReader, SpreadsheetWriter, UmyaAdapter,
@@ -51,8 +53,39 @@ impl RecalcBackend for FormualizerBackend {
     }
 }
 
+impl FormualizerBackend {
+    /// Recalculate only `targets` (1-based `(sheet, row, col)` formula cells)
+    /// and write their cached values back, leaving every other cache as-is.
+    /// The engine still evaluates whatever precedents the targets need.
+    pub async fn recalculate_cells(
+        &self,
+        fork_work_path: &Path,
+        targets: Vec<(String, u32, u32)>,
+    ) -> Result<RecalcResult> {
+        let path = fork_work_path.to_path_buf();
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        std::thread::Builder::new()
+            .name("formualizer-recalc".into())
+            .stack_size(32 * 1024 * 1024)
+            .spawn(move || {
+                let _ = tx.send(recalc_cells_sync(&path, &targets));
+            })
+            .map_err(|e| anyhow!("failed to spawn recalc thread: {e}"))?;
+        rx.await.map_err(|_| anyhow!("recalc thread panicked"))?
+    }
+}
+
 type FormualizerEngine = Engine<WBResolver>;
 
+/// Date functions and cached date results follow the workbook's date system.
+fn engine_date_system(date1904: bool) -> DateSystem {
+    if date1904 {
+        DateSystem::Excel1904
+    } else {
+        DateSystem::Excel1900
+    }
+}
+
 fn recalc_sync(path: &Path, timeout_ms: Option<u64>) -> Result<RecalcResult> {
     let start = Instant::now();
 
@@ -63,6 +96,8 @@ fn recalc_sync(path: &Path, timeout_ms: Option<u64>) -> Result<RecalcResult> {
 
     let formula_cells = adapter.formula_cells();
     let formula_cells_len = formula_cells.len();
+    // The umya writer behind `save_as_path` drops what this captures.
+    let preserved = PreservedPackage::capture(path);
 
     // Fast recalc path by default for agentic/stateless workflows:
     // - defer graph building during ingest (dramatically reduces load time)
@@ -70,6 +105,7 @@ fn recalc_sync(path: &Path, timeout_ms: Option<u64>) -> Result<RecalcResult> {
     let eval_config = EvalConfig {
         defer_graph_building: true,
         formula_parse_policy: FormulaParsePolicy::CoerceToError,
+        date_system: engine_date_system(preserved.date1904),
         ..Default::default()
     };
 
@@ -83,8 +119,7 @@ fn recalc_sync(path: &Path, timeout_ms: Option<u64>) -> Result<RecalcResult> {
 
     let eval_start = Instant::now();
     let (cells_evaluated, cycle_errors, changed_cells) =
-        evaluate_with_optional_timeout(&mut engine, timeout_ms)
-            .map_err(|e| anyhow!("formualizer evaluate_all failed: {e}"))?;
+        evaluate_with_optional_timeout(&mut engine, timeout_ms, formula_cells_len, start)?;
     let evaluate_ms = eval_start.elapsed().as_millis() as u64;
 
     let mut eval_errors = Vec::new();
@@ -150,6 +185,7 @@ fn recalc_sync(path: &Path, timeout_ms: Option<u64>) -> Result<RecalcResult> {
         adapter
             .save_as_path(path)
             .map_err(|e| anyhow!("failed to save recalculated workbook {:?}: {e}", path))?;
+        preserved.restore(path)?;
         save_as_path_ms = save_start.elapsed().as_millis() as u64;
     }
 
@@ -182,12 +218,93 @@ fn recalc_sync(path: &Path, timeout_ms: Option<u64>) -> Result<RecalcResult> {
     })
 }
 
+fn recalc_cells_sync(path: &Path, targets: &[(String, u32, u32)]) -> Result<RecalcResult> {
+    let start = Instant::now();
+
+    let mut adapter = UmyaAdapter::open_path(path)
+        .map_err(|e| anyhow!("failed to open workbook adapter {:?}: {e}", path))?;
+
+    // The umya writer behind `save_as_path` drops what this captures.
+    let preserved = PreservedPackage::capture(path);
+    // Targeted evaluation walks the dependency graph on demand, so the graph
+    // is built during ingest rather than deferred.
+    let eval_config = EvalConfig {
+        formula_parse_policy: FormulaParsePolicy::CoerceToError,
+        date_system: engine_date_system(preserved.date1904),
+        ..Default::default()
+    };
+    let mut engine = FormualizerEngine::new(WBResolver::default(), eval_config);
+    adapter
+        .stream_into_engine(&mut engine)
+        .map_err(|e| anyhow!("failed to ingest workbook into formualizer engine: {e}"))?;
+
+    let refs: Vec<(&str, u32, u32)> = targets
+        .iter()
+        .map(|(sheet, row, col)| (sheet.as_str(), *row, *col))
+        .collect();
+    if !refs.is_empty() {
+        engine
+            .evaluate_cells(&refs)
+            .map_err(|e| anyhow!("formualizer evaluate_cells failed: {e}"))?;
+    }
+
+    let mut eval_errors = Vec::new();
+    let date_system = engine.config.date_system;
+    let mut cache_updates = Vec::with_capacity(targets.len());
+    for (sheet_name, row, col) in targets {
+        let value = engine
+            .get_cell_value(sheet_name, *row, *col)
+            .unwrap_or(LiteralValue::Empty);
+        if let LiteralValue::Error(err) = &value
+            && eval_errors.len() < 200
+        {
+            let addr = format!("{}{}", column_number_to_name(*col), row);
+            eval_errors.push(format!("{}!{}: {}", sheet_name, addr, err));
+        }
+        cache_updates.push(FormulaCacheUpdate {
+            sheet: sheet_name.clone(),
+            row: *row,
+            col: *col,
+            value,
+        });
+    }
+
+    if !cache_updates.is_empty() {
+        adapter
+            .write_formula_caches_batch(&cache_updates, date_system)
+            .map_err(|e| anyhow!("failed to write formula caches in batch: {e}"))?;
+        adapter
+            .save_as_path(path)
+            .map_err(|e| anyhow!("failed to save recalculated workbook {:?}: {e}", path))?;
+        preserved.restore(path)?;
+    }
+
+    Ok(RecalcResult {
+        duration_ms: start.elapsed().as_millis() as u64,
+        was_warm: true,
+        backend_name: "formualizer",
+        cells_evaluated: Some(cache_updates.len() as u64),
+        eval_errors: if eval_errors.is_empty() {
+            None
+        } else {
+            Some(eval_errors)
+        },
+    })
+}
+
+/// Runs `evaluate_all`, cancelling after `timeout_ms`. A cancelled run comes
+/// back as a [`RecalcTimeoutError`] so callers can tell it apart from an
+/// evaluation failure.
 fn evaluate_with_optional_timeout(
     engine: &mut FormualizerEngine,
     timeout_ms: Option<u64>,
+    formula_cells: usize,
+    started: Instant,
 ) -> Result<(u64, u64, Option<HashSet<PackedSheetCell>>)> {
     let Some(timeout_ms) = timeout_ms else {
-        let (eval, delta) = engine.evaluate_all_with_delta()?;
+        let (eval, delta) = engine
+            .evaluate_all_with_delta()
+            .map_err(|e| anyhow!("formualizer evaluate_all failed: {e}"))?;
         let changed = delta.changed_cells.into_iter().collect::<HashSet<_>>();
         return Ok((
             eval.computed_vertices as u64,
@@ -213,11 +330,24 @@ fn evaluate_with_optional_timeout(
         }
     });
 
-    let result = engine.evaluate_all_cancellable(cancel_flag);
+    let result = engine.evaluate_all_cancellable(cancel_flag.clone());
     done_flag.store(true, Ordering::Relaxed);
     let _ = handle.join();
 
-    let eval = result?;
+    let eval = match result {
+        Ok(eval) => eval,
+        Err(_) if cancel_flag.load(Ordering::Relaxed) => {
+            return Err(anyhow::Error::new(RecalcTimeoutError {
+                backend: "formualizer".to_string(),
+                timeout_ms,
+                elapsed_ms: started.elapsed().as_millis() as u64,
+                phase: "evaluate".to_string(),
+                completed_phases: vec!["open".to_string(), "ingest".to_string()],
+                formula_cells: Some(formula_cells as u64),
+            }));
+        }
+        Err(e) => return Err(anyhow!("formualizer evaluate_all failed: {e}")),
+    };
     Ok((
         eval.computed_vertices as u64,
         eval.cycle_errors as u64,
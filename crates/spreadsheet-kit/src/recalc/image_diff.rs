@@ -0,0 +1,197 @@
+//! Pixel comparison of two renders of the same sheet range: how many pixels
+//! changed, the regions they fall in, and an overlay that shows them on top
+//! of the newer render.
+
+use image::{Rgba, RgbaImage};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// Changed pixels are grouped into square tiles of this size, and touching
+/// tiles into one region, so a reworded cell is one region rather than one
+/// per glyph.
+const REGION_TILE_PX: u32 = 16;
+const HIGHLIGHT: [u8; 3] = [220, 38, 38];
+/// How far unchanged pixels are faded towards white in the overlay.
+const FADE: f32 = 0.7;
+
+/// A box of changed pixels, in overlay pixel coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct PixelRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug)]
+pub struct ImageDiff {
+    pub width: u32,
+    pub height: u32,
+    pub changed_pixels: u64,
+    /// Regions of changed pixels, top to bottom then left to right
+    pub regions: Vec<PixelRegion>,
+    /// The newer render faded, with changed pixels tinted red and each
+    /// region outlined
+    pub overlay: RgbaImage,
+}
+
+impl ImageDiff {
+    pub fn total_pixels(&self) -> u64 {
+        u64::from(self.width) * u64::from(self.height)
+    }
+
+    /// Share of pixels that changed, from 0 to 1.
+    pub fn changed_ratio(&self) -> f64 {
+        match self.total_pixels() {
+            0 => 0.0,
+            total => self.changed_pixels as f64 / total as f64,
+        }
+    }
+}
+
+/// Compare `before` and `after` pixel by pixel, aligned at their top-left
+/// corners. A pixel changed when any channel differs by more than
+/// `tolerance`, which absorbs anti-aliasing noise; when the images differ
+/// in size, pixels covered by only one of them count as changed.
+pub fn diff_images(before: &RgbaImage, after: &RgbaImage, tolerance: u8) -> ImageDiff {
+    let width = before.width().max(after.width());
+    let height = before.height().max(after.height());
+    let tiles_wide = width.div_ceil(REGION_TILE_PX) as usize;
+    let tiles_high = height.div_ceil(REGION_TILE_PX) as usize;
+    // Per tile, the bounds of its changed pixels.
+    let mut tiles: Vec<Option<(u32, u32, u32, u32)>> = vec![None; tiles_wide * tiles_high];
+    let mut overlay = RgbaImage::new(width, height);
+    let mut changed_pixels = 0u64;
+
+    let blank = Rgba([255, 255, 255, 255]);
+    for y in 0..height {
+        for x in 0..width {
+            let old = before.get_pixel_checked(x, y);
+            let new = after.get_pixel_checked(x, y);
+            let changed = match (old, new) {
+                (Some(old), Some(new)) => old
+                    .0
+                    .iter()
+                    .zip(new.0.iter())
+                    .any(|(a, b)| a.abs_diff(*b) > tolerance),
+                _ => true,
+            };
+            let shown = new.or(old).copied().unwrap_or(blank);
+            let pixel = if changed {
+                changed_pixels += 1;
+                let tile = &mut tiles
+                    [(y / REGION_TILE_PX) as usize * tiles_wide + (x / REGION_TILE_PX) as usize];
+                *tile = Some(match *tile {
+                    Some((x0, y0, x1, y1)) => (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+                    None => (x, y, x, y),
+                });
+                tint(shown)
+            } else {
+                fade(shown)
+            };
+            overlay.put_pixel(x, y, pixel);
+        }
+    }
+
+    let regions = group_regions(&tiles, tiles_wide, tiles_high);
+    for region in &regions {
+        outline(&mut overlay, region);
+    }
+    ImageDiff {
+        width,
+        height,
+        changed_pixels,
+        regions,
+        overlay,
+    }
+}
+
+/// Merge tiles that touch, diagonals included, into regions.
+fn group_regions(
+    tiles: &[Option<(u32, u32, u32, u32)>],
+    tiles_wide: usize,
+    tiles_high: usize,
+) -> Vec<PixelRegion> {
+    let mut seen = vec![false; tiles.len()];
+    let mut regions = Vec::new();
+    for start in 0..tiles.len() {
+        if seen[start] || tiles[start].is_none() {
+            continue;
+        }
+        seen[start] = true;
+        let mut bounds = tiles[start].expect("changed tile");
+        let mut stack = vec![start];
+        while let Some(index) = stack.pop() {
+            let (tx, ty) = ((index % tiles_wide) as i64, (index / tiles_wide) as i64);
+            for (dx, dy) in (-1..=1).flat_map(|dx| (-1..=1).map(move |dy| (dx, dy))) {
+                let (nx, ny) = (tx + dx, ty + dy);
+                if nx < 0 || ny < 0 || nx >= tiles_wide as i64 || ny >= tiles_high as i64 {
+                    continue;
+                }
+                let neighbour = ny as usize * tiles_wide + nx as usize;
+                if seen[neighbour] {
+                    continue;
+                }
+                if let Some((x0, y0, x1, y1)) = tiles[neighbour] {
+                    seen[neighbour] = true;
+                    bounds = (
+                        bounds.0.min(x0),
+                        bounds.1.min(y0),
+                        bounds.2.max(x1),
+                        bounds.3.max(y1),
+                    );
+                    stack.push(neighbour);
+                }
+            }
+        }
+        regions.push(PixelRegion {
+            x: bounds.0,
+            y: bounds.1,
+            width: bounds.2 - bounds.0 + 1,
+            height: bounds.3 - bounds.1 + 1,
+        });
+    }
+    regions.sort_by_key(|region| (region.y, region.x));
+    regions
+}
+
+fn fade(pixel: Rgba<u8>) -> Rgba<u8> {
+    let [r, g, b, _] = pixel.0;
+    let lift = |channel: u8| channel + ((255 - channel) as f32 * FADE) as u8;
+    Rgba([lift(r), lift(g), lift(b), 255])
+}
+
+fn tint(pixel: Rgba<u8>) -> Rgba<u8> {
+    let [r, g, b, _] = pixel.0;
+    let mix =
+        |channel: u8, highlight: u8| (channel as f32 * 0.4 + highlight as f32 * 0.6).round() as u8;
+    Rgba([
+        mix(r, HIGHLIGHT[0]),
+        mix(g, HIGHLIGHT[1]),
+        mix(b, HIGHLIGHT[2]),
+        255,
+    ])
+}
+
+/// Draw a two-pixel frame just outside `region`, clipped to the image.
+fn outline(image: &mut RgbaImage, region: &PixelRegion) {
+    let color = Rgba([HIGHLIGHT[0], HIGHLIGHT[1], HIGHLIGHT[2], 255]);
+    let x0 = region.x.saturating_sub(2);
+    let y0 = region.y.saturating_sub(2);
+    let x1 = (region.x + region.width + 1).min(image.width() - 1);
+    let y1 = (region.y + region.height + 1).min(image.height() - 1);
+    for x in x0..=x1 {
+        for y in [y0, y0 + 1, y1.saturating_sub(1), y1] {
+            if y < region.y || y >= region.y + region.height {
+                image.put_pixel(x, y, color);
+            }
+        }
+    }
+    for y in y0..=y1 {
+        for x in [x0, x0 + 1, x1.saturating_sub(1), x1] {
+            if x < region.x || x >= region.x + region.width {
+                image.put_pixel(x, y, color);
+            }
+        }
+    }
+}
@@ -19,6 +19,26 @@ pub fn export_screenshot_uri(
     ))
 }
 
+/// Build a LibreOffice `macro:///...` URI for `Standard.Module1.ExportPdf`.
+///
+/// Empty `sheet_name` exports every sheet; empty `print_area` keeps the
+/// workbook's own print areas. Arguments are escaped for Basic string literal
+/// context to prevent injection.
+pub fn export_pdf_uri(
+    workbook_path: &str,
+    output_path: &str,
+    sheet_name: &str,
+    print_area: &str,
+) -> Result<String> {
+    Ok(format!(
+        "macro:///Standard.Module1.ExportPdf({},{},{},{})",
+        basic_string_literal("workbook_path", workbook_path)?,
+        basic_string_literal("output_path", output_path)?,
+        basic_string_literal("sheet_name", sheet_name)?,
+        basic_string_literal("print_area", print_area)?,
+    ))
+}
+
 /// Build a LibreOffice `macro:///...` URI for `Standard.Module1.RecalculateAndSave`.
 ///
 /// Arguments are escaped for Basic string literal context to prevent injection.
@@ -7,6 +7,8 @@ mod fire_and_forget;
 #[cfg(feature = "recalc-formualizer")]
 mod formualizer_backend;
 #[cfg(feature = "recalc")]
+pub mod image_diff;
+#[cfg(feature = "recalc")]
 pub mod macro_uri;
 #[cfg(feature = "recalc-libreoffice")]
 mod pooled;
@@ -26,22 +28,78 @@ pub use fire_and_forget::FireAndForgetExecutor;
 #[cfg(feature = "recalc-formualizer")]
 pub use formualizer_backend::FormualizerBackend;
 #[cfg(feature = "recalc-libreoffice")]
+pub(crate) use screenshot::content_bounds;
+#[cfg(feature = "recalc-libreoffice")]
 pub use screenshot::{ScreenshotExecutor, ScreenshotResult};
 
+#[cfg(feature = "recalc")]
+use anyhow::{Result, anyhow};
+#[cfg(feature = "recalc")]
+use parking_lot::Mutex;
+#[cfg(feature = "recalc")]
+use std::collections::HashMap;
+#[cfg(feature = "recalc")]
+use std::path::Path;
 use std::path::PathBuf;
 #[cfg(feature = "recalc")]
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
 #[cfg(feature = "recalc")]
-use tokio::sync::Semaphore;
+use tokio::sync::{Mutex as FileMutex, OwnedMutexGuard, OwnedSemaphorePermit, Semaphore};
 
+/// Recalculation admission: at most `permits` recalculations run at once
+/// across all files, and any one file recalculates one request at a time.
+/// Requests for different files never wait on each other beyond the permit
+/// count, so results for a file are never produced by overlapping runs.
 #[cfg(feature = "recalc")]
 #[derive(Clone)]
-pub struct GlobalRecalcLock(pub Arc<Semaphore>);
+pub struct GlobalRecalcLock {
+    permits: Arc<Semaphore>,
+    files: Arc<Mutex<HashMap<PathBuf, Weak<FileMutex<()>>>>>,
+}
+
+/// Held for the duration of one recalculation.
+#[cfg(feature = "recalc")]
+pub struct RecalcPermit {
+    _file: OwnedMutexGuard<()>,
+    _permit: OwnedSemaphorePermit,
+}
 
 #[cfg(feature = "recalc")]
 impl GlobalRecalcLock {
     pub fn new(permits: usize) -> Self {
-        Self(Arc::new(Semaphore::new(permits)))
+        Self {
+            permits: Arc::new(Semaphore::new(permits)),
+            files: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Wait until `path` is not being recalculated and a global permit is free.
+    pub async fn acquire(&self, path: &Path) -> Result<RecalcPermit> {
+        // The file lock comes first so a request queued behind the same file
+        // does not sit on a permit another file could use.
+        let file = self.file_lock(path).lock_owned().await;
+        let permit = self
+            .permits
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|e| anyhow!("failed to acquire recalc permit: {}", e))?;
+        Ok(RecalcPermit {
+            _file: file,
+            _permit: permit,
+        })
+    }
+
+    fn file_lock(&self, path: &Path) -> Arc<FileMutex<()>> {
+        let key = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let mut files = self.files.lock();
+        files.retain(|_, lock| lock.strong_count() > 0);
+        if let Some(lock) = files.get(&key).and_then(Weak::upgrade) {
+            return lock;
+        }
+        let lock = Arc::new(FileMutex::new(()));
+        files.insert(key, Arc::downgrade(&lock));
+        lock
     }
 }
 
@@ -63,12 +121,7 @@ impl Default for GlobalScreenshotLock {
     }
 }
 
-#[derive(Debug, Clone, Copy, Default)]
-pub enum ExecutorStrategy {
-    #[default]
-    FireAndForget,
-    Pooled,
-}
+pub use crate::config::ExecutorStrategy;
 
 #[derive(Debug, Clone)]
 pub struct RecalcConfig {
@@ -97,3 +150,42 @@ pub fn create_executor(config: &RecalcConfig) -> Arc<dyn RecalcExecutor> {
         }
     }
 }
+
+#[cfg(all(test, feature = "recalc"))]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio::time::timeout;
+
+    #[tokio::test]
+    async fn same_file_serializes_while_other_files_proceed() {
+        let lock = GlobalRecalcLock::new(2);
+        let held = lock.acquire(Path::new("/tmp/a.xlsx")).await.expect("a");
+
+        let other = timeout(
+            Duration::from_millis(200),
+            lock.acquire(Path::new("/tmp/b.xlsx")),
+        )
+        .await;
+        assert!(other.is_ok(), "a different file should not wait");
+        drop(other);
+
+        let same = timeout(
+            Duration::from_millis(50),
+            lock.acquire(Path::new("/tmp/a.xlsx")),
+        )
+        .await;
+        assert!(
+            same.is_err(),
+            "the same file must wait for the running recalc"
+        );
+
+        drop(held);
+        let same = timeout(
+            Duration::from_millis(200),
+            lock.acquire(Path::new("/tmp/a.xlsx")),
+        )
+        .await;
+        assert!(same.is_ok(), "the file lock is released with the permit");
+    }
+}
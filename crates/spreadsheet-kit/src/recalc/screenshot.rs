@@ -6,7 +6,7 @@ use std::time::{Duration, Instant};
 use tokio::process::Command;
 use tokio::{fs, task, time};
 
-use super::macro_uri::export_screenshot_uri;
+use super::macro_uri::{export_pdf_uri, export_screenshot_uri};
 
 pub struct ScreenshotResult {
     pub output_path: PathBuf,
@@ -36,6 +36,8 @@ impl ScreenshotExecutor {
         output_path: &Path,
         sheet_name: &str,
         range: Option<&str>,
+        dpi: Option<u32>,
+        crop: bool,
     ) -> Result<ScreenshotResult> {
         let start = Instant::now();
 
@@ -51,26 +53,44 @@ impl ScreenshotExecutor {
         let _ = std::fs::create_dir_all("/tmp/.cache");
         let _ = std::fs::create_dir_all("/tmp/.config");
 
-        fn truncate_for_log(s: &str, max_bytes: usize) -> String {
-            if s.len() <= max_bytes {
-                return s.to_string();
-            }
+        // Removed on every exit path, including conversion failures.
+        let pdf_output_path = tempfile::TempPath::from_path(output_path.with_extension("pdf"));
+        let macro_uri_pdf = export_screenshot_uri(
+            &file_path,
+            &pdf_output_path.display().to_string(),
+            sheet_name,
+            range_arg,
+        )?;
 
-            // Keep it cheap; best-effort to not cut mid-char.
-            let mut end = max_bytes;
-            while end > 0 && !s.is_char_boundary(end) {
-                end -= 1;
-            }
-            format!("{}...[truncated]", &s[..end])
-        }
+        let macro_logs = self.run_macro(macro_uri_pdf, "screenshot").await?;
 
-        struct SofficeLogs {
-            stdout: String,
-            stderr: String,
-        }
+        fs::metadata(&pdf_output_path).await.map_err(|_| {
+            anyhow!(
+                "screenshot PDF output file not created at {} (soffice stderr={}, stdout={})",
+                pdf_output_path.display(),
+                macro_logs.stderr,
+                macro_logs.stdout
+            )
+        })?;
+
+        let out_dir = output_path
+            .parent()
+            .ok_or_else(|| anyhow!("output path has no parent directory"))?;
+
+        let out_dir_str = out_dir
+            .to_str()
+            .ok_or_else(|| anyhow!("output directory is not valid UTF-8"))?;
 
-        let run_macro = |macro_uri: String| async move {
-            let macro_result = time::timeout(self.timeout, {
+        let pdf_str = pdf_output_path
+            .to_str()
+            .ok_or_else(|| anyhow!("pdf output path is not valid UTF-8"))?;
+
+        // soffice's PNG export has no resolution control, so an explicit DPI
+        // rasterizes the PDF with pdftoppm instead.
+        let mut png_path = if dpi.is_some() {
+            None
+        } else {
+            let convert_result = time::timeout(self.timeout, {
                 let mut cmd = Command::new(&self.soffice_path);
                 if let Ok(root) = std::env::var("SPREADSHEET_MCP_LIBREOFFICE_USER_INSTALLATION")
                     && !root.trim().is_empty()
@@ -89,8 +109,11 @@ impl ScreenshotExecutor {
                     "--nodefault",
                     "--nofirststartwizard",
                     "--nolockcheck",
-                    "--calc",
-                    &macro_uri,
+                    "--convert-to",
+                    "png",
+                    "--outdir",
+                    out_dir_str,
+                    pdf_str,
                 ])
                 .env("HOME", "/tmp")
                 .env("XDG_CACHE_HOME", "/tmp/.cache")
@@ -100,141 +123,48 @@ impl ScreenshotExecutor {
                 .output()
             })
             .await
-            .map_err(|_| anyhow!("soffice timed out after {:?}", self.timeout))
-            .and_then(|res| res.map_err(|e| anyhow!("failed to spawn soffice: {}", e)))?;
-
-            // LibreOffice Basic errors often land in stderr, but the process can still exit 0.
-            // Capture stdout/stderr even on success so we can surface it on downstream failures.
-            let stdout_raw = String::from_utf8_lossy(&macro_result.stdout);
-            let stderr_raw = String::from_utf8_lossy(&macro_result.stderr);
-            let stdout = truncate_for_log(stdout_raw.trim(), 16 * 1024);
-            let stderr = truncate_for_log(stderr_raw.trim(), 16 * 1024);
-
-            if !stdout.is_empty() || !stderr.is_empty() {
-                tracing::debug!(
-                    soffice_stdout = %stdout,
-                    soffice_stderr = %stderr,
-                    "soffice screenshot macro output"
-                );
-            }
+            .map_err(|_| {
+                anyhow!(
+                    "soffice PDF->PNG conversion timed out after {:?}",
+                    self.timeout
+                )
+            })
+            .and_then(|res| {
+                res.map_err(|e| anyhow!("failed to spawn soffice for conversion: {}", e))
+            })?;
 
-            if !macro_result.status.success() {
+            if !convert_result.status.success() {
+                let stderr = String::from_utf8_lossy(&convert_result.stderr);
+                let stdout = String::from_utf8_lossy(&convert_result.stdout);
                 return Err(anyhow!(
-                    "soffice screenshot macro failed (exit {}): stderr={}, stdout={}",
-                    macro_result.status.code().unwrap_or(-1),
+                    "soffice PDF->PNG conversion failed (exit {}): stderr={}, stdout={}",
+                    convert_result.status.code().unwrap_or(-1),
                     stderr,
                     stdout
                 ));
             }
 
-            Ok(SofficeLogs { stdout, stderr })
-        };
-
-        let pdf_output_path = output_path.with_extension("pdf");
-        let macro_uri_pdf = export_screenshot_uri(
-            &file_path,
-            &pdf_output_path.display().to_string(),
-            sheet_name,
-            range_arg,
-        )?;
-
-        let macro_logs = run_macro(macro_uri_pdf).await?;
-
-        fs::metadata(&pdf_output_path).await.map_err(|_| {
-            anyhow!(
-                "screenshot PDF output file not created at {} (soffice stderr={}, stdout={})",
-                pdf_output_path.display(),
-                macro_logs.stderr,
-                macro_logs.stdout
-            )
-        })?;
-
-        let out_dir = output_path
-            .parent()
-            .ok_or_else(|| anyhow!("output path has no parent directory"))?;
-
-        let out_dir_str = out_dir
-            .to_str()
-            .ok_or_else(|| anyhow!("output directory is not valid UTF-8"))?;
-
-        let pdf_str = pdf_output_path
-            .to_str()
-            .ok_or_else(|| anyhow!("pdf output path is not valid UTF-8"))?;
-
-        let convert_result = time::timeout(self.timeout, {
-            let mut cmd = Command::new(&self.soffice_path);
-            if let Ok(root) = std::env::var("SPREADSHEET_MCP_LIBREOFFICE_USER_INSTALLATION")
-                && !root.trim().is_empty()
-            {
-                let root = root.trim();
-                let uri = if root.starts_with("file://") {
-                    root.to_string()
-                } else {
-                    format!("file:///{}", root.trim_start_matches('/'))
-                };
-                cmd.arg(format!("-env:UserInstallation={}", uri));
-            }
-            cmd.args([
-                "--headless",
-                "--norestore",
-                "--nodefault",
-                "--nofirststartwizard",
-                "--nolockcheck",
-                "--convert-to",
-                "png",
-                "--outdir",
-                out_dir_str,
-                pdf_str,
-            ])
-            .env("HOME", "/tmp")
-            .env("XDG_CACHE_HOME", "/tmp/.cache")
-            .env("XDG_CONFIG_HOME", "/tmp/.config")
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
-        })
-        .await
-        .map_err(|_| {
-            anyhow!(
-                "soffice PDF->PNG conversion timed out after {:?}",
-                self.timeout
-            )
-        })
-        .and_then(|res| {
-            res.map_err(|e| anyhow!("failed to spawn soffice for conversion: {}", e))
-        })?;
-
-        if !convert_result.status.success() {
-            let stderr = String::from_utf8_lossy(&convert_result.stderr);
-            let stdout = String::from_utf8_lossy(&convert_result.stdout);
-            return Err(anyhow!(
-                "soffice PDF->PNG conversion failed (exit {}): stderr={}, stdout={}",
-                convert_result.status.code().unwrap_or(-1),
-                stderr,
-                stdout
-            ));
-        }
-
-        let mut png_path = if fs::metadata(output_path).await.is_ok() {
-            Some(output_path.to_path_buf())
-        } else {
-            let stem = pdf_output_path
-                .file_stem()
-                .and_then(|s| s.to_str())
-                .unwrap_or("");
-            let mut dir = fs::read_dir(out_dir).await?;
-            let mut found: Option<PathBuf> = None;
-            while let Some(entry) = dir.next_entry().await? {
-                let path = entry.path();
-                if path.extension().and_then(|e| e.to_str()) == Some("png")
-                    && let Some(file_stem) = path.file_stem().and_then(|s| s.to_str())
-                    && file_stem.starts_with(stem)
-                {
-                    found = Some(path);
-                    break;
+            if fs::metadata(output_path).await.is_ok() {
+                Some(output_path.to_path_buf())
+            } else {
+                let stem = pdf_output_path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or("");
+                let mut dir = fs::read_dir(out_dir).await?;
+                let mut found: Option<PathBuf> = None;
+                while let Some(entry) = dir.next_entry().await? {
+                    let path = entry.path();
+                    if path.extension().and_then(|e| e.to_str()) == Some("png")
+                        && let Some(file_stem) = path.file_stem().and_then(|s| s.to_str())
+                        && file_stem.starts_with(stem)
+                    {
+                        found = Some(path);
+                        break;
+                    }
                 }
+                found
             }
-            found
         };
 
         if png_path.is_none() {
@@ -246,7 +176,12 @@ impl ScreenshotExecutor {
             let pdftoppm_result = time::timeout(
                 self.timeout,
                 Command::new("pdftoppm")
-                    .args(["-png", "-singlefile", pdf_str, prefix_str])
+                    .args(["-png", "-singlefile"])
+                    .args(
+                        dpi.map(|dpi| vec!["-r".to_string(), dpi.to_string()])
+                            .unwrap_or_default(),
+                    )
+                    .args([pdf_str, prefix_str])
                     .stdout(Stdio::piped())
                     .stderr(Stdio::piped())
                     .output(),
@@ -274,9 +209,11 @@ impl ScreenshotExecutor {
         let png_path = png_path
             .ok_or_else(|| anyhow!("PNG output file not created in {}", out_dir.display()))?;
 
-        let _ = fs::remove_file(&pdf_output_path).await;
+        drop(pdf_output_path);
 
-        crop_png_best_effort(&png_path).await;
+        if crop {
+            crop_png_best_effort(&png_path).await;
+        }
 
         let metadata = fs::metadata(&png_path).await.map_err(|_| {
             anyhow!(
@@ -292,9 +229,134 @@ impl ScreenshotExecutor {
         })
     }
 
+    /// Export the workbook (or just `sheet_name`) to PDF at `output_path`.
+    ///
+    /// Print areas saved in the workbook are honored; `print_area` replaces
+    /// the print area of `sheet_name` for this export only.
+    pub async fn export_pdf(
+        &self,
+        workbook_path: &Path,
+        output_path: &Path,
+        sheet_name: Option<&str>,
+        print_area: Option<&str>,
+    ) -> Result<ScreenshotResult> {
+        let start = Instant::now();
+
+        let abs_path = workbook_path
+            .canonicalize()
+            .map_err(|e| anyhow!("failed to canonicalize workbook path: {}", e))?;
+
+        let _ = std::fs::create_dir_all("/tmp/.cache");
+        let _ = std::fs::create_dir_all("/tmp/.config");
+
+        let macro_uri = export_pdf_uri(
+            &abs_path.display().to_string(),
+            &output_path.display().to_string(),
+            sheet_name.unwrap_or(""),
+            print_area.unwrap_or(""),
+        )?;
+        let macro_logs = self.run_macro(macro_uri, "pdf export").await?;
+
+        let metadata = fs::metadata(output_path).await.map_err(|_| {
+            anyhow!(
+                "PDF output file not created at {} (soffice stderr={}, stdout={})",
+                output_path.display(),
+                macro_logs.stderr,
+                macro_logs.stdout
+            )
+        })?;
+
+        Ok(ScreenshotResult {
+            output_path: output_path.to_path_buf(),
+            size_bytes: metadata.len(),
+            duration_ms: start.elapsed().as_millis() as u64,
+        })
+    }
+
     pub fn is_available(&self) -> bool {
         self.soffice_path.exists()
     }
+
+    async fn run_macro(&self, macro_uri: String, label: &str) -> Result<SofficeLogs> {
+        let macro_result = time::timeout(self.timeout, {
+            let mut cmd = Command::new(&self.soffice_path);
+            if let Ok(root) = std::env::var("SPREADSHEET_MCP_LIBREOFFICE_USER_INSTALLATION")
+                && !root.trim().is_empty()
+            {
+                let root = root.trim();
+                let uri = if root.starts_with("file://") {
+                    root.to_string()
+                } else {
+                    format!("file:///{}", root.trim_start_matches('/'))
+                };
+                cmd.arg(format!("-env:UserInstallation={}", uri));
+            }
+            cmd.args([
+                "--headless",
+                "--norestore",
+                "--nodefault",
+                "--nofirststartwizard",
+                "--nolockcheck",
+                "--calc",
+                &macro_uri,
+            ])
+            .env("HOME", "/tmp")
+            .env("XDG_CACHE_HOME", "/tmp/.cache")
+            .env("XDG_CONFIG_HOME", "/tmp/.config")
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .output()
+        })
+        .await
+        .map_err(|_| anyhow!("soffice timed out after {:?}", self.timeout))
+        .and_then(|res| res.map_err(|e| anyhow!("failed to spawn soffice: {}", e)))?;
+
+        // LibreOffice Basic errors often land in stderr, but the process can still exit 0.
+        // Capture stdout/stderr even on success so we can surface it on downstream failures.
+        let stdout_raw = String::from_utf8_lossy(&macro_result.stdout);
+        let stderr_raw = String::from_utf8_lossy(&macro_result.stderr);
+        let stdout = truncate_for_log(stdout_raw.trim(), 16 * 1024);
+        let stderr = truncate_for_log(stderr_raw.trim(), 16 * 1024);
+
+        if !stdout.is_empty() || !stderr.is_empty() {
+            tracing::debug!(
+                soffice_stdout = %stdout,
+                soffice_stderr = %stderr,
+                "soffice {} macro output",
+                label
+            );
+        }
+
+        if !macro_result.status.success() {
+            return Err(anyhow!(
+                "soffice {} macro failed (exit {}): stderr={}, stdout={}",
+                label,
+                macro_result.status.code().unwrap_or(-1),
+                stderr,
+                stdout
+            ));
+        }
+
+        Ok(SofficeLogs { stdout, stderr })
+    }
+}
+
+struct SofficeLogs {
+    stdout: String,
+    stderr: String,
+}
+
+fn truncate_for_log(s: &str, max_bytes: usize) -> String {
+    if s.len() <= max_bytes {
+        return s.to_string();
+    }
+
+    // Keep it cheap; best-effort to not cut mid-char.
+    let mut end = max_bytes;
+    while end > 0 && !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}...[truncated]", &s[..end])
 }
 
 async fn crop_png_best_effort(path: &Path) {
@@ -313,28 +375,9 @@ fn crop_png_in_place(path: &Path) -> Result<()> {
 
     let rgba = img.to_rgba8();
     let (width, height) = rgba.dimensions();
-    if width == 0 || height == 0 {
-        return Ok(());
-    }
-
-    let background = estimate_background_color(&rgba, width, height);
-    // First pass uses a conservative threshold to avoid cropping to noise.
-    // If that fails (e.g., very faint gridlines), fall back to a more sensitive pass.
-    let bbox = find_foreground_bbox(&rgba, background, 20, 100)
-        .or_else(|| find_foreground_bbox(&rgba, background, 8, 20));
-
-    let Some((min_x, min_y, max_x, max_y)) = bbox else {
+    let Some((min_x, min_y, crop_w, crop_h)) = content_bounds(&rgba) else {
         return Ok(());
     };
-
-    let padding = 8u32;
-    let min_x = min_x.saturating_sub(padding);
-    let min_y = min_y.saturating_sub(padding);
-    let max_x = (max_x + padding).min(width - 1);
-    let max_y = (max_y + padding).min(height - 1);
-    let crop_w = max_x - min_x + 1;
-    let crop_h = max_y - min_y + 1;
-
     if crop_w == width && crop_h == height {
         return Ok(());
     }
@@ -347,6 +390,28 @@ fn crop_png_in_place(path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// The padded box (x, y, width, height) around what was drawn on the page,
+/// or `None` for a blank page.
+pub(crate) fn content_bounds(rgba: &image::RgbaImage) -> Option<(u32, u32, u32, u32)> {
+    let (width, height) = rgba.dimensions();
+    if width == 0 || height == 0 {
+        return None;
+    }
+
+    let background = estimate_background_color(rgba, width, height);
+    // First pass uses a conservative threshold to avoid cropping to noise.
+    // If that fails (e.g., very faint gridlines), fall back to a more sensitive pass.
+    let (min_x, min_y, max_x, max_y) = find_foreground_bbox(rgba, background, 20, 100)
+        .or_else(|| find_foreground_bbox(rgba, background, 8, 20))?;
+
+    let padding = 8u32;
+    let min_x = min_x.saturating_sub(padding);
+    let min_y = min_y.saturating_sub(padding);
+    let max_x = (max_x + padding).min(width - 1);
+    let max_y = (max_y + padding).min(height - 1);
+    Some((min_x, min_y, max_x - min_x + 1, max_y - min_y + 1))
+}
+
 fn find_foreground_bbox(
     rgba: &image::RgbaImage,
     background: [u8; 3],
@@ -0,0 +1,186 @@
+//! Stable names for workbooks, so prompts and scripts can say `q3-model`
+//! instead of a long path or a hashed id.
+//!
+//! Aliases live under `workbook_aliases` in the server config file or under
+//! `aliases` in `<workspace>/.asp/aliases.yaml`, and can be added at runtime
+//! with the `register_alias` tool:
+//!
+//! ```yaml
+//! aliases:
+//!   q3-model: finance/2024/q3/model_v7_final.xlsx
+//!   budget: budget-4f2a9c
+//! ```
+//!
+//! A target is a workspace-relative path or anything `resolve` already
+//! accepts (workbook id, short id, or virtual key). Alias lookup is
+//! case-insensitive and takes precedence over ids.
+
+use anyhow::{Context, Result, bail};
+use parking_lot::RwLock;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Workspace-relative files checked for workbook aliases, in order.
+pub const WORKSPACE_ALIAS_FILES: [&str; 3] =
+    [".asp/aliases.yaml", ".asp/aliases.yml", ".asp/aliases.json"];
+
+/// Alias name to target, as written in config.
+pub type WorkbookAliases = BTreeMap<String, String>;
+
+/// Reject names that could be mistaken for paths or URLs.
+pub fn validate_alias_name(alias: &str) -> Result<()> {
+    if alias.is_empty() {
+        bail!("alias cannot be empty");
+    }
+    if alias.starts_with('.') {
+        bail!("alias '{alias}' cannot start with '.'");
+    }
+    if let Some(invalid) = alias
+        .chars()
+        .find(|ch| !(ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.')))
+    {
+        bail!("alias '{alias}' contains '{invalid}'; use letters, digits, '-', '_' or '.'");
+    }
+    Ok(())
+}
+
+/// Load aliases from a YAML or JSON file with a top-level `aliases` map.
+pub fn load_workbook_aliases(path: &Path) -> Result<WorkbookAliases> {
+    #[derive(Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct AliasesFile {
+        #[serde(default)]
+        aliases: WorkbookAliases,
+    }
+
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("failed to read workbook aliases {:?}", path))?;
+    let is_json = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    let parsed: AliasesFile = if is_json {
+        serde_json::from_str(&contents)
+            .with_context(|| format!("failed to parse workbook aliases {:?}", path))?
+    } else {
+        serde_yaml::from_str(&contents)
+            .with_context(|| format!("failed to parse workbook aliases {:?}", path))?
+    };
+    for alias in parsed.aliases.keys() {
+        validate_alias_name(alias).with_context(|| format!("in {:?}", path))?;
+    }
+    Ok(parsed.aliases)
+}
+
+/// First existing entry of [`WORKSPACE_ALIAS_FILES`] under `workspace_root`.
+pub fn workspace_aliases_file(workspace_root: &Path) -> Option<PathBuf> {
+    WORKSPACE_ALIAS_FILES
+        .iter()
+        .map(|relative| workspace_root.join(relative))
+        .find(|path| path.is_file())
+}
+
+/// Path an alias in the workspace alias file under `workspace_root` points
+/// at, for callers that take file paths rather than workbook ids. `Ok(None)`
+/// when there is no alias file or it does not define `alias`.
+pub fn resolve_alias_path(workspace_root: &Path, alias: &str) -> Result<Option<PathBuf>> {
+    if validate_alias_name(alias).is_err() {
+        return Ok(None);
+    }
+    let Some(file) = workspace_aliases_file(workspace_root) else {
+        return Ok(None);
+    };
+    let aliases = load_workbook_aliases(&file)?;
+    Ok(aliases
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(alias))
+        .map(|(_, target)| workspace_root.join(target)))
+}
+
+#[derive(Debug, Clone)]
+struct AliasEntry {
+    alias: String,
+    target: String,
+}
+
+/// Aliases held by a repository: the configured ones plus any registered
+/// since startup. Registered aliases last for the life of the process.
+#[derive(Debug, Default)]
+pub struct AliasTable {
+    entries: RwLock<BTreeMap<String, AliasEntry>>,
+}
+
+impl AliasTable {
+    pub fn new(aliases: &WorkbookAliases) -> Self {
+        let table = Self::default();
+        for (alias, target) in aliases {
+            table.insert(alias, target);
+        }
+        table
+    }
+
+    /// Add or replace `alias`, returning the target it pointed at before.
+    pub fn insert(&self, alias: &str, target: &str) -> Option<String> {
+        self.entries
+            .write()
+            .insert(
+                alias.to_ascii_lowercase(),
+                AliasEntry {
+                    alias: alias.to_string(),
+                    target: target.to_string(),
+                },
+            )
+            .map(|previous| previous.target)
+    }
+
+    /// Target of `alias`, if one is defined.
+    pub fn target(&self, alias: &str) -> Option<String> {
+        self.entries
+            .read()
+            .get(&alias.to_ascii_lowercase())
+            .map(|entry| entry.target.clone())
+    }
+
+    /// Every alias and its target, sorted by alias.
+    pub fn entries(&self) -> Vec<(String, String)> {
+        self.entries
+            .read()
+            .values()
+            .map(|entry| (entry.alias.clone(), entry.target.clone()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alias_names_exclude_paths_and_urls() {
+        assert!(validate_alias_name("q3-model").is_ok());
+        assert!(validate_alias_name("budget_2024.v2").is_ok());
+        assert!(validate_alias_name("finance/q3").is_err());
+        assert!(validate_alias_name("https://x").is_err());
+        assert!(validate_alias_name(".hidden").is_err());
+        assert!(validate_alias_name("").is_err());
+    }
+
+    #[test]
+    fn table_lookup_ignores_case_and_reports_replacements() {
+        let table = AliasTable::new(&WorkbookAliases::from([(
+            "Q3-Model".to_string(),
+            "finance/q3.xlsx".to_string(),
+        )]));
+        assert_eq!(table.target("q3-model").as_deref(), Some("finance/q3.xlsx"));
+        assert_eq!(
+            table.insert("q3-MODEL", "finance/q3_v2.xlsx").as_deref(),
+            Some("finance/q3.xlsx")
+        );
+        assert_eq!(
+            table.entries(),
+            vec![("q3-MODEL".to_string(), "finance/q3_v2.xlsx".to_string())]
+        );
+    }
+}
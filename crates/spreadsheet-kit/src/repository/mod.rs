@@ -1,18 +1,32 @@
 use crate::model::{WorkbookId, WorkbookListResponse};
 use crate::tools::filters::WorkbookFilter;
 use crate::workbook::WorkbookContext;
-use anyhow::Result;
+use anyhow::{Context, Result, anyhow};
+use std::path::Path;
+use std::sync::Arc;
 
+pub mod alias;
 pub mod path_workspace;
+#[cfg(all(not(target_arch = "wasm32"), feature = "remote"))]
+pub mod remote;
 pub mod virtual_workspace;
+pub mod watch;
 
+pub use alias::{AliasTable, WORKSPACE_ALIAS_FILES, WorkbookAliases};
 pub use path_workspace::PathWorkspaceRepository;
-pub use virtual_workspace::{VirtualWorkbookInput, VirtualWorkspaceRepository};
+#[cfg(all(not(target_arch = "wasm32"), feature = "remote"))]
+pub use remote::{REMOTE_CACHE_DIR_ENV, RemoteCache, RemoteObject, RemoteWorkspaceRepository};
+pub use virtual_workspace::{
+    MAX_UPLOAD_BYTES, UploadStaging, VirtualWorkbookInput, VirtualWorkspaceRepository,
+};
+pub use watch::{WORKSPACE_EVENT_CAPACITY, WorkspaceEventBatch, WorkspaceEventLog};
 
 #[derive(Debug, Clone)]
 pub enum WorkbookSource {
     Path(std::path::PathBuf),
     Virtual(String),
+    /// `https://` or `s3://` URL, fetched on load.
+    Remote(String),
 }
 
 #[derive(Debug, Clone)]
@@ -27,4 +41,58 @@ pub trait WorkbookRepository: Send + Sync {
     fn list(&self, filter: &WorkbookFilter) -> Result<WorkbookListResponse>;
     fn resolve(&self, id_or_alias: &WorkbookId) -> Result<ResolvedWorkbookRef>;
     fn load_context(&self, resolved: &ResolvedWorkbookRef) -> Result<WorkbookContext>;
+
+    /// Start (or join) change notifications for this repository's workbooks.
+    fn watch_workspace(&self) -> Result<Arc<WorkspaceEventLog>> {
+        Err(anyhow!(
+            "workspace watching is not supported by this repository"
+        ))
+    }
+
+    /// Store workbook bytes under `input.key`, replacing any workbook
+    /// previously registered with that key.
+    fn register_workbook(&self, _input: VirtualWorkbookInput) -> Result<ResolvedWorkbookRef> {
+        Err(anyhow!(
+            "workbook uploads are not supported by this repository"
+        ))
+    }
+
+    /// Point `alias` at `target` for later `resolve` calls, replacing any
+    /// previous target. Returns the workbook the alias now resolves to.
+    fn register_alias(&self, alias: &str, _target: &str) -> Result<ResolvedWorkbookRef> {
+        Err(anyhow!(
+            "alias '{alias}' cannot be registered: this repository does not support aliases"
+        ))
+    }
+
+    /// Defined aliases and their targets, sorted by alias.
+    fn aliases(&self) -> Vec<(String, String)> {
+        Vec::new()
+    }
+
+    /// Raw file bytes of a resolved workbook (or fork).
+    fn workbook_bytes(&self, resolved: &ResolvedWorkbookRef) -> Result<Arc<Vec<u8>>> {
+        match &resolved.source {
+            WorkbookSource::Path(path) => read_workbook_file(path),
+            WorkbookSource::Virtual(key) => Err(anyhow!(
+                "virtual workbook {key} is not held by this repository"
+            )),
+            WorkbookSource::Remote(url) => Err(anyhow!(
+                "remote workbook {url} is not held by this repository"
+            )),
+        }
+    }
+}
+
+/// True for the workbook URL schemes a remote repository can fetch.
+pub fn is_remote_url(value: &str) -> bool {
+    let lowered = value.trim().to_ascii_lowercase();
+    ["https://", "http://", "s3://"]
+        .iter()
+        .any(|scheme| lowered.starts_with(scheme))
+}
+
+pub(crate) fn read_workbook_file(path: &Path) -> Result<Arc<Vec<u8>>> {
+    let bytes = std::fs::read(path).with_context(|| format!("failed to read {:?}", path))?;
+    Ok(Arc::new(bytes))
 }
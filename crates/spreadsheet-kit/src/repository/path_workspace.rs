@@ -1,7 +1,12 @@
+#[cfg(not(target_arch = "wasm32"))]
+use super::WorkspaceEventLog;
+use super::alias::{AliasTable, validate_alias_name};
 use super::{ResolvedWorkbookRef, WorkbookRepository, WorkbookSource};
 use crate::config::ServerConfig;
 #[cfg(feature = "recalc")]
 use crate::fork::ForkRegistry;
+#[cfg(all(not(target_arch = "wasm32"), feature = "watch"))]
+use crate::model::WorkspaceEventKind;
 use crate::model::{WorkbookDescriptor, WorkbookId, WorkbookListResponse};
 use crate::tools::filters::WorkbookFilter;
 use crate::utils::{
@@ -11,6 +16,10 @@ use crate::utils::{
 use crate::workbook::WorkbookContext;
 use anyhow::{Result, anyhow};
 use chrono::SecondsFormat;
+#[cfg(all(not(target_arch = "wasm32"), feature = "watch"))]
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+#[cfg(all(not(target_arch = "wasm32"), feature = "watch"))]
+use parking_lot::Mutex;
 use parking_lot::RwLock;
 use std::collections::HashMap;
 use std::fs;
@@ -20,9 +29,10 @@ use walkdir::WalkDir;
 
 pub struct PathWorkspaceRepository {
     config: Arc<ServerConfig>,
-    index: RwLock<HashMap<WorkbookId, IndexedWorkbook>>,
-    alias_index: RwLock<HashMap<String, WorkbookId>>,
-    legacy_alias_index: RwLock<HashMap<String, WorkbookId>>,
+    index: Arc<WorkspaceIndex>,
+    aliases: AliasTable,
+    #[cfg(all(not(target_arch = "wasm32"), feature = "watch"))]
+    watch: Mutex<Option<WorkspaceWatch>>,
     #[cfg(feature = "recalc")]
     fork_registry: Option<Arc<ForkRegistry>>,
 }
@@ -31,10 +41,11 @@ impl PathWorkspaceRepository {
     #[cfg(feature = "recalc")]
     pub fn new(config: Arc<ServerConfig>, fork_registry: Option<Arc<ForkRegistry>>) -> Self {
         Self {
+            aliases: AliasTable::new(&config.workbook_aliases),
             config,
-            index: RwLock::new(HashMap::new()),
-            alias_index: RwLock::new(HashMap::new()),
-            legacy_alias_index: RwLock::new(HashMap::new()),
+            index: Arc::new(WorkspaceIndex::default()),
+            #[cfg(all(not(target_arch = "wasm32"), feature = "watch"))]
+            watch: Mutex::new(None),
             fork_registry,
         }
     }
@@ -42,78 +53,19 @@ impl PathWorkspaceRepository {
     #[cfg(not(feature = "recalc"))]
     pub fn new(config: Arc<ServerConfig>) -> Self {
         Self {
+            aliases: AliasTable::new(&config.workbook_aliases),
             config,
-            index: RwLock::new(HashMap::new()),
-            alias_index: RwLock::new(HashMap::new()),
-            legacy_alias_index: RwLock::new(HashMap::new()),
+            index: Arc::new(WorkspaceIndex::default()),
+            #[cfg(all(not(target_arch = "wasm32"), feature = "watch"))]
+            watch: Mutex::new(None),
         }
     }
 
-    fn register(&self, located: &LocatedWorkbook) {
-        self.index.write().insert(
-            located.workbook_id.clone(),
-            IndexedWorkbook {
-                path: located.path.clone(),
-                short_id: located.short_id.clone(),
-                revision_id: located.revision_id.clone(),
-            },
-        );
-
-        let mut aliases = self.alias_index.write();
-        aliases.insert(
-            located.short_id.to_ascii_lowercase(),
-            located.workbook_id.clone(),
-        );
-        aliases.insert(
-            located.workbook_id.as_str().to_ascii_lowercase(),
-            located.workbook_id.clone(),
-        );
-
-        self.legacy_alias_index.write().insert(
-            located.legacy_id.to_ascii_lowercase(),
-            located.workbook_id.clone(),
-        );
-    }
-
-    fn register_all(&self, located: &[LocatedWorkbook]) {
-        for entry in located {
-            self.register(entry);
-        }
-    }
-
-    fn locate_by_path(&self, path: &Path) -> Result<LocatedWorkbook> {
-        let metadata = fs::metadata(path)?;
-        let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
-        let workbook_id = WorkbookId(hash_path_identity(&canonical));
-        let legacy_id = hash_path_metadata(path, &metadata);
-        let slug = path
-            .file_stem()
-            .map(|s| s.to_string_lossy().to_string())
-            .unwrap_or_else(|| "workbook".to_string());
-        let short_id = make_short_workbook_id(&slug, workbook_id.as_str());
-
-        Ok(LocatedWorkbook {
-            workbook_id,
-            short_id,
-            legacy_id,
-            slug,
-            folder: derive_folder(&self.config, path),
-            path: path.to_path_buf(),
-            bytes: metadata.len(),
-            last_modified: metadata
-                .modified()
-                .ok()
-                .and_then(system_time_to_rfc3339)
-                .map(|dt| dt.to_rfc3339_opts(SecondsFormat::Secs, true)),
-            revision_id: Some(hash_file_sha256_hex(path)?),
-        })
-    }
-
     fn scan_workbooks(&self) -> Result<Vec<LocatedWorkbook>> {
         let mut out = Vec::new();
 
         if let Some(single) = self.config.single_workbook() {
-            out.push(self.locate_by_path(single)?);
+            out.push(locate_by_path(&self.config, single)?);
             return Ok(out);
         }
 
@@ -126,30 +78,129 @@ impl PathWorkspaceRepository {
             if !has_supported_extension(&self.config.supported_extensions, path) {
                 continue;
             }
-            out.push(self.locate_by_path(path)?);
+            out.push(locate_by_path(&self.config, path)?);
         }
 
         out.sort_by(|a, b| a.slug.cmp(&b.slug));
         Ok(out)
     }
 
-    fn lookup_indexed(&self, id_or_alias: &WorkbookId) -> Option<WorkbookId> {
-        if self.index.read().contains_key(id_or_alias) {
-            return Some(id_or_alias.clone());
+    /// Resolve a workbook id, short id, legacy id, or fork id.
+    fn resolve_id(&self, id_or_alias: &WorkbookId) -> Result<ResolvedWorkbookRef> {
+        #[cfg(feature = "recalc")]
+        if let Some(registry) = &self.fork_registry
+            && let Some(path) = registry.get_fork_path(id_or_alias.as_str())
+        {
+            return Ok(ResolvedWorkbookRef {
+                workbook_id: id_or_alias.clone(),
+                short_id: make_short_workbook_id("fork", id_or_alias.as_str()),
+                revision_id: Some(hash_file_sha256_hex(&path)?),
+                source: WorkbookSource::Path(path),
+            });
         }
 
-        let lowered = id_or_alias.as_str().to_ascii_lowercase();
-        if let Some(id) = self.alias_index.read().get(&lowered).cloned() {
-            return Some(id);
+        if let Some(canonical_id) = self.index.lookup(id_or_alias) {
+            let indexed = self.index.entries.read().get(&canonical_id).cloned();
+            if let Some(indexed) = indexed {
+                return Ok(ResolvedWorkbookRef {
+                    workbook_id: canonical_id,
+                    short_id: indexed.short_id,
+                    revision_id: indexed.revision_id,
+                    source: WorkbookSource::Path(indexed.path),
+                });
+            }
         }
-        self.legacy_alias_index.read().get(&lowered).cloned()
+
+        let candidate = id_or_alias.as_str().to_ascii_lowercase();
+        let scanned = self.scan_workbooks()?;
+        self.index.register_all(&scanned);
+
+        for wb in scanned {
+            if candidate == wb.workbook_id.as_str().to_ascii_lowercase()
+                || candidate == wb.short_id.to_ascii_lowercase()
+                || candidate == wb.legacy_id.to_ascii_lowercase()
+            {
+                return Ok(wb.into_resolved());
+            }
+        }
+
+        Err(anyhow!("workbook id {} not found", id_or_alias.as_str()))
+    }
+
+    /// Resolve an alias target: a workbook file under the workspace root,
+    /// or an id.
+    fn resolve_target(&self, target: &str) -> Result<ResolvedWorkbookRef> {
+        let path = self.config.workspace_root.join(target);
+        if path.is_file() {
+            if !has_supported_extension(&self.config.supported_extensions, &path) {
+                return Err(anyhow!(
+                    "'{target}' is not a workbook with a supported extension"
+                ));
+            }
+            let root = fs::canonicalize(&self.config.workspace_root)
+                .unwrap_or_else(|_| self.config.workspace_root.clone());
+            let canonical = fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+            if !canonical.starts_with(&root) {
+                return Err(anyhow!("'{target}' is outside the workspace root"));
+            }
+            let located = locate_by_path(&self.config, &path)?;
+            self.index.register(&located);
+            return Ok(located.into_resolved());
+        }
+        self.resolve_id(&WorkbookId(target.to_string()))
+    }
+
+    /// Watch the workspace for workbook files being added, rewritten, or
+    /// removed. Each change re-hashes the file, refreshes the index so
+    /// `resolve` returns the new `revision_id`, and appends an event to the
+    /// returned log. The watcher starts on the first call and lives as long
+    /// as the repository; later calls return the same log.
+    #[cfg(all(not(target_arch = "wasm32"), feature = "watch"))]
+    pub fn watch_workspace(&self) -> Result<Arc<WorkspaceEventLog>> {
+        let mut watch = self.watch.lock();
+        if let Some(active) = watch.as_ref() {
+            return Ok(active.log.clone());
+        }
+
+        // Seed the index so the first change to an existing file is reported
+        // as `changed` with its previous revision rather than as `added`.
+        let located = self.scan_workbooks()?;
+        self.index.register_all(&located);
+
+        let log = Arc::new(WorkspaceEventLog::new());
+        let handler = WatchHandler {
+            config: self.config.clone(),
+            index: self.index.clone(),
+            log: log.clone(),
+        };
+        let mut watcher =
+            notify::recommended_watcher(move |res: notify::Result<Event>| match res {
+                Ok(event) => handler.handle(event),
+                Err(error) => tracing::warn!(%error, "workspace watcher error"),
+            })?;
+        match self.config.single_workbook() {
+            Some(single) => {
+                let parent = single
+                    .parent()
+                    .filter(|parent| !parent.as_os_str().is_empty())
+                    .unwrap_or(Path::new("."));
+                watcher.watch(parent, RecursiveMode::NonRecursive)?;
+            }
+            None => watcher.watch(&self.config.workspace_root, RecursiveMode::Recursive)?,
+        }
+
+        *watch = Some(WorkspaceWatch {
+            _watcher: watcher,
+            log: log.clone(),
+        });
+        Ok(log)
     }
 }
 
 impl WorkbookRepository for PathWorkspaceRepository {
     fn list(&self, filter: &WorkbookFilter) -> Result<WorkbookListResponse> {
         let located = self.scan_workbooks()?;
-        self.register_all(&located);
+        self.index.register_all(&located);
 
         let mut descriptors = Vec::new();
         for wb in located {
@@ -182,44 +233,45 @@ impl WorkbookRepository for PathWorkspaceRepository {
     }
 
     fn resolve(&self, id_or_alias: &WorkbookId) -> Result<ResolvedWorkbookRef> {
-        #[cfg(feature = "recalc")]
-        if let Some(registry) = &self.fork_registry
-            && let Some(path) = registry.get_fork_path(id_or_alias.as_str())
-        {
-            return Ok(ResolvedWorkbookRef {
-                workbook_id: id_or_alias.clone(),
-                short_id: make_short_workbook_id("fork", id_or_alias.as_str()),
-                revision_id: Some(hash_file_sha256_hex(&path)?),
-                source: WorkbookSource::Path(path),
+        if let Some(target) = self.aliases.target(id_or_alias.as_str()) {
+            return self.resolve_target(&target).map_err(|error| {
+                anyhow!(
+                    "alias '{}' points to '{target}': {error}",
+                    id_or_alias.as_str()
+                )
             });
         }
+        self.resolve_id(id_or_alias)
+    }
 
-        if let Some(canonical_id) = self.lookup_indexed(id_or_alias) {
-            let indexed = self.index.read().get(&canonical_id).cloned();
-            if let Some(indexed) = indexed {
-                return Ok(ResolvedWorkbookRef {
-                    workbook_id: canonical_id,
-                    short_id: indexed.short_id,
-                    revision_id: indexed.revision_id,
-                    source: WorkbookSource::Path(indexed.path),
-                });
-            }
+    fn register_alias(&self, alias: &str, target: &str) -> Result<ResolvedWorkbookRef> {
+        validate_alias_name(alias)?;
+        if self.aliases.target(target).is_some() {
+            return Err(anyhow!(
+                "'{target}' is itself an alias; point '{alias}' at its target instead"
+            ));
         }
+        let resolved = self.resolve_target(target)?;
+        self.aliases.insert(alias, target);
+        Ok(resolved)
+    }
 
-        let candidate = id_or_alias.as_str().to_ascii_lowercase();
-        let scanned = self.scan_workbooks()?;
-        self.register_all(&scanned);
+    fn aliases(&self) -> Vec<(String, String)> {
+        self.aliases.entries()
+    }
 
-        for wb in scanned {
-            if candidate == wb.workbook_id.as_str().to_ascii_lowercase()
-                || candidate == wb.short_id.to_ascii_lowercase()
-                || candidate == wb.legacy_id.to_ascii_lowercase()
-            {
-                return Ok(wb.into_resolved());
-            }
+    #[cfg(not(target_arch = "wasm32"))]
+    fn watch_workspace(&self) -> Result<Arc<WorkspaceEventLog>> {
+        #[cfg(feature = "watch")]
+        {
+            PathWorkspaceRepository::watch_workspace(self)
+        }
+        #[cfg(not(feature = "watch"))]
+        {
+            Err(anyhow!(
+                "workspace watching needs a build with the `watch` feature"
+            ))
         }
-
-        Err(anyhow!("workbook id {} not found", id_or_alias.as_str()))
     }
 
     fn load_context(&self, resolved: &ResolvedWorkbookRef) -> Result<WorkbookContext> {
@@ -234,10 +286,190 @@ impl WorkbookRepository for PathWorkspaceRepository {
             WorkbookSource::Virtual(id) => Err(anyhow!(
                 "path workspace repository cannot load virtual workbook {id}"
             )),
+            WorkbookSource::Remote(url) => Err(anyhow!(
+                "path workspace repository cannot load remote workbook {url}"
+            )),
         }
     }
 }
 
+#[derive(Default)]
+struct WorkspaceIndex {
+    entries: RwLock<HashMap<WorkbookId, IndexedWorkbook>>,
+    alias_index: RwLock<HashMap<String, WorkbookId>>,
+    legacy_alias_index: RwLock<HashMap<String, WorkbookId>>,
+}
+
+impl WorkspaceIndex {
+    fn register(&self, located: &LocatedWorkbook) {
+        self.entries.write().insert(
+            located.workbook_id.clone(),
+            IndexedWorkbook {
+                path: located.path.clone(),
+                short_id: located.short_id.clone(),
+                revision_id: located.revision_id.clone(),
+            },
+        );
+
+        let mut aliases = self.alias_index.write();
+        aliases.insert(
+            located.short_id.to_ascii_lowercase(),
+            located.workbook_id.clone(),
+        );
+        aliases.insert(
+            located.workbook_id.as_str().to_ascii_lowercase(),
+            located.workbook_id.clone(),
+        );
+
+        self.legacy_alias_index.write().insert(
+            located.legacy_id.to_ascii_lowercase(),
+            located.workbook_id.clone(),
+        );
+    }
+
+    fn register_all(&self, located: &[LocatedWorkbook]) {
+        for entry in located {
+            self.register(entry);
+        }
+    }
+
+    fn lookup(&self, id_or_alias: &WorkbookId) -> Option<WorkbookId> {
+        if self.entries.read().contains_key(id_or_alias) {
+            return Some(id_or_alias.clone());
+        }
+
+        let lowered = id_or_alias.as_str().to_ascii_lowercase();
+        if let Some(id) = self.alias_index.read().get(&lowered).cloned() {
+            return Some(id);
+        }
+        self.legacy_alias_index.read().get(&lowered).cloned()
+    }
+
+    #[cfg(all(not(target_arch = "wasm32"), feature = "watch"))]
+    fn find_by_path(&self, path: &Path) -> Option<(WorkbookId, IndexedWorkbook)> {
+        self.entries
+            .read()
+            .iter()
+            .find(|(_, indexed)| indexed.path == path)
+            .map(|(id, indexed)| (id.clone(), indexed.clone()))
+    }
+
+    #[cfg(all(not(target_arch = "wasm32"), feature = "watch"))]
+    fn remove(&self, workbook_id: &WorkbookId) {
+        self.entries.write().remove(workbook_id);
+        self.alias_index.write().retain(|_, id| id != workbook_id);
+        self.legacy_alias_index
+            .write()
+            .retain(|_, id| id != workbook_id);
+    }
+}
+
+#[cfg(all(not(target_arch = "wasm32"), feature = "watch"))]
+struct WorkspaceWatch {
+    _watcher: RecommendedWatcher,
+    log: Arc<WorkspaceEventLog>,
+}
+
+#[cfg(all(not(target_arch = "wasm32"), feature = "watch"))]
+struct WatchHandler {
+    config: Arc<ServerConfig>,
+    index: Arc<WorkspaceIndex>,
+    log: Arc<WorkspaceEventLog>,
+}
+
+#[cfg(all(not(target_arch = "wasm32"), feature = "watch"))]
+impl WatchHandler {
+    fn handle(&self, event: Event) {
+        if matches!(event.kind, EventKind::Access(_)) {
+            return;
+        }
+        for path in event.paths {
+            if !has_supported_extension(&self.config.supported_extensions, &path) {
+                continue;
+            }
+            match self.config.single_workbook() {
+                // The parent directory is watched; only the configured file counts.
+                Some(single) if single.file_name() == path.file_name() => self.refresh(single),
+                Some(_) => {}
+                None => self.refresh(&path),
+            }
+        }
+    }
+
+    fn refresh(&self, path: &Path) {
+        let previous = self.index.find_by_path(path);
+        let relative = || {
+            path_to_forward_slashes(
+                path.strip_prefix(&self.config.workspace_root)
+                    .unwrap_or(path),
+            )
+        };
+
+        if !path.is_file() {
+            if let Some((workbook_id, indexed)) = previous {
+                self.index.remove(&workbook_id);
+                self.log.push(
+                    WorkspaceEventKind::Removed,
+                    workbook_id,
+                    indexed.short_id,
+                    relative(),
+                    None,
+                    indexed.revision_id,
+                );
+            }
+            return;
+        }
+
+        // A file still being written can fail to hash; the write's next
+        // event retries.
+        let Ok(located) = locate_by_path(&self.config, path) else {
+            return;
+        };
+        let (kind, previous_revision_id) = match previous {
+            None => (WorkspaceEventKind::Added, None),
+            Some((_, indexed)) if indexed.revision_id == located.revision_id => return,
+            Some((_, indexed)) => (WorkspaceEventKind::Changed, indexed.revision_id),
+        };
+        self.index.register(&located);
+        self.log.push(
+            kind,
+            located.workbook_id,
+            located.short_id,
+            relative(),
+            located.revision_id,
+            previous_revision_id,
+        );
+    }
+}
+
+fn locate_by_path(config: &ServerConfig, path: &Path) -> Result<LocatedWorkbook> {
+    let metadata = fs::metadata(path)?;
+    let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let workbook_id = WorkbookId(hash_path_identity(&canonical));
+    let legacy_id = hash_path_metadata(path, &metadata);
+    let slug = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "workbook".to_string());
+    let short_id = make_short_workbook_id(&slug, workbook_id.as_str());
+
+    Ok(LocatedWorkbook {
+        workbook_id,
+        short_id,
+        legacy_id,
+        slug,
+        folder: derive_folder(config, path),
+        path: path.to_path_buf(),
+        bytes: metadata.len(),
+        last_modified: metadata
+            .modified()
+            .ok()
+            .and_then(system_time_to_rfc3339)
+            .map(|dt| dt.to_rfc3339_opts(SecondsFormat::Secs, true)),
+        revision_id: Some(hash_file_sha256_hex(path)?),
+    })
+}
+
 struct LocatedWorkbook {
     workbook_id: WorkbookId,
     short_id: String,
@@ -268,7 +500,7 @@ struct IndexedWorkbook {
     revision_id: Option<String>,
 }
 
-fn derive_folder(config: &Arc<ServerConfig>, path: &Path) -> Option<String> {
+fn derive_folder(config: &ServerConfig, path: &Path) -> Option<String> {
     path.strip_prefix(&config.workspace_root)
         .ok()
         .and_then(|relative| relative.parent())
@@ -0,0 +1,598 @@
+//! Workbooks fetched from `https://` and `s3://` URLs.
+//!
+//! Downloads are cached on disk next to their ETag. Later loads revalidate
+//! with `If-None-Match` and reuse the cached copy on `304 Not Modified`, or
+//! when the server cannot be reached. An error status (a deleted object,
+//! revoked access) fails the load even with a cached copy. `s3://bucket/key`
+//! URLs are fetched over HTTPS and signed with SigV4 when `AWS_ACCESS_KEY_ID`
+//! and `AWS_SECRET_ACCESS_KEY` are set; otherwise the request is anonymous.
+
+use super::virtual_workspace::sanitize_slug;
+use super::{
+    ResolvedWorkbookRef, WorkbookRepository, WorkbookSource, is_remote_url, read_workbook_file,
+};
+use crate::caps::BackendCaps;
+use crate::config::ServerConfig;
+use crate::model::{WorkbookDescriptor, WorkbookId, WorkbookListResponse};
+use crate::tools::filters::WorkbookFilter;
+use crate::utils::{hash_bytes_sha256_hex, hash_path_identity, make_short_workbook_id};
+use crate::workbook::WorkbookContext;
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Overrides the directory remote downloads are cached in.
+pub const REMOTE_CACHE_DIR_ENV: &str = "SPREADSHEET_MCP_REMOTE_CACHE_DIR";
+
+const REMOTE_FETCH_TIMEOUT: Duration = Duration::from_secs(120);
+pub(crate) const EMPTY_PAYLOAD_SHA256: &str =
+    "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+/// A downloaded workbook as it sits in the cache.
+#[derive(Debug, Clone)]
+pub struct RemoteObject {
+    pub path: PathBuf,
+    pub etag: Option<String>,
+    /// The ETag without quotes or weak prefix, or the SHA-256 of the bytes
+    /// when the server sent none.
+    pub revision_id: String,
+    pub bytes: u64,
+    /// Served from the cache without downloading the body again.
+    pub from_cache: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheMeta {
+    url: String,
+    etag: Option<String>,
+    revision_id: String,
+    bytes: u64,
+    fetched_at: String,
+}
+
+enum FetchOutcome {
+    NotModified,
+    /// The request never got a response (connect error, timeout).
+    Unreachable(anyhow::Error),
+    Body {
+        bytes: Vec<u8>,
+        etag: Option<String>,
+    },
+}
+
+/// On-disk cache of remote workbooks, one data file plus metadata per URL.
+#[derive(Debug, Clone)]
+pub struct RemoteCache {
+    dir: PathBuf,
+}
+
+impl RemoteCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Cache in `$SPREADSHEET_MCP_REMOTE_CACHE_DIR`, or a directory under the
+    /// system temp dir.
+    pub fn from_env() -> Self {
+        let dir = std::env::var_os(REMOTE_CACHE_DIR_ENV)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| std::env::temp_dir().join("spreadsheet-kit-remote"));
+        Self::new(dir)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Download `url`, or revalidate and reuse the cached copy.
+    pub fn fetch(&self, url: &str) -> Result<RemoteObject> {
+        let (data_path, meta_path) = self.paths(url);
+        let cached = read_meta(&meta_path).filter(|_| data_path.is_file());
+        let if_none_match = cached.as_ref().and_then(|meta| meta.etag.clone());
+
+        match http_get(url, if_none_match)? {
+            FetchOutcome::Unreachable(err) => {
+                let Some(meta) = cached else {
+                    return Err(err);
+                };
+                tracing::warn!(url, error = %err, "remote unreachable; using cached copy");
+                Ok(cached_object(data_path, meta))
+            }
+            FetchOutcome::NotModified => {
+                let meta = cached.ok_or_else(|| {
+                    anyhow!("{url} answered 304 Not Modified without a cached copy")
+                })?;
+                Ok(cached_object(data_path, meta))
+            }
+            FetchOutcome::Body { bytes, etag } => {
+                let revision_id = etag
+                    .as_deref()
+                    .map(normalize_etag)
+                    .filter(|etag| !etag.is_empty())
+                    .unwrap_or_else(|| hash_bytes_sha256_hex(&bytes));
+                fs::create_dir_all(&self.dir)
+                    .with_context(|| format!("failed to create cache dir {:?}", self.dir))?;
+                let staging = data_path.with_extension("part");
+                fs::write(&staging, &bytes)
+                    .with_context(|| format!("failed to write {:?}", staging))?;
+                fs::rename(&staging, &data_path)
+                    .with_context(|| format!("failed to move download to {:?}", data_path))?;
+                let meta = CacheMeta {
+                    url: url.to_string(),
+                    etag: etag.clone(),
+                    revision_id: revision_id.clone(),
+                    bytes: bytes.len() as u64,
+                    fetched_at: Utc::now().to_rfc3339(),
+                };
+                fs::write(&meta_path, serde_json::to_vec_pretty(&meta)?)
+                    .with_context(|| format!("failed to write {:?}", meta_path))?;
+                Ok(RemoteObject {
+                    path: data_path,
+                    etag,
+                    revision_id,
+                    bytes: bytes.len() as u64,
+                    from_cache: false,
+                })
+            }
+        }
+    }
+
+    fn paths(&self, url: &str) -> (PathBuf, PathBuf) {
+        let digest = hash_bytes_sha256_hex(url.as_bytes());
+        let stem = &digest[..32];
+        let extension = remote_extension(url).unwrap_or("xlsx");
+        (
+            self.dir.join(format!("{stem}.{extension}")),
+            self.dir.join(format!("{stem}.json")),
+        )
+    }
+}
+
+fn read_meta(path: &Path) -> Option<CacheMeta> {
+    let contents = fs::read(path).ok()?;
+    serde_json::from_slice(&contents).ok()
+}
+
+fn cached_object(path: PathBuf, meta: CacheMeta) -> RemoteObject {
+    RemoteObject {
+        path,
+        etag: meta.etag,
+        revision_id: meta.revision_id,
+        bytes: meta.bytes,
+        from_cache: true,
+    }
+}
+
+/// Lowercased file extension of the URL path, ignoring query and fragment.
+fn remote_extension(url: &str) -> Option<&'static str> {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let name = path.rsplit('/').next()?;
+    let ext = name.rsplit_once('.')?.1.to_ascii_lowercase();
+    ["xlsx", "xlsm", "xls", "xlsb"]
+        .into_iter()
+        .find(|known| *known == ext)
+}
+
+fn normalize_etag(etag: &str) -> String {
+    etag.trim()
+        .trim_start_matches("W/")
+        .trim_matches('"')
+        .to_string()
+}
+
+fn http_get(url: &str, if_none_match: Option<String>) -> Result<FetchOutcome> {
+    let (target, headers) = build_request(url, Utc::now())?;
+    let display = url.to_string();
+    // The caller may already be inside a runtime, so the request gets its own
+    // thread and runtime.
+    std::thread::spawn(move || -> Result<FetchOutcome> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        runtime.block_on(async move {
+            let client = reqwest::Client::builder()
+                .timeout(REMOTE_FETCH_TIMEOUT)
+                .build()?;
+            let mut request = client.get(target);
+            for (name, value) in headers {
+                request = request.header(name, value);
+            }
+            if let Some(etag) = if_none_match {
+                request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+            }
+            let response = match request.send().await {
+                Ok(response) => response,
+                Err(err) => {
+                    return Ok(FetchOutcome::Unreachable(
+                        anyhow::Error::new(err).context(format!("failed to fetch {display}")),
+                    ));
+                }
+            };
+            let status = response.status();
+            if status == reqwest::StatusCode::NOT_MODIFIED {
+                return Ok(FetchOutcome::NotModified);
+            }
+            if !status.is_success() {
+                bail!("failed to fetch {display}: HTTP {status}");
+            }
+            let etag = response
+                .headers()
+                .get(reqwest::header::ETAG)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string);
+            let bytes = response
+                .bytes()
+                .await
+                .with_context(|| format!("failed to read body of {display}"))?;
+            Ok(FetchOutcome::Body {
+                bytes: bytes.to_vec(),
+                etag,
+            })
+        })
+    })
+    .join()
+    .map_err(|_| anyhow!("remote fetch thread panicked"))?
+}
+
+/// HTTP target and extra headers for `url`; `s3://` URLs are mapped to their
+/// HTTPS endpoint and signed when credentials are available.
+fn build_request(
+    url: &str,
+    now: DateTime<Utc>,
+) -> Result<(reqwest::Url, Vec<(&'static str, String)>)> {
+    let Some(location) = strip_scheme(url, "s3://") else {
+        let target = reqwest::Url::parse(url).with_context(|| format!("invalid URL '{url}'"))?;
+        return Ok((target, Vec::new()));
+    };
+
+    let (bucket, key) = location
+        .split_once('/')
+        .filter(|(bucket, key)| !bucket.is_empty() && !key.is_empty())
+        .ok_or_else(|| anyhow!("S3 URL '{url}' must look like s3://bucket/key"))?;
+    let region =
+        env_value(&["AWS_REGION", "AWS_DEFAULT_REGION"]).unwrap_or_else(|| "us-east-1".to_string());
+    let endpoint = env_value(&["AWS_ENDPOINT_URL_S3", "AWS_ENDPOINT_URL"]);
+    let target = s3_object_url(endpoint.as_deref(), &region, bucket, key)?;
+
+    let headers = match AwsCredentials::from_env(
+        "AWS_ACCESS_KEY_ID",
+        "AWS_SECRET_ACCESS_KEY",
+        Some("AWS_SESSION_TOKEN"),
+    ) {
+        Some(credentials) => sign_s3_request(
+            "GET",
+            &target,
+            EMPTY_PAYLOAD_SHA256,
+            &region,
+            &credentials,
+            now,
+        ),
+        None => Vec::new(),
+    };
+    Ok((target, headers))
+}
+
+fn strip_scheme<'a>(url: &'a str, scheme: &str) -> Option<&'a str> {
+    let prefix = url.get(..scheme.len())?;
+    prefix
+        .eq_ignore_ascii_case(scheme)
+        .then(|| &url[scheme.len()..])
+}
+
+pub(crate) fn env_value(names: &[&str]) -> Option<String> {
+    names
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .map(|value| value.trim().to_string())
+        .find(|value| !value.is_empty())
+}
+
+/// Virtual-hosted AWS URL, or a path-style URL under a custom endpoint
+/// (MinIO, LocalStack, R2, ...).
+pub(crate) fn s3_object_url(
+    endpoint: Option<&str>,
+    region: &str,
+    bucket: &str,
+    key: &str,
+) -> Result<reqwest::Url> {
+    let key = key.split('/').map(uri_encode).collect::<Vec<_>>().join("/");
+    let raw = match endpoint {
+        Some(endpoint) => format!(
+            "{}/{}/{key}",
+            endpoint.trim_end_matches('/'),
+            uri_encode(bucket)
+        ),
+        None => format!("https://{bucket}.s3.{region}.amazonaws.com/{key}"),
+    };
+    reqwest::Url::parse(&raw).with_context(|| format!("invalid S3 object URL '{raw}'"))
+}
+
+/// SigV4 URI encoding: everything but unreserved characters is escaped.
+pub(crate) fn uri_encode(segment: &str) -> String {
+    let mut out = String::with_capacity(segment.len());
+    for byte in segment.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    out
+}
+
+pub(crate) struct AwsCredentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+impl AwsCredentials {
+    /// Credentials from the named environment variables, if both keys are set.
+    pub(crate) fn from_env(
+        access_key_var: &str,
+        secret_key_var: &str,
+        session_token_var: Option<&str>,
+    ) -> Option<Self> {
+        Some(Self {
+            access_key_id: env_value(&[access_key_var])?,
+            secret_access_key: env_value(&[secret_key_var])?,
+            session_token: session_token_var.and_then(|var| env_value(&[var])),
+        })
+    }
+}
+
+/// Headers that authenticate a `method` request to `url` whose body hashes to
+/// `payload_sha256` with AWS Signature Version 4.
+pub(crate) fn sign_s3_request(
+    method: &str,
+    url: &reqwest::Url,
+    payload_sha256: &str,
+    region: &str,
+    credentials: &AwsCredentials,
+    now: DateTime<Utc>,
+) -> Vec<(&'static str, String)> {
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+    let host = match url.port() {
+        Some(port) => format!("{}:{port}", url.host_str().unwrap_or_default()),
+        None => url.host_str().unwrap_or_default().to_string(),
+    };
+
+    // Already in the lexicographic order SigV4 requires.
+    let mut headers = vec![
+        ("host", host),
+        ("x-amz-content-sha256", payload_sha256.to_string()),
+        ("x-amz-date", amz_date.clone()),
+    ];
+    if let Some(token) = &credentials.session_token {
+        headers.push(("x-amz-security-token", token.clone()));
+    }
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(";");
+    let canonical_headers = headers
+        .iter()
+        .map(|(name, value)| format!("{name}:{}\n", value.trim()))
+        .collect::<String>();
+    let canonical_request = format!(
+        "{method}\n{}\n{}\n{canonical_headers}\n{signed_headers}\n{payload_sha256}",
+        url.path(),
+        url.query().unwrap_or_default(),
+    );
+
+    let scope = format!("{date}/{region}/s3/aws4_request");
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+        hash_bytes_sha256_hex(canonical_request.as_bytes())
+    );
+    let mut key = hmac_sha256(
+        format!("AWS4{}", credentials.secret_access_key).as_bytes(),
+        date.as_bytes(),
+    );
+    for part in [region, "s3", "aws4_request"] {
+        key = hmac_sha256(&key, part.as_bytes());
+    }
+    let signature = hmac_sha256(&key, string_to_sign.as_bytes())
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+
+    // reqwest sets Host itself from the URL.
+    headers.retain(|(name, _)| *name != "host");
+    headers.push((
+        "authorization",
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+            credentials.access_key_id
+        ),
+    ));
+    headers
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+#[derive(Debug, Clone)]
+struct RemoteWorkbook {
+    url: String,
+    slug: String,
+    workbook_id: WorkbookId,
+    short_id: String,
+    revision_id: Option<String>,
+    bytes: u64,
+}
+
+/// Repository over registered remote URLs. Listing and resolving never touch
+/// the network; the workbook is fetched (or revalidated) when it is loaded.
+pub struct RemoteWorkspaceRepository {
+    config: Arc<ServerConfig>,
+    cache: RemoteCache,
+    entries: RwLock<HashMap<WorkbookId, RemoteWorkbook>>,
+    alias_index: RwLock<HashMap<String, WorkbookId>>,
+}
+
+impl RemoteWorkspaceRepository {
+    pub fn new(config: Arc<ServerConfig>, cache: RemoteCache) -> Self {
+        Self {
+            config,
+            cache,
+            entries: RwLock::new(HashMap::new()),
+            alias_index: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Register `url`; registering the same URL again returns the same id.
+    pub fn register(&self, url: &str) -> Result<WorkbookId> {
+        let url = url.trim();
+        if !is_remote_url(url) {
+            bail!("'{url}' is not an https://, http:// or s3:// URL");
+        }
+        let workbook_id = WorkbookId(hash_path_identity(Path::new(url)));
+        if self.entries.read().contains_key(&workbook_id) {
+            return Ok(workbook_id);
+        }
+
+        let name = url
+            .split(['?', '#'])
+            .next()
+            .unwrap_or(url)
+            .rsplit('/')
+            .next()
+            .unwrap_or_default();
+        let stem = name.rsplit_once('.').map_or(name, |(stem, _)| stem);
+        let slug = Some(sanitize_slug(stem))
+            .filter(|slug| !slug.is_empty())
+            .unwrap_or_else(|| "remote".to_string());
+        let short_id = make_short_workbook_id(&slug, workbook_id.as_str());
+
+        {
+            let mut aliases = self.alias_index.write();
+            aliases.insert(short_id.to_ascii_lowercase(), workbook_id.clone());
+            aliases.insert(url.to_ascii_lowercase(), workbook_id.clone());
+        }
+        self.entries.write().insert(
+            workbook_id.clone(),
+            RemoteWorkbook {
+                url: url.to_string(),
+                slug,
+                workbook_id: workbook_id.clone(),
+                short_id,
+                revision_id: None,
+                bytes: 0,
+            },
+        );
+        Ok(workbook_id)
+    }
+
+    fn lookup(&self, id_or_alias: &WorkbookId) -> Option<RemoteWorkbook> {
+        if let Some(entry) = self.entries.read().get(id_or_alias) {
+            return Some(entry.clone());
+        }
+
+        let lowered = id_or_alias.as_str().trim().to_ascii_lowercase();
+        let id = self.alias_index.read().get(&lowered).cloned()?;
+        self.entries.read().get(&id).cloned()
+    }
+
+    fn fetch(&self, workbook_id: &WorkbookId, url: &str) -> Result<RemoteObject> {
+        let object = self.cache.fetch(url)?;
+        if let Some(entry) = self.entries.write().get_mut(workbook_id) {
+            entry.revision_id = Some(object.revision_id.clone());
+            entry.bytes = object.bytes;
+        }
+        Ok(object)
+    }
+}
+
+impl WorkbookRepository for RemoteWorkspaceRepository {
+    fn list(&self, filter: &WorkbookFilter) -> Result<WorkbookListResponse> {
+        let mut workbooks = Vec::new();
+        for entry in self.entries.read().values() {
+            if !filter.matches(&entry.slug, None, Path::new(&entry.url)) {
+                continue;
+            }
+
+            workbooks.push(WorkbookDescriptor {
+                workbook_id: entry.workbook_id.clone(),
+                short_id: entry.short_id.clone(),
+                slug: entry.slug.clone(),
+                folder: None,
+                path: Some(entry.url.clone()),
+                client_path: None,
+                bytes: entry.bytes,
+                last_modified: None,
+                revision_id: entry.revision_id.clone(),
+                caps: Some(BackendCaps::xlsx()),
+            });
+        }
+
+        workbooks.sort_by(|a, b| a.slug.cmp(&b.slug));
+
+        Ok(WorkbookListResponse {
+            workbooks,
+            next_offset: None,
+        })
+    }
+
+    fn resolve(&self, id_or_alias: &WorkbookId) -> Result<ResolvedWorkbookRef> {
+        let entry = match self.lookup(id_or_alias) {
+            Some(entry) => entry,
+            None if is_remote_url(id_or_alias.as_str()) => {
+                let workbook_id = self.register(id_or_alias.as_str())?;
+                self.lookup(&workbook_id)
+                    .ok_or_else(|| anyhow!("workbook id {} not found", workbook_id.as_str()))?
+            }
+            None => return Err(anyhow!("workbook id {} not found", id_or_alias.as_str())),
+        };
+
+        Ok(ResolvedWorkbookRef {
+            workbook_id: entry.workbook_id,
+            short_id: entry.short_id,
+            revision_id: entry.revision_id,
+            source: WorkbookSource::Remote(entry.url),
+        })
+    }
+
+    fn load_context(&self, resolved: &ResolvedWorkbookRef) -> Result<WorkbookContext> {
+        let WorkbookSource::Remote(url) = &resolved.source else {
+            return Err(anyhow!(
+                "remote repository cannot load workbook {}",
+                resolved.workbook_id.as_str()
+            ));
+        };
+        let object = self.fetch(&resolved.workbook_id, url)?;
+        WorkbookContext::load_from_path(
+            &self.config,
+            &object.path,
+            resolved.workbook_id.clone(),
+            resolved.short_id.clone(),
+            Some(object.revision_id),
+        )
+    }
+
+    fn workbook_bytes(&self, resolved: &ResolvedWorkbookRef) -> Result<Arc<Vec<u8>>> {
+        match &resolved.source {
+            WorkbookSource::Remote(url) => {
+                let object = self.fetch(&resolved.workbook_id, url)?;
+                read_workbook_file(&object.path)
+            }
+            WorkbookSource::Path(path) => read_workbook_file(path),
+            WorkbookSource::Virtual(key) => Err(anyhow!(
+                "virtual workbook {key} is not held by this repository"
+            )),
+        }
+    }
+}
@@ -1,16 +1,22 @@
-use super::{ResolvedWorkbookRef, WorkbookRepository, WorkbookSource};
+use super::alias::{AliasTable, validate_alias_name};
+use super::{ResolvedWorkbookRef, WorkbookRepository, WorkbookSource, read_workbook_file};
 use crate::caps::BackendCaps;
 use crate::config::ServerConfig;
+#[cfg(feature = "recalc")]
+use crate::fork::ForkRegistry;
 use crate::model::{WorkbookDescriptor, WorkbookId, WorkbookListResponse};
 use crate::tools::filters::WorkbookFilter;
 use crate::utils::{hash_bytes_sha256_hex, hash_path_identity, make_short_workbook_id};
 use crate::workbook::WorkbookContext;
-use anyhow::{Result, anyhow};
-use parking_lot::RwLock;
+use anyhow::{Result, anyhow, bail};
+use parking_lot::{Mutex, RwLock};
 use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Arc;
 
+/// Largest workbook accepted through a chunked upload.
+pub const MAX_UPLOAD_BYTES: u64 = 100 * 1024 * 1024; // 100MB
+
 #[derive(Debug, Clone)]
 pub struct VirtualWorkbookInput {
     pub key: String,
@@ -32,21 +38,41 @@ pub struct VirtualWorkspaceRepository {
     config: Arc<ServerConfig>,
     entries: RwLock<HashMap<WorkbookId, VirtualWorkbook>>,
     alias_index: RwLock<HashMap<String, WorkbookId>>,
+    aliases: AliasTable,
+    #[cfg(feature = "recalc")]
+    fork_registry: Option<Arc<ForkRegistry>>,
 }
 
 impl VirtualWorkspaceRepository {
     pub fn new(config: Arc<ServerConfig>) -> Self {
         Self {
+            aliases: AliasTable::new(&config.workbook_aliases),
             config,
             entries: RwLock::new(HashMap::new()),
             alias_index: RwLock::new(HashMap::new()),
+            #[cfg(feature = "recalc")]
+            fork_registry: None,
         }
     }
 
+    /// Also resolve fork ids, so read tools can inspect forks of uploaded
+    /// workbooks.
+    #[cfg(feature = "recalc")]
+    pub fn with_fork_registry(mut self, fork_registry: Option<Arc<ForkRegistry>>) -> Self {
+        self.fork_registry = fork_registry;
+        self
+    }
+
+    /// Register `input`, replacing the workbook already stored under the same
+    /// key. A replacement without a slug keeps the previous one.
     pub fn register(&self, input: VirtualWorkbookInput) -> WorkbookId {
         let key = input.key;
-        let slug = input.slug.unwrap_or_else(|| sanitize_slug(&key));
         let workbook_id = WorkbookId(hash_path_identity(Path::new(&format!("virtual/{key}"))));
+        let previous = self.entries.read().get(&workbook_id).cloned();
+        let slug = input
+            .slug
+            .or_else(|| previous.as_ref().map(|entry| entry.slug.clone()))
+            .unwrap_or_else(|| sanitize_slug(&key));
         let short_id = make_short_workbook_id(&slug, workbook_id.as_str());
         let revision_id = hash_bytes_sha256_hex(&input.bytes);
         let entry = VirtualWorkbook {
@@ -60,6 +86,9 @@ impl VirtualWorkspaceRepository {
 
         self.entries.write().insert(workbook_id.clone(), entry);
         let mut aliases = self.alias_index.write();
+        if let Some(previous) = previous {
+            aliases.remove(&previous.short_id.to_ascii_lowercase());
+        }
         aliases.insert(key.to_ascii_lowercase(), workbook_id.clone());
         aliases.insert(short_id.to_ascii_lowercase(), workbook_id.clone());
         aliases.insert(
@@ -69,6 +98,11 @@ impl VirtualWorkspaceRepository {
         workbook_id
     }
 
+    /// Stored bytes of a registered workbook.
+    pub fn bytes(&self, id_or_alias: &WorkbookId) -> Option<Arc<Vec<u8>>> {
+        self.lookup(id_or_alias).map(|entry| entry.bytes)
+    }
+
     fn lookup(&self, id_or_alias: &WorkbookId) -> Option<VirtualWorkbook> {
         if let Some(entry) = self.entries.read().get(id_or_alias) {
             return Some(entry.clone());
@@ -112,6 +146,23 @@ impl WorkbookRepository for VirtualWorkspaceRepository {
     }
 
     fn resolve(&self, id_or_alias: &WorkbookId) -> Result<ResolvedWorkbookRef> {
+        // Alias targets are keys or ids of uploaded workbooks. An alias may
+        // be configured before its workbook is uploaded.
+        let target = self.aliases.target(id_or_alias.as_str()).map(WorkbookId);
+        let id_or_alias = target.as_ref().unwrap_or(id_or_alias);
+
+        #[cfg(feature = "recalc")]
+        if let Some(registry) = &self.fork_registry
+            && let Some(path) = registry.get_fork_path(id_or_alias.as_str())
+        {
+            return Ok(ResolvedWorkbookRef {
+                workbook_id: id_or_alias.clone(),
+                short_id: make_short_workbook_id("fork", id_or_alias.as_str()),
+                revision_id: Some(crate::utils::hash_file_sha256_hex(&path)?),
+                source: WorkbookSource::Path(path),
+            });
+        }
+
         let Some(entry) = self.lookup(id_or_alias) else {
             return Err(anyhow!("workbook id {} not found", id_or_alias.as_str()));
         };
@@ -125,11 +176,16 @@ impl WorkbookRepository for VirtualWorkspaceRepository {
     }
 
     fn load_context(&self, resolved: &ResolvedWorkbookRef) -> Result<WorkbookContext> {
-        let WorkbookSource::Virtual(_) = &resolved.source else {
-            return Err(anyhow!(
-                "virtual repository cannot load non-virtual workbook"
-            ));
-        };
+        if let WorkbookSource::Path(path) = &resolved.source {
+            // Only forks resolve to a path here.
+            return WorkbookContext::load_from_path(
+                &self.config,
+                path,
+                resolved.workbook_id.clone(),
+                resolved.short_id.clone(),
+                resolved.revision_id.clone(),
+            );
+        }
 
         let entry = self
             .entries
@@ -147,9 +203,88 @@ impl WorkbookRepository for VirtualWorkspaceRepository {
             resolved.revision_id.clone(),
         )
     }
+
+    fn register_workbook(&self, input: VirtualWorkbookInput) -> Result<ResolvedWorkbookRef> {
+        let workbook_id = self.register(input);
+        self.resolve(&workbook_id)
+    }
+
+    fn register_alias(&self, alias: &str, target: &str) -> Result<ResolvedWorkbookRef> {
+        validate_alias_name(alias)?;
+        if self.aliases.target(target).is_some() {
+            return Err(anyhow!(
+                "'{target}' is itself an alias; point '{alias}' at its target instead"
+            ));
+        }
+        let target_id = WorkbookId(target.to_string());
+        let resolved = self.resolve(&target_id)?;
+        self.aliases.insert(alias, target);
+        Ok(resolved)
+    }
+
+    fn aliases(&self) -> Vec<(String, String)> {
+        self.aliases.entries()
+    }
+
+    fn workbook_bytes(&self, resolved: &ResolvedWorkbookRef) -> Result<Arc<Vec<u8>>> {
+        match &resolved.source {
+            WorkbookSource::Path(path) => read_workbook_file(path),
+            WorkbookSource::Virtual(_) => self
+                .bytes(&resolved.workbook_id)
+                .ok_or_else(|| anyhow!("virtual workbook {} not found", resolved.workbook_id.0)),
+            WorkbookSource::Remote(url) => Err(anyhow!(
+                "virtual workspace repository cannot load remote workbook {url}"
+            )),
+        }
+    }
+}
+
+/// Partially received uploads keyed by destination key. Chunks must arrive in
+/// order, each stating the byte offset it starts at; offset 0 restarts the
+/// upload.
+#[derive(Default)]
+pub struct UploadStaging {
+    pending: Mutex<HashMap<String, Vec<u8>>>,
+}
+
+impl UploadStaging {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append `chunk` at `offset` and return the number of bytes received so
+    /// far.
+    pub fn append(&self, key: &str, offset: u64, chunk: &[u8]) -> Result<u64> {
+        let mut pending = self.pending.lock();
+        if offset == 0 {
+            pending.remove(key);
+        }
+        let received = pending.get(key).map_or(0, |buffer| buffer.len() as u64);
+        if offset != received {
+            bail!("upload '{key}' expects the next chunk at offset {received} (got {offset})");
+        }
+        let total = received + chunk.len() as u64;
+        if total > MAX_UPLOAD_BYTES {
+            pending.remove(key);
+            bail!(
+                "upload '{key}' exceeds the {} MB limit",
+                MAX_UPLOAD_BYTES / 1024 / 1024
+            );
+        }
+        pending
+            .entry(key.to_string())
+            .or_default()
+            .extend_from_slice(chunk);
+        Ok(total)
+    }
+
+    /// Remove and return everything received for `key`.
+    pub fn take(&self, key: &str) -> Option<Vec<u8>> {
+        self.pending.lock().remove(key)
+    }
 }
 
-fn sanitize_slug(value: &str) -> String {
+pub(super) fn sanitize_slug(value: &str) -> String {
     let mut out = value
         .chars()
         .map(|ch| {
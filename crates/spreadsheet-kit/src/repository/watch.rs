@@ -0,0 +1,132 @@
+use crate::model::{WorkbookId, WorkspaceEvent, WorkspaceEventKind};
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// Events retained for late readers; older ones are dropped and reported as
+/// `missed_events`.
+pub const WORKSPACE_EVENT_CAPACITY: usize = 512;
+
+/// Bounded, sequence-numbered log of workspace changes fed by a repository
+/// watcher. Readers poll with the `next_seq` of their previous read.
+pub struct WorkspaceEventLog {
+    inner: Mutex<EventLogInner>,
+    notify: Notify,
+}
+
+struct EventLogInner {
+    events: VecDeque<WorkspaceEvent>,
+    next_seq: u64,
+}
+
+/// Result of reading the log from a sequence number.
+#[derive(Debug, Clone)]
+pub struct WorkspaceEventBatch {
+    pub events: Vec<WorkspaceEvent>,
+    pub next_seq: u64,
+    pub missed_events: bool,
+}
+
+impl Default for WorkspaceEventLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WorkspaceEventLog {
+    pub fn new() -> Self {
+        Self {
+            inner: Mutex::new(EventLogInner {
+                events: VecDeque::new(),
+                next_seq: 1,
+            }),
+            notify: Notify::new(),
+        }
+    }
+
+    pub fn push(
+        &self,
+        kind: WorkspaceEventKind,
+        workbook_id: WorkbookId,
+        short_id: String,
+        path: String,
+        revision_id: Option<String>,
+        previous_revision_id: Option<String>,
+    ) -> u64 {
+        let seq = {
+            let mut inner = self.inner.lock();
+            let seq = inner.next_seq;
+            inner.next_seq += 1;
+            if inner.events.len() == WORKSPACE_EVENT_CAPACITY {
+                inner.events.pop_front();
+            }
+            inner.events.push_back(WorkspaceEvent {
+                seq,
+                kind,
+                workbook_id,
+                short_id,
+                path,
+                revision_id,
+                previous_revision_id,
+            });
+            seq
+        };
+        self.notify.notify_waiters();
+        seq
+    }
+
+    /// Sequence number the next event will receive.
+    pub fn next_seq(&self) -> u64 {
+        self.inner.lock().next_seq
+    }
+
+    /// Events with `seq >= since_seq`, oldest first, at most `limit`.
+    pub fn read_since(&self, since_seq: u64, limit: usize) -> WorkspaceEventBatch {
+        let inner = self.inner.lock();
+        let oldest = inner
+            .events
+            .front()
+            .map(|event| event.seq)
+            .unwrap_or(inner.next_seq);
+        let events: Vec<WorkspaceEvent> = inner
+            .events
+            .iter()
+            .filter(|event| event.seq >= since_seq)
+            .take(limit)
+            .cloned()
+            .collect();
+        let next_seq = events
+            .last()
+            .map(|event| event.seq + 1)
+            .unwrap_or_else(|| since_seq.clamp(oldest, inner.next_seq));
+        WorkspaceEventBatch {
+            events,
+            next_seq,
+            // Sequence numbers start at 1, so anything below `oldest` beyond
+            // that was evicted by the capacity bound.
+            missed_events: oldest > 1 && since_seq < oldest,
+        }
+    }
+
+    /// Like [`read_since`](Self::read_since), but waits up to `timeout` for
+    /// the first event when none is pending.
+    pub async fn wait_since(
+        &self,
+        since_seq: u64,
+        limit: usize,
+        timeout: Duration,
+    ) -> WorkspaceEventBatch {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let notified = self.notify.notified();
+            let batch = self.read_since(since_seq, limit);
+            if !batch.events.is_empty() || tokio::time::Instant::now() >= deadline {
+                return batch;
+            }
+            if tokio::time::timeout_at(deadline, notified).await.is_err() {
+                return self.read_since(since_seq, limit);
+            }
+        }
+    }
+}
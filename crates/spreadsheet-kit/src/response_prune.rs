@@ -51,6 +51,7 @@ fn preserve_empty_array_for_key(key: &str) -> bool {
             | "adjacent_sum_targets"
             | "merged_ranges_fully_contained"
             | "merged_ranges_crossing_boundary"
+            | "children"
     )
 }
 
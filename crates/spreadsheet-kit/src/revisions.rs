@@ -0,0 +1,283 @@
+//! Revision history kept next to a workbook.
+//!
+//! `--output auto` writes `<stem>.rev-<n>.<ext>` beside the source workbook,
+//! numbering revisions from 1, and appends a record to `<stem>.revisions.json`
+//! in the same directory. Editing a revision with `--output auto` produces the
+//! next revision of the same history, with the edited revision as its parent.
+//! Revision 0 always names the base workbook itself.
+
+use crate::utils::hash_file_sha256_hex;
+use anyhow::{Context, Result, anyhow, bail};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// `--output` value that selects the next revision path.
+pub const AUTO_OUTPUT: &str = "auto";
+
+const REVISION_MARKER: &str = ".rev-";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RevisionManifest {
+    /// File name of the base workbook (revision 0).
+    pub base_file: String,
+    /// Recorded revisions, oldest first.
+    pub revisions: Vec<RevisionRecord>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevisionRecord {
+    pub revision: u32,
+    /// Name of the revision workbook, in the base workbook's directory.
+    pub file_name: String,
+    /// Revision the write started from; 0 is the base workbook.
+    pub parent: u32,
+    /// RFC 3339 UTC time the revision was written.
+    pub created_at: String,
+    /// Command that wrote the revision.
+    pub command: String,
+    pub size_bytes: u64,
+    /// SHA-256 of the revision as written.
+    pub sha256: String,
+}
+
+/// The revision history a workbook path belongs to.
+#[derive(Debug, Clone)]
+pub struct RevisionHistory {
+    dir: PathBuf,
+    stem: String,
+    extension: String,
+}
+
+impl RevisionHistory {
+    /// History of `path`, plus the revision `path` itself is (0 for the base
+    /// workbook).
+    pub fn of(path: &Path) -> Result<(Self, u32)> {
+        let dir = path
+            .parent()
+            .ok_or_else(|| anyhow!("'{}' has no parent directory", path.display()))?;
+        let dir = fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+        let stem = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .ok_or_else(|| anyhow!("'{}' has no usable file name", path.display()))?;
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or("xlsx")
+            .to_string();
+
+        let (stem, revision) = match parse_revision_stem(stem) {
+            Some((base, revision)) => (base, revision),
+            None => (stem, 0),
+        };
+        Ok((
+            Self {
+                dir,
+                stem: stem.to_string(),
+                extension,
+            },
+            revision,
+        ))
+    }
+
+    pub fn base_path(&self) -> PathBuf {
+        self.dir.join(format!("{}.{}", self.stem, self.extension))
+    }
+
+    /// Path of `revision`; revision 0 is the base workbook.
+    pub fn revision_path(&self, revision: u32) -> PathBuf {
+        if revision == 0 {
+            return self.base_path();
+        }
+        self.dir.join(self.revision_file_name(revision))
+    }
+
+    pub fn manifest_path(&self) -> PathBuf {
+        self.dir.join(format!("{}.revisions.json", self.stem))
+    }
+
+    fn revision_file_name(&self, revision: u32) -> String {
+        format!(
+            "{}{REVISION_MARKER}{revision}.{}",
+            self.stem, self.extension
+        )
+    }
+
+    /// The manifest, or an empty one when no revision has been recorded yet.
+    pub fn manifest(&self) -> Result<RevisionManifest> {
+        let path = self.manifest_path();
+        let bytes = match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(RevisionManifest {
+                    base_file: format!("{}.{}", self.stem, self.extension),
+                    revisions: Vec::new(),
+                });
+            }
+            Err(error) => {
+                return Err(error).with_context(|| format!("unable to read '{}'", path.display()));
+            }
+        };
+        serde_json::from_slice(&bytes)
+            .with_context(|| format!("revision manifest '{}' is not valid", path.display()))
+    }
+
+    /// Number the next revision will get: one past the highest revision either
+    /// recorded in the manifest or present on disk.
+    pub fn next_revision(&self) -> Result<u32> {
+        let recorded = self
+            .manifest()?
+            .revisions
+            .iter()
+            .map(|record| record.revision)
+            .max()
+            .unwrap_or(0);
+        let entries = fs::read_dir(&self.dir)
+            .with_context(|| format!("unable to read '{}'", self.dir.display()))?;
+        let mut on_disk = 0;
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some(self.extension.as_str()) {
+                continue;
+            }
+            if let Some((stem, revision)) = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(parse_revision_stem)
+                && stem == self.stem
+            {
+                on_disk = on_disk.max(revision);
+            }
+        }
+        Ok(recorded.max(on_disk) + 1)
+    }
+
+    /// Record the workbook already written at `revision` in the manifest.
+    pub fn record(&self, revision: u32, parent: u32, command: &str) -> Result<RevisionRecord> {
+        let path = self.revision_path(revision);
+        let sha256 = hash_file_sha256_hex(&path)
+            .with_context(|| format!("unable to read '{}'", path.display()))?;
+        let size_bytes = fs::metadata(&path)?.len();
+        let record = RevisionRecord {
+            revision,
+            file_name: self.revision_file_name(revision),
+            parent,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            command: command.to_string(),
+            size_bytes,
+            sha256,
+        };
+
+        let mut manifest = self.manifest()?;
+        manifest
+            .revisions
+            .retain(|existing| existing.revision != revision);
+        manifest.revisions.push(record.clone());
+        manifest.revisions.sort_by_key(|existing| existing.revision);
+        self.write_manifest(&manifest)?;
+        Ok(record)
+    }
+
+    /// Copy `source` to the next revision and record it.
+    pub fn copy_to_next_revision(
+        &self,
+        source: &Path,
+        parent: u32,
+        command: &str,
+    ) -> Result<RevisionRecord> {
+        let revision = self.next_revision()?;
+        let path = self.revision_path(revision);
+        if path.exists() {
+            bail!("revision '{}' already exists", path.display());
+        }
+        let staging_path = self.dir.join(format!(
+            ".{}.{}.tmp",
+            self.revision_file_name(revision),
+            std::process::id()
+        ));
+        fs::copy(source, &staging_path)
+            .and_then(|_| fs::rename(&staging_path, &path))
+            .with_context(|| format!("unable to write revision '{}'", path.display()))?;
+        self.record(revision, parent, command)
+    }
+
+    /// Path of `revision`, checking that a recorded revision still matches its
+    /// recorded hash unless `allow_modified` is set.
+    pub fn load(&self, revision: u32, allow_modified: bool) -> Result<PathBuf> {
+        let path = self.revision_path(revision);
+        if !path.is_file() {
+            bail!(
+                "revision {} ('{}') not found; run `revisions list` to see available revisions",
+                revision,
+                path.display()
+            );
+        }
+        if revision == 0 || allow_modified {
+            return Ok(path);
+        }
+        if let Some(record) = self
+            .manifest()?
+            .revisions
+            .into_iter()
+            .find(|record| record.revision == revision)
+            && hash_file_sha256_hex(&path)? != record.sha256
+        {
+            bail!(
+                "revision {} ('{}') was modified after it was written",
+                revision,
+                path.display()
+            );
+        }
+        Ok(path)
+    }
+
+    fn write_manifest(&self, manifest: &RevisionManifest) -> Result<()> {
+        let path = self.manifest_path();
+        let staging_path = self.dir.join(format!(
+            ".{}.revisions.json.{}.tmp",
+            self.stem,
+            std::process::id()
+        ));
+        fs::write(&staging_path, serde_json::to_vec_pretty(manifest)?)
+            .and_then(|()| fs::rename(&staging_path, &path))
+            .with_context(|| format!("unable to write revision manifest '{}'", path.display()))
+    }
+}
+
+/// Path `--output auto` resolves to for a write from `source`.
+pub fn next_revision_path(source: &Path) -> Result<PathBuf> {
+    let (history, _) = RevisionHistory::of(source)?;
+    Ok(history.revision_path(history.next_revision()?))
+}
+
+/// Record `target` in the manifest when it is a revision path in the history
+/// `source` belongs to; other targets are left alone.
+pub fn record_revision(
+    source: &Path,
+    target: &Path,
+    command: &str,
+) -> Result<Option<RevisionRecord>> {
+    let (history, parent) = RevisionHistory::of(source)?;
+    let (target_history, revision) = RevisionHistory::of(target)?;
+    if revision == 0
+        || target_history.dir != history.dir
+        || target_history.stem != history.stem
+        || target_history.extension != history.extension
+    {
+        return Ok(None);
+    }
+    history.record(revision, parent, command).map(Some)
+}
+
+/// Split `<stem>.rev-<n>` into `(<stem>, n)` for n >= 1.
+fn parse_revision_stem(stem: &str) -> Option<(&str, u32)> {
+    let (base, revision) = stem.rsplit_once(REVISION_MARKER)?;
+    if base.is_empty() || !revision.chars().all(|ch| ch.is_ascii_digit()) {
+        return None;
+    }
+    match revision.parse::<u32>() {
+        Ok(revision) if revision > 0 => Some((base, revision)),
+        _ => None,
+    }
+}
@@ -1,9 +1,17 @@
-use crate::config::{OutputProfile, RecalcBackendKind, ServerConfig, TransportKind};
+use crate::config::{
+    ExecutorStrategy, OutputProfile, RecalcBackendKind, ServerConfig, TransportKind,
+};
 use crate::core;
 use crate::core::types::{CellEdit, RecalculateOutcome};
 use crate::model::WorkbookId;
+use crate::parse_cache::PARSE_CACHE_DIR_ENV;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::repository::is_remote_url;
+#[cfg(all(not(target_arch = "wasm32"), feature = "remote"))]
+use crate::repository::{RemoteCache, RemoteWorkspaceRepository};
 use crate::state::AppState;
 use crate::tools::filters::WorkbookFilter;
+use crate::tools::value_locale::ValueLocale;
 use anyhow::{Result, anyhow};
 use serde_json::Value;
 use std::fs;
@@ -26,8 +34,14 @@ impl StatelessRuntime {
         fs::copy(source, dest).map_err(Into::into)
     }
 
-    pub fn apply_edits(&self, path: &Path, sheet_name: &str, edits: &[CellEdit]) -> Result<()> {
-        core::write::apply_edits_to_file(path, sheet_name, edits)
+    pub fn apply_edits(
+        &self,
+        path: &Path,
+        sheet_name: &str,
+        edits: &[CellEdit],
+        locale: Option<&ValueLocale>,
+    ) -> Result<()> {
+        core::write::apply_edits_to_file(path, sheet_name, edits, locale)
     }
 
     pub fn diff_json(&self, original: &Path, modified: &Path) -> Result<Value> {
@@ -35,21 +49,72 @@ impl StatelessRuntime {
     }
 
     pub async fn recalculate_file(&self, path: &Path) -> Result<RecalculateOutcome> {
+        self.recalculate_file_with_engine(path, None, None, ExecutorStrategy::default())
+            .await
+    }
+
+    /// Recalculate with an explicit engine, or the `SPREADSHEET_MCP_RECALC_BACKEND`
+    /// preference (default `auto`) when `engine` is `None`. `timeout_ms` defaults
+    /// to 30 seconds; `strategy` only applies to the LibreOffice engine.
+    pub async fn recalculate_file_with_engine(
+        &self,
+        path: &Path,
+        engine: Option<RecalcBackendKind>,
+        timeout_ms: Option<u64>,
+        strategy: ExecutorStrategy,
+    ) -> Result<RecalculateOutcome> {
+        #[cfg(not(feature = "recalc"))]
+        {
+            let _ = (path, engine, timeout_ms, strategy);
+            core::recalc::unavailable()?;
+            unreachable!();
+        }
+
+        #[cfg(feature = "recalc")]
+        {
+            let backend = match engine {
+                Some(engine) => core::recalc::select_backend(engine, strategy)?,
+                None => core::recalc::select_backend_from_env()?,
+            };
+            core::recalc::execute_with_backend(path, timeout_ms.or(Some(30_000)), backend).await
+        }
+    }
+
+    /// Recalculate only `targets` (1-based `(sheet, row, col)` formula cells)
+    /// with the built-in engine.
+    pub async fn recalculate_cells(
+        &self,
+        path: &Path,
+        targets: Vec<(String, u32, u32)>,
+    ) -> Result<RecalculateOutcome> {
         #[cfg(not(feature = "recalc"))]
         {
-            let _ = path;
+            let _ = (path, targets);
             core::recalc::unavailable()?;
             unreachable!();
         }
 
         #[cfg(feature = "recalc")]
         {
-            let backend = core::recalc::select_backend_from_env()?;
-            core::recalc::execute_with_backend(path, Some(30_000), backend).await
+            core::recalc::execute_partial(path, targets).await
         }
     }
 
     pub async fn open_state_for_file(&self, path: &Path) -> Result<(Arc<AppState>, WorkbookId)> {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(url) = path.to_str().filter(|value| is_remote_url(value)) {
+            #[cfg(feature = "remote")]
+            {
+                return self.open_state_for_url(url);
+            }
+            #[cfg(not(feature = "remote"))]
+            {
+                return Err(anyhow!(
+                    "'{url}' is a remote URL; remote workbooks need a build with the `remote` feature"
+                ));
+            }
+        }
+
         let absolute = self.normalize_existing_file(path)?;
         let config = Arc::new(self.build_cli_config(&absolute));
         let state = Arc::new(AppState::new(config));
@@ -63,6 +128,21 @@ impl StatelessRuntime {
         Ok((state, workbook_id))
     }
 
+    /// Read-only state over a single `https://` or `s3://` workbook, fetched
+    /// through the remote download cache.
+    #[cfg(all(not(target_arch = "wasm32"), feature = "remote"))]
+    fn open_state_for_url(&self, url: &str) -> Result<(Arc<AppState>, WorkbookId)> {
+        let cache = RemoteCache::from_env();
+        let mut config = self.build_cli_config(Path::new(url));
+        config.workspace_root = cache.dir().to_path_buf();
+        config.single_workbook = None;
+        let config = Arc::new(config);
+        let repository = RemoteWorkspaceRepository::new(config.clone(), cache);
+        let workbook_id = repository.register(url)?;
+        let state = Arc::new(AppState::new_with_repository(config, Arc::new(repository)));
+        Ok((state, workbook_id))
+    }
+
     fn build_cli_config(&self, file: &Path) -> ServerConfig {
         let workspace_root = file
             .parent()
@@ -91,6 +171,17 @@ impl StatelessRuntime {
             max_cells: Some(10_000),
             max_items: Some(500),
             allow_overwrite: true,
+            named_queries: Default::default(),
+            workbook_aliases: Default::default(),
+            virtual_workspace: false,
+            pin_revisions: false,
+            parse_cache_dir: std::env::var_os(PARSE_CACHE_DIR_ENV).map(PathBuf::from),
+            scratch_dir: None,
+            scratch_max_bytes: None,
+            checkpoint_store: None,
+            schedule: None,
+            schedule_mode: None,
+            profile: None,
         }
     }
 }
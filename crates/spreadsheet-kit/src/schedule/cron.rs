@@ -0,0 +1,246 @@
+//! Five-field cron expressions (`minute hour day-of-month month
+//! day-of-week`), evaluated in local time.
+//!
+//! Fields accept `*`, numbers, ranges (`1-5`), steps (`*/15`, `0-30/10`)
+//! and comma lists; months and weekdays also accept three-letter names.
+//! Weekday 0 and 7 are both Sunday. As in Vixie cron, when both the
+//! day-of-month and day-of-week fields are restricted a day matching either
+//! fires. `@hourly`, `@daily`, `@weekly`, `@monthly` and `@yearly` are
+//! accepted as shorthands.
+
+use anyhow::{Result, anyhow, bail};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Timelike};
+
+const MONTH_NAMES: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAY_NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// Give up looking for a matching minute after this many years, so
+/// expressions such as `0 0 30 2 *` fail instead of looping.
+const SEARCH_YEARS: i32 = 5;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    dom_restricted: bool,
+    dow_restricted: bool,
+}
+
+impl CronSchedule {
+    pub fn parse(spec: &str) -> Result<Self> {
+        let trimmed = spec.trim();
+        let expanded = match trimmed {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        if fields.len() != 5 {
+            bail!(
+                "invalid cron expression '{trimmed}': expected 5 fields (minute hour day-of-month month day-of-week)"
+            );
+        }
+        let field = |index: usize, name: &str, min: u32, max: u32, names: &[&str]| {
+            parse_field(fields[index], min, max, names)
+                .map_err(|e| anyhow!("invalid cron expression '{trimmed}': {name} {e}"))
+        };
+        let minutes = field(0, "minute", 0, 59, &[])?;
+        let hours = field(1, "hour", 0, 23, &[])?;
+        let days_of_month = field(2, "day-of-month", 1, 31, &[])?;
+        let months = field(3, "month", 1, 12, &MONTH_NAMES)?;
+        let mut days_of_week = field(4, "day-of-week", 0, 7, &WEEKDAY_NAMES)?;
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week = (days_of_week & !(1 << 7)) | 1;
+        }
+        Ok(Self {
+            minutes,
+            hours,
+            days_of_month,
+            months,
+            days_of_week,
+            dom_restricted: !fields[2].starts_with('*'),
+            dow_restricted: !fields[4].starts_with('*'),
+        })
+    }
+
+    fn matches_day(&self, date: NaiveDate) -> bool {
+        if self.months & (1 << date.month()) == 0 {
+            return false;
+        }
+        let dom = self.days_of_month & (1 << date.day()) != 0;
+        let dow = self.days_of_week & (1 << date.weekday().num_days_from_sunday()) != 0;
+        match (self.dom_restricted, self.dow_restricted) {
+            (true, true) => dom || dow,
+            (true, false) => dom,
+            (false, true) => dow,
+            (false, false) => true,
+        }
+    }
+
+    /// First matching minute strictly after `after`.
+    pub fn next_after_naive(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let start = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let limit = NaiveDate::from_ymd_opt(start.year() + SEARCH_YEARS, 1, 1)?;
+        let mut date = start.date();
+        let mut first_day = true;
+        while date < limit {
+            if self.matches_day(date) {
+                let (from_hour, from_minute) = if first_day {
+                    (start.hour(), start.minute())
+                } else {
+                    (0, 0)
+                };
+                for hour in from_hour..24 {
+                    if self.hours & (1 << hour) == 0 {
+                        continue;
+                    }
+                    let minute_floor = if hour == from_hour { from_minute } else { 0 };
+                    if let Some(minute) =
+                        (minute_floor..60).find(|minute| self.minutes & (1 << minute) != 0)
+                    {
+                        return date.and_hms_opt(hour, minute, 0);
+                    }
+                }
+            }
+            date = date.succ_opt()?;
+            first_day = false;
+        }
+        None
+    }
+
+    /// Next local firing time after `after`. Minutes skipped by a daylight
+    /// saving change are passed over; repeated ones fire once.
+    pub fn next_after(&self, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        let mut cursor = after.naive_local();
+        for _ in 0..4 {
+            let next = self.next_after_naive(cursor)?;
+            if let Some(local) = Local.from_local_datetime(&next).earliest() {
+                if local > after {
+                    return Some(local);
+                }
+            }
+            cursor = next;
+        }
+        None
+    }
+}
+
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str]) -> Result<u64> {
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .map_err(|_| anyhow!("has an invalid step '{step}'"))?;
+                if step == 0 {
+                    bail!("has a zero step");
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (
+                parse_value(start, min, max, names)?,
+                parse_value(end, min, max, names)?,
+            )
+        } else {
+            let value = parse_value(range, min, max, names)?;
+            // `5/15` means every 15 starting at 5.
+            (value, if step > 1 { max } else { value })
+        };
+        if start > end {
+            bail!("has a reversed range '{range}'");
+        }
+        for value in (start..=end).step_by(step as usize) {
+            mask |= 1 << value;
+        }
+    }
+    Ok(mask)
+}
+
+fn parse_value(raw: &str, min: u32, max: u32, names: &[&str]) -> Result<u32> {
+    let lower = raw.to_ascii_lowercase();
+    let value = match names.iter().position(|name| *name == lower) {
+        // Month names are 1-based, weekday names 0-based.
+        Some(index) => index as u32 + min.min(1),
+        None => raw
+            .parse()
+            .map_err(|_| anyhow!("has an invalid value '{raw}'"))?,
+    };
+    if value < min || value > max {
+        bail!("value {value} is outside {min}-{max}");
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(date: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M").expect("datetime")
+    }
+
+    fn next(spec: &str, after: &str) -> String {
+        CronSchedule::parse(spec)
+            .expect("parse")
+            .next_after_naive(at(after))
+            .expect("next")
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+    }
+
+    #[test]
+    fn steps_ranges_and_lists() {
+        assert_eq!(next("*/15 * * * *", "2026-03-02 10:07"), "2026-03-02 10:15");
+        assert_eq!(next("*/15 * * * *", "2026-03-02 10:45"), "2026-03-02 11:00");
+        assert_eq!(next("30 2 * * *", "2026-03-02 02:30"), "2026-03-03 02:30");
+        assert_eq!(
+            next("0 9-17/4 * * *", "2026-03-02 10:00"),
+            "2026-03-02 13:00"
+        );
+        assert_eq!(next("0 6,18 * * *", "2026-03-02 07:00"), "2026-03-02 18:00");
+        assert_eq!(next("@monthly", "2026-03-02 07:00"), "2026-04-01 00:00");
+    }
+
+    #[test]
+    fn weekdays_months_and_day_union() {
+        // 2026-03-06 is a Friday.
+        assert_eq!(
+            next("0 6 * * mon-fri", "2026-03-06 07:00"),
+            "2026-03-09 06:00"
+        );
+        assert_eq!(next("0 0 * * 7", "2026-03-06 07:00"), "2026-03-08 00:00");
+        assert_eq!(next("0 0 1 jun *", "2026-03-06 07:00"), "2026-06-01 00:00");
+        // Day-of-month 15 or any Monday, whichever comes first.
+        assert_eq!(next("0 0 15 * 1", "2026-03-06 07:00"), "2026-03-09 00:00");
+        assert_eq!(next("0 0 29 2 *", "2026-03-06 07:00"), "2028-02-29 00:00");
+    }
+
+    #[test]
+    fn rejects_malformed_expressions() {
+        for spec in [
+            "* * * *",
+            "60 * * * *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "0 0 * foo *",
+        ] {
+            assert!(CronSchedule::parse(spec).is_err(), "{spec} should fail");
+        }
+        let never = CronSchedule::parse("0 0 30 2 *").expect("parse");
+        assert!(never.next_after_naive(at("2026-03-06 07:00")).is_none());
+    }
+}
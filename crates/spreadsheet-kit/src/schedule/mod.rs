@@ -0,0 +1,216 @@
+//! Scheduled jobs for the server binary.
+//!
+//! A `schedule` block in the server config lists jobs, each a cron
+//! expression, the workspace workbooks it applies to, and one of a small set
+//! of commands (`refresh`, `recalculate`, `lint`, `export`). Started with
+//! `--schedule`, the server runs those jobs instead of serving a transport;
+//! `--schedule-once` runs each job once and exits, failing if any job failed
+//! or raised an alert.
+//!
+//! Jobs run the same code paths as the matching `asp` commands, so writes
+//! stay atomic and go through the configured write hooks. Workbooks and
+//! outputs are confined to the workspace root, rewriting a workbook in place
+//! requires `allow_overwrite`, and each run is bounded by the tool timeout
+//! unless the job sets its own. Every run is appended to the schedule log and
+//! alerts are posted to the webhook.
+
+pub mod cron;
+#[cfg(all(not(target_arch = "wasm32"), feature = "recalc"))]
+pub mod runner;
+
+pub use cron::CronSchedule;
+#[cfg(all(not(target_arch = "wasm32"), feature = "recalc"))]
+pub use runner::{ScheduledRun, ScheduledRunStatus, WorkbookRun, run_job, run_schedule};
+
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+
+/// How the server was asked to run its schedule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduleMode {
+    /// Run jobs at their cron times until interrupted.
+    Loop,
+    /// Run every job once, then exit.
+    Once,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ScheduleConfig {
+    /// JSONL file each run is appended to, relative to the workspace root.
+    #[serde(default)]
+    pub log: Option<PathBuf>,
+    /// URL that receives each alerting run (or every run with
+    /// `notify: all`) as a JSON POST, signed like write webhooks when
+    /// `SPREADSHEET_MCP_WEBHOOK_SECRET` is set.
+    #[serde(default)]
+    pub webhook: Option<String>,
+    #[serde(default)]
+    pub notify: ScheduleNotify,
+    #[serde(default)]
+    pub jobs: Vec<ScheduledJob>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduleNotify {
+    /// Post runs that failed or raised an alert.
+    #[default]
+    Alerts,
+    /// Post every run.
+    All,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScheduledJob {
+    pub name: String,
+    /// Five-field cron expression, in local time.
+    pub cron: String,
+    /// Workspace-relative workbook paths or globs (`reports/*.xlsx`).
+    pub workbooks: Vec<String>,
+    #[serde(flatten)]
+    pub task: ScheduledTask,
+    /// Per-run timeout; defaults to the tool timeout, 0 disables.
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+/// What a job does to each workbook. Output paths are relative to the
+/// workspace root and may use `{stem}` (the workbook's file stem) and
+/// `{date}` (the run's local date, `YYYY-MM-DD`).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum ScheduledTask {
+    /// Re-run recorded materializations (`asp refresh`).
+    Refresh {
+        #[serde(default)]
+        sheets: Vec<String>,
+        /// Write the refreshed copy here instead of rewriting the workbook.
+        #[serde(default)]
+        output: Option<String>,
+    },
+    /// Recalculate formulas (`asp recalculate`).
+    Recalculate {
+        /// Write the recalculated copy here instead of rewriting the workbook.
+        #[serde(default)]
+        output: Option<String>,
+        /// `auto`, `builtin` or `libreoffice`.
+        #[serde(default)]
+        engine: Option<String>,
+    },
+    /// Lint against a profile (`asp verify lint`); a failed lint is an alert.
+    Lint {
+        /// Workspace-relative lint profile.
+        #[serde(default)]
+        profile: Option<String>,
+        /// `error`, `warning`, `info` or `never`.
+        #[serde(default)]
+        fail_on: Option<String>,
+        #[serde(default)]
+        sheet: Option<String>,
+    },
+    /// Export a range to a file (`asp range-export`).
+    Export {
+        sheet: String,
+        range: String,
+        /// `csv`, `json`, `grid` or `arrow`.
+        #[serde(default = "default_export_format")]
+        format: String,
+        output: String,
+    },
+}
+
+fn default_export_format() -> String {
+    "csv".to_string()
+}
+
+impl ScheduledTask {
+    pub fn command_name(&self) -> &'static str {
+        match self {
+            Self::Refresh { .. } => "refresh",
+            Self::Recalculate { .. } => "recalculate",
+            Self::Lint { .. } => "lint",
+            Self::Export { .. } => "export",
+        }
+    }
+
+    pub fn output(&self) -> Option<&str> {
+        match self {
+            Self::Refresh { output, .. } | Self::Recalculate { output, .. } => output.as_deref(),
+            Self::Lint { .. } => None,
+            Self::Export { output, .. } => Some(output),
+        }
+    }
+
+    /// Whether the job replaces the workbook it reads.
+    pub fn rewrites_in_place(&self) -> bool {
+        matches!(
+            self,
+            Self::Refresh { output: None, .. } | Self::Recalculate { output: None, .. }
+        )
+    }
+}
+
+impl ScheduleConfig {
+    /// Check job names, cron expressions and paths, and refuse in-place
+    /// rewrites unless the server allows overwriting workbooks.
+    pub fn validate(&self, allow_overwrite: bool) -> Result<()> {
+        let mut names = HashSet::new();
+        for job in &self.jobs {
+            let name = job.name.trim();
+            if name.is_empty() {
+                bail!("schedule jobs require a non-empty name");
+            }
+            if !names.insert(name.to_string()) {
+                bail!("schedule job '{name}' is defined more than once");
+            }
+            CronSchedule::parse(&job.cron)
+                .map_err(|e| anyhow::anyhow!("schedule job '{name}': {e}"))?;
+            if job.workbooks.is_empty() {
+                bail!("schedule job '{name}' lists no workbooks");
+            }
+            let context = format!("schedule job '{name}'");
+            for pattern in &job.workbooks {
+                ensure_workspace_relative(&context, "workbook pattern", pattern)?;
+            }
+            if let Some(output) = job.task.output() {
+                if output.trim().is_empty() || output.trim() == "-" {
+                    bail!("schedule job '{name}' needs an output file path");
+                }
+                ensure_workspace_relative(&context, "output", output)?;
+            }
+            if let ScheduledTask::Lint {
+                profile: Some(profile),
+                ..
+            } = &job.task
+            {
+                ensure_workspace_relative(&context, "lint profile", profile)?;
+            }
+            if job.task.rewrites_in_place() && !allow_overwrite {
+                bail!(
+                    "schedule job '{name}' rewrites workbooks in place; set allow_overwrite or give the job an output path"
+                );
+            }
+        }
+        if let Some(log) = &self.log {
+            ensure_workspace_relative("schedule", "log", &log.to_string_lossy())?;
+        }
+        Ok(())
+    }
+}
+
+/// Reject absolute paths and `..` so jobs stay inside the workspace root.
+fn ensure_workspace_relative(context: &str, what: &str, raw: &str) -> Result<()> {
+    let path = Path::new(raw.trim());
+    let escapes = path.components().any(|component| {
+        matches!(
+            component,
+            Component::ParentDir | Component::RootDir | Component::Prefix(_)
+        )
+    });
+    if escapes {
+        bail!("{context}: {what} '{raw}' must be relative to the workspace root");
+    }
+    Ok(())
+}
@@ -0,0 +1,465 @@
+//! Running scheduled jobs: resolving their workbooks, running the command
+//! for each, and reporting the run to the schedule log and webhook.
+
+use super::{
+    CronSchedule, ScheduleConfig, ScheduleMode, ScheduleNotify, ScheduledJob, ScheduledTask,
+};
+use crate::cli::{Cli, run_command};
+use crate::config::ServerConfig;
+use crate::webhook::SCHEDULED_RUN_EVENT;
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::{DateTime, Local};
+use clap::Parser;
+use globset::Glob;
+use serde::Serialize;
+use serde_json::Value;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use walkdir::WalkDir;
+
+/// Longest single sleep between schedule checks, so clock changes and
+/// suspends are noticed.
+const MAX_SLEEP: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduledRunStatus {
+    Ok,
+    /// The command ran but reported a problem (a failed lint).
+    Alert,
+    Failed,
+}
+
+/// One job run, as written to the schedule log and posted to the webhook.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduledRun {
+    pub job: String,
+    pub command: String,
+    pub started_at: String,
+    pub duration_ms: u64,
+    pub status: ScheduledRunStatus,
+    pub workbooks: Vec<WorkbookRun>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkbookRun {
+    /// Workbook path relative to the workspace root.
+    pub path: String,
+    pub status: ScheduledRunStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Run the configured schedule until interrupted, or once with
+/// [`ScheduleMode::Once`].
+pub async fn run_schedule(config: &ServerConfig, mode: ScheduleMode) -> Result<()> {
+    let schedule = config
+        .schedule
+        .as_ref()
+        .filter(|schedule| !schedule.jobs.is_empty())
+        .ok_or_else(|| {
+            anyhow!("--schedule needs a `schedule` block with at least one job in --config")
+        })?;
+    let crons = schedule
+        .jobs
+        .iter()
+        .map(|job| CronSchedule::parse(&job.cron))
+        .collect::<Result<Vec<_>>>()?;
+    for job in &schedule.jobs {
+        // Surface bad engine or fail_on values before the first run.
+        Cli::try_parse_from(command_argv(config, job, Path::new("workbook.xlsx"), None))
+            .map_err(|e| anyhow!("schedule job '{}': {}", job.name, e))?;
+    }
+
+    if mode == ScheduleMode::Once {
+        let mut problems = 0;
+        for job in &schedule.jobs {
+            let run = run_job(config, job).await;
+            if run.status != ScheduledRunStatus::Ok {
+                problems += 1;
+            }
+            report_run(config, schedule, &run).await;
+        }
+        if problems > 0 {
+            bail!(
+                "{problems} of {} scheduled job(s) failed or raised alerts",
+                schedule.jobs.len()
+            );
+        }
+        return Ok(());
+    }
+
+    let now = Local::now();
+    let mut next_runs: Vec<Option<DateTime<Local>>> =
+        crons.iter().map(|cron| cron.next_after(now)).collect();
+    for (job, next) in schedule.jobs.iter().zip(&next_runs) {
+        match next {
+            Some(at) => {
+                tracing::info!(job = %job.name, next_run = %at.to_rfc3339(), "scheduled job")
+            }
+            None => {
+                tracing::warn!(job = %job.name, cron = %job.cron, "cron expression never fires")
+            }
+        }
+    }
+
+    loop {
+        let Some(earliest) = next_runs.iter().flatten().min().copied() else {
+            bail!("no scheduled job has a future run time");
+        };
+        let wait = (earliest - Local::now())
+            .to_std()
+            .unwrap_or(Duration::ZERO)
+            .min(MAX_SLEEP);
+        if !wait.is_zero() {
+            tokio::select! {
+                _ = tokio::time::sleep(wait) => {}
+                _ = tokio::signal::ctrl_c() => {
+                    tracing::info!("shutdown signal received; stopping schedule");
+                    return Ok(());
+                }
+            }
+            continue;
+        }
+
+        let due_before = Local::now();
+        for (index, job) in schedule.jobs.iter().enumerate() {
+            if next_runs[index].is_none_or(|at| at > due_before) {
+                continue;
+            }
+            let run = run_job(config, job).await;
+            report_run(config, schedule, &run).await;
+            // A run that overran later firings skips them rather than
+            // running back to back.
+            next_runs[index] = crons[index].next_after(Local::now());
+        }
+    }
+}
+
+/// Run `job` once against each of its workbooks.
+pub async fn run_job(config: &ServerConfig, job: &ScheduledJob) -> ScheduledRun {
+    let started_at = Local::now();
+    let clock = Instant::now();
+    let mut run = ScheduledRun {
+        job: job.name.clone(),
+        command: job.task.command_name().to_string(),
+        started_at: started_at.to_rfc3339(),
+        duration_ms: 0,
+        status: ScheduledRunStatus::Ok,
+        workbooks: Vec::new(),
+        error: None,
+    };
+
+    match resolve_workbooks(config, job) {
+        Ok(workbooks) if workbooks.is_empty() => {
+            run.status = ScheduledRunStatus::Failed;
+            run.error = Some("no workbooks matched".to_string());
+        }
+        Ok(workbooks) => {
+            let timeout = match job.timeout_ms {
+                Some(0) => None,
+                Some(ms) => Some(Duration::from_millis(ms)),
+                None => config.tool_timeout(),
+            };
+            for workbook in workbooks {
+                let outcome = run_for_workbook(config, job, &workbook, &started_at, timeout).await;
+                run.status = run.status.max(outcome.status);
+                run.workbooks.push(outcome);
+            }
+        }
+        Err(error) => {
+            run.status = ScheduledRunStatus::Failed;
+            run.error = Some(error.to_string());
+        }
+    }
+    run.duration_ms = clock.elapsed().as_millis() as u64;
+    run
+}
+
+async fn run_for_workbook(
+    config: &ServerConfig,
+    job: &ScheduledJob,
+    workbook: &Path,
+    started_at: &DateTime<Local>,
+    timeout: Option<Duration>,
+) -> WorkbookRun {
+    let mut outcome = WorkbookRun {
+        path: workspace_display(config, workbook),
+        status: ScheduledRunStatus::Ok,
+        output: None,
+        result: None,
+        error: None,
+    };
+
+    let output = match job.task.output() {
+        Some(template) => match prepare_output(config, template, workbook, started_at) {
+            Ok(path) => Some(path),
+            Err(error) => {
+                outcome.status = ScheduledRunStatus::Failed;
+                outcome.error = Some(error.to_string());
+                return outcome;
+            }
+        },
+        None => None,
+    };
+    outcome.output = output
+        .as_deref()
+        .map(|path| workspace_display(config, path));
+
+    let argv = command_argv(config, job, workbook, output.as_deref());
+    let command = match Cli::try_parse_from(argv) {
+        Ok(cli) => cli.command,
+        Err(error) => {
+            outcome.status = ScheduledRunStatus::Failed;
+            outcome.error = Some(error.to_string());
+            return outcome;
+        }
+    };
+
+    let result = match timeout {
+        Some(limit) => match tokio::time::timeout(limit, run_command(command)).await {
+            Ok(result) => result,
+            Err(_) => Err(anyhow!(
+                "job '{}' timed out after {}ms",
+                job.name,
+                limit.as_millis()
+            )),
+        },
+        None => run_command(command).await,
+    };
+    match result {
+        Ok(payload) => {
+            if let ScheduledTask::Lint { .. } = job.task {
+                if payload.get("passed").and_then(Value::as_bool) == Some(false) {
+                    outcome.status = ScheduledRunStatus::Alert;
+                }
+                outcome.result = Some(serde_json::json!({
+                    "passed": payload.get("passed"),
+                    "summary": payload.get("summary"),
+                }));
+            } else {
+                outcome.result = Some(payload);
+            }
+        }
+        Err(error) => {
+            outcome.status = ScheduledRunStatus::Failed;
+            outcome.error = Some(format!("{error:#}"));
+        }
+    }
+    outcome
+}
+
+/// The `asp` command line a job runs for one workbook.
+fn command_argv(
+    config: &ServerConfig,
+    job: &ScheduledJob,
+    workbook: &Path,
+    output: Option<&Path>,
+) -> Vec<String> {
+    let mut argv = vec!["asp".to_string()];
+    let file = workbook.display().to_string();
+    let output = output.map(|path| path.display().to_string());
+    match &job.task {
+        ScheduledTask::Refresh { sheets, .. } => {
+            argv.extend(["refresh".to_string(), file]);
+            for sheet in sheets {
+                argv.extend(["--sheet".to_string(), sheet.clone()]);
+            }
+            match output {
+                Some(path) => argv.extend(["--output".to_string(), path, "--force".to_string()]),
+                None => argv.push("--in-place".to_string()),
+            }
+        }
+        ScheduledTask::Recalculate { engine, .. } => {
+            argv.extend(["recalculate".to_string(), file]);
+            if let Some(engine) = engine {
+                argv.extend(["--engine".to_string(), engine.clone()]);
+            }
+            if let Some(path) = output {
+                argv.extend(["--output".to_string(), path, "--force".to_string()]);
+            }
+        }
+        ScheduledTask::Lint {
+            profile,
+            fail_on,
+            sheet,
+        } => {
+            argv.extend(["lint".to_string(), file]);
+            if let Some(profile) = profile {
+                let profile = config.workspace_root.join(profile);
+                argv.extend(["--profile".to_string(), profile.display().to_string()]);
+            }
+            if let Some(fail_on) = fail_on {
+                argv.extend(["--fail-on".to_string(), fail_on.clone()]);
+            }
+            if let Some(sheet) = sheet {
+                argv.extend(["--sheet".to_string(), sheet.clone()]);
+            }
+        }
+        ScheduledTask::Export {
+            sheet,
+            range,
+            format,
+            ..
+        } => {
+            argv.extend([
+                "range-export".to_string(),
+                file,
+                sheet.clone(),
+                range.clone(),
+                "--format".to_string(),
+                format.clone(),
+                "--output".to_string(),
+                output.unwrap_or_else(|| "export.out".to_string()),
+            ]);
+        }
+    }
+    argv
+}
+
+/// Workspace files matching the job's patterns, in path order.
+fn resolve_workbooks(config: &ServerConfig, job: &ScheduledJob) -> Result<Vec<PathBuf>> {
+    let root = &config.workspace_root;
+    let mut matched = Vec::new();
+    for pattern in &job.workbooks {
+        let pattern = pattern.trim();
+        let literal = root.join(pattern);
+        if literal.is_file() {
+            matched.push(literal);
+            continue;
+        }
+        let matcher = Glob::new(pattern)
+            .map_err(|err| anyhow!("invalid workbook pattern {pattern}: {err}"))?
+            .compile_matcher();
+        for entry in WalkDir::new(root) {
+            let entry = entry?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let path = entry.path();
+            let supported = path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| {
+                    config
+                        .supported_extensions
+                        .contains(&ext.to_ascii_lowercase())
+                });
+            if supported
+                && path
+                    .strip_prefix(root)
+                    .is_ok_and(|relative| matcher.is_match(relative))
+            {
+                matched.push(path.to_path_buf());
+            }
+        }
+    }
+    matched.sort();
+    matched.dedup();
+    if matched.len() > 1
+        && let Some(output) = job.task.output()
+        && !output.contains("{stem}")
+    {
+        bail!(
+            "{} workbooks matched but output '{output}' has no {{stem}}, so each would overwrite the last",
+            matched.len()
+        );
+    }
+    Ok(matched)
+}
+
+fn prepare_output(
+    config: &ServerConfig,
+    template: &str,
+    workbook: &Path,
+    started_at: &DateTime<Local>,
+) -> Result<PathBuf> {
+    let stem = workbook
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("workbook");
+    let rendered = template
+        .trim()
+        .replace("{stem}", stem)
+        .replace("{date}", &started_at.format("%Y-%m-%d").to_string());
+    let path = config.workspace_root.join(rendered);
+    if path == workbook {
+        bail!("output resolves to the workbook itself; omit output to rewrite it in place");
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create output directory {:?}", parent))?;
+    }
+    Ok(path)
+}
+
+fn workspace_display(config: &ServerConfig, path: &Path) -> String {
+    path.strip_prefix(&config.workspace_root)
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+async fn report_run(config: &ServerConfig, schedule: &ScheduleConfig, run: &ScheduledRun) {
+    match run.status {
+        ScheduledRunStatus::Ok => tracing::info!(
+            job = %run.job,
+            workbooks = run.workbooks.len(),
+            duration_ms = run.duration_ms,
+            "scheduled job finished"
+        ),
+        ScheduledRunStatus::Alert => tracing::warn!(
+            job = %run.job,
+            workbooks = run.workbooks.len(),
+            "scheduled job raised an alert"
+        ),
+        ScheduledRunStatus::Failed => tracing::warn!(
+            job = %run.job,
+            error = run.error.as_deref().unwrap_or("see workbook results"),
+            "scheduled job failed"
+        ),
+    }
+
+    if let Some(log) = &schedule.log
+        && let Err(error) = append_log(&config.workspace_root.join(log), run)
+    {
+        tracing::warn!(?error, "failed to append to schedule log");
+    }
+
+    let notify = match schedule.notify {
+        ScheduleNotify::All => true,
+        ScheduleNotify::Alerts => run.status != ScheduledRunStatus::Ok,
+    };
+    if notify
+        && let Some(url) = &schedule.webhook
+        && let Err(error) = post_webhook(url, run).await
+    {
+        tracing::warn!(?error, job = %run.job, "failed to post schedule webhook");
+    }
+}
+
+fn append_log(path: &Path, run: &ScheduledRun) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("failed to open schedule log {:?}", path))?;
+    let mut line = serde_json::to_vec(run)?;
+    line.push(b'\n');
+    file.write_all(&line)?;
+    Ok(())
+}
+
+async fn post_webhook(url: &str, run: &ScheduledRun) -> Result<()> {
+    crate::webhook::post_signed(url, SCHEDULED_RUN_EVENT, serde_json::to_vec(run)?).await
+}
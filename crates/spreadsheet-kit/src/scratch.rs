@@ -0,0 +1,294 @@
+//! Scratch space for temporary workbooks.
+//!
+//! Files that exist only to be thrown away live under one scratch root
+//! (`SPREADSHEET_MCP_SCRATCH_DIR`, default `<system temp>/spreadsheet-mcp`):
+//!
+//! - `tmp/` holds short-lived CLI temp files and directories: session reads,
+//!   render and PDF export staging, recalculation copies. They delete
+//!   themselves when dropped; anything a killed process left behind is removed
+//!   once it is older than the orphan age.
+//! - `owner-<id>/` holds one server process's fork working copies,
+//!   checkpoints, and staged-change snapshots. The process refreshes a
+//!   heartbeat file while it runs and removes the directory when its fork
+//!   registry is dropped. A directory whose heartbeat has gone stale belonged
+//!   to a process that died; the next server start or `asp gc` removes it.
+//!
+//! Anything else under the root is left alone, so pointing the root at a
+//! shared directory never deletes files this crate did not create.
+
+use crate::utils::make_short_random_id;
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use walkdir::WalkDir;
+
+pub const SCRATCH_DIR_ENV: &str = "SPREADSHEET_MCP_SCRATCH_DIR";
+/// Scratch older than this with no live owner is treated as orphaned.
+pub const DEFAULT_ORPHAN_AGE: Duration = Duration::from_secs(15 * 60);
+
+const TEMP_AREA: &str = "tmp";
+const OWNER_PREFIX: &str = "owner";
+const HEARTBEAT_FILE: &str = ".heartbeat";
+const FORKS_AREA: &str = "forks";
+const CHECKPOINTS_AREA: &str = "checkpoints";
+const STAGED_AREA: &str = "staged";
+
+/// Scratch root from `SPREADSHEET_MCP_SCRATCH_DIR`, or the default under the
+/// system temp directory.
+pub fn default_root() -> PathBuf {
+    std::env::var_os(SCRATCH_DIR_ENV)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| std::env::temp_dir().join("spreadsheet-mcp"))
+}
+
+/// Directory for short-lived temp files under the default scratch root,
+/// created if needed. Pass it to `tempfile::Builder::tempfile_in`.
+pub fn temp_dir() -> Result<PathBuf> {
+    let dir = default_root().join(TEMP_AREA);
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("unable to create scratch directory '{}'", dir.display()))?;
+    Ok(dir)
+}
+
+/// One process's private scratch directory, with an optional size quota.
+#[derive(Debug)]
+pub struct ScratchSpace {
+    root: PathBuf,
+    dir: PathBuf,
+    max_bytes: Option<u64>,
+}
+
+impl ScratchSpace {
+    /// Create a fresh owner directory under `root`.
+    pub fn create(root: &Path, max_bytes: Option<u64>) -> Result<Self> {
+        let dir = root.join(make_short_random_id(OWNER_PREFIX, 12));
+        for area in [FORKS_AREA, CHECKPOINTS_AREA, STAGED_AREA] {
+            let area_dir = dir.join(area);
+            fs::create_dir_all(&area_dir).with_context(|| {
+                format!(
+                    "unable to create scratch directory '{}'",
+                    area_dir.display()
+                )
+            })?;
+        }
+        let space = Self {
+            root: root.to_path_buf(),
+            dir,
+            max_bytes,
+        };
+        space.heartbeat();
+        Ok(space)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn forks_dir(&self) -> PathBuf {
+        self.dir.join(FORKS_AREA)
+    }
+
+    pub fn checkpoints_dir(&self) -> PathBuf {
+        self.dir.join(CHECKPOINTS_AREA)
+    }
+
+    pub fn staged_dir(&self) -> PathBuf {
+        self.dir.join(STAGED_AREA)
+    }
+
+    pub fn max_bytes(&self) -> Option<u64> {
+        self.max_bytes
+    }
+
+    /// Mark this directory as owned by a live process.
+    pub fn heartbeat(&self) {
+        let _ = fs::write(
+            self.dir.join(HEARTBEAT_FILE),
+            std::process::id().to_string(),
+        );
+    }
+
+    pub fn used_bytes(&self) -> u64 {
+        dir_size(&self.dir)
+    }
+
+    /// Fail if writing `incoming` more bytes would exceed the quota.
+    pub fn reserve(&self, incoming: u64) -> Result<()> {
+        let Some(max_bytes) = self.max_bytes else {
+            return Ok(());
+        };
+        let used = self.used_bytes();
+        if used.saturating_add(incoming) > max_bytes {
+            bail!(
+                "scratch quota exceeded: {used} bytes in use plus {incoming} needed exceeds the {max_bytes} byte limit; discard forks, checkpoints, or staged changes"
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Drop for ScratchSpace {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScratchGcReport {
+    pub root: String,
+    pub dry_run: bool,
+    /// Entries removed (or, with `dry_run`, that would be), relative to the root.
+    pub removed: Vec<String>,
+    pub removed_bytes: u64,
+    /// Owner directories still held by a live process.
+    pub live_owners: usize,
+}
+
+/// Remove scratch left behind by processes that exited without cleaning up:
+/// owner directories whose heartbeat is older than `orphan_age`, and temp
+/// entries older than `orphan_age`. `keep` is never removed.
+pub fn collect_garbage(
+    root: &Path,
+    orphan_age: Duration,
+    keep: Option<&Path>,
+    dry_run: bool,
+) -> Result<ScratchGcReport> {
+    let mut report = ScratchGcReport {
+        root: root.display().to_string(),
+        dry_run,
+        removed: Vec::new(),
+        removed_bytes: 0,
+        live_owners: 0,
+    };
+    if !root.is_dir() {
+        return Ok(report);
+    }
+
+    let mut candidates = Vec::new();
+    for entry in read_dir_sorted(root)? {
+        let name = entry.file_name().unwrap_or_default().to_string_lossy();
+        if name.starts_with(&format!("{OWNER_PREFIX}-")) && entry.is_dir() {
+            let heartbeat = entry.join(HEARTBEAT_FILE);
+            let marker = if heartbeat.exists() {
+                &heartbeat
+            } else {
+                &entry
+            };
+            if keep == Some(entry.as_path()) || !is_older_than(marker, orphan_age) {
+                report.live_owners += 1;
+            } else {
+                candidates.push(entry);
+            }
+        } else if name == TEMP_AREA && entry.is_dir() {
+            for temp in read_dir_sorted(&entry)? {
+                if is_older_than(&temp, orphan_age) {
+                    candidates.push(temp);
+                }
+            }
+        }
+    }
+
+    for path in candidates {
+        let bytes = dir_size(&path);
+        if !dry_run {
+            let removed = if path.is_dir() {
+                fs::remove_dir_all(&path)
+            } else {
+                fs::remove_file(&path)
+            };
+            if let Err(error) = removed {
+                tracing::warn!(path = %path.display(), %error, "failed to remove orphaned scratch");
+                continue;
+            }
+        }
+        report.removed_bytes += bytes;
+        report.removed.push(
+            path.strip_prefix(root)
+                .unwrap_or(&path)
+                .display()
+                .to_string(),
+        );
+    }
+    Ok(report)
+}
+
+fn read_dir_sorted(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut entries = fs::read_dir(dir)
+        .with_context(|| format!("unable to read scratch directory '{}'", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect::<Vec<_>>();
+    entries.sort();
+    Ok(entries)
+}
+
+fn is_older_than(path: &Path, age: Duration) -> bool {
+    fs::symlink_metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|elapsed| elapsed >= age)
+}
+
+fn dir_size(path: &Path) -> u64 {
+    WalkDir::new(path)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collects_stale_owners_and_temp_files_only() {
+        let root = tempfile::tempdir().expect("root");
+        let live = ScratchSpace::create(root.path(), None).expect("live");
+        let dead = ScratchSpace::create(root.path(), None).expect("dead");
+        fs::write(dead.forks_dir().join("fork-a.xlsx"), [0u8; 64]).expect("fork file");
+        fs::create_dir_all(root.path().join(TEMP_AREA)).expect("temp area");
+        fs::write(root.path().join(TEMP_AREA).join(".render-x"), [0u8; 8]).expect("temp");
+        fs::write(root.path().join("unrelated.txt"), b"keep").expect("unrelated");
+
+        let dead_dir = dead.dir().to_path_buf();
+        std::mem::forget(dead);
+
+        // Nothing is old enough yet.
+        let report = collect_garbage(root.path(), DEFAULT_ORPHAN_AGE, None, false).expect("gc");
+        assert!(report.removed.is_empty());
+        assert_eq!(report.live_owners, 2);
+
+        let report =
+            collect_garbage(root.path(), Duration::ZERO, Some(live.dir()), true).expect("dry run");
+        assert_eq!(report.removed.len(), 2, "{report:?}");
+        assert!(dead_dir.exists());
+
+        let report =
+            collect_garbage(root.path(), Duration::ZERO, Some(live.dir()), false).expect("gc");
+        assert_eq!(report.live_owners, 1);
+        assert!(report.removed_bytes >= 64 + 8);
+        assert!(!dead_dir.exists());
+        assert!(live.dir().exists());
+        assert!(root.path().join("unrelated.txt").exists());
+    }
+
+    #[test]
+    fn reserve_enforces_quota() {
+        let root = tempfile::tempdir().expect("root");
+        let space = ScratchSpace::create(root.path(), Some(100)).expect("space");
+        space.reserve(90).expect("within quota");
+        fs::write(space.forks_dir().join("fork-a.xlsx"), [0u8; 80]).expect("fork file");
+        let error = space.reserve(30).expect_err("over quota");
+        assert!(error.to_string().contains("scratch quota exceeded"));
+    }
+}
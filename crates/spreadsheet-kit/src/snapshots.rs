@@ -0,0 +1,290 @@
+//! Pre-write snapshots for in-place batch writes.
+//!
+//! With `--snapshot-dir <dir>`, an in-place batch write copies the workbook it
+//! is about to replace into the directory before replacing it. Each snapshot is
+//! a `<id>.<ext>` copy of the workbook plus a `<id>.json` record; the record is
+//! written last, so a directory listing only ever sees complete snapshots.
+//! `restore` copies a snapshot back over its workbook.
+//!
+//! With the `object-store` feature, `--snapshot-dir` also takes an `s3://` or
+//! `gs://` prefix (see [`crate::object_store`] for credentials), so stateless
+//! deployments keep their snapshots off the container's disk.
+
+#[cfg(feature = "object-store")]
+use crate::object_store::ObjectStore;
+use crate::utils::hash_file_sha256_hex;
+use anyhow::{Context, Result, anyhow, bail};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempPath;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotRecord {
+    /// Sortable identifier: UTC creation time plus a prefix of `sha256`.
+    pub id: String,
+    /// RFC 3339 UTC time the snapshot was taken.
+    pub created_at: String,
+    /// Command whose write the snapshot precedes.
+    pub command: String,
+    /// Workbook the snapshot was copied from.
+    pub source_path: String,
+    /// Name of the workbook copy inside the snapshot directory.
+    pub file_name: String,
+    pub size_bytes: u64,
+    /// SHA-256 of the workbook copy.
+    pub sha256: String,
+}
+
+/// Where `--snapshot-dir` keeps snapshots.
+#[derive(Debug)]
+pub enum SnapshotStore {
+    Dir(PathBuf),
+    /// Objects named like the files of a snapshot directory, directly under
+    /// the store's prefix.
+    #[cfg(feature = "object-store")]
+    Object(ObjectStore),
+}
+
+impl SnapshotStore {
+    /// A directory, or an `s3://`/`gs://` prefix.
+    pub fn open(location: &Path) -> Result<Self> {
+        let Some(url) = location
+            .to_str()
+            .filter(|location| location.contains("://"))
+        else {
+            return Ok(Self::Dir(location.to_path_buf()));
+        };
+        #[cfg(feature = "object-store")]
+        {
+            Ok(Self::Object(ObjectStore::from_url(url)?))
+        }
+        #[cfg(not(feature = "object-store"))]
+        {
+            bail!("snapshot store '{url}' needs a build with the object-store feature")
+        }
+    }
+
+    /// Where the workbook copy of `record` lives, for messages and responses.
+    pub fn describe(&self, record: &SnapshotRecord) -> String {
+        match self {
+            Self::Dir(dir) => dir.join(&record.file_name).display().to_string(),
+            #[cfg(feature = "object-store")]
+            Self::Object(store) => store.display_key(&record.file_name),
+        }
+    }
+}
+
+impl fmt::Display for SnapshotStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Dir(dir) => write!(f, "{}", dir.display()),
+            #[cfg(feature = "object-store")]
+            Self::Object(store) => f.write_str(store.url()),
+        }
+    }
+}
+
+/// A snapshot's workbook copy on local disk. Copies downloaded from object
+/// storage are deleted on drop.
+#[derive(Debug)]
+pub enum SnapshotCopy {
+    Local(PathBuf),
+    Downloaded(TempPath),
+}
+
+impl SnapshotCopy {
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Local(path) => path,
+            Self::Downloaded(path) => path,
+        }
+    }
+}
+
+/// Copy `source` into `store`, creating a snapshot directory if needed, and
+/// return the record of the new snapshot.
+pub fn take_snapshot(
+    store: &SnapshotStore,
+    source: &Path,
+    command: &str,
+) -> Result<SnapshotRecord> {
+    let sha256 = hash_file_sha256_hex(source)
+        .with_context(|| format!("unable to read '{}'", source.display()))?;
+    let now = chrono::Utc::now();
+    let id = format!("{}-{}", now.format("%Y%m%dT%H%M%S%3fZ"), &sha256[..8]);
+    let extension = source
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("xlsx");
+    let file_name = format!("{id}.{extension}");
+
+    let size_bytes = match store {
+        SnapshotStore::Dir(dir) => {
+            fs::create_dir_all(dir).with_context(|| {
+                format!("unable to create snapshot directory '{}'", dir.display())
+            })?;
+            let copy_path = dir.join(&file_name);
+            let staging_path = dir.join(format!(".{file_name}.tmp"));
+            let size_bytes = fs::copy(source, &staging_path)
+                .with_context(|| format!("unable to copy '{}' into snapshot", source.display()))?;
+            fs::rename(&staging_path, &copy_path)
+                .with_context(|| format!("unable to write snapshot '{}'", copy_path.display()))?;
+            size_bytes
+        }
+        #[cfg(feature = "object-store")]
+        SnapshotStore::Object(object_store) => {
+            let bytes = fs::read(source)
+                .with_context(|| format!("unable to read '{}'", source.display()))?;
+            let size_bytes = bytes.len() as u64;
+            object_store.put(&file_name, bytes)?;
+            size_bytes
+        }
+    };
+
+    let record = SnapshotRecord {
+        id,
+        created_at: now.to_rfc3339(),
+        command: command.to_string(),
+        source_path: source.display().to_string(),
+        file_name,
+        size_bytes,
+        sha256,
+    };
+    let record_bytes = serde_json::to_vec_pretty(&record)?;
+    match store {
+        SnapshotStore::Dir(dir) => {
+            let record_path = record_path(dir, &record.id);
+            let staging_path = dir.join(format!(".{}.json.tmp", record.id));
+            fs::write(&staging_path, record_bytes)
+                .and_then(|()| fs::rename(&staging_path, &record_path))
+                .with_context(|| {
+                    format!(
+                        "unable to write snapshot record '{}'",
+                        record_path.display()
+                    )
+                })?;
+        }
+        #[cfg(feature = "object-store")]
+        SnapshotStore::Object(object_store) => {
+            object_store.put(&format!("{}.json", record.id), record_bytes)?;
+        }
+    }
+    Ok(record)
+}
+
+/// Snapshots recorded in `store`, newest first.
+pub fn list_snapshots(store: &SnapshotStore) -> Result<Vec<SnapshotRecord>> {
+    let mut records = Vec::new();
+    match store {
+        SnapshotStore::Dir(dir) => {
+            let entries = fs::read_dir(dir).with_context(|| {
+                format!("unable to read snapshot directory '{}'", dir.display())
+            })?;
+            for entry in entries {
+                let path = entry?.path();
+                if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                    continue;
+                }
+                // Other JSON files may share the directory; only snapshot
+                // records parse.
+                let Ok(bytes) = fs::read(&path) else {
+                    continue;
+                };
+                if let Ok(record) = serde_json::from_slice::<SnapshotRecord>(&bytes) {
+                    records.push(record);
+                }
+            }
+        }
+        #[cfg(feature = "object-store")]
+        SnapshotStore::Object(object_store) => {
+            for key in object_store.list("")? {
+                if key.contains('/') || !key.ends_with(".json") {
+                    continue;
+                }
+                let bytes = object_store.get(&key)?;
+                if let Ok(record) = serde_json::from_slice::<SnapshotRecord>(&bytes) {
+                    records.push(record);
+                }
+            }
+        }
+    }
+    records.sort_by(|a, b| b.created_at.cmp(&a.created_at).then(b.id.cmp(&a.id)));
+    Ok(records)
+}
+
+/// Load the record for snapshot `id` and its workbook copy, checked against
+/// the recorded hash.
+pub fn load_snapshot(store: &SnapshotStore, id: &str) -> Result<(SnapshotRecord, SnapshotCopy)> {
+    if id.is_empty()
+        || id.starts_with('.')
+        || !id
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.'))
+    {
+        bail!("invalid snapshot id '{id}'");
+    }
+    let not_found = || {
+        anyhow!(
+            "snapshot '{}' not found in '{}'; run list-snapshots to see available ids",
+            id,
+            store
+        )
+    };
+
+    match store {
+        SnapshotStore::Dir(dir) => {
+            let path = record_path(dir, id);
+            let bytes = fs::read(&path).map_err(|_| not_found())?;
+            let record: SnapshotRecord = serde_json::from_slice(&bytes)
+                .with_context(|| format!("snapshot record '{}' is not valid", path.display()))?;
+
+            let copy_path = dir.join(&record.file_name);
+            let sha256 = hash_file_sha256_hex(&copy_path).with_context(|| {
+                format!("snapshot workbook '{}' is missing", copy_path.display())
+            })?;
+            if sha256 != record.sha256 {
+                bail!(
+                    "snapshot workbook '{}' does not match its recorded hash",
+                    copy_path.display()
+                );
+            }
+            Ok((record, SnapshotCopy::Local(copy_path)))
+        }
+        #[cfg(feature = "object-store")]
+        SnapshotStore::Object(object_store) => {
+            let key = format!("{id}.json");
+            let bytes = object_store.get(&key).map_err(|_| not_found())?;
+            let record: SnapshotRecord = serde_json::from_slice(&bytes).with_context(|| {
+                format!(
+                    "snapshot record '{}' is not valid",
+                    object_store.display_key(&key)
+                )
+            })?;
+
+            let location = store.describe(&record);
+            let bytes = object_store
+                .get(&record.file_name)
+                .with_context(|| format!("snapshot workbook '{location}' is missing"))?;
+            if crate::utils::hash_bytes_sha256_hex(&bytes) != record.sha256 {
+                bail!("snapshot workbook '{location}' does not match its recorded hash");
+            }
+            let extension = Path::new(&record.file_name)
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .unwrap_or("xlsx");
+            let mut copy = tempfile::Builder::new()
+                .prefix(".snapshot-")
+                .suffix(&format!(".{extension}"))
+                .tempfile()?;
+            std::io::Write::write_all(&mut copy, &bytes)
+                .with_context(|| format!("unable to download snapshot '{location}'"))?;
+            Ok((record, SnapshotCopy::Downloaded(copy.into_temp_path())))
+        }
+    }
+}
+
+fn record_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{id}.json"))
+}
@@ -10,21 +10,43 @@ use crate::recalc::FormualizerBackend;
 use crate::recalc::{GlobalRecalcLock, GlobalScreenshotLock, RecalcBackend};
 #[cfg(feature = "recalc-libreoffice")]
 use crate::recalc::{LibreOfficeBackend, RecalcConfig};
-use crate::repository::{PathWorkspaceRepository, WorkbookRepository};
+use crate::repository::{
+    PathWorkspaceRepository, ResolvedWorkbookRef, UploadStaging, VirtualWorkbookInput,
+    VirtualWorkspaceRepository, WorkbookRepository, WorkbookSource, WorkspaceEventLog,
+};
 use crate::tools::filters::WorkbookFilter;
+use crate::utils::{hash_bytes_sha256_hex, system_time_to_rfc3339};
 use crate::workbook::WorkbookContext;
-use anyhow::Result;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
 use lru::LruCache;
 use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::fs;
 use std::num::NonZeroUsize;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::task;
 
+/// Workbook bytes captured the first time a session read a workbook, with
+/// `pin_revisions` on. Later reads, including reloads after cache eviction,
+/// come from these bytes rather than the file.
+struct PinnedRevision {
+    bytes: Arc<Vec<u8>>,
+    revision_id: String,
+    last_modified: Option<DateTime<Utc>>,
+}
+
+type RevisionPins = Arc<RwLock<HashMap<WorkbookId, Arc<PinnedRevision>>>>;
+
 pub struct AppState {
     config: Arc<ServerConfig>,
     repository: Arc<dyn WorkbookRepository>,
     cache: RwLock<LruCache<WorkbookId, Arc<WorkbookContext>>>,
+    uploads: Arc<UploadStaging>,
+    /// Pinned revisions by canonical workbook id; `None` unless
+    /// `pin_revisions` is set.
+    pins: Option<RevisionPins>,
     #[cfg(feature = "recalc")]
     fork_registry: Option<Arc<ForkRegistry>>,
     #[cfg(feature = "recalc")]
@@ -45,21 +67,34 @@ impl AppState {
         let components = init_recalc_components(&config);
 
         #[cfg(feature = "recalc")]
-        let repository: Arc<dyn WorkbookRepository> = Arc::new(PathWorkspaceRepository::new(
-            config.clone(),
-            components.fork_registry.clone(),
-        ));
+        let repository: Arc<dyn WorkbookRepository> = if config.virtual_workspace {
+            Arc::new(
+                VirtualWorkspaceRepository::new(config.clone())
+                    .with_fork_registry(components.fork_registry.clone()),
+            )
+        } else {
+            Arc::new(PathWorkspaceRepository::new(
+                config.clone(),
+                components.fork_registry.clone(),
+            ))
+        };
 
         #[cfg(not(feature = "recalc"))]
-        let repository: Arc<dyn WorkbookRepository> =
-            Arc::new(PathWorkspaceRepository::new(config.clone()));
+        let repository: Arc<dyn WorkbookRepository> = if config.virtual_workspace {
+            Arc::new(VirtualWorkspaceRepository::new(config.clone()))
+        } else {
+            Arc::new(PathWorkspaceRepository::new(config.clone()))
+        };
 
         let capacity = NonZeroUsize::new(config.cache_capacity.max(1)).unwrap();
+        let pins = config.pin_revisions.then(RevisionPins::default);
 
         Self {
             config,
             repository,
             cache: RwLock::new(LruCache::new(capacity)),
+            uploads: Arc::new(UploadStaging::new()),
+            pins,
             #[cfg(feature = "recalc")]
             fork_registry: components.fork_registry,
             #[cfg(feature = "recalc")]
@@ -80,6 +115,7 @@ impl AppState {
         repository: Arc<dyn WorkbookRepository>,
     ) -> Self {
         let capacity = NonZeroUsize::new(config.cache_capacity.max(1)).unwrap();
+        let pins = config.pin_revisions.then(RevisionPins::default);
 
         #[cfg(feature = "recalc")]
         let components = init_recalc_components(&config);
@@ -88,6 +124,8 @@ impl AppState {
             config,
             repository,
             cache: RwLock::new(LruCache::new(capacity)),
+            uploads: Arc::new(UploadStaging::new()),
+            pins,
             #[cfg(feature = "recalc")]
             fork_registry: components.fork_registry,
             #[cfg(feature = "recalc")]
@@ -103,6 +141,35 @@ impl AppState {
         }
     }
 
+    /// State for one MCP session. With `pin_revisions` the session gets its
+    /// own cache and pins over the shared repository, forks, and recalc
+    /// backends; otherwise sessions share this state.
+    pub fn for_session(self: &Arc<Self>) -> Arc<Self> {
+        if self.pins.is_none() {
+            return self.clone();
+        }
+        let capacity = NonZeroUsize::new(self.config.cache_capacity.max(1)).unwrap();
+        Arc::new(Self {
+            config: self.config.clone(),
+            repository: self.repository.clone(),
+            cache: RwLock::new(LruCache::new(capacity)),
+            uploads: self.uploads.clone(),
+            pins: Some(RevisionPins::default()),
+            #[cfg(feature = "recalc")]
+            fork_registry: self.fork_registry.clone(),
+            #[cfg(feature = "recalc")]
+            recalc_backend_preference: self.recalc_backend_preference,
+            #[cfg(feature = "recalc")]
+            formualizer_backend: self.formualizer_backend.clone(),
+            #[cfg(feature = "recalc")]
+            libreoffice_backend: self.libreoffice_backend.clone(),
+            #[cfg(feature = "recalc")]
+            recalc_semaphore: self.recalc_semaphore.clone(),
+            #[cfg(feature = "recalc")]
+            screenshot_semaphore: self.screenshot_semaphore.clone(),
+        })
+    }
+
     pub fn config(&self) -> Arc<ServerConfig> {
         self.config.clone()
     }
@@ -142,6 +209,42 @@ impl AppState {
         self.repository.list(&filter)
     }
 
+    pub fn watch_workspace(&self) -> Result<Arc<WorkspaceEventLog>> {
+        self.repository.watch_workspace()
+    }
+
+    pub fn resolve_workbook(&self, workbook_id: &WorkbookId) -> Result<ResolvedWorkbookRef> {
+        self.repository.resolve(workbook_id)
+    }
+
+    pub fn workbook_bytes(&self, resolved: &ResolvedWorkbookRef) -> Result<Arc<Vec<u8>>> {
+        self.repository.workbook_bytes(resolved)
+    }
+
+    /// Store workbook bytes in the repository and drop the stale cached
+    /// context when the key was already registered.
+    pub fn register_workbook(&self, input: VirtualWorkbookInput) -> Result<ResolvedWorkbookRef> {
+        let resolved = self.repository.register_workbook(input)?;
+        self.evict_by_id(&resolved.workbook_id);
+        Ok(resolved)
+    }
+
+    /// Point `alias` at `target` (a workspace-relative path or workbook id)
+    /// for the rest of the session.
+    pub fn register_alias(&self, alias: &str, target: &str) -> Result<ResolvedWorkbookRef> {
+        self.repository.register_alias(alias, target)
+    }
+
+    /// Defined aliases and their targets, sorted by alias.
+    pub fn aliases(&self) -> Vec<(String, String)> {
+        self.repository.aliases()
+    }
+
+    /// Chunks of in-flight uploads, assembled before `register_workbook`.
+    pub fn uploads(&self) -> &UploadStaging {
+        &self.uploads
+    }
+
     pub async fn open_workbook(&self, workbook_id: &WorkbookId) -> Result<Arc<WorkbookContext>> {
         let resolved = self.repository.resolve(workbook_id)?;
         let canonical = resolved.workbook_id.clone();
@@ -152,8 +255,17 @@ impl AppState {
             }
         }
 
-        let repo = self.repository.clone();
-        let workbook = task::spawn_blocking(move || repo.load_context(&resolved)).await??;
+        let workbook = match self.pinnable_path(&resolved) {
+            Some((pins, path)) => {
+                let config = self.config.clone();
+                task::spawn_blocking(move || load_pinned_context(&config, &pins, &resolved, &path))
+                    .await??
+            }
+            None => {
+                let repo = self.repository.clone();
+                task::spawn_blocking(move || repo.load_context(&resolved)).await??
+            }
+        };
         let workbook = Arc::new(workbook);
 
         let mut cache = self.cache.write();
@@ -161,11 +273,53 @@ impl AppState {
         Ok(workbook)
     }
 
-    pub fn close_workbook(&self, workbook_id: &WorkbookId) -> Result<()> {
+    /// Evict a workbook from the cache and release its pinned revision, so
+    /// the next read sees the file as it is now. Returns whether a pin was
+    /// released.
+    pub fn close_workbook(&self, workbook_id: &WorkbookId) -> Result<bool> {
         let canonical = self.repository.resolve(workbook_id)?.workbook_id;
-        let mut cache = self.cache.write();
-        cache.pop(&canonical);
-        Ok(())
+        self.cache.write().pop(&canonical);
+        Ok(self.release_pin(&canonical))
+    }
+
+    /// The revision a session is pinned to for `workbook_id`, if any.
+    pub fn pinned_revision(&self, workbook_id: &WorkbookId) -> Option<String> {
+        self.pins
+            .as_ref()?
+            .read()
+            .get(workbook_id)
+            .map(|pinned| pinned.revision_id.clone())
+    }
+
+    fn release_pin(&self, canonical: &WorkbookId) -> bool {
+        self.pins
+            .as_ref()
+            .is_some_and(|pins| pins.write().remove(canonical).is_some())
+    }
+
+    /// Pins and file path for workbooks read through a pin: workspace files,
+    /// but not forks, whose edits the session makes itself.
+    fn pinnable_path(&self, resolved: &ResolvedWorkbookRef) -> Option<(RevisionPins, PathBuf)> {
+        let pins = self.pins.as_ref()?;
+        let WorkbookSource::Path(path) = &resolved.source else {
+            return None;
+        };
+        #[cfg(feature = "recalc")]
+        if self.fork_registry.as_ref().is_some_and(|registry| {
+            registry
+                .get_fork_path(resolved.workbook_id.as_str())
+                .is_some()
+        }) {
+            return None;
+        }
+        Some((pins.clone(), path.clone()))
+    }
+
+    /// Drop a cached context without resolving `workbook_id`, which may no
+    /// longer exist on disk. A pinned revision is kept: this runs when the
+    /// file changes under the session.
+    pub fn evict_by_id(&self, workbook_id: &WorkbookId) {
+        self.cache.write().pop(workbook_id);
     }
 
     pub fn evict_by_path(&self, path: &Path) {
@@ -186,13 +340,63 @@ impl AppState {
             return;
         }
 
+        // The session wrote this file itself, so its pin is stale.
         let mut cache = self.cache.write();
         for id in evict_ids {
             cache.pop(&id);
+            self.release_pin(&id);
         }
     }
 }
 
+/// Load a workbook from its pinned bytes, capturing them from `path` on
+/// first read.
+fn load_pinned_context(
+    config: &Arc<ServerConfig>,
+    pins: &RevisionPins,
+    resolved: &ResolvedWorkbookRef,
+    path: &Path,
+) -> Result<WorkbookContext> {
+    let existing = pins.read().get(&resolved.workbook_id).cloned();
+    let pinned = match existing {
+        Some(pinned) => pinned,
+        None => {
+            let bytes =
+                fs::read(path).with_context(|| format!("unable to read workbook {:?}", path))?;
+            let last_modified = fs::metadata(path)
+                .ok()
+                .and_then(|metadata| metadata.modified().ok())
+                .and_then(system_time_to_rfc3339);
+            let captured = Arc::new(PinnedRevision {
+                revision_id: hash_bytes_sha256_hex(&bytes),
+                bytes: Arc::new(bytes),
+                last_modified,
+            });
+            // Another read may have pinned it meanwhile; the first pin wins.
+            pins.write()
+                .entry(resolved.workbook_id.clone())
+                .or_insert(captured)
+                .clone()
+        }
+    };
+
+    let display_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "workbook.xlsx".to_string());
+    let mut workbook = WorkbookContext::load_from_bytes(
+        config,
+        &display_name,
+        pinned.bytes.as_slice(),
+        resolved.workbook_id.clone(),
+        resolved.short_id.clone(),
+        Some(pinned.revision_id.clone()),
+    )?;
+    workbook.path = path.to_path_buf();
+    workbook.last_modified = pinned.last_modified;
+    Ok(workbook)
+}
+
 #[cfg(feature = "recalc")]
 struct RecalcComponents {
     fork_registry: Option<Arc<ForkRegistry>>,
@@ -216,7 +420,13 @@ fn init_recalc_components(config: &Arc<ServerConfig>) -> RecalcComponents {
         };
     }
 
-    let fork_config = ForkConfig::default();
+    let defaults = ForkConfig::default();
+    let fork_config = ForkConfig {
+        scratch_root: config.scratch_dir.clone().unwrap_or(defaults.scratch_root),
+        scratch_max_bytes: config.scratch_max_bytes,
+        checkpoint_store: config.checkpoint_store.clone(),
+        ..defaults
+    };
     let registry = ForkRegistry::new(fork_config)
         .map(Arc::new)
         .map_err(|e| tracing::warn!("failed to init fork registry: {}", e))
@@ -4,6 +4,7 @@ use crate::model::{
     GradientFillPatch, GradientStopDescriptor, PatternFillDescriptor, PatternFillPatch,
     StyleDescriptor, StylePatch,
 };
+use crate::number_format::{FormatLocale, convert_format_code};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
@@ -462,6 +463,17 @@ fn merge_style_patch(desc: &mut StyleDescriptor, patch: &StylePatch) {
             }
         }
     }
+
+    // Locales are validated before ops are applied.
+    if let Some(conversion) = &patch.number_format_locale
+        && let Some(code) = desc.number_format.as_mut()
+        && let (Ok(from), Ok(to)) = (
+            FormatLocale::parse(&conversion.from),
+            FormatLocale::parse(&conversion.to),
+        )
+    {
+        *code = convert_format_code(code, &from, &to);
+    }
 }
 
 fn apply_font_patch(desc: &mut FontDescriptor, patch: &FontPatch) {
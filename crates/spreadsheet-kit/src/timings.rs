@@ -0,0 +1,80 @@
+//! Opt-in per-phase timing for workbook opens.
+//!
+//! The CLI `--timings` flag enables collection before the command runs and
+//! attaches the collected phases to the response payload, so slow opens can be
+//! attributed to archive open, shared-string/workbook parsing, sheet parsing,
+//! or region detection. Collection is process-wide and off by default; when
+//! disabled, [`time_phase`] runs its closure with no bookkeeping.
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// Central directory read when the xlsx archive is opened.
+pub const PHASE_ZIP_OPEN: &str = "zip_open";
+/// Workbook-level parts: shared strings table, styles, and workbook.xml.
+pub const PHASE_SST_PARSE: &str = "sst_parse";
+/// Deserializing worksheet XML into cells.
+pub const PHASE_SHEET_PARSE: &str = "sheet_parse";
+/// Table/region detection, run lazily per sheet on first structural read.
+pub const PHASE_REGION_DETECTION: &str = "region_detection";
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static PHASES: Mutex<Vec<PhaseTiming>> = Mutex::new(Vec::new());
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PhaseTiming {
+    pub phase: String,
+    pub duration_ms: f64,
+    /// How many times the phase ran (e.g. once per sheet for region detection).
+    pub count: u32,
+}
+
+pub fn enable_timings() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn timings_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Add `elapsed` to `phase`, keeping phases in first-recorded order.
+pub fn record_phase(phase: &str, elapsed: Duration) {
+    if !timings_enabled() {
+        return;
+    }
+    let millis = elapsed.as_secs_f64() * 1_000.0;
+    let mut phases = PHASES.lock();
+    match phases.iter_mut().find(|entry| entry.phase == phase) {
+        Some(entry) => {
+            entry.duration_ms += millis;
+            entry.count += 1;
+        }
+        None => phases.push(PhaseTiming {
+            phase: phase.to_string(),
+            duration_ms: millis,
+            count: 1,
+        }),
+    }
+}
+
+/// Run `f`, recording its wall time under `phase` when timings are enabled.
+pub fn time_phase<T>(phase: &str, f: impl FnOnce() -> T) -> T {
+    if !timings_enabled() {
+        return f();
+    }
+    let start = Instant::now();
+    let result = f();
+    record_phase(phase, start.elapsed());
+    result
+}
+
+/// Drain the recorded phases, rounding durations to microsecond precision.
+pub fn take_timings() -> Vec<PhaseTiming> {
+    let mut phases = std::mem::take(&mut *PHASES.lock());
+    for entry in &mut phases {
+        entry.duration_ms = (entry.duration_ms * 1_000.0).round() / 1_000.0;
+    }
+    phases
+}
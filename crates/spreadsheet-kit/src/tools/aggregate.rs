@@ -0,0 +1,174 @@
+//! Group-by aggregation behind `query_table`.
+//!
+//! Rows are grouped on the exact values of the group-by columns (text is
+//! case-sensitive, blanks form their own group) and groups are ordered by
+//! those values the way `read_table` sorts. `sum` and `avg` skip cells that
+//! are not numbers, and a group without any numbers gets a blank rather
+//! than zero.
+
+use super::param_enums::AggregateOp;
+use super::{TableAggregation, compare_sort_values};
+use crate::model::{CellValue, TableRow};
+use anyhow::{Result, anyhow};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+pub type GroupRow = Vec<Option<CellValue>>;
+
+/// Output column for `aggregation`: its alias, else e.g. `sum_Amount`.
+pub fn aggregation_name(aggregation: &TableAggregation) -> String {
+    if let Some(alias) = aggregation.alias.as_ref().filter(|a| !a.trim().is_empty()) {
+        return alias.clone();
+    }
+    match &aggregation.column {
+        Some(column) => format!("{}_{column}", aggregation.op.as_str()),
+        None => aggregation.op.as_str().to_string(),
+    }
+}
+
+/// Group `rows` on `group_by` and compute `aggregations` per group. Returns
+/// the output columns and one row per group, group-by values first.
+pub fn aggregate_rows(
+    headers: &[String],
+    rows: &[TableRow],
+    group_by: &[String],
+    aggregations: &[TableAggregation],
+) -> Result<(Vec<String>, Vec<GroupRow>)> {
+    let check_header = |column: &str, role: &str| {
+        if headers.iter().any(|header| header == column) {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "{role} column '{column}' is not a header (headers: {})",
+                headers.join(", ")
+            ))
+        }
+    };
+    for column in group_by {
+        check_header(column, "group_by")?;
+    }
+    for aggregation in aggregations {
+        match &aggregation.column {
+            Some(column) => check_header(column, aggregation.op.as_str())?,
+            None if aggregation.op == AggregateOp::Count => {}
+            None => {
+                return Err(anyhow!(
+                    "{} needs a column; only count can omit it",
+                    aggregation.op.as_str()
+                ));
+            }
+        }
+    }
+
+    let mut columns: Vec<String> = group_by.to_vec();
+    for aggregation in aggregations {
+        let name = aggregation_name(aggregation);
+        if columns.contains(&name) {
+            return Err(anyhow!(
+                "output column '{name}' appears twice; give one of them an alias"
+            ));
+        }
+        columns.push(name);
+    }
+
+    let mut groups: Vec<(GroupRow, Vec<Accumulator>)> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for row in rows {
+        let key: GroupRow = group_by
+            .iter()
+            .map(|column| row.get(column).cloned().flatten())
+            .collect();
+        let lookup = serde_json::to_string(&key)?;
+        let slot = *index.entry(lookup).or_insert_with(|| {
+            groups.push((key, vec![Accumulator::default(); aggregations.len()]));
+            groups.len() - 1
+        });
+        for (aggregation, accumulator) in aggregations.iter().zip(&mut groups[slot].1) {
+            match &aggregation.column {
+                Some(column) => accumulator.add(row.get(column).and_then(Option::as_ref)),
+                None => accumulator.count += 1,
+            }
+        }
+    }
+
+    groups.sort_by(|(a, _), (b, _)| {
+        a.iter()
+            .zip(b)
+            .map(|(a, b)| compare_sort_values(a.as_ref(), b.as_ref(), false))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    });
+
+    let rows = groups
+        .into_iter()
+        .map(|(mut key, accumulators)| {
+            key.extend(
+                aggregations
+                    .iter()
+                    .zip(accumulators)
+                    .map(|(aggregation, accumulator)| accumulator.finish(aggregation.op)),
+            );
+            key
+        })
+        .collect();
+    Ok((columns, rows))
+}
+
+#[derive(Debug, Clone, Default)]
+struct Accumulator {
+    count: u64,
+    numbers: u64,
+    sum: f64,
+    min: Option<f64>,
+    max: Option<f64>,
+    min_date: Option<String>,
+    max_date: Option<String>,
+}
+
+impl Accumulator {
+    fn add(&mut self, value: Option<&CellValue>) {
+        let Some(value) = value else {
+            return;
+        };
+        if matches!(value, CellValue::Text(text) if text.trim().is_empty()) {
+            return;
+        }
+        self.count += 1;
+        match value {
+            CellValue::Number(n) => {
+                self.numbers += 1;
+                self.sum += n;
+                self.min = Some(self.min.map_or(*n, |min| min.min(*n)));
+                self.max = Some(self.max.map_or(*n, |max| max.max(*n)));
+            }
+            // ISO-8601 text orders chronologically.
+            CellValue::Date(date) => {
+                if self.min_date.as_ref().is_none_or(|min| date < min) {
+                    self.min_date = Some(date.clone());
+                }
+                if self.max_date.as_ref().is_none_or(|max| date > max) {
+                    self.max_date = Some(date.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn finish(self, op: AggregateOp) -> Option<CellValue> {
+        match op {
+            AggregateOp::Count => Some(CellValue::Number(self.count as f64)),
+            AggregateOp::Sum => (self.numbers > 0).then_some(CellValue::Number(self.sum)),
+            AggregateOp::Avg => {
+                (self.numbers > 0).then(|| CellValue::Number(self.sum / self.numbers as f64))
+            }
+            AggregateOp::Min => self
+                .min
+                .map(CellValue::Number)
+                .or(self.min_date.map(CellValue::Date)),
+            AggregateOp::Max => self
+                .max
+                .map(CellValue::Number)
+                .or(self.max_date.map(CellValue::Date)),
+        }
+    }
+}
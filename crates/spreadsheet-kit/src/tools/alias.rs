@@ -0,0 +1,71 @@
+//! Tools for registering and listing workbook aliases. Resolution itself
+//! happens in the repository, so every tool taking a workbook id accepts an
+//! alias without changes of its own.
+
+use crate::model::{ListAliasesResponse, RegisterAliasResponse, WorkbookAliasInfo, WorkbookId};
+use crate::state::AppState;
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::sync::Arc;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct RegisterAliasParams {
+    /// Name to register, e.g. "q3-model" (letters, digits, '-', '_', '.')
+    pub alias: String,
+    /// Workspace-relative path (e.g. "finance/q3/model_v7.xlsx"), workbook
+    /// id, short id, or virtual key the alias points at
+    pub target: String,
+}
+
+pub async fn register_alias(
+    state: Arc<AppState>,
+    params: RegisterAliasParams,
+) -> Result<RegisterAliasResponse> {
+    let alias = params.alias.trim().to_string();
+    let target = params.target.trim().to_string();
+    let replaced_target = state
+        .aliases()
+        .into_iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(&alias))
+        .map(|(_, previous)| previous)
+        .filter(|previous| *previous != target);
+    let resolved = state.register_alias(&alias, &target)?;
+    Ok(RegisterAliasResponse {
+        alias,
+        target,
+        workbook_id: resolved.workbook_id,
+        short_id: resolved.short_id,
+        replaced_target,
+    })
+}
+
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+pub struct ListAliasesParams {}
+
+pub async fn list_aliases(
+    state: Arc<AppState>,
+    _params: ListAliasesParams,
+) -> Result<ListAliasesResponse> {
+    let aliases = state
+        .aliases()
+        .into_iter()
+        .map(
+            |(alias, target)| match state.resolve_workbook(&WorkbookId(alias.clone())) {
+                Ok(resolved) => WorkbookAliasInfo {
+                    alias,
+                    target,
+                    workbook_id: Some(resolved.workbook_id),
+                    error: None,
+                },
+                Err(error) => WorkbookAliasInfo {
+                    alias,
+                    target,
+                    workbook_id: None,
+                    error: Some(format!("{error:#}")),
+                },
+            },
+        )
+        .collect();
+    Ok(ListAliasesResponse { aliases })
+}
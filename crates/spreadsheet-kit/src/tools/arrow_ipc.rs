@@ -0,0 +1,160 @@
+//! Arrow IPC (Feather v2) encoding of table reads, for handing rows to
+//! dataframe tooling without a text round-trip.
+//!
+//! Column types follow the `table_profile` inference: a column holding any
+//! number becomes nullable `Float64`, any other non-empty column nullable
+//! `Utf8`, and an all-empty column `Utf8` of nulls. Non-numeric cells in a
+//! `Float64` column are written as null and counted in
+//! [`ArrowColumn::coerced_to_null`]. Bools and dates in `Utf8` columns keep
+//! the text `read_table` reports for them (`true`, ISO `YYYY-MM-DD`).
+
+use super::summarize_columns;
+use crate::model::{CellValue, TableRow};
+use anyhow::Result;
+use arrow_array::{ArrayRef, Float64Array, RecordBatch, StringArray};
+use arrow_ipc::writer::FileWriter;
+use arrow_schema::{DataType, Field, Schema};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// One column of an encoded table.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ArrowColumn {
+    pub name: String,
+    /// Arrow type written: `float64` or `utf8`.
+    pub data_type: String,
+    /// Cells that did not fit the column type and were written as null.
+    pub coerced_to_null: u32,
+}
+
+/// Build a record batch from table rows, one column per header.
+pub fn table_record_batch(
+    headers: &[String],
+    rows: &[TableRow],
+) -> Result<(RecordBatch, Vec<ArrowColumn>)> {
+    let profile = summarize_columns(headers, rows);
+    let mut fields = Vec::with_capacity(headers.len());
+    let mut arrays: Vec<ArrayRef> = Vec::with_capacity(headers.len());
+    let mut columns = Vec::with_capacity(headers.len());
+
+    for (header, summary) in headers.iter().zip(&profile) {
+        let cells = rows
+            .iter()
+            .map(|row| row.get(header).and_then(|cell| cell.as_ref()));
+        let mut coerced_to_null = 0u32;
+        let (data_type, array): (DataType, ArrayRef) = if summary.inferred_type == "number" {
+            let values: Vec<Option<f64>> = cells
+                .map(|cell| match cell {
+                    Some(CellValue::Number(number)) => Some(*number),
+                    Some(_) => {
+                        coerced_to_null += 1;
+                        None
+                    }
+                    None => None,
+                })
+                .collect();
+            (DataType::Float64, Arc::new(Float64Array::from(values)))
+        } else {
+            let values: Vec<Option<String>> = cells.map(|cell| cell.map(cell_text)).collect();
+            (DataType::Utf8, Arc::new(StringArray::from(values)))
+        };
+
+        columns.push(ArrowColumn {
+            name: header.clone(),
+            data_type: match data_type {
+                DataType::Float64 => "float64",
+                _ => "utf8",
+            }
+            .to_string(),
+            coerced_to_null,
+        });
+        fields.push(Field::new(header, data_type, true));
+        arrays.push(array);
+    }
+
+    let batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)?;
+    Ok((batch, columns))
+}
+
+/// Encode table rows as an Arrow IPC file (Feather v2).
+pub fn table_to_ipc_file(
+    headers: &[String],
+    rows: &[TableRow],
+) -> Result<(Vec<u8>, Vec<ArrowColumn>)> {
+    let (batch, columns) = table_record_batch(headers, rows)?;
+    let mut buffer = Vec::new();
+    {
+        let mut writer = FileWriter::try_new(&mut buffer, batch.schema_ref())?;
+        writer.write(&batch)?;
+        writer.finish()?;
+    }
+    Ok((buffer, columns))
+}
+
+fn cell_text(value: &CellValue) -> String {
+    match value {
+        CellValue::Text(text) => text.clone(),
+        CellValue::Number(number) => number.to_string(),
+        CellValue::Bool(flag) => flag.to_string(),
+        CellValue::Error(error) => error.clone(),
+        CellValue::Date(date) => date.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::Array;
+    use arrow_ipc::reader::FileReader;
+    use std::io::Cursor;
+
+    fn row(cells: &[(&str, Option<CellValue>)]) -> TableRow {
+        cells
+            .iter()
+            .map(|(header, value)| (header.to_string(), value.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn ipc_file_round_trips_profiled_column_types() {
+        let headers = vec!["Name".to_string(), "Amount".to_string()];
+        let rows = vec![
+            row(&[
+                ("Name", Some(CellValue::Text("Alice".to_string()))),
+                ("Amount", Some(CellValue::Number(10.5))),
+            ]),
+            row(&[
+                ("Name", None),
+                ("Amount", Some(CellValue::Text("n/a".to_string()))),
+            ]),
+        ];
+
+        let (bytes, columns) = table_to_ipc_file(&headers, &rows).expect("encode");
+        assert_eq!(columns[0].data_type, "utf8");
+        assert_eq!(columns[1].data_type, "float64");
+        assert_eq!(columns[1].coerced_to_null, 1);
+
+        let reader = FileReader::try_new(Cursor::new(bytes), None).expect("reader");
+        let batches = reader
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .expect("batches");
+        assert_eq!(batches.len(), 1);
+        let batch = &batches[0];
+        assert_eq!(batch.num_rows(), 2);
+        let amounts = batch
+            .column(1)
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .expect("float64 column");
+        assert_eq!(amounts.value(0), 10.5);
+        assert!(amounts.is_null(1));
+        let names = batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .expect("utf8 column");
+        assert_eq!(names.value(0), "Alice");
+        assert!(names.is_null(1));
+    }
+}
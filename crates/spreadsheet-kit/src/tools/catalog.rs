@@ -0,0 +1,354 @@
+//! Workspace-wide catalog and search, for locating the right workbook before
+//! drilling into it with per-workbook tools.
+//!
+//! Both tools walk the repository listing (`list_workbooks` filters apply)
+//! and open each workbook through the shared cache. A workbook that fails to
+//! open is reported alongside the results rather than failing the call.
+
+use super::filters::WorkbookFilter;
+use super::{FindValueParams, MatchMode, find_value};
+use crate::model::{
+    CatalogSheet, CatalogTable, CatalogWorkbook, FindMode, NamedItemKind, RegionKind,
+    WorkbookDescriptor, WorkspaceCatalogResponse, WorkspaceSearchHit, WorkspaceSearchResponse,
+    WorkspaceSearchSkipped,
+};
+use crate::state::AppState;
+use crate::workbook::WorkbookContext;
+use anyhow::{Result, bail};
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::sync::Arc;
+
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+pub struct WorkspaceCatalogParams {
+    /// Filter by workbook slug prefix
+    #[serde(default)]
+    pub slug_prefix: Option<String>,
+    /// Filter by folder path
+    #[serde(default)]
+    pub folder: Option<String>,
+    /// Filter by glob pattern (e.g., "**/*.xlsx")
+    #[serde(default)]
+    pub path_glob: Option<String>,
+    /// Maximum number of workbooks to return (default: 25)
+    #[serde(default)]
+    pub limit: Option<u32>,
+    /// Offset for pagination; use next_offset from previous response
+    #[serde(default)]
+    pub offset: Option<u32>,
+    /// Detect tables on every sheet (default: true). Turn off for a quick
+    /// listing of sheet names and sizes.
+    #[serde(default)]
+    pub include_tables: Option<bool>,
+}
+
+pub async fn workspace_catalog(
+    state: Arc<AppState>,
+    params: WorkspaceCatalogParams,
+) -> Result<WorkspaceCatalogResponse> {
+    let filter = WorkbookFilter::new(params.slug_prefix, params.folder, params.path_glob)?;
+    let listing = state.list_workbooks(filter)?.workbooks;
+    let offset = params.offset.unwrap_or(0) as usize;
+    let limit = params.limit.unwrap_or(25).max(1) as usize;
+    let include_tables = params.include_tables.unwrap_or(true);
+
+    let mut workbooks = Vec::new();
+    for descriptor in listing.iter().skip(offset).take(limit) {
+        let entry = match state.open_workbook(&descriptor.workbook_id).await {
+            Ok(workbook) => {
+                let sheets =
+                    tokio::task::spawn_blocking(move || catalog_sheets(&workbook, include_tables))
+                        .await?;
+                match sheets {
+                    Ok(sheets) => catalog_entry(descriptor, sheets, None),
+                    Err(error) => catalog_entry(descriptor, Vec::new(), Some(error)),
+                }
+            }
+            Err(error) => catalog_entry(descriptor, Vec::new(), Some(error)),
+        };
+        workbooks.push(entry);
+    }
+
+    let next = offset + workbooks.len();
+    Ok(WorkspaceCatalogResponse {
+        workbooks,
+        next_offset: (next < listing.len()).then_some(next as u32),
+    })
+}
+
+fn catalog_entry(
+    descriptor: &WorkbookDescriptor,
+    sheets: Vec<CatalogSheet>,
+    error: Option<anyhow::Error>,
+) -> CatalogWorkbook {
+    CatalogWorkbook {
+        workbook_id: descriptor.workbook_id.clone(),
+        short_id: descriptor.short_id.clone(),
+        slug: descriptor.slug.clone(),
+        folder: descriptor.folder.clone(),
+        path: descriptor.path.clone(),
+        bytes: descriptor.bytes,
+        last_modified: descriptor.last_modified.clone(),
+        sheet_count: sheets.len() as u32,
+        sheets,
+        error: error.map(|error| format!("{error:#}")),
+    }
+}
+
+fn catalog_sheets(workbook: &WorkbookContext, include_tables: bool) -> Result<Vec<CatalogSheet>> {
+    let excel_tables = if include_tables {
+        workbook
+            .named_items()?
+            .into_iter()
+            .filter(|item| item.kind == NamedItemKind::Table)
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let mut sheets = Vec::new();
+    for name in workbook.sheet_names() {
+        let (entry, regions) = if include_tables {
+            let entry = workbook.get_sheet_metrics(&name)?;
+            let regions = entry.detected_regions();
+            (entry, regions)
+        } else {
+            (workbook.get_sheet_metrics_fast(&name)?, Vec::new())
+        };
+
+        let mut tables: Vec<CatalogTable> = excel_tables
+            .iter()
+            .filter(|item| item.sheet_name.as_deref() == Some(name.as_str()))
+            .map(|item| CatalogTable {
+                table_name: Some(item.name.clone()),
+                region_id: None,
+                range: item.refers_to.clone(),
+                headers: Vec::new(),
+                row_count: None,
+            })
+            .collect();
+        tables.extend(
+            regions
+                .into_iter()
+                .filter(|region| {
+                    matches!(region.classification, RegionKind::Table | RegionKind::Data)
+                })
+                .map(|region| CatalogTable {
+                    table_name: None,
+                    region_id: Some(region.id),
+                    range: region.bounds,
+                    headers: region.headers,
+                    row_count: Some(region.row_count),
+                }),
+        );
+
+        sheets.push(CatalogSheet {
+            name,
+            row_count: entry.metrics.row_count,
+            column_count: entry.metrics.column_count,
+            tables,
+        });
+    }
+    Ok(sheets)
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct WorkspaceSearchParams {
+    /// Find workbooks with a sheet whose name matches, e.g. "Cap Table"
+    #[serde(default)]
+    pub sheet_name: Option<String>,
+    /// Find workbooks with a cell matching this value or label text
+    #[serde(default)]
+    pub query: Option<String>,
+    /// How `query` is matched: "value" (default) matches cell values,
+    /// "label" finds label cells and reports the value next to them
+    #[serde(default)]
+    pub mode: Option<FindMode>,
+    /// Text match mode for `sheet_name` and `query` (default: contains)
+    #[serde(default)]
+    pub match_mode: Option<MatchMode>,
+    /// Case-sensitive matching (default: false)
+    #[serde(default)]
+    pub case_sensitive: bool,
+    /// Filter by workbook slug prefix
+    #[serde(default)]
+    pub slug_prefix: Option<String>,
+    /// Filter by folder path
+    #[serde(default)]
+    pub folder: Option<String>,
+    /// Filter by glob pattern (e.g., "**/*.xlsx")
+    #[serde(default)]
+    pub path_glob: Option<String>,
+    /// Cell matches returned per workbook (default: 5)
+    #[serde(default)]
+    pub matches_per_workbook: Option<u32>,
+    /// Maximum number of workbooks to open (default: 200)
+    #[serde(default)]
+    pub max_workbooks: Option<u32>,
+}
+
+pub async fn workspace_search(
+    state: Arc<AppState>,
+    params: WorkspaceSearchParams,
+) -> Result<WorkspaceSearchResponse> {
+    let sheet_query = params.sheet_name.filter(|name| !name.trim().is_empty());
+    let cell_query = params.query.filter(|query| !query.trim().is_empty());
+    if sheet_query.is_none() && cell_query.is_none() {
+        bail!("workspace_search needs a sheet_name, a query, or both");
+    }
+    let match_mode = params.match_mode.unwrap_or_default();
+    let sheet_matcher = sheet_query
+        .as_deref()
+        .map(|query| NameMatcher::new(query, match_mode, params.case_sensitive))
+        .transpose()?;
+    let matches_per_workbook = params.matches_per_workbook.unwrap_or(5).max(1);
+    let max_workbooks = params.max_workbooks.unwrap_or(200) as usize;
+
+    let filter = WorkbookFilter::new(params.slug_prefix, params.folder, params.path_glob)?;
+    let listing = state.list_workbooks(filter)?.workbooks;
+    let truncated = listing.len() > max_workbooks;
+
+    let mut hits = Vec::new();
+    let mut skipped = Vec::new();
+    let mut workbooks_searched = 0u32;
+    for descriptor in listing.iter().take(max_workbooks) {
+        let skip = |reason: anyhow::Error| WorkspaceSearchSkipped {
+            workbook_id: descriptor.workbook_id.clone(),
+            slug: descriptor.slug.clone(),
+            reason: format!("{reason:#}"),
+        };
+        let workbook = match state.open_workbook(&descriptor.workbook_id).await {
+            Ok(workbook) => workbook,
+            Err(error) => {
+                skipped.push(skip(error));
+                continue;
+            }
+        };
+        workbooks_searched += 1;
+
+        let matching_sheets: Vec<String> = match &sheet_matcher {
+            Some(matcher) => workbook
+                .sheet_names()
+                .into_iter()
+                .filter(|name| matcher.matches(name))
+                .collect(),
+            None => Vec::new(),
+        };
+        if sheet_matcher.is_some() && matching_sheets.is_empty() {
+            continue;
+        }
+
+        let (matches, more_matches) = match &cell_query {
+            Some(query) => {
+                // With both filters, only the matching sheets are searched.
+                let sheets: Vec<Option<String>> = if matching_sheets.is_empty() {
+                    vec![None]
+                } else {
+                    matching_sheets.iter().cloned().map(Some).collect()
+                };
+                let mut matches = Vec::new();
+                let mut more_matches = false;
+                for sheet_name in sheets {
+                    let remaining = matches_per_workbook - matches.len() as u32;
+                    if remaining == 0 {
+                        more_matches = true;
+                        break;
+                    }
+                    let mode = params.mode.clone().unwrap_or(FindMode::Value);
+                    let find = FindValueParams {
+                        workbook_or_fork_id: descriptor.workbook_id.clone(),
+                        query: query.clone(),
+                        label: matches!(mode, FindMode::Label).then(|| query.clone()),
+                        mode: Some(mode),
+                        match_mode: Some(match_mode),
+                        case_sensitive: params.case_sensitive,
+                        sheet_name,
+                        limit: remaining,
+                        ..FindValueParams::default()
+                    };
+                    match find_value(state.clone(), find).await {
+                        Ok(found) => {
+                            more_matches |= found.next_offset.is_some();
+                            matches.extend(found.matches);
+                        }
+                        Err(error) => {
+                            skipped.push(skip(error));
+                            break;
+                        }
+                    }
+                }
+                if matches.is_empty() {
+                    continue;
+                }
+                (matches, more_matches)
+            }
+            None => (Vec::new(), false),
+        };
+
+        hits.push(WorkspaceSearchHit {
+            workbook_id: descriptor.workbook_id.clone(),
+            slug: descriptor.slug.clone(),
+            path: descriptor.path.clone(),
+            matching_sheets,
+            matches,
+            more_matches,
+        });
+    }
+
+    Ok(WorkspaceSearchResponse {
+        workbooks_searched,
+        hits,
+        truncated,
+        skipped,
+    })
+}
+
+/// Sheet-name matching with the same semantics `find_value` applies to cell
+/// text.
+struct NameMatcher {
+    needle: String,
+    mode: MatchMode,
+    case_sensitive: bool,
+    regex: Option<Regex>,
+}
+
+impl NameMatcher {
+    fn new(query: &str, mode: MatchMode, case_sensitive: bool) -> Result<Self> {
+        let regex = match mode {
+            MatchMode::Regex => Some(
+                regex::RegexBuilder::new(query)
+                    .case_insensitive(!case_sensitive)
+                    .build()?,
+            ),
+            _ => None,
+        };
+        Ok(Self {
+            needle: if case_sensitive {
+                query.to_string()
+            } else {
+                query.to_lowercase()
+            },
+            mode,
+            case_sensitive,
+            regex,
+        })
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        let haystack = if self.case_sensitive {
+            name.to_string()
+        } else {
+            name.to_lowercase()
+        };
+        match self.mode {
+            MatchMode::Exact => haystack == self.needle,
+            MatchMode::Prefix => haystack.starts_with(&self.needle),
+            MatchMode::Contains => haystack.contains(&self.needle),
+            MatchMode::Regex => self
+                .regex
+                .as_ref()
+                .is_some_and(|regex| regex.is_match(name)),
+        }
+    }
+}
@@ -0,0 +1,118 @@
+//! Whole-column statistics behind `table_profile`.
+//!
+//! Every data row counts, not just the sample. Value frequencies key numbers,
+//! booleans and dates by their text form, and text is case-sensitive, as in
+//! `query_table` grouping. Outliers use Tukey's fences (1.5 interquartile
+//! ranges beyond the quartiles, with quartiles interpolated as Excel's
+//! `QUARTILE.INC`), and are only flagged in columns with at least
+//! [`MIN_VALUES_FOR_OUTLIERS`] numbers.
+
+use crate::model::{CellValue, ColumnOutliers, ColumnStats, OutlierCell, TableRow, ValueFrequency};
+use std::collections::HashMap;
+
+pub const DEFAULT_TOP_N: usize = 5;
+pub const MIN_VALUES_FOR_OUTLIERS: usize = 4;
+
+const OUTLIER_FENCE_IQRS: f64 = 1.5;
+const MAX_OUTLIER_CELLS: usize = 5;
+
+/// Statistics for `column` over `rows`, where `rows[i]` sits on sheet row
+/// `first_row + i`.
+pub fn column_stats(column: &str, rows: &[TableRow], first_row: u32, top_n: usize) -> ColumnStats {
+    let mut nulls = 0u32;
+    let mut frequencies: HashMap<String, u32> = HashMap::new();
+    let mut numbers: Vec<(u32, f64)> = Vec::new();
+
+    for (index, row) in rows.iter().enumerate() {
+        let key = match row.get(column).and_then(Option::as_ref) {
+            None => None,
+            Some(CellValue::Text(text)) if text.trim().is_empty() => None,
+            Some(CellValue::Number(n)) => {
+                numbers.push((first_row + index as u32, *n));
+                Some(n.to_string())
+            }
+            Some(CellValue::Text(text)) => Some(text.clone()),
+            Some(CellValue::Bool(b)) => Some(b.to_string()),
+            Some(CellValue::Date(date)) => Some(date.clone()),
+            Some(CellValue::Error(error)) => Some(error.clone()),
+        };
+        match key {
+            Some(key) => *frequencies.entry(key).or_default() += 1,
+            None => nulls += 1,
+        }
+    }
+
+    let count = rows.len() as u32 - nulls;
+    let distinct = frequencies.len() as u32;
+    let mut top_values: Vec<ValueFrequency> = frequencies
+        .into_iter()
+        .map(|(value, count)| ValueFrequency { value, count })
+        .collect();
+    // Ties break on the value so output is stable.
+    top_values.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+    top_values.truncate(top_n);
+
+    let values: Vec<f64> = numbers.iter().map(|(_, value)| *value).collect();
+    let mean = (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64);
+    let stddev = mean.filter(|_| values.len() > 1).map(|mean| {
+        let squares: f64 = values.iter().map(|value| (value - mean).powi(2)).sum();
+        (squares / (values.len() - 1) as f64).sqrt()
+    });
+
+    ColumnStats {
+        count,
+        nulls,
+        null_pct: if rows.is_empty() {
+            0.0
+        } else {
+            f64::from(nulls) * 100.0 / rows.len() as f64
+        },
+        distinct,
+        top_values,
+        numeric_count: values.len() as u32,
+        min: values.iter().copied().reduce(f64::min),
+        max: values.iter().copied().reduce(f64::max),
+        mean,
+        stddev,
+        outliers: outliers(&numbers),
+    }
+}
+
+fn outliers(numbers: &[(u32, f64)]) -> Option<ColumnOutliers> {
+    if numbers.len() < MIN_VALUES_FOR_OUTLIERS {
+        return None;
+    }
+    let mut sorted: Vec<f64> = numbers.iter().map(|(_, value)| *value).collect();
+    sorted.sort_by(f64::total_cmp);
+    let q1 = quantile(&sorted, 0.25);
+    let q3 = quantile(&sorted, 0.75);
+    let iqr = q3 - q1;
+    let lower_fence = q1 - OUTLIER_FENCE_IQRS * iqr;
+    let upper_fence = q3 + OUTLIER_FENCE_IQRS * iqr;
+
+    let outlying: Vec<OutlierCell> = numbers
+        .iter()
+        .filter(|(_, value)| *value < lower_fence || *value > upper_fence)
+        .map(|(row, value)| OutlierCell {
+            row: *row,
+            value: *value,
+        })
+        .collect();
+    if outlying.is_empty() {
+        return None;
+    }
+    Some(ColumnOutliers {
+        lower_fence,
+        upper_fence,
+        count: outlying.len() as u32,
+        cells: outlying.into_iter().take(MAX_OUTLIER_CELLS).collect(),
+    })
+}
+
+/// Linearly interpolated quantile of non-empty `sorted` values.
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let position = q * (sorted.len() - 1) as f64;
+    let lower = position.floor() as usize;
+    let upper = position.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (position - lower as f64)
+}
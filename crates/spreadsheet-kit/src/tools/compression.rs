@@ -0,0 +1,187 @@
+//! Optional compression of large read results.
+//!
+//! With `compression` set, `read_table`, `sheet_page` and `range_values`
+//! serialize their data fields as one JSON object, compress it, base64-encode
+//! it and return it in `compressed` in their place, alongside the sizes
+//! before and after. Results smaller than `compress_min_bytes` come back
+//! uncompressed, since the base64 overhead outweighs the saving on small
+//! pages.
+
+use crate::model::{
+    CompressedPayload, RangeValuesResponse, ReadTableResponse, ResponseCompression,
+    SheetPageResponse,
+};
+use anyhow::{Context, Result};
+use base64::Engine;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::io::Write;
+
+pub const DEFAULT_COMPRESS_MIN_BYTES: u64 = 64 * 1024;
+
+#[cfg(not(target_arch = "wasm32"))]
+const ZSTD_LEVEL: i32 = 3;
+
+/// A response whose bulk data can move into a [`CompressedPayload`].
+pub trait CompressibleResponse {
+    /// The data fields that are present, keyed by their JSON names.
+    fn data_fields(&self) -> Result<Map<String, Value>>;
+
+    /// Drop the data fields and carry `payload` in their place.
+    fn replace_data(&mut self, payload: CompressedPayload);
+}
+
+/// Move the data fields of `response` into a compressed blob when they
+/// serialize to at least `min_bytes`.
+pub fn compress_response(
+    response: &mut impl CompressibleResponse,
+    encoding: ResponseCompression,
+    min_bytes: u64,
+) -> Result<()> {
+    let data = response.data_fields()?;
+    let fields: Vec<String> = data.keys().cloned().collect();
+    let original = serde_json::to_vec(&data)?;
+    if fields.is_empty() || (original.len() as u64) < min_bytes {
+        return Ok(());
+    }
+
+    let compressed = compress_bytes(&original, encoding)?;
+    response.replace_data(CompressedPayload {
+        encoding,
+        fields,
+        original_bytes: original.len() as u64,
+        compressed_bytes: compressed.len() as u64,
+        data: base64::engine::general_purpose::STANDARD.encode(&compressed),
+    });
+    Ok(())
+}
+
+fn insert_field(data: &mut Map<String, Value>, name: &str, value: &impl Serialize) -> Result<()> {
+    data.insert(name.into(), serde_json::to_value(value)?);
+    Ok(())
+}
+
+impl CompressibleResponse for ReadTableResponse {
+    fn data_fields(&self) -> Result<Map<String, Value>> {
+        let mut data = Map::new();
+        if !self.headers.is_empty() {
+            insert_field(&mut data, "headers", &self.headers)?;
+        }
+        if !self.rows.is_empty() {
+            insert_field(&mut data, "rows", &self.rows)?;
+        }
+        if let Some(values) = &self.values {
+            insert_field(&mut data, "values", values)?;
+        }
+        if let Some(types) = &self.types {
+            insert_field(&mut data, "types", types)?;
+        }
+        if let Some(date_serials) = &self.date_serials {
+            insert_field(&mut data, "date_serials", date_serials)?;
+        }
+        if let Some(csv) = &self.csv {
+            insert_field(&mut data, "csv", csv)?;
+        }
+        Ok(data)
+    }
+
+    fn replace_data(&mut self, payload: CompressedPayload) {
+        self.headers.clear();
+        self.rows.clear();
+        self.values = None;
+        self.types = None;
+        self.date_serials = None;
+        self.csv = None;
+        self.compressed = Some(payload);
+    }
+}
+
+impl CompressibleResponse for SheetPageResponse {
+    fn data_fields(&self) -> Result<Map<String, Value>> {
+        let mut data = Map::new();
+        if !self.rows.is_empty() {
+            insert_field(&mut data, "rows", &self.rows)?;
+        }
+        if let Some(header_row) = &self.header_row {
+            insert_field(&mut data, "header_row", header_row)?;
+        }
+        if let Some(compact) = &self.compact {
+            insert_field(&mut data, "compact", compact)?;
+        }
+        if let Some(values_only) = &self.values_only {
+            insert_field(&mut data, "values_only", values_only)?;
+        }
+        if let Some(column_page) = &self.column_page {
+            insert_field(&mut data, "column_page", column_page)?;
+        }
+        if let Some(date_serials) = &self.date_serials {
+            insert_field(&mut data, "date_serials", date_serials)?;
+        }
+        Ok(data)
+    }
+
+    fn replace_data(&mut self, payload: CompressedPayload) {
+        self.rows.clear();
+        self.header_row = None;
+        self.compact = None;
+        self.values_only = None;
+        self.column_page = None;
+        self.date_serials = None;
+        self.compressed = Some(payload);
+    }
+}
+
+/// Each entry keeps its `range` and `next_start_row` in the compressed
+/// `values`, so callers decode before paging.
+impl CompressibleResponse for RangeValuesResponse {
+    fn data_fields(&self) -> Result<Map<String, Value>> {
+        let mut data = Map::new();
+        if !self.values.is_empty() {
+            insert_field(&mut data, "values", &self.values)?;
+        }
+        Ok(data)
+    }
+
+    fn replace_data(&mut self, payload: CompressedPayload) {
+        self.values.clear();
+        self.compressed = Some(payload);
+    }
+}
+
+pub fn compress_bytes(bytes: &[u8], encoding: ResponseCompression) -> Result<Vec<u8>> {
+    match encoding {
+        ResponseCompression::Gzip => {
+            let mut encoder =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(bytes)?;
+            encoder.finish().context("gzip compression failed")
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        ResponseCompression::Zstd => {
+            zstd::stream::encode_all(bytes, ZSTD_LEVEL).context("zstd compression failed")
+        }
+        #[cfg(target_arch = "wasm32")]
+        ResponseCompression::Zstd => {
+            anyhow::bail!("zstd compression is not available in this build; use gzip")
+        }
+    }
+}
+
+/// Reverse [`compress_bytes`].
+pub fn decompress_bytes(bytes: &[u8], encoding: ResponseCompression) -> Result<Vec<u8>> {
+    match encoding {
+        ResponseCompression::Gzip => {
+            let mut decoder = flate2::write::GzDecoder::new(Vec::new());
+            decoder.write_all(bytes)?;
+            decoder.finish().context("gzip decompression failed")
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        ResponseCompression::Zstd => {
+            zstd::stream::decode_all(bytes).context("zstd decompression failed")
+        }
+        #[cfg(target_arch = "wasm32")]
+        ResponseCompression::Zstd => {
+            anyhow::bail!("zstd compression is not available in this build; use gzip")
+        }
+    }
+}
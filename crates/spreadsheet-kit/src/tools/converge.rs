@@ -0,0 +1,441 @@
+//! Desired-state planning behind the CLI `plan` command.
+//!
+//! A spec describes blocks of cells as they should end up: values, formulas
+//! and style patches. The planner compares them with the workbook and emits
+//! only the ops needed to get there, as an `apply-plan` payload whose steps
+//! also work as `transform-batch` and `style-batch` payloads. Cells already
+//! in the desired state produce no ops, so planning again after applying a
+//! plan yields an empty one.
+//!
+//! Values compare on the stored cell text: numbers numerically, booleans
+//! case-insensitively and text exactly. Formulas compare on their text
+//! without the leading `=`. Styles compare on the style a patch would leave,
+//! so a patch the cell already satisfies is not emitted.
+
+use crate::styles::{StylePatchMode, apply_style_patch, descriptor_from_style, stable_style_id};
+use crate::tools::fork::{
+    MatrixCell, StyleBatchParamsInput, StyleOp, StyleOpInput, StyleTarget, TransformOp,
+    TransformTarget, normalize_style_batch,
+};
+use crate::utils::cell_address;
+use anyhow::{Result, anyhow, bail};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use umya_spreadsheet::{Spreadsheet, Style, Worksheet};
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DesiredStateSpec {
+    pub targets: Vec<DesiredTarget>,
+}
+
+/// One block of cells on a sheet. Later targets win where blocks overlap.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DesiredTarget {
+    pub sheet_name: String,
+    /// Top-left cell of `rows` (default `A1`)
+    #[serde(default)]
+    pub anchor: Option<String>,
+    /// Cell contents row by row from `anchor`. `null` means empty, text
+    /// starting with `=` is a formula, and `{"v": ...}` or `{"f": ...}`
+    /// spell out a value or formula. Cells past the end of a short row are
+    /// left alone.
+    #[serde(default)]
+    pub rows: Vec<Vec<DesiredCell>>,
+    #[serde(default)]
+    pub styles: Vec<DesiredStyle>,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum DesiredCell {
+    Explicit(MatrixCell),
+    Plain(Value),
+}
+
+/// A style patch, as in `style-batch`, that every cell of `range` should
+/// satisfy.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DesiredStyle {
+    pub range: String,
+    pub patch: Value,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PlannedAction {
+    Write,
+    Clear,
+    Restyle,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct PlannedChange {
+    pub sheet_name: String,
+    pub cell: String,
+    pub action: PlannedAction,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub desired: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct ConvergencePlan {
+    pub cells_checked: u32,
+    pub cells_to_write: u32,
+    pub cells_to_clear: u32,
+    pub cells_to_restyle: u32,
+    pub changes: Vec<PlannedChange>,
+    pub transform_ops: Vec<TransformOp>,
+    pub style_ops: Vec<StyleOp>,
+}
+
+impl ConvergencePlan {
+    pub fn is_converged(&self) -> bool {
+        self.transform_ops.is_empty() && self.style_ops.is_empty()
+    }
+
+    pub fn op_count(&self) -> usize {
+        self.transform_ops.len() + self.style_ops.len()
+    }
+
+    /// The plan as an `apply-plan` payload, with empty steps left out.
+    pub fn to_apply_plan_payload(&self) -> Result<Value> {
+        let mut steps = Vec::new();
+        if !self.transform_ops.is_empty() {
+            steps.push(serde_json::json!({
+                "kind": "transform",
+                "ops": serde_json::to_value(&self.transform_ops)?,
+            }));
+        }
+        if !self.style_ops.is_empty() {
+            steps.push(serde_json::json!({
+                "kind": "style",
+                "ops": serde_json::to_value(&self.style_ops)?,
+            }));
+        }
+        Ok(serde_json::json!({ "steps": steps }))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Content {
+    Empty,
+    Value(Value),
+    Formula(String),
+}
+
+impl Content {
+    fn desired(cell: &DesiredCell) -> Result<Self> {
+        let value = match cell {
+            DesiredCell::Explicit(MatrixCell::Formula(formula)) => {
+                return Ok(Content::Formula(normalize_formula(formula).to_string()));
+            }
+            DesiredCell::Explicit(MatrixCell::Value(value)) => value,
+            DesiredCell::Plain(Value::String(text)) if text.starts_with('=') => {
+                return Ok(Content::Formula(normalize_formula(text).to_string()));
+            }
+            DesiredCell::Plain(value) => value,
+        };
+        match value {
+            Value::Null => Ok(Content::Empty),
+            Value::String(text) if text.is_empty() => Ok(Content::Empty),
+            Value::Array(_) | Value::Object(_) => {
+                bail!("cell values must be text, numbers, booleans or null, got {value}")
+            }
+            scalar => Ok(Content::Value(scalar.clone())),
+        }
+    }
+
+    fn current(cell: Option<&umya_spreadsheet::Cell>) -> Self {
+        let Some(cell) = cell else {
+            return Content::Empty;
+        };
+        if cell.is_formula() {
+            return Content::Formula(normalize_formula(cell.get_formula()).to_string());
+        }
+        let raw = cell.get_value();
+        if raw.is_empty() {
+            Content::Empty
+        } else {
+            Content::Value(Value::String(raw.to_string()))
+        }
+    }
+
+    /// Whether a cell holding `self` is already in the `desired` state.
+    fn satisfies(&self, desired: &Content) -> bool {
+        match (self, desired) {
+            (Content::Empty, Content::Empty) => true,
+            (Content::Formula(current), Content::Formula(desired)) => current == desired,
+            (Content::Value(Value::String(raw)), Content::Value(desired)) => match desired {
+                Value::Number(number) => raw
+                    .parse::<f64>()
+                    .is_ok_and(|current| Some(current) == number.as_f64()),
+                Value::Bool(flag) => raw.eq_ignore_ascii_case(&flag.to_string()),
+                Value::String(text) => raw == text,
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    fn display(&self) -> Option<String> {
+        match self {
+            Content::Empty => None,
+            Content::Value(Value::String(text)) => Some(text.clone()),
+            Content::Value(value) => Some(value.to_string()),
+            Content::Formula(formula) => Some(format!("={formula}")),
+        }
+    }
+
+    fn into_matrix_cell(self) -> MatrixCell {
+        match self {
+            Content::Formula(formula) => MatrixCell::Formula(formula),
+            Content::Value(value) => MatrixCell::Value(value),
+            Content::Empty => MatrixCell::Value(Value::Null),
+        }
+    }
+}
+
+fn normalize_formula(formula: &str) -> &str {
+    let formula = formula.trim();
+    formula.strip_prefix('=').unwrap_or(formula)
+}
+
+/// Compare `spec` with `book` and collect the ops that converge it.
+pub fn plan_convergence(book: &Spreadsheet, spec: &DesiredStateSpec) -> Result<ConvergencePlan> {
+    let mut plan = ConvergencePlan::default();
+    // Desired contents per sheet, keyed (row, column) so runs come out in
+    // reading order.
+    let mut desired_cells: BTreeMap<String, BTreeMap<(u32, u32), Content>> = BTreeMap::new();
+
+    for (index, target) in spec.targets.iter().enumerate() {
+        let context = |error: anyhow::Error| anyhow!("targets[{index}]: {error:#}");
+        if book.get_sheet_by_name(&target.sheet_name).is_none() {
+            return Err(context(anyhow!("sheet '{}' not found", target.sheet_name)));
+        }
+        let (anchor_col, anchor_row) =
+            parse_cell(target.anchor.as_deref().unwrap_or("A1")).map_err(context)?;
+        let cells = desired_cells.entry(target.sheet_name.clone()).or_default();
+        for (r_idx, desired_row) in target.rows.iter().enumerate() {
+            for (c_idx, cell) in desired_row.iter().enumerate() {
+                let (col, row) = (anchor_col + c_idx as u32, anchor_row + r_idx as u32);
+                let content = Content::desired(cell)
+                    .map_err(|error| context(anyhow!("{}: {error}", cell_address(col, row))))?;
+                cells.insert((row, col), content);
+            }
+        }
+    }
+
+    for (sheet_name, cells) in &desired_cells {
+        let sheet = book
+            .get_sheet_by_name(sheet_name)
+            .expect("sheet checked above");
+        let mut writes: Vec<((u32, u32), Content)> = Vec::new();
+        let mut clears: Vec<String> = Vec::new();
+        for (&(row, col), desired) in cells {
+            plan.cells_checked += 1;
+            let current = Content::current(sheet.get_cell((col, row)));
+            if current.satisfies(desired) {
+                continue;
+            }
+            let action = if *desired == Content::Empty {
+                clears.push(cell_address(col, row));
+                plan.cells_to_clear += 1;
+                PlannedAction::Clear
+            } else {
+                writes.push(((row, col), desired.clone()));
+                plan.cells_to_write += 1;
+                PlannedAction::Write
+            };
+            plan.changes.push(PlannedChange {
+                sheet_name: sheet_name.clone(),
+                cell: cell_address(col, row),
+                action,
+                current: current.display(),
+                desired: desired.display(),
+            });
+        }
+        plan.transform_ops
+            .extend(write_matrix_ops(sheet_name, writes));
+        if !clears.is_empty() {
+            plan.transform_ops.push(TransformOp::ClearRange {
+                sheet_name: sheet_name.clone(),
+                target: TransformTarget::Cells { cells: clears },
+                clear_values: true,
+                clear_formulas: true,
+            });
+        }
+    }
+
+    plan_styles(book, spec, &mut plan)?;
+    Ok(plan)
+}
+
+/// One `write_matrix` op per block of changed cells: runs of adjacent cells
+/// in a row, stacked while consecutive rows span the same columns.
+fn write_matrix_ops(sheet_name: &str, writes: Vec<((u32, u32), Content)>) -> Vec<TransformOp> {
+    struct Block {
+        top_row: u32,
+        first_col: u32,
+        rows: Vec<Vec<Option<MatrixCell>>>,
+    }
+
+    let mut runs: Vec<(u32, u32, Vec<Option<MatrixCell>>)> = Vec::new();
+    for ((row, col), content) in writes {
+        match runs.last_mut() {
+            Some((run_row, first_col, cells))
+                if *run_row == row && *first_col + cells.len() as u32 == col =>
+            {
+                cells.push(Some(content.into_matrix_cell()));
+            }
+            _ => runs.push((row, col, vec![Some(content.into_matrix_cell())])),
+        }
+    }
+
+    let mut blocks: Vec<Block> = Vec::new();
+    // Block still open for stacking, keyed by its column span.
+    let mut open: HashMap<(u32, u32), usize> = HashMap::new();
+    for (row, first_col, cells) in runs {
+        let last_col = first_col + cells.len() as u32 - 1;
+        if let Some(&index) = open.get(&(first_col, last_col)) {
+            let block = &mut blocks[index];
+            if block.top_row + block.rows.len() as u32 == row {
+                block.rows.push(cells);
+                continue;
+            }
+        }
+        open.insert((first_col, last_col), blocks.len());
+        blocks.push(Block {
+            top_row: row,
+            first_col,
+            rows: vec![cells],
+        });
+    }
+
+    blocks
+        .into_iter()
+        .map(|block| TransformOp::WriteMatrix {
+            sheet_name: sheet_name.to_string(),
+            anchor: cell_address(block.first_col, block.top_row),
+            rows: block.rows,
+            overwrite_formulas: true,
+        })
+        .collect()
+}
+
+/// Emit each style patch for the cells it would change, simulating earlier
+/// patches so overlapping ranges are judged on the style they will have.
+fn plan_styles(
+    book: &Spreadsheet,
+    spec: &DesiredStateSpec,
+    plan: &mut ConvergencePlan,
+) -> Result<()> {
+    let mut simulated: HashMap<(String, u32, u32), Style> = HashMap::new();
+
+    for (index, target) in spec.targets.iter().enumerate() {
+        let sheet = book
+            .get_sheet_by_name(&target.sheet_name)
+            .expect("sheet checked above");
+        for (style_index, desired) in target.styles.iter().enumerate() {
+            let context =
+                |error: anyhow::Error| anyhow!("targets[{index}].styles[{style_index}]: {error:#}");
+            let op = normalize_style_patch(&target.sheet_name, desired).map_err(context)?;
+            let ((first_col, first_row), (last_col, last_row)) =
+                parse_range(&desired.range).map_err(context)?;
+
+            let mut changed = Vec::new();
+            let mut total = 0usize;
+            for row in first_row..=last_row {
+                for col in first_col..=last_col {
+                    total += 1;
+                    let key = (target.sheet_name.clone(), col, row);
+                    let before = simulated
+                        .get(&key)
+                        .cloned()
+                        .unwrap_or_else(|| current_style(sheet, col, row));
+                    let after = apply_style_patch(&before, &op.patch, StylePatchMode::Merge);
+                    if stable_style_id(&descriptor_from_style(&before))
+                        != stable_style_id(&descriptor_from_style(&after))
+                    {
+                        changed.push(cell_address(col, row));
+                        simulated.insert(key, after);
+                    }
+                }
+            }
+            if changed.is_empty() {
+                continue;
+            }
+
+            plan.cells_to_restyle += changed.len() as u32;
+            plan.changes
+                .extend(changed.iter().map(|cell| PlannedChange {
+                    sheet_name: target.sheet_name.clone(),
+                    cell: cell.clone(),
+                    action: PlannedAction::Restyle,
+                    current: None,
+                    desired: None,
+                }));
+            let target = if changed.len() == total {
+                StyleTarget::Range {
+                    range: desired.range.clone(),
+                }
+            } else {
+                StyleTarget::Cells { cells: changed }
+            };
+            plan.style_ops.push(StyleOp { target, ..op });
+        }
+    }
+    Ok(())
+}
+
+/// Normalize a desired patch the way `style-batch` does, so shorthands like
+/// `{"fill": {"color": "#FFEEAA"}}` are accepted.
+fn normalize_style_patch(sheet_name: &str, desired: &DesiredStyle) -> Result<StyleOp> {
+    let input: StyleOpInput = serde_json::from_value(serde_json::json!({
+        "sheet_name": sheet_name,
+        "target": { "kind": "range", "range": desired.range },
+        "patch": desired.patch,
+    }))
+    .map_err(|error| anyhow!("invalid style patch: {error}"))?;
+    let (mut params, _) = normalize_style_batch(StyleBatchParamsInput {
+        fork_id: String::new(),
+        ops: vec![input],
+        mode: None,
+        label: None,
+    })?;
+    params
+        .ops
+        .pop()
+        .ok_or_else(|| anyhow!("invalid style patch"))
+}
+
+fn current_style(sheet: &Worksheet, col: u32, row: u32) -> Style {
+    sheet
+        .get_cell((col, row))
+        .map(|cell| cell.get_style().clone())
+        .unwrap_or_default()
+}
+
+fn parse_cell(cell: &str) -> Result<(u32, u32)> {
+    use umya_spreadsheet::helper::coordinate::index_from_coordinate;
+    match index_from_coordinate(cell.trim()) {
+        (Some(col), Some(row), _, _) => Ok((col, row)),
+        _ => bail!("invalid cell reference '{cell}'"),
+    }
+}
+
+fn parse_range(range: &str) -> Result<((u32, u32), (u32, u32))> {
+    let (start, end) = range.split_once(':').unwrap_or((range, range));
+    let (start_col, start_row) = parse_cell(start)?;
+    let (end_col, end_row) = parse_cell(end)?;
+    Ok((
+        (start_col.min(end_col), start_row.min(end_row)),
+        (start_col.max(end_col), start_row.max(end_row)),
+    ))
+}
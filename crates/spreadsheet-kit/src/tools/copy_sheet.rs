@@ -0,0 +1,367 @@
+//! The `copy_sheet` structure op: copy a whole sheet from another workbook
+//! file (or the same one) into the workbook being edited, after Excel's
+//! Move or Copy Sheet.
+//!
+//! The copy carries cells, styles, merges, column widths, row heights,
+//! conditional formats, validations, tables and sheet-scoped names. Styles
+//! travel with each cell and are re-indexed when the workbook is written, so
+//! style and differential-format indices never clash with the destination's.
+//! References to the copied sheet itself follow it to its new name; tables
+//! whose names are taken in the destination get a numbered suffix, with the
+//! copied sheet's structured references renamed to match. References to
+//! other sheets are kept as written and reported when the destination has
+//! no sheet of that name.
+
+use super::fork::StructureOp;
+use crate::model::{
+    FORMULA_PARSE_FAILED_PREFIX, FormulaParseDiagnosticsBuilder, FormulaParsePolicy,
+};
+use anyhow::{Context, Result, anyhow, bail};
+use formualizer_parse::TokenSubType;
+use formualizer_parse::tokenizer::Tokenizer;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use umya_spreadsheet::{Spreadsheet, Worksheet};
+
+/// What one `copy_sheet` op changed.
+#[derive(Debug, Default)]
+pub(crate) struct CopySheetEffects {
+    /// Name of the new sheet in the destination
+    pub(crate) sheet_name: String,
+    pub(crate) formulas_adjusted: u64,
+    pub(crate) tables_renamed: u64,
+    pub(crate) warnings: Vec<String>,
+}
+
+/// Resolve the `source_file` of `copy_sheet` ops to absolute paths, so a
+/// staged batch still finds its sources when applied later. Relative paths
+/// resolve against `base_dir`; with `confine` set, paths that leave
+/// `base_dir` are rejected.
+pub fn resolve_copy_sheet_sources(
+    ops: &mut [StructureOp],
+    base_dir: &Path,
+    confine: bool,
+) -> Result<()> {
+    for op in ops {
+        let StructureOp::CopySheet { source_file, .. } = op else {
+            continue;
+        };
+        let requested = source_file.trim();
+        if requested.is_empty() {
+            bail!("copy_sheet requires a non-empty source_file");
+        }
+        let path = base_dir.join(requested);
+        let canonical = fs::canonicalize(&path)
+            .with_context(|| format!("copy_sheet source_file '{requested}' not found"))?;
+        if confine {
+            let root = fs::canonicalize(base_dir).unwrap_or_else(|_| base_dir.to_path_buf());
+            if !canonical.starts_with(&root) {
+                bail!("copy_sheet source_file '{requested}' is outside the workspace root");
+            }
+        }
+        let extension = canonical
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase);
+        if !matches!(extension.as_deref(), Some("xlsx" | "xlsm")) {
+            bail!("copy_sheet source_file '{requested}' must be an .xlsx or .xlsm workbook");
+        }
+        *source_file = canonical.display().to_string();
+    }
+    Ok(())
+}
+
+/// Copy `source_sheet` of the workbook at `source_file` into `book` as
+/// `new_name` (the source name by default), inserted at `position` or
+/// appended.
+pub(crate) fn copy_sheet_into(
+    book: &mut Spreadsheet,
+    source_file: &str,
+    source_sheet: &str,
+    new_name: Option<&str>,
+    position: Option<u32>,
+    policy: FormulaParsePolicy,
+    builder: &mut FormulaParseDiagnosticsBuilder,
+) -> Result<CopySheetEffects> {
+    let source_path = PathBuf::from(source_file);
+    let source_book = umya_spreadsheet::reader::xlsx::read(&source_path).map_err(|e| {
+        anyhow!(
+            "copy_sheet failed to read source_file '{}': {}",
+            source_path.display(),
+            e
+        )
+    })?;
+    let source_sheet = source_sheet.trim();
+    let source_index = source_book
+        .get_sheet_collection_no_check()
+        .iter()
+        .position(|sheet| sheet.get_name() == source_sheet)
+        .ok_or_else(|| {
+            let available: Vec<&str> = source_book
+                .get_sheet_collection_no_check()
+                .iter()
+                .map(|sheet| sheet.get_name())
+                .collect();
+            anyhow!(
+                "copy_sheet source_sheet '{}' not found in '{}' (sheets: {})",
+                source_sheet,
+                source_path.display(),
+                available.join(", ")
+            )
+        })?;
+
+    let target_name = new_name
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .unwrap_or(source_sheet)
+        .to_string();
+    let destination_sheets: Vec<String> = book
+        .get_sheet_collection_no_check()
+        .iter()
+        .map(|sheet| sheet.get_name().to_string())
+        .collect();
+    if destination_sheets
+        .iter()
+        .any(|name| name.eq_ignore_ascii_case(&target_name))
+    {
+        bail!(
+            "copy_sheet: sheet '{}' already exists; pass new_name to copy it under another name",
+            target_name
+        );
+    }
+
+    let mut effects = CopySheetEffects {
+        sheet_name: target_name.clone(),
+        ..Default::default()
+    };
+    let mut sheet: Worksheet = source_book.get_sheet_collection_no_check()[source_index].clone();
+    sheet.set_name(target_name.clone());
+    // Only one tab may be selected, or Excel opens the workbook with the
+    // sheets grouped.
+    for view in sheet.get_sheet_views_mut().get_sheet_view_list_mut() {
+        view.set_tab_selected(false);
+    }
+
+    let table_renames = rename_conflicting_tables(book, &mut sheet);
+    for (old, new) in &table_renames {
+        effects.warnings.push(format!(
+            "WARN_COPY_SHEET_TABLE_RENAMED: table '{}' already exists in the destination; the copy on '{}' is named '{}'.",
+            old, target_name, new
+        ));
+    }
+    effects.tables_renamed = table_renames.len() as u64;
+
+    let rewrite = ReferenceRewrite {
+        source_sheet,
+        target_sheet: &target_name,
+        table_renames: &table_renames,
+        destination_sheets: &destination_sheets,
+    };
+    let mut missing_sheets = BTreeSet::new();
+    if policy != FormulaParsePolicy::Off {
+        for cell in sheet.get_cell_collection_mut() {
+            if !cell.is_formula() || cell.get_formula().is_empty() {
+                continue;
+            }
+            let address = cell.get_coordinate().get_coordinate().to_string();
+            let formula = cell.get_formula().to_string();
+            match rewrite.apply(&formula, &mut missing_sheets) {
+                Ok(Some(rewritten)) => {
+                    cell.set_formula(rewritten);
+                    effects.formulas_adjusted += 1;
+                }
+                Ok(None) => {}
+                Err(message) => {
+                    if policy == FormulaParsePolicy::Fail {
+                        bail!(
+                            "{}tokenizer error in {}!{}: {}",
+                            FORMULA_PARSE_FAILED_PREFIX,
+                            target_name,
+                            address,
+                            message
+                        );
+                    }
+                    builder.record_error(&target_name, &address, &formula, &message);
+                }
+            }
+        }
+        for defined in sheet.get_defined_names_mut() {
+            let address = defined.get_address();
+            if let Ok(Some(rewritten)) = rewrite.apply(&address, &mut missing_sheets) {
+                defined.set_address(rewritten);
+            }
+        }
+    }
+    if !missing_sheets.is_empty() {
+        effects.warnings.push(format!(
+            "WARN_COPY_SHEET_MISSING_SHEETS: formulas on '{}' refer to sheet(s) the destination does not have yet: {}. Copy those sheets too or the formulas evaluate to #REF!.",
+            target_name,
+            missing_sheets.into_iter().collect::<Vec<_>>().join(", ")
+        ));
+    }
+
+    let left_behind: Vec<String> = source_book
+        .get_defined_names()
+        .iter()
+        .filter(|defined| {
+            (defined.has_local_sheet_id() && *defined.get_local_sheet_id() as usize == source_index)
+                || refers_to_sheet(&defined.get_address(), source_sheet)
+        })
+        .map(|defined| defined.get_name().to_string())
+        .collect();
+    if !left_behind.is_empty() {
+        effects.warnings.push(format!(
+            "WARN_COPY_SHEET_NAMES_NOT_COPIED: workbook-level defined name(s) {} refer to '{}' in the source and were not copied.",
+            left_behind.join(", "),
+            source_sheet
+        ));
+    }
+
+    book.add_sheet(sheet)
+        .map_err(|e| anyhow!("failed to add sheet '{}': {}", target_name, e))?;
+    if let Some(pos) = position {
+        let desired = pos as usize;
+        let len = book.get_sheet_collection_no_check().len();
+        if desired >= len {
+            effects.warnings.push(format!(
+                "copy_sheet position {} is out of range (sheet_count {}). Appended at end.",
+                desired, len
+            ));
+        } else if desired != len - 1 {
+            let sheets = book.get_sheet_collection_mut();
+            let copied = sheets.remove(len - 1);
+            sheets.insert(desired, copied);
+        }
+    }
+    Ok(effects)
+}
+
+/// Give tables on `sheet` whose names the destination already uses the
+/// first free `_2`, `_3`, ... suffix. Returns old to new names.
+fn rename_conflicting_tables(
+    book: &Spreadsheet,
+    sheet: &mut Worksheet,
+) -> BTreeMap<String, String> {
+    let mut taken: BTreeSet<String> = book
+        .get_sheet_collection_no_check()
+        .iter()
+        .flat_map(|sheet| sheet.get_tables())
+        .flat_map(|table| [table.get_name(), table.get_display_name()])
+        .map(str::to_ascii_lowercase)
+        .collect();
+    let mut renames = BTreeMap::new();
+    for table in sheet.get_tables_mut() {
+        let name = table.get_display_name().to_string();
+        if !taken.contains(&name.to_ascii_lowercase()) {
+            taken.insert(name.to_ascii_lowercase());
+            continue;
+        }
+        let renamed = (2..)
+            .map(|suffix| format!("{name}_{suffix}"))
+            .find(|candidate| !taken.contains(&candidate.to_ascii_lowercase()))
+            .expect("a free table name");
+        taken.insert(renamed.to_ascii_lowercase());
+        table.set_name(&renamed);
+        table.set_display_name(&renamed);
+        renames.insert(name, renamed);
+    }
+    renames
+}
+
+/// How references in the copied sheet's formulas change.
+struct ReferenceRewrite<'a> {
+    source_sheet: &'a str,
+    target_sheet: &'a str,
+    table_renames: &'a BTreeMap<String, String>,
+    destination_sheets: &'a [String],
+}
+
+impl ReferenceRewrite<'_> {
+    /// The rewritten formula (without `=`), or `None` when nothing changed.
+    /// Sheets referenced but missing from the destination are added to
+    /// `missing`. Errors carry the tokenizer message.
+    fn apply(
+        &self,
+        formula: &str,
+        missing: &mut BTreeSet<String>,
+    ) -> std::result::Result<Option<String>, String> {
+        let trimmed = formula.trim();
+        let formula_in = if trimmed.starts_with('=') {
+            trimmed.to_string()
+        } else {
+            format!("={trimmed}")
+        };
+        let tokens = Tokenizer::new(&formula_in).map_err(|e| e.message)?.items;
+        let target_prefix = super::fork::format_sheet_prefix_for_formula(self.target_sheet);
+
+        let mut out = String::with_capacity(formula_in.len());
+        let mut cursor = 0usize;
+        let mut changed = false;
+        for token in &tokens {
+            if token.start > cursor {
+                out.push_str(&formula_in[cursor..token.start]);
+            }
+            let mut value = token.value.clone();
+            if token.subtype == TokenSubType::Range {
+                if let Some((sheet_part, tail)) = value.split_once('!') {
+                    let sheet = unquote_sheet(sheet_part);
+                    if sheet == self.source_sheet {
+                        if self.target_sheet != self.source_sheet {
+                            value = format!("{target_prefix}{tail}");
+                            changed = true;
+                        }
+                    } else if !sheet.starts_with('[')
+                        && !self
+                            .destination_sheets
+                            .iter()
+                            .any(|name| name.eq_ignore_ascii_case(&sheet))
+                    {
+                        missing.insert(sheet);
+                    }
+                } else if let Some((table, tail)) = value.split_once('[')
+                    && let Some((_, renamed)) = self
+                        .table_renames
+                        .iter()
+                        .find(|(old, _)| old.eq_ignore_ascii_case(table))
+                {
+                    value = format!("{renamed}[{tail}");
+                    changed = true;
+                }
+            }
+            out.push_str(&value);
+            cursor = token.end;
+        }
+        if cursor < formula_in.len() {
+            out.push_str(&formula_in[cursor..]);
+        }
+        Ok(changed.then(|| out.strip_prefix('=').unwrap_or(&out).to_string()))
+    }
+}
+
+fn unquote_sheet(sheet_part: &str) -> String {
+    let trimmed = sheet_part.trim();
+    trimmed
+        .strip_prefix('\'')
+        .and_then(|inner| inner.strip_suffix('\''))
+        .map(|inner| inner.replace("''", "'"))
+        .unwrap_or_else(|| trimmed.to_string())
+}
+
+fn refers_to_sheet(formula: &str, sheet: &str) -> bool {
+    let trimmed = formula.trim();
+    let formula_in = if trimmed.starts_with('=') {
+        trimmed.to_string()
+    } else {
+        format!("={trimmed}")
+    };
+    Tokenizer::new(&formula_in).is_ok_and(|tokenizer| {
+        tokenizer.items.iter().any(|token| {
+            token.subtype == TokenSubType::Range
+                && token
+                    .value
+                    .split_once('!')
+                    .is_some_and(|(sheet_part, _)| unquote_sheet(sheet_part) == sheet)
+        })
+    })
+}
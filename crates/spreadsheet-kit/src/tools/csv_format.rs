@@ -0,0 +1,243 @@
+//! Dialect options for `format: csv` table and range reads.
+//!
+//! The defaults reproduce the plain RFC 4180 output the tools have always
+//! emitted. The options exist so the text can go straight into importers that
+//! expect another dialect, e.g. semicolon-delimited, comma-decimal files with
+//! a byte order mark and CRLF rows for European Excel locales.
+
+use crate::model::CellValue;
+use anyhow::{Result, bail};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+const BYTE_ORDER_MARK: char = '\u{FEFF}';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CsvQuoting {
+    /// Quote only fields containing the delimiter, a quote, or a line break.
+    #[default]
+    Minimal,
+    /// Quote every non-empty field.
+    All,
+    /// Quote every non-empty field except numbers.
+    NonNumeric,
+    /// Never quote; fields are written verbatim.
+    Never,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CsvLineEnding {
+    #[default]
+    Lf,
+    Crlf,
+}
+
+impl CsvLineEnding {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Lf => "\n",
+            Self::Crlf => "\r\n",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct CsvOptions {
+    /// Field delimiter (default ",")
+    pub delimiter: char,
+    /// Quoting policy: "minimal" (default), "all", "non_numeric", or "never"
+    pub quoting: CsvQuoting,
+    /// Start the text with a UTF-8 byte order mark (default: false)
+    pub bom: bool,
+    /// Row terminator: "lf" (default) or "crlf"
+    pub line_ending: CsvLineEnding,
+    /// Decimal separator written in numbers (default ".")
+    pub decimal_separator: char,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            quoting: CsvQuoting::Minimal,
+            bom: false,
+            line_ending: CsvLineEnding::Lf,
+            decimal_separator: '.',
+        }
+    }
+}
+
+impl CsvOptions {
+    /// Reject dialects whose output could not be parsed back unambiguously.
+    pub fn validate(&self) -> Result<()> {
+        if matches!(self.delimiter, '"' | '\n' | '\r') {
+            bail!("csv delimiter cannot be a quote or line break");
+        }
+        if matches!(self.decimal_separator, '"' | '\n' | '\r' | '-' | '0'..='9') {
+            bail!(
+                "csv decimal separator '{}' is not usable",
+                self.decimal_separator
+            );
+        }
+        if self.decimal_separator == self.delimiter {
+            bail!(
+                "csv decimal separator and delimiter are both '{}'",
+                self.delimiter
+            );
+        }
+        Ok(())
+    }
+
+    /// Empty buffer for a new CSV document, with the byte order mark when
+    /// requested.
+    pub(crate) fn start(&self) -> String {
+        let mut buffer = String::new();
+        if self.bom {
+            buffer.push(BYTE_ORDER_MARK);
+        }
+        buffer
+    }
+
+    /// Append a row of text fields, e.g. headers.
+    pub(crate) fn push_text_row<I, S>(&self, buffer: &mut String, fields: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.push_row(
+            buffer,
+            fields
+                .into_iter()
+                .map(|field| (field.as_ref().to_string(), false)),
+        );
+    }
+
+    /// Append a row of cells; empty cells become empty fields.
+    pub(crate) fn push_cell_row<'a, I>(&self, buffer: &mut String, cells: I)
+    where
+        I: IntoIterator<Item = Option<&'a CellValue>>,
+    {
+        self.push_row(
+            buffer,
+            cells.into_iter().map(|cell| match cell {
+                Some(CellValue::Number(number)) => (self.format_number(*number), true),
+                Some(CellValue::Text(text)) => (text.clone(), false),
+                Some(CellValue::Bool(value)) => (value.to_string(), false),
+                Some(CellValue::Error(error)) => (error.clone(), false),
+                Some(CellValue::Date(date)) => (date.clone(), false),
+                None => (String::new(), false),
+            }),
+        );
+    }
+
+    fn push_row<I>(&self, buffer: &mut String, fields: I)
+    where
+        I: IntoIterator<Item = (String, bool)>,
+    {
+        for (index, (field, numeric)) in fields.into_iter().enumerate() {
+            if index > 0 {
+                buffer.push(self.delimiter);
+            }
+            self.push_field(buffer, &field, numeric);
+        }
+        buffer.push_str(self.line_ending.as_str());
+    }
+
+    fn push_field(&self, buffer: &mut String, field: &str, numeric: bool) {
+        let quote = match self.quoting {
+            CsvQuoting::Minimal => field
+                .chars()
+                .any(|ch| ch == self.delimiter || matches!(ch, '"' | '\n' | '\r')),
+            CsvQuoting::All => !field.is_empty(),
+            CsvQuoting::NonNumeric => !numeric && !field.is_empty(),
+            CsvQuoting::Never => false,
+        };
+        if !quote {
+            buffer.push_str(field);
+            return;
+        }
+        buffer.push('"');
+        for ch in field.chars() {
+            if ch == '"' {
+                buffer.push('"');
+            }
+            buffer.push(ch);
+        }
+        buffer.push('"');
+    }
+
+    fn format_number(&self, number: f64) -> String {
+        let text = number.to_string();
+        if self.decimal_separator == '.' {
+            text
+        } else {
+            text.replace('.', &self.decimal_separator.to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(options: &CsvOptions) -> String {
+        let mut csv = options.start();
+        options.push_text_row(&mut csv, ["Name", "Amount"]);
+        options.push_cell_row(
+            &mut csv,
+            [
+                Some(&CellValue::Text("Smith; J".to_string())),
+                Some(&CellValue::Number(1234.5)),
+            ],
+        );
+        options.push_cell_row(&mut csv, [None, Some(&CellValue::Number(-2.0))]);
+        csv
+    }
+
+    #[test]
+    fn defaults_match_plain_csv() {
+        assert_eq!(
+            render(&CsvOptions::default()),
+            "Name,Amount\nSmith; J,1234.5\n,-2\n"
+        );
+    }
+
+    #[test]
+    fn european_dialect_uses_semicolons_and_comma_decimals() {
+        let options = CsvOptions {
+            delimiter: ';',
+            bom: true,
+            line_ending: CsvLineEnding::Crlf,
+            decimal_separator: ',',
+            ..CsvOptions::default()
+        };
+        assert_eq!(
+            render(&options),
+            "\u{FEFF}Name;Amount\r\n\"Smith; J\";1234,5\r\n;-2\r\n"
+        );
+    }
+
+    #[test]
+    fn non_numeric_quoting_leaves_numbers_and_blanks_bare() {
+        let options = CsvOptions {
+            quoting: CsvQuoting::NonNumeric,
+            ..CsvOptions::default()
+        };
+        assert_eq!(
+            render(&options),
+            "\"Name\",\"Amount\"\n\"Smith; J\",1234.5\n,-2\n"
+        );
+    }
+
+    #[test]
+    fn decimal_separator_must_differ_from_delimiter() {
+        let options = CsvOptions {
+            decimal_separator: ',',
+            ..CsvOptions::default()
+        };
+        assert!(options.validate().is_err());
+    }
+}
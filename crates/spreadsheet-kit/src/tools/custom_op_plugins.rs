@@ -0,0 +1,229 @@
+//! Custom transform ops backed by external commands.
+//!
+//! Each `*.json`, `*.yaml` or `*.yml` manifest in the directory named by
+//! `SPREADSHEET_MCP_CUSTOM_OPS_DIR` registers one op:
+//!
+//! ```yaml
+//! name: apply_price_list
+//! description: Set column B prices from a SKU price map
+//! command: python3 apply_price_list.py
+//! ```
+//!
+//! The command runs through the platform shell from the manifest's directory.
+//! It reads a [`CommandOpRequest`] (the op params and the sheet's non-empty
+//! cells) as JSON on stdin and prints a [`CommandOpResponse`] listing the
+//! cells to write. Manifests are loaded the first time the custom op registry
+//! is used, so the shipped `asp` and MCP binaries pick them up without an
+//! embedding binary.
+
+use super::custom_ops::{CustomOpEffects, CustomTransformOp};
+use crate::command_plugins::{
+    PluginCell, manifest_paths, read_manifest, run_json_command, sheet_cells,
+};
+use crate::utils::cell_address;
+use anyhow::{Result, anyhow, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use umya_spreadsheet::Worksheet;
+
+pub const CUSTOM_OPS_DIR_ENV: &str = "SPREADSHEET_MCP_CUSTOM_OPS_DIR";
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Manifest {
+    name: String,
+    #[serde(default)]
+    description: String,
+    command: String,
+}
+
+/// A custom op that delegates to an external command.
+#[derive(Debug)]
+pub struct CommandTransformOp {
+    name: String,
+    description: String,
+    command: String,
+    dir: PathBuf,
+}
+
+/// Sent to the command on stdin.
+#[derive(Debug, Serialize)]
+pub struct CommandOpRequest<'a> {
+    pub name: &'a str,
+    pub sheet_name: &'a str,
+    pub params: &'a Value,
+    pub cells: Vec<PluginCell>,
+}
+
+/// Printed by the command on stdout.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CommandOpResponse {
+    #[serde(default)]
+    pub writes: Vec<CommandOpWrite>,
+    /// Reported in the batch summary as `<op_name>.<key>`.
+    #[serde(default)]
+    pub counts: BTreeMap<String, u64>,
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CommandOpWrite {
+    pub address: String,
+    /// String, number or boolean; `null` clears the cell.
+    #[serde(default)]
+    pub value: Value,
+    /// Formula with or without its leading `=`; takes precedence over `value`.
+    #[serde(default)]
+    pub formula: Option<String>,
+}
+
+/// Ops from the manifests in [`CUSTOM_OPS_DIR_ENV`], and an error for each
+/// manifest that could not be loaded.
+pub(crate) fn load_from_env() -> (Vec<CommandTransformOp>, Vec<anyhow::Error>) {
+    match std::env::var_os(CUSTOM_OPS_DIR_ENV).filter(|dir| !dir.is_empty()) {
+        Some(dir) => load_dir(Path::new(&dir)),
+        None => (Vec::new(), Vec::new()),
+    }
+}
+
+fn load_dir(dir: &Path) -> (Vec<CommandTransformOp>, Vec<anyhow::Error>) {
+    let manifests = match manifest_paths(dir) {
+        Ok(manifests) => manifests,
+        Err(error) => return (Vec::new(), vec![error]),
+    };
+    let mut ops = Vec::new();
+    let mut errors = Vec::new();
+    for path in manifests {
+        match load_manifest(&path) {
+            Ok(op) => ops.push(op),
+            Err(error) => errors.push(error),
+        }
+    }
+    (ops, errors)
+}
+
+fn load_manifest(path: &Path) -> Result<CommandTransformOp> {
+    let manifest: Manifest = read_manifest(path)?;
+    if manifest.command.trim().is_empty() {
+        bail!("custom op manifest {:?} has an empty command", path);
+    }
+    Ok(CommandTransformOp {
+        name: manifest.name,
+        description: manifest.description,
+        command: manifest.command,
+        dir: path.parent().map(Path::to_path_buf).unwrap_or_default(),
+    })
+}
+
+impl CustomTransformOp for CommandTransformOp {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn apply(
+        &self,
+        sheet: &mut Worksheet,
+        params: &Value,
+        effects: &mut CustomOpEffects,
+    ) -> Result<()> {
+        let sheet_name = sheet.get_name().to_string();
+        let response: CommandOpResponse = run_json_command(
+            &self.command,
+            &self.dir,
+            &[("SPREADSHEET_MCP_CUSTOM_OP", &self.name)],
+            &CommandOpRequest {
+                name: &self.name,
+                sheet_name: &sheet_name,
+                params,
+                cells: sheet_cells(sheet),
+            },
+        )?;
+        apply_writes(sheet, response, effects)
+    }
+}
+
+fn apply_writes(
+    sheet: &mut Worksheet,
+    response: CommandOpResponse,
+    effects: &mut CustomOpEffects,
+) -> Result<()> {
+    use umya_spreadsheet::helper::coordinate::index_from_coordinate;
+
+    let mut changed = 0;
+    let mut bounds: Option<((u32, u32), (u32, u32))> = None;
+    for write in &response.writes {
+        let (Some(col), Some(row), _, _) = index_from_coordinate(&write.address) else {
+            bail!("response writes invalid cell address '{}'", write.address);
+        };
+        let cell = sheet.get_cell_mut((col, row));
+        let before = (
+            cell.get_value().to_string(),
+            cell.is_formula().then(|| cell.get_formula().to_string()),
+        );
+        match (&write.formula, &write.value) {
+            (Some(formula), _) => {
+                cell.set_formula(formula.strip_prefix('=').unwrap_or(formula).to_string());
+            }
+            (None, Value::Null) => {
+                cell.set_value(String::new());
+            }
+            (None, Value::Bool(flag)) => {
+                cell.set_value_bool(*flag);
+            }
+            (None, Value::Number(number)) => {
+                let number = number.as_f64().ok_or_else(|| {
+                    anyhow!("cell {} value {number} is out of range", write.address)
+                })?;
+                cell.set_value_number(number);
+            }
+            (None, Value::String(text)) => {
+                cell.set_value_string(text.clone());
+            }
+            (None, other) => bail!(
+                "cell {} value must be a string, number, boolean or null, got {other}",
+                write.address
+            ),
+        }
+        let after = (
+            cell.get_value().to_string(),
+            cell.is_formula().then(|| cell.get_formula().to_string()),
+        );
+        if before != after {
+            changed += 1;
+        }
+        bounds = Some(match bounds {
+            None => ((col, row), (col, row)),
+            Some(((min_col, min_row), (max_col, max_row))) => (
+                (min_col.min(col), min_row.min(row)),
+                (max_col.max(col), max_row.max(row)),
+            ),
+        });
+    }
+
+    effects.record_cells_changed(changed);
+    if let Some((start, end)) = bounds {
+        let start = cell_address(start.0, start.1);
+        let end = cell_address(end.0, end.1);
+        effects.record_bounds(if start == end {
+            start
+        } else {
+            format!("{start}:{end}")
+        });
+    }
+    for (key, value) in response.counts {
+        effects.add_count(key, value);
+    }
+    for warning in response.warnings {
+        effects.warn(warning);
+    }
+    Ok(())
+}
@@ -0,0 +1,208 @@
+//! Extension point for domain-specific transform op kinds.
+//!
+//! A [`CustomTransformOp`] registered with [`register_custom_transform_op`] can
+//! be invoked from any transform batch as
+//! `{"kind":"custom","name":"<op_name>","sheet_name":"...","params":{...}}`.
+//! Custom ops flow through the same resolve, dry-run, apply, staging, and
+//! summary machinery as the built-in transform kinds. Registration is
+//! process-wide so embedding binaries register their ops once at startup.
+//! Without an embedding binary, ops backed by external commands are loaded
+//! from manifests in `SPREADSHEET_MCP_CUSTOM_OPS_DIR` (see
+//! [`custom_op_plugins`](super::custom_op_plugins)).
+
+use anyhow::{Result, anyhow, bail};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::{Arc, OnceLock, RwLock};
+use umya_spreadsheet::Worksheet;
+
+/// Transform kinds that custom ops may not shadow.
+const BUILTIN_TRANSFORM_KINDS: &[&str] = &[
+    "clear_range",
+    "clear_contents",
+    "clear_formats",
+    "clear_all",
+    "fill_range",
+    "replace_in_range",
+    "write_matrix",
+    "import_rows",
+    "append_table_rows",
+    "sort_range",
+    "sort_table",
+    "copy_range",
+    "move_range",
+    "fill_series",
+    "custom",
+];
+
+/// A domain-specific transform operation applied to one sheet.
+pub trait CustomTransformOp: Send + Sync {
+    /// Stable snake_case name used as `name` in `{"kind":"custom"}` ops.
+    fn name(&self) -> &str;
+
+    fn description(&self) -> &str;
+
+    /// Reject malformed params before any workbook is touched. Runs during
+    /// op resolution, so dry runs surface the same errors as applies.
+    fn validate(&self, _sheet_name: &str, _params: &Value) -> Result<()> {
+        Ok(())
+    }
+
+    fn apply(
+        &self,
+        sheet: &mut Worksheet,
+        params: &Value,
+        effects: &mut CustomOpEffects,
+    ) -> Result<()>;
+}
+
+/// Effects a custom op reports back into the batch summary.
+#[derive(Debug, Default)]
+pub struct CustomOpEffects {
+    pub(crate) cells_changed: u64,
+    pub(crate) counts: BTreeMap<String, u64>,
+    pub(crate) affected_bounds: Vec<String>,
+    pub(crate) warnings: Vec<String>,
+}
+
+impl CustomOpEffects {
+    /// Count cells whose value or formula the op changed. Drives the batch
+    /// `changed`/`would_change` indicators.
+    pub fn record_cells_changed(&mut self, cells: u64) {
+        self.cells_changed += cells;
+    }
+
+    /// Add to an op-specific summary count, reported as `<op_name>.<key>`.
+    pub fn add_count(&mut self, key: impl Into<String>, value: u64) {
+        *self.counts.entry(key.into()).or_insert(0) += value;
+    }
+
+    pub fn record_bounds(&mut self, range: impl Into<String>) {
+        self.affected_bounds.push(range.into());
+    }
+
+    pub fn warn(&mut self, warning: impl Into<String>) {
+        self.warnings.push(warning.into());
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CustomTransformOpInfo {
+    pub name: String,
+    pub description: String,
+}
+
+type OpMap = BTreeMap<String, Arc<dyn CustomTransformOp>>;
+type Registry = RwLock<OpMap>;
+
+fn registry() -> &'static Registry {
+    static REGISTRY: OnceLock<Registry> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        let (ops, errors) = load_plugin_ops();
+        let _ = PLUGIN_ERRORS.set(errors);
+        RwLock::new(ops)
+    })
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn load_plugin_ops() -> (OpMap, Vec<String>) {
+    let (plugins, load_errors) = super::custom_op_plugins::load_from_env();
+    let mut errors: Vec<String> = load_errors
+        .iter()
+        .map(|error| format!("{error:#}"))
+        .collect();
+    let mut ops = OpMap::new();
+    for op in plugins {
+        match check_name(op.name(), &ops) {
+            Ok(()) => {
+                ops.insert(op.name().to_string(), Arc::new(op));
+            }
+            Err(error) => errors.push(format!("{error:#}")),
+        }
+    }
+    (ops, errors)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn load_plugin_ops() -> (OpMap, Vec<String>) {
+    (OpMap::new(), Vec::new())
+}
+
+static PLUGIN_ERRORS: OnceLock<Vec<String>> = OnceLock::new();
+
+/// Manifests in `SPREADSHEET_MCP_CUSTOM_OPS_DIR` that failed to load, so
+/// servers can report them at startup.
+pub fn custom_op_plugin_errors() -> Vec<String> {
+    registry();
+    PLUGIN_ERRORS.get().cloned().unwrap_or_default()
+}
+
+fn check_name(name: &str, ops: &OpMap) -> Result<()> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit() || ch == '_')
+    {
+        bail!("custom transform op name '{name}' must be non-empty snake_case");
+    }
+    if BUILTIN_TRANSFORM_KINDS.contains(&name) {
+        bail!("custom transform op name '{name}' shadows a built-in transform kind");
+    }
+    if ops.contains_key(name) {
+        bail!("custom transform op '{name}' is already registered");
+    }
+    Ok(())
+}
+
+/// Register a custom transform op for this process. Names must be snake_case
+/// and must not collide with built-in kinds or previously registered ops,
+/// including those loaded from the custom op directory.
+pub fn register_custom_transform_op(op: impl CustomTransformOp + 'static) -> Result<()> {
+    let name = op.name().to_string();
+    let mut ops = registry()
+        .write()
+        .map_err(|_| anyhow!("custom transform op registry is poisoned"))?;
+    check_name(&name, &ops)?;
+    ops.insert(name, Arc::new(op));
+    Ok(())
+}
+
+pub fn custom_transform_ops() -> Vec<CustomTransformOpInfo> {
+    registry()
+        .read()
+        .map(|ops| {
+            ops.values()
+                .map(|op| CustomTransformOpInfo {
+                    name: op.name().to_string(),
+                    description: op.description().to_string(),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub(crate) fn lookup_custom_transform_op(name: &str) -> Result<Arc<dyn CustomTransformOp>> {
+    let ops = registry()
+        .read()
+        .map_err(|_| anyhow!("custom transform op registry is poisoned"))?;
+    ops.get(name).cloned().ok_or_else(|| {
+        let known = ops.keys().cloned().collect::<Vec<_>>();
+        let mut message = if known.is_empty() {
+            format!("unknown custom transform op '{name}'; no custom ops are registered")
+        } else {
+            format!(
+                "unknown custom transform op '{name}'; registered ops: {}",
+                known.join(", ")
+            )
+        };
+        let plugin_errors = PLUGIN_ERRORS.get().map(Vec::as_slice).unwrap_or_default();
+        if !plugin_errors.is_empty() {
+            message.push_str(&format!(
+                "; custom op manifests that failed to load: {}",
+                plugin_errors.join("; ")
+            ));
+        }
+        anyhow!(message)
+    })
+}
@@ -0,0 +1,297 @@
+//! Duplicate-workbook detection across workspace roots.
+//!
+//! Every workbook gets three fingerprints: a SHA-256 of its bytes, a SHA-256
+//! of its cell content (sheet names plus every non-empty value and formula in
+//! order), and a set of row hashes. Equal byte hashes are exact copies; equal
+//! content hashes with different bytes are the same workbook saved again,
+//! which rewrites timestamps and zip layout. Workbooks that differ are
+//! compared by the Jaccard index of their row sets, so a `final_v2` that
+//! edited a few cells of `final` still pairs with it.
+
+use crate::model::{
+    DedupeScanResponse, DedupeSkippedFile, DuplicateFile, DuplicateGroup, NearDuplicatePair,
+};
+use crate::utils::{hash_file_sha256_hex, path_to_forward_slashes, system_time_to_rfc3339};
+use anyhow::{Result, bail};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use walkdir::WalkDir;
+
+/// Default similarity for near-duplicate pairs.
+pub const DEFAULT_MIN_SIMILARITY: f64 = 0.9;
+
+struct ScannedWorkbook {
+    file: DuplicateFile,
+    modified: Option<SystemTime>,
+    sha256: String,
+    content: Option<ContentFingerprint>,
+}
+
+struct ContentFingerprint {
+    sha256: String,
+    rows: HashSet<u64>,
+}
+
+/// Scan `roots` recursively for workbooks with one of `extensions` and group
+/// duplicates. Paths are reported relative to `relative_to` when given.
+pub fn scan_duplicates(
+    roots: &[PathBuf],
+    extensions: &[String],
+    min_similarity: f64,
+    relative_to: Option<&Path>,
+) -> Result<DedupeScanResponse> {
+    if !(0.0..=1.0).contains(&min_similarity) {
+        bail!("min_similarity must be between 0 and 1, got {min_similarity}");
+    }
+
+    let mut paths = Vec::new();
+    for root in roots {
+        if !root.is_dir() {
+            bail!("'{}' is not a directory", root.display());
+        }
+        for entry in WalkDir::new(root) {
+            let entry = entry?;
+            let path = entry.path();
+            if entry.file_type().is_file() && is_workbook(path, extensions) && !is_lock_file(path) {
+                paths.push(path.to_path_buf());
+            }
+        }
+    }
+    paths.sort();
+    paths.dedup();
+
+    let mut workbooks = Vec::with_capacity(paths.len());
+    let mut skipped = Vec::new();
+    for path in &paths {
+        let display = display_path(path, relative_to);
+        let metadata = std::fs::metadata(path)?;
+        let modified = metadata.modified().ok();
+        let content = match content_fingerprint(path) {
+            Ok(content) => Some(content),
+            Err(error) => {
+                skipped.push(DedupeSkippedFile {
+                    path: display.clone(),
+                    reason: format!("{error:#}"),
+                });
+                None
+            }
+        };
+        workbooks.push(ScannedWorkbook {
+            file: DuplicateFile {
+                path: display,
+                bytes: metadata.len(),
+                last_modified: modified
+                    .and_then(system_time_to_rfc3339)
+                    .map(|time| time.to_rfc3339()),
+            },
+            modified,
+            sha256: hash_file_sha256_hex(path)?,
+            content,
+        });
+    }
+
+    let exact_duplicates = duplicate_groups(&workbooks, |workbook| Some(&workbook.sha256));
+    let sha256_by_path: HashMap<&str, &str> = workbooks
+        .iter()
+        .map(|workbook| (workbook.file.path.as_str(), workbook.sha256.as_str()))
+        .collect();
+    let content_duplicates = duplicate_groups(&workbooks, |workbook| {
+        workbook.content.as_ref().map(|content| &content.sha256)
+    })
+    .into_iter()
+    // Groups whose members are all byte-identical are already exact duplicates.
+    .filter(|group| {
+        let hashes: HashSet<&str> = group
+            .files
+            .iter()
+            .map(|file| sha256_by_path[file.path.as_str()])
+            .collect();
+        hashes.len() > 1
+    })
+    .collect();
+    let near_duplicates = near_duplicate_pairs(&workbooks, min_similarity);
+
+    Ok(DedupeScanResponse {
+        roots: roots
+            .iter()
+            .map(|root| display_path(root, relative_to))
+            .collect(),
+        files_scanned: workbooks.len() as u32,
+        min_similarity,
+        exact_duplicates,
+        content_duplicates,
+        near_duplicates,
+        skipped,
+    })
+}
+
+fn is_workbook(path: &Path, extensions: &[String]) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            extensions
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(ext))
+        })
+}
+
+/// Excel's `~$name.xlsx` owner files, present while a workbook is open.
+fn is_lock_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with("~$"))
+}
+
+fn display_path(path: &Path, relative_to: Option<&Path>) -> String {
+    match relative_to.and_then(|base| path.strip_prefix(base).ok()) {
+        Some(relative) if relative.as_os_str().is_empty() => ".".to_string(),
+        Some(relative) => path_to_forward_slashes(relative),
+        None => path_to_forward_slashes(path),
+    }
+}
+
+fn content_fingerprint(path: &Path) -> Result<ContentFingerprint> {
+    let book = umya_spreadsheet::reader::xlsx::read(path)
+        .map_err(|error| anyhow::anyhow!("unable to read workbook cells: {error}"))?;
+    let mut digest = Sha256::new();
+    let mut rows = HashSet::new();
+
+    for sheet in book.get_sheet_collection() {
+        let sheet_name = sheet.get_name();
+        digest.update(b"\x00sheet\x00");
+        digest.update(sheet_name.as_bytes());
+
+        let mut cells: BTreeMap<u32, Vec<(u32, String)>> = BTreeMap::new();
+        for cell in sheet.get_cell_collection() {
+            let token = if cell.is_formula() {
+                format!("={}", cell.get_formula())
+            } else {
+                cell.get_value().to_string()
+            };
+            if token.is_empty() {
+                continue;
+            }
+            let coordinate = cell.get_coordinate();
+            cells
+                .entry(*coordinate.get_row_num())
+                .or_default()
+                .push((*coordinate.get_col_num(), token));
+        }
+
+        for (row, mut row_cells) in cells {
+            row_cells.sort();
+            let mut row_hasher = DefaultHasher::new();
+            sheet_name.hash(&mut row_hasher);
+            row_cells.hash(&mut row_hasher);
+            rows.insert(row_hasher.finish());
+
+            digest.update(row.to_le_bytes());
+            for (col, token) in &row_cells {
+                digest.update(col.to_le_bytes());
+                digest.update((token.len() as u64).to_le_bytes());
+                digest.update(token.as_bytes());
+            }
+        }
+    }
+
+    Ok(ContentFingerprint {
+        sha256: format!("{:x}", digest.finalize()),
+        rows,
+    })
+}
+
+/// Groups of two or more workbooks sharing a key, newest file first, largest
+/// group first.
+fn duplicate_groups<'a>(
+    workbooks: &'a [ScannedWorkbook],
+    key: impl Fn(&'a ScannedWorkbook) -> Option<&'a String>,
+) -> Vec<DuplicateGroup> {
+    let mut by_key: BTreeMap<&String, Vec<&ScannedWorkbook>> = BTreeMap::new();
+    for workbook in workbooks {
+        if let Some(fingerprint) = key(workbook) {
+            by_key.entry(fingerprint).or_default().push(workbook);
+        }
+    }
+
+    let mut groups: Vec<DuplicateGroup> = by_key
+        .into_iter()
+        .filter(|(_, members)| members.len() > 1)
+        .map(|(fingerprint, mut members)| {
+            members.sort_by(|a, b| newest_first(a, b));
+            DuplicateGroup {
+                fingerprint: fingerprint.clone(),
+                newest: members[0].file.path.clone(),
+                files: members.iter().map(|member| member.file.clone()).collect(),
+            }
+        })
+        .collect();
+    groups.sort_by(|a, b| {
+        b.files
+            .len()
+            .cmp(&a.files.len())
+            .then_with(|| a.newest.cmp(&b.newest))
+    });
+    groups
+}
+
+fn newest_first(a: &ScannedWorkbook, b: &ScannedWorkbook) -> std::cmp::Ordering {
+    b.modified
+        .cmp(&a.modified)
+        .then_with(|| a.file.path.cmp(&b.file.path))
+}
+
+/// Pairs of workbooks with different content whose row-set similarity is at
+/// least `min_similarity`. Only one workbook per content fingerprint takes
+/// part, so copies already reported as duplicates do not repeat every pair.
+fn near_duplicate_pairs(
+    workbooks: &[ScannedWorkbook],
+    min_similarity: f64,
+) -> Vec<NearDuplicatePair> {
+    let mut seen = HashSet::new();
+    let mut candidates: Vec<(&ScannedWorkbook, &ContentFingerprint)> = workbooks
+        .iter()
+        .filter_map(|workbook| {
+            let content = workbook.content.as_ref()?;
+            (!content.rows.is_empty() && seen.insert(&content.sha256))
+                .then_some((workbook, content))
+        })
+        .collect();
+    candidates.sort_by_key(|(_, content)| content.rows.len());
+
+    let mut pairs = Vec::new();
+    for (index, (left, left_content)) in candidates.iter().enumerate() {
+        for (right, right_content) in &candidates[index + 1..] {
+            let (small, large) = (left_content.rows.len(), right_content.rows.len());
+            // Sorted by size: the Jaccard index is at most small / large, so
+            // no later candidate can qualify either.
+            if (small as f64) < min_similarity * large as f64 {
+                break;
+            }
+            let shared = left_content.rows.intersection(&right_content.rows).count();
+            let similarity = shared as f64 / (small + large - shared) as f64;
+            if similarity < min_similarity || shared == 0 {
+                continue;
+            }
+            let (first, second) = if newest_first(left, right).is_le() {
+                ((left, left_content), (right, right_content))
+            } else {
+                ((right, right_content), (left, left_content))
+            };
+            pairs.push(NearDuplicatePair {
+                files: [first.0.file.clone(), second.0.file.clone()],
+                similarity: (similarity * 1000.0).round() / 1000.0,
+                shared_rows: shared as u32,
+                rows: [first.1.rows.len() as u32, second.1.rows.len() as u32],
+            });
+        }
+    }
+    pairs.sort_by(|a, b| {
+        b.similarity
+            .total_cmp(&a.similarity)
+            .then_with(|| a.files[0].path.cmp(&b.files[0].path))
+    });
+    pairs
+}
@@ -0,0 +1,576 @@
+//! Document properties (`docProps/core.xml`, `app.xml` and `custom.xml`):
+//! title, author and the like, plus named custom properties.
+//!
+//! Read and written in the raw package rather than through umya, so every
+//! other part is left byte-for-byte as it was and custom properties of
+//! types this module does not set (dates, for instance) survive a rewrite.
+
+use super::ooxml::{
+    escape_xml_attribute, parse_attributes, read_zip_text, relationship_tag_regex, rewrite_package,
+    unescape_xml,
+};
+use crate::model::{CustomDocumentProperty, DocumentProperties};
+use anyhow::{Result, bail};
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::{Read, Seek};
+use std::path::Path;
+
+const CORE_PART: &str = "docProps/core.xml";
+const APP_PART: &str = "docProps/app.xml";
+const CUSTOM_PART: &str = "docProps/custom.xml";
+const CONTENT_TYPES_PART: &str = "[Content_Types].xml";
+const PACKAGE_RELS_PART: &str = "_rels/.rels";
+
+const CORE_CONTENT_TYPE: &str = "application/vnd.openxmlformats-package.core-properties+xml";
+const APP_CONTENT_TYPE: &str =
+    "application/vnd.openxmlformats-officedocument.extended-properties+xml";
+const CUSTOM_CONTENT_TYPE: &str =
+    "application/vnd.openxmlformats-officedocument.custom-properties+xml";
+const CORE_REL_TYPE: &str =
+    "http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties";
+const APP_REL_TYPE: &str =
+    "http://schemas.openxmlformats.org/officeDocument/2006/relationships/extended-properties";
+const CUSTOM_REL_TYPE: &str =
+    "http://schemas.openxmlformats.org/officeDocument/2006/relationships/custom-properties";
+
+const CORE_SKELETON: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:dcterms="http://purl.org/dc/terms/" xmlns:dcmitype="http://purl.org/dc/dcmitype/" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance"></cp:coreProperties>"#;
+const APP_SKELETON: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Properties xmlns="http://schemas.openxmlformats.org/officeDocument/2006/extended-properties" xmlns:vt="http://schemas.openxmlformats.org/officeDocument/2006/docPropsVTypes"></Properties>"#;
+const CUSTOM_ROOT: &str = r#"<Properties xmlns="http://schemas.openxmlformats.org/officeDocument/2006/custom-properties" xmlns:vt="http://schemas.openxmlformats.org/officeDocument/2006/docPropsVTypes">"#;
+/// The format id Office gives every user-defined custom property.
+const CUSTOM_FMTID: &str = "{D5CDD505-2E9C-101B-9397-08002B2CF9AE}";
+
+const CORE_NAMESPACES: [(&str, &str); 2] = [
+    ("dc", "http://purl.org/dc/elements/1.1/"),
+    (
+        "cp",
+        "http://schemas.openxmlformats.org/package/2006/metadata/core-properties",
+    ),
+];
+
+/// One op of a `properties-batch` payload.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PropertyOp {
+    /// Set core properties; fields left out are unchanged and an empty
+    /// string removes the property.
+    SetCore {
+        #[serde(default)]
+        title: Option<String>,
+        #[serde(default)]
+        subject: Option<String>,
+        /// Author
+        #[serde(default)]
+        creator: Option<String>,
+        #[serde(default)]
+        keywords: Option<String>,
+        #[serde(default)]
+        description: Option<String>,
+        #[serde(default)]
+        last_modified_by: Option<String>,
+        #[serde(default)]
+        category: Option<String>,
+        #[serde(default)]
+        content_status: Option<String>,
+    },
+    /// Set extended (app) properties, with the same rules as `set_core`.
+    SetApp {
+        #[serde(default)]
+        company: Option<String>,
+        #[serde(default)]
+        manager: Option<String>,
+    },
+    /// Add a custom property or replace one with the same name, compared
+    /// case-insensitively as Excel does.
+    SetCustom {
+        name: String,
+        /// A string, number or boolean
+        value: Value,
+    },
+    RemoveCustom {
+        name: String,
+    },
+}
+
+impl PropertyOp {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            PropertyOp::SetCore { .. } => "set_core",
+            PropertyOp::SetApp { .. } => "set_app",
+            PropertyOp::SetCustom { .. } => "set_custom",
+            PropertyOp::RemoveCustom { .. } => "remove_custom",
+        }
+    }
+}
+
+pub struct PropertiesApplyResult {
+    pub ops_applied: usize,
+    /// `core_fields_set`, `app_fields_set`, `custom_set` and `custom_removed`
+    pub counts: BTreeMap<String, u64>,
+    pub warnings: Vec<String>,
+}
+
+/// Document properties of the workbook at `path`.
+pub fn read_document_properties(path: &Path) -> Result<DocumentProperties> {
+    read_document_properties_from_reader(fs::File::open(path)?)
+}
+
+pub fn read_document_properties_from_reader<R: Read + Seek>(
+    reader: R,
+) -> Result<DocumentProperties> {
+    let mut archive = zip::ZipArchive::new(reader)?;
+    let core = read_zip_text(&mut archive, CORE_PART).unwrap_or_default();
+    let app = read_zip_text(&mut archive, APP_PART).unwrap_or_default();
+    let custom = read_zip_text(&mut archive, CUSTOM_PART).unwrap_or_default();
+
+    let field = |xml: &str, local: &str| {
+        element_regex(local)
+            .captures(xml)
+            .and_then(|caps| caps.get(3))
+            .map(|text| unescape_xml(text.as_str()))
+            .filter(|text| !text.is_empty())
+    };
+    Ok(DocumentProperties {
+        title: field(&core, "title"),
+        subject: field(&core, "subject"),
+        creator: field(&core, "creator"),
+        keywords: field(&core, "keywords"),
+        description: field(&core, "description"),
+        last_modified_by: field(&core, "lastModifiedBy"),
+        category: field(&core, "category"),
+        content_status: field(&core, "contentStatus"),
+        created: field(&core, "created"),
+        modified: field(&core, "modified"),
+        company: field(&app, "Company"),
+        manager: field(&app, "Manager"),
+        application: field(&app, "Application"),
+        custom: parse_custom_properties(&custom)
+            .into_iter()
+            .map(|property| CustomDocumentProperty {
+                value: custom_value(&property.value_xml),
+                name: property.name,
+            })
+            .collect(),
+    })
+}
+
+/// Apply `ops` in order to the workbook at `path`, rewriting only the
+/// property parts (and, when a part has to be created, the content types
+/// and package relationships).
+pub fn apply_property_ops(path: &Path, ops: &[PropertyOp]) -> Result<PropertiesApplyResult> {
+    let mut archive = zip::ZipArchive::new(fs::File::open(path)?)?;
+    let mut content_types = read_zip_text(&mut archive, CONTENT_TYPES_PART)?;
+    let mut package_rels = read_zip_text(&mut archive, PACKAGE_RELS_PART)?;
+    let original_core = read_zip_text(&mut archive, CORE_PART).ok();
+    let original_app = read_zip_text(&mut archive, APP_PART).ok();
+    let original_custom = read_zip_text(&mut archive, CUSTOM_PART).ok();
+    drop(archive);
+
+    let mut core = original_core.clone();
+    let mut app = original_app.clone();
+    let mut custom = original_custom
+        .as_deref()
+        .map(parse_custom_properties)
+        .unwrap_or_default();
+    let mut counts: BTreeMap<String, u64> = BTreeMap::new();
+    let mut warnings = Vec::new();
+
+    for op in ops {
+        match op {
+            PropertyOp::SetCore {
+                title,
+                subject,
+                creator,
+                keywords,
+                description,
+                last_modified_by,
+                category,
+                content_status,
+            } => {
+                let fields = [
+                    ("dc:title", title),
+                    ("dc:subject", subject),
+                    ("dc:creator", creator),
+                    ("cp:keywords", keywords),
+                    ("dc:description", description),
+                    ("cp:lastModifiedBy", last_modified_by),
+                    ("cp:category", category),
+                    ("cp:contentStatus", content_status),
+                ];
+                if fields.iter().all(|(_, value)| value.is_none()) {
+                    bail!("set_core requires at least one property");
+                }
+                let xml = core.get_or_insert_with(|| CORE_SKELETON.to_string());
+                for (qualified, value) in fields {
+                    if let Some(value) = value {
+                        let (prefix, _) = qualified.split_once(':').expect("qualified name");
+                        if let Some((_, uri)) = CORE_NAMESPACES.iter().find(|(p, _)| *p == prefix) {
+                            *xml = ensure_namespace(xml, prefix, uri);
+                        }
+                        *xml = set_element(xml, qualified, value)?;
+                        *counts.entry("core_fields_set".to_string()).or_insert(0) += 1;
+                    }
+                }
+            }
+            PropertyOp::SetApp { company, manager } => {
+                let fields = [("Company", company), ("Manager", manager)];
+                if fields.iter().all(|(_, value)| value.is_none()) {
+                    bail!("set_app requires at least one property");
+                }
+                let xml = app.get_or_insert_with(|| APP_SKELETON.to_string());
+                for (qualified, value) in fields {
+                    if let Some(value) = value {
+                        *xml = set_element(xml, qualified, value)?;
+                        *counts.entry("app_fields_set".to_string()).or_insert(0) += 1;
+                    }
+                }
+            }
+            PropertyOp::SetCustom { name, value } => {
+                let name = name.trim();
+                if name.is_empty() {
+                    bail!("set_custom requires a non-empty name");
+                }
+                let value_xml = custom_value_xml(name, value)?;
+                match custom
+                    .iter_mut()
+                    .find(|property| property.name.eq_ignore_ascii_case(name))
+                {
+                    Some(property) => {
+                        property.name = name.to_string();
+                        property.value_xml = value_xml;
+                    }
+                    None => custom.push(RawCustomProperty {
+                        name: name.to_string(),
+                        value_xml,
+                    }),
+                }
+                *counts.entry("custom_set".to_string()).or_insert(0) += 1;
+            }
+            PropertyOp::RemoveCustom { name } => {
+                let before = custom.len();
+                custom.retain(|property| !property.name.eq_ignore_ascii_case(name.trim()));
+                if custom.len() == before {
+                    warnings.push(format!(
+                        "WARN_CUSTOM_PROPERTY_NOT_FOUND: no custom property named '{}'",
+                        name.trim()
+                    ));
+                } else {
+                    *counts.entry("custom_removed".to_string()).or_insert(0) += 1;
+                }
+            }
+        }
+    }
+
+    let mut edits: HashMap<String, Option<Vec<u8>>> = HashMap::new();
+    let mut package_changed = false;
+    if core != original_core
+        && let Some(core) = core
+    {
+        package_changed |= register_part(
+            &mut content_types,
+            &mut package_rels,
+            CORE_PART,
+            CORE_CONTENT_TYPE,
+            CORE_REL_TYPE,
+        );
+        edits.insert(CORE_PART.to_string(), Some(core.into_bytes()));
+    }
+    if app != original_app
+        && let Some(app) = app
+    {
+        package_changed |= register_part(
+            &mut content_types,
+            &mut package_rels,
+            APP_PART,
+            APP_CONTENT_TYPE,
+            APP_REL_TYPE,
+        );
+        edits.insert(APP_PART.to_string(), Some(app.into_bytes()));
+    }
+    let custom_xml = write_custom_properties(&custom);
+    let custom_changed = match &original_custom {
+        Some(original) => parse_custom_properties(original) != custom,
+        None => !custom.is_empty(),
+    };
+    if custom_changed {
+        package_changed |= register_part(
+            &mut content_types,
+            &mut package_rels,
+            CUSTOM_PART,
+            CUSTOM_CONTENT_TYPE,
+            CUSTOM_REL_TYPE,
+        );
+        edits.insert(CUSTOM_PART.to_string(), Some(custom_xml.into_bytes()));
+    }
+    if package_changed {
+        edits.insert(
+            CONTENT_TYPES_PART.to_string(),
+            Some(content_types.into_bytes()),
+        );
+        edits.insert(
+            PACKAGE_RELS_PART.to_string(),
+            Some(package_rels.into_bytes()),
+        );
+    }
+    if !edits.is_empty() {
+        rewrite_package(path, edits)?;
+    }
+
+    Ok(PropertiesApplyResult {
+        ops_applied: ops.len(),
+        counts,
+        warnings,
+    })
+}
+
+/// An element by local name, whatever its prefix: group 1 is the qualified
+/// name, 2 its attributes and 3 its text (absent when self-closing).
+fn element_regex(local: &str) -> Regex {
+    Regex::new(&format!(
+        r"(?s)<((?:\w+:)?{local})\b([^>]*?)(?:/>|>(.*?)</(?:\w+:)?{local}>)"
+    ))
+    .expect("valid element regex")
+}
+
+/// Set the text of element `qualified`, adding it before the root's closing
+/// tag when missing; an empty `value` removes the element.
+fn set_element(xml: &str, qualified: &str, value: &str) -> Result<String> {
+    let local = qualified
+        .split_once(':')
+        .map_or(qualified, |(_, local)| local);
+    let re = element_regex(local);
+    let text = escape_xml_attribute(value);
+    if let Some(caps) = re.captures(xml) {
+        let whole = caps.get(0).expect("whole match");
+        let replacement = if value.is_empty() {
+            String::new()
+        } else {
+            let name = &caps[1];
+            let attrs = caps[2].trim_end();
+            format!("<{name}{attrs}>{text}</{name}>")
+        };
+        return Ok(format!(
+            "{}{}{}",
+            &xml[..whole.start()],
+            replacement,
+            &xml[whole.end()..]
+        ));
+    }
+    if value.is_empty() {
+        return Ok(xml.to_string());
+    }
+    let Some(close) = xml.rfind("</") else {
+        bail!("document properties part has no closing root tag");
+    };
+    Ok(format!(
+        "{}<{qualified}>{text}</{qualified}>{}",
+        &xml[..close],
+        &xml[close..]
+    ))
+}
+
+/// Declare `prefix` on the root element when the part does not already.
+fn ensure_namespace(xml: &str, prefix: &str, uri: &str) -> String {
+    if xml.contains(&format!("xmlns:{prefix}=")) {
+        return xml.to_string();
+    }
+    let root = Regex::new(r"<[A-Za-z][\w:.-]*").expect("valid root regex");
+    match root.find(xml) {
+        Some(tag) => format!(
+            "{} xmlns:{prefix}=\"{uri}\"{}",
+            &xml[..tag.end()],
+            &xml[tag.end()..]
+        ),
+        None => xml.to_string(),
+    }
+}
+
+/// Make sure `part` has a content type override and a package relationship.
+/// Returns whether either had to be added.
+fn register_part(
+    content_types: &mut String,
+    package_rels: &mut String,
+    part: &str,
+    content_type: &str,
+    rel_type: &str,
+) -> bool {
+    let mut changed = false;
+    let part_name = format!("/{part}");
+    let has_override = Regex::new(r"<(?:\w+:)?Override\b[^>]*>")
+        .expect("valid Override regex")
+        .find_iter(content_types)
+        .any(|tag| parse_attributes(tag.as_str()).get("PartName") == Some(&part_name));
+    if !has_override && let Some(close) = content_types.rfind("</") {
+        content_types.insert_str(
+            close,
+            &format!(r#"<Override PartName="{part_name}" ContentType="{content_type}"/>"#),
+        );
+        changed = true;
+    }
+
+    let rel_tags: Vec<HashMap<String, String>> = relationship_tag_regex()
+        .find_iter(package_rels)
+        .map(|tag| parse_attributes(tag.as_str()))
+        .collect();
+    let has_rel = rel_tags.iter().any(|attrs| {
+        attrs
+            .get("Target")
+            .is_some_and(|target| target.trim_start_matches('/') == part)
+    });
+    if !has_rel && let Some(close) = package_rels.rfind("</") {
+        let mut next = rel_tags.len() + 1;
+        while rel_tags
+            .iter()
+            .any(|attrs| attrs.get("Id") == Some(&format!("rId{next}")))
+        {
+            next += 1;
+        }
+        package_rels.insert_str(
+            close,
+            &format!(r#"<Relationship Id="rId{next}" Type="{rel_type}" Target="{part}"/>"#),
+        );
+        changed = true;
+    }
+    changed
+}
+
+/// A custom property with its value kept as the raw `vt:*` element, so
+/// types this module never writes round-trip unchanged.
+#[derive(Debug, Clone, PartialEq)]
+struct RawCustomProperty {
+    name: String,
+    value_xml: String,
+}
+
+fn parse_custom_properties(xml: &str) -> Vec<RawCustomProperty> {
+    let property_re = Regex::new(r"(?s)<(?:\w+:)?property\b([^>]*)>(.*?)</(?:\w+:)?property>")
+        .expect("valid property regex");
+    property_re
+        .captures_iter(xml)
+        .filter_map(|caps| {
+            let attrs = parse_attributes(&caps[1]);
+            Some(RawCustomProperty {
+                name: unescape_xml(attrs.get("name")?),
+                value_xml: caps[2].trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+fn write_custom_properties(properties: &[RawCustomProperty]) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n");
+    xml.push_str(CUSTOM_ROOT);
+    // Property ids start at 2; 0 and 1 are reserved.
+    for (index, property) in properties.iter().enumerate() {
+        xml.push_str(&format!(
+            r#"<property fmtid="{CUSTOM_FMTID}" pid="{}" name="{}">{}</property>"#,
+            index + 2,
+            escape_xml_attribute(&property.name),
+            property.value_xml
+        ));
+    }
+    xml.push_str("</Properties>");
+    xml
+}
+
+/// The `vt:*` element for a custom property value: text as `lpwstr`, whole
+/// numbers that fit as `i4`, other numbers as `r8`, booleans as `bool`.
+fn custom_value_xml(name: &str, value: &Value) -> Result<String> {
+    Ok(match value {
+        Value::String(text) => format!("<vt:lpwstr>{}</vt:lpwstr>", escape_xml_attribute(text)),
+        Value::Bool(flag) => format!("<vt:bool>{flag}</vt:bool>"),
+        Value::Number(number) => match number.as_i64().and_then(|n| i32::try_from(n).ok()) {
+            Some(whole) => format!("<vt:i4>{whole}</vt:i4>"),
+            None => match number.as_f64() {
+                Some(real) if real.is_finite() => format!("<vt:r8>{real}</vt:r8>"),
+                _ => bail!("value for custom property '{name}' is not a finite number"),
+            },
+        },
+        _ => bail!("value for custom property '{name}' must be a string, number or boolean"),
+    })
+}
+
+fn custom_value(value_xml: &str) -> Value {
+    let value_re =
+        Regex::new(r"(?s)^<(?:\w+:)?(\w+)\b[^>]*?(?:/>|>(.*?)</)").expect("valid value regex");
+    let Some(caps) = value_re.captures(value_xml) else {
+        return Value::Null;
+    };
+    let text = unescape_xml(caps.get(2).map_or("", |text| text.as_str()));
+    match &caps[1] {
+        "bool" => Value::Bool(matches!(text.trim(), "true" | "1")),
+        "i1" | "i2" | "i4" | "i8" | "int" | "ui1" | "ui2" | "ui4" | "ui8" | "uint" | "r4"
+        | "r8" | "decimal" => text
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .and_then(|number| {
+                if number.fract() == 0.0 && number.abs() < 9.0e15 {
+                    Some(Value::from(number as i64))
+                } else {
+                    serde_json::Number::from_f64(number).map(Value::Number)
+                }
+            })
+            .unwrap_or(Value::String(text)),
+        _ => Value::String(text),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_element_replaces_inserts_and_removes() {
+        let xml = r#"<cp:coreProperties xmlns:cp="x" xmlns:dc="y"><dc:title>Old</dc:title><dc:creator/></cp:coreProperties>"#;
+        let xml = set_element(xml, "dc:title", "Q1 & Q2").unwrap();
+        assert!(xml.contains("<dc:title>Q1 &amp; Q2</dc:title>"));
+        let xml = set_element(&xml, "dc:creator", "Finance").unwrap();
+        assert!(xml.contains("<dc:creator>Finance</dc:creator>"));
+        let xml = set_element(&xml, "cp:category", "Report").unwrap();
+        assert!(xml.ends_with("<cp:category>Report</cp:category></cp:coreProperties>"));
+        let xml = set_element(&xml, "dc:title", "").unwrap();
+        assert!(!xml.contains("title"));
+    }
+
+    #[test]
+    fn custom_values_round_trip_by_type() {
+        let properties = vec![
+            RawCustomProperty {
+                name: "RunId".to_string(),
+                value_xml: custom_value_xml("RunId", &Value::from("run-42")).unwrap(),
+            },
+            RawCustomProperty {
+                name: "Batch".to_string(),
+                value_xml: custom_value_xml("Batch", &Value::from(7)).unwrap(),
+            },
+            RawCustomProperty {
+                name: "Rate".to_string(),
+                value_xml: custom_value_xml("Rate", &Value::from(0.25)).unwrap(),
+            },
+            RawCustomProperty {
+                name: "Final".to_string(),
+                value_xml: custom_value_xml("Final", &Value::from(true)).unwrap(),
+            },
+        ];
+        let parsed = parse_custom_properties(&write_custom_properties(&properties));
+        assert_eq!(parsed, properties);
+        let values: Vec<Value> = parsed.iter().map(|p| custom_value(&p.value_xml)).collect();
+        assert_eq!(
+            values,
+            vec![
+                Value::from("run-42"),
+                Value::from(7),
+                Value::from(0.25),
+                Value::from(true)
+            ]
+        );
+        assert!(custom_value_xml("Tags", &serde_json::json!(["a"])).is_err());
+    }
+}
@@ -0,0 +1,313 @@
+//! Embedded OLE objects and linked documents.
+//!
+//! umya-spreadsheet does not model `<oleObjects>`, so the inventory is read
+//! from the raw package: each worksheet's `oleObject` elements, resolved
+//! through the worksheet rels to an embedded part (`xl/embeddings/...`) or,
+//! for linked objects, through the workbook's external links to the linked
+//! file. Embedded parts are where file size hides, and neither kind of
+//! object is visible to cell reads.
+
+use super::ooxml::{parse_attributes, read_zip_text, relationship_tag_regex, resolve_sheet_parts};
+use crate::model::{EmbeddedObject, EmbeddedObjectKind};
+use crate::utils::cell_address;
+use anyhow::Result;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{Read, Seek};
+use std::path::Path;
+use std::sync::OnceLock;
+
+const OLE_OBJECT_REL: &str = "/oleObject";
+const PACKAGE_REL: &str = "/package";
+
+/// Embedded and linked objects by sheet name, in document order.
+pub fn read_embedded_objects(path: &Path) -> Result<HashMap<String, Vec<EmbeddedObject>>> {
+    read_embedded_objects_from_reader(fs::File::open(path)?)
+}
+
+pub fn read_embedded_objects_from_reader<R: Read + Seek>(
+    reader: R,
+) -> Result<HashMap<String, Vec<EmbeddedObject>>> {
+    let mut archive = zip::ZipArchive::new(reader)?;
+    let sheet_parts = resolve_sheet_parts(&mut archive)?;
+    let external_links = resolve_external_link_targets(&mut archive);
+
+    static OLE_OBJECT: OnceLock<Regex> = OnceLock::new();
+    let object_re = OLE_OBJECT.get_or_init(|| {
+        Regex::new(r"(?s)<(?:\w+:)?oleObject\b([^>]*?)(?:/>|>(.*?)</(?:\w+:)?oleObject>)")
+            .expect("valid oleObject regex")
+    });
+
+    let mut objects = HashMap::new();
+    for (sheet_name, part) in sheet_parts {
+        let Ok(xml) = read_zip_text(&mut archive, &part) else {
+            continue;
+        };
+        if !xml.contains("oleObject") {
+            continue;
+        }
+        let rels = read_relationships(&mut archive, &part);
+
+        let mut seen = HashSet::new();
+        let mut sheet_objects = Vec::new();
+        for caps in object_re.captures_iter(&xml) {
+            let attrs = parse_attributes(&caps[1]);
+            let body = caps.get(2).map(|m| m.as_str()).unwrap_or_default();
+            // mc:AlternateContent repeats each object in Choice and Fallback.
+            let key = attrs
+                .get("shapeId")
+                .or_else(|| attrs.get("r:id"))
+                .or_else(|| attrs.get("link"))
+                .cloned()
+                .unwrap_or_default();
+            if !seen.insert(key) {
+                continue;
+            }
+
+            let prog_id = attrs.get("progId").cloned();
+            let anchor_cell = parse_anchor_cell(body);
+            let rel = attrs.get("r:id").and_then(|id| rels.get(id));
+            let object = match (rel, attrs.get("link")) {
+                (Some(rel), _) if rel.external => EmbeddedObject {
+                    kind: EmbeddedObjectKind::Linked,
+                    prog_id,
+                    file_type: file_type_of(&rel.target),
+                    part: None,
+                    target: Some(rel.target.clone()),
+                    size_bytes: None,
+                    anchor_cell,
+                },
+                (Some(rel), _) => EmbeddedObject {
+                    kind: if rel.rel_type.ends_with(PACKAGE_REL) {
+                        EmbeddedObjectKind::Package
+                    } else {
+                        EmbeddedObjectKind::Ole
+                    },
+                    prog_id,
+                    file_type: file_type_of(&rel.target),
+                    size_bytes: archive.by_name(&rel.target).ok().map(|file| file.size()),
+                    part: Some(rel.target.clone()),
+                    target: None,
+                    anchor_cell,
+                },
+                (None, Some(link)) => {
+                    let target = external_link_index(link)
+                        .and_then(|index| external_links.get(index))
+                        .cloned()
+                        .flatten();
+                    EmbeddedObject {
+                        kind: EmbeddedObjectKind::Linked,
+                        prog_id,
+                        file_type: target.as_deref().and_then(file_type_of),
+                        part: None,
+                        target,
+                        size_bytes: None,
+                        anchor_cell,
+                    }
+                }
+                (None, None) => continue,
+            };
+            sheet_objects.push(object);
+        }
+        if !sheet_objects.is_empty() {
+            objects.insert(sheet_name, sheet_objects);
+        }
+    }
+    Ok(objects)
+}
+
+pub(crate) struct Relationship {
+    pub(crate) rel_type: String,
+    /// Package part path for internal targets, raw target otherwise.
+    pub(crate) target: String,
+    pub(crate) external: bool,
+}
+
+/// Object and external-link relationships of `part`, keyed by id.
+pub(crate) fn read_relationships<R: Read + Seek>(
+    archive: &mut zip::ZipArchive<R>,
+    part: &str,
+) -> HashMap<String, Relationship> {
+    let (dir, file) = part.rsplit_once('/').unwrap_or(("", part));
+    let rels_path = format!("{dir}/_rels/{file}.rels");
+    let Ok(rels_xml) = read_zip_text(archive, &rels_path) else {
+        return HashMap::new();
+    };
+    relationship_tag_regex()
+        .find_iter(&rels_xml)
+        .filter_map(|tag| {
+            let attrs = parse_attributes(tag.as_str());
+            let id = attrs.get("Id")?.clone();
+            let target = attrs.get("Target")?;
+            let external = attrs
+                .get("TargetMode")
+                .is_some_and(|mode| mode == "External");
+            let rel_type = attrs.get("Type").cloned().unwrap_or_default();
+            let target = if external {
+                target.clone()
+            } else {
+                resolve_part_path(dir, target)
+            };
+            Some((
+                id,
+                Relationship {
+                    rel_type,
+                    target,
+                    external,
+                },
+            ))
+        })
+        .filter(|(_, rel)| {
+            rel.rel_type.ends_with(OLE_OBJECT_REL)
+                || rel.rel_type.ends_with(PACKAGE_REL)
+                || rel.rel_type.ends_with("/externalLink")
+                || rel.rel_type.ends_with("/externalLinkPath")
+        })
+        .collect()
+}
+
+/// Linked file of each external link, in `<externalReference>` order (the
+/// `[n]` in an `oleObject link`).
+fn resolve_external_link_targets<R: Read + Seek>(
+    archive: &mut zip::ZipArchive<R>,
+) -> Vec<Option<String>> {
+    let Ok(workbook_xml) = read_zip_text(archive, "xl/workbook.xml") else {
+        return Vec::new();
+    };
+    let workbook_rels = read_relationships(archive, "xl/workbook.xml");
+    static EXTERNAL_REFERENCE: OnceLock<Regex> = OnceLock::new();
+    let reference_re = EXTERNAL_REFERENCE.get_or_init(|| {
+        Regex::new(r"<(?:\w+:)?externalReference\b[^>]*>").expect("valid externalReference regex")
+    });
+    let link_parts: Vec<Option<String>> = reference_re
+        .find_iter(&workbook_xml)
+        .map(|tag| {
+            parse_attributes(tag.as_str())
+                .get("r:id")
+                .and_then(|id| workbook_rels.get(id))
+                .map(|rel| rel.target.clone())
+        })
+        .collect();
+    link_parts
+        .into_iter()
+        .map(|part| {
+            let part = part?;
+            read_relationships(archive, &part)
+                .into_values()
+                .find(|rel| rel.external)
+                .map(|rel| rel.target)
+        })
+        .collect()
+}
+
+/// Zero-based external link index from an `oleObject link` such as `[2]`.
+fn external_link_index(link: &str) -> Option<usize> {
+    let inner = link.trim().strip_prefix('[')?;
+    let (digits, _) = inner.split_once(']')?;
+    digits.parse::<usize>().ok()?.checked_sub(1)
+}
+
+/// Resolve a relationship target relative to the directory of its source part.
+pub(crate) fn resolve_part_path(base_dir: &str, target: &str) -> String {
+    if let Some(absolute) = target.strip_prefix('/') {
+        return absolute.to_string();
+    }
+    let mut segments: Vec<&str> = base_dir.split('/').filter(|s| !s.is_empty()).collect();
+    for segment in target.split('/') {
+        match segment {
+            ".." => {
+                segments.pop();
+            }
+            "." | "" => {}
+            other => segments.push(other),
+        }
+    }
+    segments.join("/")
+}
+
+/// Top-left cell of the object's `<anchor><from>` marker.
+fn parse_anchor_cell(body: &str) -> Option<String> {
+    static ANCHOR_FROM: OnceLock<Regex> = OnceLock::new();
+    let from_re = ANCHOR_FROM.get_or_init(|| {
+        Regex::new(
+            r"(?s)<(?:\w+:)?from>.*?<(?:\w+:)?col>(\d+)</(?:\w+:)?col>.*?<(?:\w+:)?row>(\d+)</(?:\w+:)?row>",
+        )
+        .expect("valid anchor regex")
+    });
+    let caps = from_re.captures(body)?;
+    let col = caps[1].parse::<u32>().ok()?;
+    let row = caps[2].parse::<u32>().ok()?;
+    Some(cell_address(col + 1, row + 1))
+}
+
+fn file_type_of(target: &str) -> Option<String> {
+    let name = target.rsplit(['/', '\\']).next().unwrap_or(target);
+    let (_, extension) = name.rsplit_once('.')?;
+    (!extension.is_empty()).then(|| extension.to_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+    use zip::write::FileOptions;
+
+    #[test]
+    fn lists_embedded_and_linked_objects_per_sheet() {
+        let mut buffer = Cursor::new(Vec::new());
+        {
+            let mut zip = zip::ZipWriter::new(&mut buffer);
+            let options = FileOptions::default();
+            let mut add = |name: &str, body: &[u8]| {
+                zip.start_file(name, options).expect("start file");
+                zip.write_all(body).expect("write file");
+            };
+            add(
+                "xl/workbook.xml",
+                br#"<workbook xmlns:r="r"><sheets><sheet name="Report" sheetId="1" r:id="rId1"/></sheets><externalReferences><externalReference r:id="rId2"/></externalReferences></workbook>"#,
+            );
+            add(
+                "xl/_rels/workbook.xml.rels",
+                br#"<Relationships><Relationship Id="rId1" Type="http://x/relationships/worksheet" Target="worksheets/sheet1.xml"/><Relationship Id="rId2" Type="http://x/relationships/externalLink" Target="externalLinks/externalLink1.xml"/></Relationships>"#,
+            );
+            add(
+                "xl/externalLinks/_rels/externalLink1.xml.rels",
+                br#"<Relationships><Relationship Id="rId1" Type="http://x/relationships/oleObject" Target="file:///C:\Docs\Terms.docx" TargetMode="External"/></Relationships>"#,
+            );
+            add(
+                "xl/worksheets/sheet1.xml",
+                br#"<worksheet><sheetData/><oleObjects><mc:AlternateContent><mc:Choice><oleObject progId="Word.Document.12" shapeId="1025" r:id="rId3"><objectPr><anchor><from><xdr:col>1</xdr:col><xdr:colOff>0</xdr:colOff><xdr:row>4</xdr:row></from></anchor></objectPr></oleObject></mc:Choice><mc:Fallback><oleObject progId="Word.Document.12" shapeId="1025" r:id="rId3"/></mc:Fallback></mc:AlternateContent><oleObject progId="Word.Document.12" shapeId="1026" link="[1]!''''"/></oleObjects></worksheet>"#,
+            );
+            add(
+                "xl/worksheets/_rels/sheet1.xml.rels",
+                br#"<Relationships><Relationship Id="rId3" Type="http://x/relationships/package" Target="../embeddings/Microsoft_Word_Document.docx"/></Relationships>"#,
+            );
+            add("xl/embeddings/Microsoft_Word_Document.docx", &[0u8; 1234]);
+            zip.finish().expect("finish zip");
+        }
+        buffer.set_position(0);
+
+        let objects = read_embedded_objects_from_reader(buffer).expect("read objects");
+        let report = &objects["Report"];
+        assert_eq!(report.len(), 2);
+
+        assert_eq!(report[0].kind, EmbeddedObjectKind::Package);
+        assert_eq!(report[0].prog_id.as_deref(), Some("Word.Document.12"));
+        assert_eq!(
+            report[0].part.as_deref(),
+            Some("xl/embeddings/Microsoft_Word_Document.docx")
+        );
+        assert_eq!(report[0].size_bytes, Some(1234));
+        assert_eq!(report[0].file_type.as_deref(), Some("docx"));
+        assert_eq!(report[0].anchor_cell.as_deref(), Some("B5"));
+
+        assert_eq!(report[1].kind, EmbeddedObjectKind::Linked);
+        assert_eq!(
+            report[1].target.as_deref(),
+            Some(r"file:///C:\Docs\Terms.docx")
+        );
+        assert_eq!(report[1].file_type.as_deref(), Some("docx"));
+        assert_eq!(report[1].size_bytes, None);
+    }
+}
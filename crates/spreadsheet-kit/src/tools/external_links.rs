@@ -0,0 +1,750 @@
+//! Links to other workbooks (`xl/externalLinks/*`).
+//!
+//! umya-spreadsheet does not model external links, so they are read and
+//! rewritten in the raw package. Each `<externalReference>` in workbook.xml
+//! points at a part that names the linked file (through the part's rels) and
+//! caches the values last read from it. Formulas refer to a link by its
+//! 1-based position in that list: `[1]Rates!B2`, `'[2]Q1 Plan'!C4`,
+//! `[1]!TaxRate`.
+
+use super::embedded_objects::read_relationships;
+use super::ooxml::{
+    escape_xml_attribute, parse_attributes, read_zip_text, relationship_tag_regex,
+    resolve_sheet_parts, rewrite_package, unescape_xml,
+};
+use crate::model::{ExternalLink, ExternalLinkStatus, ExternalLinkUse, ExternalStaleCell};
+use anyhow::{Result, anyhow, bail};
+use regex::{Captures, Regex};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::io::{Read, Seek};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Stale cells listed per link; `stale_cell_count` covers all of them.
+const STALE_CELL_SAMPLE: usize = 25;
+/// Formula cells listed per link; `used_by_count` covers all of them.
+const USE_SAMPLE: usize = 50;
+
+/// External links in `<externalReference>` order, with the formulas that use
+/// them and whether their cached values still match the linked workbooks.
+/// Relative targets resolve against the directory of `path`.
+pub fn read_external_links(path: &Path) -> Result<Vec<ExternalLink>> {
+    let mut archive = zip::ZipArchive::new(fs::File::open(path)?)?;
+    let (mut links, parts) = collect_links(&mut archive)?;
+    let base_dir = path.parent().unwrap_or(Path::new("."));
+    for (link, part) in links.iter_mut().zip(&parts) {
+        check_staleness(link, &part.cached, base_dir);
+    }
+    Ok(links)
+}
+
+/// External links and their uses, without comparing cached values against
+/// the linked workbooks: every status is `unchecked` or `unresolvable`.
+pub fn read_external_links_from_reader<R: Read + Seek>(reader: R) -> Result<Vec<ExternalLink>> {
+    let mut archive = zip::ZipArchive::new(reader)?;
+    Ok(collect_links(&mut archive)?.0)
+}
+
+fn collect_links<R: Read + Seek>(
+    archive: &mut zip::ZipArchive<R>,
+) -> Result<(Vec<ExternalLink>, Vec<LinkPart>)> {
+    let parts = read_link_parts(archive)?;
+    if parts.is_empty() {
+        return Ok((Vec::new(), parts));
+    }
+
+    let mut links: Vec<ExternalLink> = parts
+        .iter()
+        .enumerate()
+        .map(|(position, part)| ExternalLink {
+            index: position as u32 + 1,
+            part: part.part.clone(),
+            target: part.target.clone(),
+            resolved_path: None,
+            status: if part.target.is_some() {
+                ExternalLinkStatus::Unchecked
+            } else {
+                ExternalLinkStatus::Unresolvable
+            },
+            sheet_names: part.sheet_names.clone(),
+            cached_cell_count: part.cached.len() as u32,
+            stale_cell_count: 0,
+            stale_cells: Vec::new(),
+            used_by_count: 0,
+            used_by: Vec::new(),
+            defined_names: Vec::new(),
+        })
+        .collect();
+
+    for cell in scan_formula_cells(archive)? {
+        for index in link_references(&cell.formula) {
+            let Some(link) = links.get_mut(index as usize - 1) else {
+                continue;
+            };
+            link.used_by_count += 1;
+            if link.used_by.len() < USE_SAMPLE {
+                link.used_by.push(ExternalLinkUse {
+                    sheet: cell.sheet.clone(),
+                    cell: cell.cell.clone(),
+                    formula: unescape_xml(&cell.formula),
+                });
+            }
+        }
+    }
+
+    let workbook_xml = read_zip_text(archive, "xl/workbook.xml")?;
+    for caps in defined_name_regex().captures_iter(&workbook_xml) {
+        let Some(name) = parse_attributes(&caps[1])
+            .get("name")
+            .map(|name| unescape_xml(name))
+        else {
+            continue;
+        };
+        for index in link_references(&caps[2]) {
+            if let Some(link) = links.get_mut(index as usize - 1) {
+                link.defined_names.push(name.clone());
+            }
+        }
+    }
+    Ok((links, parts))
+}
+
+/// What [`break_external_links`] changed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BreakLinksOutcome {
+    pub links_removed: u32,
+    /// Formula cells replaced by their cached values.
+    pub formulas_converted: u32,
+    /// Defined names set to `#REF!` because they referenced a removed link.
+    pub names_invalidated: u32,
+}
+
+/// Remove the links at the given 1-based indices, as Excel's Break Link does:
+/// formulas that reference them are replaced by their cached values, defined
+/// names that reference them become `#REF!`, and references to the remaining
+/// links are renumbered.
+pub fn break_external_links(path: &Path, indices: &BTreeSet<u32>) -> Result<BreakLinksOutcome> {
+    if indices.is_empty() {
+        return Ok(BreakLinksOutcome::default());
+    }
+    let mut archive = zip::ZipArchive::new(fs::File::open(path)?)?;
+    let parts = read_link_parts(&mut archive)?;
+    for index in indices {
+        if *index == 0 || *index as usize > parts.len() {
+            bail!(
+                "external link [{}] not found; the workbook has {} external link(s)",
+                index,
+                parts.len()
+            );
+        }
+    }
+
+    let mut renumbered: HashMap<u32, Option<u32>> = HashMap::new();
+    let mut next = 1;
+    for index in 1..=parts.len() as u32 {
+        if indices.contains(&index) {
+            renumbered.insert(index, None);
+        } else {
+            renumbered.insert(index, Some(next));
+            next += 1;
+        }
+    }
+
+    let mut outcome = BreakLinksOutcome {
+        links_removed: indices.len() as u32,
+        ..Default::default()
+    };
+    let mut edits: HashMap<String, Option<Vec<u8>>> = HashMap::new();
+
+    for (_, sheet_part) in ordered_sheet_parts(&mut archive)? {
+        let Ok(xml) = read_zip_text(&mut archive, &sheet_part) else {
+            continue;
+        };
+        let (rewritten, converted) = rewrite_sheet_links(&xml, &renumbered);
+        outcome.formulas_converted += converted;
+        if rewritten != xml {
+            edits.insert(sheet_part, Some(rewritten.into_bytes()));
+        }
+    }
+
+    let mut workbook_xml = read_zip_text(&mut archive, "xl/workbook.xml")?;
+    let removed_rel_ids: BTreeSet<&str> = indices
+        .iter()
+        .map(|index| parts[*index as usize - 1].rel_id.as_str())
+        .collect();
+    workbook_xml = external_reference_regex()
+        .replace_all(&workbook_xml, |caps: &Captures| {
+            let attrs = parse_attributes(&caps[0]);
+            match attrs.get("r:id") {
+                Some(id) if removed_rel_ids.contains(id.as_str()) => String::new(),
+                _ => caps[0].to_string(),
+            }
+        })
+        .into_owned();
+    workbook_xml = Regex::new(
+        r"<(?:\w+:)?externalReferences\b[^>]*>\s*</(?:\w+:)?externalReferences>|<(?:\w+:)?externalReferences\b[^>]*/>",
+    )
+    .expect("valid externalReferences regex")
+    .replace_all(&workbook_xml, "")
+    .into_owned();
+    workbook_xml = defined_name_regex()
+        .replace_all(&workbook_xml, |caps: &Captures| {
+            let full = &caps[0];
+            let body = &caps[2];
+            let referenced = link_references(body);
+            if referenced.iter().any(|index| indices.contains(index)) {
+                outcome.names_invalidated += 1;
+                full.replacen(&format!(">{body}<"), ">#REF!<", 1)
+            } else if referenced.is_empty() {
+                full.to_string()
+            } else {
+                let rewritten = renumber_link_references(body, &renumbered);
+                full.replacen(&format!(">{body}<"), &format!(">{rewritten}<"), 1)
+            }
+        })
+        .into_owned();
+
+    let mut removed_parts: Vec<String> = Vec::new();
+    for index in indices {
+        let part = &parts[*index as usize - 1].part;
+        removed_parts.push(part.clone());
+        edits.insert(part.clone(), None);
+        edits.insert(rels_path_of(part), None);
+    }
+
+    let workbook_rels_path = rels_path_of("xl/workbook.xml");
+    let mut workbook_rels = read_zip_text(&mut archive, &workbook_rels_path)?;
+    // The calc chain lists formula cells; stale entries make Excel repair the
+    // file, and it is rebuilt on the next calculation.
+    let drop_calc_chain =
+        outcome.formulas_converted > 0 && archive.by_name("xl/calcChain.xml").is_ok();
+    if drop_calc_chain {
+        removed_parts.push("xl/calcChain.xml".to_string());
+        edits.insert("xl/calcChain.xml".to_string(), None);
+    }
+    workbook_rels = relationship_tag_regex()
+        .replace_all(&workbook_rels, |caps: &Captures| {
+            let attrs = parse_attributes(&caps[0]);
+            let id = attrs.get("Id").map(String::as_str).unwrap_or_default();
+            let rel_type = attrs.get("Type").map(String::as_str).unwrap_or_default();
+            let removed = removed_rel_ids.contains(id)
+                || (drop_calc_chain && rel_type.ends_with("/calcChain"));
+            if removed {
+                String::new()
+            } else {
+                caps[0].to_string()
+            }
+        })
+        .into_owned();
+
+    let mut content_types = read_zip_text(&mut archive, "[Content_Types].xml")?;
+    content_types = Regex::new(r"<(?:\w+:)?Override\b[^>]*>")
+        .expect("valid Override regex")
+        .replace_all(&content_types, |caps: &Captures| {
+            let attrs = parse_attributes(&caps[0]);
+            let part_name = attrs
+                .get("PartName")
+                .map(|name| name.trim_start_matches('/'))
+                .unwrap_or_default();
+            if removed_parts.iter().any(|part| part == part_name) {
+                String::new()
+            } else {
+                caps[0].to_string()
+            }
+        })
+        .into_owned();
+    drop(archive);
+
+    edits.insert(
+        "xl/workbook.xml".to_string(),
+        Some(workbook_xml.into_bytes()),
+    );
+    edits.insert(workbook_rels_path, Some(workbook_rels.into_bytes()));
+    edits.insert(
+        "[Content_Types].xml".to_string(),
+        Some(content_types.into_bytes()),
+    );
+    rewrite_package(path, edits)?;
+    Ok(outcome)
+}
+
+/// Point the link at 1-based `index` to `target`, keeping its cached values.
+/// Formulas are unchanged since they refer to the link by position.
+pub fn repoint_external_link(path: &Path, index: u32, target: &str) -> Result<()> {
+    if target.trim().is_empty() {
+        bail!("repoint_external_link requires a non-empty target");
+    }
+    let mut archive = zip::ZipArchive::new(fs::File::open(path)?)?;
+    let parts = read_link_parts(&mut archive)?;
+    let part = index
+        .checked_sub(1)
+        .and_then(|position| parts.get(position as usize))
+        .ok_or_else(|| {
+            anyhow!(
+                "external link [{}] not found; the workbook has {} external link(s)",
+                index,
+                parts.len()
+            )
+        })?;
+
+    let rels_path = rels_path_of(&part.part);
+    let rels_xml = read_zip_text(&mut archive, &rels_path).map_err(|_| {
+        anyhow!(
+            "external link [{}] has no target to repoint ('{}' is missing)",
+            index,
+            rels_path
+        )
+    })?;
+    let escaped = escape_xml_attribute(target);
+    let mut replaced = false;
+    let rels_xml = relationship_tag_regex()
+        .replace_all(&rels_xml, |caps: &Captures| {
+            let tag = &caps[0];
+            let attrs = parse_attributes(tag);
+            if replaced || attrs.get("TargetMode").map(String::as_str) != Some("External") {
+                return tag.to_string();
+            }
+            replaced = true;
+            let target_re =
+                Regex::new(r#"\bTarget\s*=\s*"[^"]*""#).expect("valid Target attribute regex");
+            target_re
+                .replace(tag, format!(r#"Target="{escaped}""#).as_str())
+                .into_owned()
+        })
+        .into_owned();
+    if !replaced {
+        bail!(
+            "external link [{}] has no external target to repoint",
+            index
+        );
+    }
+
+    // Alternate URLs (relative and absolute forms of the old location) would
+    // otherwise win over the new target when Excel resolves the link.
+    let part_xml = read_zip_text(&mut archive, &part.part)?;
+    let part_xml =
+        Regex::new(r"(?s)<(?:\w+:)?alternateUrls\b[^>]*?(?:/>|>.*?</(?:\w+:)?alternateUrls>)")
+            .expect("valid alternateUrls regex")
+            .replace_all(&part_xml, "")
+            .into_owned();
+    drop(archive);
+
+    let mut edits = HashMap::new();
+    edits.insert(rels_path, Some(rels_xml.into_bytes()));
+    edits.insert(part.part.clone(), Some(part_xml.into_bytes()));
+    rewrite_package(path, edits)
+}
+
+struct LinkPart {
+    rel_id: String,
+    part: String,
+    target: Option<String>,
+    sheet_names: Vec<String>,
+    cached: Vec<CachedValue>,
+}
+
+struct CachedValue {
+    sheet: String,
+    cell: String,
+    value: String,
+    numeric: bool,
+}
+
+fn read_link_parts<R: Read + Seek>(archive: &mut zip::ZipArchive<R>) -> Result<Vec<LinkPart>> {
+    let workbook_xml = read_zip_text(archive, "xl/workbook.xml")?;
+    let workbook_rels = read_relationships(archive, "xl/workbook.xml");
+    let references: Vec<(String, Option<String>)> = external_reference_regex()
+        .find_iter(&workbook_xml)
+        .filter_map(|tag| {
+            let id = parse_attributes(tag.as_str()).get("r:id")?.clone();
+            let part = workbook_rels.get(&id).map(|rel| rel.target.clone());
+            Some((id, part))
+        })
+        .collect();
+
+    let mut parts = Vec::with_capacity(references.len());
+    for (rel_id, part) in references {
+        let part = part.unwrap_or_default();
+        let target = read_relationships(archive, &part)
+            .into_values()
+            .find(|rel| rel.external)
+            .map(|rel| rel.target);
+        let xml = read_zip_text(archive, &part).unwrap_or_default();
+        let (sheet_names, cached) = parse_link_part(&xml);
+        parts.push(LinkPart {
+            rel_id,
+            part,
+            target,
+            sheet_names,
+            cached,
+        });
+    }
+    Ok(parts)
+}
+
+/// Sheet names and cached cell values of an `externalLink` part.
+fn parse_link_part(xml: &str) -> (Vec<String>, Vec<CachedValue>) {
+    let sheet_name_re = Regex::new(r"<(?:\w+:)?sheetName\b[^>]*>").expect("valid sheetName regex");
+    let sheet_names: Vec<String> = sheet_name_re
+        .find_iter(xml)
+        .filter_map(|tag| {
+            parse_attributes(tag.as_str())
+                .get("val")
+                .map(|v| unescape_xml(v))
+        })
+        .collect();
+
+    let sheet_data_re =
+        Regex::new(r"(?s)<(?:\w+:)?sheetData\b([^>]*?)(?:/>|>(.*?)</(?:\w+:)?sheetData>)")
+            .expect("valid sheetData regex");
+    let cell_re = Regex::new(r"(?s)<(?:\w+:)?cell\b([^>]*?)(?:/>|>(.*?)</(?:\w+:)?cell>)")
+        .expect("valid cell regex");
+    let value_re = Regex::new(r"(?s)<(?:\w+:)?v>(.*?)</(?:\w+:)?v>").expect("valid value regex");
+
+    let mut cached = Vec::new();
+    for sheet_caps in sheet_data_re.captures_iter(xml) {
+        let attrs = parse_attributes(&sheet_caps[1]);
+        let Some(sheet) = attrs
+            .get("sheetId")
+            .and_then(|id| id.parse::<usize>().ok())
+            .and_then(|id| sheet_names.get(id))
+        else {
+            continue;
+        };
+        let body = sheet_caps.get(2).map(|m| m.as_str()).unwrap_or_default();
+        for cell_caps in cell_re.captures_iter(body) {
+            let attrs = parse_attributes(&cell_caps[1]);
+            let Some(cell) = attrs.get("r") else {
+                continue;
+            };
+            let Some(raw) = cell_caps
+                .get(2)
+                .and_then(|m| value_re.captures(m.as_str()))
+                .map(|caps| unescape_xml(&caps[1]))
+            else {
+                continue;
+            };
+            let kind = attrs.get("t").map(String::as_str).unwrap_or("n");
+            let value = match kind {
+                "b" if raw == "1" => "TRUE".to_string(),
+                "b" => "FALSE".to_string(),
+                _ => raw,
+            };
+            cached.push(CachedValue {
+                sheet: sheet.clone(),
+                cell: cell.clone(),
+                value,
+                numeric: kind == "n",
+            });
+        }
+    }
+    (sheet_names, cached)
+}
+
+fn check_staleness(link: &mut ExternalLink, cached: &[CachedValue], base_dir: &Path) {
+    let Some(target) = link.target.as_deref() else {
+        return;
+    };
+    let Some(candidates) = local_candidates(target, base_dir) else {
+        link.status = ExternalLinkStatus::Unresolvable;
+        return;
+    };
+    let Some(resolved) = candidates.into_iter().find(|path| path.is_file()) else {
+        link.status = ExternalLinkStatus::Missing;
+        return;
+    };
+    link.resolved_path = Some(resolved.display().to_string());
+
+    let is_xlsx = resolved
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| matches!(ext.to_ascii_lowercase().as_str(), "xlsx" | "xlsm"));
+    if cached.is_empty() || !is_xlsx {
+        link.status = ExternalLinkStatus::Unchecked;
+        return;
+    }
+    let Ok(book) = umya_spreadsheet::reader::xlsx::read(&resolved) else {
+        link.status = ExternalLinkStatus::Unchecked;
+        return;
+    };
+
+    for value in cached {
+        let current = book
+            .get_sheet_by_name(&value.sheet)
+            .map(|sheet| sheet.get_value(value.cell.as_str()));
+        let matches = current
+            .as_deref()
+            .is_some_and(|current| cached_value_matches(value, current));
+        if matches {
+            continue;
+        }
+        link.stale_cell_count += 1;
+        if link.stale_cells.len() < STALE_CELL_SAMPLE {
+            link.stale_cells.push(ExternalStaleCell {
+                sheet: value.sheet.clone(),
+                cell: value.cell.clone(),
+                cached: value.value.clone(),
+                current,
+            });
+        }
+    }
+    link.status = if link.stale_cell_count > 0 {
+        ExternalLinkStatus::Stale
+    } else {
+        ExternalLinkStatus::Current
+    };
+}
+
+fn cached_value_matches(cached: &CachedValue, current: &str) -> bool {
+    if cached.numeric
+        && let (Ok(a), Ok(b)) = (cached.value.parse::<f64>(), current.trim().parse::<f64>())
+    {
+        return (a - b).abs() <= 1e-9 * a.abs().max(b.abs()).max(1.0);
+    }
+    cached.value == current
+        || (matches!(cached.value.as_str(), "TRUE" | "FALSE")
+            && cached.value.eq_ignore_ascii_case(current.trim()))
+}
+
+/// Local paths a link target may resolve to, most specific first: the path
+/// as recorded, then the same file name next to the linking workbook (where
+/// Excel also looks). `None` for URLs.
+fn local_candidates(target: &str, base_dir: &Path) -> Option<Vec<PathBuf>> {
+    let target = target.trim();
+    let decoded = match target.strip_prefix("file:///") {
+        Some(rest) => rest.replace("%20", " "),
+        None if target.contains("://") => return None,
+        None => target.replace("%20", " "),
+    };
+    let normalized = decoded.replace('\\', "/");
+    let recorded = Path::new(&normalized);
+    let mut candidates = vec![if recorded.is_absolute() {
+        recorded.to_path_buf()
+    } else {
+        base_dir.join(recorded)
+    }];
+    if let Some(file_name) = normalized
+        .rsplit('/')
+        .next()
+        .filter(|name| !name.is_empty())
+    {
+        let sibling = base_dir.join(file_name);
+        if !candidates.contains(&sibling) {
+            candidates.push(sibling);
+        }
+    }
+    Some(candidates)
+}
+
+struct FormulaCell {
+    sheet: String,
+    cell: String,
+    /// Formula text as stored (XML-escaped); shared-formula children carry
+    /// their master's text.
+    formula: String,
+}
+
+fn scan_formula_cells<R: Read + Seek>(
+    archive: &mut zip::ZipArchive<R>,
+) -> Result<Vec<FormulaCell>> {
+    let mut cells = Vec::new();
+    for (sheet, part) in ordered_sheet_parts(archive)? {
+        let Ok(xml) = read_zip_text(archive, &part) else {
+            continue;
+        };
+        if !xml.contains('[') {
+            continue;
+        }
+        let shared = shared_formula_masters(&xml);
+        for caps in worksheet_cell_regex().captures_iter(&xml) {
+            let Some(body) = caps.get(3) else {
+                continue;
+            };
+            let Some(formula) = cell_formula(body.as_str(), &shared) else {
+                continue;
+            };
+            let Some(cell) = parse_attributes(&caps[2]).get("r").cloned() else {
+                continue;
+            };
+            cells.push(FormulaCell {
+                sheet: sheet.clone(),
+                cell,
+                formula,
+            });
+        }
+    }
+    Ok(cells)
+}
+
+/// Worksheet parts in workbook order.
+fn ordered_sheet_parts<R: Read + Seek>(
+    archive: &mut zip::ZipArchive<R>,
+) -> Result<Vec<(String, String)>> {
+    let mut parts = resolve_sheet_parts(archive)?;
+    let workbook_xml = read_zip_text(archive, "xl/workbook.xml")?;
+    let sheet_re = Regex::new(r"<(?:\w+:)?sheet\b[^>]*>").expect("valid sheet tag regex");
+    Ok(sheet_re
+        .find_iter(&workbook_xml)
+        .filter_map(|tag| {
+            let name = unescape_xml(parse_attributes(tag.as_str()).get("name")?);
+            let part = parts.remove(&name)?;
+            Some((name, part))
+        })
+        .collect())
+}
+
+/// Formula text of each shared-formula master, keyed by `si`.
+fn shared_formula_masters(xml: &str) -> HashMap<String, String> {
+    formula_regex()
+        .captures_iter(xml)
+        .filter_map(|caps| {
+            let attrs = parse_attributes(&caps[1]);
+            let text = caps.get(2)?.as_str();
+            if attrs.get("t").map(String::as_str) != Some("shared") || text.is_empty() {
+                return None;
+            }
+            Some((attrs.get("si")?.clone(), text.to_string()))
+        })
+        .collect()
+}
+
+fn cell_formula(body: &str, shared: &HashMap<String, String>) -> Option<String> {
+    let caps = formula_regex().captures(body)?;
+    let text = caps.get(2).map(|m| m.as_str()).unwrap_or_default();
+    if !text.is_empty() {
+        return Some(text.to_string());
+    }
+    let attrs = parse_attributes(&caps[1]);
+    shared.get(attrs.get("si")?).cloned()
+}
+
+/// Rewrite one worksheet for a break: cells whose formulas reference a
+/// removed link keep only their cached value, and references to the other
+/// links are renumbered. Returns the new XML and the cells converted.
+fn rewrite_sheet_links(xml: &str, renumbered: &HashMap<u32, Option<u32>>) -> (String, u32) {
+    let shared = shared_formula_masters(xml);
+    let value_re = Regex::new(r"(?s)<(?:\w+:)?v>(.*?)</(?:\w+:)?v>").expect("valid value regex");
+    let mut converted = 0;
+    let rewritten = worksheet_cell_regex()
+        .replace_all(xml, |caps: &Captures| {
+            let (prefix, attrs) = (&caps[1], &caps[2]);
+            let Some(body) = caps.get(3).map(|m| m.as_str()) else {
+                return caps[0].to_string();
+            };
+            let Some(formula) = cell_formula(body, &shared) else {
+                return caps[0].to_string();
+            };
+            let referenced = link_references(&formula);
+            if referenced.is_empty() {
+                return caps[0].to_string();
+            }
+            let breaks = referenced
+                .iter()
+                .any(|index| renumbered.get(index).is_some_and(Option::is_none));
+            if !breaks {
+                let body = formula_regex().replace(body, |f: &Captures| match f.get(2) {
+                    Some(text) if !text.as_str().is_empty() => f[0].replacen(
+                        &format!(">{}<", text.as_str()),
+                        &format!(">{}<", renumber_link_references(text.as_str(), renumbered)),
+                        1,
+                    ),
+                    _ => f[0].to_string(),
+                });
+                return format!("<{prefix}c{attrs}>{body}</{prefix}c>");
+            }
+
+            converted += 1;
+            let body = formula_regex().replace(body, "").into_owned();
+            if parse_attributes(attrs).get("t").map(String::as_str) != Some("str") {
+                return format!("<{prefix}c{attrs}>{body}</{prefix}c>");
+            }
+            // A string result without a formula is stored as an inline string.
+            let attrs = attrs.replacen(r#"t="str""#, r#"t="inlineStr""#, 1);
+            let body = value_re.replace(&body, r#"<is><t xml:space="preserve">$1</t></is>"#);
+            format!("<{prefix}c{attrs}>{body}</{prefix}c>")
+        })
+        .into_owned();
+    (rewritten, converted)
+}
+
+/// 1-based link indices referenced by a formula.
+fn link_references(formula: &str) -> BTreeSet<u32> {
+    link_reference_regex()
+        .captures_iter(formula)
+        .filter_map(|caps| caps[2].parse::<u32>().ok())
+        .filter(|index| *index > 0)
+        .collect()
+}
+
+fn renumber_link_references(formula: &str, renumbered: &HashMap<u32, Option<u32>>) -> String {
+    link_reference_regex()
+        .replace_all(formula, |caps: &Captures| {
+            match caps[2]
+                .parse::<u32>()
+                .ok()
+                .and_then(|index| renumbered.get(&index))
+            {
+                Some(Some(new_index)) => format!("{}[{}]{}", &caps[1], new_index, &caps[3]),
+                _ => caps[0].to_string(),
+            }
+        })
+        .into_owned()
+}
+
+/// `[n]` followed by an optional sheet name and `!`, not part of a
+/// structured reference or an identifier. The leading character is captured
+/// because the regex crate has no look-behind.
+fn link_reference_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(r"(^|[^A-Za-z0-9_.\[\]])\[(\d+)\]([^\[\]!(),]*!)")
+            .expect("valid link reference regex")
+    })
+}
+
+/// Captures the namespace prefix, the attributes and the body of a `<c>`.
+fn worksheet_cell_regex() -> Regex {
+    Regex::new(r"(?s)<((?:\w+:)?)c\b([^>]*?)(?:/>|>(.*?)</(?:\w+:)?c>)").expect("valid cell regex")
+}
+
+fn formula_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(r"(?s)<(?:\w+:)?f\b([^>]*?)(?:/>|>(.*?)</(?:\w+:)?f>)")
+            .expect("valid formula regex")
+    })
+}
+
+fn defined_name_regex() -> Regex {
+    Regex::new(r"(?s)<(?:\w+:)?definedName\b([^>]*)>(.*?)</(?:\w+:)?definedName>")
+        .expect("valid definedName regex")
+}
+
+fn external_reference_regex() -> Regex {
+    Regex::new(r"<(?:\w+:)?externalReference\b[^>]*>").expect("valid externalReference regex")
+}
+
+fn rels_path_of(part: &str) -> String {
+    let (dir, file) = part.rsplit_once('/').unwrap_or(("", part));
+    format!("{dir}/_rels/{file}.rels")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_and_renumbers_link_references_but_not_structured_references() {
+        let formula =
+            "SUM([1]Rates!B2:B4)+&apos;[3]Q1 Plan&apos;!C4*[3]!TaxRate+Table1[[#This Row],[2]]";
+        assert_eq!(link_references(formula), BTreeSet::from([1, 3]));
+
+        let renumbered = HashMap::from([(1, Some(1)), (2, None), (3, Some(2))]);
+        assert_eq!(
+            renumber_link_references(formula, &renumbered),
+            "SUM([1]Rates!B2:B4)+&apos;[2]Q1 Plan&apos;!C4*[2]!TaxRate+Table1[[#This Row],[2]]"
+        );
+        assert!(link_references("Data[2020]+[0]!Local").is_empty());
+    }
+}
@@ -0,0 +1,349 @@
+//! The `fill_series` transform op: linear, growth and date series, after
+//! Excel's Home > Fill > Series.
+//!
+//! Each line of the target (a column when filling down, a row when filling
+//! across) starts from a seed in its first cell, either `start` or the
+//! number already there. Later cells get the seed stepped by their distance
+//! from it, so long series carry no accumulated rounding, and month and year
+//! steps land on the seed's day of month, clamped to shorter months as
+//! EDATE does. A `stop` value ends each line early, leaving the cells past
+//! it untouched.
+
+use crate::metadata::annotation_bounds;
+use crate::utils::cell_address;
+use crate::workbook::{excel_serial_to_iso, is_date_formatted, iso_to_excel_serial};
+use anyhow::{Result, anyhow, bail};
+use chrono::{Datelike, Months, NaiveDate, Weekday};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use umya_spreadsheet::Worksheet;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SeriesType {
+    /// Add `step` to each value
+    #[default]
+    Linear,
+    /// Multiply each value by `step`
+    Growth,
+    /// Advance a date by `step` of `date_unit`
+    Date,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DateUnit {
+    #[default]
+    Day,
+    /// Monday to Friday only
+    Weekday,
+    Month,
+    Year,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SeriesDirection {
+    /// Fill each row of the target from its leftmost cell
+    Rows,
+    /// Fill each column of the target from its top cell
+    Columns,
+}
+
+/// A series start or stop: a number, or ISO date text (`2025-01-31`,
+/// optionally with a `T` or space and `HH:MM:SS`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum SeriesValue {
+    Number(f64),
+    Text(String),
+}
+
+impl SeriesValue {
+    /// The value as a number, with dates as serials; `is_date` is set for
+    /// date text.
+    fn resolve(&self, field: &str, use_1904_system: bool) -> Result<(f64, bool)> {
+        match self {
+            Self::Number(number) if number.is_finite() => Ok((*number, false)),
+            Self::Number(_) => bail!("fill_series {field} must be a finite number"),
+            Self::Text(text) => {
+                let text = text.trim();
+                if let Ok(number) = text.parse::<f64>()
+                    && number.is_finite()
+                {
+                    return Ok((number, false));
+                }
+                let iso = text.replacen(' ', "T", 1);
+                if iso.len() >= 10
+                    && let Some(serial) = iso_to_excel_serial(&iso, use_1904_system)
+                {
+                    return Ok((serial, true));
+                }
+                bail!("fill_series {field} '{text}' must be a number or an ISO date (YYYY-MM-DD)")
+            }
+        }
+    }
+}
+
+/// How one `fill_series` op is stepped.
+pub(crate) struct SeriesSpec<'a> {
+    pub(crate) range: &'a str,
+    pub(crate) start: Option<&'a SeriesValue>,
+    pub(crate) series_type: SeriesType,
+    pub(crate) step: f64,
+    pub(crate) date_unit: DateUnit,
+    pub(crate) stop: Option<&'a SeriesValue>,
+    pub(crate) series_in: Option<SeriesDirection>,
+    pub(crate) overwrite_formulas: bool,
+}
+
+/// What one `fill_series` op changed.
+#[derive(Debug, Default)]
+pub(crate) struct SeriesEffects {
+    /// Cells written, seeds from `start` included
+    pub(crate) cells_filled: u64,
+    pub(crate) cells_skipped_keep_formulas: u64,
+    pub(crate) bounds: Option<String>,
+    pub(crate) warnings: Vec<String>,
+}
+
+/// Check the parts of a `fill_series` op that do not depend on the workbook.
+pub(crate) fn validate_series(spec: &SeriesSpec) -> Result<()> {
+    annotation_bounds(spec.range).map_err(|err| anyhow!("fill_series range: {err}"))?;
+    if !spec.step.is_finite() {
+        bail!("fill_series step must be a finite number");
+    }
+    if spec.series_type == SeriesType::Date && spec.step.fract() != 0.0 {
+        bail!("fill_series step must be a whole number for date series");
+    }
+    if let Some(start) = spec.start {
+        start.resolve("start", false)?;
+    }
+    if let Some(stop) = spec.stop {
+        stop.resolve("stop", false)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn fill_series_in_sheet(
+    sheet: &mut Worksheet,
+    sheet_name: &str,
+    spec: &SeriesSpec,
+    use_1904_system: bool,
+) -> Result<SeriesEffects> {
+    validate_series(spec)?;
+    let ((min_col, min_row), (max_col, max_row)) = annotation_bounds(spec.range)?;
+    let direction = spec
+        .series_in
+        .unwrap_or(if max_col - min_col > max_row - min_row {
+            SeriesDirection::Rows
+        } else {
+            SeriesDirection::Columns
+        });
+    let start = spec
+        .start
+        .map(|start| start.resolve("start", use_1904_system))
+        .transpose()?;
+    let stop = spec
+        .stop
+        .map(|stop| {
+            stop.resolve("stop", use_1904_system)
+                .map(|(value, _)| value)
+        })
+        .transpose()?;
+
+    let lines: Vec<Vec<(u32, u32)>> = match direction {
+        SeriesDirection::Columns => (min_col..=max_col)
+            .map(|col| (min_row..=max_row).map(|row| (col, row)).collect())
+            .collect(),
+        SeriesDirection::Rows => (min_row..=max_row)
+            .map(|row| (min_col..=max_col).map(|col| (col, row)).collect())
+            .collect(),
+    };
+
+    let mut effects = SeriesEffects {
+        bounds: Some(format!(
+            "{}:{}",
+            cell_address(min_col, min_row),
+            cell_address(max_col, max_row)
+        )),
+        ..Default::default()
+    };
+    for line in lines {
+        let seed_coordinate = line[0];
+        let seed = match start {
+            Some((value, is_date)) => {
+                if write_series_cell(sheet, seed_coordinate, value, spec, &mut effects) {
+                    let cell = sheet.get_cell_mut(seed_coordinate);
+                    if is_date && !is_date_formatted(cell) {
+                        cell.get_style_mut()
+                            .get_number_format_mut()
+                            .set_format_code(if value.fract() == 0.0 {
+                                "yyyy-mm-dd"
+                            } else {
+                                "yyyy-mm-dd hh:mm:ss"
+                            });
+                    }
+                }
+                value
+            }
+            None => match sheet
+                .get_cell(seed_coordinate)
+                .and_then(|cell| cell.get_value().trim().parse::<f64>().ok())
+                .filter(|value| value.is_finite())
+            {
+                Some(value) => value,
+                None => {
+                    effects.warnings.push(format!(
+                        "WARN_FILL_SERIES_NO_SEED: {}!{} holds no number or date to start the series from; line skipped.",
+                        sheet_name,
+                        cell_address(seed_coordinate.0, seed_coordinate.1)
+                    ));
+                    continue;
+                }
+            },
+        };
+        let seed_format = sheet
+            .get_cell(seed_coordinate)
+            .and_then(|cell| cell.get_style().get_number_format())
+            .map(|format| format.get_format_code().to_string())
+            .filter(|code| code != "General");
+
+        let ascending = series_value(seed, 1, spec, use_1904_system)? >= seed;
+        for (index, coordinate) in line.iter().enumerate().skip(1) {
+            let value = series_value(seed, index as u32, spec, use_1904_system)?;
+            if let Some(stop) = stop
+                && (if ascending {
+                    value > stop
+                } else {
+                    value < stop
+                })
+            {
+                break;
+            }
+            if write_series_cell(sheet, *coordinate, value, spec, &mut effects)
+                && let Some(code) = &seed_format
+            {
+                sheet
+                    .get_cell_mut(*coordinate)
+                    .get_style_mut()
+                    .get_number_format_mut()
+                    .set_format_code(code.clone());
+            }
+        }
+    }
+    Ok(effects)
+}
+
+/// Write one number, keeping formulas unless `overwrite_formulas` is set.
+/// Returns whether the cell was written.
+fn write_series_cell(
+    sheet: &mut Worksheet,
+    coordinate: (u32, u32),
+    value: f64,
+    spec: &SeriesSpec,
+    effects: &mut SeriesEffects,
+) -> bool {
+    let cell = sheet.get_cell_mut(coordinate);
+    if cell.is_formula() {
+        if !spec.overwrite_formulas {
+            effects.cells_skipped_keep_formulas += 1;
+            return false;
+        }
+        cell.set_formula(String::new());
+    }
+    cell.set_value_number(value);
+    effects.cells_filled += 1;
+    true
+}
+
+/// The `index`th value of a series starting at `seed`.
+fn series_value(seed: f64, index: u32, spec: &SeriesSpec, use_1904_system: bool) -> Result<f64> {
+    let value = match spec.series_type {
+        SeriesType::Linear => seed + spec.step * f64::from(index),
+        SeriesType::Growth => seed * spec.step.powi(index as i32),
+        SeriesType::Date => {
+            let steps = spec.step as i64 * i64::from(index);
+            match spec.date_unit {
+                DateUnit::Day => seed + steps as f64,
+                DateUnit::Weekday => {
+                    shift_date(seed, use_1904_system, |date| add_weekdays(date, steps))?
+                }
+                DateUnit::Month => {
+                    shift_date(seed, use_1904_system, |date| add_months(date, steps))?
+                }
+                DateUnit::Year => {
+                    shift_date(seed, use_1904_system, |date| add_months(date, steps * 12))?
+                }
+            }
+        }
+    };
+    if !value.is_finite() {
+        bail!("fill_series overflowed at step {index} from {seed}");
+    }
+    // Excel keeps 15 significant digits, so 0.1 stepped three times is 0.3.
+    Ok(format!("{value:.14e}").parse().unwrap_or(value))
+}
+
+/// Move the date part of `serial` with `shift`, keeping its time of day.
+fn shift_date(
+    serial: f64,
+    use_1904_system: bool,
+    shift: impl FnOnce(NaiveDate) -> Option<NaiveDate>,
+) -> Result<f64> {
+    let days = serial.floor();
+    let out_of_range = || anyhow!("fill_series date {serial} is outside Excel's date range");
+    let date = NaiveDate::parse_from_str(&excel_serial_to_iso(days, use_1904_system), "%Y-%m-%d")
+        .map_err(|_| out_of_range())?;
+    let shifted = shift(date).ok_or_else(out_of_range)?;
+    let shifted_days =
+        iso_to_excel_serial(&shifted.format("%Y-%m-%d").to_string(), use_1904_system)
+            .ok_or_else(out_of_range)?;
+    Ok(shifted_days + (serial - days))
+}
+
+/// Month arithmetic as EDATE does it: the 31st plus one month is the last
+/// day of a shorter month.
+fn add_months(date: NaiveDate, months: i64) -> Option<NaiveDate> {
+    let count = Months::new(u32::try_from(months.unsigned_abs()).ok()?);
+    if months >= 0 {
+        date.checked_add_months(count)
+    } else {
+        date.checked_sub_months(count)
+    }
+}
+
+/// Step over Saturdays and Sundays, as a weekday series does.
+fn add_weekdays(mut date: NaiveDate, weekdays: i64) -> Option<NaiveDate> {
+    let is_weekend = |date: NaiveDate| matches!(date.weekday(), Weekday::Sat | Weekday::Sun);
+    let forward = weekdays >= 0;
+    let mut remaining = weekdays.unsigned_abs();
+    let step = |date: NaiveDate| {
+        let mut next = date;
+        loop {
+            next = if forward {
+                next.succ_opt()?
+            } else {
+                next.pred_opt()?
+            };
+            if !is_weekend(next) {
+                return Some(next);
+            }
+        }
+    };
+    // From a weekday, five weekdays are exactly one calendar week.
+    if remaining > 0 && is_weekend(date) {
+        date = step(date)?;
+        remaining -= 1;
+    }
+    let weeks = i64::try_from(remaining / 5).ok()?;
+    date = date.checked_add_signed(chrono::Duration::try_weeks(if forward {
+        weeks
+    } else {
+        -weeks
+    })?)?;
+    for _ in 0..remaining % 5 {
+        date = step(date)?;
+    }
+    Some(date)
+}
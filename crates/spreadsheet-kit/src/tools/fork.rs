@@ -1,18 +1,45 @@
-use super::param_enums::{BatchMode, FillDirection, FormulaRelativeMode, ReplaceMatchMode};
+use super::param_enums::{
+    BatchMode, FillDirection, FormulaRelativeMode, RenameMatchMode, ReplaceMatchMode,
+};
 use crate::config::RecalcBackendKind;
 use crate::fork::{ChangeSummary, EditOp, StagedChange, StagedOp};
 use crate::formula::pattern::{RelativeMode, parse_base_formula, shift_formula_ast};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::hooks::ChangedCellManifest;
 use crate::model::{
     AlignmentPatch, BordersPatch, CommandClass, FORMULA_PARSE_FAILED_PREFIX, FillPatch, FontPatch,
-    FormulaParseDiagnostics, FormulaParseDiagnosticsBuilder, FormulaParsePolicy, PatternFillPatch,
-    StylePatch, Warning, WorkbookId, validate_formula,
+    FormulaParseDiagnostics, FormulaParseDiagnosticsBuilder, FormulaParsePolicy,
+    NumberFormatLocalePatch, PatternFillPatch, SheetVisibility, StylePatch, Warning, WorkbookId,
+    validate_formula,
 };
 use crate::recalc::RecalcBackend;
+use crate::repository::{VirtualWorkbookInput, WorkbookSource};
 #[cfg(not(target_arch = "wasm32"))]
 use crate::security::sanitize_filename_component;
 use crate::state::AppState;
+use crate::tools::copy_sheet::{copy_sheet_into, resolve_copy_sheet_sources};
+use crate::tools::fill_series::{
+    DateUnit, SeriesDirection, SeriesSpec, SeriesType, SeriesValue, fill_series_in_sheet,
+    validate_series,
+};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::tools::fork_webhook::{ForkBaseline, changed_cells_between, send_fork_event};
+use crate::tools::import_rows::{
+    ImportRowsData, load_import_row_files, resolve_append_table_rows, resolve_import_rows,
+    table_matches, write_import_value,
+};
+use crate::tools::outline::SheetOutline;
+use crate::tools::patch_save::SaveOutcome;
+use crate::tools::range_transfer::{PasteMode, transfer_range, validate_transfer};
+use crate::tools::sort_rows::{
+    SortKey, sort_range_in_sheet, sort_table_in_sheet, validate_sort_keys,
+};
+use crate::tools::value_locale::ValueLocale;
 use crate::tools::write_normalize::{EditBatchParamsInput, normalize_edit_batch};
 use crate::utils::make_short_random_id;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::webhook::{WebhookEvent, WebhookRecalcSummary, webhook_configured};
+use crate::write::{PreservedPackage, save_workbook, save_workbook_preserving};
 use anyhow::{Result, anyhow, bail};
 use chrono::Utc;
 use formualizer_parse::tokenizer::Tokenizer;
@@ -59,7 +86,19 @@ pub async fn create_fork(
     let config = state.config();
     let workspace_root = &config.workspace_root;
 
-    let fork_id = registry.create_fork(base_path, workspace_root)?;
+    let resolved = state.resolve_workbook(&params.workbook_or_fork_id)?;
+    let fork_id = match &resolved.source {
+        WorkbookSource::Virtual(key) => {
+            let bytes = state.workbook_bytes(&resolved)?;
+            registry.create_fork_from_bytes(&bytes, key)?
+        }
+        WorkbookSource::Path(_) => registry.create_fork(base_path, workspace_root)?,
+        WorkbookSource::Remote(url) => {
+            return Err(anyhow!(
+                "remote workbook {url} is read-only; download it to fork it"
+            ));
+        }
+    };
 
     Ok(CreateForkResponse {
         fork_id,
@@ -180,7 +219,7 @@ pub async fn edit_batch(
                     is_formula: edit.is_formula,
                 })
                 .collect::<Vec<_>>();
-            crate::core::write::apply_edits_to_file(&work_path, &sheet_name, &core_edits)
+            crate::core::write::apply_edits_to_file(&work_path, &sheet_name, &core_edits, None)
         }
     })
     .await??;
@@ -220,6 +259,14 @@ fn default_replace_case_sensitive() -> bool {
     true
 }
 
+fn default_coerce_types() -> bool {
+    true
+}
+
+fn default_series_step() -> f64 {
+    1.0
+}
+
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct TransformBatchParams {
     pub fork_id: String,
@@ -229,6 +276,12 @@ pub struct TransformBatchParams {
     pub label: Option<String>,
     #[serde(default)]
     pub formula_parse_policy: Option<FormulaParsePolicy>,
+    /// Keep every package part except the edited sheets' cell data
+    /// byte-identical, for clean package diffs. Batches with table appends
+    /// or custom ops, and edits the original tables cannot express, fall back
+    /// to a full save with a warning. Not faster than a full save.
+    #[serde(default)]
+    pub differential_save: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -250,6 +303,23 @@ pub enum TransformOp {
         #[serde(default)]
         clear_formulas: bool,
     },
+    /// Remove values and formulas from the target and keep its formatting,
+    /// like Excel's Clear Contents.
+    ClearContents {
+        sheet_name: String,
+        target: TransformTarget,
+    },
+    /// Reset the target's cells to the default format and keep their values.
+    ClearFormats {
+        sheet_name: String,
+        target: TransformTarget,
+    },
+    /// Remove values, formulas and formatting from the target. Comments and
+    /// hyperlinks are kept.
+    ClearAll {
+        sheet_name: String,
+        target: TransformTarget,
+    },
     FillRange {
         sheet_name: String,
         target: TransformTarget,
@@ -258,6 +328,10 @@ pub enum TransformOp {
         is_formula: bool,
         #[serde(default = "default_overwrite_formulas")]
         overwrite_formulas: bool,
+        /// Read `value` as a number or date typed in this locale, e.g. `de-DE`
+        /// for `1.234,56` or `31.12.2024`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        locale: Option<String>,
     },
     ReplaceInRange {
         sheet_name: String,
@@ -278,6 +352,128 @@ pub enum TransformOp {
         #[serde(default = "default_overwrite_formulas")]
         overwrite_formulas: bool,
     },
+    /// Write many rows at once from inline arrays or objects, or from an
+    /// `@file.csv`/`@file.json` reference (`transform_batch` only; MCP paths
+    /// are relative to the workspace root). Headers of object rows (or of
+    /// the first row, with `has_header`) can be matched to the header row at
+    /// `anchor`; `append` writes below the last used row instead of at the
+    /// anchor. With `coerce_types`, numeric, `true`/`false` and ISO date text
+    /// becomes typed cells. Null values leave their cell untouched.
+    ImportRows {
+        sheet_name: String,
+        /// Top-left cell of the block; with `match_headers`, the first header
+        /// cell of the existing table
+        anchor: String,
+        rows: ImportRowsData,
+        /// The first row holds column names (implied for object rows)
+        #[serde(default)]
+        has_header: bool,
+        /// Place each column under the header with the same name in the row
+        /// at `anchor` (case-insensitive)
+        #[serde(default)]
+        match_headers: bool,
+        /// Write after the last non-empty row of the block instead of at
+        /// `anchor`; a header row is then not written again
+        #[serde(default)]
+        append: bool,
+        #[serde(default = "default_coerce_types")]
+        coerce_types: bool,
+        #[serde(default = "default_overwrite_formulas")]
+        overwrite_formulas: bool,
+    },
+    /// Append records to a named Excel table and grow its range. Rows are
+    /// arrays in table column order or objects keyed by column name, inline
+    /// or as an `@file` reference like `import_rows`. Calculated columns
+    /// left empty are filled by copying down the formula of the last data
+    /// row; a totals row moves down and its ranges over the data grow.
+    AppendTableRows {
+        sheet_name: String,
+        table_name: String,
+        rows: ImportRowsData,
+        /// The first row holds column names to match against the table's
+        /// (implied for object rows)
+        #[serde(default)]
+        has_header: bool,
+        #[serde(default = "default_coerce_types")]
+        coerce_types: bool,
+    },
+    /// Reorder the rows of `range` by one or more keys. Each row of the
+    /// range moves as a whole, values, formulas and styles alike, and moved
+    /// formulas are re-anchored to their new row as Excel does when it
+    /// sorts. With `has_header` the first row stays on top and keys may
+    /// name its headers. Previews report the resulting row order.
+    SortRange {
+        sheet_name: String,
+        range: String,
+        keys: Vec<SortKey>,
+        #[serde(default)]
+        has_header: bool,
+    },
+    /// Sort the data rows of a named Excel table like `sort_range`; the
+    /// header and any totals row stay put and keys may name table columns.
+    SortTable {
+        sheet_name: String,
+        table_name: String,
+        keys: Vec<SortKey>,
+    },
+    /// Copy `src_range` so its top-left cell lands on `dest_anchor`,
+    /// optionally on another sheet. Relative references in copied formulas
+    /// shift with the block and absolute ones stay, as in Excel; `paste`
+    /// picks values, formulas, formats or all three.
+    CopyRange {
+        sheet_name: String,
+        src_range: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        dest_sheet_name: Option<String>,
+        dest_anchor: String,
+        #[serde(default)]
+        paste: PasteMode,
+    },
+    /// Move `src_range` like Excel's cut and paste: moved formulas keep
+    /// pointing at the cells they referenced, and references anywhere in
+    /// the workbook to moved cells follow them. The source keeps whatever
+    /// `paste` does not carry.
+    MoveRange {
+        sheet_name: String,
+        src_range: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        dest_sheet_name: Option<String>,
+        dest_anchor: String,
+        #[serde(default)]
+        paste: PasteMode,
+    },
+    /// Fill `range` with a series like Excel's Fill > Series. Each column
+    /// (or each row, with `series_in: rows`) starts from `start`, or from the
+    /// number or date already in its first cell, and steps by `step`: added
+    /// for `linear`, multiplied for `growth`, and in `date_unit`s for `date`.
+    /// Filling stops at `stop`, when given. `series_in` defaults to rows for
+    /// ranges wider than they are tall and to columns otherwise.
+    FillSeries {
+        sheet_name: String,
+        range: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        start: Option<SeriesValue>,
+        #[serde(default)]
+        series_type: SeriesType,
+        #[serde(default = "default_series_step")]
+        step: f64,
+        #[serde(default)]
+        date_unit: DateUnit,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        stop: Option<SeriesValue>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        series_in: Option<SeriesDirection>,
+        #[serde(default = "default_overwrite_formulas")]
+        overwrite_formulas: bool,
+    },
+    /// Domain-specific op registered via
+    /// [`register_custom_transform_op`](super::custom_ops::register_custom_transform_op).
+    Custom {
+        name: String,
+        sheet_name: String,
+        #[serde(default)]
+        params: serde_json::Value,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -302,6 +498,8 @@ pub struct TransformBatchResponse {
 #[derive(Debug, Serialize, Deserialize)]
 struct TransformBatchStagedPayload {
     ops: Vec<TransformOp>,
+    #[serde(default)]
+    differential_save: bool,
 }
 
 pub(crate) fn resolve_transform_ops_for_workbook(
@@ -309,15 +507,97 @@ pub(crate) fn resolve_transform_ops_for_workbook(
     ops: &[TransformOp],
 ) -> Result<Vec<TransformOp>> {
     let mut resolved_ops = Vec::with_capacity(ops.len());
+    let mut imported_to = BTreeMap::new();
 
     for op in ops {
         match op {
             TransformOp::WriteMatrix { .. } => {
                 resolved_ops.push(op.clone());
             }
+            TransformOp::ImportRows {
+                sheet_name,
+                anchor,
+                rows,
+                has_header,
+                match_headers,
+                append,
+                coerce_types,
+                overwrite_formulas,
+            } => {
+                resolved_ops.push(resolve_import_rows(
+                    workbook,
+                    &mut imported_to,
+                    sheet_name,
+                    anchor,
+                    rows,
+                    *has_header,
+                    *match_headers,
+                    *append,
+                    *coerce_types,
+                    *overwrite_formulas,
+                )?);
+            }
+            TransformOp::AppendTableRows {
+                sheet_name,
+                table_name,
+                rows,
+                has_header,
+                coerce_types,
+            } => {
+                resolved_ops.push(resolve_append_table_rows(
+                    workbook,
+                    sheet_name,
+                    table_name,
+                    rows,
+                    *has_header,
+                    *coerce_types,
+                )?);
+            }
+            TransformOp::SortRange { keys, .. } => {
+                validate_sort_keys("sort_range", keys)?;
+                resolved_ops.push(op.clone());
+            }
+            TransformOp::SortTable { keys, .. } => {
+                validate_sort_keys("sort_table", keys)?;
+                resolved_ops.push(op.clone());
+            }
+            TransformOp::CopyRange {
+                src_range,
+                dest_anchor,
+                ..
+            } => {
+                validate_transfer("copy_range", src_range, dest_anchor)?;
+                resolved_ops.push(op.clone());
+            }
+            TransformOp::MoveRange {
+                src_range,
+                dest_anchor,
+                ..
+            } => {
+                validate_transfer("move_range", src_range, dest_anchor)?;
+                resolved_ops.push(op.clone());
+            }
+            TransformOp::FillSeries { .. } => {
+                validate_series(&series_spec(op).expect("fill_series op"))?;
+                resolved_ops.push(op.clone());
+            }
+            TransformOp::Custom {
+                name,
+                sheet_name,
+                params,
+            } => {
+                let custom = super::custom_ops::lookup_custom_transform_op(name)?;
+                custom.validate(sheet_name, params).map_err(|err| {
+                    anyhow!("custom transform op '{}' rejected params: {}", name, err)
+                })?;
+                resolved_ops.push(op.clone());
+            }
             TransformOp::ClearRange {
                 sheet_name, target, ..
             }
+            | TransformOp::ClearContents { sheet_name, target }
+            | TransformOp::ClearFormats { sheet_name, target }
+            | TransformOp::ClearAll { sheet_name, target }
             | TransformOp::FillRange {
                 sheet_name, target, ..
             }
@@ -357,19 +637,42 @@ pub(crate) fn resolve_transform_ops_for_workbook(
                             clear_formulas: *clear_formulas,
                         });
                     }
+                    TransformOp::ClearContents { sheet_name, .. } => {
+                        resolved_ops.push(TransformOp::ClearContents {
+                            sheet_name: sheet_name.clone(),
+                            target: resolved_target,
+                        });
+                    }
+                    TransformOp::ClearFormats { sheet_name, .. } => {
+                        resolved_ops.push(TransformOp::ClearFormats {
+                            sheet_name: sheet_name.clone(),
+                            target: resolved_target,
+                        });
+                    }
+                    TransformOp::ClearAll { sheet_name, .. } => {
+                        resolved_ops.push(TransformOp::ClearAll {
+                            sheet_name: sheet_name.clone(),
+                            target: resolved_target,
+                        });
+                    }
                     TransformOp::FillRange {
                         sheet_name,
                         value,
                         is_formula,
                         overwrite_formulas,
+                        locale,
                         ..
                     } => {
+                        if let Some(tag) = locale {
+                            ValueLocale::parse(tag)?;
+                        }
                         resolved_ops.push(TransformOp::FillRange {
                             sheet_name: sheet_name.clone(),
                             target: resolved_target,
                             value: value.clone(),
                             is_formula: *is_formula,
                             overwrite_formulas: *overwrite_formulas,
+                            locale: locale.clone(),
                         });
                     }
                     TransformOp::ReplaceInRange {
@@ -391,7 +694,17 @@ pub(crate) fn resolve_transform_ops_for_workbook(
                             include_formulas: *include_formulas,
                         });
                     }
-                    TransformOp::WriteMatrix { .. } => unreachable!(),
+                    TransformOp::WriteMatrix { .. }
+                    | TransformOp::ImportRows { .. }
+                    | TransformOp::AppendTableRows { .. }
+                    | TransformOp::SortRange { .. }
+                    | TransformOp::SortTable { .. }
+                    | TransformOp::CopyRange { .. }
+                    | TransformOp::MoveRange { .. }
+                    | TransformOp::FillSeries { .. }
+                    | TransformOp::Custom { .. } => {
+                        unreachable!()
+                    }
                 }
             }
         }
@@ -402,8 +715,10 @@ pub(crate) fn resolve_transform_ops_for_workbook(
 
 pub async fn transform_batch(
     state: Arc<AppState>,
-    params: TransformBatchParams,
+    mut params: TransformBatchParams,
 ) -> Result<TransformBatchResponse> {
+    load_import_row_files(&mut params.ops, &state.config().workspace_root, true)?;
+
     let registry = state
         .fork_registry()
         .ok_or_else(|| anyhow!("fork registry not available"))?;
@@ -517,17 +832,17 @@ pub async fn transform_batch(
     };
 
     let mode = params.mode.unwrap_or_default();
+    let differential_save = params.differential_save.unwrap_or(false);
 
     if mode.is_preview() {
         let change_id = make_short_random_id("chg", 12);
-        let snapshot_path = stage_snapshot_path(&params.fork_id, &change_id);
-        fs::create_dir_all(snapshot_path.parent().unwrap())?;
-        fs::copy(&work_path, &snapshot_path)?;
+        let snapshot = registry.stage_snapshot(&params.fork_id, &change_id, &work_path)?;
+        let snapshot_path = snapshot.to_path_buf();
 
         let snapshot_for_apply = snapshot_path.clone();
         let apply_result = tokio::task::spawn_blocking({
             let ops = ops_to_apply.clone();
-            move || apply_transform_ops_to_file(&snapshot_for_apply, &ops)
+            move || apply_transform_ops_to_file(&snapshot_for_apply, &ops, differential_save)
         })
         .await??;
 
@@ -539,6 +854,7 @@ pub async fn transform_batch(
             kind: "transform_batch".to_string(),
             payload: serde_json::to_value(TransformBatchStagedPayload {
                 ops: ops_to_apply.clone(),
+                differential_save,
             })?,
         };
 
@@ -548,7 +864,7 @@ pub async fn transform_batch(
             label: params.label.clone(),
             ops: vec![staged_op],
             summary: summary.clone(),
-            fork_path_snapshot: Some(snapshot_path),
+            fork_path_snapshot: Some(snapshot.keep()?),
         };
 
         registry.add_staged_change(&params.fork_id, staged)?;
@@ -565,7 +881,7 @@ pub async fn transform_batch(
         let apply_result = tokio::task::spawn_blocking({
             let ops = ops_to_apply.clone();
             let work_path = work_path.clone();
-            move || apply_transform_ops_to_file(&work_path, &ops)
+            move || apply_transform_ops_to_file(&work_path, &ops, differential_save)
         })
         .await??;
 
@@ -708,6 +1024,8 @@ struct StylePatchInput {
     pub alignment: Option<Option<AlignmentPatch>>,
     #[serde(default)]
     pub number_format: Option<Option<NumberFormatPatchInput>>,
+    #[serde(default)]
+    pub number_format_locale: Option<NumberFormatLocalePatch>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -758,6 +1076,8 @@ struct FillColorPatch {
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct StyleOp {
+    /// Sheet to style; not needed for `workbook` targets
+    #[serde(default)]
     pub sheet_name: String,
     pub target: StyleTarget,
     pub patch: StylePatch,
@@ -768,9 +1088,19 @@ pub struct StyleOp {
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "kind", rename_all = "snake_case")]
 pub enum StyleTarget {
-    Range { range: String },
-    Region { region_id: u32 },
-    Cells { cells: Vec<String> },
+    Range {
+        range: String,
+    },
+    Region {
+        region_id: u32,
+    },
+    Cells {
+        cells: Vec<String>,
+    },
+    /// Every stored cell of the sheet
+    Sheet,
+    /// Every stored cell of every sheet
+    Workbook,
 }
 
 #[derive(Debug, Serialize, JsonSchema)]
@@ -809,6 +1139,18 @@ pub enum ColumnSizeSpec {
     Width {
         width_chars: f64,
     },
+    /// Measure displayed cell text (after number formats), scaled by font size
+    /// and weight, and size each column to its widest cell. Columns without
+    /// content keep their current width.
+    Autofit {
+        #[serde(default)]
+        min_width_chars: Option<f64>,
+        #[serde(default)]
+        max_width_chars: Option<f64>,
+        /// Extra characters added to the measured width (default 1.0).
+        #[serde(default)]
+        padding_chars: Option<f64>,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -928,9 +1270,8 @@ pub async fn column_size_batch(
 
     if mode.is_preview() {
         let change_id = make_short_random_id("chg", 12);
-        let snapshot_path = stage_snapshot_path(&params.fork_id, &change_id);
-        fs::create_dir_all(snapshot_path.parent().unwrap())?;
-        fs::copy(&work_path, &snapshot_path)?;
+        let snapshot = registry.stage_snapshot(&params.fork_id, &change_id, &work_path)?;
+        let snapshot_path = snapshot.to_path_buf();
 
         let snapshot_path_for_apply = snapshot_path.clone();
         let apply_result = tokio::task::spawn_blocking({
@@ -959,7 +1300,7 @@ pub async fn column_size_batch(
             label: params.label.clone(),
             ops: vec![staged_op],
             summary: summary.clone(),
-            fork_path_snapshot: Some(snapshot_path),
+            fork_path_snapshot: Some(snapshot.keep()?),
         };
 
         registry.add_staged_change(&params.fork_id, staged)?;
@@ -999,77 +1340,314 @@ pub async fn column_size_batch(
     }
 }
 
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct RowSizeBatchParamsInput {
+    pub fork_id: String,
+    pub sheet_name: String,
+    pub ops: Vec<RowSizeOpInput>,
+    pub mode: Option<BatchMode>, // preview|apply (default apply)
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RowTarget {
+    /// Row span such as `"2:10"` or a single row `"5"`.
+    Rows { range: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RowSizeSpec {
+    /// Estimate height from cell text, font size, and wrap settings.
+    Auto {
+        #[serde(default)]
+        min_height_points: Option<f64>,
+        #[serde(default)]
+        max_height_points: Option<f64>,
+    },
+    Height {
+        height_points: f64,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RowSizeOp {
+    pub target: RowTarget,
+    pub size: RowSizeSpec,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum RowSizeOpInput {
+    Canonical(RowSizeOp),
+    Shorthand { range: String, size: RowSizeSpec },
+}
+
+impl From<RowSizeOp> for RowSizeOpInput {
+    fn from(value: RowSizeOp) -> Self {
+        Self::Canonical(value)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct RowSizeBatchParams {
+    fork_id: String,
+    sheet_name: String,
+    ops: Vec<RowSizeOp>,
+    mode: Option<BatchMode>,
+    label: Option<String>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct RowSizeBatchResponse {
+    pub fork_id: String,
+    pub sheet_name: String,
+    pub mode: String,
+    pub change_id: Option<String>,
+    pub ops_applied: usize,
+    pub summary: ChangeSummary,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
-struct StyleBatchStagedPayload {
-    ops: Vec<StyleOp>,
+struct RowSizeBatchStagedPayload {
+    sheet_name: String,
+    ops: Vec<RowSizeOp>,
 }
 
-pub fn normalize_style_batch(
-    input: StyleBatchParamsInput,
-) -> Result<(StyleBatchParams, Vec<Warning>)> {
+fn normalize_row_size_batch(
+    params: RowSizeBatchParamsInput,
+) -> Result<(RowSizeBatchParams, Vec<crate::model::Warning>)> {
     let mut warnings = Vec::new();
-    let mut ops = Vec::with_capacity(input.ops.len());
+    let mut ops = Vec::with_capacity(params.ops.len());
 
-    for op_input in input.ops {
-        if op_input.shorthand_used {
-            warnings.push(Warning {
-                code: "WARN_STYLE_SHORTHAND".to_string(),
-                message: "Normalized style op shorthand to canonical form".to_string(),
-            });
-        }
-        if op_input.fill_color_used {
-            warnings.push(Warning {
-                code: "WARN_FILL_COLOR".to_string(),
-                message: "Normalized fill color shorthand to pattern fill".to_string(),
-            });
+    for entry in params.ops {
+        match entry {
+            RowSizeOpInput::Canonical(op) => ops.push(op),
+            RowSizeOpInput::Shorthand { range, size } => {
+                warnings.push(crate::model::Warning {
+                    code: "WARN_ROW_SHORTHAND_TARGET".to_string(),
+                    message: "Used range shorthand; prefer target:{kind:'rows',range:'2:10'}"
+                        .to_string(),
+                });
+                ops.push(RowSizeOp {
+                    target: RowTarget::Rows { range },
+                    size,
+                });
+            }
         }
-        if op_input.color_alpha_defaulted {
-            warnings.push(Warning {
-                code: "WARN_COLOR_ALPHA_DEFAULT".to_string(),
-                message: "Normalized RGB hex to ARGB with default alpha".to_string(),
-            });
+    }
+
+    for op in &ops {
+        match &op.size {
+            RowSizeSpec::Height { height_points } => validate_row_height(*height_points)?,
+            RowSizeSpec::Auto {
+                min_height_points,
+                max_height_points,
+            } => {
+                for bound in [min_height_points, max_height_points].into_iter().flatten() {
+                    validate_row_height(*bound)?;
+                }
+                if let (Some(min), Some(max)) = (min_height_points, max_height_points)
+                    && min > max
+                {
+                    bail!("min_height_points ({min}) must not exceed max_height_points ({max})");
+                }
+            }
         }
-        ops.push(op_input.op);
     }
 
     Ok((
-        StyleBatchParams {
-            fork_id: input.fork_id,
+        RowSizeBatchParams {
+            fork_id: params.fork_id,
+            sheet_name: params.sheet_name,
             ops,
-            mode: input.mode,
-            label: input.label,
+            mode: params.mode,
+            label: params.label,
         },
         warnings,
     ))
 }
 
-fn normalize_style_patch_input(input: StylePatchInput) -> (StylePatch, bool, bool) {
-    let mut fill_color_used = false;
-    let mut color_alpha_defaulted = false;
-    let fill = match input.fill {
-        None => None,
-        Some(None) => Some(None),
-        Some(Some(fill_input)) => {
-            let normalized = match fill_input {
-                FillPatchInput::Canonical(fill) => fill,
-                FillPatchInput::Color(color) => {
-                    fill_color_used = true;
-                    FillPatch::Pattern(PatternFillPatch {
-                        pattern_type: Some(Some("solid".to_string())),
-                        foreground_color: Some(Some(color.color)),
-                        background_color: None,
-                    })
-                }
-            };
-            Some(Some(normalized))
-        }
-    };
-
-    let number_format: Option<Option<String>> = match input.number_format {
-        None => None,
-        Some(None) => Some(None),
-        Some(Some(nf)) => match nf {
-            NumberFormatPatchInput::FormatCode(code) => Some(Some(code)),
+pub(crate) fn normalize_row_size_payload(
+    sheet_name: String,
+    ops: Vec<RowSizeOpInput>,
+) -> Result<(Vec<RowSizeOp>, Vec<Warning>)> {
+    let (params, warnings) = normalize_row_size_batch(RowSizeBatchParamsInput {
+        fork_id: String::new(),
+        sheet_name,
+        ops,
+        mode: None,
+        label: None,
+    })?;
+    Ok((params.ops, warnings))
+}
+
+pub async fn row_size_batch(
+    state: Arc<AppState>,
+    params: RowSizeBatchParamsInput,
+) -> Result<RowSizeBatchResponse> {
+    let (params, warnings) = normalize_row_size_batch(params)?;
+    let warning_messages: Vec<String> = warnings
+        .into_iter()
+        .map(|warning| format!("{}: {}", warning.code, warning.message))
+        .collect();
+    let registry = state
+        .fork_registry()
+        .ok_or_else(|| anyhow!("fork registry not available"))?;
+
+    let fork_ctx = registry.get_fork(&params.fork_id)?;
+    let work_path = fork_ctx.work_path.clone();
+
+    let fork_workbook_id = WorkbookId(params.fork_id.clone());
+    let workbook = state.open_workbook(&fork_workbook_id).await?;
+    let _ = workbook.with_sheet(&params.sheet_name, |_| Ok::<_, anyhow::Error>(()))?;
+
+    let mode = params.mode.unwrap_or_default();
+
+    if mode.is_preview() {
+        let change_id = make_short_random_id("chg", 12);
+        let snapshot = registry.stage_snapshot(&params.fork_id, &change_id, &work_path)?;
+        let snapshot_path = snapshot.to_path_buf();
+
+        let snapshot_path_for_apply = snapshot_path.clone();
+        let apply_result = tokio::task::spawn_blocking({
+            let ops = params.ops.clone();
+            let sheet_name = params.sheet_name.clone();
+            move || apply_row_size_ops_to_file(&snapshot_path_for_apply, &sheet_name, &ops)
+        })
+        .await??;
+
+        let mut summary = apply_result.summary;
+        summary.op_kinds = vec!["row_size_batch".to_string()];
+        summary.warnings.extend(warning_messages.clone());
+        set_recalc_needed_flag(&mut summary, fork_ctx.recalc_needed);
+
+        let staged_op = StagedOp {
+            kind: "row_size_batch".to_string(),
+            payload: serde_json::to_value(RowSizeBatchStagedPayload {
+                sheet_name: params.sheet_name.clone(),
+                ops: params.ops.clone(),
+            })?,
+        };
+
+        let staged = StagedChange {
+            change_id: change_id.clone(),
+            created_at: Utc::now(),
+            label: params.label.clone(),
+            ops: vec![staged_op],
+            summary: summary.clone(),
+            fork_path_snapshot: Some(snapshot.keep()?),
+        };
+
+        registry.add_staged_change(&params.fork_id, staged)?;
+
+        Ok(RowSizeBatchResponse {
+            fork_id: params.fork_id,
+            sheet_name: params.sheet_name,
+            mode: mode.as_str().to_string(),
+            change_id: Some(change_id),
+            ops_applied: apply_result.ops_applied,
+            summary,
+        })
+    } else {
+        let apply_result = tokio::task::spawn_blocking({
+            let ops = params.ops.clone();
+            let sheet_name = params.sheet_name.clone();
+            let work_path = work_path.clone();
+            move || apply_row_size_ops_to_file(&work_path, &sheet_name, &ops)
+        })
+        .await??;
+
+        let mut summary = apply_result.summary;
+        summary.op_kinds = vec!["row_size_batch".to_string()];
+        summary.warnings.extend(warning_messages);
+        set_recalc_needed_flag(&mut summary, fork_ctx.recalc_needed);
+
+        let _ = state.close_workbook(&fork_workbook_id);
+
+        Ok(RowSizeBatchResponse {
+            fork_id: params.fork_id,
+            sheet_name: params.sheet_name,
+            mode: mode.as_str().to_string(),
+            change_id: None,
+            ops_applied: apply_result.ops_applied,
+            summary,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StyleBatchStagedPayload {
+    ops: Vec<StyleOp>,
+}
+
+pub fn normalize_style_batch(
+    input: StyleBatchParamsInput,
+) -> Result<(StyleBatchParams, Vec<Warning>)> {
+    let mut warnings = Vec::new();
+    let mut ops = Vec::with_capacity(input.ops.len());
+
+    for op_input in input.ops {
+        if op_input.shorthand_used {
+            warnings.push(Warning {
+                code: "WARN_STYLE_SHORTHAND".to_string(),
+                message: "Normalized style op shorthand to canonical form".to_string(),
+            });
+        }
+        if op_input.fill_color_used {
+            warnings.push(Warning {
+                code: "WARN_FILL_COLOR".to_string(),
+                message: "Normalized fill color shorthand to pattern fill".to_string(),
+            });
+        }
+        if op_input.color_alpha_defaulted {
+            warnings.push(Warning {
+                code: "WARN_COLOR_ALPHA_DEFAULT".to_string(),
+                message: "Normalized RGB hex to ARGB with default alpha".to_string(),
+            });
+        }
+        ops.push(op_input.op);
+    }
+
+    Ok((
+        StyleBatchParams {
+            fork_id: input.fork_id,
+            ops,
+            mode: input.mode,
+            label: input.label,
+        },
+        warnings,
+    ))
+}
+
+fn normalize_style_patch_input(input: StylePatchInput) -> (StylePatch, bool, bool) {
+    let mut fill_color_used = false;
+    let mut color_alpha_defaulted = false;
+    let fill = match input.fill {
+        None => None,
+        Some(None) => Some(None),
+        Some(Some(fill_input)) => {
+            let normalized = match fill_input {
+                FillPatchInput::Canonical(fill) => fill,
+                FillPatchInput::Color(color) => {
+                    fill_color_used = true;
+                    FillPatch::Pattern(PatternFillPatch {
+                        pattern_type: Some(Some("solid".to_string())),
+                        foreground_color: Some(Some(color.color)),
+                        background_color: None,
+                    })
+                }
+            };
+            Some(Some(normalized))
+        }
+    };
+
+    let number_format: Option<Option<String>> = match input.number_format {
+        None => None,
+        Some(None) => Some(None),
+        Some(Some(nf)) => match nf {
+            NumberFormatPatchInput::FormatCode(code) => Some(Some(code)),
             NumberFormatPatchInput::Shorthand(sh) => {
                 if let Some(code) = sh.format_code {
                     Some(Some(code))
@@ -1088,6 +1666,7 @@ fn normalize_style_patch_input(input: StylePatchInput) -> (StylePatch, bool, boo
         borders: input.borders,
         alignment: input.alignment,
         number_format,
+        number_format_locale: input.number_format_locale,
     };
     normalize_style_patch_colors(&mut patch, &mut color_alpha_defaulted);
 
@@ -1209,9 +1788,8 @@ pub async fn style_batch(
 
     if mode.is_preview() {
         let change_id = make_short_random_id("chg", 12);
-        let snapshot_path = stage_snapshot_path(&params.fork_id, &change_id);
-        fs::create_dir_all(snapshot_path.parent().unwrap())?;
-        fs::copy(&work_path, &snapshot_path)?;
+        let snapshot = registry.stage_snapshot(&params.fork_id, &change_id, &work_path)?;
+        let snapshot_path = snapshot.to_path_buf();
 
         let snapshot_path_for_apply = snapshot_path.clone();
         let apply_result = tokio::task::spawn_blocking({
@@ -1238,7 +1816,7 @@ pub async fn style_batch(
             label: params.label.clone(),
             ops: vec![staged_op],
             summary: summary.clone(),
-            fork_path_snapshot: Some(snapshot_path),
+            fork_path_snapshot: Some(snapshot.keep()?),
         };
 
         registry.add_staged_change(&params.fork_id, staged)?;
@@ -1350,9 +1928,8 @@ pub async fn apply_formula_pattern(
 
     if mode.is_preview() {
         let change_id = make_short_random_id("chg", 12);
-        let snapshot_path = stage_snapshot_path(&params.fork_id, &change_id);
-        fs::create_dir_all(snapshot_path.parent().unwrap())?;
-        fs::copy(&work_path, &snapshot_path)?;
+        let snapshot = registry.stage_snapshot(&params.fork_id, &change_id, &work_path)?;
+        let snapshot_path = snapshot.to_path_buf();
 
         let sheet_name = params.sheet_name.clone();
         let target_range = params.target_range.clone();
@@ -1400,7 +1977,7 @@ pub async fn apply_formula_pattern(
             label: params.label.clone(),
             ops: vec![staged_op],
             summary: summary.clone(),
-            fork_path_snapshot: Some(snapshot_path),
+            fork_path_snapshot: Some(snapshot.keep()?),
         };
 
         registry.add_staged_change(&params.fork_id, staged)?;
@@ -1494,7 +2071,7 @@ fn apply_formula_pattern_to_file(
         }
     }
 
-    umya_spreadsheet::writer::xlsx::write(&book, path)?;
+    save_workbook(&book, path)?;
 
     let mut counts = BTreeMap::new();
     counts.insert("cells_filled".to_string(), cells_filled);
@@ -1815,6 +2392,22 @@ pub enum StructureOp {
         old_name: String,
         new_name: String,
     },
+    /// Rename every sheet whose name matches `find`, rewriting formula and
+    /// defined-name references to each renamed sheet.
+    RenameSheets {
+        find: String,
+        replace: String,
+        #[serde(default)]
+        match_mode: RenameMatchMode,
+    },
+    /// Rename every defined name (at any scope) matching `find`, rewriting
+    /// formula references to each renamed name.
+    RenameNames {
+        find: String,
+        replace: String,
+        #[serde(default)]
+        match_mode: RenameMatchMode,
+    },
     CreateSheet {
         name: String,
         #[serde(default)]
@@ -1823,6 +2416,42 @@ pub enum StructureOp {
     DeleteSheet {
         name: String,
     },
+    /// Copy `source_sheet` of the workbook at `source_file` into this one
+    /// with its styles, merges, conditional formats, validations and tables.
+    /// References to the sheet itself follow it to `new_name`.
+    CopySheet {
+        /// Path to the source workbook (may be the workbook being edited)
+        source_file: String,
+        source_sheet: String,
+        /// Name for the copy; defaults to `source_sheet`
+        #[serde(default)]
+        new_name: Option<String>,
+        /// 0-based tab position; appended when omitted
+        #[serde(default)]
+        position: Option<u32>,
+    },
+    /// Set the sheet's tab color (`#RGB`, `#RRGGBB` or `#AARRGGBB`); omit
+    /// `color` to remove it.
+    SetTabColor {
+        sheet_name: String,
+        #[serde(default)]
+        color: Option<String>,
+    },
+    /// Hide the sheet; `very_hidden` also hides it from Excel's Unhide
+    /// dialog. At least one sheet must stay visible.
+    HideSheet {
+        sheet_name: String,
+        #[serde(default)]
+        very_hidden: bool,
+    },
+    UnhideSheet {
+        sheet_name: String,
+    },
+    /// Move the listed sheets to the front of the tab bar in this order;
+    /// unlisted sheets keep their relative order after them.
+    ReorderSheets {
+        order: Vec<String>,
+    },
     CopyRange {
         sheet_name: String,
         #[serde(default)]
@@ -1841,6 +2470,18 @@ pub enum StructureOp {
         include_styles: bool,
         include_formulas: bool,
     },
+    /// Remove external link `[link]` (1-based, as listed by
+    /// `list-external-links`), replacing formulas that use it with their
+    /// cached values. Other links are renumbered.
+    BreakExternalLink {
+        link: u32,
+    },
+    /// Point external link `[link]` at another workbook; formulas keep
+    /// referring to it by position.
+    RepointExternalLink {
+        link: u32,
+        target: String,
+    },
 }
 
 fn structure_ops_require_recalc(ops: &[StructureOp]) -> bool {
@@ -1852,7 +2493,10 @@ fn structure_ops_require_recalc(ops: &[StructureOp]) -> bool {
                 | StructureOp::InsertCols { .. }
                 | StructureOp::DeleteCols { .. }
                 | StructureOp::RenameSheet { .. }
+                | StructureOp::RenameSheets { .. }
+                | StructureOp::RenameNames { .. }
                 | StructureOp::CloneRow { .. }
+                | StructureOp::CopySheet { .. }
                 | StructureOp::CopyRange {
                     include_formulas: true,
                     ..
@@ -1895,7 +2539,8 @@ pub async fn structure_batch(
 ) -> Result<StructureBatchResponse> {
     let want_impact = params.impact_report.unwrap_or(false);
     let want_delta = params.show_formula_delta.unwrap_or(false);
-    let (params, warnings) = normalize_structure_batch(params)?;
+    let (mut params, warnings) = normalize_structure_batch(params)?;
+    resolve_copy_sheet_sources(&mut params.ops, &state.config().workspace_root, true)?;
     let policy =
         params
             .formula_parse_policy
@@ -1919,9 +2564,8 @@ pub async fn structure_batch(
 
     if mode.is_preview() {
         let change_id = make_short_random_id("chg", 12);
-        let snapshot_path = stage_snapshot_path(&params.fork_id, &change_id);
-        fs::create_dir_all(snapshot_path.parent().unwrap())?;
-        fs::copy(&work_path, &snapshot_path)?;
+        let snapshot = registry.stage_snapshot(&params.fork_id, &change_id, &work_path)?;
+        let snapshot_path = snapshot.to_path_buf();
 
         let snapshot_for_apply = snapshot_path.clone();
         let ops_for_apply = params.ops.clone();
@@ -1971,7 +2615,7 @@ pub async fn structure_batch(
             label: params.label.clone(),
             ops: vec![staged_op],
             summary: summary.clone(),
-            fork_path_snapshot: Some(snapshot_path),
+            fork_path_snapshot: Some(snapshot.keep()?),
         };
 
         registry.add_staged_change(&params.fork_id, staged)?;
@@ -2044,14 +2688,54 @@ pub(crate) struct StructureApplyResult {
     pub(crate) formula_parse_diagnostics: Option<FormulaParseDiagnostics>,
 }
 
+/// Apply the batch's `repoint_external_link` ops, then its
+/// `break_external_link` ops together, so every op names links by their
+/// number before the batch.
+fn apply_external_link_ops(
+    path: &Path,
+    ops: &[StructureOp],
+    counts: &mut BTreeMap<String, u64>,
+    warnings: &mut Vec<String>,
+) -> Result<()> {
+    let mut broken = BTreeSet::new();
+    for op in ops {
+        match op {
+            StructureOp::RepointExternalLink { link, target } => {
+                crate::tools::external_links::repoint_external_link(path, *link, target)?;
+                *counts
+                    .entry("external_links_repointed".to_string())
+                    .or_insert(0) += 1;
+            }
+            StructureOp::BreakExternalLink { link } => {
+                broken.insert(*link);
+            }
+            _ => {}
+        }
+    }
+    if broken.is_empty() {
+        return Ok(());
+    }
+    let outcome = crate::tools::external_links::break_external_links(path, &broken)?;
+    *counts
+        .entry("external_links_broken".to_string())
+        .or_insert(0) += outcome.links_removed as u64;
+    *counts
+        .entry("external_formulas_converted".to_string())
+        .or_insert(0) += outcome.formulas_converted as u64;
+    if outcome.names_invalidated > 0 {
+        warnings.push(format!(
+            "Set {} defined name(s) that referenced a broken external link to #REF!.",
+            outcome.names_invalidated
+        ));
+    }
+    Ok(())
+}
+
 pub(crate) fn apply_structure_ops_to_file(
     path: &Path,
     ops: &[StructureOp],
     policy: FormulaParsePolicy,
 ) -> Result<StructureApplyResult> {
-    let mut book = umya_spreadsheet::reader::xlsx::read(path)?;
-    let mut formula_parse_diagnostics_builder = FormulaParseDiagnosticsBuilder::new(policy);
-
     let mut affected_sheets: BTreeSet<String> = BTreeSet::new();
     let affected_bounds: Vec<String> = Vec::new();
     let mut counts: BTreeMap<String, u64> = BTreeMap::new();
@@ -2059,6 +2743,35 @@ pub(crate) fn apply_structure_ops_to_file(
         "Structural edits may not fully rewrite formulas/named ranges like Excel. After apply, run recalculate and review get_changeset.".to_string(),
     ];
 
+    // External links live outside the umya model, so they are rewritten in
+    // the package first. Link numbers refer to the workbook before the batch.
+    let link_ops_only = ops.iter().all(|op| {
+        matches!(
+            op,
+            StructureOp::BreakExternalLink { .. } | StructureOp::RepointExternalLink { .. }
+        )
+    });
+    apply_external_link_ops(path, ops, &mut counts, &mut warnings)?;
+    if link_ops_only {
+        return Ok(StructureApplyResult {
+            ops_applied: ops.len(),
+            summary: ChangeSummary {
+                op_kinds: vec!["structure_batch".to_string()],
+                affected_bounds,
+                counts,
+                warnings,
+                ..Default::default()
+            },
+            formula_parse_diagnostics: None,
+        });
+    }
+
+    // Outline levels are restored after the save, so they follow the rows,
+    // columns and sheets the ops below move.
+    let mut preserved = PreservedPackage::capture(path);
+    let mut book = umya_spreadsheet::reader::xlsx::read(path)?;
+    let mut formula_parse_diagnostics_builder = FormulaParseDiagnosticsBuilder::new(policy);
+
     for op in ops {
         match op {
             StructureOp::MergeCells {
@@ -2115,6 +2828,9 @@ pub(crate) fn apply_structure_ops_to_file(
                         .ok_or_else(|| anyhow!("sheet '{}' not found", sheet_name))?;
                     sheet.insert_new_row(at_row, count);
                 }
+                if let Some(outline) = preserved.outlines.get_mut(sheet_name) {
+                    outline.insert_rows(*at_row, *count);
+                }
                 rewrite_formulas_for_sheet_row_insert(
                     &mut book,
                     sheet_name,
@@ -2179,6 +2895,9 @@ pub(crate) fn apply_structure_ops_to_file(
                         .ok_or_else(|| anyhow!("sheet '{}' not found", sheet_name))?;
                     sheet.insert_new_row(insert_at, count);
                 }
+                if let Some(outline) = preserved.outlines.get_mut(sheet_name) {
+                    outline.insert_rows(*insert_at, *count);
+                }
                 rewrite_formulas_for_sheet_row_insert(
                     &mut book,
                     sheet_name,
@@ -2248,6 +2967,9 @@ pub(crate) fn apply_structure_ops_to_file(
                         .ok_or_else(|| anyhow!("sheet '{}' not found", sheet_name))?;
                     sheet.remove_row(start_row, count);
                 }
+                if let Some(outline) = preserved.outlines.get_mut(sheet_name) {
+                    outline.delete_rows(*start_row, *count);
+                }
                 rewrite_formulas_for_sheet_row_delete(
                     &mut book,
                     sheet_name,
@@ -2287,6 +3009,9 @@ pub(crate) fn apply_structure_ops_to_file(
                         .ok_or_else(|| anyhow!("sheet '{}' not found", sheet_name))?;
                     sheet.insert_new_column(&col_letters, count);
                 }
+                if let Some(outline) = preserved.outlines.get_mut(sheet_name) {
+                    outline.insert_cols(root_col, *count);
+                }
                 rewrite_formulas_for_sheet_col_insert(
                     &mut book,
                     sheet_name,
@@ -2326,6 +3051,9 @@ pub(crate) fn apply_structure_ops_to_file(
                         .ok_or_else(|| anyhow!("sheet '{}' not found", sheet_name))?;
                     sheet.remove_column(&col_letters, count);
                 }
+                if let Some(outline) = preserved.outlines.get_mut(sheet_name) {
+                    outline.delete_cols(root_col, *count);
+                }
                 rewrite_formulas_for_sheet_col_delete(
                     &mut book,
                     sheet_name,
@@ -2355,28 +3083,16 @@ pub(crate) fn apply_structure_ops_to_file(
                     bail!("rename_sheet requires non-empty old_name and new_name");
                 }
 
-                let sheet_index = book
-                    .get_sheet_collection_no_check()
-                    .iter()
-                    .position(|s| s.get_name() == old_name)
-                    .ok_or_else(|| anyhow!("sheet '{}' not found", old_name))?;
-                book.set_sheet_name(sheet_index, new_name.to_string())
-                    .map_err(|e| anyhow!("failed to rename sheet '{}': {}", old_name, e))?;
-
-                rewrite_formulas_for_sheet_rename(
-                    &mut book,
-                    old_name,
-                    new_name,
-                    policy,
-                    &mut formula_parse_diagnostics_builder,
-                )?;
-                rewrite_defined_name_formulas_for_sheet_rename(
+                rename_sheet_with_references(
                     &mut book,
                     old_name,
                     new_name,
                     policy,
                     &mut formula_parse_diagnostics_builder,
                 )?;
+                if let Some(outline) = preserved.outlines.remove(old_name) {
+                    preserved.outlines.insert(new_name.to_string(), outline);
+                }
 
                 affected_sheets.insert(old_name.to_string());
                 affected_sheets.insert(new_name.to_string());
@@ -2385,27 +3101,93 @@ pub(crate) fn apply_structure_ops_to_file(
                     .and_modify(|v| *v += 1)
                     .or_insert(1);
             }
-            StructureOp::CreateSheet { name, position } => {
-                let name_trimmed = name.trim();
-                if name_trimmed.is_empty() {
-                    bail!("create_sheet requires non-empty name");
+            StructureOp::RenameSheets {
+                find,
+                replace,
+                match_mode,
+            } => {
+                let pattern = RenamePattern::new("rename_sheets", find, replace, *match_mode)?;
+                let sheet_names: Vec<String> = book
+                    .get_sheet_collection_no_check()
+                    .iter()
+                    .map(|sheet| sheet.get_name().to_string())
+                    .collect();
+                let renames = planned_sheet_renames(&sheet_names, &pattern)?;
+                if renames.is_empty() {
+                    warnings.push(format!("rename_sheets: no sheet name matched '{}'", find));
                 }
-                let requested_position = *position;
-                book.new_sheet(name_trimmed.to_string())
-                    .map_err(|e| anyhow!("failed to create sheet '{}': {}", name_trimmed, e))?;
 
-                if let Some(pos) = requested_position {
-                    let desired = pos as usize;
-                    let len = book.get_sheet_collection_no_check().len();
-                    if desired >= len {
-                        warnings.push(format!(
-                            "create_sheet position {} is out of range (sheet_count {}). Appended at end.",
-                            desired, len
-                        ));
-                    } else if desired != len - 1 {
-                        let sheets = book.get_sheet_collection_mut();
-                        let created = sheets.remove(len - 1);
-                        sheets.insert(desired, created);
+                // Go through placeholder names so chained or swapped renames
+                // (FY24 -> FY25 alongside FY23 -> FY24) never capture each
+                // other's references.
+                for (idx, (old_name, _)) in renames.iter().enumerate() {
+                    rename_sheet_with_references(
+                        &mut book,
+                        old_name,
+                        &rename_placeholder(idx),
+                        policy,
+                        &mut formula_parse_diagnostics_builder,
+                    )?;
+                }
+                for (idx, (old_name, new_name)) in renames.iter().enumerate() {
+                    rename_sheet_with_references(
+                        &mut book,
+                        &rename_placeholder(idx),
+                        new_name,
+                        policy,
+                        &mut formula_parse_diagnostics_builder,
+                    )?;
+                    affected_sheets.insert(old_name.clone());
+                    affected_sheets.insert(new_name.clone());
+                }
+                let moved: Vec<(String, SheetOutline)> = renames
+                    .iter()
+                    .filter_map(|(old_name, new_name)| {
+                        let outline = preserved.outlines.remove(old_name)?;
+                        Some((new_name.clone(), outline))
+                    })
+                    .collect();
+                preserved.outlines.extend(moved);
+                *counts.entry("sheets_renamed".to_string()).or_insert(0) += renames.len() as u64;
+            }
+            StructureOp::RenameNames {
+                find,
+                replace,
+                match_mode,
+            } => {
+                let pattern = RenamePattern::new("rename_names", find, replace, *match_mode)?;
+                let renamed = rename_defined_names(
+                    &mut book,
+                    &pattern,
+                    policy,
+                    &mut formula_parse_diagnostics_builder,
+                )?;
+                if renamed == 0 {
+                    warnings.push(format!("rename_names: no defined name matched '{}'", find));
+                }
+                *counts.entry("names_renamed".to_string()).or_insert(0) += renamed as u64;
+            }
+            StructureOp::CreateSheet { name, position } => {
+                let name_trimmed = name.trim();
+                if name_trimmed.is_empty() {
+                    bail!("create_sheet requires non-empty name");
+                }
+                let requested_position = *position;
+                book.new_sheet(name_trimmed.to_string())
+                    .map_err(|e| anyhow!("failed to create sheet '{}': {}", name_trimmed, e))?;
+
+                if let Some(pos) = requested_position {
+                    let desired = pos as usize;
+                    let len = book.get_sheet_collection_no_check().len();
+                    if desired >= len {
+                        warnings.push(format!(
+                            "create_sheet position {} is out of range (sheet_count {}). Appended at end.",
+                            desired, len
+                        ));
+                    } else if desired != len - 1 {
+                        let sheets = book.get_sheet_collection_mut();
+                        let created = sheets.remove(len - 1);
+                        sheets.insert(desired, created);
                     }
                 }
 
@@ -2425,12 +3207,81 @@ pub(crate) fn apply_structure_ops_to_file(
                 }
                 book.remove_sheet_by_name(name_trimmed)
                     .map_err(|e| anyhow!("failed to delete sheet '{}': {}", name_trimmed, e))?;
+                preserved.outlines.remove(name_trimmed);
                 affected_sheets.insert(name_trimmed.to_string());
                 counts
                     .entry("sheets_deleted".to_string())
                     .and_modify(|v| *v += 1)
                     .or_insert(1);
             }
+            StructureOp::CopySheet {
+                source_file,
+                source_sheet,
+                new_name,
+                position,
+            } => {
+                let effects = copy_sheet_into(
+                    &mut book,
+                    source_file,
+                    source_sheet,
+                    new_name.as_deref(),
+                    *position,
+                    policy,
+                    &mut formula_parse_diagnostics_builder,
+                )?;
+                affected_sheets.insert(effects.sheet_name);
+                *counts.entry("sheets_copied".to_string()).or_insert(0) += 1;
+                if effects.formulas_adjusted > 0 {
+                    *counts.entry("formulas_adjusted".to_string()).or_insert(0) +=
+                        effects.formulas_adjusted;
+                }
+                if effects.tables_renamed > 0 {
+                    *counts.entry("tables_renamed".to_string()).or_insert(0) +=
+                        effects.tables_renamed;
+                }
+                warnings.extend(effects.warnings);
+            }
+            StructureOp::SetTabColor { sheet_name, color } => {
+                warnings.extend(crate::tools::sheet_tabs::set_tab_color(
+                    &mut book,
+                    sheet_name,
+                    color.as_deref(),
+                )?);
+                affected_sheets.insert(sheet_name.clone());
+                *counts.entry("tab_colors_set".to_string()).or_insert(0) += 1;
+            }
+            StructureOp::HideSheet {
+                sheet_name,
+                very_hidden,
+            } => {
+                let visibility = if *very_hidden {
+                    SheetVisibility::VeryHidden
+                } else {
+                    SheetVisibility::Hidden
+                };
+                if crate::tools::sheet_tabs::set_sheet_visibility(
+                    &mut book, sheet_name, visibility,
+                )? {
+                    affected_sheets.insert(sheet_name.clone());
+                    *counts.entry("sheets_hidden".to_string()).or_insert(0) += 1;
+                }
+            }
+            StructureOp::UnhideSheet { sheet_name } => {
+                if crate::tools::sheet_tabs::set_sheet_visibility(
+                    &mut book,
+                    sheet_name,
+                    SheetVisibility::Visible,
+                )? {
+                    affected_sheets.insert(sheet_name.clone());
+                    *counts.entry("sheets_unhidden".to_string()).or_insert(0) += 1;
+                }
+            }
+            StructureOp::ReorderSheets { order } => {
+                let moved = crate::tools::sheet_tabs::reorder_sheets(&mut book, order)?;
+                if moved > 0 {
+                    *counts.entry("sheets_moved".to_string()).or_insert(0) += moved as u64;
+                }
+            }
             StructureOp::CopyRange {
                 sheet_name,
                 dest_sheet_name,
@@ -2497,6 +3348,7 @@ pub(crate) fn apply_structure_ops_to_file(
                     .or_insert(1);
                 warnings.extend(result.warnings);
             }
+            StructureOp::BreakExternalLink { .. } | StructureOp::RepointExternalLink { .. } => {}
         }
     }
 
@@ -2508,7 +3360,7 @@ pub(crate) fn apply_structure_ops_to_file(
         ));
     }
 
-    umya_spreadsheet::writer::xlsx::write(&book, path)?;
+    save_workbook_preserving(&book, path, &preserved)?;
 
     // Temporary guardrail: patch overflowing workbook-scoped defined-name row references
     // directly in workbook.xml after structural writes. Remove once Formualizer/Umya
@@ -2851,6 +3703,363 @@ fn copy_or_move_range(
     })
 }
 
+pub(crate) fn rename_sheet_with_references(
+    book: &mut umya_spreadsheet::Spreadsheet,
+    old_name: &str,
+    new_name: &str,
+    policy: FormulaParsePolicy,
+    builder: &mut FormulaParseDiagnosticsBuilder,
+) -> Result<()> {
+    let sheet_index = book
+        .get_sheet_collection_no_check()
+        .iter()
+        .position(|s| s.get_name() == old_name)
+        .ok_or_else(|| anyhow!("sheet '{}' not found", old_name))?;
+    book.set_sheet_name(sheet_index, new_name.to_string())
+        .map_err(|e| anyhow!("failed to rename sheet '{}': {}", old_name, e))?;
+
+    rewrite_formulas_for_sheet_rename(book, old_name, new_name, policy, builder)?;
+    rewrite_defined_name_formulas_for_sheet_rename(book, old_name, new_name, policy, builder)
+}
+
+/// `find`/`replace` pattern shared by `rename_sheets` and `rename_names`.
+struct RenamePattern {
+    find: String,
+    replace: String,
+    mode: RenameMatchMode,
+    regex: Option<Regex>,
+}
+
+impl RenamePattern {
+    fn new(op: &str, find: &str, replace: &str, mode: RenameMatchMode) -> Result<Self> {
+        if find.is_empty() {
+            bail!("{op} requires a non-empty find");
+        }
+        let regex = match mode {
+            RenameMatchMode::Regex => {
+                Some(Regex::new(find).map_err(|e| anyhow!("{op} find is not a valid regex: {e}"))?)
+            }
+            _ => None,
+        };
+        Ok(Self {
+            find: find.to_string(),
+            replace: replace.to_string(),
+            mode,
+            regex,
+        })
+    }
+
+    /// New name for `name`, or `None` when the pattern does not match or
+    /// leaves it unchanged.
+    fn apply(&self, name: &str) -> Option<String> {
+        let renamed = match self.mode {
+            RenameMatchMode::Contains => name
+                .contains(&self.find)
+                .then(|| name.replace(&self.find, &self.replace)),
+            RenameMatchMode::Prefix => name
+                .strip_prefix(&self.find)
+                .map(|rest| format!("{}{}", self.replace, rest)),
+            RenameMatchMode::Suffix => name
+                .strip_suffix(&self.find)
+                .map(|head| format!("{}{}", head, self.replace)),
+            RenameMatchMode::Regex => {
+                let regex = self.regex.as_ref().expect("regex compiled in new");
+                regex
+                    .is_match(name)
+                    .then(|| regex.replace_all(name, self.replace.as_str()).into_owned())
+            }
+        }?;
+        (renamed != name).then_some(renamed)
+    }
+}
+
+fn rename_placeholder(idx: usize) -> String {
+    format!("__rename_{idx}__")
+}
+
+/// `(old, new)` pairs for the sheets `pattern` renames, after checking that
+/// every new name is a legal sheet name and that no two sheets end up with
+/// the same name.
+fn planned_sheet_renames(
+    sheet_names: &[String],
+    pattern: &RenamePattern,
+) -> Result<Vec<(String, String)>> {
+    let mut renames = Vec::new();
+    let mut final_names: BTreeMap<String, String> = BTreeMap::new();
+    for name in sheet_names {
+        let final_name = match pattern.apply(name) {
+            Some(new_name) => {
+                validate_sheet_name(&new_name)
+                    .map_err(|e| anyhow!("rename_sheets cannot rename '{}': {}", name, e))?;
+                renames.push((name.clone(), new_name.clone()));
+                new_name
+            }
+            None => name.clone(),
+        };
+        if let Some(previous) = final_names.insert(final_name.to_lowercase(), name.clone()) {
+            bail!(
+                "rename_sheets would give '{}' and '{}' the same name '{}'",
+                previous,
+                name,
+                final_name
+            );
+        }
+    }
+    if sheet_names
+        .iter()
+        .any(|name| name.starts_with("__rename_") && name.ends_with("__"))
+        && !renames.is_empty()
+    {
+        bail!("rename_sheets cannot run while a sheet is named like its __rename_N__ placeholders");
+    }
+    Ok(renames)
+}
+
+pub(crate) fn validate_sheet_name(name: &str) -> Result<()> {
+    if name.trim().is_empty() {
+        bail!("sheet names cannot be empty");
+    }
+    if name.chars().count() > 31 {
+        bail!("'{}' is longer than 31 characters", name);
+    }
+    if let Some(ch) = name
+        .chars()
+        .find(|ch| matches!(ch, ':' | '\\' | '/' | '?' | '*' | '[' | ']'))
+    {
+        bail!("'{}' contains '{}', which sheet names cannot use", name, ch);
+    }
+    if name.starts_with('\'') || name.ends_with('\'') {
+        bail!("'{}' cannot start or end with an apostrophe", name);
+    }
+    Ok(())
+}
+
+/// Rename every defined name matching `pattern` at workbook and sheet scope,
+/// then rewrite references to the old names in cell and defined-name
+/// formulas. Returns the number of defined-name entries renamed.
+fn rename_defined_names(
+    book: &mut umya_spreadsheet::Spreadsheet,
+    pattern: &RenamePattern,
+    policy: FormulaParsePolicy,
+    builder: &mut FormulaParseDiagnosticsBuilder,
+) -> Result<usize> {
+    // Names live in the workbook list (global, or local via localSheetId) and
+    // in per-sheet lists; uniqueness is per scope.
+    let mut scoped_names: Vec<(String, String)> = Vec::new();
+    for defined in book.get_defined_names() {
+        let scope = if defined.has_local_sheet_id() {
+            format!("sheet#{}", defined.get_local_sheet_id())
+        } else {
+            "workbook".to_string()
+        };
+        scoped_names.push((scope, defined.get_name().to_string()));
+    }
+    for (idx, sheet) in book.get_sheet_collection_no_check().iter().enumerate() {
+        for defined in sheet.get_defined_names() {
+            scoped_names.push((format!("sheet#{idx}"), defined.get_name().to_string()));
+        }
+    }
+
+    let mut renames: BTreeMap<String, String> = BTreeMap::new();
+    let mut final_names: BTreeSet<(String, String)> = BTreeSet::new();
+    for (scope, name) in &scoped_names {
+        let final_name = match pattern.apply(name) {
+            Some(new_name) => {
+                validate_defined_name(&new_name)
+                    .map_err(|e| anyhow!("rename_names cannot rename '{}': {}", name, e))?;
+                renames.insert(name.to_lowercase(), new_name.clone());
+                new_name
+            }
+            None => name.clone(),
+        };
+        if !final_names.insert((scope.clone(), final_name.to_lowercase())) {
+            bail!(
+                "rename_names would leave two names called '{}' in the same scope",
+                final_name
+            );
+        }
+    }
+    if renames.is_empty() {
+        return Ok(0);
+    }
+
+    let mut renamed = 0usize;
+    let mut rename_entry = |defined: &mut umya_spreadsheet::DefinedName| {
+        if let Some(new_name) = renames.get(&defined.get_name().to_lowercase()) {
+            defined.set_name(new_name.clone());
+            renamed += 1;
+        }
+    };
+    for defined in book.get_defined_names_mut() {
+        rename_entry(defined);
+    }
+    for sheet in book.get_sheet_collection_mut() {
+        for defined in sheet.get_defined_names_mut() {
+            rename_entry(defined);
+        }
+    }
+
+    rewrite_formulas_for_name_rename(book, &renames, policy, builder)?;
+    Ok(renamed)
+}
+
+/// Excel's rules for a defined name: starts with a letter, `_`, or `\`; then
+/// letters, digits, `_`, `.`, or `\`; and never readable as a cell reference.
+fn validate_defined_name(name: &str) -> Result<()> {
+    let mut chars = name.chars();
+    let Some(first) = chars.next() else {
+        bail!("defined names cannot be empty");
+    };
+    if !(first.is_alphabetic() || first == '_' || first == '\\')
+        || !chars.all(|ch| ch.is_alphanumeric() || matches!(ch, '_' | '.' | '\\'))
+    {
+        bail!("'{}' is not a valid defined name", name);
+    }
+    if name.chars().count() > 255 {
+        bail!("'{}' is longer than 255 characters", name);
+    }
+    let a1_re = Regex::new(r"^(?i)([a-z]{1,3})([0-9]+)$").expect("valid A1 regex");
+    let r1c1_re = Regex::new(r"^(?i)(r[0-9]*c?[0-9]*|c[0-9]*)$").expect("valid R1C1 regex");
+    let looks_like_a1 = a1_re.captures(name).is_some_and(|caps| {
+        let col = caps[1].bytes().fold(0u32, |acc, b| {
+            acc * 26 + u32::from(b.to_ascii_uppercase() - b'A' + 1)
+        });
+        let row = caps[2].parse::<u64>().unwrap_or(u64::MAX);
+        col <= 16_384 && (1..=1_048_576).contains(&row)
+    });
+    if looks_like_a1 || r1c1_re.is_match(name) {
+        bail!("'{}' reads as a cell reference", name);
+    }
+    Ok(())
+}
+
+/// Rewrite references to renamed defined names (`old lowercase -> new`) in
+/// every cell formula and defined-name formula.
+fn rewrite_formulas_for_name_rename(
+    book: &mut umya_spreadsheet::Spreadsheet,
+    renames: &BTreeMap<String, String>,
+    policy: FormulaParsePolicy,
+    builder: &mut FormulaParseDiagnosticsBuilder,
+) -> Result<()> {
+    if policy == FormulaParsePolicy::Off {
+        return Ok(());
+    }
+
+    for sheet in book.get_sheet_collection_mut().iter_mut() {
+        let sheet_name = sheet.get_name().to_string();
+        for cell in sheet.get_cell_collection_mut() {
+            if !cell.is_formula() || cell.get_formula().is_empty() {
+                continue;
+            }
+            let formula_text = cell.get_formula().to_string();
+            let cell_address = cell.get_coordinate().get_coordinate().to_string();
+            let rewritten = match rename_name_tokens(&formula_text, renames) {
+                Ok(rewritten) => rewritten,
+                Err(message) => {
+                    if policy == FormulaParsePolicy::Fail {
+                        bail!(
+                            "{}tokenizer error in {}!{}: {}",
+                            FORMULA_PARSE_FAILED_PREFIX,
+                            sheet_name,
+                            cell_address,
+                            message
+                        );
+                    }
+                    builder.record_error(&sheet_name, &cell_address, &formula_text, &message);
+                    continue;
+                }
+            };
+            if let Some(new_formula) = rewritten {
+                cell.set_formula(new_formula);
+                cell.set_formula_result_default("");
+            }
+        }
+    }
+
+    for defined in book.get_defined_names_mut() {
+        let refers_to = defined.get_address();
+        let trimmed = refers_to.trim();
+        let defined_name = defined.get_name().to_string();
+        match rename_name_tokens(trimmed, renames) {
+            Ok(Some(new_refers_to)) => defined.set_address(new_refers_to),
+            Ok(None) => {}
+            Err(message) => {
+                if policy == FormulaParsePolicy::Fail {
+                    bail!(
+                        "{}tokenizer error in defined name '{}': {}",
+                        FORMULA_PARSE_FAILED_PREFIX,
+                        defined_name,
+                        message
+                    );
+                }
+                builder.record_error("[DefinedName]", &defined_name, trimmed, &message);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// `formula` with references to renamed names replaced, `None` when it
+/// references none of them, or the tokenizer error message.
+fn rename_name_tokens(
+    formula: &str,
+    renames: &BTreeMap<String, String>,
+) -> std::result::Result<Option<String>, String> {
+    let lowered = formula.to_lowercase();
+    if !renames
+        .keys()
+        .any(|old_name| lowered.contains(old_name.as_str()))
+    {
+        return Ok(None);
+    }
+    let had_equals = formula.starts_with('=');
+    let formula_in = if had_equals {
+        formula.to_string()
+    } else {
+        format!("={}", formula)
+    };
+    let tokens = Tokenizer::new(&formula_in).map_err(|e| e.message)?.items;
+
+    let mut out = String::with_capacity(formula_in.len());
+    let mut cursor = 0usize;
+    let mut changed = false;
+    for token in &tokens {
+        if token.start > cursor {
+            out.push_str(&formula_in[cursor..token.start]);
+        }
+        let mut value = token.value.clone();
+        if token.subtype == formualizer_parse::TokenSubType::Range {
+            // Bare `Revenue` or sheet-qualified `Sheet1!Revenue`.
+            let (qualifier, name) = match value.rsplit_once('!') {
+                Some((sheet_part, name)) => (Some(sheet_part), name),
+                None => (None, value.as_str()),
+            };
+            if let Some(new_name) = renames.get(&name.to_lowercase()) {
+                value = match qualifier {
+                    Some(sheet_part) => format!("{}!{}", sheet_part, new_name),
+                    None => new_name.clone(),
+                };
+                changed = true;
+            }
+        }
+        out.push_str(&value);
+        cursor = token.end;
+    }
+    if cursor < formula_in.len() {
+        out.push_str(&formula_in[cursor..]);
+    }
+
+    if !changed {
+        return Ok(None);
+    }
+    Ok(Some(if had_equals {
+        out
+    } else {
+        out.strip_prefix('=').unwrap_or(&out).to_string()
+    }))
+}
+
 fn rewrite_formulas_for_sheet_rename(
     book: &mut umya_spreadsheet::Spreadsheet,
     old_name: &str,
@@ -3501,7 +4710,8 @@ fn sanitize_workbook_xml_defined_name_rows(path: &Path) -> Result<usize> {
         return Ok(0);
     }
 
-    let temp_path = path.with_extension("xlsx.tmp");
+    // Removed on drop if writing fails before the rename.
+    let temp_path = tempfile::TempPath::from_path(path.with_extension("xlsx.tmp"));
     let output_file = fs::File::create(&temp_path)?;
     let mut writer = ZipWriter::new(output_file);
 
@@ -3522,7 +4732,7 @@ fn sanitize_workbook_xml_defined_name_rows(path: &Path) -> Result<usize> {
     }
 
     writer.finish()?;
-    fs::rename(temp_path, path)?;
+    temp_path.persist(path)?;
     Ok(clamped_defined_names)
 }
 
@@ -4010,32 +5220,260 @@ fn stamp_template_rows(
     Ok(warnings)
 }
 
-fn sheet_part_matches(sheet_part: &str, old_name: &str) -> bool {
-    let trimmed = sheet_part.trim();
-    if let Some(stripped) = trimmed.strip_prefix('\'')
-        && let Some(inner) = stripped.strip_suffix('\'')
-    {
-        return inner.replace("''", "'") == old_name;
-    }
-    trimmed == old_name
-}
+// ---------------------------------------------------------------------------
+// Table append helpers
+// ---------------------------------------------------------------------------
 
-fn format_sheet_prefix_for_formula(sheet_name: &str) -> String {
-    if sheet_name_needs_quoting_for_formula(sheet_name) {
-        let escaped = sheet_name.replace('\'', "''");
-        format!("'{escaped}'!")
-    } else {
-        format!("{sheet_name}!")
-    }
+/// What one `append_table_rows` op changed.
+#[derive(Debug, Default)]
+struct TableAppendEffects {
+    rows_appended: u64,
+    values_set: u64,
+    formulas_filled: u64,
+    totals_adjusted: u64,
+    bounds: Option<String>,
+    warnings: Vec<String>,
 }
 
-fn sheet_name_needs_quoting_for_formula(name: &str) -> bool {
-    if name.is_empty() {
-        return false;
-    }
-    let bytes = name.as_bytes();
-    if bytes[0].is_ascii_digit() {
-        return true;
+/// Append `rows` (arrays in table column order) below the data of a named
+/// table. Sheet rows are inserted when a totals row or other content sits
+/// directly below the data; otherwise the table grows into empty rows.
+/// Empty cells in columns whose last data row holds a formula get that
+/// formula copied down, and totals-row ranges ending at the old last data
+/// row are extended over the new rows.
+fn append_table_rows_in_book(
+    book: &mut umya_spreadsheet::Spreadsheet,
+    sheet_name: &str,
+    table_name: &str,
+    rows: &[serde_json::Value],
+    coerce_types: bool,
+    use_1904_system: bool,
+) -> Result<TableAppendEffects> {
+    let mut effects = TableAppendEffects::default();
+    let count = rows.len() as u32;
+
+    let (start_col, header_row, end_col, end_row, has_totals, template, blocked) = {
+        let sheet = book
+            .get_sheet_by_name(sheet_name)
+            .ok_or_else(|| anyhow!("sheet '{}' not found", sheet_name))?;
+        let table = sheet
+            .get_tables()
+            .iter()
+            .find(|table| table_matches(table, table_name))
+            .ok_or_else(|| {
+                anyhow!(
+                    "table '{}' was not found on sheet '{}'",
+                    table_name,
+                    sheet_name
+                )
+            })?;
+        let (start, end) = table.get_area();
+        let (start_col, header_row) = (*start.get_col_num(), *start.get_row_num());
+        let (end_col, end_row) = (*end.get_col_num(), *end.get_row_num());
+        let has_totals = *table.get_totals_row_count() > 0;
+        let last_data_row = if has_totals { end_row - 1 } else { end_row };
+
+        // The last data row seeds styles and calculated-column formulas.
+        let template: Vec<TemplateCellData> = if last_data_row > header_row {
+            capture_row_template(sheet, last_data_row)?
+                .into_iter()
+                .filter(|cell| (start_col..=end_col).contains(&cell.col))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let blocked = has_totals
+            || (last_data_row + 1..=last_data_row + count).any(|row| {
+                (start_col..=end_col).any(|col| {
+                    sheet
+                        .get_cell((col, row))
+                        .is_some_and(|cell| cell.is_formula() || !cell.get_value().is_empty())
+                })
+            });
+        (
+            start_col, header_row, end_col, end_row, has_totals, template, blocked,
+        )
+    };
+    if count == 0 {
+        return Ok(effects);
+    }
+    let last_data_row = if has_totals { end_row - 1 } else { end_row };
+    let at_row = last_data_row + 1;
+
+    if blocked {
+        book.get_sheet_by_name_mut(sheet_name)
+            .ok_or_else(|| anyhow!("sheet '{}' not found", sheet_name))?
+            .insert_new_row(&at_row, &count);
+        let policy = FormulaParsePolicy::Warn;
+        let mut diagnostics = FormulaParseDiagnosticsBuilder::new(policy);
+        rewrite_formulas_for_sheet_row_insert(
+            book,
+            sheet_name,
+            at_row,
+            count,
+            policy,
+            &mut diagnostics,
+        )?;
+        rewrite_defined_name_formulas_for_sheet_row_insert(
+            book,
+            sheet_name,
+            at_row,
+            count,
+            policy,
+            &mut diagnostics,
+        )?;
+        if diagnostics.has_errors() {
+            effects.warnings.push(format!(
+                "WARN_TABLE_APPEND_FORMULA_REWRITE: some formulas referring below table '{}' could not be parsed and were left unchanged.",
+                table_name
+            ));
+        }
+        if !has_totals {
+            effects.warnings.push(format!(
+                "WARN_TABLE_APPEND_ROWS_INSERTED: content below table '{}' was moved down {} row(s).",
+                table_name, count
+            ));
+        }
+    }
+
+    let sheet = book
+        .get_sheet_by_name_mut(sheet_name)
+        .ok_or_else(|| anyhow!("sheet '{}' not found", sheet_name))?;
+    for (offset, row) in rows.iter().enumerate() {
+        let row_num = at_row + offset as u32;
+        let values = row.as_array().map(Vec::as_slice).unwrap_or_default();
+        for col in start_col..=end_col {
+            let template_cell = template.iter().find(|cell| cell.col == col);
+            if let Some(template_cell) = template_cell {
+                sheet
+                    .get_cell_mut((col, row_num))
+                    .set_style(template_cell.style.clone());
+            }
+
+            let value = values
+                .get((col - start_col) as usize)
+                .unwrap_or(&serde_json::Value::Null);
+            if !value.is_null() {
+                let cell = sheet.get_cell_mut((col, row_num));
+                if write_import_value(cell, value, coerce_types, use_1904_system) {
+                    effects.values_set += 1;
+                }
+                continue;
+            }
+
+            let Some(formula) = template_cell.and_then(|cell| cell.formula.as_ref()) else {
+                continue;
+            };
+            let delta_row = (row_num - last_data_row) as i32;
+            let filled = match parse_base_formula(formula)
+                .and_then(|ast| shift_formula_ast(&ast, 0, delta_row, RelativeMode::Excel))
+            {
+                Ok(shifted) => shifted.strip_prefix('=').unwrap_or(&shifted).to_string(),
+                Err(err) => {
+                    effects.warnings.push(format!(
+                        "WARN_TABLE_APPEND_FORMULA_SHIFT: could not shift formula '{}' for row {}: {}; copied verbatim.",
+                        formula, row_num, err
+                    ));
+                    formula.clone()
+                }
+            };
+            let cell = sheet.get_cell_mut((col, row_num));
+            cell.set_formula(filled);
+            cell.set_formula_result_default("");
+            effects.formulas_filled += 1;
+        }
+    }
+
+    if has_totals {
+        let totals_row = end_row + count;
+        for col in start_col..=end_col {
+            let Some(cell) = sheet.get_cell((col, totals_row)) else {
+                continue;
+            };
+            if !cell.is_formula() {
+                continue;
+            }
+            let formula = cell.get_formula().to_string();
+            if let Some(extended) =
+                extend_ranges_ending_at(&formula, header_row + 1, last_data_row, at_row + count - 1)
+            {
+                let cell = sheet.get_cell_mut((col, totals_row));
+                cell.set_formula(extended);
+                cell.set_formula_result_default("");
+                effects.totals_adjusted += 1;
+            }
+        }
+    }
+
+    let table = sheet
+        .get_tables_mut()
+        .iter_mut()
+        .find(|table| table_matches(table, table_name))
+        .ok_or_else(|| anyhow!("table '{}' was not found after append", table_name))?;
+    table.set_area(((start_col, header_row), (end_col, end_row + count)));
+
+    effects.rows_appended = count as u64;
+    effects.bounds = Some(format!(
+        "{}:{}",
+        crate::utils::cell_address(start_col, at_row),
+        crate::utils::cell_address(end_col, at_row + count - 1)
+    ));
+    Ok(effects)
+}
+
+/// `formula` with every same-sheet `A1:B2` range that starts inside the data
+/// (`first_row..=old_end`) and ends on `old_end` stretched to `new_end`, or
+/// `None` if no range qualifies. Structured references such as
+/// `SUBTOTAL(109,Table1[Amount])` follow the table range and are left alone.
+fn extend_ranges_ending_at(
+    formula: &str,
+    first_row: u32,
+    old_end: u32,
+    new_end: u32,
+) -> Option<String> {
+    let range_re =
+        Regex::new(r"(\$?[A-Za-z]{1,3}\$?)(\d+):(\$?[A-Za-z]{1,3}\$?)(\d+)").expect("valid regex");
+    let mut changed = false;
+    let extended = range_re.replace_all(formula, |caps: &regex::Captures| {
+        let whole = caps.get(0).expect("match");
+        let qualified = formula[..whole.start()].ends_with('!');
+        let start: u32 = caps[2].parse().unwrap_or(0);
+        let end: u32 = caps[4].parse().unwrap_or(0);
+        if qualified || end != old_end || start < first_row || start > old_end {
+            return whole.as_str().to_string();
+        }
+        changed = true;
+        format!("{}{}:{}{}", &caps[1], start, &caps[3], new_end)
+    });
+    changed.then(|| extended.into_owned())
+}
+
+fn sheet_part_matches(sheet_part: &str, old_name: &str) -> bool {
+    let trimmed = sheet_part.trim();
+    if let Some(stripped) = trimmed.strip_prefix('\'')
+        && let Some(inner) = stripped.strip_suffix('\'')
+    {
+        return inner.replace("''", "'") == old_name;
+    }
+    trimmed == old_name
+}
+
+pub(crate) fn format_sheet_prefix_for_formula(sheet_name: &str) -> String {
+    if sheet_name_needs_quoting_for_formula(sheet_name) {
+        let escaped = sheet_name.replace('\'', "''");
+        format!("'{escaped}'!")
+    } else {
+        format!("{sheet_name}!")
+    }
+}
+
+fn sheet_name_needs_quoting_for_formula(name: &str) -> bool {
+    if name.is_empty() {
+        return false;
+    }
+    let bytes = name.as_bytes();
+    if bytes[0].is_ascii_digit() {
+        return true;
     }
     for &byte in bytes {
         match byte {
@@ -4057,10 +5495,6 @@ pub(crate) struct StyleApplyResult {
     pub(crate) summary: ChangeSummary,
 }
 
-pub(crate) fn stage_snapshot_path(fork_id: &str, change_id: &str) -> PathBuf {
-    PathBuf::from("/tmp/mcp-staged").join(format!("{fork_id}_{change_id}.xlsx"))
-}
-
 pub(crate) struct ColumnSizeApplyResult {
     pub(crate) ops_applied: usize,
     pub(crate) summary: ChangeSummary,
@@ -4094,6 +5528,122 @@ fn parse_column_span(spec: &str) -> Result<(u32, u32)> {
     Ok((min, max))
 }
 
+/// Excel caps column widths at 255 characters.
+const AUTOFIT_MAX_WIDTH_CHARS: f64 = 255.0;
+const AUTOFIT_DEFAULT_PADDING_CHARS: f64 = 1.0;
+const AUTOFIT_DEFAULT_FONT_SIZE: f64 = 11.0;
+/// Bold Calibri renders roughly 8% wider than regular.
+const AUTOFIT_BOLD_FACTOR: f64 = 1.08;
+
+struct ColumnContentWidths {
+    /// Widest displayed text per column, in default-font characters.
+    widths: BTreeMap<u32, f64>,
+    saw_formula_without_cached: bool,
+}
+
+/// Measure displayed text for columns `start_col..=end_col`. Cells that are
+/// the anchor of a multi-column merge are skipped (their text spans several
+/// columns), and wrap-text cells contribute their longest word rather than
+/// their longest line.
+fn measure_column_content_widths(
+    sheet: &umya_spreadsheet::Worksheet,
+    start_col: u32,
+    end_col: u32,
+) -> ColumnContentWidths {
+    let multi_column_merges: Vec<(u32, u32, u32, u32)> = sheet
+        .get_merge_cells()
+        .iter()
+        .filter_map(|range| {
+            let raw = range.get_range();
+            let (a, b) = raw.split_once(':')?;
+            let (c1, r1, _, _) = umya_spreadsheet::helper::coordinate::index_from_coordinate(a);
+            let (c2, r2, _, _) = umya_spreadsheet::helper::coordinate::index_from_coordinate(b);
+            let (c1, r1, c2, r2) = (c1?, r1?, c2?, r2?);
+            (c1 != c2).then_some((c1.min(c2), r1.min(r2), c1.max(c2), r1.max(r2)))
+        })
+        .collect();
+
+    let mut widths: BTreeMap<u32, f64> = BTreeMap::new();
+    let mut saw_formula_without_cached = false;
+    for cell in sheet.get_cell_collection() {
+        let col = *cell.get_coordinate().get_col_num();
+        let row = *cell.get_coordinate().get_row_num();
+        if col < start_col || col > end_col {
+            continue;
+        }
+        if multi_column_merges
+            .iter()
+            .any(|(c1, r1, c2, r2)| (*c1..=*c2).contains(&col) && (*r1..=*r2).contains(&row))
+        {
+            continue;
+        }
+
+        let text = cell.get_formatted_value();
+        if text.is_empty() {
+            if cell.is_formula() {
+                saw_formula_without_cached = true;
+            }
+            continue;
+        }
+
+        let style = cell.get_style();
+        let font = style.get_font();
+        let mut scale = font
+            .map(|font| *font.get_size())
+            .filter(|size| *size > 0.0)
+            .unwrap_or(AUTOFIT_DEFAULT_FONT_SIZE)
+            / AUTOFIT_DEFAULT_FONT_SIZE;
+        if font.is_some_and(|font| *font.get_bold()) {
+            scale *= AUTOFIT_BOLD_FACTOR;
+        }
+        let wrap_text = style
+            .get_alignment()
+            .map(|alignment| *alignment.get_wrap_text())
+            .unwrap_or(false);
+
+        let longest = text
+            .split('\n')
+            .flat_map(|line| {
+                if wrap_text {
+                    line.split_whitespace().collect::<Vec<_>>()
+                } else {
+                    vec![line]
+                }
+            })
+            .map(display_width_chars)
+            .fold(0.0_f64, f64::max);
+        let entry = widths.entry(col).or_insert(0.0);
+        *entry = entry.max(longest * scale);
+    }
+
+    ColumnContentWidths {
+        widths,
+        saw_formula_without_cached,
+    }
+}
+
+/// Approximate rendered width in default-font characters: East Asian wide
+/// characters take two cells, capitals are slightly wider, and thin glyphs
+/// narrower.
+fn display_width_chars(text: &str) -> f64 {
+    text.chars()
+        .map(|ch| match ch {
+            '\u{1100}'..='\u{115F}'
+            | '\u{2E80}'..='\u{A4CF}'
+            | '\u{AC00}'..='\u{D7A3}'
+            | '\u{F900}'..='\u{FAFF}'
+            | '\u{FE30}'..='\u{FE4F}'
+            | '\u{FF00}'..='\u{FF60}'
+            | '\u{FFE0}'..='\u{FFE6}' => 2.0,
+            'i' | 'j' | 'l' | 'I' | '.' | ',' | ':' | ';' | '\'' | '!' | '|' => 0.5,
+            'f' | 'r' | 't' | ' ' | '(' | ')' | '[' | ']' | '-' => 0.75,
+            'M' | 'W' | 'm' | 'w' | '@' | '%' => 1.4,
+            ch if ch.is_ascii_uppercase() => 1.15,
+            _ => 1.0,
+        })
+        .sum()
+}
+
 pub(crate) fn apply_column_size_ops_to_file(
     path: &Path,
     sheet_name: &str,
@@ -4110,6 +5660,7 @@ pub(crate) fn apply_column_size_ops_to_file(
     let mut columns_sized: u64 = 0;
     let mut auto_ops: u64 = 0;
     let mut width_ops: u64 = 0;
+    let mut autofit_ops: u64 = 0;
 
     for op in ops {
         let ColumnTarget::Columns { range } = &op.target;
@@ -4178,15 +5729,55 @@ pub(crate) fn apply_column_size_ops_to_file(
                     columns_sized += 1;
                 }
             }
+            ColumnSizeSpec::Autofit {
+                min_width_chars,
+                max_width_chars,
+                padding_chars,
+            } => {
+                autofit_ops += 1;
+
+                let measured = measure_column_content_widths(sheet, start_col, end_col);
+                if measured.saw_formula_without_cached {
+                    warnings.push(
+                        "WARN_AUTOFIT_FORMULA_NO_CACHED: Autofit measured empty values for some formula cells; results may be too narrow. Recalc the sheet before autofit for best results."
+                            .to_string(),
+                    );
+                }
+
+                let padding = padding_chars
+                    .unwrap_or(AUTOFIT_DEFAULT_PADDING_CHARS)
+                    .max(0.0);
+                for (col, content_width) in measured.widths {
+                    let mut width = content_width + padding;
+                    if let Some(min_width) = min_width_chars
+                        && width < *min_width
+                    {
+                        width = *min_width;
+                    }
+                    if let Some(max_width) = max_width_chars
+                        && width > *max_width
+                    {
+                        width = *max_width;
+                    }
+                    let width = ((width * 100.0).round() / 100.0).min(AUTOFIT_MAX_WIDTH_CHARS);
+
+                    let col_dim = sheet.get_column_dimension_by_number_mut(&col);
+                    col_dim.set_width(width);
+                    col_dim.set_best_fit(true);
+                    col_dim.set_auto_width(false);
+                    columns_sized += 1;
+                }
+            }
         }
     }
 
-    umya_spreadsheet::writer::xlsx::write(&book, path)?;
+    save_workbook(&book, path)?;
 
     let mut counts = BTreeMap::new();
     counts.insert("columns_sized".to_string(), columns_sized);
     counts.insert("auto_ops".to_string(), auto_ops);
     counts.insert("width_ops".to_string(), width_ops);
+    counts.insert("autofit_ops".to_string(), autofit_ops);
 
     Ok(ColumnSizeApplyResult {
         ops_applied: ops.len(),
@@ -4201,16 +5792,225 @@ pub(crate) fn apply_column_size_ops_to_file(
     })
 }
 
+pub(crate) struct RowSizeApplyResult {
+    pub(crate) ops_applied: usize,
+    pub(crate) summary: ChangeSummary,
+}
+
+/// Excel caps row heights at 409 points.
+const MAX_ROW_HEIGHT_POINTS: f64 = 409.0;
+const DEFAULT_ROW_FONT_SIZE: f64 = 11.0;
+/// Default 15pt rows for 11pt body text.
+const ROW_HEIGHT_PER_FONT_POINT: f64 = 15.0 / 11.0;
+
+fn validate_row_height(height_points: f64) -> Result<()> {
+    if !height_points.is_finite() || !(0.0..=MAX_ROW_HEIGHT_POINTS).contains(&height_points) {
+        bail!("row height {height_points} must be between 0 and {MAX_ROW_HEIGHT_POINTS} points");
+    }
+    Ok(())
+}
+
+/// Estimate the height a cell needs: explicit line breaks always count, and
+/// wrapped cells also break long lines at the column width (in characters,
+/// scaled by font size).
+fn estimate_cell_height(text: &str, font_size: f64, wrap_text: bool, col_width_chars: f64) -> f64 {
+    let scale = font_size / DEFAULT_ROW_FONT_SIZE;
+    let lines: f64 = text
+        .split('\n')
+        .map(|line| {
+            if !wrap_text || col_width_chars <= 0.0 {
+                return 1.0;
+            }
+            let chars = line.chars().count() as f64 * scale;
+            (chars / col_width_chars).ceil().max(1.0)
+        })
+        .sum();
+    lines * font_size * ROW_HEIGHT_PER_FONT_POINT
+}
+
+pub(crate) fn apply_row_size_ops_to_file(
+    path: &Path,
+    sheet_name: &str,
+    ops: &[RowSizeOp],
+) -> Result<RowSizeApplyResult> {
+    let mut book = umya_spreadsheet::reader::xlsx::read(path)?;
+    let sheet = book
+        .get_sheet_by_name_mut(sheet_name)
+        .ok_or_else(|| anyhow!("sheet '{}' not found", sheet_name))?;
+
+    let mut affected_bounds: Vec<String> = Vec::new();
+    let mut warnings: Vec<String> = Vec::new();
+
+    let mut rows_sized: u64 = 0;
+    let mut auto_ops: u64 = 0;
+    let mut height_ops: u64 = 0;
+
+    for op in ops {
+        let RowTarget::Rows { range } = &op.target;
+        let (start_row, end_row) = crate::tools::sheet_layout::parse_row_span(range)?;
+        affected_bounds.push(format!("{start_row}:{end_row}"));
+
+        match &op.size {
+            RowSizeSpec::Height { height_points } => {
+                height_ops += 1;
+                for row in start_row..=end_row {
+                    let row_dim = sheet.get_row_dimension_mut(&row);
+                    row_dim.set_height(*height_points);
+                    row_dim.set_custom_height(true);
+                    rows_sized += 1;
+                }
+            }
+            RowSizeSpec::Auto {
+                min_height_points,
+                max_height_points,
+            } => {
+                auto_ops += 1;
+
+                let mut estimates: BTreeMap<u32, f64> = BTreeMap::new();
+                let mut saw_formula_without_cached = false;
+                for cell in sheet.get_cell_collection() {
+                    let row = *cell.get_coordinate().get_row_num();
+                    if row < start_row || row > end_row {
+                        continue;
+                    }
+                    let text = cell.get_value();
+                    if text.is_empty() {
+                        if cell.is_formula() {
+                            saw_formula_without_cached = true;
+                        }
+                        continue;
+                    }
+                    let style = cell.get_style();
+                    let font_size = style
+                        .get_font()
+                        .map(|font| *font.get_size())
+                        .filter(|size| *size > 0.0)
+                        .unwrap_or(DEFAULT_ROW_FONT_SIZE);
+                    let wrap_text = style
+                        .get_alignment()
+                        .map(|alignment| *alignment.get_wrap_text())
+                        .unwrap_or(false);
+                    let col_width_chars = sheet
+                        .get_column_dimension_by_number(cell.get_coordinate().get_col_num())
+                        .map(|col| *col.get_width())
+                        .filter(|width| *width > 0.0)
+                        .unwrap_or(super::LAYOUT_DEFAULT_COL_WIDTH);
+                    let height = estimate_cell_height(&text, font_size, wrap_text, col_width_chars);
+                    let entry = estimates.entry(row).or_insert(0.0);
+                    *entry = entry.max(height);
+                }
+                if saw_formula_without_cached {
+                    warnings.push(
+                        "WARN_AUTOHEIGHT_FORMULA_NO_CACHED: Autosize measured empty values for some formula cells; results may be too short. Recalc the sheet before autosize for best results."
+                            .to_string(),
+                    );
+                }
+
+                let default_height = DEFAULT_ROW_FONT_SIZE * ROW_HEIGHT_PER_FONT_POINT;
+                for row in start_row..=end_row {
+                    let mut height = estimates.get(&row).copied().unwrap_or(default_height);
+                    if let Some(min_height) = min_height_points
+                        && height < *min_height
+                    {
+                        height = *min_height;
+                    }
+                    if let Some(max_height) = max_height_points
+                        && height > *max_height
+                    {
+                        height = *max_height;
+                    }
+                    let height = (height * 4.0).round() / 4.0;
+                    let height = height.min(MAX_ROW_HEIGHT_POINTS);
+                    let row_dim = sheet.get_row_dimension_mut(&row);
+                    row_dim.set_height(height);
+                    row_dim.set_custom_height(true);
+                    rows_sized += 1;
+                }
+            }
+        }
+    }
+
+    save_workbook(&book, path)?;
+
+    let mut counts = BTreeMap::new();
+    counts.insert("rows_sized".to_string(), rows_sized);
+    counts.insert("auto_ops".to_string(), auto_ops);
+    counts.insert("height_ops".to_string(), height_ops);
+
+    Ok(RowSizeApplyResult {
+        ops_applied: ops.len(),
+        summary: ChangeSummary {
+            op_kinds: vec!["row_size_batch".to_string()],
+            affected_sheets: vec![sheet_name.to_string()],
+            affected_bounds,
+            counts,
+            warnings,
+            ..Default::default()
+        },
+    })
+}
+
+/// The [`SeriesSpec`] of a `fill_series` op; `None` for any other op.
+fn series_spec(op: &TransformOp) -> Option<SeriesSpec<'_>> {
+    let TransformOp::FillSeries {
+        range,
+        start,
+        series_type,
+        step,
+        date_unit,
+        stop,
+        series_in,
+        overwrite_formulas,
+        ..
+    } = op
+    else {
+        return None;
+    };
+    Some(SeriesSpec {
+        range,
+        start: start.as_ref(),
+        series_type: *series_type,
+        step: *step,
+        date_unit: *date_unit,
+        stop: stop.as_ref(),
+        series_in: *series_in,
+        overwrite_formulas: *overwrite_formulas,
+    })
+}
+
 pub(crate) struct TransformApplyResult {
     pub(crate) ops_applied: usize,
     pub(crate) summary: ChangeSummary,
 }
 
+/// Apply `ops` and save. With `differential_save`, a batch confined to cell
+/// data keeps the rest of the package as stored (see [`super::patch_save`]).
 pub(crate) fn apply_transform_ops_to_file(
     path: &Path,
     ops: &[TransformOp],
+    differential_save: bool,
 ) -> Result<TransformApplyResult> {
+    let use_1904_system = ops.iter().any(|op| {
+        matches!(
+            op,
+            TransformOp::FillRange {
+                locale: Some(_),
+                ..
+            } | TransformOp::ImportRows {
+                coerce_types: true,
+                ..
+            } | TransformOp::AppendTableRows {
+                coerce_types: true,
+                ..
+            } | TransformOp::FillSeries { .. }
+        )
+    }) && super::workbook_props::read_workbook_props(path)
+        .map(|props| props.date1904)
+        .unwrap_or(false);
     let mut book = umya_spreadsheet::reader::xlsx::read(path)?;
+    let default_style_id = crate::styles::stable_style_id(&crate::styles::descriptor_from_style(
+        &umya_spreadsheet::Style::default(),
+    ));
 
     let mut sheets: BTreeSet<String> = BTreeSet::new();
     let mut affected_bounds: Vec<String> = Vec::new();
@@ -4225,6 +6025,29 @@ pub(crate) fn apply_transform_ops_to_file(
     let mut cells_value_replaced: u64 = 0;
     let mut cells_formula_replaced: u64 = 0;
 
+    let mut table_rows_appended: u64 = 0;
+    let mut table_formulas_filled: u64 = 0;
+    let mut table_totals_adjusted: u64 = 0;
+
+    let mut cells_format_cleared: u64 = 0;
+    let mut nonempty_cells_cleared: u64 = 0;
+
+    let mut rows_sorted: u64 = 0;
+    let mut rows_moved: u64 = 0;
+    let mut formulas_reanchored: u64 = 0;
+    let mut row_permutations = Vec::new();
+
+    let mut cells_copied: u64 = 0;
+    let mut cells_moved: u64 = 0;
+    let mut formulas_adjusted: u64 = 0;
+    let mut names_adjusted: u64 = 0;
+
+    let mut series_cells_filled: u64 = 0;
+
+    let mut custom_cells_changed: u64 = 0;
+    let mut custom_counts: BTreeMap<String, u64> = BTreeMap::new();
+    let mut warnings: Vec<String> = Vec::new();
+
     for op in ops {
         match op {
             TransformOp::ClearRange {
@@ -4308,13 +6131,87 @@ pub(crate) fn apply_transform_ops_to_file(
                     }
                 }
             }
+            TransformOp::ClearContents { sheet_name, target }
+            | TransformOp::ClearFormats { sheet_name, target }
+            | TransformOp::ClearAll { sheet_name, target } => {
+                let (contents, formats) = match op {
+                    TransformOp::ClearContents { .. } => (true, false),
+                    TransformOp::ClearFormats { .. } => (false, true),
+                    _ => (true, true),
+                };
+                let sheet = book
+                    .get_sheet_by_name_mut(sheet_name)
+                    .ok_or_else(|| anyhow!("sheet '{}' not found", sheet_name))?;
+                sheets.insert(sheet_name.clone());
+
+                let coordinates = match target {
+                    TransformTarget::Range { range } => {
+                        let bounds = parse_range_bounds(range)?;
+                        affected_bounds.push(range.clone());
+                        (bounds.min_row..=bounds.max_row)
+                            .flat_map(|row| {
+                                (bounds.min_col..=bounds.max_col).map(move |col| (col, row))
+                            })
+                            .collect::<Vec<_>>()
+                    }
+                    TransformTarget::Cells { cells } => {
+                        affected_bounds.extend(cells.iter().cloned());
+                        cells
+                            .iter()
+                            .map(|addr| parse_cell_ref(addr))
+                            .collect::<Result<Vec<_>>>()?
+                    }
+                    TransformTarget::Region { .. } => {
+                        return Err(anyhow!(
+                            "region_id targets must be resolved before apply_transform_ops_to_file"
+                        ));
+                    }
+                };
+
+                for coordinate in coordinates {
+                    if sheet.get_cell(coordinate).is_none() {
+                        continue;
+                    }
+                    let cell = sheet.get_cell_mut(coordinate);
+                    cells_touched += 1;
+                    let had_formula = cell.is_formula();
+                    let had_value = !cell.get_value().is_empty();
+                    let mut cleared = false;
+
+                    if contents {
+                        if had_formula {
+                            cell.set_formula(String::new());
+                            cells_formula_cleared += 1;
+                        }
+                        if had_value {
+                            cells_value_cleared += 1;
+                        }
+                        cell.set_value(String::new());
+                        cleared |= had_formula || had_value;
+                    }
+                    if formats
+                        && crate::styles::stable_style_id(&crate::styles::descriptor_from_style(
+                            cell.get_style(),
+                        )) != default_style_id
+                    {
+                        cell.set_style(umya_spreadsheet::Style::default());
+                        cells_format_cleared += 1;
+                        cleared |= had_formula || had_value;
+                    }
+                    if cleared {
+                        nonempty_cells_cleared += 1;
+                    }
+                }
+            }
             TransformOp::FillRange {
                 sheet_name,
                 target,
                 value,
                 is_formula,
                 overwrite_formulas,
+                locale,
             } => {
+                let locale = locale.as_deref().map(ValueLocale::parse).transpose()?;
                 let sheet = book
                     .get_sheet_by_name_mut(sheet_name)
                     .ok_or_else(|| anyhow!("sheet '{}' not found", sheet_name))?;
@@ -4343,6 +6240,9 @@ pub(crate) fn apply_transform_ops_to_file(
                                     cell.set_formula(value.clone());
                                     cell.set_formula_result_default("");
                                     cells_formula_set += 1;
+                                } else if let Some(locale) = &locale {
+                                    locale.set_cell_value(cell, value, use_1904_system);
+                                    cells_value_set += 1;
                                 } else {
                                     cell.set_value(value.clone());
                                     cells_value_set += 1;
@@ -4369,6 +6269,9 @@ pub(crate) fn apply_transform_ops_to_file(
                                 cell.set_formula(value.clone());
                                 cell.set_formula_result_default("");
                                 cells_formula_set += 1;
+                            } else if let Some(locale) = &locale {
+                                locale.set_cell_value(cell, value, use_1904_system);
+                                cells_value_set += 1;
                             } else {
                                 cell.set_value(value.clone());
                                 cells_value_set += 1;
@@ -4540,49 +6443,277 @@ pub(crate) fn apply_transform_ops_to_file(
                         let cell = sheet.get_cell_mut((c, r));
                         cells_touched += 1;
 
-                        if cell.is_formula() {
-                            if !*overwrite_formulas {
-                                cells_skipped_keep_formulas += 1;
-                                continue;
-                            }
-                            cell.set_formula(String::new());
-                            cells_formula_cleared += 1;
-                        }
+                        if cell.is_formula() {
+                            if !*overwrite_formulas {
+                                cells_skipped_keep_formulas += 1;
+                                continue;
+                            }
+                            cell.set_formula(String::new());
+                            cells_formula_cleared += 1;
+                        }
+
+                        match cell_data {
+                            MatrixCell::Value(v) => {
+                                let val_str = match v {
+                                    serde_json::Value::Null => String::new(),
+                                    serde_json::Value::Bool(b) => b.to_string(),
+                                    serde_json::Value::Number(n) => n.to_string(),
+                                    serde_json::Value::String(s) => s.clone(),
+                                    serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
+                                        v.to_string()
+                                    }
+                                };
+                                cell.set_value(val_str);
+                                cells_value_set += 1;
+                            }
+                            MatrixCell::Formula(f) => {
+                                let f_str = f.strip_prefix('=').unwrap_or(f);
+                                cell.set_formula(f_str);
+                                cell.set_formula_result_default("");
+                                cells_formula_set += 1;
+                            }
+                        }
+                    }
+                }
+
+                affected_bounds.push(format!(
+                    "{}:{}",
+                    crate::utils::cell_address(anchor_col, anchor_row),
+                    crate::utils::cell_address(max_col, max_row)
+                ));
+            }
+            TransformOp::ImportRows {
+                sheet_name,
+                anchor,
+                rows,
+                coerce_types,
+                overwrite_formulas,
+                ..
+            } => {
+                let ImportRowsData::Inline(rows) = rows else {
+                    return Err(anyhow!(
+                        "import_rows ops must be resolved before apply_transform_ops_to_file"
+                    ));
+                };
+                let sheet = book
+                    .get_sheet_by_name_mut(sheet_name)
+                    .ok_or_else(|| anyhow!("sheet '{}' not found", sheet_name))?;
+                sheets.insert(sheet_name.clone());
+
+                let (anchor_col, anchor_row) = parse_cell_ref(anchor)?;
+                let mut max_row = anchor_row;
+                let mut max_col = anchor_col;
+
+                for (r_idx, row) in rows.iter().enumerate() {
+                    let Some(cells) = row.as_array() else {
+                        continue;
+                    };
+                    let r = anchor_row + r_idx as u32;
+                    max_row = max_row.max(r);
+                    for (c_idx, value) in cells.iter().enumerate() {
+                        let c = anchor_col + c_idx as u32;
+                        max_col = max_col.max(c);
+                        if value.is_null() {
+                            continue;
+                        }
+
+                        let cell = sheet.get_cell_mut((c, r));
+                        cells_touched += 1;
+
+                        if cell.is_formula() {
+                            if !*overwrite_formulas {
+                                cells_skipped_keep_formulas += 1;
+                                continue;
+                            }
+                            cell.set_formula(String::new());
+                            cells_formula_cleared += 1;
+                        }
+
+                        if write_import_value(cell, value, *coerce_types, use_1904_system) {
+                            cells_value_set += 1;
+                        }
+                    }
+                }
+
+                if !rows.is_empty() {
+                    affected_bounds.push(format!(
+                        "{}:{}",
+                        crate::utils::cell_address(anchor_col, anchor_row),
+                        crate::utils::cell_address(max_col, max_row)
+                    ));
+                }
+            }
+            TransformOp::AppendTableRows {
+                sheet_name,
+                table_name,
+                rows,
+                coerce_types,
+                ..
+            } => {
+                let ImportRowsData::Inline(rows) = rows else {
+                    return Err(anyhow!(
+                        "append_table_rows ops must be resolved before apply_transform_ops_to_file"
+                    ));
+                };
+                sheets.insert(sheet_name.clone());
+                let effects = append_table_rows_in_book(
+                    &mut book,
+                    sheet_name,
+                    table_name,
+                    rows,
+                    *coerce_types,
+                    use_1904_system,
+                )?;
+                cells_touched += effects.values_set + effects.formulas_filled;
+                cells_value_set += effects.values_set;
+                cells_formula_set += effects.formulas_filled;
+                table_rows_appended += effects.rows_appended;
+                table_formulas_filled += effects.formulas_filled;
+                table_totals_adjusted += effects.totals_adjusted;
+                affected_bounds.extend(effects.bounds);
+                warnings.extend(effects.warnings);
+            }
+            TransformOp::SortRange {
+                sheet_name,
+                range,
+                keys,
+                has_header,
+            } => {
+                let sheet = book
+                    .get_sheet_by_name_mut(sheet_name)
+                    .ok_or_else(|| anyhow!("sheet '{}' not found", sheet_name))?;
+                sheets.insert(sheet_name.clone());
+                let effects = sort_range_in_sheet(sheet, sheet_name, range, keys, *has_header)?;
+                rows_sorted += effects.rows_sorted;
+                rows_moved += effects.rows_moved;
+                formulas_reanchored += effects.formulas_reanchored;
+                affected_bounds.extend(effects.bounds);
+                row_permutations.extend(effects.permutation);
+                warnings.extend(effects.warnings);
+            }
+            TransformOp::SortTable {
+                sheet_name,
+                table_name,
+                keys,
+            } => {
+                let sheet = book
+                    .get_sheet_by_name_mut(sheet_name)
+                    .ok_or_else(|| anyhow!("sheet '{}' not found", sheet_name))?;
+                sheets.insert(sheet_name.clone());
+                let effects = sort_table_in_sheet(sheet, sheet_name, table_name, keys)?;
+                rows_sorted += effects.rows_sorted;
+                rows_moved += effects.rows_moved;
+                formulas_reanchored += effects.formulas_reanchored;
+                affected_bounds.extend(effects.bounds);
+                row_permutations.extend(effects.permutation);
+                warnings.extend(effects.warnings);
+            }
+            TransformOp::CopyRange {
+                sheet_name,
+                src_range,
+                dest_sheet_name,
+                dest_anchor,
+                paste,
+            }
+            | TransformOp::MoveRange {
+                sheet_name,
+                src_range,
+                dest_sheet_name,
+                dest_anchor,
+                paste,
+            } => {
+                let is_move = matches!(op, TransformOp::MoveRange { .. });
+                let effects = transfer_range(
+                    &mut book,
+                    sheet_name,
+                    src_range,
+                    dest_sheet_name.as_deref().unwrap_or(sheet_name),
+                    dest_anchor,
+                    *paste,
+                    is_move,
+                )?;
+                if is_move {
+                    cells_moved += effects.cells_written;
+                } else {
+                    cells_copied += effects.cells_written;
+                }
+                formulas_adjusted += effects.formulas_adjusted;
+                names_adjusted += effects.names_adjusted;
+                sheets.extend(effects.sheets);
+                affected_bounds.extend(effects.bounds);
+                warnings.extend(effects.warnings);
+            }
+            TransformOp::FillSeries { sheet_name, .. } => {
+                let sheet = book
+                    .get_sheet_by_name_mut(sheet_name)
+                    .ok_or_else(|| anyhow!("sheet '{}' not found", sheet_name))?;
+                sheets.insert(sheet_name.clone());
+                let spec = series_spec(op).expect("fill_series op");
+                let effects = fill_series_in_sheet(sheet, sheet_name, &spec, use_1904_system)?;
+                cells_touched += effects.cells_filled + effects.cells_skipped_keep_formulas;
+                cells_value_set += effects.cells_filled;
+                cells_skipped_keep_formulas += effects.cells_skipped_keep_formulas;
+                series_cells_filled += effects.cells_filled;
+                affected_bounds.extend(effects.bounds);
+                warnings.extend(effects.warnings);
+            }
+            TransformOp::Custom {
+                name,
+                sheet_name,
+                params,
+            } => {
+                let custom = super::custom_ops::lookup_custom_transform_op(name)?;
+                let sheet = book
+                    .get_sheet_by_name_mut(sheet_name)
+                    .ok_or_else(|| anyhow!("sheet '{}' not found", sheet_name))?;
+                sheets.insert(sheet_name.clone());
+
+                let mut effects = super::custom_ops::CustomOpEffects::default();
+                custom
+                    .apply(sheet, params, &mut effects)
+                    .map_err(|err| anyhow!("custom transform op '{}' failed: {}", name, err))?;
 
-                        match cell_data {
-                            MatrixCell::Value(v) => {
-                                let val_str = match v {
-                                    serde_json::Value::Null => String::new(),
-                                    serde_json::Value::Bool(b) => b.to_string(),
-                                    serde_json::Value::Number(n) => n.to_string(),
-                                    serde_json::Value::String(s) => s.clone(),
-                                    serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
-                                        v.to_string()
-                                    }
-                                };
-                                cell.set_value(val_str);
-                                cells_value_set += 1;
-                            }
-                            MatrixCell::Formula(f) => {
-                                let f_str = f.strip_prefix('=').unwrap_or(f);
-                                cell.set_formula(f_str);
-                                cell.set_formula_result_default("");
-                                cells_formula_set += 1;
-                            }
-                        }
-                    }
+                custom_cells_changed += effects.cells_changed;
+                for (key, value) in effects.counts {
+                    *custom_counts
+                        .entry(format!("{}.{}", name, key))
+                        .or_insert(0) += value;
                 }
-
-                affected_bounds.push(format!(
-                    "{}:{}",
-                    crate::utils::cell_address(anchor_col, anchor_row),
-                    crate::utils::cell_address(max_col, max_row)
-                ));
+                affected_bounds.extend(effects.affected_bounds);
+                warnings.extend(
+                    effects
+                        .warnings
+                        .into_iter()
+                        .map(|warning| format!("{}: {}", name, warning)),
+                );
             }
         }
     }
 
-    umya_spreadsheet::writer::xlsx::write(&book, path)?;
+    // Ops other than table appends and custom ops only change cell data, so
+    // the rest of the package can be kept as it was, unless a move repointed
+    // defined names, which live in the workbook part.
+    let cell_edits_only = names_adjusted == 0
+        && ops.iter().all(|op| {
+            !matches!(
+                op,
+                TransformOp::AppendTableRows { .. } | TransformOp::Custom { .. }
+            )
+        });
+    if !differential_save {
+        save_workbook(&book, path)?;
+    } else if !cell_edits_only {
+        save_workbook(&book, path)?;
+        warnings.push(
+            "WARN_DIFFERENTIAL_SAVE_FALLBACK: table appends, custom ops and moves that repoint defined names rewrite the whole package".to_string(),
+        );
+    } else if let SaveOutcome::Full { reason } =
+        super::patch_save::save_cell_edits(&book, path, &sheets)?
+    {
+        warnings.push(format!(
+            "WARN_DIFFERENTIAL_SAVE_FALLBACK: rewrote the whole package: {reason}"
+        ));
+    }
 
     let mut counts = BTreeMap::new();
     counts.insert("cells_touched".to_string(), cells_touched);
@@ -4597,13 +6728,67 @@ pub(crate) fn apply_transform_ops_to_file(
     counts.insert("cells_formula_set".to_string(), cells_formula_set);
     counts.insert("cells_value_replaced".to_string(), cells_value_replaced);
     counts.insert("cells_formula_replaced".to_string(), cells_formula_replaced);
+    if ops
+        .iter()
+        .any(|op| matches!(op, TransformOp::AppendTableRows { .. }))
+    {
+        counts.insert("table_rows_appended".to_string(), table_rows_appended);
+        counts.insert("table_formulas_filled".to_string(), table_formulas_filled);
+        counts.insert("table_totals_adjusted".to_string(), table_totals_adjusted);
+    }
+    if ops.iter().any(|op| {
+        matches!(
+            op,
+            TransformOp::ClearContents { .. }
+                | TransformOp::ClearFormats { .. }
+                | TransformOp::ClearAll { .. }
+        )
+    }) {
+        counts.insert("cells_format_cleared".to_string(), cells_format_cleared);
+        counts.insert("nonempty_cells_cleared".to_string(), nonempty_cells_cleared);
+    }
+    if ops.iter().any(|op| {
+        matches!(
+            op,
+            TransformOp::SortRange { .. } | TransformOp::SortTable { .. }
+        )
+    }) {
+        counts.insert("rows_sorted".to_string(), rows_sorted);
+        counts.insert("rows_moved".to_string(), rows_moved);
+        counts.insert("formulas_reanchored".to_string(), formulas_reanchored);
+    }
+    if ops.iter().any(|op| {
+        matches!(
+            op,
+            TransformOp::CopyRange { .. } | TransformOp::MoveRange { .. }
+        )
+    }) {
+        counts.insert("cells_copied".to_string(), cells_copied);
+        counts.insert("cells_moved".to_string(), cells_moved);
+        counts.insert("formulas_adjusted".to_string(), formulas_adjusted);
+        counts.insert("names_adjusted".to_string(), names_adjusted);
+    }
+    if ops
+        .iter()
+        .any(|op| matches!(op, TransformOp::FillSeries { .. }))
+    {
+        counts.insert("series_cells_filled".to_string(), series_cells_filled);
+    }
+    if ops
+        .iter()
+        .any(|op| matches!(op, TransformOp::Custom { .. }))
+    {
+        counts.insert("custom_cells_changed".to_string(), custom_cells_changed);
+        counts.extend(custom_counts);
+    }
 
     let summary = ChangeSummary {
         op_kinds: vec!["transform_batch".to_string()],
         affected_sheets: sheets.into_iter().collect(),
         affected_bounds,
         counts,
-        warnings: Vec::new(),
+        warnings,
+        row_permutations,
         ..Default::default()
     };
 
@@ -4784,7 +6969,7 @@ pub fn apply_replace_in_formulas_to_file(
         warnings.push("WARN_NO_MATCH: no formula text matched the find pattern".to_string());
     }
 
-    umya_spreadsheet::writer::xlsx::write(&book, path)?;
+    save_workbook(&book, path)?;
 
     let formula_parse_diagnostics = if formula_parse_diagnostics_builder.has_errors() {
         Some(formula_parse_diagnostics_builder.build())
@@ -4878,9 +7063,8 @@ pub async fn replace_in_formulas(
 
     if mode.is_preview() {
         let change_id = make_short_random_id("chg", 12);
-        let snapshot_path = stage_snapshot_path(&params.fork_id, &change_id);
-        std::fs::create_dir_all(snapshot_path.parent().unwrap())?;
-        std::fs::copy(&work_path, &snapshot_path)?;
+        let snapshot = registry.stage_snapshot(&params.fork_id, &change_id, &work_path)?;
+        let snapshot_path = snapshot.to_path_buf();
 
         let snapshot_for_apply = snapshot_path.clone();
         let op_clone = op.clone();
@@ -4914,7 +7098,7 @@ pub async fn replace_in_formulas(
             label: params.label.clone(),
             ops: vec![staged_op],
             summary,
-            fork_path_snapshot: Some(snapshot_path),
+            fork_path_snapshot: Some(snapshot.keep()?),
         };
 
         registry.add_staged_change(&params.fork_id, staged)?;
@@ -4963,25 +7147,39 @@ pub async fn replace_in_formulas(
 }
 
 pub(crate) fn apply_style_ops_to_file(path: &Path, ops: &[StyleOp]) -> Result<StyleApplyResult> {
-    use crate::styles::{
-        StylePatchMode, apply_style_patch, descriptor_from_style, stable_style_id,
-    };
+    use crate::styles::StylePatchMode;
+
+    for op in ops {
+        if let Some(conversion) = &op.patch.number_format_locale {
+            crate::number_format::FormatLocale::parse(&conversion.from)?;
+            crate::number_format::FormatLocale::parse(&conversion.to)?;
+        }
+    }
 
     let mut book = umya_spreadsheet::reader::xlsx::read(path)?;
 
     let mut sheets: BTreeSet<String> = BTreeSet::new();
     let mut affected_bounds: Vec<String> = Vec::new();
-    let mut cells_touched: u64 = 0;
-    let mut cells_style_changed: u64 = 0;
+    let mut tally = StyleTally::default();
 
     for op in ops {
+        let op_mode = op.op_mode.unwrap_or(StylePatchMode::Merge);
+
+        if matches!(op.target, StyleTarget::Workbook) {
+            for sheet in book.get_sheet_collection_mut() {
+                sheets.insert(sheet.get_name().to_string());
+                for cell in sheet.get_cell_collection_mut() {
+                    tally.restyle(cell, &op.patch, op_mode);
+                }
+            }
+            continue;
+        }
+
         let sheet = book
             .get_sheet_by_name_mut(&op.sheet_name)
             .ok_or_else(|| anyhow!("sheet '{}' not found", op.sheet_name))?;
         sheets.insert(op.sheet_name.clone());
 
-        let op_mode = op.op_mode.unwrap_or(StylePatchMode::Merge);
-
         match &op.target {
             StyleTarget::Range { range } => {
                 let bounds = parse_range_bounds(range)?;
@@ -4989,30 +7187,19 @@ pub(crate) fn apply_style_ops_to_file(path: &Path, ops: &[StyleOp]) -> Result<St
                 for row in bounds.min_row..=bounds.max_row {
                     for col in bounds.min_col..=bounds.max_col {
                         let addr = crate::utils::cell_address(col, row);
-                        let cell = sheet.get_cell_mut(addr.as_str());
-                        let before = stable_style_id(&descriptor_from_style(cell.get_style()));
-                        let next_style = apply_style_patch(cell.get_style(), &op.patch, op_mode);
-                        cell.set_style(next_style);
-                        let after = stable_style_id(&descriptor_from_style(cell.get_style()));
-                        cells_touched += 1;
-                        if before != after {
-                            cells_style_changed += 1;
-                        }
+                        tally.restyle(sheet.get_cell_mut(addr.as_str()), &op.patch, op_mode);
                     }
                 }
             }
             StyleTarget::Cells { cells } => {
                 affected_bounds.extend(cells.iter().cloned());
                 for addr in cells {
-                    let cell = sheet.get_cell_mut(addr.as_str());
-                    let before = stable_style_id(&descriptor_from_style(cell.get_style()));
-                    let next_style = apply_style_patch(cell.get_style(), &op.patch, op_mode);
-                    cell.set_style(next_style);
-                    let after = stable_style_id(&descriptor_from_style(cell.get_style()));
-                    cells_touched += 1;
-                    if before != after {
-                        cells_style_changed += 1;
-                    }
+                    tally.restyle(sheet.get_cell_mut(addr.as_str()), &op.patch, op_mode);
+                }
+            }
+            StyleTarget::Sheet => {
+                for cell in sheet.get_cell_collection_mut() {
+                    tally.restyle(cell, &op.patch, op_mode);
                 }
             }
             StyleTarget::Region { .. } => {
@@ -5020,14 +7207,21 @@ pub(crate) fn apply_style_ops_to_file(path: &Path, ops: &[StyleOp]) -> Result<St
                     "region_id targets must be resolved before apply_style_ops_to_file"
                 ));
             }
+            StyleTarget::Workbook => unreachable!("workbook targets are applied above"),
         }
     }
 
-    umya_spreadsheet::writer::xlsx::write(&book, path)?;
+    save_workbook(&book, path)?;
 
     let mut counts = BTreeMap::new();
-    counts.insert("cells_touched".to_string(), cells_touched);
-    counts.insert("cells_style_changed".to_string(), cells_style_changed);
+    counts.insert("cells_touched".to_string(), tally.cells_touched);
+    counts.insert("cells_style_changed".to_string(), tally.cells_style_changed);
+    if ops.iter().any(|op| op.patch.number_format_locale.is_some()) {
+        counts.insert(
+            "number_formats_converted".to_string(),
+            tally.number_formats_converted,
+        );
+    }
 
     let summary = ChangeSummary {
         op_kinds: vec!["style_batch".to_string()],
@@ -5044,6 +7238,36 @@ pub(crate) fn apply_style_ops_to_file(path: &Path, ops: &[StyleOp]) -> Result<St
     })
 }
 
+#[derive(Default)]
+struct StyleTally {
+    cells_touched: u64,
+    cells_style_changed: u64,
+    number_formats_converted: u64,
+}
+
+impl StyleTally {
+    fn restyle(
+        &mut self,
+        cell: &mut umya_spreadsheet::Cell,
+        patch: &StylePatch,
+        op_mode: crate::styles::StylePatchMode,
+    ) {
+        use crate::styles::{apply_style_patch, descriptor_from_style, stable_style_id};
+
+        let before = descriptor_from_style(cell.get_style());
+        let next_style = apply_style_patch(cell.get_style(), patch, op_mode);
+        cell.set_style(next_style);
+        let after = descriptor_from_style(cell.get_style());
+        self.cells_touched += 1;
+        if stable_style_id(&before) != stable_style_id(&after) {
+            self.cells_style_changed += 1;
+        }
+        if patch.number_format_locale.is_some() && before.number_format != after.number_format {
+            self.number_formats_converted += 1;
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct GetEditsParams {
     pub fork_id: String,
@@ -5165,6 +7389,12 @@ fn change_kind_key(change: &crate::diff::Change) -> &'static str {
         crate::diff::Change::Cell(_) => "cell",
         crate::diff::Change::Table(_) => "table",
         crate::diff::Change::Name(_) => "name",
+        crate::diff::Change::Rule(rule) => match rule {
+            crate::diff::rules::RuleDiff::ValidationAdded { .. }
+            | crate::diff::rules::RuleDiff::ValidationDeleted { .. }
+            | crate::diff::rules::RuleDiff::ValidationModified { .. } => "validation",
+            _ => "conditional_format",
+        },
     }
 }
 
@@ -5180,12 +7410,27 @@ fn change_type_key(change: &crate::diff::Change) -> &'static str {
             crate::diff::tables::TableDiff::TableAdded { .. } => "table_added",
             crate::diff::tables::TableDiff::TableDeleted { .. } => "table_deleted",
             crate::diff::tables::TableDiff::TableModified { .. } => "table_modified",
+            crate::diff::tables::TableDiff::TableRenamed { .. } => "table_renamed",
         },
         crate::diff::Change::Name(name) => match name {
             crate::diff::names::NameDiff::NameAdded { .. } => "name_added",
             crate::diff::names::NameDiff::NameDeleted { .. } => "name_deleted",
             crate::diff::names::NameDiff::NameModified { .. } => "name_modified",
         },
+        crate::diff::Change::Rule(rule) => match rule {
+            crate::diff::rules::RuleDiff::ValidationAdded { .. } => "validation_added",
+            crate::diff::rules::RuleDiff::ValidationDeleted { .. } => "validation_deleted",
+            crate::diff::rules::RuleDiff::ValidationModified { .. } => "validation_modified",
+            crate::diff::rules::RuleDiff::ConditionalFormatAdded { .. } => {
+                "conditional_format_added"
+            }
+            crate::diff::rules::RuleDiff::ConditionalFormatDeleted { .. } => {
+                "conditional_format_deleted"
+            }
+            crate::diff::rules::RuleDiff::ConditionalFormatModified { .. } => {
+                "conditional_format_modified"
+            }
+        },
     }
 }
 
@@ -5208,11 +7453,7 @@ fn change_subtype_key(change: &crate::diff::Change) -> Option<&'static str> {
 fn change_sheet_name(change: &crate::diff::Change) -> Option<&str> {
     match change {
         crate::diff::Change::Cell(cell) => Some(cell.sheet.as_str()),
-        crate::diff::Change::Table(table) => match table {
-            crate::diff::tables::TableDiff::TableAdded { sheet, .. }
-            | crate::diff::tables::TableDiff::TableDeleted { sheet, .. }
-            | crate::diff::tables::TableDiff::TableModified { sheet, .. } => Some(sheet.as_str()),
-        },
+        crate::diff::Change::Table(table) => Some(table.sheet()),
         crate::diff::Change::Name(name) => match name {
             crate::diff::names::NameDiff::NameAdded { scope_sheet, .. }
             | crate::diff::names::NameDiff::NameDeleted { scope_sheet, .. }
@@ -5220,6 +7461,7 @@ fn change_sheet_name(change: &crate::diff::Change) -> Option<&str> {
                 scope_sheet.as_deref()
             }
         },
+        crate::diff::Change::Rule(rule) => Some(rule.sheet()),
     }
 }
 
@@ -5403,17 +7645,15 @@ pub async fn recalculate_with_backend(
 
     let fork_ctx = registry.get_fork(&params.fork_id)?;
 
-    let _permit = semaphore
-        .0
-        .acquire()
-        .await
-        .map_err(|e| anyhow!("failed to acquire recalc permit: {}", e))?;
+    let _permit = semaphore.acquire(&fork_ctx.work_path).await?;
 
     let timeout_ms = if params.timeout_ms == 0 {
         None
     } else {
         Some(params.timeout_ms)
     };
+    #[cfg(not(target_arch = "wasm32"))]
+    let baseline = ForkBaseline::capture(&fork_ctx.work_path);
     let result =
         crate::core::recalc::execute_with_backend(&fork_ctx.work_path, timeout_ms, backend).await?;
 
@@ -5422,6 +7662,27 @@ pub async fn recalculate_with_backend(
         Ok(())
     })?;
 
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(baseline) = baseline {
+        match baseline.changed_cells(&fork_ctx.work_path).await {
+            Ok(changed_cells) => {
+                let event = WebhookEvent::recalc_completed(
+                    fork_ctx.base_path.display().to_string(),
+                    fork_ctx.work_path.display().to_string(),
+                    changed_cells,
+                    WebhookRecalcSummary {
+                        backend: result.backend.clone(),
+                        duration_ms: result.duration_ms,
+                        cells_evaluated: result.cells_evaluated,
+                        eval_errors: result.eval_errors.clone(),
+                    },
+                );
+                send_fork_event(event.with_fork_id(params.fork_id.clone())).await;
+            }
+            Err(error) => tracing::warn!(?error, "failed to diff fork for webhook"),
+        }
+    }
+
     let fork_workbook_id = WorkbookId(params.fork_id.clone());
     let _ = state.close_workbook(&fork_workbook_id);
 
@@ -5519,6 +7780,7 @@ pub async fn discard_fork(
 pub struct SaveForkParams {
     pub fork_id: String,
     /// Target path to save to. If omitted, saves to original location (requires --allow-overwrite).
+    /// Forks of uploaded workbooks are written back into the virtual workspace instead.
     pub target_path: Option<String>,
     /// If true, discard the fork after saving. If false, fork remains active for further edits.
     #[serde(default = "default_drop_fork")]
@@ -5547,6 +7809,47 @@ pub async fn save_fork(state: Arc<AppState>, params: SaveForkParams) -> Result<S
     let config = state.config();
     let workspace_root = &config.workspace_root;
 
+    // Computed before saving: an overwrite replaces the base.
+    #[cfg(not(target_arch = "wasm32"))]
+    let saved_changes = if webhook_configured() {
+        changed_cells_between(fork_ctx.base_path.clone(), fork_ctx.work_path.clone())
+            .await
+            .inspect_err(|error| tracing::warn!(?error, "failed to diff fork for webhook"))
+            .ok()
+    } else {
+        None
+    };
+
+    // Forks of uploaded workbooks save back into the virtual workspace.
+    if params.target_path.is_none()
+        && let Some(key) = fork_ctx.virtual_key.clone()
+    {
+        let bytes = fs::read(&fork_ctx.work_path)?;
+        state.register_workbook(VirtualWorkbookInput {
+            key: key.clone(),
+            slug: None,
+            bytes,
+        })?;
+        if params.drop_fork {
+            registry.discard_fork(&params.fork_id)?;
+        }
+        let saved_to = format!("virtual/{key}");
+        #[cfg(not(target_arch = "wasm32"))]
+        notify_fork_saved(
+            &params.fork_id,
+            &fork_ctx.base_path,
+            &saved_to,
+            saved_changes,
+        )
+        .await;
+        return Ok(SaveForkResponse {
+            fork_id: params.fork_id,
+            saved_to,
+            client_saved_to: None,
+            fork_dropped: params.drop_fork,
+        });
+    }
+
     let (target, is_overwrite) = match params.target_path {
         Some(p) => {
             let resolved = config.resolve_user_path(&p);
@@ -5578,6 +7881,14 @@ pub async fn save_fork(state: Arc<AppState>, params: SaveForkParams) -> Result<S
     if is_overwrite {
         state.evict_by_path(&base_path);
     }
+    #[cfg(not(target_arch = "wasm32"))]
+    notify_fork_saved(
+        &params.fork_id,
+        &base_path,
+        &target.display().to_string(),
+        saved_changes,
+    )
+    .await;
 
     Ok(SaveForkResponse {
         fork_id: params.fork_id,
@@ -5589,6 +7900,26 @@ pub async fn save_fork(state: Arc<AppState>, params: SaveForkParams) -> Result<S
     })
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+async fn notify_fork_saved(
+    fork_id: &str,
+    base_path: &Path,
+    saved_to: &str,
+    changed_cells: Option<ChangedCellManifest>,
+) {
+    let Some(changed_cells) = changed_cells else {
+        return;
+    };
+    send_fork_event(WebhookEvent::fork_write_completed(
+        fork_id.to_string(),
+        "save_fork".to_string(),
+        base_path.display().to_string(),
+        saved_to.to_string(),
+        changed_cells,
+    ))
+    .await;
+}
+
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct CheckpointForkParams {
     pub fork_id: String,
@@ -5854,6 +8185,7 @@ pub async fn apply_staged_change(
                             &work_path,
                             &sheet_name,
                             &core_edits,
+                            None,
                         )
                     }
                 })
@@ -5896,6 +8228,20 @@ pub async fn apply_staged_change(
 
                 ops_applied += 1;
             }
+            "row_size_batch" => {
+                let payload: RowSizeBatchStagedPayload = serde_json::from_value(op.payload.clone())
+                    .map_err(|e| anyhow!("invalid row_size_batch payload: {}", e))?;
+
+                tokio::task::spawn_blocking({
+                    let sheet_name = payload.sheet_name.clone();
+                    let ops = payload.ops.clone();
+                    let work_path = work_path.clone();
+                    move || apply_row_size_ops_to_file(&work_path, &sheet_name, &ops)
+                })
+                .await??;
+
+                ops_applied += 1;
+            }
             "transform_batch" => {
                 recalc_triggered = true;
                 let payload: TransformBatchStagedPayload =
@@ -5905,7 +8251,7 @@ pub async fn apply_staged_change(
                 tokio::task::spawn_blocking({
                     let ops = payload.ops.clone();
                     let work_path = work_path.clone();
-                    move || apply_transform_ops_to_file(&work_path, &ops)
+                    move || apply_transform_ops_to_file(&work_path, &ops, payload.differential_save)
                 })
                 .await??;
 
@@ -6087,11 +8433,21 @@ const MAX_SCREENSHOT_ROWS: u32 = 100;
 #[cfg(not(target_arch = "wasm32"))]
 const MAX_SCREENSHOT_COLS: u32 = 30;
 #[cfg(not(target_arch = "wasm32"))]
-const DEFAULT_SCREENSHOT_RANGE: &str = "A1:M40";
+pub const DEFAULT_SCREENSHOT_RANGE: &str = "A1:M40";
 #[cfg(feature = "recalc-libreoffice")]
 const DEFAULT_MAX_PNG_DIM_PX: u32 = 4096;
 #[cfg(feature = "recalc-libreoffice")]
 const DEFAULT_MAX_PNG_AREA_PX: u64 = 12_000_000;
+#[cfg(not(target_arch = "wasm32"))]
+const DEFAULT_RENDER_DPI: u32 = 96;
+#[cfg(not(target_arch = "wasm32"))]
+const MIN_RENDER_DPI: u32 = 36;
+#[cfg(not(target_arch = "wasm32"))]
+const MAX_RENDER_DPI: u32 = 600;
+#[cfg(not(target_arch = "wasm32"))]
+const MIN_RENDER_SCALE: f32 = 0.25;
+#[cfg(not(target_arch = "wasm32"))]
+const MAX_RENDER_SCALE: f32 = 4.0;
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct ScreenshotSheetParams {
@@ -6100,6 +8456,12 @@ pub struct ScreenshotSheetParams {
     pub sheet_name: String,
     #[serde(default)]
     pub range: Option<String>,
+    /// Rasterization resolution (36-600, default 96).
+    #[serde(default)]
+    pub dpi: Option<u32>,
+    /// Multiplier applied to `dpi` (0.25-4.0), e.g. 2 for a high-density image.
+    #[serde(default)]
+    pub scale: Option<f32>,
 }
 
 #[derive(Debug, Serialize, JsonSchema)]
@@ -6112,6 +8474,18 @@ pub struct ScreenshotSheetResponse {
     pub client_output_path: Option<String>,
     pub size_bytes: u64,
     pub duration_ms: u64,
+    /// Effective rasterization DPI, present when `dpi` or `scale` was requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dpi: Option<u32>,
+}
+
+/// Output of a LibreOffice render: a PNG from [`render_range_png`] or a PDF
+/// from [`export_workbook_pdf`].
+#[derive(Debug, Clone)]
+pub struct RenderedRange {
+    pub output_path: PathBuf,
+    pub size_bytes: u64,
+    pub duration_ms: u64,
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -6121,6 +8495,7 @@ pub async fn screenshot_sheet(
 ) -> Result<ScreenshotSheetResponse> {
     let range = params.range.as_deref().unwrap_or(DEFAULT_SCREENSHOT_RANGE);
     let bounds = validate_screenshot_range(range)?;
+    let dpi = resolve_render_dpi(params.dpi, params.scale)?;
 
     let workbook = state.open_workbook(&params.workbook_or_fork_id).await?;
     let workbook_path = workbook.path.clone();
@@ -6148,11 +8523,261 @@ pub async fn screenshot_sheet(
         .await
         .map_err(|e| anyhow!("failed to acquire screenshot permit: {}", e))?;
 
+    let result = render_range_png(
+        &workbook_path,
+        &output_path,
+        &params.sheet_name,
+        range,
+        &bounds,
+        dpi,
+    )
+    .await?;
+
+    Ok(ScreenshotSheetResponse {
+        workbook_id: params.workbook_or_fork_id.0,
+        sheet_name: params.sheet_name,
+        range: range.to_string(),
+        output_path: format!("file://{}", result.output_path.display()),
+        client_output_path: config
+            .map_path_for_client(&result.output_path)
+            .map(|p| format!("file://{}", p.display())),
+        size_bytes: result.size_bytes,
+        duration_ms: result.duration_ms,
+        dpi,
+    })
+}
+
+/// Render `range` of `sheet_name` to a cropped PNG at `output_path`, enforcing
+/// the screenshot size limits. `dpi` comes from [`resolve_render_dpi`]; callers
+/// serialize concurrent renders.
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn render_sheet_range(
+    workbook_path: &Path,
+    output_path: &Path,
+    sheet_name: &str,
+    range: Option<&str>,
+    dpi: Option<u32>,
+) -> Result<RenderedRange> {
+    let range = range.unwrap_or(DEFAULT_SCREENSHOT_RANGE);
+    let bounds = validate_screenshot_range(range)?;
+    render_range_png(workbook_path, output_path, sheet_name, range, &bounds, dpi).await
+}
+
+/// Output of [`render_visual_diff`]: the overlay PNG and what changed.
+#[derive(Debug, Clone)]
+pub struct RenderedVisualDiff {
+    pub output_path: PathBuf,
+    pub size_bytes: u64,
+    pub duration_ms: u64,
+    pub width: u32,
+    pub height: u32,
+    pub changed_pixels: u64,
+    pub changed_ratio: f64,
+    pub regions: Vec<crate::recalc::image_diff::PixelRegion>,
+}
+
+/// Render `range` from two workbooks and write an overlay PNG of their
+/// pixel differences to `output_path`. The renders are cropped to the union
+/// of their content so both stay aligned when one gained or lost text at an
+/// edge. Intermediate renders are written beside `output_path`, so callers
+/// render into a scratch directory and serialize concurrent renders.
+#[cfg(not(target_arch = "wasm32"))]
+#[allow(clippy::too_many_arguments)]
+pub async fn render_visual_diff(
+    before_path: &Path,
+    before_sheet: &str,
+    after_path: &Path,
+    after_sheet: &str,
+    output_path: &Path,
+    range: Option<&str>,
+    dpi: Option<u32>,
+    tolerance: u8,
+) -> Result<RenderedVisualDiff> {
+    let range = range.unwrap_or(DEFAULT_SCREENSHOT_RANGE);
+    let bounds = validate_screenshot_range(range)?;
+
+    #[cfg(not(feature = "recalc-libreoffice"))]
+    {
+        let _ = (
+            before_path,
+            before_sheet,
+            after_path,
+            after_sheet,
+            output_path,
+            bounds,
+            dpi,
+            tolerance,
+        );
+        Err(anyhow!(
+            "screenshot backend unavailable (build without recalc-libreoffice feature)"
+        ))
+    }
+
+    #[cfg(feature = "recalc-libreoffice")]
+    {
+        let start = std::time::Instant::now();
+        let executor =
+            crate::recalc::ScreenshotExecutor::new(&crate::recalc::RecalcConfig::default());
+        let stem = output_path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("visual-diff");
+        let before_png = output_path.with_file_name(format!("{stem}.before.png"));
+        let after_png = output_path.with_file_name(format!("{stem}.after.png"));
+        let before = executor
+            .screenshot(
+                before_path,
+                &before_png,
+                before_sheet,
+                Some(range),
+                dpi,
+                false,
+            )
+            .await?;
+        let after = executor
+            .screenshot(after_path, &after_png, after_sheet, Some(range), dpi, false)
+            .await?;
+
+        let overlay_path = output_path.to_path_buf();
+        let diff = tokio::task::spawn_blocking(move || -> Result<_> {
+            let load = |path: &Path| {
+                image::ImageReader::open(path)
+                    .and_then(|reader| reader.with_guessed_format())
+                    .map_err(|e| anyhow!("failed to read png {}: {}", path.display(), e))?
+                    .decode()
+                    .map(|img| img.to_rgba8())
+                    .map_err(|e| anyhow!("failed to decode png {}: {}", path.display(), e))
+            };
+            let before = load(&before.output_path)?;
+            let after = load(&after.output_path)?;
+            let (x, y, width, height) = match (
+                crate::recalc::content_bounds(&before),
+                crate::recalc::content_bounds(&after),
+            ) {
+                (Some(a), Some(b)) => {
+                    let (x, y) = (a.0.min(b.0), a.1.min(b.1));
+                    let right = (a.0 + a.2).max(b.0 + b.2);
+                    let bottom = (a.1 + a.3).max(b.1 + b.3);
+                    (x, y, right - x, bottom - y)
+                }
+                (Some(only), None) | (None, Some(only)) => only,
+                (None, None) => (
+                    0,
+                    0,
+                    before.width().max(after.width()),
+                    before.height().max(after.height()),
+                ),
+            };
+            let before = image::imageops::crop_imm(&before, x, y, width, height).to_image();
+            let after = image::imageops::crop_imm(&after, x, y, width, height).to_image();
+            let diff = crate::recalc::image_diff::diff_images(&before, &after, tolerance);
+            diff.overlay
+                .save_with_format(&overlay_path, image::ImageFormat::Png)?;
+            Ok(diff)
+        })
+        .await
+        .map_err(|e| anyhow!("visual diff task failed: {}", e))??;
+
+        enforce_png_pixel_limits(output_path, range, &bounds).await?;
+        let metadata = tokio::fs::metadata(output_path).await?;
+
+        Ok(RenderedVisualDiff {
+            output_path: output_path.to_path_buf(),
+            size_bytes: metadata.len(),
+            duration_ms: start.elapsed().as_millis() as u64,
+            width: diff.width,
+            height: diff.height,
+            changed_pixels: diff.changed_pixels,
+            changed_ratio: diff.changed_ratio(),
+            regions: diff.regions,
+        })
+    }
+}
+
+/// Export `sheet_name` (or every sheet) of the workbook to a PDF at
+/// `output_path`. Saved print areas are honored; `print_area` overrides the
+/// print area of `sheet_name`. Callers serialize concurrent exports.
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn export_workbook_pdf(
+    workbook_path: &Path,
+    output_path: &Path,
+    sheet_name: Option<&str>,
+    print_area: Option<&str>,
+) -> Result<RenderedRange> {
+    if let Some(print_area) = print_area {
+        if sheet_name.is_none() {
+            return Err(anyhow!("print_area requires sheet_name"));
+        }
+        parse_range_bounds(print_area)?;
+    }
+
+    #[cfg(not(feature = "recalc-libreoffice"))]
+    {
+        let _ = (workbook_path, output_path, sheet_name, print_area);
+        Err(anyhow!(
+            "pdf export backend unavailable (build without recalc-libreoffice feature)"
+        ))
+    }
+
+    #[cfg(feature = "recalc-libreoffice")]
+    {
+        let executor =
+            crate::recalc::ScreenshotExecutor::new(&crate::recalc::RecalcConfig::default());
+        let result = executor
+            .export_pdf(workbook_path, output_path, sheet_name, print_area)
+            .await?;
+
+        Ok(RenderedRange {
+            output_path: result.output_path,
+            size_bytes: result.size_bytes,
+            duration_ms: result.duration_ms,
+        })
+    }
+}
+
+/// Effective rasterization DPI for `dpi` x `scale`; `None` when neither is set
+/// so the default LibreOffice PNG export is used.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn resolve_render_dpi(dpi: Option<u32>, scale: Option<f32>) -> Result<Option<u32>> {
+    if dpi.is_none() && scale.is_none() {
+        return Ok(None);
+    }
+    if let Some(dpi) = dpi
+        && !(MIN_RENDER_DPI..=MAX_RENDER_DPI).contains(&dpi)
+    {
+        return Err(anyhow!(
+            "dpi must be between {MIN_RENDER_DPI} and {MAX_RENDER_DPI} (got {dpi})"
+        ));
+    }
+    if let Some(scale) = scale
+        && !(MIN_RENDER_SCALE..=MAX_RENDER_SCALE).contains(&scale)
+    {
+        return Err(anyhow!(
+            "scale must be between {MIN_RENDER_SCALE} and {MAX_RENDER_SCALE} (got {scale})"
+        ));
+    }
+    let effective =
+        (dpi.unwrap_or(DEFAULT_RENDER_DPI) as f32 * scale.unwrap_or(1.0)).round() as u32;
+    if effective > MAX_RENDER_DPI {
+        return Err(anyhow!(
+            "dpi x scale must not exceed {MAX_RENDER_DPI} (got {effective})"
+        ));
+    }
+    Ok(Some(effective.max(MIN_RENDER_DPI)))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn render_range_png(
+    workbook_path: &Path,
+    output_path: &Path,
+    sheet_name: &str,
+    range: &str,
+    bounds: &ScreenshotBounds,
+    dpi: Option<u32>,
+) -> Result<RenderedRange> {
     #[cfg(not(feature = "recalc-libreoffice"))]
     {
-        let _ = workbook_path;
-        let _ = output_path;
-        let _ = bounds;
+        let _ = (workbook_path, output_path, sheet_name, range, bounds, dpi);
         Err(anyhow!(
             "screenshot backend unavailable (build without recalc-libreoffice feature)"
         ))
@@ -6164,23 +8789,19 @@ pub async fn screenshot_sheet(
             crate::recalc::ScreenshotExecutor::new(&crate::recalc::RecalcConfig::default());
         let result = executor
             .screenshot(
-                &workbook_path,
-                &output_path,
-                &params.sheet_name,
+                workbook_path,
+                output_path,
+                sheet_name,
                 Some(range),
+                dpi,
+                true,
             )
             .await?;
 
-        enforce_png_pixel_limits(&result.output_path, range, &bounds).await?;
+        enforce_png_pixel_limits(&result.output_path, range, bounds).await?;
 
-        Ok(ScreenshotSheetResponse {
-            workbook_id: params.workbook_or_fork_id.0,
-            sheet_name: params.sheet_name,
-            range: range.to_string(),
-            output_path: format!("file://{}", result.output_path.display()),
-            client_output_path: config
-                .map_path_for_client(&result.output_path)
-                .map(|p| format!("file://{}", p.display())),
+        Ok(RenderedRange {
+            output_path: result.output_path,
             size_bytes: result.size_bytes,
             duration_ms: result.duration_ms,
         })
@@ -6540,6 +9161,7 @@ pub async fn grid_import(
                 borders: Some(None),
                 alignment: Some(None),
                 number_format: Some(None),
+                number_format_locale: None,
             },
             op_mode: None,
         });
@@ -6683,9 +9305,8 @@ pub async fn grid_import(
 
     if mode.is_preview() {
         let change_id = make_short_random_id("chg", 12);
-        let snapshot_path = stage_snapshot_path(&params.fork_id, &change_id);
-        fs::create_dir_all(snapshot_path.parent().unwrap())?;
-        fs::copy(&work_path, &snapshot_path)?;
+        let snapshot = registry.stage_snapshot(&params.fork_id, &change_id, &work_path)?;
+        let snapshot_path = snapshot.to_path_buf();
 
         let snapshot_for_apply = snapshot_path.clone();
         let sheet_name_for_col = params.sheet_name.clone();
@@ -6710,7 +9331,7 @@ pub async fn grid_import(
                 merge_summary_counts(&mut summary, &res.summary);
             }
             if !ops_to_apply.is_empty() {
-                let res = apply_transform_ops_to_file(&snapshot_for_apply, &ops_to_apply)?;
+                let res = apply_transform_ops_to_file(&snapshot_for_apply, &ops_to_apply, false)?;
                 merge_summary_counts(&mut summary, &res.summary);
             }
             if !resolved_style_ops.is_empty() {
@@ -6741,7 +9362,7 @@ pub async fn grid_import(
             label: params.label.clone(),
             ops: vec![staged_op],
             summary: summary.clone(),
-            fork_path_snapshot: Some(snapshot_path),
+            fork_path_snapshot: Some(snapshot.keep()?),
         };
 
         registry.add_staged_change(&params.fork_id, staged)?;
@@ -6777,7 +9398,7 @@ pub async fn grid_import(
                 merge_summary_counts(&mut summary, &res.summary);
             }
             if !ops_to_apply.is_empty() {
-                let res = apply_transform_ops_to_file(&work_path_for_apply, &ops_to_apply)?;
+                let res = apply_transform_ops_to_file(&work_path_for_apply, &ops_to_apply, false)?;
                 merge_summary_counts(&mut summary, &res.summary);
             }
             if !resolved_style_ops.is_empty() {
@@ -0,0 +1,123 @@
+//! Webhook notifications for writes to server forks.
+//!
+//! With a webhook configured (see [`crate::webhook`]), fork edits,
+//! `save_fork` and `recalculate` send the same events as the CLI, tagged with
+//! the fork id. The fork is copied before an edit so its event can carry the
+//! cells the edit changed. Delivery failures are logged rather than returned,
+//! since the write has already landed.
+
+use crate::hooks::{ChangedCellManifest, changed_cell_manifest};
+use crate::state::AppState;
+use crate::utils::hash_file_sha256_hex;
+use crate::webhook::{WebhookEvent, notify_webhook, webhook_configured};
+use anyhow::Result;
+use std::fs;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use tempfile::TempPath;
+
+/// Copy of a fork's workbook taken just before a write.
+pub(crate) struct ForkBaseline {
+    copy: TempPath,
+}
+
+impl ForkBaseline {
+    /// `None` without a webhook, so writes nobody is notified of copy nothing.
+    pub(crate) fn capture(work_path: &Path) -> Option<Self> {
+        if !webhook_configured() {
+            return None;
+        }
+        let copy = crate::scratch::temp_dir().and_then(|dir| {
+            let copy = tempfile::Builder::new()
+                .prefix(".fork-webhook-")
+                .suffix(".xlsx")
+                .tempfile_in(dir)?
+                .into_temp_path();
+            fs::copy(work_path, &copy)?;
+            Ok(copy)
+        });
+        match copy {
+            Ok(copy) => Some(Self { copy }),
+            Err(error) => {
+                tracing::warn!(?error, "failed to copy fork for its webhook notification");
+                None
+            }
+        }
+    }
+
+    /// Cells changed since the copy, or `None` when the workbook is
+    /// byte-for-byte unchanged (a preview, or a no-op edit).
+    async fn changes(&self, work_path: &Path) -> Result<Option<ChangedCellManifest>> {
+        let before = self.copy.to_path_buf();
+        let after = work_path.to_path_buf();
+        tokio::task::spawn_blocking(move || {
+            if hash_file_sha256_hex(&before)? == hash_file_sha256_hex(&after)? {
+                return Ok(None);
+            }
+            changed_cell_manifest(&before, &after).map(Some)
+        })
+        .await?
+    }
+
+    /// Cells changed since the copy.
+    pub(crate) async fn changed_cells(&self, work_path: &Path) -> Result<ChangedCellManifest> {
+        changed_cells_between(self.copy.to_path_buf(), work_path.to_path_buf()).await
+    }
+}
+
+pub(crate) async fn changed_cells_between(
+    before: PathBuf,
+    after: PathBuf,
+) -> Result<ChangedCellManifest> {
+    tokio::task::spawn_blocking(move || changed_cell_manifest(&before, &after)).await?
+}
+
+/// Run `edit` against fork `fork_id` and notify the webhook if it changed
+/// the fork's workbook.
+pub async fn with_fork_webhook<T>(
+    state: &AppState,
+    fork_id: &str,
+    command: &str,
+    edit: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    let fork = state
+        .fork_registry()
+        .filter(|_| webhook_configured())
+        .and_then(|registry| registry.get_fork(fork_id).ok());
+    let baseline = fork
+        .as_ref()
+        .and_then(|fork| ForkBaseline::capture(&fork.work_path));
+
+    let result = edit.await?;
+
+    if let (Some(fork), Some(baseline)) = (fork, baseline) {
+        match baseline.changes(&fork.work_path).await {
+            Ok(Some(changed_cells)) => {
+                send_fork_event(WebhookEvent::fork_write_completed(
+                    fork_id.to_string(),
+                    command.to_string(),
+                    fork.base_path.display().to_string(),
+                    fork.work_path.display().to_string(),
+                    changed_cells,
+                ))
+                .await;
+            }
+            Ok(None) => {}
+            Err(error) => {
+                tracing::warn!(?error, fork_id, command, "failed to diff fork for webhook");
+            }
+        }
+    }
+    Ok(result)
+}
+
+pub(crate) async fn send_fork_event(event: WebhookEvent) {
+    if let Err(error) = notify_webhook(&event).await {
+        tracing::warn!(
+            ?error,
+            fork_id = event.fork_id.as_deref().unwrap_or_default(),
+            command = %event.command,
+            "failed to notify webhook of fork write"
+        );
+    }
+}
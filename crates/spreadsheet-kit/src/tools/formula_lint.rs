@@ -0,0 +1,379 @@
+//! Formula style checks: hard-coded constants, whole-column aggregations,
+//! deep IF nesting, and formulas that break their neighbours' pattern.
+//!
+//! These are review hints rather than defects, so they live apart from the
+//! `lint` quality gate. Formulas that do not parse are skipped here; `lint`
+//! reports them as `formula_parse_error`.
+
+use crate::formula::pattern::r1c1_pattern;
+use crate::model::{FormulaLintFinding, FormulaLintRule, FormulaLintRuleCount};
+use crate::utils::cell_address;
+use formualizer_parse::parser::ReferenceType;
+use formualizer_parse::{ASTNode, ASTNodeType, LiteralValue};
+use std::collections::BTreeMap;
+use umya_spreadsheet::Worksheet;
+
+pub const DEFAULT_MAX_IF_DEPTH: u32 = 3;
+
+/// Functions whose whole-column arguments make Excel scan a million rows.
+const AGGREGATION_FUNCTIONS: &[&str] = &[
+    "SUM",
+    "SUMIF",
+    "SUMIFS",
+    "SUMPRODUCT",
+    "PRODUCT",
+    "COUNT",
+    "COUNTA",
+    "COUNTBLANK",
+    "COUNTIF",
+    "COUNTIFS",
+    "AVERAGE",
+    "AVERAGEA",
+    "AVERAGEIF",
+    "AVERAGEIFS",
+    "MIN",
+    "MINA",
+    "MINIFS",
+    "MAX",
+    "MAXA",
+    "MAXIFS",
+    "MEDIAN",
+    "STDEV",
+    "STDEV.S",
+    "STDEV.P",
+    "VAR",
+    "VAR.S",
+    "VAR.P",
+    "SUBTOTAL",
+    "AGGREGATE",
+];
+
+#[derive(Debug, Clone)]
+pub struct FormulaLintOptions {
+    /// Rules to run; empty runs all of them.
+    pub rules: Vec<FormulaLintRule>,
+    pub max_if_depth: u32,
+}
+
+impl Default for FormulaLintOptions {
+    fn default() -> Self {
+        Self {
+            rules: Vec::new(),
+            max_if_depth: DEFAULT_MAX_IF_DEPTH,
+        }
+    }
+}
+
+impl FormulaLintOptions {
+    fn enabled(&self, rule: FormulaLintRule) -> bool {
+        self.rules.is_empty() || self.rules.contains(&rule)
+    }
+}
+
+struct FormulaCell {
+    formula: String,
+    ast: ASTNode,
+    /// Formula with references rewritten relative to this cell, so copies of
+    /// one formula down a column or across a row compare equal.
+    signature: String,
+}
+
+/// Style findings for one sheet, in row-major order.
+pub fn lint_sheet_formulas(
+    sheet: &Worksheet,
+    options: &FormulaLintOptions,
+) -> Vec<FormulaLintFinding> {
+    let sheet_name = sheet.get_name().to_string();
+    let mut cells: BTreeMap<(u32, u32), FormulaCell> = BTreeMap::new();
+    for cell in sheet.get_cell_collection() {
+        if !cell.is_formula() {
+            continue;
+        }
+        let formula = cell.get_formula().trim().to_string();
+        if formula.is_empty() {
+            continue;
+        }
+        let with_prefix = if formula.starts_with('=') {
+            formula.clone()
+        } else {
+            format!("={formula}")
+        };
+        let Ok(ast) = formualizer_parse::parse(&with_prefix) else {
+            continue;
+        };
+        let coordinate = cell.get_coordinate();
+        let (row, col) = (*coordinate.get_row_num(), *coordinate.get_col_num());
+        let signature = r1c1_pattern(&ast, row, col);
+        cells.insert(
+            (row, col),
+            FormulaCell {
+                formula,
+                ast,
+                signature,
+            },
+        );
+    }
+
+    let mut findings = Vec::new();
+    for (&(row, col), cell) in &cells {
+        let mut push = |rule: FormulaLintRule, message: String| {
+            findings.push(FormulaLintFinding {
+                rule,
+                sheet_name: sheet_name.clone(),
+                address: cell_address(col, row),
+                message,
+                formula: cell.formula.clone(),
+            });
+        };
+
+        if options.enabled(FormulaLintRule::HardcodedConstant) {
+            let mut constants = Vec::new();
+            collect_arithmetic_constants(&cell.ast, &mut constants);
+            constants.dedup();
+            if !constants.is_empty() {
+                push(
+                    FormulaLintRule::HardcodedConstant,
+                    format!(
+                        "hard-coded constant{} {} in arithmetic; move to a labeled input cell",
+                        if constants.len() == 1 { "" } else { "s" },
+                        constants.join(", ")
+                    ),
+                );
+            }
+        }
+
+        if options.enabled(FormulaLintRule::FullColumnReference) {
+            let mut references = Vec::new();
+            collect_full_column_aggregations(&cell.ast, None, &mut references);
+            references.dedup();
+            if !references.is_empty() {
+                push(
+                    FormulaLintRule::FullColumnReference,
+                    format!(
+                        "{}; bound the range or use a table reference",
+                        references.join(", ")
+                    ),
+                );
+            }
+        }
+
+        if options.enabled(FormulaLintRule::NestedIf) {
+            let depth = if_depth(&cell.ast);
+            if depth > options.max_if_depth {
+                push(
+                    FormulaLintRule::NestedIf,
+                    format!(
+                        "IF nested {depth} deep (max {}); consider IFS, SWITCH, or a lookup table",
+                        options.max_if_depth
+                    ),
+                );
+            }
+        }
+
+        if options.enabled(FormulaLintRule::InconsistentFormula)
+            && let Some(message) = inconsistency(&cells, row, col, cell)
+        {
+            push(FormulaLintRule::InconsistentFormula, message);
+        }
+    }
+    findings
+}
+
+/// Excel's rule: both neighbours along a row or column agree with each other
+/// but not with this cell.
+fn inconsistency(
+    cells: &BTreeMap<(u32, u32), FormulaCell>,
+    row: u32,
+    col: u32,
+    cell: &FormulaCell,
+) -> Option<String> {
+    let neighbours = [
+        (
+            "above and below",
+            (row.saturating_sub(1), col),
+            (row + 1, col),
+        ),
+        (
+            "left and right",
+            (row, col.saturating_sub(1)),
+            (row, col + 1),
+        ),
+    ];
+    for (direction, before, after) in neighbours {
+        let (Some(first), Some(second)) = (cells.get(&before), cells.get(&after)) else {
+            continue;
+        };
+        if first.signature == second.signature && first.signature != cell.signature {
+            return Some(format!(
+                "differs from the formulas {direction} ({}, {}), e.g. ={}",
+                cell_address(before.1, before.0),
+                cell_address(after.1, after.0),
+                first.formula.trim_start_matches('=')
+            ));
+        }
+    }
+    None
+}
+
+fn children(node: &ASTNode) -> Vec<&ASTNode> {
+    match &node.node_type {
+        ASTNodeType::UnaryOp { expr, .. } => vec![expr.as_ref()],
+        ASTNodeType::BinaryOp { left, right, .. } => vec![left.as_ref(), right.as_ref()],
+        ASTNodeType::Function { args, .. } => args.iter().collect(),
+        ASTNodeType::Array(rows) => rows.iter().flatten().collect(),
+        ASTNodeType::Reference { .. } | ASTNodeType::Literal(_) => Vec::new(),
+    }
+}
+
+fn function_name(name: &str) -> String {
+    name.trim_start_matches("_xlfn.").to_ascii_uppercase()
+}
+
+/// Numeric literals used as operands of `+ - * / ^`. 0 and 1 are left alone;
+/// they are identities far more often than assumptions.
+fn collect_arithmetic_constants(node: &ASTNode, out: &mut Vec<String>) {
+    if let ASTNodeType::BinaryOp { op, left, right } = &node.node_type
+        && matches!(op.as_str(), "+" | "-" | "*" | "/" | "^")
+    {
+        for operand in [left.as_ref(), right.as_ref()] {
+            if let Some(constant) = numeric_constant(operand)
+                && !matches!(constant.as_str(), "0" | "1" | "-1")
+            {
+                out.push(constant);
+            }
+        }
+    }
+    for child in children(node) {
+        collect_arithmetic_constants(child, out);
+    }
+}
+
+/// `5`, `-5`, or `8%` as written, if `node` is a numeric literal.
+fn numeric_constant(node: &ASTNode) -> Option<String> {
+    match &node.node_type {
+        ASTNodeType::Literal(LiteralValue::Int(value)) => Some(value.to_string()),
+        ASTNodeType::Literal(LiteralValue::Number(value)) => Some(value.to_string()),
+        ASTNodeType::UnaryOp { op, expr } => {
+            let inner = numeric_constant(expr)?;
+            match op.as_str() {
+                "-" => Some(format!("-{inner}")),
+                "+" => Some(inner),
+                "%" => Some(format!("{inner}%")),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// `SUM over C:C` for each whole-column range under an aggregation function.
+fn collect_full_column_aggregations(
+    node: &ASTNode,
+    aggregation: Option<&str>,
+    out: &mut Vec<String>,
+) {
+    match &node.node_type {
+        ASTNodeType::Reference {
+            original,
+            reference:
+                ReferenceType::Range {
+                    start_row: None,
+                    end_row: None,
+                    start_col: Some(_),
+                    ..
+                },
+        } => {
+            if let Some(function) = aggregation {
+                out.push(format!("{function} over full column {original}"));
+            }
+        }
+        ASTNodeType::Function { name, args } => {
+            let name = function_name(name);
+            let aggregation = if AGGREGATION_FUNCTIONS.contains(&name.as_str()) {
+                Some(name.as_str())
+            } else {
+                aggregation
+            };
+            for arg in args {
+                collect_full_column_aggregations(arg, aggregation, out);
+            }
+        }
+        _ => {
+            for child in children(node) {
+                collect_full_column_aggregations(child, aggregation, out);
+            }
+        }
+    }
+}
+
+fn if_depth(node: &ASTNode) -> u32 {
+    let own = match &node.node_type {
+        ASTNodeType::Function { name, .. } if function_name(name) == "IF" => 1,
+        _ => 0,
+    };
+    own + children(node).into_iter().map(if_depth).max().unwrap_or(0)
+}
+
+/// Findings per rule and the sheets they occur on, in rule order.
+pub fn count_by_rule(findings: &[FormulaLintFinding]) -> Vec<FormulaLintRuleCount> {
+    let mut counts: BTreeMap<FormulaLintRule, FormulaLintRuleCount> = BTreeMap::new();
+    for finding in findings {
+        let entry = counts
+            .entry(finding.rule)
+            .or_insert_with(|| FormulaLintRuleCount {
+                rule: finding.rule,
+                count: 0,
+                sheets: Vec::new(),
+            });
+        entry.count += 1;
+        if !entry.sheets.contains(&finding.sheet_name) {
+            entry.sheets.push(finding.sheet_name.clone());
+        }
+    }
+    counts.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn findings_for(formulas: &[(&str, &str)]) -> HashMap<String, Vec<FormulaLintRule>> {
+        let mut book = umya_spreadsheet::new_file();
+        let sheet = book.get_sheet_mut(&0).expect("sheet");
+        for (address, formula) in formulas {
+            sheet.get_cell_mut(*address).set_formula(*formula);
+        }
+        let mut by_address: HashMap<String, Vec<FormulaLintRule>> = HashMap::new();
+        for finding in lint_sheet_formulas(sheet, &FormulaLintOptions::default()) {
+            by_address
+                .entry(finding.address)
+                .or_default()
+                .push(finding.rule);
+        }
+        by_address
+    }
+
+    #[test]
+    fn flags_each_style_rule() {
+        let findings = findings_for(&[
+            ("B1", "A1*1.08"),
+            ("B2", "A2*1.08"),
+            ("C1", "SUM(A:A)"),
+            ("C2", "INDEX(A:A,1)+1"),
+            ("D1", "IF(A1>1,IF(A1>2,IF(A1>3,IF(A1>4,4,3),2),1),0)"),
+            ("E1", "A1+B1"),
+            ("E2", "A2+B2"),
+            ("E3", "A3*B3"),
+            ("E4", "A4+B4"),
+            ("E5", "A5+B5"),
+        ]);
+
+        assert_eq!(findings["B1"], vec![FormulaLintRule::HardcodedConstant]);
+        assert_eq!(findings["C1"], vec![FormulaLintRule::FullColumnReference]);
+        assert!(!findings.contains_key("C2"), "{findings:?}");
+        assert_eq!(findings["D1"], vec![FormulaLintRule::NestedIf]);
+        assert_eq!(findings["E3"], vec![FormulaLintRule::InconsistentFormula]);
+        assert!(!findings.contains_key("E2") && !findings.contains_key("E4"));
+    }
+}
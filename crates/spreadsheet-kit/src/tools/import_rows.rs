@@ -0,0 +1,492 @@
+//! Bulk row writes for the `import_rows` and `append_table_rows` transform
+//! ops.
+//!
+//! Rows arrive inline (arrays of values, or objects keyed by header) or as an
+//! `@path.csv`/`@path.json` reference that the batch entry point loads before
+//! resolution. Resolution turns every variant into the same plain form: a
+//! grid of values written from a concrete anchor, so header matching and
+//! append positioning are fixed when the batch is previewed or staged.
+
+use super::fork::TransformOp;
+use crate::utils::cell_address;
+use crate::workbook::{WorkbookContext, iso_to_excel_serial};
+use anyhow::{Context, Result, anyhow, bail};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Rows for an `import_rows` op.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ImportRowsData {
+    /// `@path/to/rows.csv` or `@path/to/rows.json` (a JSON array of rows)
+    File(String),
+    /// Arrays of cell values, or objects keyed by column header
+    Inline(Vec<Value>),
+}
+
+/// Replace `@file` row references in `import_rows` and `append_table_rows`
+/// ops with the rows they contain. Relative paths resolve against `base_dir`; with `confine` set,
+/// paths that leave `base_dir` are rejected.
+pub fn load_import_row_files(
+    ops: &mut [TransformOp],
+    base_dir: &Path,
+    confine: bool,
+) -> Result<()> {
+    for op in ops {
+        let (kind, rows) = match op {
+            TransformOp::ImportRows { rows, .. } => ("import_rows", rows),
+            TransformOp::AppendTableRows { rows, .. } => ("append_table_rows", rows),
+            _ => continue,
+        };
+        let ImportRowsData::File(reference) = rows else {
+            continue;
+        };
+        let relative = reference.strip_prefix('@').ok_or_else(|| {
+            anyhow!("{kind} rows must be an array or an '@file' reference, got '{reference}'")
+        })?;
+        let path = base_dir.join(relative);
+        if confine {
+            let root = fs::canonicalize(base_dir).unwrap_or_else(|_| base_dir.to_path_buf());
+            let canonical = fs::canonicalize(&path)
+                .with_context(|| format!("{kind} file '{relative}' not found"))?;
+            if !canonical.starts_with(&root) {
+                bail!("{kind} file '{relative}' is outside the workspace root");
+            }
+        }
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {kind} file '{relative}'"))?;
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase);
+        let loaded = match extension.as_deref() {
+            Some("csv") => parse_csv_records(contents.trim_start_matches('\u{feff}'))
+                .with_context(|| format!("failed to parse {kind} file '{relative}'"))?
+                .into_iter()
+                .map(|record| Value::Array(record.into_iter().map(Value::String).collect()))
+                .collect(),
+            Some("json") => serde_json::from_str::<Vec<Value>>(&contents).with_context(|| {
+                format!("{kind} file '{relative}' must hold a JSON array of rows")
+            })?,
+            _ => bail!("{kind} file '{relative}' must end in .csv or .json"),
+        };
+        *rows = ImportRowsData::Inline(loaded);
+    }
+    Ok(())
+}
+
+/// Resolve an `import_rows` op against the workbook: match headers, find the
+/// append position, and return the op as a plain grid written at a fixed
+/// anchor. Ops are resolved before any of them is applied, so `imported_to`
+/// carries the last row each earlier op in the batch writes on a sheet;
+/// appends land below it.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn resolve_import_rows(
+    workbook: &WorkbookContext,
+    imported_to: &mut BTreeMap<String, u32>,
+    sheet_name: &str,
+    anchor: &str,
+    rows: &ImportRowsData,
+    has_header: bool,
+    match_headers: bool,
+    append: bool,
+    coerce_types: bool,
+    overwrite_formulas: bool,
+) -> Result<TransformOp> {
+    let ImportRowsData::Inline(rows) = rows else {
+        bail!("import_rows file references are loaded by transform_batch; pass rows inline here");
+    };
+    let (anchor_col, anchor_row) = parse_anchor(anchor)?;
+    let (headers, data) = split_rows(rows, has_header)?;
+
+    let (grid, data_start) = if match_headers {
+        let headers = headers.ok_or_else(|| {
+            anyhow!("import_rows match_headers needs a header row (has_header) or object rows")
+        })?;
+        let sheet_headers = workbook.with_sheet(sheet_name, |sheet| {
+            (anchor_col..)
+                .map_while(|col| {
+                    let text = sheet.get_value((col, anchor_row));
+                    let text = text.trim();
+                    (!text.is_empty()).then(|| text.to_string())
+                })
+                .collect::<Vec<_>>()
+        })?;
+        if sheet_headers.is_empty() {
+            bail!(
+                "import_rows match_headers found no headers at {}!{}",
+                sheet_name,
+                anchor
+            );
+        }
+        let mut positions = Vec::with_capacity(headers.len());
+        for header in &headers {
+            let position = sheet_headers
+                .iter()
+                .position(|existing| existing.eq_ignore_ascii_case(header.trim()))
+                .ok_or_else(|| {
+                    anyhow!(
+                        "import_rows column '{}' has no matching header at {}!{} (headers: {})",
+                        header,
+                        sheet_name,
+                        anchor,
+                        sheet_headers.join(", ")
+                    )
+                })?;
+            positions.push(position);
+        }
+        let grid = data
+            .into_iter()
+            .map(|row| {
+                let mut placed = vec![Value::Null; sheet_headers.len()];
+                for (value, &position) in row.into_iter().zip(&positions) {
+                    placed[position] = value;
+                }
+                placed
+            })
+            .collect::<Vec<_>>();
+        (grid, anchor_row + 1)
+    } else {
+        let mut grid = Vec::with_capacity(data.len() + 1);
+        // Appended rows go under headers that are already on the sheet.
+        if let Some(headers) = headers.filter(|_| !append) {
+            grid.push(headers.into_iter().map(Value::String).collect());
+        }
+        grid.extend(data);
+        (grid, anchor_row)
+    };
+
+    let start_row = if append {
+        let width = grid.iter().map(Vec::len).max().unwrap_or(0).max(1) as u32;
+        let last_used = workbook.with_sheet(sheet_name, |sheet| {
+            sheet
+                .get_cell_collection()
+                .into_iter()
+                .filter_map(|cell| {
+                    let coordinate = cell.get_coordinate();
+                    let (col, row) = (*coordinate.get_col_num(), *coordinate.get_row_num());
+                    let in_block = col >= anchor_col && col < anchor_col + width;
+                    let used = cell.is_formula() || !cell.get_value().is_empty();
+                    (in_block && row >= data_start && used).then_some(row)
+                })
+                .max()
+        })?;
+        let after_batch = imported_to
+            .get(sheet_name)
+            .map_or(data_start, |row| row + 1);
+        last_used.map_or(data_start, |row| row + 1).max(after_batch)
+    } else {
+        data_start
+    };
+    if !grid.is_empty() {
+        let last_row = start_row + grid.len() as u32 - 1;
+        let entry = imported_to
+            .entry(sheet_name.to_string())
+            .or_insert(last_row);
+        *entry = (*entry).max(last_row);
+    }
+
+    Ok(TransformOp::ImportRows {
+        sheet_name: sheet_name.to_string(),
+        anchor: cell_address(anchor_col, start_row),
+        rows: ImportRowsData::Inline(grid.into_iter().map(Value::Array).collect()),
+        has_header: false,
+        match_headers: false,
+        append: false,
+        coerce_types,
+        overwrite_formulas,
+    })
+}
+
+/// Resolve an `append_table_rows` op: check the table exists and return the
+/// rows as arrays in table column order. Where the rows land is decided at
+/// apply time, so several appends to one table in a batch stack up.
+pub(crate) fn resolve_append_table_rows(
+    workbook: &WorkbookContext,
+    sheet_name: &str,
+    table_name: &str,
+    rows: &ImportRowsData,
+    has_header: bool,
+    coerce_types: bool,
+) -> Result<TransformOp> {
+    let ImportRowsData::Inline(rows) = rows else {
+        bail!(
+            "append_table_rows file references are loaded by transform_batch; pass rows inline here"
+        );
+    };
+    let table_headers = workbook.with_sheet(sheet_name, |sheet| {
+        sheet
+            .get_tables()
+            .iter()
+            .find(|table| table_matches(table, table_name))
+            .map(|table| {
+                let (start, end) = table.get_area();
+                let header_row = *start.get_row_num();
+                (*start.get_col_num()..=*end.get_col_num())
+                    .map(|col| sheet.get_value((col, header_row)).trim().to_string())
+                    .collect::<Vec<_>>()
+            })
+    })?;
+    let table_headers = table_headers.ok_or_else(|| {
+        anyhow!(
+            "table '{}' was not found on sheet '{}'",
+            table_name,
+            sheet_name
+        )
+    })?;
+
+    let (headers, data) = split_rows(rows, has_header)?;
+    let grid = match headers {
+        Some(headers) => {
+            let mut positions = Vec::with_capacity(headers.len());
+            for header in &headers {
+                let position = table_headers
+                    .iter()
+                    .position(|existing| existing.eq_ignore_ascii_case(header.trim()))
+                    .ok_or_else(|| {
+                        anyhow!(
+                            "table '{}' has no column '{}' (columns: {})",
+                            table_name,
+                            header,
+                            table_headers.join(", ")
+                        )
+                    })?;
+                positions.push(position);
+            }
+            data.into_iter()
+                .map(|row| {
+                    let mut placed = vec![Value::Null; table_headers.len()];
+                    for (value, &position) in row.into_iter().zip(&positions) {
+                        placed[position] = value;
+                    }
+                    placed
+                })
+                .collect::<Vec<_>>()
+        }
+        None => {
+            if let Some((index, row)) = data
+                .iter()
+                .enumerate()
+                .find(|(_, row)| row.len() > table_headers.len())
+            {
+                bail!(
+                    "append_table_rows row {} has {} values but table '{}' has {} columns",
+                    index + 1,
+                    row.len(),
+                    table_name,
+                    table_headers.len()
+                );
+            }
+            data
+        }
+    };
+
+    Ok(TransformOp::AppendTableRows {
+        sheet_name: sheet_name.to_string(),
+        table_name: table_name.to_string(),
+        rows: ImportRowsData::Inline(grid.into_iter().map(Value::Array).collect()),
+        has_header: false,
+        coerce_types,
+    })
+}
+
+/// Tables are matched by name or display name, ignoring case.
+pub(crate) fn table_matches(table: &umya_spreadsheet::structs::Table, name: &str) -> bool {
+    table.get_name().eq_ignore_ascii_case(name)
+        || table.get_display_name().eq_ignore_ascii_case(name)
+}
+
+/// Header names and data rows. Object rows take their headers from their
+/// keys; JSON objects are read with sorted keys, so those columns come out
+/// in alphabetical order unless `match_headers` places them.
+fn split_rows(rows: &[Value], has_header: bool) -> Result<(Option<Vec<String>>, Vec<Vec<Value>>)> {
+    if rows.iter().any(Value::is_object) {
+        let mut headers: Vec<String> = Vec::new();
+        for (index, row) in rows.iter().enumerate() {
+            let Value::Object(object) = row else {
+                bail!(
+                    "import_rows row {} is not an object like the others",
+                    index + 1
+                );
+            };
+            for key in object.keys() {
+                if !headers.contains(key) {
+                    headers.push(key.clone());
+                }
+            }
+        }
+        let data = rows
+            .iter()
+            .filter_map(Value::as_object)
+            .map(|object| {
+                headers
+                    .iter()
+                    .map(|header| object.get(header).cloned().unwrap_or(Value::Null))
+                    .collect()
+            })
+            .collect();
+        return Ok((Some(headers), data));
+    }
+
+    let mut data = Vec::with_capacity(rows.len());
+    for (index, row) in rows.iter().enumerate() {
+        let Value::Array(cells) = row else {
+            bail!(
+                "import_rows row {} must be an array or an object",
+                index + 1
+            );
+        };
+        data.push(cells.clone());
+    }
+    if !has_header {
+        return Ok((None, data));
+    }
+    if data.is_empty() {
+        bail!("import_rows has_header is set but there are no rows");
+    }
+    let headers = data
+        .remove(0)
+        .into_iter()
+        .map(|value| match value {
+            Value::String(text) => text,
+            Value::Null => String::new(),
+            other => other.to_string(),
+        })
+        .collect();
+    Ok((Some(headers), data))
+}
+
+fn parse_anchor(anchor: &str) -> Result<(u32, u32)> {
+    use umya_spreadsheet::helper::coordinate::index_from_coordinate;
+    match index_from_coordinate(anchor) {
+        (Some(col), Some(row), _, _) => Ok((col, row)),
+        _ => Err(anyhow!("Invalid cell reference: {}", anchor)),
+    }
+}
+
+/// Write one imported value. Nulls leave the cell untouched and return
+/// `false`. With `coerce_types`, text holding a plain number, `true`/`false`,
+/// or an ISO date (`YYYY-MM-DD`, optionally with `THH:MM:SS`) becomes a
+/// number, boolean, or formatted date serial; numbers with leading zeros such
+/// as ZIP codes stay text.
+pub(crate) fn write_import_value(
+    cell: &mut umya_spreadsheet::Cell,
+    value: &Value,
+    coerce_types: bool,
+    use_1904_system: bool,
+) -> bool {
+    match value {
+        Value::Null => return false,
+        Value::Bool(flag) => {
+            cell.set_value_bool(*flag);
+        }
+        Value::Number(number) => match number.as_f64() {
+            Some(number) => {
+                cell.set_value_number(number);
+            }
+            None => {
+                cell.set_value_string(number.to_string());
+            }
+        },
+        Value::String(text) if coerce_types => write_coerced_text(cell, text, use_1904_system),
+        Value::String(text) => {
+            cell.set_value_string(text.clone());
+        }
+        Value::Array(_) | Value::Object(_) => {
+            cell.set_value_string(value.to_string());
+        }
+    }
+    true
+}
+
+fn write_coerced_text(cell: &mut umya_spreadsheet::Cell, text: &str, use_1904_system: bool) {
+    let trimmed = text.trim();
+    let unsigned = trimmed.trim_start_matches(['-', '+']);
+    let leading_zero =
+        unsigned.len() > 1 && unsigned.starts_with('0') && !unsigned.starts_with("0.");
+    if !leading_zero
+        && let Ok(number) = trimmed.parse::<f64>()
+        && number.is_finite()
+    {
+        cell.set_value_number(number);
+        return;
+    }
+    if trimmed.eq_ignore_ascii_case("true") || trimmed.eq_ignore_ascii_case("false") {
+        cell.set_value_bool(trimmed.eq_ignore_ascii_case("true"));
+        return;
+    }
+    let iso = trimmed.replacen(' ', "T", 1);
+    let looks_like_date = iso.len() >= 10 && iso.as_bytes()[4] == b'-' && iso.as_bytes()[7] == b'-';
+    if looks_like_date && let Some(serial) = iso_to_excel_serial(&iso, use_1904_system) {
+        cell.set_value_number(serial);
+        let format = if iso.len() > 10 {
+            "yyyy-mm-dd hh:mm:ss"
+        } else {
+            "yyyy-mm-dd"
+        };
+        cell.get_style_mut()
+            .get_number_format_mut()
+            .set_format_code(format);
+        return;
+    }
+    cell.set_value_string(text.to_string());
+}
+
+/// Split RFC 4180 text into records of fields.
+pub fn parse_csv_records(raw: &str) -> Result<Vec<Vec<String>>> {
+    let mut records: Vec<Vec<String>> = Vec::new();
+    let mut row: Vec<String> = Vec::new();
+    let mut field = String::new();
+    let mut chars = raw.chars().peekable();
+    let mut in_quotes = false;
+
+    while let Some(ch) = chars.next() {
+        if in_quotes {
+            if ch == '"' {
+                if matches!(chars.peek(), Some('"')) {
+                    let _ = chars.next();
+                    field.push('"');
+                } else {
+                    in_quotes = false;
+                }
+            } else {
+                field.push(ch);
+            }
+            continue;
+        }
+
+        match ch {
+            '"' => in_quotes = true,
+            ',' => {
+                row.push(std::mem::take(&mut field));
+            }
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut row));
+            }
+            '\r' => {
+                if matches!(chars.peek(), Some('\n')) {
+                    let _ = chars.next();
+                }
+                row.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut row));
+            }
+            _ => field.push(ch),
+        }
+    }
+
+    if in_quotes {
+        return Err(anyhow!("unterminated quoted field"));
+    }
+
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        records.push(row);
+    }
+
+    Ok(records)
+}
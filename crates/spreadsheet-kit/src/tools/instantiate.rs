@@ -0,0 +1,361 @@
+//! Template instantiation: fill `{{placeholder}}` tokens in a workbook from a
+//! map of values, for the `instantiate` command.
+//!
+//! Tokens are replaced in text cells, sheet headers and footers, and defined
+//! names. A cell holding nothing but one token takes the value's type, so
+//! `{{revenue}}` with `1250.5` becomes a number the sheet's formulas can use;
+//! anywhere else the value is spliced in as text. Formulas are left alone:
+//! point them at a cell holding the placeholder instead.
+
+use crate::write::save_workbook;
+use anyhow::{Context, Result, anyhow, bail};
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::OnceLock;
+use umya_spreadsheet::Spreadsheet;
+
+/// Locations kept per unresolved placeholder.
+const MAX_LOCATIONS: usize = 10;
+
+/// A value for one placeholder.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplateValue {
+    Text(String),
+    Number(f64),
+    Bool(bool),
+}
+
+impl TemplateValue {
+    fn as_text(&self) -> String {
+        match self {
+            Self::Text(text) => text.clone(),
+            Self::Number(number) => number.to_string(),
+            Self::Bool(flag) => if *flag { "TRUE" } else { "FALSE" }.to_string(),
+        }
+    }
+}
+
+/// Placeholder values keyed by name. Nested objects are flattened with dots,
+/// so `{"client":{"name":"Acme"}}` fills `{{client.name}}`.
+pub fn parse_template_values(json: &Value) -> Result<BTreeMap<String, TemplateValue>> {
+    let Value::Object(object) = json else {
+        bail!("values must be a JSON object of placeholder names to values");
+    };
+    let mut values = BTreeMap::new();
+    flatten_values("", object, &mut values)?;
+    Ok(values)
+}
+
+fn flatten_values(
+    prefix: &str,
+    object: &serde_json::Map<String, Value>,
+    values: &mut BTreeMap<String, TemplateValue>,
+) -> Result<()> {
+    for (key, value) in object {
+        let name = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        let value = match value {
+            Value::Object(nested) => {
+                flatten_values(&name, nested, values)?;
+                continue;
+            }
+            Value::String(text) => TemplateValue::Text(text.clone()),
+            Value::Number(number) => TemplateValue::Number(
+                number
+                    .as_f64()
+                    .ok_or_else(|| anyhow!("value for '{name}' is not a finite number"))?,
+            ),
+            Value::Bool(flag) => TemplateValue::Bool(*flag),
+            Value::Null => TemplateValue::Text(String::new()),
+            Value::Array(_) => bail!("value for '{name}' must be a string, number or boolean"),
+        };
+        values.insert(name, value);
+    }
+    Ok(())
+}
+
+/// A placeholder with no supplied value.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct UnresolvedPlaceholder {
+    pub placeholder: String,
+    pub occurrences: u64,
+    /// Where it was found (`Sheet1!B2`, `Sheet1 header`, `name ClientName`),
+    /// first ten only
+    pub locations: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize, JsonSchema)]
+pub struct InstantiateSummary {
+    pub placeholders_replaced: u64,
+    pub cells_updated: u64,
+    pub headers_footers_updated: u64,
+    pub names_updated: u64,
+    /// Replacements per supplied value
+    pub values_used: BTreeMap<String, u64>,
+    /// Supplied values no placeholder asked for
+    pub values_unused: Vec<String>,
+    pub unresolved: Vec<UnresolvedPlaceholder>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+/// Fill placeholders in the workbook at `path`, rewriting it in place.
+pub fn instantiate_workbook(
+    path: &Path,
+    values: &BTreeMap<String, TemplateValue>,
+) -> Result<InstantiateSummary> {
+    let mut book = umya_spreadsheet::reader::xlsx::read(path)
+        .with_context(|| format!("failed to read template '{}'", path.display()))?;
+    let summary = instantiate_book(&mut book, values);
+    save_workbook(&book, path)?;
+    Ok(summary)
+}
+
+pub fn instantiate_book(
+    book: &mut Spreadsheet,
+    values: &BTreeMap<String, TemplateValue>,
+) -> InstantiateSummary {
+    let mut filler = Filler {
+        values,
+        summary: InstantiateSummary::default(),
+        unresolved: BTreeMap::new(),
+    };
+    let mut formula_cells = Vec::new();
+
+    for sheet in book.get_sheet_collection_mut() {
+        let sheet_name = sheet.get_name().to_string();
+        for cell in sheet.get_cell_collection_mut() {
+            let address = cell.get_coordinate().get_coordinate().to_string();
+            if cell.is_formula() {
+                if cell.get_formula().contains("{{") {
+                    formula_cells.push(format!("{sheet_name}!{address}"));
+                }
+                continue;
+            }
+            let text = cell.get_value().to_string();
+            if !text.contains("{{") {
+                continue;
+            }
+            let location = format!("{sheet_name}!{address}");
+            if let Some(name) = sole_placeholder(&text)
+                && let Some(value) = values.get(name)
+            {
+                match value {
+                    TemplateValue::Number(number) => {
+                        cell.set_value_number(*number);
+                    }
+                    TemplateValue::Bool(flag) => {
+                        cell.set_value_bool(*flag);
+                    }
+                    TemplateValue::Text(text) => {
+                        cell.set_value_string(text.clone());
+                    }
+                }
+                filler.record_use(name);
+                filler.summary.cells_updated += 1;
+                continue;
+            }
+            if let Some(filled) = filler.fill(&text, &location, |text| text.to_string()) {
+                cell.set_value_string(filled);
+                filler.summary.cells_updated += 1;
+            }
+        }
+
+        let header_footer = sheet.get_header_footer();
+        let parts = [
+            (
+                "header",
+                header_footer.get_odd_header().get_value().to_string(),
+            ),
+            (
+                "footer",
+                header_footer.get_odd_footer().get_value().to_string(),
+            ),
+            (
+                "even page header",
+                header_footer.get_even_header().get_value().to_string(),
+            ),
+            (
+                "even page footer",
+                header_footer.get_even_footer().get_value().to_string(),
+            ),
+            (
+                "first page header",
+                header_footer.get_first_header().get_value().to_string(),
+            ),
+            (
+                "first page footer",
+                header_footer.get_first_footer().get_value().to_string(),
+            ),
+        ];
+        for (label, text) in parts {
+            let location = format!("{sheet_name} {label}");
+            // `&` starts a header/footer code, so literal ampersands are doubled.
+            let Some(filled) = filler.fill(&text, &location, |text| text.replace('&', "&&")) else {
+                continue;
+            };
+            let header_footer = sheet.get_header_footer_mut();
+            match label {
+                "header" => {
+                    header_footer.get_odd_header_mut().set_value(filled);
+                }
+                "footer" => {
+                    header_footer.get_odd_footer_mut().set_value(filled);
+                }
+                "even page header" => {
+                    header_footer.get_even_header_mut().set_value(filled);
+                }
+                "even page footer" => {
+                    header_footer.get_even_footer_mut().set_value(filled);
+                }
+                "first page header" => {
+                    header_footer.get_first_header_mut().set_value(filled);
+                }
+                _ => {
+                    header_footer.get_first_footer_mut().set_value(filled);
+                }
+            }
+            filler.summary.headers_footers_updated += 1;
+        }
+
+        for defined in sheet.get_defined_names_mut() {
+            filler.fill_defined_name(defined);
+        }
+    }
+    for defined in book.get_defined_names_mut() {
+        filler.fill_defined_name(defined);
+    }
+
+    if !formula_cells.is_empty() {
+        let shown: Vec<&str> = formula_cells
+            .iter()
+            .take(MAX_LOCATIONS)
+            .map(String::as_str)
+            .collect();
+        filler.summary.warnings.push(format!(
+            "WARN_PLACEHOLDER_IN_FORMULA: {} formula cell(s) contain '{{{{' and were left unchanged ({}); put the placeholder in a cell the formula references instead.",
+            formula_cells.len(),
+            shown.join(", ")
+        ));
+    }
+
+    let Filler {
+        mut summary,
+        unresolved,
+        ..
+    } = filler;
+    summary.values_unused = values
+        .keys()
+        .filter(|name| !summary.values_used.contains_key(*name))
+        .cloned()
+        .collect();
+    summary.unresolved = unresolved.into_values().collect();
+    summary
+}
+
+struct Filler<'a> {
+    values: &'a BTreeMap<String, TemplateValue>,
+    summary: InstantiateSummary,
+    unresolved: BTreeMap<String, UnresolvedPlaceholder>,
+}
+
+impl Filler<'_> {
+    /// `text` with supplied placeholders replaced by their escaped values,
+    /// or `None` when nothing was replaced. Placeholders without a value
+    /// are kept and recorded at `location`.
+    fn fill(
+        &mut self,
+        text: &str,
+        location: &str,
+        escape: impl Fn(&str) -> String,
+    ) -> Option<String> {
+        if !text.contains("{{") {
+            return None;
+        }
+        let mut replaced = false;
+        let mut used = Vec::new();
+        let mut missing = Vec::new();
+        let filled = placeholder_pattern().replace_all(text, |captures: &regex::Captures| {
+            let name = &captures[1];
+            match self.values.get(name) {
+                Some(value) => {
+                    replaced = true;
+                    used.push(name.to_string());
+                    escape(&value.as_text())
+                }
+                None => {
+                    missing.push(name.to_string());
+                    captures[0].to_string()
+                }
+            }
+        });
+        let filled = filled.into_owned();
+        for name in used {
+            self.record_use(&name);
+        }
+        for name in missing {
+            let entry =
+                self.unresolved
+                    .entry(name.clone())
+                    .or_insert_with(|| UnresolvedPlaceholder {
+                        placeholder: name,
+                        occurrences: 0,
+                        locations: Vec::new(),
+                    });
+            entry.occurrences += 1;
+            if entry.locations.len() < MAX_LOCATIONS
+                && entry.locations.last().map(String::as_str) != Some(location)
+            {
+                entry.locations.push(location.to_string());
+            }
+        }
+        replaced.then_some(filled)
+    }
+
+    fn fill_defined_name(&mut self, defined: &mut umya_spreadsheet::DefinedName) {
+        let location = format!("name {}", defined.get_name());
+        // Names hold formulas, where text sits in double-quoted literals.
+        if let Some(filled) = self.fill(&defined.get_address(), &location, |text| {
+            text.replace('"', "\"\"")
+        }) {
+            defined.set_address(filled);
+            self.summary.names_updated += 1;
+        }
+    }
+
+    fn record_use(&mut self, name: &str) {
+        self.summary.placeholders_replaced += 1;
+        *self
+            .summary
+            .values_used
+            .entry(name.to_string())
+            .or_insert(0) += 1;
+    }
+}
+
+/// `{{name}}`, with optional spaces inside the braces; names are letters,
+/// digits, `_`, `.` and `-`.
+fn placeholder_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_.\-]*)\s*\}\}").expect("valid placeholder regex")
+    })
+}
+
+/// The placeholder name when `text` is one placeholder and nothing else.
+fn sole_placeholder(text: &str) -> Option<&str> {
+    let trimmed = text.trim();
+    let captures = placeholder_pattern().captures(trimmed)?;
+    let whole = captures.get(0)?;
+    if whole.start() != 0 || whole.end() != trimmed.len() {
+        return None;
+    }
+    captures.get(1).map(|name| name.as_str())
+}
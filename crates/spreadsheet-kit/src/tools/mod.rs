@@ -1,28 +1,87 @@
+pub mod aggregate;
+pub mod alias;
+#[cfg(feature = "arrow")]
+pub mod arrow_ipc;
+pub mod catalog;
+pub mod column_stats;
+#[cfg(feature = "compression")]
+pub mod compression;
+#[cfg(feature = "recalc")]
+pub mod converge;
+#[cfg(feature = "recalc")]
+pub mod copy_sheet;
+pub mod csv_format;
+#[cfg(all(feature = "recalc", not(target_arch = "wasm32")))]
+pub mod custom_op_plugins;
+#[cfg(feature = "recalc")]
+pub mod custom_ops;
+pub mod dedupe;
+pub mod doc_properties;
+pub mod embedded_objects;
+pub mod external_links;
+#[cfg(feature = "recalc")]
+pub mod fill_series;
 pub mod filters;
 #[cfg(feature = "recalc")]
 pub mod fork;
+#[cfg(all(feature = "recalc", not(target_arch = "wasm32")))]
+pub mod fork_webhook;
+pub mod formula_lint;
+#[cfg(feature = "recalc")]
+pub mod import_rows;
+pub mod instantiate;
+pub mod named_query;
+pub mod ooxml;
+pub mod outline;
 pub mod param_enums;
 #[cfg(feature = "recalc")]
+pub mod patch_save;
+#[cfg(feature = "recalc")]
+pub mod range_transfer;
+pub mod ref_repair;
+pub mod rounding;
+#[cfg(feature = "recalc")]
 pub mod rules_batch;
+pub mod sheet_direction;
 #[cfg(feature = "recalc")]
 pub mod sheet_layout;
 #[cfg(feature = "recalc")]
+pub mod sheet_tabs;
+#[cfg(feature = "recalc")]
+pub mod sort_rows;
+#[cfg(feature = "recalc")]
 pub mod structure_impact;
+pub mod table_schema;
+#[cfg(feature = "recalc")]
+pub mod translation;
+pub mod value_locale;
 pub mod vba;
+pub mod workbook_props;
+#[cfg(feature = "recalc")]
+pub mod write_cells;
 #[cfg(feature = "recalc")]
 pub mod write_normalize;
 
 use crate::analysis::{formula::FormulaGraph, stats};
+use crate::column_map::{header_similarity, round3};
 use crate::config::OutputProfile;
 use crate::model::*;
+use crate::number_format::{cell_display_text, cell_format_code};
+use crate::repository::VirtualWorkbookInput;
 use crate::state::AppState;
-use crate::utils::column_number_to_name;
+use crate::utils::{column_number_to_name, hash_bytes_sha256_hex};
 use crate::verification::{VerifyOptions, VerifyResponse, compare_workbooks};
-use crate::workbook::{WorkbookContext, cell_to_value};
+use crate::workbook::{WorkbookContext, cell_to_value, iso_to_excel_serial, with_date_handling};
+use crate::write::save_workbook;
 use anyhow::{Context, Result, anyhow};
+use csv_format::CsvOptions;
+use param_enums::{
+    AggregateOp, DateHandling, HeaderMatchMode, RoundingMode, SchemaFormat, TableOrientation,
+};
 use regex::Regex;
+use rounding::RoundingPolicy;
 use schemars::JsonSchema;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::PathBuf;
@@ -535,6 +594,14 @@ pub struct SheetPageParams {
     /// Limit to columns by header text (matched case-insensitively)
     #[serde(default)]
     pub columns_by_header: Option<Vec<String>>,
+    /// How columns_by_header matches headers: "exact" (default), "contains"
+    /// (every header containing the text) or "fuzzy" (the most similar header).
+    /// The response lists what each selector resolved to.
+    #[serde(default)]
+    pub header_match: Option<HeaderMatchMode>,
+    /// Minimum similarity (0-1] for header_match=fuzzy (default: 0.6)
+    #[serde(default)]
+    pub header_match_threshold: Option<f64>,
     /// Include formulas (default: false in token_dense profile)
     #[serde(default = "default_include_formulas")]
     pub include_formulas: bool,
@@ -547,6 +614,24 @@ pub struct SheetPageParams {
     /// Output format: "compact" (default in token_dense) or "full" (per-cell objects)
     #[serde(default)]
     pub format: Option<SheetPageFormat>,
+    /// "rows" (default) or "columns". Column pages return one record per column
+    /// with every value from start_row down; page_size then counts columns.
+    #[serde(default)]
+    pub orientation: Option<SheetPageOrientation>,
+    /// First column letter for column-oriented pages (continue with next_start_column)
+    #[serde(default)]
+    pub start_column: Option<String>,
+    /// Date-formatted numbers: "iso" (default, ISO-8601 text), "raw" (serial
+    /// number), or "both" (ISO text plus serials in `date_serials`)
+    #[serde(default)]
+    pub date_handling: Option<DateHandling>,
+    /// Compress the page data of large results: "gzip" or "zstd". The data
+    /// fields move into a base64 `compressed` blob with its sizes
+    #[serde(default)]
+    pub compression: Option<ResponseCompression>,
+    /// Smallest page data, in bytes of JSON, worth compressing (default: 65536)
+    #[serde(default)]
+    pub compress_min_bytes: Option<u64>,
 }
 
 impl Default for SheetPageParams {
@@ -558,10 +643,17 @@ impl Default for SheetPageParams {
             page_size: default_page_size(),
             columns: None,
             columns_by_header: None,
+            header_match: None,
+            header_match_threshold: None,
             include_formulas: default_include_formulas(),
             include_styles: false,
             include_header: default_include_header(),
             format: None,
+            orientation: None,
+            start_column: None,
+            date_handling: None,
+            compression: None,
+            compress_min_bytes: None,
         }
     }
 }
@@ -619,6 +711,13 @@ pub struct FindValueParams {
     /// Number of cells in each direction for context (default: 3)
     #[serde(default)]
     pub context_width: Option<u32>,
+    /// Return per-sheet match counts and first addresses in `sheets` instead
+    /// of individual matches; `limit`/`offset` then page the sheets
+    #[serde(default)]
+    pub group_by_sheet: bool,
+    /// Compare numbers or dates against `query` instead of matching text
+    #[serde(default)]
+    pub comparison: Option<ValueComparison>,
 }
 
 impl Default for FindValueParams {
@@ -640,6 +739,8 @@ impl Default for FindValueParams {
             offset: None,
             context: None,
             context_width: None,
+            group_by_sheet: false,
+            comparison: None,
         }
     }
 }
@@ -664,9 +765,16 @@ pub struct ReadTableParams {
     /// 1-based row number for headers (auto-detected if omitted)
     #[serde(default)]
     pub header_row: Option<u32>,
-    /// Number of header rows for multi-row headers (default: 1)
+    /// Number of header rows; several are flattened into names like
+    /// "Q1 / Actual" (default: 1; 0 reads every row as data, naming columns
+    /// by letter)
     #[serde(default)]
     pub header_rows: Option<u32>,
+    /// "rows" (default) or "transposed": headers down the first column and
+    /// one record per column. Transposed, `header_row`/`header_rows` count
+    /// columns, and filters, sort, and types apply to the records
+    #[serde(default)]
+    pub orientation: Option<TableOrientation>,
     /// Limit to specific columns by letter (e.g., ["A", "C", "D"])
     #[serde(default)]
     pub columns: Option<Vec<String>>,
@@ -691,9 +799,60 @@ pub struct ReadTableParams {
     /// Include column type information (default: false)
     #[serde(default)]
     pub include_types: Option<bool>,
+    /// Sort matching rows before paging, by header name; first key wins ties
+    #[serde(default)]
+    pub sort: Option<Vec<TableSort>>,
+    /// Named query from workspace config; explicit params override its fields
+    #[serde(default)]
+    pub query: Option<String>,
+    /// Round numeric results to this many decimal places (default: unrounded)
+    #[serde(default)]
+    pub decimals: Option<u32>,
+    /// Tie-breaking for `decimals`: "half_up" (default, as Excel ROUND) or "half_even" (banker's)
+    #[serde(default)]
+    pub rounding: Option<RoundingMode>,
+    /// Scope of `table_name` when it is defined at both workbook and sheet
+    /// scope: "workbook" or "sheet" (default: the `sheet_name` scope, else workbook)
+    #[serde(default)]
+    pub name_scope: Option<NamedRangeScope>,
+    /// Delimiter, quoting, BOM, line ending, and decimal separator for "csv" output
+    #[serde(default)]
+    pub csv_options: Option<CsvOptions>,
+    /// Date-formatted numbers: "iso" (default, ISO-8601 text), "raw" (serial
+    /// number), or "both" (ISO text plus serials in `date_serials`)
+    #[serde(default)]
+    pub date_handling: Option<DateHandling>,
+    /// Compress the table data of large results: "gzip" or "zstd". The data
+    /// fields move into a base64 `compressed` blob with its sizes
+    #[serde(default)]
+    pub compression: Option<ResponseCompression>,
+    /// Smallest table data, in bytes of JSON, worth compressing (default: 65536)
+    #[serde(default)]
+    pub compress_min_bytes: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+pub struct TableSort {
+    /// Header name
+    pub column: String,
+    /// Sort largest/last first (default: ascending)
+    #[serde(default)]
+    pub descending: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+pub struct TableAggregation {
+    /// "sum", "avg", "count", "min", or "max"
+    pub op: AggregateOp,
+    /// Header name; count without a column counts rows
+    #[serde(default)]
+    pub column: Option<String>,
+    /// Output column name (default: e.g. "sum_Amount", or "count")
+    #[serde(default)]
+    pub alias: Option<String>,
 }
 
-#[derive(Debug, Deserialize, JsonSchema, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 pub struct TableFilter {
     /// Column letter or header name
     pub column: String,
@@ -726,6 +885,19 @@ pub struct TableProfileParams {
     /// Return only column types without samples (default: true in token_dense profile)
     #[serde(default)]
     pub summary_only: Option<bool>,
+    /// Round numeric results to this many decimal places (default: unrounded)
+    #[serde(default)]
+    pub decimals: Option<u32>,
+    /// Tie-breaking for `decimals`: "half_up" (default, as Excel ROUND) or "half_even" (banker's)
+    #[serde(default)]
+    pub rounding: Option<RoundingMode>,
+    /// Most frequent values to list per column in `stats.top_values` (default: 5)
+    #[serde(default)]
+    pub top_n: Option<u32>,
+    /// Also return a schema for the table, inferred over every row:
+    /// "jsonschema" or "arrow"
+    #[serde(default)]
+    pub emit_schema: Option<SchemaFormat>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -743,12 +915,26 @@ pub struct RangeValuesParams {
     /// Include formula text payload (matrix for json, sparse list for dense) (default: false)
     #[serde(default)]
     pub include_formulas: Option<bool>,
+    /// Include number format codes and display text (e.g. "12.3%") for non-empty
+    /// cells; csv output then carries the display text (default: false)
+    #[serde(default)]
+    pub include_formatting: Option<bool>,
     /// Output format: "dense" (default), "values", "csv", or "json"
     #[serde(default)]
     pub format: Option<TableOutputFormat>,
     /// Maximum rows per range before pagination
     #[serde(default)]
     pub page_size: Option<u32>,
+    /// Delimiter, quoting, BOM, line ending, and decimal separator for "csv" output
+    #[serde(default)]
+    pub csv_options: Option<CsvOptions>,
+    /// Compress large results: "gzip" or "zstd". `values` moves into a base64
+    /// `compressed` blob with its sizes
+    #[serde(default)]
+    pub compression: Option<ResponseCompression>,
+    /// Smallest `values`, in bytes of JSON, worth compressing (default: 65536)
+    #[serde(default)]
+    pub compress_min_bytes: Option<u64>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -772,6 +958,15 @@ pub struct InspectCellsParams {
 pub async fn sheet_page(
     state: Arc<AppState>,
     params: SheetPageParams,
+) -> Result<SheetPageResponse> {
+    let (encoding, min_bytes) = (params.compression, params.compress_min_bytes);
+    let response = read_sheet_page(state, params).await?;
+    with_requested_compression(response, encoding, min_bytes)
+}
+
+async fn read_sheet_page(
+    state: Arc<AppState>,
+    params: SheetPageParams,
 ) -> Result<SheetPageResponse> {
     if params.page_size == 0 {
         return Err(anyhow!("page_size must be greater than zero"));
@@ -801,21 +996,50 @@ pub async fn sheet_page(
             params.include_styles
         };
     let columns = params.columns.clone();
-    let columns_by_header = params.columns_by_header.clone();
+    let header_selector = HeaderSelector::from_params(&params)?;
     let include_header = params.include_header;
 
-    let mut page = workbook.with_sheet(&params.sheet_name, |sheet| {
-        build_page(
-            sheet,
-            start_row,
-            page_size,
-            columns.clone(),
-            columns_by_header.clone(),
-            include_formulas,
-            include_styles,
-            include_header,
-        )
+    if matches!(params.orientation, Some(SheetPageOrientation::Columns)) {
+        return sheet_page_by_columns(
+            &workbook,
+            &params,
+            ColumnPageOptions {
+                start_row,
+                page_size,
+                row_count: metrics.metrics.row_count,
+                format,
+                include_formulas,
+                include_styles,
+                max_cells: config.max_cells(),
+                max_payload_bytes: config.max_payload_bytes(),
+            },
+        );
+    }
+    if params.start_column.is_some() {
+        return Err(anyhow!("start_column requires orientation=columns"));
+    }
+
+    let date_handling = params.date_handling.unwrap_or_default();
+    let mut page = with_date_handling(date_handling, || {
+        workbook.with_sheet(&params.sheet_name, |sheet| {
+            build_page(
+                sheet,
+                start_row,
+                page_size,
+                columns.clone(),
+                header_selector.as_ref(),
+                include_formulas,
+                include_styles,
+                include_header,
+            )
+        })
     })?;
+    let outline = workbook.sheet_outline(&params.sheet_name);
+    if !outline.rows.is_empty() {
+        for row in page.rows.iter_mut().chain(page.header.iter_mut()) {
+            row.outline_level = outline.row_level(row.row_index);
+        }
+    }
 
     let max_cells = config.max_cells();
     let max_payload_bytes = config.max_payload_bytes();
@@ -892,9 +1116,284 @@ pub async fn sheet_page(
     );
     response.truncated = truncated;
     response.budget = budget;
+    response.resolved_headers = page.resolved_headers;
+    response.unmatched_headers = page.unmatched_headers;
+    if date_handling == DateHandling::Both {
+        let cells = page
+            .header
+            .iter()
+            .chain(&page.rows)
+            .flat_map(|row| &row.cells);
+        response.date_serials = Some(cell_date_serials(cells, workbook.uses_1904_date_system()));
+    }
+    Ok(response)
+}
+
+struct ColumnPageOptions {
+    start_row: u32,
+    page_size: u32,
+    row_count: u32,
+    format: SheetPageFormat,
+    include_formulas: bool,
+    include_styles: bool,
+    max_cells: Option<usize>,
+    max_payload_bytes: Option<usize>,
+}
+
+/// Column-oriented variant of `sheet_page`: continuation runs across the
+/// selected columns instead of rows.
+fn sheet_page_by_columns(
+    workbook: &WorkbookContext,
+    params: &SheetPageParams,
+    options: ColumnPageOptions,
+) -> Result<SheetPageResponse> {
+    let start_column = params
+        .start_column
+        .as_deref()
+        .map(parse_column_letters)
+        .transpose()?;
+    let first_row = if params.include_header {
+        options.start_row.max(2)
+    } else {
+        options.start_row
+    };
+    let last_row = options.row_count;
+    let rows_per_column = (last_row + 1).saturating_sub(first_row) as usize;
+
+    let header_selector = HeaderSelector::from_params(params)?;
+    let date_handling = params.date_handling.unwrap_or_default();
+    let (mut columns, selected, selection) = with_date_handling(date_handling, || {
+        workbook.with_sheet(&params.sheet_name, |sheet| {
+            let mut selection = resolve_columns_with_headers(
+                sheet,
+                params.columns.as_ref(),
+                header_selector.as_ref(),
+                sheet.get_highest_column(),
+            );
+            let selected: Vec<u32> = std::mem::take(&mut selection.columns)
+                .into_iter()
+                .filter(|col| start_column.is_none_or(|start| *col >= start))
+                .collect();
+            let columns: Vec<ColumnSnapshot> = selected
+                .iter()
+                .take(options.page_size as usize)
+                .map(|&col| {
+                    build_column_snapshot(
+                        sheet,
+                        col,
+                        first_row,
+                        last_row,
+                        options.format,
+                        options.include_formulas,
+                        options.include_styles,
+                        params.include_header,
+                    )
+                })
+                .collect();
+            (columns, selected, selection)
+        })
+    })?;
+
+    let original_column_count = columns.len();
+    let mut column_limit = cap_rows_by_cells(columns.len(), rows_per_column, options.max_cells);
+    if column_limit > 0 {
+        column_limit =
+            cap_rows_by_payload_bytes(column_limit, options.max_payload_bytes, |count| {
+                let response = build_column_page_response(
+                    workbook,
+                    &params.sheet_name,
+                    options.format,
+                    first_row,
+                    last_row,
+                    columns[..count].to_vec(),
+                    None,
+                );
+                serde_json::to_vec(&response)
+                    .map(|payload| payload.len())
+                    .unwrap_or(usize::MAX)
+            });
+    }
+    let truncated = column_limit < original_column_count;
+    columns.truncate(column_limit);
+
+    let next_start_column = selected
+        .get(columns.len())
+        .map(|col| column_number_to_name(*col));
+    let columns_returned = columns.len();
+    let budget = if truncated || options.max_cells.is_some() || options.max_payload_bytes.is_some()
+    {
+        let continuation = next_start_column.as_ref().map(|next| {
+            format!(
+                "use start_column={} to fetch the next page ({} columns remaining)",
+                next,
+                selected.len() - columns_returned
+            )
+        });
+        Some(ReadBudget {
+            max_cells: options.max_cells,
+            max_payload_bytes: options.max_payload_bytes,
+            rows_returned: rows_per_column,
+            cells_returned: rows_per_column * columns_returned,
+            total_rows_available: Some(options.row_count),
+            continuation,
+        })
+    } else {
+        None
+    };
+
+    let date_serials = (date_handling == DateHandling::Both)
+        .then(|| column_page_date_serials(&columns, first_row, workbook.uses_1904_date_system()));
+    let mut response = build_column_page_response(
+        workbook,
+        &params.sheet_name,
+        options.format,
+        first_row,
+        last_row,
+        columns,
+        next_start_column,
+    );
+    response.truncated = truncated;
+    response.budget = budget;
+    response.date_serials = date_serials;
+    response.resolved_headers = selection.resolved_headers;
+    response.unmatched_headers = selection.unmatched_headers;
     Ok(response)
 }
 
+/// Serials behind the date cells among `cells`, by A1 address.
+fn cell_date_serials<'a>(
+    cells: impl IntoIterator<Item = &'a CellSnapshot>,
+    use_1904_system: bool,
+) -> BTreeMap<String, f64> {
+    cells
+        .into_iter()
+        .filter_map(|cell| match &cell.value {
+            Some(CellValue::Date(text)) => iso_to_excel_serial(text, use_1904_system)
+                .map(|serial| (cell.address.clone(), serial)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Serials behind the date cells of a column-oriented page, by A1 address.
+fn column_page_date_serials(
+    columns: &[ColumnSnapshot],
+    first_row: u32,
+    use_1904_system: bool,
+) -> BTreeMap<String, f64> {
+    let mut serials = BTreeMap::new();
+    for column in columns {
+        for (offset, value) in column.values.iter().enumerate() {
+            if let Some(CellValue::Date(text)) = value
+                && let Some(serial) = iso_to_excel_serial(text, use_1904_system)
+            {
+                serials.insert(
+                    format!("{}{}", column.column, first_row + offset as u32),
+                    serial,
+                );
+            }
+        }
+        serials.extend(cell_date_serials(&column.cells, use_1904_system));
+    }
+    serials
+}
+
+fn parse_column_letters(raw: &str) -> Result<u32> {
+    let trimmed = raw.trim();
+    if trimmed.is_empty()
+        || trimmed.len() > 3
+        || !trimmed.chars().all(|ch| ch.is_ascii_alphabetic())
+    {
+        return Err(anyhow!(
+            "invalid start_column '{}': expected a column letter such as D",
+            raw
+        ));
+    }
+    Ok(
+        umya_spreadsheet::helper::coordinate::column_index_from_string(
+            trimmed.to_ascii_uppercase(),
+        ),
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn build_column_snapshot(
+    sheet: &umya_spreadsheet::Worksheet,
+    col: u32,
+    first_row: u32,
+    last_row: u32,
+    format: SheetPageFormat,
+    include_formulas: bool,
+    include_styles: bool,
+    include_header: bool,
+) -> ColumnSnapshot {
+    let header = if include_header {
+        sheet
+            .get_cell((col, 1u32))
+            .map(|cell| cell.get_value().to_string())
+            .filter(|text| !text.is_empty())
+    } else {
+        None
+    };
+
+    let mut values = Vec::new();
+    let mut cells = Vec::new();
+    for row in first_row..=last_row {
+        if matches!(format, SheetPageFormat::Full) {
+            cells.push(cell_snapshot_at(
+                sheet,
+                col,
+                row,
+                include_formulas,
+                include_styles,
+            ));
+        } else {
+            values.push(sheet.get_cell((col, row)).and_then(cell_to_value));
+        }
+    }
+
+    ColumnSnapshot {
+        column: column_number_to_name(col),
+        header,
+        values,
+        cells,
+    }
+}
+
+fn build_column_page_response(
+    workbook: &WorkbookContext,
+    sheet_name: &str,
+    format: SheetPageFormat,
+    first_row: u32,
+    last_row: u32,
+    columns: Vec<ColumnSnapshot>,
+    next_start_column: Option<String>,
+) -> SheetPageResponse {
+    SheetPageResponse {
+        workbook_id: workbook.id.clone(),
+        sheet_name: sheet_name.to_string(),
+        rows: Vec::new(),
+        next_start_row: None,
+        header_row: None,
+        compact: None,
+        values_only: None,
+        orientation: Some(SheetPageOrientation::Columns),
+        column_page: Some(SheetPageColumns {
+            first_row,
+            last_row,
+            columns,
+        }),
+        next_start_column,
+        date_serials: None,
+        resolved_headers: Vec::new(),
+        unmatched_headers: Vec::new(),
+        format,
+        truncated: false,
+        budget: None,
+        compressed: None,
+    }
+}
+
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct SheetFormulaMapParams {
     /// Workbook ID or fork ID
@@ -925,6 +1424,14 @@ pub struct SheetFormulaMapParams {
     /// Formula parse policy: fail, warn (default), or off
     #[serde(default)]
     pub formula_parse_policy: Option<FormulaParsePolicy>,
+    /// Report cells that break the formula pattern of their filled column or
+    /// row region instead of formula groups (default: false)
+    #[serde(default)]
+    pub inconsistencies: bool,
+    /// Display and group formulas as "a1" (default) or "r1c1"; in r1c1 form a
+    /// formula filled down a column collapses into one group
+    #[serde(default)]
+    pub reference_style: ReferenceStyle,
 }
 
 #[derive(Debug, Clone, Copy, Default, Deserialize, JsonSchema)]
@@ -966,8 +1473,114 @@ pub enum FindContext {
     Both,
 }
 
+/// Numeric or date comparison for find_value, in place of text matching.
+/// `query` holds the bound: a number (`1,000,000` is accepted) or an ISO
+/// date (`2026-01-31` or `2026-01-31T09:00:00`).
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct ValueComparison {
+    /// Which cells to compare: plain numbers or date-formatted numbers
+    pub value_type: ComparisonValueType,
+    pub op: ComparisonOp,
+    /// Upper bound for `between`
+    #[serde(default)]
+    pub upper: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ComparisonValueType {
+    Number,
+    Date,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ComparisonOp {
+    /// Greater than the bound
+    Gt,
+    /// Less than the bound
+    Lt,
+    /// Between `query` and `upper`, inclusive
+    Between,
+}
+
+/// A `ValueComparison` with its bounds parsed to numbers (date serials for
+/// dates).
+#[derive(Debug, Clone, Copy)]
+struct ComparisonBounds {
+    value_type: ComparisonValueType,
+    op: ComparisonOp,
+    lower: f64,
+    upper: f64,
+}
+
+impl ComparisonBounds {
+    fn parse(comparison: &ValueComparison, query: &str, use_1904_system: bool) -> Result<Self> {
+        let parse_bound = |raw: &str| -> Result<f64> {
+            let raw = raw.trim();
+            match comparison.value_type {
+                ComparisonValueType::Number => raw
+                    .replace([',', '_'], "")
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|value| value.is_finite())
+                    .ok_or_else(|| anyhow!("find_value: '{raw}' is not a number")),
+                ComparisonValueType::Date => iso_to_excel_serial(raw, use_1904_system)
+                    .ok_or_else(|| {
+                        anyhow!(
+                            "find_value: '{raw}' is not an ISO date (YYYY-MM-DD or YYYY-MM-DDTHH:MM:SS)"
+                        )
+                    }),
+            }
+        };
+        let lower = parse_bound(query)?;
+        let upper = match (comparison.op, comparison.upper.as_deref()) {
+            (ComparisonOp::Between, Some(upper)) => parse_bound(upper)?,
+            (ComparisonOp::Between, None) => {
+                return Err(anyhow!("find_value: op 'between' requires an upper bound"));
+            }
+            (_, Some(_)) => {
+                return Err(anyhow!(
+                    "find_value: an upper bound is only used with op 'between'"
+                ));
+            }
+            (_, None) => lower,
+        };
+        if lower > upper {
+            return Err(anyhow!(
+                "find_value: lower bound '{}' is above upper bound '{}'",
+                query.trim(),
+                comparison.upper.as_deref().unwrap_or_default().trim()
+            ));
+        }
+        Ok(Self {
+            value_type: comparison.value_type,
+            op: comparison.op,
+            lower,
+            upper,
+        })
+    }
+
+    /// Compare the cell's stored number; date-formatted cells only count as
+    /// dates and other numbers only as numbers.
+    fn matches(&self, cell: &umya_spreadsheet::Cell) -> bool {
+        let Ok(value) = cell.get_value().parse::<f64>() else {
+            return false;
+        };
+        let is_date = crate::workbook::is_date_formatted(cell);
+        if is_date != (self.value_type == ComparisonValueType::Date) {
+            return false;
+        }
+        match self.op {
+            ComparisonOp::Gt => value > self.lower,
+            ComparisonOp::Lt => value < self.lower,
+            ComparisonOp::Between => value >= self.lower && value <= self.upper,
+        }
+    }
+}
+
 /// Sampling mode for table reads
-#[derive(Debug, Clone, Copy, Default, Deserialize, JsonSchema, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum SampleMode {
     /// First N rows (default)
@@ -991,7 +1604,7 @@ pub enum StyleGranularity {
 }
 
 /// Filter operators for table queries
-#[derive(Debug, Clone, Copy, Deserialize, JsonSchema, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum FilterOp {
     /// Equal
@@ -1043,6 +1656,48 @@ pub async fn sheet_formula_map(
     let max_items = config.max_items();
     let max_payload_bytes = config.max_payload_bytes();
 
+    if params.inconsistencies {
+        let mut items = workbook.with_sheet(
+            &params.sheet_name,
+            crate::analysis::consistency::find_formula_inconsistencies,
+        )?;
+        if let Some(range) = &params.range {
+            items.retain(|item| address_in_range(&item.address, range));
+        }
+        let total_items = items.len();
+        if let Some(limit) = params.limit {
+            items.truncate(limit as usize);
+        }
+        if let Some(max_items) = max_items {
+            items.truncate(max_items);
+        }
+        if let Some(max_bytes) = max_payload_bytes {
+            let item_limit = cap_rows_by_payload_bytes(items.len(), Some(max_bytes), |count| {
+                let response = SheetFormulaMapResponse {
+                    workbook_id: workbook.id.clone(),
+                    sheet_name: params.sheet_name.clone(),
+                    groups: Vec::new(),
+                    inconsistencies: Some(items[..count].to_vec()),
+                    formula_parse_diagnostics: None,
+                    next_offset: None,
+                };
+                serde_json::to_vec(&response)
+                    .map(|payload| payload.len())
+                    .unwrap_or(usize::MAX)
+            });
+            items.truncate(item_limit);
+        }
+        let next_offset = (items.len() < total_items).then_some(items.len() as u32);
+        return Ok(SheetFormulaMapResponse {
+            workbook_id: workbook.id.clone(),
+            sheet_name: params.sheet_name.clone(),
+            groups: Vec::new(),
+            inconsistencies: Some(items),
+            formula_parse_diagnostics: None,
+            next_offset,
+        });
+    }
+
     let policy = params
         .formula_parse_policy
         .unwrap_or(FormulaParsePolicy::Warn);
@@ -1053,7 +1708,10 @@ pub async fn sheet_formula_map(
     } else {
         None
     };
-    let all_groups = graph.groups();
+    let all_groups = match params.reference_style {
+        ReferenceStyle::A1 => graph.groups(),
+        ReferenceStyle::R1C1 => group_formulas_by_r1c1(graph.groups()),
+    };
     let mut groups = Vec::new();
 
     for mut group in all_groups {
@@ -1112,6 +1770,7 @@ pub async fn sheet_formula_map(
                 workbook_id: workbook.id.clone(),
                 sheet_name: params.sheet_name.clone(),
                 groups: groups[..count].to_vec(),
+                inconsistencies: None,
                 formula_parse_diagnostics: formula_parse_diagnostics.clone(),
                 next_offset: None,
             };
@@ -1135,6 +1794,7 @@ pub async fn sheet_formula_map(
         workbook_id: workbook.id.clone(),
         sheet_name: params.sheet_name.clone(),
         groups,
+        inconsistencies: None,
         formula_parse_diagnostics,
         next_offset,
     };
@@ -1157,6 +1817,9 @@ pub struct FormulaTraceParams {
     /// Formula parse policy: fail, warn (default), or off
     #[serde(default)]
     pub formula_parse_policy: Option<FormulaParsePolicy>,
+    /// Display formulas as "a1" (default) or "r1c1"
+    #[serde(default)]
+    pub reference_style: ReferenceStyle,
 }
 
 pub async fn formula_trace(
@@ -1174,7 +1837,11 @@ pub async fn formula_trace(
     } else {
         None
     };
-    let formula_lookup = build_formula_lookup(&graph);
+    let groups = match params.reference_style {
+        ReferenceStyle::A1 => graph.groups(),
+        ReferenceStyle::R1C1 => group_formulas_by_r1c1(graph.groups()),
+    };
+    let formula_lookup = build_formula_lookup(groups);
     let depth = params.depth.unwrap_or(3).clamp(1, 5);
     let page_size = params
         .page_size
@@ -1217,6 +1884,9 @@ pub struct NamedRangesParams {
     pub workbook_or_fork_id: WorkbookId,
     pub sheet_name: Option<String>,
     pub name_prefix: Option<String>,
+    /// Only names of this scope: "workbook" or "sheet".
+    #[serde(default)]
+    pub scope: Option<NamedRangeScope>,
 }
 
 pub async fn named_ranges(
@@ -1238,6 +1908,9 @@ pub async fn named_ranges(
         let prefix_lower = prefix.to_ascii_lowercase();
         items.retain(|item| item.name.to_ascii_lowercase().starts_with(&prefix_lower));
     }
+    if let Some(scope) = params.scope {
+        items.retain(|item| item.scope_kind == Some(scope));
+    }
 
     let response = NamedRangesResponse {
         workbook_id: workbook.id.clone(),
@@ -1291,6 +1964,7 @@ pub async fn verify_workbook(
                     workbook_or_fork_id: params.baseline_workbook_or_fork_id.clone(),
                     sheet_name: params.sheet_name.clone(),
                     name_prefix: None,
+                    scope: None,
                 },
             )
             .await?,
@@ -1306,6 +1980,7 @@ pub async fn verify_workbook(
                     workbook_or_fork_id: params.current_workbook_or_fork_id.clone(),
                     sheet_name: params.sheet_name.clone(),
                     name_prefix: None,
+                    scope: None,
                 },
             )
             .await?,
@@ -1391,21 +2066,79 @@ pub fn parse_scope_kind_optional(scope: Option<&str>) -> Result<Option<NamedRang
     }
 }
 
-fn resolve_sheet_index_on_book(
-    book: &umya_spreadsheet::Spreadsheet,
-    sheet_name: &str,
-) -> Result<u32> {
-    for (idx, sheet) in book.get_sheet_collection().iter().enumerate() {
-        if sheet.get_name() == sheet_name {
-            return Ok(idx as u32);
+/// Sheets on which `name` is defined with sheet scope, and whether it is also
+/// defined at workbook scope.
+fn defined_name_scopes(book: &umya_spreadsheet::Spreadsheet, name: &str) -> (bool, Vec<String>) {
+    let sheet_names: Vec<String> = book
+        .get_sheet_collection()
+        .iter()
+        .map(|s| s.get_name().to_string())
+        .collect();
+    let mut workbook = false;
+    let mut sheets = Vec::new();
+    for defined in book.get_defined_names() {
+        if defined.get_name() != name {
+            continue;
+        }
+        if !defined.has_local_sheet_id() {
+            workbook = true;
+        } else if let Some(sn) = sheet_names.get(*defined.get_local_sheet_id() as usize) {
+            sheets.push(sn.clone());
         }
     }
-    Err(anyhow!("sheet '{}' not found", sheet_name))
+    for sheet in book.get_sheet_collection() {
+        if sheet
+            .get_defined_names()
+            .iter()
+            .any(|d| d.get_name() == name)
+            && !sheets.iter().any(|sn| sn == sheet.get_name())
+        {
+            sheets.push(sheet.get_name().to_string());
+        }
+    }
+    (workbook, sheets)
 }
 
-/// Apply define_name to an on-disk workbook file.
-pub(crate) fn define_name_in_file(
-    path: &std::path::Path,
+/// Refuse to pick between a workbook-scoped and a sheet-scoped name of the
+/// same name unless the caller said which one it means.
+fn ensure_name_scope_unambiguous(
+    book: &umya_spreadsheet::Spreadsheet,
+    name: &str,
+    scope_kind: Option<NamedRangeScope>,
+    scope_sheet_name: Option<&str>,
+) -> Result<()> {
+    if scope_kind.is_some() {
+        return Ok(());
+    }
+    let (workbook, mut sheets) = defined_name_scopes(book, name);
+    if let Some(filter) = scope_sheet_name {
+        sheets.retain(|sn| sn.eq_ignore_ascii_case(filter));
+    }
+    if workbook && !sheets.is_empty() {
+        return Err(anyhow!(
+            "name '{}' is defined at workbook scope and at sheet scope on {}; pass scope 'workbook' or 'sheet' to choose one",
+            name,
+            sheets.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+fn resolve_sheet_index_on_book(
+    book: &umya_spreadsheet::Spreadsheet,
+    sheet_name: &str,
+) -> Result<u32> {
+    for (idx, sheet) in book.get_sheet_collection().iter().enumerate() {
+        if sheet.get_name() == sheet_name {
+            return Ok(idx as u32);
+        }
+    }
+    Err(anyhow!("sheet '{}' not found", sheet_name))
+}
+
+/// Apply define_name to an on-disk workbook file.
+pub(crate) fn define_name_in_file(
+    path: &std::path::Path,
     name: &str,
     refers_to: &str,
     scope_kind: NamedRangeScope,
@@ -1460,7 +2193,7 @@ pub(crate) fn define_name_in_file(
         }
     }
 
-    umya_spreadsheet::writer::xlsx::write(&book, path)?;
+    save_workbook(&book, path)?;
     Ok(())
 }
 
@@ -1475,39 +2208,61 @@ pub(crate) fn update_name_in_file(
     let mut book = umya_spreadsheet::reader::xlsx::read(path)
         .with_context(|| format!("failed to read workbook '{}'", path.display()))?;
 
+    ensure_name_scope_unambiguous(&book, name, scope_kind, scope_sheet_name)?;
+    let sheet_names: Vec<String> = book
+        .get_sheet_collection()
+        .iter()
+        .map(|s: &umya_spreadsheet::Worksheet| s.get_name().to_string())
+        .collect();
+    let sheet_matches =
+        |sn: &str| scope_sheet_name.is_none_or(|filter| sn.eq_ignore_ascii_case(filter));
+
     let mut found = false;
     let mut previous_refers_to = String::new();
     let mut effective_scope = NamedRangeScope::Workbook;
     let mut effective_sheet: Option<String> = None;
 
-    // Try workbook-level defined names.
-    if scope_kind.is_none() || scope_kind == Some(NamedRangeScope::Workbook) {
-        for defined in book.get_defined_names_mut().iter_mut() {
-            if defined.get_name() == name
-                && (scope_kind == Some(NamedRangeScope::Workbook) || !defined.has_local_sheet_id())
-            {
-                previous_refers_to = defined.get_address();
-                if let Some(new_addr) = new_refers_to {
-                    defined.set_address(new_addr.to_string());
-                }
-                effective_scope = NamedRangeScope::Workbook;
-                found = true;
-                break;
-            }
+    // Workbook-level entries: global names, plus sheet-local names stored with a
+    // localSheetId.
+    for defined in book.get_defined_names_mut().iter_mut() {
+        if defined.get_name() != name {
+            continue;
+        }
+        let local_sheet = defined
+            .has_local_sheet_id()
+            .then(|| {
+                sheet_names
+                    .get(*defined.get_local_sheet_id() as usize)
+                    .cloned()
+            })
+            .flatten();
+        let wanted = match (&local_sheet, scope_kind) {
+            (None, Some(NamedRangeScope::Sheet)) => false,
+            (None, _) => true,
+            (Some(_), Some(NamedRangeScope::Workbook)) => false,
+            (Some(sn), _) => sheet_matches(sn),
+        };
+        if !wanted {
+            continue;
         }
+        previous_refers_to = defined.get_address();
+        if let Some(new_addr) = new_refers_to {
+            defined.set_address(new_addr.to_string());
+        }
+        effective_scope = if local_sheet.is_some() {
+            NamedRangeScope::Sheet
+        } else {
+            NamedRangeScope::Workbook
+        };
+        effective_sheet = local_sheet;
+        found = true;
+        break;
     }
 
     // Try sheet-level.
-    if !found && (scope_kind.is_none() || scope_kind == Some(NamedRangeScope::Sheet)) {
-        let sheet_names: Vec<String> = book
-            .get_sheet_collection()
-            .iter()
-            .map(|s: &umya_spreadsheet::Worksheet| s.get_name().to_string())
-            .collect();
+    if !found && scope_kind != Some(NamedRangeScope::Workbook) {
         for sn in &sheet_names {
-            if let Some(filter_sheet) = scope_sheet_name
-                && !sn.eq_ignore_ascii_case(filter_sheet)
-            {
+            if !sheet_matches(sn) {
                 continue;
             }
             if let Some(sheet) = book.get_sheet_by_name_mut(sn) {
@@ -1534,7 +2289,7 @@ pub(crate) fn update_name_in_file(
         return Err(anyhow!("named range '{}' not found", name));
     }
 
-    umya_spreadsheet::writer::xlsx::write(&book, path)?;
+    save_workbook(&book, path)?;
     Ok((previous_refers_to, effective_scope, effective_sheet))
 }
 
@@ -1548,29 +2303,37 @@ pub(crate) fn delete_name_in_file(
     let mut book = umya_spreadsheet::reader::xlsx::read(path)
         .with_context(|| format!("failed to read workbook '{}'", path.display()))?;
 
-    let mut deleted = false;
-
-    // Try workbook-level.
-    if scope_kind.is_none() || scope_kind == Some(NamedRangeScope::Workbook) {
-        let names = book.get_defined_names_mut();
-        let before_len = names.len();
-        names.retain(|d: &umya_spreadsheet::DefinedName| d.get_name() != name);
-        if names.len() < before_len {
-            deleted = true;
+    ensure_name_scope_unambiguous(&book, name, scope_kind, scope_sheet_name)?;
+    let sheet_names: Vec<String> = book
+        .get_sheet_collection()
+        .iter()
+        .map(|s: &umya_spreadsheet::Worksheet| s.get_name().to_string())
+        .collect();
+    let sheet_matches =
+        |sn: &str| scope_sheet_name.is_none_or(|filter| sn.eq_ignore_ascii_case(filter));
+
+    // Workbook-level entries: global names, plus sheet-local names stored with a
+    // localSheetId.
+    let names = book.get_defined_names_mut();
+    let before_len = names.len();
+    names.retain(|d: &umya_spreadsheet::DefinedName| {
+        if d.get_name() != name {
+            return true;
         }
-    }
+        if !d.has_local_sheet_id() {
+            return scope_kind == Some(NamedRangeScope::Sheet);
+        }
+        scope_kind == Some(NamedRangeScope::Workbook)
+            || !sheet_names
+                .get(*d.get_local_sheet_id() as usize)
+                .is_some_and(|sn| sheet_matches(sn))
+    });
+    let mut deleted = names.len() < before_len;
 
     // Try sheet-level.
-    if !deleted && (scope_kind.is_none() || scope_kind == Some(NamedRangeScope::Sheet)) {
-        let sheet_names: Vec<String> = book
-            .get_sheet_collection()
-            .iter()
-            .map(|s: &umya_spreadsheet::Worksheet| s.get_name().to_string())
-            .collect();
+    if !deleted && scope_kind != Some(NamedRangeScope::Workbook) {
         for sn in &sheet_names {
-            if let Some(filter_sheet) = scope_sheet_name
-                && !sn.eq_ignore_ascii_case(filter_sheet)
-            {
+            if !sheet_matches(sn) {
                 continue;
             }
             if let Some(sheet) = book.get_sheet_by_name_mut(sn) {
@@ -1589,7 +2352,7 @@ pub(crate) fn delete_name_in_file(
         return Err(anyhow!("named range '{}' not found", name));
     }
 
-    umya_spreadsheet::writer::xlsx::write(&book, path)?;
+    save_workbook(&book, path)?;
     Ok(true)
 }
 
@@ -1743,6 +2506,8 @@ pub async fn delete_name(
 struct PageBuildResult {
     rows: Vec<RowSnapshot>,
     header: Option<RowSnapshot>,
+    resolved_headers: Vec<HeaderResolution>,
+    unmatched_headers: Vec<String>,
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -1751,15 +2516,15 @@ fn build_page(
     start_row: u32,
     page_size: u32,
     columns: Option<Vec<String>>,
-    columns_by_header: Option<Vec<String>>,
+    header_selector: Option<&HeaderSelector>,
     include_formulas: bool,
     include_styles: bool,
     include_header: bool,
 ) -> PageBuildResult {
     let max_col = sheet.get_highest_column();
     let end_row = (start_row + page_size - 1).min(sheet.get_highest_row().max(start_row));
-    let column_indices =
-        resolve_columns_with_headers(sheet, columns.as_ref(), columns_by_header.as_ref(), max_col);
+    let selection = resolve_columns_with_headers(sheet, columns.as_ref(), header_selector, max_col);
+    let column_indices = selection.columns;
 
     let header = if include_header {
         Some(build_row_snapshot(
@@ -1784,7 +2549,12 @@ fn build_page(
         ));
     }
 
-    PageBuildResult { rows, header }
+    PageBuildResult {
+        rows,
+        header,
+        resolved_headers: selection.resolved_headers,
+        unmatched_headers: selection.unmatched_headers,
+    }
 }
 
 fn build_row_snapshot(
@@ -1794,25 +2564,42 @@ fn build_row_snapshot(
     include_formulas: bool,
     include_styles: bool,
 ) -> RowSnapshot {
-    let mut cells = Vec::new();
-    for &col in columns {
-        if let Some(cell) = sheet.get_cell((col, row_index)) {
-            cells.push(build_cell_snapshot(cell, include_formulas, include_styles));
-        } else {
-            let address = crate::utils::cell_address(col, row_index);
-            cells.push(CellSnapshot {
-                address,
-                value: None,
-                formula: None,
-                cached_value: None,
-                number_format: None,
-                style_tags: Vec::new(),
-                notes: Vec::new(),
-            });
-        }
+    let cells = columns
+        .iter()
+        .map(|&col| cell_snapshot_at(sheet, col, row_index, include_formulas, include_styles))
+        .collect();
+
+    let hidden = sheet
+        .get_row_dimension(&row_index)
+        .is_some_and(|row| *row.get_hidden());
+
+    RowSnapshot {
+        row_index,
+        hidden,
+        outline_level: 0,
+        cells,
     }
+}
 
-    RowSnapshot { row_index, cells }
+fn cell_snapshot_at(
+    sheet: &umya_spreadsheet::Worksheet,
+    col: u32,
+    row: u32,
+    include_formulas: bool,
+    include_styles: bool,
+) -> CellSnapshot {
+    match sheet.get_cell((col, row)) {
+        Some(cell) => build_cell_snapshot(cell, include_formulas, include_styles),
+        None => CellSnapshot {
+            address: crate::utils::cell_address(col, row),
+            value: None,
+            formula: None,
+            cached_value: None,
+            number_format: None,
+            style_tags: Vec::new(),
+            notes: Vec::new(),
+        },
+    }
 }
 
 fn build_cell_snapshot(
@@ -1889,67 +2676,156 @@ fn resolve_columns(columns: Option<&Vec<String>>, max_column: u32) -> Vec<u32> {
     indices.into_iter().collect()
 }
 
+/// Default `header_match_threshold` for fuzzy header matching.
+const HEADER_MATCH_DEFAULT_THRESHOLD: f64 = 0.6;
+
+/// `columns_by_header` selectors and how to match them.
+struct HeaderSelector<'a> {
+    headers: &'a [String],
+    mode: HeaderMatchMode,
+    threshold: f64,
+}
+
+impl<'a> HeaderSelector<'a> {
+    fn from_params(params: &'a SheetPageParams) -> Result<Option<Self>> {
+        let mode = params.header_match.unwrap_or_default();
+        if let Some(threshold) = params.header_match_threshold {
+            if mode != HeaderMatchMode::Fuzzy {
+                return Err(anyhow!(
+                    "header_match_threshold requires header_match=fuzzy"
+                ));
+            }
+            if !(threshold > 0.0 && threshold <= 1.0) {
+                return Err(anyhow!(
+                    "header_match_threshold must be greater than 0 and at most 1"
+                ));
+            }
+        }
+        Ok(params
+            .columns_by_header
+            .as_deref()
+            .map(|headers| HeaderSelector {
+                headers,
+                mode,
+                threshold: params
+                    .header_match_threshold
+                    .unwrap_or(HEADER_MATCH_DEFAULT_THRESHOLD),
+            }))
+    }
+}
+
+#[derive(Default)]
+struct ColumnSelection {
+    columns: Vec<u32>,
+    resolved_headers: Vec<HeaderResolution>,
+    unmatched_headers: Vec<String>,
+}
+
 fn resolve_columns_with_headers(
     sheet: &umya_spreadsheet::Worksheet,
     columns: Option<&Vec<String>>,
-    columns_by_header: Option<&Vec<String>>,
+    header_selector: Option<&HeaderSelector>,
     max_column: u32,
-) -> Vec<u32> {
+) -> ColumnSelection {
     use std::collections::BTreeSet;
 
-    if columns_by_header.is_none() {
-        return resolve_columns(columns, max_column);
-    }
+    let Some(selector) = header_selector else {
+        return ColumnSelection {
+            columns: resolve_columns(columns, max_column),
+            ..Default::default()
+        };
+    };
 
     let mut selected: BTreeSet<u32> = if columns.is_some() {
         resolve_columns(columns, max_column).into_iter().collect()
     } else {
         BTreeSet::new()
     };
-    let mut matched_header = false;
-    let header_targets: Vec<String> = columns_by_header
-        .unwrap()
-        .iter()
-        .map(|h| h.trim().to_ascii_lowercase())
+    let sheet_headers: Vec<(u32, String)> = (1..=max_column.max(1))
+        .filter_map(|col_idx| {
+            let text = sheet
+                .get_cell((col_idx, 1u32))
+                .and_then(cell_to_value)
+                .map(cell_value_to_text)?;
+            let text = text.trim();
+            (!text.is_empty()).then(|| (col_idx, text.to_string()))
+        })
         .collect();
 
-    for col_idx in 1..=max_column.max(1) {
-        let header_cell = sheet.get_cell((col_idx, 1u32));
-        let header_value = header_cell
-            .and_then(cell_to_value)
-            .map(cell_value_to_string_lower);
-        if let Some(hval) = header_value
-            && header_targets.iter().any(|target| target == &hval)
-        {
+    let mut resolved_headers = Vec::new();
+    let mut unmatched_headers = Vec::new();
+    for selector_text in selector.headers {
+        let target = selector_text.trim().to_lowercase();
+        let matches: Vec<(u32, &str, Option<f64>)> = match selector.mode {
+            HeaderMatchMode::Exact => sheet_headers
+                .iter()
+                .filter(|(_, header)| header.to_lowercase() == target)
+                .map(|(col, header)| (*col, header.as_str(), None))
+                .collect(),
+            HeaderMatchMode::Contains if target.is_empty() => Vec::new(),
+            HeaderMatchMode::Contains => sheet_headers
+                .iter()
+                .filter(|(_, header)| header.to_lowercase().contains(&target))
+                .map(|(col, header)| (*col, header.as_str(), None))
+                .collect(),
+            // Only the best header, so a loose threshold cannot pull in
+            // several columns; ties go to the leftmost.
+            HeaderMatchMode::Fuzzy => sheet_headers
+                .iter()
+                .map(|(col, header)| (*col, header.as_str(), header_similarity(&target, header)))
+                .filter(|(_, _, score)| *score >= selector.threshold)
+                .fold(
+                    None,
+                    |best: Option<(u32, &str, f64)>, candidate| match best {
+                        Some(best) if best.2 >= candidate.2 => Some(best),
+                        _ => Some(candidate),
+                    },
+                )
+                .map(|(col, header, score)| (col, header, Some(round3(score))))
+                .into_iter()
+                .collect(),
+        };
+        if matches.is_empty() {
+            unmatched_headers.push(selector_text.clone());
+        }
+        for (col_idx, header, score) in matches {
             selected.insert(col_idx);
-            matched_header = true;
+            resolved_headers.push(HeaderResolution {
+                selector: selector_text.clone(),
+                column: column_number_to_name(col_idx),
+                header: header.to_string(),
+                score,
+            });
         }
     }
 
-    if !matched_header && columns.is_none() {
+    let columns = if resolved_headers.is_empty() && columns.is_none() {
         resolve_columns(None, max_column)
     } else {
         selected.into_iter().collect()
+    };
+    ColumnSelection {
+        columns,
+        resolved_headers,
+        unmatched_headers,
     }
 }
 
-fn cell_value_to_string_lower(value: CellValue) -> String {
+fn cell_value_to_text(value: CellValue) -> String {
     match value {
-        CellValue::Text(s) => s.to_ascii_lowercase(),
-        CellValue::Number(n) => n.to_string().to_ascii_lowercase(),
+        CellValue::Text(s) | CellValue::Error(s) | CellValue::Date(s) => s,
+        CellValue::Number(n) => n.to_string(),
         CellValue::Bool(b) => b.to_string(),
-        CellValue::Error(e) => e.to_ascii_lowercase(),
-        CellValue::Date(d) => d.to_ascii_lowercase(),
     }
 }
 
-fn cell_value_to_plain_string(value: &CellValue) -> String {
+fn cell_value_to_string_lower(value: CellValue) -> String {
     match value {
-        CellValue::Text(s) => s.clone(),
-        CellValue::Number(n) => n.to_string(),
+        CellValue::Text(s) => s.to_ascii_lowercase(),
+        CellValue::Number(n) => n.to_string().to_ascii_lowercase(),
         CellValue::Bool(b) => b.to_string(),
-        CellValue::Error(e) => e.clone(),
-        CellValue::Date(d) => d.clone(),
+        CellValue::Error(e) => e.to_ascii_lowercase(),
+        CellValue::Date(d) => d.to_ascii_lowercase(),
     }
 }
 
@@ -1973,39 +2849,6 @@ fn cell_value_to_primitive(value: &CellValue) -> CellValuePrimitive {
     }
 }
 
-fn csv_escape_field(field: &str) -> String {
-    if field.contains(',') || field.contains('"') || field.contains('\n') || field.contains('\r') {
-        let mut escaped = String::with_capacity(field.len() + 2);
-        escaped.push('"');
-        for ch in field.chars() {
-            if ch == '"' {
-                escaped.push('"');
-            }
-            escaped.push(ch);
-        }
-        escaped.push('"');
-        escaped
-    } else {
-        field.to_string()
-    }
-}
-
-fn push_csv_row<I>(buffer: &mut String, fields: I)
-where
-    I: IntoIterator<Item = String>,
-{
-    let mut first = true;
-    for field in fields {
-        if !first {
-            buffer.push(',');
-        }
-        first = false;
-        let escaped = csv_escape_field(&field);
-        buffer.push_str(&escaped);
-    }
-    buffer.push('\n');
-}
-
 fn table_rows_to_values(
     headers: &[String],
     rows: &[TableRow],
@@ -2041,19 +2884,39 @@ fn table_rows_to_types(headers: &[String], rows: &[TableRow]) -> Vec<Vec<Option<
     out
 }
 
-fn table_rows_to_csv(headers: &[String], rows: &[TableRow], include_headers: bool) -> String {
-    let mut csv = String::new();
+fn table_rows_to_date_serials(
+    headers: &[String],
+    rows: &[TableRow],
+    use_1904_system: bool,
+) -> Vec<Vec<Option<f64>>> {
+    rows.iter()
+        .map(|row| {
+            headers
+                .iter()
+                .map(|header| match row.get(header) {
+                    Some(Some(CellValue::Date(text))) => iso_to_excel_serial(text, use_1904_system),
+                    _ => None,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn table_rows_to_csv(
+    headers: &[String],
+    rows: &[TableRow],
+    include_headers: bool,
+    options: &CsvOptions,
+) -> String {
+    let mut csv = options.start();
     if include_headers {
-        push_csv_row(&mut csv, headers.iter().cloned());
+        options.push_text_row(&mut csv, headers);
     }
     for row in rows {
-        let values = headers.iter().map(|header| {
-            row.get(header)
-                .and_then(|cell| cell.as_ref())
-                .map(cell_value_to_plain_string)
-                .unwrap_or_default()
-        });
-        push_csv_row(&mut csv, values);
+        let cells = headers
+            .iter()
+            .map(|header| row.get(header).and_then(|cell| cell.as_ref()));
+        options.push_cell_row(&mut csv, cells);
     }
     csv
 }
@@ -2082,6 +2945,7 @@ fn build_read_table_payload(
     rows: &[TableRow],
     include_headers: bool,
     include_types: bool,
+    csv_options: &CsvOptions,
 ) -> ReadTablePayload {
     let headers_out = if include_headers {
         headers.to_vec()
@@ -2111,7 +2975,12 @@ fn build_read_table_payload(
             Vec::new(),
             None,
             types_out,
-            Some(table_rows_to_csv(headers, rows, include_headers)),
+            Some(table_rows_to_csv(
+                headers,
+                rows,
+                include_headers,
+                csv_options,
+            )),
         ),
     }
 }
@@ -2128,15 +2997,10 @@ fn cell_matrix_to_values(rows: &[Vec<Option<CellValue>>]) -> Vec<Vec<Option<Cell
     out
 }
 
-fn cell_matrix_to_csv(rows: &[Vec<Option<CellValue>>]) -> String {
-    let mut csv = String::new();
+fn cell_matrix_to_csv(rows: &[Vec<Option<CellValue>>], options: &CsvOptions) -> String {
+    let mut csv = options.start();
     for row in rows {
-        let values = row.iter().map(|cell| {
-            cell.as_ref()
-                .map(cell_value_to_plain_string)
-                .unwrap_or_default()
-        });
-        push_csv_row(&mut csv, values);
+        options.push_cell_row(&mut csv, row.iter().map(Option::as_ref));
     }
     csv
 }
@@ -2272,13 +3136,23 @@ fn build_range_values_entry(
     range: &str,
     rows: &[Vec<Option<CellValue>>],
     formulas: Option<&[Vec<Option<String>>]>,
+    formatting: Option<&[RangeValuesCellFormat]>,
     next_start_row: Option<u32>,
+    csv_options: &CsvOptions,
 ) -> RangeValuesEntry {
-    match format {
+    let formatting = formatting.map(|cells| {
+        cells
+            .iter()
+            .filter(|cell| (cell.row as usize) < rows.len())
+            .cloned()
+            .collect::<Vec<_>>()
+    });
+    let mut entry = match format {
         TableOutputFormat::Json => RangeValuesEntry {
             range: range.to_string(),
             rows: Some(rows.to_vec()),
             formulas: formulas.map(|matrix| matrix.to_vec()),
+            formatting: None,
             values: None,
             dense: None,
             csv: None,
@@ -2289,6 +3163,7 @@ fn build_range_values_entry(
             range: range.to_string(),
             rows: None,
             formulas: None,
+            formatting: None,
             values: Some(cell_matrix_to_values(rows)),
             dense: None,
             csv: None,
@@ -2299,9 +3174,14 @@ fn build_range_values_entry(
             range: range.to_string(),
             rows: None,
             formulas: None,
+            formatting: None,
             values: None,
             dense: None,
-            csv: Some(cell_matrix_to_csv(rows)),
+            // With formatting requested, csv carries the display text.
+            csv: Some(match &formatting {
+                Some(cells) => cell_matrix_to_csv(&display_matrix(rows, cells), csv_options),
+                None => cell_matrix_to_csv(rows, csv_options),
+            }),
             rows_keyed: None,
             next_start_row,
         },
@@ -2309,6 +3189,7 @@ fn build_range_values_entry(
             range: range.to_string(),
             rows: None,
             formulas: None,
+            formatting: None,
             values: None,
             dense: Some(cell_matrix_to_dense(rows, formulas)),
             csv: None,
@@ -2319,13 +3200,33 @@ fn build_range_values_entry(
             range: range.to_string(),
             rows: None,
             formulas: None,
+            formatting: None,
             values: None,
             dense: None,
             csv: None,
             rows_keyed: Some(cell_matrix_to_rows_keyed(range, rows)),
             next_start_row,
         },
+    };
+    entry.formatting = formatting;
+    entry
+}
+
+/// `rows` with every formatted cell replaced by its display text.
+fn display_matrix(
+    rows: &[Vec<Option<CellValue>>],
+    formatting: &[RangeValuesCellFormat],
+) -> Vec<Vec<Option<CellValue>>> {
+    let mut display = rows.to_vec();
+    for cell in formatting {
+        if let Some(slot) = display
+            .get_mut(cell.row as usize)
+            .and_then(|row| row.get_mut(cell.col as usize))
+        {
+            *slot = Some(CellValue::Text(cell.display.clone()));
+        }
     }
+    display
 }
 
 fn cap_rows_by_cells(row_count: usize, cells_per_row: usize, max_cells: Option<usize>) -> usize {
@@ -2394,6 +3295,16 @@ fn build_compact_payload(
         headers,
         header_row,
         rows: data_rows,
+        hidden_rows: rows
+            .iter()
+            .filter(|row| row.hidden)
+            .map(|row| row.row_index)
+            .collect(),
+        row_outline_levels: rows
+            .iter()
+            .filter(|row| row.outline_level > 0)
+            .map(|row| (row.row_index, row.outline_level))
+            .collect(),
     }
 }
 
@@ -2454,9 +3365,16 @@ fn build_sheet_page_response(
         header_row,
         compact: compact_payload,
         values_only: values_only_payload,
+        orientation: None,
+        column_page: None,
+        next_start_column: None,
+        date_serials: None,
+        resolved_headers: Vec::new(),
+        unmatched_headers: Vec::new(),
         format,
         truncated: false,
         budget: None,
+        compressed: None,
     }
 }
 
@@ -2504,6 +3422,12 @@ pub struct SheetStatisticsParams {
     /// Return stats without sample values (default: true in token_dense profile)
     #[serde(default)]
     pub summary_only: Option<bool>,
+    /// Round numeric results to this many decimal places (default: unrounded)
+    #[serde(default)]
+    pub decimals: Option<u32>,
+    /// Tie-breaking for `decimals`: "half_up" (default, as Excel ROUND) or "half_even" (banker's)
+    #[serde(default)]
+    pub rounding: Option<RoundingMode>,
 }
 
 pub async fn sheet_statistics(
@@ -2523,6 +3447,9 @@ pub async fn sheet_statistics(
     })?;
     let mut numeric_columns = stats.numeric_columns;
     let mut text_columns = stats.text_columns;
+    if let Some(policy) = RoundingPolicy::from_params(params.decimals, params.rounding) {
+        policy.round_column_summaries(&mut numeric_columns);
+    }
 
     if summary_only {
         for column in &mut numeric_columns {
@@ -2629,12 +3556,87 @@ fn parse_address(address: &str) -> Option<(u32, u32)> {
     }
 }
 
+/// The block a table read covers. `range` is in table coordinates,
+/// `(field, record)`: sheet `(column, row)`, or `(row, column)` when
+/// `transposed`.
 #[derive(Clone)]
 struct TableTarget {
     sheet_name: String,
     table_name: Option<String>,
     range: ((u32, u32), (u32, u32)),
     header_hint: Option<u32>,
+    transposed: bool,
+}
+
+impl TableTarget {
+    /// Read the first column as headers and each later column as a record.
+    /// Table and region header hints name rows, so they are dropped.
+    fn transposed(self) -> Self {
+        let ((start_col, start_row), (end_col, end_row)) = self.range;
+        Self {
+            range: ((start_row, start_col), (end_row, end_col)),
+            header_hint: None,
+            transposed: true,
+            ..self
+        }
+    }
+
+    /// `range` in sheet `(column, row)` coordinates.
+    fn sheet_range(&self) -> ((u32, u32), (u32, u32)) {
+        let (start, end) = self.range;
+        (self.sheet_cell(start), self.sheet_cell(end))
+    }
+
+    /// Sheet `(column, row)` of table cell `(field, record)`.
+    fn sheet_cell(&self, (field, record): (u32, u32)) -> (u32, u32) {
+        if self.transposed {
+            (record, field)
+        } else {
+            (field, record)
+        }
+    }
+
+    /// Name for a field without a header: its column letter, or its row
+    /// number when transposed.
+    fn field_name(&self, field: u32) -> String {
+        if self.transposed {
+            field.to_string()
+        } else {
+            crate::utils::column_number_to_name(field)
+        }
+    }
+}
+
+/// Named items in the order a lookup of `name` should try them: exact matches
+/// before partial ones, restricted to `scope` when given. Without a scope, a
+/// name defined on `sheet_name` shadows the workbook-level one, as it does in
+/// that sheet's formulas.
+fn name_lookup_order(
+    mut items: Vec<NamedRangeDescriptor>,
+    name: &str,
+    scope: Option<NamedRangeScope>,
+    sheet_name: Option<&str>,
+) -> Vec<NamedRangeDescriptor> {
+    let on_sheet = |item: &NamedRangeDescriptor| {
+        item.scope_kind == Some(NamedRangeScope::Sheet)
+            && sheet_name.is_none_or(|sheet| {
+                item.scope_sheet_name
+                    .as_deref()
+                    .is_some_and(|scope_sheet| scope_sheet.eq_ignore_ascii_case(sheet))
+            })
+    };
+    match scope {
+        Some(NamedRangeScope::Workbook) => {
+            items.retain(|item| item.scope_kind == Some(NamedRangeScope::Workbook))
+        }
+        Some(NamedRangeScope::Sheet) => items.retain(on_sheet),
+        None => {}
+    }
+    items.sort_by_key(|item| {
+        let shadows = sheet_name.is_some() && item.kind != NamedItemKind::Table && on_sheet(item);
+        (!item.name.eq_ignore_ascii_case(name), !shadows)
+    });
+    items
 }
 
 fn resolve_table_target(
@@ -2650,11 +3652,17 @@ fn resolve_table_target(
             table_name: None,
             range: parse_range(&region.bounds).unwrap_or(((1, 1), (1, 1))),
             header_hint: region.header_row,
+            transposed: false,
         });
     }
 
     if let Some(table_name) = &params.table_name {
-        let items = workbook.named_items()?;
+        let items = name_lookup_order(
+            workbook.named_items()?,
+            table_name,
+            params.name_scope,
+            params.sheet_name.as_deref(),
+        );
         for item in items {
             if item.name.eq_ignore_ascii_case(table_name)
                 || item
@@ -2683,6 +3691,7 @@ fn resolve_table_target(
                         } else {
                             None
                         },
+                        transposed: false,
                     });
                 }
             }
@@ -2702,6 +3711,7 @@ fn resolve_table_target(
             table_name: None,
             range,
             header_hint: None,
+            transposed: false,
         });
     }
 
@@ -2713,68 +3723,177 @@ fn resolve_table_target(
         table_name: None,
         range: ((1, 1), (end_col, end_row)),
         header_hint: None,
+        transposed: false,
     })
 }
 
+/// Choose the header rows for `target`: an explicit `header_row`, else the
+/// table or detected header, else the top of the range. `header_rows: 0`
+/// reads every row as data.
+fn resolve_header_layout(
+    target: &TableTarget,
+    header_row: Option<u32>,
+    header_rows: Option<u32>,
+) -> HeaderDetection {
+    let ((_, start_row), (_, end_row)) = target.range;
+    let header_rows = header_rows.unwrap_or(1);
+    if header_rows == 0 {
+        return HeaderDetection {
+            source: HeaderSource::NoHeader,
+            header_row: None,
+            header_rows: 0,
+            data_start_row: start_row,
+        };
+    }
+    let (mut header_start, mut source) = match (header_row, target.header_hint) {
+        (Some(row), _) => (row, HeaderSource::Explicit),
+        (None, Some(row)) if target.table_name.is_some() => (row, HeaderSource::Table),
+        (None, Some(row)) => (row, HeaderSource::Detected),
+        (None, None) => (start_row, HeaderSource::RangeTop),
+    };
+    if header_start < start_row || header_start > end_row {
+        header_start = start_row;
+        source = HeaderSource::RangeTop;
+    }
+    HeaderDetection {
+        source,
+        header_row: Some(header_start),
+        header_rows,
+        data_start_row: header_start + header_rows,
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 fn extract_table_rows(
     sheet: &umya_spreadsheet::Worksheet,
     target: &TableTarget,
-    header_row: Option<u32>,
-    header_rows: Option<u32>,
+    layout: &HeaderDetection,
     columns: Option<Vec<String>>,
     filters: Option<Vec<TableFilter>>,
+    sort: Option<&[TableSort]>,
     limit: usize,
     offset: usize,
     sample_mode: SampleMode,
 ) -> Result<(Vec<String>, Vec<TableRow>, u32)> {
-    let ((start_col, start_row), (end_col, end_row)) = target.range;
-    let mut header_start = header_row.or(target.header_hint).unwrap_or(start_row);
-    if header_start < start_row {
-        header_start = start_row;
-    }
-    if header_start > end_row {
-        header_start = start_row;
-    }
-    let header_rows_count = header_rows.unwrap_or(1).max(1);
-    let data_start_row = (header_start + header_rows_count).max(start_row + header_rows_count);
+    let ((start_col, _), (end_col, end_row)) = target.range;
     let column_indices: Vec<u32> = if let Some(cols) = columns.as_ref() {
         resolve_columns(Some(cols), end_col).into_iter().collect()
     } else {
         (start_col..=end_col).collect()
     };
 
-    let headers = build_headers(sheet, &column_indices, header_start, header_rows_count);
+    let headers = match layout.header_row {
+        Some(header_start) => build_headers(
+            sheet,
+            target,
+            &column_indices,
+            header_start,
+            layout.header_rows,
+        ),
+        None => column_indices
+            .iter()
+            .map(|col| target.field_name(*col))
+            .collect(),
+    };
     let mut all_rows: Vec<TableRow> = Vec::new();
     let mut total_rows: u32 = 0;
 
-    for row_idx in data_start_row..=end_row {
+    for row_idx in layout.data_start_row..=end_row {
         let mut row = BTreeMap::new();
         for (i, col_idx) in column_indices.iter().enumerate() {
             let header = headers
                 .get(i)
                 .cloned()
                 .unwrap_or_else(|| format!("Col{col_idx}"));
-            let value = sheet.get_cell((*col_idx, row_idx)).and_then(cell_to_value);
+            let value = sheet
+                .get_cell(target.sheet_cell((*col_idx, row_idx)))
+                .and_then(cell_to_value);
             row.insert(header, value);
         }
         if !row_passes_filters(&row, filters.as_ref()) {
             continue;
         }
         total_rows += 1;
-        if matches!(sample_mode, SampleMode::First) && total_rows as usize > offset + limit {
+        if sort.is_none()
+            && matches!(sample_mode, SampleMode::First)
+            && total_rows as usize > offset + limit
+        {
             continue;
         }
         all_rows.push(row);
     }
 
+    if let Some(keys) = sort {
+        for key in keys {
+            if !headers.contains(&key.column) {
+                return Err(anyhow!(
+                    "sort column '{}' is not a header (headers: {})",
+                    key.column,
+                    headers.join(", ")
+                ));
+            }
+        }
+        // Stable, so rows equal on every key keep sheet order.
+        all_rows.sort_by(|a, b| {
+            keys.iter()
+                .map(|key| {
+                    compare_sort_values(
+                        a.get(&key.column).and_then(Option::as_ref),
+                        b.get(&key.column).and_then(Option::as_ref),
+                        key.descending,
+                    )
+                })
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(Ordering::Equal)
+        });
+    }
+
     let rows = sample_rows(all_rows, limit, offset, sample_mode);
 
     Ok((headers, rows, total_rows))
 }
 
+/// Numbers, then dates, booleans, text (case-insensitive), and errors.
+/// Blank cells sort last in either direction.
+fn compare_sort_values(a: Option<&CellValue>, b: Option<&CellValue>, descending: bool) -> Ordering {
+    fn rank(value: &CellValue) -> u8 {
+        match value {
+            CellValue::Number(_) => 0,
+            CellValue::Date(_) => 1,
+            CellValue::Bool(_) => 2,
+            CellValue::Text(_) => 3,
+            CellValue::Error(_) => 4,
+        }
+    }
+
+    let (a, b) = match (a, b) {
+        (None, None) => return Ordering::Equal,
+        (None, Some(_)) => return Ordering::Greater,
+        (Some(_), None) => return Ordering::Less,
+        (Some(a), Some(b)) => (a, b),
+    };
+    let ordering = match (a, b) {
+        (CellValue::Number(x), CellValue::Number(y)) => x.total_cmp(y),
+        (CellValue::Bool(x), CellValue::Bool(y)) => x.cmp(y),
+        (CellValue::Text(x), CellValue::Text(y)) => x
+            .to_lowercase()
+            .cmp(&y.to_lowercase())
+            .then_with(|| x.cmp(y)),
+        (CellValue::Date(x), CellValue::Date(y)) | (CellValue::Error(x), CellValue::Error(y)) => {
+            x.cmp(y)
+        }
+        _ => rank(a).cmp(&rank(b)),
+    };
+    if descending {
+        ordering.reverse()
+    } else {
+        ordering
+    }
+}
+
 fn build_headers(
     sheet: &umya_spreadsheet::Worksheet,
+    target: &TableTarget,
     columns: &[u32],
     header_start: u32,
     header_rows: u32,
@@ -2782,8 +3901,13 @@ fn build_headers(
     let mut headers = Vec::new();
     for col_idx in columns {
         let mut parts = Vec::new();
+        let mut last_origin = None;
         for h in header_start..(header_start + header_rows) {
-            let (origin_col, origin_row) = sheet.map_merged_cell((*col_idx, h));
+            let (origin_col, origin_row) = sheet.map_merged_cell(target.sheet_cell((*col_idx, h)));
+            // A cell merged down across header rows names the column once.
+            if last_origin.replace((origin_col, origin_row)) == Some((origin_col, origin_row)) {
+                continue;
+            }
             if let Some(value) = sheet
                 .get_cell((origin_col, origin_row))
                 .and_then(cell_to_value)
@@ -2799,17 +3923,14 @@ fn build_headers(
             }
         }
         if parts.is_empty() {
-            headers.push(crate::utils::column_number_to_name(*col_idx));
+            headers.push(target.field_name(*col_idx));
         } else {
             headers.push(parts.join(" / "));
         }
     }
 
     if headers.iter().all(|h| h.trim().is_empty()) {
-        return columns
-            .iter()
-            .map(|c| crate::utils::column_number_to_name(*c))
-            .collect();
+        return columns.iter().map(|c| target.field_name(*c)).collect();
     }
 
     dedupe_headers(headers)
@@ -3088,6 +4209,7 @@ fn summarize_columns(headers: &[String], rows: &[TableRow]) -> Vec<ColumnTypeSum
             min,
             max,
             mean,
+            stats: None,
         });
     }
     summaries
@@ -3103,17 +4225,14 @@ fn collect_value_matches(
     params: &FindValueParams,
     region: Option<&DetectedRegion>,
     default_bounds: ((u32, u32), (u32, u32)),
+    regex: Option<&Regex>,
+    comparison: Option<&ComparisonBounds>,
     offset: u32,
     limit: u32,
     seen_so_far: u32,
 ) -> Result<(Vec<FindValueMatch>, u32, bool)> {
     let mut results = Vec::new();
     let mut seen = seen_so_far;
-    let regex = if match_mode == MatchMode::Regex {
-        Regex::new(&params.query).ok()
-    } else {
-        None
-    };
     let bounds = region
         .as_ref()
         .and_then(|r| parse_range(&r.bounds))
@@ -3143,18 +4262,22 @@ fn collect_value_matches(
         {
             continue;
         }
-        if matches!(mode, FindMode::Value) {
+        if let Some(comparison) = comparison {
+            if !comparison.matches(cell) {
+                continue;
+            }
+        } else if matches!(mode, FindMode::Value) {
             if !value_matches(
                 &value,
                 &params.query,
                 match_mode,
                 params.case_sensitive,
-                &regex,
+                regex,
             ) {
                 continue;
             }
         } else if let Some(label) = &params.label {
-            if !label_matches(cell, label, match_mode, params.case_sensitive, &regex) {
+            if !label_matches(cell, label, match_mode, params.case_sensitive, regex) {
                 continue;
             }
         } else {
@@ -3235,12 +4358,12 @@ fn value_matches(
     query: &str,
     mode: MatchMode,
     case_sensitive: bool,
-    regex: &Option<Regex>,
+    regex: Option<&Regex>,
 ) -> bool {
-    if value.is_none() {
+    let Some(value) = value else {
         return false;
-    }
-    let haystack = cell_value_to_string_lower(value.clone().unwrap());
+    };
+    let haystack = cell_value_to_string_lower(value.clone());
     let needle = if case_sensitive {
         query.to_string()
     } else {
@@ -3250,10 +4373,8 @@ fn value_matches(
     match mode {
         MatchMode::Exact => haystack == needle,
         MatchMode::Prefix => haystack.starts_with(&needle),
-        MatchMode::Regex => regex
-            .as_ref()
-            .map(|re| re.is_match(&haystack))
-            .unwrap_or(false),
+        // Regexes carry their own case handling and see the original text.
+        MatchMode::Regex => regex.is_some_and(|re| re.is_match(&cell_value_to_text(value.clone()))),
         MatchMode::Contains => haystack.contains(&needle),
     }
 }
@@ -3263,13 +4384,12 @@ fn label_matches(
     label: &str,
     mode: MatchMode,
     case_sensitive: bool,
-    regex: &Option<Regex>,
+    regex: Option<&Regex>,
 ) -> bool {
-    let value = cell_to_value(cell);
-    if value.is_none() {
+    let Some(value) = cell_to_value(cell) else {
         return false;
-    }
-    let haystack = cell_value_to_string_lower(value.unwrap());
+    };
+    let haystack = cell_value_to_string_lower(value.clone());
     let needle = if case_sensitive {
         label.to_string()
     } else {
@@ -3278,10 +4398,7 @@ fn label_matches(
     match mode {
         MatchMode::Exact => haystack == needle,
         MatchMode::Prefix => haystack.starts_with(&needle),
-        MatchMode::Regex => regex
-            .as_ref()
-            .map(|re| re.is_match(&haystack))
-            .unwrap_or(false),
+        MatchMode::Regex => regex.is_some_and(|re| re.is_match(&cell_value_to_text(value))),
         MatchMode::Contains => haystack.contains(&needle),
     }
 }
@@ -3386,6 +4503,10 @@ pub struct FindFormulaParams {
     /// Columns of context to include left/right (requires include_context=true)
     #[serde(default)]
     pub context_cols: Option<u32>,
+    /// Display formulas as "a1" (default) or "r1c1"; the query matches the
+    /// displayed form
+    #[serde(default)]
+    pub reference_style: ReferenceStyle,
 }
 
 pub async fn find_formula(
@@ -3422,6 +4543,7 @@ pub async fn find_formula(
                     &sheet_name,
                     &query,
                     params.case_sensitive,
+                    params.reference_style,
                     params.include_context,
                     context_rows,
                     context_cols,
@@ -3629,6 +4751,288 @@ pub async fn scan_volatiles(
     Ok(response)
 }
 
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ScanErrorsParams {
+    /// Workbook ID or fork ID
+    #[serde(alias = "workbook_id")]
+    pub workbook_or_fork_id: WorkbookId,
+    /// Limit to specific sheet (scans all if omitted)
+    pub sheet_name: Option<String>,
+    /// Return per-error-type counts only without cell entries (default: false)
+    #[serde(default)]
+    pub summary_only: Option<bool>,
+    /// Maximum cell entries to return for this page
+    #[serde(default)]
+    pub limit: Option<u32>,
+    /// Entry offset for pagination; use next_offset from previous response
+    #[serde(default)]
+    pub offset: Option<u32>,
+}
+
+/// Cells whose cached value is an error (`#REF!`, `#DIV/0!`, `#N/A`, ...),
+/// grouped by error type. Values come from the last save or recalculation;
+/// nothing is recomputed.
+pub async fn scan_errors(
+    state: Arc<AppState>,
+    params: ScanErrorsParams,
+) -> Result<ErrorScanResponse> {
+    let workbook = state.open_workbook(&params.workbook_or_fork_id).await?;
+    let config = state.config();
+    let summary_only = params.summary_only.unwrap_or(false);
+    let max_items = config.max_items();
+    let max_payload_bytes = config.max_payload_bytes();
+
+    let target_sheets: Vec<String> = if let Some(sheet) = &params.sheet_name {
+        vec![sheet.clone()]
+    } else {
+        workbook.sheet_names()
+    };
+
+    let mut cells = Vec::new();
+    for (sheet_index, sheet_name) in target_sheets.iter().enumerate() {
+        let sheet_cells = workbook.with_sheet(sheet_name, |sheet| {
+            sheet
+                .get_cell_collection()
+                .into_iter()
+                .filter_map(|cell| {
+                    let raw = cell.get_value();
+                    if !crate::verification::is_error_text(&raw) {
+                        return None;
+                    }
+                    let coordinate = cell.get_coordinate();
+                    let formula = cell.get_formula().trim();
+                    Some((
+                        (*coordinate.get_row_num(), *coordinate.get_col_num()),
+                        ErrorScanEntry {
+                            sheet_name: sheet_name.clone(),
+                            address: coordinate.get_coordinate(),
+                            error: raw.trim().to_ascii_uppercase(),
+                            formula: (!formula.is_empty()).then(|| formula.to_string()),
+                        },
+                    ))
+                })
+                .collect::<Vec<_>>()
+        })?;
+        cells.extend(
+            sheet_cells
+                .into_iter()
+                .map(|(position, entry)| (sheet_index, position, entry)),
+        );
+    }
+    cells.sort_by(|left, right| {
+        left.2
+            .error
+            .cmp(&right.2.error)
+            .then_with(|| left.0.cmp(&right.0))
+            .then_with(|| left.1.cmp(&right.1))
+    });
+    let items: Vec<ErrorScanEntry> = cells.into_iter().map(|(_, _, entry)| entry).collect();
+
+    let mut groups: Vec<ErrorScanGroup> = Vec::new();
+    for item in &items {
+        match groups.last_mut() {
+            Some(group) if group.error == item.error => {
+                group.count += 1;
+                if !group.sheets.contains(&item.sheet_name) {
+                    group.sheets.push(item.sheet_name.clone());
+                }
+            }
+            _ => groups.push(ErrorScanGroup {
+                error: item.error.clone(),
+                count: 1,
+                sheets: vec![item.sheet_name.clone()],
+            }),
+        }
+    }
+    groups.sort_by(|left, right| {
+        right
+            .count
+            .cmp(&left.count)
+            .then_with(|| left.error.cmp(&right.error))
+    });
+    let total_errors = items.len();
+
+    if summary_only {
+        return Ok(ErrorScanResponse {
+            workbook_id: workbook.id.clone(),
+            total_errors: total_errors as u32,
+            groups,
+            items: Vec::new(),
+            next_offset: None,
+        });
+    }
+
+    let offset = params.offset.unwrap_or(0) as usize;
+    let page_limit = params
+        .limit
+        .map(|limit| limit.max(1) as usize)
+        .unwrap_or(usize::MAX);
+    let start = offset.min(total_errors);
+    let end = start.saturating_add(page_limit).min(total_errors);
+    let mut page_items = items[start..end].to_vec();
+
+    if let Some(max_items) = max_items
+        && page_items.len() > max_items
+    {
+        page_items.truncate(max_items);
+    }
+
+    if let Some(max_bytes) = max_payload_bytes {
+        let item_limit = cap_rows_by_payload_bytes(page_items.len(), Some(max_bytes), |count| {
+            let response = ErrorScanResponse {
+                workbook_id: workbook.id.clone(),
+                total_errors: total_errors as u32,
+                groups: groups.clone(),
+                items: page_items[..count].to_vec(),
+                next_offset: None,
+            };
+            serde_json::to_vec(&response)
+                .map(|payload| payload.len())
+                .unwrap_or(usize::MAX)
+        });
+
+        if item_limit < page_items.len() {
+            page_items.truncate(item_limit);
+        }
+    }
+
+    let emitted = page_items.len();
+    let absolute_next = start.saturating_add(emitted);
+    let next_offset = if emitted > 0 && absolute_next < total_errors {
+        Some(absolute_next as u32)
+    } else {
+        None
+    };
+
+    Ok(ErrorScanResponse {
+        workbook_id: workbook.id.clone(),
+        total_errors: total_errors as u32,
+        groups,
+        items: page_items,
+        next_offset,
+    })
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct LintFormulasParams {
+    /// Workbook ID or fork ID
+    #[serde(alias = "workbook_id")]
+    pub workbook_or_fork_id: WorkbookId,
+    /// Limit to specific sheet (scans all if omitted)
+    pub sheet_name: Option<String>,
+    /// Rules to run (default: all)
+    #[serde(default)]
+    pub rules: Vec<FormulaLintRule>,
+    /// Deepest IF nesting allowed before nested_if fires (default: 3)
+    #[serde(default)]
+    pub max_if_depth: Option<u32>,
+    /// Return per-rule counts only without findings (default: false)
+    #[serde(default)]
+    pub summary_only: Option<bool>,
+    /// Maximum findings to return for this page
+    #[serde(default)]
+    pub limit: Option<u32>,
+    /// Finding offset for pagination; use next_offset from previous response
+    #[serde(default)]
+    pub offset: Option<u32>,
+}
+
+/// Formula style findings (hard-coded constants, whole-column aggregations,
+/// deep IF nesting, inconsistent formulas) grouped by rule id.
+pub async fn lint_formulas(
+    state: Arc<AppState>,
+    params: LintFormulasParams,
+) -> Result<FormulaLintResponse> {
+    let workbook = state.open_workbook(&params.workbook_or_fork_id).await?;
+    let config = state.config();
+    let summary_only = params.summary_only.unwrap_or(false);
+    let max_items = config.max_items();
+    let max_payload_bytes = config.max_payload_bytes();
+    let options = formula_lint::FormulaLintOptions {
+        rules: params.rules.clone(),
+        max_if_depth: params
+            .max_if_depth
+            .unwrap_or(formula_lint::DEFAULT_MAX_IF_DEPTH),
+    };
+
+    let target_sheets: Vec<String> = if let Some(sheet) = &params.sheet_name {
+        vec![sheet.clone()]
+    } else {
+        workbook.sheet_names()
+    };
+
+    let mut items = Vec::new();
+    for sheet_name in &target_sheets {
+        items.extend(workbook.with_sheet(sheet_name, |sheet| {
+            formula_lint::lint_sheet_formulas(sheet, &options)
+        })?);
+    }
+    // Stable: keeps sheet order and row-major positions within each rule.
+    items.sort_by_key(|item| item.rule);
+    let rules = formula_lint::count_by_rule(&items);
+    let total_findings = items.len();
+
+    if summary_only {
+        return Ok(FormulaLintResponse {
+            workbook_id: workbook.id.clone(),
+            total_findings: total_findings as u32,
+            rules,
+            items: Vec::new(),
+            next_offset: None,
+        });
+    }
+
+    let offset = params.offset.unwrap_or(0) as usize;
+    let page_limit = params
+        .limit
+        .map(|limit| limit.max(1) as usize)
+        .unwrap_or(usize::MAX);
+    let start = offset.min(total_findings);
+    let end = start.saturating_add(page_limit).min(total_findings);
+    let mut page_items = items[start..end].to_vec();
+
+    if let Some(max_items) = max_items
+        && page_items.len() > max_items
+    {
+        page_items.truncate(max_items);
+    }
+
+    if let Some(max_bytes) = max_payload_bytes {
+        let item_limit = cap_rows_by_payload_bytes(page_items.len(), Some(max_bytes), |count| {
+            let response = FormulaLintResponse {
+                workbook_id: workbook.id.clone(),
+                total_findings: total_findings as u32,
+                rules: rules.clone(),
+                items: page_items[..count].to_vec(),
+                next_offset: None,
+            };
+            serde_json::to_vec(&response)
+                .map(|payload| payload.len())
+                .unwrap_or(usize::MAX)
+        });
+
+        if item_limit < page_items.len() {
+            page_items.truncate(item_limit);
+        }
+    }
+
+    let emitted = page_items.len();
+    let absolute_next = start.saturating_add(emitted);
+    let next_offset = if emitted > 0 && absolute_next < total_findings {
+        Some(absolute_next as u32)
+    } else {
+        None
+    };
+
+    Ok(FormulaLintResponse {
+        workbook_id: workbook.id.clone(),
+        total_findings: total_findings as u32,
+        rules,
+        items: page_items,
+        next_offset,
+    })
+}
+
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct WorkbookStyleSummaryParams {
     /// Workbook ID or fork ID
@@ -4222,11 +5626,14 @@ pub async fn range_values(
     });
     let include_headers = params.include_headers.unwrap_or(false);
     let include_formulas = params.include_formulas.unwrap_or(false);
+    let include_formatting = params.include_formatting.unwrap_or(false);
     if let Some(page_size) = params.page_size
         && page_size == 0
     {
         return Err(anyhow!("page_size must be greater than zero"));
     }
+    let csv_options = params.csv_options.unwrap_or_default();
+    csv_options.validate()?;
     let max_cells = config.max_cells();
     let max_payload_bytes = config.max_payload_bytes();
     #[cfg(feature = "recalc")]
@@ -4255,6 +5662,7 @@ pub async fn range_values(
                         && matches!(format, TableOutputFormat::Json | TableOutputFormat::Dense);
                     let mut rows = Vec::new();
                     let mut formula_rows = include_formula_matrix.then(Vec::new);
+                    let mut formatting = include_formatting.then(Vec::new);
                     for r in start_row..=end_row {
                         if rows.len() >= row_limit {
                             break;
@@ -4269,6 +5677,17 @@ pub async fn range_values(
                             };
                             let cell = sheet.get_cell((c, row_index));
                             row_vals.push(cell.and_then(cell_to_value));
+                            if let Some(formatting) = formatting.as_mut()
+                                && let Some(cell) = cell
+                                && let Some(display) = cell_display_text(cell)
+                            {
+                                formatting.push(RangeValuesCellFormat {
+                                    row: rows.len() as u32,
+                                    col: c - start_col,
+                                    number_format: cell_format_code(cell),
+                                    display,
+                                });
+                            }
                             if let Some(formulas) = row_formulas.as_mut() {
                                 formulas.push(cell.and_then(|entry| {
                                     entry.is_formula().then(|| entry.get_formula().to_string())
@@ -4292,7 +5711,9 @@ pub async fn range_values(
                                     range,
                                     &rows[..count],
                                     formula_rows.as_ref().map(|matrix| &matrix[..count]),
+                                    formatting.as_deref(),
                                     None,
+                                    &csv_options,
                                 );
                                 serde_json::to_vec(&entry)
                                     .map(|payload| payload.len())
@@ -4318,7 +5739,9 @@ pub async fn range_values(
                         range,
                         &rows,
                         formula_rows.as_deref(),
+                        formatting.as_deref(),
                         next_start_row,
+                        &csv_options,
                     )
                 })
             })
@@ -4345,6 +5768,7 @@ pub async fn range_values(
                         && matches!(format, TableOutputFormat::Json | TableOutputFormat::Dense);
                     let mut rows = Vec::new();
                     let mut formula_rows = include_formula_matrix.then(Vec::new);
+                    let mut formatting = include_formatting.then(Vec::new);
                     for r in start_row..=end_row {
                         if rows.len() >= row_limit {
                             break;
@@ -4359,6 +5783,17 @@ pub async fn range_values(
                             };
                             let cell = sheet.get_cell((c, row_index));
                             row_vals.push(cell.and_then(cell_to_value));
+                            if let Some(formatting) = formatting.as_mut()
+                                && let Some(cell) = cell
+                                && let Some(display) = cell_display_text(cell)
+                            {
+                                formatting.push(RangeValuesCellFormat {
+                                    row: rows.len() as u32,
+                                    col: c - start_col,
+                                    number_format: cell_format_code(cell),
+                                    display,
+                                });
+                            }
                             if let Some(formulas) = row_formulas.as_mut() {
                                 formulas.push(cell.and_then(|entry| {
                                     entry.is_formula().then(|| entry.get_formula().to_string())
@@ -4382,7 +5817,9 @@ pub async fn range_values(
                                     range,
                                     &rows[..count],
                                     formula_rows.as_ref().map(|matrix| &matrix[..count]),
+                                    formatting.as_deref(),
                                     None,
+                                    &csv_options,
                                 );
                                 serde_json::to_vec(&entry)
                                     .map(|payload| payload.len())
@@ -4408,7 +5845,9 @@ pub async fn range_values(
                         range,
                         &rows,
                         formula_rows.as_deref(),
+                        formatting.as_deref(),
                         next_start_row,
+                        &csv_options,
                     )
                 })
             })
@@ -4432,12 +5871,14 @@ pub async fn range_values(
     #[cfg(not(feature = "recalc"))]
     let warnings: Vec<Warning> = Vec::new();
 
-    Ok(RangeValuesResponse {
+    let response = RangeValuesResponse {
         workbook_id: workbook.id.clone(),
         sheet_name: params.sheet_name,
         warnings,
         values,
-    })
+        compressed: None,
+    };
+    with_requested_compression(response, params.compression, params.compress_min_bytes)
 }
 
 pub async fn inspect_cells(
@@ -4593,12 +6034,16 @@ pub async fn inspect_cells(
     })
 }
 
+/// Addresses listed per sheet when `find_value` groups matches by sheet.
+const FIND_GROUP_SAMPLE_ADDRESSES: usize = 5;
+
 pub async fn find_value(
     state: Arc<AppState>,
     params: FindValueParams,
 ) -> Result<FindValueResponse> {
     let workbook = state.open_workbook(&params.workbook_or_fork_id).await?;
     let mut matches = Vec::new();
+    let mut grouped = Vec::new();
     let mut truncated = false;
     let mut seen: u32 = 0;
     let offset = params.offset.unwrap_or(0);
@@ -4612,6 +6057,30 @@ pub async fn find_value(
     });
     let match_mode = params.match_mode.unwrap_or_default();
     let direction = params.direction.clone().unwrap_or(LabelDirection::Any);
+    let comparison = match &params.comparison {
+        Some(_) if matches!(mode, FindMode::Label) => {
+            return Err(anyhow!("find_value: comparison requires mode 'value'"));
+        }
+        Some(comparison) => Some(ComparisonBounds::parse(
+            comparison,
+            &params.query,
+            workbook.uses_1904_date_system(),
+        )?),
+        None => None,
+    };
+    let regex = if match_mode == MatchMode::Regex && comparison.is_none() {
+        let pattern = match (&mode, &params.label) {
+            (FindMode::Label, Some(label)) => label,
+            _ => &params.query,
+        };
+        let regex = regex::RegexBuilder::new(pattern)
+            .case_insensitive(!params.case_sensitive)
+            .build()
+            .map_err(|e| anyhow!("invalid regex '{pattern}': {e}"))?;
+        Some(regex)
+    } else {
+        None
+    };
 
     let target_sheets: Vec<String> = if let Some(sheet) = &params.sheet_name {
         vec![sheet.clone()]
@@ -4631,6 +6100,12 @@ pub async fn find_value(
         let region_bounds = params
             .region_id
             .and_then(|id| workbook.detected_region(&sheet_name, id).ok());
+        // Grouping counts every match; pagination then applies to sheets.
+        let (sheet_offset, sheet_limit, seen_before) = if params.group_by_sheet {
+            (0, u32::MAX, 0)
+        } else {
+            (offset, limit, seen)
+        };
         let (sheet_matches, sheet_seen, sheet_truncated) =
             workbook.with_sheet(&sheet_name, |sheet| {
                 collect_value_matches(
@@ -4642,11 +6117,27 @@ pub async fn find_value(
                     &params,
                     region_bounds.as_ref(),
                     default_bounds,
-                    offset,
-                    limit,
-                    seen,
+                    regex.as_ref(),
+                    comparison.as_ref(),
+                    sheet_offset,
+                    sheet_limit,
+                    seen_before,
                 )
             })??;
+        if params.group_by_sheet {
+            if !sheet_matches.is_empty() {
+                grouped.push(FindValueSheetMatches {
+                    sheet_name: sheet_name.clone(),
+                    match_count: sheet_matches.len() as u32,
+                    first_addresses: sheet_matches
+                        .iter()
+                        .take(FIND_GROUP_SAMPLE_ADDRESSES)
+                        .map(|m| m.address.clone())
+                        .collect(),
+                });
+            }
+            continue;
+        }
         seen = sheet_seen;
         matches.extend(sheet_matches);
         if sheet_truncated {
@@ -4655,6 +6146,24 @@ pub async fn find_value(
         }
     }
 
+    if params.group_by_sheet {
+        let match_count = grouped.iter().map(|sheet| sheet.match_count).sum();
+        let total_sheets = grouped.len();
+        let sheets: Vec<FindValueSheetMatches> = grouped
+            .into_iter()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .collect();
+        let next_offset = offset.saturating_add(sheets.len() as u32);
+        return Ok(FindValueResponse {
+            workbook_id: workbook.id.clone(),
+            matches: Vec::new(),
+            match_count,
+            sheets,
+            next_offset: ((next_offset as usize) < total_sheets).then_some(next_offset),
+        });
+    }
+
     let next_offset = if truncated {
         Some(offset.saturating_add(matches.len() as u32))
     } else {
@@ -4665,129 +6174,546 @@ pub async fn find_value(
         workbook_id: workbook.id.clone(),
         match_count: matches.len() as u32,
         matches,
+        sheets: Vec::new(),
+        next_offset,
+    })
+}
+
+pub async fn read_table(
+    state: Arc<AppState>,
+    params: ReadTableParams,
+) -> Result<ReadTableResponse> {
+    let config = state.config();
+    let params = named_query::resolve_read_table_query(&config.named_queries, params)?;
+    let workbook = state.open_workbook(&params.workbook_or_fork_id).await?;
+    let output_profile = config.output_profile();
+    let format = params.format.unwrap_or(match output_profile {
+        OutputProfile::TokenDense => TableOutputFormat::Csv,
+        OutputProfile::Verbose => TableOutputFormat::Json,
+    });
+    let include_headers = params.include_headers.unwrap_or(true);
+    let include_types = params.include_types.unwrap_or(false);
+    let csv_options = params.csv_options.unwrap_or_default();
+    csv_options.validate()?;
+    let mut resolved = resolve_table_target(&workbook, &params)?;
+    if params.orientation == Some(TableOrientation::Transposed) {
+        if params.columns.is_some() {
+            return Err(anyhow!(
+                "columns selects sheet columns and cannot be combined with orientation: transposed"
+            ));
+        }
+        resolved = resolved.transposed();
+    }
+    let limit = params.limit.unwrap_or(100) as usize;
+    let offset = params.offset.unwrap_or(0) as usize;
+    let sample_mode = params.sample_mode.unwrap_or_default();
+    let date_handling = params.date_handling.unwrap_or_default();
+    if params.header_rows == Some(0) && params.header_row.is_some() {
+        return Err(anyhow!(
+            "header_row cannot be combined with header_rows: 0 (no header)"
+        ));
+    }
+    let layout = resolve_header_layout(&resolved, params.header_row, params.header_rows);
+
+    #[cfg(feature = "recalc")]
+    let (headers, rows, total_rows, has_formula_in_target) =
+        with_date_handling(date_handling, || {
+            workbook.with_sheet(&resolved.sheet_name, |sheet| {
+                let has_formula_in_target =
+                    sheet_has_formula_in_bounds(sheet, &[resolved.sheet_range()]);
+                let (headers, rows, total_rows) = extract_table_rows(
+                    sheet,
+                    &resolved,
+                    &layout,
+                    params.columns.clone(),
+                    params.filters.clone(),
+                    params.sort.as_deref(),
+                    limit,
+                    offset,
+                    sample_mode,
+                )?;
+                Ok::<_, anyhow::Error>((headers, rows, total_rows, has_formula_in_target))
+            })
+        })??;
+
+    #[cfg(not(feature = "recalc"))]
+    let (headers, rows, total_rows) = with_date_handling(date_handling, || {
+        workbook.with_sheet(&resolved.sheet_name, |sheet| {
+            let (headers, rows, total_rows) = extract_table_rows(
+                sheet,
+                &resolved,
+                &layout,
+                params.columns.clone(),
+                params.filters.clone(),
+                params.sort.as_deref(),
+                limit,
+                offset,
+                sample_mode,
+            )?;
+            Ok::<_, anyhow::Error>((headers, rows, total_rows))
+        })
+    })??;
+
+    #[cfg(feature = "recalc")]
+    let warnings: Vec<Warning> = {
+        if fork_recalc_needed(&state, &params.workbook_or_fork_id) && has_formula_in_target {
+            vec![Warning {
+                code: "WARN_STALE_FORMULAS".to_string(),
+                message: "Fork has pending edits and may contain stale formula results; call recalculate on the fork for fresh values.".to_string(),
+            }]
+        } else {
+            Vec::new()
+        }
+    };
+
+    #[cfg(not(feature = "recalc"))]
+    let warnings: Vec<Warning> = Vec::new();
+
+    let (mut rows, mut warnings) = (rows, warnings);
+    match RoundingPolicy::from_params(params.decimals, params.rounding) {
+        Some(policy) => policy.round_rows(&mut rows),
+        None if workbook.precision_as_displayed() => {
+            warnings.push(precision_as_displayed_warning());
+        }
+        None => {}
+    }
+
+    let date1904 = workbook.uses_1904_date_system();
+    let date_serials_for = |rows: &[TableRow]| {
+        (date_handling == DateHandling::Both)
+            .then(|| table_rows_to_date_serials(&headers, rows, date1904))
+    };
+
+    let max_cells = config.max_cells();
+    let max_payload_bytes = config.max_payload_bytes();
+    let mut row_limit = cap_rows_by_cells(rows.len(), headers.len().max(1), max_cells);
+    if row_limit > 0 {
+        row_limit = cap_rows_by_payload_bytes(row_limit, max_payload_bytes, |count| {
+            let (headers_out, rows_out, values_out, types_out, csv_out) = build_read_table_payload(
+                format,
+                &headers,
+                &rows[..count],
+                include_headers,
+                include_types,
+                &csv_options,
+            );
+            let response = ReadTableResponse {
+                workbook_id: workbook.id.clone(),
+                sheet_name: resolved.sheet_name.clone(),
+                table_name: resolved.table_name.clone(),
+                warnings: warnings.clone(),
+                headers: headers_out,
+                rows: rows_out,
+                values: values_out,
+                types: types_out,
+                date_serials: date_serials_for(&rows[..count]),
+                csv: csv_out,
+                total_rows,
+                next_offset: None,
+                header_detection: Some(layout.clone()),
+                compressed: None,
+            };
+            serde_json::to_vec(&response)
+                .map(|payload| payload.len())
+                .unwrap_or(usize::MAX)
+        });
+    }
+
+    let rows = rows.into_iter().take(row_limit).collect::<Vec<_>>();
+    let next_offset = if offset + rows.len() < total_rows as usize {
+        Some((offset + rows.len()) as u32)
+    } else {
+        None
+    };
+    let (headers_out, rows_out, values_out, types_out, csv_out) = build_read_table_payload(
+        format,
+        &headers,
+        &rows,
+        include_headers,
+        include_types,
+        &csv_options,
+    );
+
+    let response = ReadTableResponse {
+        workbook_id: workbook.id.clone(),
+        sheet_name: resolved.sheet_name,
+        table_name: resolved.table_name,
+        warnings,
+        headers: headers_out,
+        rows: rows_out,
+        values: values_out,
+        types: types_out,
+        date_serials: date_serials_for(&rows),
+        csv: csv_out,
+        total_rows,
+        next_offset,
+        header_detection: Some(layout),
+        compressed: None,
+    };
+    with_requested_compression(response, params.compression, params.compress_min_bytes)
+}
+
+/// Apply a read tool's `compression` and `compress_min_bytes` params.
+#[cfg(feature = "compression")]
+fn with_requested_compression<T: compression::CompressibleResponse>(
+    mut response: T,
+    encoding: Option<ResponseCompression>,
+    min_bytes: Option<u64>,
+) -> Result<T> {
+    if let Some(encoding) = encoding {
+        compression::compress_response(
+            &mut response,
+            encoding,
+            min_bytes.unwrap_or(compression::DEFAULT_COMPRESS_MIN_BYTES),
+        )?;
+    }
+    Ok(response)
+}
+
+#[cfg(not(feature = "compression"))]
+fn with_requested_compression<T>(
+    response: T,
+    encoding: Option<ResponseCompression>,
+    _min_bytes: Option<u64>,
+) -> Result<T> {
+    match encoding {
+        Some(_) => Err(anyhow!("compression requires the compression feature")),
+        None => Ok(response),
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema, Default)]
+pub struct QueryTableParams {
+    /// Workbook ID or fork ID
+    #[serde(alias = "workbook_id")]
+    pub workbook_or_fork_id: WorkbookId,
+    /// Sheet name (uses first sheet if omitted)
+    #[serde(default)]
+    pub sheet_name: Option<String>,
+    /// Query a named Excel table
+    #[serde(default)]
+    pub table_name: Option<String>,
+    /// Query a detected region by ID (from sheet_overview)
+    #[serde(default)]
+    pub region_id: Option<u32>,
+    /// A1-style range (e.g., "A1:D100")
+    #[serde(default)]
+    pub range: Option<String>,
+    /// 1-based row number for headers (auto-detected if omitted)
+    #[serde(default)]
+    pub header_row: Option<u32>,
+    /// Number of header rows, flattened into names like "Q1 / Actual" (default: 1)
+    #[serde(default)]
+    pub header_rows: Option<u32>,
+    /// Header names to group by; without any, the whole table is one group
+    #[serde(default)]
+    pub group_by: Option<Vec<String>>,
+    /// Aggregates to compute per group
+    #[serde(default)]
+    pub aggregations: Vec<TableAggregation>,
+    /// Row filters applied before grouping, as in read_table
+    #[serde(default)]
+    pub filters: Option<Vec<TableFilter>>,
+    /// Maximum groups to return (default: 100)
+    #[serde(default)]
+    pub limit: Option<u32>,
+    /// Offset into the groups; use next_offset from the previous response
+    #[serde(default)]
+    pub offset: Option<u32>,
+    /// Round aggregates to this many decimal places (default: unrounded)
+    #[serde(default)]
+    pub decimals: Option<u32>,
+    /// Tie-breaking for `decimals`: "half_up" (default, as Excel ROUND) or "half_even" (banker's)
+    #[serde(default)]
+    pub rounding: Option<RoundingMode>,
+    /// Scope of `table_name` when it is defined at both workbook and sheet scope
+    #[serde(default)]
+    pub name_scope: Option<NamedRangeScope>,
+}
+
+/// Group a table's rows and aggregate them server-side, so sums and counts
+/// don't need the whole table paged out.
+pub async fn query_table(
+    state: Arc<AppState>,
+    params: QueryTableParams,
+) -> Result<QueryTableResponse> {
+    let group_by = params.group_by.clone().unwrap_or_default();
+    if group_by.is_empty() && params.aggregations.is_empty() {
+        return Err(anyhow!(
+            "query_table needs group_by columns, aggregations, or both"
+        ));
+    }
+    if params.header_rows == Some(0) {
+        return Err(anyhow!(
+            "query_table needs a header row to name group_by and aggregation columns"
+        ));
+    }
+    let workbook = state.open_workbook(&params.workbook_or_fork_id).await?;
+    let resolved = resolve_table_target(
+        &workbook,
+        &ReadTableParams {
+            workbook_or_fork_id: params.workbook_or_fork_id.clone(),
+            sheet_name: params.sheet_name.clone(),
+            table_name: params.table_name.clone(),
+            region_id: params.region_id,
+            range: params.range.clone(),
+            name_scope: params.name_scope,
+            ..Default::default()
+        },
+    )?;
+    let layout = resolve_header_layout(&resolved, params.header_row, params.header_rows);
+
+    let (headers, rows, matched_rows, has_formula_in_target) =
+        workbook.with_sheet(&resolved.sheet_name, |sheet| {
+            let has_formula_in_target = sheet_has_formula_in_bounds(sheet, &[resolved.range]);
+            let (headers, rows, matched_rows) = extract_table_rows(
+                sheet,
+                &resolved,
+                &layout,
+                None,
+                params.filters.clone(),
+                None,
+                u32::MAX as usize,
+                0,
+                SampleMode::First,
+            )?;
+            Ok::<_, anyhow::Error>((headers, rows, matched_rows, has_formula_in_target))
+        })??;
+
+    let (columns, mut groups) =
+        aggregate::aggregate_rows(&headers, &rows, &group_by, &params.aggregations)?;
+
+    let mut warnings = Vec::new();
+    #[cfg(feature = "recalc")]
+    if fork_recalc_needed(&state, &params.workbook_or_fork_id) && has_formula_in_target {
+        warnings.push(Warning {
+            code: "WARN_STALE_FORMULAS".to_string(),
+            message: "Fork has pending edits and may contain stale formula results; call recalculate on the fork for fresh values.".to_string(),
+        });
+    }
+    #[cfg(not(feature = "recalc"))]
+    let _ = has_formula_in_target;
+    match RoundingPolicy::from_params(params.decimals, params.rounding) {
+        Some(policy) => {
+            for value in groups
+                .iter_mut()
+                .flat_map(|group| group.iter_mut().skip(group_by.len()))
+                .flatten()
+            {
+                policy.round_cell(value);
+            }
+        }
+        None if workbook.precision_as_displayed() => {
+            warnings.push(precision_as_displayed_warning());
+        }
+        None => {}
+    }
+
+    let total_groups = groups.len();
+    let limit = params.limit.unwrap_or(100) as usize;
+    let offset = params.offset.unwrap_or(0) as usize;
+    let page: Vec<Vec<Option<CellValuePrimitive>>> = groups
+        .into_iter()
+        .skip(offset)
+        .take(limit)
+        .map(|group| {
+            group
+                .iter()
+                .map(|value| value.as_ref().map(cell_value_to_primitive))
+                .collect()
+        })
+        .collect();
+    let next_offset = (offset + page.len() < total_groups).then(|| (offset + page.len()) as u32);
+
+    Ok(QueryTableResponse {
+        workbook_id: workbook.id.clone(),
+        sheet_name: resolved.sheet_name,
+        table_name: resolved.table_name,
+        warnings,
+        columns,
+        rows: page,
+        matched_rows,
+        total_groups: total_groups as u32,
         next_offset,
     })
 }
 
-pub async fn read_table(
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct EstimateReadParams {
+    /// Workbook ID or fork ID
+    #[serde(alias = "workbook_id")]
+    pub workbook_or_fork_id: WorkbookId,
+    /// Sheet name (uses first sheet if omitted)
+    #[serde(default)]
+    pub sheet_name: Option<String>,
+    /// Estimate a named Excel table
+    #[serde(default)]
+    pub table_name: Option<String>,
+    /// Estimate a detected region by ID (from sheet_overview)
+    #[serde(default)]
+    pub region_id: Option<u32>,
+    /// A1-style range (e.g., "A1:D100")
+    #[serde(default)]
+    pub range: Option<String>,
+    /// Limit to specific columns by letter (e.g., ["A", "C", "D"])
+    #[serde(default)]
+    pub columns: Option<Vec<String>>,
+    /// Row filters the read would apply
+    #[serde(default)]
+    pub filters: Option<Vec<TableFilter>>,
+    /// Page size the read would request (default: 100, as in read_table)
+    #[serde(default)]
+    pub limit: Option<u32>,
+    /// Output format the read would use
+    #[serde(default)]
+    pub format: Option<TableOutputFormat>,
+}
+
+/// Rows serialized to measure the average row size.
+const ESTIMATE_SAMPLE_ROWS: usize = 200;
+
+/// Size a prospective `read_table` call without returning its rows: matching
+/// row and cell counts, estimated payload bytes, and whether the configured
+/// caps would truncate a page of the requested size.
+pub async fn estimate_read(
     state: Arc<AppState>,
-    params: ReadTableParams,
-) -> Result<ReadTableResponse> {
+    params: EstimateReadParams,
+) -> Result<ReadEstimateResponse> {
     let workbook = state.open_workbook(&params.workbook_or_fork_id).await?;
     let config = state.config();
-    let output_profile = config.output_profile();
-    let format = params.format.unwrap_or(match output_profile {
+    let format = params.format.unwrap_or(match config.output_profile() {
         OutputProfile::TokenDense => TableOutputFormat::Csv,
         OutputProfile::Verbose => TableOutputFormat::Json,
     });
-    let include_headers = params.include_headers.unwrap_or(true);
-    let include_types = params.include_types.unwrap_or(false);
-    let resolved = resolve_table_target(&workbook, &params)?;
-    let limit = params.limit.unwrap_or(100) as usize;
-    let offset = params.offset.unwrap_or(0) as usize;
-    let sample_mode = params.sample_mode.unwrap_or_default();
-
-    #[cfg(feature = "recalc")]
-    let (headers, rows, total_rows, has_formula_in_target) =
-        workbook.with_sheet(&resolved.sheet_name, |sheet| {
-            let has_formula_in_target = sheet_has_formula_in_bounds(sheet, &[resolved.range]);
-            let (headers, rows, total_rows) = extract_table_rows(
-                sheet,
-                &resolved,
-                params.header_row,
-                params.header_rows,
-                params.columns.clone(),
-                params.filters.clone(),
-                limit,
-                offset,
-                sample_mode,
-            )?;
-            Ok::<_, anyhow::Error>((headers, rows, total_rows, has_formula_in_target))
-        })??;
+    let resolved = resolve_table_target(
+        &workbook,
+        &ReadTableParams {
+            workbook_or_fork_id: params.workbook_or_fork_id.clone(),
+            sheet_name: params.sheet_name.clone(),
+            table_name: params.table_name.clone(),
+            region_id: params.region_id,
+            range: params.range.clone(),
+            header_row: None,
+            header_rows: None,
+            orientation: None,
+            columns: params.columns.clone(),
+            filters: params.filters.clone(),
+            sample_mode: None,
+            limit: params.limit,
+            offset: None,
+            format: Some(format),
+            include_headers: None,
+            include_types: None,
+            sort: None,
+            query: None,
+            decimals: None,
+            rounding: None,
+            name_scope: None,
+            csv_options: None,
+            date_handling: None,
+            compression: None,
+            compress_min_bytes: None,
+        },
+    )?;
 
-    #[cfg(not(feature = "recalc"))]
-    let (headers, rows, total_rows) = workbook.with_sheet(&resolved.sheet_name, |sheet| {
-        let (headers, rows, total_rows) = extract_table_rows(
+    let (headers, sample, total_rows) = workbook.with_sheet(&resolved.sheet_name, |sheet| {
+        extract_table_rows(
             sheet,
             &resolved,
-            params.header_row,
-            params.header_rows,
+            &resolve_header_layout(&resolved, None, None),
             params.columns.clone(),
             params.filters.clone(),
-            limit,
-            offset,
-            sample_mode,
-        )?;
-        Ok::<_, anyhow::Error>((headers, rows, total_rows))
+            None,
+            ESTIMATE_SAMPLE_ROWS,
+            0,
+            SampleMode::Distributed,
+        )
     })??;
 
-    #[cfg(feature = "recalc")]
-    let warnings: Vec<Warning> = {
-        if fork_recalc_needed(&state, &params.workbook_or_fork_id) && has_formula_in_target {
-            vec![Warning {
-                code: "WARN_STALE_FORMULAS".to_string(),
-                message: "Fork has pending edits and may contain stale formula results; call recalculate on the fork for fresh values.".to_string(),
-            }]
-        } else {
-            Vec::new()
-        }
+    let payload_bytes = |rows: &[TableRow]| {
+        let (headers_out, rows_out, values_out, types_out, csv_out) =
+            build_read_table_payload(format, &headers, rows, true, false, &CsvOptions::default());
+        let response = ReadTableResponse {
+            workbook_id: workbook.id.clone(),
+            sheet_name: resolved.sheet_name.clone(),
+            table_name: resolved.table_name.clone(),
+            warnings: Vec::new(),
+            headers: headers_out,
+            rows: rows_out,
+            values: values_out,
+            types: types_out,
+            date_serials: None,
+            csv: csv_out,
+            total_rows,
+            next_offset: None,
+            header_detection: None,
+            compressed: None,
+        };
+        serde_json::to_vec(&response)
+            .map(|payload| payload.len())
+            .unwrap_or(0)
+    };
+    let base_bytes = payload_bytes(&[]);
+    let bytes_per_row = if sample.is_empty() {
+        0
+    } else {
+        payload_bytes(&sample)
+            .saturating_sub(base_bytes)
+            .div_ceil(sample.len())
     };
 
-    #[cfg(not(feature = "recalc"))]
-    let warnings: Vec<Warning> = Vec::new();
-
+    let total = total_rows as usize;
     let max_cells = config.max_cells();
     let max_payload_bytes = config.max_payload_bytes();
-    let mut row_limit = cap_rows_by_cells(rows.len(), headers.len().max(1), max_cells);
-    if row_limit > 0 {
-        row_limit = cap_rows_by_payload_bytes(row_limit, max_payload_bytes, |count| {
-            let (headers_out, rows_out, values_out, types_out, csv_out) = build_read_table_payload(
-                format,
-                &headers,
-                &rows[..count],
-                include_headers,
-                include_types,
-            );
-            let response = ReadTableResponse {
-                workbook_id: workbook.id.clone(),
-                sheet_name: resolved.sheet_name.clone(),
-                table_name: resolved.table_name.clone(),
-                warnings: warnings.clone(),
-                headers: headers_out,
-                rows: rows_out,
-                values: values_out,
-                types: types_out,
-                csv: csv_out,
-                total_rows,
-                next_offset: None,
-            };
-            serde_json::to_vec(&response)
-                .map(|payload| payload.len())
-                .unwrap_or(usize::MAX)
-        });
+    let mut max_rows_per_page = cap_rows_by_cells(total, headers.len().max(1), max_cells);
+    if let Some(max_bytes) = max_payload_bytes
+        && bytes_per_row > 0
+    {
+        max_rows_per_page =
+            max_rows_per_page.min(max_bytes.saturating_sub(base_bytes) / bytes_per_row);
     }
 
-    let rows = rows.into_iter().take(row_limit).collect::<Vec<_>>();
-    let next_offset = if offset + rows.len() < total_rows as usize {
-        Some((offset + rows.len()) as u32)
-    } else {
-        None
-    };
-    let (headers_out, rows_out, values_out, types_out, csv_out) =
-        build_read_table_payload(format, &headers, &rows, include_headers, include_types);
+    let requested_rows = (params.limit.unwrap_or(100) as usize).min(total);
+    let mut truncated_by = Vec::new();
+    if cap_rows_by_cells(requested_rows, headers.len().max(1), max_cells) < requested_rows {
+        truncated_by.push("max_cells".to_string());
+    }
+    if let Some(max_bytes) = max_payload_bytes
+        && base_bytes + bytes_per_row * requested_rows > max_bytes
+    {
+        truncated_by.push("max_payload_bytes".to_string());
+    }
 
-    Ok(ReadTableResponse {
+    let page_rows = requested_rows.min(max_rows_per_page).max(1);
+    let ((start_col, start_row), (end_col, end_row)) = resolved.range;
+
+    Ok(ReadEstimateResponse {
         workbook_id: workbook.id.clone(),
         sheet_name: resolved.sheet_name,
         table_name: resolved.table_name,
-        warnings,
-        headers: headers_out,
-        rows: rows_out,
-        values: values_out,
-        types: types_out,
-        csv: csv_out,
-        total_rows,
-        next_offset,
+        range: format!(
+            "{}{}:{}{}",
+            column_number_to_name(start_col),
+            start_row,
+            column_number_to_name(end_col),
+            end_row
+        ),
+        format,
+        row_count: total_rows,
+        column_count: headers.len() as u32,
+        cell_count: total_rows as u64 * headers.len() as u64,
+        estimated_payload_bytes: (base_bytes + bytes_per_row * total) as u64,
+        bytes_per_row: bytes_per_row as u64,
+        sampled_rows: sample.len() as u32,
+        caps: ReadEstimateCaps {
+            max_cells,
+            max_payload_bytes,
+        },
+        requested_rows: requested_rows as u32,
+        would_truncate: !truncated_by.is_empty(),
+        truncated_by,
+        max_rows_per_page: max_rows_per_page as u32,
+        pages_needed: total.div_ceil(page_rows) as u32,
     })
 }
 
@@ -4811,6 +6737,7 @@ pub async fn table_profile(
             range: None,
             header_row: None,
             header_rows: None,
+            orientation: None,
             columns: None,
             filters: None,
             sample_mode: params.sample_mode,
@@ -4819,24 +6746,38 @@ pub async fn table_profile(
             format: Some(TableOutputFormat::Json),
             include_headers: None,
             include_types: None,
+            sort: None,
+            query: None,
+            decimals: None,
+            rounding: None,
+            name_scope: None,
+            csv_options: None,
+            date_handling: None,
+            compression: None,
+            compress_min_bytes: None,
         },
     )?;
 
     let sample_size = params.sample_size.unwrap_or(10) as usize;
     let sample_mode = params.sample_mode.unwrap_or(SampleMode::Distributed);
+    let top_n = params
+        .top_n
+        .map_or(column_stats::DEFAULT_TOP_N, |n| n as usize);
 
-    let (mut headers, rows, total_rows) =
+    // Every row feeds the column stats; the sample is drawn from them after.
+    let layout = resolve_header_layout(&resolved, None, None);
+    let (mut headers, all_rows, total_rows) =
         workbook.with_sheet(&resolved.sheet_name, |sheet| {
             extract_table_rows(
                 sheet,
                 &resolved,
+                &layout,
                 None,
                 None,
                 None,
-                None,
-                sample_size,
+                u32::MAX as usize,
                 0,
-                sample_mode,
+                SampleMode::First,
             )
         })??;
 
@@ -4849,7 +6790,35 @@ pub async fn table_profile(
         headers.truncate(max_items);
     }
 
+    let stats: Vec<ColumnStats> = headers
+        .iter()
+        .map(|header| column_stats::column_stats(header, &all_rows, layout.data_start_row, top_n))
+        .collect();
+    let schema = params.emit_schema.map(|format| {
+        let title = resolved
+            .table_name
+            .as_deref()
+            .unwrap_or(&resolved.sheet_name);
+        table_schema::table_schema(format, title, &headers, &all_rows)
+    });
+    let rows = sample_rows(all_rows, sample_size, 0, sample_mode);
+
     let mut column_types = summarize_columns(&headers, &rows);
+    for (column, stats) in column_types.iter_mut().zip(stats) {
+        column.stats = Some(stats);
+    }
+    let mut rows = rows;
+    let mut notes = Vec::new();
+    match RoundingPolicy::from_params(params.decimals, params.rounding) {
+        Some(policy) => {
+            policy.round_column_types(&mut column_types);
+            policy.round_rows(&mut rows);
+        }
+        None if workbook.precision_as_displayed() => {
+            notes.push(precision_as_displayed_warning().message);
+        }
+        None => {}
+    }
 
     let mut samples: Vec<TableRow> = if summary_only {
         Vec::new()
@@ -4877,6 +6846,7 @@ pub async fn table_profile(
                     row_count: total_rows,
                     samples: samples[..count].to_vec(),
                     notes: Vec::new(),
+                    schema: schema.clone(),
                 };
                 serde_json::to_vec(&response)
                     .map(|payload| payload.len())
@@ -4895,6 +6865,7 @@ pub async fn table_profile(
                 row_count: total_rows,
                 samples: samples.clone(),
                 notes: Vec::new(),
+                schema: schema.clone(),
             };
             if serde_json::to_vec(&response)
                 .map(|payload| payload.len() > max_bytes)
@@ -4918,6 +6889,7 @@ pub async fn table_profile(
                             row_count: total_rows,
                             samples: samples_slice,
                             notes: Vec::new(),
+                            schema: schema.clone(),
                         };
                         serde_json::to_vec(&response)
                             .map(|payload| payload.len())
@@ -4944,10 +6916,20 @@ pub async fn table_profile(
         column_types,
         row_count: total_rows,
         samples,
-        notes: Vec::new(),
+        notes,
+        schema,
     })
 }
 
+/// Raised when a workbook uses "precision as displayed" and the caller did not
+/// ask for rounding, so results may not match the figures Excel shows.
+fn precision_as_displayed_warning() -> Warning {
+    Warning {
+        code: "WARN_PRECISION_AS_DISPLAYED".to_string(),
+        message: "Workbook uses \"precision as displayed\": Excel rounds stored values to their displayed format, but figures computed here and by recalculation are not; pass decimals to match the workbook's reported figures.".to_string(),
+    }
+}
+
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct ManifestStubParams {
     #[serde(alias = "workbook_id")]
@@ -5169,6 +7151,244 @@ pub async fn get_manifest_stub(
     ))
 }
 
+const DEFAULT_WATCH_LIMIT: u32 = 100;
+const MAX_WATCH_TIMEOUT_MS: u64 = 30_000;
+
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+pub struct WatchWorkspaceParams {
+    /// Return events with seq >= since_seq (use next_seq from the previous
+    /// call). Omit on the first call to start from now.
+    #[serde(default)]
+    pub since_seq: Option<u64>,
+    /// Wait up to this long for the first event when none is pending
+    /// (default 0, max 30000)
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    /// Maximum events to return (default 100)
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
+
+pub async fn watch_workspace(
+    state: Arc<AppState>,
+    params: WatchWorkspaceParams,
+) -> Result<WatchWorkspaceResponse> {
+    let log = state.watch_workspace()?;
+    let since_seq = params.since_seq.unwrap_or_else(|| log.next_seq());
+    let limit = params.limit.unwrap_or(DEFAULT_WATCH_LIMIT).max(1) as usize;
+    let mut timeout =
+        std::time::Duration::from_millis(params.timeout_ms.unwrap_or(0).min(MAX_WATCH_TIMEOUT_MS));
+    // Return before the server-wide tool timeout turns an idle wait into an error.
+    if let Some(tool_timeout) = state.config().tool_timeout() {
+        timeout = timeout.min(tool_timeout.mul_f64(0.9));
+    }
+
+    let batch = log.wait_since(since_seq, limit, timeout).await;
+    for event in &batch.events {
+        if event.kind != WorkspaceEventKind::Added {
+            state.evict_by_id(&event.workbook_id);
+        }
+    }
+    Ok(WatchWorkspaceResponse {
+        events: batch.events,
+        next_seq: batch.next_seq,
+        missed_events: batch.missed_events,
+    })
+}
+
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+pub struct DedupeScanParams {
+    /// Folder under the workspace root to scan (default: the whole workspace)
+    #[serde(default)]
+    pub folder: Option<String>,
+    /// Row similarity (0-1) at which two different workbooks are reported as
+    /// near duplicates (default 0.9)
+    #[serde(default)]
+    pub min_similarity: Option<f64>,
+}
+
+/// Fingerprint every workbook under the workspace root and report exact,
+/// same-content, and near-duplicate files.
+pub async fn dedupe_scan(
+    state: Arc<AppState>,
+    params: DedupeScanParams,
+) -> Result<DedupeScanResponse> {
+    let config = state.config();
+    if config.virtual_workspace || config.single_workbook().is_some() {
+        return Err(anyhow!(
+            "dedupe_scan requires a workspace root (not available with --virtual-workspace or a single workbook)"
+        ));
+    }
+    let workspace_root = config.workspace_root.clone();
+    let root = match params.folder.as_deref() {
+        Some(folder) => {
+            let folder = std::path::Path::new(folder);
+            if folder.is_absolute()
+                || folder
+                    .components()
+                    .any(|part| matches!(part, std::path::Component::ParentDir))
+            {
+                return Err(anyhow!(
+                    "folder must be a relative path inside the workspace"
+                ));
+            }
+            workspace_root.join(folder)
+        }
+        None => workspace_root.clone(),
+    };
+    let extensions = config.supported_extensions.clone();
+    let min_similarity = params
+        .min_similarity
+        .unwrap_or(dedupe::DEFAULT_MIN_SIMILARITY);
+    tokio::task::spawn_blocking(move || {
+        dedupe::scan_duplicates(&[root], &extensions, min_similarity, Some(&workspace_root))
+    })
+    .await?
+}
+
+#[cfg(feature = "recalc")]
+const DEFAULT_DOWNLOAD_CHUNK_BYTES: u64 = 512 * 1024;
+#[cfg(feature = "recalc")]
+/// Room left in a response for everything but the base64 payload.
+const DOWNLOAD_ENVELOPE_BYTES: usize = 1024;
+
+#[cfg(feature = "recalc")]
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct UploadWorkbookParams {
+    /// Name to store the workbook under, e.g. `budget.xlsx`; an existing
+    /// workbook with the same key is replaced
+    pub key: String,
+    /// Base64-encoded workbook bytes, or one chunk of them
+    pub data: String,
+    /// Decoded byte offset this chunk starts at (default 0; 0 restarts the
+    /// upload)
+    #[serde(default)]
+    pub offset: u64,
+    /// Set false while more chunks follow (default true)
+    #[serde(default = "default_upload_complete")]
+    pub complete: bool,
+    #[serde(default)]
+    pub slug: Option<String>,
+}
+
+#[cfg(feature = "recalc")]
+fn default_upload_complete() -> bool {
+    true
+}
+
+#[cfg(feature = "recalc")]
+pub async fn upload_workbook(
+    state: Arc<AppState>,
+    params: UploadWorkbookParams,
+) -> Result<UploadWorkbookResponse> {
+    use base64::Engine;
+
+    if !state.config().virtual_workspace {
+        return Err(anyhow!(
+            "upload_workbook requires a virtual workspace (start the server with --virtual-workspace)"
+        ));
+    }
+    let key = params.key.trim().to_string();
+    let extension = std::path::Path::new(&key)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase());
+    if !matches!(extension.as_deref(), Some("xlsx" | "xlsm")) {
+        return Err(anyhow!("key '{}' must end in .xlsx or .xlsm", params.key));
+    }
+    let chunk = base64::engine::general_purpose::STANDARD
+        .decode(params.data.trim())
+        .map_err(|err| anyhow!("data is not valid base64: {err}"))?;
+
+    let received_bytes = state.uploads().append(&key, params.offset, &chunk)?;
+    if !params.complete {
+        return Ok(UploadWorkbookResponse {
+            key,
+            received_bytes,
+            complete: false,
+            workbook_id: None,
+            short_id: None,
+            revision_id: None,
+        });
+    }
+
+    let bytes = state.uploads().take(&key).unwrap_or_default();
+    // Workbooks are zip containers; catch truncated or mis-encoded uploads
+    // before they replace a readable workbook.
+    if !bytes.starts_with(b"PK\x03\x04") {
+        return Err(anyhow!(
+            "upload '{key}' is not an xlsx workbook ({received_bytes} bytes received)"
+        ));
+    }
+    let resolved = state.register_workbook(VirtualWorkbookInput {
+        key: key.clone(),
+        slug: params.slug,
+        bytes,
+    })?;
+    Ok(UploadWorkbookResponse {
+        key,
+        received_bytes,
+        complete: true,
+        workbook_id: Some(resolved.workbook_id),
+        short_id: Some(resolved.short_id),
+        revision_id: resolved.revision_id,
+    })
+}
+
+#[cfg(feature = "recalc")]
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct DownloadWorkbookParams {
+    #[serde(alias = "workbook_id")]
+    pub workbook_or_fork_id: WorkbookId,
+    /// Decoded byte offset to start from (default 0)
+    #[serde(default)]
+    pub offset: u64,
+    /// Maximum decoded bytes to return (default 524288; capped so the
+    /// response fits the server response limit)
+    #[serde(default)]
+    pub max_bytes: Option<u64>,
+}
+
+#[cfg(feature = "recalc")]
+pub async fn download_workbook(
+    state: Arc<AppState>,
+    params: DownloadWorkbookParams,
+) -> Result<DownloadWorkbookResponse> {
+    use base64::Engine;
+
+    let resolved = state.resolve_workbook(&params.workbook_or_fork_id)?;
+    let bytes = state.workbook_bytes(&resolved)?;
+    let total_bytes = bytes.len() as u64;
+    if params.offset > total_bytes {
+        return Err(anyhow!(
+            "offset {} is past the end of the workbook ({total_bytes} bytes)",
+            params.offset
+        ));
+    }
+
+    let mut chunk_bytes = params
+        .max_bytes
+        .unwrap_or(DEFAULT_DOWNLOAD_CHUNK_BYTES)
+        .max(1);
+    if let Some(limit) = state.config().max_response_bytes() {
+        // Base64 turns every 3 bytes into 4 characters.
+        let fits = (limit.saturating_sub(DOWNLOAD_ENVELOPE_BYTES) / 4 * 3) as u64;
+        chunk_bytes = chunk_bytes.min(fits.max(3));
+    }
+    let end = total_bytes.min(params.offset + chunk_bytes);
+    let data = base64::engine::general_purpose::STANDARD
+        .encode(&bytes[params.offset as usize..end as usize]);
+
+    Ok(DownloadWorkbookResponse {
+        workbook_id: resolved.workbook_id,
+        revision_id: resolved.revision_id,
+        total_bytes,
+        offset: params.offset,
+        data,
+        next_offset: (end < total_bytes).then_some(end),
+    })
+}
+
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct CloseWorkbookParams {
     #[serde(alias = "workbook_id")]
@@ -5179,10 +7399,18 @@ pub async fn close_workbook(
     state: Arc<AppState>,
     params: CloseWorkbookParams,
 ) -> Result<CloseWorkbookResponse> {
-    state.close_workbook(&params.workbook_or_fork_id)?;
+    let released_pin = state.close_workbook(&params.workbook_or_fork_id)?;
+    let message = if released_pin {
+        format!(
+            "workbook {} evicted; pinned revision released",
+            params.workbook_or_fork_id.as_str()
+        )
+    } else {
+        format!("workbook {} evicted", params.workbook_or_fork_id.as_str())
+    };
     Ok(CloseWorkbookResponse {
         workbook_id: params.workbook_or_fork_id.clone(),
-        message: format!("workbook {} evicted", params.workbook_or_fork_id.as_str()),
+        message,
     })
 }
 #[allow(clippy::too_many_arguments)]
@@ -5191,6 +7419,7 @@ fn collect_formula_matches(
     sheet_name: &str,
     query: &str,
     case_sensitive: bool,
+    reference_style: ReferenceStyle,
     include_context: bool,
     context_rows: u32,
     context_cols: u32,
@@ -5207,9 +7436,19 @@ fn collect_formula_matches(
         if !cell.is_formula() {
             continue;
         }
-        let formula = cell.get_formula();
+        let coord = cell.get_coordinate();
+        let column = *coord.get_col_num();
+        let row = *coord.get_row_num();
+
+        let formula = match reference_style {
+            ReferenceStyle::A1 => cell.get_formula().to_string(),
+            ReferenceStyle::R1C1 => {
+                crate::formula::pattern::formula_to_r1c1(cell.get_formula(), row, column)
+                    .unwrap_or_else(|| cell.get_formula().to_string())
+            }
+        };
         let haystack = if case_sensitive {
-            formula.to_string()
+            formula.clone()
         } else {
             formula.to_ascii_lowercase()
         };
@@ -5226,10 +7465,6 @@ fn collect_formula_matches(
             return (results, seen, true);
         }
 
-        let coord = cell.get_coordinate();
-        let column = *coord.get_col_num();
-        let row = *coord.get_row_num();
-
         let context = if include_context {
             let col_start = column.saturating_sub(context_cols / 2).max(1);
             let col_end = column + context_cols / 2;
@@ -5258,7 +7493,7 @@ fn collect_formula_matches(
         results.push(FindFormulaMatch {
             address: coord.get_coordinate(),
             sheet_name: sheet_name.to_string(),
-            formula: formula.to_string(),
+            formula,
             cached_value: cell_to_value(cell),
             context,
         });
@@ -5280,6 +7515,7 @@ struct TraceEdgeRaw {
     from: String,
     to: String,
     neighbor: String,
+    via_name: Option<String>,
 }
 
 #[derive(Clone)]
@@ -5301,9 +7537,48 @@ struct NeighborDetail {
     external: bool,
 }
 
-fn build_formula_lookup(graph: &FormulaGraph) -> HashMap<String, TraceFormulaInfo> {
+/// Split and merge formula groups so each holds the cells sharing one R1C1
+/// formula. The group formula becomes `=` plus that R1C1 text and the
+/// fingerprint a hash of it.
+fn group_formulas_by_r1c1(groups: Vec<FormulaGroup>) -> Vec<FormulaGroup> {
+    let mut merged: Vec<FormulaGroup> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for group in groups {
+        let ast = crate::formula::pattern::parse_base_formula(&group.formula).ok();
+        for address in &group.addresses {
+            let r1c1 = ast
+                .as_ref()
+                .zip(parse_address(address))
+                .map(|(ast, (col, row))| {
+                    format!("={}", crate::formula::pattern::r1c1_pattern(ast, row, col))
+                })
+                .unwrap_or_else(|| group.formula.clone());
+            let slot = *index.entry(r1c1.clone()).or_insert_with(|| {
+                merged.push(FormulaGroup {
+                    fingerprint: hash_bytes_sha256_hex(r1c1.as_bytes())[..16].to_string(),
+                    addresses: Vec::new(),
+                    count: Some(0),
+                    formula: r1c1.clone(),
+                    is_array: false,
+                    is_shared: false,
+                    is_volatile: false,
+                });
+                merged.len() - 1
+            });
+            let target = &mut merged[slot];
+            target.addresses.push(address.clone());
+            target.count = Some(target.addresses.len() as u32);
+            target.is_array |= group.is_array;
+            target.is_shared |= group.is_shared;
+            target.is_volatile |= group.is_volatile;
+        }
+    }
+    merged
+}
+
+fn build_formula_lookup(groups: Vec<FormulaGroup>) -> HashMap<String, TraceFormulaInfo> {
     let mut map = HashMap::new();
-    for group in graph.groups() {
+    for group in groups {
         for address in group.addresses.clone() {
             map.insert(
                 address.to_ascii_uppercase(),
@@ -5468,11 +7743,13 @@ fn collect_layer_links(
                         from: cell.clone(),
                         to: neighbor_upper.clone(),
                         neighbor: neighbor_upper.clone(),
+                        via_name: graph.precedent_name(cell, &neighbor).map(str::to_string),
                     },
                     TraceDirection::Dependents => TraceEdgeRaw {
                         from: neighbor_upper.clone(),
                         to: cell.clone(),
                         neighbor: neighbor_upper.clone(),
+                        via_name: graph.precedent_name(&neighbor, cell).map(str::to_string),
                     },
                 };
                 edges.push(edge);
@@ -5819,6 +8096,7 @@ fn build_edges_for_layer(
                 to: edge.to.clone(),
                 formula,
                 note: None,
+                via_name: edge.via_name.clone(),
             });
         }
     }
@@ -6293,6 +8571,7 @@ pub async fn grid_export(
                                 })
                             }),
                             number_format: None,
+                            number_format_locale: None,
                         });
                     }
 
@@ -6602,6 +8881,7 @@ pub async fn layout_page(
         max_row,
     );
 
+    let right_to_left = workbook.sheet_is_right_to_left(&params.sheet_name);
     let ascii_render = match render {
         LayoutRender::Ascii | LayoutRender::Both => Some(render_layout_ascii(
             &columns,
@@ -6611,6 +8891,7 @@ pub async fn layout_page(
             } else {
                 Some(requested_max_col_width as usize)
             },
+            right_to_left,
         )),
         LayoutRender::Json => None,
     };
@@ -6629,11 +8910,40 @@ pub async fn layout_page(
         merged_cells,
         rows,
         ascii_render,
+        right_to_left,
         truncated,
         notes,
     })
 }
 
+/// Columns and cells in right-to-left display order, with left and right
+/// borders swapped to match.
+fn mirror_layout(
+    columns: &[LayoutPageColumnInfo],
+    rows: &[LayoutRowInfo],
+) -> (Vec<LayoutPageColumnInfo>, Vec<LayoutRowInfo>) {
+    let columns = columns.iter().rev().cloned().collect();
+    let rows = rows
+        .iter()
+        .map(|row| LayoutRowInfo {
+            row: row.row,
+            cells: row
+                .cells
+                .iter()
+                .rev()
+                .cloned()
+                .map(|mut cell| {
+                    if let Some(borders) = cell.borders.as_mut() {
+                        std::mem::swap(&mut borders.left, &mut borders.right);
+                    }
+                    cell
+                })
+                .collect(),
+        })
+        .collect();
+    (columns, rows)
+}
+
 /// Format a cell's value as a display string for the layout render.
 fn cell_display_string(cell: &umya_spreadsheet::Cell) -> String {
     use crate::workbook::cell_to_value;
@@ -6679,6 +8989,7 @@ fn render_layout_ascii(
     columns: &[LayoutPageColumnInfo],
     rows: &[LayoutRowInfo],
     max_col_width: Option<usize>,
+    right_to_left: bool,
 ) -> String {
     use std::fmt::Write;
 
@@ -6686,6 +8997,14 @@ fn render_layout_ascii(
         return String::new();
     }
 
+    let mirrored;
+    let (columns, rows) = if right_to_left {
+        mirrored = mirror_layout(columns, rows);
+        (mirrored.0.as_slice(), mirrored.1.as_slice())
+    } else {
+        (columns, rows)
+    };
+
     // Column display widths (capped, minimum 3 for truncation marker)
     let col_widths: Vec<usize> = columns
         .iter()
@@ -6854,8 +9173,14 @@ fn render_layout_ascii(
                 .next()
                 .map(|c| c.is_ascii_digit())
                 .unwrap_or(false);
-            let right_align = cell.align_h.as_deref() == Some("right")
-                || (is_numeric && cell.align_h.as_deref().map(|a| a != "left").unwrap_or(true));
+            let right_align = match cell.align_h.as_deref() {
+                Some("right") => true,
+                Some("left") => false,
+                // General alignment follows the sheet direction: in a right-to-left
+                // sheet text hugs the right edge and numbers the left.
+                None => is_numeric != right_to_left,
+                Some(_) => is_numeric,
+            };
 
             // Build decorated content
             let decorated = {
@@ -0,0 +1,189 @@
+//! Named `read_table` queries maintained centrally in workspace config.
+//!
+//! Definitions live under `named_queries` in the server config file or under
+//! `queries` in `<workspace>/.asp/queries.yaml`:
+//!
+//! ```yaml
+//! queries:
+//!   monthly_expenses:
+//!     sheet_name: Expenses
+//!     range: A1:F500
+//!     filters: [{ column: Category, op: eq, value: Travel }]
+//!     sort: [{ column: Amount, descending: true }]
+//!     limit: 200
+//! ```
+
+use super::param_enums::{RoundingMode, TableOrientation};
+use super::{ReadTableParams, SampleMode, TableFilter, TableSort};
+use crate::errors::InvalidParamsError;
+use crate::model::TableOutputFormat;
+use anyhow::{Context, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Workspace-relative files checked for named queries, in order.
+pub const WORKSPACE_QUERY_FILES: [&str; 3] =
+    [".asp/queries.yaml", ".asp/queries.yml", ".asp/queries.json"];
+
+pub type NamedQueries = BTreeMap<String, NamedQuery>;
+
+/// A stored `read_table` request. Every field is optional; fields passed
+/// explicitly on the call override the stored ones.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct NamedQuery {
+    /// What the query returns, for humans browsing the config
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, alias = "sheet", skip_serializing_if = "Option::is_none")]
+    pub sheet_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub table_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region_id: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header_row: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header_rows: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub orientation: Option<TableOrientation>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub columns: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filters: Option<Vec<TableFilter>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sort: Option<Vec<TableSort>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample_mode: Option<SampleMode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<TableOutputFormat>,
+    /// Decimal places numeric values are rounded to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decimals: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rounding: Option<RoundingMode>,
+}
+
+impl NamedQuery {
+    /// Capture the query-shaping fields of `params`, e.g. to store a request
+    /// that was assembled from flags and re-run it later.
+    pub fn from_params(params: &ReadTableParams) -> Self {
+        Self {
+            description: None,
+            sheet_name: params.sheet_name.clone(),
+            table_name: params.table_name.clone(),
+            region_id: params.region_id,
+            range: params.range.clone(),
+            header_row: params.header_row,
+            header_rows: params.header_rows,
+            orientation: params.orientation,
+            columns: params.columns.clone(),
+            filters: params.filters.clone(),
+            sort: params.sort.clone(),
+            sample_mode: params.sample_mode,
+            limit: params.limit,
+            format: params.format,
+            decimals: params.decimals,
+            rounding: params.rounding,
+        }
+    }
+
+    /// Fill the fields `params` leaves unset from this query.
+    pub fn apply_to(&self, params: &mut ReadTableParams) {
+        // A table, region, or range on the call replaces the stored target
+        // as a whole rather than mixing with it.
+        let explicit_target =
+            params.table_name.is_some() || params.region_id.is_some() || params.range.is_some();
+        if !explicit_target {
+            params.table_name = self.table_name.clone();
+            params.region_id = self.region_id;
+            params.range = self.range.clone();
+            params.header_row = params.header_row.or(self.header_row);
+            params.header_rows = params.header_rows.or(self.header_rows);
+            params.orientation = params.orientation.or(self.orientation);
+        }
+        fill(&mut params.sheet_name, &self.sheet_name);
+        fill(&mut params.columns, &self.columns);
+        fill(&mut params.filters, &self.filters);
+        fill(&mut params.sort, &self.sort);
+        params.sample_mode = params.sample_mode.or(self.sample_mode);
+        params.limit = params.limit.or(self.limit);
+        params.format = params.format.or(self.format);
+        params.decimals = params.decimals.or(self.decimals);
+        params.rounding = params.rounding.or(self.rounding);
+    }
+}
+
+fn fill<T: Clone>(slot: &mut Option<T>, stored: &Option<T>) {
+    if slot.is_none() {
+        slot.clone_from(stored);
+    }
+}
+
+/// Replace `params.query` with the stored definition it names.
+pub fn resolve_read_table_query(
+    queries: &NamedQueries,
+    mut params: ReadTableParams,
+) -> Result<ReadTableParams> {
+    let Some(name) = params.query.take() else {
+        return Ok(params);
+    };
+    let Some(query) = queries.get(&name) else {
+        let known = if queries.is_empty() {
+            "none are defined".to_string()
+        } else {
+            format!(
+                "defined: {}",
+                queries.keys().cloned().collect::<Vec<_>>().join(", ")
+            )
+        };
+        return Err(InvalidParamsError::new(
+            "read_table",
+            format!("unknown named query '{name}' ({known})"),
+        )
+        .with_path("query")
+        .into());
+    };
+    query.apply_to(&mut params);
+    Ok(params)
+}
+
+/// Load named queries from a YAML or JSON file with a top-level `queries` map.
+pub fn load_named_queries(path: &Path) -> Result<NamedQueries> {
+    #[derive(Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct QueriesFile {
+        #[serde(default)]
+        queries: NamedQueries,
+    }
+
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("failed to read named queries {:?}", path))?;
+    let is_json = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    let parsed: QueriesFile = if is_json {
+        serde_json::from_str(&contents)
+            .with_context(|| format!("failed to parse named queries {:?}", path))?
+    } else {
+        serde_yaml::from_str(&contents)
+            .with_context(|| format!("failed to parse named queries {:?}", path))?
+    };
+    Ok(parsed.queries)
+}
+
+/// First existing entry of [`WORKSPACE_QUERY_FILES`] under `workspace_root`.
+pub fn workspace_queries_file(workspace_root: &Path) -> Option<PathBuf> {
+    WORKSPACE_QUERY_FILES
+        .iter()
+        .map(|relative| workspace_root.join(relative))
+        .find(|path| path.is_file())
+}
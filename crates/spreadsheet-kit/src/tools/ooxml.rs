@@ -0,0 +1,155 @@
+//! Raw OOXML package access for the parts umya-spreadsheet does not model.
+//!
+//! Readers locate parts through the workbook relationships and pick
+//! attributes out of tags; writers rewrite a few parts of an existing
+//! package and copy every other entry as stored.
+
+use anyhow::Result;
+use regex::Regex;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufWriter, Cursor, Read, Seek, Write};
+use std::path::Path;
+use std::sync::OnceLock;
+
+pub(crate) fn read_zip_text<R: Read + Seek>(
+    archive: &mut zip::ZipArchive<R>,
+    name: &str,
+) -> Result<String> {
+    let mut file = archive.by_name(name)?;
+    let mut text = String::new();
+    file.read_to_string(&mut text)?;
+    Ok(text)
+}
+
+/// Map sheet names to their worksheet part paths via workbook.xml and its rels.
+pub(crate) fn resolve_sheet_parts<R: Read + Seek>(
+    archive: &mut zip::ZipArchive<R>,
+) -> Result<HashMap<String, String>> {
+    let workbook_xml = read_zip_text(archive, "xl/workbook.xml")?;
+    let rels_xml = read_zip_text(archive, "xl/_rels/workbook.xml.rels")?;
+
+    let mut targets = HashMap::new();
+    for tag in relationship_tag_regex().find_iter(&rels_xml) {
+        let attrs = parse_attributes(tag.as_str());
+        if let (Some(id), Some(target)) = (attrs.get("Id"), attrs.get("Target")) {
+            let part = match target.strip_prefix('/') {
+                Some(absolute) => absolute.to_string(),
+                None => format!("xl/{target}"),
+            };
+            targets.insert(id.clone(), part);
+        }
+    }
+
+    let mut parts = HashMap::new();
+    for tag in sheet_tag_regex().find_iter(&workbook_xml) {
+        let attrs = parse_attributes(tag.as_str());
+        if let (Some(name), Some(rid)) = (attrs.get("name"), attrs.get("r:id"))
+            && let Some(part) = targets.get(rid)
+        {
+            parts.insert(unescape_xml(name), part.clone());
+        }
+    }
+    Ok(parts)
+}
+
+/// Double-quoted attributes of a start tag, keyed by qualified name.
+pub(crate) fn parse_attributes(tag: &str) -> HashMap<String, String> {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX
+        .get_or_init(|| Regex::new(r#"([\w:]+)\s*=\s*"([^"]*)""#).expect("valid attribute regex"))
+        .captures_iter(tag)
+        .map(|caps| (caps[1].to_string(), caps[2].to_string()))
+        .collect()
+}
+
+/// `<Relationship>` tags of a `.rels` part.
+pub(crate) fn relationship_tag_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX
+        .get_or_init(|| Regex::new(r"<(?:\w+:)?Relationship\b[^>]*>").expect("valid rel tag regex"))
+}
+
+fn sheet_tag_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(r"<(?:\w+:)?sheet\b[^>]*>").expect("valid sheet tag regex"))
+}
+
+pub(crate) fn unescape_xml(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+pub(crate) fn escape_xml_attribute(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Rewrite the package in place: `Some` replaces an entry, `None` removes it.
+/// Entries not yet in the package are added at the end; the rest are copied
+/// without recompressing them.
+pub(crate) fn rewrite_package(path: &Path, edits: HashMap<String, Option<Vec<u8>>>) -> Result<()> {
+    let mut archive = zip::ZipArchive::new(fs::File::open(path)?)?;
+    // Removed on drop if writing fails before the rename.
+    let temp_path = tempfile::TempPath::from_path(path.with_extension("xlsx.rewrite.tmp"));
+    let writer = BufWriter::new(fs::File::create(&temp_path)?);
+    copy_with_edits(&mut archive, writer, edits)?.flush()?;
+    drop(archive);
+    temp_path.persist(path)?;
+    Ok(())
+}
+
+/// [`rewrite_package`] for a package held in memory.
+pub(crate) fn rewrite_package_bytes(
+    bytes: &[u8],
+    edits: HashMap<String, Option<Vec<u8>>>,
+) -> Result<Vec<u8>> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes))?;
+    Ok(copy_with_edits(&mut archive, Cursor::new(Vec::new()), edits)?.into_inner())
+}
+
+fn copy_with_edits<R: Read + Seek, W: Write + Seek>(
+    archive: &mut zip::ZipArchive<R>,
+    output: W,
+    mut edits: HashMap<String, Option<Vec<u8>>>,
+) -> Result<W> {
+    let mut writer = zip::ZipWriter::new(output);
+    for idx in 0..archive.len() {
+        let file = archive.by_index_raw(idx)?;
+        let name = file.name().to_string();
+        match edits.remove(&name) {
+            None => writer.raw_copy_file(file)?,
+            Some(None) => {}
+            Some(Some(data)) => {
+                let mut options = zip::write::FileOptions::default()
+                    .compression_method(file.compression())
+                    .last_modified_time(file.last_modified());
+                if let Some(mode) = file.unix_mode() {
+                    options = options.unix_permissions(mode);
+                }
+                writer.start_file(name, options)?;
+                writer.write_all(&data)?;
+            }
+        }
+    }
+
+    let mut added: Vec<(String, Vec<u8>)> = edits
+        .into_iter()
+        .filter_map(|(name, data)| data.map(|data| (name, data)))
+        .collect();
+    added.sort_by(|a, b| a.0.cmp(&b.0));
+    for (name, data) in added {
+        let options =
+            zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        writer.start_file(name, options)?;
+        writer.write_all(&data)?;
+    }
+    Ok(writer.finish()?)
+}
@@ -0,0 +1,423 @@
+//! Row/column outline (grouping) levels.
+//!
+//! umya-spreadsheet does not model `outlineLevel` on rows or columns, so levels
+//! are read from the raw worksheet XML and written back after umya saves.
+
+use super::ooxml::{parse_attributes, read_zip_text, resolve_sheet_parts, rewrite_package};
+use anyhow::{Result, anyhow};
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::{Read, Seek};
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Deepest outline level Excel supports.
+pub const MAX_OUTLINE_LEVEL: u8 = 7;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SheetOutline {
+    pub rows: BTreeMap<u32, u8>,
+    pub cols: BTreeMap<u32, u8>,
+}
+
+impl SheetOutline {
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty() && self.cols.is_empty()
+    }
+
+    pub fn row_level(&self, row: u32) -> u8 {
+        self.rows.get(&row).copied().unwrap_or(0)
+    }
+
+    pub fn col_level(&self, col: u32) -> u8 {
+        self.cols.get(&col).copied().unwrap_or(0)
+    }
+
+    /// Raise (`delta > 0`) or lower (`delta < 0`) the level of each row in `start..=end`.
+    pub fn adjust_rows(&mut self, start: u32, end: u32, delta: i8) {
+        adjust_levels(&mut self.rows, start, end, delta);
+    }
+
+    pub fn adjust_cols(&mut self, start: u32, end: u32, delta: i8) {
+        adjust_levels(&mut self.cols, start, end, delta);
+    }
+
+    /// Move levels down past `count` rows inserted at `at`.
+    pub fn insert_rows(&mut self, at: u32, count: u32) {
+        insert_indices(&mut self.rows, at, count);
+    }
+
+    /// Drop levels of `count` rows deleted from `start` and move the rest up.
+    pub fn delete_rows(&mut self, start: u32, count: u32) {
+        delete_indices(&mut self.rows, start, count);
+    }
+
+    pub fn insert_cols(&mut self, at: u32, count: u32) {
+        insert_indices(&mut self.cols, at, count);
+    }
+
+    pub fn delete_cols(&mut self, start: u32, count: u32) {
+        delete_indices(&mut self.cols, start, count);
+    }
+}
+
+fn insert_indices(levels: &mut BTreeMap<u32, u8>, at: u32, count: u32) {
+    *levels = std::mem::take(levels)
+        .into_iter()
+        .map(|(index, level)| {
+            let index = if index >= at { index + count } else { index };
+            (index, level)
+        })
+        .collect();
+}
+
+fn delete_indices(levels: &mut BTreeMap<u32, u8>, start: u32, count: u32) {
+    let end = start.saturating_add(count);
+    *levels = std::mem::take(levels)
+        .into_iter()
+        .filter_map(|(index, level)| match index {
+            index if index < start => Some((index, level)),
+            index if index < end => None,
+            index => Some((index - count, level)),
+        })
+        .collect();
+}
+
+fn adjust_levels(levels: &mut BTreeMap<u32, u8>, start: u32, end: u32, delta: i8) {
+    for index in start..=end {
+        let current = levels.get(&index).copied().unwrap_or(0) as i16;
+        let next = (current + delta as i16).clamp(0, MAX_OUTLINE_LEVEL as i16) as u8;
+        if next == 0 {
+            levels.remove(&index);
+        } else {
+            levels.insert(index, next);
+        }
+    }
+}
+
+/// Read outline levels for every sheet in the workbook at `path`, keyed by sheet name.
+/// Sheets without any grouped rows or columns are omitted.
+pub fn read_outlines(path: &Path) -> Result<HashMap<String, SheetOutline>> {
+    read_outlines_from_reader(fs::File::open(path)?)
+}
+
+pub fn read_outlines_from_reader<R: Read + Seek>(
+    reader: R,
+) -> Result<HashMap<String, SheetOutline>> {
+    let mut archive = zip::ZipArchive::new(reader)?;
+    let sheet_parts = resolve_sheet_parts(&mut archive)?;
+
+    let mut outlines = HashMap::new();
+    for (sheet_name, part) in sheet_parts {
+        let Ok(xml) = read_zip_text(&mut archive, &part) else {
+            continue;
+        };
+        let outline = parse_sheet_outline(&xml);
+        if !outline.is_empty() {
+            outlines.insert(sheet_name, outline);
+        }
+    }
+    Ok(outlines)
+}
+
+/// Rewrite outline levels for the given sheets in place. Sheets not present in
+/// `outlines` are left untouched; an empty `SheetOutline` clears all levels.
+pub fn write_outlines(path: &Path, outlines: &HashMap<String, SheetOutline>) -> Result<()> {
+    if outlines.is_empty() {
+        return Ok(());
+    }
+
+    let mut archive = zip::ZipArchive::new(fs::File::open(path)?)?;
+    let sheet_parts = resolve_sheet_parts(&mut archive)?;
+    let mut edits = HashMap::new();
+    for (sheet_name, outline) in outlines {
+        let part = sheet_parts
+            .get(sheet_name)
+            .ok_or_else(|| anyhow!("sheet '{}' not found", sheet_name))?;
+        let xml = read_zip_text(&mut archive, part)?;
+        edits.insert(
+            part.clone(),
+            Some(apply_sheet_outline(&xml, outline).into_bytes()),
+        );
+    }
+    drop(archive);
+    rewrite_package(path, edits)
+}
+
+/// Put levels captured before an umya save back onto the saved file. Unlike
+/// [`write_outlines`], sheets the save no longer has are skipped.
+pub(crate) fn restore_outlines(
+    path: &Path,
+    outlines: &HashMap<String, SheetOutline>,
+) -> Result<()> {
+    if outlines.is_empty() {
+        return Ok(());
+    }
+    let sheet_parts = resolve_sheet_parts(&mut zip::ZipArchive::new(fs::File::open(path)?)?)?;
+    let present: HashMap<String, SheetOutline> = outlines
+        .iter()
+        .filter(|(sheet_name, _)| sheet_parts.contains_key(*sheet_name))
+        .map(|(sheet_name, outline)| (sheet_name.clone(), outline.clone()))
+        .collect();
+    write_outlines(path, &present)
+}
+
+fn parse_sheet_outline(xml: &str) -> SheetOutline {
+    static ROW_TAG: OnceLock<Regex> = OnceLock::new();
+    static COL_TAG: OnceLock<Regex> = OnceLock::new();
+    let row_re = ROW_TAG.get_or_init(|| Regex::new(r"<row\b[^>]*>").expect("valid row regex"));
+    let col_re = COL_TAG.get_or_init(|| Regex::new(r"<col\b[^>]*>").expect("valid col regex"));
+
+    let mut outline = SheetOutline::default();
+    for tag in row_re.find_iter(xml) {
+        let attrs = parse_attributes(tag.as_str());
+        let level = attrs
+            .get("outlineLevel")
+            .and_then(|value| value.parse::<u8>().ok())
+            .unwrap_or(0);
+        if level > 0
+            && let Some(row) = attrs.get("r").and_then(|value| value.parse::<u32>().ok())
+        {
+            outline.rows.insert(row, level.min(MAX_OUTLINE_LEVEL));
+        }
+    }
+    for tag in col_re.find_iter(xml) {
+        let attrs = parse_attributes(tag.as_str());
+        let level = attrs
+            .get("outlineLevel")
+            .and_then(|value| value.parse::<u8>().ok())
+            .unwrap_or(0);
+        let min = attrs.get("min").and_then(|value| value.parse::<u32>().ok());
+        let max = attrs.get("max").and_then(|value| value.parse::<u32>().ok());
+        if level > 0
+            && let (Some(min), Some(max)) = (min, max)
+        {
+            for col in min..=max.max(min) {
+                outline.cols.insert(col, level.min(MAX_OUTLINE_LEVEL));
+            }
+        }
+    }
+    outline
+}
+
+fn apply_sheet_outline(xml: &str, outline: &SheetOutline) -> String {
+    let xml = rewrite_sheet_data_rows(xml, &outline.rows);
+    let xml = rewrite_cols(&xml, &outline.cols);
+    rewrite_sheet_format_levels(
+        &xml,
+        outline.rows.values().copied().max().unwrap_or(0),
+        outline.cols.values().copied().max().unwrap_or(0),
+    )
+}
+
+/// Attributes [`set_attribute`] rewrites.
+const REWRITTEN_ATTRIBUTES: [&str; 5] = [
+    "min",
+    "max",
+    "outlineLevel",
+    "outlineLevelRow",
+    "outlineLevelCol",
+];
+
+fn set_attribute(attrs: &str, name: &'static str, value: Option<String>) -> String {
+    static REGEXES: OnceLock<HashMap<&'static str, Regex>> = OnceLock::new();
+    let attr_re = &REGEXES.get_or_init(|| {
+        REWRITTEN_ATTRIBUTES
+            .into_iter()
+            .map(|name| {
+                let regex = Regex::new(&format!(r#"\s+{name}\s*=\s*"[^"]*""#))
+                    .expect("valid attribute regex");
+                (name, regex)
+            })
+            .collect()
+    })[name];
+    let mut stripped = attr_re.replace_all(attrs, "").to_string();
+    if let Some(value) = value {
+        stripped.push_str(&format!(r#" {name}="{value}""#));
+    }
+    stripped
+}
+
+fn rewrite_sheet_data_rows(xml: &str, levels: &BTreeMap<u32, u8>) -> String {
+    static SHEET_DATA: OnceLock<Regex> = OnceLock::new();
+    static ROW: OnceLock<Regex> = OnceLock::new();
+    let sheet_data_re = SHEET_DATA.get_or_init(|| {
+        Regex::new(r"(?s)<sheetData\s*/>|<sheetData>(.*?)</sheetData>")
+            .expect("valid sheetData regex")
+    });
+    let row_re = ROW.get_or_init(|| {
+        Regex::new(r"(?s)<row\b([^>]*?)/>|<row\b([^>]*)>(.*?)</row>").expect("valid row regex")
+    });
+
+    let Some(caps) = sheet_data_re.captures(xml) else {
+        return xml.to_string();
+    };
+    let whole = caps.get(0).expect("match");
+    let inner = caps.get(1).map(|m| m.as_str()).unwrap_or_default();
+
+    let mut rows: BTreeMap<u32, String> = BTreeMap::new();
+    let mut unnumbered: Vec<String> = Vec::new();
+    for row in row_re.captures_iter(inner) {
+        let (attrs, body) = match (row.get(1), row.get(2)) {
+            (Some(attrs), _) => (attrs.as_str(), None),
+            (None, Some(attrs)) => (attrs.as_str(), row.get(3).map(|m| m.as_str())),
+            _ => continue,
+        };
+        let index = parse_attributes(attrs)
+            .get("r")
+            .and_then(|value| value.parse::<u32>().ok());
+        let Some(index) = index else {
+            unnumbered.push(row[0].to_string());
+            continue;
+        };
+        let level = levels.get(&index).copied().unwrap_or(0);
+        let attrs = set_attribute(
+            attrs,
+            "outlineLevel",
+            (level > 0).then(|| level.to_string()),
+        );
+        let element = match body {
+            Some(body) => format!("<row{attrs}>{body}</row>"),
+            None => format!("<row{attrs}/>"),
+        };
+        rows.insert(index, element);
+    }
+    if !unnumbered.is_empty() {
+        // Rows without explicit indices depend on document order; leave them alone.
+        return xml.to_string();
+    }
+    for (&index, &level) in levels {
+        rows.entry(index)
+            .or_insert_with(|| format!(r#"<row r="{index}" outlineLevel="{level}"/>"#));
+    }
+
+    let rebuilt = if rows.is_empty() {
+        "<sheetData/>".to_string()
+    } else {
+        format!(
+            "<sheetData>{}</sheetData>",
+            rows.into_values().collect::<String>()
+        )
+    };
+    format!(
+        "{}{}{}",
+        &xml[..whole.start()],
+        rebuilt,
+        &xml[whole.end()..]
+    )
+}
+
+fn rewrite_cols(xml: &str, levels: &BTreeMap<u32, u8>) -> String {
+    static COLS: OnceLock<Regex> = OnceLock::new();
+    static COL: OnceLock<Regex> = OnceLock::new();
+    let cols_re =
+        COLS.get_or_init(|| Regex::new(r"(?s)<cols>(.*?)</cols>").expect("valid cols regex"));
+    let col_re = COL.get_or_init(|| Regex::new(r"<col\b([^>]*?)/?>").expect("valid col regex"));
+
+    // Expand every <col> range into per-column attribute strings (minus min/max).
+    let mut columns: BTreeMap<u32, String> = BTreeMap::new();
+    let existing = cols_re.captures(xml);
+    if let Some(caps) = &existing {
+        for col in col_re.captures_iter(&caps[1]) {
+            let attrs = parse_attributes(&col[1]);
+            let (Some(min), Some(max)) = (
+                attrs.get("min").and_then(|value| value.parse::<u32>().ok()),
+                attrs.get("max").and_then(|value| value.parse::<u32>().ok()),
+            ) else {
+                return xml.to_string();
+            };
+            let rest = set_attribute(&set_attribute(&col[1], "min", None), "max", None);
+            for index in min..=max.max(min) {
+                columns.insert(index, rest.clone());
+            }
+        }
+    }
+    for &index in levels.keys() {
+        columns.entry(index).or_default();
+    }
+
+    // Re-emit with outline levels, merging adjacent columns that share attributes.
+    let mut elements = String::new();
+    let mut run: Option<(u32, u32, String)> = None;
+    for (index, rest) in columns {
+        let level = levels.get(&index).copied().unwrap_or(0);
+        let attrs = set_attribute(
+            &rest,
+            "outlineLevel",
+            (level > 0).then(|| level.to_string()),
+        );
+        match &mut run {
+            Some((_, end, current)) if *end + 1 == index && *current == attrs => *end = index,
+            _ => {
+                if let Some((start, end, current)) = run.take() {
+                    elements.push_str(&format!(r#"<col min="{start}" max="{end}"{current}/>"#));
+                }
+                run = Some((index, index, attrs));
+            }
+        }
+    }
+    if let Some((start, end, current)) = run {
+        elements.push_str(&format!(r#"<col min="{start}" max="{end}"{current}/>"#));
+    }
+
+    let block = if elements.is_empty() {
+        String::new()
+    } else {
+        format!("<cols>{elements}</cols>")
+    };
+    match existing {
+        Some(caps) => {
+            let whole = caps.get(0).expect("match");
+            format!("{}{}{}", &xml[..whole.start()], block, &xml[whole.end()..])
+        }
+        None if block.is_empty() => xml.to_string(),
+        None => match xml.find("<sheetData") {
+            Some(at) => format!("{}{}{}", &xml[..at], block, &xml[at..]),
+            None => xml.to_string(),
+        },
+    }
+}
+
+fn rewrite_sheet_format_levels(xml: &str, row_level: u8, col_level: u8) -> String {
+    static SHEET_FORMAT: OnceLock<Regex> = OnceLock::new();
+    let format_re = SHEET_FORMAT.get_or_init(|| {
+        Regex::new(r"<sheetFormatPr\b([^>]*?)(/?)>").expect("valid sheetFormatPr regex")
+    });
+    if let Some(caps) = format_re.captures(xml) {
+        let whole = caps.get(0).expect("match");
+        let attrs = set_attribute(
+            &caps[1],
+            "outlineLevelRow",
+            (row_level > 0).then(|| row_level.to_string()),
+        );
+        let attrs = set_attribute(
+            &attrs,
+            "outlineLevelCol",
+            (col_level > 0).then(|| col_level.to_string()),
+        );
+        return format!(
+            "{}<sheetFormatPr{}{}>{}",
+            &xml[..whole.start()],
+            attrs,
+            &caps[2],
+            &xml[whole.end()..]
+        );
+    }
+    if row_level == 0 && col_level == 0 {
+        return xml.to_string();
+    }
+    let mut element = r#"<sheetFormatPr defaultRowHeight="15""#.to_string();
+    if row_level > 0 {
+        element.push_str(&format!(r#" outlineLevelRow="{row_level}""#));
+    }
+    if col_level > 0 {
+        element.push_str(&format!(r#" outlineLevelCol="{col_level}""#));
+    }
+    element.push_str("/>");
+    let anchor = xml.find("<cols").or_else(|| xml.find("<sheetData"));
+    match anchor {
+        Some(at) => format!("{}{}{}", &xml[..at], element, &xml[at..]),
+        None => xml.to_string(),
+    }
+}
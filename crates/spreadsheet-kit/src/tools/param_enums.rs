@@ -149,6 +149,43 @@ impl<'de> Deserialize<'de> for ReplaceMatchMode {
     }
 }
 
+/// How `rename_sheets` and `rename_names` match `find` against existing names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+#[derive(Default)]
+pub enum RenameMatchMode {
+    /// Replace every occurrence of `find` within the name.
+    #[default]
+    Contains,
+    /// Replace `find` at the start of the name.
+    Prefix,
+    /// Replace `find` at the end of the name.
+    Suffix,
+    /// `find` is a regular expression; `replace` may use `$1`-style captures.
+    Regex,
+}
+
+impl<'de> Deserialize<'de> for RenameMatchMode {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        match s.to_ascii_lowercase().as_str() {
+            "contains" => Ok(Self::Contains),
+            "prefix" => Ok(Self::Prefix),
+            "suffix" => Ok(Self::Suffix),
+            "regex" => Ok(Self::Regex),
+            other => {
+                let valid = ["contains", "prefix", "suffix", "regex"];
+                let message =
+                    enum_value_error("match_mode", other, &valid, suggest_literal(other, &valid));
+                Err(de::Error::custom(message))
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 #[derive(Default)]
@@ -279,3 +316,258 @@ impl PageOrientation {
         }
     }
 }
+
+/// How date-formatted number cells are reported by table and page reads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+#[derive(Default)]
+pub enum DateHandling {
+    /// The stored serial number, as a plain number.
+    Raw,
+    /// An ISO-8601 date, datetime, or time string.
+    #[default]
+    Iso,
+    /// The ISO-8601 string, plus the serial in a `date_serials` side table.
+    Both,
+}
+
+impl DateHandling {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Raw => "raw",
+            Self::Iso => "iso",
+            Self::Both => "both",
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for DateHandling {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        match normalize_literal(&s).as_str() {
+            "raw" | "serial" => Ok(Self::Raw),
+            "iso" | "iso8601" => Ok(Self::Iso),
+            "both" => Ok(Self::Both),
+            _ => {
+                let valid = ["raw", "iso", "both"];
+                let message =
+                    enum_value_error("date_handling", &s, &valid, suggest_literal(&s, &valid));
+                Err(de::Error::custom(message))
+            }
+        }
+    }
+}
+
+/// Which way a table runs: records in rows under a header row, or records
+/// in columns beside a header column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+#[derive(Default)]
+pub enum TableOrientation {
+    /// A header row on top, one record per row.
+    #[default]
+    Rows,
+    /// Headers down the first column, one record per column.
+    Transposed,
+}
+
+impl TableOrientation {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Rows => "rows",
+            Self::Transposed => "transposed",
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for TableOrientation {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        match normalize_literal(&s).as_str() {
+            "rows" | "row" | "records" => Ok(Self::Rows),
+            "transposed" | "transpose" | "columns" => Ok(Self::Transposed),
+            _ => {
+                let valid = ["rows", "transposed"];
+                let message =
+                    enum_value_error("orientation", &s, &valid, suggest_literal(&s, &valid));
+                Err(de::Error::custom(message))
+            }
+        }
+    }
+}
+
+/// Schema document `table_profile` can emit for the profiled table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SchemaFormat {
+    /// JSON Schema (draft 2020-12) for one row as an object keyed by header.
+    Jsonschema,
+    /// Arrow schema in the Arrow JSON format: one field per column.
+    Arrow,
+}
+
+impl SchemaFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Jsonschema => "jsonschema",
+            Self::Arrow => "arrow",
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for SchemaFormat {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        match normalize_literal(&s).as_str() {
+            "jsonschema" | "json" => Ok(Self::Jsonschema),
+            "arrow" | "arrowschema" => Ok(Self::Arrow),
+            _ => {
+                let valid = ["jsonschema", "arrow"];
+                let message =
+                    enum_value_error("emit_schema", &s, &valid, suggest_literal(&s, &valid));
+                Err(de::Error::custom(message))
+            }
+        }
+    }
+}
+
+/// Aggregate computed per group by `query_table`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AggregateOp {
+    /// Sum of the numeric cells.
+    Sum,
+    /// Mean of the numeric cells.
+    Avg,
+    /// Non-blank cells of the column, or rows without a column.
+    Count,
+    /// Smallest number, else earliest date.
+    Min,
+    /// Largest number, else latest date.
+    Max,
+}
+
+impl AggregateOp {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Sum => "sum",
+            Self::Avg => "avg",
+            Self::Count => "count",
+            Self::Min => "min",
+            Self::Max => "max",
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for AggregateOp {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        match normalize_literal(&s).as_str() {
+            "sum" | "total" => Ok(Self::Sum),
+            "avg" | "mean" | "average" => Ok(Self::Avg),
+            "count" => Ok(Self::Count),
+            "min" | "minimum" => Ok(Self::Min),
+            "max" | "maximum" => Ok(Self::Max),
+            _ => {
+                let valid = ["sum", "avg", "count", "min", "max"];
+                let message = enum_value_error("op", &s, &valid, suggest_literal(&s, &valid));
+                Err(de::Error::custom(message))
+            }
+        }
+    }
+}
+
+/// How exact ties are resolved when rounding to a number of decimal places.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+#[derive(Default)]
+pub enum RoundingMode {
+    /// Ties round away from zero, as Excel's `ROUND` does.
+    #[default]
+    HalfUp,
+    /// Ties round to the even digit ("banker's rounding").
+    HalfEven,
+}
+
+impl RoundingMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::HalfUp => "half_up",
+            Self::HalfEven => "half_even",
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for RoundingMode {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        match normalize_literal(&s).as_str() {
+            "halfup" => Ok(Self::HalfUp),
+            "halfeven" | "bankers" | "banker" => Ok(Self::HalfEven),
+            _ => {
+                let valid = ["half_up", "half_even"];
+                let message = enum_value_error("rounding", &s, &valid, suggest_literal(&s, &valid));
+                Err(de::Error::custom(message))
+            }
+        }
+    }
+}
+
+/// How `columns_by_header` selectors are matched against header text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+#[derive(Default)]
+pub enum HeaderMatchMode {
+    /// Case-insensitive equality, ignoring surrounding whitespace.
+    #[default]
+    Exact,
+    /// Every header containing the selector, case-insensitively.
+    Contains,
+    /// The most similar header, if it scores at least the threshold.
+    Fuzzy,
+}
+
+impl HeaderMatchMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Exact => "exact",
+            Self::Contains => "contains",
+            Self::Fuzzy => "fuzzy",
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for HeaderMatchMode {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        match normalize_literal(&s).as_str() {
+            "exact" => Ok(Self::Exact),
+            "contains" | "substring" => Ok(Self::Contains),
+            "fuzzy" | "similar" => Ok(Self::Fuzzy),
+            _ => {
+                let valid = ["exact", "contains", "fuzzy"];
+                let message =
+                    enum_value_error("header_match", &s, &valid, suggest_literal(&s, &valid));
+                Err(de::Error::custom(message))
+            }
+        }
+    }
+}
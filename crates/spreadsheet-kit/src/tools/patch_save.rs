@@ -0,0 +1,641 @@
+//! Differential saves: keep every part of the original package that an edit
+//! did not touch.
+//!
+//! umya-spreadsheet re-serializes the whole workbook on save, so a one-cell
+//! edit rewrites every sheet, the style sheet and the shared strings, and a
+//! byte-level diff of the result is noise. For edits confined to cell data,
+//! [`save_cell_edits`] still saves through umya, then builds the output from
+//! the original package: the `<sheetData>` of each edited sheet is spliced
+//! in from umya's output and every other part is copied as stored, without
+//! recompressing it. That is more work than a plain save, so callers opt in
+//! when a clean package diff matters.
+//!
+//! Style and shared string indices in the spliced cells are mapped back to
+//! the original tables. Strings the original table lacks are appended to it;
+//! a style it lacks, or anything else that cannot be mapped, falls back to
+//! umya's full output.
+
+use super::ooxml::{parse_attributes, read_zip_text, resolve_sheet_parts, rewrite_package};
+use super::write_cells::{CALC_CHAIN_PART, drop_calc_chain_override, drop_calc_chain_relationship};
+use crate::write::{PreservedPackage, save_workbook_preserving};
+use anyhow::{Result, anyhow, bail};
+use quick_xml::events::Event;
+use quick_xml::reader::Reader;
+use regex::{Captures, Regex};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::io::{Read, Seek};
+use std::path::Path;
+use std::sync::OnceLock;
+
+const STYLES_PART: &str = "xl/styles.xml";
+const SHARED_STRINGS_PART: &str = "xl/sharedStrings.xml";
+
+/// How [`save_cell_edits`] wrote the workbook.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SaveOutcome {
+    /// Only these parts were rewritten; the rest are byte-identical.
+    Patched { parts: Vec<String> },
+    /// The whole package was re-serialized.
+    Full { reason: String },
+}
+
+/// Save `book` over `path`, which must still hold the workbook `book` was
+/// read from, rewriting only the cell data of `sheets` when possible.
+pub fn save_cell_edits(
+    book: &umya_spreadsheet::Spreadsheet,
+    path: &Path,
+    sheets: &BTreeSet<String>,
+) -> Result<SaveOutcome> {
+    // Removed on drop unless it replaces `path` as the fallback.
+    let full_path = tempfile::TempPath::from_path(path.with_extension("xlsx.full.tmp"));
+    save_workbook_preserving(book, &full_path, &PreservedPackage::capture(path))?;
+
+    let patched = match patch_cell_data(path, &full_path, sheets) {
+        Ok(Some(plan)) => write_patched(path, plan),
+        Ok(None) => Err(anyhow!("no edited sheets")),
+        Err(error) => Err(error),
+    };
+    match patched {
+        Ok(parts) => Ok(SaveOutcome::Patched { parts }),
+        Err(reason) => {
+            tracing::debug!(path = %path.display(), %reason, "differential save fell back to a full save");
+            full_path.persist(path)?;
+            Ok(SaveOutcome::Full {
+                reason: format!("{reason:#}"),
+            })
+        }
+    }
+}
+
+/// Replacement contents for the parts a differential save rewrites.
+struct PatchPlan {
+    replacements: HashMap<String, Vec<u8>>,
+    dropped: BTreeSet<String>,
+}
+
+fn patch_cell_data(
+    original: &Path,
+    full: &Path,
+    sheets: &BTreeSet<String>,
+) -> Result<Option<PatchPlan>> {
+    if sheets.is_empty() {
+        return Ok(None);
+    }
+    let mut before = zip::ZipArchive::new(fs::File::open(original)?)?;
+    let mut after = zip::ZipArchive::new(fs::File::open(full)?)?;
+    let before_parts = resolve_sheet_parts(&mut before)?;
+    let after_parts = resolve_sheet_parts(&mut after)?;
+    if before_parts.len() != after_parts.len() {
+        bail!("the edit added or removed sheets");
+    }
+
+    let styles = StyleMap::new(
+        &read_zip_text(&mut before, STYLES_PART)?,
+        &read_zip_text(&mut after, STYLES_PART)?,
+    )?;
+    let mut strings = SharedStringMap::new(
+        optional_zip_text(&mut before, SHARED_STRINGS_PART)?,
+        optional_zip_text(&mut after, SHARED_STRINGS_PART)?,
+    );
+
+    let mut plan = PatchPlan {
+        replacements: HashMap::new(),
+        dropped: BTreeSet::new(),
+    };
+    for sheet in sheets {
+        let (Some(before_part), Some(after_part)) =
+            (before_parts.get(sheet), after_parts.get(sheet))
+        else {
+            bail!("sheet '{sheet}' is missing from one of the packages");
+        };
+        let before_xml = read_zip_text(&mut before, before_part)?;
+        let after_xml = read_zip_text(&mut after, after_part)?;
+        let sheet_data = element_regex("sheetData")
+            .find(&after_xml)
+            .ok_or_else(|| anyhow!("saved sheet '{sheet}' has no sheetData"))?;
+        let cells = remap_sheet_data(sheet_data.as_str(), &styles, &mut strings)?;
+        strings.release(count_shared_string_refs(&before_xml));
+
+        let Some(original_data) = element_regex("sheetData").find(&before_xml) else {
+            bail!("sheet '{sheet}' has no sheetData");
+        };
+        if original_data.as_str().starts_with("<sheetData") {
+            let mut patched = String::with_capacity(before_xml.len() + cells.len());
+            patched.push_str(&before_xml[..original_data.start()]);
+            patched.push_str(&cells);
+            patched.push_str(&before_xml[original_data.end()..]);
+            if let Some(dimension) = element_regex("dimension").find(&after_xml) {
+                patched = element_regex("dimension")
+                    .replace(&patched, regex::NoExpand(dimension.as_str()))
+                    .into_owned();
+            }
+            plan.replacements
+                .insert(before_part.clone(), patched.into_bytes());
+        } else {
+            // Prefixed elements would need the spliced cells re-prefixed.
+            bail!("sheet '{sheet}' uses a namespace prefix on sheetData");
+        }
+    }
+
+    if let Some(table) = strings.patched_table()? {
+        plan.replacements
+            .insert(SHARED_STRINGS_PART.to_string(), table.into_bytes());
+    }
+    // Edited cells may have held formulas, and a calc chain naming a cell
+    // that no longer holds one makes Excel repair the file. The chain is
+    // rebuilt on the next calculation.
+    if before.by_name(CALC_CHAIN_PART).is_ok() {
+        plan.dropped.insert(CALC_CHAIN_PART.to_string());
+        plan.replacements.insert(
+            "[Content_Types].xml".to_string(),
+            drop_calc_chain_override(&read_zip_text(&mut before, "[Content_Types].xml")?)
+                .into_bytes(),
+        );
+        plan.replacements.insert(
+            "xl/_rels/workbook.xml.rels".to_string(),
+            drop_calc_chain_relationship(&read_zip_text(
+                &mut before,
+                "xl/_rels/workbook.xml.rels",
+            )?)
+            .into_bytes(),
+        );
+    }
+    Ok(Some(plan))
+}
+
+/// Rewrite `path` with `plan` applied, copying other entries as stored.
+fn write_patched(path: &Path, plan: PatchPlan) -> Result<Vec<String>> {
+    let mut parts: Vec<String> = plan
+        .replacements
+        .keys()
+        .chain(plan.dropped.iter())
+        .cloned()
+        .collect();
+    parts.sort();
+    let edits = plan
+        .replacements
+        .into_iter()
+        .map(|(name, data)| (name, Some(data)))
+        .chain(plan.dropped.into_iter().map(|name| (name, None)))
+        .collect();
+    rewrite_package(path, edits)?;
+    Ok(parts)
+}
+
+fn optional_zip_text<R: Read + Seek>(
+    archive: &mut zip::ZipArchive<R>,
+    name: &str,
+) -> Result<Option<String>> {
+    if archive.by_name(name).is_err() {
+        return Ok(None);
+    }
+    read_zip_text(archive, name).map(Some)
+}
+
+/// Map the saved sheet data's style and shared string indices to the
+/// original package's tables.
+fn remap_sheet_data(
+    sheet_data: &str,
+    styles: &StyleMap,
+    strings: &mut SharedStringMap,
+) -> Result<String> {
+    let mut failure = None;
+    let rows = row_regex().replace_all(sheet_data, |caps: &Captures| {
+        match remap_style_attribute(&caps[0], styles) {
+            Ok(tag) => tag,
+            Err(error) => {
+                failure.get_or_insert(error);
+                caps[0].to_string()
+            }
+        }
+    });
+    let cells = cell_regex().replace_all(&rows, |caps: &Captures| {
+        let attrs = match remap_style_attribute(&caps[1], styles) {
+            Ok(attrs) => attrs,
+            Err(error) => {
+                failure.get_or_insert(error);
+                return caps[0].to_string();
+            }
+        };
+        let Some(body) = caps.get(2).map(|body| body.as_str()) else {
+            return format!("<c{attrs}/>");
+        };
+        if !attrs.contains(r#" t="s""#) {
+            return format!("<c{attrs}>{body}</c>");
+        }
+        let body = value_regex().replace(body, |value: &Captures| {
+            match value[1]
+                .parse::<usize>()
+                .map_err(|_| anyhow!("invalid shared string index '{}'", &value[1]))
+                .and_then(|index| strings.map(index))
+            {
+                Ok(index) => format!("<v>{index}</v>"),
+                Err(error) => {
+                    failure.get_or_insert(error);
+                    value[0].to_string()
+                }
+            }
+        });
+        format!("<c{attrs}>{body}</c>")
+    });
+    match failure {
+        Some(error) => Err(error),
+        None => Ok(cells.into_owned()),
+    }
+}
+
+fn remap_style_attribute(attrs: &str, styles: &StyleMap) -> Result<String> {
+    let mut failure = None;
+    let remapped = style_attribute_regex().replace(attrs, |caps: &Captures| {
+        match caps[2]
+            .parse::<usize>()
+            .map_err(|_| anyhow!("invalid style index '{}'", &caps[2]))
+            .and_then(|index| styles.map(index))
+        {
+            Ok(index) => format!(r#"{}s="{index}""#, &caps[1]),
+            Err(error) => {
+                failure.get_or_insert(error);
+                caps[0].to_string()
+            }
+        }
+    });
+    match failure {
+        Some(error) => Err(error),
+        None => Ok(remapped.into_owned()),
+    }
+}
+
+fn count_shared_string_refs(sheet_xml: &str) -> u64 {
+    cell_regex()
+        .captures_iter(sheet_xml)
+        .filter(|caps| caps[1].contains(r#" t="s""#))
+        .count() as u64
+}
+
+/// Saved `cellXfs` indices mapped to equivalent entries in the original.
+struct StyleMap {
+    mapping: Vec<Option<usize>>,
+}
+
+impl StyleMap {
+    fn new(before: &str, after: &str) -> Result<Self> {
+        let before = xf_signatures(before)?;
+        let after = xf_signatures(after)?;
+        let mut index: HashMap<&str, usize> = HashMap::new();
+        for (position, signature) in before.iter().enumerate() {
+            index.entry(signature.as_str()).or_insert(position);
+        }
+        Ok(Self {
+            mapping: after
+                .iter()
+                .map(|signature| index.get(signature.as_str()).copied())
+                .collect(),
+        })
+    }
+
+    fn map(&self, index: usize) -> Result<usize> {
+        self.mapping
+            .get(index)
+            .copied()
+            .flatten()
+            .ok_or_else(|| anyhow!("the edit introduced a cell style the original lacks"))
+    }
+}
+
+/// Each `cellXfs` entry with its font, fill, border and number format
+/// inlined, so equal styles compare equal across two style sheets.
+fn xf_signatures(styles_xml: &str) -> Result<Vec<String>> {
+    let number_formats: HashMap<String, String> = section_items(styles_xml, "numFmts", "numFmt")
+        .iter()
+        .filter_map(|item| {
+            let attrs = parse_attributes(item);
+            Some((
+                attrs.get("numFmtId")?.clone(),
+                attrs.get("formatCode")?.clone(),
+            ))
+        })
+        .collect();
+    let fonts = canonical_items(styles_xml, "fonts", "font")?;
+    let fills = canonical_items(styles_xml, "fills", "fill")?;
+    let borders = canonical_items(styles_xml, "borders", "border")?;
+
+    let part = |items: &[String], id: Option<&String>| -> String {
+        let id = id.and_then(|id| id.parse::<usize>().ok()).unwrap_or(0);
+        items.get(id).cloned().unwrap_or_default()
+    };
+    section_items(styles_xml, "cellXfs", "xf")
+        .iter()
+        .map(|xf| {
+            let attrs = parse_attributes(xf);
+            let format_id = attrs.get("numFmtId").cloned().unwrap_or_else(|| "0".into());
+            let format = number_formats
+                .get(&format_id)
+                .cloned()
+                .unwrap_or_else(|| format!("builtin:{format_id}"));
+            let children = canonical_xml(xf, true)?;
+            Ok::<_, anyhow::Error>(format!(
+                "{format}|{}|{}|{}|{}|{children}",
+                part(&fonts, attrs.get("fontId")),
+                part(&fills, attrs.get("fillId")),
+                part(&borders, attrs.get("borderId")),
+                attrs.get("quotePrefix").map(String::as_str).unwrap_or("0"),
+            ))
+        })
+        .collect()
+}
+
+fn canonical_items(xml: &str, section: &str, item: &str) -> Result<Vec<String>> {
+    section_items(xml, section, item)
+        .iter()
+        .map(|item| canonical_xml(item, true))
+        .collect()
+}
+
+/// Raw `<item>` elements inside the first `<section>` of `xml`.
+fn section_items(xml: &str, section: &str, item: &str) -> Vec<String> {
+    let Some(section) = element_regex(section).find(xml) else {
+        return Vec::new();
+    };
+    element_regex(item)
+        .find_iter(section.as_str())
+        .map(|found| found.as_str().to_string())
+        .collect()
+}
+
+/// An XML fragment's children in a form that ignores attribute order,
+/// indentation and empty-element syntax, and with `unordered` child order
+/// too. The root's own attributes are left out; callers compare those
+/// separately.
+fn canonical_xml(fragment: &str, unordered: bool) -> Result<String> {
+    #[derive(Default)]
+    struct Node {
+        name: String,
+        attrs: Vec<(String, String)>,
+        text: String,
+        children: Vec<String>,
+    }
+
+    impl Node {
+        fn render(mut self, unordered: bool) -> String {
+            self.attrs.sort();
+            if unordered {
+                self.children.sort();
+            }
+            // Text beside child elements is indentation.
+            if !self.children.is_empty() && self.text.trim().is_empty() {
+                self.text.clear();
+            }
+            let attrs: String = self
+                .attrs
+                .iter()
+                .map(|(key, value)| format!(" {key}={value:?}"))
+                .collect();
+            format!(
+                "<{}{attrs}>{}{}</>",
+                self.name,
+                self.text,
+                self.children.concat()
+            )
+        }
+    }
+
+    fn open(e: &quick_xml::events::BytesStart<'_>) -> Result<Node> {
+        let mut attrs = Vec::new();
+        for attr in e.attributes() {
+            let attr = attr?;
+            attrs.push((
+                String::from_utf8_lossy(attr.key.local_name().as_ref()).into_owned(),
+                attr.unescape_value()?.into_owned(),
+            ));
+        }
+        Ok(Node {
+            name: String::from_utf8_lossy(e.local_name().as_ref()).into_owned(),
+            attrs,
+            ..Node::default()
+        })
+    }
+
+    let mut reader = Reader::from_str(fragment);
+    let mut stack: Vec<Node> = Vec::new();
+    let mut root_children = Vec::new();
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) => stack.push(open(&e)?),
+            Event::Empty(e) => {
+                let node = open(&e)?.render(unordered);
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(node),
+                    None => return Ok(String::new()),
+                }
+            }
+            Event::Text(text) => {
+                if let Some(node) = stack.last_mut() {
+                    node.text.push_str(&text.unescape()?);
+                }
+            }
+            Event::End(_) => {
+                let node = stack.pop().ok_or_else(|| anyhow!("unbalanced style XML"))?;
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(node.render(unordered)),
+                    None => root_children = node.children,
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    if unordered {
+        root_children.sort();
+    }
+    Ok(root_children.concat())
+}
+
+/// Saved shared string indices mapped into the original table, appending
+/// strings it does not hold yet.
+struct SharedStringMap {
+    before: Option<String>,
+    after_items: Vec<String>,
+    index: HashMap<String, usize>,
+    before_len: usize,
+    appended: Vec<String>,
+    mapped: HashMap<usize, usize>,
+    /// References added by spliced cells minus those removed.
+    ref_delta: i64,
+}
+
+impl SharedStringMap {
+    fn new(before: Option<String>, after: Option<String>) -> Self {
+        let items = |xml: Option<&str>| -> Vec<String> {
+            xml.map(|xml| {
+                element_regex("si")
+                    .find_iter(xml)
+                    .map(|item| item.as_str().to_string())
+                    .collect()
+            })
+            .unwrap_or_default()
+        };
+        let before_items = items(before.as_deref());
+        let after_items = items(after.as_deref());
+        let mut index = HashMap::new();
+        for (position, item) in before_items.iter().enumerate() {
+            if let Ok(key) = canonical_xml(item, false) {
+                index.entry(key).or_insert(position);
+            }
+        }
+        Self {
+            before,
+            after_items,
+            index,
+            before_len: before_items.len(),
+            appended: Vec::new(),
+            mapped: HashMap::new(),
+            ref_delta: 0,
+        }
+    }
+
+    fn map(&mut self, index: usize) -> Result<usize> {
+        self.ref_delta += 1;
+        if let Some(mapped) = self.mapped.get(&index) {
+            return Ok(*mapped);
+        }
+        if self.before.is_none() {
+            bail!("the original package has no shared string table");
+        }
+        let item = self
+            .after_items
+            .get(index)
+            .ok_or_else(|| anyhow!("shared string index {index} is out of range"))?;
+        let key = canonical_xml(item, false)?;
+        let mapped = match self.index.get(&key) {
+            Some(position) => *position,
+            None => {
+                let position = self.before_len + self.appended.len();
+                self.appended.push(item.clone());
+                self.index.insert(key, position);
+                position
+            }
+        };
+        self.mapped.insert(index, mapped);
+        Ok(mapped)
+    }
+
+    /// Account for the references held by a replaced `<sheetData>`.
+    fn release(&mut self, refs: u64) {
+        self.ref_delta -= refs as i64;
+    }
+
+    /// The original table with appended strings and adjusted counts, or
+    /// `None` when it needs no change.
+    fn patched_table(&self) -> Result<Option<String>> {
+        let Some(before) = self.before.as_deref() else {
+            return Ok(None);
+        };
+        if self.appended.is_empty() && self.ref_delta == 0 {
+            return Ok(None);
+        }
+        let close = before
+            .rfind("</sst>")
+            .ok_or_else(|| anyhow!("shared string table is not closed"))?;
+        let mut table = String::with_capacity(before.len());
+        table.push_str(&before[..close]);
+        for item in &self.appended {
+            table.push_str(item);
+        }
+        table.push_str(&before[close..]);
+
+        let unique = (self.before_len + self.appended.len()).to_string();
+        let ref_delta = self.ref_delta;
+        static SST_TAG: OnceLock<Regex> = OnceLock::new();
+        let table = SST_TAG
+            .get_or_init(|| Regex::new(r#"<sst\b[^>]*>"#).expect("valid sst regex"))
+            .replace(&table, |caps: &Captures| {
+                let tag = set_count_attribute(&caps[0], "uniqueCount", |_| unique.clone());
+                set_count_attribute(&tag, "count", |count| {
+                    (count.parse::<i64>().unwrap_or(0) + ref_delta)
+                        .max(0)
+                        .to_string()
+                })
+            })
+            .into_owned();
+        Ok(Some(table))
+    }
+}
+
+fn set_count_attribute(tag: &str, name: &str, value: impl Fn(&str) -> String) -> String {
+    static REGEXES: OnceLock<HashMap<&'static str, Regex>> = OnceLock::new();
+    let pattern = REGEXES
+        .get_or_init(|| {
+            ["count", "uniqueCount"]
+                .into_iter()
+                .map(|name| {
+                    let pattern = format!(r#"\b{name}="([^"]*)""#);
+                    (name, Regex::new(&pattern).expect("valid count regex"))
+                })
+                .collect()
+        })
+        .get(name)
+        .expect("count attribute regex is precompiled");
+    pattern
+        .replace(tag, |caps: &Captures| {
+            format!(r#"{name}="{}""#, value(&caps[1]))
+        })
+        .into_owned()
+}
+
+/// Elements looked up by [`element_regex`].
+const MATCHED_ELEMENTS: [&str; 13] = [
+    "sheetData",
+    "dimension",
+    "si",
+    "numFmts",
+    "numFmt",
+    "fonts",
+    "font",
+    "fills",
+    "fill",
+    "borders",
+    "border",
+    "cellXfs",
+    "xf",
+];
+
+/// A whole element by local name, whether empty or with content.
+fn element_regex(name: &str) -> &'static Regex {
+    static REGEXES: OnceLock<HashMap<&'static str, Regex>> = OnceLock::new();
+    REGEXES
+        .get_or_init(|| {
+            MATCHED_ELEMENTS
+                .into_iter()
+                .map(|name| {
+                    let pattern =
+                        format!(r"(?s)<(?:\w+:)?{name}\b[^>]*?(?:/>|>.*?</(?:\w+:)?{name}>)");
+                    (name, Regex::new(&pattern).expect("valid element regex"))
+                })
+                .collect()
+        })
+        .get(name)
+        .expect("element regex is precompiled")
+}
+
+fn row_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(r"<row\b[^>]*>").expect("valid row regex"))
+}
+
+/// Captures the attributes of a `<c>` and, when it is not empty, its body.
+fn cell_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX
+        .get_or_init(|| Regex::new(r"(?s)<c\b([^>]*?)(?:/>|>(.*?)</c>)").expect("valid cell regex"))
+}
+
+fn value_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(r"<v>\s*(\d+)\s*</v>").expect("valid value regex"))
+}
+
+fn style_attribute_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(r#"(\s)s="(\d+)""#).expect("valid style regex"))
+}
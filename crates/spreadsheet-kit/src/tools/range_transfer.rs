@@ -0,0 +1,356 @@
+//! Block copies and moves for the `copy_range` and `move_range` transform
+//! ops, following Excel's copy and cut semantics.
+//!
+//! A copy shifts the relative references of copied formulas by the distance
+//! the block travelled and leaves absolute ones alone. A move leaves the
+//! moved formulas pointing at the cells they referenced before, and every
+//! reference in the workbook to a moved cell, cell formulas and defined
+//! names alike, follows it to its new location. Merged cells, comments,
+//! hyperlinks and conditional formats stay where they are.
+
+use crate::formula::pattern::{
+    MovedBlock, RelativeMode, parse_base_formula, relocate_formula_ast, shift_formula_ast,
+};
+use crate::metadata::annotation_bounds;
+use crate::utils::cell_address;
+use anyhow::{Result, anyhow, bail};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use umya_spreadsheet::{CellValue, Spreadsheet, Style, Worksheet};
+
+const MAX_COL: u32 = 16_384;
+const MAX_ROW: u32 = 1_048_576;
+
+/// What a `copy_range` or `move_range` op carries to the destination, after
+/// Excel's Paste Special choices.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PasteMode {
+    /// Values, formulas and formats
+    #[default]
+    All,
+    /// Values and formulas; the destination keeps its formats
+    Formulas,
+    /// Values, with formulas pasted as their cached results; the
+    /// destination keeps its formats
+    Values,
+    /// Formats only; the destination keeps its contents
+    Formats,
+}
+
+impl PasteMode {
+    fn contents(self) -> bool {
+        self != Self::Formats
+    }
+
+    fn formats(self) -> bool {
+        matches!(self, Self::All | Self::Formats)
+    }
+}
+
+/// What one copy or move changed.
+#[derive(Debug, Default)]
+pub(crate) struct TransferEffects {
+    pub(crate) cells_written: u64,
+    pub(crate) formulas_adjusted: u64,
+    pub(crate) names_adjusted: u64,
+    /// Sheets whose cells changed, including sheets whose formulas were
+    /// repointed at moved cells
+    pub(crate) sheets: BTreeSet<String>,
+    pub(crate) bounds: Vec<String>,
+    pub(crate) warnings: Vec<String>,
+}
+
+/// One source cell as it will land at the destination.
+struct Captured {
+    offset: (u32, u32),
+    value: CellValue,
+    style: Style,
+    /// Rewritten formula text (without `=`), when it differs from the source
+    formula: Option<String>,
+}
+
+pub(crate) fn validate_transfer(kind: &str, src_range: &str, dest_anchor: &str) -> Result<()> {
+    annotation_bounds(src_range).map_err(|err| anyhow!("{kind} src_range: {err}"))?;
+    annotation_bounds(dest_anchor).map_err(|err| anyhow!("{kind} dest_anchor: {err}"))?;
+    Ok(())
+}
+
+/// Copy or move `src_range` of `sheet_name` so its top-left cell lands on
+/// `dest_anchor` of `dest_sheet_name`. Source and destination may overlap.
+pub(crate) fn transfer_range(
+    book: &mut Spreadsheet,
+    sheet_name: &str,
+    src_range: &str,
+    dest_sheet_name: &str,
+    dest_anchor: &str,
+    paste: PasteMode,
+    is_move: bool,
+) -> Result<TransferEffects> {
+    let kind = if is_move { "move_range" } else { "copy_range" };
+    let ((min_col, min_row), (max_col, max_row)) = annotation_bounds(src_range)?;
+    let ((dest_col, dest_row), _) = annotation_bounds(dest_anchor)?;
+    let dest_max_col = dest_col + (max_col - min_col);
+    let dest_max_row = dest_row + (max_row - min_row);
+    if dest_max_col > MAX_COL || dest_max_row > MAX_ROW {
+        bail!(
+            "{kind}: {} at {} runs past the last column or row of the sheet",
+            src_range,
+            dest_anchor
+        );
+    }
+    let src_sheet = book
+        .get_sheet_by_name(sheet_name)
+        .ok_or_else(|| anyhow!("sheet '{}' not found", sheet_name))?;
+    let src_sheet_name = src_sheet.get_name().to_string();
+    let dest_sheet_name = book
+        .get_sheet_by_name(dest_sheet_name)
+        .ok_or_else(|| anyhow!("sheet '{}' not found", dest_sheet_name))?
+        .get_name()
+        .to_string();
+
+    let block = MovedBlock {
+        src_sheet: &src_sheet_name,
+        dest_sheet: &dest_sheet_name,
+        min: (min_col, min_row),
+        max: (max_col, max_row),
+        delta_col: dest_col as i32 - min_col as i32,
+        delta_row: dest_row as i32 - min_row as i32,
+    };
+    let src_bounds = format!(
+        "{}:{}",
+        cell_address(min_col, min_row),
+        cell_address(max_col, max_row)
+    );
+    let dest_bounds = format!(
+        "{}:{}",
+        cell_address(dest_col, dest_row),
+        cell_address(dest_max_col, dest_max_row)
+    );
+    let in_dest = |sheet: &str, col: u32, row: u32| {
+        sheet == dest_sheet_name
+            && (dest_col..=dest_max_col).contains(&col)
+            && (dest_row..=dest_max_row).contains(&row)
+    };
+
+    let mut effects = TransferEffects::default();
+    if src_sheet.get_merge_cells().iter().any(|merge| {
+        annotation_bounds(&merge.get_range()).is_ok_and(|((c1, r1), (c2, r2))| {
+            c1 <= max_col && c2 >= min_col && r1 <= max_row && r2 >= min_row
+        })
+    }) {
+        effects.warnings.push(format!(
+            "WARN_TRANSFER_MERGED_CELLS: merged cells overlapping {}!{} were not {}.",
+            src_sheet_name,
+            src_bounds,
+            if is_move { "moved" } else { "copied" }
+        ));
+    }
+
+    let mut captured = Vec::new();
+    for row in min_row..=max_row {
+        for col in min_col..=max_col {
+            let Some(cell) = src_sheet.get_cell((col, row)) else {
+                continue;
+            };
+            let mut value = cell.get_cell_value().clone();
+            let mut formula = None;
+            if cell.is_formula() {
+                if paste == PasteMode::Values {
+                    value.remove_formula();
+                } else if paste.contents() {
+                    let source = cell.get_formula().to_string();
+                    let dest = cell_address(
+                        (col as i32 + block.delta_col) as u32,
+                        (row as i32 + block.delta_row) as u32,
+                    );
+                    let rewritten = parse_base_formula(&source).and_then(|ast| {
+                        if is_move {
+                            Ok(relocate_formula_ast(
+                                &ast,
+                                &src_sheet_name,
+                                &dest_sheet_name,
+                                &block,
+                            ))
+                        } else {
+                            shift_formula_ast(
+                                &ast,
+                                block.delta_col,
+                                block.delta_row,
+                                RelativeMode::Excel,
+                            )
+                            .map(Some)
+                        }
+                    });
+                    match rewritten {
+                        Ok(Some(text)) => {
+                            formula = Some(text.strip_prefix('=').unwrap_or(&text).to_string());
+                        }
+                        Ok(None) => {}
+                        Err(err) => effects.warnings.push(format!(
+                            "WARN_TRANSFER_FORMULA: could not adjust formula '{}' from {}!{} for {}!{}: {}; copied verbatim.",
+                            source,
+                            src_sheet_name,
+                            cell_address(col, row),
+                            dest_sheet_name,
+                            dest,
+                            err
+                        )),
+                    }
+                }
+            }
+            captured.push(Captured {
+                offset: (col - min_col, row - min_row),
+                value,
+                style: cell.get_style().clone(),
+                formula,
+            });
+        }
+    }
+
+    if is_move {
+        let sheet = book
+            .get_sheet_by_name_mut(&src_sheet_name)
+            .expect("source sheet exists");
+        for row in min_row..=max_row {
+            for col in min_col..=max_col {
+                if !in_dest(&src_sheet_name, col, row) {
+                    clear_cell(sheet, (col, row), paste);
+                }
+            }
+        }
+        effects.sheets.insert(src_sheet_name.clone());
+        effects.bounds.push(src_bounds);
+    }
+
+    let dest_sheet = book
+        .get_sheet_by_name_mut(&dest_sheet_name)
+        .expect("destination sheet exists");
+    let mut written = BTreeSet::new();
+    for item in captured {
+        let coordinate = (dest_col + item.offset.0, dest_row + item.offset.1);
+        written.insert(coordinate);
+        let cell = dest_sheet.get_cell_mut(coordinate);
+        if paste.contents() {
+            *cell.get_cell_value_mut() = item.value;
+            if let Some(formula) = item.formula {
+                cell.set_formula(formula);
+                cell.set_formula_result_default("");
+                effects.formulas_adjusted += 1;
+            }
+        }
+        if paste.formats() {
+            cell.set_style(item.style);
+        }
+        effects.cells_written += 1;
+    }
+    // Blank source cells blank their destination, as a paste does.
+    for row in dest_row..=dest_max_row {
+        for col in dest_col..=dest_max_col {
+            if !written.contains(&(col, row)) {
+                clear_cell(dest_sheet, (col, row), paste);
+            }
+        }
+    }
+    effects.sheets.insert(dest_sheet_name.clone());
+    effects.bounds.push(dest_bounds);
+
+    if is_move && paste.contents() {
+        repoint_references(book, &block, &in_dest, &mut effects);
+    }
+    Ok(effects)
+}
+
+/// Remove what `paste` carries from an existing cell.
+fn clear_cell(sheet: &mut Worksheet, coordinate: (u32, u32), paste: PasteMode) {
+    if sheet.get_cell(coordinate).is_none() {
+        return;
+    }
+    match paste {
+        PasteMode::All => {
+            sheet.remove_cell(coordinate);
+        }
+        PasteMode::Formulas | PasteMode::Values => {
+            let cell = sheet.get_cell_mut(coordinate);
+            if cell.is_formula() {
+                cell.set_formula(String::new());
+            }
+            cell.set_value(String::new());
+        }
+        PasteMode::Formats => {
+            sheet.get_cell_mut(coordinate).set_style(Style::default());
+        }
+    }
+}
+
+/// Point formulas and defined names outside the destination that refer to
+/// moved cells at their new location. Cached results stay valid, since the
+/// cells they read only moved.
+fn repoint_references(
+    book: &mut Spreadsheet,
+    block: &MovedBlock,
+    in_dest: &dyn Fn(&str, u32, u32) -> bool,
+    effects: &mut TransferEffects,
+) {
+    let mut unparsed = 0u64;
+    for sheet in book.get_sheet_collection_mut() {
+        let sheet_name = sheet.get_name().to_string();
+        for cell in sheet.get_cell_collection_mut() {
+            if !cell.is_formula() || cell.get_formula().is_empty() {
+                continue;
+            }
+            let coordinate = cell.get_coordinate();
+            if in_dest(
+                &sheet_name,
+                *coordinate.get_col_num(),
+                *coordinate.get_row_num(),
+            ) {
+                continue;
+            }
+            let Ok(ast) = parse_base_formula(cell.get_formula()) else {
+                unparsed += 1;
+                continue;
+            };
+            if let Some(text) = relocate_formula_ast(&ast, &sheet_name, &sheet_name, block) {
+                cell.set_formula(text.strip_prefix('=').unwrap_or(&text).to_string());
+                effects.formulas_adjusted += 1;
+                effects.sheets.insert(sheet_name.clone());
+            }
+        }
+        for defined in sheet.get_defined_names_mut() {
+            repoint_defined_name(defined, &sheet_name, block, effects, &mut unparsed);
+        }
+    }
+    for defined in book.get_defined_names_mut() {
+        repoint_defined_name(defined, "", block, effects, &mut unparsed);
+    }
+
+    if unparsed > 0 {
+        effects.warnings.push(format!(
+            "WARN_TRANSFER_UNPARSED_FORMULAS: {} formulas or names could not be parsed; any references they hold to {}!{}:{} were not updated.",
+            unparsed,
+            block.src_sheet,
+            cell_address(block.min.0, block.min.1),
+            cell_address(block.max.0, block.max.1)
+        ));
+    }
+}
+
+fn repoint_defined_name(
+    defined: &mut umya_spreadsheet::DefinedName,
+    scope_sheet: &str,
+    block: &MovedBlock,
+    effects: &mut TransferEffects,
+    unparsed: &mut u64,
+) {
+    let refers_to = defined.get_address();
+    let Ok(ast) = parse_base_formula(&refers_to) else {
+        *unparsed += 1;
+        return;
+    };
+    if let Some(text) = relocate_formula_ast(&ast, scope_sheet, scope_sheet, block) {
+        defined.set_address(text.strip_prefix('=').unwrap_or(&text).to_string());
+        effects.names_adjusted += 1;
+    }
+}
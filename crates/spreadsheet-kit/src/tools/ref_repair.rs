@@ -0,0 +1,368 @@
+//! Repair `#REF!` references left behind by structural edits.
+//!
+//! Deleting a row, column, or sheet that a formula points at replaces the
+//! reference with `#REF!`, and the original text is gone from the workbook. A
+//! copy from before the edit (a `--snapshot-dir` snapshot or any earlier file)
+//! still has it: each broken formula is matched to a baseline formula with the
+//! same shape, and every `#REF!` token is proposed back as the reference that
+//! stood in the same position.
+
+use anyhow::Result;
+use formualizer_parse::tokenizer::{Token, TokenType, Tokenizer};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// How a broken formula was matched to its baseline counterpart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RefRepairConfidence {
+    /// Baseline formula elsewhere on the sheet whose references differ from
+    /// the broken one; the only candidate with the same shape.
+    Low,
+    /// Baseline formula elsewhere on the sheet that is identical apart from
+    /// the `#REF!` tokens, e.g. after the cell itself moved.
+    Medium,
+    /// Baseline formula at the same address, identical apart from the
+    /// `#REF!` tokens.
+    High,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RefRepairProposal {
+    pub sheet_name: String,
+    pub address: String,
+    pub formula: String,
+    /// Formula with every `#REF!` replaced; absent when no repair was found.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repaired: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<RefRepairConfidence>,
+    /// Baseline cell the restored references were taken from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub baseline_address: Option<String>,
+    /// Why no repair was proposed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    pub applied: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RefRepairResult {
+    pub broken_formulas: usize,
+    pub proposed: usize,
+    pub applied: usize,
+    pub proposals: Vec<RefRepairProposal>,
+}
+
+/// Find formulas containing `#REF!` in the workbook at `path` (optionally on
+/// one sheet), propose repairs from the workbook at `baseline`, and write the
+/// repairs at or above `min_confidence` back to `path` when `apply` is set.
+pub fn repair_refs_in_file(
+    path: &Path,
+    baseline: &Path,
+    sheet_filter: Option<&str>,
+    min_confidence: RefRepairConfidence,
+    apply: bool,
+) -> Result<RefRepairResult> {
+    let mut book = umya_spreadsheet::reader::xlsx::read(path)?;
+    let baseline_book = umya_spreadsheet::reader::xlsx::read(baseline)?;
+
+    let sheet_names: BTreeSet<String> = book
+        .get_sheet_collection()
+        .iter()
+        .map(|sheet| sheet.get_name().to_lowercase())
+        .collect();
+
+    let mut proposals = Vec::new();
+    for sheet in book.get_sheet_collection() {
+        if sheet_filter.is_some_and(|name| name != sheet.get_name()) {
+            continue;
+        }
+        let mut broken: Vec<(String, String)> = sheet
+            .get_cell_collection()
+            .into_iter()
+            .filter(|cell| cell.is_formula() && contains_ref_error(cell.get_formula()))
+            .map(|cell| {
+                (
+                    cell.get_coordinate().get_coordinate().to_string(),
+                    cell.get_formula().to_string(),
+                )
+            })
+            .collect();
+        broken.sort_by_key(|(address, _)| {
+            parse_cell(address)
+                .map(|(col, row)| (row, col))
+                .unwrap_or_default()
+        });
+
+        let baseline_formulas: Vec<(String, String)> = baseline_book
+            .get_sheet_by_name(sheet.get_name())
+            .map(|baseline_sheet| {
+                baseline_sheet
+                    .get_cell_collection()
+                    .into_iter()
+                    .filter(|cell| cell.is_formula() && !contains_ref_error(cell.get_formula()))
+                    .map(|cell| {
+                        (
+                            cell.get_coordinate().get_coordinate().to_string(),
+                            cell.get_formula().to_string(),
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        for (address, formula) in broken {
+            let mut proposal = RefRepairProposal {
+                sheet_name: sheet.get_name().to_string(),
+                address,
+                formula,
+                repaired: None,
+                confidence: None,
+                baseline_address: None,
+                reason: None,
+                applied: false,
+            };
+            if baseline_formulas.is_empty() {
+                proposal.reason = Some(format!(
+                    "baseline has no formulas on sheet '{}'",
+                    proposal.sheet_name
+                ));
+            } else {
+                match propose_repair(&proposal.address, &proposal.formula, &baseline_formulas) {
+                    Ok((repaired, confidence, baseline_address)) => {
+                        match missing_sheet_reference(&repaired, &sheet_names) {
+                            Some(missing) => {
+                                proposal.reason = Some(format!(
+                                    "restored reference points at sheet '{missing}', which is not in the workbook"
+                                ));
+                            }
+                            None => {
+                                proposal.repaired = Some(repaired);
+                                proposal.confidence = Some(confidence);
+                                proposal.baseline_address = Some(baseline_address);
+                            }
+                        }
+                    }
+                    Err(reason) => proposal.reason = Some(reason),
+                }
+            }
+            proposals.push(proposal);
+        }
+    }
+
+    let mut applied = 0usize;
+    for proposal in &mut proposals {
+        let (Some(repaired), Some(confidence)) = (&proposal.repaired, proposal.confidence) else {
+            continue;
+        };
+        if !apply || confidence < min_confidence {
+            continue;
+        }
+        let cell = book
+            .get_sheet_by_name_mut(&proposal.sheet_name)
+            .expect("sheet exists")
+            .get_cell_mut(proposal.address.as_str());
+        cell.set_formula(repaired.clone());
+        cell.set_formula_result_default("");
+        proposal.applied = true;
+        applied += 1;
+    }
+    if applied > 0 {
+        crate::write::save_workbook(&book, path)?;
+    }
+
+    Ok(RefRepairResult {
+        broken_formulas: proposals.len(),
+        proposed: proposals.iter().filter(|p| p.repaired.is_some()).count(),
+        applied,
+        proposals,
+    })
+}
+
+fn contains_ref_error(text: &str) -> bool {
+    text.to_ascii_uppercase().contains("#REF!")
+}
+
+/// Repaired formula, confidence, and baseline address for the broken
+/// `formula` at `address`, or why none could be chosen.
+fn propose_repair(
+    address: &str,
+    formula: &str,
+    baseline_formulas: &[(String, String)],
+) -> std::result::Result<(String, RefRepairConfidence, String), String> {
+    let broken = tokenize(formula).map_err(|e| format!("formula does not tokenize: {e}"))?;
+
+    if let Some((baseline_address, baseline_formula)) =
+        baseline_formulas.iter().find(|(a, _)| a == address)
+        && let Some((repaired, true)) = align(formula, &broken, baseline_formula)
+    {
+        return Ok((
+            repaired,
+            RefRepairConfidence::High,
+            baseline_address.clone(),
+        ));
+    }
+
+    let mut exact = Vec::new();
+    let mut shaped = Vec::new();
+    for (baseline_address, baseline_formula) in baseline_formulas {
+        match align(formula, &broken, baseline_formula) {
+            Some((repaired, true)) => exact.push((repaired, baseline_address)),
+            Some((repaired, false)) => shaped.push((repaired, baseline_address)),
+            None => {}
+        }
+    }
+    for (candidates, confidence) in [
+        (exact, RefRepairConfidence::Medium),
+        (shaped, RefRepairConfidence::Low),
+    ] {
+        let distinct: BTreeSet<&str> = candidates.iter().map(|(r, _)| r.as_str()).collect();
+        match distinct.len() {
+            0 => continue,
+            1 => {
+                let (repaired, baseline_address) = &candidates[0];
+                return Ok((repaired.clone(), confidence, baseline_address.to_string()));
+            }
+            n => {
+                return Err(format!(
+                    "{n} different baseline formulas match; repair by hand"
+                ));
+            }
+        }
+    }
+    Err("no baseline formula on this sheet has the same shape".to_string())
+}
+
+/// Replace each `#REF!` token in `formula` with the token at the same
+/// position in `baseline_formula`. Returns the repaired formula and whether
+/// every other token matched exactly, or `None` when the two formulas do not
+/// have the same shape (token kinds and non-reference text).
+fn align(formula: &str, broken: &[Token], baseline_formula: &str) -> Option<(String, bool)> {
+    let baseline = tokenize(baseline_formula).ok()?;
+    if broken.len() != baseline.len() {
+        return None;
+    }
+
+    let with_equals = with_leading_equals(formula);
+    let mut out = String::with_capacity(with_equals.len());
+    let mut cursor = 0usize;
+    let mut exact = true;
+    for (token, base) in broken.iter().zip(&baseline) {
+        if contains_ref_error(&token.value) {
+            if token.token_type != TokenType::Operand || base.token_type != TokenType::Operand {
+                return None;
+            }
+            out.push_str(&with_equals[cursor..token.start]);
+            out.push_str(&base.value);
+            cursor = token.end;
+            continue;
+        }
+        if token.token_type != base.token_type || token.subtype != base.subtype {
+            return None;
+        }
+        if !token.value.eq_ignore_ascii_case(&base.value) {
+            // References shift with structural edits; anything else must match.
+            if token.subtype != formualizer_parse::TokenSubType::Range {
+                return None;
+            }
+            exact = false;
+        }
+    }
+    out.push_str(&with_equals[cursor..]);
+    let repaired = out.strip_prefix('=').unwrap_or(&out).to_string();
+    Some((repaired, exact))
+}
+
+/// Non-whitespace tokens of `formula`.
+fn tokenize(formula: &str) -> std::result::Result<Vec<Token>, String> {
+    let tokens = Tokenizer::new(&with_leading_equals(formula))
+        .map_err(|e| e.message)?
+        .items;
+    Ok(tokens
+        .into_iter()
+        .filter(|token| token.token_type != TokenType::Whitespace)
+        .collect())
+}
+
+fn with_leading_equals(formula: &str) -> String {
+    if formula.starts_with('=') {
+        formula.to_string()
+    } else {
+        format!("={formula}")
+    }
+}
+
+/// First sheet named in a reference of `formula` that is not in
+/// `sheet_names` (lowercased).
+fn missing_sheet_reference(formula: &str, sheet_names: &BTreeSet<String>) -> Option<String> {
+    let tokens = tokenize(formula).ok()?;
+    tokens
+        .iter()
+        .filter(|token| token.subtype == formualizer_parse::TokenSubType::Range)
+        .filter_map(|token| token.value.rsplit_once('!').map(|(sheet, _)| sheet))
+        .map(|sheet| {
+            sheet
+                .strip_prefix('\'')
+                .and_then(|s| s.strip_suffix('\''))
+                .map(|s| s.replace("''", "'"))
+                .unwrap_or_else(|| sheet.to_string())
+        })
+        // External references (`[1]Sheet!A1`) are not checked.
+        .find(|sheet| !sheet.starts_with('[') && !sheet_names.contains(&sheet.to_lowercase()))
+}
+
+fn parse_cell(address: &str) -> Option<(u32, u32)> {
+    use umya_spreadsheet::helper::coordinate::index_from_coordinate;
+    match index_from_coordinate(address) {
+        (Some(col), Some(row), _, _) => Some((col, row)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn baseline(entries: &[(&str, &str)]) -> Vec<(String, String)> {
+        entries
+            .iter()
+            .map(|(a, f)| (a.to_string(), f.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn restores_ref_tokens_from_same_address() {
+        let (repaired, confidence, from) = propose_repair(
+            "C2",
+            "SUM(#REF!)+B2",
+            &baseline(&[("C2", "SUM(Data!A1:A9)+B2")]),
+        )
+        .expect("repair");
+        assert_eq!(repaired, "SUM(Data!A1:A9)+B2");
+        assert_eq!(confidence, RefRepairConfidence::High);
+        assert_eq!(from, "C2");
+    }
+
+    #[test]
+    fn falls_back_to_unique_shape_match_and_rejects_ambiguity() {
+        let (repaired, confidence, from) = propose_repair(
+            "C4",
+            "B4*#REF!",
+            &baseline(&[("C5", "B5*Rates!$B$1"), ("D5", "SUM(B5:C5)")]),
+        )
+        .expect("repair");
+        assert_eq!(repaired, "B4*Rates!$B$1");
+        assert_eq!(confidence, RefRepairConfidence::Low);
+        assert_eq!(from, "C5");
+
+        let err = propose_repair(
+            "C4",
+            "B4*#REF!",
+            &baseline(&[("C5", "B5*Rates!$B$1"), ("C6", "B6*Rates!$B$2")]),
+        )
+        .expect_err("ambiguous");
+        assert!(err.contains("2 different"), "{err}");
+    }
+}
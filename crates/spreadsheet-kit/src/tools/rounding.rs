@@ -0,0 +1,163 @@
+//! Decimal rounding for numbers reported by aggregate and query tools.
+//!
+//! Values are rounded on their shortest decimal representation rather than by
+//! scaling the binary float, so `2.675` rounds to `2.68` under half-up the way
+//! Excel's `ROUND` does, instead of `2.67` from `267.49999999999997`.
+
+use super::param_enums::RoundingMode;
+use crate::model::{CellValue, ColumnSummary, ColumnTypeSummary, TableRow};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Rounding applied to a response, echoed back so callers can tell rounded
+/// figures from raw ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct RoundingPolicy {
+    pub decimals: u32,
+    pub mode: RoundingMode,
+}
+
+impl RoundingPolicy {
+    /// Policy requested by a tool call; rounding is off unless `decimals` is
+    /// given.
+    pub fn from_params(decimals: Option<u32>, mode: Option<RoundingMode>) -> Option<Self> {
+        decimals.map(|decimals| Self {
+            decimals,
+            mode: mode.unwrap_or_default(),
+        })
+    }
+
+    pub fn round(&self, value: f64) -> f64 {
+        round_decimal(value, self.decimals, self.mode)
+    }
+
+    pub fn round_cell(&self, value: &mut CellValue) {
+        if let CellValue::Number(number) = value {
+            *number = self.round(*number);
+        }
+    }
+
+    pub fn round_rows(&self, rows: &mut [TableRow]) {
+        for value in rows.iter_mut().flat_map(|row| row.values_mut()).flatten() {
+            self.round_cell(value);
+        }
+    }
+
+    pub fn round_column_summaries(&self, columns: &mut [ColumnSummary]) {
+        for column in columns {
+            for stat in [&mut column.min, &mut column.max, &mut column.mean] {
+                *stat = stat.map(|value| self.round(value));
+            }
+            for sample in &mut column.samples {
+                self.round_cell(sample);
+            }
+        }
+    }
+
+    pub fn round_column_types(&self, columns: &mut [ColumnTypeSummary]) {
+        for column in columns {
+            for stat in [&mut column.min, &mut column.max, &mut column.mean] {
+                *stat = stat.map(|value| self.round(value));
+            }
+            let Some(stats) = column.stats.as_mut() else {
+                continue;
+            };
+            for stat in [
+                &mut stats.min,
+                &mut stats.max,
+                &mut stats.mean,
+                &mut stats.stddev,
+            ] {
+                *stat = stat.map(|value| self.round(value));
+            }
+            if let Some(outliers) = stats.outliers.as_mut() {
+                outliers.lower_fence = self.round(outliers.lower_fence);
+                outliers.upper_fence = self.round(outliers.upper_fence);
+                for cell in &mut outliers.cells {
+                    cell.value = self.round(cell.value);
+                }
+            }
+        }
+    }
+}
+
+/// Round `value` to `decimals` places, resolving exact ties with `mode`.
+/// Non-finite values are returned unchanged.
+pub fn round_decimal(value: f64, decimals: u32, mode: RoundingMode) -> f64 {
+    if !value.is_finite() || value == 0.0 {
+        return value;
+    }
+
+    // `{:e}` gives the shortest digits that round-trip, e.g. "2.675e0".
+    let repr = format!("{:e}", value.abs());
+    let (mantissa, exponent) = repr.split_once('e').expect("exponent notation");
+    let exponent: i64 = exponent.parse().expect("integer exponent");
+    let digits: Vec<u8> = mantissa
+        .bytes()
+        .filter(u8::is_ascii_digit)
+        .map(|digit| digit - b'0')
+        .collect();
+
+    // Digits at and after `keep` fall below the requested precision.
+    let keep = exponent + 1 + i64::from(decimals);
+    if keep >= digits.len() as i64 {
+        return value;
+    }
+    if keep < 0 {
+        return 0.0;
+    }
+    let (head, tail) = digits.split_at(keep as usize);
+    let round_up = match tail[0] {
+        0..=4 => false,
+        6..=9 => true,
+        _ if tail[1..].iter().any(|digit| *digit != 0) => true,
+        _ => match mode {
+            RoundingMode::HalfUp => true,
+            RoundingMode::HalfEven => head.last().is_some_and(|digit| digit % 2 == 1),
+        },
+    };
+
+    let mut units = head
+        .iter()
+        .fold(0u128, |acc, digit| acc * 10 + u128::from(*digit));
+    if round_up {
+        units += 1;
+    }
+    if units == 0 {
+        return 0.0;
+    }
+    let rounded: f64 = format!("{units}e-{decimals}")
+        .parse()
+        .expect("decimal literal");
+    rounded.copysign(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn half_up_rounds_ties_away_from_zero() {
+        assert_eq!(round_decimal(2.675, 2, RoundingMode::HalfUp), 2.68);
+        assert_eq!(round_decimal(-2.5, 0, RoundingMode::HalfUp), -3.0);
+        assert_eq!(round_decimal(1.005, 2, RoundingMode::HalfUp), 1.01);
+        assert_eq!(round_decimal(1234.5678, 1, RoundingMode::HalfUp), 1234.6);
+    }
+
+    #[test]
+    fn half_even_rounds_ties_to_even_digit() {
+        assert_eq!(round_decimal(2.5, 0, RoundingMode::HalfEven), 2.0);
+        assert_eq!(round_decimal(3.5, 0, RoundingMode::HalfEven), 4.0);
+        assert_eq!(round_decimal(2.665, 2, RoundingMode::HalfEven), 2.66);
+        assert_eq!(round_decimal(2.6651, 2, RoundingMode::HalfEven), 2.67);
+        assert_eq!(round_decimal(0.5, 0, RoundingMode::HalfEven), 0.0);
+    }
+
+    #[test]
+    fn values_within_precision_are_unchanged() {
+        assert_eq!(round_decimal(0.1, 2, RoundingMode::HalfUp), 0.1);
+        assert_eq!(round_decimal(42.0, 0, RoundingMode::HalfUp), 42.0);
+        assert_eq!(round_decimal(0.0004, 2, RoundingMode::HalfUp), 0.0);
+        assert!(round_decimal(f64::NAN, 2, RoundingMode::HalfUp).is_nan());
+    }
+}
@@ -7,14 +7,15 @@ use crate::model::{FillDescriptor, WorkbookId};
 use crate::state::AppState;
 use crate::styles::descriptor_from_style;
 use crate::tools::param_enums::BatchMode;
-use crate::utils::make_short_random_id;
+use crate::tools::sheet_layout::{format_sheet_prefix, parse_a1_range};
+use crate::utils::{column_number_to_name, make_short_random_id};
+use crate::write::save_workbook;
 use crate::{rules::conditional_format, styles::normalize_color_hex};
 use anyhow::{Result, anyhow, bail};
 use chrono::Utc;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, BTreeSet};
-use std::fs;
 use std::path::Path;
 use std::sync::Arc;
 use umya_spreadsheet::{
@@ -122,6 +123,7 @@ pub struct ConditionalFormatStyleSpec {
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct DataValidationSpec {
     pub kind: DataValidationKind,
+    #[serde(default)]
     pub formula1: String,
     #[serde(default)]
     pub formula2: Option<String>,
@@ -131,6 +133,20 @@ pub struct DataValidationSpec {
     pub prompt: Option<ValidationMessage>,
     #[serde(default)]
     pub error: Option<ValidationMessage>,
+    /// Dropdown source for `list` validations; resolved into `formula1` at apply time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub list_source: Option<ListSourceSpec>,
+}
+
+/// Reference-backed list source for dropdown validations.
+///
+/// `named_range` emits the defined name verbatim; `range` emits an absolute,
+/// sheet-qualified reference (e.g. `'Lookup Lists'!$A$1:$A$10`).
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ListSourceSpec {
+    NamedRange { name: String },
+    Range { sheet_name: String, range: String },
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
@@ -205,9 +221,8 @@ pub async fn rules_batch(
 
     if mode.is_preview() {
         let change_id = make_short_random_id("chg", 12);
-        let snapshot_path = crate::tools::fork::stage_snapshot_path(&params.fork_id, &change_id);
-        fs::create_dir_all(snapshot_path.parent().unwrap())?;
-        fs::copy(&work_path, &snapshot_path)?;
+        let snapshot = registry.stage_snapshot(&params.fork_id, &change_id, &work_path)?;
+        let snapshot_path = snapshot.to_path_buf();
 
         let snapshot_for_apply = snapshot_path.clone();
         let ops_for_apply = params.ops.clone();
@@ -234,7 +249,7 @@ pub async fn rules_batch(
             label: params.label.clone(),
             ops: vec![staged_op],
             summary: summary.clone(),
-            fork_path_snapshot: Some(snapshot_path),
+            fork_path_snapshot: Some(snapshot.keep()?),
         };
         registry.add_staged_change(&params.fork_id, staged)?;
 
@@ -319,6 +334,11 @@ pub(crate) fn apply_rules_ops_to_file(
 ) -> Result<RulesApplyResult> {
     let mut book = umya_spreadsheet::reader::xlsx::read(path)?;
 
+    // Resolve reference-backed list sources before any mutation so a dangling
+    // reference rejects the whole batch.
+    let resolved_ops = resolve_list_sources(&book, ops)?;
+    let ops = resolved_ops.as_slice();
+
     let mut affected_sheets: BTreeSet<String> = BTreeSet::new();
     let mut affected_bounds: Vec<String> = Vec::new();
     let mut counts: BTreeMap<String, u64> = BTreeMap::new();
@@ -476,7 +496,7 @@ pub(crate) fn apply_rules_ops_to_file(
         }
     }
 
-    umya_spreadsheet::writer::xlsx::write(&book, path)?;
+    save_workbook(&book, path)?;
 
     counts.insert("validations_set".to_string(), validations_set);
     counts.insert("validations_replaced".to_string(), validations_replaced);
@@ -525,6 +545,87 @@ pub(crate) fn apply_rules_ops_to_file(
     })
 }
 
+fn resolve_list_sources(
+    book: &umya_spreadsheet::Spreadsheet,
+    ops: &[RulesOp],
+) -> Result<Vec<RulesOp>> {
+    let mut resolved = Vec::with_capacity(ops.len());
+    for op in ops {
+        let mut op = op.clone();
+        if let RulesOp::SetDataValidation {
+            sheet_name,
+            validation,
+            ..
+        } = &mut op
+            && let Some(source) = validation.list_source.as_ref()
+        {
+            if !matches!(validation.kind, DataValidationKind::List) {
+                bail!("list_source requires validation kind 'list'");
+            }
+            if !validation.formula1.trim().is_empty() {
+                bail!("list_source cannot be combined with formula1");
+            }
+            validation.formula1 = resolve_list_source(book, sheet_name, source)?;
+        }
+        resolved.push(op);
+    }
+    Ok(resolved)
+}
+
+fn resolve_list_source(
+    book: &umya_spreadsheet::Spreadsheet,
+    target_sheet: &str,
+    source: &ListSourceSpec,
+) -> Result<String> {
+    match source {
+        ListSourceSpec::NamedRange { name } => {
+            let name = name.trim();
+            if name.is_empty() {
+                bail!("list_source.name is required");
+            }
+            let target_index = book
+                .get_sheet_collection()
+                .iter()
+                .position(|sheet| sheet.get_name() == target_sheet)
+                .map(|idx| idx as u32);
+            // Names scoped to another sheet are not visible from the target sheet.
+            let visible = |defined: &umya_spreadsheet::DefinedName| {
+                defined.get_name().eq_ignore_ascii_case(name)
+                    && (!defined.has_local_sheet_id()
+                        || Some(*defined.get_local_sheet_id()) == target_index)
+            };
+            let found = book.get_defined_names().iter().any(visible)
+                || book
+                    .get_sheet_collection()
+                    .iter()
+                    .any(|sheet| sheet.get_defined_names().iter().any(visible));
+            if !found {
+                bail!(
+                    "list_source named range '{}' not found (workbook scope or sheet '{}')",
+                    name,
+                    target_sheet
+                );
+            }
+            Ok(name.to_string())
+        }
+        ListSourceSpec::Range { sheet_name, range } => {
+            if book.get_sheet_by_name(sheet_name).is_none() {
+                bail!("list_source sheet '{}' not found", sheet_name);
+            }
+            let ((start_col, start_row), (end_col, end_row)) =
+                parse_a1_range(range).map_err(|err| anyhow!("list_source.range: {err}"))?;
+            let start = format!("${}${}", column_number_to_name(start_col), start_row);
+            let end = format!("${}${}", column_number_to_name(end_col), end_row);
+            let bounds = if start == end {
+                start
+            } else {
+                format!("{start}:{end}")
+            };
+            Ok(format!("{}{}", format_sheet_prefix(sheet_name), bounds))
+        }
+    }
+}
+
 fn normalize_sqref(input: &str) -> Result<String> {
     let trimmed = input.trim();
     if trimmed.is_empty() {
@@ -0,0 +1,65 @@
+//! Right-to-left sheet direction.
+//!
+//! umya-spreadsheet does not model `sheetView rightToLeft`, so the flag is read
+//! from the raw worksheet XML. Sheets authored for Arabic, Hebrew, or Persian
+//! locales set it so column A sits at the right edge.
+
+use super::ooxml::{parse_attributes, read_zip_text, resolve_sheet_parts};
+use anyhow::Result;
+use regex::Regex;
+use std::collections::HashSet;
+use std::fs;
+use std::io::{Read, Seek};
+use std::path::Path;
+
+/// Names of the sheets in the workbook at `path` whose first view is
+/// right-to-left.
+pub fn read_right_to_left_sheets(path: &Path) -> Result<HashSet<String>> {
+    read_right_to_left_sheets_from_reader(fs::File::open(path)?)
+}
+
+pub fn read_right_to_left_sheets_from_reader<R: Read + Seek>(reader: R) -> Result<HashSet<String>> {
+    let mut archive = zip::ZipArchive::new(reader)?;
+    let sheet_parts = resolve_sheet_parts(&mut archive)?;
+
+    let mut sheets = HashSet::new();
+    for (sheet_name, part) in sheet_parts {
+        let Ok(xml) = read_zip_text(&mut archive, &part) else {
+            continue;
+        };
+        if parse_right_to_left(&xml) {
+            sheets.insert(sheet_name);
+        }
+    }
+    Ok(sheets)
+}
+
+fn parse_right_to_left(xml: &str) -> bool {
+    let view_re = Regex::new(r"<(?:\w+:)?sheetView\b[^>]*>").expect("valid sheetView regex");
+    view_re.find(xml).is_some_and(|tag| {
+        parse_attributes(tag.as_str())
+            .get("rightToLeft")
+            .is_some_and(|value| matches!(value.trim(), "1" | "true"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_right_to_left_from_first_sheet_view() {
+        assert!(parse_right_to_left(
+            r#"<worksheet><sheetViews><sheetView rightToLeft="1" workbookViewId="0"/></sheetViews></worksheet>"#
+        ));
+        assert!(parse_right_to_left(
+            r#"<x:worksheet><x:sheetViews><x:sheetView workbookViewId="0" rightToLeft="true"></x:sheetView></x:sheetViews></x:worksheet>"#
+        ));
+        assert!(!parse_right_to_left(
+            r#"<worksheet><sheetViews><sheetView rightToLeft="0" workbookViewId="0"/></sheetViews></worksheet>"#
+        ));
+        assert!(!parse_right_to_left(
+            r#"<worksheet><sheetViews><sheetView workbookViewId="0"/></sheetViews></worksheet>"#
+        ));
+    }
+}
@@ -1,15 +1,16 @@
 use crate::fork::{ChangeSummary, StagedChange, StagedOp};
 use crate::model::WorkbookId;
 use crate::state::AppState;
+use crate::tools::outline;
 use crate::tools::param_enums::{BatchMode, PageOrientation};
-use crate::utils::make_short_random_id;
+use crate::utils::{column_number_to_name, make_short_random_id};
+use crate::write::{PreservedPackage, save_workbook_preserving};
 use anyhow::{Result, anyhow, bail};
 use chrono::Utc;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, BTreeSet};
-use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::Path;
 use std::sync::Arc;
 use umya_spreadsheet::{
     Break, Coordinate, Pane, PaneStateValues, PaneValues, Selection, SheetView, SheetViews,
@@ -66,6 +67,21 @@ pub enum SheetLayoutOp {
         #[serde(default)]
         scale_percent: Option<u32>,
     },
+    /// Set only the orientation, leaving fit-to-page and scale alone.
+    SetOrientation {
+        sheet_name: String,
+        orientation: PageOrientation,
+    },
+    /// Set the header and/or footer printed on every page. Text may use
+    /// Excel codes: `&L`/`&C`/`&R` sections, `&P` page, `&N` pages, `&D`
+    /// date, `&A` sheet name, `&F` file name. An empty string clears it.
+    SetHeaderFooter {
+        sheet_name: String,
+        #[serde(default)]
+        header: Option<String>,
+        #[serde(default)]
+        footer: Option<String>,
+    },
     SetPrintArea {
         sheet_name: String,
         range: String,
@@ -77,6 +93,39 @@ pub enum SheetLayoutOp {
         #[serde(default)]
         col_breaks: Vec<u32>,
     },
+    SetRowsHidden {
+        sheet_name: String,
+        /// Row span such as `"5"` or `"5:9"`.
+        rows: String,
+        hidden: bool,
+    },
+    SetColumnsHidden {
+        sheet_name: String,
+        /// Column span such as `"C"` or `"C:E"`.
+        columns: String,
+        hidden: bool,
+    },
+    GroupRows {
+        sheet_name: String,
+        rows: String,
+        /// Hide the grouped rows as well (collapsed group).
+        #[serde(default)]
+        collapsed: bool,
+    },
+    UngroupRows {
+        sheet_name: String,
+        rows: String,
+    },
+    GroupColumns {
+        sheet_name: String,
+        columns: String,
+        #[serde(default)]
+        collapsed: bool,
+    },
+    UngroupColumns {
+        sheet_name: String,
+        columns: String,
+    },
 }
 
 #[derive(Debug, Serialize, JsonSchema)]
@@ -121,9 +170,8 @@ pub async fn sheet_layout_batch(
 
     if mode.is_preview() {
         let change_id = make_short_random_id("chg", 12);
-        let snapshot_path = stage_snapshot_path(&params.fork_id, &change_id);
-        fs::create_dir_all(snapshot_path.parent().unwrap())?;
-        fs::copy(&work_path, &snapshot_path)?;
+        let snapshot = registry.stage_snapshot(&params.fork_id, &change_id, &work_path)?;
+        let snapshot_path = snapshot.to_path_buf();
 
         let snapshot_for_apply = snapshot_path.clone();
         let ops_for_apply = params.ops.clone();
@@ -149,7 +197,7 @@ pub async fn sheet_layout_batch(
             label: params.label.clone(),
             ops: vec![staged_op],
             summary: summary.clone(),
-            fork_path_snapshot: Some(snapshot_path),
+            fork_path_snapshot: Some(snapshot.keep()?),
         };
 
         registry.add_staged_change(&params.fork_id, staged)?;
@@ -192,15 +240,19 @@ fn op_sheet_name(op: &SheetLayoutOp) -> &str {
         | SheetLayoutOp::SetGridlines { sheet_name, .. }
         | SheetLayoutOp::SetPageMargins { sheet_name, .. }
         | SheetLayoutOp::SetPageSetup { sheet_name, .. }
+        | SheetLayoutOp::SetOrientation { sheet_name, .. }
+        | SheetLayoutOp::SetHeaderFooter { sheet_name, .. }
         | SheetLayoutOp::SetPrintArea { sheet_name, .. }
-        | SheetLayoutOp::SetPageBreaks { sheet_name, .. } => sheet_name,
+        | SheetLayoutOp::SetPageBreaks { sheet_name, .. }
+        | SheetLayoutOp::SetRowsHidden { sheet_name, .. }
+        | SheetLayoutOp::SetColumnsHidden { sheet_name, .. }
+        | SheetLayoutOp::GroupRows { sheet_name, .. }
+        | SheetLayoutOp::UngroupRows { sheet_name, .. }
+        | SheetLayoutOp::GroupColumns { sheet_name, .. }
+        | SheetLayoutOp::UngroupColumns { sheet_name, .. } => sheet_name,
     }
 }
 
-fn stage_snapshot_path(fork_id: &str, change_id: &str) -> PathBuf {
-    PathBuf::from("/tmp/mcp-staged").join(format!("{fork_id}_{change_id}.xlsx"))
-}
-
 fn set_recalc_needed_flag(summary: &mut ChangeSummary, recalc_needed: bool) {
     summary
         .flags
@@ -216,6 +268,9 @@ pub(crate) fn apply_sheet_layout_ops_to_file(
     path: &Path,
     ops: &[SheetLayoutOp],
 ) -> Result<SheetLayoutApplyResult> {
+    // umya does not round-trip outline levels; grouping ops edit the captured ones.
+    let mut preserved = PreservedPackage::capture(path);
+    let outlines = &mut preserved.outlines;
     let mut book = umya_spreadsheet::reader::xlsx::read(path)?;
 
     let mut affected_sheets: BTreeSet<String> = BTreeSet::new();
@@ -228,8 +283,16 @@ pub(crate) fn apply_sheet_layout_ops_to_file(
     let mut grid_ops: u64 = 0;
     let mut margin_ops: u64 = 0;
     let mut setup_ops: u64 = 0;
+    let mut orientation_ops: u64 = 0;
+    let mut header_footer_ops: u64 = 0;
     let mut print_area_ops: u64 = 0;
     let mut page_break_ops: u64 = 0;
+    let mut hide_row_ops: u64 = 0;
+    let mut hide_col_ops: u64 = 0;
+    let mut group_row_ops: u64 = 0;
+    let mut ungroup_row_ops: u64 = 0;
+    let mut group_col_ops: u64 = 0;
+    let mut ungroup_col_ops: u64 = 0;
 
     for op in ops {
         match op {
@@ -355,6 +418,47 @@ pub(crate) fn apply_sheet_layout_ops_to_file(
                     setup.set_scale(*v);
                 }
             }
+            SheetLayoutOp::SetOrientation {
+                sheet_name,
+                orientation,
+            } => {
+                orientation_ops += 1;
+                affected_sheets.insert(sheet_name.clone());
+                let sheet = book
+                    .get_sheet_by_name_mut(sheet_name)
+                    .ok_or_else(|| anyhow!("sheet '{}' not found", sheet_name))?;
+                sheet
+                    .get_page_setup_mut()
+                    .set_orientation(orientation.to_umya());
+            }
+            SheetLayoutOp::SetHeaderFooter {
+                sheet_name,
+                header,
+                footer,
+            } => {
+                header_footer_ops += 1;
+                affected_sheets.insert(sheet_name.clone());
+                if header.is_none() && footer.is_none() {
+                    bail!("set_header_footer requires header and/or footer");
+                }
+                for (field, text) in [("header", header), ("footer", footer)] {
+                    if let Some(text) = text
+                        && text.chars().count() > MAX_HEADER_FOOTER_CHARS
+                    {
+                        bail!("{field} must be at most {MAX_HEADER_FOOTER_CHARS} characters");
+                    }
+                }
+                let sheet = book
+                    .get_sheet_by_name_mut(sheet_name)
+                    .ok_or_else(|| anyhow!("sheet '{}' not found", sheet_name))?;
+                let header_footer = sheet.get_header_footer_mut();
+                if let Some(text) = header {
+                    header_footer.get_odd_header_mut().set_value(text.clone());
+                }
+                if let Some(text) = footer {
+                    header_footer.get_odd_footer_mut().set_value(text.clone());
+                }
+            }
             SheetLayoutOp::SetPrintArea { sheet_name, range } => {
                 print_area_ops += 1;
                 affected_sheets.insert(sheet_name.clone());
@@ -383,10 +487,124 @@ pub(crate) fn apply_sheet_layout_ops_to_file(
                     .ok_or_else(|| anyhow!("sheet '{}' not found", sheet_name))?;
                 apply_page_breaks(sheet, row_breaks, col_breaks);
             }
+            SheetLayoutOp::SetRowsHidden {
+                sheet_name,
+                rows,
+                hidden,
+            } => {
+                hide_row_ops += 1;
+                affected_sheets.insert(sheet_name.clone());
+                let (start, end) = parse_row_span(rows)?;
+                affected_bounds.push(format!("{start}:{end}"));
+                let sheet = book
+                    .get_sheet_by_name_mut(sheet_name)
+                    .ok_or_else(|| anyhow!("sheet '{}' not found", sheet_name))?;
+                set_rows_hidden(sheet, start, end, *hidden);
+            }
+            SheetLayoutOp::SetColumnsHidden {
+                sheet_name,
+                columns,
+                hidden,
+            } => {
+                hide_col_ops += 1;
+                affected_sheets.insert(sheet_name.clone());
+                let (start, end) = parse_column_span(columns)?;
+                affected_bounds.push(format_column_span(start, end));
+                let sheet = book
+                    .get_sheet_by_name_mut(sheet_name)
+                    .ok_or_else(|| anyhow!("sheet '{}' not found", sheet_name))?;
+                set_columns_hidden(sheet, start, end, *hidden);
+            }
+            SheetLayoutOp::GroupRows {
+                sheet_name, rows, ..
+            }
+            | SheetLayoutOp::UngroupRows { sheet_name, rows } => {
+                let grouping = matches!(op, SheetLayoutOp::GroupRows { .. });
+                let collapsed = matches!(
+                    op,
+                    SheetLayoutOp::GroupRows {
+                        collapsed: true,
+                        ..
+                    }
+                );
+                if grouping {
+                    group_row_ops += 1;
+                } else {
+                    ungroup_row_ops += 1;
+                }
+                affected_sheets.insert(sheet_name.clone());
+                let (start, end) = parse_row_span(rows)?;
+                affected_bounds.push(format!("{start}:{end}"));
+                let sheet = book
+                    .get_sheet_by_name_mut(sheet_name)
+                    .ok_or_else(|| anyhow!("sheet '{}' not found", sheet_name))?;
+                let outline = outlines.entry(sheet_name.clone()).or_default();
+                if grouping {
+                    if (start..=end).any(|row| outline.row_level(row) >= outline::MAX_OUTLINE_LEVEL)
+                    {
+                        warnings.push(format!(
+                            "WARN_OUTLINE_LEVEL_CAPPED: rows {start}:{end} already at outline level {}",
+                            outline::MAX_OUTLINE_LEVEL
+                        ));
+                    }
+                    outline.adjust_rows(start, end, 1);
+                    if collapsed {
+                        set_rows_hidden(sheet, start, end, true);
+                    }
+                } else {
+                    outline.adjust_rows(start, end, -1);
+                }
+            }
+            SheetLayoutOp::GroupColumns {
+                sheet_name,
+                columns,
+                ..
+            }
+            | SheetLayoutOp::UngroupColumns {
+                sheet_name,
+                columns,
+            } => {
+                let grouping = matches!(op, SheetLayoutOp::GroupColumns { .. });
+                let collapsed = matches!(
+                    op,
+                    SheetLayoutOp::GroupColumns {
+                        collapsed: true,
+                        ..
+                    }
+                );
+                if grouping {
+                    group_col_ops += 1;
+                } else {
+                    ungroup_col_ops += 1;
+                }
+                affected_sheets.insert(sheet_name.clone());
+                let (start, end) = parse_column_span(columns)?;
+                affected_bounds.push(format_column_span(start, end));
+                let sheet = book
+                    .get_sheet_by_name_mut(sheet_name)
+                    .ok_or_else(|| anyhow!("sheet '{}' not found", sheet_name))?;
+                let outline = outlines.entry(sheet_name.clone()).or_default();
+                if grouping {
+                    if (start..=end).any(|col| outline.col_level(col) >= outline::MAX_OUTLINE_LEVEL)
+                    {
+                        warnings.push(format!(
+                            "WARN_OUTLINE_LEVEL_CAPPED: columns {} already at outline level {}",
+                            format_column_span(start, end),
+                            outline::MAX_OUTLINE_LEVEL
+                        ));
+                    }
+                    outline.adjust_cols(start, end, 1);
+                    if collapsed {
+                        set_columns_hidden(sheet, start, end, true);
+                    }
+                } else {
+                    outline.adjust_cols(start, end, -1);
+                }
+            }
         }
     }
 
-    umya_spreadsheet::writer::xlsx::write(&book, path)?;
+    save_workbook_preserving(&book, path, &preserved)?;
 
     counts.insert("ops".to_string(), ops.len() as u64);
     if freeze_ops > 0 {
@@ -404,12 +622,36 @@ pub(crate) fn apply_sheet_layout_ops_to_file(
     if setup_ops > 0 {
         counts.insert("set_page_setup_ops".to_string(), setup_ops);
     }
+    if orientation_ops > 0 {
+        counts.insert("set_orientation_ops".to_string(), orientation_ops);
+    }
+    if header_footer_ops > 0 {
+        counts.insert("set_header_footer_ops".to_string(), header_footer_ops);
+    }
     if print_area_ops > 0 {
         counts.insert("set_print_area_ops".to_string(), print_area_ops);
     }
     if page_break_ops > 0 {
         counts.insert("set_page_breaks_ops".to_string(), page_break_ops);
     }
+    if hide_row_ops > 0 {
+        counts.insert("set_rows_hidden_ops".to_string(), hide_row_ops);
+    }
+    if hide_col_ops > 0 {
+        counts.insert("set_columns_hidden_ops".to_string(), hide_col_ops);
+    }
+    if group_row_ops > 0 {
+        counts.insert("group_rows_ops".to_string(), group_row_ops);
+    }
+    if ungroup_row_ops > 0 {
+        counts.insert("ungroup_rows_ops".to_string(), ungroup_row_ops);
+    }
+    if group_col_ops > 0 {
+        counts.insert("group_columns_ops".to_string(), group_col_ops);
+    }
+    if ungroup_col_ops > 0 {
+        counts.insert("ungroup_columns_ops".to_string(), ungroup_col_ops);
+    }
 
     let summary = ChangeSummary {
         op_kinds: vec!["sheet_layout_batch".to_string()],
@@ -426,6 +668,78 @@ pub(crate) fn apply_sheet_layout_ops_to_file(
     })
 }
 
+const MAX_SHEET_ROW: u32 = 1_048_576;
+/// Excel rejects header and footer text longer than this.
+const MAX_HEADER_FOOTER_CHARS: usize = 255;
+const MAX_SHEET_COL: u32 = 16_384;
+
+/// Parse `"5"` or `"5:9"` into an inclusive 1-based row span.
+pub(crate) fn parse_row_span(raw: &str) -> Result<(u32, u32)> {
+    let (a, b) = raw
+        .trim()
+        .split_once(':')
+        .unwrap_or((raw.trim(), raw.trim()));
+    let parse = |part: &str| {
+        part.trim()
+            .replace('$', "")
+            .parse::<u32>()
+            .ok()
+            .filter(|row| (1..=MAX_SHEET_ROW).contains(row))
+    };
+    let (Some(a), Some(b)) = (parse(a), parse(b)) else {
+        bail!("invalid row span '{raw}': expected e.g. \"5\" or \"5:9\"");
+    };
+    Ok((a.min(b), a.max(b)))
+}
+
+/// Parse `"C"` or `"C:E"` into an inclusive 1-based column span.
+fn parse_column_span(raw: &str) -> Result<(u32, u32)> {
+    let (a, b) = raw
+        .trim()
+        .split_once(':')
+        .unwrap_or((raw.trim(), raw.trim()));
+    let parse = |part: &str| {
+        let letters = part.trim().replace('$', "").to_ascii_uppercase();
+        if letters.is_empty() || !letters.chars().all(|ch| ch.is_ascii_uppercase()) {
+            return None;
+        }
+        Some(umya_spreadsheet::helper::coordinate::column_index_from_string(&letters))
+            .filter(|col| (1..=MAX_SHEET_COL).contains(col))
+    };
+    let (Some(a), Some(b)) = (parse(a), parse(b)) else {
+        bail!("invalid column span '{raw}': expected e.g. \"C\" or \"C:E\"");
+    };
+    Ok((a.min(b), a.max(b)))
+}
+
+fn format_column_span(start: u32, end: u32) -> String {
+    format!(
+        "{}:{}",
+        column_number_to_name(start),
+        column_number_to_name(end)
+    )
+}
+
+fn set_rows_hidden(sheet: &mut Worksheet, start: u32, end: u32, hidden: bool) {
+    for row in start..=end {
+        if !hidden && sheet.get_row_dimension(&row).is_none() {
+            continue;
+        }
+        sheet.get_row_dimension_mut(&row).set_hidden(hidden);
+    }
+}
+
+fn set_columns_hidden(sheet: &mut Worksheet, start: u32, end: u32, hidden: bool) {
+    for col in start..=end {
+        if !hidden && sheet.get_column_dimension_by_number(&col).is_none() {
+            continue;
+        }
+        sheet
+            .get_column_dimension_by_number_mut(&col)
+            .set_hidden(hidden);
+    }
+}
+
 fn primary_sheet_view_mut(sheet: &mut Worksheet) -> &mut SheetView {
     let views = sheet.get_sheet_views_mut().get_sheet_view_list_mut();
     if views.is_empty() {
@@ -607,7 +921,7 @@ fn resolve_sheet_index(book: &umya_spreadsheet::Spreadsheet, sheet_name: &str) -
     bail!("sheet '{}' not found", sheet_name)
 }
 
-fn parse_a1_range(range: &str) -> Result<((u32, u32), (u32, u32))> {
+pub(crate) fn parse_a1_range(range: &str) -> Result<((u32, u32), (u32, u32))> {
     let trimmed = range.trim();
     if trimmed.is_empty() {
         bail!("range is empty");
@@ -631,7 +945,7 @@ fn parse_a1_range(range: &str) -> Result<((u32, u32), (u32, u32))> {
     Ok(((ac.min(bc), ar.min(br)), (ac.max(bc), ar.max(br))))
 }
 
-fn format_sheet_prefix(sheet_name: &str) -> String {
+pub(crate) fn format_sheet_prefix(sheet_name: &str) -> String {
     if sheet_name_needs_quoting(sheet_name) {
         let escaped = sheet_name.replace('\'', "''");
         format!("'{escaped}'!")
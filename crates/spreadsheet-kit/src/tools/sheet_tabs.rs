@@ -0,0 +1,171 @@
+//! Sheet tab structure ops: `set_tab_color`, `hide_sheet`/`unhide_sheet` and
+//! `reorder_sheets`.
+//!
+//! Excel requires at least one visible sheet, and opens the workbook on the
+//! tab recorded as the workbook view's `activeTab`. Hiding or moving sheets
+//! keeps that tab pointing at a visible sheet, and moving sheets carries the
+//! `localSheetId` of sheet-scoped defined names along with them.
+
+use crate::model::SheetVisibility;
+use crate::styles::normalize_color_hex;
+use anyhow::{Result, anyhow, bail};
+use std::collections::BTreeSet;
+use umya_spreadsheet::Spreadsheet;
+
+fn sheet_index(book: &Spreadsheet, op: &str, sheet_name: &str) -> Result<usize> {
+    let sheets = book.get_sheet_collection_no_check();
+    sheets
+        .iter()
+        .position(|sheet| sheet.get_name() == sheet_name)
+        .or_else(|| {
+            sheets
+                .iter()
+                .position(|sheet| sheet.get_name().eq_ignore_ascii_case(sheet_name))
+        })
+        .ok_or_else(|| anyhow!("{op}: sheet '{sheet_name}' not found"))
+}
+
+/// Set (or with `None`, remove) the tab color of `sheet_name`. Returns a
+/// warning when the alpha channel was defaulted.
+pub(crate) fn set_tab_color(
+    book: &mut Spreadsheet,
+    sheet_name: &str,
+    color: Option<&str>,
+) -> Result<Option<String>> {
+    let index = sheet_index(book, "set_tab_color", sheet_name)?;
+    let sheet = &mut book.get_sheet_collection_mut()[index];
+    let Some(color) = color else {
+        sheet.remove_tab_color();
+        return Ok(None);
+    };
+    let Some((argb, defaulted_alpha)) = normalize_color_hex(color) else {
+        bail!("set_tab_color: invalid color '{color}': expected #RGB/#RRGGBB/#AARRGGBB");
+    };
+    let mut tab_color = umya_spreadsheet::Color::default();
+    tab_color.set_argb(argb);
+    sheet.set_tab_color(tab_color);
+    Ok(defaulted_alpha.then(|| {
+        format!("WARN_COLOR_ALPHA_DEFAULT: Defaulted alpha to FF for tab color of '{sheet_name}'")
+    }))
+}
+
+/// Set the visibility of `sheet_name`. Returns whether it changed.
+pub(crate) fn set_sheet_visibility(
+    book: &mut Spreadsheet,
+    sheet_name: &str,
+    visibility: SheetVisibility,
+) -> Result<bool> {
+    let op = match visibility {
+        SheetVisibility::Visible => "unhide_sheet",
+        SheetVisibility::Hidden | SheetVisibility::VeryHidden => "hide_sheet",
+    };
+    let index = sheet_index(book, op, sheet_name)?;
+    let sheets = book.get_sheet_collection_no_check();
+    if SheetVisibility::from_sheet_state(sheets[index].get_sheet_state()) == visibility {
+        return Ok(false);
+    }
+    if visibility != SheetVisibility::Visible {
+        let others_visible = sheets.iter().enumerate().any(|(i, sheet)| {
+            i != index
+                && SheetVisibility::from_sheet_state(sheet.get_sheet_state())
+                    == SheetVisibility::Visible
+        });
+        if !others_visible {
+            bail!(
+                "{op}: cannot hide '{sheet_name}', a workbook must keep at least one visible sheet"
+            );
+        }
+    }
+
+    let sheet = &mut book.get_sheet_collection_mut()[index];
+    sheet.set_sheet_state(visibility.as_sheet_state().to_string());
+    if visibility != SheetVisibility::Visible {
+        for view in sheet.get_sheet_views_mut().get_sheet_view_list_mut() {
+            view.set_tab_selected(false);
+        }
+        if active_tab(book) == index {
+            activate_first_visible(book);
+        }
+    }
+    Ok(true)
+}
+
+/// Move the sheets named in `order` to the front of the tab bar in that
+/// order; unlisted sheets follow in their current order. Returns how many
+/// sheets changed position.
+pub(crate) fn reorder_sheets(book: &mut Spreadsheet, order: &[String]) -> Result<usize> {
+    if order.is_empty() {
+        bail!("reorder_sheets requires a non-empty order");
+    }
+    let mut listed = Vec::with_capacity(order.len());
+    let mut seen = BTreeSet::new();
+    for name in order {
+        let index = sheet_index(book, "reorder_sheets", name)?;
+        if !seen.insert(index) {
+            bail!("reorder_sheets: sheet '{name}' is listed more than once");
+        }
+        listed.push(index);
+    }
+    let count = book.get_sheet_collection_no_check().len();
+    // new position -> old position
+    let permutation: Vec<usize> = listed
+        .iter()
+        .copied()
+        .chain((0..count).filter(|index| !seen.contains(index)))
+        .collect();
+    let moved = permutation
+        .iter()
+        .enumerate()
+        .filter(|(new, old)| new != *old)
+        .count();
+    if moved == 0 {
+        return Ok(0);
+    }
+    let mut new_index_of = vec![0usize; count];
+    for (new, old) in permutation.iter().enumerate() {
+        new_index_of[*old] = new;
+    }
+
+    let active = active_tab(book);
+    let sheets = book.get_sheet_collection_mut();
+    let mut slots: Vec<Option<_>> = sheets.drain(..).map(Some).collect();
+    for old in &permutation {
+        if let Some(sheet) = slots[*old].take() {
+            sheets.push(sheet);
+        }
+    }
+    for defined in book.get_defined_names_mut() {
+        if defined.has_local_sheet_id() {
+            let old = *defined.get_local_sheet_id() as usize;
+            if let Some(new) = new_index_of.get(old) {
+                defined.set_local_sheet_id(*new as u32);
+            }
+        }
+    }
+    if active < count {
+        book.get_workbook_view_mut()
+            .set_active_tab(new_index_of[active] as u32);
+    }
+    Ok(moved)
+}
+
+fn active_tab(book: &Spreadsheet) -> usize {
+    book.get_workbook_view().get_active_tab().to_owned() as usize
+}
+
+fn activate_first_visible(book: &mut Spreadsheet) {
+    let first_visible = book
+        .get_sheet_collection_no_check()
+        .iter()
+        .position(|sheet| {
+            SheetVisibility::from_sheet_state(sheet.get_sheet_state()) == SheetVisibility::Visible
+        })
+        .unwrap_or(0);
+    book.get_workbook_view_mut()
+        .set_active_tab(first_visible as u32);
+    if let Some(sheet) = book.get_sheet_collection_mut().get_mut(first_visible) {
+        for view in sheet.get_sheet_views_mut().get_sheet_view_list_mut() {
+            view.set_tab_selected(true);
+        }
+    }
+}
@@ -0,0 +1,379 @@
+//! Row sorting for the `sort_range` and `sort_table` transform ops.
+//!
+//! Sorting moves whole rows of the block: each cell of a row keeps its
+//! value, formula and style, and a moved formula is re-anchored to its new
+//! row the way Excel does when it sorts, so a formula that refers to cells
+//! on its own row still does afterwards. References into the block from
+//! outside it are left alone, as are comments, hyperlinks and conditional
+//! formats.
+
+use super::import_rows::table_matches;
+use crate::fork::RowPermutation;
+use crate::formula::pattern::{RelativeMode, parse_base_formula, shift_formula_ast};
+use crate::metadata::annotation_bounds;
+use crate::utils::cell_address;
+use crate::verification::is_error_text;
+use anyhow::{Result, anyhow, bail};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use umya_spreadsheet::Worksheet;
+
+/// One key of a `sort_range` or `sort_table` op; later keys break ties
+/// left by earlier ones.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SortKey {
+    /// Column letter (`C`), or a header name when the block has a header
+    /// row; a matching header wins over a column letter
+    pub column: String,
+    #[serde(default)]
+    pub order: SortOrder,
+    #[serde(default)]
+    pub value_type: SortValueType,
+    #[serde(default)]
+    pub case_sensitive: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// How values compare under a key. Blank cells sort last in either order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SortValueType {
+    /// Excel's order: numbers, then text, then logicals, then errors
+    #[default]
+    Auto,
+    /// Numbers first, including text such as ` 1,250 ` that reads as one
+    Number,
+    /// Every value compared as its displayed text
+    Text,
+}
+
+/// What one sort op changed.
+#[derive(Debug, Default)]
+pub(crate) struct SortEffects {
+    pub(crate) rows_sorted: u64,
+    pub(crate) rows_moved: u64,
+    pub(crate) formulas_reanchored: u64,
+    pub(crate) bounds: Option<String>,
+    pub(crate) permutation: Option<RowPermutation>,
+    pub(crate) warnings: Vec<String>,
+}
+
+/// Rows and columns of a sortable block. `header_row` is only used to
+/// resolve key names; it never moves.
+struct SortBlock {
+    min_col: u32,
+    max_col: u32,
+    header_row: Option<u32>,
+    first_row: u32,
+    last_row: u32,
+}
+
+pub(crate) fn validate_sort_keys(kind: &str, keys: &[SortKey]) -> Result<()> {
+    if keys.is_empty() {
+        bail!("{kind} needs at least one key");
+    }
+    if keys.iter().any(|key| key.column.trim().is_empty()) {
+        bail!("{kind} key column cannot be empty");
+    }
+    Ok(())
+}
+
+/// Sort the rows of `range`, keeping its first row in place with `has_header`.
+pub(crate) fn sort_range_in_sheet(
+    sheet: &mut Worksheet,
+    sheet_name: &str,
+    range: &str,
+    keys: &[SortKey],
+    has_header: bool,
+) -> Result<SortEffects> {
+    let ((min_col, min_row), (max_col, max_row)) = annotation_bounds(range)?;
+    let block = SortBlock {
+        min_col,
+        max_col,
+        header_row: has_header.then_some(min_row),
+        first_row: if has_header { min_row + 1 } else { min_row },
+        last_row: max_row,
+    };
+    sort_block(sheet, sheet_name, &block, keys)
+}
+
+/// Sort the data rows of a named table, between its header and totals rows.
+pub(crate) fn sort_table_in_sheet(
+    sheet: &mut Worksheet,
+    sheet_name: &str,
+    table_name: &str,
+    keys: &[SortKey],
+) -> Result<SortEffects> {
+    let block = {
+        let table = sheet
+            .get_tables()
+            .iter()
+            .find(|table| table_matches(table, table_name))
+            .ok_or_else(|| {
+                anyhow!(
+                    "table '{}' was not found on sheet '{}'",
+                    table_name,
+                    sheet_name
+                )
+            })?;
+        let (start, end) = table.get_area();
+        let header_row = *start.get_row_num();
+        let totals = *table.get_totals_row_count();
+        SortBlock {
+            min_col: *start.get_col_num(),
+            max_col: *end.get_col_num(),
+            header_row: Some(header_row),
+            first_row: header_row + 1,
+            last_row: end.get_row_num().saturating_sub(totals),
+        }
+    };
+    sort_block(sheet, sheet_name, &block, keys)
+}
+
+fn sort_block(
+    sheet: &mut Worksheet,
+    sheet_name: &str,
+    block: &SortBlock,
+    keys: &[SortKey],
+) -> Result<SortEffects> {
+    let mut effects = SortEffects::default();
+    if block.first_row > block.last_row {
+        return Ok(effects);
+    }
+    let bounds = format!(
+        "{}:{}",
+        cell_address(block.min_col, block.first_row),
+        cell_address(block.max_col, block.last_row)
+    );
+    for merge in sheet.get_merge_cells() {
+        let raw = merge.get_range();
+        let Ok(((merge_min_col, merge_min_row), (merge_max_col, merge_max_row))) =
+            annotation_bounds(&raw)
+        else {
+            continue;
+        };
+        if merge_min_row <= block.last_row
+            && merge_max_row >= block.first_row
+            && merge_min_col <= block.max_col
+            && merge_max_col >= block.min_col
+        {
+            bail!(
+                "cannot sort {}!{}: merged cells {} overlap the rows being sorted",
+                sheet_name,
+                bounds,
+                raw
+            );
+        }
+    }
+
+    let key_columns = keys
+        .iter()
+        .map(|key| key_column(sheet, block, key))
+        .collect::<Result<Vec<_>>>()?;
+    let rows: Vec<u32> = (block.first_row..=block.last_row).collect();
+    let values: Vec<Vec<SortValue>> = rows
+        .iter()
+        .map(|&row| {
+            keys.iter()
+                .zip(&key_columns)
+                .map(|(key, &col)| SortValue::read(&sheet.get_value((col, row)), key))
+                .collect()
+        })
+        .collect();
+
+    // `sort_by` is stable, so rows that tie on every key keep their order.
+    let mut order: Vec<usize> = (0..rows.len()).collect();
+    order.sort_by(|&a, &b| {
+        keys.iter()
+            .enumerate()
+            .map(|(index, key)| values[a][index].compare(&values[b][index], key.order))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    });
+
+    effects.rows_sorted = rows.len() as u64;
+    effects.bounds = Some(bounds.clone());
+    effects.permutation = Some(RowPermutation {
+        sheet_name: sheet_name.to_string(),
+        range: bounds,
+        source_rows: order.iter().map(|&index| rows[index]).collect(),
+    });
+    if order
+        .iter()
+        .enumerate()
+        .all(|(position, &index)| position == index)
+    {
+        return Ok(effects);
+    }
+
+    let captured: Vec<Vec<(u32, umya_spreadsheet::CellValue, umya_spreadsheet::Style)>> = rows
+        .iter()
+        .map(|&row| {
+            (block.min_col..=block.max_col)
+                .filter_map(|col| {
+                    sheet
+                        .get_cell((col, row))
+                        .map(|cell| (col, cell.get_cell_value().clone(), cell.get_style().clone()))
+                })
+                .collect()
+        })
+        .collect();
+    for &row in &rows {
+        for col in block.min_col..=block.max_col {
+            sheet.remove_cell((col, row));
+        }
+    }
+
+    for (position, &index) in order.iter().enumerate() {
+        let source_row = rows[index];
+        let dest_row = block.first_row + position as u32;
+        let delta_row = dest_row as i32 - source_row as i32;
+        if delta_row != 0 {
+            effects.rows_moved += 1;
+        }
+        for (col, value, style) in &captured[index] {
+            let cell = sheet.get_cell_mut((*col, dest_row));
+            *cell.get_cell_value_mut() = value.clone();
+            cell.set_style(style.clone());
+            if delta_row == 0 || !cell.is_formula() {
+                continue;
+            }
+            let formula = cell.get_formula().to_string();
+            match parse_base_formula(&formula)
+                .and_then(|ast| shift_formula_ast(&ast, 0, delta_row, RelativeMode::Excel))
+            {
+                Ok(shifted) => {
+                    let shifted = shifted.strip_prefix('=').unwrap_or(&shifted).to_string();
+                    cell.set_formula(shifted);
+                    cell.set_formula_result_default("");
+                    effects.formulas_reanchored += 1;
+                }
+                Err(err) => effects.warnings.push(format!(
+                    "WARN_SORT_FORMULA_SHIFT: could not re-anchor formula '{}' moved from {} to {}: {}; copied verbatim.",
+                    formula,
+                    cell_address(*col, source_row),
+                    cell_address(*col, dest_row),
+                    err
+                )),
+            }
+        }
+    }
+    Ok(effects)
+}
+
+/// Column a key sorts on: a header in the block's header row, else a
+/// column letter inside the block.
+fn key_column(sheet: &Worksheet, block: &SortBlock, key: &SortKey) -> Result<u32> {
+    let name = key.column.trim();
+    if let Some(header_row) = block.header_row
+        && let Some(col) = (block.min_col..=block.max_col).find(|&col| {
+            sheet
+                .get_value((col, header_row))
+                .trim()
+                .eq_ignore_ascii_case(name)
+        })
+    {
+        return Ok(col);
+    }
+    if name.len() <= 3 && name.chars().all(|ch| ch.is_ascii_alphabetic()) {
+        let col = umya_spreadsheet::helper::coordinate::column_index_from_string(
+            name.to_ascii_uppercase(),
+        );
+        if (block.min_col..=block.max_col).contains(&col) {
+            return Ok(col);
+        }
+        bail!(
+            "sort key column '{}' is outside columns {}:{}",
+            name,
+            crate::utils::column_number_to_name(block.min_col),
+            crate::utils::column_number_to_name(block.max_col)
+        );
+    }
+    if block.header_row.is_some() {
+        bail!("sort key column '{}' matches no header", name);
+    }
+    bail!(
+        "sort key column '{}' is not a column letter; set has_header to sort by header name",
+        name
+    );
+}
+
+/// A cell value as one key compares it. Variants are declared in Excel's
+/// ascending order.
+#[derive(Debug, PartialEq)]
+enum SortValue {
+    Number(f64),
+    Text(String),
+    Bool(bool),
+    Error(String),
+    Blank,
+}
+
+impl SortValue {
+    fn read(raw: &str, key: &SortKey) -> Self {
+        if raw.is_empty() {
+            return Self::Blank;
+        }
+        let text = || {
+            Self::Text(if key.case_sensitive {
+                raw.to_string()
+            } else {
+                raw.to_lowercase()
+            })
+        };
+        match key.value_type {
+            SortValueType::Text => text(),
+            SortValueType::Number => match raw.trim().replace(',', "").parse::<f64>() {
+                Ok(number) if number.is_finite() => Self::Number(number),
+                _ => text(),
+            },
+            SortValueType::Auto => {
+                if let Ok(number) = raw.parse::<f64>() {
+                    Self::Number(number)
+                } else if raw.eq_ignore_ascii_case("true") {
+                    Self::Bool(true)
+                } else if raw.eq_ignore_ascii_case("false") {
+                    Self::Bool(false)
+                } else if is_error_text(raw) {
+                    Self::Error(raw.to_ascii_uppercase())
+                } else {
+                    text()
+                }
+            }
+        }
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            Self::Number(_) => 0,
+            Self::Text(_) => 1,
+            Self::Bool(_) => 2,
+            Self::Error(_) => 3,
+            Self::Blank => 4,
+        }
+    }
+
+    fn compare(&self, other: &Self, order: SortOrder) -> Ordering {
+        let ordering = match (self, other) {
+            (Self::Blank, Self::Blank) => return Ordering::Equal,
+            (Self::Blank, _) => return Ordering::Greater,
+            (_, Self::Blank) => return Ordering::Less,
+            (Self::Number(a), Self::Number(b)) => a.partial_cmp(b).unwrap_or(Ordering::Equal),
+            (Self::Text(a), Self::Text(b)) | (Self::Error(a), Self::Error(b)) => a.cmp(b),
+            (Self::Bool(a), Self::Bool(b)) => a.cmp(b),
+            _ => self.rank().cmp(&other.rank()),
+        };
+        match order {
+            SortOrder::Asc => ordering,
+            SortOrder::Desc => ordering.reverse(),
+        }
+    }
+}
@@ -0,0 +1,228 @@
+//! Schema export behind `table_profile`'s `emit_schema`.
+//!
+//! Each column's type is inferred over every data row. Numbers make an
+//! integer column when all are whole and a number column otherwise; booleans
+//! and dates keep their own types, with date and date-time cells together
+//! widening to date-time. Any other mix, and text or error cells, make a
+//! string column. Blank cells do not vote; they make the column nullable,
+//! and a column with no values at all is typed null.
+
+use super::param_enums::SchemaFormat;
+use crate::model::{CellValue, TableRow};
+use serde_json::{Map, Value, json};
+
+const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Largest integer an f64 holds exactly; whole numbers past it stay `Number`.
+const MAX_EXACT_INTEGER: f64 = 9_007_199_254_740_992.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnType {
+    Null,
+    Integer,
+    Number,
+    Boolean,
+    Date,
+    DateTime,
+    Time,
+    String,
+}
+
+impl ColumnType {
+    fn of(value: &CellValue) -> Option<Self> {
+        Some(match value {
+            CellValue::Text(text) if text.trim().is_empty() => return None,
+            CellValue::Number(n) if n.fract() == 0.0 && n.abs() <= MAX_EXACT_INTEGER => {
+                Self::Integer
+            }
+            CellValue::Number(_) => Self::Number,
+            CellValue::Bool(_) => Self::Boolean,
+            CellValue::Date(date) if date.contains('T') => Self::DateTime,
+            CellValue::Date(date) if date.contains(':') => Self::Time,
+            CellValue::Date(_) => Self::Date,
+            CellValue::Text(_) | CellValue::Error(_) => Self::String,
+        })
+    }
+
+    fn widen(self, other: Self) -> Self {
+        match (self, other) {
+            (a, b) if a == b => a,
+            (Self::Null, other) | (other, Self::Null) => other,
+            (Self::Integer, Self::Number) | (Self::Number, Self::Integer) => Self::Number,
+            (Self::Date, Self::DateTime) | (Self::DateTime, Self::Date) => Self::DateTime,
+            _ => Self::String,
+        }
+    }
+
+    fn json_schema(self) -> Value {
+        match self {
+            Self::Null => json!({ "type": "null" }),
+            Self::Integer => json!({ "type": "integer" }),
+            Self::Number => json!({ "type": "number" }),
+            Self::Boolean => json!({ "type": "boolean" }),
+            Self::Date => json!({ "type": "string", "format": "date" }),
+            Self::DateTime => json!({ "type": "string", "format": "date-time" }),
+            Self::Time => json!({ "type": "string", "format": "time" }),
+            Self::String => json!({ "type": "string" }),
+        }
+    }
+
+    fn arrow_type(self) -> Value {
+        match self {
+            Self::Null => json!({ "name": "null" }),
+            Self::Integer => json!({ "name": "int", "bitWidth": 64, "isSigned": true }),
+            Self::Number => json!({ "name": "floatingpoint", "precision": "DOUBLE" }),
+            Self::Boolean => json!({ "name": "bool" }),
+            Self::Date => json!({ "name": "date", "unit": "DAY" }),
+            Self::DateTime => json!({ "name": "timestamp", "unit": "SECOND" }),
+            Self::Time => json!({ "name": "time", "unit": "SECOND", "bitWidth": 32 }),
+            Self::String => json!({ "name": "utf8" }),
+        }
+    }
+}
+
+struct InferredColumn<'a> {
+    name: &'a str,
+    column_type: ColumnType,
+    nullable: bool,
+}
+
+fn infer_column<'a>(column: &'a str, rows: &[TableRow]) -> InferredColumn<'a> {
+    let mut column_type = ColumnType::Null;
+    let mut nullable = false;
+    for row in rows {
+        match row
+            .get(column)
+            .and_then(Option::as_ref)
+            .and_then(ColumnType::of)
+        {
+            Some(cell_type) => column_type = column_type.widen(cell_type),
+            None => nullable = true,
+        }
+    }
+    InferredColumn {
+        name: column,
+        column_type,
+        nullable: nullable || column_type == ColumnType::Null,
+    }
+}
+
+/// Schema for a table with `headers` over all of its data `rows`.
+pub fn table_schema(
+    format: SchemaFormat,
+    title: &str,
+    headers: &[String],
+    rows: &[TableRow],
+) -> Value {
+    let columns: Vec<InferredColumn> = headers
+        .iter()
+        .map(|header| infer_column(header, rows))
+        .collect();
+    match format {
+        SchemaFormat::Jsonschema => json_schema(title, &columns),
+        SchemaFormat::Arrow => arrow_schema(&columns),
+    }
+}
+
+fn json_schema(title: &str, columns: &[InferredColumn]) -> Value {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    for column in columns {
+        let mut schema = column.column_type.json_schema();
+        if column.nullable && column.column_type != ColumnType::Null {
+            let column_type = schema["type"].take();
+            schema["type"] = json!([column_type, "null"]);
+        }
+        if !column.nullable {
+            required.push(column.name);
+        }
+        properties.insert(column.name.to_string(), schema);
+    }
+    json!({
+        "$schema": JSON_SCHEMA_DIALECT,
+        "title": title,
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
+
+fn arrow_schema(columns: &[InferredColumn]) -> Value {
+    let fields: Vec<Value> = columns
+        .iter()
+        .map(|column| {
+            json!({
+                "name": column.name,
+                "nullable": column.nullable,
+                "type": column.column_type.arrow_type(),
+                "children": [],
+            })
+        })
+        .collect();
+    json!({ "fields": fields })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(cells: &[(&str, Option<CellValue>)]) -> TableRow {
+        cells
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn types_widen_across_rows() {
+        let headers = vec![
+            "Id".to_string(),
+            "Amount".to_string(),
+            "When".to_string(),
+            "Note".to_string(),
+        ];
+        let rows = vec![
+            row(&[
+                ("Id", Some(CellValue::Number(1.0))),
+                ("Amount", Some(CellValue::Number(2.0))),
+                ("When", Some(CellValue::Date("2024-01-31".to_string()))),
+                ("Note", Some(CellValue::Number(3.0))),
+            ]),
+            row(&[
+                ("Id", Some(CellValue::Number(2.0))),
+                ("Amount", Some(CellValue::Number(2.5))),
+                (
+                    "When",
+                    Some(CellValue::Date("2024-02-01T09:30:00".to_string())),
+                ),
+                ("Note", None),
+            ]),
+            row(&[
+                ("Id", Some(CellValue::Number(3.0))),
+                ("Amount", Some(CellValue::Text(" ".to_string()))),
+                ("When", Some(CellValue::Date("2024-02-02".to_string()))),
+                ("Note", Some(CellValue::Text("n/a".to_string()))),
+            ]),
+        ];
+
+        let schema = table_schema(SchemaFormat::Jsonschema, "Sales", &headers, &rows);
+        assert_eq!(schema["properties"]["Id"]["type"], "integer");
+        assert_eq!(
+            schema["properties"]["Amount"]["type"],
+            json!(["number", "null"])
+        );
+        assert_eq!(schema["properties"]["When"]["format"], "date-time");
+        assert_eq!(
+            schema["properties"]["Note"]["type"],
+            json!(["string", "null"])
+        );
+        assert_eq!(schema["required"], json!(["Id", "When"]));
+
+        let schema = table_schema(SchemaFormat::Arrow, "Sales", &headers, &rows);
+        assert_eq!(schema["fields"][0]["type"]["name"], "int");
+        assert_eq!(schema["fields"][1]["type"]["precision"], "DOUBLE");
+        assert_eq!(schema["fields"][1]["nullable"], true);
+        assert_eq!(schema["fields"][2]["type"]["name"], "timestamp");
+    }
+}
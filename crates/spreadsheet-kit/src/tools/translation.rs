@@ -0,0 +1,844 @@
+//! Translation tables for localizing report templates: `extract-strings`
+//! lists the user-visible text of a workbook, and `apply-strings` writes
+//! translated text back by the same ids.
+//!
+//! Strings are text cells (formulas are left alone: their text comes from
+//! the cells they reference), sheet names, page headers and footers, and
+//! chart titles. Each has a stable id:
+//!
+//! - `Sheet1!B2` for a cell
+//! - `sheet:Sheet1` for a sheet name (sheet names cannot contain `:`)
+//! - `Sheet1#header`, `Sheet1#footer`, `Sheet1#even_header`, ... for page
+//!   headers and footers, Excel codes such as `&C` or `&P` included
+//! - `Sheet1#chart2` for the title of the second chart on a sheet
+//!
+//! Chart titles are not reachable through the workbook model, so they are
+//! edited in the raw package before the workbook is loaded; sheet renames
+//! repoint chart series there too, and cell formulas and defined names
+//! through the `rename_sheet` path. Replacing a cell's text keeps its
+//! style but not rich-text runs within the cell.
+
+use super::csv_format::CsvOptions;
+use super::embedded_objects::resolve_part_path;
+use super::fork::{
+    format_sheet_prefix_for_formula, rename_sheet_with_references, validate_sheet_name,
+};
+use super::import_rows::parse_csv_records;
+use super::ooxml::{
+    escape_xml_attribute, parse_attributes, read_zip_text, relationship_tag_regex,
+    resolve_sheet_parts, rewrite_package, unescape_xml,
+};
+use crate::metadata::METADATA_SHEET_NAME;
+use crate::model::{FormulaParseDiagnosticsBuilder, FormulaParsePolicy};
+use crate::write::save_workbook;
+use anyhow::{Context, Result, anyhow, bail};
+use regex::{Captures, Regex};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::{Read, Seek};
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Header and footer slots, as they appear in ids.
+const HEADER_FOOTER_SLOTS: [&str; 6] = [
+    "header",
+    "footer",
+    "even_header",
+    "even_footer",
+    "first_header",
+    "first_footer",
+];
+const EXCEL_ERRORS: [&str; 10] = [
+    "#N/A",
+    "#REF!",
+    "#VALUE!",
+    "#DIV/0!",
+    "#NAME?",
+    "#NUM!",
+    "#NULL!",
+    "#SPILL!",
+    "#CALC!",
+    "#GETTING_DATA",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StringKind {
+    Cell,
+    SheetName,
+    HeaderFooter,
+    ChartTitle,
+}
+
+/// One user-visible string.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct TranslatableString {
+    /// Stable key to send back to `apply-strings`
+    pub id: String,
+    pub kind: StringKind,
+    pub sheet: String,
+    pub source: String,
+}
+
+/// A translated string to write back. Entries with an empty `target` are
+/// skipped as untranslated; when `source` is given and no longer matches
+/// the workbook, the entry is skipped as stale.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct StringTranslation {
+    pub id: String,
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub target: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, JsonSchema)]
+pub struct ApplyStringsSummary {
+    pub strings_applied: u64,
+    pub cells_updated: u64,
+    pub sheets_renamed: u64,
+    pub headers_footers_updated: u64,
+    pub chart_titles_updated: u64,
+    /// Entries without a target
+    pub skipped_untranslated: u64,
+    /// Entries whose target equals the current text
+    pub skipped_unchanged: u64,
+    /// Entries whose `source` no longer matches, or whose id was not found
+    pub skipped_stale: u64,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+/// Where a string id points.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum StringTarget {
+    Cell { sheet: String, address: String },
+    SheetName { sheet: String },
+    HeaderFooter { sheet: String, slot: &'static str },
+    ChartTitle { sheet: String, index: usize },
+}
+
+impl StringTarget {
+    fn parse(id: &str) -> Option<Self> {
+        if let Some(sheet) = id.strip_prefix("sheet:") {
+            return Some(Self::SheetName {
+                sheet: sheet.to_string(),
+            });
+        }
+        if let Some((sheet, address)) = id.rsplit_once('!')
+            && a1_pattern().is_match(address)
+        {
+            return Some(Self::Cell {
+                sheet: sheet.to_string(),
+                address: address.replace('$', "").to_ascii_uppercase(),
+            });
+        }
+        let (sheet, suffix) = id.rsplit_once('#')?;
+        if let Some(slot) = HEADER_FOOTER_SLOTS.iter().find(|slot| **slot == suffix) {
+            return Some(Self::HeaderFooter {
+                sheet: sheet.to_string(),
+                slot,
+            });
+        }
+        let index = suffix.strip_prefix("chart")?.parse::<usize>().ok()?;
+        (index > 0).then(|| Self::ChartTitle {
+            sheet: sheet.to_string(),
+            index,
+        })
+    }
+}
+
+fn a1_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"^\$?[A-Za-z]{1,3}\$?[0-9]{1,7}$").expect("valid A1 regex"))
+}
+
+/// Every user-visible string of the workbook at `path`, sheet by sheet:
+/// the sheet name, its text cells in row order, headers and footers, then
+/// chart titles. The hidden tool-metadata sheet is skipped.
+pub fn extract_strings(path: &Path) -> Result<Vec<TranslatableString>> {
+    let book = umya_spreadsheet::reader::xlsx::read(path)
+        .with_context(|| format!("failed to read workbook '{}'", path.display()))?;
+    let chart_titles = {
+        let mut archive = zip::ZipArchive::new(fs::File::open(path)?)?;
+        read_chart_titles(&mut archive)?
+    };
+
+    let mut strings = Vec::new();
+    for sheet in book.get_sheet_collection() {
+        let sheet_name = sheet.get_name().to_string();
+        if sheet_name == METADATA_SHEET_NAME {
+            continue;
+        }
+        strings.push(TranslatableString {
+            id: format!("sheet:{sheet_name}"),
+            kind: StringKind::SheetName,
+            sheet: sheet_name.clone(),
+            source: sheet_name.clone(),
+        });
+
+        let mut cells: Vec<(u32, u32, String)> = sheet
+            .get_cell_collection()
+            .into_iter()
+            .filter(|cell| !cell.is_formula())
+            .filter_map(|cell| {
+                let text = cell.get_value().to_string();
+                is_translatable(&text).then(|| {
+                    let coordinate = cell.get_coordinate();
+                    (*coordinate.get_row_num(), *coordinate.get_col_num(), text)
+                })
+            })
+            .collect();
+        cells.sort_by_key(|(row, col, _)| (*row, *col));
+        for (row, col, text) in cells {
+            strings.push(TranslatableString {
+                id: format!("{sheet_name}!{}", crate::utils::cell_address(col, row)),
+                kind: StringKind::Cell,
+                sheet: sheet_name.clone(),
+                source: text,
+            });
+        }
+
+        for slot in HEADER_FOOTER_SLOTS {
+            let text = header_footer_text(sheet, slot);
+            if !text.trim().is_empty() {
+                strings.push(TranslatableString {
+                    id: format!("{sheet_name}#{slot}"),
+                    kind: StringKind::HeaderFooter,
+                    sheet: sheet_name.clone(),
+                    source: text,
+                });
+            }
+        }
+
+        if let Some(titles) = chart_titles.get(&sheet_name) {
+            for (position, title) in titles.iter().enumerate() {
+                if let Some(title) = title {
+                    strings.push(TranslatableString {
+                        id: format!("{sheet_name}#chart{}", position + 1),
+                        kind: StringKind::ChartTitle,
+                        sheet: sheet_name.clone(),
+                        source: title.clone(),
+                    });
+                }
+            }
+        }
+    }
+    Ok(strings)
+}
+
+/// Render extracted strings as CSV with an empty `target` column to fill in.
+pub fn strings_to_csv(strings: &[TranslatableString]) -> String {
+    let options = CsvOptions::default();
+    let mut buffer = options.start();
+    options.push_text_row(&mut buffer, ["id", "kind", "sheet", "source", "target"]);
+    for string in strings {
+        let kind = match string.kind {
+            StringKind::Cell => "cell",
+            StringKind::SheetName => "sheet_name",
+            StringKind::HeaderFooter => "header_footer",
+            StringKind::ChartTitle => "chart_title",
+        };
+        options.push_text_row(
+            &mut buffer,
+            [string.id.as_str(), kind, &string.sheet, &string.source, ""],
+        );
+    }
+    buffer
+}
+
+/// Parse a filled-in translation table: JSON (`{"strings":[...]}` or a bare
+/// array) or CSV with a header row naming `id` and `target` columns.
+pub fn parse_translation_table(raw: &str) -> Result<Vec<StringTranslation>> {
+    let trimmed = raw.trim_start_matches('\u{feff}').trim_start();
+    if trimmed.starts_with('{') || trimmed.starts_with('[') {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Table {
+            Wrapped { strings: Vec<StringTranslation> },
+            Bare(Vec<StringTranslation>),
+        }
+        let table: Table = serde_json::from_str(trimmed).context(
+            "translation table is not valid JSON: expected {\"strings\":[{\"id\",\"target\"}]} or an array of entries",
+        )?;
+        return Ok(match table {
+            Table::Wrapped { strings } | Table::Bare(strings) => strings,
+        });
+    }
+
+    let mut records = parse_csv_records(trimmed)?.into_iter();
+    let header = records
+        .next()
+        .ok_or_else(|| anyhow!("translation table is empty"))?;
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|field| field.trim().eq_ignore_ascii_case(name))
+    };
+    let (Some(id_column), Some(target_column)) = (column("id"), column("target")) else {
+        bail!("translation table CSV needs a header row with 'id' and 'target' columns");
+    };
+    let source_column = column("source");
+    Ok(records
+        .filter(|record| record.iter().any(|field| !field.is_empty()))
+        .map(|record| StringTranslation {
+            id: record.get(id_column).cloned().unwrap_or_default(),
+            source: source_column
+                .and_then(|index| record.get(index).cloned())
+                .filter(|source| !source.is_empty()),
+            target: record.get(target_column).cloned(),
+        })
+        .collect())
+}
+
+/// Text worth translating: has a letter, and is not a number, a boolean or
+/// an error value.
+fn is_translatable(text: &str) -> bool {
+    let trimmed = text.trim();
+    trimmed.chars().any(char::is_alphabetic)
+        && trimmed.parse::<f64>().is_err()
+        && !matches!(trimmed, "TRUE" | "FALSE")
+        && !EXCEL_ERRORS.contains(&trimmed)
+}
+
+fn header_footer_text(sheet: &umya_spreadsheet::Worksheet, slot: &str) -> String {
+    let header_footer = sheet.get_header_footer();
+    match slot {
+        "header" => header_footer.get_odd_header().get_value(),
+        "footer" => header_footer.get_odd_footer().get_value(),
+        "even_header" => header_footer.get_even_header().get_value(),
+        "even_footer" => header_footer.get_even_footer().get_value(),
+        "first_header" => header_footer.get_first_header().get_value(),
+        _ => header_footer.get_first_footer().get_value(),
+    }
+    .to_string()
+}
+
+fn set_header_footer_text(sheet: &mut umya_spreadsheet::Worksheet, slot: &str, text: String) {
+    let header_footer = sheet.get_header_footer_mut();
+    match slot {
+        "header" => {
+            header_footer.get_odd_header_mut().set_value(text);
+        }
+        "footer" => {
+            header_footer.get_odd_footer_mut().set_value(text);
+        }
+        "even_header" => {
+            header_footer.get_even_header_mut().set_value(text);
+        }
+        "even_footer" => {
+            header_footer.get_even_footer_mut().set_value(text);
+        }
+        "first_header" => {
+            header_footer.get_first_header_mut().set_value(text);
+        }
+        _ => {
+            header_footer.get_first_footer_mut().set_value(text);
+        }
+    }
+}
+
+/// Write `translations` into the workbook at `path`, rewriting it in place.
+pub fn apply_strings(
+    path: &Path,
+    translations: &[StringTranslation],
+) -> Result<ApplyStringsSummary> {
+    let mut summary = ApplyStringsSummary::default();
+    let mut pending: Vec<(StringTarget, &StringTranslation, &str)> = Vec::new();
+    for translation in translations {
+        let Some(target_text) = translation.target.as_deref().filter(|t| !t.is_empty()) else {
+            summary.skipped_untranslated += 1;
+            continue;
+        };
+        let Some(target) = StringTarget::parse(&translation.id) else {
+            bail!(
+                "'{}' is not a string id from extract-strings",
+                translation.id
+            );
+        };
+        pending.push((target, translation, target_text));
+    }
+
+    // Sheet renames, checked up front so a bad name rejects the whole table.
+    let mut book = umya_spreadsheet::reader::xlsx::read(path)
+        .with_context(|| format!("failed to read workbook '{}'", path.display()))?;
+    let sheet_names: Vec<String> = book
+        .get_sheet_collection()
+        .iter()
+        .map(|sheet| sheet.get_name().to_string())
+        .collect();
+    let mut renames: Vec<(String, String)> = Vec::new();
+    for (target, translation, text) in &pending {
+        let StringTarget::SheetName { sheet } = target else {
+            continue;
+        };
+        let text = text.trim();
+        if !sheet_names.contains(sheet) || stale(translation, sheet) {
+            summary.skip_stale(&translation.id);
+            continue;
+        }
+        if text == sheet {
+            summary.skipped_unchanged += 1;
+            continue;
+        }
+        validate_sheet_name(text).map_err(|error| {
+            anyhow!(
+                "translation for '{}' is not a valid sheet name: {error}",
+                translation.id
+            )
+        })?;
+        // Renames apply one at a time, so swapping two names is rejected too.
+        let taken = sheet_names
+            .iter()
+            .any(|name| name.eq_ignore_ascii_case(text) && name != sheet)
+            || renames
+                .iter()
+                .any(|(_, new_name)| new_name.eq_ignore_ascii_case(text));
+        if taken {
+            bail!(
+                "translation for '{}' renames the sheet to '{}', which another sheet already uses",
+                translation.id,
+                text
+            );
+        }
+        renames.push((sheet.clone(), text.to_string()));
+    }
+
+    // Chart titles and chart series references, in the raw package.
+    let chart_edits: Vec<(&String, usize, &StringTranslation, &str)> = pending
+        .iter()
+        .filter_map(|(target, translation, text)| match target {
+            StringTarget::ChartTitle { sheet, index } => Some((sheet, *index, *translation, *text)),
+            _ => None,
+        })
+        .collect();
+    if !chart_edits.is_empty() || !renames.is_empty() {
+        drop(book);
+        apply_chart_edits(path, &chart_edits, &renames, &mut summary)?;
+        book = umya_spreadsheet::reader::xlsx::read(path)
+            .with_context(|| format!("failed to read workbook '{}'", path.display()))?;
+    }
+
+    for (target, translation, text) in &pending {
+        match target {
+            StringTarget::Cell { sheet, address } => {
+                let Some(worksheet) = book.get_sheet_by_name_mut(sheet) else {
+                    summary.skip_stale(&translation.id);
+                    continue;
+                };
+                let Some(cell) = worksheet.get_cell(address.as_str()) else {
+                    summary.skip_stale(&translation.id);
+                    continue;
+                };
+                let current = cell.get_value().to_string();
+                if cell.is_formula() || stale(translation, &current) {
+                    summary.skip_stale(&translation.id);
+                    continue;
+                }
+                if current == *text {
+                    summary.skipped_unchanged += 1;
+                    continue;
+                }
+                worksheet
+                    .get_cell_mut(address.as_str())
+                    .set_value_string(text.to_string());
+                summary.cells_updated += 1;
+                summary.strings_applied += 1;
+            }
+            StringTarget::HeaderFooter { sheet, slot } => {
+                let Some(worksheet) = book.get_sheet_by_name_mut(sheet) else {
+                    summary.skip_stale(&translation.id);
+                    continue;
+                };
+                let current = header_footer_text(worksheet, slot);
+                if current.is_empty() || stale(translation, &current) {
+                    summary.skip_stale(&translation.id);
+                    continue;
+                }
+                if current == *text {
+                    summary.skipped_unchanged += 1;
+                    continue;
+                }
+                set_header_footer_text(worksheet, slot, text.to_string());
+                summary.headers_footers_updated += 1;
+                summary.strings_applied += 1;
+            }
+            StringTarget::SheetName { .. } | StringTarget::ChartTitle { .. } => {}
+        }
+    }
+
+    let mut builder = FormulaParseDiagnosticsBuilder::new(FormulaParsePolicy::Warn);
+    for (old_name, new_name) in &renames {
+        rename_sheet_with_references(
+            &mut book,
+            old_name,
+            new_name,
+            FormulaParsePolicy::Warn,
+            &mut builder,
+        )?;
+        summary.sheets_renamed += 1;
+        summary.strings_applied += 1;
+    }
+
+    if summary.cells_updated > 0 || summary.headers_footers_updated > 0 || !renames.is_empty() {
+        save_workbook(&book, path)?;
+    }
+    Ok(summary)
+}
+
+impl ApplyStringsSummary {
+    fn skip_stale(&mut self, id: &str) {
+        self.skipped_stale += 1;
+        self.warnings.push(format!(
+            "WARN_STRING_STALE: '{id}' was not applied: the workbook no longer has that text; extract the strings again"
+        ));
+    }
+}
+
+fn stale(translation: &StringTranslation, current: &str) -> bool {
+    translation
+        .source
+        .as_deref()
+        .is_some_and(|source| source != current)
+}
+
+/// Chart titles by sheet name, one slot per chart in drawing order; `None`
+/// for charts without a rich-text title (no title, or one taken from a
+/// cell).
+fn read_chart_titles<R: Read + Seek>(
+    archive: &mut zip::ZipArchive<R>,
+) -> Result<HashMap<String, Vec<Option<String>>>> {
+    let mut titles = HashMap::new();
+    for (sheet_name, chart_parts) in chart_parts_by_sheet(archive)? {
+        let sheet_titles = chart_parts
+            .into_iter()
+            .map(|part| {
+                let xml = read_zip_text(archive, &part).ok()?;
+                title_text(&xml)
+            })
+            .collect();
+        titles.insert(sheet_name, sheet_titles);
+    }
+    Ok(titles)
+}
+
+/// Chart parts of each sheet, in the order its drawing anchors them.
+fn chart_parts_by_sheet<R: Read + Seek>(
+    archive: &mut zip::ZipArchive<R>,
+) -> Result<BTreeMap<String, Vec<String>>> {
+    let chart_ref_re =
+        Regex::new(r#"<(?:\w+:)?chart\b[^>]*\br:id="([^"]+)""#).expect("valid chart ref regex");
+    let mut charts = BTreeMap::new();
+    for (sheet_name, sheet_part) in resolve_sheet_parts(archive)? {
+        let sheet_rels = part_relationships(archive, &sheet_part);
+        let mut sheet_charts = Vec::new();
+        for (rel_type, drawing_part) in sheet_rels.values() {
+            if !rel_type.ends_with("/drawing") {
+                continue;
+            }
+            let Ok(drawing_xml) = read_zip_text(archive, drawing_part) else {
+                continue;
+            };
+            let drawing_rels = part_relationships(archive, drawing_part);
+            for caps in chart_ref_re.captures_iter(&drawing_xml) {
+                if let Some((rel_type, chart_part)) = drawing_rels.get(&caps[1])
+                    && rel_type.ends_with("/chart")
+                {
+                    sheet_charts.push(chart_part.clone());
+                }
+            }
+        }
+        if !sheet_charts.is_empty() {
+            charts.insert(sheet_name, sheet_charts);
+        }
+    }
+    Ok(charts)
+}
+
+/// Internal relationships of `part` as `id -> (type, part path)`.
+fn part_relationships<R: Read + Seek>(
+    archive: &mut zip::ZipArchive<R>,
+    part: &str,
+) -> HashMap<String, (String, String)> {
+    let (dir, file) = part.rsplit_once('/').unwrap_or(("", part));
+    let Ok(rels_xml) = read_zip_text(archive, &format!("{dir}/_rels/{file}.rels")) else {
+        return HashMap::new();
+    };
+    relationship_tag_regex()
+        .find_iter(&rels_xml)
+        .filter_map(|tag| {
+            let attrs = parse_attributes(tag.as_str());
+            if attrs
+                .get("TargetMode")
+                .is_some_and(|mode| mode == "External")
+            {
+                return None;
+            }
+            Some((
+                attrs.get("Id")?.clone(),
+                (
+                    attrs.get("Type").cloned().unwrap_or_default(),
+                    resolve_part_path(dir, attrs.get("Target")?),
+                ),
+            ))
+        })
+        .collect()
+}
+
+/// The chart's own title element (axis titles sit inside `plotArea`).
+fn chart_title_span(xml: &str) -> Option<(usize, usize)> {
+    let end_of_search = xml.find("<c:plotArea").unwrap_or(xml.len());
+    let title_re =
+        Regex::new(r"(?s)<(?:\w+:)?title>.*?</(?:\w+:)?title>").expect("valid title regex");
+    let found = title_re.find(&xml[..end_of_search])?;
+    Some((found.start(), found.end()))
+}
+
+/// Paragraphs of the title's rich text, joined by line breaks.
+fn title_text(xml: &str) -> Option<String> {
+    let (start, end) = chart_title_span(xml)?;
+    let title = &xml[start..end];
+    if !title.contains("rich>") {
+        return None;
+    }
+    let text = paragraph_regex()
+        .captures_iter(title)
+        .map(|paragraph| {
+            run_text_regex()
+                .captures_iter(&paragraph[0])
+                .map(|run| unescape_xml(&run[2]))
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    (!text.trim().is_empty()).then_some(text)
+}
+
+fn paragraph_regex() -> Regex {
+    Regex::new(r"(?s)<(?:\w+:)?p\b[^>]*>.*?</(?:\w+:)?p>").expect("valid paragraph regex")
+}
+
+fn run_text_regex() -> Regex {
+    Regex::new(r"(?s)<((?:\w+:)?t)>(.*?)</(?:\w+:)?t>").expect("valid run text regex")
+}
+
+/// The title with its text replaced: each line of `text` goes into the
+/// first run of the matching paragraph and later runs are emptied, so run
+/// formatting carries over. With more lines than paragraphs, the extra
+/// lines join the last paragraph.
+fn replace_title_text(title: &str, text: &str) -> String {
+    let paragraph_count = paragraph_regex().find_iter(title).count().max(1);
+    let mut lines: Vec<String> = text.split('\n').map(str::to_string).collect();
+    if lines.len() > paragraph_count {
+        let tail = lines.split_off(paragraph_count - 1).join(" ");
+        lines.push(tail);
+    }
+    let mut paragraph_index = 0;
+    paragraph_regex()
+        .replace_all(title, |paragraph: &Captures| {
+            let line = lines.get(paragraph_index).cloned().unwrap_or_default();
+            paragraph_index += 1;
+            let mut first = true;
+            run_text_regex()
+                .replace_all(&paragraph[0], |run: &Captures| {
+                    let content = if first {
+                        escape_xml_attribute(&line)
+                    } else {
+                        String::new()
+                    };
+                    first = false;
+                    format!("<{0}>{content}</{0}>", &run[1])
+                })
+                .into_owned()
+        })
+        .into_owned()
+}
+
+/// Rewrite chart titles and, for renamed sheets, the sheet prefixes of
+/// chart series references (`<c:f>`).
+fn apply_chart_edits(
+    path: &Path,
+    edits: &[(&String, usize, &StringTranslation, &str)],
+    renames: &[(String, String)],
+    summary: &mut ApplyStringsSummary,
+) -> Result<()> {
+    let mut archive = zip::ZipArchive::new(fs::File::open(path)?)?;
+    let charts = chart_parts_by_sheet(&mut archive)?;
+    let mut rewritten: BTreeMap<String, String> = BTreeMap::new();
+
+    for (sheet, index, translation, text) in edits {
+        let Some(part) = charts
+            .get(sheet.as_str())
+            .and_then(|parts| parts.get(index - 1))
+        else {
+            summary.skip_stale(&translation.id);
+            continue;
+        };
+        let xml = match rewritten.get(part) {
+            Some(xml) => xml.clone(),
+            None => read_zip_text(&mut archive, part)?,
+        };
+        let Some(current) = title_text(&xml) else {
+            summary.skip_stale(&translation.id);
+            continue;
+        };
+        if stale(translation, &current) {
+            summary.skip_stale(&translation.id);
+            continue;
+        }
+        if current == *text {
+            summary.skipped_unchanged += 1;
+            continue;
+        }
+        let (start, end) = chart_title_span(&xml).expect("title found above");
+        let updated = format!(
+            "{}{}{}",
+            &xml[..start],
+            replace_title_text(&xml[start..end], text),
+            &xml[end..]
+        );
+        rewritten.insert(part.clone(), updated);
+        summary.chart_titles_updated += 1;
+        summary.strings_applied += 1;
+    }
+
+    if !renames.is_empty() {
+        let formula_re =
+            Regex::new(r"(?s)<((?:\w+:)?f)>(.*?)</(?:\w+:)?f>").expect("valid chart formula regex");
+        for part in charts.values().flatten() {
+            let xml = match rewritten.get(part) {
+                Some(xml) => xml.clone(),
+                None => read_zip_text(&mut archive, part)?,
+            };
+            let updated = formula_re
+                .replace_all(&xml, |caps: &Captures| {
+                    let mut formula = unescape_xml(&caps[2]);
+                    for (old_name, new_name) in renames {
+                        formula = replace_sheet_prefix(&formula, old_name, new_name);
+                    }
+                    format!("<{0}>{1}</{0}>", &caps[1], escape_xml_attribute(&formula))
+                })
+                .into_owned();
+            if updated != xml {
+                rewritten.insert(part.clone(), updated);
+            }
+        }
+    }
+    drop(archive);
+
+    if !rewritten.is_empty() {
+        rewrite_package(
+            path,
+            rewritten
+                .into_iter()
+                .map(|(part, xml)| (part, Some(xml.into_bytes())))
+                .collect(),
+        )?;
+    }
+    Ok(())
+}
+
+/// `formula` with references to sheet `old_name` pointed at `new_name`.
+fn replace_sheet_prefix(formula: &str, old_name: &str, new_name: &str) -> String {
+    let quoted = regex::escape(&format!("'{}'!", old_name.replace('\'', "''")));
+    let bare = regex::escape(&format!("{old_name}!"));
+    let prefix_re =
+        Regex::new(&format!(r"(^|[(,=\s])(?:{quoted}|{bare})")).expect("valid sheet prefix regex");
+    let new_prefix = format_sheet_prefix_for_formula(new_name);
+    prefix_re
+        .replace_all(formula, |caps: &Captures| {
+            format!("{}{new_prefix}", &caps[1])
+        })
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn string_ids_parse_to_their_targets() {
+        assert_eq!(
+            StringTarget::parse("Q1 Plan!b7"),
+            Some(StringTarget::Cell {
+                sheet: "Q1 Plan".to_string(),
+                address: "B7".to_string()
+            })
+        );
+        assert_eq!(
+            StringTarget::parse("sheet:Q1 Plan"),
+            Some(StringTarget::SheetName {
+                sheet: "Q1 Plan".to_string()
+            })
+        );
+        assert_eq!(
+            StringTarget::parse("Notes #2#first_footer"),
+            Some(StringTarget::HeaderFooter {
+                sheet: "Notes #2".to_string(),
+                slot: "first_footer"
+            })
+        );
+        assert_eq!(
+            StringTarget::parse("Sales#chart3"),
+            Some(StringTarget::ChartTitle {
+                sheet: "Sales".to_string(),
+                index: 3
+            })
+        );
+        assert_eq!(StringTarget::parse("Sales#chart0"), None);
+        assert_eq!(StringTarget::parse("Sales"), None);
+    }
+
+    #[test]
+    fn chart_title_text_is_read_and_replaced_per_paragraph() {
+        let xml = r#"<c:chartSpace><c:chart><c:title><c:tx><c:rich><a:bodyPr/><a:p><a:pPr/><a:r><a:rPr b="1"/><a:t>Revenue</a:t></a:r><a:r><a:t> by region</a:t></a:r></a:p><a:p><a:r><a:t>FY24</a:t></a:r></a:p></c:rich></c:tx></c:title><c:plotArea><c:valAx><c:title><c:tx><c:rich><a:p><a:r><a:t>EUR</a:t></a:r></a:p></c:rich></c:tx></c:title></c:valAx></c:plotArea></c:chart></c:chartSpace>"#;
+        assert_eq!(title_text(xml).as_deref(), Some("Revenue by region\nFY24"));
+
+        let (start, end) = chart_title_span(xml).unwrap();
+        let title = replace_title_text(&xml[start..end], "Umsatz nach Region\nGJ24");
+        assert!(title.contains(r#"<a:rPr b="1"/><a:t>Umsatz nach Region</a:t>"#));
+        assert!(title.contains("<a:t></a:t>"));
+        assert!(title.contains("<a:t>GJ24</a:t>"));
+        let updated = format!("{}{}{}", &xml[..start], title, &xml[end..]);
+        assert_eq!(
+            title_text(&updated).as_deref(),
+            Some("Umsatz nach Region\nGJ24")
+        );
+        assert!(updated.contains("<a:t>EUR</a:t>"), "axis title untouched");
+    }
+
+    #[test]
+    fn translation_tables_parse_from_json_and_csv() {
+        let json = r#"{"strings":[{"id":"Sheet1!A1","kind":"cell","sheet":"Sheet1","source":"Total","target":"Gesamt"}]}"#;
+        let parsed = parse_translation_table(json).unwrap();
+        assert_eq!(parsed[0].id, "Sheet1!A1");
+        assert_eq!(parsed[0].target.as_deref(), Some("Gesamt"));
+
+        let csv = "id,kind,sheet,source,target\nSheet1!A1,cell,Sheet1,\"Total, net\",\"Gesamt, netto\"\nsheet:Sheet1,sheet_name,Sheet1,,Blatt1\n";
+        let parsed = parse_translation_table(csv).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].source.as_deref(), Some("Total, net"));
+        assert_eq!(parsed[0].target.as_deref(), Some("Gesamt, netto"));
+        assert_eq!(parsed[1].source, None);
+
+        assert!(parse_translation_table("id,source\nSheet1!A1,Total\n").is_err());
+    }
+
+    #[test]
+    fn sheet_prefixes_follow_renames() {
+        assert_eq!(
+            replace_sheet_prefix("Sales!$B$2:$B$5", "Sales", "Ventas"),
+            "Ventas!$B$2:$B$5"
+        );
+        assert_eq!(
+            replace_sheet_prefix("'Q1 Sales'!$A$2,MySales!$A$1", "Q1 Sales", "Ventas T1"),
+            "'Ventas T1'!$A$2,MySales!$A$1"
+        );
+        assert_eq!(
+            replace_sheet_prefix("MySales!$A$1", "Sales", "Ventas"),
+            "MySales!$A$1"
+        );
+    }
+}
@@ -0,0 +1,308 @@
+//! Locale-aware parsing of typed-in cell values.
+//!
+//! `edit --locale de-DE B2=1.234,56` and `fill_range` ops carrying a `locale`
+//! read numbers and dates the way a user of that locale types them and store
+//! a number instead of text; dates become serials with a date format matching
+//! what was typed. Digits that do not follow the locale's separators are
+//! stored as text rather than guessed at, so `1.5` under `de-DE` is neither
+//! fifteen nor one and a half.
+
+use crate::workbook::iso_to_excel_serial;
+use anyhow::{Result, bail};
+
+const SPACE_GROUPS: &[char] = &[' ', '\u{a0}', '\u{202f}'];
+const APOSTROPHE_GROUPS: &[char] = &['\'', '\u{2019}'];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DateOrder {
+    DayMonthYear,
+    MonthDayYear,
+    YearMonthDay,
+}
+
+/// Number and date conventions of one locale.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValueLocale {
+    tag: String,
+    decimal_separator: char,
+    group_separators: &'static [char],
+    date_order: DateOrder,
+}
+
+/// A value recognised under a locale.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LocalizedValue {
+    Number(f64),
+    /// ISO `YYYY-MM-DD` or `YYYY-MM-DDTHH:MM:SS`, with the number format that
+    /// displays it the way it was typed.
+    Date {
+        iso: String,
+        number_format: String,
+    },
+}
+
+impl ValueLocale {
+    /// Conventions for a BCP 47 tag such as `de-DE`, `fr-FR` or `en-US`
+    /// (`de_DE` is accepted too). A bare language uses its most common region.
+    pub fn parse(tag: &str) -> Result<Self> {
+        let mut parts = tag.trim().split(['-', '_']);
+        let language = parts.next().unwrap_or_default().to_ascii_lowercase();
+        let region = parts.next().map(|region| region.to_ascii_uppercase());
+        let region = region.as_deref();
+
+        use DateOrder::*;
+        let (decimal_separator, group_separators, date_order): (char, &'static [char], _) = match (
+            language.as_str(),
+            region,
+        ) {
+            ("en", None | Some("US" | "PH")) => ('.', &[','], MonthDayYear),
+            ("en", Some(_)) => ('.', &[','], DayMonthYear),
+            ("de" | "it" | "fr", Some("CH" | "LI")) => ('.', APOSTROPHE_GROUPS, DayMonthYear),
+            ("es", Some("MX" | "US")) => ('.', &[','], DayMonthYear),
+            (
+                "de" | "nl" | "es" | "it" | "pt" | "da" | "id" | "tr" | "el" | "ro" | "hr" | "sl"
+                | "sr",
+                _,
+            ) => (',', &['.'], DayMonthYear),
+            ("sv" | "hu" | "lt", _) => (',', SPACE_GROUPS, YearMonthDay),
+            (
+                "fr" | "nb" | "nn" | "no" | "fi" | "pl" | "cs" | "sk" | "ru" | "uk" | "bg" | "lv"
+                | "et",
+                _,
+            ) => (',', SPACE_GROUPS, DayMonthYear),
+            ("ja" | "zh" | "ko", _) => ('.', &[','], YearMonthDay),
+            _ => bail!(
+                "unsupported locale '{tag}' (expected a tag such as en-US, en-GB, de-DE, fr-FR, es-ES, it-IT, nl-NL, pt-BR, sv-SE or ja-JP)"
+            ),
+        };
+
+        Ok(Self {
+            tag: tag.trim().to_string(),
+            decimal_separator,
+            group_separators,
+            date_order,
+        })
+    }
+
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    /// The number or date `text` spells in this locale, if any.
+    pub fn parse_value(&self, text: &str) -> Option<LocalizedValue> {
+        let text = text.trim();
+        if let Some(number) = self.parse_number(text) {
+            return Some(LocalizedValue::Number(number));
+        }
+        self.parse_date(text)
+    }
+
+    /// Write `text` to `cell` as a number or date when it spells one in this
+    /// locale. Other numeric-looking text is kept as a string; anything else
+    /// goes through the usual value inference (booleans, plain text).
+    pub fn set_cell_value(
+        &self,
+        cell: &mut umya_spreadsheet::Cell,
+        text: &str,
+        use_1904_system: bool,
+    ) {
+        match self.parse_value(text) {
+            Some(LocalizedValue::Number(number)) => {
+                cell.set_value_number(number);
+            }
+            Some(LocalizedValue::Date { iso, number_format }) => {
+                match iso_to_excel_serial(&iso, use_1904_system) {
+                    Some(serial) => {
+                        cell.set_value_number(serial);
+                        cell.get_style_mut()
+                            .get_number_format_mut()
+                            .set_format_code(number_format);
+                    }
+                    None => {
+                        cell.set_value_string(text);
+                    }
+                }
+            }
+            None if text.trim().parse::<f64>().is_ok() => {
+                cell.set_value_string(text);
+            }
+            None => {
+                cell.set_value(text);
+            }
+        }
+    }
+
+    fn parse_number(&self, text: &str) -> Option<f64> {
+        let (negative, unsigned) = match text.chars().next()? {
+            '-' | '\u{2212}' => (true, &text[text.chars().next()?.len_utf8()..]),
+            '+' => (false, &text[1..]),
+            _ => (false, text),
+        };
+        let (integer, fraction) = match unsigned.split_once(self.decimal_separator) {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (unsigned, None),
+        };
+
+        let mut digits = String::with_capacity(unsigned.len() + 1);
+        if negative {
+            digits.push('-');
+        }
+        if !integer.is_empty() {
+            digits.push_str(&self.ungroup(integer)?);
+        }
+        match fraction {
+            Some(fraction) if !fraction.is_empty() && is_ascii_digits(fraction) => {
+                digits.push('.');
+                digits.push_str(fraction);
+            }
+            Some(_) => return None,
+            None if integer.is_empty() => return None,
+            None => {}
+        }
+        if digits.is_empty() || digits == "-" {
+            return None;
+        }
+        digits.parse().ok()
+    }
+
+    /// Integer digits with group separators removed, when the grouping is
+    /// well formed: one to three leading digits, then groups of exactly three
+    /// split by a single kind of separator.
+    fn ungroup(&self, integer: &str) -> Option<String> {
+        if is_ascii_digits(integer) {
+            return Some(integer.to_string());
+        }
+        let separator = integer.chars().find(|ch| !ch.is_ascii_digit())?;
+        if !self.group_separators.contains(&separator) {
+            return None;
+        }
+        let mut groups = integer.split(separator);
+        let first = groups.next()?;
+        if first.is_empty() || first.len() > 3 || !is_ascii_digits(first) {
+            return None;
+        }
+        let mut digits = first.to_string();
+        for group in groups {
+            if group.len() != 3 || !is_ascii_digits(group) {
+                return None;
+            }
+            digits.push_str(group);
+        }
+        Some(digits)
+    }
+
+    fn parse_date(&self, text: &str) -> Option<LocalizedValue> {
+        let (date, time) = match text.split_once(char::is_whitespace) {
+            Some((date, time)) => (date, Some(time.trim())),
+            None => (text, None),
+        };
+        let separator = date.chars().find(|ch| matches!(ch, '.' | '/' | '-'))?;
+        let parts: Vec<&str> = date.split(separator).collect();
+        if parts.len() != 3 || parts.iter().any(|part| !is_ascii_digits(part)) {
+            return None;
+        }
+
+        // A leading four-digit year reads as year-month-day in every locale.
+        let order = if parts[0].len() == 4 {
+            DateOrder::YearMonthDay
+        } else {
+            self.date_order
+        };
+        let (year, month, day) = match order {
+            DateOrder::DayMonthYear => (parts[2], parts[1], parts[0]),
+            DateOrder::MonthDayYear => (parts[2], parts[0], parts[1]),
+            DateOrder::YearMonthDay => (parts[0], parts[1], parts[2]),
+        };
+        if month.len() > 2 || day.len() > 2 {
+            return None;
+        }
+        let full_year: i32 = match year.len() {
+            4 => year.parse().ok()?,
+            // Excel's two-digit year window: 00-29 is 20xx, 30-99 is 19xx.
+            2 => match year.parse::<i32>().ok()? {
+                short @ 0..=29 => 2000 + short,
+                short => 1900 + short,
+            },
+            _ => return None,
+        };
+        let date_value =
+            chrono::NaiveDate::from_ymd_opt(full_year, month.parse().ok()?, day.parse().ok()?)?;
+
+        let year_code = if year.len() == 4 { "yyyy" } else { "yy" };
+        let separator = separator.to_string();
+        let mut number_format = match order {
+            DateOrder::DayMonthYear => ["dd", "mm", year_code].join(&separator),
+            DateOrder::MonthDayYear => ["mm", "dd", year_code].join(&separator),
+            DateOrder::YearMonthDay => [year_code, "mm", "dd"].join(&separator),
+        };
+        let mut iso = date_value.format("%Y-%m-%d").to_string();
+
+        if let Some(time) = time {
+            let (time_value, with_seconds) =
+                match chrono::NaiveTime::parse_from_str(time, "%H:%M:%S") {
+                    Ok(value) => (value, true),
+                    Err(_) => (
+                        chrono::NaiveTime::parse_from_str(time, "%H:%M").ok()?,
+                        false,
+                    ),
+                };
+            iso.push_str(&time_value.format("T%H:%M:%S").to_string());
+            number_format.push_str(if with_seconds { " hh:mm:ss" } else { " hh:mm" });
+        }
+
+        Some(LocalizedValue::Date { iso, number_format })
+    }
+}
+
+fn is_ascii_digits(text: &str) -> bool {
+    !text.is_empty() && text.bytes().all(|byte| byte.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn number(tag: &str, text: &str) -> Option<f64> {
+        match ValueLocale::parse(tag).expect("locale").parse_value(text) {
+            Some(LocalizedValue::Number(number)) => Some(number),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn numbers_follow_locale_separators() {
+        assert_eq!(number("de-DE", "1.234,56"), Some(1234.56));
+        assert_eq!(number("de-DE", "-0,5"), Some(-0.5));
+        assert_eq!(number("fr-FR", "1\u{202f}234,5"), Some(1234.5));
+        assert_eq!(number("de-CH", "1'234.5"), Some(1234.5));
+        assert_eq!(number("en-US", "1,234.56"), Some(1234.56));
+        assert_eq!(number("de-DE", "1.5"), None);
+        assert_eq!(number("en-US", "1,23"), None);
+    }
+
+    #[test]
+    fn dates_follow_locale_order_and_keep_typed_layout() {
+        let german = ValueLocale::parse("de_DE").expect("locale");
+        assert_eq!(
+            german.parse_value("31.12.2024"),
+            Some(LocalizedValue::Date {
+                iso: "2024-12-31".to_string(),
+                number_format: "dd.mm.yyyy".to_string(),
+            })
+        );
+        let american = ValueLocale::parse("en-US").expect("locale");
+        assert_eq!(
+            american.parse_value("12/31/24 14:30"),
+            Some(LocalizedValue::Date {
+                iso: "2024-12-31T14:30:00".to_string(),
+                number_format: "mm/dd/yy hh:mm".to_string(),
+            })
+        );
+        assert_eq!(american.parse_value("31/12/2024"), None);
+    }
+
+    #[test]
+    fn unknown_locales_are_rejected() {
+        assert!(ValueLocale::parse("xx-YY").is_err());
+    }
+}
@@ -0,0 +1,153 @@
+//! Workbook-level properties from `xl/workbook.xml`.
+//!
+//! umya-spreadsheet does not expose `workbookPr` or `calcPr`, so flags that change how cell
+//! values must be interpreted are read from the raw part.
+
+use super::ooxml::{read_zip_text, rewrite_package, rewrite_package_bytes};
+use anyhow::Result;
+use regex::Regex;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Cursor, Read, Seek};
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Days between the 1900 and 1904 epochs: serial 0 in the 1904 system is
+/// serial 1462 in the 1900 system.
+pub const EXCEL_1904_OFFSET_DAYS: u32 = 1462;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WorkbookProps {
+    /// Date serials count days from 1904-01-01 rather than 1900-01-00. Set by
+    /// workbooks created in older Mac Excel.
+    pub date1904: bool,
+    /// Calculated values are stored rounded to their displayed number format
+    /// (`calcPr fullPrecision="0"`, "Set precision as displayed").
+    pub precision_as_displayed: bool,
+}
+
+/// Read properties of the workbook at `path`.
+pub fn read_workbook_props(path: &Path) -> Result<WorkbookProps> {
+    read_workbook_props_from_reader(fs::File::open(path)?)
+}
+
+pub fn read_workbook_props_from_reader<R: Read + Seek>(reader: R) -> Result<WorkbookProps> {
+    let mut archive = zip::ZipArchive::new(reader)?;
+    let xml = read_zip_text(&mut archive, "xl/workbook.xml")?;
+    Ok(parse_workbook_props(&xml))
+}
+
+/// Set the `date1904` flag of the workbook at `path`. umya always writes its
+/// own `workbookPr`, so callers saving a 1904-based workbook through umya use
+/// this to restore the flag. Leaves the file untouched if it already matches.
+pub fn write_date1904(path: &Path, date1904: bool) -> Result<()> {
+    let mut archive = zip::ZipArchive::new(fs::File::open(path)?)?;
+    let Some(edits) = date1904_edits(&mut archive, date1904)? else {
+        return Ok(());
+    };
+    drop(archive);
+    rewrite_package(path, edits)
+}
+
+/// Workbook `bytes` with the `date1904` flag set, or `None` when the flag
+/// already has that value.
+pub fn rewrite_date1904(bytes: &[u8], date1904: bool) -> Result<Option<Vec<u8>>> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes))?;
+    let Some(edits) = date1904_edits(&mut archive, date1904)? else {
+        return Ok(None);
+    };
+    Ok(Some(rewrite_package_bytes(bytes, edits)?))
+}
+
+fn date1904_edits<R: Read + Seek>(
+    archive: &mut zip::ZipArchive<R>,
+    date1904: bool,
+) -> Result<Option<HashMap<String, Option<Vec<u8>>>>> {
+    let workbook_xml = read_zip_text(archive, "xl/workbook.xml")?;
+    if parse_workbook_props(&workbook_xml).date1904 == date1904 {
+        return Ok(None);
+    }
+    let workbook_xml = set_date1904(&workbook_xml, date1904);
+    Ok(Some(HashMap::from([(
+        "xl/workbook.xml".to_string(),
+        Some(workbook_xml.into_bytes()),
+    )])))
+}
+
+fn set_date1904(xml: &str, date1904: bool) -> String {
+    static WORKBOOK_PR: OnceLock<Regex> = OnceLock::new();
+    static DATE1904_ATTRIBUTE: OnceLock<Regex> = OnceLock::new();
+    static ANCHOR: OnceLock<Regex> = OnceLock::new();
+    let pr_re = WORKBOOK_PR.get_or_init(|| {
+        Regex::new(r"<(?:\w+:)?workbookPr\b[^>]*?(/?)>").expect("valid workbookPr regex")
+    });
+    let value = if date1904 { "1" } else { "0" };
+    if let Some(caps) = pr_re.captures(xml) {
+        let tag = caps.get(0).expect("whole match");
+        let attr_re = DATE1904_ATTRIBUTE.get_or_init(|| {
+            Regex::new(r#"\sdate1904\s*=\s*["'][^"']*["']"#).expect("valid attribute regex")
+        });
+        let rewritten = if attr_re.is_match(tag.as_str()) {
+            attr_re
+                .replace(tag.as_str(), format!(r#" date1904="{value}""#))
+                .into_owned()
+        } else {
+            let close = caps.get(1).map_or("", |m| m.as_str());
+            let head = &tag.as_str()[..tag.as_str().len() - close.len() - 1];
+            format!(r#"{head} date1904="{value}"{close}>"#)
+        };
+        return format!("{}{}{}", &xml[..tag.start()], rewritten, &xml[tag.end()..]);
+    }
+
+    // No workbookPr yet: it follows fileVersion/fileSharing in schema order.
+    let anchor_re = ANCHOR.get_or_init(|| {
+        Regex::new(
+            r"<(?:\w+:)?fileSharing\b[^>]*>|<(?:\w+:)?fileVersion\b[^>]*>|<(?:\w+:)?workbook\b[^>]*>",
+        )
+        .expect("valid anchor regex")
+    });
+    let Some(anchor) = anchor_re.find_iter(xml).last() else {
+        return xml.to_string();
+    };
+    format!(
+        r#"{}<workbookPr date1904="{value}"/>{}"#,
+        &xml[..anchor.end()],
+        &xml[anchor.end()..]
+    )
+}
+
+fn parse_workbook_props(xml: &str) -> WorkbookProps {
+    static WORKBOOK_PR: OnceLock<Regex> = OnceLock::new();
+    static CALC_PR: OnceLock<Regex> = OnceLock::new();
+    let pr_re = WORKBOOK_PR.get_or_init(|| {
+        Regex::new(r"<(?:\w+:)?workbookPr\b[^>]*>").expect("valid workbookPr regex")
+    });
+    let calc_re = CALC_PR
+        .get_or_init(|| Regex::new(r"<(?:\w+:)?calcPr\b[^>]*>").expect("valid calcPr regex"));
+    let date1904 = pr_re
+        .find(xml)
+        .and_then(|tag| bool_attribute(tag.as_str(), "date1904"))
+        .unwrap_or(false);
+    let full_precision = calc_re
+        .find(xml)
+        .and_then(|tag| bool_attribute(tag.as_str(), "fullPrecision"))
+        .unwrap_or(true);
+    WorkbookProps {
+        date1904,
+        precision_as_displayed: !full_precision,
+    }
+}
+
+/// An `xsd:boolean` attribute value, which may be `1`/`0` or `true`/`false`.
+fn bool_attribute(tag: &str, name: &str) -> Option<bool> {
+    static ATTRIBUTE: OnceLock<Regex> = OnceLock::new();
+    let attr_re = ATTRIBUTE.get_or_init(|| {
+        Regex::new(r#"([\w:]+)\s*=\s*["']([^"']*)["']"#).expect("valid attribute regex")
+    });
+    let caps = attr_re.captures_iter(tag).find(|caps| &caps[1] == name)?;
+    match caps[2].trim() {
+        "1" | "true" => Some(true),
+        "0" | "false" => Some(false),
+        _ => None,
+    }
+}
@@ -0,0 +1,692 @@
+//! Bulk cell writes that stream worksheet XML instead of loading the workbook.
+//!
+//! `write-cells` takes a columnar payload — blocks of typed value arrays laid
+//! out from an anchor cell — and merges the values into each target sheet's
+//! `<sheetData>` in a single pass. Other package parts are copied without
+//! being decompressed, so memory and time grow with the size of the written
+//! sheets rather than the whole object model:
+//!
+//! ```json
+//! {"blocks": [{"sheet_name": "Data", "range": "A2",
+//!   "columns": [{"type": "string", "values": ["a", "b"]},
+//!               {"type": "number", "values": [1.5, null]}]}]}
+//! ```
+//!
+//! A `null` leaves the target cell as it is. Strings are stored inline, so
+//! the shared string table is untouched, and replaced cells keep their style.
+
+use super::ooxml::{parse_attributes, read_zip_text, relationship_tag_regex, resolve_sheet_parts};
+use anyhow::{Context, Result, anyhow, bail};
+use quick_xml::Writer;
+use quick_xml::events::{BytesEnd, BytesStart, Event};
+use quick_xml::reader::Reader;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::Instant;
+
+const MAX_ROW: u32 = 1_048_576;
+const MAX_COL: u32 = 16_384;
+pub(crate) const CALC_CHAIN_PART: &str = "xl/calcChain.xml";
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CellBlocksPayload {
+    pub blocks: Vec<CellBlock>,
+}
+
+/// Columns of values written side by side, the first at `range`'s top-left
+/// cell. When `range` spans more than one cell it must match the block's
+/// shape exactly.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CellBlock {
+    pub sheet_name: String,
+    pub range: String,
+    pub columns: Vec<CellColumn>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum CellColumn {
+    Number { values: Vec<Option<f64>> },
+    String { values: Vec<Option<String>> },
+    Bool { values: Vec<Option<bool>> },
+}
+
+impl CellColumn {
+    fn len(&self) -> usize {
+        match self {
+            CellColumn::Number { values } => values.len(),
+            CellColumn::String { values } => values.len(),
+            CellColumn::Bool { values } => values.len(),
+        }
+    }
+
+    fn value(&self, index: usize) -> Option<CellWrite<'_>> {
+        match self {
+            CellColumn::Number { values } => values[index].map(CellWrite::Number),
+            CellColumn::String { values } => values[index].as_deref().map(CellWrite::Text),
+            CellColumn::Bool { values } => values[index].map(CellWrite::Bool),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum CellWrite<'a> {
+    Number(f64),
+    Text(&'a str),
+    Bool(bool),
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WriteCellsSummary {
+    pub blocks: u64,
+    pub sheets_written: u64,
+    pub cells_written: u64,
+    /// Written cells that replaced an existing cell.
+    pub cells_replaced: u64,
+    /// Replaced cells that held a formula.
+    pub formulas_replaced: u64,
+    pub rows_created: u64,
+    /// `null` entries, which leave their cell unchanged.
+    pub nulls_skipped: u64,
+    /// Uncompressed size of the written worksheet parts before and after.
+    pub sheet_bytes_in: u64,
+    pub sheet_bytes_out: u64,
+    pub write_ms: u64,
+}
+
+/// Written values for one sheet, row-major.
+type SheetWrites<'a> = BTreeMap<u32, Vec<(u32, CellWrite<'a>)>>;
+
+/// Check block shapes and references without touching a workbook.
+pub fn validate_cell_blocks(payload: &CellBlocksPayload) -> Result<()> {
+    if payload.blocks.is_empty() {
+        bail!("payload has no blocks");
+    }
+    for (index, block) in payload.blocks.iter().enumerate() {
+        block_origin(block).with_context(|| format!("blocks[{index}]"))?;
+    }
+    Ok(())
+}
+
+/// Top-left cell of `block` after checking its shape fits the sheet.
+fn block_origin(block: &CellBlock) -> Result<(u32, u32)> {
+    let Some(first) = block.columns.first() else {
+        bail!("block for sheet '{}' has no columns", block.sheet_name);
+    };
+    let height = first.len();
+    if let Some(column) = block.columns.iter().position(|col| col.len() != height) {
+        bail!(
+            "columns[{column}] holds {} values but columns[0] holds {height}; every column in a block must be the same length",
+            block.columns[column].len()
+        );
+    }
+
+    let (start, end) = match block.range.split_once(':') {
+        Some((start, end)) => (start, Some(end)),
+        None => (block.range.as_str(), None),
+    };
+    let (col, row) = parse_cell(start)?;
+    let width = block.columns.len() as u32;
+    let height = height as u32;
+    if let Some(end) = end {
+        let (end_col, end_row) = parse_cell(end)?;
+        if end_col < col || end_row < row {
+            bail!(
+                "range '{}' must run from top-left to bottom-right",
+                block.range
+            );
+        }
+        if end_col - col + 1 != width || end_row - row + 1 != height {
+            bail!(
+                "range '{}' is {}x{} but the block holds {height} rows of {width} columns",
+                block.range,
+                end_row - row + 1,
+                end_col - col + 1
+            );
+        }
+    }
+    if height > 0 && (col + width - 1 > MAX_COL || row + height - 1 > MAX_ROW) {
+        bail!(
+            "block at '{}' with {height} rows of {width} columns runs past the sheet edge",
+            block.range
+        );
+    }
+    Ok((col, row))
+}
+
+fn parse_cell(reference: &str) -> Result<(u32, u32)> {
+    let (col, row, _, _) =
+        umya_spreadsheet::helper::coordinate::index_from_coordinate(reference.trim());
+    match (col, row) {
+        (Some(col), Some(row)) if (1..=MAX_COL).contains(&col) && (1..=MAX_ROW).contains(&row) => {
+            Ok((col, row))
+        }
+        _ => bail!("invalid cell reference '{reference}' (expected A1-style reference)"),
+    }
+}
+
+/// Write every block into the workbook at `path`, rewriting it in place.
+pub fn write_cells_to_path(path: &Path, payload: &CellBlocksPayload) -> Result<WriteCellsSummary> {
+    let started = Instant::now();
+    validate_cell_blocks(payload)?;
+
+    let mut summary = WriteCellsSummary {
+        blocks: payload.blocks.len() as u64,
+        ..WriteCellsSummary::default()
+    };
+    let mut writes: HashMap<&str, SheetWrites<'_>> = HashMap::new();
+    for block in &payload.blocks {
+        let (col, row) = block_origin(block)?;
+        let sheet = writes.entry(block.sheet_name.as_str()).or_default();
+        for (offset, column) in block.columns.iter().enumerate() {
+            for index in 0..column.len() {
+                match column.value(index) {
+                    Some(value) => sheet
+                        .entry(row + index as u32)
+                        .or_default()
+                        .push((col + offset as u32, value)),
+                    None => summary.nulls_skipped += 1,
+                }
+            }
+        }
+    }
+    for rows in writes.values_mut().flat_map(|sheet| sheet.values_mut()) {
+        // Later blocks win where they overlap.
+        rows.reverse();
+        rows.sort_by_key(|(col, _)| *col);
+        rows.dedup_by_key(|(col, _)| *col);
+    }
+
+    let mut archive = zip::ZipArchive::new(fs::File::open(path)?)?;
+    let sheet_parts = resolve_sheet_parts(&mut archive)?;
+    let mut targets: HashMap<String, &SheetWrites<'_>> = HashMap::new();
+    for (sheet_name, rows) in &writes {
+        let part = sheet_parts
+            .get(*sheet_name)
+            .ok_or_else(|| anyhow!("sheet '{}' not found", sheet_name))?;
+        targets.insert(part.clone(), rows);
+    }
+
+    // Written cells may replace formulas, and a calc chain naming a cell that
+    // no longer holds one makes Excel repair the file. The chain is rebuilt on
+    // the next calculation.
+    let mut rewritten: HashMap<String, Vec<u8>> = HashMap::new();
+    if archive.by_name(CALC_CHAIN_PART).is_ok() {
+        rewritten.insert(
+            "[Content_Types].xml".to_string(),
+            drop_calc_chain_override(&read_zip_text(&mut archive, "[Content_Types].xml")?)
+                .into_bytes(),
+        );
+        rewritten.insert(
+            "xl/_rels/workbook.xml.rels".to_string(),
+            drop_calc_chain_relationship(&read_zip_text(
+                &mut archive,
+                "xl/_rels/workbook.xml.rels",
+            )?)
+            .into_bytes(),
+        );
+    }
+
+    let temp_path = path.with_extension("xlsx.cells.tmp");
+    let mut writer = zip::ZipWriter::new(BufWriter::new(fs::File::create(&temp_path)?));
+    for idx in 0..archive.len() {
+        let name = archive.by_index_raw(idx)?.name().to_string();
+        if name == CALC_CHAIN_PART {
+            continue;
+        }
+        if let Some(rows) = targets.get(&name) {
+            let file = archive.by_index(idx)?;
+            summary.sheet_bytes_in += file.size();
+            let mut options = zip::write::FileOptions::default()
+                .compression_method(file.compression())
+                .last_modified_time(file.last_modified());
+            if let Some(mode) = file.unix_mode() {
+                options = options.unix_permissions(mode);
+            }
+            writer.start_file(name.as_str(), options)?;
+            let mut counted = CountingWriter::new(BufWriter::new(&mut writer));
+            merge_sheet_cells(BufReader::new(file), &mut counted, rows, &mut summary)
+                .with_context(|| format!("while writing '{name}'"))?;
+            summary.sheet_bytes_out += counted.written;
+            counted.into_inner().flush()?;
+            summary.sheets_written += 1;
+        } else if let Some(data) = rewritten.remove(&name) {
+            let file = archive.by_index_raw(idx)?;
+            let options = zip::write::FileOptions::default()
+                .compression_method(file.compression())
+                .last_modified_time(file.last_modified());
+            writer.start_file(name.as_str(), options)?;
+            writer.write_all(&data)?;
+        } else {
+            writer.raw_copy_file(archive.by_index_raw(idx)?)?;
+        }
+    }
+    writer.finish()?.flush()?;
+    drop(archive);
+    fs::rename(&temp_path, path)?;
+
+    summary.write_ms = started.elapsed().as_millis() as u64;
+    Ok(summary)
+}
+
+/// Streaming merge of one sheet's written cells into its worksheet XML.
+struct SheetMerge<'s, 'a, W: Write> {
+    writer: Writer<W>,
+    rows:
+        std::iter::Peekable<std::collections::btree_map::Iter<'s, u32, Vec<(u32, CellWrite<'a>)>>>,
+    summary: &'s mut WriteCellsSummary,
+    /// Namespace prefix of `<sheetData>`, e.g. `x:`, reused for new elements.
+    prefix: String,
+    /// Row being copied and the written cells still to place in it.
+    current: Option<(u32, &'s [(u32, CellWrite<'a>)])>,
+    last_row: u32,
+    last_col: u32,
+}
+
+/// Copy worksheet XML from `input` to `output`, merging `rows` into
+/// `<sheetData>` and widening `<dimension>` to cover them.
+fn merge_sheet_cells<R: BufRead, W: Write>(
+    input: R,
+    output: W,
+    rows: &SheetWrites<'_>,
+    summary: &mut WriteCellsSummary,
+) -> Result<()> {
+    let mut reader = Reader::from_reader(input);
+    let mut merge = SheetMerge {
+        writer: Writer::new(output),
+        rows: rows.iter().peekable(),
+        summary,
+        prefix: String::new(),
+        current: None,
+        last_row: 0,
+        last_col: 0,
+    };
+    let mut in_sheet_data = false;
+    let mut buf = Vec::new();
+    let mut skip_buf = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Eof => break,
+            Event::Empty(e) if e.local_name().as_ref() == b"dimension" => {
+                let widened = widen_dimension(&e, rows)?;
+                merge.writer.write_event(Event::Empty(widened))?;
+            }
+            Event::Start(e) if e.local_name().as_ref() == b"sheetData" => {
+                merge.prefix = element_prefix(&e);
+                in_sheet_data = true;
+                merge.writer.write_event(Event::Start(e))?;
+            }
+            Event::Empty(e) if e.local_name().as_ref() == b"sheetData" => {
+                merge.prefix = element_prefix(&e);
+                let end = e.to_end().into_owned();
+                merge.writer.write_event(Event::Start(e))?;
+                merge.flush_rows_before(u32::MAX)?;
+                merge.writer.write_event(Event::End(end))?;
+            }
+            Event::End(e) if e.local_name().as_ref() == b"sheetData" => {
+                merge.flush_rows_before(u32::MAX)?;
+                in_sheet_data = false;
+                merge.writer.write_event(Event::End(e))?;
+            }
+            Event::Start(e) if in_sheet_data && e.local_name().as_ref() == b"row" => {
+                merge.start_row(e, false)?;
+            }
+            Event::Empty(e) if in_sheet_data && e.local_name().as_ref() == b"row" => {
+                merge.start_row(e, true)?;
+            }
+            Event::End(e) if in_sheet_data && e.local_name().as_ref() == b"row" => {
+                merge.finish_row()?;
+                merge.writer.write_event(Event::End(e))?;
+            }
+            Event::Start(e) if merge.current.is_some() && e.local_name().as_ref() == b"c" => {
+                if merge.place_cell(&e)? {
+                    let end = e.to_end().into_owned();
+                    if skip_cell_children(&mut reader, &end, &mut skip_buf)? {
+                        merge.summary.formulas_replaced += 1;
+                    }
+                } else {
+                    merge.writer.write_event(Event::Start(e))?;
+                }
+            }
+            Event::Empty(e) if merge.current.is_some() && e.local_name().as_ref() == b"c" => {
+                if !merge.place_cell(&e)? {
+                    merge.writer.write_event(Event::Empty(e))?;
+                }
+            }
+            other => merge.writer.write_event(other)?,
+        }
+        buf.clear();
+    }
+    Ok(())
+}
+
+impl<W: Write> SheetMerge<'_, '_, W> {
+    /// Write new rows for every pending row above `row`.
+    fn flush_rows_before(&mut self, row: u32) -> Result<()> {
+        while let Some((index, cells)) = self.rows.next_if(|(index, _)| **index < row) {
+            let out = self.writer.get_mut();
+            write!(out, r#"<{}row r="{index}">"#, self.prefix)?;
+            for (col, value) in cells {
+                write_cell(out, &self.prefix, *index, *col, *value, None)?;
+            }
+            write!(out, "</{}row>", self.prefix)?;
+            self.summary.cells_written += cells.len() as u64;
+            self.summary.rows_created += 1;
+        }
+        Ok(())
+    }
+
+    /// Copy a `<row>` start tag, preparing to merge into it when it has
+    /// written cells. An empty `<row/>` is closed straight away.
+    fn start_row(&mut self, e: BytesStart<'_>, empty: bool) -> Result<()> {
+        let row = match attribute(&e, b"r") {
+            Some(value) => value
+                .parse()
+                .map_err(|_| anyhow!("invalid row number '{value}'"))?,
+            // Rows without `r` follow the previous one.
+            None => self.last_row + 1,
+        };
+        if row <= self.last_row {
+            bail!("rows are out of order at row {row}");
+        }
+        self.last_row = row;
+        self.last_col = 0;
+        self.flush_rows_before(row)?;
+
+        let Some((_, cells)) = self.rows.next_if(|(index, _)| **index == row) else {
+            let event = if empty {
+                Event::Empty(e)
+            } else {
+                Event::Start(e)
+            };
+            self.writer.write_event(event)?;
+            return Ok(());
+        };
+        // `spans` is an optional hint that written cells may fall outside.
+        let mut start = BytesStart::new(String::from_utf8_lossy(e.name().as_ref()).into_owned());
+        start.extend_attributes(
+            e.attributes()
+                .filter_map(|attr| attr.ok())
+                .filter(|attr| attr.key.as_ref() != b"spans"),
+        );
+        let end = start.to_end().into_owned();
+        self.writer.write_event(Event::Start(start))?;
+        self.current = Some((row, cells.as_slice()));
+        if empty {
+            self.finish_row()?;
+            self.writer.write_event(Event::End(end))?;
+        }
+        Ok(())
+    }
+
+    /// Write the written cells right of the last existing cell in the row.
+    fn finish_row(&mut self) -> Result<()> {
+        if let Some((row, cells)) = self.current.take() {
+            for (col, value) in cells {
+                write_cell(self.writer.get_mut(), &self.prefix, row, *col, *value, None)?;
+            }
+            self.summary.cells_written += cells.len() as u64;
+        }
+        Ok(())
+    }
+
+    /// Write the written cells left of existing cell `e`, then its
+    /// replacement if there is one. Returns whether `e` was replaced, in
+    /// which case the caller drops the original.
+    fn place_cell(&mut self, e: &BytesStart<'_>) -> Result<bool> {
+        let Some((row, cells)) = self.current else {
+            return Ok(false);
+        };
+        let Some(reference) = attribute(e, b"r") else {
+            bail!(
+                "row {row} has a cell without an r attribute; write-cells needs explicit cell references"
+            );
+        };
+        let (col, _) = parse_cell(&reference)?;
+        if col <= self.last_col {
+            bail!("cells in row {row} are out of order at {reference}");
+        }
+        self.last_col = col;
+
+        let before = cells
+            .iter()
+            .take_while(|(new_col, _)| *new_col < col)
+            .count();
+        for (new_col, value) in &cells[..before] {
+            write_cell(
+                self.writer.get_mut(),
+                &self.prefix,
+                row,
+                *new_col,
+                *value,
+                None,
+            )?;
+        }
+        self.summary.cells_written += before as u64;
+        let rest = &cells[before..];
+
+        match rest.first() {
+            Some((new_col, value)) if *new_col == col => {
+                let style = attribute(e, b"s");
+                write_cell(
+                    self.writer.get_mut(),
+                    &self.prefix,
+                    row,
+                    col,
+                    *value,
+                    style.as_deref(),
+                )?;
+                self.summary.cells_written += 1;
+                self.summary.cells_replaced += 1;
+                self.current = Some((row, &rest[1..]));
+                Ok(true)
+            }
+            _ => {
+                self.current = Some((row, rest));
+                Ok(false)
+            }
+        }
+    }
+}
+
+/// Consume the children of a replaced `<c>` up to `end`, returning whether
+/// it held a formula. Cells anchoring a shared or array formula are
+/// rejected, since other cells depend on the formula text they carry.
+fn skip_cell_children<R: BufRead>(
+    reader: &mut Reader<R>,
+    end: &BytesEnd<'_>,
+    buf: &mut Vec<u8>,
+) -> Result<bool> {
+    let mut had_formula = false;
+    loop {
+        buf.clear();
+        match reader.read_event_into(buf)? {
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"f" => {
+                had_formula = true;
+                if attribute(&e, b"ref").is_some() {
+                    bail!(
+                        "a written cell anchors a shared or array formula that other cells use; write it with transform-batch instead"
+                    );
+                }
+            }
+            Event::End(e) if e.name() == end.name() => break,
+            Event::Eof => bail!("worksheet ended inside a cell"),
+            _ => {}
+        }
+    }
+    Ok(had_formula)
+}
+
+fn write_cell<W: Write>(
+    out: &mut W,
+    prefix: &str,
+    row: u32,
+    col: u32,
+    value: CellWrite<'_>,
+    style: Option<&str>,
+) -> Result<()> {
+    let reference = format!(
+        "{}{row}",
+        umya_spreadsheet::helper::coordinate::string_from_column_index(&col)
+    );
+    let style = style
+        .map(|style| format!(r#" s="{style}""#))
+        .unwrap_or_default();
+    match value {
+        CellWrite::Number(number) => write!(
+            out,
+            r#"<{prefix}c r="{reference}"{style}><{prefix}v>{number}</{prefix}v></{prefix}c>"#
+        )?,
+        CellWrite::Bool(flag) => write!(
+            out,
+            r#"<{prefix}c r="{reference}"{style} t="b"><{prefix}v>{}</{prefix}v></{prefix}c>"#,
+            u8::from(flag)
+        )?,
+        CellWrite::Text(text) => {
+            let space = if text.trim() != text || text.contains('\n') {
+                r#" xml:space="preserve""#
+            } else {
+                ""
+            };
+            write!(
+                out,
+                r#"<{prefix}c r="{reference}"{style} t="inlineStr"><{prefix}is><{prefix}t{space}>{}</{prefix}t></{prefix}is></{prefix}c>"#,
+                quick_xml::escape::escape(text)
+            )?
+        }
+    }
+    Ok(())
+}
+
+fn attribute(e: &BytesStart<'_>, key: &[u8]) -> Option<String> {
+    e.attributes()
+        .filter_map(|attr| attr.ok())
+        .find(|attr| attr.key.as_ref() == key)
+        .map(|attr| String::from_utf8_lossy(&attr.value).into_owned())
+}
+
+fn element_prefix(e: &BytesStart<'_>) -> String {
+    let name = e.name();
+    let local = e.local_name();
+    let prefix_len = name.as_ref().len() - local.as_ref().len();
+    String::from_utf8_lossy(&name.as_ref()[..prefix_len]).into_owned()
+}
+
+/// `<dimension>` with its `ref` grown to include every written cell.
+fn widen_dimension(e: &BytesStart<'_>, rows: &SheetWrites<'_>) -> Result<BytesStart<'static>> {
+    let (Some(first_row), Some(last_row)) = (rows.keys().next(), rows.keys().next_back()) else {
+        return Ok(e.clone().into_owned());
+    };
+    let mut min_col = rows
+        .values()
+        .filter_map(|cells| cells.first())
+        .map(|(col, _)| *col)
+        .min();
+    let mut max_col = rows
+        .values()
+        .filter_map(|cells| cells.last())
+        .map(|(col, _)| *col)
+        .max();
+    let (mut min_row, mut max_row) = (*first_row, *last_row);
+
+    // A sheet with no cells reports `A1`, which says nothing about extent.
+    if let Some(current) = attribute(e, b"ref")
+        && current != "A1"
+    {
+        let (start, end) = current
+            .split_once(':')
+            .unwrap_or((current.as_str(), current.as_str()));
+        let ((start_col, start_row), (end_col, end_row)) = (parse_cell(start)?, parse_cell(end)?);
+        min_row = min_row.min(start_row);
+        max_row = max_row.max(end_row);
+        min_col = min_col.map(|col| col.min(start_col));
+        max_col = max_col.map(|col| col.max(end_col));
+    }
+    let (Some(min_col), Some(max_col)) = (min_col, max_col) else {
+        return Ok(e.clone().into_owned());
+    };
+    let column = umya_spreadsheet::helper::coordinate::string_from_column_index;
+    let reference = format!(
+        "{}{min_row}:{}{max_row}",
+        column(&min_col),
+        column(&max_col)
+    );
+
+    let mut widened = BytesStart::new(String::from_utf8_lossy(e.name().as_ref()).into_owned());
+    widened.extend_attributes(
+        e.attributes()
+            .filter_map(|attr| attr.ok())
+            .filter(|attr| attr.key.as_ref() != b"ref"),
+    );
+    widened.push_attribute(("ref", reference.as_str()));
+    Ok(widened)
+}
+
+pub(crate) fn drop_calc_chain_override(content_types: &str) -> String {
+    static OVERRIDE_TAG: OnceLock<Regex> = OnceLock::new();
+    OVERRIDE_TAG
+        .get_or_init(|| Regex::new(r"<(?:\w+:)?Override\b[^>]*>").expect("valid Override regex"))
+        .replace_all(content_types, |caps: &Captures| {
+            let attrs = parse_attributes(&caps[0]);
+            let part = attrs
+                .get("PartName")
+                .map(|name| name.trim_start_matches('/'))
+                .unwrap_or_default();
+            if part == CALC_CHAIN_PART {
+                String::new()
+            } else {
+                caps[0].to_string()
+            }
+        })
+        .into_owned()
+}
+
+pub(crate) fn drop_calc_chain_relationship(rels: &str) -> String {
+    relationship_tag_regex()
+        .replace_all(rels, |caps: &Captures| {
+            let attrs = parse_attributes(&caps[0]);
+            let rel_type = attrs.get("Type").map(String::as_str).unwrap_or_default();
+            if rel_type.ends_with("/calcChain") {
+                String::new()
+            } else {
+                caps[0].to_string()
+            }
+        })
+        .into_owned()
+}
+
+/// Counts bytes on their way to the zip entry, for throughput reporting.
+struct CountingWriter<W: Write> {
+    inner: W,
+    written: u64,
+}
+
+impl<W: Write> CountingWriter<W> {
+    fn new(inner: W) -> Self {
+        Self { inner, written: 0 }
+    }
+
+    fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(data)?;
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -499,7 +499,7 @@ fn non_empty_formula(raw: &str) -> Option<String> {
     (!trimmed.is_empty()).then(|| trimmed.to_string())
 }
 
-fn is_error_text(raw: &str) -> bool {
+pub(crate) fn is_error_text(raw: &str) -> bool {
     let upper = raw.trim().to_ascii_uppercase();
     matches!(
         upper.as_str(),
@@ -0,0 +1,260 @@
+//! Webhook notifications for completed workbook writes and recalculations.
+//!
+//! With `SPREADSHEET_MCP_WEBHOOK_URL` set, every CLI write that replaces a
+//! workbook (the same writes that run pre/post-apply hooks) and every
+//! `recalculate` POSTs a [`WebhookEvent`] to that URL once the target is
+//! written. The event carries the command, its op counts and the
+//! changed-cell manifest, so receivers can react without re-reading the file.
+//!
+//! With `SPREADSHEET_MCP_WEBHOOK_SECRET` set, requests are signed: the
+//! `X-Spreadsheet-Signature` header is `sha256=<hex>`, the HMAC-SHA256 of
+//! `<timestamp>.<body>` keyed by the secret, where `<timestamp>` is the
+//! `X-Spreadsheet-Timestamp` header (Unix seconds). Receivers recompute it
+//! with [`sign_payload`] and should reject stale timestamps.
+//!
+//! The MCP server sends the same events for fork edits, `save_fork` and
+//! `recalculate`, with `fork_id` set, and scheduled jobs post their runs to
+//! their own webhook URL through the same signed sender ([`post_signed`]).
+//!
+//! Delivery happens after the write, so a failed delivery never undoes it;
+//! like a failing post-apply hook, the CLI reports it as an error, while the
+//! server and scheduler log it. Sending needs the `remote` feature; without
+//! it a configured webhook fails every delivery.
+
+use crate::hooks::{ChangedCellManifest, WriteHookEvent};
+#[cfg(feature = "remote")]
+use anyhow::Context;
+use anyhow::{Result, bail};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use std::collections::BTreeMap;
+#[cfg(feature = "remote")]
+use std::sync::OnceLock;
+#[cfg(feature = "remote")]
+use std::time::Duration;
+
+pub const WEBHOOK_URL_ENV: &str = "SPREADSHEET_MCP_WEBHOOK_URL";
+pub const WEBHOOK_SECRET_ENV: &str = "SPREADSHEET_MCP_WEBHOOK_SECRET";
+
+pub const SIGNATURE_HEADER: &str = "X-Spreadsheet-Signature";
+pub const TIMESTAMP_HEADER: &str = "X-Spreadsheet-Timestamp";
+pub const EVENT_HEADER: &str = "X-Spreadsheet-Event";
+
+#[cfg(feature = "remote")]
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// `X-Spreadsheet-Event` of scheduled job runs.
+pub const SCHEDULED_RUN_EVENT: &str = "scheduled_run";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEventKind {
+    WriteCompleted,
+    RecalcCompleted,
+}
+
+impl WebhookEventKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::WriteCompleted => "write_completed",
+            Self::RecalcCompleted => "recalc_completed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookEvent {
+    pub event: WebhookEventKind,
+    pub command: String,
+    pub timestamp: String,
+    pub source_path: String,
+    pub target_path: String,
+    /// Fork the server wrote, for events sent by the MCP server.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fork_id: Option<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub operation_counts: BTreeMap<String, u64>,
+    pub changed_cells: ChangedCellManifest,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recalc: Option<WebhookRecalcSummary>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookRecalcSummary {
+    pub backend: String,
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cells_evaluated: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub eval_errors: Option<Vec<String>>,
+}
+
+impl WebhookEvent {
+    /// The notification for a write whose post-apply hooks have run.
+    pub fn write_completed(event: &WriteHookEvent) -> Self {
+        Self {
+            event: WebhookEventKind::WriteCompleted,
+            command: event.command.clone(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            source_path: event.source_path.clone(),
+            target_path: event.target_path.clone(),
+            fork_id: None,
+            operation_counts: event.operation_counts.clone(),
+            changed_cells: event.changed_cells.clone(),
+            recalc: None,
+        }
+    }
+
+    /// The notification for an edit or save of a server fork.
+    pub fn fork_write_completed(
+        fork_id: String,
+        command: String,
+        source_path: String,
+        target_path: String,
+        changed_cells: ChangedCellManifest,
+    ) -> Self {
+        Self {
+            event: WebhookEventKind::WriteCompleted,
+            command,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            source_path,
+            target_path,
+            fork_id: Some(fork_id),
+            operation_counts: BTreeMap::new(),
+            changed_cells,
+            recalc: None,
+        }
+    }
+
+    pub fn recalc_completed(
+        source_path: String,
+        target_path: String,
+        changed_cells: ChangedCellManifest,
+        recalc: WebhookRecalcSummary,
+    ) -> Self {
+        Self {
+            event: WebhookEventKind::RecalcCompleted,
+            command: "recalculate".to_string(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            source_path,
+            target_path,
+            fork_id: None,
+            operation_counts: BTreeMap::new(),
+            changed_cells,
+            recalc: Some(recalc),
+        }
+    }
+
+    pub fn with_fork_id(mut self, fork_id: String) -> Self {
+        self.fork_id = Some(fork_id);
+        self
+    }
+}
+
+fn webhook_url() -> Option<String> {
+    std::env::var(WEBHOOK_URL_ENV)
+        .ok()
+        .map(|url| url.trim().to_string())
+        .filter(|url| !url.is_empty())
+}
+
+/// True when a webhook URL is configured, so callers can skip building the
+/// changed-cell manifest otherwise.
+pub fn webhook_configured() -> bool {
+    webhook_url().is_some()
+}
+
+/// `sha256=<hex>` signature of `body` sent at `timestamp` (Unix seconds).
+pub fn sign_payload(secret: &[u8], timestamp: i64, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    let digest: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    format!("sha256={digest}")
+}
+
+/// POST `event` to the configured webhook; a no-op without one.
+pub async fn notify_webhook(event: &WebhookEvent) -> Result<()> {
+    let Some(url) = webhook_url() else {
+        return Ok(());
+    };
+    post_signed(&url, event.event.as_str(), serde_json::to_vec(event)?).await
+}
+
+/// POST a JSON `body` to `url`, signed when `SPREADSHEET_MCP_WEBHOOK_SECRET`
+/// is set, on the caller's runtime.
+#[cfg(feature = "remote")]
+pub async fn post_signed(url: &str, event: &str, body: Vec<u8>) -> Result<()> {
+    let timestamp = chrono::Utc::now().timestamp();
+    let mut request = client()?
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(EVENT_HEADER, event)
+        .header(TIMESTAMP_HEADER, timestamp.to_string());
+    if let Some(secret) = std::env::var(WEBHOOK_SECRET_ENV)
+        .ok()
+        .filter(|secret| !secret.is_empty())
+    {
+        request = request.header(
+            SIGNATURE_HEADER,
+            sign_payload(secret.as_bytes(), timestamp, &body),
+        );
+    }
+    let response = request
+        .body(body)
+        .send()
+        .await
+        .with_context(|| format!("webhook {url} could not be reached"))?;
+    let status = response.status();
+    if !status.is_success() {
+        bail!("webhook {url} returned HTTP {status}");
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "remote"))]
+pub async fn post_signed(url: &str, _event: &str, _body: Vec<u8>) -> Result<()> {
+    bail!("webhook {url} needs a build with the `remote` feature")
+}
+
+#[cfg(feature = "remote")]
+fn client() -> Result<&'static reqwest::Client> {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    if let Some(client) = CLIENT.get() {
+        return Ok(client);
+    }
+    let client = reqwest::Client::builder()
+        .timeout(WEBHOOK_TIMEOUT)
+        .build()?;
+    Ok(CLIENT.get_or_init(|| client))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_covers_timestamp_and_body() {
+        let signature = sign_payload(b"secret", 1_700_000_000, b"{\"event\":\"write_completed\"}");
+        // Matches `hmac.new(b"secret", b"1700000000." + body, sha256)`.
+        assert_eq!(
+            signature,
+            "sha256=7f86e4573d92cdca806722a6faf8df8d0c9337a769d814126874c24fbde6c116"
+        );
+        assert_ne!(
+            signature,
+            sign_payload(b"secret", 1_700_000_001, b"{\"event\":\"write_completed\"}")
+        );
+        assert_ne!(
+            signature,
+            sign_payload(b"other", 1_700_000_000, b"{\"event\":\"write_completed\"}")
+        );
+    }
+}
@@ -1,22 +1,40 @@
 use crate::analysis::{
     classification,
-    formula::{FormulaAtlas, FormulaGraph},
+    formula::{FormulaAtlas, FormulaGraph, ReferenceNames},
+    layout::{self, LayoutCell, SheetKindInputs},
     style,
 };
 use crate::caps::BackendCaps;
 use crate::config::ServerConfig;
 use crate::model::{
-    FormulaParseDiagnostics, FormulaParseDiagnosticsBuilder, FormulaParsePolicy, NamedItemKind,
-    NamedRangeDescriptor, NamedRangeScope, SheetClassification, SheetOverviewResponse,
-    SheetSummary, WorkbookDescription, WorkbookId, WorkbookListResponse,
+    DocumentProperties, EmbeddedObject, EmbeddedObjectKind, FormLayout, FormulaParseDiagnostics,
+    FormulaParseDiagnosticsBuilder, FormulaParsePolicy, NamedItemKind, NamedRangeDescriptor,
+    NamedRangeScope, SheetClassification, SheetEmbeddedObjects, SheetKind, SheetOverviewLayout,
+    SheetOverviewResponse, SheetPageMargins, SheetPaneLayout, SheetPrintLayout, SheetSummary,
+    SheetVisibility, WorkbookDescription, WorkbookId, WorkbookListResponse,
 };
+use crate::parse_cache::{ParseCache, WorkbookStructure};
+use crate::timings;
+use crate::tools::doc_properties::{
+    read_document_properties, read_document_properties_from_reader,
+};
+use crate::tools::embedded_objects::{read_embedded_objects, read_embedded_objects_from_reader};
 use crate::tools::filters::WorkbookFilter;
+use crate::tools::outline::{SheetOutline, read_outlines, read_outlines_from_reader};
+use crate::tools::param_enums::DateHandling;
+use crate::tools::sheet_direction::{
+    read_right_to_left_sheets, read_right_to_left_sheets_from_reader,
+};
+use crate::tools::workbook_props::{
+    WorkbookProps, read_workbook_props, read_workbook_props_from_reader,
+};
 use crate::utils::{
-    hash_bytes_sha256_hex, hash_file_sha256_hex, hash_path_identity, make_short_workbook_id,
-    path_to_forward_slashes, system_time_to_rfc3339,
+    column_number_to_name, hash_bytes_sha256_hex, hash_file_sha256_hex, hash_path_identity,
+    make_short_workbook_id, path_to_forward_slashes, system_time_to_rfc3339,
 };
 use anyhow::{Context, Result, anyhow};
 use chrono::{DateTime, Utc};
+use once_cell::sync::OnceCell;
 use parking_lot::RwLock;
 use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
@@ -74,9 +92,23 @@ pub struct WorkbookContext {
     pub caps: BackendCaps,
     pub bytes: u64,
     pub last_modified: Option<DateTime<Utc>>,
-    spreadsheet: Arc<RwLock<Spreadsheet>>,
+    /// Parsed on first use when the structure came from the parse cache.
+    spreadsheet: OnceCell<Arc<RwLock<Spreadsheet>>>,
     sheet_cache: RwLock<HashMap<String, Arc<SheetCacheEntry>>>,
     formula_atlas: Arc<FormulaAtlas>,
+    sheet_outlines: RwLock<Option<Arc<HashMap<String, SheetOutline>>>>,
+    right_to_left_sheets: RwLock<Option<Arc<HashSet<String>>>>,
+    embedded_objects: RwLock<Option<Arc<HashMap<String, Vec<EmbeddedObject>>>>>,
+    parse_cache: Option<CachedStructure>,
+    props: WorkbookProps,
+    doc_properties: Option<DocumentProperties>,
+}
+
+/// Parse cache entry backing a context, updated as structural reads run.
+struct CachedStructure {
+    cache: ParseCache,
+    key: String,
+    structure: RwLock<WorkbookStructure>,
 }
 
 pub struct SheetCacheEntry {
@@ -151,14 +183,12 @@ impl WorkbookContext {
             .unwrap_or_else(|| "workbook".to_string());
         let id = WorkbookId(hash_path_identity(&canonical));
         let short_id = make_short_workbook_id(&slug, id.as_str());
-        let revision_id = hash_file_sha256_hex(path)
-            .with_context(|| format!("unable to hash workbook {:?}", path))?;
 
-        Self::load_from_path(_config, path, id, short_id, Some(revision_id))
+        Self::load_from_path(_config, path, id, short_id, None)
     }
 
     pub fn load_from_path(
-        _config: &Arc<ServerConfig>,
+        config: &Arc<ServerConfig>,
         path: &Path,
         stable_id: WorkbookId,
         short_id: String,
@@ -172,13 +202,48 @@ impl WorkbookContext {
             .unwrap_or_else(|| "workbook".to_string());
         let bytes = metadata.len();
         let last_modified = metadata.modified().ok().and_then(system_time_to_rfc3339);
-        let revision_id = match revision_id {
-            Some(id) => id,
-            None => hash_file_sha256_hex(path)
-                .with_context(|| format!("unable to hash workbook {:?}", path))?,
+        // Caller-supplied revision ids need not be content hashes, so only a
+        // hash computed here doubles as the parse cache key.
+        let (revision_id, content_hash) = match revision_id {
+            Some(id) => (id, None),
+            None => {
+                let hash = hash_file_sha256_hex(path)
+                    .with_context(|| format!("unable to hash workbook {:?}", path))?;
+                (hash.clone(), Some(hash))
+            }
+        };
+
+        let spreadsheet = OnceCell::new();
+        let parse_cache = match &config.parse_cache_dir {
+            Some(dir) => {
+                let cache = ParseCache::new(dir);
+                let key = match content_hash {
+                    Some(hash) => hash,
+                    None => ParseCache::key_for_file(path)?,
+                };
+                let structure = match cache.load(&key) {
+                    Some(structure) => structure,
+                    None => {
+                        let book = parse_spreadsheet(path)?;
+                        let structure = base_structure(&book);
+                        store_structure(&cache, &key, &structure);
+                        let _ = spreadsheet.set(Arc::new(RwLock::new(book)));
+                        structure
+                    }
+                };
+                Some(CachedStructure {
+                    cache,
+                    key,
+                    structure: RwLock::new(structure),
+                })
+            }
+            None => {
+                let _ = spreadsheet.set(Arc::new(RwLock::new(parse_spreadsheet(path)?)));
+                None
+            }
         };
-        let spreadsheet =
-            xlsx::read(path).with_context(|| format!("failed to parse workbook {:?}", path))?;
+        let props = read_workbook_props(path).unwrap_or_default();
+        let doc_properties = read_document_properties(path).ok();
 
         Ok(Self {
             id: stable_id,
@@ -189,9 +254,15 @@ impl WorkbookContext {
             caps: BackendCaps::xlsx(),
             bytes,
             last_modified,
-            spreadsheet: Arc::new(RwLock::new(spreadsheet)),
+            spreadsheet,
             sheet_cache: RwLock::new(HashMap::new()),
             formula_atlas: Arc::new(FormulaAtlas::default()),
+            sheet_outlines: RwLock::new(None),
+            right_to_left_sheets: RwLock::new(None),
+            embedded_objects: RwLock::new(None),
+            parse_cache,
+            props,
+            doc_properties,
         })
     }
 
@@ -211,6 +282,14 @@ impl WorkbookContext {
         let spreadsheet = xlsx::read_reader(cursor, true)
             .with_context(|| format!("failed to parse workbook bytes for {display_name}"))?;
         let revision_id = revision_id.unwrap_or_else(|| hash_bytes_sha256_hex(bytes));
+        // No file backs a bytes-loaded workbook, so capture outline levels now.
+        let sheet_outlines = read_outlines_from_reader(Cursor::new(bytes)).unwrap_or_default();
+        let right_to_left_sheets =
+            read_right_to_left_sheets_from_reader(Cursor::new(bytes)).unwrap_or_default();
+        let embedded_objects =
+            read_embedded_objects_from_reader(Cursor::new(bytes)).unwrap_or_default();
+        let props = read_workbook_props_from_reader(Cursor::new(bytes)).unwrap_or_default();
+        let doc_properties = read_document_properties_from_reader(Cursor::new(bytes)).ok();
 
         Ok(Self {
             id: stable_id,
@@ -221,14 +300,99 @@ impl WorkbookContext {
             caps: BackendCaps::xlsx(),
             bytes: bytes.len() as u64,
             last_modified: None,
-            spreadsheet: Arc::new(RwLock::new(spreadsheet)),
+            spreadsheet: OnceCell::with_value(Arc::new(RwLock::new(spreadsheet))),
             sheet_cache: RwLock::new(HashMap::new()),
             formula_atlas: Arc::new(FormulaAtlas::default()),
+            sheet_outlines: RwLock::new(Some(Arc::new(sheet_outlines))),
+            right_to_left_sheets: RwLock::new(Some(Arc::new(right_to_left_sheets))),
+            embedded_objects: RwLock::new(Some(Arc::new(embedded_objects))),
+            parse_cache: None,
+            props,
+            doc_properties,
         })
     }
 
+    /// The parsed workbook, parsing it now if only cached structure was loaded.
+    fn book(&self) -> Result<&Arc<RwLock<Spreadsheet>>> {
+        self.spreadsheet
+            .get_or_try_init(|| Ok(Arc::new(RwLock::new(parse_spreadsheet(&self.path)?))))
+    }
+
+    /// Apply `update` to the cached structure and persist it. Failing to
+    /// write the cache only costs a re-parse later, so it is not an error.
+    fn update_cached_structure(&self, update: impl FnOnce(&mut WorkbookStructure)) {
+        let Some(cached) = &self.parse_cache else {
+            return;
+        };
+        let snapshot = {
+            let mut structure = cached.structure.write();
+            update(&mut structure);
+            structure.clone()
+        };
+        store_structure(&cached.cache, &cached.key, &snapshot);
+    }
+
+    /// Row/column outline levels for a sheet. umya does not parse them, so they are
+    /// read from the workbook file on first use; unreadable files report no levels.
+    pub fn sheet_outline(&self, sheet_name: &str) -> SheetOutline {
+        if let Some(outlines) = self.sheet_outlines.read().as_ref() {
+            return outlines.get(sheet_name).cloned().unwrap_or_default();
+        }
+        let outlines = Arc::new(read_outlines(&self.path).unwrap_or_default());
+        let outline = outlines.get(sheet_name).cloned().unwrap_or_default();
+        *self.sheet_outlines.write() = Some(outlines);
+        outline
+    }
+
+    /// Whether a sheet is laid out right to left. Read from the workbook file
+    /// on first use, like outline levels.
+    pub fn sheet_is_right_to_left(&self, sheet_name: &str) -> bool {
+        if let Some(sheets) = self.right_to_left_sheets.read().as_ref() {
+            return sheets.contains(sheet_name);
+        }
+        let sheets = Arc::new(read_right_to_left_sheets(&self.path).unwrap_or_default());
+        let right_to_left = sheets.contains(sheet_name);
+        *self.right_to_left_sheets.write() = Some(sheets);
+        right_to_left
+    }
+
+    /// Embedded OLE objects and linked documents by sheet name. Read from the
+    /// workbook file on first use, like outline levels.
+    fn embedded_objects(&self) -> Arc<HashMap<String, Vec<EmbeddedObject>>> {
+        if let Some(objects) = self.embedded_objects.read().as_ref() {
+            return objects.clone();
+        }
+        let objects = Arc::new(read_embedded_objects(&self.path).unwrap_or_default());
+        *self.embedded_objects.write() = Some(objects.clone());
+        objects
+    }
+
+    pub fn sheet_embedded_objects(&self, sheet_name: &str) -> Vec<EmbeddedObject> {
+        self.embedded_objects()
+            .get(sheet_name)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Whether date serials in this workbook count from 1904-01-01.
+    pub fn uses_1904_date_system(&self) -> bool {
+        self.props.date1904
+    }
+
+    /// Whether Excel stores calculated values rounded to their displayed
+    /// format ("Set precision as displayed").
+    pub fn precision_as_displayed(&self) -> bool {
+        self.props.precision_as_displayed
+    }
+
     pub fn sheet_names(&self) -> Vec<String> {
-        let book = self.spreadsheet.read();
+        if let Some(cached) = &self.parse_cache {
+            return cached.structure.read().sheet_names.clone();
+        }
+        let Ok(book) = self.book() else {
+            return Vec::new();
+        };
+        let book = book.read();
         book.get_sheet_collection()
             .iter()
             .map(|sheet| sheet.get_name().to_string())
@@ -236,14 +400,46 @@ impl WorkbookContext {
     }
 
     pub fn describe(&self) -> WorkbookDescription {
-        let book = self.spreadsheet.read();
-        let defined_names_count = book.get_defined_names().len();
-        let table_count: usize = book
-            .get_sheet_collection()
-            .iter()
-            .map(|sheet| sheet.get_tables().len())
-            .sum();
+        let (sheet_names, defined_names_count, table_count) = match &self.parse_cache {
+            Some(cached) => {
+                let structure = cached.structure.read();
+                (
+                    structure.sheet_names.clone(),
+                    structure.defined_names,
+                    structure.tables,
+                )
+            }
+            None => self
+                .book()
+                .map(|book| {
+                    let structure = base_structure(&book.read());
+                    (
+                        structure.sheet_names,
+                        structure.defined_names,
+                        structure.tables,
+                    )
+                })
+                .unwrap_or_default(),
+        };
+        let sheet_count = sheet_names.len();
         let macros_present = false;
+        let objects = self.embedded_objects();
+        let embedded_objects = sheet_names
+            .iter()
+            .filter_map(|sheet_name| {
+                let sheet_objects = objects.get(sheet_name)?;
+                let linked = sheet_objects
+                    .iter()
+                    .filter(|object| object.kind == EmbeddedObjectKind::Linked)
+                    .count() as u32;
+                Some(SheetEmbeddedObjects {
+                    sheet_name: sheet_name.clone(),
+                    embedded: sheet_objects.len() as u32 - linked,
+                    linked,
+                    embedded_bytes: sheet_objects.iter().filter_map(|o| o.size_bytes).sum(),
+                })
+            })
+            .collect();
 
         WorkbookDescription {
             workbook_id: self.id.clone(),
@@ -252,7 +448,7 @@ impl WorkbookContext {
             path: path_to_forward_slashes(&self.path),
             client_path: None,
             bytes: self.bytes,
-            sheet_count: book.get_sheet_collection().len(),
+            sheet_count,
             defined_names: defined_names_count,
             tables: table_count,
             macros_present,
@@ -261,6 +457,10 @@ impl WorkbookContext {
                 .map(|dt| dt.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)),
             revision_id: Some(self.revision_id.clone()),
             caps: self.caps.clone(),
+            date1904: self.props.date1904,
+            precision_as_displayed: self.props.precision_as_displayed,
+            embedded_objects,
+            properties: self.doc_properties.clone(),
         }
     }
 
@@ -274,7 +474,7 @@ impl WorkbookContext {
             return Ok(entry.clone());
         }
 
-        let book = self.spreadsheet.read();
+        let book = self.book()?.read();
         let sheet = book
             .get_sheet_by_name(sheet_name)
             .ok_or_else(|| anyhow!("sheet {} not found", sheet_name))?;
@@ -299,64 +499,104 @@ impl WorkbookContext {
             return Ok(entry);
         }
 
-        let book = self.spreadsheet.read();
+        if let Some(cached) = &self.parse_cache
+            && let Some(overview) = cached.structure.read().overviews.get(sheet_name)
+        {
+            entry.set_detected_regions(overview.detected_regions.clone());
+            entry.set_region_notes(overview.notes.clone());
+            return Ok(entry);
+        }
+
+        let book = self.book()?.read();
         let sheet = book
             .get_sheet_by_name(sheet_name)
             .ok_or_else(|| anyhow!("sheet {} not found", sheet_name))?;
-        let detected = detect_regions(sheet, &entry.metrics);
+        let detected = timings::time_phase(timings::PHASE_REGION_DETECTION, || {
+            with_date_system(self.props.date1904, || {
+                detect_regions(sheet, &entry.metrics)
+            })
+        });
         entry.set_detected_regions(detected.regions);
         entry.set_region_notes(detected.notes);
         Ok(entry)
     }
 
     pub fn list_summaries(&self, include_bounds: bool) -> Result<Vec<SheetSummary>> {
-        let book = self.spreadsheet.read();
-        let mut summaries = Vec::new();
-        for sheet in book.get_sheet_collection() {
-            let name = sheet.get_name().to_string();
-            let entry = self.get_sheet_metrics_fast(&name)?;
-            summaries.push(SheetSummary {
-                name: name.clone(),
-                visible: sheet.get_sheet_state() != "hidden",
-                row_count: include_bounds.then_some(entry.metrics.row_count),
-                column_count: include_bounds.then_some(entry.metrics.column_count),
-                non_empty_cells: include_bounds.then_some(entry.metrics.non_empty_cells),
-                formula_cells: include_bounds.then_some(entry.metrics.formula_cells),
-                cached_values: include_bounds.then_some(entry.metrics.cached_values),
-                classification: entry.metrics.classification.clone(),
-                style_tags: if include_bounds {
-                    entry.style_tags.clone()
-                } else {
-                    Vec::new()
-                },
-            });
+        let cached = self
+            .parse_cache
+            .as_ref()
+            .and_then(|cached| cached.structure.read().summaries.clone());
+        let summaries = match cached {
+            Some(summaries) => summaries,
+            None => {
+                let book = self.book()?.read();
+                let mut summaries = Vec::new();
+                for sheet in book.get_sheet_collection() {
+                    let name = sheet.get_name().to_string();
+                    let entry = self.get_sheet_metrics_fast(&name)?;
+                    let visibility = SheetVisibility::from_sheet_state(sheet.get_sheet_state());
+                    summaries.push(SheetSummary {
+                        name: name.clone(),
+                        visible: visibility == SheetVisibility::Visible,
+                        visibility,
+                        tab_color: sheet
+                            .get_tab_color()
+                            .map(|color| color.get_argb().to_string())
+                            .filter(|argb| !argb.is_empty()),
+                        row_count: Some(entry.metrics.row_count),
+                        column_count: Some(entry.metrics.column_count),
+                        non_empty_cells: Some(entry.metrics.non_empty_cells),
+                        formula_cells: Some(entry.metrics.formula_cells),
+                        cached_values: Some(entry.metrics.cached_values),
+                        classification: entry.metrics.classification.clone(),
+                        style_tags: entry.style_tags.clone(),
+                    });
+                }
+                self.update_cached_structure(|structure| {
+                    structure.summaries = Some(summaries.clone());
+                });
+                summaries
+            }
+        };
+
+        if include_bounds {
+            return Ok(summaries);
         }
-        Ok(summaries)
+        Ok(summaries
+            .into_iter()
+            .map(|summary| SheetSummary {
+                row_count: None,
+                column_count: None,
+                non_empty_cells: None,
+                formula_cells: None,
+                cached_values: None,
+                style_tags: Vec::new(),
+                ..summary
+            })
+            .collect())
     }
 
     pub fn with_sheet<T, F>(&self, sheet_name: &str, func: F) -> Result<T>
     where
         F: FnOnce(&Worksheet) -> T,
     {
-        let book = self.spreadsheet.read();
+        let book = self.book()?.read();
         let sheet = book
             .get_sheet_by_name(sheet_name)
             .ok_or_else(|| anyhow!("sheet {} not found", sheet_name))?;
-        Ok(func(sheet))
+        Ok(with_date_system(self.props.date1904, || func(sheet)))
     }
 
     pub fn with_spreadsheet<T, F>(&self, func: F) -> Result<T>
     where
         F: FnOnce(&Spreadsheet) -> T,
     {
-        let book = self.spreadsheet.read();
-        Ok(func(&book))
+        let book = self.book()?.read();
+        Ok(with_date_system(self.props.date1904, || func(&book)))
     }
 
     pub fn formula_graph(&self, sheet_name: &str) -> Result<FormulaGraph> {
-        self.with_sheet(sheet_name, |sheet| {
-            FormulaGraph::build(sheet, &self.formula_atlas, FormulaParsePolicy::Warn, None)
-        })?
+        self.build_formula_graph(sheet_name, FormulaParsePolicy::Warn, None)
     }
 
     pub fn formula_graph_with_diagnostics(
@@ -365,9 +605,7 @@ impl WorkbookContext {
         policy: FormulaParsePolicy,
     ) -> Result<(FormulaGraph, FormulaParseDiagnostics)> {
         let mut builder = FormulaParseDiagnosticsBuilder::new(policy);
-        let graph = self.with_sheet(sheet_name, |sheet| {
-            FormulaGraph::build(sheet, &self.formula_atlas, policy, Some(&mut builder))
-        })??;
+        let graph = self.build_formula_graph(sheet_name, policy, Some(&mut builder))?;
         Ok((graph, builder.build()))
     }
 
@@ -377,13 +615,28 @@ impl WorkbookContext {
         policy: FormulaParsePolicy,
         builder: &mut FormulaParseDiagnosticsBuilder,
     ) -> Result<FormulaGraph> {
-        self.with_sheet(sheet_name, |sheet| {
-            FormulaGraph::build(sheet, &self.formula_atlas, policy, Some(builder))
+        self.build_formula_graph(sheet_name, policy, Some(builder))
+    }
+
+    /// Formula graph of one sheet with defined names and table references
+    /// resolved to the cells they cover.
+    fn build_formula_graph(
+        &self,
+        sheet_name: &str,
+        policy: FormulaParsePolicy,
+        diagnostics: Option<&mut FormulaParseDiagnosticsBuilder>,
+    ) -> Result<FormulaGraph> {
+        self.with_spreadsheet(|book| {
+            let sheet = book
+                .get_sheet_by_name(sheet_name)
+                .ok_or_else(|| anyhow!("sheet {} not found", sheet_name))?;
+            let names = ReferenceNames::for_sheet(book, sheet_name);
+            FormulaGraph::build_with_names(sheet, &names, &self.formula_atlas, policy, diagnostics)
         })?
     }
 
     pub fn named_items(&self) -> Result<Vec<NamedRangeDescriptor>> {
-        let book = self.spreadsheet.read();
+        let book = self.book()?.read();
         let sheet_names: Vec<String> = book
             .get_sheet_collection()
             .iter()
@@ -398,11 +651,7 @@ impl WorkbookContext {
             } else {
                 None
             };
-            let kind = if refers_to.starts_with('=') {
-                NamedItemKind::Formula
-            } else {
-                NamedItemKind::NamedRange
-            };
+            let kind = classify_refers_to(&refers_to);
 
             let (scope_kind, scope_sheet_name) = if defined.has_local_sheet_id() {
                 let idx = *defined.get_local_sheet_id() as usize;
@@ -420,6 +669,8 @@ impl WorkbookContext {
                 kind,
                 sheet_name: scope,
                 comment: None,
+                value: None,
+                scope_conflict: false,
             });
         }
 
@@ -427,11 +678,7 @@ impl WorkbookContext {
         for sheet in book.get_sheet_collection() {
             for defined in sheet.get_defined_names() {
                 let refers_to = defined.get_address();
-                let kind = if refers_to.starts_with('=') {
-                    NamedItemKind::Formula
-                } else {
-                    NamedItemKind::NamedRange
-                };
+                let kind = classify_refers_to(&refers_to);
                 let sheet_name_str = sheet.get_name().to_string();
                 // Avoid duplicates: skip if already present from workbook-level
                 let already_present = items.iter().any(|item| {
@@ -451,6 +698,8 @@ impl WorkbookContext {
                     kind,
                     sheet_name: Some(sheet_name_str),
                     comment: None,
+                    value: None,
+                    scope_conflict: false,
                 });
             }
 
@@ -466,21 +715,38 @@ impl WorkbookContext {
                     kind: NamedItemKind::Table,
                     sheet_name: Some(sheet.get_name().to_string()),
                     comment: None,
+                    value: None,
+                    scope_conflict: false,
                 });
             }
         }
 
+        mark_scope_conflicts(&mut items);
         Ok(items)
     }
 
     pub fn sheet_overview(&self, sheet_name: &str) -> Result<SheetOverviewResponse> {
+        if let Some(cached) = &self.parse_cache
+            && let Some(overview) = cached.structure.read().overviews.get(sheet_name)
+        {
+            return Ok(SheetOverviewResponse {
+                workbook_id: self.id.clone(),
+                ..overview.clone()
+            });
+        }
+
         let entry = self.get_sheet_metrics(sheet_name)?;
         let narrative = classification::narrative(&entry.metrics);
         let regions = classification::regions(&entry.metrics);
         let key_ranges = classification::key_ranges(&entry.metrics);
         let detected_regions = entry.detected_regions();
+        let mut layout = self.with_sheet(sheet_name, sheet_overview_layout)?;
+        layout.right_to_left = self.sheet_is_right_to_left(sheet_name);
+        let (sheet_kind, sheet_kind_reason, form) = self.with_sheet(sheet_name, |sheet| {
+            sheet_layout_kind(sheet, &detected_regions)
+        })?;
 
-        Ok(SheetOverviewResponse {
+        let overview = SheetOverviewResponse {
             workbook_id: self.id.clone(),
             sheet_name: sheet_name.to_string(),
             narrative,
@@ -496,7 +762,18 @@ impl WorkbookContext {
             },
             notable_features: entry.style_tags.clone(),
             notes: entry.region_notes(),
-        })
+            layout,
+            sheet_kind,
+            sheet_kind_reason,
+            form,
+            embedded_objects: self.sheet_embedded_objects(sheet_name),
+        };
+        self.update_cached_structure(|structure| {
+            structure
+                .overviews
+                .insert(sheet_name.to_string(), overview.clone());
+        });
+        Ok(overview)
     }
 
     pub fn detected_region(
@@ -514,6 +791,220 @@ impl WorkbookContext {
     }
 }
 
+const MAX_OVERVIEW_HIDDEN_SPANS: usize = 50;
+
+/// Sheet kind, its reason, and the form block when the sheet reads as a
+/// form.
+fn sheet_layout_kind(
+    sheet: &Worksheet,
+    detected_regions: &[crate::model::DetectedRegion],
+) -> (SheetKind, String, Option<FormLayout>) {
+    let cells: Vec<LayoutCell> = sheet
+        .get_cell_collection()
+        .into_iter()
+        .map(|cell| LayoutCell {
+            row: *cell.get_coordinate().get_row_num(),
+            col: *cell.get_coordinate().get_col_num(),
+            value: cell_to_value(cell),
+            is_formula: cell.is_formula(),
+        })
+        .collect();
+
+    let mut inputs = SheetKindInputs {
+        occupied_cells: cells.len(),
+        charts: sheet.get_chart_collection().len(),
+        detected_regions: detected_regions.len(),
+        ..Default::default()
+    };
+    let mut used_columns = HashSet::new();
+    for cell in &cells {
+        match &cell.value {
+            Some(crate::model::CellValue::Text(_)) => inputs.text_cells += 1,
+            Some(crate::model::CellValue::Number(_) | crate::model::CellValue::Date(_)) => {
+                inputs.number_cells += 1
+            }
+            _ => {}
+        }
+        if cell.is_formula {
+            inputs.formula_cells += 1;
+        }
+        if cell.value.is_some() {
+            used_columns.insert(cell.col);
+        }
+    }
+    inputs.used_columns = used_columns.len();
+
+    for region in detected_regions {
+        let Ok(((start_col, start_row), (end_col, end_row))) =
+            crate::tools::sheet_layout::parse_a1_range(&region.bounds)
+        else {
+            continue;
+        };
+        let region_cells = cells
+            .iter()
+            .filter(|cell| {
+                (start_row..=end_row).contains(&cell.row)
+                    && (start_col..=end_col).contains(&cell.col)
+            })
+            .count();
+        inputs.largest_region_cells = inputs.largest_region_cells.max(region_cells);
+        let is_table = region.header_row.is_some()
+            && region.row_count >= 3
+            && !matches!(
+                region.classification,
+                crate::model::RegionKind::Form
+                    | crate::model::RegionKind::Parameters
+                    | crate::model::RegionKind::Metadata
+            );
+        if is_table {
+            inputs.table_cells += region_cells;
+        }
+    }
+
+    let form = layout::scan_form(&cells);
+    let (kind, reason) = layout::classify_sheet_kind(&inputs, &form);
+    let form = (kind == SheetKind::Form).then(|| form.into_layout());
+    (kind, reason, form)
+}
+
+fn sheet_overview_layout(sheet: &Worksheet) -> SheetOverviewLayout {
+    let view = sheet.get_sheets_views().get_sheet_view_list().first();
+    let pane = view.and_then(|view| view.get_pane()).map(|pane| {
+        let state = match pane.get_state().get_value_string() {
+            "frozenSplit" => "frozen_split",
+            "split" => "split",
+            _ => "frozen",
+        };
+        let (frozen_rows, frozen_cols) = if state == "split" {
+            (0, 0)
+        } else {
+            (
+                pane.get_vertical_split().max(0.0) as u32,
+                pane.get_horizontal_split().max(0.0) as u32,
+            )
+        };
+        let top_left_cell = pane.get_top_left_cell().to_string();
+        SheetPaneLayout {
+            state: state.to_string(),
+            frozen_rows,
+            frozen_cols,
+            top_left_cell: (!top_left_cell.is_empty()).then_some(top_left_cell),
+        }
+    });
+    let zoom_percent = view
+        .map(|view| *view.get_zoom_scale())
+        .filter(|zoom| *zoom > 0)
+        .unwrap_or(100);
+    let show_gridlines = view.map(|view| *view.get_show_grid_lines()).unwrap_or(true);
+
+    let hidden_rows: Vec<u32> = sheet
+        .get_row_dimensions()
+        .into_iter()
+        .filter(|row| *row.get_hidden())
+        .map(|row| *row.get_row_num())
+        .collect();
+    let hidden_cols: Vec<u32> = sheet
+        .get_column_dimensions()
+        .iter()
+        .filter(|col| *col.get_hidden())
+        .map(|col| *col.get_col_num())
+        .collect();
+
+    let row_spans = collapse_index_spans(hidden_rows.clone(), |row| row.to_string());
+    let col_spans = collapse_index_spans(hidden_cols.clone(), column_number_to_name);
+    let hidden_spans_truncated =
+        row_spans.len() > MAX_OVERVIEW_HIDDEN_SPANS || col_spans.len() > MAX_OVERVIEW_HIDDEN_SPANS;
+
+    SheetOverviewLayout {
+        pane,
+        zoom_percent,
+        show_gridlines,
+        // Not modeled by umya; the caller fills it in from the sheet XML.
+        right_to_left: false,
+        hidden_row_count: hidden_rows.len() as u32,
+        hidden_rows: row_spans
+            .into_iter()
+            .take(MAX_OVERVIEW_HIDDEN_SPANS)
+            .collect(),
+        hidden_column_count: hidden_cols.len() as u32,
+        hidden_columns: col_spans
+            .into_iter()
+            .take(MAX_OVERVIEW_HIDDEN_SPANS)
+            .collect(),
+        hidden_spans_truncated,
+        print: sheet_print_layout(sheet),
+    }
+}
+
+fn sheet_print_layout(sheet: &Worksheet) -> SheetPrintLayout {
+    // set_print_area stores the area as a sheet-scoped `_xlnm.Print_Area`
+    // name such as `'Sheet1'!$A$1:$G$30`.
+    let print_area = sheet
+        .get_defined_names()
+        .iter()
+        .find(|name| name.get_name() == "_xlnm.Print_Area")
+        .map(|name| {
+            let address = name.get_address();
+            let range = address
+                .rsplit_once('!')
+                .map(|(_, range)| range)
+                .unwrap_or(&address);
+            range.replace('$', "")
+        });
+    let orientation = match sheet.get_page_setup().get_orientation() {
+        umya_spreadsheet::OrientationValues::Landscape => "landscape",
+        _ => "portrait",
+    };
+    let margins = sheet.get_page_margins();
+    let header_footer = sheet.get_header_footer();
+    let text = |value: &str| (!value.is_empty()).then(|| value.to_string());
+    SheetPrintLayout {
+        print_area,
+        orientation: orientation.to_string(),
+        margins: SheetPageMargins {
+            left: *margins.get_left(),
+            right: *margins.get_right(),
+            top: *margins.get_top(),
+            bottom: *margins.get_bottom(),
+            header: *margins.get_header(),
+            footer: *margins.get_footer(),
+        },
+        header: text(header_footer.get_odd_header().get_value()),
+        footer: text(header_footer.get_odd_footer().get_value()),
+    }
+}
+
+/// Collapse sorted 1-based indices into `"a"` / `"a:b"` spans.
+fn collapse_index_spans(mut indices: Vec<u32>, label: impl Fn(u32) -> String) -> Vec<String> {
+    indices.sort_unstable();
+    indices.dedup();
+    let mut spans = Vec::new();
+    let mut iter = indices.into_iter();
+    let Some(mut start) = iter.next() else {
+        return spans;
+    };
+    let mut end = start;
+    for index in iter {
+        if index == end + 1 {
+            end = index;
+            continue;
+        }
+        spans.push(format_index_span(start, end, &label));
+        start = index;
+        end = index;
+    }
+    spans.push(format_index_span(start, end, &label));
+    spans
+}
+
+fn format_index_span(start: u32, end: u32, label: &impl Fn(u32) -> String) -> String {
+    if start == end {
+        label(start)
+    } else {
+        format!("{}:{}", label(start), label(end))
+    }
+}
+
 fn contains_date_time_token(format_code: &str) -> bool {
     let mut in_quote = false;
     let mut in_bracket = false;
@@ -558,7 +1049,7 @@ const DATE_FORMAT_IDS: &[u32] = &[
 
 const EXCEL_LEAP_YEAR_BUG_SERIAL: i64 = 60;
 
-fn is_date_formatted(cell: &umya_spreadsheet::Cell) -> bool {
+pub(crate) fn is_date_formatted(cell: &umya_spreadsheet::Cell) -> bool {
     let Some(nf) = cell.get_style().get_number_format() else {
         return false;
     };
@@ -609,8 +1100,172 @@ pub fn excel_serial_to_iso_with_leap_bug(
         .unwrap_or_else(|| serial.to_string())
 }
 
+/// Date and/or time parts shown by a number format code, as `(date, time)`.
+/// Month and minute share `m`; an `m` next to an hour, second, or `:` is read
+/// as minutes.
+fn format_date_time_parts(format_code: &str) -> (bool, bool) {
+    let code = format_code
+        .to_ascii_lowercase()
+        .replace("am/pm", "")
+        .replace("a/p", "");
+    let chars: Vec<char> = code.chars().collect();
+    let mut in_quote = false;
+    let mut bracket: Option<String> = None;
+    let (mut date, mut time) = (false, false);
+
+    for (i, &ch) in chars.iter().enumerate() {
+        if let Some(content) = bracket.as_mut() {
+            if ch == ']' {
+                // Elapsed-time tokens: [h], [mm], [ss].
+                if !content.is_empty() && content.chars().all(|c| matches!(c, 'h' | 'm' | 's')) {
+                    time = true;
+                }
+                bracket = None;
+            } else {
+                content.push(ch);
+            }
+            continue;
+        }
+        match ch {
+            '"' => in_quote = !in_quote,
+            _ if in_quote => {}
+            '[' => bracket = Some(String::new()),
+            'y' | 'd' => date = true,
+            'h' | 's' => time = true,
+            'm' => {
+                let before = chars[..i].iter().rev().find(|c| **c != 'm');
+                let after = chars[i..].iter().find(|c| **c != 'm');
+                if matches!(before, Some('h' | ':')) || matches!(after, Some(':' | 's')) {
+                    time = true;
+                } else {
+                    date = true;
+                }
+            }
+            _ => {}
+        }
+    }
+    (date, time)
+}
+
+/// ISO-8601 text for a serial shown with `format_code`: `YYYY-MM-DD` for
+/// date-only formats, `YYYY-MM-DDTHH:MM:SS` when the format also shows a
+/// time, and `HH:MM:SS` for time-only formats below one day.
+pub fn excel_serial_to_iso_for_format(
+    serial: f64,
+    use_1904_system: bool,
+    format_code: &str,
+) -> String {
+    let (date, time) = format_date_time_parts(format_code);
+    if !time {
+        return excel_serial_to_iso(serial, use_1904_system);
+    }
+
+    let mut days = serial.floor();
+    let mut seconds = ((serial - days) * 86_400.0).round() as i64;
+    if seconds >= 86_400 {
+        days += 1.0;
+        seconds -= 86_400;
+    }
+    let clock = format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    );
+    if !date && days == 0.0 {
+        return clock;
+    }
+    format!("{}T{clock}", excel_serial_to_iso(days, use_1904_system))
+}
+
+/// Serial number for ISO-8601 text produced by
+/// [`excel_serial_to_iso_for_format`]; `None` for any other text.
+pub fn iso_to_excel_serial(text: &str, use_1904_system: bool) -> Option<f64> {
+    use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+
+    let fraction = |time: NaiveTime| f64::from(time.num_seconds_from_midnight()) / 86_400.0;
+    let days = |date: NaiveDate| {
+        let epoch = if use_1904_system {
+            NaiveDate::from_ymd_opt(1904, 1, 1)
+        } else if date >= NaiveDate::from_ymd_opt(1900, 3, 1)? {
+            NaiveDate::from_ymd_opt(1899, 12, 30)
+        } else {
+            NaiveDate::from_ymd_opt(1899, 12, 31)
+        }?;
+        Some((date - epoch).num_days() as f64)
+    };
+
+    if let Ok(datetime) = NaiveDateTime::parse_from_str(text, "%Y-%m-%dT%H:%M:%S") {
+        return Some(days(datetime.date())? + fraction(datetime.time()));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        return days(date);
+    }
+    NaiveTime::parse_from_str(text, "%H:%M:%S")
+        .ok()
+        .map(fraction)
+}
+
+thread_local! {
+    static USE_1904_DATE_SYSTEM: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+    static DATE_HANDLING: std::cell::Cell<DateHandling> =
+        const { std::cell::Cell::new(DateHandling::Iso) };
+}
+
+/// Run `func` with [`cell_to_value`] reading date serials in the 1904 date
+/// system when `use_1904_system` is set. [`WorkbookContext`] applies its own
+/// date system around every closure it lends a sheet or workbook to, so cell
+/// reads inside `with_sheet`/`with_spreadsheet` need no extra plumbing.
+pub fn with_date_system<T>(use_1904_system: bool, func: impl FnOnce() -> T) -> T {
+    let _scope = DateSystemScope::enter(use_1904_system);
+    func()
+}
+
+/// Guard form of [`with_date_system`]: the date system applies until the
+/// guard is dropped, for methods that read cells throughout their body.
+#[must_use = "the date system is restored as soon as the scope is dropped"]
+pub(crate) struct DateSystemScope {
+    previous: bool,
+}
+
+impl DateSystemScope {
+    pub(crate) fn enter(use_1904_system: bool) -> Self {
+        Self {
+            previous: USE_1904_DATE_SYSTEM.with(|flag| flag.replace(use_1904_system)),
+        }
+    }
+}
+
+impl Drop for DateSystemScope {
+    fn drop(&mut self) {
+        USE_1904_DATE_SYSTEM.with(|flag| flag.set(self.previous));
+    }
+}
+
+/// Whether the enclosing [`with_date_system`] scope reads the 1904 date
+/// system.
+pub(crate) fn uses_1904_date_system() -> bool {
+    USE_1904_DATE_SYSTEM.with(|flag| flag.get())
+}
+
+/// Run `func` with [`cell_to_value`] reporting date-formatted numbers as
+/// `handling` asks: [`DateHandling::Raw`] keeps the serial as a number, the
+/// other modes decode it to ISO-8601 text.
+pub fn with_date_handling<T>(handling: DateHandling, func: impl FnOnce() -> T) -> T {
+    struct Restore(DateHandling);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            DATE_HANDLING.with(|mode| mode.set(self.0));
+        }
+    }
+    let _restore = Restore(DATE_HANDLING.with(|mode| mode.replace(handling)));
+    func()
+}
+
+/// Typed value of `cell`, reading date serials in the date system set by the
+/// enclosing [`with_date_system`] scope (1900 outside any scope).
 pub fn cell_to_value(cell: &umya_spreadsheet::Cell) -> Option<crate::model::CellValue> {
-    cell_to_value_with_date_system(cell, false)
+    cell_to_value_with_date_system(cell, uses_1904_date_system())
 }
 
 pub fn cell_to_value_with_date_system(
@@ -623,10 +1278,17 @@ pub fn cell_to_value_with_date_system(
     }
     if let Ok(number) = raw.parse::<f64>() {
         if is_date_formatted(cell) {
-            return Some(crate::model::CellValue::Date(excel_serial_to_iso(
-                number,
-                use_1904_system,
-            )));
+            if DATE_HANDLING.with(|mode| mode.get()) == DateHandling::Raw {
+                return Some(crate::model::CellValue::Number(number));
+            }
+            let code = cell
+                .get_style()
+                .get_number_format()
+                .map(|nf| nf.get_format_code())
+                .unwrap_or_default();
+            return Some(crate::model::CellValue::Date(
+                excel_serial_to_iso_for_format(number, use_1904_system, code),
+            ));
         }
         return Some(crate::model::CellValue::Number(number));
     }
@@ -840,6 +1502,28 @@ impl Occupancy {
     fn value_at(&self, row: u32, col: u32) -> Option<&crate::model::CellValue> {
         self.cells.get(&(row, col)).and_then(|c| c.value.as_ref())
     }
+
+    fn layout_cells_in_rect(&self, rect: &Rect) -> Vec<LayoutCell> {
+        let mut out = Vec::new();
+        for row in rect.start_row..=rect.end_row {
+            let Some(cols) = self.rows.get(&row) else {
+                continue;
+            };
+            let start_idx = lower_bound(cols, rect.start_col);
+            let end_idx = upper_bound(cols, rect.end_col);
+            for &col in &cols[start_idx..end_idx] {
+                if let Some(info) = self.cells.get(&(row, col)) {
+                    out.push(LayoutCell {
+                        row,
+                        col,
+                        value: info.value.clone(),
+                        is_formula: info.is_formula,
+                    });
+                }
+            }
+        }
+        out
+    }
 }
 
 fn lower_bound(values: &[u32], target: u32) -> usize {
@@ -988,6 +1672,55 @@ impl DetectLimits {
     }
 }
 
+fn parse_spreadsheet(path: &Path) -> Result<Spreadsheet> {
+    if timings::timings_enabled() {
+        read_spreadsheet_timed(path)
+    } else {
+        xlsx::read(path).map_err(Into::into)
+    }
+    .with_context(|| format!("failed to parse workbook {:?}", path))
+}
+
+/// Parse-cache entry holding only what a fresh parse yields directly.
+fn base_structure(book: &Spreadsheet) -> WorkbookStructure {
+    WorkbookStructure {
+        sheet_names: book
+            .get_sheet_collection()
+            .iter()
+            .map(|sheet| sheet.get_name().to_string())
+            .collect(),
+        defined_names: book.get_defined_names().len(),
+        tables: book
+            .get_sheet_collection()
+            .iter()
+            .map(|sheet| sheet.get_tables().len())
+            .sum(),
+        summaries: None,
+        overviews: Default::default(),
+    }
+}
+
+fn store_structure(cache: &ParseCache, key: &str, structure: &WorkbookStructure) {
+    if let Err(err) = cache.store(key, structure) {
+        tracing::warn!("failed to update parse cache in {:?}: {err:#}", cache.dir());
+    }
+}
+
+/// Same result as `xlsx::read`, split into separately timed phases: the archive
+/// open, the lazy read of workbook-level parts (shared strings, styles,
+/// workbook.xml), and worksheet deserialization.
+fn read_spreadsheet_timed(path: &Path) -> Result<Spreadsheet> {
+    timings::time_phase(timings::PHASE_ZIP_OPEN, || -> Result<()> {
+        zip::ZipArchive::new(fs::File::open(path)?)?;
+        Ok(())
+    })?;
+    let mut book = timings::time_phase(timings::PHASE_SST_PARSE, || xlsx::lazy_read(path))?;
+    timings::time_phase(timings::PHASE_SHEET_PARSE, || {
+        book.read_sheet_collection();
+    });
+    Ok(book)
+}
+
 fn detect_regions(sheet: &Worksheet, metrics: &SheetMetrics) -> DetectRegionsResult {
     if metrics.row_count == 0 || metrics.column_count == 0 {
         return DetectRegionsResult::default();
@@ -1334,7 +2067,17 @@ fn build_region(
 ) -> crate::model::DetectedRegion {
     let header_info = detect_headers(occupancy, rect);
     let stats = occupancy.stats_in_rect(rect);
-    let (kind, confidence) = classify_region(rect, &stats, &header_info, metrics);
+    let (mut kind, confidence) = classify_region(rect, &stats, &header_info, metrics);
+    if !matches!(
+        kind,
+        crate::model::RegionKind::Calculator | crate::model::RegionKind::Outputs
+    ) {
+        let has_table_header = header_info.header_row.is_some() && !header_info.is_key_value;
+        let form = layout::scan_form(&occupancy.layout_cells_in_rect(rect));
+        if form.looks_like_form(stats.non_empty as usize, 0.6, has_table_header) {
+            kind = crate::model::RegionKind::Form;
+        }
+    }
     let header_len = header_info.headers.len() as u32;
     let header_count = rect.end_col - rect.start_col + 1;
     let headers_truncated = header_len != header_count;
@@ -1694,6 +2437,77 @@ fn is_outputs_band(rect: &Rect, metrics: &SheetMetrics, height: u32, width: u32)
     is_band && not_at_top_left && sheet_has_depth
 }
 
+/// Classify a defined name by what it refers to: cell references (including
+/// unions and whole rows/columns) are ranges, literals and array constants are
+/// constants, and anything else is a formula to be evaluated.
+fn classify_refers_to(refers_to: &str) -> NamedItemKind {
+    let body = refers_to.trim().trim_start_matches('=').trim();
+    if body.is_empty() {
+        return NamedItemKind::Unknown;
+    }
+
+    let is_constant = body.parse::<f64>().is_ok()
+        || body.eq_ignore_ascii_case("TRUE")
+        || body.eq_ignore_ascii_case("FALSE")
+        || (body.len() >= 2 && body.starts_with('"') && body.ends_with('"'))
+        || (body.starts_with('{') && body.ends_with('}'));
+    if is_constant {
+        return NamedItemKind::Constant;
+    }
+
+    let reference = regex::Regex::new(
+        r"^(?:(?:'(?:[^']|'')+'|[A-Za-z0-9_.]+)!)?(?:\$?[A-Za-z]{1,3}\$?\d+(?::\$?[A-Za-z]{1,3}\$?\d+)?|\$?[A-Za-z]{1,3}:\$?[A-Za-z]{1,3}|\$?\d+:\$?\d+)$",
+    )
+    .expect("valid reference regex");
+    let mut parts = split_top_level_commas(body);
+    if parts.all(|part| reference.is_match(part.trim())) {
+        NamedItemKind::NamedRange
+    } else {
+        NamedItemKind::Formula
+    }
+}
+
+/// Split a union like `Sheet1!$A$1,'My, Sheet'!$B$2` on commas outside quotes.
+fn split_top_level_commas(body: &str) -> impl Iterator<Item = &str> {
+    let mut parts = Vec::new();
+    let mut in_quotes = false;
+    let mut start = 0;
+    for (idx, ch) in body.char_indices() {
+        match ch {
+            '\'' => in_quotes = !in_quotes,
+            ',' if !in_quotes => {
+                parts.push(&body[start..idx]);
+                start = idx + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&body[start..]);
+    parts.into_iter()
+}
+
+/// Flag defined names that exist at both workbook and sheet scope.
+fn mark_scope_conflicts(items: &mut [NamedRangeDescriptor]) {
+    let defined_at = |items: &[NamedRangeDescriptor], name: &str, scope: NamedRangeScope| {
+        items.iter().any(|item| {
+            item.kind != NamedItemKind::Table
+                && item.scope_kind == Some(scope)
+                && item.name.eq_ignore_ascii_case(name)
+        })
+    };
+    let conflicting: Vec<bool> = items
+        .iter()
+        .map(|item| {
+            item.kind != NamedItemKind::Table
+                && defined_at(items, &item.name, NamedRangeScope::Workbook)
+                && defined_at(items, &item.name, NamedRangeScope::Sheet)
+        })
+        .collect();
+    for (item, conflict) in items.iter_mut().zip(conflicting) {
+        item.scope_conflict = conflict;
+    }
+}
+
 fn gather_named_ranges(
     sheet: &Worksheet,
     defined_names: &[DefinedName],
@@ -1721,6 +2535,8 @@ fn gather_named_ranges(
                 kind: NamedItemKind::NamedRange,
                 sheet_name: Some(name_str.to_string()),
                 comment: None,
+                value: None,
+                scope_conflict: false,
             }
         })
         .collect()
@@ -1,5 +1,9 @@
+use crate::tools::outline::{SheetOutline, read_outlines, restore_outlines};
+use crate::tools::value_locale::ValueLocale;
+use crate::tools::workbook_props::{read_workbook_props, write_date1904};
 use crate::types::{CellEdit, CoreWarning};
 use anyhow::{Context, Result, anyhow, bail};
+use std::collections::HashMap;
 use std::path::Path;
 
 pub fn normalize_shorthand_edit(entry: &str) -> Result<(CellEdit, Vec<CoreWarning>)> {
@@ -91,7 +95,20 @@ pub fn normalize_object_edit(
     ))
 }
 
-pub fn apply_edits_to_file(path: &Path, sheet_name: &str, edits: &[CellEdit]) -> Result<()> {
+/// Apply `edits` to `sheet_name` and save. With a `locale`, literal values
+/// are read as numbers and dates in that locale (see [`ValueLocale`]).
+pub fn apply_edits_to_file(
+    path: &Path,
+    sheet_name: &str,
+    edits: &[CellEdit],
+    locale: Option<&ValueLocale>,
+) -> Result<()> {
+    let use_1904_system = match locale {
+        Some(_) => read_workbook_props(path)
+            .map(|props| props.date1904)
+            .unwrap_or(false),
+        None => false,
+    };
     let mut book = umya_spreadsheet::reader::xlsx::read(path)
         .with_context(|| format!("failed to open workbook '{}'", path.display()))?;
 
@@ -105,12 +122,63 @@ pub fn apply_edits_to_file(path: &Path, sheet_name: &str, edits: &[CellEdit]) ->
             cell.set_formula(edit.value.clone());
             cell.get_cell_value_mut()
                 .set_formula_result_default(String::new());
+        } else if let Some(locale) = locale {
+            locale.set_cell_value(cell, &edit.value, use_1904_system);
         } else {
             cell.set_value(edit.value.clone());
         }
     }
 
-    umya_spreadsheet::writer::xlsx::write(&book, path)
+    save_workbook(&book, path)
+}
+
+/// Package state umya-spreadsheet drops when it saves a workbook. Captured
+/// from the file a save replaces and put back onto the written package.
+#[derive(Debug, Clone, Default)]
+pub struct PreservedPackage {
+    pub date1904: bool,
+    /// Row/column outline levels by sheet name. Edits that insert, delete or
+    /// rename should update these to match before the save.
+    pub outlines: HashMap<String, SheetOutline>,
+}
+
+impl PreservedPackage {
+    /// State of the workbook at `path`; defaults when it is missing or unreadable.
+    pub fn capture(path: &Path) -> Self {
+        let date1904 = read_workbook_props(path)
+            .map(|props| props.date1904)
+            .unwrap_or(false);
+        let outlines = read_outlines(path).unwrap_or_default();
+        Self { date1904, outlines }
+    }
+
+    /// Write the captured state back onto the freshly saved package at `path`.
+    pub fn restore(&self, path: &Path) -> Result<()> {
+        if self.date1904 {
+            write_date1904(path, true).with_context(|| {
+                format!("failed to restore 1904 date system on '{}'", path.display())
+            })?;
+        }
+        restore_outlines(path, &self.outlines)
+            .with_context(|| format!("failed to restore outline levels on '{}'", path.display()))
+    }
+}
+
+/// Save `book` over `path` through umya, keeping what umya would drop from
+/// the workbook it replaces.
+pub fn save_workbook(book: &umya_spreadsheet::Spreadsheet, path: &Path) -> Result<()> {
+    let preserved = PreservedPackage::capture(path);
+    save_workbook_preserving(book, path, &preserved)
+}
+
+/// Save `book` to `path` and restore `preserved`, for callers that captured
+/// it from a different file or before the original was replaced.
+pub fn save_workbook_preserving(
+    book: &umya_spreadsheet::Spreadsheet,
+    path: &Path,
+    preserved: &PreservedPackage,
+) -> Result<()> {
+    umya_spreadsheet::writer::xlsx::write(book, path)
         .with_context(|| format!("failed to save workbook '{}'", path.display()))?;
-    Ok(())
+    preserved.restore(path)
 }
@@ -259,8 +259,10 @@ fn schema_and_example_commands_support_batch_and_session_discovery() {
         "apply-formula-pattern",
         "structure-batch",
         "column-size-batch",
+        "row-size-batch",
         "sheet-layout-batch",
         "rules-batch",
+        "properties-batch",
     ] {
         let schema = run_cli(&["schema", command]);
         assert!(
@@ -710,7 +712,7 @@ fn readme_cli_docs_parity_examples_execute_with_local_fixtures() {
         "range-values `--include-formulas`:** includes sparse formula coordinates in dense mode",
         "`read cells <file> <sheet> <target> [target...] [--include-empty]`",
         "`workbook create <path> [--sheets Inputs,Calc,...] [--overwrite]`",
-        "`analyze find-value <file> <query> [--sheet S] [--mode value\\|label] [--label-direction right\\|below\\|any]`",
+        "`analyze find-value <file> <query> [--sheet S\\|--all-sheets] [--mode value\\|label] [--label-direction right\\|below\\|any] [--regex] [--value-type number\\|date --op gt\\|lt\\|between [--upper V]] [--limit N] [--offset N]`",
         "`write batch transform <file> --ops @ops.json (--dry-run\\|--in-place\\|--output PATH)",
         "#### Formula write-path provenance (`write_path_provenance`)",
         "`written_via`: write path (`edit`, `transform_batch`, `apply_formula_pattern`)",
@@ -832,7 +834,7 @@ fn npm_readme_cli_docs_parity_examples_execute_with_local_fixtures() {
         "asp write batch transform data.xlsx --ops @ops.json --dry-run",
         "asp analyze find-value data.xlsx \"Net Income\" --mode label --label-direction below",
         "`read page <file> <sheet> --format <full|compact|values_only>",
-        "`analyze find-value <file> <query> [--sheet S] [--mode value\\|label] [--label-direction right\\|below\\|any]`",
+        "`analyze find-value <file> <query> [--sheet S\\|--all-sheets] [--mode value\\|label] [--label-direction right\\|below\\|any] [--regex] [--value-type number\\|date --op gt\\|lt\\|between [--upper V]] [--limit N] [--offset N]`",
         "`write batch transform <file> --ops @ops.json (--dry-run\\|--in-place\\|--output PATH)",
         "Canonical (default/omitted): return `values: [...]` when entries are present; omit `values` when all requested ranges are pruned (for example, invalid ranges).",
         "Global `--output-format csv` is currently unsupported; use command-specific CSV options such as `read table --table-format csv`.",
@@ -901,6 +903,80 @@ fn npm_readme_cli_docs_parity_examples_execute_with_local_fixtures() {
     }
 }
 
+#[test]
+fn cli_timings_flag_reports_workbook_open_phases() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("timings.xlsx");
+    write_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
+
+    let output = run_cli(&["--timings", "sheet-overview", file, "Sheet1"]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["sheet_name"], "Sheet1");
+    assert!(payload["timings"]["command_ms"].is_u64(), "{payload}");
+    let phases: Vec<&str> = payload["timings"]["phases"]
+        .as_array()
+        .expect("phases array")
+        .iter()
+        .filter_map(|phase| phase["phase"].as_str())
+        .collect();
+    for expected in ["zip_open", "sst_parse", "sheet_parse", "region_detection"] {
+        assert!(phases.contains(&expected), "missing {expected}: {payload}");
+    }
+
+    let output = run_cli(&["sheet-overview", file, "Sheet1"]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    assert!(parse_stdout_json(&output).get("timings").is_none());
+}
+
+#[test]
+fn cli_canonical_json_sorts_keys_and_is_byte_stable() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("canonical.xlsx");
+    write_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
+    let args = [
+        "read-table",
+        file,
+        "--sheet",
+        "Sheet1",
+        "--table-format",
+        "values",
+    ];
+
+    let mut canonical_args = vec!["--canonical-json"];
+    canonical_args.extend(args);
+    let first = run_cli(&canonical_args);
+    assert!(first.status.success(), "stderr: {:?}", first.stderr);
+    let second = run_cli(&canonical_args);
+    assert_eq!(first.stdout, second.stdout);
+
+    let raw = String::from_utf8(first.stdout.clone()).expect("utf8 stdout");
+    let positions: Vec<usize> = [
+        "\"header_detection\":",
+        "\"headers\":",
+        "\"sheet_name\":",
+        "\"total_rows\":",
+        "\"values\":",
+        "\"workbook_id\":",
+    ]
+    .iter()
+    .map(|key| {
+        raw.find(key)
+            .unwrap_or_else(|| panic!("{key} missing: {raw}"))
+    })
+    .collect();
+    assert!(positions.windows(2).all(|pair| pair[0] < pair[1]), "{raw}");
+    // Whole floats print as integers: 10.0 is 10.
+    assert!(raw.contains(r#"["Alice",10,"#), "{raw}");
+
+    let default = run_cli(&args);
+    assert!(default.status.success(), "stderr: {:?}", default.stderr);
+    let default_raw = String::from_utf8(default.stdout).expect("utf8 stdout");
+    assert!(default_raw.contains(r#"["Alice",10.0,"#), "{default_raw}");
+}
+
 #[test]
 fn cli_read_commands_cover_ticket_surface() {
     let tmp = tempdir().expect("tempdir");
@@ -1015,6 +1091,130 @@ fn cli_read_commands_cover_ticket_surface() {
     );
 }
 
+#[test]
+fn cli_table_profile_reports_whole_column_stats_and_outliers() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("table-profile-stats.xlsx");
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook
+            .get_sheet_by_name_mut("Sheet1")
+            .expect("default sheet exists");
+        sheet.get_cell_mut("A1").set_value("Region");
+        sheet.get_cell_mut("B1").set_value("Amount");
+        let amounts = [
+            10.0, 11.0, 12.0, 13.0, 14.0, 15.0, 500.0, 16.0, 17.0, 18.0, 19.0, 20.0,
+        ];
+        for (offset, amount) in amounts.into_iter().enumerate() {
+            let row = offset as u32 + 2;
+            // The last two rows leave Region blank.
+            let region = match row {
+                2..=7 => Some("North"),
+                8..=11 => Some("South"),
+                _ => None,
+            };
+            if let Some(region) = region {
+                sheet.get_cell_mut((1, row)).set_value(region);
+            }
+            sheet.get_cell_mut((2, row)).set_value_number(amount);
+        }
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+    let file = workbook_path.to_str().expect("path utf8");
+
+    let output = run_cli(&[
+        "table-profile",
+        file,
+        "--sheet",
+        "Sheet1",
+        "--top-n",
+        "1",
+        "--decimals",
+        "2",
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    let column = |name: &str| {
+        payload["column_types"]
+            .as_array()
+            .and_then(|columns| columns.iter().find(|column| column["name"] == name))
+            .unwrap_or_else(|| panic!("column {name}: {payload}"))["stats"]
+            .clone()
+    };
+
+    let region = column("Region");
+    assert_eq!(region["count"], 10);
+    assert_eq!(region["nulls"], 2);
+    assert_eq!(region["null_pct"].as_f64(), Some(100.0 * 2.0 / 12.0));
+    assert_eq!(region["distinct"], 2);
+    assert_eq!(
+        region["top_values"],
+        serde_json::json!([{"value": "North", "count": 6}])
+    );
+    assert!(region.get("outliers").is_none(), "{region}");
+
+    let amount = column("Amount");
+    assert_eq!(amount["numeric_count"], 12);
+    assert_eq!(amount["min"], 10.0);
+    assert_eq!(amount["max"], 500.0);
+    assert_eq!(amount["mean"], 55.42);
+    assert!(
+        amount["stddev"]
+            .as_f64()
+            .is_some_and(|stddev| stddev > 100.0)
+    );
+    assert_eq!(amount["outliers"]["count"], 1);
+    assert_eq!(
+        amount["outliers"]["cells"],
+        serde_json::json!([{"row": 8, "value": 500.0}])
+    );
+
+    let output = run_cli(&[
+        "table-profile",
+        file,
+        "--sheet",
+        "Sheet1",
+        "--emit-schema",
+        "jsonschema",
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let schema = parse_stdout_json(&output);
+    assert_eq!(
+        schema["$schema"],
+        "https://json-schema.org/draft/2020-12/schema"
+    );
+    assert_eq!(
+        schema["properties"]["Region"]["type"],
+        serde_json::json!(["string", "null"])
+    );
+    assert_eq!(schema["properties"]["Amount"]["type"], "integer");
+    assert_eq!(schema["required"], serde_json::json!(["Amount"]));
+    assert!(schema.get("column_types").is_none(), "{schema}");
+
+    let output = run_cli(&[
+        "table-profile",
+        file,
+        "--sheet",
+        "Sheet1",
+        "--emit-schema",
+        "arrow",
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let schema = parse_stdout_json(&output);
+    assert_eq!(
+        schema["fields"],
+        serde_json::json!([
+            {"name": "Region", "nullable": true, "type": {"name": "utf8"}, "children": []},
+            {
+                "name": "Amount",
+                "nullable": false,
+                "type": {"name": "int", "bitWidth": 64, "isSigned": true},
+                "children": []
+            }
+        ])
+    );
+}
+
 #[test]
 fn cli_find_value_label_mode_uses_query_as_label_and_direction() {
     let tmp = tempdir().expect("tempdir");
@@ -1369,6 +1569,312 @@ fn cli_verify_reports_target_deltas_error_provenance_and_named_range_deltas() {
     assert_eq!(named[0]["after_refers_to"], "'Sheet1'!$B$3");
 }
 
+#[test]
+fn cli_verify_lint_gates_on_severity_with_profile_overrides() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("lint.xlsx");
+    write_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
+
+    let clean = run_cli(&["verify", "lint", file]);
+    assert_eq!(clean.status.code(), Some(0), "stderr: {:?}", clean.stderr);
+    let clean_payload = parse_stdout_json(&clean);
+    assert_eq!(clean_payload["passed"], true);
+    assert_eq!(clean_payload["fail_on"], "error");
+    assert_eq!(clean_payload["findings"], serde_json::json!([]));
+
+    let mut workbook = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read fixture");
+    {
+        let summary = workbook
+            .get_sheet_by_name_mut("Summary")
+            .expect("summary sheet");
+        summary.get_cell_mut("C1").set_formula("NOW()");
+        summary.get_cell_mut("D1").set_value("#DIV/0!");
+
+        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet1");
+        sheet.get_cell_mut("D2").set_value("Closed");
+        sheet.get_cell_mut("D3").set_value("Pending");
+        let mut dv = umya_spreadsheet::structs::DataValidation::default();
+        dv.set_type(umya_spreadsheet::structs::DataValidationValues::List);
+        dv.get_sequence_of_references_mut().set_sqref("D2:D4");
+        dv.set_formula1("\"Open,Closed\"");
+        sheet.set_data_validations(umya_spreadsheet::structs::DataValidations::default());
+        sheet
+            .get_data_validations_mut()
+            .unwrap()
+            .add_data_validation_list(dv);
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+
+    let failing = run_cli(&["verify", "lint", file]);
+    assert_eq!(
+        failing.status.code(),
+        Some(2),
+        "stderr: {:?}",
+        failing.stderr
+    );
+    let payload = parse_stdout_json(&failing);
+    assert_eq!(payload["passed"], false);
+    assert_eq!(payload["summary"]["error"], 1);
+    assert_eq!(payload["summary"]["warning"], 2);
+    assert_eq!(payload["summary"]["by_rule"]["error_value"], 1);
+    assert_eq!(payload["summary"]["by_rule"]["volatile_function"], 1);
+    assert_eq!(payload["summary"]["by_rule"]["validation_violation"], 1);
+    let violation = payload["findings"]
+        .as_array()
+        .expect("findings")
+        .iter()
+        .find(|finding| finding["rule"] == "validation_violation")
+        .expect("validation finding");
+    assert_eq!(violation["location"], "Sheet1!D3");
+
+    let profile_path = tmp.path().join("lint.yaml");
+    fs::write(
+        &profile_path,
+        "rules:\n  error_value: \"off\"\n  volatile_function: info\nexclude_sheets: []\n",
+    )
+    .expect("write profile");
+    let profile = profile_path.to_str().expect("profile utf8");
+    let relaxed = run_cli(&["verify", "lint", file, "--profile", profile]);
+    assert_eq!(
+        relaxed.status.code(),
+        Some(0),
+        "stderr: {:?}",
+        relaxed.stderr
+    );
+    let relaxed_payload = parse_stdout_json(&relaxed);
+    assert_eq!(relaxed_payload["passed"], true);
+    assert_eq!(relaxed_payload["summary"]["error"], 0);
+    assert_eq!(relaxed_payload["summary"]["info"], 1);
+
+    let strict = run_cli(&[
+        "verify",
+        "lint",
+        file,
+        "--profile",
+        profile,
+        "--fail-on",
+        "warning",
+    ]);
+    assert_eq!(strict.status.code(), Some(2), "stderr: {:?}", strict.stderr);
+
+    fs::write(&profile_path, "rules:\n  no_such_rule: error\n").expect("write profile");
+    let err = assert_invalid_argument(&["verify", "lint", file, "--profile", profile]);
+    assert!(
+        err["message"]
+            .as_str()
+            .unwrap_or_default()
+            .contains("unknown rule 'no_such_rule'")
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn cli_verify_lint_runs_command_rules_from_rules_dir() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("lint-rules.xlsx");
+    let rules_dir = tmp.path().join("lint-rules");
+    fs::create_dir(&rules_dir).expect("rules dir");
+    fs::write(
+        rules_dir.join("big_amount.yaml"),
+        "id: big_amount\ndescription: Amount above 25\ndefault_severity: error\ncommand: sh big_amount.sh\n",
+    )
+    .expect("write manifest");
+    fs::write(
+        rules_dir.join("big_amount.sh"),
+        r#"if grep -q '"sheet_name":"Sheet1"'; then
+  printf '%s' '{"findings":[{"location":"B4","message":"amount above 25"}]}'
+else
+  printf '%s' '{"findings":[]}'
+fi
+"#,
+    )
+    .expect("write rule");
+    write_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
+    let rules = rules_dir.to_str().expect("rules dir utf8");
+
+    let output = run_cli(&["verify", "lint", file, "--rules-dir", rules]);
+    assert_eq!(output.status.code(), Some(2), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["summary"]["by_rule"]["big_amount"], 1);
+    let finding = payload["findings"]
+        .as_array()
+        .expect("findings")
+        .iter()
+        .find(|finding| finding["rule"] == "big_amount")
+        .expect("custom rule finding");
+    assert_eq!(finding["location"], "Sheet1!B4");
+    assert_eq!(finding["severity"], "error");
+
+    let profile_path = tmp.path().join("lint.yaml");
+    fs::write(&profile_path, "rules:\n  big_amount: \"off\"\n").expect("write profile");
+    let profile = profile_path.to_str().expect("profile utf8");
+    let relaxed = run_cli(&[
+        "verify",
+        "lint",
+        file,
+        "--rules-dir",
+        rules,
+        "--profile",
+        profile,
+    ]);
+    assert_eq!(
+        relaxed.status.code(),
+        Some(0),
+        "stderr: {:?}",
+        relaxed.stderr
+    );
+
+    fs::write(rules_dir.join("broken.yaml"), "id: broken\n").expect("write bad manifest");
+    let err = assert_invalid_argument(&["verify", "lint", file, "--rules-dir", rules]);
+    assert!(
+        err["message"]
+            .as_str()
+            .unwrap_or_default()
+            .contains("broken.yaml")
+    );
+}
+
+#[test]
+fn cli_verify_lint_emits_sarif_with_sheet_artifacts_and_cell_regions() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("lint-sarif.xlsx");
+    write_fixture(&workbook_path);
+    let mut workbook = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read fixture");
+    workbook
+        .get_sheet_by_name_mut("Summary")
+        .expect("summary sheet")
+        .get_cell_mut("D3")
+        .set_value("#N/A");
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+    let file = workbook_path.to_str().expect("path utf8");
+
+    let output = run_cli(&["verify", "lint", file, "--format", "sarif"]);
+    assert_eq!(output.status.code(), Some(2), "stderr: {:?}", output.stderr);
+    let sarif = parse_stdout_json(&output);
+    assert_eq!(sarif["version"], "2.1.0");
+    let run = &sarif["runs"][0];
+    assert_eq!(run["properties"]["passed"], false);
+    assert!(
+        run["originalUriBaseIds"]["WORKBOOK"]["uri"]
+            .as_str()
+            .unwrap_or_default()
+            .ends_with("lint-sarif.xlsx/")
+    );
+    assert!(
+        run["tool"]["driver"]["rules"]
+            .as_array()
+            .expect("rules")
+            .iter()
+            .any(|rule| rule["id"] == "error_value")
+    );
+
+    let result = &run["results"][0];
+    assert_eq!(result["ruleId"], "error_value");
+    assert_eq!(result["level"], "error");
+    let physical = &result["locations"][0]["physicalLocation"];
+    assert_eq!(physical["artifactLocation"]["uri"], "Summary");
+    assert_eq!(physical["artifactLocation"]["uriBaseId"], "WORKBOOK");
+    assert_eq!(physical["region"]["startLine"], 3);
+    assert_eq!(physical["region"]["startColumn"], 4);
+    assert_eq!(
+        result["locations"][0]["logicalLocations"][0]["fullyQualifiedName"],
+        "Summary!D3"
+    );
+
+    let relaxed = run_cli(&[
+        "verify",
+        "lint",
+        file,
+        "--format",
+        "sarif",
+        "--fail-on",
+        "never",
+    ]);
+    assert_eq!(
+        relaxed.status.code(),
+        Some(0),
+        "stderr: {:?}",
+        relaxed.stderr
+    );
+    let relaxed_sarif = parse_stdout_json(&relaxed);
+    assert_eq!(relaxed_sarif["runs"][0]["results"][0]["level"], "error");
+}
+
+#[test]
+fn cli_verify_run_tests_reports_tests_sheet_and_sidecar_expectations() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("run-tests.xlsx");
+    write_fixture(&workbook_path);
+
+    let mut workbook =
+        umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read fixture workbook");
+    workbook.new_sheet("_tests").expect("add tests sheet");
+    {
+        let tests = workbook
+            .get_sheet_by_name_mut("_tests")
+            .expect("tests sheet exists");
+        tests.get_cell_mut("A1").set_value("name");
+        tests.get_cell_mut("B1").set_value("cell");
+        tests.get_cell_mut("C1").set_value("expect");
+        tests.get_cell_mut("A2").set_value("alice total");
+        tests.get_cell_mut("B2").set_value("Sheet1!C2");
+        tests.get_cell_mut("C2").set_value_number(20.0);
+        tests.get_cell_mut("A3").set_value("flag");
+        tests.get_cell_mut("B3").set_value("Summary!B1");
+        tests.get_cell_mut("C3").set_value("Ready");
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+    let before = fs::read(&workbook_path).expect("read source bytes");
+
+    let file = workbook_path.to_str().expect("utf8 path");
+    let output = run_cli(&["verify", "run-tests", file]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["source"], "_tests");
+    assert_eq!(payload["recalculated"], true);
+    assert_eq!(payload["all_passed"], true);
+    assert_eq!(payload["total"], 2);
+    assert_eq!(payload["passed"], 2);
+    assert_eq!(payload["results"][0]["name"], "alice total");
+    assert_eq!(
+        fs::read(&workbook_path).expect("reread source bytes"),
+        before,
+        "run-tests must not modify the source workbook"
+    );
+
+    let sidecar_path = tmp.path().join("run-tests.json");
+    write_ops_payload(
+        &sidecar_path,
+        r#"{"tests":[{"cell":"Sheet1!C3","expect":40},{"name":"wrong","cell":"Sheet1!C4","expect":61,"tolerance":0.5}]}"#,
+    );
+    let sidecar = sidecar_path.to_str().expect("utf8 path");
+    let output = run_cli(&["verify", "run-tests", file, "--tests", sidecar]);
+    assert_eq!(output.status.code(), Some(2), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["all_passed"], false);
+    assert_eq!(payload["passed"], 1);
+    assert_eq!(payload["failed"], 1);
+    assert_eq!(payload["results"][1]["passed"], false);
+    assert_eq!(payload["results"][1]["actual"]["value"], 60.0);
+    assert!(
+        payload["results"][1]["message"]
+            .as_str()
+            .unwrap_or("")
+            .contains("expected 61")
+    );
+
+    let plain_path = tmp.path().join("run-tests-plain.xlsx");
+    write_fixture(&plain_path);
+    let err = assert_invalid_argument(&[
+        "verify",
+        "run-tests",
+        plain_path.to_str().expect("utf8 path"),
+    ]);
+    assert!(err["message"].as_str().unwrap_or("").contains("_tests"));
+}
+
 #[test]
 fn cli_phase1_named_ranges_filters_are_deterministic() {
     let tmp = tempdir().expect("tempdir");
@@ -2212,46 +2718,447 @@ fn cli_sheet_page_column_filters_support_union_and_sheet_order() {
 }
 
 #[test]
-fn cli_sheet_page_accepts_all_formats_and_sets_expected_payload_branch() {
+fn cli_sheet_page_header_match_contains_and_fuzzy_report_resolutions() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("sheet-page-formats.xlsx");
-    write_fixture(&workbook_path);
+    let workbook_path = tmp.path().join("sheet-page-header-match.xlsx");
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook
+            .get_sheet_by_name_mut("Sheet1")
+            .expect("default sheet exists");
+        sheet.get_cell_mut("A1").set_value("Customer Name");
+        sheet.get_cell_mut("B1").set_value("Quantity");
+        sheet.get_cell_mut("C1").set_value("Unit Price (USD)");
+        sheet.get_cell_mut("D1").set_value("Region");
+        sheet.get_cell_mut("A2").set_value("Acme");
+        sheet.get_cell_mut("B2").set_value_number(4.0);
+        sheet.get_cell_mut("C2").set_value_number(2.5);
+        sheet.get_cell_mut("D2").set_value("West");
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
     let file = workbook_path.to_str().expect("path utf8");
+    let page = |extra: &[&str]| {
+        let mut args = vec!["sheet-page", file, "Sheet1", "--format", "compact"];
+        args.extend_from_slice(extra);
+        let output = run_cli(&args);
+        assert!(output.status.success(), "stderr: {:?}", output.stderr);
+        parse_stdout_json(&output)
+    };
+    let headers = |payload: &Value| {
+        payload["compact"]["headers"]
+            .as_array()
+            .expect("compact headers")
+            .iter()
+            .map(|v| v.as_str().expect("header string").to_string())
+            .collect::<Vec<_>>()
+    };
 
-    for format in ["full", "compact", "values_only"] {
-        let page = run_cli(&[
-            "sheet-page",
-            file,
-            "Sheet1",
-            "--start-row",
-            "2",
-            "--page-size",
-            "1",
-            "--format",
-            format,
-        ]);
-        assert!(page.status.success(), "stderr: {:?}", page.stderr);
-        let payload = parse_stdout_json(&page);
+    let exact = page(&["--columns-by-header", " customer NAME "]);
+    assert_eq!(headers(&exact), vec!["Row", "Customer Name"]);
+    assert_eq!(exact["resolved_headers"][0]["column"], "A");
+    assert!(exact["resolved_headers"][0].get("score").is_none());
 
-        assert_eq!(payload["format"], format);
-        match format {
-            "full" => {
-                assert!(payload["rows"].is_array());
-                assert!(payload.get("compact").is_none());
-                assert!(payload.get("values_only").is_none());
-            }
-            "compact" => {
-                assert!(payload["compact"].is_object());
-                assert!(payload.get("rows").is_none());
-                assert!(payload.get("values_only").is_none());
-            }
-            "values_only" => {
-                assert!(payload["values_only"].is_object());
-                assert!(payload.get("rows").is_none());
-                assert!(payload.get("compact").is_none());
-            }
-            _ => unreachable!(),
-        }
+    let contains = page(&["--columns-by-header", "price", "--header-match", "contains"]);
+    assert_eq!(headers(&contains), vec!["Row", "Unit Price (USD)"]);
+    assert_eq!(
+        contains["resolved_headers"][0]["header"],
+        "Unit Price (USD)"
+    );
+
+    let fuzzy = page(&[
+        "--columns-by-header",
+        "cust name,qty,zzz",
+        "--header-match",
+        "fuzzy",
+    ]);
+    assert_eq!(headers(&fuzzy), vec!["Row", "Customer Name", "Quantity"]);
+    let resolved = fuzzy["resolved_headers"].as_array().expect("resolved");
+    assert_eq!(resolved.len(), 2);
+    assert_eq!(resolved[0]["selector"], "cust name");
+    assert_eq!(resolved[0]["header"], "Customer Name");
+    assert_eq!(resolved[1]["column"], "B");
+    assert!(resolved[1]["score"].as_f64().expect("score") >= 0.6);
+    assert_eq!(fuzzy["unmatched_headers"], serde_json::json!(["zzz"]));
+
+    let strict = page(&[
+        "--columns-by-header",
+        "cust name,qty",
+        "--header-match",
+        "fuzzy",
+        "--header-match-threshold",
+        "0.95",
+    ]);
+    assert_eq!(headers(&strict), vec!["Row", "Customer Name"]);
+    assert_eq!(strict["unmatched_headers"], serde_json::json!(["qty"]));
+
+    assert_invalid_argument(&[
+        "sheet-page",
+        file,
+        "Sheet1",
+        "--format",
+        "compact",
+        "--columns-by-header",
+        "qty",
+        "--header-match",
+        "contains",
+        "--header-match-threshold",
+        "0.5",
+    ]);
+}
+
+#[test]
+fn cli_workbook_page_continues_across_sheets_with_one_cursor() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("workbook-page.xlsx");
+    write_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
+
+    let first = run_cli(&[
+        "workbook-page",
+        file,
+        "--format",
+        "values_only",
+        "--page-size",
+        "3",
+    ]);
+    assert!(first.status.success(), "stderr: {:?}", first.stderr);
+    let payload = parse_stdout_json(&first);
+    assert_eq!(payload["sheet_count"], 2);
+    let pages = payload["pages"].as_array().expect("pages");
+    assert_eq!(pages.len(), 1);
+    assert_eq!(pages[0]["sheet_name"], "Sheet1");
+    assert_eq!(pages[0]["values_only"]["rows"].as_array().unwrap().len(), 3);
+    assert_eq!(payload["next_start_sheet"], "Sheet1");
+    assert_eq!(payload["next_start_row"], 4);
+
+    let next = run_cli(&[
+        "read",
+        "workbook-page",
+        file,
+        "--format",
+        "values_only",
+        "--page-size",
+        "3",
+        "--start-sheet",
+        "Sheet1",
+        "--start-row",
+        "4",
+    ]);
+    assert!(next.status.success(), "stderr: {:?}", next.stderr);
+    let payload = parse_stdout_json(&next);
+    let pages = payload["pages"].as_array().expect("pages");
+    assert_eq!(pages.len(), 2);
+    assert_eq!(pages[0]["sheet_name"], "Sheet1");
+    assert_eq!(pages[0]["values_only"]["rows"].as_array().unwrap().len(), 1);
+    assert_eq!(pages[1]["sheet_name"], "Summary");
+    assert!(payload.get("next_start_sheet").is_none(), "{payload}");
+    assert!(payload.get("next_start_row").is_none(), "{payload}");
+
+    assert_error_code(
+        &[
+            "workbook-page",
+            file,
+            "--format",
+            "compact",
+            "--start-row",
+            "2",
+        ],
+        "INVALID_ARGUMENT",
+    );
+}
+
+#[test]
+fn cli_estimate_reports_counts_and_truncation_for_prospective_read() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("estimate.xlsx");
+    write_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
+
+    let output = run_cli(&[
+        "estimate",
+        file,
+        "--sheet",
+        "Sheet1",
+        "--range",
+        "A1:C4",
+        "--table-format",
+        "json",
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["sheet_name"], "Sheet1");
+    assert_eq!(payload["range"], "A1:C4");
+    assert_eq!(payload["row_count"], 3);
+    assert_eq!(payload["column_count"], 3);
+    assert_eq!(payload["cell_count"], 9);
+    assert_eq!(payload["sampled_rows"], 3);
+    assert_eq!(payload["requested_rows"], 3);
+    assert_eq!(payload["would_truncate"], false);
+    assert_eq!(payload["pages_needed"], 1);
+    assert!(payload["estimated_payload_bytes"].as_u64().unwrap() > 0);
+    assert_eq!(payload["caps"]["max_cells"], 10_000);
+
+    let filtered = run_cli(&[
+        "read",
+        "estimate",
+        file,
+        "--sheet",
+        "Sheet1",
+        "--range",
+        "A1:C4",
+        "--filters-json",
+        r#"[{"column":"Name","op":"eq","value":"Alice"}]"#,
+    ]);
+    assert!(filtered.status.success(), "stderr: {:?}", filtered.stderr);
+    let payload = parse_stdout_json(&filtered);
+    assert_eq!(payload["row_count"], 1);
+    assert_eq!(payload["cell_count"], 3);
+
+    assert_error_code(
+        &["estimate", file, "--sheet", "Sheet1", "--limit", "0"],
+        "INVALID_ARGUMENT",
+    );
+}
+
+#[test]
+fn cli_render_validates_output_and_resolution_before_rendering() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("render.xlsx");
+    write_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
+    let png = tmp.path().join("shot.png");
+    let png = png.to_str().expect("path utf8");
+
+    assert_error_code(
+        &["render", file, "Sheet1", "--output", "shot.jpg"],
+        "INVALID_ARGUMENT",
+    );
+    assert_error_code(
+        &["render", file, "Sheet1", "--output", png, "--dpi", "10"],
+        "INVALID_ARGUMENT",
+    );
+    assert_error_code(
+        &[
+            "read", "render", file, "Sheet1", "--output", png, "--dpi", "300", "--scale", "4",
+        ],
+        "INVALID_ARGUMENT",
+    );
+    assert!(!Path::new(png).exists());
+}
+
+#[test]
+fn cli_visual_diff_validates_arguments_before_rendering() {
+    let tmp = tempdir().expect("tempdir");
+    let original_path = tmp.path().join("visual-diff-original.xlsx");
+    let modified_path = tmp.path().join("visual-diff-modified.xlsx");
+    write_fixture(&original_path);
+    write_fixture(&modified_path);
+    let original = original_path.to_str().expect("path utf8");
+    let modified = modified_path.to_str().expect("path utf8");
+    let png = tmp.path().join("diff.png");
+    let png = png.to_str().expect("path utf8");
+
+    assert_error_code(
+        &[
+            "visual-diff",
+            original,
+            modified,
+            "Sheet1",
+            "--output",
+            "diff.jpg",
+        ],
+        "INVALID_ARGUMENT",
+    );
+    assert_error_code(
+        &[
+            "verify",
+            "visual-diff",
+            original,
+            modified,
+            "Sheet1",
+            "--output",
+            png,
+            "--dpi",
+            "10",
+        ],
+        "INVALID_ARGUMENT",
+    );
+    assert!(!Path::new(png).exists());
+
+    fs::write(png, b"existing").expect("seed output");
+    assert_error_code(
+        &["visual-diff", original, modified, "Sheet1", "--output", png],
+        "OUTPUT_EXISTS",
+    );
+    assert_eq!(fs::read(png).expect("read output"), b"existing");
+}
+
+#[test]
+fn cli_export_pdf_validates_arguments_before_exporting() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("export-pdf.xlsx");
+    write_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
+    let pdf = tmp.path().join("report.pdf");
+    let pdf = pdf.to_str().expect("path utf8");
+
+    assert_error_code(
+        &["export-pdf", file, "--output", "report.png"],
+        "INVALID_ARGUMENT",
+    );
+    assert_error_code(
+        &["export-pdf", file, "--print-area", "A1:C4", "--output", pdf],
+        "INVALID_ARGUMENT",
+    );
+    assert!(!Path::new(pdf).exists());
+
+    fs::write(pdf, b"existing").expect("seed output");
+    assert_error_code(
+        &[
+            "workbook",
+            "export-pdf",
+            file,
+            "--sheet",
+            "Sheet1",
+            "--output",
+            pdf,
+        ],
+        "OUTPUT_EXISTS",
+    );
+    assert_eq!(fs::read(pdf).expect("read output"), b"existing");
+}
+
+#[test]
+fn cli_sheet_page_column_orientation_pages_by_column() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("sheet-page-orientation.xlsx");
+    write_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
+
+    let first = run_cli(&[
+        "sheet-page",
+        file,
+        "Sheet1",
+        "--format",
+        "compact",
+        "--orientation",
+        "columns",
+        "--page-size",
+        "2",
+    ]);
+    assert!(first.status.success(), "stderr: {:?}", first.stderr);
+    let payload = parse_stdout_json(&first);
+    assert_eq!(payload["orientation"], "columns");
+    assert!(payload.get("compact").is_none(), "{payload}");
+    assert_eq!(payload["column_page"]["first_row"], 2);
+    assert_eq!(payload["column_page"]["last_row"], 4);
+    let columns = payload["column_page"]["columns"]
+        .as_array()
+        .expect("columns");
+    assert_eq!(columns.len(), 2);
+    assert_eq!(columns[0]["column"], "A");
+    assert_eq!(columns[0]["header"], "Name");
+    assert_eq!(columns[0]["values"][2]["value"], "Carol");
+    assert_eq!(columns[1]["header"], "Amount");
+    assert_eq!(columns[1]["values"][0]["value"], 10.0);
+    assert_eq!(payload["next_start_column"], "C");
+
+    let next = run_cli(&[
+        "sheet-page",
+        file,
+        "Sheet1",
+        "--format",
+        "values_only",
+        "--orientation",
+        "columns",
+        "--page-size",
+        "2",
+        "--start-column",
+        "C",
+    ]);
+    assert!(next.status.success(), "stderr: {:?}", next.stderr);
+    let payload = parse_stdout_json(&next);
+    let columns = payload["column_page"]["columns"]
+        .as_array()
+        .expect("columns");
+    assert_eq!(columns.len(), 1);
+    assert_eq!(columns[0]["header"], "Total");
+    assert!(payload.get("next_start_column").is_none(), "{payload}");
+
+    let by_header = run_cli(&[
+        "sheet-page",
+        file,
+        "Sheet1",
+        "--format",
+        "compact",
+        "--orientation",
+        "columns",
+        "--columns-by-header",
+        "Amount",
+    ]);
+    assert!(by_header.status.success(), "stderr: {:?}", by_header.stderr);
+    let payload = parse_stdout_json(&by_header);
+    let columns = payload["column_page"]["columns"]
+        .as_array()
+        .expect("columns");
+    assert_eq!(columns.len(), 1);
+    assert_eq!(columns[0]["column"], "B");
+
+    assert_error_code(
+        &[
+            "sheet-page",
+            file,
+            "Sheet1",
+            "--format",
+            "compact",
+            "--start-column",
+            "B",
+        ],
+        "INVALID_ARGUMENT",
+    );
+}
+
+#[test]
+fn cli_sheet_page_accepts_all_formats_and_sets_expected_payload_branch() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("sheet-page-formats.xlsx");
+    write_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
+
+    for format in ["full", "compact", "values_only"] {
+        let page = run_cli(&[
+            "sheet-page",
+            file,
+            "Sheet1",
+            "--start-row",
+            "2",
+            "--page-size",
+            "1",
+            "--format",
+            format,
+        ]);
+        assert!(page.status.success(), "stderr: {:?}", page.stderr);
+        let payload = parse_stdout_json(&page);
+
+        assert_eq!(payload["format"], format);
+        match format {
+            "full" => {
+                assert!(payload["rows"].is_array());
+                assert!(payload.get("compact").is_none());
+                assert!(payload.get("values_only").is_none());
+            }
+            "compact" => {
+                assert!(payload["compact"].is_object());
+                assert!(payload.get("rows").is_none());
+                assert!(payload.get("values_only").is_none());
+            }
+            "values_only" => {
+                assert!(payload["values_only"].is_object());
+                assert!(payload.get("rows").is_none());
+                assert!(payload.get("compact").is_none());
+            }
+            _ => unreachable!(),
+        }
     }
 }
 
@@ -3382,41 +4289,387 @@ fn cli_read_table_filters_support_unfiltered_json_and_file_inputs() {
 }
 
 #[test]
-fn cli_read_table_allows_last_and_distributed_sampling_at_zero_offset() {
+fn cli_read_table_header_rows_flatten_and_no_header_reads_every_row() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("read-table-sample-modes.xlsx");
-    write_fixture(&workbook_path);
+    let workbook_path = tmp.path().join("read-table-headers.xlsx");
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook
+            .get_sheet_by_name_mut("Sheet1")
+            .expect("default sheet exists");
+        sheet.get_cell_mut("A1").set_value("Region");
+        sheet.add_merge_cells("A1:A2");
+        sheet.get_cell_mut("B1").set_value("Q1");
+        sheet.add_merge_cells("B1:C1");
+        sheet.get_cell_mut("B2").set_value("Actual");
+        sheet.get_cell_mut("C2").set_value("Plan");
+        sheet.get_cell_mut("D1").set_value("Notes");
+        sheet.get_cell_mut("A3").set_value("North");
+        sheet.get_cell_mut("B3").set_value_number(10.0);
+        sheet.get_cell_mut("C3").set_value_number(12.0);
+        sheet.get_cell_mut("A4").set_value("South");
+        sheet.get_cell_mut("B4").set_value_number(8.0);
+        sheet.get_cell_mut("C4").set_value_number(9.0);
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
     let file = workbook_path.to_str().expect("path utf8");
 
-    let last = run_cli(&[
+    let default = run_cli(&[
         "read-table",
         file,
         "--sheet",
         "Sheet1",
-        "--range",
-        "A1:C4",
         "--table-format",
-        "json",
-        "--sample-mode",
-        "last",
-        "--offset",
-        "0",
-        "--limit",
-        "2",
+        "values",
     ]);
-    assert!(last.status.success(), "stderr: {:?}", last.stderr);
-    let last_payload = parse_stdout_json(&last);
-    assert!(last_payload["rows"].is_array());
+    assert!(default.status.success(), "stderr: {:?}", default.stderr);
+    let payload = parse_stdout_json(&default);
+    assert_eq!(payload["header_detection"]["source"], "range_top");
+    assert_eq!(payload["header_detection"]["header_rows"], 1);
+    assert_eq!(payload["header_detection"]["data_start_row"], 2);
 
-    let distributed = run_cli(&[
+    let stacked = run_cli(&[
         "read-table",
         file,
         "--sheet",
         "Sheet1",
-        "--range",
-        "A1:C4",
         "--table-format",
-        "json",
+        "values",
+        "--header-rows",
+        "2",
+    ]);
+    assert!(stacked.status.success(), "stderr: {:?}", stacked.stderr);
+    let payload = parse_stdout_json(&stacked);
+    assert_eq!(
+        payload["headers"],
+        serde_json::json!(["Region", "Q1 / Actual", "Q1 / Plan", "Notes"])
+    );
+    assert_eq!(payload["total_rows"], 2);
+    assert_eq!(payload["values"][0][0], "North");
+    assert_eq!(
+        payload["header_detection"],
+        serde_json::json!({
+            "source": "range_top",
+            "header_row": 1,
+            "header_rows": 2,
+            "data_start_row": 3
+        })
+    );
+
+    let no_header = run_cli(&[
+        "read-table",
+        file,
+        "--sheet",
+        "Sheet1",
+        "--table-format",
+        "values",
+        "--no-header",
+    ]);
+    assert!(no_header.status.success(), "stderr: {:?}", no_header.stderr);
+    let payload = parse_stdout_json(&no_header);
+    assert_eq!(payload["headers"], serde_json::json!(["A", "B", "C", "D"]));
+    assert_eq!(payload["total_rows"], 4);
+    assert_eq!(payload["values"][0][0], "Region");
+    assert_eq!(
+        payload["header_detection"],
+        serde_json::json!({"source": "none", "header_rows": 0, "data_start_row": 1})
+    );
+
+    let conflicting = run_cli(&["read-table", file, "--no-header", "--header-rows", "2"]);
+    assert!(!conflicting.status.success());
+    let err = assert_invalid_argument(&["read-table", file, "--header-rows", "0"]);
+    assert!(
+        err["message"]
+            .as_str()
+            .is_some_and(|message| message.contains("--header-rows")),
+        "{err}"
+    );
+}
+
+#[test]
+fn cli_read_table_transposed_reads_columns_as_records() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("read-table-transposed.xlsx");
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook
+            .get_sheet_by_name_mut("Sheet1")
+            .expect("default sheet exists");
+        sheet.get_cell_mut("A1").set_value("Year");
+        sheet.get_cell_mut("A2").set_value("Scenario");
+        sheet.get_cell_mut("A3").set_value("Growth");
+        for (col, year, scenario, growth) in [
+            ("B", "FY24", "Base", 0.05),
+            ("C", "FY25", "Upside", 0.08),
+            ("D", "FY26", "Base", 0.06),
+        ] {
+            sheet.get_cell_mut(format!("{col}1")).set_value(year);
+            sheet.get_cell_mut(format!("{col}2")).set_value(scenario);
+            sheet
+                .get_cell_mut(format!("{col}3"))
+                .set_value_number(growth);
+        }
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+    let file = workbook_path.to_str().expect("path utf8");
+
+    let transposed = run_cli(&[
+        "read-table",
+        file,
+        "--sheet",
+        "Sheet1",
+        "--range",
+        "A1:D3",
+        "--orientation",
+        "transposed",
+        "--table-format",
+        "values",
+        "--filters-json",
+        r#"[{"column":"Scenario","op":"eq","value":"Base"}]"#,
+    ]);
+    assert!(
+        transposed.status.success(),
+        "stderr: {:?}",
+        transposed.stderr
+    );
+    let payload = parse_stdout_json(&transposed);
+    assert_eq!(
+        payload["headers"],
+        serde_json::json!(["Year", "Scenario", "Growth"])
+    );
+    assert_eq!(payload["total_rows"], 2);
+    assert_eq!(
+        payload["values"],
+        serde_json::json!([["FY24", "Base", 0.05], ["FY26", "Base", 0.06]])
+    );
+    assert_eq!(payload["header_detection"]["header_row"], 1);
+    assert_eq!(payload["header_detection"]["data_start_row"], 2);
+
+    let no_header = run_cli(&[
+        "read-table",
+        file,
+        "--sheet",
+        "Sheet1",
+        "--range",
+        "A1:D3",
+        "--orientation",
+        "transposed",
+        "--no-header",
+        "--table-format",
+        "values",
+    ]);
+    assert!(no_header.status.success(), "stderr: {:?}", no_header.stderr);
+    let payload = parse_stdout_json(&no_header);
+    assert_eq!(payload["headers"], serde_json::json!(["1", "2", "3"]));
+    assert_eq!(payload["total_rows"], 4);
+    assert_eq!(
+        payload["values"][0],
+        serde_json::json!(["Year", "Scenario", "Growth"])
+    );
+}
+
+#[test]
+fn cli_query_table_groups_filters_and_aggregates() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("query-table.xlsx");
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook
+            .get_sheet_by_name_mut("Sheet1")
+            .expect("default sheet exists");
+        sheet.get_cell_mut("A1").set_value("Region");
+        sheet.get_cell_mut("B1").set_value("Amount");
+        for (row, region, amount) in [(2, "North", 10.0), (3, "South", 20.0), (4, "North", 30.0)] {
+            sheet.get_cell_mut((1, row)).set_value(region);
+            sheet.get_cell_mut((2, row)).set_value_number(amount);
+        }
+        sheet.get_cell_mut("A5").set_value("South");
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+    let file = workbook_path.to_str().expect("path utf8");
+
+    let grouped = run_cli(&[
+        "query-table",
+        file,
+        "--sheet",
+        "Sheet1",
+        "--group-by",
+        "Region",
+        "--agg",
+        "sum:Amount",
+        "--agg",
+        "count",
+        "--agg",
+        "avg:Amount=mean",
+    ]);
+    assert!(grouped.status.success(), "stderr: {:?}", grouped.stderr);
+    let payload = parse_stdout_json(&grouped);
+    assert_eq!(
+        payload["columns"],
+        serde_json::json!(["Region", "sum_Amount", "count", "mean"])
+    );
+    assert_eq!(
+        payload["rows"],
+        serde_json::json!([["North", 40.0, 2.0, 20.0], ["South", 20.0, 2.0, 20.0]])
+    );
+    assert_eq!(payload["matched_rows"], 4);
+    assert_eq!(payload["total_groups"], 2);
+
+    let filtered = run_cli(&[
+        "analyze",
+        "query-table",
+        file,
+        "--agg",
+        "max:Amount",
+        "--agg",
+        "count:Amount",
+        "--filters-json",
+        r#"[{"column":"Amount","op":"gt","value":15}]"#,
+    ]);
+    assert!(filtered.status.success(), "stderr: {:?}", filtered.stderr);
+    let payload = parse_stdout_json(&filtered);
+    assert_eq!(payload["rows"], serde_json::json!([[30.0, 2.0]]));
+    assert_eq!(payload["matched_rows"], 2);
+
+    let err = assert_invalid_argument(&["query-table", file, "--agg", "median:Amount"]);
+    assert!(
+        err["message"]
+            .as_str()
+            .is_some_and(|message| message.contains("median")),
+        "{err}"
+    );
+    assert_invalid_argument(&["query-table", file]);
+    let unknown = run_cli(&["query-table", file, "--group-by", "Country"]);
+    assert!(!unknown.status.success());
+}
+
+#[test]
+fn cli_read_table_named_query_applies_stored_fields_and_flags_override() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("read-table-query.xlsx");
+    write_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
+
+    let queries_file = tmp.path().join("queries.yaml");
+    std::fs::write(
+        &queries_file,
+        r#"queries:
+  top_amounts:
+    sheet_name: Sheet1
+    range: A1:C4
+    filters: [{ column: Amount, op: gt, value: 10 }]
+    sort: [{ column: Amount, descending: true }]
+    format: json
+"#,
+    )
+    .expect("write queries file");
+    let queries_path = queries_file.to_str().expect("queries path utf8");
+
+    let stored = run_cli(&[
+        "read-table",
+        file,
+        "--query",
+        "top_amounts",
+        "--queries-file",
+        queries_path,
+    ]);
+    assert!(stored.status.success(), "stderr: {:?}", stored.stderr);
+    let stored_payload = parse_stdout_json(&stored);
+    let names: Vec<&str> = stored_payload["rows"]
+        .as_array()
+        .expect("rows")
+        .iter()
+        .map(|row| row["Name"]["value"].as_str().expect("name"))
+        .collect();
+    assert_eq!(names, vec!["Carol", "Bob"]);
+
+    let overridden = run_cli(&[
+        "read-table",
+        file,
+        "--query",
+        "top_amounts",
+        "--queries-file",
+        queries_path,
+        "--limit",
+        "1",
+    ]);
+    assert!(
+        overridden.status.success(),
+        "stderr: {:?}",
+        overridden.stderr
+    );
+    let overridden_payload = parse_stdout_json(&overridden);
+    assert_eq!(overridden_payload["rows"].as_array().map(Vec::len), Some(1));
+    assert_eq!(overridden_payload["rows"][0]["Name"]["value"], "Carol");
+
+    // Without --queries-file the workspace file is read from .asp/queries.yaml.
+    std::fs::create_dir_all(tmp.path().join(".asp")).expect("create .asp");
+    std::fs::copy(&queries_file, tmp.path().join(".asp/queries.yaml")).expect("copy queries");
+    let workspace = Command::new(assert_cmd::cargo::cargo_bin!("agent-spreadsheet"))
+        .current_dir(tmp.path())
+        .args(["read-table", file, "--query", "top_amounts"])
+        .output()
+        .expect("run agent-spreadsheet");
+    assert!(workspace.status.success(), "stderr: {:?}", workspace.stderr);
+    assert_eq!(
+        parse_stdout_json(&workspace)["rows"]
+            .as_array()
+            .map(Vec::len),
+        Some(2)
+    );
+
+    let err = assert_invalid_argument(&[
+        "read-table",
+        file,
+        "--query",
+        "missing",
+        "--queries-file",
+        queries_path,
+    ]);
+    assert!(
+        err["message"]
+            .as_str()
+            .is_some_and(|message| message.contains("top_amounts")),
+        "unexpected error envelope: {err}"
+    );
+}
+
+#[test]
+fn cli_read_table_allows_last_and_distributed_sampling_at_zero_offset() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("read-table-sample-modes.xlsx");
+    write_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
+
+    let last = run_cli(&[
+        "read-table",
+        file,
+        "--sheet",
+        "Sheet1",
+        "--range",
+        "A1:C4",
+        "--table-format",
+        "json",
+        "--sample-mode",
+        "last",
+        "--offset",
+        "0",
+        "--limit",
+        "2",
+    ]);
+    assert!(last.status.success(), "stderr: {:?}", last.stderr);
+    let last_payload = parse_stdout_json(&last);
+    assert!(last_payload["rows"].is_array());
+
+    let distributed = run_cli(&[
+        "read-table",
+        file,
+        "--sheet",
+        "Sheet1",
+        "--range",
+        "A1:C4",
+        "--table-format",
+        "json",
         "--sample-mode",
         "distributed",
         "--offset",
@@ -4112,808 +5365,1342 @@ fn cli_transform_batch_in_place_applies_atomically() {
 }
 
 #[test]
-fn cli_transform_batch_output_and_force_modes_apply_with_overwrite_checks() {
+fn cli_transform_batch_continue_on_error_applies_independent_ops() {
     let tmp = tempdir().expect("tempdir");
-    let source_path = tmp.path().join("transform-batch-source.xlsx");
-    let output_path = tmp.path().join("transform-batch-output.xlsx");
-    let ops_path_first = tmp.path().join("ops-first.json");
-    let ops_path_second = tmp.path().join("ops-second.json");
-    write_fixture(&source_path);
-    write_ops_payload(
-        &ops_path_first,
-        r#"{"ops":[{"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"cells","cells":["B2"]},"value":"51"}]}"#,
-    );
+    let workbook_path = tmp.path().join("transform-batch-continue.xlsx");
+    let ops_path = tmp.path().join("ops.json");
+    write_fixture(&workbook_path);
     write_ops_payload(
-        &ops_path_second,
-        r#"{"ops":[{"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"cells","cells":["B3"]},"value":"91"}]}"#,
+        &ops_path,
+        r#"{"ops":[
+            {"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"cells","cells":["B2"]},"value":"44"},
+            {"kind":"fill_range","sheet_name":"Missing","target":{"kind":"cells","cells":["A1"]},"value":"x"},
+            {"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"cells","cells":["D2"]},"value":"SUM(A1:A10","is_formula":true},
+            {"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"cells","cells":["B3"]},"value":"55"}
+        ]}"#,
     );
 
-    let source = source_path.to_str().expect("source utf8");
-    let output = output_path.to_str().expect("output utf8");
-    let ops_first_ref = format!("@{}", ops_path_first.to_str().expect("ops path utf8"));
-    let ops_second_ref = format!("@{}", ops_path_second.to_str().expect("ops path utf8"));
+    let file = workbook_path.to_str().expect("path utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
+    let before = fs::read(&workbook_path).expect("read source");
 
-    let first = run_cli(&[
+    // Without the flag the missing sheet aborts the whole batch.
+    let output = run_cli(&[
         "transform-batch",
-        source,
+        file,
         "--ops",
-        ops_first_ref.as_str(),
-        "--output",
-        output,
+        ops_ref.as_str(),
+        "--in-place",
+        "--formula-parse-policy",
+        "warn",
     ]);
-    assert!(first.status.success(), "stderr: {:?}", first.stderr);
+    assert!(!output.status.success());
+    assert_eq!(fs::read(&workbook_path).expect("read source"), before);
 
-    let source_book = umya_spreadsheet::reader::xlsx::read(&source_path).expect("read source");
-    let source_sheet = source_book
-        .get_sheet_by_name("Sheet1")
-        .expect("sheet exists");
-    assert_eq!(
-        source_sheet
-            .get_cell("B2")
-            .expect("source B2 exists")
-            .get_value(),
-        "10"
-    );
+    let output = run_cli(&[
+        "transform-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
+        "--formula-parse-policy",
+        "warn",
+        "--continue-on-error",
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
 
-    let output_book = umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output");
-    let output_sheet = output_book
-        .get_sheet_by_name("Sheet1")
-        .expect("sheet exists");
-    assert_eq!(
-        output_sheet
-            .get_cell("B2")
-            .expect("output B2 exists")
-            .get_value(),
-        "51"
+    assert_eq!(payload["op_count"], 4);
+    assert_eq!(payload["applied_count"], 2);
+    let indices = |key: &str| -> Vec<u64> {
+        payload[key]
+            .as_array()
+            .unwrap_or_else(|| panic!("{key} array: {payload}"))
+            .iter()
+            .map(|entry| entry["index"].as_u64().expect("index"))
+            .collect()
+    };
+    assert_eq!(indices("applied"), vec![0, 3]);
+    assert_eq!(indices("skipped"), vec![2]);
+    assert_eq!(indices("failed"), vec![1]);
+    assert_eq!(payload["applied"][0]["kind"], "fill_range");
+    assert!(
+        payload["failed"][0]["reason"]
+            .as_str()
+            .is_some_and(|reason| reason.contains("Missing")),
+        "{payload}"
     );
+    assert!(payload["failed"][0]["code"].is_string(), "{payload}");
 
-    assert_error_code(
-        &[
-            "transform-batch",
-            source,
-            "--ops",
-            ops_second_ref.as_str(),
-            "--output",
-            output,
-        ],
-        "OUTPUT_EXISTS",
-    );
-
-    let forced = run_cli(&[
-        "transform-batch",
-        source,
-        "--ops",
-        ops_second_ref.as_str(),
-        "--output",
-        output,
-        "--force",
-    ]);
-    assert!(forced.status.success(), "stderr: {:?}", forced.stderr);
-    let forced_payload = parse_stdout_json(&forced);
-    assert_json_path_eq(&forced_payload, "target_path", output);
-
-    let overwritten = umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output");
-    let overwritten_sheet = overwritten
-        .get_sheet_by_name("Sheet1")
-        .expect("sheet exists");
-    assert_eq!(
-        overwritten_sheet
-            .get_cell("B3")
-            .expect("output B3 exists")
-            .get_value(),
-        "91"
-    );
+    let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read workbook");
+    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet exists");
+    assert_eq!(sheet.get_cell("B2").expect("B2 exists").get_value(), "44");
+    assert_eq!(sheet.get_cell("B3").expect("B3 exists").get_value(), "55");
+    assert!(book.get_sheet_by_name("Missing").is_none());
 }
 
-#[cfg(unix)]
 #[test]
-fn cli_transform_batch_rejects_dangling_symlink_output_without_force() {
+fn cli_transform_batch_verify_with_gates_in_place_write() {
     let tmp = tempdir().expect("tempdir");
-    let source_path = tmp
-        .path()
-        .join("transform-batch-source-dangling-symlink.xlsx");
+    let workbook_path = tmp.path().join("transform-batch-verify-with.xlsx");
     let ops_path = tmp.path().join("ops.json");
-    let output_link = tmp.path().join("dangling-output.xlsx");
-    let missing_target = tmp.path().join("missing-target.xlsx");
-
-    write_fixture(&source_path);
+    let passing_path = tmp.path().join("passing.tests.json");
+    let failing_path = tmp.path().join("failing.tests.json");
+    write_fixture(&workbook_path);
     write_ops_payload(
         &ops_path,
-        r#"{"ops":[{"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"cells","cells":["B2"]},"value":"66"}]}"#,
+        r#"{"ops":[{"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"cells","cells":["A2"]},"value":"Alicia"}]}"#,
+    );
+    write_ops_payload(
+        &passing_path,
+        r#"{"tests":[{"cell":"Sheet1!C3","expect":40},{"cell":"Sheet1!A2","expect":"Alicia"}]}"#,
+    );
+    write_ops_payload(
+        &failing_path,
+        r#"[{"name":"bob total","cell":"Sheet1!C3","expect":41}]"#,
     );
 
-    symlink(&missing_target, &output_link).expect("create dangling symlink");
-
-    let source = source_path.to_str().expect("source utf8");
-    let output = output_link.to_str().expect("output utf8");
+    let file = workbook_path.to_str().expect("path utf8");
     let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
+    let before = fs::read(&workbook_path).expect("read source before gate failure");
 
     let err = assert_error_code(
         &[
-            "transform-batch",
-            source,
+            "write",
+            "batch",
+            "transform",
+            file,
             "--ops",
             ops_ref.as_str(),
-            "--output",
-            output,
+            "--in-place",
+            "--verify-with",
+            failing_path.to_str().expect("tests path utf8"),
         ],
-        "OUTPUT_EXISTS",
+        "VERIFICATION_FAILED",
     );
-    assert!(
-        err["message"]
-            .as_str()
-            .unwrap_or_default()
-            .contains("already exists")
+    let message = err["message"].as_str().unwrap_or_default();
+    assert!(message.contains("1 of 1"), "message: {message}");
+    assert!(message.contains("bob total"), "message: {message}");
+    assert_eq!(
+        fs::read(&workbook_path).expect("read source after gate failure"),
+        before,
+        "failed verification must leave the source untouched"
     );
 
-    assert!(
-        fs::symlink_metadata(&output_link).is_ok(),
-        "dangling symlink should remain in place"
+    let output = run_cli(&[
+        "write",
+        "batch",
+        "transform",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
+        "--verify-with",
+        passing_path.to_str().expect("tests path utf8"),
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["verification"]["total"], 2);
+    assert_eq!(payload["verification"]["passed"], 2);
+
+    let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read workbook");
+    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet exists");
+    assert_eq!(
+        sheet.get_cell("A2").expect("A2 exists").get_value(),
+        "Alicia"
     );
+    // Verification recalculates a throwaway copy, so the written file keeps
+    // the fixture's uncached formula.
+    assert_eq!(sheet.get_cell("C3").expect("C3 exists").get_value(), "");
 }
 
 #[test]
-fn cli_transform_batch_rejects_invalid_mode_combinations() {
+fn cli_range_import_verify_with_gates_write() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("transform-batch-mode-matrix.xlsx");
-    let ops_path = tmp.path().join("ops.json");
+    let workbook_path = tmp.path().join("range-import-verify-with.xlsx");
+    let csv_path = tmp.path().join("amounts.csv");
+    let passing_path = tmp.path().join("passing.tests.json");
+    let failing_path = tmp.path().join("failing.tests.json");
     write_fixture(&workbook_path);
+    fs::write(&csv_path, "15\n25\n").expect("write csv");
     write_ops_payload(
-        &ops_path,
-        r#"{"ops":[{"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"cells","cells":["B2"]},"value":"7"}]}"#,
+        &passing_path,
+        r#"{"tests":[{"cell":"Sheet1!C2","expect":30},{"cell":"Sheet1!C3","expect":50}]}"#,
+    );
+    write_ops_payload(
+        &failing_path,
+        r#"[{"name":"bob total","cell":"Sheet1!C3","expect":40}]"#,
     );
 
     let file = workbook_path.to_str().expect("path utf8");
-    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
+    let csv = csv_path.to_str().expect("csv path utf8");
+    let before = fs::read(&workbook_path).expect("read source before gate failure");
 
-    assert_invalid_argument(&["transform-batch", file, "--ops", ops_ref.as_str()]);
-    assert_invalid_argument(&[
-        "transform-batch",
+    let err = assert_error_code(
+        &[
+            "range-import",
+            file,
+            "Sheet1",
+            "--anchor",
+            "B2",
+            "--from-csv",
+            csv,
+            "--in-place",
+            "--verify-with",
+            failing_path.to_str().expect("tests path utf8"),
+        ],
+        "VERIFICATION_FAILED",
+    );
+    let message = err["message"].as_str().unwrap_or_default();
+    assert!(message.contains("bob total"), "message: {message}");
+    assert_eq!(
+        fs::read(&workbook_path).expect("read source after gate failure"),
+        before,
+        "failed verification must leave the source untouched"
+    );
+
+    let output = run_cli(&[
+        "range-import",
         file,
-        "--ops",
-        ops_ref.as_str(),
-        "--dry-run",
+        "Sheet1",
+        "--anchor",
+        "B2",
+        "--from-csv",
+        csv,
         "--in-place",
+        "--verify-with",
+        passing_path.to_str().expect("tests path utf8"),
     ]);
-    assert_invalid_argument(&[
-        "transform-batch",
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["verification"]["passed"], 2);
+
+    let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read workbook");
+    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet exists");
+    assert_eq!(sheet.get_cell("B3").expect("B3 exists").get_value(), "25");
+}
+
+#[test]
+fn cli_range_import_journals_applied_writes() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("range-import-audit.xlsx");
+    let csv_path = tmp.path().join("amounts.csv");
+    let audit_path = tmp.path().join("audit.jsonl");
+    write_fixture(&workbook_path);
+    fs::write(&csv_path, "15\n25\n").expect("write csv");
+
+    let file = workbook_path.to_str().expect("path utf8");
+    let csv = csv_path.to_str().expect("csv path utf8");
+    let audit = audit_path.to_str().expect("audit path utf8");
+    let original_hash = spreadsheet_kit::utils::hash_file_sha256_hex(&workbook_path).expect("hash");
+    let csv_hash = spreadsheet_kit::utils::hash_file_sha256_hex(&csv_path).expect("hash csv");
+
+    let dry_run = run_cli(&[
+        "range-import",
         file,
-        "--ops",
-        ops_ref.as_str(),
+        "Sheet1",
+        "--anchor",
+        "B2",
+        "--from-csv",
+        csv,
         "--dry-run",
-        "--output",
-        "out.xlsx",
+        "--audit-log",
+        audit,
     ]);
-    assert_invalid_argument(&[
-        "transform-batch",
+    assert!(dry_run.status.success(), "stderr: {:?}", dry_run.stderr);
+    assert!(!audit_path.exists(), "dry runs must not journal");
+
+    let output = run_cli(&[
+        "range-import",
         file,
-        "--ops",
-        ops_ref.as_str(),
+        "Sheet1",
+        "--anchor",
+        "B2",
+        "--from-csv",
+        csv,
         "--in-place",
-        "--output",
-        "out.xlsx",
-    ]);
-    assert_invalid_argument(&[
-        "transform-batch",
-        file,
-        "--ops",
-        ops_ref.as_str(),
-        "--force",
-    ]);
-    assert_invalid_argument(&[
-        "transform-batch",
-        file,
-        "--ops",
-        ops_ref.as_str(),
-        "--output",
-        file,
+        "--audit-log",
+        audit,
     ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+
+    let journal = fs::read_to_string(&audit_path).expect("read audit log");
+    let records: Vec<Value> = journal
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("record json"))
+        .collect();
+    assert_eq!(records.len(), 1, "journal: {journal}");
+    assert_eq!(records[0]["command"], "range-import");
+    assert_eq!(records[0]["ops_hash"], csv_hash);
+    assert_eq!(records[0]["before_hash"], original_hash);
+    assert_eq!(records[0]["operation_counts"]["grid_import"], 1);
 }
 
 #[test]
-fn cli_transform_batch_rejects_invalid_ops_payloads() {
+fn cli_range_import_snapshots_in_place_writes() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("transform-batch-invalid-ops.xlsx");
-    let malformed_path = tmp.path().join("ops-malformed.json");
-    let schema_path = tmp.path().join("ops-schema.json");
+    let workbook_path = tmp.path().join("range-import-snapshot.xlsx");
+    let csv_path = tmp.path().join("amounts.csv");
+    let snapshot_dir = tmp.path().join("snapshots");
     write_fixture(&workbook_path);
-    write_ops_payload(&malformed_path, "{not-json}");
-    write_ops_payload(&schema_path, r#"{"ops":[{"kind":"unknown_op"}]}"#);
+    fs::write(&csv_path, "15\n25\n").expect("write csv");
 
     let file = workbook_path.to_str().expect("path utf8");
+    let csv = csv_path.to_str().expect("csv path utf8");
+    let dir = snapshot_dir.to_str().expect("dir utf8");
 
-    assert_error_code(
-        &["transform-batch", file, "--ops", "ops.json", "--dry-run"],
-        "INVALID_OPS_PAYLOAD",
+    let err = assert_invalid_argument(&[
+        "range-import",
+        file,
+        "Sheet1",
+        "--anchor",
+        "B2",
+        "--from-csv",
+        csv,
+        "--dry-run",
+        "--snapshot-dir",
+        dir,
+    ]);
+    assert!(
+        err["message"]
+            .as_str()
+            .is_some_and(|message| message.contains("--snapshot-dir requires --in-place")),
+        "error: {err}"
     );
+    assert!(!snapshot_dir.exists());
 
-    let malformed_ref = format!("@{}", malformed_path.to_str().expect("ops path utf8"));
-    assert_error_code(
-        &[
-            "transform-batch",
-            file,
-            "--ops",
-            malformed_ref.as_str(),
-            "--dry-run",
-        ],
-        "INVALID_OPS_PAYLOAD",
-    );
+    let original_hash = spreadsheet_kit::utils::hash_file_sha256_hex(&workbook_path).expect("hash");
+    let output = run_cli(&[
+        "range-import",
+        file,
+        "Sheet1",
+        "--anchor",
+        "B2",
+        "--from-csv",
+        csv,
+        "--in-place",
+        "--snapshot-dir",
+        dir,
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    let snapshot_id = payload["snapshot"]["id"]
+        .as_str()
+        .expect("snapshot id")
+        .to_string();
+    assert_eq!(payload["snapshot"]["command"], "range-import");
+    assert_eq!(payload["snapshot"]["sha256"], original_hash);
 
-    let schema_ref = format!("@{}", schema_path.to_str().expect("ops path utf8"));
-    assert_error_code(
-        &[
-            "transform-batch",
-            file,
-            "--ops",
-            schema_ref.as_str(),
-            "--dry-run",
-        ],
-        "INVALID_OPS_PAYLOAD",
+    let restored = run_cli(&[
+        "restore",
+        file,
+        "--snapshot",
+        snapshot_id.as_str(),
+        "--snapshot-dir",
+        dir,
+    ]);
+    assert!(restored.status.success(), "stderr: {:?}", restored.stderr);
+    assert_eq!(
+        spreadsheet_kit::utils::hash_file_sha256_hex(&workbook_path).expect("hash"),
+        original_hash
     );
 }
 
 #[cfg(unix)]
 #[test]
-fn cli_transform_batch_maps_write_failures_and_preserves_source() {
+fn cli_write_hooks_receive_manifest_and_pre_apply_can_veto() {
     let tmp = tempdir().expect("tempdir");
-    let source_path = tmp.path().join("transform-batch-write-fail-source.xlsx");
-    let blocked_dir = tmp.path().join("blocked");
-    let blocked_output = blocked_dir.join("output.xlsx");
+    let workbook_path = tmp.path().join("transform-batch-hooks.xlsx");
     let ops_path = tmp.path().join("ops.json");
-    write_fixture(&source_path);
+    let pre_event_path = tmp.path().join("pre.json");
+    let post_event_path = tmp.path().join("post.json");
+    write_fixture(&workbook_path);
     write_ops_payload(
         &ops_path,
-        r#"{"ops":[{"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"cells","cells":["B2"]},"value":"123"}]}"#,
+        r#"{"ops":[{"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"cells","cells":["B2"]},"value":"44"}]}"#,
     );
-    fs::create_dir(&blocked_dir).expect("create blocked dir");
-
-    let mut perms = fs::metadata(&blocked_dir)
-        .expect("blocked metadata")
-        .permissions();
-    perms.set_mode(0o555);
-    fs::set_permissions(&blocked_dir, perms.clone()).expect("set blocked perms");
 
-    let before = fs::read(&source_path).expect("read source before write failure");
-    let source = source_path.to_str().expect("source utf8");
-    let output = blocked_output.to_str().expect("output utf8");
+    let file = workbook_path.to_str().expect("path utf8");
     let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
-
-    let err = assert_error_code(
-        &[
-            "transform-batch",
-            source,
-            "--ops",
-            ops_ref.as_str(),
-            "--output",
-            output,
-        ],
-        "WRITE_FAILED",
-    );
+    let args = [
+        "transform-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
+    ];
+
+    let before = fs::read(&workbook_path).expect("read source before veto");
+    let vetoed = Command::new(assert_cmd::cargo::cargo_bin!("agent-spreadsheet"))
+        .args(args)
+        .env(
+            "SPREADSHEET_MCP_PRE_APPLY_HOOK",
+            "echo 'change freeze in effect' >&2; exit 3",
+        )
+        .output()
+        .expect("run agent-spreadsheet");
+    assert!(!vetoed.status.success());
+    let err = parse_stderr_json(&vetoed);
+    assert_eq!(err["code"], "HOOK_REJECTED", "unexpected envelope: {err}");
+    let message = err["message"].as_str().unwrap_or_default();
+    assert!(message.contains("exit code 3"), "message: {message}");
     assert!(
-        err["message"]
-            .as_str()
-            .unwrap_or_default()
-            .contains("unable to allocate temp file")
-            || err["message"]
-                .as_str()
-                .unwrap_or_default()
-                .contains("Permission denied")
+        message.contains("change freeze in effect"),
+        "message: {message}"
+    );
+    assert_eq!(
+        fs::read(&workbook_path).expect("read source after veto"),
+        before,
+        "a vetoed write must leave the target untouched"
     );
 
-    let mut restore = perms;
-    restore.set_mode(0o755);
-    fs::set_permissions(&blocked_dir, restore).expect("restore blocked perms");
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("agent-spreadsheet"))
+        .args(args)
+        .env(
+            "SPREADSHEET_MCP_PRE_APPLY_HOOK",
+            format!("cat > '{}'", pre_event_path.display()),
+        )
+        .env(
+            "SPREADSHEET_MCP_POST_APPLY_HOOK",
+            format!("cat > '{}'", post_event_path.display()),
+        )
+        .output()
+        .expect("run agent-spreadsheet");
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
 
-    let after = fs::read(&source_path).expect("read source after write failure");
-    assert_eq!(before, after, "source workbook changed after write failure");
+    let pre: Value =
+        serde_json::from_slice(&fs::read(&pre_event_path).expect("pre event")).expect("json");
+    assert_eq!(pre["phase"], "pre_apply");
+    assert_eq!(pre["command"], "transform-batch");
+    assert_eq!(pre["operation_counts"]["fill_range"], 1);
+    assert!(pre["staged_path"].is_string());
+    assert_eq!(pre["changed_cells"]["total_changed"], 1);
+    assert_eq!(pre["changed_cells"]["cells"][0]["address"], "B2");
+    assert_eq!(pre["changed_cells"]["cells"][0]["before"], "10");
+    assert_eq!(pre["changed_cells"]["cells"][0]["after"], "44");
+
+    let post: Value =
+        serde_json::from_slice(&fs::read(&post_event_path).expect("post event")).expect("json");
+    assert_eq!(post["phase"], "post_apply");
+    assert!(post.get("staged_path").is_none());
+    assert_eq!(post["changed_cells"]["by_sheet"]["Sheet1"], 1);
 }
 
+#[cfg(unix)]
 #[test]
-fn phase_a_help_examples_for_style_and_formula_commands() {
-    let style_help = run_cli(&["style-batch", "--help"]);
-    assert!(
-        style_help.status.success(),
-        "stderr: {:?}",
-        style_help.stderr
+fn cli_write_hook_with_large_manifest_and_chatty_stderr_completes() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("transform-batch-hook-pipes.xlsx");
+    let ops_path = tmp.path().join("ops.json");
+    let event_path = tmp.path().join("pre.json");
+    write_fixture(&workbook_path);
+    // A thousand long values make the event bigger than a pipe buffer.
+    write_ops_payload(
+        &ops_path,
+        &format!(
+            r#"{{"ops":[{{"kind":"fill_range","sheet_name":"Sheet1","target":{{"kind":"range","range":"E10:N109"}},"value":"{}"}}]}}"#,
+            "x".repeat(100)
+        ),
     );
-    let style = parse_stdout_text(&style_help);
-    assert!(style.contains("Examples:"));
-    assert!(style.contains("asp write batch style workbook.xlsx --ops @style_ops.json --dry-run"));
-    assert!(style.contains(
-        "asp write batch style workbook.xlsx --ops @style_ops.json --output styled.xlsx --force"
-    ));
-    assert!(style.contains("Payload examples (`--ops @style_ops.json`):"));
-    assert!(style.contains("\"patch\":{\"font\":{\"bold\":true}}"));
-    assert!(style.contains("Required envelope:"));
 
-    let formula_help = run_cli(&["apply-formula-pattern", "--help"]);
-    assert!(
-        formula_help.status.success(),
-        "stderr: {:?}",
-        formula_help.stderr
-    );
-    let formula = parse_stdout_text(&formula_help);
-    assert!(formula.contains("Examples:"));
-    assert!(formula.contains(
-        "asp write batch formula-pattern workbook.xlsx --ops @formula_ops.json --in-place"
-    ));
-    assert!(formula.contains(
-        "asp write batch formula-pattern workbook.xlsx --ops @formula_ops.json --dry-run"
-    ));
-    assert!(formula.contains("Payload examples (`--ops @formula_ops.json`):"));
-    assert!(formula.contains("\"target_range\":\"C2:C4\""));
-    assert!(formula.contains("\"fill_direction\":\"both\""));
-    assert!(formula.contains("relative_mode` valid values: excel|abs_cols|abs_rows"));
-    assert!(formula.contains(
-        "Updated formula cells clear cached results. Run recalculate to refresh computed values."
-    ));
-    assert!(
-        formula.contains("write_path_provenance"),
-        "apply-formula-pattern help should mention provenance diagnostics"
-    );
+    let file = workbook_path.to_str().expect("path utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
+    // The hook fills its stderr pipe before it reads the event from stdin.
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("agent-spreadsheet"))
+        .args([
+            "transform-batch",
+            file,
+            "--ops",
+            ops_ref.as_str(),
+            "--in-place",
+        ])
+        .env(
+            "SPREADSHEET_MCP_PRE_APPLY_HOOK",
+            format!(
+                "head -c 200000 /dev/zero | tr '\\0' x >&2; cat > '{}'",
+                event_path.display()
+            ),
+        )
+        .output()
+        .expect("run agent-spreadsheet");
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+
+    let event: Value =
+        serde_json::from_slice(&fs::read(&event_path).expect("pre event")).expect("json");
+    assert_eq!(event["changed_cells"]["total_changed"], 1000);
+    assert!(fs::metadata(&event_path).expect("event size").len() > 64 * 1024);
 }
 
+#[cfg(unix)]
 #[test]
-fn phase_a_style_batch_positive_dry_run_and_output_target_only() {
+fn cli_transform_batch_runs_custom_ops_from_plugin_dir() {
     let tmp = tempdir().expect("tempdir");
-    let source_path = tmp.path().join("phase-a-style-source.xlsx");
-    let output_path = tmp.path().join("phase-a-style-output.xlsx");
-    let ops_path = tmp.path().join("style-ops.json");
-    write_fixture(&source_path);
+    let workbook_path = tmp.path().join("transform-batch-custom.xlsx");
+    let ops_path = tmp.path().join("ops.json");
+    let plugin_dir = tmp.path().join("custom-ops");
+    fs::create_dir(&plugin_dir).expect("plugin dir");
+    fs::write(
+        plugin_dir.join("double_first.yaml"),
+        "name: double_first\ndescription: Double the first amount\ncommand: sh double_first.sh\n",
+    )
+    .expect("write manifest");
+    fs::write(
+        plugin_dir.join("double_first.sh"),
+        r#"cat > request.json
+printf '%s' '{"writes":[{"address":"D2","value":20},{"address":"E2","formula":"=D2+1"}],"counts":{"rows":1}}'
+"#,
+    )
+    .expect("write plugin");
+    write_fixture(&workbook_path);
     write_ops_payload(
         &ops_path,
-        r#"{"ops":[{"sheet_name":"Sheet1","range":"B2:B2","style":{"font":{"bold":true}}}]}"#,
+        r#"{"ops":[{"kind":"custom","name":"double_first","sheet_name":"Sheet1","params":{"factor":2}}]}"#,
     );
 
-    let source = source_path.to_str().expect("source utf8");
-    let output = output_path.to_str().expect("output utf8");
-    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
-
-    let before = fs::read(&source_path).expect("read source before dry-run");
-    let dry_run = run_cli(&[
-        "style-batch",
-        source,
-        "--ops",
-        ops_ref.as_str(),
-        "--dry-run",
-    ]);
-    assert!(dry_run.status.success(), "stderr: {:?}", dry_run.stderr);
-    let dry_payload = parse_stdout_json(&dry_run);
-    assert_eq!(dry_payload["op_count"].as_u64(), Some(1));
-    assert_eq!(dry_payload["validated_count"].as_u64(), Some(1));
-    assert!(dry_payload["would_change"].as_bool().unwrap_or(false));
-
-    let after_dry = fs::read(&source_path).expect("read source after dry-run");
-    assert_eq!(before, after_dry, "dry-run mutated source file");
+    let file = workbook_path.to_str().expect("path utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("asp"))
+        .args([
+            "write",
+            "batch",
+            "transform",
+            file,
+            "--ops",
+            ops_ref.as_str(),
+            "--in-place",
+        ])
+        .env("SPREADSHEET_MCP_CUSTOM_OPS_DIR", &plugin_dir)
+        .output()
+        .expect("run asp");
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["applied_count"].as_u64(), Some(1));
+    assert!(payload["changed"].as_bool().unwrap_or(false));
 
-    let output_run = run_cli(&[
-        "style-batch",
-        source,
-        "--ops",
-        ops_ref.as_str(),
-        "--output",
-        output,
-    ]);
+    let request: Value =
+        serde_json::from_slice(&fs::read(plugin_dir.join("request.json")).expect("request"))
+            .expect("request json");
+    assert_eq!(request["name"], "double_first");
+    assert_eq!(request["sheet_name"], "Sheet1");
+    assert_eq!(request["params"]["factor"], 2);
     assert!(
-        output_run.status.success(),
-        "stderr: {:?}",
-        output_run.stderr
+        request["cells"]
+            .as_array()
+            .expect("cells")
+            .iter()
+            .any(|cell| cell["address"] == "C2" && cell["formula"] == "B2*2"),
+        "request: {request}"
     );
-    let output_payload = parse_stdout_json(&output_run);
-    assert_json_path_eq(&output_payload, "target_path", output);
-    assert_json_path_eq(&output_payload, "source_path", source);
-    assert!(output_payload["changed"].as_bool().unwrap_or(false));
 
-    let source_after = fs::read(&source_path).expect("read source after output mode");
-    let output_after = fs::read(&output_path).expect("read output after output mode");
-    assert_eq!(before, source_after, "source changed during --output mode");
-    assert_ne!(source_after, output_after, "output file did not change");
+    let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read workbook");
+    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet exists");
+    assert_eq!(sheet.get_cell("D2").expect("D2 exists").get_value(), "20");
+    assert_eq!(
+        sheet.get_cell("E2").expect("E2 exists").get_formula(),
+        "D2+1"
+    );
+}
+
+/// Accepts `responses.len()` webhook requests, answering each with the
+/// matching status line, and forwards `(lowercased headers, body)`.
+#[cfg(feature = "remote")]
+fn serve_webhooks(
+    responses: Vec<&'static str>,
+) -> (String, std::sync::mpsc::Receiver<(String, Vec<u8>)>) {
+    use std::io::{Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind listener");
+    let url = format!(
+        "http://{}/hooks/spreadsheets",
+        listener.local_addr().unwrap()
+    );
+    let (sender, receiver) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        for (stream, status) in listener.incoming().zip(responses) {
+            let mut stream = stream.expect("accept webhook");
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            let header_end = loop {
+                if let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                    break end + 4;
+                }
+                let read = stream.read(&mut buf).expect("read request");
+                assert!(read > 0, "connection closed before headers");
+                request.extend_from_slice(&buf[..read]);
+            };
+            let headers = String::from_utf8_lossy(&request[..header_end]).to_ascii_lowercase();
+            let length: usize = headers
+                .lines()
+                .find_map(|line| line.strip_prefix("content-length:"))
+                .map(|value| value.trim().parse().expect("content length"))
+                .unwrap_or(0);
+            while request.len() < header_end + length {
+                let read = stream.read(&mut buf).expect("read body");
+                assert!(read > 0, "connection closed before body");
+                request.extend_from_slice(&buf[..read]);
+            }
+            let body = request[header_end..header_end + length].to_vec();
+            stream
+                .write_all(
+                    format!("HTTP/1.1 {status}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+                        .as_bytes(),
+                )
+                .expect("write response");
+            let _ = sender.send((headers, body));
+        }
+    });
+    (url, receiver)
+}
+
+#[cfg(feature = "remote")]
+fn header_value<'a>(headers: &'a str, name: &str) -> &'a str {
+    headers
+        .lines()
+        .find_map(|line| line.strip_prefix(&format!("{name}:")))
+        .map(str::trim)
+        .unwrap_or_else(|| panic!("missing header {name} in {headers}"))
 }
 
+#[cfg(feature = "remote")]
 #[test]
-fn phase_a_style_batch_output_force_overwrite_semantics() {
+fn cli_webhook_receives_signed_write_and_recalc_events() {
     let tmp = tempdir().expect("tempdir");
-    let source_path = tmp.path().join("phase-a-style-force-source.xlsx");
-    let output_path = tmp.path().join("phase-a-style-force-output.xlsx");
-    let ops_first_path = tmp.path().join("style-ops-first.json");
-    let ops_second_path = tmp.path().join("style-ops-second.json");
-    write_fixture(&source_path);
-    write_ops_payload(
-        &ops_first_path,
-        r#"{"ops":[{"sheet_name":"Sheet1","range":"B2:B2","style":{"font":{"bold":true}}}]}"#,
-    );
+    let workbook_path = tmp.path().join("webhook.xlsx");
+    let recalc_path = tmp.path().join("webhook-recalc.xlsx");
+    let ops_path = tmp.path().join("ops.json");
+    write_fixture(&workbook_path);
     write_ops_payload(
-        &ops_second_path,
-        r#"{"ops":[{"sheet_name":"Sheet1","range":"B2:B2","style":{"font":{"italic":true}}}]}"#,
+        &ops_path,
+        r#"{"ops":[{"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"cells","cells":["B2"]},"value":"44"}]}"#,
     );
+    let file = workbook_path.to_str().expect("path utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
+    let secret = "whsec-test";
+    let (url, requests) = serve_webhooks(vec!["200 OK", "204 No Content", "500 Server Error"]);
+    let run = |args: &[&str]| {
+        Command::new(assert_cmd::cargo::cargo_bin!("agent-spreadsheet"))
+            .args(args)
+            .env("SPREADSHEET_MCP_WEBHOOK_URL", &url)
+            .env("SPREADSHEET_MCP_WEBHOOK_SECRET", secret)
+            .output()
+            .expect("run agent-spreadsheet")
+    };
+    let verify = |headers: &str, body: &[u8]| {
+        let timestamp: i64 = header_value(headers, "x-spreadsheet-timestamp")
+            .parse()
+            .expect("timestamp");
+        assert_eq!(
+            header_value(headers, "x-spreadsheet-signature"),
+            spreadsheet_kit::webhook::sign_payload(secret.as_bytes(), timestamp, body)
+        );
+        serde_json::from_slice::<Value>(body).expect("webhook json")
+    };
 
-    let source = source_path.to_str().expect("source utf8");
-    let output = output_path.to_str().expect("output utf8");
-    let first_ref = format!("@{}", ops_first_path.to_str().expect("ops utf8"));
-    let second_ref = format!("@{}", ops_second_path.to_str().expect("ops utf8"));
-
-    let first = run_cli(&[
-        "style-batch",
-        source,
+    let write = run(&[
+        "transform-batch",
+        file,
         "--ops",
-        first_ref.as_str(),
-        "--output",
-        output,
+        ops_ref.as_str(),
+        "--in-place",
     ]);
-    assert!(first.status.success(), "stderr: {:?}", first.stderr);
-    let first_bytes = fs::read(&output_path).expect("read first output bytes");
-
-    assert_error_code(
-        &[
-            "style-batch",
-            source,
-            "--ops",
-            second_ref.as_str(),
-            "--output",
-            output,
-        ],
-        "OUTPUT_EXISTS",
+    assert!(write.status.success(), "stderr: {:?}", write.stderr);
+    let (headers, body) = requests.recv().expect("write webhook");
+    assert_eq!(
+        header_value(&headers, "x-spreadsheet-event"),
+        "write_completed"
     );
-    let after_failed_bytes = fs::read(&output_path).expect("read output after failed overwrite");
-    assert_eq!(first_bytes, after_failed_bytes);
+    let event = verify(&headers, &body);
+    assert_eq!(event["event"], "write_completed");
+    assert_eq!(event["command"], "transform-batch");
+    assert_eq!(event["operation_counts"]["fill_range"], 1);
+    assert_eq!(event["changed_cells"]["total_changed"], 1);
+    assert_eq!(event["changed_cells"]["cells"][0]["after"], "44");
 
-    let forced = run_cli(&[
-        "style-batch",
-        source,
-        "--ops",
-        second_ref.as_str(),
+    let recalc = run(&[
+        "recalculate",
+        file,
         "--output",
-        output,
-        "--force",
+        recalc_path.to_str().expect("recalc path utf8"),
     ]);
-    assert!(forced.status.success(), "stderr: {:?}", forced.stderr);
+    assert!(recalc.status.success(), "stderr: {:?}", recalc.stderr);
+    let (headers, body) = requests.recv().expect("recalc webhook");
+    let event = verify(&headers, &body);
+    assert_eq!(event["event"], "recalc_completed");
+    assert_eq!(event["target_path"], recalc_path.display().to_string());
+    assert!(event["recalc"]["backend"].is_string(), "event: {event}");
+    assert!(
+        event["changed_cells"]["by_sheet"]["Sheet1"]
+            .as_u64()
+            .is_some_and(|count| count > 0),
+        "event: {event}"
+    );
 
-    let forced_bytes = fs::read(&output_path).expect("read forced output bytes");
-    assert_ne!(
-        first_bytes, forced_bytes,
-        "force overwrite did not update output"
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"cells","cells":["B2"]},"value":"55"}]}"#,
     );
+    let rejected = run(&[
+        "transform-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
+    ]);
+    assert!(!rejected.status.success(), "a failed delivery is reported");
+    let err = parse_stderr_json(&rejected);
+    let message = err["message"].as_str().unwrap_or_default();
+    assert!(message.contains("webhook failed"), "message: {message}");
+    assert!(message.contains("500"), "message: {message}");
+    requests.recv().expect("rejected webhook");
 }
 
 #[test]
-fn phase_a_apply_formula_pattern_positive_dry_run_and_output_target_only() {
+fn cli_transform_batch_audit_log_appends_one_record_per_applied_write() {
     let tmp = tempdir().expect("tempdir");
-    let source_path = tmp.path().join("phase-a-formula-source.xlsx");
-    let output_path = tmp.path().join("phase-a-formula-output.xlsx");
-    let ops_path = tmp.path().join("formula-ops.json");
-    write_fixture(&source_path);
+    let workbook_path = tmp.path().join("transform-batch-audit.xlsx");
+    let ops_path = tmp.path().join("ops.json");
+    let audit_path = tmp.path().join("audit.jsonl");
+    write_fixture(&workbook_path);
     write_ops_payload(
         &ops_path,
-        r#"{"ops":[{"sheet_name":"Sheet1","target_range":"C2:C4","anchor_cell":"C2","base_formula":"B2*3","fill_direction":"down","relative_mode":"excel"}]}"#,
+        r#"{"ops":[{"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"cells","cells":["B2"]},"value":"44"}]}"#,
     );
 
-    let source = source_path.to_str().expect("source utf8");
-    let output = output_path.to_str().expect("output utf8");
-    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
+    let file = workbook_path.to_str().expect("path utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
+    let audit = audit_path.to_str().expect("audit path utf8");
 
-    let before = fs::read(&source_path).expect("read source before dry-run");
     let dry_run = run_cli(&[
-        "apply-formula-pattern",
-        source,
+        "transform-batch",
+        file,
         "--ops",
         ops_ref.as_str(),
         "--dry-run",
+        "--audit-log",
+        audit,
     ]);
     assert!(dry_run.status.success(), "stderr: {:?}", dry_run.stderr);
-    let dry_payload = parse_stdout_json(&dry_run);
-    assert_eq!(dry_payload["op_count"].as_u64(), Some(1));
-    assert!(dry_payload["would_change"].as_bool().unwrap_or(false));
-    assert_eq!(
-        dry_payload["write_path_provenance"]["written_via"],
-        Value::String("apply_formula_pattern".to_string())
+    assert!(!audit_path.exists(), "dry runs must not be journaled");
+
+    let before_hash = spreadsheet_kit::utils::hash_file_sha256_hex(&workbook_path).expect("hash");
+    for _ in 0..2 {
+        let output = run_cli(&[
+            "transform-batch",
+            file,
+            "--ops",
+            ops_ref.as_str(),
+            "--in-place",
+            "--audit-log",
+            audit,
+        ]);
+        assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    }
+    let after_hash = spreadsheet_kit::utils::hash_file_sha256_hex(&workbook_path).expect("hash");
+
+    let journal = fs::read_to_string(&audit_path).expect("read audit log");
+    let records: Vec<Value> = journal
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("record json"))
+        .collect();
+    assert_eq!(records.len(), 2, "journal: {journal}");
+
+    let first = &records[0];
+    assert_eq!(first["command"], "transform-batch");
+    assert!(first["timestamp"].is_string());
+    assert_eq!(first["target_path"], first["source_path"]);
+    assert_eq!(first["operation_counts"]["fill_range"], 1);
+    assert_eq!(first["before_hash"], before_hash);
+    assert_eq!(first["changed_cells"]["total_changed"], 1);
+    assert_eq!(first["changed_cells"]["cells"][0]["address"], "B2");
+    assert_eq!(first["changed_cells"]["cells"][0]["after"], "44");
+
+    let second = &records[1];
+    assert_eq!(second["ops_hash"], first["ops_hash"]);
+    assert_eq!(second["before_hash"], first["after_hash"]);
+    assert_eq!(second["after_hash"], after_hash);
+    assert_eq!(second["changed_cells"]["total_changed"], 0);
+}
+
+#[test]
+fn cli_snapshot_dir_enables_restore_of_in_place_batch_writes() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("snapshot-source.xlsx");
+    let other_path = tmp.path().join("snapshot-other.xlsx");
+    let ops_path = tmp.path().join("ops.json");
+    let snapshot_dir = tmp.path().join("snapshots");
+    write_fixture(&workbook_path);
+    write_fixture(&other_path);
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"cells","cells":["B2"]},"value":"999"}]}"#,
     );
-    let dry_targets = dry_payload["write_path_provenance"]["formula_targets"]
-        .as_array()
-        .expect("formula_targets array");
+
+    let file = workbook_path.to_str().expect("path utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
+    let dir = snapshot_dir.to_str().expect("dir utf8");
+
+    let err = assert_invalid_argument(&[
+        "transform-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--dry-run",
+        "--snapshot-dir",
+        dir,
+    ]);
     assert!(
-        dry_targets
-            .iter()
-            .any(|target| target.as_str() == Some("Sheet1!C2:C4"))
+        err["message"]
+            .as_str()
+            .is_some_and(|message| message.contains("--snapshot-dir requires --in-place")),
+        "error: {err}"
     );
-    let after_dry = fs::read(&source_path).expect("read source after dry-run");
-    assert_eq!(before, after_dry, "dry-run mutated source file");
+    assert!(!snapshot_dir.exists());
 
-    let output_run = run_cli(&[
-        "apply-formula-pattern",
-        source,
+    let original_hash = spreadsheet_kit::utils::hash_file_sha256_hex(&workbook_path).expect("hash");
+    let output = run_cli(&[
+        "transform-batch",
+        file,
         "--ops",
         ops_ref.as_str(),
-        "--output",
-        output,
+        "--in-place",
+        "--snapshot-dir",
+        dir,
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    let snapshot_id = payload["snapshot"]["id"]
+        .as_str()
+        .expect("snapshot id")
+        .to_string();
+    assert_eq!(payload["snapshot"]["command"], "transform-batch");
+    assert_eq!(payload["snapshot"]["sha256"], original_hash);
+    assert_ne!(
+        spreadsheet_kit::utils::hash_file_sha256_hex(&workbook_path).expect("hash"),
+        original_hash
+    );
+
+    let listed = run_cli(&["list-snapshots", "--snapshot-dir", dir, "--file", file]);
+    assert!(listed.status.success(), "stderr: {:?}", listed.stderr);
+    let listed = parse_stdout_json(&listed);
+    assert_eq!(listed["count"], 1);
+    assert_eq!(listed["snapshots"][0]["id"], snapshot_id.as_str());
+
+    let other = other_path.to_str().expect("other path utf8");
+    let err = assert_invalid_argument(&[
+        "restore",
+        other,
+        "--snapshot",
+        snapshot_id.as_str(),
+        "--snapshot-dir",
+        dir,
     ]);
     assert!(
-        output_run.status.success(),
-        "stderr: {:?}",
-        output_run.stderr
-    );
-    let output_payload = parse_stdout_json(&output_run);
-    assert!(output_payload["changed"].as_bool().unwrap_or(false));
-    assert_eq!(
-        output_payload["write_path_provenance"]["written_via"],
-        Value::String("apply_formula_pattern".to_string())
+        err["message"]
+            .as_str()
+            .is_some_and(|message| message.contains("--force")),
+        "error: {err}"
     );
 
-    let source_book =
-        umya_spreadsheet::reader::xlsx::read(&source_path).expect("read source workbook");
-    let source_sheet = source_book
-        .get_sheet_by_name("Sheet1")
-        .expect("source sheet");
+    let restored = run_cli(&[
+        "restore",
+        file,
+        "--snapshot",
+        snapshot_id.as_str(),
+        "--snapshot-dir",
+        dir,
+    ]);
+    assert!(restored.status.success(), "stderr: {:?}", restored.stderr);
+    let restored = parse_stdout_json(&restored);
+    assert_eq!(restored["restored"]["id"], snapshot_id.as_str());
+    assert_eq!(restored["snapshot"]["command"], "restore");
     assert_eq!(
-        source_sheet
-            .get_cell("C2")
-            .expect("C2 source")
-            .get_formula(),
-        "B2*2"
+        spreadsheet_kit::utils::hash_file_sha256_hex(&workbook_path).expect("hash"),
+        original_hash
     );
 
-    let output_book =
-        umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output workbook");
-    let output_sheet = output_book
-        .get_sheet_by_name("Sheet1")
-        .expect("output sheet");
-    assert_eq!(
-        output_sheet
-            .get_cell("C2")
-            .expect("C2 output")
-            .get_formula()
-            .replace(' ', ""),
-        "B2*3"
+    let listed = parse_stdout_json(&run_cli(&["list-snapshots", "--snapshot-dir", dir]));
+    assert_eq!(listed["count"], 2);
+    assert_eq!(listed["snapshots"][0]["command"], "restore");
+}
+
+#[test]
+fn cli_plan_emits_minimal_ops_and_converges() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("plan.xlsx");
+    let spec_path = tmp.path().join("desired.json");
+    let plan_path = tmp.path().join("plan.json");
+    write_fixture(&workbook_path);
+    write_ops_payload(
+        &spec_path,
+        r#"{"targets":[{
+            "sheet_name":"Sheet1",
+            "rows":[
+                ["Name","Amount","Total"],
+                ["Alice",10,"=B2*2"],
+                ["Bob",25,"=B3*3"],
+                ["Carol",30,null]
+            ],
+            "styles":[{"range":"A1:C1","patch":{"font":{"bold":true}}}]
+        }]}"#,
     );
+    let file = workbook_path.to_str().expect("path utf8");
+    let spec_ref = format!("@{}", spec_path.to_str().expect("spec utf8"));
+    let before_hash = spreadsheet_kit::utils::hash_file_sha256_hex(&workbook_path).expect("hash");
+
+    let output = run_cli(&[
+        "plan",
+        file,
+        "--spec",
+        spec_ref.as_str(),
+        "--output",
+        plan_path.to_str().expect("plan utf8"),
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["converged"], false);
+    assert_eq!(payload["cells_checked"], 12);
+    assert_eq!(payload["cells_to_write"], 2);
+    assert_eq!(payload["cells_to_clear"], 1);
+    assert_eq!(payload["cells_to_restyle"], 3);
+    assert_eq!(payload["op_count"], 3);
+    assert_eq!(payload["changes"][0]["cell"], "B3");
+    assert_eq!(payload["changes"][0]["current"], "20");
+    assert_eq!(payload["changes"][0]["desired"], "25");
+
+    let steps = &payload["plan"]["steps"];
+    assert_eq!(steps[0]["kind"], "transform");
+    assert_eq!(steps[0]["ops"][0]["kind"], "write_matrix");
+    assert_eq!(steps[0]["ops"][0]["anchor"], "B3");
+    assert_eq!(steps[0]["ops"][0]["rows"][0][1]["f"], "B3*3");
+    assert_eq!(steps[0]["ops"][1]["kind"], "clear_range");
     assert_eq!(
-        output_sheet
-            .get_cell("C3")
-            .expect("C3 output")
-            .get_formula()
-            .replace(' ', ""),
-        "B3*3"
+        steps[0]["ops"][1]["target"]["cells"],
+        serde_json::json!(["C4"])
     );
+    assert_eq!(steps[1]["kind"], "style");
+    assert_eq!(steps[1]["ops"][0]["target"]["range"], "A1:C1");
     assert_eq!(
-        output_sheet
-            .get_cell("C4")
-            .expect("C4 output")
-            .get_formula()
-            .replace(' ', ""),
-        "B4*3"
+        spreadsheet_kit::utils::hash_file_sha256_hex(&workbook_path).expect("hash"),
+        before_hash,
+        "planning must not modify the workbook"
     );
+
+    let plan_ref = format!("@{}", plan_path.to_str().expect("plan utf8"));
+    let output = run_cli(&[
+        "apply-plan",
+        file,
+        "--plan",
+        plan_ref.as_str(),
+        "--in-place",
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+
+    let output = run_cli(&["plan", file, "--spec", spec_ref.as_str()]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["converged"], true, "payload: {payload}");
+    assert_eq!(payload["op_count"], 0);
+    assert_eq!(payload["cells_to_write"], 0);
+    assert_eq!(payload["cells_to_clear"], 0);
+    assert_eq!(payload["cells_to_restyle"], 0);
+    // CLI output pruning drops the empty step list, and with it the plan and
+    // change list.
+    assert!(payload.get("plan").is_none(), "payload: {payload}");
+    assert!(payload.get("changes").is_none(), "payload: {payload}");
 }
 
 #[test]
-fn phase_a_apply_formula_pattern_clears_formula_cache_for_touched_cells() {
+fn cli_apply_state_converges_and_reports_drift() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("phase-a-formula-cache-clear.xlsx");
-    let ops_path = tmp.path().join("formula-cache-ops.json");
+    let workbook_path = tmp.path().join("apply-state.xlsx");
+    let spec_path = tmp.path().join("desired.json");
+    write_fixture(&workbook_path);
+    write_ops_payload(
+        &spec_path,
+        r#"{"targets":[{"sheet_name":"Summary","rows":[["Flag","Ready"],["Owner","Finance"]]}]}"#,
+    );
+    let file = workbook_path.to_str().expect("path utf8");
+    let spec_ref = format!("@{}", spec_path.to_str().expect("spec utf8"));
+
+    let check = run_cli(&["apply-state", file, "--spec", spec_ref.as_str(), "--check"]);
+    assert_eq!(check.status.code(), Some(2), "stderr: {:?}", check.stderr);
+    let payload = parse_stdout_json(&check);
+    assert_eq!(payload["passed"], false);
+    assert_eq!(payload["drift"]["cells_to_write"], 2);
+    assert_eq!(payload["applied"], false);
+
+    let output = run_cli(&[
+        "apply-state",
+        file,
+        "--spec",
+        spec_ref.as_str(),
+        "--in-place",
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["converged"], false);
+    assert_eq!(payload["applied"], true);
+    assert_eq!(payload["drift"]["changes"][0]["cell"], "A2");
+    assert_eq!(
+        payload["apply"]["operation_counts"]["transform.write_matrix"],
+        1
+    );
+
+    let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read workbook");
+    let sheet = book.get_sheet_by_name("Summary").expect("sheet");
+    assert_eq!(sheet.get_value("B2"), "Finance");
+
+    let rerun = run_cli(&[
+        "apply-state",
+        file,
+        "--spec",
+        spec_ref.as_str(),
+        "--in-place",
+    ]);
+    assert!(rerun.status.success(), "stderr: {:?}", rerun.stderr);
+    let payload = parse_stdout_json(&rerun);
+    assert_eq!(payload["converged"], true);
+    assert_eq!(payload["applied"], false);
+    assert_eq!(payload["drift"]["op_count"], 0);
 
-    let mut workbook = umya_spreadsheet::new_file();
     {
-        let sheet = workbook
-            .get_sheet_by_name_mut("Sheet1")
-            .expect("default sheet exists");
-        sheet.get_cell_mut("A1").set_value("Name");
-        sheet.get_cell_mut("B1").set_value("Amount");
-        sheet.get_cell_mut("C1").set_value("Total");
-        sheet.get_cell_mut("A2").set_value("Alice");
-        sheet.get_cell_mut("B2").set_value_number(10.0);
-        let c2 = sheet.get_cell_mut("C2");
-        c2.set_formula("B2*2");
-        c2.get_cell_value_mut().set_formula_result_default("20");
+        let mut book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read workbook");
+        let sheet = book.get_sheet_by_name_mut("Summary").expect("sheet");
+        sheet.get_cell_mut("B1").set_value("Paused");
+        umya_spreadsheet::writer::xlsx::write(&book, &workbook_path).expect("write workbook");
     }
-    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+    let check = run_cli(&["apply-state", file, "--spec", spec_ref.as_str(), "--check"]);
+    assert_eq!(check.status.code(), Some(2), "stderr: {:?}", check.stderr);
+    let payload = parse_stdout_json(&check);
+    assert_eq!(payload["drift"]["changes"][0]["cell"], "B1");
+    assert_eq!(payload["drift"]["changes"][0]["current"], "Paused");
+    assert_eq!(payload["drift"]["changes"][0]["desired"], "Ready");
+}
 
+#[test]
+fn cli_apply_plan_applies_all_steps_or_writes_nothing() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("apply-plan.xlsx");
+    let plan_path = tmp.path().join("plan.json");
+    let failing_plan_path = tmp.path().join("failing-plan.json");
+    write_fixture(&workbook_path);
     write_ops_payload(
-        &ops_path,
-        r#"{"ops":[{"sheet_name":"Sheet1","target_range":"C2:C2","anchor_cell":"C2","base_formula":"B2*3","fill_direction":"down","relative_mode":"excel"}]}"#,
+        &plan_path,
+        r#"{"steps":[
+            {"kind":"structure","ops":[{"kind":"insert_rows","sheet_name":"Sheet1","at_row":2,"count":1}]},
+            {"kind":"transform","ops":[{"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"cells","cells":["A2","B2"]},"value":"New"}]},
+            {"kind":"style","ops":[{"sheet_name":"Sheet1","target":{"kind":"range","range":"A2:B2"},"patch":{"font":{"bold":true}}}]}
+        ]}"#,
+    );
+    write_ops_payload(
+        &failing_plan_path,
+        r#"{"steps":[
+            {"kind":"transform","ops":[{"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"cells","cells":["B2"]},"value":"77"}]},
+            {"kind":"transform","ops":[{"kind":"fill_range","sheet_name":"Missing","target":{"kind":"cells","cells":["A1"]},"value":"x"}]}
+        ]}"#,
     );
-
     let file = workbook_path.to_str().expect("path utf8");
-    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
 
+    let failing_ref = format!("@{}", failing_plan_path.to_str().expect("plan utf8"));
+    let before_hash = spreadsheet_kit::utils::hash_file_sha256_hex(&workbook_path).expect("hash");
+    let err = assert_error_code(
+        &[
+            "apply-plan",
+            file,
+            "--plan",
+            failing_ref.as_str(),
+            "--in-place",
+        ],
+        "INVALID_OPS_PAYLOAD",
+    );
+    assert!(
+        err["message"]
+            .as_str()
+            .is_some_and(|message| message.contains("plan step 1 failed")),
+        "error: {err}"
+    );
+    assert_eq!(
+        spreadsheet_kit::utils::hash_file_sha256_hex(&workbook_path).expect("hash"),
+        before_hash,
+        "a failed plan must leave the workbook untouched"
+    );
+
+    let plan_ref = format!("@{}", plan_path.to_str().expect("plan utf8"));
     let output = run_cli(&[
-        "apply-formula-pattern",
+        "apply-plan",
         file,
-        "--ops",
-        ops_ref.as_str(),
+        "--plan",
+        plan_ref.as_str(),
         "--in-place",
     ]);
     assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["step_count"], 3);
+    assert_eq!(payload["changed"], true);
+    assert_eq!(payload["operation_counts"]["structure.insert_rows"], 1);
+    assert_eq!(payload["operation_counts"]["transform.fill_range"], 1);
+    assert_eq!(payload["steps"][2]["kind"], "style");
+    assert_eq!(payload["steps"][2]["changed"], true);
 
     let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read workbook");
-    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet exists");
-    let c2 = sheet.get_cell("C2").expect("C2 exists");
-    assert_eq!(c2.get_formula().replace(' ', ""), "B2*3");
-    assert_eq!(c2.get_value(), "", "expected formula cache to be cleared");
-
-    let read = run_cli(&["range-values", file, "Sheet1", "C2", "--shape", "compact"]);
-    assert!(read.status.success(), "stderr: {:?}", read.stderr);
-    let payload = parse_stdout_json(&read);
-    assert!(
-        payload["rows"][0][0].is_null(),
-        "range-values should report null until recalculate refreshes cache"
-    );
+    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet");
+    assert_eq!(sheet.get_value("A2"), "New");
+    assert_eq!(sheet.get_value("A3"), "Alice");
 }
 
 #[test]
-fn phase_a_apply_formula_pattern_output_force_overwrite_semantics() {
+fn cli_transform_batch_output_and_force_modes_apply_with_overwrite_checks() {
     let tmp = tempdir().expect("tempdir");
-    let source_path = tmp.path().join("phase-a-force-source.xlsx");
-    let output_path = tmp.path().join("phase-a-force-output.xlsx");
-    let ops_first_path = tmp.path().join("formula-ops-first.json");
-    let ops_second_path = tmp.path().join("formula-ops-second.json");
+    let source_path = tmp.path().join("transform-batch-source.xlsx");
+    let output_path = tmp.path().join("transform-batch-output.xlsx");
+    let ops_path_first = tmp.path().join("ops-first.json");
+    let ops_path_second = tmp.path().join("ops-second.json");
     write_fixture(&source_path);
     write_ops_payload(
-        &ops_first_path,
-        r#"{"ops":[{"sheet_name":"Sheet1","target_range":"C2:C2","anchor_cell":"C2","base_formula":"B2*3","fill_direction":"down"}]}"#,
+        &ops_path_first,
+        r#"{"ops":[{"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"cells","cells":["B2"]},"value":"51"}]}"#,
     );
     write_ops_payload(
-        &ops_second_path,
-        r#"{"ops":[{"sheet_name":"Sheet1","target_range":"C2:C2","anchor_cell":"C2","base_formula":"B2*5","fill_direction":"down"}]}"#,
+        &ops_path_second,
+        r#"{"ops":[{"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"cells","cells":["B3"]},"value":"91"}]}"#,
     );
 
     let source = source_path.to_str().expect("source utf8");
     let output = output_path.to_str().expect("output utf8");
-    let first_ref = format!("@{}", ops_first_path.to_str().expect("ops utf8"));
-    let second_ref = format!("@{}", ops_second_path.to_str().expect("ops utf8"));
+    let ops_first_ref = format!("@{}", ops_path_first.to_str().expect("ops path utf8"));
+    let ops_second_ref = format!("@{}", ops_path_second.to_str().expect("ops path utf8"));
 
     let first = run_cli(&[
-        "apply-formula-pattern",
+        "transform-batch",
         source,
         "--ops",
-        first_ref.as_str(),
+        ops_first_ref.as_str(),
         "--output",
         output,
     ]);
     assert!(first.status.success(), "stderr: {:?}", first.stderr);
 
-    assert_error_code(
-        &[
-            "apply-formula-pattern",
-            source,
-            "--ops",
-            second_ref.as_str(),
-            "--output",
-            output,
-        ],
-        "OUTPUT_EXISTS",
-    );
+    let source_book = umya_spreadsheet::reader::xlsx::read(&source_path).expect("read source");
+    let source_sheet = source_book
+        .get_sheet_by_name("Sheet1")
+        .expect("sheet exists");
+    assert_eq!(
+        source_sheet
+            .get_cell("B2")
+            .expect("source B2 exists")
+            .get_value(),
+        "10"
+    );
+
+    let output_book = umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output");
+    let output_sheet = output_book
+        .get_sheet_by_name("Sheet1")
+        .expect("sheet exists");
+    assert_eq!(
+        output_sheet
+            .get_cell("B2")
+            .expect("output B2 exists")
+            .get_value(),
+        "51"
+    );
+
+    assert_error_code(
+        &[
+            "transform-batch",
+            source,
+            "--ops",
+            ops_second_ref.as_str(),
+            "--output",
+            output,
+        ],
+        "OUTPUT_EXISTS",
+    );
 
     let forced = run_cli(&[
-        "apply-formula-pattern",
+        "transform-batch",
         source,
         "--ops",
-        second_ref.as_str(),
+        ops_second_ref.as_str(),
         "--output",
         output,
         "--force",
     ]);
     assert!(forced.status.success(), "stderr: {:?}", forced.stderr);
+    let forced_payload = parse_stdout_json(&forced);
+    assert_json_path_eq(&forced_payload, "target_path", output);
 
-    let output_book = umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output");
-    let output_sheet = output_book
+    let overwritten = umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output");
+    let overwritten_sheet = overwritten
         .get_sheet_by_name("Sheet1")
         .expect("sheet exists");
     assert_eq!(
-        output_sheet
-            .get_cell("C2")
-            .expect("C2 output")
-            .get_formula()
-            .replace(' ', ""),
-        "B2*5"
+        overwritten_sheet
+            .get_cell("B3")
+            .expect("output B3 exists")
+            .get_value(),
+        "91"
     );
 }
 
+#[cfg(unix)]
 #[test]
-fn phase_a_negative_invalid_ops_payloads() {
+fn cli_transform_batch_rejects_dangling_symlink_output_without_force() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("phase-a-invalid-ops.xlsx");
-    let style_bad_path = tmp.path().join("style-bad.json");
-    let formula_bad_path = tmp.path().join("formula-bad.json");
-    write_fixture(&workbook_path);
-    write_ops_payload(
-        &style_bad_path,
-        r#"{"ops":[{"sheet_name":"Sheet1","target":{"kind":"unknown"},"patch":{}}]}"#,
-    );
+    let source_path = tmp
+        .path()
+        .join("transform-batch-source-dangling-symlink.xlsx");
+    let ops_path = tmp.path().join("ops.json");
+    let output_link = tmp.path().join("dangling-output.xlsx");
+    let missing_target = tmp.path().join("missing-target.xlsx");
+
+    write_fixture(&source_path);
     write_ops_payload(
-        &formula_bad_path,
-        r#"{"ops":[{"sheet_name":"Sheet1","target_range":"C2:C4","anchor_cell":"C1","base_formula":"B2*3","fill_direction":"down"}]}"#,
+        &ops_path,
+        r#"{"ops":[{"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"cells","cells":["B2"]},"value":"66"}]}"#,
     );
 
-    let file = workbook_path.to_str().expect("path utf8");
-    let style_ref = format!("@{}", style_bad_path.to_str().expect("ops utf8"));
-    let formula_ref = format!("@{}", formula_bad_path.to_str().expect("ops utf8"));
+    symlink(&missing_target, &output_link).expect("create dangling symlink");
 
-    assert_error_code(
+    let source = source_path.to_str().expect("source utf8");
+    let output = output_link.to_str().expect("output utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
+
+    let err = assert_error_code(
         &[
-            "style-batch",
-            file,
+            "transform-batch",
+            source,
             "--ops",
-            style_ref.as_str(),
-            "--dry-run",
+            ops_ref.as_str(),
+            "--output",
+            output,
         ],
-        "INVALID_OPS_PAYLOAD",
+        "OUTPUT_EXISTS",
     );
-    assert_error_code(
-        &[
-            "apply-formula-pattern",
-            file,
-            "--ops",
-            formula_ref.as_str(),
-            "--dry-run",
-        ],
-        "INVALID_OPS_PAYLOAD",
+    assert!(
+        err["message"]
+            .as_str()
+            .unwrap_or_default()
+            .contains("already exists")
+    );
+
+    assert!(
+        fs::symlink_metadata(&output_link).is_ok(),
+        "dangling symlink should remain in place"
     );
 }
 
 #[test]
-fn phase_a_invalid_relative_mode_reports_valid_literals_and_suggestion() {
+fn cli_transform_batch_rejects_invalid_mode_combinations() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("phase-a-invalid-relative-mode.xlsx");
-    let formula_bad_path = tmp.path().join("formula-bad-relative-mode.json");
+    let workbook_path = tmp.path().join("transform-batch-mode-matrix.xlsx");
+    let ops_path = tmp.path().join("ops.json");
     write_fixture(&workbook_path);
     write_ops_payload(
-        &formula_bad_path,
-        r#"{"ops":[{"sheet_name":"Sheet1","target_range":"C2:C4","anchor_cell":"C2","base_formula":"B2*3","fill_direction":"down","relative_mode":"fully_relative"}]}"#,
+        &ops_path,
+        r#"{"ops":[{"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"cells","cells":["B2"]},"value":"7"}]}"#,
     );
 
     let file = workbook_path.to_str().expect("path utf8");
-    let formula_ref = format!("@{}", formula_bad_path.to_str().expect("ops utf8"));
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
 
-    let err = assert_error_code(
+    assert_invalid_argument(&["transform-batch", file, "--ops", ops_ref.as_str()]);
+    assert_invalid_argument(&[
+        "transform-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--dry-run",
+        "--in-place",
+    ]);
+    assert_invalid_argument(&[
+        "transform-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--dry-run",
+        "--output",
+        "out.xlsx",
+    ]);
+    assert_invalid_argument(&[
+        "transform-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
+        "--output",
+        "out.xlsx",
+    ]);
+    assert_invalid_argument(&[
+        "transform-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--force",
+    ]);
+    assert_invalid_argument(&[
+        "transform-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--output",
+        file,
+    ]);
+}
+
+#[test]
+fn cli_transform_batch_rejects_invalid_ops_payloads() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("transform-batch-invalid-ops.xlsx");
+    let malformed_path = tmp.path().join("ops-malformed.json");
+    let schema_path = tmp.path().join("ops-schema.json");
+    write_fixture(&workbook_path);
+    write_ops_payload(&malformed_path, "{not-json}");
+    write_ops_payload(&schema_path, r#"{"ops":[{"kind":"unknown_op"}]}"#);
+
+    let file = workbook_path.to_str().expect("path utf8");
+
+    assert_error_code(
+        &["transform-batch", file, "--ops", "ops.json", "--dry-run"],
+        "INVALID_OPS_PAYLOAD",
+    );
+
+    let malformed_ref = format!("@{}", malformed_path.to_str().expect("ops path utf8"));
+    assert_error_code(
         &[
-            "apply-formula-pattern",
+            "transform-batch",
             file,
             "--ops",
-            formula_ref.as_str(),
+            malformed_ref.as_str(),
             "--dry-run",
         ],
         "INVALID_OPS_PAYLOAD",
     );
 
-    let message = err["message"].as_str().unwrap_or_default();
-    assert!(message.contains("invalid relative_mode 'fully_relative'"));
-    assert!(message.contains("Did you mean 'excel'?"));
-    assert!(message.contains("valid: excel|abs_cols|abs_rows"));
-}
-
-#[test]
-fn phase_a_safety_mode_matrix_for_style_and_formula_commands() {
-    let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("phase-a-safety.xlsx");
-    let style_ops_path = tmp.path().join("style-ops.json");
-    let formula_ops_path = tmp.path().join("formula-ops.json");
-    write_fixture(&workbook_path);
-    write_ops_payload(
-        &style_ops_path,
-        r#"{"ops":[{"sheet_name":"Sheet1","range":"B2:B2","style":{"font":{"bold":true}}}]}"#,
-    );
-    write_ops_payload(
-        &formula_ops_path,
-        r#"{"ops":[{"sheet_name":"Sheet1","target_range":"C2:C4","anchor_cell":"C2","base_formula":"B2*3","fill_direction":"down"}]}"#,
+    let schema_ref = format!("@{}", schema_path.to_str().expect("ops path utf8"));
+    assert_error_code(
+        &[
+            "transform-batch",
+            file,
+            "--ops",
+            schema_ref.as_str(),
+            "--dry-run",
+        ],
+        "INVALID_OPS_PAYLOAD",
     );
-
-    let file = workbook_path.to_str().expect("path utf8");
-    let style_ref = format!("@{}", style_ops_path.to_str().expect("ops utf8"));
-    let formula_ref = format!("@{}", formula_ops_path.to_str().expect("ops utf8"));
-
-    assert_batch_mode_matrix("style-batch", file, style_ref.as_str());
-    assert_batch_mode_matrix("apply-formula-pattern", file, formula_ref.as_str());
 }
 
 #[cfg(unix)]
 #[test]
-fn phase_a_style_batch_maps_write_failures_and_preserves_source() {
+fn cli_transform_batch_maps_write_failures_and_preserves_source() {
     let tmp = tempdir().expect("tempdir");
-    let source_path = tmp.path().join("phase-a-style-write-fail-source.xlsx");
+    let source_path = tmp.path().join("transform-batch-write-fail-source.xlsx");
     let blocked_dir = tmp.path().join("blocked");
     let blocked_output = blocked_dir.join("output.xlsx");
     let ops_path = tmp.path().join("ops.json");
     write_fixture(&source_path);
     write_ops_payload(
         &ops_path,
-        r#"{"ops":[{"sheet_name":"Sheet1","range":"B2:B2","style":{"font":{"bold":true}}}]}"#,
+        r#"{"ops":[{"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"cells","cells":["B2"]},"value":"123"}]}"#,
     );
     fs::create_dir(&blocked_dir).expect("create blocked dir");
 
@@ -4928,9 +6715,9 @@ fn phase_a_style_batch_maps_write_failures_and_preserves_source() {
     let output = blocked_output.to_str().expect("output utf8");
     let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
 
-    assert_error_code(
+    let err = assert_error_code(
         &[
-            "style-batch",
+            "transform-batch",
             source,
             "--ops",
             ops_ref.as_str(),
@@ -4940,58 +6727,14 @@ fn phase_a_style_batch_maps_write_failures_and_preserves_source() {
         "WRITE_FAILED",
     );
     assert!(
-        !blocked_output.exists(),
-        "write failure left a partial output artifact"
-    );
-
-    let mut restore = perms;
-    restore.set_mode(0o755);
-    fs::set_permissions(&blocked_dir, restore).expect("restore blocked perms");
-
-    let after = fs::read(&source_path).expect("read source after write failure");
-    assert_eq!(before, after, "source workbook changed after write failure");
-}
-
-#[cfg(unix)]
-#[test]
-fn phase_a_apply_formula_pattern_maps_write_failures_and_preserves_source() {
-    let tmp = tempdir().expect("tempdir");
-    let source_path = tmp.path().join("phase-a-formula-write-fail-source.xlsx");
-    let blocked_dir = tmp.path().join("blocked-formula");
-    let blocked_output = blocked_dir.join("output.xlsx");
-    let ops_path = tmp.path().join("formula-ops.json");
-    write_fixture(&source_path);
-    write_ops_payload(
-        &ops_path,
-        r#"{"ops":[{"sheet_name":"Sheet1","target_range":"C2:C4","anchor_cell":"C2","base_formula":"B2*3","fill_direction":"down"}]}"#,
-    );
-    fs::create_dir(&blocked_dir).expect("create blocked dir");
-
-    let mut perms = fs::metadata(&blocked_dir)
-        .expect("blocked metadata")
-        .permissions();
-    perms.set_mode(0o555);
-    fs::set_permissions(&blocked_dir, perms.clone()).expect("set blocked perms");
-
-    let before = fs::read(&source_path).expect("read source before write failure");
-    let source = source_path.to_str().expect("source utf8");
-    let output = blocked_output.to_str().expect("output utf8");
-    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
-
-    assert_error_code(
-        &[
-            "apply-formula-pattern",
-            source,
-            "--ops",
-            ops_ref.as_str(),
-            "--output",
-            output,
-        ],
-        "WRITE_FAILED",
-    );
-    assert!(
-        !blocked_output.exists(),
-        "write failure left a partial output artifact"
+        err["message"]
+            .as_str()
+            .unwrap_or_default()
+            .contains("unable to allocate temp file")
+            || err["message"]
+                .as_str()
+                .unwrap_or_default()
+                .contains("Permission denied")
     );
 
     let mut restore = perms;
@@ -5003,110 +6746,60 @@ fn phase_a_apply_formula_pattern_maps_write_failures_and_preserves_source() {
 }
 
 #[test]
-fn phase_b_help_examples_for_structure_column_and_layout_commands() {
-    let structure_help = run_cli(&["structure-batch", "--help"]);
+fn phase_a_help_examples_for_style_and_formula_commands() {
+    let style_help = run_cli(&["style-batch", "--help"]);
     assert!(
-        structure_help.status.success(),
+        style_help.status.success(),
         "stderr: {:?}",
-        structure_help.stderr
-    );
-    let structure = parse_stdout_text(&structure_help);
-    assert!(structure.contains("Examples:"));
-    assert!(
-        structure.contains(
-            "asp write batch structure workbook.xlsx --ops @structure_ops.json --dry-run"
-        )
+        style_help.stderr
     );
-    assert!(structure.contains(
-        "asp write batch structure workbook.xlsx --ops @structure_ops.json --output structured.xlsx"
+    let style = parse_stdout_text(&style_help);
+    assert!(style.contains("Examples:"));
+    assert!(style.contains("asp write batch style workbook.xlsx --ops @style_ops.json --dry-run"));
+    assert!(style.contains(
+        "asp write batch style workbook.xlsx --ops @style_ops.json --output styled.xlsx --force"
     ));
-    assert!(structure.contains("Payload examples (`--ops @structure_ops.json`):"));
-    assert!(structure.contains("\"kind\":\"rename_sheet\""));
-    assert!(structure.contains("\"kind\":\"copy_range\""));
+    assert!(style.contains("Payload examples (`--ops @style_ops.json`):"));
+    assert!(style.contains("\"patch\":{\"font\":{\"bold\":true}}"));
+    assert!(style.contains("Required envelope:"));
 
-    let column_help = run_cli(&["column-size-batch", "--help"]);
+    let formula_help = run_cli(&["apply-formula-pattern", "--help"]);
     assert!(
-        column_help.status.success(),
+        formula_help.status.success(),
         "stderr: {:?}",
-        column_help.stderr
+        formula_help.stderr
     );
-    let column = parse_stdout_text(&column_help);
-    assert!(column.contains("Examples:"));
-    assert!(column.contains(
-        "asp write batch column-size workbook.xlsx --ops @column_size_ops.json --in-place"
+    let formula = parse_stdout_text(&formula_help);
+    assert!(formula.contains("Examples:"));
+    assert!(formula.contains(
+        "asp write batch formula-pattern workbook.xlsx --ops @formula_ops.json --in-place"
     ));
-    assert!(column.contains(
-        "asp write batch column-size workbook.xlsx --ops @column_size_ops.json --output columns.xlsx"
+    assert!(formula.contains(
+        "asp write batch formula-pattern workbook.xlsx --ops @formula_ops.json --dry-run"
+    ));
+    assert!(formula.contains("Payload examples (`--ops @formula_ops.json`):"));
+    assert!(formula.contains("\"target_range\":\"C2:C4\""));
+    assert!(formula.contains("\"fill_direction\":\"both\""));
+    assert!(formula.contains("relative_mode` valid values: excel|abs_cols|abs_rows"));
+    assert!(formula.contains(
+        "Updated formula cells clear cached results. Run recalculate to refresh computed values."
     ));
-    assert!(column.contains("Payload examples (`--ops @column_size_ops.json`):"));
-    assert!(column.contains("\"sheet_name\":\"Sheet1\""));
-    assert!(column.contains("\"kind\":\"width\""));
-    assert!(column.contains("Also accepted: top-level `ops` where each op includes `sheet_name`."));
-
-    let layout_help = run_cli(&["sheet-layout-batch", "--help"]);
-    assert!(
-        layout_help.status.success(),
-        "stderr: {:?}",
-        layout_help.stderr
-    );
-    let layout = parse_stdout_text(&layout_help);
-    assert!(layout.contains("Examples:"));
-    assert!(
-        layout.contains(
-            "asp write batch sheet-layout workbook.xlsx --ops @layout_ops.json --dry-run"
-        )
-    );
     assert!(
-        layout.contains(
-            "asp write batch sheet-layout workbook.xlsx --ops @layout_ops.json --in-place"
-        )
-    );
-    assert!(layout.contains("Payload examples (`--ops @layout_ops.json`):"));
-    assert!(layout.contains("\"kind\":\"freeze_panes\""));
-    assert!(layout.contains("\"kind\":\"set_page_setup\""));
-}
-
-#[test]
-fn phase_b_structure_batch_positive_in_place_renames_sheet() {
-    let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("phase-b-structure-in-place.xlsx");
-    let ops_path = tmp.path().join("structure-ops.json");
-    write_fixture(&workbook_path);
-    write_ops_payload(
-        &ops_path,
-        r#"{"ops":[{"kind":"rename_sheet","old_name":"Summary","new_name":"Dashboard"}]}"#,
+        formula.contains("write_path_provenance"),
+        "apply-formula-pattern help should mention provenance diagnostics"
     );
-
-    let file = workbook_path.to_str().expect("path utf8");
-    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
-
-    let output = run_cli(&[
-        "structure-batch",
-        file,
-        "--ops",
-        ops_ref.as_str(),
-        "--in-place",
-    ]);
-    assert!(output.status.success(), "stderr: {:?}", output.stderr);
-    let payload = parse_stdout_json(&output);
-    assert_eq!(payload["op_count"].as_u64(), Some(1));
-    assert!(payload["changed"].as_bool().unwrap_or(false));
-
-    let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read workbook");
-    assert!(book.get_sheet_by_name("Dashboard").is_some());
-    assert!(book.get_sheet_by_name("Summary").is_none());
 }
 
 #[test]
-fn phase_b_structure_batch_positive_dry_run_and_output_target_only() {
+fn phase_a_style_batch_positive_dry_run_and_output_target_only() {
     let tmp = tempdir().expect("tempdir");
-    let source_path = tmp.path().join("phase-b-structure-source.xlsx");
-    let output_path = tmp.path().join("phase-b-structure-output.xlsx");
-    let ops_path = tmp.path().join("structure-ops-output.json");
+    let source_path = tmp.path().join("phase-a-style-source.xlsx");
+    let output_path = tmp.path().join("phase-a-style-output.xlsx");
+    let ops_path = tmp.path().join("style-ops.json");
     write_fixture(&source_path);
     write_ops_payload(
         &ops_path,
-        r#"{"ops":[{"kind":"rename_sheet","old_name":"Summary","new_name":"Dashboard"}]}"#,
+        r#"{"ops":[{"sheet_name":"Sheet1","range":"B2:B2","style":{"font":{"bold":true}}}]}"#,
     );
 
     let source = source_path.to_str().expect("source utf8");
@@ -5114,9 +6807,8 @@ fn phase_b_structure_batch_positive_dry_run_and_output_target_only() {
     let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
 
     let before = fs::read(&source_path).expect("read source before dry-run");
-
     let dry_run = run_cli(&[
-        "structure-batch",
+        "style-batch",
         source,
         "--ops",
         ops_ref.as_str(),
@@ -5124,13 +6816,15 @@ fn phase_b_structure_batch_positive_dry_run_and_output_target_only() {
     ]);
     assert!(dry_run.status.success(), "stderr: {:?}", dry_run.stderr);
     let dry_payload = parse_stdout_json(&dry_run);
+    assert_eq!(dry_payload["op_count"].as_u64(), Some(1));
+    assert_eq!(dry_payload["validated_count"].as_u64(), Some(1));
     assert!(dry_payload["would_change"].as_bool().unwrap_or(false));
 
-    let source_after_dry = fs::read(&source_path).expect("read source after dry-run");
-    assert_eq!(before, source_after_dry, "dry-run mutated source workbook");
+    let after_dry = fs::read(&source_path).expect("read source after dry-run");
+    assert_eq!(before, after_dry, "dry-run mutated source file");
 
     let output_run = run_cli(&[
-        "structure-batch",
+        "style-batch",
         source,
         "--ops",
         ops_ref.as_str(),
@@ -5142,33 +6836,32 @@ fn phase_b_structure_batch_positive_dry_run_and_output_target_only() {
         "stderr: {:?}",
         output_run.stderr
     );
-    let payload = parse_stdout_json(&output_run);
-    assert!(payload["changed"].as_bool().unwrap_or(false));
-
-    let source_book = umya_spreadsheet::reader::xlsx::read(&source_path).expect("read source");
-    assert!(source_book.get_sheet_by_name("Summary").is_some());
-    assert!(source_book.get_sheet_by_name("Dashboard").is_none());
+    let output_payload = parse_stdout_json(&output_run);
+    assert_json_path_eq(&output_payload, "target_path", output);
+    assert_json_path_eq(&output_payload, "source_path", source);
+    assert!(output_payload["changed"].as_bool().unwrap_or(false));
 
-    let output_book = umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output");
-    assert!(output_book.get_sheet_by_name("Dashboard").is_some());
-    assert!(output_book.get_sheet_by_name("Summary").is_none());
+    let source_after = fs::read(&source_path).expect("read source after output mode");
+    let output_after = fs::read(&output_path).expect("read output after output mode");
+    assert_eq!(before, source_after, "source changed during --output mode");
+    assert_ne!(source_after, output_after, "output file did not change");
 }
 
 #[test]
-fn phase_b_structure_batch_output_force_overwrite_semantics() {
+fn phase_a_style_batch_output_force_overwrite_semantics() {
     let tmp = tempdir().expect("tempdir");
-    let source_path = tmp.path().join("phase-b-structure-force-source.xlsx");
-    let output_path = tmp.path().join("phase-b-structure-force-output.xlsx");
-    let ops_first_path = tmp.path().join("structure-ops-first.json");
-    let ops_second_path = tmp.path().join("structure-ops-second.json");
+    let source_path = tmp.path().join("phase-a-style-force-source.xlsx");
+    let output_path = tmp.path().join("phase-a-style-force-output.xlsx");
+    let ops_first_path = tmp.path().join("style-ops-first.json");
+    let ops_second_path = tmp.path().join("style-ops-second.json");
     write_fixture(&source_path);
     write_ops_payload(
         &ops_first_path,
-        r#"{"ops":[{"kind":"rename_sheet","old_name":"Summary","new_name":"Dashboard"}]}"#,
+        r#"{"ops":[{"sheet_name":"Sheet1","range":"B2:B2","style":{"font":{"bold":true}}}]}"#,
     );
     write_ops_payload(
         &ops_second_path,
-        r#"{"ops":[{"kind":"rename_sheet","old_name":"Summary","new_name":"Board"}]}"#,
+        r#"{"ops":[{"sheet_name":"Sheet1","range":"B2:B2","style":{"font":{"italic":true}}}]}"#,
     );
 
     let source = source_path.to_str().expect("source utf8");
@@ -5177,7 +6870,7 @@ fn phase_b_structure_batch_output_force_overwrite_semantics() {
     let second_ref = format!("@{}", ops_second_path.to_str().expect("ops utf8"));
 
     let first = run_cli(&[
-        "structure-batch",
+        "style-batch",
         source,
         "--ops",
         first_ref.as_str(),
@@ -5185,10 +6878,11 @@ fn phase_b_structure_batch_output_force_overwrite_semantics() {
         output,
     ]);
     assert!(first.status.success(), "stderr: {:?}", first.stderr);
+    let first_bytes = fs::read(&output_path).expect("read first output bytes");
 
     assert_error_code(
         &[
-            "structure-batch",
+            "style-batch",
             source,
             "--ops",
             second_ref.as_str(),
@@ -5197,9 +6891,11 @@ fn phase_b_structure_batch_output_force_overwrite_semantics() {
         ],
         "OUTPUT_EXISTS",
     );
+    let after_failed_bytes = fs::read(&output_path).expect("read output after failed overwrite");
+    assert_eq!(first_bytes, after_failed_bytes);
 
     let forced = run_cli(&[
-        "structure-batch",
+        "style-batch",
         source,
         "--ops",
         second_ref.as_str(),
@@ -5209,21 +6905,23 @@ fn phase_b_structure_batch_output_force_overwrite_semantics() {
     ]);
     assert!(forced.status.success(), "stderr: {:?}", forced.stderr);
 
-    let output_book = umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output");
-    assert!(output_book.get_sheet_by_name("Board").is_some());
-    assert!(output_book.get_sheet_by_name("Summary").is_none());
+    let forced_bytes = fs::read(&output_path).expect("read forced output bytes");
+    assert_ne!(
+        first_bytes, forced_bytes,
+        "force overwrite did not update output"
+    );
 }
 
 #[test]
-fn phase_b_column_size_batch_positive_output_mutates_target_only() {
+fn phase_a_apply_formula_pattern_positive_dry_run_and_output_target_only() {
     let tmp = tempdir().expect("tempdir");
-    let source_path = tmp.path().join("phase-b-column-source.xlsx");
-    let output_path = tmp.path().join("phase-b-column-output.xlsx");
-    let ops_path = tmp.path().join("column-ops.json");
+    let source_path = tmp.path().join("phase-a-formula-source.xlsx");
+    let output_path = tmp.path().join("phase-a-formula-output.xlsx");
+    let ops_path = tmp.path().join("formula-ops.json");
     write_fixture(&source_path);
     write_ops_payload(
         &ops_path,
-        r#"{"sheet_name":"Sheet1","ops":[{"range":"A:A","size":{"kind":"width","width_chars":25.0}}]}"#,
+        r#"{"ops":[{"sheet_name":"Sheet1","target_range":"C2:C4","anchor_cell":"C2","base_formula":"B2*3","fill_direction":"down","relative_mode":"excel"}]}"#,
     );
 
     let source = source_path.to_str().expect("source utf8");
@@ -5231,9 +6929,8 @@ fn phase_b_column_size_batch_positive_output_mutates_target_only() {
     let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
 
     let before = fs::read(&source_path).expect("read source before dry-run");
-
     let dry_run = run_cli(&[
-        "column-size-batch",
+        "apply-formula-pattern",
         source,
         "--ops",
         ops_ref.as_str(),
@@ -5241,155 +6938,156 @@ fn phase_b_column_size_batch_positive_output_mutates_target_only() {
     ]);
     assert!(dry_run.status.success(), "stderr: {:?}", dry_run.stderr);
     let dry_payload = parse_stdout_json(&dry_run);
+    assert_eq!(dry_payload["op_count"].as_u64(), Some(1));
     assert!(dry_payload["would_change"].as_bool().unwrap_or(false));
-
-    let source_after_dry = fs::read(&source_path).expect("read source after dry-run");
-    assert_eq!(before, source_after_dry, "dry-run mutated source workbook");
-
-    let run = run_cli(&[
-        "column-size-batch",
-        source,
-        "--ops",
-        ops_ref.as_str(),
-        "--output",
-        output,
-    ]);
-    assert!(run.status.success(), "stderr: {:?}", run.stderr);
-    let payload = parse_stdout_json(&run);
-    assert!(payload["changed"].as_bool().unwrap_or(false));
-
-    let source_after = fs::read(&source_path).expect("read source after output mode");
-    assert_eq!(before, source_after, "source changed during --output mode");
-
-    let output_book =
-        umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output workbook");
-    let output_sheet = output_book
-        .get_sheet_by_name("Sheet1")
-        .expect("sheet exists");
-    let width = *output_sheet
-        .get_column_dimension("A")
-        .expect("A column")
-        .get_width();
-    assert!((width - 25.0).abs() < 0.001);
-}
-
-#[test]
-fn phase_b_column_size_batch_accepts_per_op_sheet_name_shape() {
-    let tmp = tempdir().expect("tempdir");
-    let source_path = tmp.path().join("phase-b-column-per-op-source.xlsx");
-    let output_path = tmp.path().join("phase-b-column-per-op-output.xlsx");
-    let ops_path = tmp.path().join("column-ops-per-op-sheet.json");
-    write_fixture(&source_path);
-    write_ops_payload(
-        &ops_path,
-        r#"{"ops":[{"sheet_name":"Sheet1","range":"A:A","size":{"kind":"width","width_chars":21.0}}]}"#,
+    assert_eq!(
+        dry_payload["write_path_provenance"]["written_via"],
+        Value::String("apply_formula_pattern".to_string())
     );
+    let dry_targets = dry_payload["write_path_provenance"]["formula_targets"]
+        .as_array()
+        .expect("formula_targets array");
+    assert!(
+        dry_targets
+            .iter()
+            .any(|target| target.as_str() == Some("Sheet1!C2:C4"))
+    );
+    let after_dry = fs::read(&source_path).expect("read source after dry-run");
+    assert_eq!(before, after_dry, "dry-run mutated source file");
 
-    let source = source_path.to_str().expect("source utf8");
-    let output = output_path.to_str().expect("output utf8");
-    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
-
-    let run = run_cli(&[
-        "column-size-batch",
+    let output_run = run_cli(&[
+        "apply-formula-pattern",
         source,
         "--ops",
         ops_ref.as_str(),
         "--output",
         output,
     ]);
-    assert!(run.status.success(), "stderr: {:?}", run.stderr);
+    assert!(
+        output_run.status.success(),
+        "stderr: {:?}",
+        output_run.stderr
+    );
+    let output_payload = parse_stdout_json(&output_run);
+    assert!(output_payload["changed"].as_bool().unwrap_or(false));
+    assert_eq!(
+        output_payload["write_path_provenance"]["written_via"],
+        Value::String("apply_formula_pattern".to_string())
+    );
+
+    let source_book =
+        umya_spreadsheet::reader::xlsx::read(&source_path).expect("read source workbook");
+    let source_sheet = source_book
+        .get_sheet_by_name("Sheet1")
+        .expect("source sheet");
+    assert_eq!(
+        source_sheet
+            .get_cell("C2")
+            .expect("C2 source")
+            .get_formula(),
+        "B2*2"
+    );
 
     let output_book =
         umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output workbook");
     let output_sheet = output_book
         .get_sheet_by_name("Sheet1")
-        .expect("sheet exists");
-    let width = *output_sheet
-        .get_column_dimension("A")
-        .expect("A column")
-        .get_width();
-    assert!((width - 21.0).abs() < 0.001);
-}
-
-#[test]
-fn phase_b_column_size_batch_rejects_mixed_per_op_sheet_names() {
-    let tmp = tempdir().expect("tempdir");
-    let source_path = tmp.path().join("phase-b-column-mixed-per-op-source.xlsx");
-    let ops_path = tmp.path().join("column-ops-mixed-sheets.json");
-    write_fixture(&source_path);
-    write_ops_payload(
-        &ops_path,
-        r#"{"ops":[{"sheet_name":"Sheet1","range":"A:A","size":{"kind":"width","width_chars":21.0}},{"sheet_name":"Summary","range":"A:A","size":{"kind":"width","width_chars":10.0}}]}"#,
+        .expect("output sheet");
+    assert_eq!(
+        output_sheet
+            .get_cell("C2")
+            .expect("C2 output")
+            .get_formula()
+            .replace(' ', ""),
+        "B2*3"
     );
-
-    let source = source_path.to_str().expect("source utf8");
-    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
-
-    let err = assert_error_code(
-        &[
-            "column-size-batch",
-            source,
-            "--ops",
-            ops_ref.as_str(),
-            "--dry-run",
-        ],
-        "INVALID_OPS_PAYLOAD",
+    assert_eq!(
+        output_sheet
+            .get_cell("C3")
+            .expect("C3 output")
+            .get_formula()
+            .replace(' ', ""),
+        "B3*3"
     );
-    assert!(
-        err["message"]
-            .as_str()
-            .unwrap_or_default()
-            .contains("mixed sheet_name values")
+    assert_eq!(
+        output_sheet
+            .get_cell("C4")
+            .expect("C4 output")
+            .get_formula()
+            .replace(' ', ""),
+        "B4*3"
     );
 }
 
 #[test]
-fn phase_b_column_size_batch_rejects_hybrid_mixed_sheet_names() {
+fn phase_a_apply_formula_pattern_clears_formula_cache_for_touched_cells() {
     let tmp = tempdir().expect("tempdir");
-    let source_path = tmp.path().join("phase-b-column-hybrid-mixed-source.xlsx");
-    let ops_path = tmp.path().join("column-ops-hybrid-mixed-sheets.json");
-    write_fixture(&source_path);
+    let workbook_path = tmp.path().join("phase-a-formula-cache-clear.xlsx");
+    let ops_path = tmp.path().join("formula-cache-ops.json");
+
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook
+            .get_sheet_by_name_mut("Sheet1")
+            .expect("default sheet exists");
+        sheet.get_cell_mut("A1").set_value("Name");
+        sheet.get_cell_mut("B1").set_value("Amount");
+        sheet.get_cell_mut("C1").set_value("Total");
+        sheet.get_cell_mut("A2").set_value("Alice");
+        sheet.get_cell_mut("B2").set_value_number(10.0);
+        let c2 = sheet.get_cell_mut("C2");
+        c2.set_formula("B2*2");
+        c2.get_cell_value_mut().set_formula_result_default("20");
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+
     write_ops_payload(
         &ops_path,
-        r#"{"sheet_name":"Settings","ops":[{"sheet_name":"Summary","range":"A:A","size":{"kind":"width","width_chars":21.0}}]}"#,
+        r#"{"ops":[{"sheet_name":"Sheet1","target_range":"C2:C2","anchor_cell":"C2","base_formula":"B2*3","fill_direction":"down","relative_mode":"excel"}]}"#,
     );
 
-    let source = source_path.to_str().expect("source utf8");
+    let file = workbook_path.to_str().expect("path utf8");
     let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
 
-    let err = assert_error_code(
-        &[
-            "column-size-batch",
-            source,
-            "--ops",
-            ops_ref.as_str(),
-            "--dry-run",
-        ],
-        "INVALID_OPS_PAYLOAD",
-    );
+    let output = run_cli(&[
+        "apply-formula-pattern",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+
+    let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read workbook");
+    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet exists");
+    let c2 = sheet.get_cell("C2").expect("C2 exists");
+    assert_eq!(c2.get_formula().replace(' ', ""), "B2*3");
+    assert_eq!(c2.get_value(), "", "expected formula cache to be cleared");
+
+    let read = run_cli(&["range-values", file, "Sheet1", "C2", "--shape", "compact"]);
+    assert!(read.status.success(), "stderr: {:?}", read.stderr);
+    let payload = parse_stdout_json(&read);
     assert!(
-        err["message"]
-            .as_str()
-            .unwrap_or_default()
-            .contains("mixed sheet_name values")
+        payload["rows"][0][0].is_null(),
+        "range-values should report null until recalculate refreshes cache"
     );
 }
 
 #[test]
-fn phase_b_column_size_batch_output_force_overwrite_semantics() {
+fn phase_a_apply_formula_pattern_output_force_overwrite_semantics() {
     let tmp = tempdir().expect("tempdir");
-    let source_path = tmp.path().join("phase-b-column-force-source.xlsx");
-    let output_path = tmp.path().join("phase-b-column-force-output.xlsx");
-    let ops_first_path = tmp.path().join("column-ops-first.json");
-    let ops_second_path = tmp.path().join("column-ops-second.json");
+    let source_path = tmp.path().join("phase-a-force-source.xlsx");
+    let output_path = tmp.path().join("phase-a-force-output.xlsx");
+    let ops_first_path = tmp.path().join("formula-ops-first.json");
+    let ops_second_path = tmp.path().join("formula-ops-second.json");
     write_fixture(&source_path);
     write_ops_payload(
         &ops_first_path,
-        r#"{"sheet_name":"Sheet1","ops":[{"range":"A:A","size":{"kind":"width","width_chars":25.0}}]}"#,
+        r#"{"ops":[{"sheet_name":"Sheet1","target_range":"C2:C2","anchor_cell":"C2","base_formula":"B2*3","fill_direction":"down"}]}"#,
     );
     write_ops_payload(
         &ops_second_path,
-        r#"{"sheet_name":"Sheet1","ops":[{"range":"A:A","size":{"kind":"width","width_chars":18.0}}]}"#,
+        r#"{"ops":[{"sheet_name":"Sheet1","target_range":"C2:C2","anchor_cell":"C2","base_formula":"B2*5","fill_direction":"down"}]}"#,
     );
 
     let source = source_path.to_str().expect("source utf8");
@@ -5398,7 +7096,7 @@ fn phase_b_column_size_batch_output_force_overwrite_semantics() {
     let second_ref = format!("@{}", ops_second_path.to_str().expect("ops utf8"));
 
     let first = run_cli(&[
-        "column-size-batch",
+        "apply-formula-pattern",
         source,
         "--ops",
         first_ref.as_str(),
@@ -5409,7 +7107,7 @@ fn phase_b_column_size_batch_output_force_overwrite_semantics() {
 
     assert_error_code(
         &[
-            "column-size-batch",
+            "apply-formula-pattern",
             source,
             "--ops",
             second_ref.as_str(),
@@ -5419,19 +7117,8 @@ fn phase_b_column_size_batch_output_force_overwrite_semantics() {
         "OUTPUT_EXISTS",
     );
 
-    let without_force_book =
-        umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output without force");
-    let without_force_sheet = without_force_book
-        .get_sheet_by_name("Sheet1")
-        .expect("sheet exists");
-    let without_force_width = *without_force_sheet
-        .get_column_dimension("A")
-        .expect("A column")
-        .get_width();
-    assert!((without_force_width - 25.0).abs() < 0.001);
-
     let forced = run_cli(&[
-        "column-size-batch",
+        "apply-formula-pattern",
         source,
         "--ops",
         second_ref.as_str(),
@@ -5441,426 +7128,179 @@ fn phase_b_column_size_batch_output_force_overwrite_semantics() {
     ]);
     assert!(forced.status.success(), "stderr: {:?}", forced.stderr);
 
-    let forced_book = umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output");
-    let forced_sheet = forced_book
+    let output_book = umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output");
+    let output_sheet = output_book
         .get_sheet_by_name("Sheet1")
         .expect("sheet exists");
-    let forced_width = *forced_sheet
-        .get_column_dimension("A")
-        .expect("A column")
-        .get_width();
-    assert!((forced_width - 18.0).abs() < 0.001);
+    assert_eq!(
+        output_sheet
+            .get_cell("C2")
+            .expect("C2 output")
+            .get_formula()
+            .replace(' ', ""),
+        "B2*5"
+    );
 }
 
 #[test]
-fn phase_b_sheet_layout_batch_positive_dry_run_and_in_place() {
+fn phase_a_negative_invalid_ops_payloads() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("phase-b-layout.xlsx");
-    let ops_path = tmp.path().join("layout-ops.json");
+    let workbook_path = tmp.path().join("phase-a-invalid-ops.xlsx");
+    let style_bad_path = tmp.path().join("style-bad.json");
+    let formula_bad_path = tmp.path().join("formula-bad.json");
     write_fixture(&workbook_path);
     write_ops_payload(
-        &ops_path,
-        r#"{"ops":[{"kind":"freeze_panes","sheet_name":"Sheet1","freeze_rows":1,"freeze_cols":1}]}"#,
+        &style_bad_path,
+        r#"{"ops":[{"sheet_name":"Sheet1","target":{"kind":"unknown"},"patch":{}}]}"#,
+    );
+    write_ops_payload(
+        &formula_bad_path,
+        r#"{"ops":[{"sheet_name":"Sheet1","target_range":"C2:C4","anchor_cell":"C1","base_formula":"B2*3","fill_direction":"down"}]}"#,
     );
 
     let file = workbook_path.to_str().expect("path utf8");
-    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
-
-    let before = fs::read(&workbook_path).expect("read before dry-run");
-    let dry_run = run_cli(&[
-        "sheet-layout-batch",
-        file,
-        "--ops",
-        ops_ref.as_str(),
-        "--dry-run",
-    ]);
-    assert!(dry_run.status.success(), "stderr: {:?}", dry_run.stderr);
-    let dry_payload = parse_stdout_json(&dry_run);
-    assert!(dry_payload["would_change"].as_bool().unwrap_or(false));
-    let after_dry = fs::read(&workbook_path).expect("read after dry-run");
-    assert_eq!(before, after_dry, "dry-run mutated workbook");
-
-    let in_place = run_cli(&[
-        "sheet-layout-batch",
-        file,
-        "--ops",
-        ops_ref.as_str(),
-        "--in-place",
-    ]);
-    assert!(in_place.status.success(), "stderr: {:?}", in_place.stderr);
+    let style_ref = format!("@{}", style_bad_path.to_str().expect("ops utf8"));
+    let formula_ref = format!("@{}", formula_bad_path.to_str().expect("ops utf8"));
 
-    let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read workbook");
-    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet exists");
-    let views = sheet.get_sheets_views().get_sheet_view_list();
-    let view = views.first().expect("sheet view");
-    let pane = view.get_pane().expect("pane");
-    assert_eq!(*pane.get_horizontal_split(), 1.0);
-    assert_eq!(*pane.get_vertical_split(), 1.0);
-    assert_eq!(pane.get_top_left_cell().to_string(), "B2");
-    assert_eq!(
-        view.get_top_left_cell(),
-        "",
-        "sheetView topLeftCell should remain unset for LO compatibility"
+    assert_error_code(
+        &[
+            "style-batch",
+            file,
+            "--ops",
+            style_ref.as_str(),
+            "--dry-run",
+        ],
+        "INVALID_OPS_PAYLOAD",
     );
-
-    let selection = view.get_selection().first().expect("selection");
-    assert_eq!(selection.get_sequence_of_references().get_sqref(), "B2");
-    assert_eq!(
-        selection.get_active_cell().map(|coord| coord.to_string()),
-        Some("B2".to_string())
+    assert_error_code(
+        &[
+            "apply-formula-pattern",
+            file,
+            "--ops",
+            formula_ref.as_str(),
+            "--dry-run",
+        ],
+        "INVALID_OPS_PAYLOAD",
     );
 }
 
 #[test]
-fn phase_b_sheet_layout_batch_clears_preexisting_sheet_view_top_left_cell() {
+fn phase_a_invalid_relative_mode_reports_valid_literals_and_suggestion() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("phase-b-layout-preexisting-top-left.xlsx");
-    let ops_path = tmp.path().join("layout-preexisting-top-left-ops.json");
+    let workbook_path = tmp.path().join("phase-a-invalid-relative-mode.xlsx");
+    let formula_bad_path = tmp.path().join("formula-bad-relative-mode.json");
     write_fixture(&workbook_path);
-
-    {
-        let mut book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read workbook");
-        let sheet = book.get_sheet_by_name_mut("Sheet1").expect("sheet");
-        let view = sheet
-            .get_sheet_views_mut()
-            .get_sheet_view_list_mut()
-            .first_mut()
-            .expect("sheet view");
-        view.set_top_left_cell("C3");
-        umya_spreadsheet::writer::xlsx::write(&book, &workbook_path).expect("write workbook");
-    }
-
     write_ops_payload(
-        &ops_path,
-        r#"{"ops":[{"kind":"freeze_panes","sheet_name":"Sheet1","freeze_rows":1,"freeze_cols":1}]}"#,
+        &formula_bad_path,
+        r#"{"ops":[{"sheet_name":"Sheet1","target_range":"C2:C4","anchor_cell":"C2","base_formula":"B2*3","fill_direction":"down","relative_mode":"fully_relative"}]}"#,
     );
 
     let file = workbook_path.to_str().expect("path utf8");
-    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
-
-    let in_place = run_cli(&[
-        "sheet-layout-batch",
-        file,
-        "--ops",
-        ops_ref.as_str(),
-        "--in-place",
-    ]);
-    assert!(in_place.status.success(), "stderr: {:?}", in_place.stderr);
+    let formula_ref = format!("@{}", formula_bad_path.to_str().expect("ops utf8"));
 
-    let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read workbook");
-    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet exists");
-    let view = sheet
-        .get_sheets_views()
-        .get_sheet_view_list()
-        .first()
-        .expect("sheet view");
-    assert_eq!(
-        view.get_top_left_cell(),
-        "",
-        "preexisting sheetView topLeftCell should be cleared for LO compatibility"
+    let err = assert_error_code(
+        &[
+            "apply-formula-pattern",
+            file,
+            "--ops",
+            formula_ref.as_str(),
+            "--dry-run",
+        ],
+        "INVALID_OPS_PAYLOAD",
     );
-    let pane = view.get_pane().expect("pane");
-    assert_eq!(pane.get_top_left_cell().to_string(), "B2");
+
+    let message = err["message"].as_str().unwrap_or_default();
+    assert!(message.contains("invalid relative_mode 'fully_relative'"));
+    assert!(message.contains("Did you mean 'excel'?"));
+    assert!(message.contains("valid: excel|abs_cols|abs_rows"));
 }
 
 #[test]
-fn phase_b_sheet_layout_batch_positive_output_mutates_target_only() {
+fn phase_a_safety_mode_matrix_for_style_and_formula_commands() {
     let tmp = tempdir().expect("tempdir");
-    let source_path = tmp.path().join("phase-b-layout-source.xlsx");
-    let output_path = tmp.path().join("phase-b-layout-output.xlsx");
-    let ops_path = tmp.path().join("layout-output-ops.json");
-    write_fixture(&source_path);
+    let workbook_path = tmp.path().join("phase-a-safety.xlsx");
+    let style_ops_path = tmp.path().join("style-ops.json");
+    let formula_ops_path = tmp.path().join("formula-ops.json");
+    write_fixture(&workbook_path);
     write_ops_payload(
-        &ops_path,
-        r#"{"ops":[{"kind":"freeze_panes","sheet_name":"Sheet1","freeze_rows":1,"freeze_cols":1}]}"#,
+        &style_ops_path,
+        r#"{"ops":[{"sheet_name":"Sheet1","range":"B2:B2","style":{"font":{"bold":true}}}]}"#,
+    );
+    write_ops_payload(
+        &formula_ops_path,
+        r#"{"ops":[{"sheet_name":"Sheet1","target_range":"C2:C4","anchor_cell":"C2","base_formula":"B2*3","fill_direction":"down"}]}"#,
     );
 
-    let source = source_path.to_str().expect("source utf8");
-    let output = output_path.to_str().expect("output utf8");
-    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
-
-    let before = fs::read(&source_path).expect("read source before output mode");
-
-    let run = run_cli(&[
-        "sheet-layout-batch",
-        source,
-        "--ops",
-        ops_ref.as_str(),
-        "--output",
-        output,
-    ]);
-    assert!(run.status.success(), "stderr: {:?}", run.stderr);
-    let payload = parse_stdout_json(&run);
-    assert!(payload["changed"].as_bool().unwrap_or(false));
-
-    let source_after = fs::read(&source_path).expect("read source after output mode");
-    assert_eq!(before, source_after, "source changed during --output mode");
+    let file = workbook_path.to_str().expect("path utf8");
+    let style_ref = format!("@{}", style_ops_path.to_str().expect("ops utf8"));
+    let formula_ref = format!("@{}", formula_ops_path.to_str().expect("ops utf8"));
 
-    let output_book = umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output");
-    let output_sheet = output_book
-        .get_sheet_by_name("Sheet1")
-        .expect("sheet exists");
-    let view = output_sheet
-        .get_sheets_views()
-        .get_sheet_view_list()
-        .first()
-        .expect("sheet view");
-    let pane = view.get_pane().expect("pane");
-    assert_eq!(pane.get_top_left_cell().to_string(), "B2");
-    assert_eq!(view.get_top_left_cell(), "");
-    let selection = view.get_selection().first().expect("selection");
-    assert_eq!(selection.get_sequence_of_references().get_sqref(), "B2");
+    assert_batch_mode_matrix("style-batch", file, style_ref.as_str());
+    assert_batch_mode_matrix("apply-formula-pattern", file, formula_ref.as_str());
 }
 
+#[cfg(unix)]
 #[test]
-fn phase_b_sheet_layout_batch_output_force_overwrite_semantics() {
+fn phase_a_style_batch_maps_write_failures_and_preserves_source() {
     let tmp = tempdir().expect("tempdir");
-    let source_path = tmp.path().join("phase-b-layout-force-source.xlsx");
-    let output_path = tmp.path().join("phase-b-layout-force-output.xlsx");
-    let ops_first_path = tmp.path().join("layout-ops-first.json");
-    let ops_second_path = tmp.path().join("layout-ops-second.json");
+    let source_path = tmp.path().join("phase-a-style-write-fail-source.xlsx");
+    let blocked_dir = tmp.path().join("blocked");
+    let blocked_output = blocked_dir.join("output.xlsx");
+    let ops_path = tmp.path().join("ops.json");
     write_fixture(&source_path);
     write_ops_payload(
-        &ops_first_path,
-        r#"{"ops":[{"kind":"freeze_panes","sheet_name":"Sheet1","freeze_rows":1,"freeze_cols":1}]}"#,
-    );
-    write_ops_payload(
-        &ops_second_path,
-        r#"{"ops":[{"kind":"freeze_panes","sheet_name":"Sheet1","freeze_rows":2,"freeze_cols":0}]}"#,
+        &ops_path,
+        r#"{"ops":[{"sheet_name":"Sheet1","range":"B2:B2","style":{"font":{"bold":true}}}]}"#,
     );
+    fs::create_dir(&blocked_dir).expect("create blocked dir");
 
-    let source = source_path.to_str().expect("source utf8");
-    let output = output_path.to_str().expect("output utf8");
-    let first_ref = format!("@{}", ops_first_path.to_str().expect("ops utf8"));
-    let second_ref = format!("@{}", ops_second_path.to_str().expect("ops utf8"));
+    let mut perms = fs::metadata(&blocked_dir)
+        .expect("blocked metadata")
+        .permissions();
+    perms.set_mode(0o555);
+    fs::set_permissions(&blocked_dir, perms.clone()).expect("set blocked perms");
 
-    let first = run_cli(&[
-        "sheet-layout-batch",
-        source,
-        "--ops",
-        first_ref.as_str(),
-        "--output",
-        output,
-    ]);
-    assert!(first.status.success(), "stderr: {:?}", first.stderr);
+    let before = fs::read(&source_path).expect("read source before write failure");
+    let source = source_path.to_str().expect("source utf8");
+    let output = blocked_output.to_str().expect("output utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
 
     assert_error_code(
         &[
-            "sheet-layout-batch",
+            "style-batch",
             source,
             "--ops",
-            second_ref.as_str(),
+            ops_ref.as_str(),
             "--output",
             output,
         ],
-        "OUTPUT_EXISTS",
+        "WRITE_FAILED",
+    );
+    assert!(
+        !blocked_output.exists(),
+        "write failure left a partial output artifact"
     );
 
-    let without_force_book =
-        umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output without force");
-    let without_force_sheet = without_force_book
-        .get_sheet_by_name("Sheet1")
-        .expect("sheet exists");
-    let without_force_pane = without_force_sheet
-        .get_sheets_views()
-        .get_sheet_view_list()
-        .first()
-        .and_then(|view| view.get_pane())
-        .expect("pane without force");
-    assert_eq!(without_force_pane.get_top_left_cell().to_string(), "B2");
+    let mut restore = perms;
+    restore.set_mode(0o755);
+    fs::set_permissions(&blocked_dir, restore).expect("restore blocked perms");
 
-    let forced = run_cli(&[
-        "sheet-layout-batch",
-        source,
-        "--ops",
-        second_ref.as_str(),
-        "--output",
-        output,
-        "--force",
-    ]);
-    assert!(forced.status.success(), "stderr: {:?}", forced.stderr);
-
-    let forced_book = umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output");
-    let forced_sheet = forced_book
-        .get_sheet_by_name("Sheet1")
-        .expect("sheet exists");
-    let forced_pane = forced_sheet
-        .get_sheets_views()
-        .get_sheet_view_list()
-        .first()
-        .and_then(|view| view.get_pane())
-        .expect("forced pane");
-    assert_eq!(forced_pane.get_top_left_cell().to_string(), "A3");
-}
-
-#[test]
-fn phase_b_negative_invalid_ops_payloads() {
-    let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("phase-b-invalid-ops.xlsx");
-    let structure_bad_path = tmp.path().join("structure-bad.json");
-    let column_bad_path = tmp.path().join("column-bad.json");
-    let layout_bad_path = tmp.path().join("layout-bad.json");
-    write_fixture(&workbook_path);
-    write_ops_payload(&structure_bad_path, r#"{"ops":[{"kind":"unknown_kind"}]}"#);
-    write_ops_payload(
-        &column_bad_path,
-        r#"{"ops":[{"range":"A:A","size":{"kind":"width","width_chars":12.0}}]}"#,
-    );
-    write_ops_payload(
-        &layout_bad_path,
-        r#"{"ops":[{"kind":"set_zoom","sheet_name":"Sheet1","zoom_percent":5}]}"#,
-    );
-
-    let file = workbook_path.to_str().expect("path utf8");
-    let structure_ref = format!("@{}", structure_bad_path.to_str().expect("ops utf8"));
-    let column_ref = format!("@{}", column_bad_path.to_str().expect("ops utf8"));
-    let layout_ref = format!("@{}", layout_bad_path.to_str().expect("ops utf8"));
-
-    assert_error_code(
-        &[
-            "structure-batch",
-            file,
-            "--ops",
-            structure_ref.as_str(),
-            "--dry-run",
-        ],
-        "INVALID_OPS_PAYLOAD",
-    );
-    assert_error_code(
-        &[
-            "column-size-batch",
-            file,
-            "--ops",
-            column_ref.as_str(),
-            "--dry-run",
-        ],
-        "INVALID_OPS_PAYLOAD",
-    );
-    assert_error_code(
-        &[
-            "sheet-layout-batch",
-            file,
-            "--ops",
-            layout_ref.as_str(),
-            "--dry-run",
-        ],
-        "INVALID_OPS_PAYLOAD",
-    );
-}
-
-#[test]
-fn invalid_ops_payload_errors_include_shape_and_minimal_example() {
-    let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("invalid-ops-message.xlsx");
-    let transform_bad_path = tmp.path().join("transform-bad.json");
-    let column_bad_path = tmp.path().join("column-bad-shape.json");
-    let rules_bad_path = tmp.path().join("rules-bad.json");
-    write_fixture(&workbook_path);
-
-    // Not an object at all.
-    write_ops_payload(&transform_bad_path, r#"[]"#);
-    // Missing required top-level field `sheet_name`.
-    write_ops_payload(&column_bad_path, r#"{"ops":[]}"#);
-    // Missing required fields inside the op.
-    write_ops_payload(
-        &rules_bad_path,
-        r#"{"ops":[{"kind":"set_data_validation"}]}"#,
-    );
-
-    let file = workbook_path.to_str().expect("path utf8");
-    let transform_ref = format!("@{}", transform_bad_path.to_str().expect("ops utf8"));
-    let column_ref = format!("@{}", column_bad_path.to_str().expect("ops utf8"));
-    let rules_ref = format!("@{}", rules_bad_path.to_str().expect("ops utf8"));
-
-    let transform_err = assert_error_code(
-        &[
-            "transform-batch",
-            file,
-            "--ops",
-            transform_ref.as_str(),
-            "--dry-run",
-        ],
-        "INVALID_OPS_PAYLOAD",
-    );
-    let transform_message = transform_err["message"].as_str().unwrap_or_default();
-    assert!(transform_message.contains("expected top-level shape:"));
-    assert!(transform_message.contains("minimal valid example:"));
-    assert!(transform_message.contains("\"kind\":\"fill_range\""));
-
-    let column_err = assert_error_code(
-        &[
-            "column-size-batch",
-            file,
-            "--ops",
-            column_ref.as_str(),
-            "--dry-run",
-        ],
-        "INVALID_OPS_PAYLOAD",
-    );
-    let column_message = column_err["message"].as_str().unwrap_or_default();
-    assert!(column_message.contains("expected top-level shape:"));
-    assert!(column_message.contains("minimal valid example:"));
-    assert!(column_message.contains("\"sheet_name\":\"Sheet1\""));
-
-    let rules_err = assert_error_code(
-        &[
-            "rules-batch",
-            file,
-            "--ops",
-            rules_ref.as_str(),
-            "--dry-run",
-        ],
-        "INVALID_OPS_PAYLOAD",
-    );
-    let rules_message = rules_err["message"].as_str().unwrap_or_default();
-    assert!(rules_message.contains("expected top-level shape:"));
-    assert!(rules_message.contains("minimal valid example:"));
-    assert!(rules_message.contains("\"kind\":\"set_data_validation\""));
-}
-
-#[test]
-fn phase_b_safety_mode_matrix_for_structure_column_layout_commands() {
-    let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("phase-b-safety.xlsx");
-    let structure_ops_path = tmp.path().join("structure-ops.json");
-    let column_ops_path = tmp.path().join("column-ops.json");
-    let layout_ops_path = tmp.path().join("layout-ops.json");
-    write_fixture(&workbook_path);
-    write_ops_payload(
-        &structure_ops_path,
-        r#"{"ops":[{"kind":"rename_sheet","old_name":"Summary","new_name":"Dashboard"}]}"#,
-    );
-    write_ops_payload(
-        &column_ops_path,
-        r#"{"sheet_name":"Sheet1","ops":[{"range":"A:A","size":{"kind":"width","width_chars":20.0}}]}"#,
-    );
-    write_ops_payload(
-        &layout_ops_path,
-        r#"{"ops":[{"kind":"freeze_panes","sheet_name":"Sheet1","freeze_rows":1,"freeze_cols":1}]}"#,
-    );
-
-    let file = workbook_path.to_str().expect("path utf8");
-    let structure_ref = format!("@{}", structure_ops_path.to_str().expect("ops utf8"));
-    let column_ref = format!("@{}", column_ops_path.to_str().expect("ops utf8"));
-    let layout_ref = format!("@{}", layout_ops_path.to_str().expect("ops utf8"));
-
-    assert_batch_mode_matrix("structure-batch", file, structure_ref.as_str());
-    assert_batch_mode_matrix("column-size-batch", file, column_ref.as_str());
-    assert_batch_mode_matrix("sheet-layout-batch", file, layout_ref.as_str());
+    let after = fs::read(&source_path).expect("read source after write failure");
+    assert_eq!(before, after, "source workbook changed after write failure");
 }
 
 #[cfg(unix)]
 #[test]
-fn phase_b_structure_batch_maps_write_failures_and_preserves_source() {
+fn phase_a_apply_formula_pattern_maps_write_failures_and_preserves_source() {
     let tmp = tempdir().expect("tempdir");
-    let source_path = tmp.path().join("phase-b-structure-write-fail-source.xlsx");
-    let blocked_dir = tmp.path().join("blocked");
+    let source_path = tmp.path().join("phase-a-formula-write-fail-source.xlsx");
+    let blocked_dir = tmp.path().join("blocked-formula");
     let blocked_output = blocked_dir.join("output.xlsx");
-    let ops_path = tmp.path().join("ops.json");
+    let ops_path = tmp.path().join("formula-ops.json");
     write_fixture(&source_path);
     write_ops_payload(
         &ops_path,
-        r#"{"ops":[{"kind":"rename_sheet","old_name":"Summary","new_name":"Dashboard"}]}"#,
+        r#"{"ops":[{"sheet_name":"Sheet1","target_range":"C2:C4","anchor_cell":"C2","base_formula":"B2*3","fill_direction":"down"}]}"#,
     );
     fs::create_dir(&blocked_dir).expect("create blocked dir");
 
@@ -5877,7 +7317,7 @@ fn phase_b_structure_batch_maps_write_failures_and_preserves_source() {
 
     assert_error_code(
         &[
-            "structure-batch",
+            "apply-formula-pattern",
             source,
             "--ops",
             ops_ref.as_str(),
@@ -5899,162 +7339,111 @@ fn phase_b_structure_batch_maps_write_failures_and_preserves_source() {
     assert_eq!(before, after, "source workbook changed after write failure");
 }
 
-#[cfg(unix)]
 #[test]
-fn phase_b_column_size_batch_maps_write_failures_and_preserves_source() {
-    let tmp = tempdir().expect("tempdir");
-    let source_path = tmp.path().join("phase-b-column-write-fail-source.xlsx");
-    let blocked_dir = tmp.path().join("blocked-column");
-    let blocked_output = blocked_dir.join("output.xlsx");
-    let ops_path = tmp.path().join("ops-column.json");
-    write_fixture(&source_path);
-    write_ops_payload(
-        &ops_path,
-        r#"{"sheet_name":"Sheet1","ops":[{"range":"A:A","size":{"kind":"width","width_chars":20.0}}]}"#,
+fn phase_b_help_examples_for_structure_column_and_layout_commands() {
+    let structure_help = run_cli(&["structure-batch", "--help"]);
+    assert!(
+        structure_help.status.success(),
+        "stderr: {:?}",
+        structure_help.stderr
     );
-    fs::create_dir(&blocked_dir).expect("create blocked dir");
-
-    let mut perms = fs::metadata(&blocked_dir)
-        .expect("blocked metadata")
-        .permissions();
-    perms.set_mode(0o555);
-    fs::set_permissions(&blocked_dir, perms.clone()).expect("set blocked perms");
-
-    let before = fs::read(&source_path).expect("read source before write failure");
-    let source = source_path.to_str().expect("source utf8");
-    let output = blocked_output.to_str().expect("output utf8");
-    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
-
-    assert_error_code(
-        &[
-            "column-size-batch",
-            source,
-            "--ops",
-            ops_ref.as_str(),
-            "--output",
-            output,
-        ],
-        "WRITE_FAILED",
+    let structure = parse_stdout_text(&structure_help);
+    assert!(structure.contains("Examples:"));
+    assert!(
+        structure.contains(
+            "asp write batch structure workbook.xlsx --ops @structure_ops.json --dry-run"
+        )
     );
+    assert!(structure.contains(
+        "asp write batch structure workbook.xlsx --ops @structure_ops.json --output structured.xlsx"
+    ));
+    assert!(structure.contains("Payload examples (`--ops @structure_ops.json`):"));
+    assert!(structure.contains("\"kind\":\"rename_sheet\""));
+    assert!(structure.contains("\"kind\":\"copy_range\""));
+
+    let column_help = run_cli(&["column-size-batch", "--help"]);
     assert!(
-        !blocked_output.exists(),
-        "write failure left a partial output artifact"
+        column_help.status.success(),
+        "stderr: {:?}",
+        column_help.stderr
     );
+    let column = parse_stdout_text(&column_help);
+    assert!(column.contains("Examples:"));
+    assert!(column.contains(
+        "asp write batch column-size workbook.xlsx --ops @column_size_ops.json --in-place"
+    ));
+    assert!(column.contains(
+        "asp write batch column-size workbook.xlsx --ops @column_size_ops.json --output columns.xlsx"
+    ));
+    assert!(column.contains("Payload examples (`--ops @column_size_ops.json`):"));
+    assert!(column.contains("\"sheet_name\":\"Sheet1\""));
+    assert!(column.contains("\"kind\":\"width\""));
+    assert!(column.contains("Also accepted: top-level `ops` where each op includes `sheet_name`."));
 
-    let mut restore = perms;
-    restore.set_mode(0o755);
-    fs::set_permissions(&blocked_dir, restore).expect("restore blocked perms");
-
-    let after = fs::read(&source_path).expect("read source after write failure");
-    assert_eq!(before, after, "source workbook changed after write failure");
-}
-
-#[cfg(unix)]
-#[test]
-fn phase_b_sheet_layout_batch_maps_write_failures_and_preserves_source() {
-    let tmp = tempdir().expect("tempdir");
-    let source_path = tmp.path().join("phase-b-layout-write-fail-source.xlsx");
-    let blocked_dir = tmp.path().join("blocked-layout");
-    let blocked_output = blocked_dir.join("output.xlsx");
-    let ops_path = tmp.path().join("ops-layout.json");
-    write_fixture(&source_path);
-    write_ops_payload(
-        &ops_path,
-        r#"{"ops":[{"kind":"freeze_panes","sheet_name":"Sheet1","freeze_rows":1,"freeze_cols":1}]}"#,
-    );
-    fs::create_dir(&blocked_dir).expect("create blocked dir");
-
-    let mut perms = fs::metadata(&blocked_dir)
-        .expect("blocked metadata")
-        .permissions();
-    perms.set_mode(0o555);
-    fs::set_permissions(&blocked_dir, perms.clone()).expect("set blocked perms");
-
-    let before = fs::read(&source_path).expect("read source before write failure");
-    let source = source_path.to_str().expect("source utf8");
-    let output = blocked_output.to_str().expect("output utf8");
-    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
-
-    assert_error_code(
-        &[
-            "sheet-layout-batch",
-            source,
-            "--ops",
-            ops_ref.as_str(),
-            "--output",
-            output,
-        ],
-        "WRITE_FAILED",
+    let layout_help = run_cli(&["sheet-layout-batch", "--help"]);
+    assert!(
+        layout_help.status.success(),
+        "stderr: {:?}",
+        layout_help.stderr
     );
+    let layout = parse_stdout_text(&layout_help);
+    assert!(layout.contains("Examples:"));
     assert!(
-        !blocked_output.exists(),
-        "write failure left a partial output artifact"
+        layout.contains(
+            "asp write batch sheet-layout workbook.xlsx --ops @layout_ops.json --dry-run"
+        )
     );
-
-    let mut restore = perms;
-    restore.set_mode(0o755);
-    fs::set_permissions(&blocked_dir, restore).expect("restore blocked perms");
-
-    let after = fs::read(&source_path).expect("read source after write failure");
-    assert_eq!(before, after, "source workbook changed after write failure");
-}
-
-#[test]
-fn phase_c_help_examples_for_rules_command() {
-    let rules_help = run_cli(&["rules-batch", "--help"]);
     assert!(
-        rules_help.status.success(),
-        "stderr: {:?}",
-        rules_help.stderr
+        layout.contains(
+            "asp write batch sheet-layout workbook.xlsx --ops @layout_ops.json --in-place"
+        )
     );
-    let rules = parse_stdout_text(&rules_help);
-    assert!(rules.contains("Examples:"));
-    assert!(rules.contains("asp write batch rules workbook.xlsx --ops @rules_ops.json --dry-run"));
-    assert!(rules.contains(
-        "asp write batch rules workbook.xlsx --ops @rules_ops.json --output ruled.xlsx --force"
-    ));
-    assert!(rules.contains("Payload examples (`--ops @rules_ops.json`):"));
-    assert!(rules.contains("\"kind\":\"set_data_validation\""));
-    assert!(rules.contains("\"kind\":\"set_conditional_format\""));
+    assert!(layout.contains("Payload examples (`--ops @layout_ops.json`):"));
+    assert!(layout.contains("\"kind\":\"freeze_panes\""));
+    assert!(layout.contains("\"kind\":\"set_page_setup\""));
 }
 
 #[test]
-fn phase_c_rules_batch_positive_in_place_sets_validation() {
+fn phase_b_structure_batch_positive_in_place_renames_sheet() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("phase-c-rules-in-place.xlsx");
-    let ops_path = tmp.path().join("rules-ops.json");
+    let workbook_path = tmp.path().join("phase-b-structure-in-place.xlsx");
+    let ops_path = tmp.path().join("structure-ops.json");
     write_fixture(&workbook_path);
     write_ops_payload(
         &ops_path,
-        r#"{"ops":[{"kind":"set_data_validation","sheet_name":"Sheet1","target_range":"B2:B4","validation":{"kind":"list","formula1":"\"A,B,C\""}}]}"#,
+        r#"{"ops":[{"kind":"rename_sheet","old_name":"Summary","new_name":"Dashboard"}]}"#,
     );
 
     let file = workbook_path.to_str().expect("path utf8");
     let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
 
-    let output = run_cli(&["rules-batch", file, "--ops", ops_ref.as_str(), "--in-place"]);
+    let output = run_cli(&[
+        "structure-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
+    ]);
     assert!(output.status.success(), "stderr: {:?}", output.stderr);
     let payload = parse_stdout_json(&output);
+    assert_eq!(payload["op_count"].as_u64(), Some(1));
     assert!(payload["changed"].as_bool().unwrap_or(false));
 
     let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read workbook");
-    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet exists");
-    let dvs = sheet.get_data_validations().expect("data validations");
-    let list = dvs.get_data_validation_list();
-    assert_eq!(list.len(), 1);
-    assert_eq!(list[0].get_sequence_of_references().get_sqref(), "B2:B4");
+    assert!(book.get_sheet_by_name("Dashboard").is_some());
+    assert!(book.get_sheet_by_name("Summary").is_none());
 }
 
 #[test]
-fn phase_c_rules_batch_positive_dry_run_and_output_target_only() {
+fn phase_b_structure_batch_positive_dry_run_and_output_target_only() {
     let tmp = tempdir().expect("tempdir");
-    let source_path = tmp.path().join("phase-c-rules-source.xlsx");
-    let output_path = tmp.path().join("phase-c-rules-output.xlsx");
-    let ops_path = tmp.path().join("rules-ops-output.json");
+    let source_path = tmp.path().join("phase-b-structure-source.xlsx");
+    let output_path = tmp.path().join("phase-b-structure-output.xlsx");
+    let ops_path = tmp.path().join("structure-ops-output.json");
     write_fixture(&source_path);
     write_ops_payload(
         &ops_path,
-        r#"{"ops":[{"kind":"set_data_validation","sheet_name":"Sheet1","target_range":"C2:C4","validation":{"kind":"list","formula1":"\"X,Y,Z\""}}]}"#,
+        r#"{"ops":[{"kind":"rename_sheet","old_name":"Summary","new_name":"Dashboard"}]}"#,
     );
 
     let source = source_path.to_str().expect("source utf8");
@@ -6064,7 +7453,7 @@ fn phase_c_rules_batch_positive_dry_run_and_output_target_only() {
     let before = fs::read(&source_path).expect("read source before dry-run");
 
     let dry_run = run_cli(&[
-        "rules-batch",
+        "structure-batch",
         source,
         "--ops",
         ops_ref.as_str(),
@@ -6078,7 +7467,7 @@ fn phase_c_rules_batch_positive_dry_run_and_output_target_only() {
     assert_eq!(before, source_after_dry, "dry-run mutated source workbook");
 
     let output_run = run_cli(&[
-        "rules-batch",
+        "structure-batch",
         source,
         "--ops",
         ops_ref.as_str(),
@@ -6090,40 +7479,33 @@ fn phase_c_rules_batch_positive_dry_run_and_output_target_only() {
         "stderr: {:?}",
         output_run.stderr
     );
+    let payload = parse_stdout_json(&output_run);
+    assert!(payload["changed"].as_bool().unwrap_or(false));
 
-    let source_after_output = fs::read(&source_path).expect("read source after output mode");
-    assert_eq!(
-        before, source_after_output,
-        "source changed during --output mode"
-    );
+    let source_book = umya_spreadsheet::reader::xlsx::read(&source_path).expect("read source");
+    assert!(source_book.get_sheet_by_name("Summary").is_some());
+    assert!(source_book.get_sheet_by_name("Dashboard").is_none());
 
     let output_book = umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output");
-    let output_sheet = output_book
-        .get_sheet_by_name("Sheet1")
-        .expect("sheet exists");
-    let dvs = output_sheet
-        .get_data_validations()
-        .expect("data validations");
-    let list = dvs.get_data_validation_list();
-    assert_eq!(list.len(), 1);
-    assert_eq!(list[0].get_sequence_of_references().get_sqref(), "C2:C4");
+    assert!(output_book.get_sheet_by_name("Dashboard").is_some());
+    assert!(output_book.get_sheet_by_name("Summary").is_none());
 }
 
 #[test]
-fn phase_c_rules_batch_output_force_overwrite_semantics() {
+fn phase_b_structure_batch_output_force_overwrite_semantics() {
     let tmp = tempdir().expect("tempdir");
-    let source_path = tmp.path().join("phase-c-rules-force-source.xlsx");
-    let output_path = tmp.path().join("phase-c-rules-force-output.xlsx");
-    let ops_first_path = tmp.path().join("rules-ops-first.json");
-    let ops_second_path = tmp.path().join("rules-ops-second.json");
+    let source_path = tmp.path().join("phase-b-structure-force-source.xlsx");
+    let output_path = tmp.path().join("phase-b-structure-force-output.xlsx");
+    let ops_first_path = tmp.path().join("structure-ops-first.json");
+    let ops_second_path = tmp.path().join("structure-ops-second.json");
     write_fixture(&source_path);
     write_ops_payload(
         &ops_first_path,
-        r#"{"ops":[{"kind":"set_data_validation","sheet_name":"Sheet1","target_range":"B2:B4","validation":{"kind":"list","formula1":"\"A,B,C\""}}]}"#,
+        r#"{"ops":[{"kind":"rename_sheet","old_name":"Summary","new_name":"Dashboard"}]}"#,
     );
     write_ops_payload(
         &ops_second_path,
-        r#"{"ops":[{"kind":"set_data_validation","sheet_name":"Sheet1","target_range":"C2:C4","validation":{"kind":"list","formula1":"\"X,Y,Z\""}}]}"#,
+        r#"{"ops":[{"kind":"rename_sheet","old_name":"Summary","new_name":"Board"}]}"#,
     );
 
     let source = source_path.to_str().expect("source utf8");
@@ -6132,7 +7514,7 @@ fn phase_c_rules_batch_output_force_overwrite_semantics() {
     let second_ref = format!("@{}", ops_second_path.to_str().expect("ops utf8"));
 
     let first = run_cli(&[
-        "rules-batch",
+        "structure-batch",
         source,
         "--ops",
         first_ref.as_str(),
@@ -6143,7 +7525,7 @@ fn phase_c_rules_batch_output_force_overwrite_semantics() {
 
     assert_error_code(
         &[
-            "rules-batch",
+            "structure-batch",
             source,
             "--ops",
             second_ref.as_str(),
@@ -6154,7 +7536,7 @@ fn phase_c_rules_batch_output_force_overwrite_semantics() {
     );
 
     let forced = run_cli(&[
-        "rules-batch",
+        "structure-batch",
         source,
         "--ops",
         second_ref.as_str(),
@@ -6165,4015 +7547,9406 @@ fn phase_c_rules_batch_output_force_overwrite_semantics() {
     assert!(forced.status.success(), "stderr: {:?}", forced.stderr);
 
     let output_book = umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output");
-    let output_sheet = output_book
-        .get_sheet_by_name("Sheet1")
-        .expect("sheet exists");
-    let dvs = output_sheet
-        .get_data_validations()
-        .expect("data validations");
-    let list = dvs.get_data_validation_list();
-    assert_eq!(list.len(), 1);
-    assert_eq!(list[0].get_sequence_of_references().get_sqref(), "C2:C4");
+    assert!(output_book.get_sheet_by_name("Board").is_some());
+    assert!(output_book.get_sheet_by_name("Summary").is_none());
 }
 
 #[test]
-fn phase_c_negative_invalid_ops_payload() {
+fn phase_b_column_size_batch_positive_output_mutates_target_only() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("phase-c-invalid-ops.xlsx");
-    let bad_ops_path = tmp.path().join("rules-bad.json");
-    write_fixture(&workbook_path);
-    write_ops_payload(&bad_ops_path, r#"{"ops":[{"kind":"unknown_rule"}]}"#);
-
-    let file = workbook_path.to_str().expect("path utf8");
-    let ops_ref = format!("@{}", bad_ops_path.to_str().expect("ops utf8"));
-    assert_error_code(
-        &["rules-batch", file, "--ops", ops_ref.as_str(), "--dry-run"],
-        "INVALID_OPS_PAYLOAD",
+    let source_path = tmp.path().join("phase-b-column-source.xlsx");
+    let output_path = tmp.path().join("phase-b-column-output.xlsx");
+    let ops_path = tmp.path().join("column-ops.json");
+    write_fixture(&source_path);
+    write_ops_payload(
+        &ops_path,
+        r#"{"sheet_name":"Sheet1","ops":[{"range":"A:A","size":{"kind":"width","width_chars":25.0}}]}"#,
     );
+
+    let source = source_path.to_str().expect("source utf8");
+    let output = output_path.to_str().expect("output utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
+
+    let before = fs::read(&source_path).expect("read source before dry-run");
+
+    let dry_run = run_cli(&[
+        "column-size-batch",
+        source,
+        "--ops",
+        ops_ref.as_str(),
+        "--dry-run",
+    ]);
+    assert!(dry_run.status.success(), "stderr: {:?}", dry_run.stderr);
+    let dry_payload = parse_stdout_json(&dry_run);
+    assert!(dry_payload["would_change"].as_bool().unwrap_or(false));
+
+    let source_after_dry = fs::read(&source_path).expect("read source after dry-run");
+    assert_eq!(before, source_after_dry, "dry-run mutated source workbook");
+
+    let run = run_cli(&[
+        "column-size-batch",
+        source,
+        "--ops",
+        ops_ref.as_str(),
+        "--output",
+        output,
+    ]);
+    assert!(run.status.success(), "stderr: {:?}", run.stderr);
+    let payload = parse_stdout_json(&run);
+    assert!(payload["changed"].as_bool().unwrap_or(false));
+
+    let source_after = fs::read(&source_path).expect("read source after output mode");
+    assert_eq!(before, source_after, "source changed during --output mode");
+
+    let output_book =
+        umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output workbook");
+    let output_sheet = output_book
+        .get_sheet_by_name("Sheet1")
+        .expect("sheet exists");
+    let width = *output_sheet
+        .get_column_dimension("A")
+        .expect("A column")
+        .get_width();
+    assert!((width - 25.0).abs() < 0.001);
 }
 
 #[test]
-fn phase_c_safety_mode_matrix_for_rules_command() {
+fn phase_b_column_size_batch_accepts_per_op_sheet_name_shape() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("phase-c-safety.xlsx");
-    let ops_path = tmp.path().join("rules-ops.json");
-    write_fixture(&workbook_path);
+    let source_path = tmp.path().join("phase-b-column-per-op-source.xlsx");
+    let output_path = tmp.path().join("phase-b-column-per-op-output.xlsx");
+    let ops_path = tmp.path().join("column-ops-per-op-sheet.json");
+    write_fixture(&source_path);
     write_ops_payload(
         &ops_path,
-        r#"{"ops":[{"kind":"set_data_validation","sheet_name":"Sheet1","target_range":"B2:B4","validation":{"kind":"list","formula1":"\"A,B,C\""}}]}"#,
+        r#"{"ops":[{"sheet_name":"Sheet1","range":"A:A","size":{"kind":"width","width_chars":21.0}}]}"#,
     );
 
-    let file = workbook_path.to_str().expect("path utf8");
+    let source = source_path.to_str().expect("source utf8");
+    let output = output_path.to_str().expect("output utf8");
     let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
-    assert_batch_mode_matrix("rules-batch", file, ops_ref.as_str());
-}
 
-#[cfg(unix)]
+    let run = run_cli(&[
+        "column-size-batch",
+        source,
+        "--ops",
+        ops_ref.as_str(),
+        "--output",
+        output,
+    ]);
+    assert!(run.status.success(), "stderr: {:?}", run.stderr);
+
+    let output_book =
+        umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output workbook");
+    let output_sheet = output_book
+        .get_sheet_by_name("Sheet1")
+        .expect("sheet exists");
+    let width = *output_sheet
+        .get_column_dimension("A")
+        .expect("A column")
+        .get_width();
+    assert!((width - 21.0).abs() < 0.001);
+}
+
 #[test]
-fn phase_c_rules_batch_maps_write_failures_and_preserves_source() {
+fn row_size_batch_sets_heights_and_rejects_mixed_sheet_names() {
     let tmp = tempdir().expect("tempdir");
-    let source_path = tmp.path().join("phase-c-rules-write-fail-source.xlsx");
-    let blocked_dir = tmp.path().join("blocked");
-    let blocked_output = blocked_dir.join("output.xlsx");
-    let ops_path = tmp.path().join("ops.json");
+    let source_path = tmp.path().join("row-size-source.xlsx");
+    let output_path = tmp.path().join("row-size-output.xlsx");
+    let ops_path = tmp.path().join("row-ops.json");
     write_fixture(&source_path);
     write_ops_payload(
         &ops_path,
-        r#"{"ops":[{"kind":"set_data_validation","sheet_name":"Sheet1","target_range":"B2:B4","validation":{"kind":"list","formula1":"\"A,B,C\""}}]}"#,
+        r#"{"ops":[{"sheet_name":"Sheet1","range":"2:3","size":{"kind":"height","height_points":28.5}},{"sheet_name":"Sheet1","target":{"kind":"rows","range":"1"},"size":{"kind":"auto","min_height_points":20.0}}]}"#,
     );
-    fs::create_dir(&blocked_dir).expect("create blocked dir");
-
-    let mut perms = fs::metadata(&blocked_dir)
-        .expect("blocked metadata")
-        .permissions();
-    perms.set_mode(0o555);
-    fs::set_permissions(&blocked_dir, perms.clone()).expect("set blocked perms");
 
-    let before = fs::read(&source_path).expect("read source before write failure");
     let source = source_path.to_str().expect("source utf8");
-    let output = blocked_output.to_str().expect("output utf8");
-    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
+    let output = output_path.to_str().expect("output utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
 
-    assert_error_code(
-        &[
-            "rules-batch",
-            source,
-            "--ops",
-            ops_ref.as_str(),
-            "--output",
-            output,
-        ],
-        "WRITE_FAILED",
-    );
+    let dry_run = run_cli(&[
+        "write",
+        "batch",
+        "row-size",
+        source,
+        "--ops",
+        ops_ref.as_str(),
+        "--dry-run",
+    ]);
+    assert!(dry_run.status.success(), "stderr: {:?}", dry_run.stderr);
+    let dry_payload = parse_stdout_json(&dry_run);
+    assert!(dry_payload["would_change"].as_bool().unwrap_or(false));
+    assert_eq!(dry_payload["summary"]["operation_counts"]["height"], 1);
+    assert_eq!(dry_payload["summary"]["operation_counts"]["auto"], 1);
+
+    let run = run_cli(&[
+        "row-size-batch",
+        source,
+        "--ops",
+        ops_ref.as_str(),
+        "--output",
+        output,
+    ]);
+    assert!(run.status.success(), "stderr: {:?}", run.stderr);
     assert!(
-        !blocked_output.exists(),
-        "write failure left a partial output artifact"
+        parse_stdout_json(&run)["changed"]
+            .as_bool()
+            .unwrap_or(false)
     );
 
-    let mut restore = perms;
-    restore.set_mode(0o755);
-    fs::set_permissions(&blocked_dir, restore).expect("restore blocked perms");
+    let output_book =
+        umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output workbook");
+    let output_sheet = output_book
+        .get_sheet_by_name("Sheet1")
+        .expect("sheet exists");
+    let height = |row: u32| {
+        *output_sheet
+            .get_row_dimension(&row)
+            .expect("row dimension")
+            .get_height()
+    };
+    assert!((height(2) - 28.5).abs() < 0.001);
+    assert!((height(3) - 28.5).abs() < 0.001);
+    assert!((height(1) - 20.0).abs() < 0.001);
 
-    let after = fs::read(&source_path).expect("read source after write failure");
-    assert_eq!(before, after, "source workbook changed after write failure");
+    write_ops_payload(
+        &ops_path,
+        r#"{"sheet_name":"Sheet1","ops":[{"sheet_name":"Summary","range":"1","size":{"kind":"height","height_points":20.0}}]}"#,
+    );
+    assert_invalid_argument(&[
+        "row-size-batch",
+        source,
+        "--ops",
+        ops_ref.as_str(),
+        "--dry-run",
+    ]);
 }
 
 #[test]
-fn cli_create_workbook_bootstraps_read_write_flow() {
+fn phase_b_column_size_batch_rejects_mixed_per_op_sheet_names() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("bootstrap.xlsx");
-    let file = workbook_path.to_str().expect("path utf8");
-
-    let create = run_cli(&["create-workbook", file, "--sheets", "Inputs,Calc,Output"]);
-    assert!(create.status.success(), "stderr: {:?}", create.stderr);
-    let payload = parse_stdout_json(&create);
-    assert_eq!(payload["path"], Value::String(file.to_string()));
-    assert_eq!(payload["overwritten"], Value::Bool(false));
-
-    let list = run_cli(&["list-sheets", file]);
-    assert!(list.status.success(), "stderr: {:?}", list.stderr);
-    let list_payload = parse_stdout_json(&list);
-    let sheet_names: Vec<_> = list_payload["sheets"]
-        .as_array()
-        .expect("sheets array")
-        .iter()
-        .filter_map(|entry| entry["name"].as_str().map(str::to_string))
-        .collect();
-    assert_eq!(sheet_names, vec!["Inputs", "Calc", "Output"]);
+    let source_path = tmp.path().join("phase-b-column-mixed-per-op-source.xlsx");
+    let ops_path = tmp.path().join("column-ops-mixed-sheets.json");
+    write_fixture(&source_path);
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"sheet_name":"Sheet1","range":"A:A","size":{"kind":"width","width_chars":21.0}},{"sheet_name":"Summary","range":"A:A","size":{"kind":"width","width_chars":10.0}}]}"#,
+    );
 
-    let edit = run_cli(&["edit", file, "Inputs", "A1=42"]);
-    assert!(edit.status.success(), "stderr: {:?}", edit.stderr);
+    let source = source_path.to_str().expect("source utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
 
-    #[cfg(feature = "recalc")]
-    {
-        let recalc = run_cli(&["recalculate", file]);
-        assert!(recalc.status.success(), "stderr: {:?}", recalc.stderr);
-    }
+    let err = assert_error_code(
+        &[
+            "column-size-batch",
+            source,
+            "--ops",
+            ops_ref.as_str(),
+            "--dry-run",
+        ],
+        "INVALID_OPS_PAYLOAD",
+    );
+    assert!(
+        err["message"]
+            .as_str()
+            .unwrap_or_default()
+            .contains("mixed sheet_name values")
+    );
 }
 
 #[test]
-fn cli_create_workbook_rejects_existing_file_without_overwrite() {
+fn phase_b_column_size_batch_rejects_hybrid_mixed_sheet_names() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("existing.xlsx");
-    write_fixture(&workbook_path);
-    let file = workbook_path.to_str().expect("path utf8");
-
-    let output = run_cli(&["create-workbook", file]);
-    assert!(!output.status.success(), "expected non-zero status");
-    let error = parse_stderr_json(&output);
-    assert_eq!(error["code"], Value::String("COMMAND_FAILED".to_string()));
-    let message = error["message"].as_str().unwrap_or_default();
-    assert!(message.contains("already exists"));
-    assert!(message.contains("--overwrite"));
-}
+    let source_path = tmp.path().join("phase-b-column-hybrid-mixed-source.xlsx");
+    let ops_path = tmp.path().join("column-ops-hybrid-mixed-sheets.json");
+    write_fixture(&source_path);
+    write_ops_payload(
+        &ops_path,
+        r#"{"sheet_name":"Settings","ops":[{"sheet_name":"Summary","range":"A:A","size":{"kind":"width","width_chars":21.0}}]}"#,
+    );
 
-#[test]
-fn cli_edit_invalid_shorthand_error_suggests_formula_double_equals() {
-    let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("edit-invalid-shorthand.xlsx");
-    write_fixture(&workbook_path);
-    let file = workbook_path.to_str().expect("path utf8");
+    let source = source_path.to_str().expect("source utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
 
-    let output = run_cli(&["edit", file, "Sheet1", "A1"]);
-    assert!(!output.status.success(), "expected non-zero status");
-    let error = parse_stderr_json(&output);
-    assert_eq!(
-        error["code"],
-        Value::String("INVALID_EDIT_SYNTAX".to_string())
+    let err = assert_error_code(
+        &[
+            "column-size-batch",
+            source,
+            "--ops",
+            ops_ref.as_str(),
+            "--dry-run",
+        ],
+        "INVALID_OPS_PAYLOAD",
     );
-
-    let message = error["message"].as_str().unwrap_or_default();
-    assert!(message.contains("invalid shorthand edit"));
     assert!(
-        message.contains("double equals") || message.contains("==SUM"),
-        "error should include corrective formula shorthand hint: {message}"
+        err["message"]
+            .as_str()
+            .unwrap_or_default()
+            .contains("mixed sheet_name values")
     );
 }
 
 #[test]
-fn cli_copy_edit_diff_are_stateless_and_persisted() {
+fn phase_b_column_size_batch_output_force_overwrite_semantics() {
     let tmp = tempdir().expect("tempdir");
-    let original = tmp.path().join("original.xlsx");
-    let modified = tmp.path().join("modified.xlsx");
-    write_fixture(&original);
+    let source_path = tmp.path().join("phase-b-column-force-source.xlsx");
+    let output_path = tmp.path().join("phase-b-column-force-output.xlsx");
+    let ops_first_path = tmp.path().join("column-ops-first.json");
+    let ops_second_path = tmp.path().join("column-ops-second.json");
+    write_fixture(&source_path);
+    write_ops_payload(
+        &ops_first_path,
+        r#"{"sheet_name":"Sheet1","ops":[{"range":"A:A","size":{"kind":"width","width_chars":25.0}}]}"#,
+    );
+    write_ops_payload(
+        &ops_second_path,
+        r#"{"sheet_name":"Sheet1","ops":[{"range":"A:A","size":{"kind":"width","width_chars":18.0}}]}"#,
+    );
 
-    let copy = run_cli(&[
-        "copy",
-        original.to_str().expect("path utf8"),
-        modified.to_str().expect("path utf8"),
-    ]);
-    assert!(copy.status.success(), "stderr: {:?}", copy.stderr);
-    let copy_payload = parse_stdout_json(&copy);
-    assert!(copy_payload["bytes_copied"].as_u64().unwrap_or(0) > 0);
+    let source = source_path.to_str().expect("source utf8");
+    let output = output_path.to_str().expect("output utf8");
+    let first_ref = format!("@{}", ops_first_path.to_str().expect("ops utf8"));
+    let second_ref = format!("@{}", ops_second_path.to_str().expect("ops utf8"));
 
-    let edit = run_cli(&[
-        "edit",
-        modified.to_str().expect("path utf8"),
-        "Sheet1",
-        "B2=11",
-        "C2==B2*3",
+    let first = run_cli(&[
+        "column-size-batch",
+        source,
+        "--ops",
+        first_ref.as_str(),
+        "--output",
+        output,
     ]);
-    assert!(edit.status.success(), "stderr: {:?}", edit.stderr);
-    let edit_payload = parse_stdout_json(&edit);
-    assert_eq!(edit_payload["edits_applied"], 2);
-    assert_eq!(edit_payload["recalc_needed"], true);
-
-    let book = umya_spreadsheet::reader::xlsx::read(&modified).expect("read modified");
-    let sheet = book
-        .get_sheet_by_name("Sheet1")
-        .expect("modified sheet exists");
-    assert_eq!(sheet.get_cell("B2").expect("B2 exists").get_value(), "11");
-    assert_eq!(
-        sheet.get_cell("C2").expect("C2 exists").get_formula(),
-        "B2*3"
-    );
+    assert!(first.status.success(), "stderr: {:?}", first.stderr);
 
-    let diff = run_cli(&[
-        "diff",
-        original.to_str().expect("path utf8"),
-        modified.to_str().expect("path utf8"),
+    assert_error_code(
+        &[
+            "column-size-batch",
+            source,
+            "--ops",
+            second_ref.as_str(),
+            "--output",
+            output,
+        ],
+        "OUTPUT_EXISTS",
+    );
+
+    let without_force_book =
+        umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output without force");
+    let without_force_sheet = without_force_book
+        .get_sheet_by_name("Sheet1")
+        .expect("sheet exists");
+    let without_force_width = *without_force_sheet
+        .get_column_dimension("A")
+        .expect("A column")
+        .get_width();
+    assert!((without_force_width - 25.0).abs() < 0.001);
+
+    let forced = run_cli(&[
+        "column-size-batch",
+        source,
+        "--ops",
+        second_ref.as_str(),
+        "--output",
+        output,
+        "--force",
     ]);
-    assert!(diff.status.success(), "stderr: {:?}", diff.stderr);
-    let diff_payload = parse_stdout_json(&diff);
-    assert!(diff_payload["change_count"].as_u64().unwrap_or(0) >= 2);
+    assert!(forced.status.success(), "stderr: {:?}", forced.stderr);
+
+    let forced_book = umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output");
+    let forced_sheet = forced_book
+        .get_sheet_by_name("Sheet1")
+        .expect("sheet exists");
+    let forced_width = *forced_sheet
+        .get_column_dimension("A")
+        .expect("A column")
+        .get_width();
+    assert!((forced_width - 18.0).abs() < 0.001);
 }
 
 #[test]
-fn cli_diff_defaults_to_summary_only() {
+fn cli_sheet_overview_reports_layout_block() {
     let tmp = tempdir().expect("tempdir");
-    let original = tmp.path().join("diff-summary-original.xlsx");
-    let modified = tmp.path().join("diff-summary-modified.xlsx");
-    write_fixture(&original);
-    fs::copy(&original, &modified).expect("copy workbook");
+    let workbook_path = tmp.path().join("overview-layout.xlsx");
+    let ops_path = tmp.path().join("layout-ops.json");
+    write_fixture(&workbook_path);
 
-    let edit = run_cli(&[
-        "edit",
-        modified.to_str().expect("path utf8"),
-        "Sheet1",
-        "B2=11",
-        "C2==B2*3",
-    ]);
-    assert!(edit.status.success(), "stderr: {:?}", edit.stderr);
+    let mut workbook =
+        umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read fixture workbook");
+    {
+        let sheet = workbook
+            .get_sheet_by_name_mut("Sheet1")
+            .expect("sheet exists");
+        sheet.get_row_dimension_mut(&3).set_hidden(true);
+        sheet.get_row_dimension_mut(&4).set_hidden(true);
+        sheet.get_column_dimension_mut("C").set_hidden(true);
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
 
-    let diff = run_cli(&[
-        "diff",
-        original.to_str().expect("path utf8"),
-        modified.to_str().expect("path utf8"),
-    ]);
-    assert!(diff.status.success(), "stderr: {:?}", diff.stderr);
+    let file = workbook_path.to_str().expect("path utf8");
+    let plain = run_cli(&["read", "overview", file, "Summary"]);
+    assert!(plain.status.success(), "stderr: {:?}", plain.stderr);
+    let plain_payload = parse_stdout_json(&plain);
+    assert!(plain_payload["layout"]["pane"].is_null());
+    assert_eq!(plain_payload["layout"]["hidden_row_count"], 0);
 
-    let payload = parse_stdout_json(&diff);
-    assert!(payload.get("summary").is_some());
-    assert!(payload.get("changes").is_none());
-    assert_eq!(payload["summary"]["returned_changes"].as_u64(), Some(0));
-    assert_eq!(
-        payload["summary"]["total_changes"].as_u64(),
-        payload["change_count"].as_u64()
-    );
-    assert!(
-        payload["summary"]["counts_by_kind"]["cell"]
-            .as_u64()
-            .unwrap_or(0)
-            >= 1
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"freeze_panes","sheet_name":"Sheet1","freeze_rows":1,"freeze_cols":1},{"kind":"set_zoom","sheet_name":"Sheet1","zoom_percent":85}]}"#,
     );
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
+    let applied = run_cli(&[
+        "sheet-layout-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
+    ]);
+    assert!(applied.status.success(), "stderr: {:?}", applied.stderr);
+
+    let overview = run_cli(&["read", "overview", file, "Sheet1"]);
+    assert!(overview.status.success(), "stderr: {:?}", overview.stderr);
+    let layout = &parse_stdout_json(&overview)["layout"];
+    assert_eq!(layout["pane"]["state"], "frozen");
+    assert_eq!(layout["pane"]["frozen_rows"], 1);
+    assert_eq!(layout["pane"]["frozen_cols"], 1);
+    assert_eq!(layout["pane"]["top_left_cell"], "B2");
+    assert_eq!(layout["zoom_percent"], 85);
+    assert_eq!(layout["hidden_row_count"], 2);
+    assert_eq!(layout["hidden_rows"], serde_json::json!(["3:4"]));
+    assert_eq!(layout["hidden_column_count"], 1);
+    assert_eq!(layout["hidden_columns"], serde_json::json!(["C"]));
+    assert_eq!(layout["hidden_spans_truncated"], false);
 }
 
 #[test]
-fn cli_diff_details_supports_pagination() {
+fn cli_sheet_layout_batch_hides_and_groups_rows_for_sheet_page() {
     let tmp = tempdir().expect("tempdir");
-    let original = tmp.path().join("diff-page-original.xlsx");
-    let modified = tmp.path().join("diff-page-modified.xlsx");
-    write_fixture(&original);
-    fs::copy(&original, &modified).expect("copy workbook");
+    let workbook_path = tmp.path().join("layout-outline.xlsx");
+    let ops_path = tmp.path().join("layout-ops.json");
+    write_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
 
-    let edit = run_cli(&[
-        "edit",
-        modified.to_str().expect("path utf8"),
-        "Sheet1",
-        "B2=11",
-        "C2==B2*3",
-        "B3=25",
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"group_rows","sheet_name":"Sheet1","rows":"2:3","collapsed":true},{"kind":"group_rows","sheet_name":"Sheet1","rows":"3"},{"kind":"group_columns","sheet_name":"Sheet1","columns":"B:C"},{"kind":"set_columns_hidden","sheet_name":"Sheet1","columns":"C","hidden":true}]}"#,
+    );
+    let applied = run_cli(&[
+        "sheet-layout-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
     ]);
-    assert!(edit.status.success(), "stderr: {:?}", edit.stderr);
+    assert!(applied.status.success(), "stderr: {:?}", applied.stderr);
+    assert_eq!(parse_stdout_json(&applied)["changed"], true);
 
-    let first = run_cli(&[
-        "diff",
-        original.to_str().expect("path utf8"),
-        modified.to_str().expect("path utf8"),
-        "--details",
-        "--limit",
+    let page = run_cli(&[
+        "sheet-page",
+        file,
+        "Sheet1",
+        "--start-row",
         "1",
+        "--page-size",
+        "4",
+        "--format",
+        "full",
     ]);
-    assert!(first.status.success(), "stderr: {:?}", first.stderr);
-    let first_payload = parse_stdout_json(&first);
-    assert_eq!(
-        first_payload["summary"]["returned_changes"].as_u64(),
-        Some(1)
+    assert!(page.status.success(), "stderr: {:?}", page.stderr);
+    let rows = parse_stdout_json(&page)["rows"].clone();
+    assert!(rows[0].get("hidden").is_none());
+    assert!(rows[0].get("outline_level").is_none());
+    assert_eq!(rows[1]["hidden"], true);
+    assert_eq!(rows[1]["outline_level"], 1);
+    assert_eq!(rows[2]["hidden"], true);
+    assert_eq!(rows[2]["outline_level"], 2);
+    assert!(rows[3].get("hidden").is_none());
+
+    let overview = run_cli(&["read", "overview", file, "Sheet1"]);
+    assert!(overview.status.success(), "stderr: {:?}", overview.stderr);
+    let layout = &parse_stdout_json(&overview)["layout"];
+    assert_eq!(layout["hidden_rows"], serde_json::json!(["2:3"]));
+    assert_eq!(layout["hidden_columns"], serde_json::json!(["C"]));
+
+    // Unhiding and ungrouping leaves the remaining inner level in place, and the
+    // levels survive an unrelated layout write.
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"set_rows_hidden","sheet_name":"Sheet1","rows":"2:3","hidden":false},{"kind":"ungroup_rows","sheet_name":"Sheet1","rows":"2:3"},{"kind":"set_zoom","sheet_name":"Sheet1","zoom_percent":90}]}"#,
     );
-    let first_changes = first_payload["changes"].as_array().expect("changes page");
-    assert_eq!(first_changes.len(), 1);
-    let next_offset = first_payload["summary"]["next_offset"]
-        .as_u64()
-        .expect("next offset");
+    let applied = run_cli(&[
+        "sheet-layout-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
+    ]);
+    assert!(applied.status.success(), "stderr: {:?}", applied.stderr);
 
-    let second = run_cli(&[
-        "diff",
-        original.to_str().expect("path utf8"),
-        modified.to_str().expect("path utf8"),
-        "--details",
-        "--limit",
-        "1",
-        "--offset",
-        &next_offset.to_string(),
+    let page = run_cli(&[
+        "sheet-page",
+        file,
+        "Sheet1",
+        "--start-row",
+        "2",
+        "--page-size",
+        "2",
+        "--format",
+        "compact",
     ]);
-    assert!(second.status.success(), "stderr: {:?}", second.stderr);
-    let second_payload = parse_stdout_json(&second);
-    let second_changes = second_payload["changes"].as_array().expect("changes page");
-    assert_eq!(second_changes.len(), 1);
-    assert_eq!(
-        second_payload["change_count"], first_payload["change_count"],
-        "paged requests must report stable total change_count"
+    assert!(page.status.success(), "stderr: {:?}", page.stderr);
+    let compact = parse_stdout_json(&page)["compact"].clone();
+    assert!(compact.get("hidden_rows").is_none());
+    assert_eq!(compact["row_outline_levels"], serde_json::json!({"3": 1}));
+
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"group_rows","sheet_name":"Sheet1","rows":"B:C"}]}"#,
+    );
+    let invalid = run_cli(&[
+        "sheet-layout-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--dry-run",
+    ]);
+    assert!(!invalid.status.success());
+    let err = parse_stderr_json(&invalid);
+    assert!(
+        err["message"]
+            .as_str()
+            .unwrap_or_default()
+            .contains("invalid row span")
     );
 }
 
 #[test]
-fn cli_diff_sheet_and_range_filters_scope_results() {
+fn cli_outline_levels_survive_structure_and_cell_writes() {
     let tmp = tempdir().expect("tempdir");
-    let original = tmp.path().join("diff-filter-original.xlsx");
-    let modified = tmp.path().join("diff-filter-modified.xlsx");
-    write_fixture(&original);
-    fs::copy(&original, &modified).expect("copy workbook");
+    let workbook_path = tmp.path().join("outline-writes.xlsx");
+    let ops_path = tmp.path().join("ops.json");
+    write_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
 
-    let edit = run_cli(&[
-        "edit",
-        modified.to_str().expect("path utf8"),
-        "Sheet1",
-        "B2=11",
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"group_rows","sheet_name":"Sheet1","rows":"3"}]}"#,
+    );
+    let grouped = run_cli(&[
+        "sheet-layout-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
     ]);
-    assert!(edit.status.success(), "stderr: {:?}", edit.stderr);
+    assert!(grouped.status.success(), "stderr: {:?}", grouped.stderr);
 
-    let filtered = run_cli(&[
-        "diff",
-        original.to_str().expect("path utf8"),
-        modified.to_str().expect("path utf8"),
-        "--sheet",
-        "Sheet1",
-        "--range",
-        "B2:B2",
-        "--details",
-        "--limit",
-        "50",
+    // The grouped row moves down with the rows inserted above it.
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"insert_rows","sheet_name":"Sheet1","at_row":2,"count":1}]}"#,
+    );
+    let inserted = run_cli(&[
+        "structure-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
     ]);
-    assert!(filtered.status.success(), "stderr: {:?}", filtered.stderr);
-    let payload = parse_stdout_json(&filtered);
-    assert_eq!(payload["change_count"].as_u64(), Some(1));
+    assert!(inserted.status.success(), "stderr: {:?}", inserted.stderr);
 
-    let changes = payload["changes"].as_array().expect("changes");
-    assert_eq!(changes.len(), 1);
-    assert_eq!(changes[0]["address"].as_str(), Some("B2"));
-}
+    let edit = run_cli(&["edit", file, "Sheet1", "A1=42"]);
+    assert!(edit.status.success(), "stderr: {:?}", edit.stderr);
 
-#[test]
-fn cli_diff_summary_includes_group_buckets_and_subtype_counts() {
-    let tmp = tempdir().expect("tempdir");
-    let original = tmp.path().join("diff-group-summary-original.xlsx");
-    let modified = tmp.path().join("diff-group-summary-modified.xlsx");
-    write_fixture(&original);
-    fs::copy(&original, &modified).expect("copy workbook");
-
-    let edit = run_cli(&[
-        "edit",
-        modified.to_str().expect("path utf8"),
+    let page = run_cli(&[
+        "sheet-page",
+        file,
         "Sheet1",
-        "B2=11",
-        "B3=25",
-        "C2==B2*3",
+        "--start-row",
+        "1",
+        "--page-size",
+        "5",
+        "--format",
+        "compact",
     ]);
-    assert!(edit.status.success(), "stderr: {:?}", edit.stderr);
+    assert!(page.status.success(), "stderr: {:?}", page.stderr);
+    let compact = parse_stdout_json(&page)["compact"].clone();
+    assert_eq!(compact["row_outline_levels"], serde_json::json!({"4": 1}));
+}
 
-    let diff = run_cli(&[
-        "diff",
-        original.to_str().expect("path utf8"),
-        modified.to_str().expect("path utf8"),
-    ]);
-    assert!(diff.status.success(), "stderr: {:?}", diff.stderr);
+#[test]
+fn cli_sheet_layout_batch_print_setup_surfaces_in_overview() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("layout-print.xlsx");
+    let ops_path = tmp.path().join("layout-ops.json");
+    write_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
 
-    let payload = parse_stdout_json(&diff);
-    assert_eq!(payload["summary"]["counts_by_subtype"]["value_edit"], 2);
-    assert_eq!(payload["summary"]["counts_by_subtype"]["formula_edit"], 1);
-    assert_eq!(payload["summary"]["recalc_result_change_count"], 0);
-    assert_eq!(
-        payload["summary"]["direct_change_count"],
-        payload["change_count"]
+    let overview = run_cli(&["read", "overview", file, "Sheet1"]);
+    assert!(overview.status.success(), "stderr: {:?}", overview.stderr);
+    let print = parse_stdout_json(&overview)["layout"]["print"].clone();
+    assert!(print.get("print_area").is_none());
+    assert_eq!(print["orientation"], "portrait");
+    assert!(print.get("header").is_none());
+
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"set_print_area","sheet_name":"Sheet1","range":"A1:C4"},{"kind":"set_page_margins","sheet_name":"Sheet1","left":0.5,"right":0.5,"top":0.75,"bottom":0.75,"header":0.3,"footer":0.3},{"kind":"set_orientation","sheet_name":"Sheet1","orientation":"landscape"},{"kind":"set_header_footer","sheet_name":"Sheet1","header":"&CQuarterly Report","footer":"&RPage &P of &N"}]}"#,
     );
-    assert_eq!(payload["summary"]["group_count"], 2);
+    let applied = run_cli(&[
+        "sheet-layout-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
+    ]);
+    assert!(applied.status.success(), "stderr: {:?}", applied.stderr);
+    assert_eq!(parse_stdout_json(&applied)["changed"], true);
 
-    let sheet_summaries = payload["summary"]["sheet_summaries"]
-        .as_array()
-        .expect("sheet summaries");
-    assert_eq!(sheet_summaries.len(), 1);
-    assert_eq!(sheet_summaries[0]["sheet"], "Sheet1");
-    assert_eq!(sheet_summaries[0]["total_changes"], 3);
-    assert_eq!(sheet_summaries[0]["direct_change_count"], 3);
-    assert_eq!(sheet_summaries[0]["recalc_result_change_count"], 0);
-    assert_eq!(sheet_summaries[0]["counts_by_group_type"]["value_edit"], 1);
-    assert_eq!(
-        sheet_summaries[0]["counts_by_group_type"]["formula_edit"],
-        1
+    let overview = run_cli(&["read", "overview", file, "Sheet1"]);
+    assert!(overview.status.success(), "stderr: {:?}", overview.stderr);
+    let print = parse_stdout_json(&overview)["layout"]["print"].clone();
+    assert_eq!(print["print_area"], "A1:C4");
+    assert_eq!(print["orientation"], "landscape");
+    assert_eq!(print["margins"]["left"], 0.5);
+    assert_eq!(print["margins"]["top"], 0.75);
+    assert_eq!(print["header"], "&CQuarterly Report");
+    assert_eq!(print["footer"], "&RPage &P of &N");
+
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"set_header_footer","sheet_name":"Sheet1","header":""}]}"#,
     );
+    let cleared = run_cli(&[
+        "sheet-layout-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
+    ]);
+    assert!(cleared.status.success(), "stderr: {:?}", cleared.stderr);
+    let overview = run_cli(&["read", "overview", file, "Sheet1"]);
+    let print = parse_stdout_json(&overview)["layout"]["print"].clone();
+    assert!(print.get("header").is_none());
+    assert_eq!(print["footer"], "&RPage &P of &N");
 
-    let preview = payload["summary"]["group_preview"]
-        .as_array()
-        .expect("group preview");
-    assert_eq!(preview.len(), 2);
-    let value_group = preview
-        .iter()
-        .find(|group| group["group_type"] == "value_edit")
-        .expect("value_edit group");
-    assert_eq!(value_group["range"], "B2:B3");
-    assert_eq!(value_group["change_count"], 2);
-    assert_eq!(value_group["review_priority"], "direct");
-    let formula_group = preview
-        .iter()
-        .find(|group| group["group_type"] == "formula_edit")
-        .expect("formula_edit group");
-    assert_eq!(formula_group["range"], "C2");
-    assert_eq!(formula_group["review_priority"], "direct");
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"set_header_footer","sheet_name":"Sheet1"}]}"#,
+    );
+    let invalid = run_cli(&[
+        "sheet-layout-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--dry-run",
+    ]);
+    assert!(!invalid.status.success());
 }
 
 #[test]
-fn cli_diff_can_exclude_recalc_result_noise() {
+fn phase_b_sheet_layout_batch_positive_dry_run_and_in_place() {
     let tmp = tempdir().expect("tempdir");
-    let original = tmp.path().join("diff-exclude-recalc-original.xlsx");
-    let modified = tmp.path().join("diff-exclude-recalc-modified.xlsx");
-    write_fixture(&original);
-    fs::copy(&original, &modified).expect("copy workbook");
-
-    let edit = run_cli(&[
-        "edit",
-        modified.to_str().expect("path utf8"),
-        "Sheet1",
-        "B2=11",
-    ]);
-    assert!(edit.status.success(), "stderr: {:?}", edit.stderr);
+    let workbook_path = tmp.path().join("phase-b-layout.xlsx");
+    let ops_path = tmp.path().join("layout-ops.json");
+    write_fixture(&workbook_path);
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"freeze_panes","sheet_name":"Sheet1","freeze_rows":1,"freeze_cols":1}]}"#,
+    );
 
-    let recalc = run_cli(&["recalculate", modified.to_str().expect("path utf8")]);
-    assert!(recalc.status.success(), "stderr: {:?}", recalc.stderr);
+    let file = workbook_path.to_str().expect("path utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
 
-    let full = run_cli(&[
-        "diff",
-        original.to_str().expect("path utf8"),
-        modified.to_str().expect("path utf8"),
-        "--details",
-        "--limit",
-        "50",
+    let before = fs::read(&workbook_path).expect("read before dry-run");
+    let dry_run = run_cli(&[
+        "sheet-layout-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--dry-run",
     ]);
-    assert!(full.status.success(), "stderr: {:?}", full.stderr);
-    let full_payload = parse_stdout_json(&full);
-    assert_eq!(
-        full_payload["summary"]["counts_by_subtype"]["value_edit"],
-        1
-    );
-    let recalc_count = full_payload["summary"]["counts_by_subtype"]["recalc_result"]
-        .as_u64()
-        .unwrap_or(0);
-    assert!(
-        recalc_count >= 1,
-        "expected recalc churn, got {full_payload}"
-    );
-    assert_eq!(
-        full_payload["summary"]["recalc_result_change_count"],
-        recalc_count
-    );
-    assert_eq!(full_payload["summary"]["direct_change_count"], 1);
-    assert_eq!(
-        full_payload["change_count"].as_u64().unwrap_or(0),
-        recalc_count + 1
-    );
-    let full_sheet_summaries = full_payload["summary"]["sheet_summaries"]
-        .as_array()
-        .expect("sheet summaries");
-    assert_eq!(full_sheet_summaries.len(), 1);
-    assert_eq!(full_sheet_summaries[0]["sheet"], "Sheet1");
-    assert_eq!(full_sheet_summaries[0]["direct_change_count"], 1);
-    assert_eq!(
-        full_sheet_summaries[0]["recalc_result_change_count"],
-        recalc_count
-    );
+    assert!(dry_run.status.success(), "stderr: {:?}", dry_run.stderr);
+    let dry_payload = parse_stdout_json(&dry_run);
+    assert!(dry_payload["would_change"].as_bool().unwrap_or(false));
+    let after_dry = fs::read(&workbook_path).expect("read after dry-run");
+    assert_eq!(before, after_dry, "dry-run mutated workbook");
 
-    let filtered = run_cli(&[
-        "diff",
-        original.to_str().expect("path utf8"),
-        modified.to_str().expect("path utf8"),
-        "--details",
-        "--limit",
-        "50",
-        "--exclude-recalc-result",
+    let in_place = run_cli(&[
+        "sheet-layout-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
     ]);
-    assert!(filtered.status.success(), "stderr: {:?}", filtered.stderr);
-    let filtered_payload = parse_stdout_json(&filtered);
-    assert_eq!(filtered_payload["change_count"], 1);
-    assert_eq!(filtered_payload["summary"]["recalc_result_change_count"], 0);
-    assert_eq!(filtered_payload["summary"]["direct_change_count"], 1);
+    assert!(in_place.status.success(), "stderr: {:?}", in_place.stderr);
+
+    let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read workbook");
+    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet exists");
+    let views = sheet.get_sheets_views().get_sheet_view_list();
+    let view = views.first().expect("sheet view");
+    let pane = view.get_pane().expect("pane");
+    assert_eq!(*pane.get_horizontal_split(), 1.0);
+    assert_eq!(*pane.get_vertical_split(), 1.0);
+    assert_eq!(pane.get_top_left_cell().to_string(), "B2");
     assert_eq!(
-        filtered_payload["summary"]["filters"]["exclude_recalc_result"],
-        true
-    );
-    assert!(
-        filtered_payload["summary"]["counts_by_subtype"]
-            .get("recalc_result")
-            .is_none()
+        view.get_top_left_cell(),
+        "",
+        "sheetView topLeftCell should remain unset for LO compatibility"
     );
-    let changes = filtered_payload["changes"].as_array().expect("changes");
-    assert_eq!(changes.len(), 1);
-    assert_eq!(changes[0]["address"], "B2");
 
-    let full_groups = full_payload["groups"].as_array().expect("groups");
-    assert_eq!(full_groups[0]["review_priority"], "direct");
-    assert_ne!(full_groups[0]["group_type"], "recalc_result");
+    let selection = view.get_selection().first().expect("selection");
+    assert_eq!(selection.get_sequence_of_references().get_sqref(), "B2");
     assert_eq!(
-        full_groups.last().expect("at least one group")["review_priority"],
-        "derived"
+        selection.get_active_cell().map(|coord| coord.to_string()),
+        Some("B2".to_string())
     );
 }
 
 #[test]
-fn cli_append_region_dry_run_reports_footer_aware_plan() {
+fn phase_b_sheet_layout_batch_clears_preexisting_sheet_view_top_left_cell() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("append-region-dry-run.xlsx");
-    let rows_path = tmp.path().join("rows.json");
+    let workbook_path = tmp.path().join("phase-b-layout-preexisting-top-left.xlsx");
+    let ops_path = tmp.path().join("layout-preexisting-top-left-ops.json");
+    write_fixture(&workbook_path);
 
-    let mut workbook = umya_spreadsheet::new_file();
     {
-        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet1");
-        sheet.get_cell_mut("A1").set_value("Name");
-        sheet.get_cell_mut("B1").set_value("Amount");
-        sheet.get_cell_mut("A2").set_value("Alice");
-        sheet.get_cell_mut("B2").set_value_number(10.0);
-        sheet.get_cell_mut("A3").set_value("Bob");
-        sheet.get_cell_mut("B3").set_value_number(20.0);
-        sheet.get_cell_mut("A4").set_value("Total");
-        let total = sheet.get_cell_mut("B4");
-        total.set_formula("SUM(B2:B3)");
-        total.get_cell_value_mut().set_formula_result_default("30");
+        let mut book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read workbook");
+        let sheet = book.get_sheet_by_name_mut("Sheet1").expect("sheet");
+        let view = sheet
+            .get_sheet_views_mut()
+            .get_sheet_view_list_mut()
+            .first_mut()
+            .expect("sheet view");
+        view.set_top_left_cell("C3");
+        umya_spreadsheet::writer::xlsx::write(&book, &workbook_path).expect("write workbook");
     }
-    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"freeze_panes","sheet_name":"Sheet1","freeze_rows":1,"freeze_cols":1}]}"#,
+    );
+
+    let file = workbook_path.to_str().expect("path utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
+
+    let in_place = run_cli(&[
+        "sheet-layout-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
+    ]);
+    assert!(in_place.status.success(), "stderr: {:?}", in_place.stderr);
+
+    let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read workbook");
+    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet exists");
+    let view = sheet
+        .get_sheets_views()
+        .get_sheet_view_list()
+        .first()
+        .expect("sheet view");
+    assert_eq!(
+        view.get_top_left_cell(),
+        "",
+        "preexisting sheetView topLeftCell should be cleared for LO compatibility"
+    );
+    let pane = view.get_pane().expect("pane");
+    assert_eq!(pane.get_top_left_cell().to_string(), "B2");
+}
+
+#[test]
+fn phase_b_sheet_layout_batch_positive_output_mutates_target_only() {
+    let tmp = tempdir().expect("tempdir");
+    let source_path = tmp.path().join("phase-b-layout-source.xlsx");
+    let output_path = tmp.path().join("phase-b-layout-output.xlsx");
+    let ops_path = tmp.path().join("layout-output-ops.json");
+    write_fixture(&source_path);
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"freeze_panes","sheet_name":"Sheet1","freeze_rows":1,"freeze_cols":1}]}"#,
+    );
+
+    let source = source_path.to_str().expect("source utf8");
+    let output = output_path.to_str().expect("output utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
+
+    let before = fs::read(&source_path).expect("read source before output mode");
+
+    let run = run_cli(&[
+        "sheet-layout-batch",
+        source,
+        "--ops",
+        ops_ref.as_str(),
+        "--output",
+        output,
+    ]);
+    assert!(run.status.success(), "stderr: {:?}", run.stderr);
+    let payload = parse_stdout_json(&run);
+    assert!(payload["changed"].as_bool().unwrap_or(false));
+
+    let source_after = fs::read(&source_path).expect("read source after output mode");
+    assert_eq!(before, source_after, "source changed during --output mode");
+
+    let output_book = umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output");
+    let output_sheet = output_book
+        .get_sheet_by_name("Sheet1")
+        .expect("sheet exists");
+    let view = output_sheet
+        .get_sheets_views()
+        .get_sheet_view_list()
+        .first()
+        .expect("sheet view");
+    let pane = view.get_pane().expect("pane");
+    assert_eq!(pane.get_top_left_cell().to_string(), "B2");
+    assert_eq!(view.get_top_left_cell(), "");
+    let selection = view.get_selection().first().expect("selection");
+    assert_eq!(selection.get_sequence_of_references().get_sqref(), "B2");
+}
+
+#[test]
+fn phase_b_sheet_layout_batch_output_force_overwrite_semantics() {
+    let tmp = tempdir().expect("tempdir");
+    let source_path = tmp.path().join("phase-b-layout-force-source.xlsx");
+    let output_path = tmp.path().join("phase-b-layout-force-output.xlsx");
+    let ops_first_path = tmp.path().join("layout-ops-first.json");
+    let ops_second_path = tmp.path().join("layout-ops-second.json");
+    write_fixture(&source_path);
+    write_ops_payload(
+        &ops_first_path,
+        r#"{"ops":[{"kind":"freeze_panes","sheet_name":"Sheet1","freeze_rows":1,"freeze_cols":1}]}"#,
+    );
+    write_ops_payload(
+        &ops_second_path,
+        r#"{"ops":[{"kind":"freeze_panes","sheet_name":"Sheet1","freeze_rows":2,"freeze_cols":0}]}"#,
+    );
+
+    let source = source_path.to_str().expect("source utf8");
+    let output = output_path.to_str().expect("output utf8");
+    let first_ref = format!("@{}", ops_first_path.to_str().expect("ops utf8"));
+    let second_ref = format!("@{}", ops_second_path.to_str().expect("ops utf8"));
+
+    let first = run_cli(&[
+        "sheet-layout-batch",
+        source,
+        "--ops",
+        first_ref.as_str(),
+        "--output",
+        output,
+    ]);
+    assert!(first.status.success(), "stderr: {:?}", first.stderr);
+
+    assert_error_code(
+        &[
+            "sheet-layout-batch",
+            source,
+            "--ops",
+            second_ref.as_str(),
+            "--output",
+            output,
+        ],
+        "OUTPUT_EXISTS",
+    );
+
+    let without_force_book =
+        umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output without force");
+    let without_force_sheet = without_force_book
+        .get_sheet_by_name("Sheet1")
+        .expect("sheet exists");
+    let without_force_pane = without_force_sheet
+        .get_sheets_views()
+        .get_sheet_view_list()
+        .first()
+        .and_then(|view| view.get_pane())
+        .expect("pane without force");
+    assert_eq!(without_force_pane.get_top_left_cell().to_string(), "B2");
+
+    let forced = run_cli(&[
+        "sheet-layout-batch",
+        source,
+        "--ops",
+        second_ref.as_str(),
+        "--output",
+        output,
+        "--force",
+    ]);
+    assert!(forced.status.success(), "stderr: {:?}", forced.stderr);
+
+    let forced_book = umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output");
+    let forced_sheet = forced_book
+        .get_sheet_by_name("Sheet1")
+        .expect("sheet exists");
+    let forced_pane = forced_sheet
+        .get_sheets_views()
+        .get_sheet_view_list()
+        .first()
+        .and_then(|view| view.get_pane())
+        .expect("forced pane");
+    assert_eq!(forced_pane.get_top_left_cell().to_string(), "A3");
+}
+
+#[test]
+fn phase_b_negative_invalid_ops_payloads() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("phase-b-invalid-ops.xlsx");
+    let structure_bad_path = tmp.path().join("structure-bad.json");
+    let column_bad_path = tmp.path().join("column-bad.json");
+    let layout_bad_path = tmp.path().join("layout-bad.json");
+    write_fixture(&workbook_path);
+    write_ops_payload(&structure_bad_path, r#"{"ops":[{"kind":"unknown_kind"}]}"#);
+    write_ops_payload(
+        &column_bad_path,
+        r#"{"ops":[{"range":"A:A","size":{"kind":"width","width_chars":12.0}}]}"#,
+    );
+    write_ops_payload(
+        &layout_bad_path,
+        r#"{"ops":[{"kind":"set_zoom","sheet_name":"Sheet1","zoom_percent":5}]}"#,
+    );
+
+    let file = workbook_path.to_str().expect("path utf8");
+    let structure_ref = format!("@{}", structure_bad_path.to_str().expect("ops utf8"));
+    let column_ref = format!("@{}", column_bad_path.to_str().expect("ops utf8"));
+    let layout_ref = format!("@{}", layout_bad_path.to_str().expect("ops utf8"));
+
+    assert_error_code(
+        &[
+            "structure-batch",
+            file,
+            "--ops",
+            structure_ref.as_str(),
+            "--dry-run",
+        ],
+        "INVALID_OPS_PAYLOAD",
+    );
+    assert_error_code(
+        &[
+            "column-size-batch",
+            file,
+            "--ops",
+            column_ref.as_str(),
+            "--dry-run",
+        ],
+        "INVALID_OPS_PAYLOAD",
+    );
+    assert_error_code(
+        &[
+            "sheet-layout-batch",
+            file,
+            "--ops",
+            layout_ref.as_str(),
+            "--dry-run",
+        ],
+        "INVALID_OPS_PAYLOAD",
+    );
+}
+
+#[test]
+fn invalid_ops_payload_errors_include_shape_and_minimal_example() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("invalid-ops-message.xlsx");
+    let transform_bad_path = tmp.path().join("transform-bad.json");
+    let column_bad_path = tmp.path().join("column-bad-shape.json");
+    let rules_bad_path = tmp.path().join("rules-bad.json");
+    write_fixture(&workbook_path);
+
+    // Not an object at all.
+    write_ops_payload(&transform_bad_path, r#"[]"#);
+    // Missing required top-level field `sheet_name`.
+    write_ops_payload(&column_bad_path, r#"{"ops":[]}"#);
+    // Missing required fields inside the op.
+    write_ops_payload(
+        &rules_bad_path,
+        r#"{"ops":[{"kind":"set_data_validation"}]}"#,
+    );
+
+    let file = workbook_path.to_str().expect("path utf8");
+    let transform_ref = format!("@{}", transform_bad_path.to_str().expect("ops utf8"));
+    let column_ref = format!("@{}", column_bad_path.to_str().expect("ops utf8"));
+    let rules_ref = format!("@{}", rules_bad_path.to_str().expect("ops utf8"));
+
+    let transform_err = assert_error_code(
+        &[
+            "transform-batch",
+            file,
+            "--ops",
+            transform_ref.as_str(),
+            "--dry-run",
+        ],
+        "INVALID_OPS_PAYLOAD",
+    );
+    let transform_message = transform_err["message"].as_str().unwrap_or_default();
+    assert!(transform_message.contains("expected top-level shape:"));
+    assert!(transform_message.contains("minimal valid example:"));
+    assert!(transform_message.contains("\"kind\":\"fill_range\""));
+
+    let column_err = assert_error_code(
+        &[
+            "column-size-batch",
+            file,
+            "--ops",
+            column_ref.as_str(),
+            "--dry-run",
+        ],
+        "INVALID_OPS_PAYLOAD",
+    );
+    let column_message = column_err["message"].as_str().unwrap_or_default();
+    assert!(column_message.contains("expected top-level shape:"));
+    assert!(column_message.contains("minimal valid example:"));
+    assert!(column_message.contains("\"sheet_name\":\"Sheet1\""));
+
+    let rules_err = assert_error_code(
+        &[
+            "rules-batch",
+            file,
+            "--ops",
+            rules_ref.as_str(),
+            "--dry-run",
+        ],
+        "INVALID_OPS_PAYLOAD",
+    );
+    let rules_message = rules_err["message"].as_str().unwrap_or_default();
+    assert!(rules_message.contains("expected top-level shape:"));
+    assert!(rules_message.contains("minimal valid example:"));
+    assert!(rules_message.contains("\"kind\":\"set_data_validation\""));
+}
+
+#[test]
+fn phase_b_safety_mode_matrix_for_structure_column_layout_commands() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("phase-b-safety.xlsx");
+    let structure_ops_path = tmp.path().join("structure-ops.json");
+    let column_ops_path = tmp.path().join("column-ops.json");
+    let layout_ops_path = tmp.path().join("layout-ops.json");
+    write_fixture(&workbook_path);
+    write_ops_payload(
+        &structure_ops_path,
+        r#"{"ops":[{"kind":"rename_sheet","old_name":"Summary","new_name":"Dashboard"}]}"#,
+    );
+    write_ops_payload(
+        &column_ops_path,
+        r#"{"sheet_name":"Sheet1","ops":[{"range":"A:A","size":{"kind":"width","width_chars":20.0}}]}"#,
+    );
+    write_ops_payload(
+        &layout_ops_path,
+        r#"{"ops":[{"kind":"freeze_panes","sheet_name":"Sheet1","freeze_rows":1,"freeze_cols":1}]}"#,
+    );
+
+    let file = workbook_path.to_str().expect("path utf8");
+    let structure_ref = format!("@{}", structure_ops_path.to_str().expect("ops utf8"));
+    let column_ref = format!("@{}", column_ops_path.to_str().expect("ops utf8"));
+    let layout_ref = format!("@{}", layout_ops_path.to_str().expect("ops utf8"));
+
+    assert_batch_mode_matrix("structure-batch", file, structure_ref.as_str());
+    assert_batch_mode_matrix("column-size-batch", file, column_ref.as_str());
+    assert_batch_mode_matrix("sheet-layout-batch", file, layout_ref.as_str());
+}
+
+#[cfg(unix)]
+#[test]
+fn phase_b_structure_batch_maps_write_failures_and_preserves_source() {
+    let tmp = tempdir().expect("tempdir");
+    let source_path = tmp.path().join("phase-b-structure-write-fail-source.xlsx");
+    let blocked_dir = tmp.path().join("blocked");
+    let blocked_output = blocked_dir.join("output.xlsx");
+    let ops_path = tmp.path().join("ops.json");
+    write_fixture(&source_path);
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"rename_sheet","old_name":"Summary","new_name":"Dashboard"}]}"#,
+    );
+    fs::create_dir(&blocked_dir).expect("create blocked dir");
+
+    let mut perms = fs::metadata(&blocked_dir)
+        .expect("blocked metadata")
+        .permissions();
+    perms.set_mode(0o555);
+    fs::set_permissions(&blocked_dir, perms.clone()).expect("set blocked perms");
+
+    let before = fs::read(&source_path).expect("read source before write failure");
+    let source = source_path.to_str().expect("source utf8");
+    let output = blocked_output.to_str().expect("output utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
+
+    assert_error_code(
+        &[
+            "structure-batch",
+            source,
+            "--ops",
+            ops_ref.as_str(),
+            "--output",
+            output,
+        ],
+        "WRITE_FAILED",
+    );
+    assert!(
+        !blocked_output.exists(),
+        "write failure left a partial output artifact"
+    );
+
+    let mut restore = perms;
+    restore.set_mode(0o755);
+    fs::set_permissions(&blocked_dir, restore).expect("restore blocked perms");
+
+    let after = fs::read(&source_path).expect("read source after write failure");
+    assert_eq!(before, after, "source workbook changed after write failure");
+}
+
+#[cfg(unix)]
+#[test]
+fn phase_b_column_size_batch_maps_write_failures_and_preserves_source() {
+    let tmp = tempdir().expect("tempdir");
+    let source_path = tmp.path().join("phase-b-column-write-fail-source.xlsx");
+    let blocked_dir = tmp.path().join("blocked-column");
+    let blocked_output = blocked_dir.join("output.xlsx");
+    let ops_path = tmp.path().join("ops-column.json");
+    write_fixture(&source_path);
+    write_ops_payload(
+        &ops_path,
+        r#"{"sheet_name":"Sheet1","ops":[{"range":"A:A","size":{"kind":"width","width_chars":20.0}}]}"#,
+    );
+    fs::create_dir(&blocked_dir).expect("create blocked dir");
+
+    let mut perms = fs::metadata(&blocked_dir)
+        .expect("blocked metadata")
+        .permissions();
+    perms.set_mode(0o555);
+    fs::set_permissions(&blocked_dir, perms.clone()).expect("set blocked perms");
+
+    let before = fs::read(&source_path).expect("read source before write failure");
+    let source = source_path.to_str().expect("source utf8");
+    let output = blocked_output.to_str().expect("output utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
+
+    assert_error_code(
+        &[
+            "column-size-batch",
+            source,
+            "--ops",
+            ops_ref.as_str(),
+            "--output",
+            output,
+        ],
+        "WRITE_FAILED",
+    );
+    assert!(
+        !blocked_output.exists(),
+        "write failure left a partial output artifact"
+    );
+
+    let mut restore = perms;
+    restore.set_mode(0o755);
+    fs::set_permissions(&blocked_dir, restore).expect("restore blocked perms");
+
+    let after = fs::read(&source_path).expect("read source after write failure");
+    assert_eq!(before, after, "source workbook changed after write failure");
+}
+
+#[cfg(unix)]
+#[test]
+fn phase_b_sheet_layout_batch_maps_write_failures_and_preserves_source() {
+    let tmp = tempdir().expect("tempdir");
+    let source_path = tmp.path().join("phase-b-layout-write-fail-source.xlsx");
+    let blocked_dir = tmp.path().join("blocked-layout");
+    let blocked_output = blocked_dir.join("output.xlsx");
+    let ops_path = tmp.path().join("ops-layout.json");
+    write_fixture(&source_path);
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"freeze_panes","sheet_name":"Sheet1","freeze_rows":1,"freeze_cols":1}]}"#,
+    );
+    fs::create_dir(&blocked_dir).expect("create blocked dir");
+
+    let mut perms = fs::metadata(&blocked_dir)
+        .expect("blocked metadata")
+        .permissions();
+    perms.set_mode(0o555);
+    fs::set_permissions(&blocked_dir, perms.clone()).expect("set blocked perms");
+
+    let before = fs::read(&source_path).expect("read source before write failure");
+    let source = source_path.to_str().expect("source utf8");
+    let output = blocked_output.to_str().expect("output utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
+
+    assert_error_code(
+        &[
+            "sheet-layout-batch",
+            source,
+            "--ops",
+            ops_ref.as_str(),
+            "--output",
+            output,
+        ],
+        "WRITE_FAILED",
+    );
+    assert!(
+        !blocked_output.exists(),
+        "write failure left a partial output artifact"
+    );
+
+    let mut restore = perms;
+    restore.set_mode(0o755);
+    fs::set_permissions(&blocked_dir, restore).expect("restore blocked perms");
+
+    let after = fs::read(&source_path).expect("read source after write failure");
+    assert_eq!(before, after, "source workbook changed after write failure");
+}
+
+#[test]
+fn phase_c_help_examples_for_rules_command() {
+    let rules_help = run_cli(&["rules-batch", "--help"]);
+    assert!(
+        rules_help.status.success(),
+        "stderr: {:?}",
+        rules_help.stderr
+    );
+    let rules = parse_stdout_text(&rules_help);
+    assert!(rules.contains("Examples:"));
+    assert!(rules.contains("asp write batch rules workbook.xlsx --ops @rules_ops.json --dry-run"));
+    assert!(rules.contains(
+        "asp write batch rules workbook.xlsx --ops @rules_ops.json --output ruled.xlsx --force"
+    ));
+    assert!(rules.contains("Payload examples (`--ops @rules_ops.json`):"));
+    assert!(rules.contains("\"kind\":\"set_data_validation\""));
+    assert!(rules.contains("\"kind\":\"set_conditional_format\""));
+}
+
+#[test]
+fn phase_c_rules_batch_positive_in_place_sets_validation() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("phase-c-rules-in-place.xlsx");
+    let ops_path = tmp.path().join("rules-ops.json");
+    write_fixture(&workbook_path);
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"set_data_validation","sheet_name":"Sheet1","target_range":"B2:B4","validation":{"kind":"list","formula1":"\"A,B,C\""}}]}"#,
+    );
+
+    let file = workbook_path.to_str().expect("path utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
+
+    let output = run_cli(&["rules-batch", file, "--ops", ops_ref.as_str(), "--in-place"]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert!(payload["changed"].as_bool().unwrap_or(false));
+
+    let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read workbook");
+    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet exists");
+    let dvs = sheet.get_data_validations().expect("data validations");
+    let list = dvs.get_data_validation_list();
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0].get_sequence_of_references().get_sqref(), "B2:B4");
+}
+
+#[test]
+fn phase_c_rules_batch_positive_dry_run_and_output_target_only() {
+    let tmp = tempdir().expect("tempdir");
+    let source_path = tmp.path().join("phase-c-rules-source.xlsx");
+    let output_path = tmp.path().join("phase-c-rules-output.xlsx");
+    let ops_path = tmp.path().join("rules-ops-output.json");
+    write_fixture(&source_path);
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"set_data_validation","sheet_name":"Sheet1","target_range":"C2:C4","validation":{"kind":"list","formula1":"\"X,Y,Z\""}}]}"#,
+    );
+
+    let source = source_path.to_str().expect("source utf8");
+    let output = output_path.to_str().expect("output utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
+
+    let before = fs::read(&source_path).expect("read source before dry-run");
+
+    let dry_run = run_cli(&[
+        "rules-batch",
+        source,
+        "--ops",
+        ops_ref.as_str(),
+        "--dry-run",
+    ]);
+    assert!(dry_run.status.success(), "stderr: {:?}", dry_run.stderr);
+    let dry_payload = parse_stdout_json(&dry_run);
+    assert!(dry_payload["would_change"].as_bool().unwrap_or(false));
+
+    let source_after_dry = fs::read(&source_path).expect("read source after dry-run");
+    assert_eq!(before, source_after_dry, "dry-run mutated source workbook");
+
+    let output_run = run_cli(&[
+        "rules-batch",
+        source,
+        "--ops",
+        ops_ref.as_str(),
+        "--output",
+        output,
+    ]);
+    assert!(
+        output_run.status.success(),
+        "stderr: {:?}",
+        output_run.stderr
+    );
+
+    let source_after_output = fs::read(&source_path).expect("read source after output mode");
+    assert_eq!(
+        before, source_after_output,
+        "source changed during --output mode"
+    );
+
+    let output_book = umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output");
+    let output_sheet = output_book
+        .get_sheet_by_name("Sheet1")
+        .expect("sheet exists");
+    let dvs = output_sheet
+        .get_data_validations()
+        .expect("data validations");
+    let list = dvs.get_data_validation_list();
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0].get_sequence_of_references().get_sqref(), "C2:C4");
+}
+
+#[test]
+fn phase_c_rules_batch_output_force_overwrite_semantics() {
+    let tmp = tempdir().expect("tempdir");
+    let source_path = tmp.path().join("phase-c-rules-force-source.xlsx");
+    let output_path = tmp.path().join("phase-c-rules-force-output.xlsx");
+    let ops_first_path = tmp.path().join("rules-ops-first.json");
+    let ops_second_path = tmp.path().join("rules-ops-second.json");
+    write_fixture(&source_path);
+    write_ops_payload(
+        &ops_first_path,
+        r#"{"ops":[{"kind":"set_data_validation","sheet_name":"Sheet1","target_range":"B2:B4","validation":{"kind":"list","formula1":"\"A,B,C\""}}]}"#,
+    );
+    write_ops_payload(
+        &ops_second_path,
+        r#"{"ops":[{"kind":"set_data_validation","sheet_name":"Sheet1","target_range":"C2:C4","validation":{"kind":"list","formula1":"\"X,Y,Z\""}}]}"#,
+    );
+
+    let source = source_path.to_str().expect("source utf8");
+    let output = output_path.to_str().expect("output utf8");
+    let first_ref = format!("@{}", ops_first_path.to_str().expect("ops utf8"));
+    let second_ref = format!("@{}", ops_second_path.to_str().expect("ops utf8"));
+
+    let first = run_cli(&[
+        "rules-batch",
+        source,
+        "--ops",
+        first_ref.as_str(),
+        "--output",
+        output,
+    ]);
+    assert!(first.status.success(), "stderr: {:?}", first.stderr);
+
+    assert_error_code(
+        &[
+            "rules-batch",
+            source,
+            "--ops",
+            second_ref.as_str(),
+            "--output",
+            output,
+        ],
+        "OUTPUT_EXISTS",
+    );
+
+    let forced = run_cli(&[
+        "rules-batch",
+        source,
+        "--ops",
+        second_ref.as_str(),
+        "--output",
+        output,
+        "--force",
+    ]);
+    assert!(forced.status.success(), "stderr: {:?}", forced.stderr);
+
+    let output_book = umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output");
+    let output_sheet = output_book
+        .get_sheet_by_name("Sheet1")
+        .expect("sheet exists");
+    let dvs = output_sheet
+        .get_data_validations()
+        .expect("data validations");
+    let list = dvs.get_data_validation_list();
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0].get_sequence_of_references().get_sqref(), "C2:C4");
+}
+
+#[test]
+fn phase_c_negative_invalid_ops_payload() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("phase-c-invalid-ops.xlsx");
+    let bad_ops_path = tmp.path().join("rules-bad.json");
+    write_fixture(&workbook_path);
+    write_ops_payload(&bad_ops_path, r#"{"ops":[{"kind":"unknown_rule"}]}"#);
+
+    let file = workbook_path.to_str().expect("path utf8");
+    let ops_ref = format!("@{}", bad_ops_path.to_str().expect("ops utf8"));
+    assert_error_code(
+        &["rules-batch", file, "--ops", ops_ref.as_str(), "--dry-run"],
+        "INVALID_OPS_PAYLOAD",
+    );
+}
+
+#[test]
+fn phase_c_safety_mode_matrix_for_rules_command() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("phase-c-safety.xlsx");
+    let ops_path = tmp.path().join("rules-ops.json");
+    write_fixture(&workbook_path);
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"set_data_validation","sheet_name":"Sheet1","target_range":"B2:B4","validation":{"kind":"list","formula1":"\"A,B,C\""}}]}"#,
+    );
+
+    let file = workbook_path.to_str().expect("path utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
+    assert_batch_mode_matrix("rules-batch", file, ops_ref.as_str());
+}
+
+#[cfg(unix)]
+#[test]
+fn phase_c_rules_batch_maps_write_failures_and_preserves_source() {
+    let tmp = tempdir().expect("tempdir");
+    let source_path = tmp.path().join("phase-c-rules-write-fail-source.xlsx");
+    let blocked_dir = tmp.path().join("blocked");
+    let blocked_output = blocked_dir.join("output.xlsx");
+    let ops_path = tmp.path().join("ops.json");
+    write_fixture(&source_path);
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"set_data_validation","sheet_name":"Sheet1","target_range":"B2:B4","validation":{"kind":"list","formula1":"\"A,B,C\""}}]}"#,
+    );
+    fs::create_dir(&blocked_dir).expect("create blocked dir");
+
+    let mut perms = fs::metadata(&blocked_dir)
+        .expect("blocked metadata")
+        .permissions();
+    perms.set_mode(0o555);
+    fs::set_permissions(&blocked_dir, perms.clone()).expect("set blocked perms");
+
+    let before = fs::read(&source_path).expect("read source before write failure");
+    let source = source_path.to_str().expect("source utf8");
+    let output = blocked_output.to_str().expect("output utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
+
+    assert_error_code(
+        &[
+            "rules-batch",
+            source,
+            "--ops",
+            ops_ref.as_str(),
+            "--output",
+            output,
+        ],
+        "WRITE_FAILED",
+    );
+    assert!(
+        !blocked_output.exists(),
+        "write failure left a partial output artifact"
+    );
+
+    let mut restore = perms;
+    restore.set_mode(0o755);
+    fs::set_permissions(&blocked_dir, restore).expect("restore blocked perms");
+
+    let after = fs::read(&source_path).expect("read source after write failure");
+    assert_eq!(before, after, "source workbook changed after write failure");
+}
+
+#[test]
+fn cli_create_workbook_bootstraps_read_write_flow() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("bootstrap.xlsx");
+    let file = workbook_path.to_str().expect("path utf8");
+
+    let create = run_cli(&["create-workbook", file, "--sheets", "Inputs,Calc,Output"]);
+    assert!(create.status.success(), "stderr: {:?}", create.stderr);
+    let payload = parse_stdout_json(&create);
+    assert_eq!(payload["path"], Value::String(file.to_string()));
+    assert_eq!(payload["overwritten"], Value::Bool(false));
+
+    let list = run_cli(&["list-sheets", file]);
+    assert!(list.status.success(), "stderr: {:?}", list.stderr);
+    let list_payload = parse_stdout_json(&list);
+    let sheet_names: Vec<_> = list_payload["sheets"]
+        .as_array()
+        .expect("sheets array")
+        .iter()
+        .filter_map(|entry| entry["name"].as_str().map(str::to_string))
+        .collect();
+    assert_eq!(sheet_names, vec!["Inputs", "Calc", "Output"]);
+
+    let edit = run_cli(&["edit", file, "Inputs", "A1=42"]);
+    assert!(edit.status.success(), "stderr: {:?}", edit.stderr);
+
+    #[cfg(feature = "recalc")]
+    {
+        let recalc = run_cli(&["recalculate", file]);
+        assert!(recalc.status.success(), "stderr: {:?}", recalc.stderr);
+    }
+}
+
+#[test]
+fn cli_create_workbook_rejects_existing_file_without_overwrite() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("existing.xlsx");
+    write_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
+
+    let output = run_cli(&["create-workbook", file]);
+    assert!(!output.status.success(), "expected non-zero status");
+    let error = parse_stderr_json(&output);
+    assert_eq!(error["code"], Value::String("COMMAND_FAILED".to_string()));
+    let message = error["message"].as_str().unwrap_or_default();
+    assert!(message.contains("already exists"));
+    assert!(message.contains("--overwrite"));
+}
+
+#[test]
+fn cli_edit_invalid_shorthand_error_suggests_formula_double_equals() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("edit-invalid-shorthand.xlsx");
+    write_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
+
+    let output = run_cli(&["edit", file, "Sheet1", "A1"]);
+    assert!(!output.status.success(), "expected non-zero status");
+    let error = parse_stderr_json(&output);
+    assert_eq!(
+        error["code"],
+        Value::String("INVALID_EDIT_SYNTAX".to_string())
+    );
+
+    let message = error["message"].as_str().unwrap_or_default();
+    assert!(message.contains("invalid shorthand edit"));
+    assert!(
+        message.contains("double equals") || message.contains("==SUM"),
+        "error should include corrective formula shorthand hint: {message}"
+    );
+}
+
+#[test]
+fn cli_copy_edit_diff_are_stateless_and_persisted() {
+    let tmp = tempdir().expect("tempdir");
+    let original = tmp.path().join("original.xlsx");
+    let modified = tmp.path().join("modified.xlsx");
+    write_fixture(&original);
+
+    let copy = run_cli(&[
+        "copy",
+        original.to_str().expect("path utf8"),
+        modified.to_str().expect("path utf8"),
+    ]);
+    assert!(copy.status.success(), "stderr: {:?}", copy.stderr);
+    let copy_payload = parse_stdout_json(&copy);
+    assert!(copy_payload["bytes_copied"].as_u64().unwrap_or(0) > 0);
+
+    let edit = run_cli(&[
+        "edit",
+        modified.to_str().expect("path utf8"),
+        "Sheet1",
+        "B2=11",
+        "C2==B2*3",
+    ]);
+    assert!(edit.status.success(), "stderr: {:?}", edit.stderr);
+    let edit_payload = parse_stdout_json(&edit);
+    assert_eq!(edit_payload["edits_applied"], 2);
+    assert_eq!(edit_payload["recalc_needed"], true);
+
+    let book = umya_spreadsheet::reader::xlsx::read(&modified).expect("read modified");
+    let sheet = book
+        .get_sheet_by_name("Sheet1")
+        .expect("modified sheet exists");
+    assert_eq!(sheet.get_cell("B2").expect("B2 exists").get_value(), "11");
+    assert_eq!(
+        sheet.get_cell("C2").expect("C2 exists").get_formula(),
+        "B2*3"
+    );
+
+    let diff = run_cli(&[
+        "diff",
+        original.to_str().expect("path utf8"),
+        modified.to_str().expect("path utf8"),
+    ]);
+    assert!(diff.status.success(), "stderr: {:?}", diff.stderr);
+    let diff_payload = parse_stdout_json(&diff);
+    assert!(diff_payload["change_count"].as_u64().unwrap_or(0) >= 2);
+}
+
+#[test]
+fn cli_diff_defaults_to_summary_only() {
+    let tmp = tempdir().expect("tempdir");
+    let original = tmp.path().join("diff-summary-original.xlsx");
+    let modified = tmp.path().join("diff-summary-modified.xlsx");
+    write_fixture(&original);
+    fs::copy(&original, &modified).expect("copy workbook");
+
+    let edit = run_cli(&[
+        "edit",
+        modified.to_str().expect("path utf8"),
+        "Sheet1",
+        "B2=11",
+        "C2==B2*3",
+    ]);
+    assert!(edit.status.success(), "stderr: {:?}", edit.stderr);
+
+    let diff = run_cli(&[
+        "diff",
+        original.to_str().expect("path utf8"),
+        modified.to_str().expect("path utf8"),
+    ]);
+    assert!(diff.status.success(), "stderr: {:?}", diff.stderr);
+
+    let payload = parse_stdout_json(&diff);
+    assert!(payload.get("summary").is_some());
+    assert!(payload.get("changes").is_none());
+    assert_eq!(payload["summary"]["returned_changes"].as_u64(), Some(0));
+    assert_eq!(
+        payload["summary"]["total_changes"].as_u64(),
+        payload["change_count"].as_u64()
+    );
+    assert!(
+        payload["summary"]["counts_by_kind"]["cell"]
+            .as_u64()
+            .unwrap_or(0)
+            >= 1
+    );
+}
+
+#[test]
+fn cli_diff_details_supports_pagination() {
+    let tmp = tempdir().expect("tempdir");
+    let original = tmp.path().join("diff-page-original.xlsx");
+    let modified = tmp.path().join("diff-page-modified.xlsx");
+    write_fixture(&original);
+    fs::copy(&original, &modified).expect("copy workbook");
+
+    let edit = run_cli(&[
+        "edit",
+        modified.to_str().expect("path utf8"),
+        "Sheet1",
+        "B2=11",
+        "C2==B2*3",
+        "B3=25",
+    ]);
+    assert!(edit.status.success(), "stderr: {:?}", edit.stderr);
+
+    let first = run_cli(&[
+        "diff",
+        original.to_str().expect("path utf8"),
+        modified.to_str().expect("path utf8"),
+        "--details",
+        "--limit",
+        "1",
+    ]);
+    assert!(first.status.success(), "stderr: {:?}", first.stderr);
+    let first_payload = parse_stdout_json(&first);
+    assert_eq!(
+        first_payload["summary"]["returned_changes"].as_u64(),
+        Some(1)
+    );
+    let first_changes = first_payload["changes"].as_array().expect("changes page");
+    assert_eq!(first_changes.len(), 1);
+    let next_offset = first_payload["summary"]["next_offset"]
+        .as_u64()
+        .expect("next offset");
+
+    let second = run_cli(&[
+        "diff",
+        original.to_str().expect("path utf8"),
+        modified.to_str().expect("path utf8"),
+        "--details",
+        "--limit",
+        "1",
+        "--offset",
+        &next_offset.to_string(),
+    ]);
+    assert!(second.status.success(), "stderr: {:?}", second.stderr);
+    let second_payload = parse_stdout_json(&second);
+    let second_changes = second_payload["changes"].as_array().expect("changes page");
+    assert_eq!(second_changes.len(), 1);
+    assert_eq!(
+        second_payload["change_count"], first_payload["change_count"],
+        "paged requests must report stable total change_count"
+    );
+}
+
+#[test]
+fn cli_diff_sheet_and_range_filters_scope_results() {
+    let tmp = tempdir().expect("tempdir");
+    let original = tmp.path().join("diff-filter-original.xlsx");
+    let modified = tmp.path().join("diff-filter-modified.xlsx");
+    write_fixture(&original);
+    fs::copy(&original, &modified).expect("copy workbook");
+
+    let edit = run_cli(&[
+        "edit",
+        modified.to_str().expect("path utf8"),
+        "Sheet1",
+        "B2=11",
+    ]);
+    assert!(edit.status.success(), "stderr: {:?}", edit.stderr);
+
+    let filtered = run_cli(&[
+        "diff",
+        original.to_str().expect("path utf8"),
+        modified.to_str().expect("path utf8"),
+        "--sheet",
+        "Sheet1",
+        "--range",
+        "B2:B2",
+        "--details",
+        "--limit",
+        "50",
+    ]);
+    assert!(filtered.status.success(), "stderr: {:?}", filtered.stderr);
+    let payload = parse_stdout_json(&filtered);
+    assert_eq!(payload["change_count"].as_u64(), Some(1));
+
+    let changes = payload["changes"].as_array().expect("changes");
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0]["address"].as_str(), Some("B2"));
+}
+
+#[test]
+fn cli_diff_summary_includes_group_buckets_and_subtype_counts() {
+    let tmp = tempdir().expect("tempdir");
+    let original = tmp.path().join("diff-group-summary-original.xlsx");
+    let modified = tmp.path().join("diff-group-summary-modified.xlsx");
+    write_fixture(&original);
+    fs::copy(&original, &modified).expect("copy workbook");
+
+    let edit = run_cli(&[
+        "edit",
+        modified.to_str().expect("path utf8"),
+        "Sheet1",
+        "B2=11",
+        "B3=25",
+        "C2==B2*3",
+    ]);
+    assert!(edit.status.success(), "stderr: {:?}", edit.stderr);
+
+    let diff = run_cli(&[
+        "diff",
+        original.to_str().expect("path utf8"),
+        modified.to_str().expect("path utf8"),
+    ]);
+    assert!(diff.status.success(), "stderr: {:?}", diff.stderr);
+
+    let payload = parse_stdout_json(&diff);
+    assert_eq!(payload["summary"]["counts_by_subtype"]["value_edit"], 2);
+    assert_eq!(payload["summary"]["counts_by_subtype"]["formula_edit"], 1);
+    assert_eq!(payload["summary"]["recalc_result_change_count"], 0);
+    assert_eq!(
+        payload["summary"]["direct_change_count"],
+        payload["change_count"]
+    );
+    assert_eq!(payload["summary"]["group_count"], 2);
+
+    let sheet_summaries = payload["summary"]["sheet_summaries"]
+        .as_array()
+        .expect("sheet summaries");
+    assert_eq!(sheet_summaries.len(), 1);
+    assert_eq!(sheet_summaries[0]["sheet"], "Sheet1");
+    assert_eq!(sheet_summaries[0]["total_changes"], 3);
+    assert_eq!(sheet_summaries[0]["direct_change_count"], 3);
+    assert_eq!(sheet_summaries[0]["recalc_result_change_count"], 0);
+    assert_eq!(sheet_summaries[0]["counts_by_group_type"]["value_edit"], 1);
+    assert_eq!(
+        sheet_summaries[0]["counts_by_group_type"]["formula_edit"],
+        1
+    );
+
+    let preview = payload["summary"]["group_preview"]
+        .as_array()
+        .expect("group preview");
+    assert_eq!(preview.len(), 2);
+    let value_group = preview
+        .iter()
+        .find(|group| group["group_type"] == "value_edit")
+        .expect("value_edit group");
+    assert_eq!(value_group["range"], "B2:B3");
+    assert_eq!(value_group["change_count"], 2);
+    assert_eq!(value_group["review_priority"], "direct");
+    let formula_group = preview
+        .iter()
+        .find(|group| group["group_type"] == "formula_edit")
+        .expect("formula_edit group");
+    assert_eq!(formula_group["range"], "C2");
+    assert_eq!(formula_group["review_priority"], "direct");
+}
+
+#[test]
+fn cli_diff_can_exclude_recalc_result_noise() {
+    let tmp = tempdir().expect("tempdir");
+    let original = tmp.path().join("diff-exclude-recalc-original.xlsx");
+    let modified = tmp.path().join("diff-exclude-recalc-modified.xlsx");
+    write_fixture(&original);
+    fs::copy(&original, &modified).expect("copy workbook");
+
+    let edit = run_cli(&[
+        "edit",
+        modified.to_str().expect("path utf8"),
+        "Sheet1",
+        "B2=11",
+    ]);
+    assert!(edit.status.success(), "stderr: {:?}", edit.stderr);
+
+    let recalc = run_cli(&["recalculate", modified.to_str().expect("path utf8")]);
+    assert!(recalc.status.success(), "stderr: {:?}", recalc.stderr);
+
+    let full = run_cli(&[
+        "diff",
+        original.to_str().expect("path utf8"),
+        modified.to_str().expect("path utf8"),
+        "--details",
+        "--limit",
+        "50",
+    ]);
+    assert!(full.status.success(), "stderr: {:?}", full.stderr);
+    let full_payload = parse_stdout_json(&full);
+    assert_eq!(
+        full_payload["summary"]["counts_by_subtype"]["value_edit"],
+        1
+    );
+    let recalc_count = full_payload["summary"]["counts_by_subtype"]["recalc_result"]
+        .as_u64()
+        .unwrap_or(0);
+    assert!(
+        recalc_count >= 1,
+        "expected recalc churn, got {full_payload}"
+    );
+    assert_eq!(
+        full_payload["summary"]["recalc_result_change_count"],
+        recalc_count
+    );
+    assert_eq!(full_payload["summary"]["direct_change_count"], 1);
+    assert_eq!(
+        full_payload["change_count"].as_u64().unwrap_or(0),
+        recalc_count + 1
+    );
+    let full_sheet_summaries = full_payload["summary"]["sheet_summaries"]
+        .as_array()
+        .expect("sheet summaries");
+    assert_eq!(full_sheet_summaries.len(), 1);
+    assert_eq!(full_sheet_summaries[0]["sheet"], "Sheet1");
+    assert_eq!(full_sheet_summaries[0]["direct_change_count"], 1);
+    assert_eq!(
+        full_sheet_summaries[0]["recalc_result_change_count"],
+        recalc_count
+    );
+
+    let filtered = run_cli(&[
+        "diff",
+        original.to_str().expect("path utf8"),
+        modified.to_str().expect("path utf8"),
+        "--details",
+        "--limit",
+        "50",
+        "--exclude-recalc-result",
+    ]);
+    assert!(filtered.status.success(), "stderr: {:?}", filtered.stderr);
+    let filtered_payload = parse_stdout_json(&filtered);
+    assert_eq!(filtered_payload["change_count"], 1);
+    assert_eq!(filtered_payload["summary"]["recalc_result_change_count"], 0);
+    assert_eq!(filtered_payload["summary"]["direct_change_count"], 1);
+    assert_eq!(
+        filtered_payload["summary"]["filters"]["exclude_recalc_result"],
+        true
+    );
+    assert!(
+        filtered_payload["summary"]["counts_by_subtype"]
+            .get("recalc_result")
+            .is_none()
+    );
+    let changes = filtered_payload["changes"].as_array().expect("changes");
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0]["address"], "B2");
+
+    let full_groups = full_payload["groups"].as_array().expect("groups");
+    assert_eq!(full_groups[0]["review_priority"], "direct");
+    assert_ne!(full_groups[0]["group_type"], "recalc_result");
+    assert_eq!(
+        full_groups.last().expect("at least one group")["review_priority"],
+        "derived"
+    );
+}
+
+#[test]
+fn cli_append_region_dry_run_reports_footer_aware_plan() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("append-region-dry-run.xlsx");
+    let rows_path = tmp.path().join("rows.json");
+
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet1");
+        sheet.get_cell_mut("A1").set_value("Name");
+        sheet.get_cell_mut("B1").set_value("Amount");
+        sheet.get_cell_mut("A2").set_value("Alice");
+        sheet.get_cell_mut("B2").set_value_number(10.0);
+        sheet.get_cell_mut("A3").set_value("Bob");
+        sheet.get_cell_mut("B3").set_value_number(20.0);
+        sheet.get_cell_mut("A4").set_value("Total");
+        let total = sheet.get_cell_mut("B4");
+        total.set_formula("SUM(B2:B3)");
+        total.get_cell_value_mut().set_formula_result_default("30");
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+    fs::write(&rows_path, r#"{"rows":[["Cara",30]]}"#).expect("write rows payload");
+
+    let file = workbook_path.to_str().expect("path utf8");
+    let overview = run_cli(&["sheet-overview", file, "Sheet1"]);
+    assert!(overview.status.success(), "stderr: {:?}", overview.stderr);
+    let overview_payload = parse_stdout_json(&overview);
+    let region_id = overview_payload["detected_regions"][0]["id"]
+        .as_u64()
+        .expect("region id")
+        .to_string();
+
+    let output = run_cli(&[
+        "append-region",
+        file,
+        "--sheet",
+        "Sheet1",
+        "--region-id",
+        region_id.as_str(),
+        "--rows",
+        &format!("@{}", rows_path.display()),
+        "--dry-run",
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["mode"], "dry_run");
+    assert_eq!(payload["sheet_name"], "Sheet1");
+    assert_eq!(payload["target_kind"], "detected_region");
+    assert_eq!(
+        payload["region_id"],
+        region_id.parse::<u64>().expect("region id num")
+    );
+    assert_eq!(payload["footer_policy"], "auto");
+    assert_eq!(payload["insert_at_row"], 4);
+    assert_eq!(
+        payload["insert_reason"],
+        "auto policy selected detected footer row 4"
+    );
+    assert_eq!(payload["footer_row"], 4);
+    assert_eq!(payload["target_anchor"], "A4");
+    assert_eq!(payload["target_range"], "A4:B4");
+    assert_eq!(payload["rows_appended"], 1);
+    assert_eq!(payload["columns_written"], 2);
+    assert_eq!(payload["expand_adjacent_sums"], true);
+    assert_eq!(payload["confidence"], "high");
+    assert!(
+        payload["confidence_reason"]
+            .as_str()
+            .unwrap_or_default()
+            .contains("explicit footer keyword detected")
+    );
+    assert_eq!(payload["footer_formula_targets"][0], "B4");
+    assert_eq!(
+        payload["footer_candidates"].as_array().map(Vec::len),
+        Some(2)
+    );
+    assert_eq!(payload["would_change"], true);
+}
+
+#[test]
+fn cli_append_region_output_inserts_before_footer_and_expands_sum() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("append-region-output-source.xlsx");
+    let output_path = tmp.path().join("append-region-output-target.xlsx");
+    let rows_path = tmp.path().join("rows.json");
+
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet1");
+        sheet.get_cell_mut("A1").set_value("Name");
+        sheet.get_cell_mut("B1").set_value("Amount");
+        sheet.get_cell_mut("A2").set_value("Alice");
+        sheet.get_cell_mut("B2").set_value_number(10.0);
+        sheet.get_cell_mut("A3").set_value("Bob");
+        sheet.get_cell_mut("B3").set_value_number(20.0);
+        sheet.get_cell_mut("A4").set_value("Total");
+        let total = sheet.get_cell_mut("B4");
+        total.set_formula("SUM(B2:B3)");
+        total.get_cell_value_mut().set_formula_result_default("30");
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+    fs::write(&rows_path, r#"{"rows":[["Cara",30]]}"#).expect("write rows payload");
+
+    let file = workbook_path.to_str().expect("path utf8");
+    let out = output_path.to_str().expect("output path utf8");
+    let overview = run_cli(&["sheet-overview", file, "Sheet1"]);
+    assert!(overview.status.success(), "stderr: {:?}", overview.stderr);
+    let overview_payload = parse_stdout_json(&overview);
+    let region_id = overview_payload["detected_regions"][0]["id"]
+        .as_u64()
+        .expect("region id")
+        .to_string();
+
+    let output = run_cli(&[
+        "append-region",
+        file,
+        "--sheet",
+        "Sheet1",
+        "--region-id",
+        region_id.as_str(),
+        "--rows",
+        &format!("@{}", rows_path.display()),
+        "--output",
+        out,
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["mode"], "output");
+    assert_eq!(payload["file"], out);
+    assert_eq!(payload["target_path"], out);
+    assert_eq!(payload["changed"], true);
+
+    let book = umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output workbook");
+    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet1 exists");
+    assert_eq!(sheet.get_cell("A4").expect("A4").get_value(), "Cara");
+    assert_eq!(sheet.get_cell("B4").expect("B4").get_value(), "30");
+    assert_eq!(sheet.get_cell("A5").expect("A5").get_value(), "Total");
+    assert_eq!(
+        sheet.get_cell("B5").expect("B5").get_formula(),
+        "SUM(B2:B4)"
+    );
+}
+
+#[test]
+fn cli_append_region_detects_formula_footer_even_with_blank_label_cell() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("append-region-blank-footer.xlsx");
+    let rows_path = tmp.path().join("rows.json");
+
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet1");
+        sheet.get_cell_mut("A1").set_value("Name");
+        sheet.get_cell_mut("B1").set_value("Amount");
+        sheet.get_cell_mut("A2").set_value("Alice");
+        sheet.get_cell_mut("B2").set_value_number(10.0);
+        sheet.get_cell_mut("A3").set_value("Bob");
+        sheet.get_cell_mut("B3").set_value_number(20.0);
+        let total = sheet.get_cell_mut("B4");
+        total.set_formula("SUM(B2:B3)");
+        total.get_cell_value_mut().set_formula_result_default("30");
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+    fs::write(&rows_path, r#"{"rows":[["Cara",30]]}"#).expect("write rows payload");
+
+    let file = workbook_path.to_str().expect("path utf8");
+    let overview = run_cli(&["sheet-overview", file, "Sheet1"]);
+    assert!(overview.status.success(), "stderr: {:?}", overview.stderr);
+    let overview_payload = parse_stdout_json(&overview);
+    let region_id = overview_payload["detected_regions"][0]["id"]
+        .as_u64()
+        .expect("region id")
+        .to_string();
+
+    let output = run_cli(&[
+        "append-region",
+        file,
+        "--sheet",
+        "Sheet1",
+        "--region-id",
+        region_id.as_str(),
+        "--rows",
+        &format!("@{}", rows_path.display()),
+        "--dry-run",
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["footer_row"], 4);
+    assert_eq!(payload["insert_at_row"], 4);
+    assert!(
+        payload["footer_detection"]
+            .as_str()
+            .unwrap_or_default()
+            .contains("formula-bearing summary row")
+    );
+}
+
+#[test]
+fn cli_append_region_from_csv_skips_header_and_handles_quotes_blanks_and_crlf() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("append-region-csv-source.xlsx");
+    let output_path = tmp.path().join("append-region-csv-target.xlsx");
+    let csv_path = tmp.path().join("rows.csv");
+
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet1");
+        sheet.get_cell_mut("A1").set_value("Name");
+        sheet.get_cell_mut("B1").set_value("Amount");
+        sheet.get_cell_mut("C1").set_value("Notes");
+        sheet.get_cell_mut("A2").set_value("Alice");
+        sheet.get_cell_mut("B2").set_value_number(10.0);
+        sheet.get_cell_mut("A3").set_value("Bob");
+        sheet.get_cell_mut("B3").set_value_number(20.0);
+        sheet.get_cell_mut("A4").set_value("Total");
+        let total = sheet.get_cell_mut("B4");
+        total.set_formula("SUM(B2:B3)");
+        total.get_cell_value_mut().set_formula_result_default("30");
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+    fs::write(
+        &csv_path,
+        "Name,Amount,Notes\r\n\"Cara, Jr\",30,\r\nDina,40,\"Needs review\"\r\n",
+    )
+    .expect("write csv payload");
+
+    let file = workbook_path.to_str().expect("path utf8");
+    let out = output_path.to_str().expect("output path utf8");
+    let overview = run_cli(&["sheet-overview", file, "Sheet1"]);
+    assert!(overview.status.success(), "stderr: {:?}", overview.stderr);
+    let overview_payload = parse_stdout_json(&overview);
+    let region_id = overview_payload["detected_regions"][0]["id"]
+        .as_u64()
+        .expect("region id")
+        .to_string();
+
+    let output = run_cli(&[
+        "append-region",
+        file,
+        "--sheet",
+        "Sheet1",
+        "--region-id",
+        region_id.as_str(),
+        "--from-csv",
+        csv_path.to_str().expect("csv utf8"),
+        "--header",
+        "--output",
+        out,
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["rows_appended"], 2);
+    assert_eq!(payload["columns_written"], 3);
+    assert_eq!(payload["insert_at_row"], 4);
+    assert_eq!(payload["target_range"], "A4:C5");
+
+    let book = umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output workbook");
+    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet1 exists");
+    assert_eq!(sheet.get_cell("A4").expect("A4").get_value(), "Cara, Jr");
+    assert_eq!(sheet.get_cell("B4").expect("B4").get_value(), "30");
+    assert!(
+        sheet.get_cell("C4").is_none()
+            || sheet
+                .get_cell("C4")
+                .expect("C4 present when not none")
+                .get_value()
+                .is_empty()
+    );
+    assert_eq!(sheet.get_cell("A5").expect("A5").get_value(), "Dina");
+    assert_eq!(sheet.get_cell("B5").expect("B5").get_value(), "40");
+    assert_eq!(
+        sheet.get_cell("C5").expect("C5").get_value(),
+        "Needs review"
+    );
+    assert_eq!(sheet.get_cell("A6").expect("A6").get_value(), "Total");
+    assert_eq!(
+        sheet.get_cell("B6").expect("B6").get_formula(),
+        "SUM(B2:B5)"
+    );
+}
+
+#[test]
+fn cli_append_region_rejects_rows_and_from_csv_together() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("append-region-invalid-source.xlsx");
+    let rows_path = tmp.path().join("rows.json");
+    let csv_path = tmp.path().join("rows.csv");
+
+    write_fixture(&workbook_path);
+    fs::write(&rows_path, r#"{"rows":[["Cara",30]]}"#).expect("write rows payload");
+    fs::write(&csv_path, "Name,Amount\nCara,30\n").expect("write csv payload");
+
+    let file = workbook_path.to_str().expect("path utf8");
+    let overview = run_cli(&["sheet-overview", file, "Sheet1"]);
+    assert!(overview.status.success(), "stderr: {:?}", overview.stderr);
+    let overview_payload = parse_stdout_json(&overview);
+    let region_id = overview_payload["detected_regions"][0]["id"]
+        .as_u64()
+        .expect("region id")
+        .to_string();
+
+    let output = run_cli(&[
+        "append-region",
+        file,
+        "--sheet",
+        "Sheet1",
+        "--region-id",
+        region_id.as_str(),
+        "--rows",
+        &format!("@{}", rows_path.display()),
+        "--from-csv",
+        csv_path.to_str().expect("csv utf8"),
+        "--dry-run",
+    ]);
+    assert!(!output.status.success());
+    let err = parse_stderr_json(&output);
+    assert_eq!(err["code"], "INVALID_ARGUMENT");
+    assert!(
+        err["message"]
+            .as_str()
+            .unwrap_or_default()
+            .contains("mutually exclusive")
+    );
+}
+
+#[test]
+fn cli_append_region_supports_table_name_targeting() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("append-region-table-target.xlsx");
+    let output_path = tmp.path().join("append-region-table-target-out.xlsx");
+    let rows_path = tmp.path().join("rows.json");
+
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet1");
+        sheet.get_cell_mut("A1").set_value("Name");
+        sheet.get_cell_mut("B1").set_value("Amount");
+        sheet.get_cell_mut("A2").set_value("Alice");
+        sheet.get_cell_mut("B2").set_value_number(10.0);
+        sheet.get_cell_mut("A3").set_value("Bob");
+        sheet.get_cell_mut("B3").set_value_number(20.0);
+        let mut table = umya_spreadsheet::structs::Table::new("SalesTable", ("A1", "B3"));
+        table.set_display_name("SalesTable");
+        sheet.add_table(table);
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+    fs::write(&rows_path, r#"{"rows":[["Cara",30]]}"#).expect("write rows payload");
+
+    let output = run_cli(&[
+        "append-region",
+        workbook_path.to_str().expect("path utf8"),
+        "--sheet",
+        "Sheet1",
+        "--table-name",
+        "SalesTable",
+        "--rows",
+        &format!("@{}", rows_path.display()),
+        "--output",
+        output_path.to_str().expect("output utf8"),
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["target_kind"], "table");
+    assert_eq!(payload["table_name"], "SalesTable");
+    assert!(payload.get("region_id").is_none());
+    assert_eq!(payload["insert_at_row"], 4);
+    assert_eq!(payload["target_range"], "A4:B4");
+
+    let book = umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output workbook");
+    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet1 exists");
+    assert_eq!(sheet.get_cell("A4").expect("A4").get_value(), "Cara");
+    assert_eq!(sheet.get_cell("B4").expect("B4").get_value(), "30");
+    let table = sheet
+        .get_tables()
+        .iter()
+        .find(|table| table.get_name() == "SalesTable")
+        .expect("sales table");
+    assert_eq!(table.get_area().0.get_coordinate(), "A1");
+    assert_eq!(table.get_area().1.get_coordinate(), "B4");
+
+    let named = run_cli(&[
+        "named-ranges",
+        output_path.to_str().expect("output utf8"),
+        "--sheet",
+        "Sheet1",
+    ]);
+    assert!(named.status.success(), "stderr: {:?}", named.stderr);
+    let named_payload = parse_stdout_json(&named);
+    let sales_table = named_payload["items"]
+        .as_array()
+        .expect("named items")
+        .iter()
+        .find(|item| item["name"] == "SalesTable")
+        .expect("sales table item");
+    assert_eq!(sales_table["kind"], "table");
+    assert_eq!(sales_table["refers_to"], "A1:B4");
+
+    let read_table = run_cli(&[
+        "read-table",
+        output_path.to_str().expect("output utf8"),
+        "--sheet",
+        "Sheet1",
+        "--table-name",
+        "SalesTable",
+        "--table-format",
+        "values",
+    ]);
+    assert!(
+        read_table.status.success(),
+        "stderr: {:?}",
+        read_table.stderr
+    );
+    let read_table_payload = parse_stdout_json(&read_table);
+    assert_eq!(read_table_payload["table_name"], "SalesTable");
+    assert_eq!(read_table_payload["total_rows"], 3);
+}
+
+#[test]
+fn cli_append_region_append_at_end_policy_bypasses_detected_footer() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("append-region-append-at-end.xlsx");
+    let rows_path = tmp.path().join("rows.json");
+
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet1");
+        sheet.get_cell_mut("A1").set_value("Name");
+        sheet.get_cell_mut("B1").set_value("Amount");
+        sheet.get_cell_mut("A2").set_value("Alice");
+        sheet.get_cell_mut("B2").set_value_number(10.0);
+        sheet.get_cell_mut("A3").set_value("Bob");
+        sheet.get_cell_mut("B3").set_value_number(20.0);
+        sheet.get_cell_mut("A4").set_value("Total");
+        let total = sheet.get_cell_mut("B4");
+        total.set_formula("SUM(B2:B3)");
+        total.get_cell_value_mut().set_formula_result_default("30");
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+    fs::write(&rows_path, r#"{"rows":[["Cara",30]]}"#).expect("write rows payload");
+
+    let file = workbook_path.to_str().expect("path utf8");
+    let overview = run_cli(&["sheet-overview", file, "Sheet1"]);
+    assert!(overview.status.success(), "stderr: {:?}", overview.stderr);
+    let overview_payload = parse_stdout_json(&overview);
+    let region_id = overview_payload["detected_regions"][0]["id"]
+        .as_u64()
+        .expect("region id")
+        .to_string();
+
+    let output = run_cli(&[
+        "append-region",
+        file,
+        "--sheet",
+        "Sheet1",
+        "--region-id",
+        region_id.as_str(),
+        "--rows",
+        &format!("@{}", rows_path.display()),
+        "--footer-policy",
+        "append-at-end",
+        "--dry-run",
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["footer_policy"], "append_at_end");
+    assert_eq!(payload["footer_row"], 4);
+    assert_eq!(payload["insert_at_row"], 5);
+    assert!(
+        payload["insert_reason"]
+            .as_str()
+            .unwrap_or_default()
+            .contains("bypassed detected footer row 4")
+    );
+    assert!(
+        payload["warnings"]
+            .as_array()
+            .expect("warnings")
+            .iter()
+            .any(|warning| warning
+                .as_str()
+                .unwrap_or_default()
+                .contains("ignored detected footer row 4"))
+    );
+}
+
+#[test]
+fn cli_append_region_before_footer_policy_requires_detected_footer() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("append-region-before-footer.xlsx");
+    let rows_path = tmp.path().join("rows.json");
+
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet1");
+        sheet.get_cell_mut("A1").set_value("Name");
+        sheet.get_cell_mut("B1").set_value("Amount");
+        sheet.get_cell_mut("A2").set_value("Alice");
+        sheet.get_cell_mut("B2").set_value_number(10.0);
+        sheet.get_cell_mut("A3").set_value("Bob");
+        sheet.get_cell_mut("B3").set_value_number(20.0);
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
     fs::write(&rows_path, r#"{"rows":[["Cara",30]]}"#).expect("write rows payload");
 
-    let file = workbook_path.to_str().expect("path utf8");
-    let overview = run_cli(&["sheet-overview", file, "Sheet1"]);
-    assert!(overview.status.success(), "stderr: {:?}", overview.stderr);
-    let overview_payload = parse_stdout_json(&overview);
-    let region_id = overview_payload["detected_regions"][0]["id"]
-        .as_u64()
-        .expect("region id")
-        .to_string();
+    let file = workbook_path.to_str().expect("path utf8");
+    let overview = run_cli(&["sheet-overview", file, "Sheet1"]);
+    assert!(overview.status.success(), "stderr: {:?}", overview.stderr);
+    let overview_payload = parse_stdout_json(&overview);
+    let region_id = overview_payload["detected_regions"][0]["id"]
+        .as_u64()
+        .expect("region id")
+        .to_string();
+
+    let output = run_cli(&[
+        "append-region",
+        file,
+        "--sheet",
+        "Sheet1",
+        "--region-id",
+        region_id.as_str(),
+        "--rows",
+        &format!("@{}", rows_path.display()),
+        "--footer-policy",
+        "before-footer",
+        "--dry-run",
+    ]);
+    assert!(!output.status.success());
+    let err = parse_stderr_json(&output);
+    assert_eq!(err["code"], "INVALID_ARGUMENT");
+    assert!(
+        err["message"]
+            .as_str()
+            .unwrap_or_default()
+            .contains("requires a detected footer/subtotal row")
+    );
+}
+
+#[test]
+fn cli_clone_template_row_dry_run_reports_targets_and_confidence() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("clone-template-row-dry-run.xlsx");
+
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet1");
+        sheet.get_cell_mut("A1").set_value("Item");
+        sheet.get_cell_mut("B1").set_value("Input");
+        sheet.get_cell_mut("C1").set_value("Calc");
+        sheet.get_cell_mut("A2").set_value("Alpha");
+        sheet.get_cell_mut("B2").set_value_number(10.0);
+        sheet.get_cell_mut("C2").set_formula("B2*2");
+        sheet.get_cell_mut("A3").set_value("Total");
+        sheet.get_cell_mut("C3").set_formula("SUM(C2:C2)");
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+
+    let output = run_cli(&[
+        "clone-template-row",
+        workbook_path.to_str().expect("path utf8"),
+        "--sheet",
+        "Sheet1",
+        "--source-row",
+        "2",
+        "--after",
+        "2",
+        "--count",
+        "2",
+        "--expand-adjacent-sums",
+        "--dry-run",
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["helper_kind"], "clone_template_row");
+    assert_eq!(payload["anchor_kind"], "after");
+    assert_eq!(payload["insert_at_row"], 3);
+    assert_eq!(payload["inserted_row_range"], "3:4");
+    assert_eq!(payload["formula_targets"][0], "C3");
+    assert_eq!(payload["formula_targets"][1], "C4");
+    assert_eq!(payload["likely_patch_targets"][0], "B3");
+    assert_eq!(payload["likely_patch_targets"][1], "B4");
+    assert_eq!(payload["adjacent_sum_targets"][0], "C5");
+    assert_eq!(payload["confidence"], "high");
+    assert_eq!(payload["would_change"], true);
+}
+
+#[test]
+fn cli_clone_template_row_strict_merge_policy_fails_on_crossing_merge() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("clone-template-row-strict-merge.xlsx");
+
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet1");
+        sheet.get_cell_mut("A1").set_value("Header");
+        sheet.get_cell_mut("A2").set_value("Alpha");
+        sheet.get_cell_mut("B2").set_value_number(10.0);
+        sheet.add_merge_cells("A1:A2");
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+
+    let output = run_cli(&[
+        "clone-template-row",
+        workbook_path.to_str().expect("path utf8"),
+        "--sheet",
+        "Sheet1",
+        "--source-row",
+        "2",
+        "--before",
+        "3",
+        "--merge-policy",
+        "strict",
+        "--dry-run",
+    ]);
+    assert!(!output.status.success());
+    let err = parse_stderr_json(&output);
+    assert_eq!(err["code"], "UNSAFE_CLONE_TEMPLATE");
+    assert!(
+        err["message"]
+            .as_str()
+            .unwrap_or_default()
+            .contains("cross the clone boundary")
+    );
+}
+
+#[test]
+fn cli_clone_template_row_output_preserves_horizontal_merge_and_validation() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("clone-template-row-output.xlsx");
+    let output_path = tmp.path().join("clone-template-row-output-target.xlsx");
+
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet1");
+        sheet.get_cell_mut("A1").set_value("Name");
+        sheet.get_cell_mut("B1").set_value("Input");
+        sheet.get_cell_mut("C1").set_value("Calc");
+        sheet.get_cell_mut("A2").set_value("Alpha");
+        sheet.get_cell_mut("B2").set_value_number(10.0);
+        sheet.get_cell_mut("C2").set_formula("B2*2");
+        sheet.add_merge_cells("A2:B2");
+
+        let mut dv = umya_spreadsheet::structs::DataValidation::default();
+        dv.set_type(umya_spreadsheet::structs::DataValidationValues::List);
+        dv.get_sequence_of_references_mut().set_sqref("B2:B2");
+        dv.set_formula1("\"A,B,C\"");
+        sheet.set_data_validations(umya_spreadsheet::structs::DataValidations::default());
+        sheet
+            .get_data_validations_mut()
+            .unwrap()
+            .add_data_validation_list(dv);
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+
+    let output = run_cli(&[
+        "clone-template-row",
+        workbook_path.to_str().expect("path utf8"),
+        "--sheet",
+        "Sheet1",
+        "--source-row",
+        "2",
+        "--before",
+        "3",
+        "--count",
+        "2",
+        "--patch-targets",
+        "all-non-formula",
+        "--output",
+        output_path.to_str().expect("output utf8"),
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["changed"], true);
+    assert_eq!(payload["inserted_row_range"], "3:4");
+
+    let book = umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output workbook");
+    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet1 exists");
+    assert_eq!(sheet.get_cell("A3").expect("A3").get_value(), "Alpha");
+    assert_eq!(sheet.get_cell("B4").expect("B4").get_value(), "10");
+    let merge_ranges: Vec<String> = sheet
+        .get_merge_cells()
+        .iter()
+        .map(|range| range.get_range())
+        .collect();
+    assert!(merge_ranges.contains(&"A3:B3".to_string()));
+    assert!(merge_ranges.contains(&"A4:B4".to_string()));
+    let validations = sheet.get_data_validations().expect("validations");
+    let sqrefs: Vec<String> = validations
+        .get_data_validation_list()
+        .iter()
+        .map(|dv| dv.get_sequence_of_references().get_sqref())
+        .collect();
+    assert!(sqrefs.iter().any(|sqref| sqref.contains("B3")));
+    assert!(sqrefs.iter().any(|sqref| sqref.contains("B4")));
+}
+
+#[test]
+fn cli_clone_row_band_dry_run_reports_blocks_and_targets() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("clone-row-band-dry-run.xlsx");
+
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet1");
+        sheet.get_cell_mut("A1").set_value("Item");
+        sheet.get_cell_mut("B1").set_value("Input");
+        sheet.get_cell_mut("C1").set_value("Calc");
+        sheet.get_cell_mut("A2").set_value("Alpha");
+        sheet.get_cell_mut("B2").set_value_number(10.0);
+        sheet.get_cell_mut("C2").set_formula("B2*2");
+        sheet.get_cell_mut("A3").set_value("Beta");
+        sheet.get_cell_mut("B3").set_value_number(20.0);
+        sheet.get_cell_mut("C3").set_formula("B3*2");
+        sheet.get_cell_mut("A4").set_value("Total");
+        sheet.get_cell_mut("C4").set_formula("SUM(C2:C3)");
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+
+    let output = run_cli(&[
+        "clone-row-band",
+        workbook_path.to_str().expect("path utf8"),
+        "--sheet",
+        "Sheet1",
+        "--source-rows",
+        "2:3",
+        "--after",
+        "3",
+        "--repeat",
+        "2",
+        "--expand-adjacent-sums",
+        "--dry-run",
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["helper_kind"], "clone_row_band");
+    assert_eq!(payload["source_row_range"], "2:3");
+    assert_eq!(payload["source_row_count"], 2);
+    assert_eq!(payload["inserted_row_range"], "4:7");
+    assert_eq!(payload["inserted_blocks"][0]["row_range"], "4:5");
+    assert_eq!(payload["inserted_blocks"][1]["row_range"], "6:7");
+    assert_eq!(payload["formula_targets"][0], "C4");
+    assert_eq!(payload["adjacent_sum_targets"][0], "C8");
+    assert_eq!(payload["confidence"], "high");
+}
+
+#[test]
+fn cli_clone_row_band_strict_merge_policy_fails_on_crossing_merge() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("clone-row-band-strict-merge.xlsx");
+
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet1");
+        sheet.get_cell_mut("A1").set_value("Header");
+        sheet.get_cell_mut("A2").set_value("Alpha");
+        sheet.get_cell_mut("A3").set_value("Beta");
+        sheet.add_merge_cells("A1:A2");
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+
+    let output = run_cli(&[
+        "clone-row-band",
+        workbook_path.to_str().expect("path utf8"),
+        "--sheet",
+        "Sheet1",
+        "--source-rows",
+        "2:3",
+        "--before",
+        "4",
+        "--merge-policy",
+        "strict",
+        "--dry-run",
+    ]);
+    assert!(!output.status.success());
+    let err = parse_stderr_json(&output);
+    assert_eq!(err["code"], "UNSAFE_CLONE_TEMPLATE");
+    assert!(
+        err["message"]
+            .as_str()
+            .unwrap_or_default()
+            .contains("cross the clone boundary")
+    );
+}
+
+#[test]
+fn cli_clone_row_band_output_preserves_merges_validations_and_row_heights() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("clone-row-band-output.xlsx");
+    let output_path = tmp.path().join("clone-row-band-output-target.xlsx");
+
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet1");
+        sheet.get_cell_mut("A1").set_value("Name");
+        sheet.get_cell_mut("B1").set_value("Input");
+        sheet.get_cell_mut("C1").set_value("Calc");
+        sheet.get_cell_mut("A2").set_value("Alpha");
+        sheet.get_cell_mut("B2").set_value_number(10.0);
+        sheet.get_cell_mut("C2").set_formula("B2*2");
+        sheet.get_cell_mut("A3").set_value("Beta");
+        sheet.get_cell_mut("B3").set_value_number(20.0);
+        sheet.get_cell_mut("C3").set_formula("B3*2");
+        sheet.add_merge_cells("A2:A3");
+        sheet
+            .get_row_dimension_mut(&2)
+            .set_height(28.0)
+            .set_custom_height(true);
+        sheet
+            .get_row_dimension_mut(&3)
+            .set_height(32.0)
+            .set_custom_height(true);
+
+        let mut dv = umya_spreadsheet::structs::DataValidation::default();
+        dv.set_type(umya_spreadsheet::structs::DataValidationValues::List);
+        dv.get_sequence_of_references_mut().set_sqref("B2:B3");
+        dv.set_formula1("\"A,B,C\"");
+        sheet.set_data_validations(umya_spreadsheet::structs::DataValidations::default());
+        sheet
+            .get_data_validations_mut()
+            .unwrap()
+            .add_data_validation_list(dv);
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+
+    let output = run_cli(&[
+        "clone-row-band",
+        workbook_path.to_str().expect("path utf8"),
+        "--sheet",
+        "Sheet1",
+        "--source-rows",
+        "2:3",
+        "--before",
+        "4",
+        "--repeat",
+        "2",
+        "--patch-targets",
+        "all-non-formula",
+        "--output",
+        output_path.to_str().expect("output utf8"),
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["changed"], true);
+    assert_eq!(payload["inserted_blocks"][0]["row_range"], "4:5");
+    assert_eq!(payload["inserted_blocks"][1]["row_range"], "6:7");
+
+    let book = umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output workbook");
+    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet1 exists");
+    assert_eq!(sheet.get_cell("A4").expect("A4").get_value(), "Alpha");
+    assert_eq!(sheet.get_cell("A5").expect("A5").get_value(), "Beta");
+    assert_eq!(
+        sheet
+            .get_cell("C4")
+            .expect("C4")
+            .get_formula()
+            .replace(' ', ""),
+        "B4*2"
+    );
+    assert_eq!(
+        sheet
+            .get_cell("C7")
+            .expect("C7")
+            .get_formula()
+            .replace(' ', ""),
+        "B7*2"
+    );
+    let merge_ranges: Vec<String> = sheet
+        .get_merge_cells()
+        .iter()
+        .map(|range| range.get_range())
+        .collect();
+    assert!(merge_ranges.contains(&"A4:A5".to_string()));
+    assert!(merge_ranges.contains(&"A6:A7".to_string()));
+    assert_eq!(
+        sheet.get_row_dimension(&4).map(|row| *row.get_height()),
+        Some(28.0)
+    );
+    assert_eq!(
+        sheet.get_row_dimension(&5).map(|row| *row.get_height()),
+        Some(32.0)
+    );
+    let validations = sheet.get_data_validations().expect("validations");
+    let sqrefs: Vec<String> = validations
+        .get_data_validation_list()
+        .iter()
+        .map(|dv| dv.get_sequence_of_references().get_sqref())
+        .collect();
+    assert!(
+        sqrefs
+            .iter()
+            .any(|sqref| sqref.contains("B4") && sqref.contains("B5"))
+    );
+    assert!(
+        sqrefs
+            .iter()
+            .any(|sqref| sqref.contains("B6") && sqref.contains("B7"))
+    );
+}
+
+#[test]
+fn cli_diff_groups_multi_digit_row_runs_by_coordinates_not_lexicographic_order() {
+    let tmp = tempdir().expect("tempdir");
+    let original = tmp.path().join("diff-multi-digit-original.xlsx");
+    let modified = tmp.path().join("diff-multi-digit-modified.xlsx");
+
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet1");
+        for row in 2..=12u32 {
+            sheet.get_cell_mut((2, row)).set_value_number(row as i32);
+        }
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &original).expect("write original");
+    fs::copy(&original, &modified).expect("copy workbook");
+
+    let mut args = vec![
+        "edit".to_string(),
+        modified.to_str().expect("path utf8").to_string(),
+        "Sheet1".to_string(),
+    ];
+    for row in 2..=12u32 {
+        args.push(format!("B{}={}", row, row + 100));
+    }
+    let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+    let edit = run_cli(arg_refs.as_slice());
+    assert!(edit.status.success(), "stderr: {:?}", edit.stderr);
+
+    let diff = run_cli(&[
+        "diff",
+        original.to_str().expect("path utf8"),
+        modified.to_str().expect("path utf8"),
+    ]);
+    assert!(diff.status.success(), "stderr: {:?}", diff.stderr);
+    let payload = parse_stdout_json(&diff);
+    assert_eq!(payload["summary"]["group_count"], 1);
+    let preview = payload["summary"]["group_preview"]
+        .as_array()
+        .expect("group preview");
+    assert_eq!(preview.len(), 1);
+    assert_eq!(preview[0]["group_type"], "value_edit");
+    assert_eq!(preview[0]["range"], "B2:B12");
+    assert_eq!(preview[0]["change_count"], 11);
+}
+
+#[test]
+fn cli_diff_rejects_invalid_range_filter() {
+    let tmp = tempdir().expect("tempdir");
+    let original = tmp.path().join("diff-invalid-range-original.xlsx");
+    let modified = tmp.path().join("diff-invalid-range-modified.xlsx");
+    write_fixture(&original);
+    fs::copy(&original, &modified).expect("copy workbook");
+
+    let diff = run_cli(&[
+        "diff",
+        original.to_str().expect("path utf8"),
+        modified.to_str().expect("path utf8"),
+        "--range",
+        "NOT_A_RANGE",
+    ]);
+    assert!(!diff.status.success(), "diff should fail for invalid range");
+    let err = parse_stderr_json(&diff);
+    assert_eq!(err["code"], "INVALID_ARGUMENT", "unexpected error: {err}");
+}
+
+#[test]
+fn cli_diff_rejects_invalid_details_limit() {
+    let tmp = tempdir().expect("tempdir");
+    let original = tmp.path().join("diff-invalid-limit-original.xlsx");
+    let modified = tmp.path().join("diff-invalid-limit-modified.xlsx");
+    write_fixture(&original);
+    fs::copy(&original, &modified).expect("copy workbook");
+
+    let diff = run_cli(&[
+        "diff",
+        original.to_str().expect("path utf8"),
+        modified.to_str().expect("path utf8"),
+        "--details",
+        "--limit",
+        "0",
+    ]);
+    assert!(!diff.status.success(), "diff should fail for limit=0");
+    let err = parse_stderr_json(&diff);
+    assert_eq!(err["code"], "INVALID_ARGUMENT", "unexpected error: {err}");
+}
+
+#[test]
+fn cli_edit_dry_run_reports_preview_and_preserves_source() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("edit-dry-run.xlsx");
+    write_fixture(&workbook_path);
+
+    let before = fs::read(&workbook_path).expect("read source before dry-run");
+    let file = workbook_path.to_str().expect("path utf8");
+
+    let output = run_cli(&["edit", file, "Sheet1", "--dry-run", "B2=11", "C2==B2*3"]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["edits_provided"].as_u64(), Some(2));
+    assert_eq!(payload["edits_validated"].as_u64(), Some(2));
+    assert!(payload["would_change"].as_bool().unwrap_or(false));
+
+    let affected = payload["affected_cells"]
+        .as_array()
+        .expect("affected_cells array");
+    assert!(affected.iter().any(|cell| cell.as_str() == Some("B2")));
+    assert!(affected.iter().any(|cell| cell.as_str() == Some("C2")));
+
+    let after = fs::read(&workbook_path).expect("read source after dry-run");
+    assert_eq!(before, after, "dry-run mutated source workbook");
+}
+
+#[test]
+fn cli_edit_output_writes_target_only() {
+    let tmp = tempdir().expect("tempdir");
+    let source_path = tmp.path().join("edit-output-source.xlsx");
+    let output_path = tmp.path().join("edit-output-target.xlsx");
+    write_fixture(&source_path);
+
+    let before_source = fs::read(&source_path).expect("read source before output mode");
+    let source = source_path.to_str().expect("source utf8");
+    let output = output_path.to_str().expect("output utf8");
+
+    let command = run_cli(&[
+        "edit", source, "Sheet1", "--output", output, "B2=17", "C2==B2*4",
+    ]);
+    assert!(command.status.success(), "stderr: {:?}", command.stderr);
+
+    let payload = parse_stdout_json(&command);
+    assert_eq!(payload["edits_applied"].as_u64(), Some(2));
+    assert_eq!(payload["file"].as_str(), Some(output));
+    assert_json_path_eq(&payload, "source_path", source);
+    assert_json_path_eq(&payload, "target_path", output);
+
+    let after_source = fs::read(&source_path).expect("read source after output mode");
+    assert_eq!(
+        before_source, after_source,
+        "source workbook changed during --output mode"
+    );
+
+    let output_book = umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output");
+    let output_sheet = output_book
+        .get_sheet_by_name("Sheet1")
+        .expect("output sheet exists");
+    assert_eq!(output_sheet.get_cell("B2").expect("B2").get_value(), "17");
+    assert_eq!(
+        output_sheet.get_cell("C2").expect("C2").get_formula(),
+        "B2*4"
+    );
+}
+
+#[test]
+fn cli_edit_locale_stores_numbers_and_dates() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("edit-locale.xlsx");
+    write_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
+
+    let edit = run_cli(&[
+        "edit",
+        file,
+        "Sheet1",
+        "--locale",
+        "de-DE",
+        "A2=1.5",
+        "B2=1.234,56",
+        "C2=31.12.2024",
+    ]);
+    assert!(edit.status.success(), "stderr: {:?}", edit.stderr);
+
+    let ops_path = tmp.path().join("fill-locale.json");
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"range","range":"B3:B4"},"value":"-0,75","locale":"de-DE"}]}"#,
+    );
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
+    let fill = run_cli(&["transform-batch", file, "--ops", &ops_ref, "--in-place"]);
+    assert!(fill.status.success(), "stderr: {:?}", fill.stderr);
+
+    let read = run_cli(&[
+        "read-table",
+        file,
+        "--sheet",
+        "Sheet1",
+        "--table-format",
+        "json",
+    ]);
+    assert!(read.status.success(), "stderr: {:?}", read.stderr);
+    let rows = parse_stdout_json(&read)["rows"].clone();
+    assert_eq!(rows[0]["Name"]["kind"], "Text");
+    assert_eq!(rows[0]["Name"]["value"], "1.5");
+    assert_eq!(rows[0]["Amount"]["kind"], "Number");
+    assert_eq!(rows[0]["Amount"]["value"], 1234.56);
+    assert_eq!(rows[0]["Total"]["kind"], "Date");
+    assert_eq!(rows[0]["Total"]["value"], "2024-12-31");
+    assert_eq!(rows[1]["Amount"]["value"], -0.75);
+    assert_eq!(rows[2]["Amount"]["value"], -0.75);
+
+    assert_error_code(
+        &["edit", file, "Sheet1", "--locale", "xx-YY", "B2=1"],
+        "INVALID_ARGUMENT",
+    );
+}
+
+#[test]
+fn cli_edit_mode_matrix_rejects_conflicts() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("edit-mode-matrix.xlsx");
+    write_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
+
+    assert_invalid_argument(&["edit", file, "Sheet1", "--dry-run", "--in-place", "B2=1"]);
+    assert_invalid_argument(&[
+        "edit",
+        file,
+        "Sheet1",
+        "--dry-run",
+        "--output",
+        "out.xlsx",
+        "B2=1",
+    ]);
+    assert_invalid_argument(&[
+        "edit",
+        file,
+        "Sheet1",
+        "--in-place",
+        "--output",
+        "out.xlsx",
+        "B2=1",
+    ]);
+    assert_invalid_argument(&["edit", file, "Sheet1", "--force", "B2=1"]);
+    assert_invalid_argument(&["edit", file, "Sheet1", "--output", file, "B2=1"]);
+}
+
+#[test]
+fn cli_edit_dry_run_preflight_fails_for_missing_sheet() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("edit-dry-run-missing-sheet.xlsx");
+    write_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
+
+    let err = assert_error_code(
+        &["edit", file, "NoSuchSheet", "--dry-run", "A1=1"],
+        "SHEET_NOT_FOUND",
+    );
+    assert_eq!(err["message"], "sheet 'NoSuchSheet' was not found");
+    assert_eq!(
+        err["try_this"],
+        "run `asp read sheets <file>` to inspect valid names"
+    );
+}
+
+#[test]
+fn cli_errors_use_machine_envelope() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("read.xlsx");
+    write_fixture(&workbook_path);
+
+    let output = run_cli(&[
+        "formula-map",
+        workbook_path.to_str().expect("path utf8"),
+        "Shet1",
+    ]);
+    assert!(!output.status.success(), "command unexpectedly succeeded");
+
+    let err = parse_stderr_json(&output);
+    assert_eq!(err["code"], "SHEET_NOT_FOUND");
+    assert_eq!(err["did_you_mean"], "Sheet1");
+    assert!(
+        err["message"]
+            .as_str()
+            .unwrap_or_default()
+            .contains("was not found")
+    );
+    assert!(
+        err["try_this"]
+            .as_str()
+            .unwrap_or_default()
+            .contains("read sheets")
+    );
+}
+
+#[test]
+fn docs_guardrail_relative_mode_literals_are_canonical() {
+    let readme = read_repo_doc("README.md");
+    let npm_readme = read_repo_doc("npm/agent-spreadsheet/README.md");
+
+    assert!(
+        readme.contains("relative_mode` valid values: `excel`, `abs_cols`, `abs_rows`"),
+        "README should document canonical relative_mode literals"
+    );
+
+    for doc in [&readme, &npm_readme] {
+        assert!(
+            !doc.contains("fully_relative"),
+            "docs should not advertise invalid relative_mode literal fully_relative"
+        );
+    }
+}
+
+#[test]
+fn cli_legacy_global_format_csv_returns_output_format_unsupported_envelope() {
+    let output = run_cli(&["--format", "csv", "list-sheets", "/tmp/does-not-exist.xlsx"]);
+    assert!(!output.status.success(), "command unexpectedly succeeded");
+
+    let err = parse_stderr_json(&output);
+    assert_eq!(err["code"], "OUTPUT_FORMAT_UNSUPPORTED");
+    assert!(
+        err["message"]
+            .as_str()
+            .unwrap_or_default()
+            .contains("csv output is not implemented")
+    );
+}
+
+#[test]
+fn cli_legacy_global_format_json_is_accepted_for_existing_commands() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("legacy-format-json.xlsx");
+    write_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
+
+    let output = run_cli(&["--format", "json", "list-sheets", file]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["sheets"].as_array().map(Vec::len), Some(2));
+}
+
+#[cfg(feature = "recalc-formualizer")]
+#[test]
+fn cli_recalculate_flow_runs_after_copy_and_edit() {
+    let tmp = tempdir().expect("tempdir");
+    let original = tmp.path().join("original.xlsx");
+    let modified = tmp.path().join("modified.xlsx");
+    write_fixture(&original);
+
+    let copy = run_cli(&[
+        "copy",
+        original.to_str().expect("path utf8"),
+        modified.to_str().expect("path utf8"),
+    ]);
+    assert!(copy.status.success(), "stderr: {:?}", copy.stderr);
+
+    let edit = run_cli(&[
+        "edit",
+        modified.to_str().expect("path utf8"),
+        "Sheet1",
+        "B2=25",
+    ]);
+    assert!(edit.status.success(), "stderr: {:?}", edit.stderr);
+
+    let recalc = run_cli(&["recalculate", modified.to_str().expect("path utf8")]);
+    assert!(recalc.status.success(), "stderr: {:?}", recalc.stderr);
+    let recalc_payload = parse_stdout_json(&recalc);
+    assert_eq!(recalc_payload["backend"], "formualizer");
+    assert!(recalc_payload["duration_ms"].as_u64().is_some());
+
+    let diff = run_cli(&[
+        "diff",
+        original.to_str().expect("path utf8"),
+        modified.to_str().expect("path utf8"),
+    ]);
+    assert!(diff.status.success(), "stderr: {:?}", diff.stderr);
+    let diff_payload = parse_stdout_json(&diff);
+    assert!(diff_payload["change_count"].as_u64().unwrap_or(0) >= 1);
+}
+
+// ─── 3203: Write preflight formula parse policy tests ───
+
+#[test]
+fn cli_edit_invalid_formula_default_fail_returns_error_envelope() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("edit-formula-fail.xlsx");
+    write_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
+
+    // "==SUM(A1:A10" is a formula (double = means formula) with unclosed paren
+    let output = run_cli(&["edit", file, "Sheet1", "B2==SUM(A1:A10"]);
+    assert!(
+        !output.status.success(),
+        "command should fail for invalid formula"
+    );
+
+    let error = parse_stderr_json(&output);
+    assert_eq!(error["code"], "FORMULA_PARSE_FAILED");
+}
+
+#[test]
+fn cli_edit_invalid_formula_warn_mode_partial_apply_with_diagnostics() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("edit-formula-warn.xlsx");
+    write_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
+
+    let output = run_cli(&[
+        "edit",
+        file,
+        "Sheet1",
+        "B2=42",
+        "C2==SUM(A1:A10",
+        "--formula-parse-policy",
+        "warn",
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+
+    // B2=42 (value, not formula) should apply; C2 formula is invalid → skipped
+    assert_eq!(payload["edits_applied"], 1);
+    assert_eq!(payload["recalc_needed"], true);
+
+    let diagnostics = &payload["formula_parse_diagnostics"];
+    assert!(diagnostics.is_object(), "expected diagnostics object");
+    assert_eq!(diagnostics["policy"], "warn");
+    assert!(diagnostics["total_errors"].as_u64().unwrap_or(0) > 0);
+}
+
+#[test]
+fn cli_edit_invalid_formula_off_mode_permissive_write() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("edit-formula-off.xlsx");
+    write_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
+
+    let output = run_cli(&[
+        "edit",
+        file,
+        "Sheet1",
+        "B2==SUM(A1:A10",
+        "--formula-parse-policy",
+        "off",
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["edits_applied"], 1);
+    assert!(payload["formula_parse_diagnostics"].is_null());
+}
+
+#[test]
+fn cli_transform_batch_fill_invalid_formula_warn_mode_partial_apply() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("transform-fill-formula-warn.xlsx");
+    let ops_path = tmp.path().join("ops.json");
+    write_fixture(&workbook_path);
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[
+            {"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"cells","cells":["B2"]},"value":"SUM(A1:A10","is_formula":true},
+            {"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"cells","cells":["B3"]},"value":"42"}
+        ]}"#,
+    );
+
+    let file = workbook_path.to_str().expect("path utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
+
+    let output = run_cli(&[
+        "transform-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
+        "--formula-parse-policy",
+        "warn",
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+
+    // Only the second op (value fill) should apply; first (bad formula) skipped
+    assert_eq!(payload["op_count"], 1);
+    assert_eq!(payload["applied_count"], 1);
+
+    let diagnostics = &payload["formula_parse_diagnostics"];
+    assert!(diagnostics.is_object(), "expected diagnostics object");
+    assert_eq!(diagnostics["policy"], "warn");
+    assert!(diagnostics["total_errors"].as_u64().unwrap_or(0) > 0);
+}
+
+#[test]
+fn cli_transform_batch_fill_invalid_formula_fail_mode_aborts_no_output() {
+    let tmp = tempdir().expect("tempdir");
+    let source_path = tmp.path().join("transform-fill-formula-fail.xlsx");
+    let output_path = tmp.path().join("transform-fill-formula-fail-output.xlsx");
+    let ops_path = tmp.path().join("ops.json");
+    write_fixture(&source_path);
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[
+            {"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"cells","cells":["B2"]},"value":"SUM(A1:A10","is_formula":true}
+        ]}"#,
+    );
+
+    let file = source_path.to_str().expect("path utf8");
+    let out = output_path.to_str().expect("output path utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
+
+    let output = run_cli(&[
+        "transform-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--output",
+        out,
+        "--formula-parse-policy",
+        "fail",
+    ]);
+    assert!(!output.status.success(), "command should fail");
+    let error = parse_stderr_json(&output);
+    assert_eq!(error["code"], "FORMULA_PARSE_FAILED");
+
+    // No output file should be created
+    assert!(
+        !output_path.exists(),
+        "output file should not exist on fail mode abort"
+    );
+}
+
+#[test]
+fn cli_transform_batch_dry_run_formula_diagnostics_parity() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("transform-formula-dryrun.xlsx");
+    let ops_path = tmp.path().join("ops.json");
+    write_fixture(&workbook_path);
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[
+            {"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"cells","cells":["B2"]},"value":"SUM(A1:A10","is_formula":true},
+            {"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"cells","cells":["B3"]},"value":"42"}
+        ]}"#,
+    );
+
+    let file = workbook_path.to_str().expect("path utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
+
+    let output = run_cli(&[
+        "transform-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--dry-run",
+        "--formula-parse-policy",
+        "warn",
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+
+    let diagnostics = &payload["formula_parse_diagnostics"];
+    assert!(
+        diagnostics.is_object(),
+        "expected diagnostics object in dry-run"
+    );
+    assert_eq!(diagnostics["policy"], "warn");
+    assert!(diagnostics["total_errors"].as_u64().unwrap_or(0) > 0);
+
+    // Source should be untouched
+    let before = std::fs::read(&workbook_path).expect("read source");
+    let after = std::fs::read(&workbook_path).expect("read source again");
+    assert_eq!(before, after, "dry-run mutated source");
+}
+
+#[test]
+fn cli_edit_valid_formula_succeeds_with_default_policy() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("edit-formula-valid.xlsx");
+    write_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
+
+    let output = run_cli(&["edit", file, "Sheet1", "B2==SUM(A1:A4)"]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["edits_applied"], 1);
+    // No diagnostics when formula is valid
+    assert!(
+        payload["formula_parse_diagnostics"].is_null(),
+        "no diagnostics for valid formula"
+    );
+}
+
+// ─── 3204: structure-batch tokenizer policy + diagnostics tests ───
+
+#[test]
+fn cli_structure_batch_rename_with_malformed_formula_warn_mode() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("structure-rename-warn.xlsx");
+
+    {
+        let mut workbook = umya_spreadsheet::new_file();
+        {
+            let sheet = workbook
+                .get_sheet_by_name_mut("Sheet1")
+                .expect("default sheet");
+            sheet.get_cell_mut("A1").set_value("Hello");
+        }
+        workbook.new_sheet("Sheet2").expect("add Sheet2");
+        {
+            let sheet = workbook.get_sheet_by_name_mut("Sheet2").expect("Sheet2");
+            sheet.get_cell_mut("A1").set_value_number(10.0);
+            sheet.get_cell_mut("B1").set_formula("SUM(\"Sheet1!A1:A10)");
+        }
+        umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write");
+    }
+
+    let ops_path = tmp.path().join("ops.json");
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"rename_sheet","old_name":"Sheet1","new_name":"Renamed"}]}"#,
+    );
+
+    let file = workbook_path.to_str().expect("path utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
+
+    let output = run_cli(&[
+        "structure-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
+        "--formula-parse-policy",
+        "warn",
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["applied_count"], 1);
+
+    let diagnostics = &payload["formula_parse_diagnostics"];
+    assert!(
+        diagnostics.is_object(),
+        "expected formula_parse_diagnostics object"
+    );
+    assert_eq!(diagnostics["policy"], "warn");
+    assert!(diagnostics["total_errors"].as_u64().unwrap_or(0) > 0);
+}
+
+#[test]
+fn cli_structure_batch_rename_with_malformed_formula_fail_mode() {
+    let tmp = tempdir().expect("tempdir");
+    let source_path = tmp.path().join("structure-rename-fail.xlsx");
+    let output_path = tmp.path().join("structure-rename-fail-output.xlsx");
+
+    {
+        let mut workbook = umya_spreadsheet::new_file();
+        {
+            let sheet = workbook
+                .get_sheet_by_name_mut("Sheet1")
+                .expect("default sheet");
+            sheet.get_cell_mut("A1").set_value("Hello");
+        }
+        workbook.new_sheet("Sheet2").expect("add Sheet2");
+        {
+            let sheet = workbook.get_sheet_by_name_mut("Sheet2").expect("Sheet2");
+            sheet.get_cell_mut("A1").set_value_number(10.0);
+            sheet.get_cell_mut("B1").set_formula("SUM(\"Sheet1!A1:A10)");
+        }
+        umya_spreadsheet::writer::xlsx::write(&workbook, &source_path).expect("write");
+    }
+
+    let ops_path = tmp.path().join("ops.json");
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"rename_sheet","old_name":"Sheet1","new_name":"Renamed"}]}"#,
+    );
+
+    let file = source_path.to_str().expect("path utf8");
+    let out = output_path.to_str().expect("output path utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
+
+    let output = run_cli(&[
+        "structure-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--output",
+        out,
+        "--formula-parse-policy",
+        "fail",
+    ]);
+    assert!(!output.status.success(), "should fail");
+    let error = parse_stderr_json(&output);
+    assert_eq!(error["code"], "FORMULA_PARSE_FAILED");
+    assert!(
+        !output_path.exists(),
+        "output should not be created on fail"
+    );
+}
+
+#[test]
+fn cli_structure_batch_insert_rows_with_malformed_formula_warn_mode() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("structure-insert-warn.xlsx");
+
+    {
+        let mut workbook = umya_spreadsheet::new_file();
+        {
+            let sheet = workbook
+                .get_sheet_by_name_mut("Sheet1")
+                .expect("default sheet");
+            sheet.get_cell_mut("A1").set_value_number(1.0);
+            sheet.get_cell_mut("A2").set_value_number(2.0);
+        }
+        workbook.new_sheet("Sheet2").expect("add Sheet2");
+        {
+            let sheet = workbook.get_sheet_by_name_mut("Sheet2").expect("Sheet2");
+            sheet.get_cell_mut("A1").set_formula("SUM(\"Sheet1!A1:A10)");
+            sheet.get_cell_mut("B1").set_formula("Sheet1!A1+1");
+        }
+        umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write");
+    }
+
+    let ops_path = tmp.path().join("ops.json");
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"insert_rows","sheet_name":"Sheet1","at_row":1,"count":2}]}"#,
+    );
+
+    let file = workbook_path.to_str().expect("path utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
+
+    let output = run_cli(&[
+        "structure-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
+        "--formula-parse-policy",
+        "warn",
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["applied_count"], 1);
+
+    let diagnostics = &payload["formula_parse_diagnostics"];
+    assert!(
+        diagnostics.is_object(),
+        "expected formula_parse_diagnostics object"
+    );
+    assert_eq!(diagnostics["policy"], "warn");
+    assert!(diagnostics["total_errors"].as_u64().unwrap_or(0) > 0);
+}
+
+#[test]
+fn cli_structure_batch_rename_defined_name_malformed_formula_warn_diagnostics() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("structure-defname-warn.xlsx");
+
+    {
+        let mut workbook = umya_spreadsheet::new_file();
+        {
+            let sheet = workbook
+                .get_sheet_by_name_mut("Sheet1")
+                .expect("default sheet");
+            sheet.get_cell_mut("A1").set_value_number(42.0);
+        }
+        let workbook_scoped_bad_range = {
+            let sheet = workbook
+                .get_sheet_by_name_mut("Sheet1")
+                .expect("default sheet");
+            sheet
+                .add_defined_name("BadRange", "=SUM(\"abc)")
+                .expect("defined name BadRange");
+            sheet
+                .get_defined_names()
+                .first()
+                .expect("sheet defined name")
+                .clone()
+        };
+        workbook.add_defined_names(workbook_scoped_bad_range);
+
+        umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write");
+    }
+
+    let ops_path = tmp.path().join("ops.json");
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"rename_sheet","old_name":"Sheet1","new_name":"Data"}]}"#,
+    );
+
+    let file = workbook_path.to_str().expect("path utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
+
+    let output = run_cli(&[
+        "structure-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
+        "--formula-parse-policy",
+        "warn",
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+
+    let diagnostics = &payload["formula_parse_diagnostics"];
+    assert!(
+        diagnostics.is_object(),
+        "expected formula_parse_diagnostics"
+    );
+    assert_eq!(diagnostics["policy"], "warn");
+    assert!(diagnostics["total_errors"].as_u64().unwrap_or(0) > 0);
+
+    let groups = diagnostics["groups"].as_array().expect("groups array");
+    assert!(!groups.is_empty(), "should have at least one error group");
+    let first_group = &groups[0];
+    assert_eq!(
+        first_group["sheet_name"], "[DefinedName]",
+        "defined name errors should use [DefinedName] as sheet_name"
+    );
+}
+
+#[test]
+fn cli_structure_batch_no_malformed_formulas_no_diagnostics() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("structure-clean.xlsx");
+    write_fixture(&workbook_path);
+
+    let ops_path = tmp.path().join("ops.json");
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"rename_sheet","old_name":"Summary","new_name":"Results"}]}"#,
+    );
+
+    let file = workbook_path.to_str().expect("path utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
+
+    let output = run_cli(&[
+        "structure-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
+        "--formula-parse-policy",
+        "warn",
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert!(
+        payload["formula_parse_diagnostics"].is_null(),
+        "should have no diagnostics when all formulas are valid"
+    );
+}
+
+#[test]
+fn cli_structure_batch_pattern_renames_sheets_and_names_with_references() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("plan-years.xlsx");
+    let mut workbook = umya_spreadsheet::new_file();
+    workbook
+        .set_sheet_name(0, "FY23 Plan")
+        .expect("rename default sheet");
+    for (sheet_name, value) in [("FY23 Plan", 10.0), ("FY24 Plan", 20.0)] {
+        if workbook.get_sheet_by_name(sheet_name).is_none() {
+            workbook.new_sheet(sheet_name).expect("add plan sheet");
+        }
+        workbook
+            .get_sheet_by_name_mut(sheet_name)
+            .expect("plan sheet exists")
+            .get_cell_mut("B1")
+            .set_value_number(value);
+    }
+    workbook.new_sheet("Notes").expect("add notes sheet");
+    {
+        let notes = workbook
+            .get_sheet_by_name_mut("Notes")
+            .expect("notes sheet exists");
+        notes
+            .get_cell_mut("A1")
+            .set_formula("'FY24 Plan'!B1-'FY23 Plan'!B1");
+        notes.get_cell_mut("A2").set_formula("fy24_revenue*2");
+        notes
+            .add_defined_name("fy24_revenue", "'FY24 Plan'!$B$1")
+            .expect("defined name fy24_revenue");
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+
+    let file = workbook_path.to_str().expect("path utf8");
+    let ops_path = tmp.path().join("ops.json");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
+
+    // Both plan sheets would become "FY Plan": nothing is renamed.
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"rename_sheets","find":"^FY2\\d","replace":"FY","match_mode":"regex"}]}"#,
+    );
+    let output = run_cli(&[
+        "structure-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
+    ]);
+    assert!(!output.status.success(), "colliding rename should fail");
+    let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("reread");
+    assert!(book.get_sheet_by_name("FY24 Plan").is_some());
+
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"rename_names","find":"fy24_revenue","replace":"FY25"}]}"#,
+    );
+    let output = run_cli(&[
+        "structure-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
+    ]);
+    assert!(!output.status.success(), "cell-reference name should fail");
+
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[
+            {"kind":"rename_sheets","find":"FY24","replace":"FY25","match_mode":"prefix"},
+            {"kind":"rename_sheets","find":"FY23","replace":"FY24","match_mode":"prefix"},
+            {"kind":"rename_names","find":"fy24_","replace":"fy25_","match_mode":"prefix"}
+        ]}"#,
+    );
+    let output = run_cli(&[
+        "structure-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+
+    let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("reread");
+    let names: Vec<&str> = book
+        .get_sheet_collection()
+        .iter()
+        .map(|sheet| sheet.get_name())
+        .collect();
+    assert_eq!(names, vec!["FY24 Plan", "FY25 Plan", "Notes"]);
+    let notes = book.get_sheet_by_name("Notes").expect("notes sheet");
+    assert_eq!(
+        notes.get_cell("A1").expect("A1").get_formula(),
+        "'FY25 Plan'!B1-'FY24 Plan'!B1"
+    );
+    assert_eq!(
+        notes.get_cell("A2").expect("A2").get_formula(),
+        "fy25_revenue*2"
+    );
+    let listed = run_cli(&["named-ranges", file]);
+    assert!(listed.status.success(), "stderr: {:?}", listed.stderr);
+    let payload = parse_stdout_json(&listed);
+    let names: Vec<&str> = payload["items"]
+        .as_array()
+        .expect("items")
+        .iter()
+        .filter_map(|item| item["name"].as_str())
+        .collect();
+    assert!(names.contains(&"fy25_revenue"), "payload: {payload}");
+    assert!(!names.contains(&"fy24_revenue"), "payload: {payload}");
+}
+
+#[test]
+fn cli_structure_batch_copy_sheet_between_workbooks() {
+    let tmp = tempdir().expect("tempdir");
+    let source_path = tmp.path().join("template.xlsx");
+    let mut source = umya_spreadsheet::new_file();
+    source
+        .set_sheet_name(0, "Template")
+        .expect("rename default sheet");
+    source.new_sheet("Lookup").expect("add lookup sheet");
+    {
+        let sheet = source
+            .get_sheet_by_name_mut("Template")
+            .expect("template sheet");
+        sheet.get_cell_mut("A1").set_value("Quarterly report");
+        sheet.get_style_mut("A1").get_font_mut().set_bold(true);
+        sheet.add_merge_cells("A1:C1");
+        sheet.get_cell_mut("B2").set_value_number(5.0);
+        sheet.get_cell_mut("B3").set_formula("Template!B2*2");
+        sheet.get_cell_mut("B4").set_formula("Lookup!A1");
+        sheet.get_cell_mut("A6").set_value("Item");
+        sheet.get_cell_mut("B6").set_value("Amount");
+        sheet.get_cell_mut("A7").set_value("Widget");
+        sheet.get_cell_mut("B7").set_value_number(3.0);
+        sheet.get_cell_mut("B8").set_formula("SUM(Data[Amount])");
+        let mut table = umya_spreadsheet::structs::Table::new("Data", ("A6", "B7"));
+        table.set_display_name("Data");
+        sheet.add_table(table);
+    }
+    umya_spreadsheet::writer::xlsx::write(&source, &source_path).expect("write source");
+    let rules_path = tmp.path().join("rules.json");
+    write_ops_payload(
+        &rules_path,
+        r##"{"ops":[{"kind":"set_conditional_format","sheet_name":"Template","target_range":"B2:B4","rule":{"kind":"expression","formula":"B2>3"},"style":{"fill_color":"#FFF2CC"}}]}"##,
+    );
+    let rules_ref = format!("@{}", rules_path.to_str().expect("rules utf8"));
+    let output = run_cli(&[
+        "rules-batch",
+        source_path.to_str().expect("source utf8"),
+        "--ops",
+        rules_ref.as_str(),
+        "--in-place",
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+
+    let dest_path = tmp.path().join("pack.xlsx");
+    let mut dest = umya_spreadsheet::new_file();
+    {
+        let sheet = dest.get_sheet_by_name_mut("Sheet1").expect("sheet1");
+        sheet.get_cell_mut("A1").set_value("Item");
+        sheet.get_cell_mut("A2").set_value("Gadget");
+        let mut table = umya_spreadsheet::structs::Table::new("Data", ("A1", "A2"));
+        table.set_display_name("Data");
+        sheet.add_table(table);
+    }
+    umya_spreadsheet::writer::xlsx::write(&dest, &dest_path).expect("write destination");
+
+    let ops_path = tmp.path().join("ops.json");
+    write_ops_payload(
+        &ops_path,
+        &format!(
+            r#"{{"ops":[{{"kind":"copy_sheet","source_file":{},"source_sheet":"Template","new_name":"Q3 Report","position":0}}]}}"#,
+            serde_json::to_string(source_path.to_str().expect("source utf8")).expect("json path")
+        ),
+    );
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
+    let file = dest_path.to_str().expect("dest utf8");
+    let output = run_cli(&[
+        "structure-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    let warnings = payload["warnings"].as_array().expect("warnings");
+    for code in [
+        "WARN_COPY_SHEET_MISSING_SHEETS",
+        "WARN_COPY_SHEET_TABLE_RENAMED",
+    ] {
+        assert!(
+            warnings.iter().any(|warning| warning["code"] == code),
+            "missing {code}: {payload}"
+        );
+    }
+
+    let book = umya_spreadsheet::reader::xlsx::read(&dest_path).expect("reread destination");
+    let names: Vec<&str> = book
+        .get_sheet_collection()
+        .iter()
+        .map(|sheet| sheet.get_name())
+        .collect();
+    assert_eq!(names, vec!["Q3 Report", "Sheet1"]);
+    let copied = book.get_sheet_by_name("Q3 Report").expect("copied sheet");
+    assert_eq!(copied.get_value("A1"), "Quarterly report");
+    assert!(
+        *copied
+            .get_cell("A1")
+            .expect("A1")
+            .get_style()
+            .get_font()
+            .expect("font")
+            .get_bold()
+    );
+    let merges: Vec<String> = copied
+        .get_merge_cells()
+        .iter()
+        .map(|range| range.get_range())
+        .collect();
+    assert_eq!(merges, vec!["A1:C1".to_string()]);
+    assert_eq!(copied.get_conditional_formatting_collection().len(), 1);
+    assert_eq!(
+        copied.get_cell("B3").expect("B3").get_formula(),
+        "'Q3 Report'!B2*2"
+    );
+    assert_eq!(
+        copied.get_cell("B4").expect("B4").get_formula(),
+        "Lookup!A1"
+    );
+    assert_eq!(
+        copied.get_cell("B8").expect("B8").get_formula(),
+        "SUM(Data_2[Amount])"
+    );
+    assert_eq!(copied.get_tables()[0].get_display_name(), "Data_2");
+    let original = book.get_sheet_by_name("Sheet1").expect("sheet1");
+    assert_eq!(original.get_tables()[0].get_display_name(), "Data");
+
+    let output = run_cli(&[
+        "structure-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
+    ]);
+    assert!(
+        !output.status.success(),
+        "copying onto an existing sheet name should fail"
+    );
+}
+
+#[test]
+fn cli_structure_batch_sets_tab_colors_visibility_and_order() {
+    let tmp = tempdir().expect("tempdir");
+    let path = tmp.path().join("tabs.xlsx");
+    let mut book = umya_spreadsheet::new_file();
+    book.new_sheet("Inputs").expect("add inputs");
+    book.new_sheet("Lookups").expect("add lookups");
+    book.new_sheet("Summary").expect("add summary");
+    umya_spreadsheet::writer::xlsx::write(&book, &path).expect("write workbook");
+    let file = path.to_str().expect("path utf8");
+
+    let ops_path = tmp.path().join("ops.json");
+    write_ops_payload(
+        &ops_path,
+        r##"{"ops":[{"kind":"set_tab_color","sheet_name":"Inputs","color":"#FFC000"},{"kind":"hide_sheet","sheet_name":"Lookups","very_hidden":true},{"kind":"hide_sheet","sheet_name":"Sheet1"},{"kind":"reorder_sheets","order":["Summary","Inputs"]}]}"##,
+    );
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
+    let output = run_cli(&[
+        "structure-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+
+    let list = run_cli(&["list-sheets", file]);
+    assert!(list.status.success(), "stderr: {:?}", list.stderr);
+    let payload = parse_stdout_json(&list);
+    let sheets = payload["sheets"].as_array().expect("sheets");
+    let summary: Vec<(String, String, bool)> = sheets
+        .iter()
+        .map(|sheet| {
+            (
+                sheet["name"].as_str().expect("name").to_string(),
+                sheet["visibility"]
+                    .as_str()
+                    .expect("visibility")
+                    .to_string(),
+                sheet["visible"].as_bool().expect("visible"),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("Summary".to_string(), "visible".to_string(), true),
+            ("Inputs".to_string(), "visible".to_string(), true),
+            ("Sheet1".to_string(), "hidden".to_string(), false),
+            ("Lookups".to_string(), "very_hidden".to_string(), false),
+        ]
+    );
+    assert_eq!(sheets[1]["tab_color"], "FFFFC000");
+    assert!(sheets[0].get("tab_color").is_none());
+
+    // Every other sheet is hidden, so hiding the last visible ones must fail.
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"hide_sheet","sheet_name":"Summary"},{"kind":"hide_sheet","sheet_name":"Inputs"}]}"#,
+    );
+    let output = run_cli(&[
+        "structure-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
+    ]);
+    assert!(
+        !output.status.success(),
+        "hiding every sheet should be rejected"
+    );
+
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"unhide_sheet","sheet_name":"Lookups"},{"kind":"set_tab_color","sheet_name":"Inputs"}]}"#,
+    );
+    let output = run_cli(&[
+        "structure-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let book = umya_spreadsheet::reader::xlsx::read(&path).expect("reread workbook");
+    let lookups = book.get_sheet_by_name("Lookups").expect("lookups");
+    assert_ne!(lookups.get_sheet_state(), "veryHidden");
+    assert_ne!(lookups.get_sheet_state(), "hidden");
+    assert!(
+        book.get_sheet_by_name("Inputs")
+            .expect("inputs")
+            .get_tab_color()
+            .is_none()
+    );
+}
+
+#[test]
+fn cli_structure_batch_copy_range_with_malformed_formula_warn_mode_diagnostics() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("structure-copy-warn.xlsx");
+
+    {
+        let mut workbook = umya_spreadsheet::new_file();
+        {
+            let sheet = workbook
+                .get_sheet_by_name_mut("Sheet1")
+                .expect("default sheet");
+            sheet.get_cell_mut("A1").set_value_number(1.0);
+            sheet.get_cell_mut("A2").set_value_number(2.0);
+            // Malformed formula that parse_base_formula will fail on
+            sheet.get_cell_mut("B1").set_formula("SUM(A1:A2");
+        }
+        umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write");
+    }
+
+    let ops_path = tmp.path().join("ops.json");
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"copy_range","sheet_name":"Sheet1","src_range":"A1:B2","dest_anchor":"D1","include_styles":false,"include_formulas":true}]}"#,
+    );
+
+    let file = workbook_path.to_str().expect("path utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
+
+    let output = run_cli(&[
+        "structure-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
+        "--formula-parse-policy",
+        "warn",
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["applied_count"], 1);
+
+    let diagnostics = &payload["formula_parse_diagnostics"];
+    assert!(
+        diagnostics.is_object(),
+        "expected formula_parse_diagnostics for copy with malformed formula"
+    );
+    assert_eq!(diagnostics["policy"], "warn");
+    assert!(diagnostics["total_errors"].as_u64().unwrap_or(0) > 0);
+}
+
+#[test]
+fn cli_structure_batch_copy_range_with_malformed_formula_fail_mode_aborts() {
+    let tmp = tempdir().expect("tempdir");
+    let source_path = tmp.path().join("structure-copy-fail.xlsx");
+    let output_path = tmp.path().join("structure-copy-fail-output.xlsx");
+
+    {
+        let mut workbook = umya_spreadsheet::new_file();
+        {
+            let sheet = workbook
+                .get_sheet_by_name_mut("Sheet1")
+                .expect("default sheet");
+            sheet.get_cell_mut("A1").set_value_number(1.0);
+            sheet.get_cell_mut("B1").set_formula("SUM(A1:A2");
+        }
+        umya_spreadsheet::writer::xlsx::write(&workbook, &source_path).expect("write");
+    }
+
+    let ops_path = tmp.path().join("ops.json");
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"copy_range","sheet_name":"Sheet1","src_range":"A1:B1","dest_anchor":"D1","include_styles":false,"include_formulas":true}]}"#,
+    );
+
+    let file = source_path.to_str().expect("path utf8");
+    let out = output_path.to_str().expect("output path utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
+
+    let output = run_cli(&[
+        "structure-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--output",
+        out,
+        "--formula-parse-policy",
+        "fail",
+    ]);
+    assert!(!output.status.success(), "should fail with fail policy");
+    let error = parse_stderr_json(&output);
+    assert_eq!(error["code"], "FORMULA_PARSE_FAILED");
+    assert!(
+        !output_path.exists(),
+        "output should not be created on fail mode abort"
+    );
+}
+
+// ─── 3205: Rules-batch formula parse policy tests ───
+
+#[test]
+fn cli_rules_batch_invalid_dv_formula_warn_mode_partial_apply() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("rules-dv-warn.xlsx");
+    let ops_path = tmp.path().join("ops.json");
+    write_fixture(&workbook_path);
+    // Two ops: one valid list DV, one with a malformed custom formula (unclosed paren)
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[
+            {"kind":"set_data_validation","sheet_name":"Sheet1","target_range":"B2:B4","validation":{"kind":"list","formula1":"\"A,B,C\""}},
+            {"kind":"set_data_validation","sheet_name":"Sheet1","target_range":"C2:C4","validation":{"kind":"custom","formula1":"=AND(C2>0,LEN(C2"}}
+        ]}"#,
+    );
+
+    let file = workbook_path.to_str().expect("path utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
+
+    let output = run_cli(&[
+        "rules-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
+        "--formula-parse-policy",
+        "warn",
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+
+    // The custom formula with unclosed paren should be skipped; the valid list DV should apply
+    assert_eq!(
+        payload["op_count"].as_u64().unwrap(),
+        2,
+        "op_count should reflect total ops in payload"
+    );
+    assert_eq!(
+        payload["applied_count"].as_u64().unwrap(),
+        1,
+        "only the valid op should be applied"
+    );
+    let diagnostics = &payload["formula_parse_diagnostics"];
+    assert!(
+        diagnostics.is_object(),
+        "expected formula_parse_diagnostics object"
+    );
+    assert_eq!(diagnostics["policy"], "warn");
+    assert!(diagnostics["total_errors"].as_u64().unwrap_or(0) > 0);
+}
+
+#[test]
+fn cli_rules_batch_invalid_dv_formula_fail_mode_aborts() {
+    let tmp = tempdir().expect("tempdir");
+    let source_path = tmp.path().join("rules-dv-fail.xlsx");
+    let output_path = tmp.path().join("rules-dv-fail-output.xlsx");
+    let ops_path = tmp.path().join("ops.json");
+    write_fixture(&source_path);
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[
+            {"kind":"set_data_validation","sheet_name":"Sheet1","target_range":"C2:C4","validation":{"kind":"custom","formula1":"=AND(C2>0,LEN(C2"}}
+        ]}"#,
+    );
+
+    let file = source_path.to_str().expect("path utf8");
+    let out = output_path.to_str().expect("output path utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
+
+    let output = run_cli(&[
+        "rules-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--output",
+        out,
+        "--formula-parse-policy",
+        "fail",
+    ]);
+    assert!(!output.status.success(), "command should fail");
+    let error = parse_stderr_json(&output);
+    assert_eq!(error["code"], "FORMULA_PARSE_FAILED");
+    assert!(
+        !output_path.exists(),
+        "output file should not exist on fail mode abort"
+    );
+}
+
+#[test]
+fn cli_rules_batch_invalid_cf_formula_warn_mode_with_diagnostics() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("rules-cf-warn.xlsx");
+    let ops_path = tmp.path().join("ops.json");
+    write_fixture(&workbook_path);
+    // One valid CF expression, one with malformed formula (unclosed paren)
+    write_ops_payload(
+        &ops_path,
+        r##"{"ops":[
+            {"kind":"add_conditional_format","sheet_name":"Sheet1","target_range":"A1:A10","rule":{"kind":"expression","formula":"A1>0"},"style":{"fill_color":"#FF0000"}},
+            {"kind":"add_conditional_format","sheet_name":"Sheet1","target_range":"B1:B10","rule":{"kind":"expression","formula":"AND(B1>0,LEN(B1"},"style":{"fill_color":"#00FF00"}}
+        ]}"##,
+    );
+
+    let file = workbook_path.to_str().expect("path utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
+
+    let output = run_cli(&[
+        "rules-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
+        "--formula-parse-policy",
+        "warn",
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+
+    assert_eq!(
+        payload["op_count"].as_u64().unwrap(),
+        2,
+        "op_count should reflect total ops in payload"
+    );
+    assert_eq!(
+        payload["applied_count"].as_u64().unwrap(),
+        1,
+        "only the valid CF op should be applied"
+    );
+    let diagnostics = &payload["formula_parse_diagnostics"];
+    assert!(
+        diagnostics.is_object(),
+        "expected formula_parse_diagnostics object"
+    );
+    assert_eq!(diagnostics["policy"], "warn");
+    assert!(diagnostics["total_errors"].as_u64().unwrap_or(0) > 0);
+}
+
+#[test]
+fn cli_rules_batch_off_mode_permissive_behavior() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("rules-off.xlsx");
+    let ops_path = tmp.path().join("ops.json");
+    write_fixture(&workbook_path);
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[
+            {"kind":"set_data_validation","sheet_name":"Sheet1","target_range":"B2:B4","validation":{"kind":"list","formula1":"\"A,B,C\""}}
+        ]}"#,
+    );
+
+    let file = workbook_path.to_str().expect("path utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
+
+    let output = run_cli(&[
+        "rules-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
+        "--formula-parse-policy",
+        "off",
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert!(
+        payload["formula_parse_diagnostics"].is_null(),
+        "no diagnostics in off mode"
+    );
+    assert!(payload["changed"].as_bool().unwrap_or(false));
+}
+
+#[test]
+fn cli_rules_batch_dry_run_formula_diagnostics_parity() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("rules-dryrun-diag.xlsx");
+    let ops_path = tmp.path().join("ops.json");
+    write_fixture(&workbook_path);
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[
+            {"kind":"set_data_validation","sheet_name":"Sheet1","target_range":"C2:C4","validation":{"kind":"custom","formula1":"=AND(C2>0,LEN(C2"}}
+        ]}"#,
+    );
+
+    let file = workbook_path.to_str().expect("path utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
+
+    let output = run_cli(&[
+        "rules-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--dry-run",
+        "--formula-parse-policy",
+        "warn",
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+
+    let diagnostics = &payload["formula_parse_diagnostics"];
+    assert!(
+        diagnostics.is_object(),
+        "expected diagnostics in dry-run warn mode"
+    );
+    assert_eq!(diagnostics["policy"], "warn");
+    assert!(diagnostics["total_errors"].as_u64().unwrap_or(0) > 0);
+}
+
+#[test]
+fn transform_batch_fill_range_formula_clears_cache() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("fill-formula-cache.xlsx");
+    let ops_path = tmp.path().join("fill-formula-ops.json");
+
+    // Create workbook with a formula cell that has a stale cached result
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet");
+        sheet.get_cell_mut("A1").set_value_number(10.0);
+        sheet.get_cell_mut("A2").set_value_number(20.0);
+        let b1 = sheet.get_cell_mut("B1");
+        b1.set_formula("A1+1");
+        b1.get_cell_value_mut().set_formula_result_default("999"); // stale cache
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write");
+
+    // FillRange with is_formula=true should clear the cache
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"range","range":"B1:B2"},"value":"A1+100","is_formula":true,"overwrite_formulas":true}]}"#,
+    );
+
+    let file = workbook_path.to_str().expect("path");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops"));
+    let output = run_cli(&["transform-batch", file, "--ops", &ops_ref, "--in-place"]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(
+        payload["write_path_provenance"]["written_via"],
+        Value::String("transform_batch".to_string())
+    );
+
+    // Read back and verify cache is cleared
+    let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read");
+    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet");
+    let b1 = sheet.get_cell("B1").expect("B1");
+    assert_eq!(b1.get_formula().replace(' ', ""), "A1+100");
+    assert_eq!(
+        b1.get_value(),
+        "",
+        "expected formula cache to be cleared after FillRange"
+    );
+
+    let b2 = sheet.get_cell("B2").expect("B2");
+    assert_eq!(b2.get_formula().replace(' ', ""), "A1+100");
+    assert_eq!(
+        b2.get_value(),
+        "",
+        "expected formula cache to be cleared after FillRange"
+    );
+}
+
+#[test]
+fn transform_batch_replace_in_range_formula_clears_cache() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("replace-formula-cache.xlsx");
+    let ops_path = tmp.path().join("replace-formula-ops.json");
+
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet");
+        let a1 = sheet.get_cell_mut("A1");
+        a1.set_formula("SUM(B1:B10)");
+        a1.get_cell_value_mut().set_formula_result_default("500"); // stale cache
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write");
+
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"replace_in_range","sheet_name":"Sheet1","target":{"kind":"range","range":"A1:A1"},"find":"SUM","replace":"AVERAGE","match_mode":"contains","include_formulas":true}]}"#,
+    );
+
+    let file = workbook_path.to_str().expect("path");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops"));
+    let output = run_cli(&["transform-batch", file, "--ops", &ops_ref, "--in-place"]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(
+        payload["write_path_provenance"]["written_via"],
+        Value::String("transform_batch".to_string())
+    );
+
+    let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read");
+    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet");
+    let a1 = sheet.get_cell("A1").expect("A1");
+    assert!(
+        a1.get_formula().contains("AVERAGE"),
+        "formula should be replaced"
+    );
+    assert_eq!(
+        a1.get_value(),
+        "",
+        "expected formula cache to be cleared after ReplaceInRange"
+    );
+}
+
+#[test]
+fn edit_batch_formula_clears_cache() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("edit-formula-cache.xlsx");
+
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet");
+        let a1 = sheet.get_cell_mut("A1");
+        a1.set_formula("B1+C1");
+        a1.get_cell_value_mut()
+            .set_formula_result_default("old_value");
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write");
+
+    let file = workbook_path.to_str().expect("path");
+    let output = run_cli(&["edit", file, "Sheet1", "A1==SUM(B1:B5)"]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+
+    let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read");
+    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet");
+    let a1 = sheet.get_cell("A1").expect("A1");
+    assert_eq!(a1.get_formula().replace(' ', ""), "SUM(B1:B5)");
+    assert_eq!(
+        a1.get_value(),
+        "",
+        "expected formula cache to be cleared after edit"
+    );
+}
+
+#[test]
+fn edit_formula_write_emits_write_path_provenance() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("edit-provenance-formula.xlsx");
+    write_fixture(&workbook_path);
+
+    let file = workbook_path.to_str().expect("path");
+    let output = run_cli(&["edit", file, "Sheet1", "C2==B2*7"]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+
+    assert_eq!(
+        payload["write_path_provenance"]["written_via"],
+        Value::String("edit".to_string())
+    );
+    let targets = payload["write_path_provenance"]["formula_targets"]
+        .as_array()
+        .expect("formula targets array");
+    assert!(
+        targets
+            .iter()
+            .any(|value| value.as_str() == Some("Sheet1!C2"))
+    );
+}
+
+#[test]
+fn edit_literal_write_omits_write_path_provenance() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("edit-provenance-literal.xlsx");
+    write_fixture(&workbook_path);
+
+    let file = workbook_path.to_str().expect("path");
+    let output = run_cli(&["edit", file, "Sheet1", "B2=7"]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+
+    assert!(
+        payload.get("write_path_provenance").is_none(),
+        "literal-only edits should omit provenance metadata"
+    );
+}
+
+#[test]
+fn transform_batch_help_mentions_formula_cache() {
+    let output = run_cli(&["transform-batch", "--help"]);
+    let combined = format!(
+        "{}{}",
+        parse_stdout_text(&output),
+        String::from_utf8(output.stderr.clone()).expect("stderr utf8")
+    );
+    assert!(
+        combined.contains("Cache note") || combined.contains("cached results"),
+        "transform-batch help should mention formula cache behavior"
+    );
+    assert!(
+        combined.contains("write_path_provenance"),
+        "transform-batch help should mention provenance diagnostics"
+    );
+}
+
+#[test]
+fn structure_batch_help_mentions_formula_cache() {
+    let output = run_cli(&["structure-batch", "--help"]);
+    let combined = format!(
+        "{}{}",
+        parse_stdout_text(&output),
+        String::from_utf8(output.stderr.clone()).expect("stderr utf8")
+    );
+    assert!(
+        combined.contains("Cache note") || combined.contains("cached results"),
+        "structure-batch help should mention formula cache behavior"
+    );
+}
+
+fn write_complex_grid_fixture(path: &Path) {
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook
+            .get_sheet_by_name_mut("Sheet1")
+            .expect("default sheet exists");
+
+        sheet.get_cell_mut("A1").set_value("Quarterly Report");
+        sheet.add_merge_cells("A1:B1");
+        sheet.get_cell_mut("A2").set_value("Name");
+        sheet.get_cell_mut("B2").set_value("Amount");
+        sheet.get_cell_mut("A3").set_value("Alice");
+        sheet.get_cell_mut("B3").set_value_number(1234.0);
+        sheet.get_cell_mut("A4").set_value("Bob");
+        sheet.get_cell_mut("B4").set_value_number(5678.0);
 
-    let output = run_cli(&[
-        "append-region",
-        file,
-        "--sheet",
+        sheet.get_column_dimension_mut("A").set_width(26.0);
+        sheet.get_column_dimension_mut("B").set_width(14.0);
+
+        sheet.get_style_mut("A1").get_font_mut().set_bold(true);
+        sheet
+            .get_style_mut("A1")
+            .get_alignment_mut()
+            .set_horizontal(umya_spreadsheet::HorizontalAlignmentValues::Center);
+        sheet
+            .get_style_mut("A1")
+            .get_borders_mut()
+            .get_bottom_border_mut()
+            .set_border_style("medium");
+        sheet.get_style_mut("B3").get_font_mut().set_italic(true);
+        sheet
+            .get_style_mut("B3")
+            .get_number_format_mut()
+            .set_format_code("$#,##0");
+    }
+
+    umya_spreadsheet::writer::xlsx::write(&workbook, path).expect("write workbook");
+}
+
+#[test]
+fn cli_range_export_csv_and_range_import_from_csv_roundtrip() {
+    let tmp = tempdir().expect("tempdir");
+    let source_path = tmp.path().join("csv-source.xlsx");
+    let target_path = tmp.path().join("csv-target.xlsx");
+    let csv_path = tmp.path().join("export.csv");
+
+    write_fixture(&source_path);
+    umya_spreadsheet::writer::xlsx::write(&umya_spreadsheet::new_file(), &target_path)
+        .expect("write target workbook");
+
+    let source = source_path.to_str().expect("source path utf8");
+    let target = target_path.to_str().expect("target path utf8");
+    let csv = csv_path.to_str().expect("csv path utf8");
+
+    let export = run_cli(&[
+        "range-export",
+        source,
         "Sheet1",
-        "--region-id",
-        region_id.as_str(),
-        "--rows",
-        &format!("@{}", rows_path.display()),
-        "--dry-run",
+        "A1:B4",
+        "--format",
+        "csv",
+        "--output",
+        csv,
+    ]);
+    assert!(export.status.success(), "stderr: {:?}", export.stderr);
+    let export_payload = parse_stdout_json(&export);
+    assert_eq!(export_payload["status"], "ok");
+    assert_json_path_eq(&export_payload, "path", csv);
+
+    let import = run_cli(&[
+        "range-import",
+        target,
+        "Sheet1",
+        "--anchor",
+        "B2",
+        "--from-csv",
+        csv,
+        "--in-place",
+    ]);
+    assert!(import.status.success(), "stderr: {:?}", import.stderr);
+
+    let read = run_cli(&[
+        "range-values",
+        target,
+        "Sheet1",
+        "B2:C5",
+        "--format",
+        "json",
+    ]);
+    assert!(read.status.success(), "stderr: {:?}", read.stderr);
+    let payload = parse_stdout_json(&read);
+    let rows = payload["values"][0]["rows"]
+        .as_array()
+        .expect("rows matrix");
+
+    assert_eq!(rows[0][0]["value"], "Name");
+    assert_eq!(rows[0][1]["value"], "Amount");
+    assert_eq!(rows[1][0]["value"], "Alice");
+    assert_eq!(rows[1][1]["value"], 10.0);
+    assert_eq!(rows[3][0]["value"], "Carol");
+    assert_eq!(rows[3][1]["value"], 30.0);
+
+    let target_header_path = tmp.path().join("csv-target-header.xlsx");
+    umya_spreadsheet::writer::xlsx::write(&umya_spreadsheet::new_file(), &target_header_path)
+        .expect("write header target workbook");
+    let target_header = target_header_path.to_str().expect("header path utf8");
+
+    let import_header = run_cli(&[
+        "range-import",
+        target_header,
+        "Sheet1",
+        "--anchor",
+        "A1",
+        "--from-csv",
+        csv,
+        "--header",
+        "--in-place",
     ]);
-    assert!(output.status.success(), "stderr: {:?}", output.stderr);
-    let payload = parse_stdout_json(&output);
-    assert_eq!(payload["mode"], "dry_run");
-    assert_eq!(payload["sheet_name"], "Sheet1");
-    assert_eq!(payload["target_kind"], "detected_region");
-    assert_eq!(
-        payload["region_id"],
-        region_id.parse::<u64>().expect("region id num")
-    );
-    assert_eq!(payload["footer_policy"], "auto");
-    assert_eq!(payload["insert_at_row"], 4);
-    assert_eq!(
-        payload["insert_reason"],
-        "auto policy selected detected footer row 4"
-    );
-    assert_eq!(payload["footer_row"], 4);
-    assert_eq!(payload["target_anchor"], "A4");
-    assert_eq!(payload["target_range"], "A4:B4");
-    assert_eq!(payload["rows_appended"], 1);
-    assert_eq!(payload["columns_written"], 2);
-    assert_eq!(payload["expand_adjacent_sums"], true);
-    assert_eq!(payload["confidence"], "high");
     assert!(
-        payload["confidence_reason"]
-            .as_str()
-            .unwrap_or_default()
-            .contains("explicit footer keyword detected")
+        import_header.status.success(),
+        "stderr: {:?}",
+        import_header.stderr
     );
-    assert_eq!(payload["footer_formula_targets"][0], "B4");
-    assert_eq!(
-        payload["footer_candidates"].as_array().map(Vec::len),
-        Some(2)
+
+    let read_header = run_cli(&[
+        "range-values",
+        target_header,
+        "Sheet1",
+        "A1:B3",
+        "--format",
+        "json",
+    ]);
+    assert!(
+        read_header.status.success(),
+        "stderr: {:?}",
+        read_header.stderr
     );
-    assert_eq!(payload["would_change"], true);
+    let header_payload = parse_stdout_json(&read_header);
+    let header_rows = header_payload["values"][0]["rows"]
+        .as_array()
+        .expect("header rows matrix");
+    assert_eq!(header_rows[0][0]["value"], "Alice");
+    assert_eq!(header_rows[0][1]["value"], 10.0);
 }
 
 #[test]
-fn cli_append_region_output_inserts_before_footer_and_expands_sum() {
+fn cli_csv_dialect_flags_shape_export_and_read_table_csv() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("append-region-output-source.xlsx");
-    let output_path = tmp.path().join("append-region-output-target.xlsx");
-    let rows_path = tmp.path().join("rows.json");
+    let workbook_path = tmp.path().join("csv-dialect.xlsx");
+    let csv_path = tmp.path().join("export-eu.csv");
 
     let mut workbook = umya_spreadsheet::new_file();
     {
-        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet1");
+        let sheet = workbook
+            .get_sheet_by_name_mut("Sheet1")
+            .expect("default sheet exists");
         sheet.get_cell_mut("A1").set_value("Name");
         sheet.get_cell_mut("B1").set_value("Amount");
-        sheet.get_cell_mut("A2").set_value("Alice");
-        sheet.get_cell_mut("B2").set_value_number(10.0);
-        sheet.get_cell_mut("A3").set_value("Bob");
-        sheet.get_cell_mut("B3").set_value_number(20.0);
-        sheet.get_cell_mut("A4").set_value("Total");
-        let total = sheet.get_cell_mut("B4");
-        total.set_formula("SUM(B2:B3)");
-        total.get_cell_value_mut().set_formula_result_default("30");
+        sheet.get_cell_mut("A2").set_value("Smith; J");
+        sheet.get_cell_mut("B2").set_value_number(1234.5);
+        sheet.get_cell_mut("A3").set_value("Lee");
+        sheet.get_cell_mut("B3").set_value_number(-2.25);
     }
     umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
-    fs::write(&rows_path, r#"{"rows":[["Cara",30]]}"#).expect("write rows payload");
-
     let file = workbook_path.to_str().expect("path utf8");
-    let out = output_path.to_str().expect("output path utf8");
-    let overview = run_cli(&["sheet-overview", file, "Sheet1"]);
-    assert!(overview.status.success(), "stderr: {:?}", overview.stderr);
-    let overview_payload = parse_stdout_json(&overview);
-    let region_id = overview_payload["detected_regions"][0]["id"]
-        .as_u64()
-        .expect("region id")
-        .to_string();
 
-    let output = run_cli(&[
-        "append-region",
+    let export = run_cli(&[
+        "range-export",
         file,
-        "--sheet",
         "Sheet1",
-        "--region-id",
-        region_id.as_str(),
-        "--rows",
-        &format!("@{}", rows_path.display()),
+        "A1:B3",
+        "--format",
+        "csv",
+        "--csv-delimiter",
+        ";",
+        "--csv-decimal-separator",
+        ",",
+        "--csv-bom",
+        "--csv-line-ending",
+        "crlf",
         "--output",
-        out,
+        csv_path.to_str().expect("csv path utf8"),
     ]);
-    assert!(output.status.success(), "stderr: {:?}", output.stderr);
-    let payload = parse_stdout_json(&output);
-    assert_eq!(payload["mode"], "output");
-    assert_eq!(payload["file"], out);
-    assert_eq!(payload["target_path"], out);
-    assert_eq!(payload["changed"], true);
+    assert!(export.status.success(), "stderr: {:?}", export.stderr);
+    let exported = std::fs::read(&csv_path).expect("read csv");
+    assert_eq!(
+        exported,
+        "\u{FEFF}Name;Amount\r\n\"Smith; J\";1234,5\r\nLee;-2,25\r\n".as_bytes()
+    );
 
-    let book = umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output workbook");
-    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet1 exists");
-    assert_eq!(sheet.get_cell("A4").expect("A4").get_value(), "Cara");
-    assert_eq!(sheet.get_cell("B4").expect("B4").get_value(), "30");
-    assert_eq!(sheet.get_cell("A5").expect("A5").get_value(), "Total");
+    let read = run_cli(&[
+        "read-table",
+        file,
+        "--sheet",
+        "Sheet1",
+        "--range",
+        "A1:B3",
+        "--table-format",
+        "csv",
+        "--csv-quote",
+        "non-numeric",
+        "--csv-delimiter",
+        "tab",
+    ]);
+    assert!(read.status.success(), "stderr: {:?}", read.stderr);
+    let payload = parse_stdout_json(&read);
     assert_eq!(
-        sheet.get_cell("B5").expect("B5").get_formula(),
-        "SUM(B2:B4)"
+        payload["csv"],
+        "\"Name\"\t\"Amount\"\n\"Smith; J\"\t1234.5\n\"Lee\"\t-2.25\n"
+    );
+
+    let clash = run_cli(&[
+        "read-table",
+        file,
+        "--sheet",
+        "Sheet1",
+        "--table-format",
+        "csv",
+        "--csv-decimal-separator",
+        ",",
+    ]);
+    assert!(!clash.status.success());
+    let err = parse_stderr_json(&clash);
+    assert!(
+        err["message"]
+            .as_str()
+            .unwrap_or_default()
+            .contains("decimal separator"),
+        "unexpected error: {err}"
     );
 }
 
 #[test]
-fn cli_append_region_detects_formula_footer_even_with_blank_label_cell() {
+fn cli_arrow_output_writes_ipc_files_with_profiled_types() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("append-region-blank-footer.xlsx");
-    let rows_path = tmp.path().join("rows.json");
+    let workbook_path = tmp.path().join("arrow-source.xlsx");
+    let export_path = tmp.path().join("export.arrow");
+    let table_path = tmp.path().join("table.arrow");
+    write_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
 
-    let mut workbook = umya_spreadsheet::new_file();
-    {
-        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet1");
-        sheet.get_cell_mut("A1").set_value("Name");
-        sheet.get_cell_mut("B1").set_value("Amount");
-        sheet.get_cell_mut("A2").set_value("Alice");
-        sheet.get_cell_mut("B2").set_value_number(10.0);
-        sheet.get_cell_mut("A3").set_value("Bob");
-        sheet.get_cell_mut("B3").set_value_number(20.0);
-        let total = sheet.get_cell_mut("B4");
-        total.set_formula("SUM(B2:B3)");
-        total.get_cell_value_mut().set_formula_result_default("30");
-    }
-    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
-    fs::write(&rows_path, r#"{"rows":[["Cara",30]]}"#).expect("write rows payload");
+    let export = run_cli(&[
+        "range-export",
+        file,
+        "Sheet1",
+        "A1:B4",
+        "--format",
+        "arrow",
+        "--output",
+        export_path.to_str().expect("export path utf8"),
+    ]);
+    assert!(export.status.success(), "stderr: {:?}", export.stderr);
+    let payload = parse_stdout_json(&export);
+    assert_eq!(payload["format"], "arrow");
+    assert_eq!(payload["row_count"], 3);
+    assert_eq!(payload["columns"][0]["name"], "Name");
+    assert_eq!(payload["columns"][0]["data_type"], "utf8");
+    assert_eq!(payload["columns"][1]["name"], "Amount");
+    assert_eq!(payload["columns"][1]["data_type"], "float64");
+    let bytes = std::fs::read(&export_path).expect("read arrow file");
+    assert!(bytes.starts_with(b"ARROW1"));
+    assert!(bytes.ends_with(b"ARROW1"));
 
-    let file = workbook_path.to_str().expect("path utf8");
-    let overview = run_cli(&["sheet-overview", file, "Sheet1"]);
-    assert!(overview.status.success(), "stderr: {:?}", overview.stderr);
-    let overview_payload = parse_stdout_json(&overview);
-    let region_id = overview_payload["detected_regions"][0]["id"]
-        .as_u64()
-        .expect("region id")
-        .to_string();
+    let read = run_cli(&[
+        "read-table",
+        file,
+        "--sheet",
+        "Sheet1",
+        "--range",
+        "A1:B4",
+        "--table-format",
+        "arrow",
+        "--limit",
+        "2",
+        "--output",
+        table_path.to_str().expect("table path utf8"),
+    ]);
+    assert!(read.status.success(), "stderr: {:?}", read.stderr);
+    let payload = parse_stdout_json(&read);
+    assert_eq!(payload["row_count"], 2);
+    assert_eq!(payload["next_offset"], 2);
+    assert!(
+        std::fs::read(&table_path)
+            .expect("read arrow page")
+            .starts_with(b"ARROW1")
+    );
 
-    let output = run_cli(&[
-        "append-region",
+    let missing_output = run_cli(&[
+        "read-table",
         file,
         "--sheet",
         "Sheet1",
-        "--region-id",
-        region_id.as_str(),
-        "--rows",
-        &format!("@{}", rows_path.display()),
-        "--dry-run",
+        "--table-format",
+        "arrow",
     ]);
-    assert!(output.status.success(), "stderr: {:?}", output.stderr);
-    let payload = parse_stdout_json(&output);
-    assert_eq!(payload["footer_row"], 4);
-    assert_eq!(payload["insert_at_row"], 4);
+    assert!(!missing_output.status.success());
+    let err = parse_stderr_json(&missing_output);
     assert!(
-        payload["footer_detection"]
+        err["message"]
             .as_str()
             .unwrap_or_default()
-            .contains("formula-bearing summary row")
+            .contains("--output"),
+        "unexpected error: {err}"
     );
 }
 
 #[test]
-fn cli_append_region_from_csv_skips_header_and_handles_quotes_blanks_and_crlf() {
+fn cli_range_values_include_formatting_reports_display_text() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("append-region-csv-source.xlsx");
-    let output_path = tmp.path().join("append-region-csv-target.xlsx");
-    let csv_path = tmp.path().join("rows.csv");
+    let workbook_path = tmp.path().join("formatted.xlsx");
+    let csv_path = tmp.path().join("displayed.csv");
 
     let mut workbook = umya_spreadsheet::new_file();
     {
-        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet1");
-        sheet.get_cell_mut("A1").set_value("Name");
-        sheet.get_cell_mut("B1").set_value("Amount");
-        sheet.get_cell_mut("C1").set_value("Notes");
-        sheet.get_cell_mut("A2").set_value("Alice");
-        sheet.get_cell_mut("B2").set_value_number(10.0);
-        sheet.get_cell_mut("A3").set_value("Bob");
-        sheet.get_cell_mut("B3").set_value_number(20.0);
-        sheet.get_cell_mut("A4").set_value("Total");
-        let total = sheet.get_cell_mut("B4");
-        total.set_formula("SUM(B2:B3)");
-        total.get_cell_value_mut().set_formula_result_default("30");
+        let sheet = workbook
+            .get_sheet_by_name_mut("Sheet1")
+            .expect("default sheet exists");
+        sheet.get_cell_mut("A1").set_value("Margin");
+        sheet.get_cell_mut("B1").set_value_number(0.1234);
+        sheet
+            .get_style_mut("B1")
+            .get_number_format_mut()
+            .set_format_code("0.0%");
+        sheet.get_cell_mut("C1").set_value_number(7.0);
     }
     umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
-    fs::write(
-        &csv_path,
-        "Name,Amount,Notes\r\n\"Cara, Jr\",30,\r\nDina,40,\"Needs review\"\r\n",
-    )
-    .expect("write csv payload");
-
     let file = workbook_path.to_str().expect("path utf8");
-    let out = output_path.to_str().expect("output path utf8");
-    let overview = run_cli(&["sheet-overview", file, "Sheet1"]);
-    assert!(overview.status.success(), "stderr: {:?}", overview.stderr);
-    let overview_payload = parse_stdout_json(&overview);
-    let region_id = overview_payload["detected_regions"][0]["id"]
-        .as_u64()
-        .expect("region id")
-        .to_string();
 
     let output = run_cli(&[
-        "append-region",
+        "range-values",
         file,
-        "--sheet",
         "Sheet1",
-        "--region-id",
-        region_id.as_str(),
-        "--from-csv",
-        csv_path.to_str().expect("csv utf8"),
-        "--header",
-        "--output",
-        out,
+        "A1:C1",
+        "--format",
+        "json",
+        "--include-formatting",
     ]);
     assert!(output.status.success(), "stderr: {:?}", output.stderr);
     let payload = parse_stdout_json(&output);
-    assert_eq!(payload["rows_appended"], 2);
-    assert_eq!(payload["columns_written"], 3);
-    assert_eq!(payload["insert_at_row"], 4);
-    assert_eq!(payload["target_range"], "A4:C5");
-
-    let book = umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output workbook");
-    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet1 exists");
-    assert_eq!(sheet.get_cell("A4").expect("A4").get_value(), "Cara, Jr");
-    assert_eq!(sheet.get_cell("B4").expect("B4").get_value(), "30");
+    let entry = &payload["values"][0];
+    assert_eq!(entry["rows"][0][1]["value"], 0.1234);
+    let formatting = entry["formatting"].as_array().expect("formatting list");
+    assert_eq!(formatting.len(), 3);
+    assert_eq!(formatting[0]["display"], "Margin");
+    assert!(formatting[0].get("number_format").is_none());
+    assert_eq!(formatting[1]["col"], 1);
+    assert_eq!(formatting[1]["number_format"], "0.0%");
+    assert_eq!(formatting[1]["display"], "12.3%");
+    assert_eq!(formatting[2]["display"], "7");
+
+    let plain = run_cli(&["range-values", file, "Sheet1", "A1:C1", "--format", "json"]);
+    assert!(plain.status.success(), "stderr: {:?}", plain.stderr);
     assert!(
-        sheet.get_cell("C4").is_none()
-            || sheet
-                .get_cell("C4")
-                .expect("C4 present when not none")
-                .get_value()
-                .is_empty()
-    );
-    assert_eq!(sheet.get_cell("A5").expect("A5").get_value(), "Dina");
-    assert_eq!(sheet.get_cell("B5").expect("B5").get_value(), "40");
-    assert_eq!(
-        sheet.get_cell("C5").expect("C5").get_value(),
-        "Needs review"
+        parse_stdout_json(&plain)["values"][0]
+            .get("formatting")
+            .is_none()
     );
-    assert_eq!(sheet.get_cell("A6").expect("A6").get_value(), "Total");
+
+    let export = run_cli(&[
+        "range-export",
+        file,
+        "Sheet1",
+        "A1:C1",
+        "--format",
+        "csv",
+        "--include-formatting",
+        "--output",
+        csv_path.to_str().expect("csv path utf8"),
+    ]);
+    assert!(export.status.success(), "stderr: {:?}", export.stderr);
     assert_eq!(
-        sheet.get_cell("B6").expect("B6").get_formula(),
-        "SUM(B2:B5)"
+        std::fs::read_to_string(&csv_path).expect("read csv"),
+        "Margin,12.3%,7\n"
     );
 }
 
 #[test]
-fn cli_append_region_rejects_rows_and_from_csv_together() {
+fn cli_grid_export_import_roundtrip_preserves_layout_and_styles() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("append-region-invalid-source.xlsx");
-    let rows_path = tmp.path().join("rows.json");
-    let csv_path = tmp.path().join("rows.csv");
+    let source_path = tmp.path().join("grid-source.xlsx");
+    let target_path = tmp.path().join("grid-target.xlsx");
+    let grid_path = tmp.path().join("region.grid.json");
 
-    write_fixture(&workbook_path);
-    fs::write(&rows_path, r#"{"rows":[["Cara",30]]}"#).expect("write rows payload");
-    fs::write(&csv_path, "Name,Amount\nCara,30\n").expect("write csv payload");
+    write_complex_grid_fixture(&source_path);
+    umya_spreadsheet::writer::xlsx::write(&umya_spreadsheet::new_file(), &target_path)
+        .expect("write target workbook");
 
-    let file = workbook_path.to_str().expect("path utf8");
-    let overview = run_cli(&["sheet-overview", file, "Sheet1"]);
-    assert!(overview.status.success(), "stderr: {:?}", overview.stderr);
-    let overview_payload = parse_stdout_json(&overview);
-    let region_id = overview_payload["detected_regions"][0]["id"]
-        .as_u64()
-        .expect("region id")
-        .to_string();
+    let source = source_path.to_str().expect("source path utf8");
+    let target = target_path.to_str().expect("target path utf8");
+    let grid = grid_path.to_str().expect("grid path utf8");
 
-    let output = run_cli(&[
-        "append-region",
-        file,
-        "--sheet",
+    let export = run_cli(&[
+        "range-export",
+        source,
         "Sheet1",
-        "--region-id",
-        region_id.as_str(),
-        "--rows",
-        &format!("@{}", rows_path.display()),
-        "--from-csv",
-        csv_path.to_str().expect("csv utf8"),
-        "--dry-run",
+        "A1:B4",
+        "--format",
+        "grid",
+        "--output",
+        grid,
     ]);
-    assert!(!output.status.success());
-    let err = parse_stderr_json(&output);
-    assert_eq!(err["code"], "INVALID_ARGUMENT");
+    assert!(export.status.success(), "stderr: {:?}", export.stderr);
+
+    let import = run_cli(&[
+        "range-import",
+        target,
+        "Sheet1",
+        "--anchor",
+        "A1",
+        "--from-grid",
+        grid,
+        "--in-place",
+    ]);
+    assert!(import.status.success(), "stderr: {:?}", import.stderr);
+
+    let layout = run_cli(&[
+        "layout-page",
+        target,
+        "Sheet1",
+        "--range",
+        "A1:B4",
+        "--max-col-width",
+        "40",
+        "--skip-empty-columns-trim",
+    ]);
+    assert!(layout.status.success(), "stderr: {:?}", layout.stderr);
+    let layout_payload = parse_stdout_json(&layout);
+
+    let merges = layout_payload["merged_cells"]
+        .as_array()
+        .expect("merged cells");
     assert!(
-        err["message"]
-            .as_str()
-            .unwrap_or_default()
-            .contains("mutually exclusive")
+        merges.iter().any(|v| v.as_str() == Some("A1:B1")),
+        "expected A1:B1 merge, got {:?}",
+        merges
     );
+
+    let columns = layout_payload["columns"].as_array().expect("columns");
+    assert_eq!(columns[0]["width_chars"], 26.0);
+    assert_eq!(columns[1]["width_chars"], 14.0);
+
+    let row1_cells = layout_payload["rows"][0]["cells"]
+        .as_array()
+        .expect("row1 cells");
+    let a1 = row1_cells
+        .iter()
+        .find(|c| c["address"] == "A1")
+        .expect("A1 cell");
+    assert_eq!(a1["bold"], true);
+
+    let inspect = run_cli(&["inspect-cells", target, "Sheet1", "B3:B3"]);
+    assert!(inspect.status.success(), "stderr: {:?}", inspect.stderr);
+    let inspect_payload = parse_stdout_json(&inspect);
+    let b3 = inspect_payload["cells"].as_array().expect("cells")[0].clone();
+    assert_eq!(b3["number_format"], "$#,##0");
 }
 
 #[test]
-fn cli_append_region_supports_table_name_targeting() {
+fn cli_range_import_from_csv_handles_quotes_crlf_and_blanks() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("append-region-table-target.xlsx");
-    let output_path = tmp.path().join("append-region-table-target-out.xlsx");
-    let rows_path = tmp.path().join("rows.json");
+    let target_path = tmp.path().join("csv-edge-target.xlsx");
+    let csv_path = tmp.path().join("edge.csv");
 
-    let mut workbook = umya_spreadsheet::new_file();
-    {
-        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet1");
-        sheet.get_cell_mut("A1").set_value("Name");
-        sheet.get_cell_mut("B1").set_value("Amount");
-        sheet.get_cell_mut("A2").set_value("Alice");
-        sheet.get_cell_mut("B2").set_value_number(10.0);
-        sheet.get_cell_mut("A3").set_value("Bob");
-        sheet.get_cell_mut("B3").set_value_number(20.0);
-        let mut table = umya_spreadsheet::structs::Table::new("SalesTable", ("A1", "B3"));
-        table.set_display_name("SalesTable");
-        sheet.add_table(table);
-    }
-    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
-    fs::write(&rows_path, r#"{"rows":[["Cara",30]]}"#).expect("write rows payload");
+    umya_spreadsheet::writer::xlsx::write(&umya_spreadsheet::new_file(), &target_path)
+        .expect("write target workbook");
 
-    let output = run_cli(&[
-        "append-region",
-        workbook_path.to_str().expect("path utf8"),
-        "--sheet",
-        "Sheet1",
-        "--table-name",
-        "SalesTable",
-        "--rows",
-        &format!("@{}", rows_path.display()),
-        "--output",
-        output_path.to_str().expect("output utf8"),
-    ]);
-    assert!(output.status.success(), "stderr: {:?}", output.stderr);
-    let payload = parse_stdout_json(&output);
-    assert_eq!(payload["target_kind"], "table");
-    assert_eq!(payload["table_name"], "SalesTable");
-    assert!(payload.get("region_id").is_none());
-    assert_eq!(payload["insert_at_row"], 4);
-    assert_eq!(payload["target_range"], "A4:B4");
+    let csv_content = concat!(
+        "Name,Note,Amount,Extra\r\n",
+        "\"Doe, Jane\",\"He said \"\"Hi\"\"\",123,\r\n",
+        "\"Multiline\",\"First line\r\nSecond line\",45.67,\"\"\r\n"
+    );
+    fs::write(&csv_path, csv_content).expect("write csv");
 
-    let book = umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output workbook");
-    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet1 exists");
-    assert_eq!(sheet.get_cell("A4").expect("A4").get_value(), "Cara");
-    assert_eq!(sheet.get_cell("B4").expect("B4").get_value(), "30");
-    let table = sheet
-        .get_tables()
-        .iter()
-        .find(|table| table.get_name() == "SalesTable")
-        .expect("sales table");
-    assert_eq!(table.get_area().0.get_coordinate(), "A1");
-    assert_eq!(table.get_area().1.get_coordinate(), "B4");
+    let target = target_path.to_str().expect("target path utf8");
+    let csv = csv_path.to_str().expect("csv path utf8");
 
-    let named = run_cli(&[
-        "named-ranges",
-        output_path.to_str().expect("output utf8"),
-        "--sheet",
+    let import = run_cli(&[
+        "range-import",
+        target,
         "Sheet1",
+        "--anchor",
+        "A1",
+        "--from-csv",
+        csv,
+        "--in-place",
     ]);
-    assert!(named.status.success(), "stderr: {:?}", named.stderr);
-    let named_payload = parse_stdout_json(&named);
-    let sales_table = named_payload["items"]
-        .as_array()
-        .expect("named items")
-        .iter()
-        .find(|item| item["name"] == "SalesTable")
-        .expect("sales table item");
-    assert_eq!(sales_table["kind"], "table");
-    assert_eq!(sales_table["refers_to"], "A1:B4");
+    assert!(import.status.success(), "stderr: {:?}", import.stderr);
 
-    let read_table = run_cli(&[
-        "read-table",
-        output_path.to_str().expect("output utf8"),
-        "--sheet",
+    let read = run_cli(&[
+        "range-values",
+        target,
         "Sheet1",
-        "--table-name",
-        "SalesTable",
-        "--table-format",
-        "values",
+        "A1:D3",
+        "--format",
+        "json",
     ]);
+    assert!(read.status.success(), "stderr: {:?}", read.stderr);
+    let payload = parse_stdout_json(&read);
+    let rows = payload["values"][0]["rows"]
+        .as_array()
+        .expect("rows matrix");
+
+    assert_eq!(rows[0][0]["value"], "Name");
+    assert_eq!(rows[0][1]["value"], "Note");
+    assert_eq!(rows[0][2]["value"], "Amount");
+    assert_eq!(rows[0][3]["value"], "Extra");
+
+    assert_eq!(rows[1][0]["value"], "Doe, Jane");
+    assert_eq!(rows[1][1]["value"], "He said \"Hi\"");
+    assert_eq!(rows[1][2]["value"], 123.0);
+    assert!(rows[1][3].is_null());
+
+    assert_eq!(rows[2][0]["value"], "Multiline");
+    let multiline = rows[2][1]["value"].as_str().expect("multiline text value");
+    assert!(multiline.contains("First line"));
+    assert!(multiline.contains("Second line"));
+    assert_eq!(rows[2][2]["value"], 45.67);
+    assert!(rows[2][3].is_null());
+}
+
+#[test]
+fn edit_help_mentions_formula_cache_and_modes() {
+    let output = run_cli(&["edit", "--help"]);
+    let combined = format!(
+        "{}{}",
+        parse_stdout_text(&output),
+        String::from_utf8(output.stderr.clone()).expect("stderr utf8")
+    );
     assert!(
-        read_table.status.success(),
-        "stderr: {:?}",
-        read_table.stderr
+        combined.contains("Cache note") || combined.contains("cached results"),
+        "edit help should mention formula cache behavior"
+    );
+    assert!(
+        combined.contains("--dry-run")
+            && combined.contains("--in-place")
+            && combined.contains("--output"),
+        "edit help should mention dry-run/in-place/output modes"
+    );
+    assert!(
+        combined.contains("Formula shorthand")
+            && combined.contains("double equals")
+            && combined.contains("Single equals writes a literal"),
+        "edit help should clearly explain formula shorthand syntax"
+    );
+    assert!(
+        combined.contains("write_path_provenance"),
+        "edit help should mention provenance diagnostics"
     );
-    let read_table_payload = parse_stdout_json(&read_table);
-    assert_eq!(read_table_payload["table_name"], "SalesTable");
-    assert_eq!(read_table_payload["total_rows"], 3);
 }
 
+// ─── 4101: structure-batch impact report & formula delta preview ───
+
 #[test]
-fn cli_append_region_append_at_end_policy_bypasses_detected_footer() {
+fn structure_batch_impact_report_dry_run() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("append-region-append-at-end.xlsx");
-    let rows_path = tmp.path().join("rows.json");
-
-    let mut workbook = umya_spreadsheet::new_file();
-    {
-        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet1");
-        sheet.get_cell_mut("A1").set_value("Name");
-        sheet.get_cell_mut("B1").set_value("Amount");
-        sheet.get_cell_mut("A2").set_value("Alice");
-        sheet.get_cell_mut("B2").set_value_number(10.0);
-        sheet.get_cell_mut("A3").set_value("Bob");
-        sheet.get_cell_mut("B3").set_value_number(20.0);
-        sheet.get_cell_mut("A4").set_value("Total");
-        let total = sheet.get_cell_mut("B4");
-        total.set_formula("SUM(B2:B3)");
-        total.get_cell_value_mut().set_formula_result_default("30");
-    }
-    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
-    fs::write(&rows_path, r#"{"rows":[["Cara",30]]}"#).expect("write rows payload");
+    let workbook_path = tmp.path().join("impact-report.xlsx");
+    write_fixture(&workbook_path);
+    let ops_path = tmp.path().join("impact-ops.json");
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"insert_rows","sheet_name":"Sheet1","at_row":2,"count":3}]}"#,
+    );
 
-    let file = workbook_path.to_str().expect("path utf8");
-    let overview = run_cli(&["sheet-overview", file, "Sheet1"]);
-    assert!(overview.status.success(), "stderr: {:?}", overview.stderr);
-    let overview_payload = parse_stdout_json(&overview);
-    let region_id = overview_payload["detected_regions"][0]["id"]
-        .as_u64()
-        .expect("region id")
-        .to_string();
+    let file = workbook_path.to_str().unwrap();
+    let ops_ref = format!("@{}", ops_path.to_str().unwrap());
 
     let output = run_cli(&[
-        "append-region",
+        "structure-batch",
         file,
-        "--sheet",
-        "Sheet1",
-        "--region-id",
-        region_id.as_str(),
-        "--rows",
-        &format!("@{}", rows_path.display()),
-        "--footer-policy",
-        "append-at-end",
+        "--ops",
+        ops_ref.as_str(),
         "--dry-run",
+        "--impact-report",
     ]);
     assert!(output.status.success(), "stderr: {:?}", output.stderr);
     let payload = parse_stdout_json(&output);
-    assert_eq!(payload["footer_policy"], "append_at_end");
-    assert_eq!(payload["footer_row"], 4);
-    assert_eq!(payload["insert_at_row"], 5);
+
+    // Standard dry-run fields are still present.
+    assert!(payload["would_change"].as_bool().unwrap_or(false));
+    assert!(payload["op_count"].as_u64().is_some());
+
+    // Impact report is present.
+    let ir = &payload["impact_report"];
+    assert!(!ir.is_null(), "impact_report should be present");
     assert!(
-        payload["insert_reason"]
-            .as_str()
-            .unwrap_or_default()
-            .contains("bypassed detected footer row 4")
+        !ir["shifted_spans"].as_array().unwrap().is_empty(),
+        "should have at least one shifted span"
     );
-    assert!(
-        payload["warnings"]
-            .as_array()
-            .expect("warnings")
-            .iter()
-            .any(|warning| warning
-                .as_str()
-                .unwrap_or_default()
-                .contains("ignored detected footer row 4"))
+    assert!(ir["tokens_affected"].is_number());
+    assert!(ir["tokens_unaffected"].is_number());
+}
+
+#[test]
+fn structure_batch_show_formula_delta_dry_run() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("formula-delta.xlsx");
+    write_fixture(&workbook_path);
+    let ops_path = tmp.path().join("delta-ops.json");
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"insert_rows","sheet_name":"Sheet1","at_row":2,"count":1}]}"#,
     );
+
+    let file = workbook_path.to_str().unwrap();
+    let ops_ref = format!("@{}", ops_path.to_str().unwrap());
+
+    let output = run_cli(&[
+        "structure-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--dry-run",
+        "--show-formula-delta",
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+
+    // Formula delta preview should be present.
+    let fdp = &payload["formula_delta_preview"];
+    assert!(fdp.is_array(), "formula_delta_preview should be an array");
+    let items = fdp.as_array().unwrap();
+    assert!(!items.is_empty(), "should have at least one delta item");
+
+    // Each item should have the expected fields.
+    let first = &items[0];
+    assert!(first["cell"].is_string());
+    assert!(first["before"].is_string());
+    assert!(first["after"].is_string());
+    assert!(first["classification"].is_string());
 }
 
 #[test]
-fn cli_append_region_before_footer_policy_requires_detected_footer() {
+fn structure_batch_impact_flags_require_dry_run() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("append-region-before-footer.xlsx");
-    let rows_path = tmp.path().join("rows.json");
-
-    let mut workbook = umya_spreadsheet::new_file();
-    {
-        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet1");
-        sheet.get_cell_mut("A1").set_value("Name");
-        sheet.get_cell_mut("B1").set_value("Amount");
-        sheet.get_cell_mut("A2").set_value("Alice");
-        sheet.get_cell_mut("B2").set_value_number(10.0);
-        sheet.get_cell_mut("A3").set_value("Bob");
-        sheet.get_cell_mut("B3").set_value_number(20.0);
-    }
-    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
-    fs::write(&rows_path, r#"{"rows":[["Cara",30]]}"#).expect("write rows payload");
+    let workbook_path = tmp.path().join("impact-no-dry.xlsx");
+    write_fixture(&workbook_path);
+    let ops_path = tmp.path().join("impact-no-dry-ops.json");
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"insert_rows","sheet_name":"Sheet1","at_row":2,"count":1}]}"#,
+    );
 
-    let file = workbook_path.to_str().expect("path utf8");
-    let overview = run_cli(&["sheet-overview", file, "Sheet1"]);
-    assert!(overview.status.success(), "stderr: {:?}", overview.stderr);
-    let overview_payload = parse_stdout_json(&overview);
-    let region_id = overview_payload["detected_regions"][0]["id"]
-        .as_u64()
-        .expect("region id")
-        .to_string();
+    let file = workbook_path.to_str().unwrap();
+    let ops_ref = format!("@{}", ops_path.to_str().unwrap());
 
+    // --impact-report without --dry-run → error
     let output = run_cli(&[
-        "append-region",
+        "structure-batch",
         file,
-        "--sheet",
-        "Sheet1",
-        "--region-id",
-        region_id.as_str(),
-        "--rows",
-        &format!("@{}", rows_path.display()),
-        "--footer-policy",
-        "before-footer",
-        "--dry-run",
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
+        "--impact-report",
     ]);
-    assert!(!output.status.success());
-    let err = parse_stderr_json(&output);
-    assert_eq!(err["code"], "INVALID_ARGUMENT");
+    assert!(!output.status.success(), "should fail without --dry-run");
+    let stderr = String::from_utf8_lossy(&output.stderr);
     assert!(
-        err["message"]
-            .as_str()
-            .unwrap_or_default()
-            .contains("requires a detected footer/subtotal row")
+        stderr.contains("--dry-run") || stderr.contains("dry-run"),
+        "error should mention --dry-run: {}",
+        stderr
     );
+
+    // --show-formula-delta without --dry-run → error
+    let output2 = run_cli(&[
+        "structure-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
+        "--show-formula-delta",
+    ]);
+    assert!(!output2.status.success(), "should fail without --dry-run");
 }
 
 #[test]
-fn cli_clone_template_row_dry_run_reports_targets_and_confidence() {
+fn structure_batch_dry_run_without_impact_flags_is_backward_compatible() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("clone-template-row-dry-run.xlsx");
+    let workbook_path = tmp.path().join("compat.xlsx");
+    write_fixture(&workbook_path);
+    let ops_path = tmp.path().join("compat-ops.json");
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"insert_rows","sheet_name":"Sheet1","at_row":2,"count":1}]}"#,
+    );
 
-    let mut workbook = umya_spreadsheet::new_file();
-    {
-        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet1");
-        sheet.get_cell_mut("A1").set_value("Item");
-        sheet.get_cell_mut("B1").set_value("Input");
-        sheet.get_cell_mut("C1").set_value("Calc");
-        sheet.get_cell_mut("A2").set_value("Alpha");
-        sheet.get_cell_mut("B2").set_value_number(10.0);
-        sheet.get_cell_mut("C2").set_formula("B2*2");
-        sheet.get_cell_mut("A3").set_value("Total");
-        sheet.get_cell_mut("C3").set_formula("SUM(C2:C2)");
-    }
-    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+    let file = workbook_path.to_str().unwrap();
+    let ops_ref = format!("@{}", ops_path.to_str().unwrap());
 
     let output = run_cli(&[
-        "clone-template-row",
-        workbook_path.to_str().expect("path utf8"),
-        "--sheet",
-        "Sheet1",
-        "--source-row",
-        "2",
-        "--after",
-        "2",
-        "--count",
-        "2",
-        "--expand-adjacent-sums",
+        "structure-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
         "--dry-run",
     ]);
     assert!(output.status.success(), "stderr: {:?}", output.stderr);
     let payload = parse_stdout_json(&output);
-    assert_eq!(payload["helper_kind"], "clone_template_row");
-    assert_eq!(payload["anchor_kind"], "after");
-    assert_eq!(payload["insert_at_row"], 3);
-    assert_eq!(payload["inserted_row_range"], "3:4");
-    assert_eq!(payload["formula_targets"][0], "C3");
-    assert_eq!(payload["formula_targets"][1], "C4");
-    assert_eq!(payload["likely_patch_targets"][0], "B3");
-    assert_eq!(payload["likely_patch_targets"][1], "B4");
-    assert_eq!(payload["adjacent_sum_targets"][0], "C5");
-    assert_eq!(payload["confidence"], "high");
-    assert_eq!(payload["would_change"], true);
+
+    // impact_report and formula_delta_preview should NOT be present when not requested.
+    assert!(
+        payload.get("impact_report").is_none() || payload["impact_report"].is_null(),
+        "impact_report should be absent when not requested"
+    );
+    assert!(
+        payload.get("formula_delta_preview").is_none()
+            || payload["formula_delta_preview"].is_null(),
+        "formula_delta_preview should be absent when not requested"
+    );
 }
 
+// ── Named Range CRUD Tests ───────────────────────────────────────────────────
+
 #[test]
-fn cli_clone_template_row_strict_merge_policy_fails_on_crossing_merge() {
+fn cli_define_name_dry_run_validates_without_mutating() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("clone-template-row-strict-merge.xlsx");
-
-    let mut workbook = umya_spreadsheet::new_file();
-    {
-        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet1");
-        sheet.get_cell_mut("A1").set_value("Header");
-        sheet.get_cell_mut("A2").set_value("Alpha");
-        sheet.get_cell_mut("B2").set_value_number(10.0);
-        sheet.add_merge_cells("A1:A2");
-    }
-    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+    let workbook_path = tmp.path().join("define-name-dry-run.xlsx");
+    write_phase1_read_surface_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
 
     let output = run_cli(&[
-        "clone-template-row",
-        workbook_path.to_str().expect("path utf8"),
-        "--sheet",
-        "Sheet1",
-        "--source-row",
-        "2",
-        "--before",
-        "3",
-        "--merge-policy",
-        "strict",
+        "define-name",
+        file,
+        "NewRange",
+        "Sheet1!$A$1:$C$4",
         "--dry-run",
     ]);
-    assert!(!output.status.success());
-    let err = parse_stderr_json(&output);
-    assert_eq!(err["code"], "UNSAFE_CLONE_TEMPLATE");
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["name"], "NewRange");
+    assert_eq!(payload["refers_to"], "Sheet1!$A$1:$C$4");
+    assert_eq!(payload["scope_kind"], "workbook");
+    assert_eq!(payload["dry_run"], true);
+
+    // Verify the original file is unchanged: no NewRange should exist.
+    let check = run_cli(&["named-ranges", file, "--name-prefix", "NewRange"]);
+    assert!(check.status.success());
+    let check_payload = parse_stdout_json(&check);
+    // Empty arrays are pruned by the output layer, so items may be absent or empty.
+    let items = check_payload["items"].as_array();
     assert!(
-        err["message"]
-            .as_str()
-            .unwrap_or_default()
-            .contains("cross the clone boundary")
+        items.is_none() || items.unwrap().is_empty(),
+        "dry-run should not have mutated the file"
     );
 }
 
 #[test]
-fn cli_clone_template_row_output_preserves_horizontal_merge_and_validation() {
+fn cli_define_name_in_place_creates_workbook_scoped_name() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("clone-template-row-output.xlsx");
-    let output_path = tmp.path().join("clone-template-row-output-target.xlsx");
+    let workbook_path = tmp.path().join("define-name-inplace.xlsx");
+    write_phase1_read_surface_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
 
-    let mut workbook = umya_spreadsheet::new_file();
-    {
-        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet1");
-        sheet.get_cell_mut("A1").set_value("Name");
-        sheet.get_cell_mut("B1").set_value("Input");
-        sheet.get_cell_mut("C1").set_value("Calc");
-        sheet.get_cell_mut("A2").set_value("Alpha");
-        sheet.get_cell_mut("B2").set_value_number(10.0);
-        sheet.get_cell_mut("C2").set_formula("B2*2");
-        sheet.add_merge_cells("A2:B2");
+    let output = run_cli(&[
+        "define-name",
+        file,
+        "TotalSales",
+        "Sheet1!$B$2:$B$4",
+        "--in-place",
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["name"], "TotalSales");
+    assert_eq!(payload["scope_kind"], "workbook");
+    assert_eq!(payload["dry_run"], false);
 
-        let mut dv = umya_spreadsheet::structs::DataValidation::default();
-        dv.set_type(umya_spreadsheet::structs::DataValidationValues::List);
-        dv.get_sequence_of_references_mut().set_sqref("B2:B2");
-        dv.set_formula1("\"A,B,C\"");
-        sheet.set_data_validations(umya_spreadsheet::structs::DataValidations::default());
-        sheet
-            .get_data_validations_mut()
-            .unwrap()
-            .add_data_validation_list(dv);
-    }
-    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+    // Verify the name is now visible.
+    let check = run_cli(&["named-ranges", file, "--name-prefix", "TotalSales"]);
+    assert!(check.status.success());
+    let check_payload = parse_stdout_json(&check);
+    let items = check_payload["items"].as_array().expect("items array");
+    assert!(
+        !items.is_empty(),
+        "TotalSales should exist after define-name --in-place"
+    );
+    assert_eq!(items[0]["name"], "TotalSales");
+}
+
+#[test]
+fn cli_define_name_sheet_scoped_with_output() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("define-name-sheet.xlsx");
+    let output_path = tmp.path().join("define-name-sheet-out.xlsx");
+    write_phase1_read_surface_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
+    let output_file = output_path.to_str().expect("path utf8");
 
     let output = run_cli(&[
-        "clone-template-row",
-        workbook_path.to_str().expect("path utf8"),
-        "--sheet",
+        "define-name",
+        file,
+        "LocalName",
+        "Sheet1!$A$1",
+        "--scope",
+        "sheet",
+        "--scope-sheet-name",
         "Sheet1",
-        "--source-row",
-        "2",
-        "--before",
-        "3",
-        "--count",
-        "2",
-        "--patch-targets",
-        "all-non-formula",
         "--output",
-        output_path.to_str().expect("output utf8"),
+        output_file,
     ]);
     assert!(output.status.success(), "stderr: {:?}", output.stderr);
     let payload = parse_stdout_json(&output);
-    assert_eq!(payload["changed"], true);
-    assert_eq!(payload["inserted_row_range"], "3:4");
+    assert_eq!(payload["name"], "LocalName");
+    assert_eq!(payload["scope_kind"], "sheet");
+    assert_eq!(payload["scope_sheet_name"], "Sheet1");
 
-    let book = umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output workbook");
-    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet1 exists");
-    assert_eq!(sheet.get_cell("A3").expect("A3").get_value(), "Alpha");
-    assert_eq!(sheet.get_cell("B4").expect("B4").get_value(), "10");
-    let merge_ranges: Vec<String> = sheet
-        .get_merge_cells()
-        .iter()
-        .map(|range| range.get_range())
-        .collect();
-    assert!(merge_ranges.contains(&"A3:B3".to_string()));
-    assert!(merge_ranges.contains(&"A4:B4".to_string()));
-    let validations = sheet.get_data_validations().expect("validations");
-    let sqrefs: Vec<String> = validations
-        .get_data_validation_list()
-        .iter()
-        .map(|dv| dv.get_sequence_of_references().get_sqref())
-        .collect();
-    assert!(sqrefs.iter().any(|sqref| sqref.contains("B3")));
-    assert!(sqrefs.iter().any(|sqref| sqref.contains("B4")));
+    // Verify in the output file.
+    let check = run_cli(&["named-ranges", output_file, "--name-prefix", "LocalName"]);
+    assert!(check.status.success());
+    let check_payload = parse_stdout_json(&check);
+    let items = check_payload["items"].as_array().expect("items array");
+    assert!(!items.is_empty(), "LocalName should exist in output file");
 }
 
 #[test]
-fn cli_clone_row_band_dry_run_reports_blocks_and_targets() {
+fn cli_update_name_in_place_changes_refers_to() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("clone-row-band-dry-run.xlsx");
+    let workbook_path = tmp.path().join("update-name.xlsx");
+    write_phase1_read_surface_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
 
-    let mut workbook = umya_spreadsheet::new_file();
-    {
-        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet1");
-        sheet.get_cell_mut("A1").set_value("Item");
-        sheet.get_cell_mut("B1").set_value("Input");
-        sheet.get_cell_mut("C1").set_value("Calc");
-        sheet.get_cell_mut("A2").set_value("Alpha");
-        sheet.get_cell_mut("B2").set_value_number(10.0);
-        sheet.get_cell_mut("C2").set_formula("B2*2");
-        sheet.get_cell_mut("A3").set_value("Beta");
-        sheet.get_cell_mut("B3").set_value_number(20.0);
-        sheet.get_cell_mut("C3").set_formula("B3*2");
-        sheet.get_cell_mut("A4").set_value("Total");
-        sheet.get_cell_mut("C4").set_formula("SUM(C2:C3)");
-    }
-    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+    // First define a name.
+    let def = run_cli(&[
+        "define-name",
+        file,
+        "MyRange",
+        "Sheet1!$A$1:$B$2",
+        "--in-place",
+    ]);
+    assert!(def.status.success(), "define failed: {:?}", def.stderr);
 
+    // Update it.
     let output = run_cli(&[
-        "clone-row-band",
-        workbook_path.to_str().expect("path utf8"),
-        "--sheet",
-        "Sheet1",
-        "--source-rows",
-        "2:3",
-        "--after",
-        "3",
-        "--repeat",
-        "2",
-        "--expand-adjacent-sums",
-        "--dry-run",
+        "update-name",
+        file,
+        "MyRange",
+        "Sheet1!$A$1:$D$10",
+        "--in-place",
     ]);
     assert!(output.status.success(), "stderr: {:?}", output.stderr);
     let payload = parse_stdout_json(&output);
-    assert_eq!(payload["helper_kind"], "clone_row_band");
-    assert_eq!(payload["source_row_range"], "2:3");
-    assert_eq!(payload["source_row_count"], 2);
-    assert_eq!(payload["inserted_row_range"], "4:7");
-    assert_eq!(payload["inserted_blocks"][0]["row_range"], "4:5");
-    assert_eq!(payload["inserted_blocks"][1]["row_range"], "6:7");
-    assert_eq!(payload["formula_targets"][0], "C4");
-    assert_eq!(payload["adjacent_sum_targets"][0], "C8");
-    assert_eq!(payload["confidence"], "high");
+    assert_eq!(payload["name"], "MyRange");
+    assert_eq!(payload["refers_to"], "Sheet1!$A$1:$D$10");
+    assert!(payload["previous_refers_to"].is_string());
+    assert_eq!(payload["dry_run"], false);
 }
 
 #[test]
-fn cli_clone_row_band_strict_merge_policy_fails_on_crossing_merge() {
+fn cli_update_name_scope_only_keeps_existing_refers_to() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("clone-row-band-strict-merge.xlsx");
+    let workbook_path = tmp.path().join("update-name-scope-only.xlsx");
+    write_phase1_read_surface_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
 
-    let mut workbook = umya_spreadsheet::new_file();
-    {
-        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet1");
-        sheet.get_cell_mut("A1").set_value("Header");
-        sheet.get_cell_mut("A2").set_value("Alpha");
-        sheet.get_cell_mut("A3").set_value("Beta");
-        sheet.add_merge_cells("A1:A2");
-    }
-    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+    let def = run_cli(&[
+        "define-name",
+        file,
+        "ScopeOnlyName",
+        "Sheet1!$A$1:$B$2",
+        "--in-place",
+    ]);
+    assert!(def.status.success(), "define failed: {:?}", def.stderr);
 
     let output = run_cli(&[
-        "clone-row-band",
-        workbook_path.to_str().expect("path utf8"),
-        "--sheet",
+        "update-name",
+        file,
+        "ScopeOnlyName",
+        "--scope",
+        "sheet",
+        "--scope-sheet-name",
         "Sheet1",
-        "--source-rows",
-        "2:3",
-        "--before",
-        "4",
-        "--merge-policy",
-        "strict",
-        "--dry-run",
+        "--in-place",
     ]);
-    assert!(!output.status.success());
-    let err = parse_stderr_json(&output);
-    assert_eq!(err["code"], "UNSAFE_CLONE_TEMPLATE");
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["name"], "ScopeOnlyName");
+    assert_eq!(payload["refers_to"], "'Sheet1'!$A$1:$B$2");
+    assert_eq!(payload["scope_kind"], "sheet");
+    assert_eq!(payload["scope_sheet_name"], "Sheet1");
+    assert!(payload["previous_refers_to"].is_string());
+}
+
+#[test]
+fn cli_delete_name_in_place_removes_name() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("delete-name.xlsx");
+    write_phase1_read_surface_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
+
+    // The fixture already has Sales_Amount.
+    let before = run_cli(&["named-ranges", file, "--name-prefix", "Sales_Amount"]);
+    assert!(before.status.success());
+    let before_payload = parse_stdout_json(&before);
+    let before_items = before_payload["items"].as_array().expect("items");
     assert!(
-        err["message"]
-            .as_str()
-            .unwrap_or_default()
-            .contains("cross the clone boundary")
+        !before_items.is_empty(),
+        "Sales_Amount should exist before delete"
+    );
+
+    let output = run_cli(&["delete-name", file, "Sales_Amount", "--in-place"]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["name"], "Sales_Amount");
+    assert_eq!(payload["deleted"], true);
+
+    // Verify it's gone.
+    let after = run_cli(&["named-ranges", file, "--name-prefix", "Sales_Amount"]);
+    assert!(after.status.success());
+    let after_payload = parse_stdout_json(&after);
+    let after_items = after_payload["items"].as_array();
+    assert!(
+        after_items.is_none() || after_items.unwrap().is_empty(),
+        "Sales_Amount should not exist after delete"
+    );
+}
+
+#[test]
+fn cli_delete_name_not_found_returns_error() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("delete-name-notfound.xlsx");
+    write_phase1_read_surface_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
+
+    let output = run_cli(&["delete-name", file, "NonExistent", "--in-place"]);
+    assert!(
+        !output.status.success(),
+        "should fail for non-existent name"
     );
 }
 
 #[test]
-fn cli_clone_row_band_output_preserves_merges_validations_and_row_heights() {
+fn cli_named_ranges_includes_scope_metadata() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("scope-metadata.xlsx");
+    write_phase1_read_surface_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
+
+    let output = run_cli(&["named-ranges", file]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    let items = payload["items"].as_array().expect("items array");
+    assert!(!items.is_empty());
+
+    // All items should have scope_kind.
+    for item in items {
+        let scope_kind = item["scope_kind"].as_str();
+        assert!(
+            scope_kind == Some("workbook") || scope_kind == Some("sheet"),
+            "item {:?} should have scope_kind 'workbook' or 'sheet', got {:?}",
+            item["name"],
+            scope_kind
+        );
+        if scope_kind == Some("sheet") {
+            assert!(
+                item["scope_sheet_name"].is_string(),
+                "sheet-scoped item should have scope_sheet_name"
+            );
+        }
+    }
+}
+
+// ─── 4105: Recalculate output mode and stateless safety ───
+
+#[test]
+fn cli_recalculate_in_place_preserves_existing_behavior() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("clone-row-band-output.xlsx");
-    let output_path = tmp.path().join("clone-row-band-output-target.xlsx");
-
-    let mut workbook = umya_spreadsheet::new_file();
-    {
-        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet1");
-        sheet.get_cell_mut("A1").set_value("Name");
-        sheet.get_cell_mut("B1").set_value("Input");
-        sheet.get_cell_mut("C1").set_value("Calc");
-        sheet.get_cell_mut("A2").set_value("Alpha");
-        sheet.get_cell_mut("B2").set_value_number(10.0);
-        sheet.get_cell_mut("C2").set_formula("B2*2");
-        sheet.get_cell_mut("A3").set_value("Beta");
-        sheet.get_cell_mut("B3").set_value_number(20.0);
-        sheet.get_cell_mut("C3").set_formula("B3*2");
-        sheet.add_merge_cells("A2:A3");
-        sheet
-            .get_row_dimension_mut(&2)
-            .set_height(28.0)
-            .set_custom_height(true);
-        sheet
-            .get_row_dimension_mut(&3)
-            .set_height(32.0)
-            .set_custom_height(true);
-
-        let mut dv = umya_spreadsheet::structs::DataValidation::default();
-        dv.set_type(umya_spreadsheet::structs::DataValidationValues::List);
-        dv.get_sequence_of_references_mut().set_sqref("B2:B3");
-        dv.set_formula1("\"A,B,C\"");
-        sheet.set_data_validations(umya_spreadsheet::structs::DataValidations::default());
-        sheet
-            .get_data_validations_mut()
-            .unwrap()
-            .add_data_validation_list(dv);
-    }
-    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+    let workbook_path = tmp.path().join("recalc-inplace.xlsx");
+    write_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
 
-    let output = run_cli(&[
-        "clone-row-band",
-        workbook_path.to_str().expect("path utf8"),
-        "--sheet",
-        "Sheet1",
-        "--source-rows",
-        "2:3",
-        "--before",
-        "4",
-        "--repeat",
-        "2",
-        "--patch-targets",
-        "all-non-formula",
-        "--output",
-        output_path.to_str().expect("output utf8"),
-    ]);
+    let output = run_cli(&["recalculate", file]);
     assert!(output.status.success(), "stderr: {:?}", output.stderr);
-    let payload = parse_stdout_json(&output);
-    assert_eq!(payload["changed"], true);
-    assert_eq!(payload["inserted_blocks"][0]["row_range"], "4:5");
-    assert_eq!(payload["inserted_blocks"][1]["row_range"], "6:7");
 
-    let book = umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output workbook");
-    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet1 exists");
-    assert_eq!(sheet.get_cell("A4").expect("A4").get_value(), "Alpha");
-    assert_eq!(sheet.get_cell("A5").expect("A5").get_value(), "Beta");
-    assert_eq!(
-        sheet
-            .get_cell("C4")
-            .expect("C4")
-            .get_formula()
-            .replace(' ', ""),
-        "B4*2"
-    );
-    assert_eq!(
-        sheet
-            .get_cell("C7")
-            .expect("C7")
-            .get_formula()
-            .replace(' ', ""),
-        "B7*2"
+    let payload = parse_stdout_json(&output);
+    assert!(payload["file"].as_str().is_some(), "file field present");
+    assert!(
+        payload["backend"].as_str().is_some(),
+        "backend field present"
     );
-    let merge_ranges: Vec<String> = sheet
-        .get_merge_cells()
-        .iter()
-        .map(|range| range.get_range())
-        .collect();
-    assert!(merge_ranges.contains(&"A4:A5".to_string()));
-    assert!(merge_ranges.contains(&"A6:A7".to_string()));
-    assert_eq!(
-        sheet.get_row_dimension(&4).map(|row| *row.get_height()),
-        Some(28.0)
+    assert!(
+        payload["duration_ms"].as_u64().is_some(),
+        "duration_ms present"
     );
-    assert_eq!(
-        sheet.get_row_dimension(&5).map(|row| *row.get_height()),
-        Some(32.0)
+    // In-place mode should NOT have source_path/target_path/changed
+    assert!(
+        payload.get("source_path").is_none(),
+        "in-place should not emit source_path"
     );
-    let validations = sheet.get_data_validations().expect("validations");
-    let sqrefs: Vec<String> = validations
-        .get_data_validation_list()
-        .iter()
-        .map(|dv| dv.get_sequence_of_references().get_sqref())
-        .collect();
     assert!(
-        sqrefs
-            .iter()
-            .any(|sqref| sqref.contains("B4") && sqref.contains("B5"))
+        payload.get("target_path").is_none(),
+        "in-place should not emit target_path"
     );
     assert!(
-        sqrefs
-            .iter()
-            .any(|sqref| sqref.contains("B6") && sqref.contains("B7"))
+        payload.get("changed").is_none(),
+        "in-place should not emit changed"
     );
 }
 
 #[test]
-fn cli_diff_groups_multi_digit_row_runs_by_coordinates_not_lexicographic_order() {
+fn cli_recalculate_output_mode_copies_and_recalcs_target() {
     let tmp = tempdir().expect("tempdir");
-    let original = tmp.path().join("diff-multi-digit-original.xlsx");
-    let modified = tmp.path().join("diff-multi-digit-modified.xlsx");
+    let source_path = tmp.path().join("recalc-output-source.xlsx");
+    let target_path = tmp.path().join("recalc-output-target.xlsx");
+    write_fixture(&source_path);
+    let source = source_path.to_str().expect("path utf8");
+    let target = target_path.to_str().expect("path utf8");
 
-    let mut workbook = umya_spreadsheet::new_file();
-    {
-        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet1");
-        for row in 2..=12u32 {
-            sheet.get_cell_mut((2, row)).set_value_number(row as i32);
-        }
-    }
-    umya_spreadsheet::writer::xlsx::write(&workbook, &original).expect("write original");
-    fs::copy(&original, &modified).expect("copy workbook");
+    // Capture source bytes before recalc
+    let source_bytes_before = fs::read(&source_path).expect("read source before");
 
-    let mut args = vec![
-        "edit".to_string(),
-        modified.to_str().expect("path utf8").to_string(),
-        "Sheet1".to_string(),
-    ];
-    for row in 2..=12u32 {
-        args.push(format!("B{}={}", row, row + 100));
-    }
-    let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
-    let edit = run_cli(arg_refs.as_slice());
-    assert!(edit.status.success(), "stderr: {:?}", edit.stderr);
+    let output = run_cli(&["recalculate", source, "--output", target]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
 
-    let diff = run_cli(&[
-        "diff",
-        original.to_str().expect("path utf8"),
-        modified.to_str().expect("path utf8"),
-    ]);
-    assert!(diff.status.success(), "stderr: {:?}", diff.stderr);
-    let payload = parse_stdout_json(&diff);
-    assert_eq!(payload["summary"]["group_count"], 1);
-    let preview = payload["summary"]["group_preview"]
-        .as_array()
-        .expect("group preview");
-    assert_eq!(preview.len(), 1);
-    assert_eq!(preview[0]["group_type"], "value_edit");
-    assert_eq!(preview[0]["range"], "B2:B12");
-    assert_eq!(preview[0]["change_count"], 11);
+    let payload = parse_stdout_json(&output);
+
+    // Response metadata fields
+    assert!(
+        payload["source_path"].as_str().is_some(),
+        "source_path should be present in output mode"
+    );
+    assert!(
+        payload["target_path"].as_str().is_some(),
+        "target_path should be present in output mode"
+    );
+    assert_eq!(
+        payload["changed"], true,
+        "changed should be true in output mode"
+    );
+
+    // file field points to the target
+    assert_json_path_eq(&payload, "target_path", target);
+    assert_json_path_eq(&payload, "source_path", source);
+
+    // Target file should exist
+    assert!(
+        target_path.exists(),
+        "target file should exist after recalculate --output"
+    );
+
+    // Source should be unchanged
+    let source_bytes_after = fs::read(&source_path).expect("read source after");
+    assert_eq!(
+        source_bytes_before, source_bytes_after,
+        "source file should remain unchanged in output mode"
+    );
 }
 
 #[test]
-fn cli_diff_rejects_invalid_range_filter() {
+fn cli_recalculate_output_mode_rejects_existing_target_without_force() {
     let tmp = tempdir().expect("tempdir");
-    let original = tmp.path().join("diff-invalid-range-original.xlsx");
-    let modified = tmp.path().join("diff-invalid-range-modified.xlsx");
-    write_fixture(&original);
-    fs::copy(&original, &modified).expect("copy workbook");
+    let source_path = tmp.path().join("recalc-force-source.xlsx");
+    let target_path = tmp.path().join("recalc-force-target.xlsx");
+    write_fixture(&source_path);
+    // Create an existing target
+    write_fixture(&target_path);
+    let source = source_path.to_str().expect("path utf8");
+    let target = target_path.to_str().expect("path utf8");
 
-    let diff = run_cli(&[
-        "diff",
-        original.to_str().expect("path utf8"),
-        modified.to_str().expect("path utf8"),
-        "--range",
-        "NOT_A_RANGE",
-    ]);
-    assert!(!diff.status.success(), "diff should fail for invalid range");
-    let err = parse_stderr_json(&diff);
-    assert_eq!(err["code"], "INVALID_ARGUMENT", "unexpected error: {err}");
+    let output = run_cli(&["recalculate", source, "--output", target]);
+    assert!(
+        !output.status.success(),
+        "should fail when target exists without --force"
+    );
+    let err = parse_stderr_json(&output);
+    assert_eq!(err["code"], "OUTPUT_EXISTS", "unexpected error: {err}");
 }
 
 #[test]
-fn cli_diff_rejects_invalid_details_limit() {
+fn cli_recalculate_output_mode_allows_existing_target_with_force() {
     let tmp = tempdir().expect("tempdir");
-    let original = tmp.path().join("diff-invalid-limit-original.xlsx");
-    let modified = tmp.path().join("diff-invalid-limit-modified.xlsx");
-    write_fixture(&original);
-    fs::copy(&original, &modified).expect("copy workbook");
-
-    let diff = run_cli(&[
-        "diff",
-        original.to_str().expect("path utf8"),
-        modified.to_str().expect("path utf8"),
-        "--details",
-        "--limit",
-        "0",
-    ]);
-    assert!(!diff.status.success(), "diff should fail for limit=0");
-    let err = parse_stderr_json(&diff);
-    assert_eq!(err["code"], "INVALID_ARGUMENT", "unexpected error: {err}");
+    let source_path = tmp.path().join("recalc-force-ok-source.xlsx");
+    let target_path = tmp.path().join("recalc-force-ok-target.xlsx");
+    write_fixture(&source_path);
+    write_fixture(&target_path);
+    let source = source_path.to_str().expect("path utf8");
+    let target = target_path.to_str().expect("path utf8");
+
+    let output = run_cli(&["recalculate", source, "--output", target, "--force"]);
+    assert!(
+        output.status.success(),
+        "should succeed with --force, stderr: {:?}",
+        output.stderr
+    );
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["changed"], true);
+    assert_json_path_eq(&payload, "target_path", target);
 }
 
 #[test]
-fn cli_edit_dry_run_reports_preview_and_preserves_source() {
+fn cli_recalculate_output_force_failure_preserves_existing_target() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("edit-dry-run.xlsx");
-    write_fixture(&workbook_path);
+    let source_path = tmp.path().join("recalc-force-fail-source.xlsx");
+    let target_path = tmp.path().join("recalc-force-fail-target.xlsx");
 
-    let before = fs::read(&workbook_path).expect("read source before dry-run");
-    let file = workbook_path.to_str().expect("path utf8");
+    // Invalid source payload to force recalc failure.
+    fs::write(&source_path, b"not-an-xlsx").expect("write invalid source");
+    write_fixture(&target_path);
 
-    let output = run_cli(&["edit", file, "Sheet1", "--dry-run", "B2=11", "C2==B2*3"]);
-    assert!(output.status.success(), "stderr: {:?}", output.stderr);
-    let payload = parse_stdout_json(&output);
-    assert_eq!(payload["edits_provided"].as_u64(), Some(2));
-    assert_eq!(payload["edits_validated"].as_u64(), Some(2));
-    assert!(payload["would_change"].as_bool().unwrap_or(false));
+    let source = source_path.to_str().expect("path utf8");
+    let target = target_path.to_str().expect("path utf8");
 
-    let affected = payload["affected_cells"]
-        .as_array()
-        .expect("affected_cells array");
-    assert!(affected.iter().any(|cell| cell.as_str() == Some("B2")));
-    assert!(affected.iter().any(|cell| cell.as_str() == Some("C2")));
+    let target_before = fs::read(&target_path).expect("read target before");
 
-    let after = fs::read(&workbook_path).expect("read source after dry-run");
-    assert_eq!(before, after, "dry-run mutated source workbook");
+    let output = run_cli(&["recalculate", source, "--output", target, "--force"]);
+    assert!(
+        !output.status.success(),
+        "recalc should fail for invalid source payload"
+    );
+
+    // Existing target must remain untouched on failure.
+    assert!(
+        target_path.exists(),
+        "target should still exist after failure"
+    );
+    let target_after = fs::read(&target_path).expect("read target after");
+    assert_eq!(
+        target_before, target_after,
+        "existing target content should be preserved on recalc failure"
+    );
 }
 
 #[test]
-fn cli_edit_output_writes_target_only() {
+fn cli_recalculate_output_rejects_same_path_as_source() {
     let tmp = tempdir().expect("tempdir");
-    let source_path = tmp.path().join("edit-output-source.xlsx");
-    let output_path = tmp.path().join("edit-output-target.xlsx");
+    let source_path = tmp.path().join("recalc-same.xlsx");
     write_fixture(&source_path);
+    let source = source_path.to_str().expect("path utf8");
 
-    let before_source = fs::read(&source_path).expect("read source before output mode");
-    let source = source_path.to_str().expect("source utf8");
-    let output = output_path.to_str().expect("output utf8");
-
-    let command = run_cli(&[
-        "edit", source, "Sheet1", "--output", output, "B2=17", "C2==B2*4",
-    ]);
-    assert!(command.status.success(), "stderr: {:?}", command.stderr);
-
-    let payload = parse_stdout_json(&command);
-    assert_eq!(payload["edits_applied"].as_u64(), Some(2));
-    assert_eq!(payload["file"].as_str(), Some(output));
-    assert_json_path_eq(&payload, "source_path", source);
-    assert_json_path_eq(&payload, "target_path", output);
-
-    let after_source = fs::read(&source_path).expect("read source after output mode");
+    let output = run_cli(&["recalculate", source, "--output", source]);
+    assert!(
+        !output.status.success(),
+        "should fail when output == source"
+    );
+    let err = parse_stderr_json(&output);
     assert_eq!(
-        before_source, after_source,
-        "source workbook changed during --output mode"
+        err["code"], "INVALID_ARGUMENT",
+        "unexpected error envelope: {err}"
     );
+}
 
-    let output_book = umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output");
-    let output_sheet = output_book
-        .get_sheet_by_name("Sheet1")
-        .expect("output sheet exists");
-    assert_eq!(output_sheet.get_cell("B2").expect("B2").get_value(), "17");
+#[test]
+fn cli_recalculate_force_without_output_is_invalid() {
+    let tmp = tempdir().expect("tempdir");
+    let source_path = tmp.path().join("recalc-force-alone.xlsx");
+    write_fixture(&source_path);
+    let source = source_path.to_str().expect("path utf8");
+
+    let output = run_cli(&["recalculate", source, "--force"]);
+    assert!(
+        !output.status.success(),
+        "should fail when --force used without --output"
+    );
+    let err = parse_stderr_json(&output);
     assert_eq!(
-        output_sheet.get_cell("C2").expect("C2").get_formula(),
-        "B2*4"
+        err["code"], "INVALID_ARGUMENT",
+        "unexpected error envelope: {err}"
     );
 }
 
 #[test]
-fn cli_edit_mode_matrix_rejects_conflicts() {
+fn cli_recalculate_output_invalid_parent_dir_returns_error() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("edit-mode-matrix.xlsx");
-    write_fixture(&workbook_path);
-    let file = workbook_path.to_str().expect("path utf8");
+    let source_path = tmp.path().join("recalc-invalid-output.xlsx");
+    write_fixture(&source_path);
+    let source = source_path.to_str().expect("path utf8");
 
-    assert_invalid_argument(&["edit", file, "Sheet1", "--dry-run", "--in-place", "B2=1"]);
-    assert_invalid_argument(&[
-        "edit",
-        file,
-        "Sheet1",
-        "--dry-run",
-        "--output",
-        "out.xlsx",
-        "B2=1",
-    ]);
-    assert_invalid_argument(&[
-        "edit",
-        file,
-        "Sheet1",
-        "--in-place",
-        "--output",
-        "out.xlsx",
-        "B2=1",
-    ]);
-    assert_invalid_argument(&["edit", file, "Sheet1", "--force", "B2=1"]);
-    assert_invalid_argument(&["edit", file, "Sheet1", "--output", file, "B2=1"]);
+    let bad_target = tmp.path().join("nonexistent_dir").join("output.xlsx");
+    let target = bad_target.to_str().expect("path utf8");
+
+    let output = run_cli(&["recalculate", source, "--output", target]);
+    assert!(
+        !output.status.success(),
+        "should fail when output parent dir doesn't exist"
+    );
 }
 
 #[test]
-fn cli_edit_dry_run_preflight_fails_for_missing_sheet() {
+fn cli_recalculate_engine_flag_selects_or_explains_engine() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("edit-dry-run-missing-sheet.xlsx");
+    let workbook_path = tmp.path().join("recalc-engine.xlsx");
     write_fixture(&workbook_path);
     let file = workbook_path.to_str().expect("path utf8");
 
-    let err = assert_error_code(
-        &["edit", file, "NoSuchSheet", "--dry-run", "A1=1"],
-        "SHEET_NOT_FOUND",
-    );
-    assert_eq!(err["message"], "sheet 'NoSuchSheet' was not found");
-    assert_eq!(
-        err["try_this"],
-        "run `asp read sheets <file>` to inspect valid names"
-    );
+    let output = run_cli(&["recalculate", file, "--engine", "builtin"]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["backend"], "formualizer");
+
+    if !cfg!(feature = "recalc-libreoffice") {
+        let output = run_cli(&["recalculate", file, "--engine", "libreoffice"]);
+        assert!(!output.status.success(), "libreoffice engine is not built");
+        let err = parse_stderr_json(&output);
+        let message = err["message"].as_str().unwrap_or_default();
+        assert!(
+            message.contains("recalc engine 'libreoffice' unavailable"),
+            "unexpected error envelope: {err}"
+        );
+        assert!(message.contains("--engine builtin"), "{err}");
+    }
 }
 
 #[test]
-fn cli_errors_use_machine_envelope() {
+fn cli_recalculate_cell_scope_recalculates_only_the_dependency_subtree() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("read.xlsx");
-    write_fixture(&workbook_path);
+    let workbook_path = tmp.path().join("recalc-partial.xlsx");
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet");
+        sheet.get_cell_mut("B2").set_value_number(10.0);
+        sheet.get_cell_mut("C2").set_formula("B2*2");
+        sheet.get_cell_mut("B3").set_value_number(20.0);
+        sheet.get_cell_mut("C3").set_formula("B3*2");
+    }
+    workbook.new_sheet("Summary").expect("add summary sheet");
+    workbook
+        .get_sheet_by_name_mut("Summary")
+        .expect("summary")
+        .get_cell_mut("B2")
+        .set_formula("Sheet1!C2+1");
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+    let file = workbook_path.to_str().expect("path utf8");
 
     let output = run_cli(&[
-        "formula-map",
-        workbook_path.to_str().expect("path utf8"),
-        "Shet1",
+        "recalculate",
+        file,
+        "--cell",
+        "Sheet1!B2",
+        "--scope",
+        "dependents",
     ]);
-    assert!(!output.status.success(), "command unexpectedly succeeded");
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["partial"]["cell"], "Sheet1!B2");
+    assert_eq!(payload["partial"]["scope"], "dependents");
+    assert_eq!(payload["partial"]["formula_cells"], 2);
 
-    let err = parse_stderr_json(&output);
-    assert_eq!(err["code"], "SHEET_NOT_FOUND");
-    assert_eq!(err["did_you_mean"], "Sheet1");
-    assert!(
-        err["message"]
-            .as_str()
-            .unwrap_or_default()
-            .contains("was not found")
+    let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read workbook");
+    let sheet1 = book.get_sheet_by_name("Sheet1").expect("sheet1");
+    assert_eq!(sheet1.get_value("C2"), "20");
+    assert_eq!(sheet1.get_value("C3"), "", "C3 is outside the subtree");
+    let summary = book.get_sheet_by_name("Summary").expect("summary");
+    assert_eq!(summary.get_value("B2"), "21");
+
+    assert_error_code(
+        &["recalculate", file, "--cell", "Sheet1!B2"],
+        "INVALID_ARGUMENT",
     );
-    assert!(
-        err["try_this"]
-            .as_str()
-            .unwrap_or_default()
-            .contains("read sheets")
+    assert_error_code(
+        &[
+            "recalculate",
+            file,
+            "--cell",
+            "Sheet1!B2",
+            "--scope",
+            "dependents",
+            "--engine",
+            "libreoffice",
+        ],
+        "INVALID_ARGUMENT",
     );
 }
 
 #[test]
-fn docs_guardrail_relative_mode_literals_are_canonical() {
-    let readme = read_repo_doc("README.md");
-    let npm_readme = read_repo_doc("npm/agent-spreadsheet/README.md");
+fn cli_recalculate_diff_lists_changed_cached_values() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("recalc-diff.xlsx");
+    write_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
 
-    assert!(
-        readme.contains("relative_mode` valid values: `excel`, `abs_cols`, `abs_rows`"),
-        "README should document canonical relative_mode literals"
-    );
+    let output = run_cli(&["recalculate", file]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let output = run_cli(&["edit", file, "Sheet1", "B2=15"]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
 
-    for doc in [&readme, &npm_readme] {
-        assert!(
-            !doc.contains("fully_relative"),
-            "docs should not advertise invalid relative_mode literal fully_relative"
-        );
-    }
+    let output = run_cli(&["recalculate", file, "--diff"]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    let report = &payload["recalc_diff"];
+    assert_eq!(report["change_count"], 1, "{payload}");
+    assert_eq!(report["truncated"], false);
+    assert_eq!(report["by_sheet"]["Sheet1"], 1);
+    let change = &report["changes"][0];
+    assert_eq!(change["sheet"], "Sheet1");
+    assert_eq!(change["address"], "C2");
+    assert_eq!(change["old_value"], "20");
+    assert_eq!(change["new_value"], "30");
+
+    let output = run_cli(&["recalculate", file, "--diff"]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["recalc_diff"]["change_count"], 0);
 }
 
 #[test]
-fn cli_legacy_global_format_csv_returns_output_format_unsupported_envelope() {
-    let output = run_cli(&["--format", "csv", "list-sheets", "/tmp/does-not-exist.xlsx"]);
-    assert!(!output.status.success(), "command unexpectedly succeeded");
+fn cli_recalculate_timeout_flags_validate_and_map_to_recalc_timeout() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("recalc-timeout.xlsx");
+    write_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
 
-    let err = parse_stderr_json(&output);
-    assert_eq!(err["code"], "OUTPUT_FORMAT_UNSUPPORTED");
+    let output = run_cli(&["recalculate", file, "--timeout-ms", "60000"]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+
+    assert_error_code(
+        &["recalculate", file, "--timeout-ms", "0"],
+        "INVALID_ARGUMENT",
+    );
+    assert_error_code(
+        &["recalculate", file, "--strategy", "pooled"],
+        "INVALID_ARGUMENT",
+    );
+    let err = assert_error_code(
+        &[
+            "recalculate",
+            file,
+            "--engine",
+            "libreoffice",
+            "--strategy",
+            "pooled",
+        ],
+        "INVALID_ARGUMENT",
+    );
     assert!(
         err["message"]
             .as_str()
-            .unwrap_or_default()
-            .contains("csv output is not implemented")
-    );
+            .is_some_and(|message| message.contains("not implemented")),
+        "error: {err}"
+    );
+
+    let error = anyhow::Error::new(spreadsheet_kit::errors::RecalcTimeoutError {
+        backend: "libreoffice".to_string(),
+        timeout_ms: 500,
+        elapsed_ms: 502,
+        phase: "soffice".to_string(),
+        completed_phases: Vec::new(),
+        formula_cells: None,
+    })
+    .context("recalculating workbook");
+    let envelope = spreadsheet_kit::cli::errors::envelope_for(&error);
+    assert_eq!(envelope.code, "RECALC_TIMEOUT");
+    let details = envelope.details.expect("timeout details");
+    assert_eq!(details["phase"], "soffice");
+    assert_eq!(details["timeout_ms"], 500);
 }
 
 #[test]
-fn cli_legacy_global_format_json_is_accepted_for_existing_commands() {
-    let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("legacy-format-json.xlsx");
-    write_fixture(&workbook_path);
-    let file = workbook_path.to_str().expect("path utf8");
-
-    let output = run_cli(&["--format", "json", "list-sheets", file]);
-    assert!(output.status.success(), "stderr: {:?}", output.stderr);
-
-    let payload = parse_stdout_json(&output);
-    assert_eq!(payload["sheets"].as_array().map(Vec::len), Some(2));
+fn cli_recalculate_help_shows_output_mode_docs() {
+    let help = run_cli(&["recalculate", "--help"]);
+    assert!(help.status.success(), "stderr: {:?}", help.stderr);
+    let text = parse_stdout_text(&help);
+    assert!(text.contains("--output"), "help should document --output");
+    assert!(text.contains("--force"), "help should document --force");
+    assert!(
+        text.contains("source stays unchanged"),
+        "help should explain source safety"
+    );
 }
 
-#[cfg(feature = "recalc-formualizer")]
 #[test]
-fn cli_recalculate_flow_runs_after_copy_and_edit() {
-    let tmp = tempdir().expect("tempdir");
-    let original = tmp.path().join("original.xlsx");
-    let modified = tmp.path().join("modified.xlsx");
-    write_fixture(&original);
+fn cli_recalculate_parse_output_and_force_flags() {
+    use clap::Parser;
+    use spreadsheet_kit::cli::{Cli, Commands};
 
-    let copy = run_cli(&[
-        "copy",
-        original.to_str().expect("path utf8"),
-        modified.to_str().expect("path utf8"),
-    ]);
-    assert!(copy.status.success(), "stderr: {:?}", copy.stderr);
+    let cli = Cli::try_parse_from([
+        "agent-spreadsheet",
+        "recalculate",
+        "workbook.xlsx",
+        "--output",
+        "out.xlsx",
+        "--force",
+    ])
+    .expect("parse recalculate with output and force");
 
-    let edit = run_cli(&[
-        "edit",
-        modified.to_str().expect("path utf8"),
-        "Sheet1",
-        "B2=25",
-    ]);
-    assert!(edit.status.success(), "stderr: {:?}", edit.stderr);
+    match cli.command {
+        Commands::Recalculate {
+            file,
+            output,
+            force,
+            ..
+        } => {
+            assert_eq!(file, PathBuf::from("workbook.xlsx"));
+            assert_eq!(output, Some(PathBuf::from("out.xlsx")));
+            assert!(force);
+        }
+        other => panic!("unexpected command: {other:?}"),
+    }
 
-    let recalc = run_cli(&["recalculate", modified.to_str().expect("path utf8")]);
-    assert!(recalc.status.success(), "stderr: {:?}", recalc.stderr);
-    let recalc_payload = parse_stdout_json(&recalc);
-    assert_eq!(recalc_payload["backend"], "formualizer");
-    assert!(recalc_payload["duration_ms"].as_u64().is_some());
+    // Without output/force
+    let cli2 = Cli::try_parse_from(["agent-spreadsheet", "recalculate", "workbook.xlsx"])
+        .expect("parse recalculate without flags");
 
-    let diff = run_cli(&[
-        "diff",
-        original.to_str().expect("path utf8"),
-        modified.to_str().expect("path utf8"),
-    ]);
-    assert!(diff.status.success(), "stderr: {:?}", diff.stderr);
-    let diff_payload = parse_stdout_json(&diff);
-    assert!(diff_payload["change_count"].as_u64().unwrap_or(0) >= 1);
+    match cli2.command {
+        Commands::Recalculate {
+            file,
+            output,
+            force,
+            ..
+        } => {
+            assert_eq!(file, PathBuf::from("workbook.xlsx"));
+            assert!(output.is_none());
+            assert!(!force);
+        }
+        other => panic!("unexpected command: {other:?}"),
+    }
 }
 
-// ─── 3203: Write preflight formula parse policy tests ───
+// ---------------------------------------------------------------------------
+// Ticket 4104 – CLI integration: insert_rows expand_adjacent_sums + clone_row
+// ---------------------------------------------------------------------------
+
+fn write_sum_fixture(path: &Path) {
+    let mut workbook = umya_spreadsheet::new_file();
+    let sheet = workbook.get_sheet_by_name_mut("Sheet1").unwrap();
+    sheet.get_cell_mut("A1").set_value_number(10.0);
+    sheet.get_cell_mut("A2").set_value_number(20.0);
+    sheet.get_cell_mut("A3").set_value_number(30.0);
+    sheet.get_cell_mut("A4").set_formula("SUM(A1:A3)");
+    umya_spreadsheet::writer::xlsx::write(&workbook, path).expect("write fixture");
+}
 
 #[test]
-fn cli_edit_invalid_formula_default_fail_returns_error_envelope() {
+fn cli_structure_batch_insert_rows_expand_adjacent_sums() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("edit-formula-fail.xlsx");
-    write_fixture(&workbook_path);
-    let file = workbook_path.to_str().expect("path utf8");
+    let wb = tmp.path().join("expand_sum.xlsx");
+    let ops_path = tmp.path().join("ops.json");
+    write_sum_fixture(&wb);
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"insert_rows","sheet_name":"Sheet1","at_row":4,"count":1,"expand_adjacent_sums":true}]}"#,
+    );
 
-    // "==SUM(A1:A10" is a formula (double = means formula) with unclosed paren
-    let output = run_cli(&["edit", file, "Sheet1", "B2==SUM(A1:A10"]);
+    let file = wb.to_str().unwrap();
+    let ops_ref = format!("@{}", ops_path.to_str().unwrap());
+
+    let output = run_cli(&[
+        "structure-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
+    ]);
     assert!(
-        !output.status.success(),
-        "command should fail for invalid formula"
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
     );
 
-    let error = parse_stderr_json(&output);
-    assert_eq!(error["code"], "FORMULA_PARSE_FAILED");
+    let book = umya_spreadsheet::reader::xlsx::read(&wb).expect("read workbook");
+    let sheet = book.get_sheet_by_name("Sheet1").unwrap();
+    // Subtotal shifted to row 5; formula expanded to include new row 4.
+    let formula = sheet.get_cell("A5").unwrap().get_formula().to_string();
+    assert_eq!(
+        formula.to_uppercase().replace(' ', ""),
+        "SUM(A1:A4)",
+        "SUM should expand to include inserted row"
+    );
 }
 
 #[test]
-fn cli_edit_invalid_formula_warn_mode_partial_apply_with_diagnostics() {
+fn cli_structure_batch_clone_row_in_place() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("edit-formula-warn.xlsx");
-    write_fixture(&workbook_path);
-    let file = workbook_path.to_str().expect("path utf8");
+    let wb = tmp.path().join("clone_row.xlsx");
+    let ops_path = tmp.path().join("ops.json");
+
+    // Build fixture: header, template row, subtotal
+    {
+        let mut workbook = umya_spreadsheet::new_file();
+        let sheet = workbook.get_sheet_by_name_mut("Sheet1").unwrap();
+        sheet.get_cell_mut("A1").set_value("Header");
+        sheet.get_cell_mut("B1").set_value_number(100.0);
+        sheet.get_cell_mut("A2").set_value("Total");
+        sheet.get_cell_mut("B2").set_formula("SUM(B1:B1)");
+        umya_spreadsheet::writer::xlsx::write(&workbook, &wb).expect("write fixture");
+    }
+
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"clone_row","sheet_name":"Sheet1","source_row":1,"insert_at":2,"count":2,"expand_adjacent_sums":true}]}"#,
+    );
+
+    let file = wb.to_str().unwrap();
+    let ops_ref = format!("@{}", ops_path.to_str().unwrap());
 
     let output = run_cli(&[
-        "edit",
+        "structure-batch",
         file,
-        "Sheet1",
-        "B2=42",
-        "C2==SUM(A1:A10",
-        "--formula-parse-policy",
-        "warn",
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
     ]);
-    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
     let payload = parse_stdout_json(&output);
+    assert!(payload["changed"].as_bool().unwrap_or(false));
 
-    // B2=42 (value, not formula) should apply; C2 formula is invalid → skipped
-    assert_eq!(payload["edits_applied"], 1);
-    assert_eq!(payload["recalc_needed"], true);
+    let book = umya_spreadsheet::reader::xlsx::read(&wb).expect("read workbook");
+    let sheet = book.get_sheet_by_name("Sheet1").unwrap();
 
-    let diagnostics = &payload["formula_parse_diagnostics"];
-    assert!(diagnostics.is_object(), "expected diagnostics object");
-    assert_eq!(diagnostics["policy"], "warn");
-    assert!(diagnostics["total_errors"].as_u64().unwrap_or(0) > 0);
+    // Cloned rows at 2 and 3 should copy template values.
+    let a2 = sheet.get_cell("A2").unwrap().get_value().to_string();
+    assert_eq!(a2, "Header");
+    let b2 = sheet.get_cell("B2").unwrap().get_value().to_string();
+    assert_eq!(b2, "100");
+
+    // Subtotal shifted to row 4; formula expanded.
+    let formula = sheet.get_cell("B4").unwrap().get_formula().to_string();
+    assert_eq!(
+        formula.to_uppercase().replace(' ', ""),
+        "SUM(B1:B3)",
+        "SUM should expand to include cloned rows"
+    );
 }
 
 #[test]
-fn cli_edit_invalid_formula_off_mode_permissive_write() {
+fn cli_end_to_end_budget_cloning_and_appending() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("edit-formula-off.xlsx");
-    write_fixture(&workbook_path);
-    let file = workbook_path.to_str().expect("path utf8");
+    let wb = tmp.path().join("budget.xlsx");
+    let rows_path = tmp.path().join("rows.json");
 
-    let output = run_cli(&[
-        "edit",
-        file,
-        "Sheet1",
-        "B2==SUM(A1:A10",
-        "--formula-parse-policy",
-        "off",
-    ]);
-    assert!(output.status.success(), "stderr: {:?}", output.stderr);
-    let payload = parse_stdout_json(&output);
-    assert_eq!(payload["edits_applied"], 1);
-    assert!(payload["formula_parse_diagnostics"].is_null());
-}
+    // 1. Build initial budget template
+    {
+        let mut workbook = umya_spreadsheet::new_file();
+        let sheet = workbook.get_sheet_by_name_mut("Sheet1").unwrap();
+        sheet.get_cell_mut("A1").set_value("Dept: Marketing");
 
-#[test]
-fn cli_transform_batch_fill_invalid_formula_warn_mode_partial_apply() {
-    let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("transform-fill-formula-warn.xlsx");
-    let ops_path = tmp.path().join("ops.json");
-    write_fixture(&workbook_path);
-    write_ops_payload(
-        &ops_path,
-        r#"{"ops":[
-            {"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"cells","cells":["B2"]},"value":"SUM(A1:A10","is_formula":true},
-            {"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"cells","cells":["B3"]},"value":"42"}
-        ]}"#,
-    );
+        sheet.get_cell_mut("A2").set_value("Item");
+        sheet.get_cell_mut("B2").set_value("Cost");
 
-    let file = workbook_path.to_str().expect("path utf8");
-    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
+        sheet.get_cell_mut("A3").set_value("Ads");
+        sheet.get_cell_mut("B3").set_value_number(5000.0);
 
-    let output = run_cli(&[
-        "transform-batch",
-        file,
-        "--ops",
-        ops_ref.as_str(),
-        "--in-place",
-        "--formula-parse-policy",
-        "warn",
-    ]);
-    assert!(output.status.success(), "stderr: {:?}", output.stderr);
-    let payload = parse_stdout_json(&output);
+        sheet.get_cell_mut("A4").set_value("Subtotal");
+        sheet.get_cell_mut("B4").set_formula("SUM(B3:B3)");
 
-    // Only the second op (value fill) should apply; first (bad formula) skipped
-    assert_eq!(payload["op_count"], 1);
-    assert_eq!(payload["applied_count"], 1);
+        // Let's make "Dept: Marketing" span A1:B1 to test safe merge policy drop
+        sheet.add_merge_cells("A1:B1");
 
-    let diagnostics = &payload["formula_parse_diagnostics"];
-    assert!(diagnostics.is_object(), "expected diagnostics object");
-    assert_eq!(diagnostics["policy"], "warn");
-    assert!(diagnostics["total_errors"].as_u64().unwrap_or(0) > 0);
-}
+        // Grand Total row at the bottom (Row 7 now, leaving row 5, 6 blank to space it out)
+        sheet.get_cell_mut("A7").set_value("Grand Total");
+        sheet.get_cell_mut("B7").set_formula("B4"); // Simple ref to Dept Total
 
-#[test]
-fn cli_transform_batch_fill_invalid_formula_fail_mode_aborts_no_output() {
-    let tmp = tempdir().expect("tempdir");
-    let source_path = tmp.path().join("transform-fill-formula-fail.xlsx");
-    let output_path = tmp.path().join("transform-fill-formula-fail-output.xlsx");
-    let ops_path = tmp.path().join("ops.json");
-    write_fixture(&source_path);
-    write_ops_payload(
-        &ops_path,
-        r#"{"ops":[
-            {"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"cells","cells":["B2"]},"value":"SUM(A1:A10","is_formula":true}
-        ]}"#,
-    );
+        umya_spreadsheet::writer::xlsx::write(&workbook, &wb).expect("write fixture");
+    }
 
-    let file = source_path.to_str().expect("path utf8");
-    let out = output_path.to_str().expect("output path utf8");
-    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
+    let baseline = tmp.path().join("baseline.xlsx");
+    fs::copy(&wb, &baseline).unwrap();
 
-    let output = run_cli(&[
-        "transform-batch",
+    let file = wb.to_str().unwrap();
+    let baseline_file = baseline.to_str().unwrap();
+
+    // 2. Clone the department band (Rows 1:5) to create a new department below it
+    // Row 5 is blank, providing a gutter. We insert after row 5. It will become rows 6:10.
+    let clone_out = run_cli(&[
+        "clone-row-band",
         file,
-        "--ops",
-        ops_ref.as_str(),
-        "--output",
-        out,
-        "--formula-parse-policy",
-        "fail",
+        "--sheet",
+        "Sheet1",
+        "--source-rows",
+        "1:5",
+        "--after",
+        "5",
+        "--expand-adjacent-sums",
+        "--patch-targets",
+        "likely-inputs",
+        "--merge-policy",
+        "safe",
+        "--in-place",
     ]);
-    assert!(!output.status.success(), "command should fail");
-    let error = parse_stderr_json(&output);
-    assert_eq!(error["code"], "FORMULA_PARSE_FAILED");
-
-    // No output file should be created
     assert!(
-        !output_path.exists(),
-        "output file should not exist on fail mode abort"
+        clone_out.status.success(),
+        "clone failed: {:?}",
+        clone_out.stderr
     );
-}
 
-#[test]
-fn cli_transform_batch_dry_run_formula_diagnostics_parity() {
-    let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("transform-formula-dryrun.xlsx");
-    let ops_path = tmp.path().join("ops.json");
-    write_fixture(&workbook_path);
-    write_ops_payload(
-        &ops_path,
-        r#"{"ops":[
-            {"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"cells","cells":["B2"]},"value":"SUM(A1:A10","is_formula":true},
-            {"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"cells","cells":["B3"]},"value":"42"}
-        ]}"#,
+    // 3. Edit the new department's patch targets (It cloned to rows 6:10)
+    // The "likely inputs" should be B8 (the number 5000.0). We also want to edit A6 to "Dept: Sales"
+    let edit_out = run_cli(&[
+        "edit",
+        file,
+        "Sheet1",
+        "A6=Dept: Sales",
+        "A8=Travel",
+        "B8=2000",
+        "B12==B4+B9", // Update Grand Total to include new dept. Grand total shifted from row 7 to 12.
+    ]);
+    assert!(
+        edit_out.status.success(),
+        "edit failed: {:?}",
+        edit_out.stderr
     );
 
-    let file = workbook_path.to_str().expect("path utf8");
-    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
-
-    let output = run_cli(&[
-        "transform-batch",
+    // 4. Append a new line item to the new "Sales" department (Rows 6:10)
+    // The table for Sales is A7:B8, with footer at row 9 ("Dept Total").
+    // We append to region 2.
+    fs::write(&rows_path, r#"{"rows":[["Software",1500]]}"#).unwrap();
+    let append_out = run_cli(&[
+        "append-region",
         file,
-        "--ops",
-        ops_ref.as_str(),
-        "--dry-run",
-        "--formula-parse-policy",
-        "warn",
+        "--sheet",
+        "Sheet1",
+        "--region-id",
+        "0",
+        "--rows",
+        &format!("@{}", rows_path.to_str().unwrap()),
+        "--in-place",
     ]);
-    assert!(output.status.success(), "stderr: {:?}", output.stderr);
-    let payload = parse_stdout_json(&output);
+    assert!(
+        append_out.status.success(),
+        "append failed: {}",
+        String::from_utf8_lossy(&append_out.stderr)
+    );
 
-    let diagnostics = &payload["formula_parse_diagnostics"];
+    // 5. Recalculate
+    let recalc_out = run_cli(&["recalculate", file]);
     assert!(
-        diagnostics.is_object(),
-        "expected diagnostics object in dry-run"
+        recalc_out.status.success(),
+        "recalc failed: {:?}",
+        recalc_out.stderr
     );
-    assert_eq!(diagnostics["policy"], "warn");
-    assert!(diagnostics["total_errors"].as_u64().unwrap_or(0) > 0);
 
-    // Source should be untouched
-    let before = std::fs::read(&workbook_path).expect("read source");
-    let after = std::fs::read(&workbook_path).expect("read source again");
-    assert_eq!(before, after, "dry-run mutated source");
-}
+    // 6. Verify and Diff
+    let verify_out = run_cli(&["verify", "--sheet", "Sheet1", baseline_file, file]);
+    assert!(
+        verify_out.status.success(),
+        "verify failed: {:?}",
+        verify_out.stderr
+    );
+    let verify_json = parse_stdout_json(&verify_out);
+    assert_eq!(
+        verify_json["summary"]["new_error_count"], 0,
+        "should have no new errors"
+    );
 
-#[test]
-fn cli_edit_valid_formula_succeeds_with_default_policy() {
-    let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("edit-formula-valid.xlsx");
-    write_fixture(&workbook_path);
-    let file = workbook_path.to_str().expect("path utf8");
+    let final_book = umya_spreadsheet::reader::xlsx::read(&wb).unwrap();
+    let final_sheet = final_book.get_sheet_by_name("Sheet1").unwrap();
 
-    let output = run_cli(&["edit", file, "Sheet1", "B2==SUM(A1:A4)"]);
-    assert!(output.status.success(), "stderr: {:?}", output.stderr);
-    let payload = parse_stdout_json(&output);
-    assert_eq!(payload["edits_applied"], 1);
-    // No diagnostics when formula is valid
-    assert!(
-        payload["formula_parse_diagnostics"].is_null(),
-        "no diagnostics for valid formula"
+    for i in 1..=14 {
+        let a = final_sheet
+            .get_cell((1, i))
+            .map(|c| c.get_value().to_string())
+            .unwrap_or_default();
+        let b = final_sheet
+            .get_cell((2, i))
+            .map(|c| c.get_value().to_string())
+            .unwrap_or_default();
+        let bf = final_sheet
+            .get_cell((2, i))
+            .map(|c| c.get_formula().to_string())
+            .unwrap_or_default();
+        println!("Row {i}: {a} | {b} | {bf}");
+    }
+
+    // Check original Dept
+    assert_eq!(
+        final_sheet.get_cell("A1").unwrap().get_value(),
+        "Dept: Marketing"
     );
-}
+    assert_eq!(
+        final_sheet
+            .get_cell("B4")
+            .unwrap()
+            .get_formula()
+            .replace(' ', ""),
+        "SUM(B3:B3)"
+    );
+    assert_eq!(final_sheet.get_cell("B4").unwrap().get_value(), "5000"); // Cached from recalc
 
-// ─── 3204: structure-batch tokenizer policy + diagnostics tests ───
+    // Check new Dept (Sales)
+    assert_eq!(
+        final_sheet.get_cell("A6").unwrap().get_value(),
+        "Dept: Sales"
+    );
+    assert_eq!(final_sheet.get_cell("A8").unwrap().get_value(), "Travel");
+    assert_eq!(final_sheet.get_cell("B8").unwrap().get_value(), "2000");
 
-#[test]
-fn cli_structure_batch_rename_with_malformed_formula_warn_mode() {
-    let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("structure-rename-warn.xlsx");
+    // Check appended row (inserted at row 9, pushing footer to 10)
+    assert_eq!(final_sheet.get_cell("A9").unwrap().get_value(), "Software");
+    assert_eq!(final_sheet.get_cell("B9").unwrap().get_value(), "1500");
 
-    {
-        let mut workbook = umya_spreadsheet::new_file();
-        {
-            let sheet = workbook
-                .get_sheet_by_name_mut("Sheet1")
-                .expect("default sheet");
-            sheet.get_cell_mut("A1").set_value("Hello");
-        }
-        workbook.new_sheet("Sheet2").expect("add Sheet2");
-        {
-            let sheet = workbook.get_sheet_by_name_mut("Sheet2").expect("Sheet2");
-            sheet.get_cell_mut("A1").set_value_number(10.0);
-            sheet.get_cell_mut("B1").set_formula("SUM(\"Sheet1!A1:A10)");
-        }
-        umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write");
-    }
+    // Check new footer
+    assert_eq!(final_sheet.get_cell("A10").unwrap().get_value(), "Subtotal");
+    assert_eq!(
+        final_sheet
+            .get_cell("B10")
+            .unwrap()
+            .get_formula()
+            .replace(' ', ""),
+        "SUM(B8:B9)"
+    );
+    assert_eq!(final_sheet.get_cell("B10").unwrap().get_value(), "3500"); // 2000 + 1500
 
-    let ops_path = tmp.path().join("ops.json");
-    write_ops_payload(
-        &ops_path,
-        r#"{"ops":[{"kind":"rename_sheet","old_name":"Sheet1","new_name":"Renamed"}]}"#,
+    // Check Grand Total (shifted to row 13 due to the append-region insertion)
+    assert_eq!(
+        final_sheet.get_cell("A13").unwrap().get_value(),
+        "Grand Total"
+    );
+    assert_eq!(
+        final_sheet
+            .get_cell("B13")
+            .unwrap()
+            .get_formula()
+            .replace(' ', ""),
+        "B4+B10"
     );
+    assert_eq!(final_sheet.get_cell("B13").unwrap().get_value(), "8500"); // 5000 + 3500
+}
 
+#[test]
+fn cli_materialize_writes_summary_sheet_and_refreshes_from_recorded_query() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("materialize.xlsx");
+    write_fixture(&workbook_path);
     let file = workbook_path.to_str().expect("path utf8");
-    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
 
-    let output = run_cli(&[
-        "structure-batch",
+    let created = run_cli(&[
+        "materialize",
         file,
-        "--ops",
-        ops_ref.as_str(),
+        "--target-sheet",
+        "Big Amounts",
+        "--sheet",
+        "Sheet1",
+        "--range",
+        "A1:B4",
+        "--filters-json",
+        r#"[{"column":"Amount","op":"gt","value":15}]"#,
         "--in-place",
-        "--formula-parse-policy",
-        "warn",
     ]);
-    assert!(output.status.success(), "stderr: {:?}", output.stderr);
-    let payload = parse_stdout_json(&output);
-    assert_eq!(payload["applied_count"], 1);
+    assert!(created.status.success(), "stderr: {:?}", created.stderr);
+    let payload = parse_stdout_json(&created);
+    assert_eq!(payload["mode"], "in_place");
+    assert_eq!(payload["results"][0]["range"], "A1:B3");
+    assert_eq!(payload["results"][0]["rows"], 2);
 
-    let diagnostics = &payload["formula_parse_diagnostics"];
+    let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read workbook");
+    let summary = book.get_sheet_by_name("Big Amounts").expect("target sheet");
+    assert_eq!(summary.get_value("A1"), "Name");
+    assert_eq!(summary.get_value("A2"), "Bob");
+    assert_eq!(summary.get_value("B3"), "30");
+    let definitions = book
+        .get_sheet_by_name("_materialized")
+        .expect("metadata sheet");
+    assert_eq!(definitions.get_sheet_state(), "hidden");
+    assert_eq!(definitions.get_value("A2"), "Big Amounts");
+
+    let edit = run_cli(&["edit", file, "Sheet1", "B2=50"]);
+    assert!(edit.status.success(), "stderr: {:?}", edit.stderr);
+    let refreshed = run_cli(&["materialize", file, "--refresh", "--in-place"]);
+    assert!(refreshed.status.success(), "stderr: {:?}", refreshed.stderr);
+    let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read workbook");
+    let summary = book.get_sheet_by_name("Big Amounts").expect("target sheet");
+    assert_eq!(summary.get_value("A2"), "Alice");
+    assert_eq!(summary.get_value("A4"), "Carol");
+
+    let fresh_path = tmp.path().join("summary-only.xlsx");
+    let fresh = fresh_path.to_str().expect("path utf8");
+    let new_workbook = run_cli(&[
+        "materialize",
+        file,
+        "--target-sheet",
+        "Names",
+        "--sheet",
+        "Sheet1",
+        "--range",
+        "A1:A4",
+        "--new-workbook",
+        "--output",
+        fresh,
+    ]);
     assert!(
-        diagnostics.is_object(),
-        "expected formula_parse_diagnostics object"
+        new_workbook.status.success(),
+        "stderr: {:?}",
+        new_workbook.stderr
     );
-    assert_eq!(diagnostics["policy"], "warn");
-    assert!(diagnostics["total_errors"].as_u64().unwrap_or(0) > 0);
-}
-
-#[test]
-fn cli_structure_batch_rename_with_malformed_formula_fail_mode() {
-    let tmp = tempdir().expect("tempdir");
-    let source_path = tmp.path().join("structure-rename-fail.xlsx");
-    let output_path = tmp.path().join("structure-rename-fail-output.xlsx");
-
-    {
-        let mut workbook = umya_spreadsheet::new_file();
-        {
-            let sheet = workbook
-                .get_sheet_by_name_mut("Sheet1")
-                .expect("default sheet");
-            sheet.get_cell_mut("A1").set_value("Hello");
-        }
-        workbook.new_sheet("Sheet2").expect("add Sheet2");
-        {
-            let sheet = workbook.get_sheet_by_name_mut("Sheet2").expect("Sheet2");
-            sheet.get_cell_mut("A1").set_value_number(10.0);
-            sheet.get_cell_mut("B1").set_formula("SUM(\"Sheet1!A1:A10)");
-        }
-        umya_spreadsheet::writer::xlsx::write(&workbook, &source_path).expect("write");
-    }
-
-    let ops_path = tmp.path().join("ops.json");
-    write_ops_payload(
-        &ops_path,
-        r#"{"ops":[{"kind":"rename_sheet","old_name":"Sheet1","new_name":"Renamed"}]}"#,
+    let book = umya_spreadsheet::reader::xlsx::read(&fresh_path).expect("read new workbook");
+    assert!(book.get_sheet_by_name("Sheet1").is_none());
+    assert_eq!(
+        book.get_sheet_by_name("Names")
+            .expect("target sheet")
+            .get_value("A4"),
+        "Carol"
     );
 
-    let file = source_path.to_str().expect("path utf8");
-    let out = output_path.to_str().expect("output path utf8");
-    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
-
-    let output = run_cli(&[
-        "structure-batch",
+    assert_invalid_argument(&[
+        "materialize",
         file,
-        "--ops",
-        ops_ref.as_str(),
-        "--output",
-        out,
-        "--formula-parse-policy",
-        "fail",
+        "--target-sheet",
+        "Sheet1",
+        "--sheet",
+        "Sheet1",
+        "--dry-run",
+    ]);
+    assert_invalid_argument(&[
+        "materialize",
+        fresh,
+        "--refresh",
+        "--target-sheet",
+        "Nope",
+        "--dry-run",
     ]);
-    assert!(!output.status.success(), "should fail");
-    let error = parse_stderr_json(&output);
-    assert_eq!(error["code"], "FORMULA_PARSE_FAILED");
-    assert!(
-        !output_path.exists(),
-        "output should not be created on fail"
-    );
 }
 
 #[test]
-fn cli_structure_batch_insert_rows_with_malformed_formula_warn_mode() {
+fn cli_refresh_rewrites_materialized_sheets_and_reports_row_deltas() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("structure-insert-warn.xlsx");
-
-    {
-        let mut workbook = umya_spreadsheet::new_file();
-        {
-            let sheet = workbook
-                .get_sheet_by_name_mut("Sheet1")
-                .expect("default sheet");
-            sheet.get_cell_mut("A1").set_value_number(1.0);
-            sheet.get_cell_mut("A2").set_value_number(2.0);
-        }
-        workbook.new_sheet("Sheet2").expect("add Sheet2");
-        {
-            let sheet = workbook.get_sheet_by_name_mut("Sheet2").expect("Sheet2");
-            sheet.get_cell_mut("A1").set_formula("SUM(\"Sheet1!A1:A10)");
-            sheet.get_cell_mut("B1").set_formula("Sheet1!A1+1");
-        }
-        umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write");
-    }
-
-    let ops_path = tmp.path().join("ops.json");
-    write_ops_payload(
-        &ops_path,
-        r#"{"ops":[{"kind":"insert_rows","sheet_name":"Sheet1","at_row":1,"count":2}]}"#,
-    );
-
+    let workbook_path = tmp.path().join("refresh.xlsx");
+    write_fixture(&workbook_path);
     let file = workbook_path.to_str().expect("path utf8");
-    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
 
-    let output = run_cli(&[
-        "structure-batch",
+    let created = run_cli(&[
+        "materialize",
         file,
-        "--ops",
-        ops_ref.as_str(),
+        "--target-sheet",
+        "Big Amounts",
+        "--sheet",
+        "Sheet1",
+        "--range",
+        "A1:B4",
+        "--filters-json",
+        r#"[{"column":"Amount","op":"gt","value":15}]"#,
         "--in-place",
-        "--formula-parse-policy",
-        "warn",
     ]);
-    assert!(output.status.success(), "stderr: {:?}", output.stderr);
-    let payload = parse_stdout_json(&output);
-    assert_eq!(payload["applied_count"], 1);
-
-    let diagnostics = &payload["formula_parse_diagnostics"];
-    assert!(
-        diagnostics.is_object(),
-        "expected formula_parse_diagnostics object"
-    );
-    assert_eq!(diagnostics["policy"], "warn");
-    assert!(diagnostics["total_errors"].as_u64().unwrap_or(0) > 0);
-}
+    assert!(created.status.success(), "stderr: {:?}", created.stderr);
 
-#[test]
-fn cli_structure_batch_rename_defined_name_malformed_formula_warn_diagnostics() {
-    let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("structure-defname-warn.xlsx");
+    let edit = run_cli(&["edit", file, "Sheet1", "B2=50"]);
+    assert!(edit.status.success(), "stderr: {:?}", edit.stderr);
 
-    {
-        let mut workbook = umya_spreadsheet::new_file();
-        {
-            let sheet = workbook
-                .get_sheet_by_name_mut("Sheet1")
-                .expect("default sheet");
-            sheet.get_cell_mut("A1").set_value_number(42.0);
-        }
-        let workbook_scoped_bad_range = {
-            let sheet = workbook
-                .get_sheet_by_name_mut("Sheet1")
-                .expect("default sheet");
-            sheet
-                .add_defined_name("BadRange", "=SUM(\"abc)")
-                .expect("defined name BadRange");
-            sheet
-                .get_defined_names()
-                .first()
-                .expect("sheet defined name")
-                .clone()
-        };
-        workbook.add_defined_names(workbook_scoped_bad_range);
+    let preview = run_cli(&["write", "refresh", file, "--dry-run"]);
+    assert!(preview.status.success(), "stderr: {:?}", preview.stderr);
+    let payload = parse_stdout_json(&preview);
+    assert_eq!(payload["mode"], "dry_run");
+    assert_eq!(payload["refresh"], true);
+    assert_eq!(payload["row_count_changes"], 1);
+    assert_eq!(payload["results"][0]["previous_rows"], 2);
+    assert_eq!(payload["results"][0]["rows"], 3);
+    assert_eq!(payload["results"][0]["row_delta"], 1);
+    assert!(payload["results"][0]["previous_refreshed_at"].is_string());
+
+    let refreshed = run_cli(&["refresh", file, "--sheet", "big amounts", "--in-place"]);
+    assert!(refreshed.status.success(), "stderr: {:?}", refreshed.stderr);
+    let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read workbook");
+    let summary = book.get_sheet_by_name("Big Amounts").expect("target sheet");
+    assert_eq!(summary.get_value("A2"), "Alice");
+    assert_eq!(summary.get_value("A4"), "Carol");
 
-        umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write");
-    }
+    let unchanged = run_cli(&["refresh", file, "--dry-run"]);
+    assert!(unchanged.status.success(), "stderr: {:?}", unchanged.stderr);
+    let payload = parse_stdout_json(&unchanged);
+    assert_eq!(payload["row_count_changes"], 0);
+    assert_eq!(payload["results"][0]["row_delta"], 0);
 
-    let ops_path = tmp.path().join("ops.json");
-    write_ops_payload(
-        &ops_path,
-        r#"{"ops":[{"kind":"rename_sheet","old_name":"Sheet1","new_name":"Data"}]}"#,
-    );
+    assert_invalid_argument(&["refresh", file, "--sheet", "Nope", "--dry-run"]);
+}
 
+#[test]
+fn cli_metadata_round_trips_through_hidden_sheet() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("metadata.xlsx");
+    write_fixture(&workbook_path);
     let file = workbook_path.to_str().expect("path utf8");
-    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
 
-    let output = run_cli(&[
-        "structure-batch",
+    let empty = run_cli(&["metadata", file]);
+    assert!(empty.status.success(), "stderr: {:?}", empty.stderr);
+    assert_eq!(parse_stdout_json(&empty)["count"], 0);
+
+    let set = run_cli(&[
+        "set-metadata",
         file,
-        "--ops",
-        ops_ref.as_str(),
+        "names",
+        "revenue",
+        r#"{"range":"Sheet1!B2:B4","unit":"USD"}"#,
         "--in-place",
-        "--formula-parse-policy",
-        "warn",
     ]);
-    assert!(output.status.success(), "stderr: {:?}", output.stderr);
-    let payload = parse_stdout_json(&output);
-
-    let diagnostics = &payload["formula_parse_diagnostics"];
-    assert!(
-        diagnostics.is_object(),
-        "expected formula_parse_diagnostics"
-    );
-    assert_eq!(diagnostics["policy"], "warn");
-    assert!(diagnostics["total_errors"].as_u64().unwrap_or(0) > 0);
+    assert!(set.status.success(), "stderr: {:?}", set.stderr);
+    let payload = parse_stdout_json(&set);
+    assert_eq!(payload["replaced"], false);
+    assert_eq!(payload["value"]["unit"], "USD");
+
+    let note = run_cli(&[
+        "write",
+        "metadata",
+        "set",
+        file,
+        "audit",
+        "last_review",
+        "signed off",
+        "--in-place",
+    ]);
+    assert!(note.status.success(), "stderr: {:?}", note.stderr);
 
-    let groups = diagnostics["groups"].as_array().expect("groups array");
-    assert!(!groups.is_empty(), "should have at least one error group");
-    let first_group = &groups[0];
-    assert_eq!(
-        first_group["sheet_name"], "[DefinedName]",
-        "defined name errors should use [DefinedName] as sheet_name"
-    );
-}
+    let replaced = run_cli(&[
+        "set-metadata",
+        file,
+        "names",
+        "revenue",
+        r#"{"range":"Sheet1!B2:B5"}"#,
+        "--in-place",
+    ]);
+    assert!(replaced.status.success(), "stderr: {:?}", replaced.stderr);
+    let payload = parse_stdout_json(&replaced);
+    assert_eq!(payload["replaced"], true);
+    assert_eq!(payload["previous_value"]["range"], "Sheet1!B2:B4");
 
-#[test]
-fn cli_structure_batch_no_malformed_formulas_no_diagnostics() {
-    let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("structure-clean.xlsx");
-    write_fixture(&workbook_path);
+    let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read workbook");
+    let sheet = book.get_sheet_by_name("_metadata").expect("metadata sheet");
+    assert_eq!(sheet.get_sheet_state(), "hidden");
 
-    let ops_path = tmp.path().join("ops.json");
-    write_ops_payload(
-        &ops_path,
-        r#"{"ops":[{"kind":"rename_sheet","old_name":"Summary","new_name":"Results"}]}"#,
-    );
+    let listed = run_cli(&["read", "metadata", file]);
+    assert!(listed.status.success(), "stderr: {:?}", listed.stderr);
+    let payload = parse_stdout_json(&listed);
+    assert_eq!(payload["count"], 2);
+    assert_eq!(payload["entries"][0]["value"]["range"], "Sheet1!B2:B5");
+    assert_eq!(payload["entries"][1]["value"], "signed off");
 
-    let file = workbook_path.to_str().expect("path utf8");
-    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
+    let filtered = run_cli(&["metadata", file, "--namespace", "audit"]);
+    assert!(filtered.status.success(), "stderr: {:?}", filtered.stderr);
+    assert_eq!(parse_stdout_json(&filtered)["count"], 1);
 
-    let output = run_cli(&[
-        "structure-batch",
+    let cleared = run_cli(&[
+        "clear-metadata",
         file,
-        "--ops",
-        ops_ref.as_str(),
+        "--namespace",
+        "names",
+        "--key",
+        "revenue",
         "--in-place",
-        "--formula-parse-policy",
-        "warn",
     ]);
-    assert!(output.status.success(), "stderr: {:?}", output.stderr);
-    let payload = parse_stdout_json(&output);
-    assert!(
-        payload["formula_parse_diagnostics"].is_null(),
-        "should have no diagnostics when all formulas are valid"
-    );
+    assert!(cleared.status.success(), "stderr: {:?}", cleared.stderr);
+    assert_eq!(parse_stdout_json(&cleared)["removed_count"], 1);
+
+    let noop = run_cli(&["clear-metadata", file, "--namespace", "names", "--in-place"]);
+    assert!(noop.status.success(), "stderr: {:?}", noop.stderr);
+    let payload = parse_stdout_json(&noop);
+    assert_eq!(payload["removed_count"], 0);
+    assert_eq!(payload["changed"], false);
+
+    let all = run_cli(&["write", "metadata", "clear", file, "--in-place"]);
+    assert!(all.status.success(), "stderr: {:?}", all.stderr);
+    assert_eq!(parse_stdout_json(&all)["removed_count"], 1);
+    let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read workbook");
+    assert!(book.get_sheet_by_name("_metadata").is_none());
 }
 
 #[test]
-fn cli_structure_batch_copy_range_with_malformed_formula_warn_mode_diagnostics() {
+fn cli_1904_date_system_is_reported_applied_and_preserved_by_writes() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("structure-copy-warn.xlsx");
-
+    let workbook_path = tmp.path().join("mac-dates.xlsx");
+    let mut workbook = umya_spreadsheet::new_file();
     {
-        let mut workbook = umya_spreadsheet::new_file();
-        {
-            let sheet = workbook
-                .get_sheet_by_name_mut("Sheet1")
-                .expect("default sheet");
-            sheet.get_cell_mut("A1").set_value_number(1.0);
-            sheet.get_cell_mut("A2").set_value_number(2.0);
-            // Malformed formula that parse_base_formula will fail on
-            sheet.get_cell_mut("B1").set_formula("SUM(A1:A2");
-        }
-        umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write");
+        let sheet = workbook
+            .get_sheet_by_name_mut("Sheet1")
+            .expect("default sheet exists");
+        sheet.get_cell_mut("A1").set_value("Closed");
+        sheet.get_cell_mut("B1").set_value("Amount");
+        let cell = sheet.get_cell_mut("A2");
+        cell.set_value_number(44135.0);
+        cell.get_style_mut()
+            .get_number_format_mut()
+            .set_format_code("yyyy-mm-dd");
+        sheet.get_cell_mut("B2").set_value_number(10.0);
     }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+    spreadsheet_kit::tools::workbook_props::write_date1904(&workbook_path, true)
+        .expect("set date1904");
+    let file = workbook_path.to_str().expect("path utf8");
 
-    let ops_path = tmp.path().join("ops.json");
-    write_ops_payload(
-        &ops_path,
-        r#"{"ops":[{"kind":"copy_range","sheet_name":"Sheet1","src_range":"A1:B2","dest_anchor":"D1","include_styles":false,"include_formulas":true}]}"#,
-    );
+    let describe = run_cli(&["describe", file]);
+    assert!(describe.status.success(), "stderr: {:?}", describe.stderr);
+    assert_eq!(parse_stdout_json(&describe)["date1904"], true);
 
-    let file = workbook_path.to_str().expect("path utf8");
-    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
+    let values = run_cli(&["range-values", file, "Sheet1", "A2"]);
+    assert!(values.status.success(), "stderr: {:?}", values.stderr);
+    let stdout = String::from_utf8_lossy(&values.stdout);
+    assert!(stdout.contains("2024-11-01"), "stdout: {stdout}");
 
-    let output = run_cli(&[
-        "structure-batch",
+    let filtered = run_cli(&[
+        "read-table",
         file,
-        "--ops",
-        ops_ref.as_str(),
-        "--in-place",
-        "--formula-parse-policy",
-        "warn",
+        "--sheet",
+        "Sheet1",
+        "--filters-json",
+        r#"[{"column":"Closed","op":"eq","value":"2024-11-01"}]"#,
     ]);
-    assert!(output.status.success(), "stderr: {:?}", output.stderr);
-    let payload = parse_stdout_json(&output);
-    assert_eq!(payload["applied_count"], 1);
+    assert!(filtered.status.success(), "stderr: {:?}", filtered.stderr);
+    assert_eq!(parse_stdout_json(&filtered)["total_rows"], 1);
 
-    let diagnostics = &payload["formula_parse_diagnostics"];
-    assert!(
-        diagnostics.is_object(),
-        "expected formula_parse_diagnostics for copy with malformed formula"
-    );
-    assert_eq!(diagnostics["policy"], "warn");
-    assert!(diagnostics["total_errors"].as_u64().unwrap_or(0) > 0);
+    let edit = run_cli(&["edit", file, "Sheet1", "B2=20"]);
+    assert!(edit.status.success(), "stderr: {:?}", edit.stderr);
+    let props = spreadsheet_kit::tools::workbook_props::read_workbook_props(&workbook_path)
+        .expect("read props");
+    assert!(props.date1904, "write dropped the 1904 date system");
+
+    let after = run_cli(&["range-values", file, "Sheet1", "A2"]);
+    assert!(after.status.success(), "stderr: {:?}", after.stderr);
+    let stdout = String::from_utf8_lossy(&after.stdout);
+    assert!(stdout.contains("2024-11-01"), "stdout: {stdout}");
 }
 
+#[cfg(feature = "serve")]
 #[test]
-fn cli_structure_batch_copy_range_with_malformed_formula_fail_mode_aborts() {
-    let tmp = tempdir().expect("tempdir");
-    let source_path = tmp.path().join("structure-copy-fail.xlsx");
-    let output_path = tmp.path().join("structure-copy-fail-output.xlsx");
+fn cli_serve_keeps_workbooks_open_across_stdio_mcp_requests() {
+    use std::io::{BufRead, BufReader, Write};
+    use std::process::Stdio;
 
-    {
-        let mut workbook = umya_spreadsheet::new_file();
-        {
-            let sheet = workbook
-                .get_sheet_by_name_mut("Sheet1")
-                .expect("default sheet");
-            sheet.get_cell_mut("A1").set_value_number(1.0);
-            sheet.get_cell_mut("B1").set_formula("SUM(A1:A2");
-        }
-        umya_spreadsheet::writer::xlsx::write(&workbook, &source_path).expect("write");
+    fn tool_call(name: &str, arguments: Value) -> Value {
+        serde_json::json!({ "name": name, "arguments": arguments })
     }
 
-    let ops_path = tmp.path().join("ops.json");
-    write_ops_payload(
-        &ops_path,
-        r#"{"ops":[{"kind":"copy_range","sheet_name":"Sheet1","src_range":"A1:B1","dest_anchor":"D1","include_styles":false,"include_formulas":true}]}"#,
-    );
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("serve.xlsx");
+    write_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
 
-    let file = source_path.to_str().expect("path utf8");
-    let out = output_path.to_str().expect("output path utf8");
-    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
+    let mut child = Command::new(assert_cmd::cargo::cargo_bin!("asp"))
+        .arg("serve")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("spawn asp serve");
+    let mut stdin = child.stdin.take().expect("stdin");
+    let mut stdout = BufReader::new(child.stdout.take().expect("stdout"));
+    let mut next_id = 0;
+    // Notifications get no response; anything else returns the next line.
+    let mut request = |method: &str, params: Value| -> Value {
+        let notification = method.starts_with("notifications/");
+        let mut message =
+            serde_json::json!({ "jsonrpc": "2.0", "method": method, "params": params });
+        if !notification {
+            next_id += 1;
+            message["id"] = Value::from(next_id);
+        }
+        writeln!(stdin, "{message}").expect("write message");
+        stdin.flush().expect("flush message");
+        if notification {
+            return Value::Null;
+        }
+        let mut line = String::new();
+        stdout.read_line(&mut line).expect("read response");
+        let response: Value = serde_json::from_str(&line).expect("valid json response");
+        assert_eq!(response["jsonrpc"], "2.0");
+        assert_eq!(response["id"], next_id);
+        response
+    };
+
+    let initialized = request(
+        "initialize",
+        serde_json::json!({
+            "protocolVersion": "2025-06-18",
+            "capabilities": {},
+            "clientInfo": { "name": "test", "version": "0" }
+        }),
+    );
+    assert_eq!(initialized["result"]["protocolVersion"], "2025-06-18");
+    assert!(initialized["result"]["capabilities"]["tools"].is_object());
+    request("notifications/initialized", Value::Null);
 
-    let output = run_cli(&[
-        "structure-batch",
-        file,
-        "--ops",
-        ops_ref.as_str(),
-        "--output",
-        out,
-        "--formula-parse-policy",
-        "fail",
-    ]);
-    assert!(!output.status.success(), "should fail with fail policy");
-    let error = parse_stderr_json(&output);
-    assert_eq!(error["code"], "FORMULA_PARSE_FAILED");
+    let listed = request("tools/list", serde_json::json!({}));
+    let tools = listed["result"]["tools"].as_array().expect("tools");
+    assert!(tools.iter().any(|tool| tool["name"] == "open_workbook"));
+    let range_values = tools
+        .iter()
+        .find(|tool| tool["name"] == "range_values")
+        .expect("range_values tool");
+    let properties = &range_values["inputSchema"]["properties"];
     assert!(
-        !output_path.exists(),
-        "output should not be created on fail mode abort"
+        properties["workbook_id"].is_object(),
+        "tool: {range_values}"
     );
-}
+    assert!(properties.get("workbook_or_fork_id").is_none());
 
-// ─── 3205: Rules-batch formula parse policy tests ───
+    let opened = request(
+        "tools/call",
+        tool_call("open_workbook", serde_json::json!({ "path": file })),
+    )["result"]
+        .clone();
+    assert_eq!(opened["isError"], false, "result: {opened}");
+    assert_eq!(opened["structuredContent"]["reloaded"], false);
+    let workbook_id = opened["structuredContent"]["workbook_id"]
+        .as_str()
+        .expect("workbook id")
+        .to_string();
+    let revision_id = opened["structuredContent"]["revision_id"].clone();
+
+    let values = request(
+        "tools/call",
+        tool_call(
+            "range_values",
+            serde_json::json!({
+                "workbook_id": workbook_id, "sheet_name": "Sheet1", "ranges": ["A1:B4"]
+            }),
+        ),
+    )["result"]
+        .clone();
+    assert_eq!(values["isError"], false, "result: {values}");
+    assert_eq!(values["structuredContent"]["revision_id"], revision_id);
+    assert!(values["content"][0]["text"].to_string().contains("Carol"));
+
+    let not_open = request(
+        "tools/call",
+        tool_call(
+            "range_values",
+            serde_json::json!({ "workbook_id": "not-open", "sheet_name": "Sheet1" }),
+        ),
+    )["result"]
+        .clone();
+    assert_eq!(not_open["isError"], true, "result: {not_open}");
 
-#[test]
-fn cli_rules_batch_invalid_dv_formula_warn_mode_partial_apply() {
-    let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("rules-dv-warn.xlsx");
-    let ops_path = tmp.path().join("ops.json");
-    write_fixture(&workbook_path);
-    // Two ops: one valid list DV, one with a malformed custom formula (unclosed paren)
-    write_ops_payload(
-        &ops_path,
-        r#"{"ops":[
-            {"kind":"set_data_validation","sheet_name":"Sheet1","target_range":"B2:B4","validation":{"kind":"list","formula1":"\"A,B,C\""}},
-            {"kind":"set_data_validation","sheet_name":"Sheet1","target_range":"C2:C4","validation":{"kind":"custom","formula1":"=AND(C2>0,LEN(C2"}}
-        ]}"#,
+    let unknown = request(
+        "tools/call",
+        tool_call("no_such_tool", serde_json::json!({})),
     );
+    assert_eq!(unknown["error"]["code"], -32602, "response: {unknown}");
 
-    let file = workbook_path.to_str().expect("path utf8");
-    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
+    let edit = run_cli(&["edit", file, "Sheet1", "B2=99"]);
+    assert!(edit.status.success(), "stderr: {:?}", edit.stderr);
 
-    let output = run_cli(&[
-        "rules-batch",
-        file,
-        "--ops",
-        ops_ref.as_str(),
-        "--in-place",
-        "--formula-parse-policy",
-        "warn",
-    ]);
-    assert!(output.status.success(), "stderr: {:?}", output.stderr);
-    let payload = parse_stdout_json(&output);
+    let revision = request(
+        "tools/call",
+        tool_call("workbook_revision", serde_json::json!({ "path": file })),
+    )["result"]
+        .clone();
+    assert_eq!(
+        revision["structuredContent"]["stale"], true,
+        "result: {revision}"
+    );
+    assert_eq!(revision["structuredContent"]["revision_id"], revision_id);
 
-    // The custom formula with unclosed paren should be skipped; the valid list DV should apply
+    let reopened = request(
+        "tools/call",
+        tool_call("open_workbook", serde_json::json!({ "path": file })),
+    )["result"]
+        .clone();
     assert_eq!(
-        payload["op_count"].as_u64().unwrap(),
-        2,
-        "op_count should reflect total ops in payload"
+        reopened["structuredContent"]["reloaded"], true,
+        "result: {reopened}"
     );
+    assert_ne!(reopened["structuredContent"]["revision_id"], revision_id);
+
+    let closed = request(
+        "tools/call",
+        tool_call("close_workbook", serde_json::json!({ "path": file })),
+    )["result"]
+        .clone();
     assert_eq!(
-        payload["applied_count"].as_u64().unwrap(),
-        1,
-        "only the valid op should be applied"
+        closed["structuredContent"]["closed"], true,
+        "result: {closed}"
     );
-    let diagnostics = &payload["formula_parse_diagnostics"];
-    assert!(
-        diagnostics.is_object(),
-        "expected formula_parse_diagnostics object"
+    let resident = request(
+        "tools/call",
+        tool_call("list_open_workbooks", serde_json::json!({})),
+    )["result"]
+        .clone();
+    assert_eq!(
+        resident["structuredContent"]["workbooks"],
+        serde_json::json!([])
     );
-    assert_eq!(diagnostics["policy"], "warn");
-    assert!(diagnostics["total_errors"].as_u64().unwrap_or(0) > 0);
+
+    drop(stdin);
+    assert!(child.wait().expect("wait for asp serve").success());
 }
 
+#[cfg(feature = "serve")]
 #[test]
-fn cli_rules_batch_invalid_dv_formula_fail_mode_aborts() {
-    let tmp = tempdir().expect("tempdir");
-    let source_path = tmp.path().join("rules-dv-fail.xlsx");
-    let output_path = tmp.path().join("rules-dv-fail-output.xlsx");
-    let ops_path = tmp.path().join("ops.json");
-    write_fixture(&source_path);
-    write_ops_payload(
-        &ops_path,
-        r#"{"ops":[
-            {"kind":"set_data_validation","sheet_name":"Sheet1","target_range":"C2:C4","validation":{"kind":"custom","formula1":"=AND(C2>0,LEN(C2"}}
-        ]}"#,
-    );
-
-    let file = source_path.to_str().expect("path utf8");
-    let out = output_path.to_str().expect("output path utf8");
-    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
-
-    let output = run_cli(&[
-        "rules-batch",
-        file,
-        "--ops",
-        ops_ref.as_str(),
-        "--output",
-        out,
-        "--formula-parse-policy",
-        "fail",
-    ]);
-    assert!(!output.status.success(), "command should fail");
-    let error = parse_stderr_json(&output);
-    assert_eq!(error["code"], "FORMULA_PARSE_FAILED");
-    assert!(
-        !output_path.exists(),
-        "output file should not exist on fail mode abort"
-    );
+fn cli_serve_rejects_non_loopback_http_addresses() {
+    let err = assert_invalid_argument(&["serve", "--http", "0.0.0.0:0"]);
+    let message = err["message"].as_str().unwrap_or_default();
+    assert!(message.contains("loopback"), "message: {message}");
 }
 
 #[test]
-fn cli_rules_batch_invalid_cf_formula_warn_mode_with_diagnostics() {
+fn cli_named_ranges_classifies_and_evaluates_formula_names() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("rules-cf-warn.xlsx");
-    let ops_path = tmp.path().join("ops.json");
-    write_fixture(&workbook_path);
-    // One valid CF expression, one with malformed formula (unclosed paren)
-    write_ops_payload(
-        &ops_path,
-        r##"{"ops":[
-            {"kind":"add_conditional_format","sheet_name":"Sheet1","target_range":"A1:A10","rule":{"kind":"expression","formula":"A1>0"},"style":{"fill_color":"#FF0000"}},
-            {"kind":"add_conditional_format","sheet_name":"Sheet1","target_range":"B1:B10","rule":{"kind":"expression","formula":"AND(B1>0,LEN(B1"},"style":{"fill_color":"#00FF00"}}
-        ]}"##,
-    );
-
+    let workbook_path = tmp.path().join("assumptions.xlsx");
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook
+            .get_sheet_by_name_mut("Sheet1")
+            .expect("default sheet exists");
+        sheet.get_cell_mut("A1").set_value("Base");
+        sheet.get_cell_mut("B1").set_value_number(100.0);
+        sheet
+            .add_defined_name("BaseRef", "Sheet1!$B$1")
+            .expect("defined name BaseRef");
+        sheet
+            .add_defined_name("TaxRate", "0.5")
+            .expect("defined name TaxRate");
+        sheet
+            .add_defined_name("GrossUp", "Sheet1!$B$1*(1+TaxRate)")
+            .expect("defined name GrossUp");
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
     let file = workbook_path.to_str().expect("path utf8");
-    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
-
-    let output = run_cli(&[
-        "rules-batch",
-        file,
-        "--ops",
-        ops_ref.as_str(),
-        "--in-place",
-        "--formula-parse-policy",
-        "warn",
-    ]);
-    assert!(output.status.success(), "stderr: {:?}", output.stderr);
-    let payload = parse_stdout_json(&output);
-
-    assert_eq!(
-        payload["op_count"].as_u64().unwrap(),
-        2,
-        "op_count should reflect total ops in payload"
-    );
-    assert_eq!(
-        payload["applied_count"].as_u64().unwrap(),
-        1,
-        "only the valid CF op should be applied"
-    );
-    let diagnostics = &payload["formula_parse_diagnostics"];
-    assert!(
-        diagnostics.is_object(),
-        "expected formula_parse_diagnostics object"
-    );
-    assert_eq!(diagnostics["policy"], "warn");
-    assert!(diagnostics["total_errors"].as_u64().unwrap_or(0) > 0);
+    let kind_of = |payload: &Value, name: &str| -> Value {
+        payload["items"]
+            .as_array()
+            .expect("items")
+            .iter()
+            .find(|item| item["name"] == name)
+            .unwrap_or_else(|| panic!("missing {name}: {payload}"))
+            .clone()
+    };
+
+    let listed = run_cli(&["named-ranges", file]);
+    assert!(listed.status.success(), "stderr: {:?}", listed.stderr);
+    let payload = parse_stdout_json(&listed);
+    assert_eq!(kind_of(&payload, "BaseRef")["kind"], "named_range");
+    assert_eq!(kind_of(&payload, "TaxRate")["kind"], "constant");
+    assert_eq!(kind_of(&payload, "GrossUp")["kind"], "formula");
+    assert!(kind_of(&payload, "GrossUp").get("value").is_none());
+
+    let evaluated = run_cli(&["named-ranges", file, "--evaluate"]);
+    assert!(evaluated.status.success(), "stderr: {:?}", evaluated.stderr);
+    let payload = parse_stdout_json(&evaluated);
+    assert!(payload["evaluated_with"].is_string(), "payload: {payload}");
+    assert_eq!(kind_of(&payload, "TaxRate")["value"]["value"], 0.5);
+    assert_eq!(kind_of(&payload, "GrossUp")["value"]["value"], 150.0);
+    assert!(kind_of(&payload, "BaseRef").get("value").is_none());
+
+    let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("reread");
+    assert!(book.get_sheet_by_name("_evaluate_names").is_none());
 }
 
 #[test]
-fn cli_rules_batch_off_mode_permissive_behavior() {
+fn cli_names_defined_at_both_scopes_are_listed_and_disambiguated() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("rules-off.xlsx");
-    let ops_path = tmp.path().join("ops.json");
-    write_fixture(&workbook_path);
-    write_ops_payload(
-        &ops_path,
-        r#"{"ops":[
-            {"kind":"set_data_validation","sheet_name":"Sheet1","target_range":"B2:B4","validation":{"kind":"list","formula1":"\"A,B,C\""}}
-        ]}"#,
+    let workbook_path = tmp.path().join("scoped-names.xlsx");
+    let mut workbook = umya_spreadsheet::new_file();
+    workbook.new_sheet("Sheet2").expect("add Sheet2");
+    for (sheet_name, value) in [("Sheet1", 1.0), ("Sheet2", 2.0)] {
+        let sheet = workbook
+            .get_sheet_by_name_mut(sheet_name)
+            .expect("sheet exists");
+        sheet.get_cell_mut("A1").set_value("v");
+        sheet.get_cell_mut("A2").set_value_number(value);
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+    let file = workbook_path.to_str().expect("path utf8");
+
+    for args in [
+        vec![
+            "define-name",
+            file,
+            "Data",
+            "Sheet1!$A$1:$A$2",
+            "--in-place",
+        ],
+        vec![
+            "define-name",
+            file,
+            "Data",
+            "Sheet2!$A$1:$A$2",
+            "--scope",
+            "sheet",
+            "--scope-sheet-name",
+            "Sheet2",
+            "--in-place",
+        ],
+    ] {
+        let output = run_cli(&args);
+        assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    }
+
+    let listed = run_cli(&["named-ranges", file, "--name-prefix", "Data"]);
+    assert!(listed.status.success(), "stderr: {:?}", listed.stderr);
+    let payload = parse_stdout_json(&listed);
+    let items = payload["items"].as_array().expect("items");
+    assert_eq!(items.len(), 2, "payload: {payload}");
+    assert!(items.iter().all(|item| item["scope_conflict"] == true));
+
+    let sheet_only = run_cli(&["named-ranges", file, "--scope", "sheet"]);
+    assert!(
+        sheet_only.status.success(),
+        "stderr: {:?}",
+        sheet_only.stderr
     );
+    let payload = parse_stdout_json(&sheet_only);
+    let items = payload["items"].as_array().expect("items");
+    assert_eq!(items.len(), 1, "payload: {payload}");
+    assert_eq!(items[0]["scope_sheet_name"], "Sheet2");
 
-    let file = workbook_path.to_str().expect("path utf8");
-    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
+    let read_value = |extra: &[&str]| -> Value {
+        let mut args = vec!["read-table", file, "--table-name", "Data"];
+        args.extend_from_slice(extra);
+        args.extend_from_slice(&["--table-format", "values"]);
+        let output = run_cli(&args);
+        assert!(output.status.success(), "stderr: {:?}", output.stderr);
+        parse_stdout_json(&output)["values"][0][0].clone()
+    };
+    assert_eq!(read_value(&[]), 1.0);
+    assert_eq!(read_value(&["--sheet", "Sheet2"]), 2.0);
+    assert_eq!(
+        read_value(&["--sheet", "Sheet2", "--scope", "workbook"]),
+        1.0
+    );
 
-    let output = run_cli(&[
-        "rules-batch",
+    let ambiguous = run_cli(&[
+        "update-name",
         file,
-        "--ops",
-        ops_ref.as_str(),
+        "Data",
+        "Sheet1!$A$1:$A$3",
         "--in-place",
-        "--formula-parse-policy",
-        "off",
     ]);
-    assert!(output.status.success(), "stderr: {:?}", output.stderr);
-    let payload = parse_stdout_json(&output);
+    assert!(!ambiguous.status.success());
+    let stderr = String::from_utf8_lossy(&ambiguous.stderr);
     assert!(
-        payload["formula_parse_diagnostics"].is_null(),
-        "no diagnostics in off mode"
+        stderr.contains("workbook scope and at sheet scope"),
+        "stderr: {stderr}"
     );
-    assert!(payload["changed"].as_bool().unwrap_or(false));
+
+    let deleted = run_cli(&[
+        "delete-name",
+        file,
+        "Data",
+        "--scope",
+        "workbook",
+        "--in-place",
+    ]);
+    assert!(deleted.status.success(), "stderr: {:?}", deleted.stderr);
+    let listed = run_cli(&["named-ranges", file, "--name-prefix", "Data"]);
+    let payload = parse_stdout_json(&listed);
+    let items = payload["items"].as_array().expect("items");
+    assert_eq!(items.len(), 1, "payload: {payload}");
+    assert_eq!(items[0]["scope_kind"], "sheet");
+    assert!(items[0].get("scope_conflict").is_none());
 }
 
 #[test]
-fn cli_rules_batch_dry_run_formula_diagnostics_parity() {
+fn cli_repair_refs_restores_broken_references_from_baseline() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("rules-dryrun-diag.xlsx");
-    let ops_path = tmp.path().join("ops.json");
-    write_fixture(&workbook_path);
-    write_ops_payload(
-        &ops_path,
-        r#"{"ops":[
-            {"kind":"set_data_validation","sheet_name":"Sheet1","target_range":"C2:C4","validation":{"kind":"custom","formula1":"=AND(C2>0,LEN(C2"}}
-        ]}"#,
-    );
-
+    let write_model = |path: &Path, b2: &str, b4: &str| {
+        let mut workbook = umya_spreadsheet::new_file();
+        workbook.set_sheet_name(0, "Calc").expect("rename sheet");
+        workbook.new_sheet("Data").expect("add data sheet");
+        {
+            let data = workbook.get_sheet_by_name_mut("Data").expect("data");
+            for row in 1..=3 {
+                data.get_cell_mut((1, row)).set_value_number(row as f64);
+            }
+        }
+        let calc = workbook.get_sheet_by_name_mut("Calc").expect("calc");
+        calc.get_cell_mut("B2").set_formula(b2);
+        calc.get_cell_mut("B3").set_formula("B2*2");
+        calc.get_cell_mut("B4").set_formula(b4);
+        umya_spreadsheet::writer::xlsx::write(&workbook, path).expect("write workbook");
+    };
+    let baseline_path = tmp.path().join("before.xlsx");
+    let workbook_path = tmp.path().join("model.xlsx");
+    write_model(&baseline_path, "SUM(Data!A1:A3)", "Gone!A1+1");
+    write_model(&workbook_path, "SUM(#REF!)", "#REF!+1");
     let file = workbook_path.to_str().expect("path utf8");
-    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
+    let baseline = baseline_path.to_str().expect("baseline utf8");
 
-    let output = run_cli(&[
-        "rules-batch",
-        file,
-        "--ops",
-        ops_ref.as_str(),
-        "--dry-run",
-        "--formula-parse-policy",
-        "warn",
-    ]);
+    assert_invalid_argument(&["repair-refs", file, "--dry-run"]);
+
+    let output = run_cli(&["repair-refs", file, "--baseline", baseline, "--dry-run"]);
     assert!(output.status.success(), "stderr: {:?}", output.stderr);
     let payload = parse_stdout_json(&output);
-
-    let diagnostics = &payload["formula_parse_diagnostics"];
+    assert_eq!(payload["broken_formulas"], 2, "payload: {payload}");
+    assert_eq!(payload["proposed"], 1, "payload: {payload}");
+    assert_eq!(payload["applied"], 0);
+    assert_eq!(payload["changed"], false);
+    let proposals = payload["proposals"].as_array().expect("proposals");
+    assert_eq!(proposals[0]["address"], "B2");
+    assert_eq!(proposals[0]["repaired"], "SUM(Data!A1:A3)");
+    assert_eq!(proposals[0]["confidence"], "high");
+    assert_eq!(proposals[1]["address"], "B4");
+    assert!(proposals[1].get("repaired").is_none(), "payload: {payload}");
     assert!(
-        diagnostics.is_object(),
-        "expected diagnostics in dry-run warn mode"
+        proposals[1]["reason"]
+            .as_str()
+            .is_some_and(|reason| reason.contains("'Gone'")),
+        "payload: {payload}"
     );
-    assert_eq!(diagnostics["policy"], "warn");
-    assert!(diagnostics["total_errors"].as_u64().unwrap_or(0) > 0);
+
+    let output = run_cli(&["repair-refs", file, "--baseline", baseline, "--in-place"]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["applied"], 1, "payload: {payload}");
+    assert_eq!(payload["changed"], true);
+
+    let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("reread");
+    let calc = book.get_sheet_by_name("Calc").expect("calc");
+    assert_eq!(
+        calc.get_cell("B2").expect("B2").get_formula(),
+        "SUM(Data!A1:A3)"
+    );
+    assert_eq!(calc.get_cell("B4").expect("B4").get_formula(), "#REF!+1");
 }
 
 #[test]
-fn transform_batch_fill_range_formula_clears_cache() {
+fn cli_map_columns_proposes_mapping_consumed_by_append_column_map() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("fill-formula-cache.xlsx");
-    let ops_path = tmp.path().join("fill-formula-ops.json");
+    let source_path = tmp.path().join("export.xlsx");
+    let target_path = tmp.path().join("ledger.xlsx");
+    let mapping_path = tmp.path().join("mapping.json");
+    let csv_path = tmp.path().join("rows.csv");
+    let output_path = tmp.path().join("ledger-out.xlsx");
 
-    // Create workbook with a formula cell that has a stale cached result
-    let mut workbook = umya_spreadsheet::new_file();
+    let mut source = umya_spreadsheet::new_file();
     {
-        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet");
-        sheet.get_cell_mut("A1").set_value_number(10.0);
-        sheet.get_cell_mut("A2").set_value_number(20.0);
-        let b1 = sheet.get_cell_mut("B1");
-        b1.set_formula("A1+1");
-        b1.get_cell_value_mut().set_formula_result_default("999"); // stale cache
+        let sheet = source.get_sheet_by_name_mut("Sheet1").expect("sheet1");
+        for (col, header) in ["Amt", "Client", "Ref"].iter().enumerate() {
+            sheet.get_cell_mut((col as u32 + 1, 1)).set_value(*header);
+        }
+        sheet.get_cell_mut("A2").set_value_number(120.0);
+        sheet.get_cell_mut("B2").set_value("Acme");
+        sheet.get_cell_mut("C2").set_value("INV-0001");
+        sheet.get_cell_mut("A3").set_value_number(80.0);
+        sheet.get_cell_mut("B3").set_value("Globex");
+        sheet.get_cell_mut("C3").set_value("INV-0002");
     }
-    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write");
+    umya_spreadsheet::writer::xlsx::write(&source, &source_path).expect("write source");
 
-    // FillRange with is_formula=true should clear the cache
-    write_ops_payload(
-        &ops_path,
-        r#"{"ops":[{"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"range","range":"B1:B2"},"value":"A1+100","is_formula":true,"overwrite_formulas":true}]}"#,
-    );
+    let mut target = umya_spreadsheet::new_file();
+    {
+        let sheet = target.get_sheet_by_name_mut("Sheet1").expect("sheet1");
+        for (col, header) in ["Invoice", "Customer", "Amount", "Notes"]
+            .iter()
+            .enumerate()
+        {
+            sheet.get_cell_mut((col as u32 + 1, 1)).set_value(*header);
+        }
+        sheet.get_cell_mut("A2").set_value("INV-0107");
+        sheet.get_cell_mut("B2").set_value("Acme");
+        sheet.get_cell_mut("C2").set_value_number(95.0);
+        sheet.get_cell_mut("A3").set_value("INV-0108");
+        sheet.get_cell_mut("B3").set_value("Globex");
+        sheet.get_cell_mut("C3").set_value_number(150.0);
+    }
+    umya_spreadsheet::writer::xlsx::write(&target, &target_path).expect("write target");
 
-    let file = workbook_path.to_str().expect("path");
-    let ops_ref = format!("@{}", ops_path.to_str().expect("ops"));
-    let output = run_cli(&["transform-batch", file, "--ops", &ops_ref, "--in-place"]);
+    let source_file = source_path.to_str().expect("source utf8");
+    let target_file = target_path.to_str().expect("target utf8");
+    let output = run_cli(&[
+        "map-columns",
+        source_file,
+        target_file,
+        "--source-range",
+        "A1:C3",
+        "--target-range",
+        "A1:D3",
+        "--min-score",
+        "0.3",
+        "--output",
+        mapping_path.to_str().expect("mapping utf8"),
+    ]);
     assert!(output.status.success(), "stderr: {:?}", output.stderr);
     let payload = parse_stdout_json(&output);
+    let pairs: Vec<(String, String)> = payload["columns"]
+        .as_array()
+        .expect("columns")
+        .iter()
+        .map(|column| {
+            (
+                column["source"].as_str().unwrap_or_default().to_string(),
+                column["target"].as_str().unwrap_or_default().to_string(),
+            )
+        })
+        .collect();
     assert_eq!(
-        payload["write_path_provenance"]["written_via"],
-        Value::String("transform_batch".to_string())
+        pairs,
+        vec![
+            ("Ref".to_string(), "Invoice".to_string()),
+            ("Client".to_string(), "Customer".to_string()),
+            ("Amt".to_string(), "Amount".to_string()),
+        ],
+        "payload: {payload}"
     );
+    assert_eq!(payload["unmapped_target"], serde_json::json!(["Notes"]));
+    assert!(mapping_path.exists(), "mapping file written");
 
-    // Read back and verify cache is cleared
-    let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read");
-    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet");
-    let b1 = sheet.get_cell("B1").expect("B1");
-    assert_eq!(b1.get_formula().replace(' ', ""), "A1+100");
-    assert_eq!(
-        b1.get_value(),
-        "",
-        "expected formula cache to be cleared after FillRange"
-    );
+    assert_invalid_argument(&[
+        "map-columns",
+        source_file,
+        target_file,
+        "--min-score",
+        "1.5",
+    ]);
 
-    let b2 = sheet.get_cell("B2").expect("B2");
-    assert_eq!(b2.get_formula().replace(' ', ""), "A1+100");
-    assert_eq!(
-        b2.get_value(),
-        "",
-        "expected formula cache to be cleared after FillRange"
-    );
+    fs::write(&csv_path, "Amt,Client,Ref\n42,Initech,INV-0003\n").expect("write csv");
+    let overview = run_cli(&["sheet-overview", target_file, "Sheet1"]);
+    assert!(overview.status.success(), "stderr: {:?}", overview.stderr);
+    let region_id = parse_stdout_json(&overview)["detected_regions"][0]["id"]
+        .as_u64()
+        .expect("region id")
+        .to_string();
+
+    let output = run_cli(&[
+        "append-region",
+        target_file,
+        "--sheet",
+        "Sheet1",
+        "--region-id",
+        region_id.as_str(),
+        "--from-csv",
+        csv_path.to_str().expect("csv utf8"),
+        "--header",
+        "--column-map",
+        &format!("@{}", mapping_path.display()),
+        "--output",
+        output_path.to_str().expect("output utf8"),
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+
+    let book = umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output");
+    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet1");
+    assert_eq!(sheet.get_cell("A4").expect("A4").get_value(), "INV-0003");
+    assert_eq!(sheet.get_cell("B4").expect("B4").get_value(), "Initech");
+    assert_eq!(sheet.get_cell("C4").expect("C4").get_value(), "42");
 }
 
 #[test]
-fn transform_batch_replace_in_range_formula_clears_cache() {
+fn cli_scan_errors_groups_cached_errors_by_type_with_pagination() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("replace-formula-cache.xlsx");
-    let ops_path = tmp.path().join("replace-formula-ops.json");
+    let workbook_path = tmp.path().join("scan-errors.xlsx");
 
     let mut workbook = umya_spreadsheet::new_file();
+    workbook.set_sheet_name(0, "Calc").expect("rename sheet");
+    workbook.new_sheet("Inputs").expect("add inputs sheet");
     {
-        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet");
-        let a1 = sheet.get_cell_mut("A1");
-        a1.set_formula("SUM(B1:B10)");
-        a1.get_cell_value_mut().set_formula_result_default("500"); // stale cache
+        let calc = workbook.get_sheet_by_name_mut("Calc").expect("calc");
+        for (address, formula, cached) in [
+            ("B2", "1/0", "#DIV/0!"),
+            ("B3", "A3/0", "#DIV/0!"),
+            ("B4", "VLOOKUP(\"x\",A1:A2,1,FALSE)", "#N/A"),
+            ("B5", "A1+1", "2"),
+        ] {
+            let cell = calc.get_cell_mut(address);
+            cell.set_formula(formula);
+            cell.set_formula_result_default(cached);
+        }
+        calc.get_cell_mut("A1").set_value_number(1.0);
     }
-    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write");
-
-    write_ops_payload(
-        &ops_path,
-        r#"{"ops":[{"kind":"replace_in_range","sheet_name":"Sheet1","target":{"kind":"range","range":"A1:A1"},"find":"SUM","replace":"AVERAGE","match_mode":"contains","include_formulas":true}]}"#,
-    );
+    {
+        let inputs = workbook.get_sheet_by_name_mut("Inputs").expect("inputs");
+        let cell = inputs.get_cell_mut("C1");
+        cell.set_formula("#REF!*2");
+        cell.set_formula_result_default("#REF!");
+        let cell = inputs.get_cell_mut("C2");
+        cell.set_formula("B2/0");
+        cell.set_formula_result_default("#DIV/0!");
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+    let file = workbook_path.to_str().expect("path utf8");
 
-    let file = workbook_path.to_str().expect("path");
-    let ops_ref = format!("@{}", ops_path.to_str().expect("ops"));
-    let output = run_cli(&["transform-batch", file, "--ops", &ops_ref, "--in-place"]);
+    let output = run_cli(&["scan-errors", file, "--limit", "2"]);
     assert!(output.status.success(), "stderr: {:?}", output.stderr);
     let payload = parse_stdout_json(&output);
+    assert_eq!(payload["total_errors"], 5, "payload: {payload}");
+    assert_eq!(payload["groups"][0]["error"], "#DIV/0!");
+    assert_eq!(payload["groups"][0]["count"], 3);
     assert_eq!(
-        payload["write_path_provenance"]["written_via"],
-        Value::String("transform_batch".to_string())
-    );
+        payload["groups"][0]["sheets"],
+        serde_json::json!(["Calc", "Inputs"])
+    );
+    assert_eq!(payload["groups"].as_array().map(Vec::len), Some(3));
+    let items = payload["items"].as_array().expect("items");
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0]["sheet_name"], "Calc");
+    assert_eq!(items[0]["address"], "B2");
+    assert_eq!(items[0]["error"], "#DIV/0!");
+    assert_eq!(items[0]["formula"], "1/0");
+    assert_eq!(items[1]["address"], "B3");
+    assert_eq!(payload["next_offset"], 2);
+
+    let output = run_cli(&["analyze", "scan-errors", file, "--offset", "2"]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    let addresses: Vec<String> = payload["items"]
+        .as_array()
+        .expect("items")
+        .iter()
+        .map(|item| {
+            format!(
+                "{}!{}",
+                item["sheet_name"].as_str().unwrap_or_default(),
+                item["address"].as_str().unwrap_or_default()
+            )
+        })
+        .collect();
+    assert_eq!(addresses, vec!["Inputs!C2", "Calc!B4", "Inputs!C1"]);
+    assert!(payload.get("next_offset").is_none(), "payload: {payload}");
 
-    let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read");
-    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet");
-    let a1 = sheet.get_cell("A1").expect("A1");
-    assert!(
-        a1.get_formula().contains("AVERAGE"),
-        "formula should be replaced"
-    );
-    assert_eq!(
-        a1.get_value(),
-        "",
-        "expected formula cache to be cleared after ReplaceInRange"
-    );
+    let output = run_cli(&["scan-errors", file, "--sheet", "Inputs", "--summary-only"]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["total_errors"], 2);
+    assert_eq!(payload["items"], serde_json::json!([]));
 }
 
 #[test]
-fn edit_batch_formula_clears_cache() {
+fn cli_lint_formulas_reports_findings_by_rule_with_pagination() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("edit-formula-cache.xlsx");
+    let workbook_path = tmp.path().join("lint-formulas.xlsx");
 
     let mut workbook = umya_spreadsheet::new_file();
+    workbook.set_sheet_name(0, "Calc").expect("rename sheet");
     {
-        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet");
-        let a1 = sheet.get_cell_mut("A1");
-        a1.set_formula("B1+C1");
-        a1.get_cell_value_mut()
-            .set_formula_result_default("old_value");
+        let calc = workbook.get_sheet_by_name_mut("Calc").expect("calc");
+        for (address, formula) in [
+            ("C2", "A2*B2"),
+            ("C3", "A3*B3"),
+            ("C4", "A4*1.2"),
+            ("C5", "A5*B5"),
+            ("C6", "A6*B6"),
+            ("D2", "SUM(C:C)"),
+            ("E2", "IF(A2>3,\"d\",IF(A2>2,\"c\",IF(A2>1,\"b\",\"a\")))"),
+        ] {
+            calc.get_cell_mut(address).set_formula(formula);
+        }
     }
-    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write");
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+    let file = workbook_path.to_str().expect("path utf8");
 
-    let file = workbook_path.to_str().expect("path");
-    let output = run_cli(&["edit", file, "Sheet1", "A1==SUM(B1:B5)"]);
+    let output = run_cli(&["lint-formulas", file, "--limit", "2"]);
     assert!(output.status.success(), "stderr: {:?}", output.stderr);
-
-    let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read");
-    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet");
-    let a1 = sheet.get_cell("A1").expect("A1");
-    assert_eq!(a1.get_formula().replace(' ', ""), "SUM(B1:B5)");
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["total_findings"], 3, "payload: {payload}");
+    let rules: Vec<&str> = payload["rules"]
+        .as_array()
+        .expect("rules")
+        .iter()
+        .map(|rule| rule["rule"].as_str().unwrap_or_default())
+        .collect();
     assert_eq!(
-        a1.get_value(),
-        "",
-        "expected formula cache to be cleared after edit"
-    );
-}
-
-#[test]
-fn edit_formula_write_emits_write_path_provenance() {
-    let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("edit-provenance-formula.xlsx");
-    write_fixture(&workbook_path);
+        rules,
+        vec![
+            "hardcoded_constant",
+            "full_column_reference",
+            "inconsistent_formula"
+        ]
+    );
+    let items = payload["items"].as_array().expect("items");
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0]["rule"], "hardcoded_constant");
+    assert_eq!(items[0]["address"], "C4");
+    assert_eq!(items[0]["formula"], "A4*1.2");
+    assert_eq!(items[1]["rule"], "full_column_reference");
+    assert_eq!(items[1]["address"], "D2");
+    assert_eq!(payload["next_offset"], 2);
+
+    let output = run_cli(&["analyze", "lint-formulas", file, "--offset", "2"]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["items"][0]["rule"], "inconsistent_formula");
+    assert_eq!(payload["items"][0]["address"], "C4");
+    assert!(payload.get("next_offset").is_none(), "payload: {payload}");
 
-    let file = workbook_path.to_str().expect("path");
-    let output = run_cli(&["edit", file, "Sheet1", "C2==B2*7"]);
+    let output = run_cli(&[
+        "lint-formulas",
+        file,
+        "--rule",
+        "nested_if",
+        "--max-if-depth",
+        "2",
+        "--summary-only",
+    ]);
     assert!(output.status.success(), "stderr: {:?}", output.stderr);
     let payload = parse_stdout_json(&output);
-
-    assert_eq!(
-        payload["write_path_provenance"]["written_via"],
-        Value::String("edit".to_string())
-    );
-    let targets = payload["write_path_provenance"]["formula_targets"]
-        .as_array()
-        .expect("formula targets array");
-    assert!(
-        targets
-            .iter()
-            .any(|value| value.as_str() == Some("Sheet1!C2"))
-    );
+    assert_eq!(payload["total_findings"], 1);
+    assert_eq!(payload["rules"][0]["rule"], "nested_if");
+    assert_eq!(payload["rules"][0]["sheets"], serde_json::json!(["Calc"]));
+    assert_eq!(payload["items"], serde_json::json!([]));
 }
 
 #[test]
-fn edit_literal_write_omits_write_path_provenance() {
+fn cli_formula_map_inconsistencies_reports_pattern_breaks_with_expected_formula() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("edit-provenance-literal.xlsx");
-    write_fixture(&workbook_path);
+    let workbook_path = tmp.path().join("formula-inconsistencies.xlsx");
 
-    let file = workbook_path.to_str().expect("path");
-    let output = run_cli(&["edit", file, "Sheet1", "B2=7"]);
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook.get_sheet_mut(&0).expect("sheet");
+        sheet.get_cell_mut("C1").set_value("Total");
+        for row in 2..=8 {
+            sheet
+                .get_cell_mut(format!("A{row}").as_str())
+                .set_value_number(row as f64);
+            sheet
+                .get_cell_mut(format!("B{row}").as_str())
+                .set_value_number(10.0);
+            let cell = sheet.get_cell_mut(format!("C{row}").as_str());
+            match row {
+                4 => {
+                    cell.set_value_number(999.0);
+                }
+                6 => {
+                    cell.set_formula("A6+B6");
+                }
+                _ => {
+                    cell.set_formula(format!("A{row}*B{row}"));
+                }
+            }
+        }
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+    let file = workbook_path.to_str().expect("path utf8");
+
+    let output = run_cli(&["formula-map", file, "Sheet1", "--inconsistencies"]);
     assert!(output.status.success(), "stderr: {:?}", output.stderr);
     let payload = parse_stdout_json(&output);
-
-    assert!(
-        payload.get("write_path_provenance").is_none(),
-        "literal-only edits should omit provenance metadata"
-    );
-}
-
-#[test]
-fn transform_batch_help_mentions_formula_cache() {
-    let output = run_cli(&["transform-batch", "--help"]);
-    let combined = format!(
-        "{}{}",
-        parse_stdout_text(&output),
-        String::from_utf8(output.stderr.clone()).expect("stderr utf8")
-    );
-    assert!(
-        combined.contains("Cache note") || combined.contains("cached results"),
-        "transform-batch help should mention formula cache behavior"
-    );
-    assert!(
-        combined.contains("write_path_provenance"),
-        "transform-batch help should mention provenance diagnostics"
-    );
+    assert_eq!(payload["groups"], serde_json::json!([]));
+    let items = payload["inconsistencies"]
+        .as_array()
+        .expect("inconsistencies");
+    assert_eq!(items.len(), 2, "payload: {payload}");
+
+    assert_eq!(items[0]["address"], "C4");
+    assert_eq!(items[0]["kind"], "hardcoded_value");
+    assert_eq!(items[0]["axis"], "column");
+    assert_eq!(items[0]["region"], "C2:C8");
+    assert_eq!(items[0]["pattern_cells"], 5);
+    assert_eq!(items[0]["expected_pattern"], "RC[-2]*RC[-1]");
+    assert_eq!(items[0]["expected_formula"], "A4*B4");
+    assert_eq!(items[0]["actual_value"], "999");
+
+    assert_eq!(items[1]["address"], "C6");
+    assert_eq!(items[1]["kind"], "formula_mismatch");
+    assert_eq!(items[1]["actual_formula"], "A6+B6");
+    assert_eq!(items[1]["actual_pattern"], "RC[-2]+RC[-1]");
+    assert_eq!(items[1]["expected_formula"], "A6*B6");
 }
 
 #[test]
-fn structure_batch_help_mentions_formula_cache() {
-    let output = run_cli(&["structure-batch", "--help"]);
-    let combined = format!(
-        "{}{}",
-        parse_stdout_text(&output),
-        String::from_utf8(output.stderr.clone()).expect("stderr utf8")
-    );
-    assert!(
-        combined.contains("Cache note") || combined.contains("cached results"),
-        "structure-batch help should mention formula cache behavior"
-    );
-}
+fn cli_reference_style_r1c1_groups_filled_formulas() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("reference-style.xlsx");
 
-fn write_complex_grid_fixture(path: &Path) {
     let mut workbook = umya_spreadsheet::new_file();
     {
-        let sheet = workbook
-            .get_sheet_by_name_mut("Sheet1")
-            .expect("default sheet exists");
+        let sheet = workbook.get_sheet_mut(&0).expect("sheet");
+        for row in 2..=4 {
+            sheet
+                .get_cell_mut(format!("A{row}").as_str())
+                .set_value_number(row as f64);
+            sheet
+                .get_cell_mut(format!("B{row}").as_str())
+                .set_value_number(10.0);
+            sheet
+                .get_cell_mut(format!("C{row}").as_str())
+                .set_formula(format!("A{row}*B{row}"));
+        }
+        sheet.get_cell_mut("C5").set_formula("SUM(C2:C4)");
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+    let file = workbook_path.to_str().expect("path utf8");
 
-        sheet.get_cell_mut("A1").set_value("Quarterly Report");
-        sheet.add_merge_cells("A1:B1");
-        sheet.get_cell_mut("A2").set_value("Name");
-        sheet.get_cell_mut("B2").set_value("Amount");
-        sheet.get_cell_mut("A3").set_value("Alice");
-        sheet.get_cell_mut("B3").set_value_number(1234.0);
-        sheet.get_cell_mut("A4").set_value("Bob");
-        sheet.get_cell_mut("B4").set_value_number(5678.0);
+    let output = run_cli(&[
+        "formula-map",
+        file,
+        "Sheet1",
+        "--reference-style",
+        "r1c1",
+        "--sort-by",
+        "count",
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    let groups = payload["groups"].as_array().expect("groups");
+    assert_eq!(groups.len(), 2, "payload: {payload}");
+    assert_eq!(groups[0]["formula"], "=RC[-2]*RC[-1]");
+    assert_eq!(groups[0]["count"], 3);
+    assert_eq!(groups[1]["formula"], "=SUM(R[-3]C:R[-1]C)");
 
-        sheet.get_column_dimension_mut("A").set_width(26.0);
-        sheet.get_column_dimension_mut("B").set_width(14.0);
+    let output = run_cli(&["find-formula", file, "rc[-1]", "--reference-style", "r1c1"]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    let matches = payload["matches"].as_array().expect("matches");
+    assert_eq!(matches.len(), 3, "payload: {payload}");
+    assert_eq!(matches[0]["formula"], "RC[-2]*RC[-1]");
+}
 
-        sheet.get_style_mut("A1").get_font_mut().set_bold(true);
-        sheet
-            .get_style_mut("A1")
-            .get_alignment_mut()
-            .set_horizontal(umya_spreadsheet::HorizontalAlignmentValues::Center);
-        sheet
-            .get_style_mut("A1")
-            .get_borders_mut()
-            .get_bottom_border_mut()
-            .set_border_style("medium");
-        sheet.get_style_mut("B3").get_font_mut().set_italic(true);
-        sheet
-            .get_style_mut("B3")
-            .get_number_format_mut()
-            .set_format_code("$#,##0");
-    }
+#[test]
+fn cli_gc_removes_orphaned_scratch_only() {
+    let tmp = tempdir().expect("tempdir");
+    let root = tmp.path();
+    let owner = root.join("owner-abc123");
+    fs::create_dir_all(owner.join("forks")).expect("owner dir");
+    fs::write(owner.join("forks").join("fork-1.xlsx"), [0u8; 32]).expect("fork file");
+    fs::write(owner.join(".heartbeat"), "1").expect("heartbeat");
+    fs::create_dir_all(root.join("tmp")).expect("temp area");
+    fs::write(root.join("tmp").join(".render-x"), [0u8; 8]).expect("temp file");
+    fs::write(root.join("notes.txt"), "keep").expect("unrelated file");
+    let root_arg = root.to_str().expect("path utf8");
+
+    let output = run_asp(&["gc", "--scratch-dir", root_arg]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["removed"], serde_json::json!([]));
+    assert_eq!(payload["live_owners"], 1);
+
+    let output = run_asp(&[
+        "gc",
+        "--scratch-dir",
+        root_arg,
+        "--older-than",
+        "0",
+        "--dry-run",
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["dry_run"], true);
+    assert_eq!(payload["removed"].as_array().map(Vec::len), Some(2));
+    assert!(owner.exists());
 
-    umya_spreadsheet::writer::xlsx::write(&workbook, path).expect("write workbook");
+    let output = run_asp(&["gc", "--scratch-dir", root_arg, "--older-than", "0"]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["live_owners"], 0);
+    assert!(payload["removed_bytes"].as_u64().unwrap_or_default() >= 40);
+    assert!(!owner.exists());
+    assert!(!root.join("tmp").join(".render-x").exists());
+    assert!(root.join("notes.txt").exists());
 }
 
 #[test]
-fn cli_range_export_csv_and_range_import_from_csv_roundtrip() {
+fn cli_formula_trace_follows_named_ranges_and_table_references() {
     let tmp = tempdir().expect("tempdir");
-    let source_path = tmp.path().join("csv-source.xlsx");
-    let target_path = tmp.path().join("csv-target.xlsx");
-    let csv_path = tmp.path().join("export.csv");
+    let workbook_path = tmp.path().join("named-trace.xlsx");
 
-    write_fixture(&source_path);
-    umya_spreadsheet::writer::xlsx::write(&umya_spreadsheet::new_file(), &target_path)
-        .expect("write target workbook");
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook.get_sheet_mut(&0).expect("sheet");
+        sheet.get_cell_mut("A1").set_value("Region");
+        sheet.get_cell_mut("B1").set_value("Amount");
+        for (row, region) in [(2, "North"), (3, "South"), (4, "West")] {
+            sheet
+                .get_cell_mut(format!("A{row}").as_str())
+                .set_value(region);
+            sheet
+                .get_cell_mut(format!("B{row}").as_str())
+                .set_formula(format!("D{row}*2"));
+            sheet
+                .get_cell_mut(format!("D{row}").as_str())
+                .set_value_number(row as f64);
+        }
+        let mut table = umya_spreadsheet::structs::Table::new("Sales", ("A1", "B4"));
+        table.set_display_name("Sales");
+        sheet.add_table(table);
 
-    let source = source_path.to_str().expect("source path utf8");
-    let target = target_path.to_str().expect("target path utf8");
-    let csv = csv_path.to_str().expect("csv path utf8");
+        sheet.get_cell_mut("E1").set_value_number(0.1);
+        sheet.get_cell_mut("F1").set_formula("SUM(Sales[Amount])");
+        sheet.get_cell_mut("F2").set_formula("F1*Rate");
+        sheet
+            .add_defined_name("Rate", "Sheet1!$E$1")
+            .expect("defined name Rate");
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+    let file = workbook_path.to_str().expect("path utf8");
 
-    let export = run_cli(&[
-        "range-export",
-        source,
+    let output = run_cli(&[
+        "formula-trace",
+        file,
         "Sheet1",
-        "A1:B4",
-        "--format",
-        "csv",
-        "--output",
-        csv,
+        "F2",
+        "precedents",
+        "--depth",
+        "3",
     ]);
-    assert!(export.status.success(), "stderr: {:?}", export.stderr);
-    let export_payload = parse_stdout_json(&export);
-    assert_eq!(export_payload["status"], "ok");
-    assert_json_path_eq(&export_payload, "path", csv);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    let layers = payload["layers"].as_array().expect("layers");
+    let edges = |depth: usize| {
+        layers[depth]["edges"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default()
+    };
 
-    let import = run_cli(&[
-        "range-import",
-        target,
-        "Sheet1",
-        "--anchor",
-        "B2",
-        "--from-csv",
-        csv,
-        "--in-place",
-    ]);
-    assert!(import.status.success(), "stderr: {:?}", import.stderr);
+    let first = edges(0);
+    let rate = first
+        .iter()
+        .find(|edge| edge["to"] == "E1")
+        .unwrap_or_else(|| panic!("E1 edge missing: {payload}"));
+    assert_eq!(rate["via_name"], "Rate");
+    let direct = first
+        .iter()
+        .find(|edge| edge["to"] == "F1")
+        .expect("F1 edge");
+    assert!(direct.get("via_name").is_none());
 
-    let read = run_cli(&[
-        "range-values",
-        target,
-        "Sheet1",
-        "B2:C5",
-        "--format",
-        "json",
-    ]);
-    assert!(read.status.success(), "stderr: {:?}", read.stderr);
-    let payload = parse_stdout_json(&read);
-    let rows = payload["values"][0]["rows"]
-        .as_array()
-        .expect("rows matrix");
+    let second = edges(1);
+    let targets: Vec<&str> = second
+        .iter()
+        .filter_map(|edge| edge["to"].as_str())
+        .collect();
+    assert_eq!(targets, vec!["B2", "B3", "B4"], "payload: {payload}");
+    assert!(second.iter().all(|edge| {
+        edge["via_name"]
+            .as_str()
+            .is_some_and(|name| name.contains("Sales"))
+    }));
 
-    assert_eq!(rows[0][0]["value"], "Name");
-    assert_eq!(rows[0][1]["value"], "Amount");
-    assert_eq!(rows[1][0]["value"], "Alice");
-    assert_eq!(rows[1][1]["value"], 10.0);
-    assert_eq!(rows[3][0]["value"], "Carol");
-    assert_eq!(rows[3][1]["value"], 30.0);
+    let third = edges(2);
+    assert!(
+        third.iter().any(|edge| edge["to"] == "D2"),
+        "payload: {payload}"
+    );
 
-    let target_header_path = tmp.path().join("csv-target-header.xlsx");
-    umya_spreadsheet::writer::xlsx::write(&umya_spreadsheet::new_file(), &target_header_path)
-        .expect("write header target workbook");
-    let target_header = target_header_path.to_str().expect("header path utf8");
+    let output = run_cli(&["formula-trace", file, "Sheet1", "D3", "dependents"]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    let layers = payload["layers"].as_array().expect("layers");
+    let reached: Vec<&str> = layers
+        .iter()
+        .flat_map(|layer| layer["edges"].as_array().into_iter().flatten())
+        .filter_map(|edge| edge["from"].as_str())
+        .collect();
+    assert!(reached.contains(&"F1"), "payload: {payload}");
+}
 
-    let import_header = run_cli(&[
-        "range-import",
-        target_header,
-        "Sheet1",
-        "--anchor",
-        "A1",
-        "--from-csv",
-        csv,
-        "--header",
-        "--in-place",
-    ]);
-    assert!(
-        import_header.status.success(),
-        "stderr: {:?}",
-        import_header.stderr
+#[test]
+fn cli_output_auto_records_revisions_for_list_diff_and_restore() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("model.xlsx");
+    let ops_path = tmp.path().join("ops.json");
+    write_fixture(&workbook_path);
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"cells","cells":["B2"]},"value":"999"}]}"#,
     );
+    let file = workbook_path.to_str().expect("path utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
+    let original_hash = spreadsheet_kit::utils::hash_file_sha256_hex(&workbook_path).expect("hash");
 
-    let read_header = run_cli(&[
-        "range-values",
-        target_header,
-        "Sheet1",
-        "A1:B3",
-        "--format",
-        "json",
+    let output = run_cli(&[
+        "transform-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--output",
+        "auto",
     ]);
-    assert!(
-        read_header.status.success(),
-        "stderr: {:?}",
-        read_header.stderr
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let rev1 = tmp.path().join("model.rev-1.xlsx");
+    assert!(rev1.exists());
+    assert_eq!(
+        spreadsheet_kit::utils::hash_file_sha256_hex(&workbook_path).expect("hash"),
+        original_hash
+    );
+
+    let rev1_arg = rev1.to_str().expect("rev path utf8");
+    let output = run_cli(&["edit", rev1_arg, "Sheet1", "C3=7", "--output", "auto"]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    assert!(tmp.path().join("model.rev-2.xlsx").exists());
+
+    let listed = run_asp(&["revisions", "list", file]);
+    assert!(listed.status.success(), "stderr: {:?}", listed.stderr);
+    let listed = parse_stdout_json(&listed);
+    assert_eq!(listed["count"], 2);
+    assert_eq!(listed["revisions"][0]["revision"], 1);
+    assert_eq!(listed["revisions"][0]["parent"], 0);
+    assert_eq!(listed["revisions"][0]["command"], "transform-batch");
+    assert_eq!(listed["revisions"][1]["parent"], 1);
+    assert_eq!(listed["revisions"][1]["status"], "ok");
+    assert!(tmp.path().join("model.revisions.json").exists());
+
+    let diffed = run_asp(&["revisions", "diff", file]);
+    assert!(diffed.status.success(), "stderr: {:?}", diffed.stderr);
+    let diffed = parse_stdout_json(&diffed);
+    assert_eq!(diffed["from_revision"], 1);
+    assert_eq!(diffed["to_revision"], 2);
+
+    let restored = run_asp(&["revisions", "restore", file, "--revision", "1"]);
+    assert!(restored.status.success(), "stderr: {:?}", restored.stderr);
+    let restored = parse_stdout_json(&restored);
+    assert_eq!(restored["backup"]["revision"], 3);
+    assert_eq!(
+        spreadsheet_kit::utils::hash_file_sha256_hex(&workbook_path).expect("hash"),
+        spreadsheet_kit::utils::hash_file_sha256_hex(&rev1).expect("hash")
+    );
+    assert_eq!(
+        spreadsheet_kit::utils::hash_file_sha256_hex(&tmp.path().join("model.rev-3.xlsx"))
+            .expect("hash"),
+        original_hash
     );
-    let header_payload = parse_stdout_json(&read_header);
-    let header_rows = header_payload["values"][0]["rows"]
-        .as_array()
-        .expect("header rows matrix");
-    assert_eq!(header_rows[0][0]["value"], "Alice");
-    assert_eq!(header_rows[0][1]["value"], 10.0);
 }
 
 #[test]
-fn cli_grid_export_import_roundtrip_preserves_layout_and_styles() {
+fn cli_annotations_attach_json_to_ranges_without_visible_comments() {
     let tmp = tempdir().expect("tempdir");
-    let source_path = tmp.path().join("grid-source.xlsx");
-    let target_path = tmp.path().join("grid-target.xlsx");
-    let grid_path = tmp.path().join("region.grid.json");
-
-    write_complex_grid_fixture(&source_path);
-    umya_spreadsheet::writer::xlsx::write(&umya_spreadsheet::new_file(), &target_path)
-        .expect("write target workbook");
-
-    let source = source_path.to_str().expect("source path utf8");
-    let target = target_path.to_str().expect("target path utf8");
-    let grid = grid_path.to_str().expect("grid path utf8");
+    let workbook_path = tmp.path().join("annotated.xlsx");
+    write_fixture(&workbook_path);
+    let file = workbook_path.to_str().expect("path utf8");
 
-    let export = run_cli(&[
-        "range-export",
-        source,
+    let set = run_cli(&[
+        "annotate",
+        file,
         "Sheet1",
-        "A1:B4",
-        "--format",
-        "grid",
-        "--output",
-        grid,
+        "$b$2",
+        r#"{"verified_against":"invoice #123"}"#,
+        "--in-place",
     ]);
-    assert!(export.status.success(), "stderr: {:?}", export.stderr);
-
-    let import = run_cli(&[
-        "range-import",
-        target,
+    assert!(set.status.success(), "stderr: {:?}", set.stderr);
+    let payload = parse_stdout_json(&set);
+    assert_eq!(payload["range"], "B2");
+    assert_eq!(payload["replaced"], false);
+
+    let range = run_cli(&[
+        "write",
+        "annotate",
+        file,
         "Sheet1",
-        "--anchor",
-        "A1",
-        "--from-grid",
-        grid,
+        "C2:C4",
+        "pending",
         "--in-place",
     ]);
-    assert!(import.status.success(), "stderr: {:?}", import.stderr);
+    assert!(range.status.success(), "stderr: {:?}", range.stderr);
 
-    let layout = run_cli(&[
-        "layout-page",
-        target,
+    let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read workbook");
+    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet");
+    assert!(sheet.get_comments().is_empty());
+
+    let listed = run_cli(&["annotations", file]);
+    assert!(listed.status.success(), "stderr: {:?}", listed.stderr);
+    let payload = parse_stdout_json(&listed);
+    assert_eq!(payload["count"], 2);
+    assert_eq!(
+        payload["annotations"][0]["value"]["verified_against"],
+        "invoice #123"
+    );
+
+    let overlapping = run_cli(&[
+        "read",
+        "annotations",
+        file,
+        "--sheet",
         "Sheet1",
         "--range",
-        "A1:B4",
-        "--max-col-width",
-        "40",
-        "--skip-empty-columns-trim",
+        "C3:D9",
     ]);
-    assert!(layout.status.success(), "stderr: {:?}", layout.stderr);
-    let layout_payload = parse_stdout_json(&layout);
-
-    let merges = layout_payload["merged_cells"]
-        .as_array()
-        .expect("merged cells");
     assert!(
-        merges.iter().any(|v| v.as_str() == Some("A1:B1")),
-        "expected A1:B1 merge, got {:?}",
-        merges
+        overlapping.status.success(),
+        "stderr: {:?}",
+        overlapping.stderr
     );
+    let payload = parse_stdout_json(&overlapping);
+    assert_eq!(payload["count"], 1);
+    assert_eq!(payload["annotations"][0]["range"], "C2:C4");
+    assert_eq!(payload["annotations"][0]["value"], "pending");
 
-    let columns = layout_payload["columns"].as_array().expect("columns");
-    assert_eq!(columns[0]["width_chars"], 26.0);
-    assert_eq!(columns[1]["width_chars"], 14.0);
+    assert_invalid_argument(&["annotate", file, "Missing", "A1", "x", "--in-place"]);
 
-    let row1_cells = layout_payload["rows"][0]["cells"]
-        .as_array()
-        .expect("row1 cells");
-    let a1 = row1_cells
-        .iter()
-        .find(|c| c["address"] == "A1")
-        .expect("A1 cell");
-    assert_eq!(a1["bold"], true);
+    let removed = run_cli(&["annotate", file, "Sheet1", "B2", "--remove", "--in-place"]);
+    assert!(removed.status.success(), "stderr: {:?}", removed.stderr);
+    let payload = parse_stdout_json(&removed);
+    assert_eq!(payload["removed"], true);
+    assert_eq!(
+        payload["previous_value"]["verified_against"],
+        "invoice #123"
+    );
 
-    let inspect = run_cli(&["inspect-cells", target, "Sheet1", "B3:B3"]);
-    assert!(inspect.status.success(), "stderr: {:?}", inspect.stderr);
-    let inspect_payload = parse_stdout_json(&inspect);
-    let b3 = inspect_payload["cells"].as_array().expect("cells")[0].clone();
-    assert_eq!(b3["number_format"], "$#,##0");
+    let listed = run_cli(&["annotations", file, "--sheet", "Sheet1"]);
+    assert_eq!(parse_stdout_json(&listed)["count"], 1);
+}
+
+/// Add external link `[1]` to `target` with a cached `Rates!B2` of 0.04, and
+/// point `Sheet1!D2` at it.
+fn add_external_link_fixture(path: &Path, target: &str) {
+    use std::io::{Read, Write};
+
+    let mut book = umya_spreadsheet::reader::xlsx::read(path).expect("read workbook");
+    book.get_sheet_by_name_mut("Sheet1")
+        .expect("sheet")
+        .get_cell_mut("D2")
+        .set_formula("[1]Rates!B2*2");
+    umya_spreadsheet::writer::xlsx::write(&book, path).expect("write workbook");
+
+    let mut archive = zip::ZipArchive::new(fs::File::open(path).expect("open")).expect("zip");
+    let mut entries = Vec::new();
+    for idx in 0..archive.len() {
+        let mut file = archive.by_index(idx).expect("entry");
+        let mut data = Vec::new();
+        file.read_to_end(&mut data).expect("read entry");
+        let name = file.name().to_string();
+        let data = match name.as_str() {
+            "xl/workbook.xml" => String::from_utf8(data)
+                .expect("utf8")
+                .replacen(
+                    "</sheets>",
+                    r#"</sheets><externalReferences><externalReference r:id="rIdExt1"/></externalReferences>"#,
+                    1,
+                )
+                .into_bytes(),
+            "xl/_rels/workbook.xml.rels" => String::from_utf8(data)
+                .expect("utf8")
+                .replacen(
+                    "</Relationships>",
+                    r#"<Relationship Id="rIdExt1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/externalLink" Target="externalLinks/externalLink1.xml"/></Relationships>"#,
+                    1,
+                )
+                .into_bytes(),
+            "[Content_Types].xml" => String::from_utf8(data)
+                .expect("utf8")
+                .replacen(
+                    "</Types>",
+                    r#"<Override PartName="/xl/externalLinks/externalLink1.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.externalLink+xml"/></Types>"#,
+                    1,
+                )
+                .into_bytes(),
+            _ => data,
+        };
+        entries.push((name, data));
+    }
+    entries.push((
+        "xl/externalLinks/externalLink1.xml".to_string(),
+        br#"<externalLink xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><externalBook r:id="rId1"><sheetNames><sheetName val="Rates"/></sheetNames><sheetDataSet><sheetData sheetId="0"><row r="2"><cell r="B2"><v>0.04</v></cell></row></sheetData></sheetDataSet></externalBook></externalLink>"#.to_vec(),
+    ));
+    entries.push((
+        "xl/externalLinks/_rels/externalLink1.xml.rels".to_string(),
+        format!(
+            r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/externalLinkPath" Target="{target}" TargetMode="External"/></Relationships>"#
+        )
+        .into_bytes(),
+    ));
+    drop(archive);
+
+    let mut writer = zip::ZipWriter::new(fs::File::create(path).expect("create"));
+    for (name, data) in entries {
+        writer
+            .start_file(name, zip::write::FileOptions::default())
+            .expect("start entry");
+        writer.write_all(&data).expect("write entry");
+    }
+    writer.finish().expect("finish zip");
 }
 
 #[test]
-fn cli_range_import_from_csv_handles_quotes_crlf_and_blanks() {
+fn cli_external_links_report_staleness_and_break_or_repoint() {
     let tmp = tempdir().expect("tempdir");
-    let target_path = tmp.path().join("csv-edge-target.xlsx");
-    let csv_path = tmp.path().join("edge.csv");
+    let rates_path = tmp.path().join("rates.xlsx");
+    let mut rates = umya_spreadsheet::new_file();
+    {
+        let sheet = rates.get_sheet_by_name_mut("Sheet1").expect("sheet");
+        sheet.set_name("Rates");
+        sheet.get_cell_mut("B2").set_value_number(0.05);
+    }
+    umya_spreadsheet::writer::xlsx::write(&rates, &rates_path).expect("write rates");
 
-    umya_spreadsheet::writer::xlsx::write(&umya_spreadsheet::new_file(), &target_path)
-        .expect("write target workbook");
+    let workbook_path = tmp.path().join("model.xlsx");
+    write_fixture(&workbook_path);
+    add_external_link_fixture(&workbook_path, "rates.xlsx");
+    let file = workbook_path.to_str().expect("path utf8");
 
-    let csv_content = concat!(
-        "Name,Note,Amount,Extra\r\n",
-        "\"Doe, Jane\",\"He said \"\"Hi\"\"\",123,\r\n",
-        "\"Multiline\",\"First line\r\nSecond line\",45.67,\"\"\r\n"
+    let listed = run_cli(&["list-external-links", file]);
+    assert!(listed.status.success(), "stderr: {:?}", listed.stderr);
+    let payload = parse_stdout_json(&listed);
+    assert_eq!(payload["count"], 1);
+    assert_eq!(payload["stale_count"], 1);
+    let link = &payload["links"][0];
+    assert_eq!(link["index"], 1);
+    assert_eq!(link["target"], "rates.xlsx");
+    assert_eq!(link["status"], "stale");
+    assert_eq!(link["sheet_names"], serde_json::json!(["Rates"]));
+    assert_eq!(link["stale_cells"][0]["cell"], "B2");
+    assert_eq!(link["stale_cells"][0]["cached"], "0.04");
+    assert_eq!(link["stale_cells"][0]["current"], "0.05");
+    assert_eq!(link["used_by_count"], 1);
+    assert_eq!(link["used_by"][0]["sheet"], "Sheet1");
+    assert_eq!(link["used_by"][0]["cell"], "D2");
+
+    let ops_path = tmp.path().join("links.json");
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"repoint_external_link","link":1,"target":"rates-2025.xlsx"}]}"#,
     );
-    fs::write(&csv_path, csv_content).expect("write csv");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
+    let repointed = run_cli(&["structure-batch", file, "--ops", &ops_ref, "--in-place"]);
+    assert!(repointed.status.success(), "stderr: {:?}", repointed.stderr);
 
-    let target = target_path.to_str().expect("target path utf8");
-    let csv = csv_path.to_str().expect("csv path utf8");
+    let payload = parse_stdout_json(&run_asp(&["read", "external-links", file]));
+    assert_eq!(payload["links"][0]["target"], "rates-2025.xlsx");
+    assert_eq!(payload["links"][0]["status"], "missing");
 
-    let import = run_cli(&[
-        "range-import",
-        target,
-        "Sheet1",
-        "--anchor",
-        "A1",
-        "--from-csv",
-        csv,
-        "--in-place",
-    ]);
-    assert!(import.status.success(), "stderr: {:?}", import.stderr);
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"break_external_link","link":1}]}"#,
+    );
+    let broken = run_cli(&["structure-batch", file, "--ops", &ops_ref, "--in-place"]);
+    assert!(broken.status.success(), "stderr: {:?}", broken.stderr);
 
-    let read = run_cli(&[
-        "range-values",
-        target,
+    let payload = parse_stdout_json(&run_cli(&["list-external-links", file]));
+    assert_eq!(payload["count"], 0);
+    let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read workbook");
+    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet");
+    assert!(!sheet.get_cell("D2").expect("D2").is_formula());
+    assert!(sheet.get_cell("C2").expect("C2").is_formula());
+
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"break_external_link","link":1}]}"#,
+    );
+    let missing = run_cli(&["structure-batch", file, "--ops", &ops_ref, "--in-place"]);
+    assert!(!missing.status.success());
+}
+
+#[test]
+fn cli_date_handling_switches_between_iso_text_and_serials() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("dates.xlsx");
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook
+            .get_sheet_by_name_mut("Sheet1")
+            .expect("default sheet exists");
+        sheet.get_cell_mut("A1").set_value("Posted");
+        sheet.get_cell_mut("B1").set_value("Amount");
+        let cell = sheet.get_cell_mut("A2");
+        cell.set_value_number(44197.5);
+        cell.get_style_mut()
+            .get_number_format_mut()
+            .set_format_code("yyyy-mm-dd hh:mm");
+        sheet.get_cell_mut("B2").set_value_number(10.0);
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+    let file = workbook_path.to_str().expect("path utf8");
+
+    let read = |mode: &str| {
+        let output = run_cli(&[
+            "read-table",
+            file,
+            "--sheet",
+            "Sheet1",
+            "--table-format",
+            "json",
+            "--date-handling",
+            mode,
+        ]);
+        assert!(output.status.success(), "stderr: {:?}", output.stderr);
+        parse_stdout_json(&output)
+    };
+
+    let iso = read("iso");
+    assert_eq!(iso["rows"][0]["Posted"]["kind"], "Date");
+    assert_eq!(iso["rows"][0]["Posted"]["value"], "2021-01-01T12:00:00");
+    assert!(iso.get("date_serials").is_none());
+
+    let raw = read("raw");
+    assert_eq!(raw["rows"][0]["Posted"]["kind"], "Number");
+    assert_eq!(raw["rows"][0]["Posted"]["value"], 44197.5);
+
+    let both = read("both");
+    assert_eq!(both["rows"][0]["Posted"]["value"], "2021-01-01T12:00:00");
+    assert_eq!(both["date_serials"][0][0], 44197.5);
+    assert_eq!(both["date_serials"][0][1], Value::Null);
+
+    let page = run_cli(&[
+        "sheet-page",
+        file,
         "Sheet1",
-        "A1:D3",
         "--format",
-        "json",
+        "values_only",
+        "--date-handling",
+        "both",
     ]);
-    assert!(read.status.success(), "stderr: {:?}", read.stderr);
-    let payload = parse_stdout_json(&read);
-    let rows = payload["values"][0]["rows"]
-        .as_array()
-        .expect("rows matrix");
+    assert!(page.status.success(), "stderr: {:?}", page.stderr);
+    let page = parse_stdout_json(&page);
+    assert_eq!(page["date_serials"]["A2"], 44197.5);
+}
 
-    assert_eq!(rows[0][0]["value"], "Name");
-    assert_eq!(rows[0][1]["value"], "Note");
-    assert_eq!(rows[0][2]["value"], "Amount");
-    assert_eq!(rows[0][3]["value"], "Extra");
+#[test]
+fn cli_dedupe_scan_groups_copies_and_pairs_near_duplicates() {
+    let tmp = tempdir().expect("tempdir");
+    let shared = tmp.path().join("shared");
+    let archive = tmp.path().join("archive");
+    fs::create_dir_all(&shared).expect("create shared");
+    fs::create_dir_all(&archive).expect("create archive");
 
-    assert_eq!(rows[1][0]["value"], "Doe, Jane");
-    assert_eq!(rows[1][1]["value"], "He said \"Hi\"");
-    assert_eq!(rows[1][2]["value"], 123.0);
-    assert!(rows[1][3].is_null());
+    let original = shared.join("report_final.xlsx");
+    write_fixture(&original);
+    fs::copy(&original, archive.join("report_final copy.xlsx")).expect("copy workbook");
+    fs::copy(&original, shared.join("~$report_final.xlsx")).expect("copy lock file");
+    fs::write(shared.join("notes.txt"), b"not a workbook").expect("write notes");
 
-    assert_eq!(rows[2][0]["value"], "Multiline");
-    let multiline = rows[2][1]["value"].as_str().expect("multiline text value");
-    assert!(multiline.contains("First line"));
-    assert!(multiline.contains("Second line"));
-    assert_eq!(rows[2][2]["value"], 45.67);
-    assert!(rows[2][3].is_null());
-}
+    let mut restyled = umya_spreadsheet::reader::xlsx::read(&original).expect("read fixture");
+    restyled
+        .get_sheet_by_name_mut("Sheet1")
+        .expect("sheet")
+        .get_style_mut("A1")
+        .get_font_mut()
+        .set_bold(true);
+    umya_spreadsheet::writer::xlsx::write(&restyled, archive.join("report_styled.xlsx"))
+        .expect("write restyled");
+
+    let mut edited = umya_spreadsheet::reader::xlsx::read(&original).expect("read fixture");
+    edited
+        .get_sheet_by_name_mut("Sheet1")
+        .expect("sheet")
+        .get_cell_mut("B4")
+        .set_value_number(35.0);
+    umya_spreadsheet::writer::xlsx::write(&edited, shared.join("report_final_v2.xlsx"))
+        .expect("write edited");
+
+    let shared_arg = shared.to_str().expect("path utf8");
+    let archive_arg = archive.to_str().expect("path utf8");
+    let output = run_cli(&[
+        "workbook",
+        "dedupe-scan",
+        shared_arg,
+        archive_arg,
+        "--min-similarity",
+        "0.6",
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
 
-#[test]
-fn edit_help_mentions_formula_cache_and_modes() {
-    let output = run_cli(&["edit", "--help"]);
-    let combined = format!(
-        "{}{}",
-        parse_stdout_text(&output),
-        String::from_utf8(output.stderr.clone()).expect("stderr utf8")
-    );
+    assert_eq!(payload["files_scanned"], 4);
+    let exact = payload["exact_duplicates"].as_array().expect("exact");
+    assert_eq!(exact.len(), 1);
+    let exact_files: Vec<&str> = exact[0]["files"]
+        .as_array()
+        .expect("files")
+        .iter()
+        .map(|file| file["path"].as_str().expect("path"))
+        .collect();
+    assert_eq!(exact_files.len(), 2);
     assert!(
-        combined.contains("Cache note") || combined.contains("cached results"),
-        "edit help should mention formula cache behavior"
+        exact_files
+            .iter()
+            .any(|path| path.ends_with("report_final.xlsx"))
     );
     assert!(
-        combined.contains("--dry-run")
-            && combined.contains("--in-place")
-            && combined.contains("--output"),
-        "edit help should mention dry-run/in-place/output modes"
+        exact_files
+            .iter()
+            .any(|path| path.ends_with("report_final copy.xlsx"))
     );
+
+    let content = payload["content_duplicates"].as_array().expect("content");
+    assert_eq!(content.len(), 1);
+    assert_eq!(content[0]["files"].as_array().expect("files").len(), 3);
+
+    let near = payload["near_duplicates"].as_array().expect("near");
+    assert_eq!(near.len(), 1);
+    let pair: Vec<&str> = near[0]["files"]
+        .as_array()
+        .expect("pair")
+        .iter()
+        .map(|file| file["path"].as_str().expect("path"))
+        .collect();
     assert!(
-        combined.contains("Formula shorthand")
-            && combined.contains("double equals")
-            && combined.contains("Single equals writes a literal"),
-        "edit help should clearly explain formula shorthand syntax"
+        pair.iter()
+            .any(|path| path.ends_with("report_final_v2.xlsx"))
     );
-    assert!(
-        combined.contains("write_path_provenance"),
-        "edit help should mention provenance diagnostics"
+    assert_eq!(near[0]["shared_rows"], 4);
+    assert_eq!(near[0]["similarity"], 0.667);
+
+    assert_error_code(
+        &["dedupe-scan", shared_arg, "--min-similarity", "1.5"],
+        "INVALID_ARGUMENT",
+    );
+}
+
+#[test]
+fn cli_file_arguments_accept_workspace_aliases() {
+    let tmp = tempdir().expect("tempdir");
+    std::fs::create_dir_all(tmp.path().join("finance/q3")).expect("create dirs");
+    write_fixture(&tmp.path().join("finance/q3/model_v7_final.xlsx"));
+    std::fs::create_dir_all(tmp.path().join(".asp")).expect("create .asp");
+    std::fs::write(
+        tmp.path().join(".asp/aliases.yaml"),
+        "aliases:\n  q3-model: finance/q3/model_v7_final.xlsx\n  stale: finance/q2/model.xlsx\n",
+    )
+    .expect("write aliases");
+
+    let run_in_workspace = |args: &[&str]| {
+        Command::new(assert_cmd::cargo::cargo_bin!("agent-spreadsheet"))
+            .current_dir(tmp.path())
+            .args(args)
+            .output()
+            .expect("run agent-spreadsheet")
+    };
+
+    let list = run_in_workspace(&["list-sheets", "Q3-Model"]);
+    assert!(list.status.success(), "stderr: {:?}", list.stderr);
+    assert_eq!(
+        parse_stdout_json(&list)["sheets"].as_array().map(Vec::len),
+        Some(2)
+    );
+
+    let stale = run_in_workspace(&["list-sheets", "stale"]);
+    assert!(!stale.status.success());
+    let stderr = String::from_utf8_lossy(&stale.stderr);
+    assert!(
+        stderr.contains("alias 'stale' points to"),
+        "stderr: {stderr}"
     );
 }
 
-// ─── 4101: structure-batch impact report & formula delta preview ───
-
 #[test]
-fn structure_batch_impact_report_dry_run() {
+fn cli_transform_batch_import_rows_appends_csv_and_objects_under_headers() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("impact-report.xlsx");
+    let workbook_path = tmp.path().join("import-rows.xlsx");
     write_fixture(&workbook_path);
-    let ops_path = tmp.path().join("impact-ops.json");
-    write_ops_payload(
-        &ops_path,
-        r#"{"ops":[{"kind":"insert_rows","sheet_name":"Sheet1","at_row":2,"count":3}]}"#,
-    );
-
-    let file = workbook_path.to_str().unwrap();
-    let ops_ref = format!("@{}", ops_path.to_str().unwrap());
+    fs::write(
+        tmp.path().join("rows.csv"),
+        "Amount,Name\n40,Dave\n\"1,5\",\"Eve, Jr.\"\n",
+    )
+    .expect("write csv");
+    fs::write(
+        tmp.path().join("ops.json"),
+        r#"{"ops":[
+            {"kind":"import_rows","sheet_name":"Sheet1","anchor":"A1","rows":"@rows.csv","has_header":true,"match_headers":true,"append":true},
+            {"kind":"import_rows","sheet_name":"Sheet1","anchor":"A1","rows":[{"name":"Frank","amount":"007"}],"match_headers":true,"append":true}
+        ]}"#,
+    )
+    .expect("write ops");
 
-    let output = run_cli(&[
-        "structure-batch",
-        file,
-        "--ops",
-        ops_ref.as_str(),
-        "--dry-run",
-        "--impact-report",
-    ]);
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("agent-spreadsheet"))
+        .current_dir(tmp.path())
+        .args([
+            "transform-batch",
+            "import-rows.xlsx",
+            "--ops",
+            "@ops.json",
+            "--in-place",
+        ])
+        .output()
+        .expect("run agent-spreadsheet");
     assert!(output.status.success(), "stderr: {:?}", output.stderr);
     let payload = parse_stdout_json(&output);
-
-    // Standard dry-run fields are still present.
-    assert!(payload["would_change"].as_bool().unwrap_or(false));
-    assert!(payload["op_count"].as_u64().is_some());
-
-    // Impact report is present.
-    let ir = &payload["impact_report"];
-    assert!(!ir.is_null(), "impact_report should be present");
-    assert!(
-        !ir["shifted_spans"].as_array().unwrap().is_empty(),
-        "should have at least one shifted span"
+    assert_eq!(payload["applied_count"].as_u64(), Some(2));
+    assert_eq!(
+        payload["summary"]["operation_counts"]["import_rows"].as_u64(),
+        Some(2)
     );
-    assert!(ir["tokens_affected"].is_number());
-    assert!(ir["tokens_unaffected"].is_number());
+
+    let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read workbook");
+    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet exists");
+    let value = |address: &str| {
+        sheet
+            .get_cell(address)
+            .map(|cell| cell.get_value().to_string())
+            .unwrap_or_default()
+    };
+    assert_eq!(value("A5"), "Dave");
+    assert_eq!(value("B5"), "40");
+    assert!(matches!(
+        spreadsheet_kit::workbook::cell_to_value(sheet.get_cell("B5").expect("B5")),
+        Some(spreadsheet_kit::model::CellValue::Number(_))
+    ));
+    assert_eq!(value("A6"), "Eve, Jr.");
+    assert_eq!(value("B6"), "1,5");
+    assert_eq!(value("A7"), "Frank");
+    assert_eq!(value("B7"), "007");
+    assert_eq!(value("C5"), "");
 }
 
 #[test]
-fn structure_batch_show_formula_delta_dry_run() {
+fn cli_transform_batch_append_table_rows_grows_table_above_totals_row() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("formula-delta.xlsx");
-    write_fixture(&workbook_path);
-    let ops_path = tmp.path().join("delta-ops.json");
+    let workbook_path = tmp.path().join("append-table-rows.xlsx");
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook
+            .get_sheet_by_name_mut("Sheet1")
+            .expect("default sheet exists");
+        sheet.get_cell_mut("A1").set_value("Task");
+        sheet.get_cell_mut("B1").set_value("Hours");
+        sheet.get_cell_mut("C1").set_value("Double");
+        sheet.get_cell_mut("A2").set_value("Scope");
+        sheet.get_cell_mut("B2").set_value_number(1.0);
+        sheet.get_cell_mut("C2").set_formula("B2*2");
+        sheet.get_cell_mut("A3").set_value("Build");
+        sheet.get_cell_mut("B3").set_value_number(2.0);
+        sheet.get_cell_mut("C3").set_formula("B3*2");
+        sheet.get_cell_mut("A4").set_value("Total");
+        sheet.get_cell_mut("B4").set_formula("SUBTOTAL(109,B2:B3)");
+        sheet
+            .get_cell_mut("C4")
+            .set_formula("SUBTOTAL(109,Tasks[Double])");
+        let mut table = umya_spreadsheet::structs::Table::new("Tasks", ("A1", "C4"));
+        table.set_display_name("Tasks");
+        table.set_totals_row_count(1);
+        sheet.add_table(table);
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+
+    let ops_path = tmp.path().join("ops.json");
     write_ops_payload(
         &ops_path,
-        r#"{"ops":[{"kind":"insert_rows","sheet_name":"Sheet1","at_row":2,"count":1}]}"#,
+        r#"{"ops":[{"kind":"append_table_rows","sheet_name":"Sheet1","table_name":"tasks","rows":[{"task":"Test","hours":"3"},["Ship",4]]}]}"#,
     );
-
-    let file = workbook_path.to_str().unwrap();
-    let ops_ref = format!("@{}", ops_path.to_str().unwrap());
+    let file = workbook_path.to_str().expect("path utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
 
     let output = run_cli(&[
-        "structure-batch",
+        "transform-batch",
         file,
         "--ops",
         ops_ref.as_str(),
-        "--dry-run",
-        "--show-formula-delta",
+        "--in-place",
     ]);
     assert!(output.status.success(), "stderr: {:?}", output.stderr);
     let payload = parse_stdout_json(&output);
+    assert_eq!(
+        payload["summary"]["operation_counts"]["append_table_rows"].as_u64(),
+        Some(1)
+    );
 
-    // Formula delta preview should be present.
-    let fdp = &payload["formula_delta_preview"];
-    assert!(fdp.is_array(), "formula_delta_preview should be an array");
-    let items = fdp.as_array().unwrap();
-    assert!(!items.is_empty(), "should have at least one delta item");
+    let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read workbook");
+    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet exists");
+    let cell = |address: &str| sheet.get_cell(address).expect("cell exists");
+    assert_eq!(cell("A4").get_value(), "Test");
+    assert_eq!(cell("B4").get_value(), "3");
+    assert_eq!(cell("C4").get_formula(), "B4*2");
+    assert_eq!(cell("A5").get_value(), "Ship");
+    assert_eq!(cell("C5").get_formula(), "B5*2");
+    assert_eq!(cell("A6").get_value(), "Total");
+    assert_eq!(cell("B6").get_formula(), "SUBTOTAL(109,B2:B5)");
+    assert_eq!(cell("C6").get_formula(), "SUBTOTAL(109,Tasks[Double])");
+
+    let table = &sheet.get_tables()[0];
+    assert_eq!(table.get_area().0.get_coordinate(), "A1");
+    assert_eq!(table.get_area().1.get_coordinate(), "C6");
+}
 
-    // Each item should have the expected fields.
-    let first = &items[0];
-    assert!(first["cell"].is_string());
-    assert!(first["before"].is_string());
-    assert!(first["after"].is_string());
-    assert!(first["classification"].is_string());
+#[test]
+fn cli_write_cells_streams_columnar_blocks_into_sheet_xml() {
+    let tmp = tempdir().expect("tempdir");
+    let workbook_path = tmp.path().join("write-cells.xlsx");
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook
+            .get_sheet_by_name_mut("Sheet1")
+            .expect("default sheet exists");
+        sheet.get_cell_mut("A1").set_value("Region");
+        sheet.get_cell_mut("B1").set_value("Amount");
+        sheet.get_cell_mut("A2").set_value("North");
+        sheet.get_cell_mut("B2").set_value_number(10.0);
+        sheet
+            .get_cell_mut("B2")
+            .get_style_mut()
+            .get_number_format_mut()
+            .set_format_code("0.00");
+        sheet.get_cell_mut("C2").set_formula("B2*2");
+        sheet.get_cell_mut("A3").set_value("South");
+        sheet.get_cell_mut("B3").set_value_number(20.0);
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+
+    let payload_path = tmp.path().join("cells.json");
+    write_ops_payload(
+        &payload_path,
+        r#"{"blocks":[
+            {"sheet_name":"Sheet1","range":"B2:B3","columns":[{"type":"number","values":[100,null]}]},
+            {"sheet_name":"Sheet1","range":"C2","columns":[{"type":"string","values":["done"]}]},
+            {"sheet_name":"Sheet1","range":"D2","columns":[
+                {"type":"string","values":["x"," padded <b> "]},
+                {"type":"bool","values":[true,false]}]},
+            {"sheet_name":"Sheet1","range":"A20","columns":[{"type":"number","values":[1.5,2]}]}
+        ]}"#,
+    );
+    let file = workbook_path.to_str().expect("path utf8");
+    let payload_ref = format!("@{}", payload_path.to_str().expect("payload path utf8"));
+
+    let output = run_cli(&["write-cells", file, "--payload", &payload_ref, "--in-place"]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let response = parse_stdout_json(&output);
+    assert_eq!(response["changed"], true);
+    let summary = &response["summary"];
+    assert_eq!(summary["cells_written"].as_u64(), Some(8));
+    assert_eq!(summary["cells_replaced"].as_u64(), Some(2));
+    assert_eq!(summary["formulas_replaced"].as_u64(), Some(1));
+    assert_eq!(summary["rows_created"].as_u64(), Some(2));
+    assert_eq!(summary["nulls_skipped"].as_u64(), Some(1));
+    assert!(response["metrics"]["cells_per_second"].as_u64().is_some());
+
+    let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read workbook");
+    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet exists");
+    let cell = |address: &str| sheet.get_cell(address).expect("cell exists");
+    assert_eq!(cell("B2").get_value(), "100");
+    assert_eq!(
+        cell("B2")
+            .get_style()
+            .get_number_format()
+            .unwrap()
+            .get_format_code(),
+        "0.00"
+    );
+    assert_eq!(cell("B3").get_value(), "20");
+    assert_eq!(cell("C2").get_value(), "done");
+    assert_eq!(cell("C2").get_formula(), "");
+    assert_eq!(cell("D3").get_value(), " padded <b> ");
+    assert_eq!(cell("E2").get_value(), "TRUE");
+    assert_eq!(cell("A2").get_value(), "North");
+    assert_eq!(cell("A21").get_value(), "2");
+    assert_eq!(sheet.get_highest_row(), 21);
 }
 
 #[test]
-fn structure_batch_impact_flags_require_dry_run() {
+fn cli_write_cells_journals_and_snapshots_in_place_writes() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("impact-no-dry.xlsx");
+    let workbook_path = tmp.path().join("write-cells-audit.xlsx");
+    let payload_path = tmp.path().join("cells.json");
+    let audit_path = tmp.path().join("audit.jsonl");
+    let snapshot_dir = tmp.path().join("snapshots");
     write_fixture(&workbook_path);
-    let ops_path = tmp.path().join("impact-no-dry-ops.json");
     write_ops_payload(
-        &ops_path,
-        r#"{"ops":[{"kind":"insert_rows","sheet_name":"Sheet1","at_row":2,"count":1}]}"#,
+        &payload_path,
+        r#"{"blocks":[{"sheet_name":"Sheet1","range":"B2","columns":[{"type":"number","values":[44]}]}]}"#,
     );
 
-    let file = workbook_path.to_str().unwrap();
-    let ops_ref = format!("@{}", ops_path.to_str().unwrap());
+    let file = workbook_path.to_str().expect("path utf8");
+    let payload_ref = format!("@{}", payload_path.to_str().expect("payload path utf8"));
+    let original_hash = spreadsheet_kit::utils::hash_file_sha256_hex(&workbook_path).expect("hash");
 
-    // --impact-report without --dry-run → error
     let output = run_cli(&[
-        "structure-batch",
-        file,
-        "--ops",
-        ops_ref.as_str(),
-        "--in-place",
-        "--impact-report",
-    ]);
-    assert!(!output.status.success(), "should fail without --dry-run");
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    assert!(
-        stderr.contains("--dry-run") || stderr.contains("dry-run"),
-        "error should mention --dry-run: {}",
-        stderr
-    );
-
-    // --show-formula-delta without --dry-run → error
-    let output2 = run_cli(&[
-        "structure-batch",
+        "write-cells",
         file,
-        "--ops",
-        ops_ref.as_str(),
+        "--payload",
+        &payload_ref,
         "--in-place",
-        "--show-formula-delta",
+        "--audit-log",
+        audit_path.to_str().expect("audit path utf8"),
+        "--snapshot-dir",
+        snapshot_dir.to_str().expect("dir utf8"),
     ]);
-    assert!(!output2.status.success(), "should fail without --dry-run");
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let response = parse_stdout_json(&output);
+    assert_eq!(response["changed"], true);
+    assert_eq!(response["snapshot"]["command"], "write-cells");
+    assert_eq!(response["snapshot"]["sha256"], original_hash);
+
+    let journal = fs::read_to_string(&audit_path).expect("read audit log");
+    let records: Vec<Value> = journal
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("record json"))
+        .collect();
+    assert_eq!(records.len(), 1, "journal: {journal}");
+    assert_eq!(records[0]["command"], "write-cells");
+    assert_eq!(records[0]["before_hash"], original_hash);
+    assert_eq!(records[0]["operation_counts"]["write_cells"], 1);
+    assert_eq!(records[0]["changed_cells"]["cells"][0]["address"], "B2");
 }
 
 #[test]
-fn structure_batch_dry_run_without_impact_flags_is_backward_compatible() {
+fn cli_transform_batch_rewrites_only_edited_sheet_parts() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("compat.xlsx");
-    write_fixture(&workbook_path);
-    let ops_path = tmp.path().join("compat-ops.json");
+    let workbook_path = tmp.path().join("differential.xlsx");
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook
+            .get_sheet_by_name_mut("Sheet1")
+            .expect("default sheet exists");
+        sheet.get_cell_mut("A1").set_value("Name");
+        sheet.get_cell_mut("A2").set_value("Alice");
+        sheet.get_cell_mut("B2").set_value_number(10.0);
+    }
+    workbook.new_sheet("Other").expect("add sheet");
+    workbook
+        .get_sheet_by_name_mut("Other")
+        .expect("other sheet")
+        .get_cell_mut("A1")
+        .set_value("Untouched");
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+
+    let read_parts = |path: &Path| -> std::collections::BTreeMap<String, Vec<u8>> {
+        let mut archive =
+            zip::ZipArchive::new(fs::File::open(path).expect("open package")).expect("zip");
+        (0..archive.len())
+            .map(|idx| {
+                let mut file = archive.by_index(idx).expect("entry");
+                let mut data = Vec::new();
+                std::io::Read::read_to_end(&mut file, &mut data).expect("read entry");
+                (file.name().to_string(), data)
+            })
+            .collect()
+    };
+    let before = read_parts(&workbook_path);
+
+    let ops_path = tmp.path().join("ops.json");
     write_ops_payload(
         &ops_path,
-        r#"{"ops":[{"kind":"insert_rows","sheet_name":"Sheet1","at_row":2,"count":1}]}"#,
+        r#"{"ops":[{"kind":"fill_range","sheet_name":"Sheet1","target":{"kind":"cells","cells":["A2"]},"value":"Zed"}]}"#,
     );
-
-    let file = workbook_path.to_str().unwrap();
-    let ops_ref = format!("@{}", ops_path.to_str().unwrap());
-
+    let file = workbook_path.to_str().expect("path utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
     let output = run_cli(&[
-        "structure-batch",
+        "transform-batch",
         file,
         "--ops",
-        ops_ref.as_str(),
-        "--dry-run",
+        &ops_ref,
+        "--in-place",
+        "--differential-save",
     ]);
     assert!(output.status.success(), "stderr: {:?}", output.stderr);
-    let payload = parse_stdout_json(&output);
-
-    // impact_report and formula_delta_preview should NOT be present when not requested.
     assert!(
-        payload.get("impact_report").is_none() || payload["impact_report"].is_null(),
-        "impact_report should be absent when not requested"
+        parse_stdout_json(&output)["warnings"]
+            .as_array()
+            .is_none_or(|warnings| warnings.is_empty()),
+        "a cell-data edit should not fall back to a full save"
     );
-    assert!(
-        payload.get("formula_delta_preview").is_none()
-            || payload["formula_delta_preview"].is_null(),
-        "formula_delta_preview should be absent when not requested"
+
+    let after = read_parts(&workbook_path);
+    let changed: Vec<&String> = after
+        .iter()
+        .filter(|(name, data)| before.get(*name) != Some(*data))
+        .map(|(name, _)| name)
+        .collect();
+    assert_eq!(
+        changed,
+        vec!["xl/sharedStrings.xml", "xl/worksheets/sheet1.xml"],
+        "only the edited sheet and the appended string should change"
+    );
+    assert_eq!(
+        before.keys().collect::<Vec<_>>(),
+        after.keys().collect::<Vec<_>>()
     );
-}
 
-// ── Named Range CRUD Tests ───────────────────────────────────────────────────
+    let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read workbook");
+    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet exists");
+    let value = |address: &str| sheet.get_cell(address).expect("cell exists").get_value();
+    assert_eq!(value("A1"), "Name");
+    assert_eq!(value("A2"), "Zed");
+    assert_eq!(value("B2"), "10");
+    let other = book.get_sheet_by_name("Other").expect("other sheet");
+    assert_eq!(
+        other.get_cell("A1").expect("cell exists").get_value(),
+        "Untouched"
+    );
+}
 
 #[test]
-fn cli_define_name_dry_run_validates_without_mutating() {
+fn cli_transform_batch_sort_range_moves_rows_and_reports_permutation() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("define-name-dry-run.xlsx");
-    write_phase1_read_surface_fixture(&workbook_path);
+    let workbook_path = tmp.path().join("sort.xlsx");
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook
+            .get_sheet_by_name_mut("Sheet1")
+            .expect("default sheet exists");
+        sheet.get_cell_mut("A1").set_value("Region");
+        sheet.get_cell_mut("B1").set_value("Qty");
+        sheet.get_cell_mut("C1").set_value("Double");
+        for (row, region, qty) in [(2, "West", 5.0), (3, "East", 7.0), (4, "East", 2.0)] {
+            sheet.get_cell_mut((1, row)).set_value(region);
+            sheet.get_cell_mut((2, row)).set_value_number(qty);
+            sheet
+                .get_cell_mut((3, row))
+                .set_formula(format!("B{row}*2"));
+        }
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+
+    let ops_path = tmp.path().join("ops.json");
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"sort_range","sheet_name":"Sheet1","range":"A1:C4","has_header":true,"keys":[{"column":"Region"},{"column":"B","order":"desc","value_type":"number"}]}]}"#,
+    );
     let file = workbook_path.to_str().expect("path utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
 
-    let output = run_cli(&[
-        "define-name",
-        file,
-        "NewRange",
-        "Sheet1!$A$1:$C$4",
-        "--dry-run",
-    ]);
+    let output = run_cli(&["transform-batch", file, "--ops", &ops_ref, "--dry-run"]);
     assert!(output.status.success(), "stderr: {:?}", output.stderr);
     let payload = parse_stdout_json(&output);
-    assert_eq!(payload["name"], "NewRange");
-    assert_eq!(payload["refers_to"], "Sheet1!$A$1:$C$4");
-    assert_eq!(payload["scope_kind"], "workbook");
-    assert_eq!(payload["dry_run"], true);
+    assert_eq!(payload["would_change"], true);
+    assert_eq!(payload["summary"]["operation_counts"]["sort_range"], 1);
+    assert_eq!(payload["summary"]["result_counts"]["rows_sorted"], 3);
+    assert_eq!(payload["summary"]["result_counts"]["rows_moved"], 2);
+    assert_eq!(payload["row_permutations"][0]["range"], "A2:C4");
+    assert_eq!(
+        payload["row_permutations"][0]["source_rows"],
+        serde_json::json!([3, 4, 2])
+    );
 
-    // Verify the original file is unchanged: no NewRange should exist.
-    let check = run_cli(&["named-ranges", file, "--name-prefix", "NewRange"]);
-    assert!(check.status.success());
-    let check_payload = parse_stdout_json(&check);
-    // Empty arrays are pruned by the output layer, so items may be absent or empty.
-    let items = check_payload["items"].as_array();
-    assert!(
-        items.is_none() || items.unwrap().is_empty(),
-        "dry-run should not have mutated the file"
+    let output = run_cli(&["transform-batch", file, "--ops", &ops_ref, "--in-place"]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+
+    let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read workbook");
+    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet exists");
+    let value = |address: &str| sheet.get_cell(address).expect("cell exists").get_value();
+    let formula = |address: &str| {
+        sheet
+            .get_cell(address)
+            .expect("cell exists")
+            .get_formula()
+            .to_string()
+    };
+    assert_eq!(value("A1"), "Region");
+    assert_eq!(
+        [value("A2"), value("A3"), value("A4")],
+        ["East", "East", "West"]
     );
+    assert_eq!([value("B2"), value("B3"), value("B4")], ["7", "2", "5"]);
+    assert_eq!(formula("C2"), "B2*2");
+    assert_eq!(formula("C3"), "B3*2");
+    assert_eq!(formula("C4"), "B4*2");
 }
 
 #[test]
-fn cli_define_name_in_place_creates_workbook_scoped_name() {
+fn cli_transform_batch_move_range_repoints_references_and_copy_shifts() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("define-name-inplace.xlsx");
-    write_phase1_read_surface_fixture(&workbook_path);
+    let workbook_path = tmp.path().join("rearrange.xlsx");
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook
+            .get_sheet_by_name_mut("Sheet1")
+            .expect("default sheet exists");
+        sheet.get_cell_mut("A1").set_value_number(10.0);
+        sheet.get_cell_mut("A2").set_value_number(20.0);
+        sheet.get_cell_mut("B1").set_formula("A1+A2");
+        sheet.get_cell_mut("B2").set_formula("$A$1*2");
+        sheet.get_cell_mut("C1").set_formula("B1+Z9");
+    }
+    workbook.new_sheet("Summary").expect("add summary sheet");
+    {
+        let summary = workbook
+            .get_sheet_by_name_mut("Summary")
+            .expect("summary sheet exists");
+        summary.get_cell_mut("A1").set_formula("SUM(Sheet1!A1:A2)");
+        summary.get_cell_mut("A2").set_formula("Sheet1!B1");
+        summary.get_cell_mut("A3").set_formula("SUM(Sheet1!A1:A5)");
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+
+    let ops_path = tmp.path().join("ops.json");
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[
+            {"kind":"move_range","sheet_name":"Sheet1","src_range":"A1:B2","dest_anchor":"D5"},
+            {"kind":"copy_range","sheet_name":"Sheet1","src_range":"D5:E6","dest_anchor":"D10","paste":"formulas"}
+        ]}"#,
+    );
     let file = workbook_path.to_str().expect("path utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
 
-    let output = run_cli(&[
-        "define-name",
-        file,
-        "TotalSales",
-        "Sheet1!$B$2:$B$4",
-        "--in-place",
-    ]);
+    let output = run_cli(&["transform-batch", file, "--ops", &ops_ref, "--dry-run"]);
     assert!(output.status.success(), "stderr: {:?}", output.stderr);
     let payload = parse_stdout_json(&output);
-    assert_eq!(payload["name"], "TotalSales");
-    assert_eq!(payload["scope_kind"], "workbook");
-    assert_eq!(payload["dry_run"], false);
+    assert_eq!(payload["would_change"], true);
+    assert_eq!(payload["summary"]["operation_counts"]["move_range"], 1);
+    assert_eq!(payload["summary"]["operation_counts"]["copy_range"], 1);
+    assert_eq!(payload["summary"]["result_counts"]["cells_moved"], 4);
+    assert_eq!(payload["summary"]["result_counts"]["cells_copied"], 4);
 
-    // Verify the name is now visible.
-    let check = run_cli(&["named-ranges", file, "--name-prefix", "TotalSales"]);
-    assert!(check.status.success());
-    let check_payload = parse_stdout_json(&check);
-    let items = check_payload["items"].as_array().expect("items array");
-    assert!(
-        !items.is_empty(),
-        "TotalSales should exist after define-name --in-place"
-    );
-    assert_eq!(items[0]["name"], "TotalSales");
+    let output = run_cli(&["transform-batch", file, "--ops", &ops_ref, "--in-place"]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+
+    let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read workbook");
+    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet exists");
+    let summary = book.get_sheet_by_name("Summary").expect("summary exists");
+    let formula = |sheet: &umya_spreadsheet::Worksheet, address: &str| {
+        sheet
+            .get_cell(address)
+            .map(|cell| cell.get_formula().to_string())
+            .unwrap_or_default()
+    };
+
+    assert!(sheet.get_cell("A1").is_none(), "moved source is cleared");
+    assert_eq!(sheet.get_value("D5"), "10");
+    assert_eq!(sheet.get_value("D6"), "20");
+    // Moved formulas keep their cells, absolute references included.
+    assert_eq!(formula(sheet, "E5"), "D5+D6");
+    assert_eq!(formula(sheet, "E6"), "$D$5*2");
+    // References to moved cells from outside the block follow them; ranges
+    // only partly inside the block stay.
+    assert_eq!(formula(sheet, "C1"), "E5+Z9");
+    assert_eq!(formula(summary, "A1"), "SUM(Sheet1!D5:D6)");
+    assert_eq!(formula(summary, "A2"), "Sheet1!E5");
+    assert_eq!(formula(summary, "A3"), "SUM(Sheet1!A1:A5)");
+    // Copies shift relative references only.
+    assert_eq!(sheet.get_value("D10"), "10");
+    assert_eq!(formula(sheet, "E10"), "D10+D11");
+    assert_eq!(formula(sheet, "E11"), "$D$5*2");
 }
 
 #[test]
-fn cli_define_name_sheet_scoped_with_output() {
+fn cli_transform_batch_fill_series_steps_linear_growth_and_dates() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("define-name-sheet.xlsx");
-    let output_path = tmp.path().join("define-name-sheet-out.xlsx");
-    write_phase1_read_surface_fixture(&workbook_path);
+    let workbook_path = tmp.path().join("forecast.xlsx");
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook
+            .get_sheet_by_name_mut("Sheet1")
+            .expect("default sheet exists");
+        sheet.get_cell_mut("A3").set_value_number(5.0);
+        sheet.get_cell_mut("A8").set_value("keep");
+        sheet.get_cell_mut("C5").set_formula("1+1");
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
+
+    let ops_path = tmp.path().join("ops.json");
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[
+            {"kind":"fill_series","sheet_name":"Sheet1","range":"B1:E1","start":"2025-01-31","series_type":"date","date_unit":"month"},
+            {"kind":"fill_series","sheet_name":"Sheet1","range":"A3:A8","step":2.5,"stop":12},
+            {"kind":"fill_series","sheet_name":"Sheet1","range":"C3:C6","start":3,"series_type":"growth","step":2},
+            {"kind":"fill_series","sheet_name":"Sheet1","range":"D3:D6","start":"2025-01-03","series_type":"date","date_unit":"weekday"}
+        ]}"#,
+    );
     let file = workbook_path.to_str().expect("path utf8");
-    let output_file = output_path.to_str().expect("path utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
 
-    let output = run_cli(&[
-        "define-name",
-        file,
-        "LocalName",
-        "Sheet1!$A$1",
-        "--scope",
-        "sheet",
-        "--scope-sheet-name",
-        "Sheet1",
-        "--output",
-        output_file,
-    ]);
+    let output = run_cli(&["transform-batch", file, "--ops", &ops_ref, "--dry-run"]);
     assert!(output.status.success(), "stderr: {:?}", output.stderr);
     let payload = parse_stdout_json(&output);
-    assert_eq!(payload["name"], "LocalName");
-    assert_eq!(payload["scope_kind"], "sheet");
-    assert_eq!(payload["scope_sheet_name"], "Sheet1");
+    assert_eq!(payload["would_change"], true);
+    assert_eq!(payload["summary"]["operation_counts"]["fill_series"], 4);
+    // 4 dates, 2 linear values after the seed, 3 growth values (one formula
+    // kept) and 4 weekdays.
+    assert_eq!(
+        payload["summary"]["result_counts"]["series_cells_filled"],
+        13
+    );
+    assert_eq!(
+        payload["summary"]["result_counts"]["cells_skipped_keep_formulas"],
+        1
+    );
 
-    // Verify in the output file.
-    let check = run_cli(&["named-ranges", output_file, "--name-prefix", "LocalName"]);
-    assert!(check.status.success());
-    let check_payload = parse_stdout_json(&check);
-    let items = check_payload["items"].as_array().expect("items array");
-    assert!(!items.is_empty(), "LocalName should exist in output file");
-}
+    let output = run_cli(&["transform-batch", file, "--ops", &ops_ref, "--in-place"]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
 
-#[test]
-fn cli_update_name_in_place_changes_refers_to() {
-    let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("update-name.xlsx");
-    write_phase1_read_surface_fixture(&workbook_path);
-    let file = workbook_path.to_str().expect("path utf8");
+    let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read workbook");
+    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet exists");
+    let values = |addresses: &[&str]| {
+        addresses
+            .iter()
+            .map(|address| sheet.get_value(*address))
+            .collect::<Vec<_>>()
+    };
 
-    // First define a name.
-    let def = run_cli(&[
-        "define-name",
-        file,
-        "MyRange",
-        "Sheet1!$A$1:$B$2",
-        "--in-place",
-    ]);
-    assert!(def.status.success(), "define failed: {:?}", def.stderr);
+    // Month steps clamp to month-ends, as EDATE does.
+    assert_eq!(
+        values(&["B1", "C1", "D1", "E1"]),
+        vec!["45688", "45716", "45747", "45777"]
+    );
+    let format = sheet
+        .get_cell("E1")
+        .and_then(|cell| cell.get_style().get_number_format())
+        .map(|format| format.get_format_code().to_string());
+    assert_eq!(format.as_deref(), Some("yyyy-mm-dd"));
+    // The series stops before passing 12 and leaves later cells alone.
+    assert_eq!(
+        values(&["A3", "A4", "A5", "A8"]),
+        vec!["5", "7.5", "10", "keep"]
+    );
+    assert!(sheet.get_value("A6").is_empty());
+    assert_eq!(values(&["C3", "C4", "C6"]), vec!["3", "6", "24"]);
+    assert_eq!(
+        sheet
+            .get_cell("C5")
+            .map(|cell| cell.get_formula().to_string()),
+        Some("1+1".to_string())
+    );
+    // Friday, then Monday to Wednesday.
+    assert_eq!(
+        values(&["D3", "D4", "D5", "D6"]),
+        vec!["45660", "45663", "45664", "45665"]
+    );
 
-    // Update it.
-    let output = run_cli(&[
-        "update-name",
-        file,
-        "MyRange",
-        "Sheet1!$A$1:$D$10",
-        "--in-place",
-    ]);
-    assert!(output.status.success(), "stderr: {:?}", output.stderr);
-    let payload = parse_stdout_json(&output);
-    assert_eq!(payload["name"], "MyRange");
-    assert_eq!(payload["refers_to"], "Sheet1!$A$1:$D$10");
-    assert!(payload["previous_refers_to"].is_string());
-    assert_eq!(payload["dry_run"], false);
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"fill_series","sheet_name":"Sheet1","range":"F1:F4","start":"2025-01-01","series_type":"date","step":0.5}]}"#,
+    );
+    let invalid = run_cli(&["transform-batch", file, "--ops", &ops_ref, "--dry-run"]);
+    assert!(
+        !invalid.status.success(),
+        "fractional date step should fail"
+    );
 }
 
 #[test]
-fn cli_update_name_scope_only_keeps_existing_refers_to() {
+fn cli_diff_many_reports_each_candidate_and_reuses_baseline_parts() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("update-name-scope-only.xlsx");
-    write_phase1_read_surface_fixture(&workbook_path);
-    let file = workbook_path.to_str().expect("path utf8");
-
-    let def = run_cli(&[
-        "define-name",
-        file,
-        "ScopeOnlyName",
-        "Sheet1!$A$1:$B$2",
-        "--in-place",
-    ]);
-    assert!(def.status.success(), "define failed: {:?}", def.stderr);
+    let original = tmp.path().join("scenario-base.xlsx");
+    let low = tmp.path().join("scenario-low.xlsx");
+    let high = tmp.path().join("scenario-high.xlsx");
+    let unchanged = tmp.path().join("scenario-unchanged.xlsx");
+    write_fixture(&original);
+    for candidate in [&low, &high, &unchanged] {
+        fs::copy(&original, candidate).expect("copy workbook");
+    }
+    for (candidate, edit) in [(&low, "B2=5"), (&high, "B2=50")] {
+        let output = run_cli(&[
+            "edit",
+            candidate.to_str().expect("path utf8"),
+            "Sheet1",
+            edit,
+        ]);
+        assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    }
 
     let output = run_cli(&[
-        "update-name",
-        file,
-        "ScopeOnlyName",
-        "--scope",
-        "sheet",
-        "--scope-sheet-name",
+        "diff-many",
+        original.to_str().expect("path utf8"),
+        low.to_str().expect("path utf8"),
+        high.to_str().expect("path utf8"),
+        unchanged.to_str().expect("path utf8"),
+        "--sheet",
         "Sheet1",
-        "--in-place",
+        "--details",
     ]);
     assert!(output.status.success(), "stderr: {:?}", output.stderr);
     let payload = parse_stdout_json(&output);
-    assert_eq!(payload["name"], "ScopeOnlyName");
-    assert_eq!(payload["refers_to"], "'Sheet1'!$A$1:$B$2");
-    assert_eq!(payload["scope_kind"], "sheet");
-    assert_eq!(payload["scope_sheet_name"], "Sheet1");
-    assert!(payload["previous_refers_to"].is_string());
+
+    assert_eq!(payload["candidate_count"], 3);
+    let candidates = payload["candidates"].as_array().expect("candidates");
+    assert_eq!(
+        candidates[0]["modified"].as_str(),
+        Some(low.to_str().expect("path utf8"))
+    );
+    assert!(candidates[0].get("original").is_none());
+    let b2_value = |candidate: &serde_json::Value| {
+        candidate["changes"]
+            .as_array()
+            .expect("details")
+            .iter()
+            .find(|change| change["address"] == "B2")
+            .map(|change| change["new_value"].clone())
+    };
+    assert_eq!(b2_value(&candidates[0]), Some(serde_json::json!("5")));
+    assert_eq!(b2_value(&candidates[1]), Some(serde_json::json!("50")));
+    assert_eq!(candidates[2]["change_count"], 0);
+    assert!(
+        payload["cache"]["parts_reused"].as_u64().unwrap_or(0) >= 1,
+        "baseline sheet should be parsed once: {}",
+        payload["cache"]
+    );
 }
 
 #[test]
-fn cli_delete_name_in_place_removes_name() {
+fn cli_transform_batch_clear_ops_split_contents_and_formats() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("delete-name.xlsx");
-    write_phase1_read_surface_fixture(&workbook_path);
-    let file = workbook_path.to_str().expect("path utf8");
+    let workbook_path = tmp.path().join("clear.xlsx");
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook
+            .get_sheet_by_name_mut("Sheet1")
+            .expect("default sheet exists");
+        for row in 1..=3 {
+            sheet.get_cell_mut((1, row)).set_value_number(row as f64);
+            sheet
+                .get_cell_mut((2, row))
+                .set_formula(format!("A{row}*2"));
+            sheet.get_cell_mut((3, row)).set_value("note");
+            sheet.get_style_mut((1, row)).get_font_mut().set_bold(true);
+        }
+        sheet.get_style_mut("D1").get_font_mut().set_bold(true);
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &workbook_path).expect("write workbook");
 
-    // The fixture already has Sales_Amount.
-    let before = run_cli(&["named-ranges", file, "--name-prefix", "Sales_Amount"]);
-    assert!(before.status.success());
-    let before_payload = parse_stdout_json(&before);
-    let before_items = before_payload["items"].as_array().expect("items");
-    assert!(
-        !before_items.is_empty(),
-        "Sales_Amount should exist before delete"
+    let ops_path = tmp.path().join("ops.json");
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[
+            {"kind":"clear_contents","sheet_name":"Sheet1","target":{"kind":"range","range":"A1:B2"}},
+            {"kind":"clear_formats","sheet_name":"Sheet1","target":{"kind":"range","range":"A3:D3"}},
+            {"kind":"clear_all","sheet_name":"Sheet1","target":{"kind":"cells","cells":["C1","D1"]}}
+        ]}"#,
     );
+    let file = workbook_path.to_str().expect("path utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
 
-    let output = run_cli(&["delete-name", file, "Sales_Amount", "--in-place"]);
+    let output = run_cli(&["transform-batch", file, "--ops", &ops_ref, "--dry-run"]);
     assert!(output.status.success(), "stderr: {:?}", output.stderr);
     let payload = parse_stdout_json(&output);
-    assert_eq!(payload["name"], "Sales_Amount");
-    assert_eq!(payload["deleted"], true);
+    let counts = &payload["summary"]["result_counts"];
+    assert_eq!(payload["summary"]["operation_counts"]["clear_contents"], 1);
+    // A1, A2, B1, B2 lose content; A3 loses its bold font; C1 loses its text.
+    assert_eq!(counts["nonempty_cells_cleared"], 6);
+    assert_eq!(counts["cells_formula_cleared"], 2);
+    assert_eq!(counts["cells_format_cleared"], 2);
+    assert_eq!(payload["would_change"], true);
 
-    // Verify it's gone.
-    let after = run_cli(&["named-ranges", file, "--name-prefix", "Sales_Amount"]);
-    assert!(after.status.success());
-    let after_payload = parse_stdout_json(&after);
-    let after_items = after_payload["items"].as_array();
-    assert!(
-        after_items.is_none() || after_items.unwrap().is_empty(),
-        "Sales_Amount should not exist after delete"
-    );
+    let output = run_cli(&["transform-batch", file, "--ops", &ops_ref, "--in-place"]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+
+    let book = umya_spreadsheet::reader::xlsx::read(&workbook_path).expect("read workbook");
+    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet exists");
+    let value = |address: &str| {
+        sheet
+            .get_cell(address)
+            .map(|cell| cell.get_value().to_string())
+            .unwrap_or_default()
+    };
+    let bold = |address: &str| {
+        sheet
+            .get_cell(address)
+            .and_then(|cell| cell.get_style().get_font().map(|font| *font.get_bold()))
+            .unwrap_or(false)
+    };
+    assert_eq!(value("A1"), "");
+    assert!(bold("A1"), "clear_contents keeps formatting");
+    assert!(!sheet.get_cell("B2").expect("B2 exists").is_formula());
+    assert_eq!(value("A3"), "3");
+    assert!(!bold("A3"), "clear_formats resets formatting");
+    assert_eq!(value("C1"), "");
+    assert_eq!(value("C2"), "note");
 }
 
 #[test]
-fn cli_delete_name_not_found_returns_error() {
+fn cli_diff_compare_flags_suppress_tolerated_changes() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("delete-name-notfound.xlsx");
-    write_phase1_read_surface_fixture(&workbook_path);
-    let file = workbook_path.to_str().expect("path utf8");
+    let original = tmp.path().join("diff-tolerance-original.xlsx");
+    let modified = tmp.path().join("diff-tolerance-modified.xlsx");
+    write_fixture(&original);
+    fs::copy(&original, &modified).expect("copy workbook");
+
+    let edit = run_cli(&[
+        "edit",
+        modified.to_str().expect("path utf8"),
+        "Sheet1",
+        "B2=10.0004",
+        "A2=ALICE",
+    ]);
+    assert!(edit.status.success(), "stderr: {:?}", edit.stderr);
 
-    let output = run_cli(&["delete-name", file, "NonExistent", "--in-place"]);
+    let strict = run_cli(&[
+        "diff",
+        original.to_str().expect("path utf8"),
+        modified.to_str().expect("path utf8"),
+    ]);
+    assert!(strict.status.success(), "stderr: {:?}", strict.stderr);
+    let strict_payload = parse_stdout_json(&strict);
+    assert_eq!(strict_payload["change_count"], 2);
     assert!(
-        !output.status.success(),
-        "should fail for non-existent name"
+        strict_payload["summary"]["filters"]
+            .get("abs_tolerance")
+            .is_none()
     );
+
+    let tolerant = run_cli(&[
+        "diff",
+        original.to_str().expect("path utf8"),
+        modified.to_str().expect("path utf8"),
+        "--abs-tolerance",
+        "0.001",
+        "--ignore-case",
+    ]);
+    assert!(tolerant.status.success(), "stderr: {:?}", tolerant.stderr);
+    let tolerant_payload = parse_stdout_json(&tolerant);
+    assert_eq!(tolerant_payload["change_count"], 0);
+    let filters = &tolerant_payload["summary"]["filters"];
+    assert_eq!(filters["abs_tolerance"], 0.001);
+    assert_eq!(filters["ignore_case"], true);
+    assert_eq!(filters["ignore_volatile"], false);
+
+    let invalid = run_cli(&[
+        "diff",
+        original.to_str().expect("path utf8"),
+        modified.to_str().expect("path utf8"),
+        "--rel-tolerance=-0.5",
+    ]);
+    assert!(!invalid.status.success(), "negative tolerance should fail");
+    let err = parse_stderr_json(&invalid);
+    assert_eq!(err["code"], "INVALID_ARGUMENT", "unexpected error: {err}");
 }
 
 #[test]
-fn cli_named_ranges_includes_scope_metadata() {
+fn cli_diff_ignore_file_drops_refresh_noise() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("scope-metadata.xlsx");
-    write_phase1_read_surface_fixture(&workbook_path);
-    let file = workbook_path.to_str().expect("path utf8");
+    let original = tmp.path().join("diff-ignore-original.xlsx");
+    let modified = tmp.path().join("diff-ignore-modified.xlsx");
+    write_fixture(&original);
+    fs::copy(&original, &modified).expect("copy workbook");
 
-    let output = run_cli(&["named-ranges", file]);
+    let edit = run_cli(&[
+        "edit",
+        modified.to_str().expect("path utf8"),
+        "Sheet1",
+        "D1=LastRefreshed 2026-01-02 08:00",
+        "B2=12",
+    ]);
+    assert!(edit.status.success(), "stderr: {:?}", edit.stderr);
+
+    let ignore_file = tmp.path().join("diff-ignore.json");
+    fs::write(&ignore_file, r#"{"ranges":["Sheet1!$D$1"],"styles":true}"#)
+        .expect("write ignore file");
+    let output = run_cli(&[
+        "diff",
+        original.to_str().expect("path utf8"),
+        modified.to_str().expect("path utf8"),
+        "--ignore-file",
+        ignore_file.to_str().expect("path utf8"),
+        "--details",
+    ]);
     assert!(output.status.success(), "stderr: {:?}", output.stderr);
     let payload = parse_stdout_json(&output);
-    let items = payload["items"].as_array().expect("items array");
-    assert!(!items.is_empty());
+    assert_eq!(payload["change_count"], 1);
+    assert_eq!(payload["changes"][0]["address"], "B2");
+    assert_eq!(payload["summary"]["ignored_change_count"], 1);
+    assert_eq!(
+        payload["summary"]["filters"]["ignore_file"],
+        ignore_file.display().to_string()
+    );
 
-    // All items should have scope_kind.
-    for item in items {
-        let scope_kind = item["scope_kind"].as_str();
-        assert!(
-            scope_kind == Some("workbook") || scope_kind == Some("sheet"),
-            "item {:?} should have scope_kind 'workbook' or 'sheet', got {:?}",
-            item["name"],
-            scope_kind
-        );
-        if scope_kind == Some("sheet") {
-            assert!(
-                item["scope_sheet_name"].is_string(),
-                "sheet-scoped item should have scope_sheet_name"
-            );
-        }
-    }
+    let bad_file = tmp.path().join("diff-ignore-bad.json");
+    fs::write(&bad_file, r#"{"cells":["A1"]}"#).expect("write bad ignore file");
+    let invalid = run_cli(&[
+        "diff",
+        original.to_str().expect("path utf8"),
+        modified.to_str().expect("path utf8"),
+        "--ignore-file",
+        bad_file.to_str().expect("path utf8"),
+    ]);
+    assert!(!invalid.status.success(), "unknown ignore key should fail");
+    let err = parse_stderr_json(&invalid);
+    assert_eq!(err["code"], "INVALID_ARGUMENT", "unexpected error: {err}");
 }
 
-// ─── 4105: Recalculate output mode and stateless safety ───
-
 #[test]
-fn cli_recalculate_in_place_preserves_existing_behavior() {
+fn cli_diff_reports_validation_and_conditional_format_changes() {
     let tmp = tempdir().expect("tempdir");
-    let workbook_path = tmp.path().join("recalc-inplace.xlsx");
-    write_fixture(&workbook_path);
-    let file = workbook_path.to_str().expect("path utf8");
+    let original = tmp.path().join("diff-rules-original.xlsx");
+    let modified = tmp.path().join("diff-rules-modified.xlsx");
+    let apply_rules = |path: &std::path::Path, ops: &str| {
+        write_fixture(path);
+        let ops_path = path.with_extension("json");
+        write_ops_payload(&ops_path, ops);
+        let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
+        let output = run_cli(&[
+            "rules-batch",
+            path.to_str().expect("path utf8"),
+            "--ops",
+            ops_ref.as_str(),
+            "--in-place",
+        ]);
+        assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    };
+    apply_rules(
+        &original,
+        r##"{"ops":[
+            {"kind":"set_data_validation","sheet_name":"Sheet1","target_range":"B2:B4","validation":{"kind":"list","formula1":"\"A,B,C\""}},
+            {"kind":"set_conditional_format","sheet_name":"Sheet1","target_range":"C2:C10","rule":{"kind":"expression","formula":"C2>100"},"style":{"fill_color":"#FFF2CC","bold":true}}
+        ]}"##,
+    );
+    apply_rules(
+        &modified,
+        r##"{"ops":[
+            {"kind":"set_data_validation","sheet_name":"Sheet1","target_range":"B2:B4","validation":{"kind":"list","formula1":"\"A,B,D\""}},
+            {"kind":"set_conditional_format","sheet_name":"Sheet1","target_range":"C2:C10","rule":{"kind":"expression","formula":"C2>200"},"style":{"fill_color":"#FFF2CC","bold":true}},
+            {"kind":"set_conditional_format","sheet_name":"Sheet1","target_range":"E2:E5","rule":{"kind":"expression","formula":"E2<0"},"style":{"font_color":"#C00000"}}
+        ]}"##,
+    );
 
-    let output = run_cli(&["recalculate", file]);
+    let output = run_cli(&[
+        "diff",
+        original.to_str().expect("path utf8"),
+        modified.to_str().expect("path utf8"),
+        "--details",
+    ]);
     assert!(output.status.success(), "stderr: {:?}", output.stderr);
-
     let payload = parse_stdout_json(&output);
-    assert!(payload["file"].as_str().is_some(), "file field present");
-    assert!(
-        payload["backend"].as_str().is_some(),
-        "backend field present"
-    );
-    assert!(
-        payload["duration_ms"].as_u64().is_some(),
-        "duration_ms present"
-    );
-    // In-place mode should NOT have source_path/target_path/changed
-    assert!(
-        payload.get("source_path").is_none(),
-        "in-place should not emit source_path"
-    );
-    assert!(
-        payload.get("target_path").is_none(),
-        "in-place should not emit target_path"
-    );
-    assert!(
-        payload.get("changed").is_none(),
-        "in-place should not emit changed"
-    );
+    let changes = payload["changes"].as_array().expect("changes");
+    let of_type = |change_type: &str| {
+        changes
+            .iter()
+            .filter(|change| change["type"] == change_type)
+            .collect::<Vec<_>>()
+    };
+
+    let validations = of_type("validation_modified");
+    assert_eq!(validations.len(), 1, "changes: {changes:?}");
+    assert_eq!(validations[0]["sheet"], "Sheet1");
+    assert_eq!(validations[0]["new_range"], "B2:B4");
+    let formula1 = |rule: &str| validations[0][rule]["formula1"].as_str().unwrap_or("");
+    assert!(formula1("old_rule").contains("A,B,C"), "{}", validations[0]);
+    assert!(formula1("new_rule").contains("A,B,D"), "{}", validations[0]);
+
+    let edited = of_type("conditional_format_modified");
+    assert_eq!(edited.len(), 1, "changes: {changes:?}");
+    assert_eq!(edited[0]["old_range"], "C2:C10");
+    assert_eq!(edited[0]["old_rule"]["formulas"][0], "C2>100");
+    assert_eq!(edited[0]["new_rule"]["formulas"][0], "C2>200");
+
+    let added = of_type("conditional_format_added");
+    assert_eq!(added.len(), 1, "changes: {changes:?}");
+    assert_eq!(added[0]["range"], "E2:E5");
+    assert!(of_type("conditional_format_deleted").is_empty());
+
+    let filtered = run_cli(&[
+        "diff",
+        original.to_str().expect("path utf8"),
+        modified.to_str().expect("path utf8"),
+        "--range",
+        "E1:E3",
+        "--details",
+    ]);
+    assert!(filtered.status.success(), "stderr: {:?}", filtered.stderr);
+    let filtered = parse_stdout_json(&filtered);
+    assert_eq!(filtered["change_count"], 1);
+    assert_eq!(filtered["changes"][0]["type"], "conditional_format_added");
 }
 
 #[test]
-fn cli_recalculate_output_mode_copies_and_recalcs_target() {
+fn cli_instantiate_fills_placeholders_into_a_new_workbook() {
     let tmp = tempdir().expect("tempdir");
-    let source_path = tmp.path().join("recalc-output-source.xlsx");
-    let target_path = tmp.path().join("recalc-output-target.xlsx");
-    write_fixture(&source_path);
-    let source = source_path.to_str().expect("path utf8");
-    let target = target_path.to_str().expect("path utf8");
+    let template = tmp.path().join("report-template.xlsx");
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet1");
+        sheet
+            .get_cell_mut("A1")
+            .set_value("Report for {{client}} ({{ quarter }})");
+        sheet.get_cell_mut("B2").set_value("{{revenue}}");
+        sheet.get_cell_mut("B3").set_formula("B2*2");
+        sheet
+            .get_cell_mut("A4")
+            .set_value("Prepared by {{owner.name}}");
+        sheet.get_cell_mut("A5").set_value("Reviewer: {{reviewer}}");
+        sheet
+            .get_header_footer_mut()
+            .get_odd_header_mut()
+            .set_value("&C{{client}}");
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &template).expect("write template");
+    let template_hash =
+        spreadsheet_kit::utils::hash_file_sha256_hex(&template).expect("hash template");
 
-    // Capture source bytes before recalc
-    let source_bytes_before = fs::read(&source_path).expect("read source before");
+    let values_path = tmp.path().join("vars.json");
+    fs::write(
+        &values_path,
+        r#"{"client":"Smith & Co","quarter":"Q3 2025","revenue":1250.5,"owner":{"name":"Dana"},"region":"EMEA"}"#,
+    )
+    .expect("write values");
+    let values_ref = format!("@{}", values_path.to_str().expect("values utf8"));
+    let output_path = tmp.path().join("smith-q3.xlsx");
+    let template_arg = template.to_str().expect("template utf8");
+    let output_arg = output_path.to_str().expect("output utf8");
+
+    let strict = run_cli(&[
+        "instantiate",
+        template_arg,
+        "--values",
+        values_ref.as_str(),
+        "--output",
+        output_arg,
+        "--strict",
+    ]);
+    assert!(
+        !strict.status.success(),
+        "strict should fail on {{reviewer}}"
+    );
+    let err = parse_stderr_json(&strict);
+    assert_eq!(err["code"], "UNRESOLVED_PLACEHOLDERS", "error: {err}");
+    assert!(!output_path.exists(), "strict failure must not write");
 
-    let output = run_cli(&["recalculate", source, "--output", target]);
+    let output = run_cli(&[
+        "workbook",
+        "instantiate",
+        template_arg,
+        "--values",
+        values_ref.as_str(),
+        "--output",
+        output_arg,
+    ]);
     assert!(output.status.success(), "stderr: {:?}", output.stderr);
-
     let payload = parse_stdout_json(&output);
-
-    // Response metadata fields
-    assert!(
-        payload["source_path"].as_str().is_some(),
-        "source_path should be present in output mode"
-    );
-    assert!(
-        payload["target_path"].as_str().is_some(),
-        "target_path should be present in output mode"
-    );
+    let summary = &payload["summary"];
+    assert_eq!(summary["cells_updated"], 3, "payload: {payload}");
+    assert_eq!(summary["headers_footers_updated"], 1);
+    assert_eq!(summary["values_used"]["client"], 2);
+    assert_eq!(summary["values_unused"], serde_json::json!(["region"]));
+    assert_eq!(summary["unresolved"][0]["placeholder"], "reviewer");
     assert_eq!(
-        payload["changed"], true,
-        "changed should be true in output mode"
+        summary["unresolved"][0]["locations"],
+        serde_json::json!(["Sheet1!A5"])
     );
 
-    // file field points to the target
-    assert_json_path_eq(&payload, "target_path", target);
-    assert_json_path_eq(&payload, "source_path", source);
-
-    // Target file should exist
-    assert!(
-        target_path.exists(),
-        "target file should exist after recalculate --output"
+    let book = umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output");
+    let sheet = book.get_sheet_by_name("Sheet1").expect("sheet1");
+    assert_eq!(sheet.get_value("A1"), "Report for Smith & Co (Q3 2025)");
+    assert_eq!(
+        sheet.get_cell("B2").expect("B2").get_value_number(),
+        Some(1250.5),
+        "a cell holding only a placeholder takes the value's type"
     );
-
-    // Source should be unchanged
-    let source_bytes_after = fs::read(&source_path).expect("read source after");
+    assert_eq!(sheet.get_cell("B3").expect("B3").get_formula(), "B2*2");
+    assert_eq!(sheet.get_value("A4"), "Prepared by Dana");
+    assert_eq!(sheet.get_value("A5"), "Reviewer: {{reviewer}}");
     assert_eq!(
-        source_bytes_before, source_bytes_after,
-        "source file should remain unchanged in output mode"
+        sheet.get_header_footer().get_odd_header().get_value(),
+        "&CSmith && Co"
     );
-}
-
-#[test]
-fn cli_recalculate_output_mode_rejects_existing_target_without_force() {
-    let tmp = tempdir().expect("tempdir");
-    let source_path = tmp.path().join("recalc-force-source.xlsx");
-    let target_path = tmp.path().join("recalc-force-target.xlsx");
-    write_fixture(&source_path);
-    // Create an existing target
-    write_fixture(&target_path);
-    let source = source_path.to_str().expect("path utf8");
-    let target = target_path.to_str().expect("path utf8");
-
-    let output = run_cli(&["recalculate", source, "--output", target]);
-    assert!(
-        !output.status.success(),
-        "should fail when target exists without --force"
+    assert_eq!(
+        spreadsheet_kit::utils::hash_file_sha256_hex(&template).expect("hash template"),
+        template_hash,
+        "the template must not change"
     );
-    let err = parse_stderr_json(&output);
-    assert_eq!(err["code"], "OUTPUT_EXISTS", "unexpected error: {err}");
 }
 
 #[test]
-fn cli_recalculate_output_mode_allows_existing_target_with_force() {
+fn cli_properties_batch_stamps_core_and_custom_properties() {
     let tmp = tempdir().expect("tempdir");
-    let source_path = tmp.path().join("recalc-force-ok-source.xlsx");
-    let target_path = tmp.path().join("recalc-force-ok-target.xlsx");
-    write_fixture(&source_path);
-    write_fixture(&target_path);
-    let source = source_path.to_str().expect("path utf8");
-    let target = target_path.to_str().expect("path utf8");
+    let file_path = tmp.path().join("report.xlsx");
+    let mut workbook = umya_spreadsheet::new_file();
+    workbook
+        .get_sheet_by_name_mut("Sheet1")
+        .expect("sheet1")
+        .get_cell_mut("A1")
+        .set_value_number(42);
+    umya_spreadsheet::writer::xlsx::write(&workbook, &file_path).expect("write workbook");
+    let file = file_path.to_str().expect("path utf8");
 
-    let output = run_cli(&["recalculate", source, "--output", target, "--force"]);
+    let ops_path = tmp.path().join("props.json");
+    fs::write(
+        &ops_path,
+        r#"{"ops":[{"kind":"set_core","title":"Q3 Close","creator":"close-pipeline"},{"kind":"set_app","company":"Smith & Co"},{"kind":"set_custom","name":"RunId","value":"run-42"},{"kind":"set_custom","name":"Batch","value":7},{"kind":"set_custom","name":"Approved","value":false}]}"#,
+    )
+    .expect("write ops");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
+
+    let dry_run = run_cli(&[
+        "properties-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--dry-run",
+    ]);
+    assert!(dry_run.status.success(), "stderr: {:?}", dry_run.stderr);
+    let dry_payload = parse_stdout_json(&dry_run);
+    assert_eq!(dry_payload["would_change"], true, "payload: {dry_payload}");
+    assert_eq!(dry_payload["summary"]["result_counts"]["custom_set"], 3);
+    let describe = parse_stdout_json(&run_cli(&["describe", file]));
     assert!(
-        output.status.success(),
-        "should succeed with --force, stderr: {:?}",
-        output.stderr
+        describe["properties"]["custom"].is_null(),
+        "dry run must not write: {describe}"
     );
-    let payload = parse_stdout_json(&output);
-    assert_eq!(payload["changed"], true);
-    assert_json_path_eq(&payload, "target_path", target);
-}
 
-#[test]
-fn cli_recalculate_output_force_failure_preserves_existing_target() {
-    let tmp = tempdir().expect("tempdir");
-    let source_path = tmp.path().join("recalc-force-fail-source.xlsx");
-    let target_path = tmp.path().join("recalc-force-fail-target.xlsx");
-
-    // Invalid source payload to force recalc failure.
-    fs::write(&source_path, b"not-an-xlsx").expect("write invalid source");
-    write_fixture(&target_path);
-
-    let source = source_path.to_str().expect("path utf8");
-    let target = target_path.to_str().expect("path utf8");
+    let output = run_cli(&[
+        "write",
+        "batch",
+        "properties",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["changed"], true, "payload: {payload}");
 
-    let target_before = fs::read(&target_path).expect("read target before");
+    let describe = parse_stdout_json(&run_cli(&["describe", file]));
+    let properties = &describe["properties"];
+    assert_eq!(properties["title"], "Q3 Close", "describe: {describe}");
+    assert_eq!(properties["creator"], "close-pipeline");
+    assert_eq!(properties["company"], "Smith & Co");
+    assert_eq!(
+        properties["custom"],
+        serde_json::json!([
+            {"name": "RunId", "value": "run-42"},
+            {"name": "Batch", "value": 7},
+            {"name": "Approved", "value": false}
+        ])
+    );
 
-    let output = run_cli(&["recalculate", source, "--output", target, "--force"]);
-    assert!(
-        !output.status.success(),
-        "recalc should fail for invalid source payload"
+    // Replacing is case-insensitive and removal keeps the rest; cells are untouched.
+    fs::write(
+        &ops_path,
+        r#"{"ops":[{"kind":"set_custom","name":"runid","value":"run-43"},{"kind":"remove_custom","name":"Batch"},{"kind":"remove_custom","name":"Missing"},{"kind":"set_core","title":""}]}"#,
+    )
+    .expect("rewrite ops");
+    let output = run_cli(&[
+        "properties-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(
+        payload["warnings"][0]["code"], "WARN_CUSTOM_PROPERTY_NOT_FOUND",
+        "payload: {payload}"
     );
 
-    // Existing target must remain untouched on failure.
-    assert!(
-        target_path.exists(),
-        "target should still exist after failure"
+    let describe = parse_stdout_json(&run_cli(&["describe", file]));
+    let properties = &describe["properties"];
+    assert!(properties["title"].is_null(), "describe: {describe}");
+    assert_eq!(
+        properties["custom"],
+        serde_json::json!([
+            {"name": "runid", "value": "run-43"},
+            {"name": "Approved", "value": false}
+        ])
     );
-    let target_after = fs::read(&target_path).expect("read target after");
+    let book = umya_spreadsheet::reader::xlsx::read(&file_path).expect("read workbook");
     assert_eq!(
-        target_before, target_after,
-        "existing target content should be preserved on recalc failure"
+        book.get_sheet_by_name("Sheet1")
+            .expect("sheet1")
+            .get_value("A1"),
+        "42"
     );
 }
 
 #[test]
-fn cli_recalculate_output_rejects_same_path_as_source() {
+fn cli_properties_batch_verify_with_gates_write() {
     let tmp = tempdir().expect("tempdir");
-    let source_path = tmp.path().join("recalc-same.xlsx");
-    write_fixture(&source_path);
-    let source = source_path.to_str().expect("path utf8");
-
-    let output = run_cli(&["recalculate", source, "--output", source]);
-    assert!(
-        !output.status.success(),
-        "should fail when output == source"
+    let workbook_path = tmp.path().join("properties-verify-with.xlsx");
+    let ops_path = tmp.path().join("props.json");
+    let passing_path = tmp.path().join("passing.tests.json");
+    let failing_path = tmp.path().join("failing.tests.json");
+    write_fixture(&workbook_path);
+    write_ops_payload(
+        &ops_path,
+        r#"{"ops":[{"kind":"set_core","title":"Q3 Close"}]}"#,
     );
-    let err = parse_stderr_json(&output);
-    assert_eq!(
-        err["code"], "INVALID_ARGUMENT",
-        "unexpected error envelope: {err}"
+    write_ops_payload(
+        &passing_path,
+        r#"{"tests":[{"cell":"Sheet1!C2","expect":20},{"cell":"Sheet1!C3","expect":40}]}"#,
+    );
+    write_ops_payload(
+        &failing_path,
+        r#"[{"name":"bob total","cell":"Sheet1!C3","expect":41}]"#,
     );
-}
 
-#[test]
-fn cli_recalculate_force_without_output_is_invalid() {
-    let tmp = tempdir().expect("tempdir");
-    let source_path = tmp.path().join("recalc-force-alone.xlsx");
-    write_fixture(&source_path);
-    let source = source_path.to_str().expect("path utf8");
+    let file = workbook_path.to_str().expect("path utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops path utf8"));
+    let before = fs::read(&workbook_path).expect("read source before gate failure");
 
-    let output = run_cli(&["recalculate", source, "--force"]);
-    assert!(
-        !output.status.success(),
-        "should fail when --force used without --output"
+    let err = assert_error_code(
+        &[
+            "properties-batch",
+            file,
+            "--ops",
+            ops_ref.as_str(),
+            "--in-place",
+            "--verify-with",
+            failing_path.to_str().expect("tests path utf8"),
+        ],
+        "VERIFICATION_FAILED",
     );
-    let err = parse_stderr_json(&output);
+    let message = err["message"].as_str().unwrap_or_default();
+    assert!(message.contains("bob total"), "message: {message}");
     assert_eq!(
-        err["code"], "INVALID_ARGUMENT",
-        "unexpected error envelope: {err}"
+        fs::read(&workbook_path).expect("read source after gate failure"),
+        before,
+        "failed verification must leave the source untouched"
     );
-}
 
-#[test]
-fn cli_recalculate_output_invalid_parent_dir_returns_error() {
-    let tmp = tempdir().expect("tempdir");
-    let source_path = tmp.path().join("recalc-invalid-output.xlsx");
-    write_fixture(&source_path);
-    let source = source_path.to_str().expect("path utf8");
+    let dry_run = run_cli(&[
+        "properties-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--dry-run",
+        "--verify-with",
+        passing_path.to_str().expect("tests path utf8"),
+    ]);
+    assert!(dry_run.status.success(), "stderr: {:?}", dry_run.stderr);
+    assert_eq!(parse_stdout_json(&dry_run)["verification"]["passed"], 2);
 
-    let bad_target = tmp.path().join("nonexistent_dir").join("output.xlsx");
-    let target = bad_target.to_str().expect("path utf8");
+    let output = run_cli(&[
+        "properties-batch",
+        file,
+        "--ops",
+        ops_ref.as_str(),
+        "--in-place",
+        "--verify-with",
+        passing_path.to_str().expect("tests path utf8"),
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    assert_eq!(payload["verification"]["passed"], 2);
 
-    let output = run_cli(&["recalculate", source, "--output", target]);
-    assert!(
-        !output.status.success(),
-        "should fail when output parent dir doesn't exist"
-    );
+    let describe = parse_stdout_json(&run_cli(&["describe", file]));
+    assert_eq!(describe["properties"]["title"], "Q3 Close");
 }
 
 #[test]
-fn cli_recalculate_help_shows_output_mode_docs() {
-    let help = run_cli(&["recalculate", "--help"]);
-    assert!(help.status.success(), "stderr: {:?}", help.stderr);
-    let text = parse_stdout_text(&help);
-    assert!(text.contains("--output"), "help should document --output");
-    assert!(text.contains("--force"), "help should document --force");
-    assert!(
-        text.contains("source stays unchanged"),
-        "help should explain source safety"
+fn cli_extract_and_apply_strings_round_trip_a_translation_table() {
+    let tmp = tempdir().expect("tempdir");
+    let file_path = tmp.path().join("report.xlsx");
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet1");
+        sheet.set_name("Summary");
+        sheet.get_cell_mut("A1").set_value("Total revenue");
+        sheet.get_style_mut("A1").get_font_mut().set_bold(true);
+        sheet.get_cell_mut("B1").set_value_number(42);
+        sheet.get_cell_mut("C1").set_formula("B1*2");
+        sheet.get_cell_mut("A2").set_value("Notes");
+        sheet
+            .get_header_footer_mut()
+            .get_odd_footer_mut()
+            .set_value("&CPage &P");
+    }
+    workbook.new_sheet("Data").expect("data sheet");
+    workbook
+        .get_sheet_by_name_mut("Data")
+        .expect("data")
+        .get_cell_mut("A1")
+        .set_formula("Summary!B1");
+    umya_spreadsheet::writer::xlsx::write(&workbook, &file_path).expect("write workbook");
+    let file = file_path.to_str().expect("file utf8");
+
+    let output = run_cli(&["read", "strings", file]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    let ids: Vec<&str> = payload["strings"]
+        .as_array()
+        .expect("strings")
+        .iter()
+        .map(|entry| entry["id"].as_str().expect("id"))
+        .collect();
+    assert_eq!(
+        ids,
+        vec![
+            "sheet:Summary",
+            "Summary!A1",
+            "Summary!A2",
+            "Summary#footer",
+            "sheet:Data"
+        ],
+        "payload: {payload}"
     );
-}
 
-#[test]
-fn cli_recalculate_parse_output_and_force_flags() {
-    use clap::Parser;
-    use spreadsheet_kit::cli::{Cli, Commands};
+    let output = run_cli(&["extract-strings", file, "--format", "csv", "--output", "-"]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let csv = String::from_utf8(output.stdout).expect("csv utf8");
+    assert!(
+        csv.starts_with("id,kind,sheet,source,target\n"),
+        "csv: {csv}"
+    );
+    assert!(csv.contains("Summary!A1,cell,Summary,Total revenue,\n"));
 
-    let cli = Cli::try_parse_from([
-        "agent-spreadsheet",
-        "recalculate",
-        "workbook.xlsx",
+    let table_path = tmp.path().join("strings-de.csv");
+    fs::write(
+        &table_path,
+        "id,source,target\n\
+         sheet:Summary,Summary,Zusammenfassung\n\
+         Summary!A1,Total revenue,Gesamtumsatz\n\
+         Summary!A2,Old notes,Anmerkungen\n\
+         Summary#footer,&CPage &P,&CSeite &P\n\
+         sheet:Data,Data,\n",
+    )
+    .expect("write table");
+    let table_ref = format!("@{}", table_path.to_str().expect("table utf8"));
+    let output_path = tmp.path().join("report-de.xlsx");
+    let output = run_cli(&[
+        "write",
+        "strings",
+        file,
+        "--strings",
+        table_ref.as_str(),
         "--output",
-        "out.xlsx",
-        "--force",
-    ])
-    .expect("parse recalculate with output and force");
-
-    match cli.command {
-        Commands::Recalculate {
-            file,
-            output,
-            force,
-            ..
-        } => {
-            assert_eq!(file, PathBuf::from("workbook.xlsx"));
-            assert_eq!(output, Some(PathBuf::from("out.xlsx")));
-            assert!(force);
-        }
-        other => panic!("unexpected command: {other:?}"),
-    }
-
-    // Without output/force
-    let cli2 = Cli::try_parse_from(["agent-spreadsheet", "recalculate", "workbook.xlsx"])
-        .expect("parse recalculate without flags");
-
-    match cli2.command {
-        Commands::Recalculate {
-            file,
-            output,
-            force,
-            ..
-        } => {
-            assert_eq!(file, PathBuf::from("workbook.xlsx"));
-            assert!(output.is_none());
-            assert!(!force);
-        }
-        other => panic!("unexpected command: {other:?}"),
-    }
-}
+        output_path.to_str().expect("output utf8"),
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
+    let payload = parse_stdout_json(&output);
+    let summary = &payload["summary"];
+    assert_eq!(summary["cells_updated"], 1, "payload: {payload}");
+    assert_eq!(summary["sheets_renamed"], 1);
+    assert_eq!(summary["headers_footers_updated"], 1);
+    assert_eq!(summary["skipped_untranslated"], 1);
+    assert_eq!(summary["skipped_stale"], 1);
+    assert!(
+        summary["warnings"][0]
+            .as_str()
+            .expect("warning")
+            .starts_with("WARN_STRING_STALE: 'Summary!A2'"),
+        "payload: {payload}"
+    );
 
-// ---------------------------------------------------------------------------
-// Ticket 4104 – CLI integration: insert_rows expand_adjacent_sums + clone_row
-// ---------------------------------------------------------------------------
+    let book = umya_spreadsheet::reader::xlsx::read(&output_path).expect("read translated");
+    let sheet = book
+        .get_sheet_by_name("Zusammenfassung")
+        .expect("renamed sheet");
+    assert_eq!(sheet.get_value("A1"), "Gesamtumsatz");
+    assert_eq!(sheet.get_value("A2"), "Notes");
+    assert_eq!(
+        sheet
+            .get_cell("A1")
+            .and_then(|cell| cell.get_style().get_font().map(|font| *font.get_bold())),
+        Some(true)
+    );
+    assert_eq!(sheet.get_cell("C1").expect("c1").get_formula(), "B1*2");
+    assert_eq!(
+        sheet.get_header_footer().get_odd_footer().get_value(),
+        "&CSeite &P"
+    );
+    assert_eq!(
+        book.get_sheet_by_name("Data")
+            .expect("data")
+            .get_cell("A1")
+            .expect("a1")
+            .get_formula(),
+        "Zusammenfassung!B1"
+    );
 
-fn write_sum_fixture(path: &Path) {
-    let mut workbook = umya_spreadsheet::new_file();
-    let sheet = workbook.get_sheet_by_name_mut("Sheet1").unwrap();
-    sheet.get_cell_mut("A1").set_value_number(10.0);
-    sheet.get_cell_mut("A2").set_value_number(20.0);
-    sheet.get_cell_mut("A3").set_value_number(30.0);
-    sheet.get_cell_mut("A4").set_formula("SUM(A1:A3)");
-    umya_spreadsheet::writer::xlsx::write(&workbook, path).expect("write fixture");
+    // A translated sheet name that collides with another sheet fails without writing.
+    fs::write(&table_path, "id,target\nsheet:Summary,Data\n").expect("rewrite table");
+    let output = run_cli(&[
+        "apply-strings",
+        file,
+        "--strings",
+        table_ref.as_str(),
+        "--in-place",
+    ]);
+    assert!(!output.status.success(), "duplicate sheet name should fail");
+    let book = umya_spreadsheet::reader::xlsx::read(&file_path).expect("read source");
+    assert!(book.get_sheet_by_name("Summary").is_some());
 }
 
 #[test]
-fn cli_structure_batch_insert_rows_expand_adjacent_sums() {
+fn cli_style_batch_converts_number_formats_between_locales() {
     let tmp = tempdir().expect("tempdir");
-    let wb = tmp.path().join("expand_sum.xlsx");
-    let ops_path = tmp.path().join("ops.json");
-    write_sum_fixture(&wb);
+    let source_path = tmp.path().join("us-template.xlsx");
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet1");
+        sheet.get_cell_mut("B2").set_value_number(1234.5);
+        sheet
+            .get_style_mut("B2")
+            .get_number_format_mut()
+            .set_format_code("$#,##0.00");
+        sheet.get_cell_mut("C2").set_value_number(45292);
+        sheet
+            .get_style_mut("C2")
+            .get_number_format_mut()
+            .set_format_code("m/d/yyyy");
+        sheet.get_cell_mut("D2").set_value_number(0.25);
+        sheet
+            .get_style_mut("D2")
+            .get_number_format_mut()
+            .set_format_code("0.0%");
+    }
+    workbook.new_sheet("Notes").expect("notes sheet");
+    {
+        let sheet = workbook.get_sheet_by_name_mut("Notes").expect("notes");
+        sheet.get_cell_mut("A1").set_value_number(45292);
+        sheet
+            .get_style_mut("A1")
+            .get_number_format_mut()
+            .set_format_code("[$-409]mmmm d, yyyy");
+    }
+    umya_spreadsheet::writer::xlsx::write(&workbook, &source_path).expect("write workbook");
+
+    let ops_path = tmp.path().join("locale-ops.json");
     write_ops_payload(
         &ops_path,
-        r#"{"ops":[{"kind":"insert_rows","sheet_name":"Sheet1","at_row":4,"count":1,"expand_adjacent_sums":true}]}"#,
+        r#"{"ops":[{"target":{"kind":"workbook"},"patch":{"number_format_locale":{"from":"en-US","to":"de-DE"}}}]}"#,
     );
+    let source = source_path.to_str().expect("source utf8");
+    let ops_ref = format!("@{}", ops_path.to_str().expect("ops utf8"));
 
-    let file = wb.to_str().unwrap();
-    let ops_ref = format!("@{}", ops_path.to_str().unwrap());
-
-    let output = run_cli(&[
-        "structure-batch",
-        file,
+    let dry_run = run_cli(&[
+        "style-batch",
+        source,
         "--ops",
         ops_ref.as_str(),
-        "--in-place",
+        "--dry-run",
     ]);
-    assert!(
-        output.status.success(),
-        "stderr: {}",
-        String::from_utf8_lossy(&output.stderr)
-    );
-
-    let book = umya_spreadsheet::reader::xlsx::read(&wb).expect("read workbook");
-    let sheet = book.get_sheet_by_name("Sheet1").unwrap();
-    // Subtotal shifted to row 5; formula expanded to include new row 4.
-    let formula = sheet.get_cell("A5").unwrap().get_formula().to_string();
+    assert!(dry_run.status.success(), "stderr: {:?}", dry_run.stderr);
+    let payload = parse_stdout_json(&dry_run);
     assert_eq!(
-        formula.to_uppercase().replace(' ', ""),
-        "SUM(A1:A4)",
-        "SUM should expand to include inserted row"
+        payload["summary"]["result_counts"]["number_formats_converted"], 3,
+        "payload: {payload}"
     );
-}
 
-#[test]
-fn cli_structure_batch_clone_row_in_place() {
-    let tmp = tempdir().expect("tempdir");
-    let wb = tmp.path().join("clone_row.xlsx");
-    let ops_path = tmp.path().join("ops.json");
+    let output_path = tmp.path().join("de-template.xlsx");
+    let output = run_cli(&[
+        "write",
+        "batch",
+        "style",
+        source,
+        "--ops",
+        ops_ref.as_str(),
+        "--output",
+        output_path.to_str().expect("output utf8"),
+    ]);
+    assert!(output.status.success(), "stderr: {:?}", output.stderr);
 
-    // Build fixture: header, template row, subtotal
-    {
-        let mut workbook = umya_spreadsheet::new_file();
-        let sheet = workbook.get_sheet_by_name_mut("Sheet1").unwrap();
-        sheet.get_cell_mut("A1").set_value("Header");
-        sheet.get_cell_mut("B1").set_value_number(100.0);
-        sheet.get_cell_mut("A2").set_value("Total");
-        sheet.get_cell_mut("B2").set_formula("SUM(B1:B1)");
-        umya_spreadsheet::writer::xlsx::write(&workbook, &wb).expect("write fixture");
-    }
+    let book = umya_spreadsheet::reader::xlsx::read(&output_path).expect("read output");
+    let format_of = |sheet: &str, cell: &str| {
+        book.get_sheet_by_name(sheet)
+            .expect("sheet")
+            .get_cell(cell)
+            .and_then(|cell| cell.get_style().get_number_format())
+            .map(|format| format.get_format_code().to_string())
+            .expect("number format")
+    };
+    assert_eq!(format_of("Sheet1", "B2"), r#"#,##0.00\ "€""#);
+    assert_eq!(format_of("Sheet1", "C2"), "d.m.yyyy");
+    assert_eq!(format_of("Sheet1", "D2"), "0.0%");
+    assert_eq!(format_of("Notes", "A1"), "[$-407]mmmm d, yyyy");
 
     write_ops_payload(
         &ops_path,
-        r#"{"ops":[{"kind":"clone_row","sheet_name":"Sheet1","source_row":1,"insert_at":2,"count":2,"expand_adjacent_sums":true}]}"#,
+        r#"{"ops":[{"target":{"kind":"workbook"},"patch":{"number_format_locale":{"from":"en-US","to":"xx-YY"}}}]}"#,
     );
-
-    let file = wb.to_str().unwrap();
-    let ops_ref = format!("@{}", ops_path.to_str().unwrap());
-
-    let output = run_cli(&[
-        "structure-batch",
-        file,
+    let rejected = run_cli(&[
+        "style-batch",
+        source,
         "--ops",
         ops_ref.as_str(),
-        "--in-place",
+        "--dry-run",
     ]);
+    assert!(!rejected.status.success(), "unknown locale should fail");
+}
+
+#[test]
+fn cli_find_value_regex_groups_by_sheet_and_paginates() {
+    let tmp = tempdir().expect("tempdir");
+    let path = tmp.path().join("ledger.xlsx");
+    let mut workbook = umya_spreadsheet::new_file();
+    {
+        let sheet = workbook.get_sheet_by_name_mut("Sheet1").expect("sheet1");
+        sheet.get_cell_mut("A1").set_value("Account");
+        sheet.get_cell_mut("A2").set_value("ACC-000123");
+        sheet.get_cell_mut("A3").set_value("acc-000124");
+        sheet.get_cell_mut("A4").set_value("ACC-12");
+    }
+    workbook.new_sheet("Archive").expect("archive sheet");
+    {
+        let sheet = workbook.get_sheet_by_name_mut("Archive").expect("archive");
+        sheet.get_cell_mut("B2").set_value("ACC-900001");
+        sheet.get_cell_mut("B3").set_value("ACC-900002");
+        sheet.get_cell_mut("B4").set_value("ACC-900003");
+    }
+    workbook.new_sheet("Notes").expect("notes sheet");
+    workbook
+        .get_sheet_by_name_mut("Notes")
+        .expect("notes")
+        .get_cell_mut("A1")
+        .set_value("No accounts here");
+    umya_spreadsheet::writer::xlsx::write(&workbook, &path).expect("write workbook");
+    let file = path.to_str().expect("path utf8");
+    let pattern = r"^ACC-\d{6}$";
+
+    let first_page = run_cli(&["find-value", file, pattern, "--regex", "--limit", "2"]);
     assert!(
-        output.status.success(),
-        "stderr: {}",
-        String::from_utf8_lossy(&output.stderr)
+        first_page.status.success(),
+        "stderr: {:?}",
+        first_page.stderr
     );
+    let payload = parse_stdout_json(&first_page);
+    assert_eq!(payload["match_count"], 2, "payload: {payload}");
+    assert_eq!(payload["matches"][0]["address"], "A2");
+    assert_eq!(payload["matches"][1]["address"], "A3");
+    assert_eq!(payload["next_offset"], 2);
 
-    let payload = parse_stdout_json(&output);
-    assert!(payload["changed"].as_bool().unwrap_or(false));
-
-    let book = umya_spreadsheet::reader::xlsx::read(&wb).expect("read workbook");
-    let sheet = book.get_sheet_by_name("Sheet1").unwrap();
-
-    // Cloned rows at 2 and 3 should copy template values.
-    let a2 = sheet.get_cell("A2").unwrap().get_value().to_string();
-    assert_eq!(a2, "Header");
-    let b2 = sheet.get_cell("B2").unwrap().get_value().to_string();
-    assert_eq!(b2, "100");
-
-    // Subtotal shifted to row 4; formula expanded.
-    let formula = sheet.get_cell("B4").unwrap().get_formula().to_string();
+    let last_page = run_cli(&[
+        "find-value",
+        file,
+        pattern,
+        "--regex",
+        "--limit",
+        "2",
+        "--offset",
+        "4",
+    ]);
+    assert!(last_page.status.success(), "stderr: {:?}", last_page.stderr);
+    let payload = parse_stdout_json(&last_page);
+    assert_eq!(payload["match_count"], 1, "payload: {payload}");
+    assert_eq!(payload["matches"][0]["sheet_name"], "Archive");
+    assert!(payload.get("next_offset").is_none());
+
+    let grouped = run_cli(&["find-value", file, pattern, "--regex", "--all-sheets"]);
+    assert!(grouped.status.success(), "stderr: {:?}", grouped.stderr);
+    let payload = parse_stdout_json(&grouped);
+    assert_eq!(payload["match_count"], 5, "payload: {payload}");
+    let sheets = payload["sheets"].as_array().expect("sheet groups");
+    assert_eq!(sheets.len(), 2, "payload: {payload}");
+    assert_eq!(sheets[0]["sheet_name"], "Sheet1");
+    assert_eq!(sheets[0]["match_count"], 2);
+    assert_eq!(sheets[1]["sheet_name"], "Archive");
     assert_eq!(
-        formula.to_uppercase().replace(' ', ""),
-        "SUM(B1:B3)",
-        "SUM should expand to include cloned rows"
+        sheets[1]["first_addresses"],
+        serde_json::json!(["B2", "B3", "B4"])
     );
+    assert!(payload["matches"].as_array().expect("matches").is_empty());
+
+    let invalid = run_cli(&["find-value", file, "ACC-(", "--regex"]);
+    assert!(!invalid.status.success(), "an invalid regex should fail");
+    let stderr = String::from_utf8_lossy(&invalid.stderr);
+    assert!(stderr.contains("invalid regex"), "stderr: {stderr}");
 }
 
 #[test]
-fn cli_end_to_end_budget_cloning_and_appending() {
+fn cli_find_value_compares_numbers_and_dates() {
     let tmp = tempdir().expect("tempdir");
-    let wb = tmp.path().join("budget.xlsx");
-    let rows_path = tmp.path().join("rows.json");
-
-    // 1. Build initial budget template
+    let path = tmp.path().join("model.xlsx");
+    let mut workbook = umya_spreadsheet::new_file();
+    workbook
+        .new_sheet("Assumptions")
+        .expect("assumptions sheet");
     {
-        let mut workbook = umya_spreadsheet::new_file();
-        let sheet = workbook.get_sheet_by_name_mut("Sheet1").unwrap();
-        sheet.get_cell_mut("A1").set_value("Dept: Marketing");
-
-        sheet.get_cell_mut("A2").set_value("Item");
-        sheet.get_cell_mut("B2").set_value("Cost");
-
-        sheet.get_cell_mut("A3").set_value("Ads");
-        sheet.get_cell_mut("B3").set_value_number(5000.0);
-
-        sheet.get_cell_mut("A4").set_value("Subtotal");
-        sheet.get_cell_mut("B4").set_formula("SUM(B3:B3)");
-
-        // Let's make "Dept: Marketing" span A1:B1 to test safe merge policy drop
-        sheet.add_merge_cells("A1:B1");
-
-        // Grand Total row at the bottom (Row 7 now, leaving row 5, 6 blank to space it out)
-        sheet.get_cell_mut("A7").set_value("Grand Total");
-        sheet.get_cell_mut("B7").set_formula("B4"); // Simple ref to Dept Total
-
-        umya_spreadsheet::writer::xlsx::write(&workbook, &wb).expect("write fixture");
+        let sheet = workbook
+            .get_sheet_by_name_mut("Assumptions")
+            .expect("assumptions");
+        sheet.get_cell_mut("A1").set_value("Revenue floor");
+        sheet.get_cell_mut("B1").set_value_number(500000);
+        sheet.get_cell_mut("A2").set_value("Revenue target");
+        sheet.get_cell_mut("B2").set_value_number(1500000);
+        sheet.get_cell_mut("A3").set_value("Stretch");
+        sheet.get_cell_mut("B3").set_value_number(2500000);
+        sheet.get_cell_mut("A4").set_value("As text");
+        sheet.get_cell_mut("B4").set_value("2,000,000");
+        sheet.get_cell_mut("A5").set_value("Launch");
+        sheet.get_cell_mut("B5").set_value_number(46068);
+        sheet
+            .get_style_mut("B5")
+            .get_number_format_mut()
+            .set_format_code("yyyy-mm-dd");
+        sheet.get_cell_mut("B6").set_value_number(46143);
+        sheet
+            .get_style_mut("B6")
+            .get_number_format_mut()
+            .set_format_code("yyyy-mm-dd");
     }
+    workbook
+        .get_sheet_by_name_mut("Sheet1")
+        .expect("sheet1")
+        .get_cell_mut("A1")
+        .set_value_number(9000000);
+    umya_spreadsheet::writer::xlsx::write(&workbook, &path).expect("write workbook");
+    let file = path.to_str().expect("path utf8");
 
-    let baseline = tmp.path().join("baseline.xlsx");
-    fs::copy(&wb, &baseline).unwrap();
-
-    let file = wb.to_str().unwrap();
-    let baseline_file = baseline.to_str().unwrap();
-
-    // 2. Clone the department band (Rows 1:5) to create a new department below it
-    // Row 5 is blank, providing a gutter. We insert after row 5. It will become rows 6:10.
-    let clone_out = run_cli(&[
-        "clone-row-band",
+    let over = run_cli(&[
+        "find-value",
         file,
+        "1,000,000",
+        "--value-type",
+        "number",
+        "--op",
+        "gt",
         "--sheet",
-        "Sheet1",
-        "--source-rows",
-        "1:5",
-        "--after",
-        "5",
-        "--expand-adjacent-sums",
-        "--patch-targets",
-        "likely-inputs",
-        "--merge-policy",
-        "safe",
-        "--in-place",
+        "Assumptions",
     ]);
-    assert!(
-        clone_out.status.success(),
-        "clone failed: {:?}",
-        clone_out.stderr
-    );
+    assert!(over.status.success(), "stderr: {:?}", over.stderr);
+    let payload = parse_stdout_json(&over);
+    let addresses: Vec<&str> = payload["matches"]
+        .as_array()
+        .expect("matches")
+        .iter()
+        .map(|m| m["address"].as_str().expect("address"))
+        .collect();
+    assert_eq!(addresses, ["B2", "B3"], "payload: {payload}");
 
-    // 3. Edit the new department's patch targets (It cloned to rows 6:10)
-    // The "likely inputs" should be B8 (the number 5000.0). We also want to edit A6 to "Dept: Sales"
-    let edit_out = run_cli(&[
-        "edit",
+    let dates = run_cli(&[
+        "find-value",
         file,
-        "Sheet1",
-        "A6=Dept: Sales",
-        "A8=Travel",
-        "B8=2000",
-        "B12==B4+B9", // Update Grand Total to include new dept. Grand total shifted from row 7 to 12.
+        "2026-01-01",
+        "--value-type",
+        "date",
+        "--op",
+        "between",
+        "--upper",
+        "2026-03-31",
     ]);
-    assert!(
-        edit_out.status.success(),
-        "edit failed: {:?}",
-        edit_out.stderr
-    );
+    assert!(dates.status.success(), "stderr: {:?}", dates.stderr);
+    let payload = parse_stdout_json(&dates);
+    assert_eq!(payload["match_count"], 1, "payload: {payload}");
+    assert_eq!(payload["matches"][0]["address"], "B5");
+    assert_eq!(payload["matches"][0]["value"]["value"], "2026-02-15");
 
-    // 4. Append a new line item to the new "Sales" department (Rows 6:10)
-    // The table for Sales is A7:B8, with footer at row 9 ("Dept Total").
-    // We append to region 2.
-    fs::write(&rows_path, r#"{"rows":[["Software",1500]]}"#).unwrap();
-    let append_out = run_cli(&[
-        "append-region",
+    let under = run_cli(&[
+        "find-value",
         file,
+        "46100",
+        "--value-type",
+        "number",
+        "--op",
+        "lt",
         "--sheet",
-        "Sheet1",
-        "--region-id",
-        "0",
-        "--rows",
-        &format!("@{}", rows_path.to_str().unwrap()),
-        "--in-place",
+        "Assumptions",
     ]);
-    assert!(
-        append_out.status.success(),
-        "append failed: {}",
-        String::from_utf8_lossy(&append_out.stderr)
-    );
-
-    // 5. Recalculate
-    let recalc_out = run_cli(&["recalculate", file]);
-    assert!(
-        recalc_out.status.success(),
-        "recalc failed: {:?}",
-        recalc_out.stderr
-    );
-
-    // 6. Verify and Diff
-    let verify_out = run_cli(&["verify", "--sheet", "Sheet1", baseline_file, file]);
-    assert!(
-        verify_out.status.success(),
-        "verify failed: {:?}",
-        verify_out.stderr
-    );
-    let verify_json = parse_stdout_json(&verify_out);
-    assert_eq!(
-        verify_json["summary"]["new_error_count"], 0,
-        "should have no new errors"
-    );
-
-    let final_book = umya_spreadsheet::reader::xlsx::read(&wb).unwrap();
-    let final_sheet = final_book.get_sheet_by_name("Sheet1").unwrap();
-
-    for i in 1..=14 {
-        let a = final_sheet
-            .get_cell((1, i))
-            .map(|c| c.get_value().to_string())
-            .unwrap_or_default();
-        let b = final_sheet
-            .get_cell((2, i))
-            .map(|c| c.get_value().to_string())
-            .unwrap_or_default();
-        let bf = final_sheet
-            .get_cell((2, i))
-            .map(|c| c.get_formula().to_string())
-            .unwrap_or_default();
-        println!("Row {i}: {a} | {b} | {bf}");
-    }
-
-    // Check original Dept
-    assert_eq!(
-        final_sheet.get_cell("A1").unwrap().get_value(),
-        "Dept: Marketing"
-    );
-    assert_eq!(
-        final_sheet
-            .get_cell("B4")
-            .unwrap()
-            .get_formula()
-            .replace(' ', ""),
-        "SUM(B3:B3)"
-    );
-    assert_eq!(final_sheet.get_cell("B4").unwrap().get_value(), "5000"); // Cached from recalc
-
-    // Check new Dept (Sales)
+    assert!(under.status.success(), "stderr: {:?}", under.stderr);
     assert_eq!(
-        final_sheet.get_cell("A6").unwrap().get_value(),
-        "Dept: Sales"
+        parse_stdout_json(&under)["match_count"],
+        0,
+        "date-formatted cells are not numbers"
     );
-    assert_eq!(final_sheet.get_cell("A8").unwrap().get_value(), "Travel");
-    assert_eq!(final_sheet.get_cell("B8").unwrap().get_value(), "2000");
 
-    // Check appended row (inserted at row 9, pushing footer to 10)
-    assert_eq!(final_sheet.get_cell("A9").unwrap().get_value(), "Software");
-    assert_eq!(final_sheet.get_cell("B9").unwrap().get_value(), "1500");
-
-    // Check new footer
-    assert_eq!(final_sheet.get_cell("A10").unwrap().get_value(), "Subtotal");
-    assert_eq!(
-        final_sheet
-            .get_cell("B10")
-            .unwrap()
-            .get_formula()
-            .replace(' ', ""),
-        "SUM(B8:B9)"
+    let missing_upper = run_cli(&[
+        "find-value",
+        file,
+        "2026-01-01",
+        "--value-type",
+        "date",
+        "--op",
+        "between",
+    ]);
+    assert!(!missing_upper.status.success());
+    let stderr = String::from_utf8_lossy(&missing_upper.stderr);
+    assert!(
+        stderr.contains("requires an upper bound"),
+        "stderr: {stderr}"
     );
-    assert_eq!(final_sheet.get_cell("B10").unwrap().get_value(), "3500"); // 2000 + 1500
 
-    // Check Grand Total (shifted to row 13 due to the append-region insertion)
-    assert_eq!(
-        final_sheet.get_cell("A13").unwrap().get_value(),
-        "Grand Total"
-    );
-    assert_eq!(
-        final_sheet
-            .get_cell("B13")
-            .unwrap()
-            .get_formula()
-            .replace(' ', ""),
-        "B4+B10"
-    );
-    assert_eq!(final_sheet.get_cell("B13").unwrap().get_value(), "8500"); // 5000 + 3500
+    let missing_op = run_cli(&["find-value", file, "5", "--value-type", "number"]);
+    assert!(!missing_op.status.success(), "--value-type needs --op");
 }
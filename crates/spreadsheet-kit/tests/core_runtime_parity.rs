@@ -61,7 +61,7 @@ fn core_write_and_cli_write_produce_parity_diff_counts() {
             .expect("normalize shorthand");
         core_edits.push(edit);
     }
-    spreadsheet_kit::core::write::apply_edits_to_file(&core_modified, "Sheet1", &core_edits)
+    spreadsheet_kit::core::write::apply_edits_to_file(&core_modified, "Sheet1", &core_edits, None)
         .expect("apply core edits");
 
     let cli_diff = spreadsheet_kit::core::diff::diff_workbooks_json(&original, &cli_modified)
@@ -131,8 +131,12 @@ fn cli_read_matches_mcp_tools_for_representative_commands() {
                 ranges: vec!["A1:B3".to_string()],
                 include_headers: None,
                 include_formulas: None,
+                include_formatting: None,
                 format: Some(spreadsheet_kit::model::TableOutputFormat::Dense),
                 page_size: None,
+                csv_options: None,
+                compression: None,
+                compress_min_bytes: None,
             },
         )
         .await
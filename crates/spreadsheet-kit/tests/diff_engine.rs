@@ -2,7 +2,11 @@
 
 use spreadsheet_kit as spreadsheet_mcp;
 use spreadsheet_mcp::diff::{
-    Change, calculate_changeset,
+    Change,
+    cache::PartCache,
+    calculate_changeset, calculate_changeset_with, calculate_changeset_with_cache,
+    compare::CompareOptions,
+    ignore::DiffIgnore,
     merge::{CellDiff, ModificationType},
 };
 use std::path::PathBuf;
@@ -687,3 +691,189 @@ fn test_style_and_value_edit_keeps_value_subtype_with_style_ids() {
 // This is a known limitation. Testing would require manually crafting xlsx files
 // since umya-spreadsheet always writes strings via SST, not inline.
 // Most Excel/LibreOffice files use SST for strings, so this is a rare edge case.
+
+#[test]
+fn test_part_cache_reuses_baseline_across_candidates() {
+    let scenario = DiffScenario::new();
+    scenario.setup(
+        |book| {
+            let s = book.get_sheet_mut(&0).unwrap();
+            builders::set_cell(s, 1, 1, &CellVal::from("Apple"));
+            builders::set_cell(s, 1, 2, &CellVal::from(10));
+        },
+        |book| {
+            let s = book.get_sheet_mut(&0).unwrap();
+            builders::set_cell(s, 1, 1, &CellVal::from("Banana"));
+            builders::set_cell(s, 1, 2, &CellVal::from(10));
+        },
+    );
+
+    let cache = PartCache::default();
+    let first =
+        calculate_changeset_with_cache(&scenario.base_path, &scenario.fork_path, None, &cache)
+            .expect("cached diff");
+    let second =
+        calculate_changeset_with_cache(&scenario.base_path, &scenario.fork_path, None, &cache)
+            .expect("cached diff");
+    let uncached = scenario.run_diff(None);
+
+    let to_json = |changes: &[Change]| serde_json::to_value(changes).expect("serialize");
+    assert_eq!(to_json(&first), to_json(&uncached));
+    assert_eq!(to_json(&second), to_json(&uncached));
+
+    let stats = cache.stats();
+    assert_eq!(
+        stats.parts_parsed, 2,
+        "baseline and candidate parsed once each"
+    );
+    assert_eq!(stats.parts_reused, 2, "second diff served from the cache");
+    assert_eq!(stats.cells_cached, 4);
+}
+
+#[test]
+fn test_compare_options_suppress_noise_but_keep_real_edits() {
+    let scenario = DiffScenario::new();
+    scenario.setup(
+        |book| {
+            let s = book.get_sheet_mut(&0).unwrap();
+            builders::set_cell(s, 1, 1, &CellVal::from(100.0)); // A1
+            builders::set_cell(s, 1, 2, &CellVal::from(100.0)); // A2
+            builders::set_cell(s, 1, 3, &CellVal::from("North  Region")); // A3
+            let cell = s.get_cell_mut("A4");
+            cell.set_formula("NOW()");
+            cell.set_formula_result_default("46000.1");
+            let cell = s.get_cell_mut("A5");
+            cell.set_formula("A1*2");
+            cell.set_formula_result_default("200");
+        },
+        |book| {
+            let s = book.get_sheet_mut(&0).unwrap();
+            builders::set_cell(s, 1, 1, &CellVal::from(100.0004)); // A1: float noise
+            builders::set_cell(s, 1, 2, &CellVal::from(150.0)); // A2: real edit
+            builders::set_cell(s, 1, 3, &CellVal::from(" north region ")); // A3
+            let cell = s.get_cell_mut("A4");
+            cell.set_formula("NOW()");
+            cell.set_formula_result_default("46001.7");
+            let cell = s.get_cell_mut("A5");
+            cell.set_formula("A1*2");
+            cell.set_formula_result_default("300");
+        },
+    );
+
+    let addresses = |changes: Vec<Change>| -> Vec<String> {
+        changes
+            .into_iter()
+            .filter_map(|change| match change {
+                Change::Cell(cell) => match cell.diff {
+                    CellDiff::Modified { address, .. } => Some(address),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect()
+    };
+
+    assert_eq!(
+        addresses(scenario.run_diff(None)),
+        vec!["A1", "A2", "A3", "A4", "A5"]
+    );
+
+    let options = CompareOptions {
+        abs_tolerance: Some(0.001),
+        ignore_whitespace: true,
+        ignore_case: true,
+        ignore_volatile: true,
+        ..Default::default()
+    };
+    let changes = calculate_changeset_with(
+        &scenario.base_path,
+        &scenario.fork_path,
+        None,
+        &options,
+        None,
+    )
+    .expect("diff with options");
+    assert_eq!(addresses(changes), vec!["A2", "A5"]);
+
+    let relative = CompareOptions {
+        rel_tolerance: Some(0.5),
+        ..Default::default()
+    };
+    let changes = calculate_changeset_with(
+        &scenario.base_path,
+        &scenario.fork_path,
+        None,
+        &relative,
+        None,
+    )
+    .expect("diff with relative tolerance");
+    assert_eq!(addresses(changes), vec!["A3"]);
+
+    let invalid = CompareOptions {
+        abs_tolerance: Some(-1.0),
+        ..Default::default()
+    };
+    assert!(
+        calculate_changeset_with(
+            &scenario.base_path,
+            &scenario.fork_path,
+            None,
+            &invalid,
+            None
+        )
+        .is_err()
+    );
+}
+
+#[test]
+fn test_diff_ignore_drops_sheets_ranges_and_styles() {
+    let scenario = DiffScenario::new();
+    let setup = |book: &mut Spreadsheet, refreshed: &str, value: f64| {
+        let summary = book.get_sheet_mut(&0).unwrap();
+        summary.set_name("Summary");
+        builders::set_cell(summary, 1, 1, &CellVal::from("LastRefreshed")); // A1
+        builders::set_cell(summary, 2, 1, &CellVal::from(refreshed)); // B1
+        builders::set_cell(summary, 2, 2, &CellVal::from(value)); // B2
+        let scratch = book.new_sheet("Scratch Notes").unwrap();
+        builders::set_cell(scratch, 1, 1, &CellVal::from(refreshed));
+    };
+    scenario.setup(
+        |book| setup(book, "2026-01-01 08:00", 10.0),
+        |book| {
+            setup(book, "2026-01-02 08:00", 12.0);
+            let summary = book.get_sheet_mut(&0).unwrap();
+            summary.get_style_mut("A1").get_font_mut().set_bold(true);
+        },
+    );
+
+    let mut changes = scenario.run_diff(None);
+    assert_eq!(changes.len(), 4);
+
+    let ignore: DiffIgnore = serde_json::from_value(serde_json::json!({
+        "sheets": ["scratch*"],
+        "ranges": ["Summary!$B$1"],
+        "styles": true
+    }))
+    .unwrap();
+    assert_eq!(ignore.retain(&mut changes).unwrap(), 3);
+    assert_eq!(changes.len(), 1);
+    match &changes[0] {
+        Change::Cell(cell) => {
+            assert_eq!(cell.sheet, "Summary");
+            match &cell.diff {
+                CellDiff::Modified { address, .. } => assert_eq!(address, "B2"),
+                other => panic!("unexpected diff: {:?}", other),
+            }
+        }
+        other => panic!("unexpected change: {:?}", other),
+    }
+
+    let unqualified: DiffIgnore =
+        serde_json::from_value(serde_json::json!({ "ranges": ["A1:B1"] })).unwrap();
+    let mut changes = scenario.run_diff(None);
+    assert_eq!(unqualified.retain(&mut changes).unwrap(), 3);
+
+    let invalid: DiffIgnore =
+        serde_json::from_value(serde_json::json!({ "ranges": ["Summary!not a range"] })).unwrap();
+    assert!(invalid.retain(&mut scenario.run_diff(None)).is_err());
+}
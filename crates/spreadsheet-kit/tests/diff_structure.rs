@@ -54,6 +54,14 @@ impl DiffScenario {
 
 // Helper to inject defined names into an existing XLSX file
 fn inject_defined_names(path: &PathBuf, names: &[(&str, &str)]) {
+    let names_xml: String = names
+        .iter()
+        .map(|(n, f)| format!("<definedName name=\"{}\">{}</definedName>", n, f))
+        .collect();
+    inject_defined_names_xml(path, &names_xml);
+}
+
+fn inject_defined_names_xml(path: &PathBuf, names_xml: &str) {
     let file = File::open(path).unwrap();
     let mut archive = ZipArchive::new(file).unwrap();
 
@@ -76,11 +84,6 @@ fn inject_defined_names(path: &PathBuf, names: &[(&str, &str)]) {
 
     // 2. Modify workbook.xml
     // Insert <definedNames> before </workbook>
-    let names_xml: String = names
-        .iter()
-        .map(|(n, f)| format!("<definedName name=\"{}\">{}</definedName>", n, f))
-        .collect();
-
     let replacement = format!("<definedNames>{}</definedNames></workbook>", names_xml);
     workbook_xml = workbook_xml.replace("</workbook>", &replacement);
 
@@ -205,3 +208,94 @@ fn test_table_addition() {
     let added = diffs.iter().find(|d| matches!(d, Change::Table(TableDiff::TableAdded { display_name, .. }) if display_name == "NewTable"));
     assert!(added.is_some());
 }
+
+#[test]
+fn test_scoped_names_follow_their_sheet() {
+    let scenario = DiffScenario::new();
+
+    // Base: Sheet1, Data. Fork: Data, Sheet1. "Rate" is scoped to Data in
+    // both, so its localSheetId changes from 1 to 0 without the name moving.
+    scenario.setup(
+        |book| {
+            book.get_sheet_mut(&0).unwrap().set_name("Sheet1");
+            book.new_sheet("Data").unwrap();
+        },
+        |book| {
+            book.get_sheet_mut(&0).unwrap().set_name("Data");
+            book.new_sheet("Sheet1").unwrap();
+        },
+    );
+    inject_defined_names_xml(
+        &scenario.base_path,
+        r#"<definedName name="Rate" localSheetId="1">Data!$B$1</definedName><definedName name="Limit" localSheetId="1">Data!$B$2</definedName>"#,
+    );
+    inject_defined_names_xml(
+        &scenario.fork_path,
+        r#"<definedName name="rate" localSheetId="0">Data!$B$1</definedName><definedName name="Limit" localSheetId="0">Data!$B$3</definedName>"#,
+    );
+
+    let name_diffs: Vec<_> = scenario
+        .run_diff(None)
+        .into_iter()
+        .filter_map(|d| match d {
+            Change::Name(name) => Some(name),
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(
+        name_diffs,
+        vec![NameDiff::NameModified {
+            name: "Limit".to_string(),
+            scope_sheet: Some("Data".to_string()),
+            old_formula: "Data!$B$2".to_string(),
+            new_formula: "Data!$B$3".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn test_table_rename() {
+    let scenario = DiffScenario::new();
+
+    let add_table = |book: &mut Spreadsheet, display_name: &str, rows: u32| {
+        let sheet = book.get_sheet_mut(&0).unwrap();
+        sheet.set_name("Sheet1");
+        let mut table = Table::default();
+        table.set_name(display_name);
+        table.set_display_name(display_name);
+        table.set_area(((1, 1), (3, rows)));
+        sheet.add_table(table);
+    };
+    scenario.setup(
+        |book| add_table(book, "Sales", 5),
+        |book| add_table(book, "Revenue", 8),
+    );
+
+    let diffs = scenario.run_diff(None);
+    let table_diffs: Vec<_> = diffs
+        .iter()
+        .filter_map(|d| match d {
+            Change::Table(table) => Some(table),
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(table_diffs.len(), 1, "{table_diffs:?}");
+    match table_diffs[0] {
+        TableDiff::TableRenamed {
+            display_name,
+            old_display_name,
+            sheet,
+            old_range,
+            new_range,
+        } => {
+            assert_eq!(display_name, "Revenue");
+            assert_eq!(old_display_name, "Sales");
+            assert_eq!(sheet, "Sheet1");
+            assert_eq!(old_range, "A1:C5");
+            assert_eq!(new_range, "A1:C8");
+        }
+        other => panic!("expected a rename, got {other:?}"),
+    }
+}
@@ -153,8 +153,12 @@ async fn new_tools_cover_navigation_and_reads() -> Result<()> {
             ranges: vec!["B2".into(), "B3:C3".into()],
             include_headers: Some(true),
             include_formulas: None,
+            include_formatting: None,
             format: None,
             page_size: None,
+            csv_options: None,
+            compression: None,
+            compress_min_bytes: None,
         },
     )
     .await?;
@@ -268,6 +272,174 @@ async fn find_value_search_headers_only() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "current_thread")]
+async fn workspace_catalog_and_search_locate_workbooks() -> Result<()> {
+    use spreadsheet_mcp::tools::catalog::{
+        WorkspaceCatalogParams, WorkspaceSearchParams, workspace_catalog, workspace_search,
+    };
+
+    let workspace = support::TestWorkspace::new();
+    workspace.create_workbook("ops/inputs.xlsx", build_inputs_workbook);
+    workspace.create_workbook("finance/captable.xlsx", |book| {
+        let sheet = book.get_sheet_by_name_mut("Sheet1").unwrap();
+        sheet.set_name("Cap Table");
+        sheet.get_cell_mut("A1").set_value("Holder");
+        sheet.get_cell_mut("B1").set_value("Shares");
+        sheet.get_cell_mut("A2").set_value("Founders");
+        sheet.get_cell_mut("B2").set_value_number(800_000.0);
+        sheet.get_cell_mut("A3").set_value("Option Pool");
+        sheet.get_cell_mut("B3").set_value_number(200_000.0);
+    });
+    let state = workspace.app_state();
+
+    let catalog = workspace_catalog(state.clone(), WorkspaceCatalogParams::default()).await?;
+    assert_eq!(catalog.workbooks.len(), 2);
+    assert!(catalog.next_offset.is_none());
+    let inputs = catalog
+        .workbooks
+        .iter()
+        .find(|workbook| workbook.slug == "inputs")
+        .expect("inputs workbook listed");
+    assert!(inputs.error.is_none());
+    assert_eq!(inputs.sheet_count, 2);
+    let data = inputs
+        .sheets
+        .iter()
+        .find(|sheet| sheet.name == "Data")
+        .expect("data sheet");
+    assert_eq!(data.row_count, 4);
+    assert!(!data.tables.is_empty(), "data block detected as a table");
+
+    let paged = workspace_catalog(
+        state.clone(),
+        WorkspaceCatalogParams {
+            limit: Some(1),
+            include_tables: Some(false),
+            ..WorkspaceCatalogParams::default()
+        },
+    )
+    .await?;
+    assert_eq!(paged.workbooks.len(), 1);
+    assert_eq!(paged.next_offset, Some(1));
+    assert!(
+        paged.workbooks[0]
+            .sheets
+            .iter()
+            .all(|sheet| sheet.tables.is_empty())
+    );
+
+    let search = |sheet_name: Option<&str>, query: Option<&str>| WorkspaceSearchParams {
+        sheet_name: sheet_name.map(str::to_string),
+        query: query.map(str::to_string),
+        mode: None,
+        match_mode: None,
+        case_sensitive: false,
+        slug_prefix: None,
+        folder: None,
+        path_glob: None,
+        matches_per_workbook: None,
+        max_workbooks: None,
+    };
+
+    let by_sheet = workspace_search(state.clone(), search(Some("cap table"), None)).await?;
+    assert_eq!(by_sheet.workbooks_searched, 2);
+    assert_eq!(by_sheet.hits.len(), 1);
+    assert_eq!(by_sheet.hits[0].slug, "captable");
+    assert_eq!(
+        by_sheet.hits[0].matching_sheets,
+        vec!["Cap Table".to_string()]
+    );
+
+    let by_value = workspace_search(state.clone(), search(None, Some("comp rate"))).await?;
+    assert_eq!(by_value.hits.len(), 1);
+    assert_eq!(by_value.hits[0].slug, "inputs");
+    assert_eq!(by_value.hits[0].matches[0].address, "A2");
+
+    let both = workspace_search(state.clone(), search(Some("Cap"), Some("comp rate"))).await?;
+    assert!(both.hits.is_empty());
+
+    assert!(workspace_search(state, search(None, None)).await.is_err());
+    Ok(())
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn workbook_aliases_resolve_anywhere_an_id_is_accepted() -> Result<()> {
+    use spreadsheet_mcp::model::WorkbookId;
+    use spreadsheet_mcp::tools::alias::{
+        ListAliasesParams, RegisterAliasParams, list_aliases, register_alias,
+    };
+
+    let workspace = support::TestWorkspace::new();
+    workspace.create_workbook("finance/q3/model_v7_final.xlsx", build_inputs_workbook);
+    workspace.create_workbook("ops/tracker.xlsx", |_| {});
+    let config = workspace.config_with(|cfg| {
+        cfg.workbook_aliases.insert(
+            "Q3-Model".to_string(),
+            "finance/q3/model_v7_final.xlsx".to_string(),
+        );
+    });
+    let state = support::app_state_with_config(config);
+
+    let sheets = spreadsheet_mcp::tools::list_sheets(
+        state.clone(),
+        ListSheetsParams {
+            workbook_or_fork_id: WorkbookId("q3-model".to_string()),
+            limit: None,
+            offset: None,
+            include_bounds: None,
+        },
+    )
+    .await?;
+    assert_eq!(sheets.sheets[0].name, "Inputs");
+
+    let registered = register_alias(
+        state.clone(),
+        RegisterAliasParams {
+            alias: "tracker".to_string(),
+            target: "ops/tracker.xlsx".to_string(),
+        },
+    )
+    .await?;
+    assert!(registered.replaced_target.is_none());
+    let resolved = state.resolve_workbook(&WorkbookId("TRACKER".to_string()))?;
+    assert_eq!(resolved.workbook_id, registered.workbook_id);
+
+    // Repointing reports the old target; aliases of aliases are refused.
+    let repointed = register_alias(
+        state.clone(),
+        RegisterAliasParams {
+            alias: "tracker".to_string(),
+            target: registered.short_id.clone(),
+        },
+    )
+    .await?;
+    assert_eq!(
+        repointed.replaced_target.as_deref(),
+        Some("ops/tracker.xlsx")
+    );
+    for (alias, target) in [
+        ("latest", "q3-model"),
+        ("bad/name", "ops/tracker.xlsx"),
+        ("missing", "ops/nope.xlsx"),
+    ] {
+        let params = RegisterAliasParams {
+            alias: alias.to_string(),
+            target: target.to_string(),
+        };
+        assert!(register_alias(state.clone(), params).await.is_err());
+    }
+
+    let listed = list_aliases(state, ListAliasesParams {}).await?;
+    let names: Vec<&str> = listed
+        .aliases
+        .iter()
+        .map(|entry| entry.alias.as_str())
+        .collect();
+    assert_eq!(names, vec!["Q3-Model", "tracker"]);
+    assert!(listed.aliases.iter().all(|entry| entry.error.is_none()));
+    Ok(())
+}
+
 fn build_inputs_workbook(book: &mut Spreadsheet) {
     let inputs = book.get_sheet_by_name_mut("Sheet1").unwrap();
     inputs.set_name("Inputs");
@@ -86,8 +86,12 @@ async fn range_values_defaults_to_dense() -> Result<()> {
             ranges: vec!["A1:C2".into()],
             include_headers: Some(false),
             include_formulas: None,
+            include_formatting: None,
             format: None,
             page_size: None,
+            csv_options: None,
+            compression: None,
+            compress_min_bytes: None,
         },
     )
     .await?;
@@ -223,8 +227,12 @@ async fn range_values_truncates_with_max_cells() -> Result<()> {
             ranges: vec!["A1:C6".into()],
             include_headers: Some(false),
             include_formulas: None,
+            include_formatting: None,
             format: Some(TableOutputFormat::Values),
             page_size: None,
+            csv_options: None,
+            compression: None,
+            compress_min_bytes: None,
         },
     )
     .await?;
@@ -516,6 +524,8 @@ async fn sheet_statistics_defaults_to_summary_only() -> Result<()> {
             sheet_name: "Sheet1".into(),
             sample_rows: None,
             summary_only: None,
+            decimals: None,
+            rounding: None,
         },
     )
     .await?;
@@ -685,6 +695,8 @@ async fn sheet_statistics_truncates_with_max_items() -> Result<()> {
             sheet_name: "Sheet1".into(),
             sample_rows: None,
             summary_only: None,
+            decimals: None,
+            rounding: None,
         },
     )
     .await?;
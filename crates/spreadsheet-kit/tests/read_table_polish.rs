@@ -487,3 +487,447 @@ async fn read_table_resolves_excel_table_by_name() -> Result<()> {
     assert_eq!(table.total_rows, 2);
     Ok(())
 }
+
+#[tokio::test(flavor = "current_thread")]
+async fn read_table_sorts_before_paging_and_resolves_named_queries() -> Result<()> {
+    let workspace = support::TestWorkspace::new();
+    let _path = workspace.create_workbook("sorted.xlsx", |book| {
+        let sheet = book.get_sheet_by_name_mut("Sheet1").unwrap();
+        sheet.get_cell_mut("A1").set_value("Item");
+        sheet.get_cell_mut("B1").set_value("Amount");
+        for (row, item, amount) in [(2, "pear", 5), (3, "Apple", 40), (4, "fig", 12)] {
+            sheet.get_cell_mut((1, row)).set_value(item);
+            sheet.get_cell_mut((2, row)).set_value_number(amount);
+        }
+        sheet.get_cell_mut("A5").set_value("kiwi");
+    });
+    let config = workspace.config_with(|cfg| {
+        cfg.named_queries = serde_yaml::from_str(
+            r#"
+biggest:
+  sheet_name: Sheet1
+  range: A1:B5
+  sort: [{ column: Amount, descending: true }]
+  limit: 2
+"#,
+        )
+        .unwrap();
+    });
+    let state = support::app_state_with_config(config);
+    let workbook_id = list_workbooks(
+        state.clone(),
+        ListWorkbooksParams {
+            slug_prefix: None,
+            folder: None,
+            path_glob: None,
+            limit: None,
+            offset: None,
+            include_paths: None,
+        },
+    )
+    .await?
+    .workbooks
+    .remove(0)
+    .workbook_id;
+
+    let item_names = |rows: &[spreadsheet_mcp::model::TableRow]| -> Vec<String> {
+        rows.iter()
+            .map(|row| match row.get("Item").and_then(|v| v.as_ref()) {
+                Some(CellValue::Text(s)) => s.clone(),
+                other => panic!("unexpected item {other:?}"),
+            })
+            .collect()
+    };
+
+    // Case-insensitive text order, paged after sorting.
+    let by_item = read_table(
+        state.clone(),
+        ReadTableParams {
+            workbook_or_fork_id: workbook_id.clone(),
+            sheet_name: Some("Sheet1".into()),
+            range: Some("A1:B5".into()),
+            sort: serde_json::from_value(json!([{ "column": "Item" }]))?,
+            limit: Some(2),
+            offset: Some(1),
+            format: Some(TableOutputFormat::Json),
+            ..Default::default()
+        },
+    )
+    .await?;
+    assert_eq!(item_names(&by_item.rows), vec!["fig", "kiwi"]);
+    assert_eq!(by_item.next_offset, Some(3));
+
+    // Stored sort/limit apply; blank amounts stay last even when descending.
+    let stored = read_table(
+        state.clone(),
+        ReadTableParams {
+            workbook_or_fork_id: workbook_id.clone(),
+            query: Some("biggest".into()),
+            limit: Some(4),
+            format: Some(TableOutputFormat::Json),
+            ..Default::default()
+        },
+    )
+    .await?;
+    assert_eq!(
+        item_names(&stored.rows),
+        vec!["Apple", "fig", "pear", "kiwi"]
+    );
+
+    let err = read_table(
+        state,
+        ReadTableParams {
+            workbook_or_fork_id: workbook_id,
+            query: Some("smallest".into()),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap_err();
+    assert!(err.to_string().contains("unknown named query 'smallest'"));
+    Ok(())
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn read_table_rounds_numbers_with_requested_policy() -> Result<()> {
+    use spreadsheet_mcp::tools::param_enums::RoundingMode;
+
+    let workspace = support::TestWorkspace::new();
+    let _path = workspace.create_workbook("rounding.xlsx", |book| {
+        let sheet = book.get_sheet_by_name_mut("Sheet1").unwrap();
+        sheet.get_cell_mut("A1").set_value("Item");
+        sheet.get_cell_mut("B1").set_value("Amount");
+        for (row, item, amount) in [(2, "a", 2.675), (3, "b", 2.665), (4, "c", -1.005)] {
+            sheet.get_cell_mut((1, row)).set_value(item);
+            sheet.get_cell_mut((2, row)).set_value_number(amount);
+        }
+    });
+    let state = workspace.app_state();
+    let workbook_id = list_workbooks(
+        state.clone(),
+        ListWorkbooksParams {
+            slug_prefix: None,
+            folder: None,
+            path_glob: None,
+            limit: None,
+            offset: None,
+            include_paths: None,
+        },
+    )
+    .await?
+    .workbooks
+    .remove(0)
+    .workbook_id;
+
+    let amounts = |rows: &[spreadsheet_mcp::model::TableRow]| -> Vec<f64> {
+        rows.iter()
+            .map(|row| match row.get("Amount").and_then(|v| v.as_ref()) {
+                Some(CellValue::Number(n)) => *n,
+                other => panic!("unexpected amount {other:?}"),
+            })
+            .collect()
+    };
+    let read = |decimals: Option<u32>, rounding: Option<RoundingMode>| {
+        read_table(
+            state.clone(),
+            ReadTableParams {
+                workbook_or_fork_id: workbook_id.clone(),
+                sheet_name: Some("Sheet1".into()),
+                format: Some(TableOutputFormat::Json),
+                decimals,
+                rounding,
+                ..Default::default()
+            },
+        )
+    };
+
+    let raw = read(None, None).await?;
+    assert_eq!(amounts(&raw.rows), vec![2.675, 2.665, -1.005]);
+    assert!(raw.warnings.is_empty());
+
+    let half_up = read(Some(2), None).await?;
+    assert_eq!(amounts(&half_up.rows), vec![2.68, 2.67, -1.01]);
+
+    let half_even = read(Some(2), Some(RoundingMode::HalfEven)).await?;
+    assert_eq!(amounts(&half_even.rows), vec![2.68, 2.66, -1.0]);
+    Ok(())
+}
+
+#[cfg(feature = "compression")]
+#[tokio::test(flavor = "current_thread")]
+async fn read_table_compresses_large_results_on_request() -> Result<()> {
+    use base64::Engine;
+    use spreadsheet_mcp::model::ResponseCompression;
+    use spreadsheet_mcp::tools::compression::decompress_bytes;
+
+    let workspace = support::TestWorkspace::new();
+    let _path = workspace.create_workbook("compress.xlsx", |book| {
+        let sheet = book.get_sheet_by_name_mut("Sheet1").unwrap();
+        sheet.get_cell_mut("A1").set_value("Item");
+        sheet.get_cell_mut("B1").set_value("Amount");
+        for row in 2..=201u32 {
+            sheet
+                .get_cell_mut((1, row))
+                .set_value(format!("item-{row}"));
+            sheet
+                .get_cell_mut((2, row))
+                .set_value_number(row as f64 * 1.5);
+        }
+    });
+    let state = workspace.app_state();
+    let workbook_id = list_workbooks(
+        state.clone(),
+        ListWorkbooksParams {
+            slug_prefix: None,
+            folder: None,
+            path_glob: None,
+            limit: None,
+            offset: None,
+            include_paths: None,
+        },
+    )
+    .await?
+    .workbooks
+    .remove(0)
+    .workbook_id;
+
+    let read = |compression: Option<ResponseCompression>, compress_min_bytes: Option<u64>| {
+        read_table(
+            state.clone(),
+            ReadTableParams {
+                workbook_or_fork_id: workbook_id.clone(),
+                sheet_name: Some("Sheet1".into()),
+                limit: Some(500),
+                format: Some(TableOutputFormat::Csv),
+                compression,
+                compress_min_bytes,
+                ..Default::default()
+            },
+        )
+    };
+
+    let plain = read(None, None).await?;
+    let csv = plain.csv.clone().expect("csv");
+    assert!(plain.compressed.is_none());
+
+    // Below the default threshold the result is left alone.
+    let small = read(Some(ResponseCompression::Zstd), None).await?;
+    assert!(small.compressed.is_none());
+    assert_eq!(small.csv.as_deref(), Some(csv.as_str()));
+
+    for encoding in [ResponseCompression::Gzip, ResponseCompression::Zstd] {
+        let table = read(Some(encoding), Some(0)).await?;
+        assert!(table.csv.is_none());
+        assert_eq!(table.total_rows, 200);
+        let compressed = table.compressed.expect("compressed payload");
+        assert_eq!(compressed.encoding, encoding);
+        assert_eq!(compressed.fields, vec!["csv"]);
+        assert!(compressed.compressed_bytes < compressed.original_bytes);
+
+        let bytes = base64::engine::general_purpose::STANDARD.decode(&compressed.data)?;
+        assert_eq!(bytes.len() as u64, compressed.compressed_bytes);
+        let original = decompress_bytes(&bytes, encoding)?;
+        assert_eq!(original.len() as u64, compressed.original_bytes);
+        let data: serde_json::Value = serde_json::from_slice(&original)?;
+        assert_eq!(data, json!({ "csv": csv }));
+    }
+    Ok(())
+}
+
+#[cfg(feature = "compression")]
+#[tokio::test(flavor = "current_thread")]
+async fn sheet_page_and_range_values_compress_on_request() -> Result<()> {
+    use base64::Engine;
+    use spreadsheet_mcp::model::{CompressedPayload, ResponseCompression, SheetPageFormat};
+    use spreadsheet_mcp::tools::compression::decompress_bytes;
+    use spreadsheet_mcp::tools::{RangeValuesParams, SheetPageParams, range_values, sheet_page};
+
+    let workspace = support::TestWorkspace::new();
+    let _path = workspace.create_workbook("compress-pages.xlsx", |book| {
+        let sheet = book.get_sheet_by_name_mut("Sheet1").unwrap();
+        sheet.get_cell_mut("A1").set_value("Item");
+        sheet.get_cell_mut("B1").set_value("Amount");
+        for row in 2..=201u32 {
+            sheet
+                .get_cell_mut((1, row))
+                .set_value(format!("item-{row}"));
+            sheet
+                .get_cell_mut((2, row))
+                .set_value_number(row as f64 * 1.5);
+        }
+    });
+    let state = workspace.app_state();
+    let workbook_id = list_workbooks(
+        state.clone(),
+        ListWorkbooksParams {
+            slug_prefix: None,
+            folder: None,
+            path_glob: None,
+            limit: None,
+            offset: None,
+            include_paths: None,
+        },
+    )
+    .await?
+    .workbooks
+    .remove(0)
+    .workbook_id;
+    let decode = |compressed: &CompressedPayload| -> Result<serde_json::Value> {
+        let bytes = base64::engine::general_purpose::STANDARD.decode(&compressed.data)?;
+        Ok(serde_json::from_slice(&decompress_bytes(
+            &bytes,
+            compressed.encoding,
+        )?)?)
+    };
+
+    let page = |compression: Option<ResponseCompression>| {
+        sheet_page(
+            state.clone(),
+            SheetPageParams {
+                workbook_or_fork_id: workbook_id.clone(),
+                sheet_name: "Sheet1".into(),
+                page_size: 500,
+                format: Some(SheetPageFormat::Compact),
+                compression,
+                compress_min_bytes: Some(0),
+                ..Default::default()
+            },
+        )
+    };
+    let plain = page(None).await?;
+    assert!(plain.compressed.is_none());
+    let compressed_page = page(Some(ResponseCompression::Gzip)).await?;
+    assert!(compressed_page.compact.is_none());
+    assert_eq!(compressed_page.next_start_row, plain.next_start_row);
+    let payload = compressed_page.compressed.expect("compressed page");
+    assert_eq!(payload.fields, vec!["compact"]);
+    assert_eq!(
+        decode(&payload)?,
+        json!({ "compact": serde_json::to_value(&plain.compact)? })
+    );
+
+    let values = |compression: Option<ResponseCompression>| {
+        range_values(
+            state.clone(),
+            RangeValuesParams {
+                workbook_or_fork_id: workbook_id.clone(),
+                sheet_name: "Sheet1".into(),
+                ranges: vec!["A1:B201".into()],
+                include_headers: None,
+                include_formulas: None,
+                include_formatting: None,
+                format: Some(TableOutputFormat::Csv),
+                page_size: None,
+                csv_options: None,
+                compression,
+                compress_min_bytes: Some(0),
+            },
+        )
+    };
+    let plain = values(None).await?;
+    assert!(plain.compressed.is_none());
+    let compressed_values = values(Some(ResponseCompression::Zstd)).await?;
+    assert!(compressed_values.values.is_empty());
+    let payload = compressed_values.compressed.expect("compressed values");
+    assert_eq!(payload.fields, vec!["values"]);
+    assert!(payload.compressed_bytes < payload.original_bytes);
+    assert_eq!(
+        decode(&payload)?,
+        json!({ "values": serde_json::to_value(&plain.values)? })
+    );
+    Ok(())
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn query_table_pages_groups_and_rounds_aggregates() -> Result<()> {
+    use spreadsheet_mcp::model::CellValuePrimitive;
+    use spreadsheet_mcp::tools::param_enums::AggregateOp;
+    use spreadsheet_mcp::tools::{QueryTableParams, TableAggregation, query_table};
+
+    let workspace = support::TestWorkspace::new();
+    let _path = workspace.create_workbook("query.xlsx", |book| {
+        let sheet = book.get_sheet_by_name_mut("Sheet1").unwrap();
+        sheet.get_cell_mut("A1").set_value("Team");
+        sheet.get_cell_mut("B1").set_value("Hours");
+        for (row, team, hours) in [
+            (2, "b", 1.0),
+            (3, "a", 2.0),
+            (4, "c", 3.5),
+            (5, "a", 2.25),
+            (6, "b", 0.125),
+        ] {
+            sheet.get_cell_mut((1, row)).set_value(team);
+            sheet.get_cell_mut((2, row)).set_value_number(hours);
+        }
+    });
+    let state = workspace.app_state();
+    let workbook_id = list_workbooks(
+        state.clone(),
+        ListWorkbooksParams {
+            slug_prefix: None,
+            folder: None,
+            path_glob: None,
+            limit: None,
+            offset: None,
+            include_paths: None,
+        },
+    )
+    .await?
+    .workbooks
+    .remove(0)
+    .workbook_id;
+
+    let aggregate = |op, alias: Option<&str>| TableAggregation {
+        op,
+        column: Some("Hours".to_string()),
+        alias: alias.map(str::to_string),
+    };
+    let response = query_table(
+        state.clone(),
+        QueryTableParams {
+            workbook_or_fork_id: workbook_id.clone(),
+            sheet_name: Some("Sheet1".into()),
+            group_by: Some(vec!["Team".to_string()]),
+            aggregations: vec![
+                aggregate(AggregateOp::Avg, Some("avg")),
+                aggregate(AggregateOp::Min, None),
+            ],
+            limit: Some(2),
+            decimals: Some(1),
+            ..Default::default()
+        },
+    )
+    .await?;
+    assert_eq!(response.columns, vec!["Team", "avg", "min_Hours"]);
+    assert_eq!(response.total_groups, 3);
+    assert_eq!(response.next_offset, Some(2));
+    let numbers = |row: &[Option<CellValuePrimitive>]| -> Vec<f64> {
+        row.iter()
+            .filter_map(|value| match value {
+                Some(CellValuePrimitive::Number(n)) => Some(*n),
+                _ => None,
+            })
+            .collect()
+    };
+    // a: mean 2.125 rounds half-up to 2.1; b: mean 0.5625, min 0.125.
+    assert_eq!(numbers(&response.rows[0]), vec![2.1, 2.0]);
+    assert_eq!(numbers(&response.rows[1]), vec![0.6, 0.1]);
+
+    let err = query_table(
+        state,
+        QueryTableParams {
+            workbook_or_fork_id: workbook_id,
+            sheet_name: Some("Sheet1".into()),
+            aggregations: vec![TableAggregation {
+                op: AggregateOp::Sum,
+                column: None,
+                alias: None,
+            }],
+            ..Default::default()
+        },
+    )
+    .await
+    .expect_err("sum without a column");
+    assert!(err.to_string().contains("needs a column"), "{err}");
+    Ok(())
+}
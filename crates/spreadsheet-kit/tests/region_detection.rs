@@ -486,3 +486,89 @@ fn proper_noun_penalized_in_header_scoring() {
         "row 2 with generic headers should be preferred over row 1 with proper nouns"
     );
 }
+
+#[test]
+fn form_layout_classifies_sheet_and_region_as_form() {
+    let workspace = support::TestWorkspace::new();
+    let path = workspace.create_workbook("form.xlsx", |book| {
+        let sheet = book.get_sheet_by_name_mut("Sheet1").unwrap();
+        sheet.get_cell_mut("A1").set_value("Expense claim");
+        sheet.get_cell_mut("A2").set_value(
+            "Fill in every field and attach receipts before sending this form to finance.",
+        );
+        sheet.get_cell_mut("A4").set_value("Employee name:");
+        sheet.get_cell_mut("B4").set_value("Dana Reyes");
+        sheet.get_cell_mut("A5").set_value("Department:");
+        sheet.get_style_mut("B5").set_background_color("FFFFF2CC");
+        sheet.get_cell_mut("A6").set_value("Amount:");
+        sheet.get_cell_mut("B6").set_value_number(120);
+        sheet.get_cell_mut("A7").set_value("Cost center:");
+        sheet.get_cell_mut("B7").set_value("CC-104");
+        sheet.get_cell_mut("A9").set_value("Approved by:");
+        sheet.get_style_mut("A10").set_background_color("FFFFF2CC");
+    });
+
+    let config = Arc::new(workspace.config());
+    let ctx = WorkbookContext::load(&config, &path).expect("load");
+    let overview = ctx.sheet_overview("Sheet1").expect("overview");
+    assert_eq!(overview.sheet_kind, spreadsheet_mcp::model::SheetKind::Form);
+    let form = overview.form.expect("form block");
+    let fields: Vec<(&str, &str, bool)> = form
+        .fields
+        .iter()
+        .map(|f| (f.label.as_str(), f.input_address.as_str(), f.filled))
+        .collect();
+    assert_eq!(
+        fields,
+        vec![
+            ("Employee name", "B4", true),
+            ("Department", "B5", false),
+            ("Amount", "B6", true),
+            ("Cost center", "B7", true),
+            ("Approved by", "A10", false),
+        ]
+    );
+    assert_eq!(form.instructions, vec!["A2".to_string()]);
+    assert_eq!(form.signature_blocks, vec!["A9".to_string()]);
+    assert!(
+        overview.detected_regions.iter().any(|region| matches!(
+            region.region_kind,
+            Some(spreadsheet_mcp::model::RegionKind::Form)
+        )),
+        "regions: {:?}",
+        overview.detected_regions
+    );
+}
+
+#[test]
+fn headed_table_classifies_sheet_as_table() {
+    let workspace = support::TestWorkspace::new();
+    let path = workspace.create_workbook("table_kind.xlsx", |book| {
+        let sheet = book.get_sheet_by_name_mut("Sheet1").unwrap();
+        sheet.get_cell_mut("A1").set_value("Name");
+        sheet.get_cell_mut("B1").set_value("Region");
+        sheet.get_cell_mut("C1").set_value("Sales");
+        for row in 2..=8u32 {
+            sheet
+                .get_cell_mut((1u32, row))
+                .set_value(format!("Rep{}", row));
+            sheet
+                .get_cell_mut((2u32, row))
+                .set_value(if row % 2 == 0 { "East" } else { "West" });
+            sheet
+                .get_cell_mut((3u32, row))
+                .set_value_number((row * 100) as i32);
+        }
+    });
+
+    let config = Arc::new(workspace.config());
+    let ctx = WorkbookContext::load(&config, &path).expect("load");
+    let overview = ctx.sheet_overview("Sheet1").expect("overview");
+    assert_eq!(
+        overview.sheet_kind,
+        spreadsheet_mcp::model::SheetKind::Table,
+        "reason: {}",
+        overview.sheet_kind_reason
+    );
+    assert!(overview.form.is_none());
+}
@@ -62,8 +62,12 @@ async fn path_and_virtual_readflows_match() -> Result<()> {
             ranges: vec!["A1:B3".to_string()],
             include_headers: Some(false),
             include_formulas: None,
+            include_formatting: None,
             format: Some(TableOutputFormat::Values),
             page_size: None,
+            csv_options: None,
+            compression: None,
+            compress_min_bytes: None,
         },
     )
     .await?;
@@ -95,8 +99,12 @@ async fn path_and_virtual_readflows_match() -> Result<()> {
             ranges: vec!["A1:B3".to_string()],
             include_headers: Some(false),
             include_formulas: None,
+            include_formatting: None,
             format: Some(TableOutputFormat::Values),
             page_size: None,
+            csv_options: None,
+            compression: None,
+            compress_min_bytes: None,
         },
     )
     .await?;
@@ -2,7 +2,9 @@ use std::sync::Arc;
 
 use anyhow::Result;
 use spreadsheet_kit as spreadsheet_mcp;
-use spreadsheet_mcp::model::{FormulaParsePolicy, SheetPageFormat, TraceDirection, WorkbookId};
+use spreadsheet_mcp::model::{
+    FormulaParsePolicy, ReferenceStyle, SheetPageFormat, TraceDirection, WorkbookId,
+};
 use spreadsheet_mcp::state::AppState;
 use spreadsheet_mcp::tools::{
     DescribeWorkbookParams, FindFormulaParams, FormulaTraceParams, ListSheetsParams,
@@ -115,6 +117,11 @@ async fn paging_and_stats_suite(state: Arc<AppState>, workbook_id: WorkbookId) -
             include_styles: true,
             include_header: true,
             format: Some(SheetPageFormat::Full),
+            orientation: None,
+            start_column: None,
+            date_handling: None,
+            compression: None,
+            compress_min_bytes: None,
         },
     )
     .await?;
@@ -139,6 +146,8 @@ async fn paging_and_stats_suite(state: Arc<AppState>, workbook_id: WorkbookId) -
             sheet_name: "Data".to_string(),
             sample_rows: None,
             summary_only: Some(false),
+            decimals: None,
+            rounding: None,
         },
     )
     .await?;
@@ -176,6 +185,8 @@ async fn paging_and_stats_suite(state: Arc<AppState>, workbook_id: WorkbookId) -
             include_addresses: None,
             addresses_limit: None,
             formula_parse_policy: None,
+            inconsistencies: false,
+            reference_style: ReferenceStyle::A1,
         },
     )
     .await?;
@@ -197,6 +208,8 @@ async fn paging_and_stats_suite(state: Arc<AppState>, workbook_id: WorkbookId) -
             include_addresses: None,
             addresses_limit: None,
             formula_parse_policy: None,
+            inconsistencies: false,
+            reference_style: ReferenceStyle::A1,
         },
     )
     .await?;
@@ -227,6 +240,7 @@ async fn formula_and_dependency_suite(state: Arc<AppState>, workbook_id: Workboo
             page_size: Some(12),
             cursor: None,
             formula_parse_policy: None,
+            reference_style: ReferenceStyle::A1,
         },
     )
     .await?;
@@ -258,6 +272,7 @@ async fn formula_and_dependency_suite(state: Arc<AppState>, workbook_id: Workboo
             offset: 0,
             context_rows: None,
             context_cols: None,
+            reference_style: ReferenceStyle::A1,
         },
     )
     .await?;
@@ -273,6 +288,7 @@ async fn naming_and_styles_suite(state: Arc<AppState>, workbook_id: WorkbookId)
             workbook_or_fork_id: workbook_id.clone(),
             sheet_name: None,
             name_prefix: Some("Sales".to_string()),
+            scope: None,
         },
     )
     .await?;
@@ -454,6 +470,7 @@ async fn find_formula_defaults_and_paging() -> Result<()> {
             offset: 0,
             context_rows: None,
             context_cols: None,
+            reference_style: ReferenceStyle::A1,
         },
     )
     .await?;
@@ -474,6 +491,7 @@ async fn find_formula_defaults_and_paging() -> Result<()> {
             offset: first_page.next_offset.unwrap(),
             context_rows: None,
             context_cols: None,
+            reference_style: ReferenceStyle::A1,
         },
     )
     .await?;
@@ -496,6 +514,7 @@ async fn find_formula_defaults_and_paging() -> Result<()> {
             offset: 0,
             context_rows: None,
             context_cols: None,
+            reference_style: ReferenceStyle::A1,
         },
     )
     .await?;
@@ -75,6 +75,7 @@ async fn test_write_matrix_applies_correctly() -> Result<()> {
             mode: None,
             label: None,
             formula_parse_policy: None,
+            differential_save: None,
         },
     )
     .await?;
@@ -137,6 +138,7 @@ async fn test_write_matrix_respects_overwrite_formulas() -> Result<()> {
             mode: None,
             label: None,
             formula_parse_policy: None,
+            differential_save: None,
         },
     )
     .await?;
@@ -193,6 +195,7 @@ async fn test_write_matrix_formula_parse_policy() -> Result<()> {
             mode: None,
             label: None,
             formula_parse_policy: Some(FormulaParsePolicy::Warn),
+            differential_save: None,
         },
     )
     .await?;
@@ -215,6 +218,7 @@ async fn test_write_matrix_formula_parse_policy() -> Result<()> {
             mode: None,
             label: None,
             formula_parse_policy: Some(FormulaParsePolicy::Fail),
+            differential_save: None,
         },
     )
     .await
@@ -0,0 +1,91 @@
+#![cfg(feature = "recalc")]
+
+use image::{Rgba, RgbaImage};
+use spreadsheet_kit::recalc::image_diff::{PixelRegion, diff_images};
+
+fn page(width: u32, height: u32) -> RgbaImage {
+    RgbaImage::from_pixel(width, height, Rgba([255, 255, 255, 255]))
+}
+
+fn fill(image: &mut RgbaImage, x: u32, y: u32, width: u32, height: u32, color: [u8; 3]) {
+    for py in y..y + height {
+        for px in x..x + width {
+            image.put_pixel(px, py, Rgba([color[0], color[1], color[2], 255]));
+        }
+    }
+}
+
+#[test]
+fn identical_renders_have_no_changes() {
+    let mut before = page(120, 80);
+    fill(&mut before, 10, 10, 30, 8, [0, 0, 0]);
+    let diff = diff_images(&before, &before.clone(), 16);
+
+    assert_eq!(diff.changed_pixels, 0);
+    assert!(diff.regions.is_empty());
+    assert_eq!(diff.changed_ratio(), 0.0);
+    assert_eq!((diff.width, diff.height), (120, 80));
+    // Unchanged content is faded, not removed.
+    assert_ne!(diff.overlay.get_pixel(15, 12), &Rgba([255, 255, 255, 255]));
+}
+
+#[test]
+fn changed_blocks_are_grouped_into_regions() {
+    let before = page(200, 120);
+    let mut after = before.clone();
+    // Two nearby glyph-sized marks form one region; a distant one its own.
+    fill(&mut after, 20, 20, 6, 8, [0, 0, 0]);
+    fill(&mut after, 30, 22, 6, 8, [0, 0, 0]);
+    fill(&mut after, 150, 90, 10, 10, [0, 0, 255]);
+    let diff = diff_images(&before, &after, 16);
+
+    assert_eq!(diff.changed_pixels, 6 * 8 * 2 + 10 * 10);
+    assert_eq!(
+        diff.regions,
+        vec![
+            PixelRegion {
+                x: 20,
+                y: 20,
+                width: 16,
+                height: 10,
+            },
+            PixelRegion {
+                x: 150,
+                y: 90,
+                width: 10,
+                height: 10,
+            },
+        ]
+    );
+    let [r, g, b, _] = diff.overlay.get_pixel(152, 92).0;
+    assert!(r > g && r > b, "changed pixels are tinted red");
+}
+
+#[test]
+fn tolerance_absorbs_antialiasing_noise() {
+    let before = page(40, 40);
+    let mut after = before.clone();
+    fill(&mut after, 5, 5, 10, 10, [245, 245, 245]);
+
+    assert_eq!(diff_images(&before, &after, 16).changed_pixels, 0);
+    assert_eq!(diff_images(&before, &after, 0).changed_pixels, 100);
+}
+
+#[test]
+fn size_changes_count_uncovered_pixels() {
+    let before = page(50, 40);
+    let after = page(50, 60);
+    let diff = diff_images(&before, &after, 16);
+
+    assert_eq!((diff.width, diff.height), (50, 60));
+    assert_eq!(diff.changed_pixels, 50 * 20);
+    assert_eq!(
+        diff.regions,
+        vec![PixelRegion {
+            x: 0,
+            y: 40,
+            width: 50,
+            height: 20,
+        }]
+    );
+}
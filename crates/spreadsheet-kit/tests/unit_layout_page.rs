@@ -1,6 +1,9 @@
 use anyhow::Result;
 use spreadsheet_kit::model::{LayoutMode, LayoutRender, WorkbookId};
-use spreadsheet_kit::tools::{LayoutPageParams, ListWorkbooksParams, layout_page, list_workbooks};
+use spreadsheet_kit::tools::{
+    LayoutPageParams, ListWorkbooksParams, SheetOverviewParams, layout_page, list_workbooks,
+    sheet_overview,
+};
 use std::sync::Arc;
 
 mod support;
@@ -582,3 +585,90 @@ async fn layout_page_captures_explicit_alignment() -> Result<()> {
     assert_eq!(cell.align_h.as_deref(), Some("center"));
     Ok(())
 }
+
+// ── right-to-left sheets ──────────────────────────────────────────────────────
+
+/// Mark the first sheet view of `sheet_part` right-to-left; umya cannot set it.
+fn set_right_to_left(path: &std::path::Path, sheet_part: &str) {
+    use std::io::{Read, Write};
+
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(path).unwrap()).unwrap();
+    let mut entries = Vec::new();
+    for idx in 0..archive.len() {
+        let mut file = archive.by_index(idx).unwrap();
+        let mut data = Vec::new();
+        file.read_to_end(&mut data).unwrap();
+        if file.name() == sheet_part {
+            data = String::from_utf8(data)
+                .unwrap()
+                .replacen("<sheetView ", "<sheetView rightToLeft=\"1\" ", 1)
+                .into_bytes();
+        }
+        entries.push((file.name().to_string(), data));
+    }
+    drop(archive);
+
+    let mut writer = zip::ZipWriter::new(std::fs::File::create(path).unwrap());
+    for (name, data) in entries {
+        writer
+            .start_file(name, zip::write::FileOptions::default())
+            .unwrap();
+        writer.write_all(&data).unwrap();
+    }
+    writer.finish().unwrap();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn layout_page_mirrors_ascii_render_for_right_to_left_sheets() -> Result<()> {
+    let workspace = support::TestWorkspace::new();
+    let path = workspace.create_workbook("rtl.xlsx", |book| {
+        let sheet = book.get_sheet_by_name_mut("Sheet1").unwrap();
+        sheet.get_cell_mut("A1").set_value("Revenue");
+        sheet.get_cell_mut("B1").set_value("Costs");
+    });
+    set_right_to_left(&path, "xl/worksheets/sheet1.xml");
+
+    let state = app_state(&workspace);
+    let workbook_id = first_workbook_id(state.clone()).await?;
+    let overview = sheet_overview(
+        state.clone(),
+        SheetOverviewParams {
+            workbook_or_fork_id: workbook_id.clone(),
+            sheet_name: "Sheet1".to_string(),
+            max_regions: None,
+            max_headers: None,
+            include_headers: None,
+        },
+    )
+    .await?;
+    assert!(overview.layout.right_to_left);
+
+    let resp = layout_page(
+        state,
+        LayoutPageParams {
+            workbook_or_fork_id: workbook_id,
+            sheet_name: "Sheet1".to_string(),
+            range: Some("A1:B1".to_string()),
+            mode: None,
+            max_col_width: None,
+            fit_columns: None,
+            trim_empty_columns: Some(false),
+            render: Some(LayoutRender::Both),
+        },
+    )
+    .await?;
+
+    assert!(resp.right_to_left);
+    // JSON stays in A1 order; only the drawing is mirrored.
+    assert_eq!(resp.columns[0].col, "A");
+    let ascii = resp.ascii_render.expect("ascii_render should be present");
+    let line = ascii
+        .lines()
+        .find(|line| line.contains("Revenue"))
+        .expect("row with values");
+    assert!(
+        line.find("Costs") < line.find("Revenue"),
+        "column A should be drawn on the right:\n{ascii}"
+    );
+    Ok(())
+}
@@ -0,0 +1,219 @@
+use spreadsheet_kit::lint::{
+    LintFailOn, LintFindings, LintProfile, LintRule, LintRuleRegistry, LintRuleSetting,
+    LintSeverity, lint_workbook_with_rules,
+};
+use umya_spreadsheet::{Spreadsheet, Worksheet};
+
+struct NegativeAmounts;
+
+impl LintRule for NegativeAmounts {
+    fn id(&self) -> &str {
+        "negative_amount"
+    }
+
+    fn default_severity(&self) -> LintSeverity {
+        LintSeverity::Error
+    }
+
+    fn description(&self) -> &str {
+        "amount column holds a negative number"
+    }
+
+    fn check_sheet(&self, _book: &Spreadsheet, sheet: &Worksheet, findings: &mut LintFindings) {
+        for cell in sheet.get_cell_collection() {
+            if *cell.get_coordinate().get_col_num() != 2 {
+                continue;
+            }
+            if cell
+                .get_value()
+                .parse::<f64>()
+                .is_ok_and(|value| value < 0.0)
+            {
+                findings.report(
+                    format!(
+                        "{}!{}",
+                        sheet.get_name(),
+                        cell.get_coordinate().get_coordinate()
+                    ),
+                    "negative amount",
+                );
+            }
+        }
+    }
+}
+
+struct RequiresSummarySheet;
+
+impl LintRule for RequiresSummarySheet {
+    fn id(&self) -> &str {
+        "summary_sheet_missing"
+    }
+
+    fn default_severity(&self) -> LintSeverity {
+        LintSeverity::Warning
+    }
+
+    fn description(&self) -> &str {
+        "workbook has no Summary sheet"
+    }
+
+    fn check_workbook(&self, book: &Spreadsheet, findings: &mut LintFindings) {
+        if book.get_sheet_by_name("Summary").is_none() {
+            findings.report("workbook", "add a Summary sheet");
+        }
+    }
+}
+
+fn workbook() -> Spreadsheet {
+    let mut book = umya_spreadsheet::new_file();
+    let sheet = book.get_sheet_by_name_mut("Sheet1").unwrap();
+    sheet.get_cell_mut("A1").set_value("Item");
+    sheet.get_cell_mut("B1").set_value("Amount");
+    sheet.get_cell_mut("B2").set_value_number(12.0);
+    sheet.get_cell_mut("B3").set_value_number(-4.0);
+    book
+}
+
+fn registry() -> LintRuleRegistry {
+    LintRuleRegistry::new()
+        .with_rule(NegativeAmounts)
+        .and_then(|registry| registry.with_rule(RequiresSummarySheet))
+        .expect("register rules")
+}
+
+#[test]
+fn custom_rules_report_findings_and_gate() {
+    let report = lint_workbook_with_rules(
+        &workbook(),
+        &LintProfile::default(),
+        LintFailOn::Error,
+        None,
+        &registry(),
+    )
+    .expect("lint");
+
+    assert!(!report.passed);
+    assert_eq!(report.summary.by_rule.get("negative_amount"), Some(&1));
+    assert_eq!(
+        report.summary.by_rule.get("summary_sheet_missing"),
+        Some(&1)
+    );
+    let negative = report
+        .findings
+        .iter()
+        .find(|finding| finding.rule == "negative_amount")
+        .expect("negative finding");
+    assert_eq!(negative.location, "Sheet1!B3");
+    assert_eq!(negative.severity, LintSeverity::Error);
+}
+
+#[test]
+fn profile_overrides_apply_to_custom_rules() {
+    let mut profile = LintProfile::default();
+    profile
+        .rules
+        .insert("negative_amount".to_string(), LintRuleSetting::Off);
+
+    let report =
+        lint_workbook_with_rules(&workbook(), &profile, LintFailOn::Error, None, &registry())
+            .expect("lint");
+
+    assert!(report.passed);
+    assert_eq!(report.summary.by_rule.get("negative_amount"), None);
+    assert_eq!(report.summary.warning, 1);
+
+    let err = lint_workbook_with_rules(
+        &workbook(),
+        &profile,
+        LintFailOn::Error,
+        None,
+        &LintRuleRegistry::default(),
+    )
+    .expect_err("custom rule id is unknown without registration");
+    assert!(err.to_string().contains("unknown rule 'negative_amount'"));
+}
+
+#[test]
+fn registry_rejects_duplicate_and_builtin_ids() {
+    let mut registry = registry();
+    assert!(registry.register(NegativeAmounts).is_err());
+
+    struct ShadowsBuiltin;
+    impl LintRule for ShadowsBuiltin {
+        fn id(&self) -> &str {
+            "error_value"
+        }
+        fn default_severity(&self) -> LintSeverity {
+            LintSeverity::Info
+        }
+        fn description(&self) -> &str {
+            "shadows a built-in rule"
+        }
+    }
+    assert!(registry.register(ShadowsBuiltin).is_err());
+
+    let ids: Vec<String> = registry.rules().into_iter().map(|rule| rule.id).collect();
+    assert!(ids.contains(&"volatile_function".to_string()));
+    assert!(ids.contains(&"negative_amount".to_string()));
+}
+
+#[test]
+fn dropdown_names_resolve_against_their_sheet_scope() {
+    let mut book = umya_spreadsheet::new_file();
+    book.new_sheet("Lists").expect("lists sheet");
+    book.get_sheet_by_name_mut("Lists")
+        .unwrap()
+        .add_defined_name("Regions", "Lists!$A$1:$A$3")
+        .expect("sheet-scoped name");
+    let sheet = book.get_sheet_by_name_mut("Sheet1").unwrap();
+    let mut validations = umya_spreadsheet::structs::DataValidations::default();
+    for (sqref, source) in [
+        ("A2", "Regions"),
+        ("B2", "Lists!Regions"),
+        ("C2", "Sheet1!Regions"),
+        ("D2", "Missing"),
+    ] {
+        let mut dv = umya_spreadsheet::structs::DataValidation::default();
+        dv.set_type(umya_spreadsheet::structs::DataValidationValues::List);
+        dv.get_sequence_of_references_mut().set_sqref(sqref);
+        dv.set_formula1(source);
+        validations.add_data_validation_list(dv);
+    }
+    sheet.set_data_validations(validations);
+
+    let report = lint_workbook_with_rules(
+        &book,
+        &LintProfile::default(),
+        LintFailOn::Error,
+        None,
+        &LintRuleRegistry::default(),
+    )
+    .expect("lint");
+
+    let broken: Vec<(&str, &str)> = report
+        .findings
+        .iter()
+        .filter(|finding| finding.rule == "validation_broken")
+        .map(|finding| (finding.location.as_str(), finding.message.as_str()))
+        .collect();
+    assert_eq!(broken.len(), 3, "findings: {broken:?}");
+    assert!(
+        broken
+            .iter()
+            .any(|(location, message)| location.contains("A2")
+                && message.contains("scoped to sheet 'Lists'")
+                && message.contains("Lists!Regions"))
+    );
+    assert!(
+        broken
+            .iter()
+            .any(|(location, message)| location.contains("C2")
+                && message.contains("not defined on sheet 'Sheet1'"))
+    );
+    assert!(
+        broken
+            .iter()
+            .any(|(location, message)| location.contains("D2")
+                && message.contains("'Missing' is not defined"))
+    );
+}
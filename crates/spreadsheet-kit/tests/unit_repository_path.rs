@@ -64,3 +64,130 @@ fn path_repo_stable_id_and_revision_behavior() -> Result<()> {
     assert_eq!(resolved_short.workbook_id, stable_id_1);
     Ok(())
 }
+
+#[cfg(feature = "watch")]
+#[tokio::test(flavor = "current_thread")]
+async fn path_repo_watch_reports_added_and_changed_revisions() -> Result<()> {
+    use spreadsheet_mcp::model::WorkspaceEventKind;
+    use std::time::Duration;
+
+    let workspace = support::TestWorkspace::new();
+    let path = workspace.create_workbook("model.xlsx", |book| {
+        let sheet = book.get_sheet_by_name_mut("Sheet1").unwrap();
+        sheet.get_cell_mut("A1").set_value_number(1);
+    });
+
+    let repo = make_repo(Arc::new(workspace.config()));
+    let before = repo.list(&WorkbookFilter::default())?.workbooks[0].clone();
+    let log = repo.watch_workspace()?;
+    let mut since = log.next_seq();
+
+    let mut book = umya_spreadsheet::reader::xlsx::read(&path)?;
+    let sheet = book.get_sheet_by_name_mut("Sheet1").unwrap();
+    sheet.get_cell_mut("A1").set_value_number(2);
+    umya_spreadsheet::writer::xlsx::write(&book, &path)?;
+
+    let mut changed = None;
+    for _ in 0..20 {
+        let batch = log.wait_since(since, 100, Duration::from_millis(500)).await;
+        since = batch.next_seq;
+        changed = batch
+            .events
+            .into_iter()
+            .rev()
+            .find(|event| event.kind == WorkspaceEventKind::Changed);
+        if changed.is_some() {
+            break;
+        }
+    }
+    let changed = changed.expect("changed event");
+    assert_eq!(changed.workbook_id, before.workbook_id);
+    assert_eq!(changed.path, "model.xlsx");
+    assert_eq!(changed.previous_revision_id, before.revision_id);
+    assert_ne!(changed.revision_id, before.revision_id);
+
+    // resolve() serves the refreshed revision without another list().
+    let resolved = repo.resolve(&before.workbook_id)?;
+    assert_eq!(resolved.revision_id, changed.revision_id);
+
+    workspace.create_workbook("added.xlsx", |_| {});
+    let mut added = None;
+    for _ in 0..20 {
+        let batch = log.wait_since(since, 100, Duration::from_millis(500)).await;
+        since = batch.next_seq;
+        added = batch
+            .events
+            .into_iter()
+            .find(|event| event.kind == WorkspaceEventKind::Added);
+        if added.is_some() {
+            break;
+        }
+    }
+    let added = added.expect("added event");
+    assert_eq!(added.path, "added.xlsx");
+    assert!(added.revision_id.is_some());
+    Ok(())
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn pinned_revisions_keep_reads_consistent_until_close() -> Result<()> {
+    let workspace = support::TestWorkspace::new();
+    let path = workspace.create_workbook("tracker.xlsx", |book| {
+        let sheet = book.get_sheet_by_name_mut("Sheet1").unwrap();
+        sheet.get_cell_mut("A1").set_value_number(10);
+    });
+    workspace.create_workbook("other.xlsx", |_| {});
+    let state = support::app_state_with_config(workspace.config_with(|config| {
+        config.pin_revisions = true;
+        config.cache_capacity = 1;
+    }));
+
+    let listing = state.list_workbooks(WorkbookFilter::default())?;
+    let id_of = |slug: &str| {
+        listing
+            .workbooks
+            .iter()
+            .find(|descriptor| descriptor.slug == slug)
+            .map(|descriptor| descriptor.workbook_id.clone())
+            .expect("workbook listed")
+    };
+    let (tracker, other) = (id_of("tracker"), id_of("other"));
+    let read_a1 = |workbook: Arc<spreadsheet_mcp::workbook::WorkbookContext>| {
+        workbook
+            .with_sheet("Sheet1", |sheet| sheet.get_value("A1"))
+            .expect("read A1")
+    };
+
+    let first = state.open_workbook(&tracker).await?;
+    let pinned_revision = first.revision_id.clone();
+    assert_eq!(read_a1(first), "10");
+
+    // Saved by someone else mid-session.
+    let mut book = umya_spreadsheet::reader::xlsx::read(&path)?;
+    book.get_sheet_by_name_mut("Sheet1")
+        .unwrap()
+        .get_cell_mut("A1")
+        .set_value_number(33);
+    umya_spreadsheet::writer::xlsx::write(&book, &path)?;
+
+    // Evict the cached context; the reload still comes from the pin.
+    state.open_workbook(&other).await?;
+    let reloaded = state.open_workbook(&tracker).await?;
+    assert_eq!(reloaded.revision_id, pinned_revision);
+    assert_eq!(reloaded.path, path);
+    assert_eq!(read_a1(reloaded), "10");
+    assert_eq!(
+        state.pinned_revision(&tracker).as_deref(),
+        Some(pinned_revision.as_str())
+    );
+
+    // Another session pins what it first sees.
+    let session = state.for_session();
+    assert_eq!(read_a1(session.open_workbook(&tracker).await?), "33");
+
+    assert!(state.close_workbook(&tracker)?);
+    let refreshed = state.open_workbook(&tracker).await?;
+    assert_ne!(refreshed.revision_id, pinned_revision);
+    assert_eq!(read_a1(refreshed), "33");
+    Ok(())
+}
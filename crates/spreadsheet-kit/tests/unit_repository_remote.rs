@@ -0,0 +1,153 @@
+#![cfg(feature = "remote")]
+
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::Result;
+use spreadsheet_kit as spreadsheet_mcp;
+use spreadsheet_mcp::model::WorkbookId;
+use spreadsheet_mcp::repository::{
+    RemoteCache, RemoteWorkspaceRepository, WorkbookRepository, WorkbookSource,
+};
+use spreadsheet_mcp::tools::filters::WorkbookFilter;
+
+mod support;
+
+/// Serves `body` with ETag `"v1"` for `requests` connections, answering
+/// `304 Not Modified` to requests that already hold that ETag.
+fn serve_workbook(body: Vec<u8>, requests: usize) -> (String, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!(
+        "http://{}/reports/q3-summary.xlsx",
+        listener.local_addr().unwrap()
+    );
+    let not_modified = Arc::new(AtomicUsize::new(0));
+    let counter = not_modified.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming().take(requests) {
+            let mut stream = stream.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                let read = stream.read(&mut buf).unwrap();
+                if read == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..read]);
+            }
+            let request = String::from_utf8_lossy(&request).to_ascii_lowercase();
+            if request.contains("if-none-match: \"v1\"") {
+                counter.fetch_add(1, Ordering::SeqCst);
+                stream
+                    .write_all(
+                        b"HTTP/1.1 304 Not Modified\r\nETag: \"v1\"\r\nConnection: close\r\n\r\n",
+                    )
+                    .unwrap();
+            } else {
+                let head = format!(
+                    "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                );
+                stream.write_all(head.as_bytes()).unwrap();
+                stream.write_all(&body).unwrap();
+            }
+        }
+    });
+    (url, not_modified)
+}
+
+/// Serves `body` with ETag `"v1"` once, then answers every later request
+/// with `status`.
+fn serve_then_fail(body: Vec<u8>, status: &'static str) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!(
+        "http://{}/reports/retired.xlsx",
+        listener.local_addr().unwrap()
+    );
+    std::thread::spawn(move || {
+        for (index, stream) in listener.incoming().enumerate() {
+            let mut stream = stream.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                let read = stream.read(&mut buf).unwrap();
+                if read == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..read]);
+            }
+            let response = if index == 0 {
+                let mut response = format!(
+                    "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                )
+                .into_bytes();
+                response.extend_from_slice(&body);
+                response
+            } else {
+                format!("HTTP/1.1 {status}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+                    .into_bytes()
+            };
+            stream.write_all(&response).unwrap();
+        }
+    });
+    url
+}
+
+#[test]
+fn remote_repo_fetches_revalidates_and_falls_back_to_cache() -> Result<()> {
+    let workspace = support::TestWorkspace::new();
+    let path = workspace.create_workbook("remote_source.xlsx", |book| {
+        let sheet = book.get_sheet_by_name_mut("Sheet1").unwrap();
+        sheet.get_cell_mut("A1").set_value("remote");
+    });
+    let (url, not_modified) = serve_workbook(std::fs::read(path)?, 2);
+
+    let cache = RemoteCache::new(workspace.path("remote-cache"));
+    let repo = RemoteWorkspaceRepository::new(Arc::new(workspace.config()), cache.clone());
+    let workbook_id = repo.register(&url)?;
+
+    let resolved = repo.resolve(&workbook_id)?;
+    assert!(matches!(&resolved.source, WorkbookSource::Remote(source) if *source == url));
+    assert!(resolved.revision_id.is_none());
+    assert_eq!(
+        repo.resolve(&WorkbookId(url.clone()))?.workbook_id,
+        workbook_id
+    );
+
+    let ctx = repo.load_context(&resolved)?;
+    assert_eq!(ctx.sheet_names(), vec!["Sheet1".to_string()]);
+
+    let list = repo.list(&WorkbookFilter::default())?;
+    assert_eq!(list.workbooks.len(), 1);
+    assert_eq!(list.workbooks[0].slug, "q3-summary");
+    assert_eq!(list.workbooks[0].revision_id.as_deref(), Some("v1"));
+
+    let revalidated = cache.fetch(&url)?;
+    assert!(revalidated.from_cache);
+    assert_eq!(revalidated.revision_id, "v1");
+    assert_eq!(not_modified.load(Ordering::SeqCst), 1);
+
+    // The server has stopped answering; the cached copy is still served.
+    let offline = cache.fetch(&url)?;
+    assert!(offline.from_cache);
+    assert_eq!(offline.path, revalidated.path);
+    Ok(())
+}
+
+#[test]
+fn remote_error_status_is_not_masked_by_the_cache() -> Result<()> {
+    let workspace = support::TestWorkspace::new();
+    let path = workspace.create_workbook("retired_source.xlsx", |_| {});
+    let url = serve_then_fail(std::fs::read(path)?, "404 Not Found");
+
+    let cache = RemoteCache::new(workspace.path("remote-cache"));
+    assert!(!cache.fetch(&url)?.from_cache);
+
+    // The object is gone; a warm cache must not keep serving it.
+    let err = cache.fetch(&url).expect_err("404 should fail the fetch");
+    assert!(err.to_string().contains("HTTP 404"), "error: {err:#}");
+    Ok(())
+}
@@ -1,11 +1,23 @@
 use std::sync::Arc;
 
 use anyhow::Result;
+#[cfg(feature = "recalc")]
+use base64::Engine;
+#[cfg(feature = "recalc")]
+use base64::engine::general_purpose::STANDARD;
 use spreadsheet_kit as spreadsheet_mcp;
 use spreadsheet_mcp::model::WorkbookId;
 use spreadsheet_mcp::repository::{
     VirtualWorkbookInput, VirtualWorkspaceRepository, WorkbookRepository,
 };
+#[cfg(feature = "recalc")]
+use spreadsheet_mcp::tools::fork::{
+    CreateForkParams, SaveForkParams, create_fork, edit_batch, save_fork,
+};
+#[cfg(feature = "recalc")]
+use spreadsheet_mcp::tools::{
+    DownloadWorkbookParams, UploadWorkbookParams, download_workbook, upload_workbook,
+};
 
 mod support;
 
@@ -43,3 +55,127 @@ fn virtual_repo_register_resolve_and_load() -> Result<()> {
     assert_eq!(ctx.sheet_names(), vec!["Sheet1".to_string()]);
     Ok(())
 }
+
+#[cfg(feature = "recalc")]
+#[tokio::test(flavor = "current_thread")]
+async fn virtual_workspace_chunked_upload_fork_save_and_download() -> Result<()> {
+    let workspace = support::TestWorkspace::new();
+    let path = workspace.create_workbook("upload_source.xlsx", |book| {
+        let sheet = book.get_sheet_by_name_mut("Sheet1").unwrap();
+        sheet.get_cell_mut("A1").set_value("original");
+    });
+    let bytes = std::fs::read(path)?;
+    let state = support::app_state_with_config(workspace.config_with(|config| {
+        config.virtual_workspace = true;
+        config.recalc_enabled = true;
+    }));
+
+    let split = bytes.len() / 2;
+    let first = upload_workbook(
+        state.clone(),
+        UploadWorkbookParams {
+            key: "budget.xlsx".to_string(),
+            data: STANDARD.encode(&bytes[..split]),
+            offset: 0,
+            complete: false,
+            slug: None,
+        },
+    )
+    .await?;
+    assert!(!first.complete);
+    assert_eq!(first.received_bytes, split as u64);
+    assert!(first.workbook_id.is_none());
+
+    let out_of_order = upload_workbook(
+        state.clone(),
+        UploadWorkbookParams {
+            key: "budget.xlsx".to_string(),
+            data: STANDARD.encode(&bytes[split..]),
+            offset: split as u64 + 1,
+            complete: true,
+            slug: None,
+        },
+    )
+    .await
+    .expect_err("offset must continue the upload");
+    assert!(out_of_order.to_string().contains("expects the next chunk"));
+
+    let uploaded = upload_workbook(
+        state.clone(),
+        UploadWorkbookParams {
+            key: "budget.xlsx".to_string(),
+            data: STANDARD.encode(&bytes[split..]),
+            offset: split as u64,
+            complete: true,
+            slug: None,
+        },
+    )
+    .await?;
+    assert!(uploaded.complete);
+    assert_eq!(uploaded.received_bytes, bytes.len() as u64);
+    let workbook_id = uploaded.workbook_id.expect("workbook id once complete");
+
+    let listed = state.list_workbooks(spreadsheet_mcp::tools::filters::WorkbookFilter::default())?;
+    assert_eq!(listed.workbooks.len(), 1);
+    assert_eq!(
+        listed.workbooks[0].path.as_deref(),
+        Some("virtual/budget.xlsx")
+    );
+
+    let fork = create_fork(
+        state.clone(),
+        CreateForkParams {
+            workbook_or_fork_id: workbook_id.clone(),
+        },
+    )
+    .await?;
+    edit_batch(
+        state.clone(),
+        serde_json::from_value(serde_json::json!({
+            "fork_id": fork.fork_id,
+            "sheet_name": "Sheet1",
+            "edits": ["A1=edited"],
+        }))?,
+    )
+    .await?;
+    let saved = save_fork(
+        state.clone(),
+        SaveForkParams {
+            fork_id: fork.fork_id.clone(),
+            target_path: None,
+            drop_fork: true,
+        },
+    )
+    .await?;
+    assert_eq!(saved.saved_to, "virtual/budget.xlsx");
+
+    let mut downloaded = Vec::new();
+    let mut offset = 0;
+    loop {
+        let chunk = download_workbook(
+            state.clone(),
+            DownloadWorkbookParams {
+                workbook_or_fork_id: workbook_id.clone(),
+                offset,
+                max_bytes: Some(4096),
+            },
+        )
+        .await?;
+        assert_eq!(chunk.offset, offset);
+        downloaded.extend(STANDARD.decode(&chunk.data)?);
+        match chunk.next_offset {
+            Some(next) => offset = next,
+            None => {
+                assert_eq!(downloaded.len() as u64, chunk.total_bytes);
+                break;
+            }
+        }
+    }
+
+    let book = umya_spreadsheet::reader::xlsx::read_reader(std::io::Cursor::new(downloaded), true)?;
+    assert_eq!(
+        book.get_sheet_by_name("Sheet1").unwrap().get_value("A1"),
+        "edited"
+    );
+    Ok(())
+}
@@ -3,7 +3,9 @@ mod recalc_security {
     use spreadsheet_kit::errors::InvalidParamsError;
 
     use spreadsheet_kit::fork::{ForkConfig, ForkRegistry};
-    use spreadsheet_kit::recalc::macro_uri::{export_screenshot_uri, recalc_and_save_uri};
+    use spreadsheet_kit::recalc::macro_uri::{
+        export_pdf_uri, export_screenshot_uri, recalc_and_save_uri,
+    };
 
     #[test]
     fn macro_uri_escapes_quotes_in_sheet_name() {
@@ -30,6 +32,14 @@ mod recalc_security {
         assert!(err.is::<InvalidParamsError>());
     }
 
+    #[test]
+    fn pdf_macro_uri_escapes_quotes_and_keeps_empty_arguments() {
+        let uri = export_pdf_uri("/tmp/workbook.xlsx", "/tmp/out.pdf", "Q\"1", "").expect("uri");
+        assert!(uri.starts_with("macro:///Standard.Module1.ExportPdf("));
+        assert!(uri.contains("\"Q\"\"1\""));
+        assert!(uri.ends_with(",\"\")"));
+    }
+
     #[test]
     fn recalc_macro_uri_escapes_quotes_in_file_url() {
         let uri = recalc_and_save_uri("file:///tmp/has\"quote.xlsx").expect("uri");
@@ -52,9 +62,9 @@ mod recalc_security {
         let link = workspace.path().join("linked.xlsx");
         symlink(&outside_xlsx, &link).expect("symlink");
 
-        let fork_dir = tempfile::tempdir().expect("fork_dir");
+        let scratch_root = tempfile::tempdir().expect("scratch_root");
         let registry = ForkRegistry::new(ForkConfig {
-            fork_dir: fork_dir.path().to_path_buf(),
+            scratch_root: scratch_root.path().to_path_buf(),
             ..Default::default()
         })
         .expect("registry");
@@ -78,9 +88,9 @@ mod recalc_security {
         let base = workspace.path().join("base.xlsx");
         fs::write(&base, b"fake").expect("write base xlsx");
 
-        let fork_dir = tempfile::tempdir().expect("fork_dir");
+        let scratch_root = tempfile::tempdir().expect("scratch_root");
         let registry = ForkRegistry::new(ForkConfig {
-            fork_dir: fork_dir.path().to_path_buf(),
+            scratch_root: scratch_root.path().to_path_buf(),
             ..Default::default()
         })
         .expect("registry");
@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use spreadsheet_kit as spreadsheet_mcp;
+use spreadsheet_mcp::parse_cache::ParseCache;
 use spreadsheet_mcp::tools::filters::WorkbookFilter;
 use spreadsheet_mcp::workbook::{WorkbookContext, build_workbook_list};
 
@@ -61,6 +62,62 @@ fn workbook_context_caches_sheet_metrics() {
     assert!(summary.bytes > 0);
 }
 
+#[test]
+fn parse_cache_serves_structure_for_unchanged_workbooks() {
+    let workspace = support::TestWorkspace::new();
+    let path = workspace.create_workbook("cached.xlsx", |book| {
+        let sheet = book.get_sheet_by_name_mut("Sheet1").unwrap();
+        sheet.get_cell_mut("A1").set_value("Name");
+        sheet.get_cell_mut("B1").set_value("Amount");
+        sheet.get_cell_mut("A2").set_value("Alpha");
+        sheet.get_cell_mut("B2").set_value_number(10);
+    });
+    let copy = workspace.copy_workbook(&path, "cached-copy.xlsx");
+    let cache_dir = workspace.path("parse-cache");
+    let config = Arc::new(workspace.config_with(|cfg| {
+        cfg.parse_cache_dir = Some(cache_dir.clone());
+    }));
+
+    let first = WorkbookContext::load(&config, &path).expect("load workbook");
+    let overview = first.sheet_overview("Sheet1").expect("overview");
+    let summaries = first.list_summaries(true).expect("summaries");
+
+    let key = ParseCache::key_for_file(&path).expect("key");
+    let cache = ParseCache::new(&cache_dir);
+    let mut structure = cache.load(&key).expect("cache entry");
+    assert_eq!(structure.sheet_names, vec!["Sheet1".to_string()]);
+    assert_eq!(
+        structure.summaries.as_ref().map(Vec::len),
+        Some(summaries.len())
+    );
+
+    // Tag the stored overview so the next read shows where it came from.
+    structure.overviews.get_mut("Sheet1").unwrap().narrative = "from cache".to_string();
+    cache.store(&key, &structure).expect("store");
+
+    // Identical bytes at another path share the entry.
+    let second = WorkbookContext::load(&config, &copy).expect("load copy");
+    assert_eq!(second.sheet_names(), vec!["Sheet1".to_string()]);
+    assert_eq!(second.describe().sheet_count, 1);
+    let cached = second.sheet_overview("Sheet1").expect("cached overview");
+    assert_eq!(cached.narrative, "from cache");
+    assert_eq!(cached.workbook_id, second.id);
+    assert_eq!(
+        cached.detected_regions.len(),
+        overview.detected_regions.len()
+    );
+    let region = second
+        .detected_region("Sheet1", overview.detected_regions[0].id)
+        .expect("region");
+    assert_eq!(region.bounds, overview.detected_regions[0].bounds);
+
+    // Cell reads still work, parsing on demand.
+    let value = second
+        .with_sheet("Sheet1", |sheet| sheet.get_value("A2"))
+        .expect("cell");
+    assert_eq!(value, "Alpha");
+}
+
 #[test]
 fn build_workbook_list_single_mode_filters_properly() {
     let workspace = support::TestWorkspace::new();
@@ -176,6 +233,50 @@ fn excel_serial_date_conversion_edge_cases() {
     );
 }
 
+#[test]
+fn workbook_1904_date_system_is_detected_and_applied() {
+    use spreadsheet_mcp::model::CellValue;
+    use spreadsheet_mcp::tools::workbook_props::{read_workbook_props, write_date1904};
+    use spreadsheet_mcp::workbook::cell_to_value;
+
+    let workspace = support::TestWorkspace::new();
+    let path = workspace.create_workbook("mac_dates.xlsx", |book| {
+        let sheet = book.get_sheet_by_name_mut("Sheet1").unwrap();
+        let cell = sheet.get_cell_mut("A1");
+        cell.set_value_number(44135.0);
+        cell.get_style_mut()
+            .get_number_format_mut()
+            .set_format_code("yyyy-mm-dd");
+    });
+    assert!(!read_workbook_props(&path).unwrap().date1904);
+    write_date1904(&path, true).expect("set date1904");
+    assert!(read_workbook_props(&path).unwrap().date1904);
+
+    let config = Arc::new(workspace.config());
+    let context = WorkbookContext::load(&config, &path).expect("load workbook");
+    assert!(context.uses_1904_date_system());
+    assert!(context.describe().date1904);
+
+    let value = context
+        .with_sheet("Sheet1", |sheet| {
+            cell_to_value(sheet.get_cell("A1").unwrap())
+        })
+        .expect("read cell");
+    assert!(
+        matches!(&value, Some(CellValue::Date(d)) if d == "2024-11-01"),
+        "expected Date(2024-11-01), got {value:?}"
+    );
+
+    // Outside a workbook scope the default 1900 system applies.
+    let book = umya_spreadsheet::reader::xlsx::read(&path).expect("read");
+    let sheet = book.get_sheet_by_name("Sheet1").unwrap();
+    let value = cell_to_value(sheet.get_cell("A1").unwrap());
+    assert!(
+        matches!(&value, Some(CellValue::Date(d)) if d == "2020-10-31"),
+        "expected Date(2020-10-31), got {value:?}"
+    );
+}
+
 #[test]
 fn formula_graph_extracts_precedents() {
     use spreadsheet_mcp::analysis::formula::{FormulaAtlas, FormulaGraph};
@@ -386,7 +487,10 @@ fn dependents_are_deduplicated() {
 }
 
 mod date_conversion {
-    use spreadsheet_kit::workbook::{excel_serial_to_iso, excel_serial_to_iso_with_leap_bug};
+    use spreadsheet_kit::workbook::{
+        excel_serial_to_iso, excel_serial_to_iso_for_format, excel_serial_to_iso_with_leap_bug,
+        iso_to_excel_serial,
+    };
 
     #[test]
     fn excel_1900_system_basic_dates() {
@@ -457,4 +561,37 @@ mod date_conversion {
         assert_eq!(excel_serial_to_iso(44197.5, false), "2021-01-01");
         assert_eq!(excel_serial_to_iso(44197.999, false), "2021-01-01");
     }
+
+    #[test]
+    fn time_formats_keep_time_of_day() {
+        assert_eq!(
+            excel_serial_to_iso_for_format(44197.5, false, "yyyy-mm-dd hh:mm"),
+            "2021-01-01T12:00:00"
+        );
+        assert_eq!(
+            excel_serial_to_iso_for_format(44197.5, false, "mm/dd/yyyy"),
+            "2021-01-01"
+        );
+        assert_eq!(
+            excel_serial_to_iso_for_format(0.75, false, "h:mm AM/PM"),
+            "18:00:00"
+        );
+        assert_eq!(
+            excel_serial_to_iso_for_format(1.5, false, "[h]:mm:ss"),
+            "1900-01-01T12:00:00"
+        );
+    }
+
+    #[test]
+    fn iso_text_maps_back_to_serials() {
+        assert_eq!(iso_to_excel_serial("2021-01-01", false), Some(44197.0));
+        assert_eq!(
+            iso_to_excel_serial("2021-01-01T12:00:00", false),
+            Some(44197.5)
+        );
+        assert_eq!(iso_to_excel_serial("1900-02-28", false), Some(59.0));
+        assert_eq!(iso_to_excel_serial("2021-01-01", true), Some(42735.0));
+        assert_eq!(iso_to_excel_serial("06:00:00", false), Some(0.25));
+        assert_eq!(iso_to_excel_serial("Q1 2021", false), None);
+    }
 }
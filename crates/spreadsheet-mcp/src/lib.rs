@@ -16,12 +16,14 @@ pub mod repository;
 pub mod response_prune;
 pub mod rules;
 pub mod runtime;
+pub mod schedule;
 pub mod security;
 pub mod server;
 pub mod state;
 pub mod styles;
 pub mod tools;
 pub mod utils;
+pub mod webhook;
 pub mod workbook;
 
 pub use config::{CliArgs, OutputProfile, RecalcBackendKind, ServerConfig, TransportKind};
@@ -46,11 +48,24 @@ const HTTP_SERVICE_PATH: &str = "/mcp";
 pub async fn run_server(config: ServerConfig) -> Result<()> {
     let config = Arc::new(config);
     config.ensure_workspace_root()?;
+    #[cfg(feature = "recalc")]
+    for error in tools::custom_ops::custom_op_plugin_errors() {
+        tracing::warn!(%error, "failed to load custom transform op");
+    }
+    if let Some(mode) = config.schedule_mode {
+        tracing::info!(
+            workspace = %config.workspace_root.display(),
+            jobs = config.schedule.as_ref().map_or(0, |schedule| schedule.jobs.len()),
+            "starting spreadsheet MCP scheduler",
+        );
+        return schedule::run_schedule(&config, mode).await;
+    }
     let state = Arc::new(AppState::new(config.clone()));
 
     tracing::info!(
         transport = %config.transport,
         workspace = %config.workspace_root.display(),
+        profile = config.profile.as_deref().unwrap_or("none"),
         "starting spreadsheet MCP server",
     );
 
@@ -92,7 +107,7 @@ async fn run_stream_http_transport(config: Arc<ServerConfig>, state: Arc<AppStat
     let bind_addr = config.http_bind_address;
     let service_state = state.clone();
     let service = StreamableHttpService::new(
-        move || Ok(SpreadsheetServer::from_state(service_state.clone())),
+        move || Ok(SpreadsheetServer::from_state(service_state.for_session())),
         LocalSessionManager::default().into(),
         Default::default(),
     );
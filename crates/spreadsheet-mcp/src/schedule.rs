@@ -0,0 +1 @@
+pub use spreadsheet_kit::schedule::*;
@@ -1,14 +1,18 @@
 use crate::config::ServerConfig;
 use crate::errors::InvalidParamsError;
 use crate::model::{
-    CloseWorkbookResponse, DefineNameResponse, DeleteNameResponse, FindFormulaResponse,
-    FindValueResponse, FormulaTraceResponse, InspectCellsResponse, LayoutPageResponse,
-    ManifestStubResponse, NamedRangesResponse, RangeValuesResponse, ReadTableResponse,
-    SheetFormulaMapResponse, SheetListResponse, SheetOverviewResponse, SheetPageResponse,
-    SheetStatisticsResponse, SheetStylesResponse, TableProfileResponse, UpdateNameResponse,
-    VolatileScanResponse, WorkbookDescription, WorkbookListResponse, WorkbookStyleSummaryResponse,
-    WorkbookSummaryResponse,
+    CloseWorkbookResponse, DedupeScanResponse, DefineNameResponse, DeleteNameResponse,
+    ErrorScanResponse, FindFormulaResponse, FindValueResponse, FormulaLintResponse,
+    FormulaTraceResponse, InspectCellsResponse, LayoutPageResponse, ListAliasesResponse,
+    ManifestStubResponse, NamedRangesResponse, QueryTableResponse, RangeValuesResponse,
+    ReadTableResponse, RegisterAliasResponse, SheetFormulaMapResponse, SheetListResponse,
+    SheetOverviewResponse, SheetPageResponse, SheetStatisticsResponse, SheetStylesResponse,
+    TableProfileResponse, UpdateNameResponse, VolatileScanResponse, WatchWorkspaceResponse,
+    WorkbookDescription, WorkbookListResponse, WorkbookStyleSummaryResponse,
+    WorkbookSummaryResponse, WorkspaceCatalogResponse, WorkspaceSearchResponse,
 };
+#[cfg(feature = "recalc")]
+use crate::model::{DownloadWorkbookResponse, UploadWorkbookResponse};
 use crate::response_prune::Pruned;
 #[cfg(feature = "recalc")]
 use crate::response_prune::to_pruned_value;
@@ -39,18 +43,31 @@ Spreadsheet MCP: optimized for spreadsheet analysis.
 
 WORKFLOW:
 1) list_workbooks → list_sheets → workbook_summary for orientation
-2) sheet_overview for region detection (ids/bounds/kind/confidence)
+2) sheet_overview for region detection (ids/bounds/kind/confidence) and sheet_kind \
+(table/form/dashboard/notes/mixed); for forms, read the listed form fields instead of read_table
 3) For structured data: table_profile for quick column sense, then read_table with region_id/range, filters, sampling
 4) For spot checks: range_values or find_value (label mode for key-value sheets)
 
 TOOL SELECTION:
-- table_profile: Fast column/type summary before wide reads.
-- read_table: Structured table extraction. Prefer region_id or tight range; use limit + sample_mode.
+- table_profile: Fast column/type summary before wide reads; column_types[].stats gives whole-column null %, distinct count, top values, min/max/mean/stddev, and outliers. emit_schema=jsonschema|arrow adds a schema inferred over all rows.
+- read_table: Structured table extraction. Prefer region_id or tight range; use limit + sample_mode. \
+sort=[{column, descending}] orders rows before paging. query='<name>' runs a named query from workspace \
+config; params you pass override its stored fields. header_rows=2 flattens stacked headers into \
+'Q1 / Actual' names; header_rows=0 reads every row as data. header_detection reports which rows were headers. \
+orientation=transposed reads labels down the first column with one record per column. \
+For multi-megabyte pages, compression=gzip|zstd returns the data base64-encoded in `compressed` \
+once it reaches compress_min_bytes (default 65536); sheet_page and range_values take the same params.
+- query_table: Sums, averages, counts, min and max per group computed server-side \
+(group_by=['Region'], aggregations=[{op:'sum', column:'Amount'}], filters as in read_table). \
+Use it instead of paging a whole table out to total a column.
 - sheet_formula_map: Get formula overview. Use limit param for large sheets (e.g., limit=10). \
 Use sort_by='complexity' for most complex formulas first, or 'count' for most repeated. \
-Use range param to scope to specific region.
+Use range param to scope to specific region. inconsistencies=true instead lists cells breaking \
+the fill pattern of their column/row region (overrides, odd formulas) with the expected formula. \
+reference_style='r1c1' groups a filled formula once instead of per cell (also on find_formula, formula_trace).
 - formula_trace: Trace ONE cell's precedents/dependents. Use AFTER formula_map \
-to dive deep on specific outputs (e.g., trace the total cell to understand calc flow).
+to dive deep on specific outputs (e.g., trace the total cell to understand calc flow). \
+Named ranges and table references (Sales[Amount]) are followed to their cells; such edges carry via_name.
 - sheet_page: Raw cell dump. Use ONLY when region detection fails or for \
 unstructured sheets. Prefer read_table for tabular data. \
 Responses include a budget object with cell/byte limits and continuation hints when truncated.
@@ -58,14 +75,36 @@ Responses include a budget object with cell/byte limits and continuation hints w
 style, number format) per cell. Use for spot-checking specific cells AFTER discovering them \
 via sheet_overview or find_value. NOT for bulk reads — use sheet-page or range-values instead.
 - find_value with mode='label': For key-value layouts (label in col A, value in col B). \
-Use direction='right' or 'below' hints.
+Use direction='right' or 'below' hints. match_mode='regex' takes a pattern (account numbers, codes); \
+group_by_sheet=true returns per-sheet counts instead of cells. comparison={value_type:'number'|'date', \
+op:'gt'|'lt'|'between', upper} compares against query instead (query='1000000', op='gt'). Page with limit/offset.
 - find_formula: Search formulas. Default returns no context and only first 50 matches. \
 Use include_context=true for header+cell snapshots, and use limit/offset to page.
+- scan_errors: Cells whose cached values are #REF!, #DIV/0!, #N/A, etc., grouped by error type. \
+Run after a write or recalculation to review what broke; use limit/offset to page.
+- lint_formulas: Style findings by rule id (hardcoded_constant, full_column_reference, nested_if, \
+inconsistent_formula). Review hints for model hygiene, not errors; use summary_only for counts.
+- watch_workspace: Workbook added/changed/removed events with the new revision_id, \
+instead of re-polling list_workbooks. First call (no since_seq) starts the watch and returns next_seq; \
+pass it back as since_seq with timeout_ms (max 30000) to wait for changes. \
+missed_events=true means events were dropped: call list_workbooks to resync.
+- dedupe_scan: Exact copies, same-content re-saves, and near-duplicate workbooks \
+(e.g. report_final.xlsx vs report_final_v2.xlsx) in the workspace, each group with its newest file. \
+Run before choosing which of several similar workbooks is the source of truth.
+- workspace_catalog: Every workbook with size, last modified, sheets, and detected tables \
+(Excel tables plus table-like regions with headers). Use it to pick a file before list_sheets or sheet_overview.
+- workspace_search: Which workbooks have a sheet named like sheet_name (e.g. 'Cap Table') and/or a cell \
+matching query (mode='label' for labels). Returns the first few matches per workbook; follow up with find_value.
+- register_alias: Name a workbook once (alias='q3-model', target='finance/q3/model_v7.xlsx' or a workbook id), \
+then pass the alias as workbook_or_fork_id. Aliases from .asp/aliases.yaml are preloaded; list_aliases shows them all.
 
 OUTPUT DEFAULTS (token-dense profile):
 - read_table defaults to format=csv (flat string). Use format=values for raw arrays, or format=json for typed cells.
 - range_values defaults to format=values. Use format=csv or format=json as needed.
 - sheet_page defaults to format=compact; set format=full for per-cell objects.
+- sheet_page columns_by_header matches exactly by default; for messy headers pass header_match=contains or \
+header_match=fuzzy (header_match_threshold, default 0.6). resolved_headers shows what each selector picked; \
+unmatched_headers lists selectors that found nothing.
 - table_profile defaults to summary_only=true (no samples). Set summary_only=false to include sample rows.
 - sheet_statistics defaults to summary_only=true (no samples). Set summary_only=false to include samples.
 - sheet_styles defaults to summary_only=true (no descriptors/ranges/examples). Use include_descriptor/include_ranges/include_example_cells.
@@ -114,7 +153,7 @@ Fork-based editing allows 'what-if' analysis without modifying original files.
 WORKFLOW:
 1) create_fork: Create editable copy of a workbook. Returns fork_id.
 2) Optional: checkpoint_fork before large edits.
-3) edit_batch/transform_batch/style_batch/structure_batch/apply_formula_pattern/sheet_layout_batch/rules_batch/column_size_batch/replace_in_formulas: Apply edits to the fork.
+3) edit_batch/transform_batch/style_batch/structure_batch/apply_formula_pattern/sheet_layout_batch/rules_batch/column_size_batch/row_size_batch/replace_in_formulas: Apply edits to the fork.
 4) recalculate: Trigger the configured recalc backend to recompute all formulas.
 5) verify_workbook: Compare baseline/current workbook_or_fork ids for target proof plus new/resolved/preexisting errors.
 6) get_changeset: Diff fork against original. Use filters/limit/offset to keep it small.
@@ -131,7 +170,20 @@ TOOL DETAILS:
 - edit_batch: {fork_id, sheet_name, edits:[{address, value, is_formula} | `A1=100`]}. \
 Shorthand edits like `A1=100` or `B2==SUM(A1:A2)` are accepted. \
 Leading '=' in value/formula is accepted and stripped; prefer formula or is_formula=true for clarity.
-- transform_batch: Range-first clear/fill/replace. Prefer for bulk edits (blank/fill/rename) to avoid per-cell edit_batch bloat.
+- transform_batch: Range-first clear/fill/replace. Prefer for bulk edits (blank/fill/rename) to avoid per-cell edit_batch bloat. \
+clear_contents (values and formulas), clear_formats (back to the default format) and clear_all take {sheet_name, target} \
+and wipe stale data without deleting rows; preview mode reports nonempty_cells_cleared. \
+import_rows writes many rows at once from inline rows or '@file.csv'/'@file.json' (workspace-relative); \
+match_headers=true places columns under the existing header row at anchor, append=true writes below the last used row. \
+append_table_rows adds records to a named Excel table ({sheet_name, table_name, rows}), growing the table range, \
+copying calculated-column formulas down and keeping the totals row below the data. \
+sort_range ({sheet_name, range, keys, has_header}) and sort_table ({sheet_name, table_name, keys}) reorder whole rows by keys \
+[{column (letter or header), order asc|desc, value_type auto|number|text}]; moved formulas follow their row, \
+and preview mode reports the new row order in summary.row_permutations. \
+copy_range and move_range ({sheet_name, src_range, dest_anchor, dest_sheet_name?, paste all|formulas|values|formats}) \
+rearrange blocks: copies shift relative references like Excel's copy, and moves repoint every reference to the moved cells like Excel's cut. \
+fill_series ({sheet_name, range, start?, series_type linear|growth|date, step=1, date_unit day|weekday|month|year, stop?, series_in rows|columns}) \
+fills each line of range from start (a number or 'YYYY-MM-DD') or from its first cell, like Excel's Fill > Series.
 - recalculate: Required after edit_batch to update formula results. \
 May take several seconds for complex workbooks.
 - verify_workbook: Compare {baseline_workbook_or_fork_id, current_workbook_or_fork_id}. \
@@ -139,8 +191,11 @@ Optional: targets:[Sheet!A1], sheet_name, include_named_range_deltas, errors_onl
 Use this as the summary-first proof step after recalculate.
 - get_changeset: Returns a paged diff + summary. Use limit/offset to page. \
 Use include_types/exclude_types/include_subtypes/exclude_subtypes to filter (e.g. exclude_subtypes=['recalc_result']). \
-Use summary_only=true when you only need counts.
-- screenshot_sheet: {workbook_or_fork_id, sheet_name, range?}. Renders a cropped PNG for inspecting an area visually.
+Use summary_only=true when you only need counts. \
+Besides cells it reports names (name_added/deleted/modified), tables (table_added/deleted/modified/renamed), \
+validations (validation_added/deleted/modified) and conditional formats (conditional_format_added/deleted/modified).
+- screenshot_sheet: {workbook_or_fork_id, sheet_name, range?, dpi?, scale?}. Renders a cropped PNG for inspecting an area visually.
+  dpi (36-600, default 96) and scale (0.25-4.0, multiplies dpi) raise resolution for small text.
   workbook_or_fork_id may be either a real workbook_id OR a fork_id (to screenshot an edited fork).
   Returns a file:// URI under screenshot_dir (default: <workspace_root>/screenshots).
   If path mapping is configured (--path-map), client_output_path is included to help locate the file on the host.
@@ -170,9 +225,25 @@ BEST PRACTICES:
 - Discard forks when done to free resources (fork TTL is disabled by default).
 - For large edits, batch multiple cells in single edit_batch call.";
 
-fn build_instructions(recalc_enabled: bool, vba_enabled: bool) -> String {
+const VIRTUAL_INSTRUCTIONS: &str = "
+
+VIRTUAL WORKSPACE (enabled):
+Workbooks live in server memory; the server reads no client files.
+
+TOOLS:
+- upload_workbook: Store a base64 workbook under key (e.g. 'budget.xlsx'); the same key replaces it. \
+For large files send chunks in order: offset = decoded bytes sent so far, complete=false until the last chunk.
+- download_workbook: Base64 bytes of a workbook_id or fork_id. Call again with offset=next_offset \
+until it is absent; a changed revision_id means the workbook was replaced mid-download.
+- save_fork without target_path writes a fork of an uploaded workbook back under its key.";
+
+fn build_instructions(recalc_enabled: bool, vba_enabled: bool, virtual_workspace: bool) -> String {
     let mut instructions = BASE_INSTRUCTIONS.to_string();
 
+    if virtual_workspace {
+        instructions.push_str(VIRTUAL_INSTRUCTIONS);
+    }
+
     if vba_enabled {
         instructions.push_str(VBA_INSTRUCTIONS);
     } else {
@@ -214,6 +285,11 @@ impl SpreadsheetServer {
             router.merge(Self::vba_tool_router());
         }
 
+        #[cfg(feature = "recalc")]
+        if state.config().virtual_workspace {
+            router.merge(Self::virtual_tool_router());
+        }
+
         Self {
             state,
             tool_router: router,
@@ -283,6 +359,26 @@ impl SpreadsheetServer {
         Ok(result)
     }
 
+    /// [`Self::run_tool_with_timeout`] for a tool that writes to fork
+    /// `fork_id`, notifying the webhook when the write changed the fork.
+    #[cfg(feature = "recalc")]
+    async fn run_fork_write_with_timeout<T, F>(
+        &self,
+        tool: &str,
+        fork_id: String,
+        fut: F,
+    ) -> Result<T>
+    where
+        F: Future<Output = Result<T>>,
+        T: Serialize,
+    {
+        self.run_tool_with_timeout(
+            tool,
+            tools::fork_webhook::with_fork_webhook(&self.state, &fork_id, tool, fut),
+        )
+        .await
+    }
+
     fn ensure_response_size<T: Serialize>(&self, tool: &str, value: &T) -> Result<()> {
         let Some(limit) = self.state.config().max_response_bytes() else {
             return Ok(());
@@ -429,6 +525,25 @@ impl SpreadsheetServer {
             .map_err(|e| to_mcp_error_for_tool("read_table", e))
     }
 
+    #[tool(
+        name = "query_table",
+        description = "Group a table or region and compute sum/avg/count/min/max per group"
+    )]
+    pub async fn query_table(
+        &self,
+        Parameters(params): Parameters<tools::QueryTableParams>,
+    ) -> Result<Json<QueryTableResponse>, McpError> {
+        self.ensure_tool_enabled("query_table")
+            .map_err(|e| to_mcp_error_for_tool("query_table", e))?;
+        self.run_tool_with_timeout(
+            "query_table",
+            tools::query_table(self.state.clone(), params),
+        )
+        .await
+        .map(json)
+        .map_err(|e| to_mcp_error_for_tool("query_table", e))
+    }
+
     #[tool(name = "table_profile", description = "Profile a region or table")]
     pub async fn table_profile(
         &self,
@@ -610,6 +725,44 @@ impl SpreadsheetServer {
         .map_err(|e| to_mcp_error_for_tool("scan_volatiles", e))
     }
 
+    #[tool(
+        name = "scan_errors",
+        description = "Report cells whose cached values are errors (#REF!, #DIV/0!, #N/A, ...) grouped by error type"
+    )]
+    pub async fn scan_errors(
+        &self,
+        Parameters(params): Parameters<tools::ScanErrorsParams>,
+    ) -> Result<Json<ErrorScanResponse>, McpError> {
+        self.ensure_tool_enabled("scan_errors")
+            .map_err(|e| to_mcp_error_for_tool("scan_errors", e))?;
+        self.run_tool_with_timeout(
+            "scan_errors",
+            tools::scan_errors(self.state.clone(), params),
+        )
+        .await
+        .map(json)
+        .map_err(|e| to_mcp_error_for_tool("scan_errors", e))
+    }
+
+    #[tool(
+        name = "lint_formulas",
+        description = "Lint formulas for hard-coded constants, full-column aggregations, deeply nested IFs, and inconsistencies within a row or column"
+    )]
+    pub async fn lint_formulas(
+        &self,
+        Parameters(params): Parameters<tools::LintFormulasParams>,
+    ) -> Result<Json<FormulaLintResponse>, McpError> {
+        self.ensure_tool_enabled("lint_formulas")
+            .map_err(|e| to_mcp_error_for_tool("lint_formulas", e))?;
+        self.run_tool_with_timeout(
+            "lint_formulas",
+            tools::lint_formulas(self.state.clone(), params),
+        )
+        .await
+        .map(json)
+        .map_err(|e| to_mcp_error_for_tool("lint_formulas", e))
+    }
+
     #[tool(
         name = "sheet_styles",
         description = "Summarise style usage and properties for a sheet"
@@ -724,7 +877,10 @@ impl SpreadsheetServer {
         .map_err(|e| to_mcp_error_for_tool("execute_manifest", e))
     }
 
-    #[tool(name = "close_workbook", description = "Evict a workbook from cache")]
+    #[tool(
+        name = "close_workbook",
+        description = "Evict a workbook from cache and release its pinned revision (with --pin-revisions)"
+    )]
     pub async fn close_workbook(
         &self,
         Parameters(params): Parameters<tools::CloseWorkbookParams>,
@@ -739,6 +895,120 @@ impl SpreadsheetServer {
         .map(json)
         .map_err(|e| to_mcp_error_for_tool("close_workbook", e))
     }
+
+    #[tool(
+        name = "watch_workspace",
+        description = "Long-poll workspace workbook add/change/remove events with fresh revision_ids"
+    )]
+    pub async fn watch_workspace(
+        &self,
+        Parameters(params): Parameters<tools::WatchWorkspaceParams>,
+    ) -> Result<Json<WatchWorkspaceResponse>, McpError> {
+        self.ensure_tool_enabled("watch_workspace")
+            .map_err(|e| to_mcp_error_for_tool("watch_workspace", e))?;
+        self.run_tool_with_timeout(
+            "watch_workspace",
+            tools::watch_workspace(self.state.clone(), params),
+        )
+        .await
+        .map(json)
+        .map_err(|e| to_mcp_error_for_tool("watch_workspace", e))
+    }
+
+    #[tool(
+        name = "dedupe_scan",
+        description = "Find exact, same-content, and near-duplicate workbooks in the workspace"
+    )]
+    pub async fn dedupe_scan(
+        &self,
+        Parameters(params): Parameters<tools::DedupeScanParams>,
+    ) -> Result<Json<DedupeScanResponse>, McpError> {
+        self.ensure_tool_enabled("dedupe_scan")
+            .map_err(|e| to_mcp_error_for_tool("dedupe_scan", e))?;
+        self.run_tool_with_timeout(
+            "dedupe_scan",
+            tools::dedupe_scan(self.state.clone(), params),
+        )
+        .await
+        .map(json)
+        .map_err(|e| to_mcp_error_for_tool("dedupe_scan", e))
+    }
+
+    #[tool(
+        name = "workspace_catalog",
+        description = "List workspace workbooks with size, last modified, sheets, and detected tables"
+    )]
+    pub async fn workspace_catalog(
+        &self,
+        Parameters(params): Parameters<tools::catalog::WorkspaceCatalogParams>,
+    ) -> Result<Json<WorkspaceCatalogResponse>, McpError> {
+        self.ensure_tool_enabled("workspace_catalog")
+            .map_err(|e| to_mcp_error_for_tool("workspace_catalog", e))?;
+        self.run_tool_with_timeout(
+            "workspace_catalog",
+            tools::catalog::workspace_catalog(self.state.clone(), params),
+        )
+        .await
+        .map(json)
+        .map_err(|e| to_mcp_error_for_tool("workspace_catalog", e))
+    }
+
+    #[tool(
+        name = "workspace_search",
+        description = "Find workbooks by sheet name or by a cell value or label across the workspace"
+    )]
+    pub async fn workspace_search(
+        &self,
+        Parameters(params): Parameters<tools::catalog::WorkspaceSearchParams>,
+    ) -> Result<Json<WorkspaceSearchResponse>, McpError> {
+        self.ensure_tool_enabled("workspace_search")
+            .map_err(|e| to_mcp_error_for_tool("workspace_search", e))?;
+        self.run_tool_with_timeout(
+            "workspace_search",
+            tools::catalog::workspace_search(self.state.clone(), params),
+        )
+        .await
+        .map(json)
+        .map_err(|e| to_mcp_error_for_tool("workspace_search", e))
+    }
+
+    #[tool(
+        name = "register_alias",
+        description = "Give a workbook a stable alias usable anywhere a workbook id is accepted"
+    )]
+    pub async fn register_alias(
+        &self,
+        Parameters(params): Parameters<tools::alias::RegisterAliasParams>,
+    ) -> Result<Json<RegisterAliasResponse>, McpError> {
+        self.ensure_tool_enabled("register_alias")
+            .map_err(|e| to_mcp_error_for_tool("register_alias", e))?;
+        self.run_tool_with_timeout(
+            "register_alias",
+            tools::alias::register_alias(self.state.clone(), params),
+        )
+        .await
+        .map(json)
+        .map_err(|e| to_mcp_error_for_tool("register_alias", e))
+    }
+
+    #[tool(
+        name = "list_aliases",
+        description = "List workbook aliases with the workbook each resolves to"
+    )]
+    pub async fn list_aliases(
+        &self,
+        Parameters(params): Parameters<tools::alias::ListAliasesParams>,
+    ) -> Result<Json<ListAliasesResponse>, McpError> {
+        self.ensure_tool_enabled("list_aliases")
+            .map_err(|e| to_mcp_error_for_tool("list_aliases", e))?;
+        self.run_tool_with_timeout(
+            "list_aliases",
+            tools::alias::list_aliases(self.state.clone(), params),
+        )
+        .await
+        .map(json)
+        .map_err(|e| to_mcp_error_for_tool("list_aliases", e))
+    }
 }
 
 #[tool_router(router = vba_tool_router)]
@@ -782,6 +1052,48 @@ impl SpreadsheetServer {
     }
 }
 
+#[cfg(feature = "recalc")]
+#[tool_router(router = virtual_tool_router)]
+impl SpreadsheetServer {
+    #[tool(
+        name = "upload_workbook",
+        description = "Upload a base64 workbook (optionally in chunks) into the virtual workspace"
+    )]
+    pub async fn upload_workbook(
+        &self,
+        Parameters(params): Parameters<tools::UploadWorkbookParams>,
+    ) -> Result<Json<UploadWorkbookResponse>, McpError> {
+        self.ensure_tool_enabled("upload_workbook")
+            .map_err(|e| to_mcp_error_for_tool("upload_workbook", e))?;
+        self.run_tool_with_timeout(
+            "upload_workbook",
+            tools::upload_workbook(self.state.clone(), params),
+        )
+        .await
+        .map(json)
+        .map_err(|e| to_mcp_error_for_tool("upload_workbook", e))
+    }
+
+    #[tool(
+        name = "download_workbook",
+        description = "Download workbook or fork bytes as base64 (paged by offset)"
+    )]
+    pub async fn download_workbook(
+        &self,
+        Parameters(params): Parameters<tools::DownloadWorkbookParams>,
+    ) -> Result<Json<DownloadWorkbookResponse>, McpError> {
+        self.ensure_tool_enabled("download_workbook")
+            .map_err(|e| to_mcp_error_for_tool("download_workbook", e))?;
+        self.run_tool_with_timeout(
+            "download_workbook",
+            tools::download_workbook(self.state.clone(), params),
+        )
+        .await
+        .map(json)
+        .map_err(|e| to_mcp_error_for_tool("download_workbook", e))
+    }
+}
+
 #[cfg(feature = "recalc")]
 #[tool_router(router = fork_tool_router)]
 impl SpreadsheetServer {
@@ -814,8 +1126,9 @@ impl SpreadsheetServer {
     ) -> Result<Json<tools::fork::EditBatchResponse>, McpError> {
         self.ensure_recalc_enabled("edit_batch")
             .map_err(|e| to_mcp_error_for_tool("edit_batch", e))?;
-        self.run_tool_with_timeout(
+        self.run_fork_write_with_timeout(
             "edit_batch",
+            params.fork_id.clone(),
             tools::fork::edit_batch(self.state.clone(), params),
         )
         .await
@@ -834,8 +1147,9 @@ Mode: preview or apply (default apply)."
     ) -> Result<Json<tools::fork::TransformBatchResponse>, McpError> {
         self.ensure_recalc_enabled("transform_batch")
             .map_err(|e| to_mcp_error_for_tool("transform_batch", e))?;
-        self.run_tool_with_timeout(
+        self.run_fork_write_with_timeout(
             "transform_batch",
+            params.fork_id.clone(),
             tools::fork::transform_batch(self.state.clone(), params),
         )
         .await
@@ -854,8 +1168,9 @@ Mode: preview or apply (default apply). Op mode: merge (default), set, or clear.
     ) -> Result<Json<tools::fork::StyleBatchResponse>, McpError> {
         self.ensure_recalc_enabled("style_batch")
             .map_err(|e| to_mcp_error_for_tool("style_batch", e))?;
-        self.run_tool_with_timeout(
+        self.run_fork_write_with_timeout(
             "style_batch",
+            params.fork_id.clone(),
             tools::fork::style_batch(self.state.clone(), params),
         )
         .await
@@ -873,8 +1188,9 @@ Mode: preview or apply (default apply). Op mode: merge (default), set, or clear.
     ) -> Result<Json<tools::fork::GridImportResponse>, McpError> {
         self.ensure_recalc_enabled("grid_import")
             .map_err(|e| to_mcp_error_for_tool("grid_import", e))?;
-        self.run_tool_with_timeout(
+        self.run_fork_write_with_timeout(
             "grid_import",
+            params.fork_id.clone(),
             tools::fork::grid_import(self.state.clone(), params),
         )
         .await
@@ -886,6 +1202,7 @@ Mode: preview or apply (default apply). Op mode: merge (default), set, or clear.
         name = "column_size_batch",
         description = "Set column widths or compute auto-widths in a fork. Targets column ranges like 'A:A' or 'A:C'. \
 Mode: preview or apply (default apply). Auto computes and sets widths immediately (persisted). \
+Autofit (size.kind='autofit') measures displayed text after number formats, scaled by font size/bold, plus padding_chars; empty columns are left unchanged. \
 Note: autosize uses cached/formatted cell values; if a column is mostly formulas with no cached results, widths may be too narrow unless you recalculate first."
     )]
     pub async fn column_size_batch(
@@ -894,8 +1211,9 @@ Note: autosize uses cached/formatted cell values; if a column is mostly formulas
     ) -> Result<Json<tools::fork::ColumnSizeBatchResponse>, McpError> {
         self.ensure_recalc_enabled("column_size_batch")
             .map_err(|e| to_mcp_error_for_tool("column_size_batch", e))?;
-        self.run_tool_with_timeout(
+        self.run_fork_write_with_timeout(
             "column_size_batch",
+            params.fork_id.clone(),
             tools::fork::column_size_batch(self.state.clone(), params),
         )
         .await
@@ -903,9 +1221,31 @@ Note: autosize uses cached/formatted cell values; if a column is mostly formulas
         .map_err(|e| to_mcp_error_for_tool("column_size_batch", e))
     }
 
+    #[tool(
+        name = "row_size_batch",
+        description = "Set row heights or estimate auto-heights in a fork. Targets row spans like '2:10' or a single row '5'. \
+Mode: preview or apply (default apply). Auto estimates height from cell text, font size, explicit line breaks, and wrap-text at the column width. \
+Note: autosize uses cached/formatted cell values; formula cells with no cached results count as empty unless you recalculate first."
+    )]
+    pub async fn row_size_batch(
+        &self,
+        Parameters(params): Parameters<tools::fork::RowSizeBatchParamsInput>,
+    ) -> Result<Json<tools::fork::RowSizeBatchResponse>, McpError> {
+        self.ensure_recalc_enabled("row_size_batch")
+            .map_err(|e| to_mcp_error_for_tool("row_size_batch", e))?;
+        self.run_fork_write_with_timeout(
+            "row_size_batch",
+            params.fork_id.clone(),
+            tools::fork::row_size_batch(self.state.clone(), params),
+        )
+        .await
+        .map(json)
+        .map_err(|e| to_mcp_error_for_tool("row_size_batch", e))
+    }
+
     #[tool(
         name = "sheet_layout_batch",
-        description = "Apply sheet layout/view/print settings in a fork (freeze panes, zoom, gridlines, margins, setup, print area, page breaks). Mode: preview or apply (default apply)."
+        description = "Apply sheet layout/view/print settings in a fork (freeze panes, zoom, gridlines, margins, setup, print area, page breaks, hidden rows/columns, outline groups). Mode: preview or apply (default apply)."
     )]
     pub async fn sheet_layout_batch(
         &self,
@@ -913,8 +1253,9 @@ Note: autosize uses cached/formatted cell values; if a column is mostly formulas
     ) -> Result<Json<tools::sheet_layout::SheetLayoutBatchResponse>, McpError> {
         self.ensure_recalc_enabled("sheet_layout_batch")
             .map_err(|e| to_mcp_error_for_tool("sheet_layout_batch", e))?;
-        self.run_tool_with_timeout(
+        self.run_fork_write_with_timeout(
             "sheet_layout_batch",
+            params.fork_id.clone(),
             tools::sheet_layout::sheet_layout_batch(self.state.clone(), params),
         )
         .await
@@ -935,8 +1276,9 @@ fill_direction: down, right, both (default both)."
     ) -> Result<Json<tools::fork::ApplyFormulaPatternResponse>, McpError> {
         self.ensure_recalc_enabled("apply_formula_pattern")
             .map_err(|e| to_mcp_error_for_tool("apply_formula_pattern", e))?;
-        self.run_tool_with_timeout(
+        self.run_fork_write_with_timeout(
             "apply_formula_pattern",
+            params.fork_id.clone(),
             tools::fork::apply_formula_pattern(self.state.clone(), params),
         )
         .await
@@ -948,6 +1290,8 @@ fill_direction: down, right, both (default both)."
         name = "structure_batch",
         description = "Apply structural edits to a fork (rows/cols/sheets). \
 Mode: preview or apply (default apply). Aliases: op for kind, add_sheet for create_sheet. \
+copy_sheet copies a sheet from another workbook in the workspace (source_file, source_sheet, optional new_name, position) with its styles, merges, conditional formats and tables. \
+set_tab_color, hide_sheet (very_hidden: true hides it from Excel's Unhide dialog), unhide_sheet and reorder_sheets (order: names moved to the front) manage sheet tabs. \
 Note: structural edits may not fully rewrite formulas/named ranges like Excel; run recalculate and review get_changeset after applying."
     )]
     pub async fn structure_batch(
@@ -956,8 +1300,9 @@ Note: structural edits may not fully rewrite formulas/named ranges like Excel; r
     ) -> Result<Json<tools::fork::StructureBatchResponse>, McpError> {
         self.ensure_recalc_enabled("structure_batch")
             .map_err(|e| to_mcp_error_for_tool("structure_batch", e))?;
-        self.run_tool_with_timeout(
+        self.run_fork_write_with_timeout(
             "structure_batch",
+            params.fork_id.clone(),
             tools::fork::structure_batch(self.state.clone(), params),
         )
         .await
@@ -976,8 +1321,9 @@ Requires scope_sheet_name when scope is 'sheet'."
     ) -> Result<Json<DefineNameResponse>, McpError> {
         self.ensure_recalc_enabled("define_name")
             .map_err(|e| to_mcp_error_for_tool("define_name", e))?;
-        self.run_tool_with_timeout(
+        self.run_fork_write_with_timeout(
             "define_name",
+            params.fork_id.0.clone(),
             tools::define_name(self.state.clone(), params),
         )
         .await
@@ -996,8 +1342,9 @@ Scope filter: 'workbook' or 'sheet' to disambiguate."
     ) -> Result<Json<UpdateNameResponse>, McpError> {
         self.ensure_recalc_enabled("update_name")
             .map_err(|e| to_mcp_error_for_tool("update_name", e))?;
-        self.run_tool_with_timeout(
+        self.run_fork_write_with_timeout(
             "update_name",
+            params.fork_id.0.clone(),
             tools::update_name(self.state.clone(), params),
         )
         .await
@@ -1016,8 +1363,9 @@ Scope filter: 'workbook' or 'sheet' to disambiguate."
     ) -> Result<Json<DeleteNameResponse>, McpError> {
         self.ensure_recalc_enabled("delete_name")
             .map_err(|e| to_mcp_error_for_tool("delete_name", e))?;
-        self.run_tool_with_timeout(
+        self.run_fork_write_with_timeout(
             "delete_name",
+            params.fork_id.0.clone(),
             tools::delete_name(self.state.clone(), params),
         )
         .await
@@ -1035,8 +1383,9 @@ Scope filter: 'workbook' or 'sheet' to disambiguate."
     ) -> Result<Json<tools::rules_batch::RulesBatchResponse>, McpError> {
         self.ensure_recalc_enabled("rules_batch")
             .map_err(|e| to_mcp_error_for_tool("rules_batch", e))?;
-        self.run_tool_with_timeout(
+        self.run_fork_write_with_timeout(
             "rules_batch",
+            params.fork_id.clone(),
             tools::rules_batch::rules_batch(self.state.clone(), params),
         )
         .await
@@ -1058,8 +1407,9 @@ Returns count of changed formulas and sample diffs."
     ) -> Result<Json<tools::fork::ReplaceInFormulasResponse>, McpError> {
         self.ensure_recalc_enabled("replace_in_formulas")
             .map_err(|e| to_mcp_error_for_tool("replace_in_formulas", e))?;
-        self.run_tool_with_timeout(
+        self.run_fork_write_with_timeout(
             "replace_in_formulas",
+            params.fork_id.clone(),
             tools::fork::replace_in_formulas(self.state.clone(), params),
         )
         .await
@@ -1217,8 +1567,9 @@ Returns count of changed formulas and sample diffs."
     ) -> Result<Json<tools::fork::RestoreCheckpointResponse>, McpError> {
         self.ensure_recalc_enabled("restore_checkpoint")
             .map_err(|e| to_mcp_error_for_tool("restore_checkpoint", e))?;
-        self.run_tool_with_timeout(
+        self.run_fork_write_with_timeout(
             "restore_checkpoint",
+            params.fork_id.clone(),
             tools::fork::restore_checkpoint(self.state.clone(), params),
         )
         .await
@@ -1274,8 +1625,9 @@ Returns count of changed formulas and sample diffs."
     ) -> Result<Json<tools::fork::ApplyStagedChangeResponse>, McpError> {
         self.ensure_recalc_enabled("apply_staged_change")
             .map_err(|e| to_mcp_error_for_tool("apply_staged_change", e))?;
-        self.run_tool_with_timeout(
+        self.run_fork_write_with_timeout(
             "apply_staged_change",
+            params.fork_id.clone(),
             tools::fork::apply_staged_change(self.state.clone(), params),
         )
         .await
@@ -1305,7 +1657,8 @@ Returns count of changed formulas and sample diffs."
     #[tool(
         name = "screenshot_sheet",
         description = "Capture a visual screenshot of a spreadsheet region as PNG. \
-	Returns file URI. Max range: 100 rows x 30 columns. Default: A1:M40."
+	Returns file URI. Max range: 100 rows x 30 columns. Default: A1:M40. \
+	Optional dpi/scale control rasterization resolution."
     )]
     pub async fn screenshot_sheet(
         &self,
@@ -1384,11 +1737,17 @@ impl ServerHandler for SpreadsheetServer {
         };
 
         let vba_enabled = self.state.config().vba_enabled;
+        // The upload and download tools need the recalc feature's base64.
+        let virtual_workspace = cfg!(feature = "recalc") && self.state.config().virtual_workspace;
 
         ServerInfo {
             capabilities: ServerCapabilities::builder().enable_tools().build(),
             server_info: Implementation::from_build_env(),
-            instructions: Some(build_instructions(recalc_enabled, vba_enabled)),
+            instructions: Some(build_instructions(
+                recalc_enabled,
+                vba_enabled,
+                virtual_workspace,
+            )),
             ..ServerInfo::default()
         }
     }
@@ -1604,10 +1963,19 @@ fn tool_variants(tool: &str, problem: &str) -> Option<Vec<&'static str>> {
                     "insert_cols",
                     "delete_cols",
                     "rename_sheet",
+                    "rename_sheets",
+                    "rename_names",
                     "create_sheet",
                     "delete_sheet",
+                    "copy_sheet",
+                    "set_tab_color",
+                    "hide_sheet",
+                    "unhide_sheet",
+                    "reorder_sheets",
                     "copy_range",
                     "move_range",
+                    "break_external_link",
+                    "repoint_external_link",
                 ]);
             }
             None
@@ -1634,8 +2002,16 @@ fn tool_variants(tool: &str, problem: &str) -> Option<Vec<&'static str>> {
                     "set_gridlines",
                     "set_page_margins",
                     "set_page_setup",
+                    "set_orientation",
+                    "set_header_footer",
                     "set_print_area",
                     "set_page_breaks",
+                    "set_rows_hidden",
+                    "set_columns_hidden",
+                    "group_rows",
+                    "ungroup_rows",
+                    "group_columns",
+                    "ungroup_columns",
                 ]);
             }
             None
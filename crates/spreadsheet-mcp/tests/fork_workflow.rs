@@ -877,11 +877,13 @@ async fn test_get_changeset_paging_limit_offset_and_summary_only() -> Result<()>
                 value: "x".to_string(),
                 is_formula: false,
                 overwrite_formulas: false,
+                locale: None,
             }],
             mode: Some(spreadsheet_mcp::tools::param_enums::BatchMode::Apply),
             label: None,
 
             formula_parse_policy: None,
+            differential_save: None,
         },
     )
     .await?;
@@ -156,6 +156,11 @@ async fn mcp_sheet_page_budget_flows_through_response() -> Result<()> {
             include_styles: false,
             include_header: true,
             format: Some(SheetPageFormat::Full),
+            orientation: None,
+            start_column: None,
+            date_handling: None,
+            compression: None,
+            compress_min_bytes: None,
         }))
         .await?
         .0;
@@ -66,6 +66,11 @@ async fn server_tool_handlers_return_json() -> Result<()> {
             include_styles: false,
             include_header: true,
             format: None,
+            orientation: None,
+            start_column: None,
+            date_handling: None,
+            compression: None,
+            compress_min_bytes: None,
         }))
         .await
     {
@@ -86,6 +91,11 @@ async fn server_tool_handlers_return_json() -> Result<()> {
             columns_by_header: None,
             include_header: true,
             format: Some(SheetPageFormat::Full),
+            orientation: None,
+            start_column: None,
+            date_handling: None,
+            compression: None,
+            compress_min_bytes: None,
         }))
         .await
         .expect("page fetch")
@@ -181,6 +191,11 @@ async fn disabled_tools_return_invalid_request() -> Result<()> {
             columns_by_header: None,
             include_header: true,
             format: None,
+            orientation: None,
+            start_column: None,
+            date_handling: None,
+            compression: None,
+            compress_min_bytes: None,
         }))
         .await
     {
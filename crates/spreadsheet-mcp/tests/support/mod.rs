@@ -102,6 +102,17 @@ impl TestWorkspace {
             max_cells: Some(10_000),
             max_items: Some(500),
             allow_overwrite: false,
+            named_queries: Default::default(),
+            workbook_aliases: Default::default(),
+            virtual_workspace: false,
+            pin_revisions: false,
+            parse_cache_dir: None,
+            scratch_dir: None,
+            scratch_max_bytes: None,
+            checkpoint_store: None,
+            schedule: None,
+            schedule_mode: None,
+            profile: None,
         }
     }
 
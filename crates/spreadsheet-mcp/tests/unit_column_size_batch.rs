@@ -296,3 +296,64 @@ async fn column_size_batch_accepts_reversed_column_spans() -> Result<()> {
     assert_eq!(forward, reversed_dash);
     Ok(())
 }
+
+#[tokio::test(flavor = "current_thread")]
+async fn column_size_batch_autofit_measures_formatted_text_and_font() -> Result<()> {
+    let workspace = support::TestWorkspace::new();
+    workspace.create_workbook("cols_autofit.xlsx", |book| {
+        let sheet = book.get_sheet_by_name_mut("Sheet1").unwrap();
+        sheet.set_name("Data");
+        sheet.get_cell_mut("A1").set_value_number(1234.5);
+        sheet
+            .get_style_mut("A1")
+            .get_number_format_mut()
+            .set_format_code("$#,##0.00");
+        sheet.get_cell_mut("B1").set_value("Total");
+        let font = sheet.get_style_mut("B1").get_font_mut();
+        font.set_bold(true);
+        font.set_size(22.0);
+        sheet
+            .get_cell_mut("D1")
+            .set_value("a very long description that should be clamped by max width");
+    });
+
+    let state = app_state(&workspace);
+    let workbook_id = first_workbook_id(state.clone()).await?;
+    let fork = create_fork(
+        state.clone(),
+        CreateForkParams {
+            workbook_or_fork_id: workbook_id,
+        },
+    )
+    .await?;
+
+    let params: ColumnSizeBatchParamsInput = serde_json::from_value(json!({
+        "fork_id": fork.fork_id,
+        "sheet_name": "Data",
+        "mode": "apply",
+        "ops": [
+            {"range":"A:D", "size": {"kind":"autofit","max_width_chars": 20.0}}
+        ]
+    }))?;
+    let resp = column_size_batch(state.clone(), params).await?;
+    assert_eq!(resp.summary.counts.get("autofit_ops").copied(), Some(1));
+    assert_eq!(resp.summary.counts.get("columns_sized").copied(), Some(3));
+
+    let fork_wb = state
+        .open_workbook(&spreadsheet_mcp::model::WorkbookId(resp.fork_id.clone()))
+        .await?;
+    let (a, b, c, d) = fork_wb.with_sheet("Data", |sheet| {
+        let width = |col: &str| sheet.get_column_dimension(col).map(|dim| *dim.get_width());
+        (width("A"), width("B"), width("C"), width("D"))
+    })?;
+    // "$1,234.50" is measured, not the raw "1234.5".
+    assert!(a.expect("A column") >= 8.5, "a={a:?}");
+    // Bold 22pt "Total" is roughly twice its 11pt width.
+    assert!((b.expect("B column") - 10.5).abs() < 0.05, "b={b:?}");
+    assert!(
+        c.is_none_or(|width| width == 0.0 || (width - 8.43).abs() < 0.01),
+        "c={c:?}"
+    );
+    assert!((d.expect("D column") - 20.0).abs() < 0.001, "d={d:?}");
+    Ok(())
+}
@@ -72,6 +72,15 @@ fn empty_extensions_is_error() {
         max_cells: None,
         max_items: None,
         allow_overwrite: false,
+        virtual_workspace: false,
+        pin_revisions: false,
+        parse_cache_dir: None,
+        scratch_dir: None,
+        scratch_max_bytes: None,
+        checkpoint_store: None,
+        schedule: false,
+        schedule_once: false,
+        profile: None,
     };
     let err = ServerConfig::from_args(args).expect_err("expected failure");
     assert!(err.to_string().contains("at least one file extension"));
@@ -100,6 +109,17 @@ fn ensure_workspace_root_errors_for_missing_dir() {
         max_cells: Some(10_000),
         max_items: Some(500),
         allow_overwrite: false,
+        named_queries: Default::default(),
+        workbook_aliases: Default::default(),
+        virtual_workspace: false,
+        pin_revisions: false,
+        parse_cache_dir: None,
+        scratch_dir: None,
+        scratch_max_bytes: None,
+        checkpoint_store: None,
+        schedule: None,
+        schedule_mode: None,
+        profile: None,
     };
     let err = config.ensure_workspace_root().expect_err("missing dir");
     assert!(
@@ -188,3 +208,125 @@ fn recalc_backend_override_from_cli() {
     assert!(config.recalc_enabled);
     assert_eq!(config.recalc_backend, RecalcBackendKind::Formualizer);
 }
+
+#[test]
+fn checkpoint_store_is_validated_up_front() {
+    let workspace = tempfile::tempdir().expect("workspace tempdir");
+    let args = CliArgs::parse_from([
+        "gridbench-mcp",
+        "--workspace-root",
+        workspace.path().to_str().unwrap(),
+        "--checkpoint-store",
+        "ftp://bucket/checkpoints",
+    ]);
+    let err = ServerConfig::from_args(args).expect_err("unsupported checkpoint store");
+    assert!(
+        format!("{err:#}").contains("checkpoint_store 'ftp://bucket/checkpoints'"),
+        "{err:#}"
+    );
+
+    let args = CliArgs::parse_from([
+        "gridbench-mcp",
+        "--workspace-root",
+        workspace.path().to_str().unwrap(),
+        "--checkpoint-store",
+        "  ",
+    ]);
+    let config = ServerConfig::from_args(args).expect("blank store means scratch");
+    assert!(config.checkpoint_store.is_none());
+}
+
+const PROFILES: &str = "max_cells: 5000
+allow_overwrite: true
+default_profile: strict-prod
+profiles:
+  strict-prod:
+    enabled_tools: [list_workbooks, read_table]
+    allow_overwrite: false
+    max_cells: 2000
+    tool_timeout_ms: 10000
+  sandbox:
+    recalc_enabled: true
+    recalc_backend: formualizer
+    output_profile: verbose
+    max_items: 0
+";
+
+#[test]
+fn config_profiles_layer_over_file_defaults() {
+    let workspace = tempfile::tempdir().expect("workspace tempdir");
+    let config_dir = tempfile::tempdir().expect("config tempdir");
+    let config_path = config_dir.path().join("server.yaml");
+    let yaml = format!("workspace_root: {}\n{PROFILES}", workspace.path().display());
+    fs::write(&config_path, yaml).expect("write config");
+    let config_arg = config_path.to_str().unwrap();
+
+    let config = ServerConfig::from_args(CliArgs::parse_from([
+        "gridbench-mcp",
+        "--config",
+        config_arg,
+    ]))
+    .expect("default profile");
+    assert_eq!(config.profile.as_deref(), Some("strict-prod"));
+    assert!(!config.allow_overwrite);
+    assert_eq!(config.max_cells, Some(2000));
+    assert_eq!(config.tool_timeout_ms, Some(10_000));
+    assert!(config.is_tool_enabled("read_table"));
+    assert!(!config.is_tool_enabled("sheet_page"));
+
+    let config = ServerConfig::from_args(CliArgs::parse_from([
+        "gridbench-mcp",
+        "--config",
+        config_arg,
+        "--profile",
+        "sandbox",
+        "--max-cells",
+        "123",
+    ]))
+    .expect("sandbox profile");
+    assert_eq!(config.profile.as_deref(), Some("sandbox"));
+    assert!(config.recalc_enabled);
+    assert_eq!(config.recalc_backend, RecalcBackendKind::Formualizer);
+    assert_eq!(config.output_profile, OutputProfile::Verbose);
+    assert_eq!(config.max_items, None);
+    // File defaults apply where the profile is silent; flags beat both.
+    assert!(config.allow_overwrite);
+    assert_eq!(config.max_cells, Some(123));
+    assert!(config.enabled_tools.is_none());
+
+    let err = ServerConfig::from_args(CliArgs::parse_from([
+        "gridbench-mcp",
+        "--config",
+        config_arg,
+        "--profile",
+        "staging",
+    ]))
+    .expect_err("unknown profile");
+    assert!(
+        err.to_string().contains("available: sandbox, strict-prod"),
+        "{err}"
+    );
+
+    let err = ServerConfig::from_args(CliArgs::parse_from([
+        "gridbench-mcp",
+        "--workspace-root",
+        workspace.path().to_str().unwrap(),
+        "--profile",
+        "sandbox",
+    ]))
+    .expect_err("profile without a config file");
+    assert!(err.to_string().contains("defines no profiles"), "{err}");
+
+    let typo = format!(
+        "workspace_root: {}\nprofiles:\n  sandbox:\n    max_cell: 10\n",
+        workspace.path().display()
+    );
+    fs::write(&config_path, typo).expect("write config");
+    let err = ServerConfig::from_args(CliArgs::parse_from([
+        "gridbench-mcp",
+        "--config",
+        config_arg,
+    ]))
+    .expect_err("unknown profile field");
+    assert!(format!("{err:#}").contains("max_cell"), "{err:#}");
+}
@@ -0,0 +1,207 @@
+#![cfg(feature = "recalc")]
+
+use anyhow::{Result, anyhow};
+use serde_json::{Value, json};
+use spreadsheet_mcp::model::WorkbookId;
+use spreadsheet_mcp::tools::custom_ops::{
+    CustomOpEffects, CustomTransformOp, register_custom_transform_op,
+};
+use spreadsheet_mcp::tools::fork::{
+    CreateForkParams, TransformBatchParams, TransformOp, create_fork, transform_batch,
+};
+use spreadsheet_mcp::tools::param_enums::BatchMode;
+use spreadsheet_mcp::tools::{ListWorkbooksParams, list_workbooks};
+use std::sync::Once;
+use umya_spreadsheet::Worksheet;
+
+mod support;
+
+/// Writes a price into column B for every SKU in column A found in `params.prices`.
+struct ApplyPriceList;
+
+impl CustomTransformOp for ApplyPriceList {
+    fn name(&self) -> &str {
+        "apply_price_list"
+    }
+
+    fn description(&self) -> &str {
+        "set column B prices from a SKU price map"
+    }
+
+    fn validate(&self, _sheet_name: &str, params: &Value) -> Result<()> {
+        params
+            .get("prices")
+            .and_then(Value::as_object)
+            .map(|_| ())
+            .ok_or_else(|| anyhow!("params.prices must be an object"))
+    }
+
+    fn apply(
+        &self,
+        sheet: &mut Worksheet,
+        params: &Value,
+        effects: &mut CustomOpEffects,
+    ) -> Result<()> {
+        let prices = params["prices"].as_object().expect("validated");
+        let max_row = sheet.get_highest_row();
+        let mut unmatched = 0;
+        for row in 2..=max_row {
+            let sku = sheet.get_value((1, row));
+            match prices.get(&sku).and_then(Value::as_f64) {
+                Some(price) => {
+                    sheet.get_cell_mut((2, row)).set_value_number(price);
+                    effects.record_cells_changed(1);
+                }
+                None => unmatched += 1,
+            }
+        }
+        effects.add_count("unmatched_skus", unmatched);
+        effects.record_bounds(format!("B2:B{max_row}"));
+        if unmatched > 0 {
+            effects.warn(format!("{unmatched} SKU(s) had no price"));
+        }
+        Ok(())
+    }
+}
+
+fn register_ops() {
+    static REGISTER: Once = Once::new();
+    REGISTER.call_once(|| {
+        register_custom_transform_op(ApplyPriceList).expect("register apply_price_list");
+    });
+}
+
+async fn fork_of_price_sheet(
+    workspace: &support::TestWorkspace,
+) -> Result<(std::sync::Arc<spreadsheet_mcp::state::AppState>, String)> {
+    workspace.create_workbook("prices.xlsx", |book| {
+        let sheet = book.get_sheet_by_name_mut("Sheet1").unwrap();
+        sheet.set_name("Prices");
+        sheet.get_cell_mut("A1").set_value("SKU");
+        sheet.get_cell_mut("B1").set_value("Price");
+        sheet.get_cell_mut("A2").set_value("W-1");
+        sheet.get_cell_mut("A3").set_value("W-2");
+        sheet.get_cell_mut("A4").set_value("W-9");
+    });
+    let config = workspace.config_with(|cfg| {
+        cfg.recalc_enabled = true;
+    });
+    let state = support::app_state_with_config(config);
+    let list = list_workbooks(
+        state.clone(),
+        ListWorkbooksParams {
+            slug_prefix: None,
+            folder: None,
+            path_glob: None,
+            limit: None,
+            offset: None,
+            include_paths: None,
+        },
+    )
+    .await?;
+    let fork = create_fork(
+        state.clone(),
+        CreateForkParams {
+            workbook_or_fork_id: list.workbooks[0].workbook_id.clone(),
+        },
+    )
+    .await?;
+    Ok((state, fork.fork_id))
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn custom_op_applies_through_transform_batch_summary() -> Result<()> {
+    register_ops();
+    let workspace = support::TestWorkspace::new();
+    let (state, fork_id) = fork_of_price_sheet(&workspace).await?;
+
+    let resp = transform_batch(
+        state.clone(),
+        TransformBatchParams {
+            fork_id: fork_id.clone(),
+            ops: vec![TransformOp::Custom {
+                name: "apply_price_list".to_string(),
+                sheet_name: "Prices".to_string(),
+                params: json!({"prices": {"W-1": 9.5, "W-2": 12.0}}),
+            }],
+            mode: Some(BatchMode::Apply),
+            label: None,
+            formula_parse_policy: None,
+            differential_save: None,
+        },
+    )
+    .await?;
+
+    assert_eq!(resp.ops_applied, 1);
+    assert_eq!(
+        resp.summary.counts.get("custom_cells_changed").copied(),
+        Some(2)
+    );
+    assert_eq!(
+        resp.summary
+            .counts
+            .get("apply_price_list.unmatched_skus")
+            .copied(),
+        Some(1)
+    );
+    assert!(
+        resp.summary
+            .warnings
+            .iter()
+            .any(|warning| warning.starts_with("apply_price_list: 1 SKU"))
+    );
+
+    let fork_wb = state.open_workbook(&WorkbookId(fork_id)).await?;
+    let (b2, b4) = fork_wb.with_sheet("Prices", |sheet| {
+        (sheet.get_value("B2"), sheet.get_value("B4"))
+    })?;
+    assert_eq!(b2, "9.5");
+    assert_eq!(b4, "");
+    Ok(())
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn custom_op_rejects_unknown_names_and_bad_params() -> Result<()> {
+    register_ops();
+    let workspace = support::TestWorkspace::new();
+    let (state, fork_id) = fork_of_price_sheet(&workspace).await?;
+
+    let batch = |op: TransformOp| TransformBatchParams {
+        fork_id: fork_id.clone(),
+        ops: vec![op],
+        mode: Some(BatchMode::Preview),
+        label: None,
+        formula_parse_policy: None,
+        differential_save: None,
+    };
+
+    let err = transform_batch(
+        state.clone(),
+        batch(TransformOp::Custom {
+            name: "apply_discounts".to_string(),
+            sheet_name: "Prices".to_string(),
+            params: Value::Null,
+        }),
+    )
+    .await
+    .expect_err("unregistered op");
+    assert!(
+        err.to_string()
+            .contains("unknown custom transform op 'apply_discounts'")
+    );
+
+    let err = transform_batch(
+        state.clone(),
+        batch(TransformOp::Custom {
+            name: "apply_price_list".to_string(),
+            sheet_name: "Prices".to_string(),
+            params: json!({"prices": []}),
+        }),
+    )
+    .await
+    .expect_err("invalid params");
+    assert!(err.to_string().contains("params.prices must be an object"));
+
+    assert!(register_custom_transform_op(ApplyPriceList).is_err());
+    Ok(())
+}
@@ -0,0 +1,172 @@
+#![cfg(feature = "recalc")]
+
+use anyhow::Result;
+use serde_json::Value;
+use spreadsheet_mcp::tools::fork::{
+    CreateForkParams, SaveForkParams, TransformBatchParams, TransformOp, TransformTarget,
+    create_fork, save_fork, transform_batch,
+};
+use spreadsheet_mcp::tools::fork_webhook::with_fork_webhook;
+use spreadsheet_mcp::tools::param_enums::BatchMode;
+use spreadsheet_mcp::tools::{ListWorkbooksParams, list_workbooks};
+use spreadsheet_mcp::webhook::{WEBHOOK_SECRET_ENV, WEBHOOK_URL_ENV, sign_payload};
+use std::io::{Read, Write};
+use std::sync::mpsc;
+
+mod support;
+
+/// Accepts `count` webhook requests with `204` and forwards
+/// `(lowercased headers, body)`.
+fn serve_webhooks(count: usize) -> (String, mpsc::Receiver<(String, Vec<u8>)>) {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind listener");
+    let url = format!("http://{}/hooks", listener.local_addr().unwrap());
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        for stream in listener.incoming().take(count) {
+            let mut stream = stream.expect("accept webhook");
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            let header_end = loop {
+                if let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                    break end + 4;
+                }
+                let read = stream.read(&mut buf).expect("read request");
+                assert!(read > 0, "connection closed before headers");
+                request.extend_from_slice(&buf[..read]);
+            };
+            let headers = String::from_utf8_lossy(&request[..header_end]).to_ascii_lowercase();
+            let length: usize = headers
+                .lines()
+                .find_map(|line| line.strip_prefix("content-length:"))
+                .map(|value| value.trim().parse().expect("content length"))
+                .unwrap_or(0);
+            while request.len() < header_end + length {
+                let read = stream.read(&mut buf).expect("read body");
+                assert!(read > 0, "connection closed before body");
+                request.extend_from_slice(&buf[..read]);
+            }
+            let body = request[header_end..header_end + length].to_vec();
+            stream
+                .write_all(
+                    b"HTTP/1.1 204 No Content\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                )
+                .expect("write response");
+            let _ = sender.send((headers, body));
+        }
+    });
+    (url, receiver)
+}
+
+fn signed_event(headers: &str, body: &[u8], secret: &str) -> Value {
+    let header = |name: &str| {
+        headers
+            .lines()
+            .find_map(|line| line.strip_prefix(&format!("{name}:")))
+            .map(str::trim)
+            .unwrap_or_else(|| panic!("missing header {name} in {headers}"))
+            .to_string()
+    };
+    let timestamp: i64 = header("x-spreadsheet-timestamp")
+        .parse()
+        .expect("timestamp");
+    assert_eq!(
+        header("x-spreadsheet-signature"),
+        sign_payload(secret.as_bytes(), timestamp, body)
+    );
+    serde_json::from_slice(body).expect("webhook json")
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn fork_edits_and_saves_send_signed_webhooks() -> Result<()> {
+    let secret = "whsec-fork";
+    let (url, requests) = serve_webhooks(2);
+    // This binary holds only this test, so nothing else sees the variables.
+    unsafe {
+        std::env::set_var(WEBHOOK_URL_ENV, &url);
+        std::env::set_var(WEBHOOK_SECRET_ENV, secret);
+    }
+
+    let workspace = support::TestWorkspace::new();
+    workspace.create_workbook("hooks.xlsx", |book| {
+        let sheet = book.get_sheet_by_name_mut("Sheet1").unwrap();
+        sheet.get_cell_mut("A1").set_value("before");
+    });
+    let state = support::app_state_with_config(workspace.config_with(|cfg| {
+        cfg.recalc_enabled = true;
+    }));
+    let list = list_workbooks(
+        state.clone(),
+        ListWorkbooksParams {
+            slug_prefix: None,
+            folder: None,
+            path_glob: None,
+            limit: None,
+            offset: None,
+            include_paths: None,
+        },
+    )
+    .await?;
+    let fork = create_fork(
+        state.clone(),
+        CreateForkParams {
+            workbook_or_fork_id: list.workbooks[0].workbook_id.clone(),
+        },
+    )
+    .await?;
+
+    let edit = |mode: BatchMode| TransformBatchParams {
+        fork_id: fork.fork_id.clone(),
+        ops: vec![TransformOp::FillRange {
+            sheet_name: "Sheet1".to_string(),
+            target: TransformTarget::Range {
+                range: "A1".to_string(),
+            },
+            value: "after".to_string(),
+            is_formula: false,
+            overwrite_formulas: false,
+            locale: None,
+        }],
+        mode: Some(mode),
+        label: None,
+        formula_parse_policy: None,
+        differential_save: None,
+    };
+    // A preview leaves the fork untouched, so only the apply is sent.
+    for mode in [BatchMode::Preview, BatchMode::Apply] {
+        with_fork_webhook(
+            &state,
+            &fork.fork_id,
+            "transform_batch",
+            transform_batch(state.clone(), edit(mode)),
+        )
+        .await?;
+    }
+    let (headers, body) = requests.recv()?;
+    let event = signed_event(&headers, &body, secret);
+    assert_eq!(event["event"], "write_completed");
+    assert_eq!(event["command"], "transform_batch");
+    assert_eq!(event["fork_id"], fork.fork_id.as_str());
+    assert_eq!(event["changed_cells"]["cells"][0]["address"], "A1");
+    assert_eq!(event["changed_cells"]["cells"][0]["after"], "after");
+
+    save_fork(
+        state.clone(),
+        SaveForkParams {
+            fork_id: fork.fork_id.clone(),
+            target_path: Some("saved.xlsx".to_string()),
+            drop_fork: true,
+        },
+    )
+    .await?;
+    let (headers, body) = requests.recv()?;
+    let event = signed_event(&headers, &body, secret);
+    assert_eq!(event["command"], "save_fork");
+    assert!(
+        event["target_path"]
+            .as_str()
+            .is_some_and(|path| path.ends_with("saved.xlsx")),
+        "event: {event}"
+    );
+    assert_eq!(event["changed_cells"]["total_changed"], 1);
+    Ok(())
+}
@@ -202,8 +202,13 @@ async fn range_values_warns_when_stale_formulas_present() -> Result<()> {
             include_headers: Some(false),
             format: None,
             page_size: None,
+            csv_options: None,
 
             include_formulas: None,
+
+            include_formatting: None,
+            compression: None,
+            compress_min_bytes: None,
         },
     )
     .await?;
@@ -0,0 +1,148 @@
+#![cfg(feature = "recalc")]
+
+use anyhow::Result;
+use serde_json::json;
+use spreadsheet_mcp::model::WorkbookId;
+use spreadsheet_mcp::tools::fork::{
+    CreateForkParams, RowSizeBatchParamsInput, create_fork, row_size_batch,
+};
+use spreadsheet_mcp::tools::{ListWorkbooksParams, list_workbooks};
+
+mod support;
+
+fn app_state(
+    workspace: &support::TestWorkspace,
+) -> std::sync::Arc<spreadsheet_mcp::state::AppState> {
+    let config = workspace.config_with(|cfg| {
+        cfg.recalc_enabled = true;
+    });
+    support::app_state_with_config(config)
+}
+
+async fn first_workbook_id(
+    state: std::sync::Arc<spreadsheet_mcp::state::AppState>,
+) -> Result<WorkbookId> {
+    let list = list_workbooks(
+        state,
+        ListWorkbooksParams {
+            slug_prefix: None,
+            folder: None,
+            path_glob: None,
+            limit: None,
+            offset: None,
+            include_paths: None,
+        },
+    )
+    .await?;
+    Ok(list.workbooks[0].workbook_id.clone())
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn row_size_batch_sets_explicit_height_for_span() -> Result<()> {
+    let workspace = support::TestWorkspace::new();
+    workspace.create_workbook("rows.xlsx", |book| {
+        let sheet = book.get_sheet_by_name_mut("Sheet1").unwrap();
+        sheet.set_name("Data");
+        sheet.get_cell_mut("A1").set_value("x");
+    });
+
+    let state = app_state(&workspace);
+    let workbook_id = first_workbook_id(state.clone()).await?;
+    let fork = create_fork(
+        state.clone(),
+        CreateForkParams {
+            workbook_or_fork_id: workbook_id,
+        },
+    )
+    .await?;
+
+    let params: RowSizeBatchParamsInput = serde_json::from_value(json!({
+        "fork_id": fork.fork_id,
+        "sheet_name": "Data",
+        "mode": "apply",
+        "ops": [
+            {"target": {"kind":"rows","range":"2:3"}, "size": {"kind":"height","height_points": 32.0}}
+        ]
+    }))?;
+    let resp = row_size_batch(state.clone(), params).await?;
+
+    assert_eq!(resp.mode, "apply");
+    assert_eq!(resp.ops_applied, 1);
+    assert_eq!(resp.summary.counts.get("rows_sized").copied(), Some(2));
+
+    let fork_wb = state
+        .open_workbook(&WorkbookId(resp.fork_id.clone()))
+        .await?;
+    let heights = fork_wb.with_sheet("Data", |sheet| {
+        [2u32, 3]
+            .iter()
+            .map(|row| {
+                sheet
+                    .get_row_dimension(row)
+                    .map(|dim| *dim.get_height())
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>()
+    })?;
+    assert!(heights.iter().all(|height| (height - 32.0).abs() < 0.001));
+    Ok(())
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn row_size_batch_auto_height_grows_for_wrapped_text() -> Result<()> {
+    let workspace = support::TestWorkspace::new();
+    workspace.create_workbook("rows_auto.xlsx", |book| {
+        let sheet = book.get_sheet_by_name_mut("Sheet1").unwrap();
+        sheet.set_name("Data");
+        let long_text = "this note is long enough to wrap across several lines";
+        sheet.get_cell_mut("A1").set_value(long_text);
+        sheet.get_cell_mut("A2").set_value(long_text);
+        sheet
+            .get_style_mut("A2")
+            .get_alignment_mut()
+            .set_wrap_text(true);
+    });
+
+    let state = app_state(&workspace);
+    let workbook_id = first_workbook_id(state.clone()).await?;
+    let fork = create_fork(
+        state.clone(),
+        CreateForkParams {
+            workbook_or_fork_id: workbook_id,
+        },
+    )
+    .await?;
+
+    let params: RowSizeBatchParamsInput = serde_json::from_value(json!({
+        "fork_id": fork.fork_id,
+        "sheet_name": "Data",
+        "mode": "apply",
+        "ops": [
+            {"range":"1:2", "size": {"kind":"auto","max_height_points": 120.0}}
+        ]
+    }))?;
+    let resp = row_size_batch(state.clone(), params).await?;
+    assert!(
+        resp.summary
+            .warnings
+            .iter()
+            .any(|warning| warning.contains("WARN_ROW_SHORTHAND_TARGET"))
+    );
+
+    let fork_wb = state
+        .open_workbook(&WorkbookId(resp.fork_id.clone()))
+        .await?;
+    let (unwrapped, wrapped) = fork_wb.with_sheet("Data", |sheet| {
+        let height = |row: u32| {
+            sheet
+                .get_row_dimension(&row)
+                .map(|dim| *dim.get_height())
+                .unwrap_or_default()
+        };
+        (height(1), height(2))
+    })?;
+    assert!((unwrapped - 15.0).abs() < 0.001, "unwrapped={unwrapped}");
+    assert!(wrapped >= 45.0, "wrapped={wrapped}");
+    assert!(wrapped <= 120.0, "wrapped={wrapped}");
+    Ok(())
+}
@@ -7,8 +7,8 @@ use spreadsheet_mcp::tools::fork::{
 };
 use spreadsheet_mcp::tools::param_enums::BatchMode;
 use spreadsheet_mcp::tools::rules_batch::{
-    DataValidationKind, DataValidationSpec, RulesBatchParams, RulesOp, ValidationMessage,
-    rules_batch,
+    DataValidationKind, DataValidationSpec, ListSourceSpec, RulesBatchParams, RulesOp,
+    ValidationMessage, rules_batch,
 };
 use spreadsheet_mcp::tools::{ListWorkbooksParams, list_workbooks};
 
@@ -73,6 +73,7 @@ async fn rules_batch_set_data_validation_list_persists_and_is_idempotent() -> Re
                 title: "Invalid".to_string(),
                 message: "Use the dropdown".to_string(),
             }),
+            list_source: None,
         },
     };
 
@@ -170,6 +171,7 @@ async fn rules_batch_preview_then_apply_staged_change() -> Result<()> {
                     allow_blank: None,
                     prompt: None,
                     error: None,
+                    list_source: None,
                 },
             }],
             mode: Some(BatchMode::Preview),
@@ -210,3 +212,138 @@ async fn rules_batch_preview_then_apply_staged_change() -> Result<()> {
 
     Ok(())
 }
+
+fn list_source_op(target_range: &str, source: ListSourceSpec) -> RulesOp {
+    RulesOp::SetDataValidation {
+        sheet_name: "Sheet1".to_string(),
+        target_range: target_range.to_string(),
+        validation: DataValidationSpec {
+            kind: DataValidationKind::List,
+            formula1: String::new(),
+            formula2: None,
+            allow_blank: None,
+            prompt: None,
+            error: None,
+            list_source: Some(source),
+        },
+    }
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn rules_batch_list_source_resolves_named_range_and_quoted_sheet_range() -> Result<()> {
+    let workspace = support::TestWorkspace::new();
+    workspace.create_workbook("dv_list_source.xlsx", |book| {
+        let _ = book.new_sheet("Lookup Lists");
+        let lists = book.get_sheet_by_name_mut("Lookup Lists").unwrap();
+        lists.get_cell_mut("A1").set_value("Red");
+        lists.get_cell_mut("A2").set_value("Green");
+        lists
+            .add_defined_name("Colors", "'Lookup Lists'!$A$1:$A$2")
+            .unwrap();
+    });
+
+    let state = recalc_state(&workspace);
+    let list = list_workbooks(
+        state.clone(),
+        ListWorkbooksParams {
+            slug_prefix: None,
+            folder: None,
+            path_glob: None,
+            limit: None,
+            offset: None,
+            include_paths: None,
+        },
+    )
+    .await?;
+    let workbook_id = list.workbooks[0].workbook_id.clone();
+
+    let fork = create_fork(
+        state.clone(),
+        CreateForkParams {
+            workbook_or_fork_id: workbook_id,
+        },
+    )
+    .await?;
+
+    rules_batch(
+        state.clone(),
+        RulesBatchParams {
+            fork_id: fork.fork_id.clone(),
+            ops: vec![
+                list_source_op(
+                    "B2:B5",
+                    ListSourceSpec::NamedRange {
+                        name: "Colors".to_string(),
+                    },
+                ),
+                list_source_op(
+                    "C2:C5",
+                    ListSourceSpec::Range {
+                        sheet_name: "Lookup Lists".to_string(),
+                        range: "A1:A2".to_string(),
+                    },
+                ),
+            ],
+            mode: Some(BatchMode::Apply),
+            label: None,
+            formula_parse_policy: None,
+        },
+    )
+    .await?;
+
+    let fork_wb = state
+        .open_workbook(&WorkbookId(fork.fork_id.clone()))
+        .await?;
+    fork_wb.with_sheet("Sheet1", |sheet| {
+        let dvs = sheet.get_data_validations().expect("data validations");
+        let list = dvs.get_data_validation_list();
+        assert_eq!(list.len(), 2);
+        let by_sqref = |sqref: &str| {
+            list.iter()
+                .find(|dv| dv.get_sequence_of_references().get_sqref() == sqref)
+                .expect("validation for sqref")
+        };
+        assert_eq!(by_sqref("B2:B5").get_formula1(), "Colors");
+        assert_eq!(by_sqref("C2:C5").get_formula1(), "'Lookup Lists'!$A$1:$A$2");
+    })?;
+
+    let missing = rules_batch(
+        state.clone(),
+        RulesBatchParams {
+            fork_id: fork.fork_id.clone(),
+            ops: vec![list_source_op(
+                "D2:D5",
+                ListSourceSpec::NamedRange {
+                    name: "Missing".to_string(),
+                },
+            )],
+            mode: Some(BatchMode::Apply),
+            label: None,
+            formula_parse_policy: None,
+        },
+    )
+    .await;
+    let err = missing.expect_err("missing named range should be rejected");
+    assert!(err.to_string().contains("named range 'Missing' not found"));
+
+    let missing_sheet = rules_batch(
+        state.clone(),
+        RulesBatchParams {
+            fork_id: fork.fork_id.clone(),
+            ops: vec![list_source_op(
+                "D2:D5",
+                ListSourceSpec::Range {
+                    sheet_name: "Nope".to_string(),
+                    range: "A1:A2".to_string(),
+                },
+            )],
+            mode: Some(BatchMode::Apply),
+            label: None,
+            formula_parse_policy: None,
+        },
+    )
+    .await;
+    assert!(missing_sheet.is_err());
+
+    Ok(())
+}
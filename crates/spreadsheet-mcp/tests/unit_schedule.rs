@@ -0,0 +1,126 @@
+use std::fs;
+
+use clap::Parser;
+use spreadsheet_mcp::schedule::{ScheduleMode, ScheduledRunStatus, run_job, run_schedule};
+use spreadsheet_mcp::{CliArgs, ServerConfig};
+
+mod support;
+
+fn write_config(workspace: &support::TestWorkspace, body: &str) -> std::path::PathBuf {
+    let config_path = workspace.path("server.yaml");
+    let yaml = format!("workspace_root: {}\n{body}", workspace.root().display());
+    fs::write(&config_path, yaml).expect("write config");
+    config_path
+}
+
+const SCHEDULE: &str = r#"schedule:
+  log: logs/schedule.jsonl
+  jobs:
+    - name: export-summary
+      cron: "0 7 * * mon-fri"
+      workbooks: ["reports/*.xlsx"]
+      command: export
+      sheet: Summary
+      range: A1:B3
+      output: "exports/{stem}.csv"
+    - name: lint-models
+      cron: "@hourly"
+      workbooks: ["models/model.xlsx"]
+      command: lint
+"#;
+
+fn seed_workbooks(workspace: &support::TestWorkspace) {
+    for name in ["reports/east.xlsx", "reports/west.xlsx"] {
+        workspace.create_workbook(name, |book| {
+            book.set_sheet_name(0, "Summary").expect("rename sheet");
+            let sheet = book.get_sheet_by_name_mut("Summary").expect("summary");
+            sheet.get_cell_mut("A1").set_value("Region");
+            sheet.get_cell_mut("B1").set_value("Total");
+            sheet.get_cell_mut("A2").set_value("North");
+            sheet.get_cell_mut("B2").set_value_number(12.0);
+        });
+    }
+    workspace.create_workbook("models/model.xlsx", |book| {
+        let sheet = book.get_sheet_by_name_mut("Sheet1").expect("sheet1");
+        sheet.get_cell_mut("A1").set_value_number(1.0);
+        sheet.get_cell_mut("A2").set_formula("SUM(A1");
+    });
+}
+
+#[test]
+fn schedule_block_is_parsed_and_validated() {
+    let workspace = support::TestWorkspace::new();
+    let config_path = write_config(&workspace, SCHEDULE);
+    let args = CliArgs::parse_from([
+        "spreadsheet-mcp",
+        "--config",
+        config_path.to_str().unwrap(),
+        "--schedule",
+    ]);
+    let config = ServerConfig::from_args(args).expect("config");
+    assert_eq!(config.schedule_mode, Some(ScheduleMode::Loop));
+    let schedule = config.schedule.expect("schedule");
+    assert_eq!(schedule.jobs.len(), 2);
+    assert_eq!(schedule.jobs[0].task.command_name(), "export");
+
+    let in_place = "schedule:\n  jobs:\n    - name: nightly\n      cron: \"30 2 * * *\"\n      workbooks: [model.xlsx]\n      command: recalculate\n";
+    let config_path = write_config(&workspace, in_place);
+    let args = CliArgs::parse_from(["spreadsheet-mcp", "--config", config_path.to_str().unwrap()]);
+    let err = ServerConfig::from_args(args).expect_err("in-place job needs allow_overwrite");
+    assert!(err.to_string().contains("allow_overwrite"), "{err}");
+
+    let escaping = SCHEDULE.replace("exports/{stem}.csv", "../{stem}.csv");
+    let config_path = write_config(&workspace, &escaping);
+    let args = CliArgs::parse_from(["spreadsheet-mcp", "--config", config_path.to_str().unwrap()]);
+    let err = ServerConfig::from_args(args).expect_err("output outside the workspace");
+    assert!(
+        err.to_string().contains("relative to the workspace root"),
+        "{err}"
+    );
+
+    let args = CliArgs::parse_from(["spreadsheet-mcp", "--schedule-once"]);
+    let err = ServerConfig::from_args(args).expect_err("schedule mode needs jobs");
+    assert!(err.to_string().contains("schedule"), "{err}");
+}
+
+#[tokio::test]
+async fn scheduled_jobs_export_lint_and_log_runs() {
+    let workspace = support::TestWorkspace::new();
+    seed_workbooks(&workspace);
+    let config_path = write_config(&workspace, SCHEDULE);
+    let args = CliArgs::parse_from([
+        "spreadsheet-mcp",
+        "--config",
+        config_path.to_str().unwrap(),
+        "--schedule-once",
+    ]);
+    let config = ServerConfig::from_args(args).expect("config");
+    let schedule = config.schedule.clone().expect("schedule");
+
+    let export = run_job(&config, &schedule.jobs[0]).await;
+    assert_eq!(export.status, ScheduledRunStatus::Ok, "{export:?}");
+    assert_eq!(export.workbooks.len(), 2);
+    assert_eq!(
+        export.workbooks[0].output.as_deref(),
+        Some("exports/east.csv")
+    );
+    let csv = fs::read_to_string(workspace.path("exports/west.csv")).expect("exported csv");
+    assert!(csv.contains("North"), "{csv}");
+
+    let lint = run_job(&config, &schedule.jobs[1]).await;
+    assert_eq!(lint.status, ScheduledRunStatus::Alert, "{lint:?}");
+    assert_eq!(lint.workbooks[0].path, "models/model.xlsx");
+
+    let err = run_schedule(&config, ScheduleMode::Once)
+        .await
+        .expect_err("the lint alert fails a one-shot run");
+    assert!(err.to_string().contains("1 of 2"), "{err}");
+    let log = fs::read_to_string(workspace.path("logs/schedule.jsonl")).expect("schedule log");
+    let runs: Vec<serde_json::Value> = log
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("log line"))
+        .collect();
+    assert_eq!(runs.len(), 2);
+    assert_eq!(runs[0]["job"], "export-summary");
+    assert_eq!(runs[1]["status"], "alert");
+}
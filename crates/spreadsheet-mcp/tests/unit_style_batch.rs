@@ -611,7 +611,7 @@ async fn style_batch_idempotent_noop_counts_and_no_diff() -> Result<()> {
             CellDiff::Modified { subtype, .. } => !matches!(subtype, ModificationType::StyleEdit),
             CellDiff::Added { .. } | CellDiff::Deleted { .. } => true,
         },
-        Change::Table(_) | Change::Name(_) => true,
+        Change::Table(_) | Change::Name(_) | Change::Rule(_) => true,
     });
     assert!(!non_style_change);
 
@@ -1223,3 +1223,83 @@ fn style_batch_handles_mixed_shorthand_and_canonical_ops() {
         .count();
     assert_eq!(shorthand_warnings, 1);
 }
+
+#[tokio::test(flavor = "current_thread")]
+async fn style_batch_keeps_1904_date_system() -> Result<()> {
+    use spreadsheet_mcp::model::CellValue;
+    use spreadsheet_mcp::tools::workbook_props::write_date1904;
+    use spreadsheet_mcp::workbook::cell_to_value;
+
+    let workspace = support::TestWorkspace::new();
+    let path = workspace.create_workbook("mac_dates.xlsx", |book| {
+        let sheet = book.get_sheet_by_name_mut("Sheet1").unwrap();
+        let cell = sheet.get_cell_mut("A1");
+        cell.set_value_number(44135.0);
+        cell.get_style_mut()
+            .get_number_format_mut()
+            .set_format_code("yyyy-mm-dd");
+    });
+    write_date1904(&path, true)?;
+
+    let state = recalc_state(&workspace);
+    let list = list_workbooks(
+        state.clone(),
+        ListWorkbooksParams {
+            slug_prefix: None,
+            folder: None,
+            path_glob: None,
+            limit: None,
+            offset: None,
+            include_paths: None,
+        },
+    )
+    .await?;
+    let fork = create_fork(
+        state.clone(),
+        CreateForkParams {
+            workbook_or_fork_id: list.workbooks[0].workbook_id.clone(),
+        },
+    )
+    .await?;
+
+    // A style edit re-serializes the whole package through umya.
+    style_batch(
+        state.clone(),
+        StyleBatchParamsInput {
+            fork_id: fork.fork_id.clone(),
+            ops: vec![
+                StyleOp {
+                    sheet_name: "Sheet1".to_string(),
+                    target: StyleTarget::Cells {
+                        cells: vec!["B1".to_string()],
+                    },
+                    patch: StylePatch {
+                        font: Some(Some(FontPatch {
+                            bold: Some(Some(true)),
+                            ..Default::default()
+                        })),
+                        ..Default::default()
+                    },
+                    op_mode: Some(StylePatchMode::Merge),
+                }
+                .into(),
+            ],
+            mode: Some(BatchMode::Apply),
+            label: None,
+        },
+    )
+    .await?;
+
+    let fork_wb = state
+        .open_workbook(&spreadsheet_mcp::model::WorkbookId(fork.fork_id.clone()))
+        .await?;
+    assert!(fork_wb.uses_1904_date_system());
+    let value = fork_wb.with_sheet("Sheet1", |sheet| {
+        cell_to_value(sheet.get_cell("A1").unwrap())
+    })?;
+    assert!(
+        matches!(&value, Some(CellValue::Date(d)) if d == "2024-11-01"),
+        "expected Date(2024-11-01), got {value:?}"
+    );
+    Ok(())
+}
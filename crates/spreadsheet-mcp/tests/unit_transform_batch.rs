@@ -70,6 +70,7 @@ async fn transform_batch_clear_range_clears_values_keeps_formulas_by_default() -
             label: None,
 
             formula_parse_policy: None,
+            differential_save: None,
         },
     )
     .await?;
@@ -140,6 +141,7 @@ async fn transform_batch_preview_stages_and_apply() -> Result<()> {
             label: Some("blank inputs".to_string()),
 
             formula_parse_policy: None,
+            differential_save: None,
         },
     )
     .await?;
@@ -248,6 +250,7 @@ async fn transform_batch_region_target_resolves() -> Result<()> {
             label: None,
 
             formula_parse_policy: None,
+            differential_save: None,
         },
     )
     .await?;
@@ -321,6 +324,7 @@ async fn transform_batch_cells_target_skips_missing_and_handles_duplicates() ->
             label: None,
 
             formula_parse_policy: None,
+            differential_save: None,
         },
     )
     .await?;
@@ -401,6 +405,7 @@ async fn transform_batch_accepts_reversed_range() -> Result<()> {
             label: None,
 
             formula_parse_policy: None,
+            differential_save: None,
         },
     )
     .await?;
@@ -474,6 +479,7 @@ async fn transform_batch_noop_flags_do_not_change_cells() -> Result<()> {
             label: None,
 
             formula_parse_policy: None,
+            differential_save: None,
         },
     )
     .await?;
@@ -553,6 +559,7 @@ async fn transform_batch_counts_mixed_range() -> Result<()> {
             label: None,
 
             formula_parse_policy: None,
+            differential_save: None,
         },
     )
     .await?;
@@ -625,6 +632,7 @@ async fn transform_batch_clear_formulas_only_removes_formula_keeps_literal_value
             label: None,
 
             formula_parse_policy: None,
+            differential_save: None,
         },
     )
     .await?;
@@ -699,11 +707,13 @@ async fn transform_batch_fill_range_creates_cells_and_skips_formulas_by_default(
                 value: "x".to_string(),
                 is_formula: false,
                 overwrite_formulas: false,
+                locale: None,
             }],
             mode: Some(BatchMode::Apply),
             label: None,
 
             formula_parse_policy: None,
+            differential_save: None,
         },
     )
     .await?;
@@ -783,6 +793,7 @@ async fn transform_batch_replace_in_range_replaces_values_exact() -> Result<()>
             label: None,
 
             formula_parse_policy: None,
+            differential_save: None,
         },
     )
     .await?;
@@ -857,6 +868,7 @@ async fn transform_batch_replace_in_range_contains_case_sensitive() -> Result<()
             label: None,
 
             formula_parse_policy: None,
+            differential_save: None,
         },
     )
     .await?;
@@ -928,6 +940,7 @@ async fn transform_batch_replace_in_range_skips_formulas_by_default() -> Result<
             label: None,
 
             formula_parse_policy: None,
+            differential_save: None,
         },
     )
     .await?;
@@ -1002,6 +1015,7 @@ async fn transform_batch_replace_in_range_can_mutate_formulas_when_enabled() ->
             label: None,
 
             formula_parse_policy: None,
+            differential_save: None,
         },
     )
     .await?;
@@ -1066,11 +1080,13 @@ async fn transform_batch_fill_range_preview_stages_and_apply() -> Result<()> {
                 value: "y".to_string(),
                 is_formula: false,
                 overwrite_formulas: false,
+                locale: None,
             }],
             mode: Some(BatchMode::Preview),
             label: Some("fill".to_string()),
 
             formula_parse_policy: None,
+            differential_save: None,
         },
     )
     .await?;
@@ -1158,6 +1174,7 @@ async fn transform_batch_replace_in_range_preview_stages_and_apply() -> Result<(
             label: Some("replace".to_string()),
 
             formula_parse_policy: None,
+            differential_save: None,
         },
     )
     .await?;
@@ -1243,6 +1260,7 @@ async fn transform_batch_replace_in_range_exact_case_insensitive() -> Result<()>
             label: None,
 
             formula_parse_policy: None,
+            differential_save: None,
         },
     )
     .await?;
@@ -1312,6 +1330,7 @@ async fn transform_batch_replace_in_range_contains_replaces_all_occurrences() ->
             label: None,
 
             formula_parse_policy: None,
+            differential_save: None,
         },
     )
     .await?;
@@ -1372,6 +1391,7 @@ async fn transform_batch_multiple_ops_last_wins() -> Result<()> {
                     value: "y".to_string(),
                     is_formula: false,
                     overwrite_formulas: false,
+                    locale: None,
                 },
                 TransformOp::FillRange {
                     sheet_name: "Sheet1".to_string(),
@@ -1381,12 +1401,14 @@ async fn transform_batch_multiple_ops_last_wins() -> Result<()> {
                     value: "z".to_string(),
                     is_formula: false,
                     overwrite_formulas: false,
+                    locale: None,
                 },
             ],
             mode: Some(BatchMode::Apply),
             label: None,
 
             formula_parse_policy: None,
+            differential_save: None,
         },
     )
     .await?;
@@ -1453,6 +1475,7 @@ async fn transform_batch_replace_in_range_contains_rejects_case_insensitive() ->
             label: None,
 
             formula_parse_policy: None,
+            differential_save: None,
         },
     )
     .await
@@ -1506,11 +1529,13 @@ async fn transform_batch_fill_range_overwrite_formulas_removes_formula() -> Resu
                 value: "x".to_string(),
                 is_formula: false,
                 overwrite_formulas: true,
+                locale: None,
             }],
             mode: Some(BatchMode::Apply),
             label: None,
 
             formula_parse_policy: None,
+            differential_save: None,
         },
     )
     .await?;
@@ -83,6 +83,9 @@ def discover_cli_commands(cli_source: str) -> set[str]:
     write_top = set(re.findall(r"SurfaceWriteCommands::([A-Za-z0-9_]+)", cli_source))
     write_formula = set(re.findall(r"SurfaceWriteFormulaCommands::([A-Za-z0-9_]+)", cli_source))
     write_name = set(re.findall(r"SurfaceWriteNameCommands::([A-Za-z0-9_]+)", cli_source))
+    write_metadata = set(
+        re.findall(r"SurfaceWriteMetadataCommands::([A-Za-z0-9_]+)", cli_source)
+    )
     write_batch = set(re.findall(r"SurfaceWriteBatchCommands::([A-Za-z0-9_]+)", cli_source))
     sheetport = set(re.findall(r"SheetportCommands::([A-Za-z0-9_]+)", cli_source))
     manifest = set(re.findall(r"SheetportManifestCommands::([A-Za-z0-9_]+)", cli_source))
@@ -102,7 +105,7 @@ def discover_cli_commands(cli_source: str) -> set[str]:
         commands.add(f"verify {camel_to_kebab(name)}")
 
     for name in write_top:
-        if name in {"Batch", "Name", "Formulas"}:
+        if name in {"Batch", "Name", "Metadata", "Formulas"}:
             continue
         commands.add(f"write {camel_to_kebab(name)}")
 
@@ -112,6 +115,9 @@ def discover_cli_commands(cli_source: str) -> set[str]:
     for name in write_name:
         commands.add(f"write name {camel_to_kebab(name)}")
 
+    for name in write_metadata:
+        commands.add(f"write metadata {camel_to_kebab(name)}")
+
     for name in write_batch:
         commands.add(f"write batch {camel_to_kebab(name)}")
 
@@ -121,6 +127,12 @@ def discover_cli_commands(cli_source: str) -> set[str]:
         commands.add("example")
     if "Session" in top_level:
         commands.add("session")
+    if "Serve" in top_level:
+        commands.add("serve")
+    if "Gc" in top_level:
+        commands.add("gc")
+    if "Revisions" in top_level:
+        commands.add("revisions")
 
     for name in sheetport:
         if name == "Manifest":